@@ -27,9 +27,16 @@ pub enum PermissionReq<'a> {
     PromQuery,
     Opentsdb,
     LineProtocol,
+    Mqtt,
+    LokiPush,
+    Pipeline,
     PromStoreWrite,
     PromStoreRead,
     Otlp,
+    Upload,
+    /// Operations that need privileges of administrators, e.g. `ADMIN capture_query` writes
+    /// files on servers and `CREATE FUNCTION` runs user code on servers.
+    Admin,
 }
 
 #[derive(Debug)]
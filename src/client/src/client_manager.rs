@@ -13,20 +13,89 @@
 // limitations under the License.
 
 use std::fmt::{Debug, Formatter};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
 
 use common_grpc::channel_manager::{ChannelConfig, ChannelManager};
 use common_meta::datanode_manager::{Datanode, DatanodeManager};
 use common_meta::peer::Peer;
+use common_telemetry::{info, warn};
 use moka::future::{Cache, CacheBuilder};
+use tokio::sync::Semaphore;
 
 use crate::region::RegionRequester;
 use crate::Client;
 
+/// Options of the pool of channels to each datanode.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PoolConfig {
+    /// Number of channels to each datanode.
+    pub channels_per_peer: usize,
+    /// Max in-flight requests to each datanode, unlimited if it's 0.
+    pub max_in_flight_per_peer: usize,
+    /// Interval to check the health of channels, broken channels are evicted and
+    /// reconnected by following requests.
+    pub health_check_interval: Duration,
+}
+
+impl Default for PoolConfig {
+    fn default() -> Self {
+        Self {
+            channels_per_peer: 1,
+            max_in_flight_per_peer: 0,
+            health_check_interval: Duration::from_secs(10),
+        }
+    }
+}
+
+/// A client of a channel in the pool.
+struct PooledClient {
+    client: Client,
+    healthy: AtomicBool,
+}
+
+/// Clients to a datanode, each client uses its own channel.
+struct PeerClients {
+    clients: Vec<PooledClient>,
+    next: AtomicUsize,
+    in_flight: Option<Arc<Semaphore>>,
+}
+
+impl PeerClients {
+    fn new(clients: Vec<Client>, max_in_flight: usize) -> Self {
+        Self {
+            clients: clients
+                .into_iter()
+                .map(|client| PooledClient {
+                    client,
+                    healthy: AtomicBool::new(true),
+                })
+                .collect(),
+            next: AtomicUsize::new(0),
+            in_flight: (max_in_flight > 0).then(|| Arc::new(Semaphore::new(max_in_flight))),
+        }
+    }
+
+    /// Picks clients in turn, skipping unhealthy ones unless all clients are unhealthy.
+    fn pick(&self) -> &Client {
+        let start = self.next.fetch_add(1, Ordering::Relaxed);
+        let len = self.clients.len();
+        let pooled = (0..len)
+            .map(|i| &self.clients[(start + i) % len])
+            .find(|pooled| pooled.healthy.load(Ordering::Relaxed))
+            .unwrap_or(&self.clients[start % len]);
+        &pooled.client
+    }
+}
+
 pub struct DatanodeClients {
-    channel_manager: ChannelManager,
-    clients: Cache<Peer, Client>,
+    /// Managers of channels in each slot of the pool, so channels to the same datanode
+    /// are different connections.
+    channel_managers: Vec<ChannelManager>,
+    pool_config: PoolConfig,
+    clients: Cache<Peer, Arc<PeerClients>>,
+    health_check_started: Arc<AtomicBool>,
 }
 
 impl Default for DatanodeClients {
@@ -38,7 +107,8 @@ impl Default for DatanodeClients {
 impl Debug for DatanodeClients {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("DatanodeClients")
-            .field("channel_manager", &self.channel_manager)
+            .field("channel_managers", &self.channel_managers)
+            .field("pool_config", &self.pool_config)
             .finish()
     }
 }
@@ -46,36 +116,172 @@ impl Debug for DatanodeClients {
 #[async_trait::async_trait]
 impl DatanodeManager for DatanodeClients {
     async fn datanode(&self, datanode: &Peer) -> Arc<dyn Datanode> {
-        let client = self.get_client(datanode).await;
+        self.start_health_check();
+        let clients = self.peer_clients(datanode).await;
 
-        Arc::new(RegionRequester::new(client))
+        Arc::new(
+            RegionRequester::new(clients.pick().clone())
+                .with_in_flight_limit(clients.in_flight.clone()),
+        )
     }
 }
 
 impl DatanodeClients {
     pub fn new(config: ChannelConfig) -> Self {
+        Self::with_pool_config(config, PoolConfig::default())
+    }
+
+    pub fn with_pool_config(config: ChannelConfig, pool_config: PoolConfig) -> Self {
+        let channel_managers = (0..pool_config.channels_per_peer.max(1))
+            .map(|_| ChannelManager::with_config(config.clone()))
+            .collect();
         Self {
-            channel_manager: ChannelManager::with_config(config),
+            channel_managers,
+            pool_config,
             clients: CacheBuilder::new(1024)
                 .time_to_live(Duration::from_secs(30 * 60))
                 .time_to_idle(Duration::from_secs(5 * 60))
                 .build(),
+            health_check_started: Arc::new(AtomicBool::new(false)),
         }
     }
 
     pub async fn get_client(&self, datanode: &Peer) -> Client {
+        self.peer_clients(datanode).await.pick().clone()
+    }
+
+    async fn peer_clients(&self, datanode: &Peer) -> Arc<PeerClients> {
         self.clients
             .get_with_by_ref(datanode, async move {
-                Client::with_manager_and_urls(
-                    self.channel_manager.clone(),
-                    vec![datanode.addr.clone()],
-                )
+                let clients = self
+                    .channel_managers
+                    .iter()
+                    .map(|channel_manager| {
+                        Client::with_manager_and_urls(
+                            channel_manager.clone(),
+                            vec![datanode.addr.clone()],
+                        )
+                    })
+                    .collect();
+                Arc::new(PeerClients::new(
+                    clients,
+                    self.pool_config.max_in_flight_per_peer,
+                ))
             })
             .await
     }
 
+    fn start_health_check(&self) {
+        if self
+            .health_check_started
+            .compare_exchange(false, true, Ordering::Relaxed, Ordering::Relaxed)
+            .is_err()
+        {
+            return;
+        }
+
+        let clients = self.clients.clone();
+        let channel_managers = self.channel_managers.clone();
+        let interval = self.pool_config.health_check_interval;
+        let _handle = common_runtime::spawn_bg(async move {
+            check_health_in_loop(clients, channel_managers, interval).await;
+        });
+        info!("DatanodeClients: health check of channels is started");
+    }
+
     #[cfg(feature = "testing")]
     pub async fn insert_client(&self, datanode: Peer, client: Client) {
-        self.clients.insert(datanode, client).await
+        self.clients
+            .insert(datanode, Arc::new(PeerClients::new(vec![client], 0)))
+            .await
+    }
+}
+
+async fn check_health_in_loop(
+    clients: Cache<Peer, Arc<PeerClients>>,
+    channel_managers: Vec<ChannelManager>,
+    interval: Duration,
+) {
+    let mut interval = tokio::time::interval(interval);
+
+    loop {
+        let _ = interval.tick().await;
+        let channel_managers = &channel_managers;
+        let checks = clients.iter().flat_map(|(peer, peer_clients)| {
+            (0..peer_clients.clients.len()).map(move |slot| {
+                let peer = peer.clone();
+                let peer_clients = peer_clients.clone();
+                async move {
+                    let pooled = &peer_clients.clients[slot];
+                    match pooled.client.health_check().await {
+                        Ok(()) => {
+                            if !pooled.healthy.swap(true, Ordering::Relaxed) {
+                                info!("Channel {} to datanode {:?} is recovered", slot, peer);
+                            }
+                        }
+                        Err(e) => {
+                            // Evicts the broken channel, the next request reconnects.
+                            if let Some(channel_manager) = channel_managers.get(slot) {
+                                channel_manager.retain_channel(|addr, _| *addr != peer.addr);
+                            }
+                            if pooled.healthy.swap(false, Ordering::Relaxed) {
+                                warn!(e; "Channel {} to datanode {:?} is unhealthy", slot, peer);
+                            }
+                        }
+                    }
+                }
+            })
+        });
+        futures_util::future::join_all(checks).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pick_healthy_client() {
+        let clients = PeerClients::new(
+            vec![
+                Client::with_urls(["127.0.0.1:3001"]),
+                Client::with_urls(["127.0.0.1:3001"]),
+                Client::with_urls(["127.0.0.1:3001"]),
+            ],
+            2,
+        );
+        let picked = |clients: &PeerClients| {
+            let client = clients.pick();
+            clients
+                .clients
+                .iter()
+                .position(|pooled| std::ptr::eq(&pooled.client, client))
+                .unwrap()
+        };
+        assert_eq!(
+            vec![0, 1, 2, 0],
+            (0..4).map(|_| picked(&clients)).collect::<Vec<_>>()
+        );
+
+        clients.clients[1].healthy.store(false, Ordering::Relaxed);
+        assert_eq!(
+            vec![2, 2, 0, 2],
+            (0..4).map(|_| picked(&clients)).collect::<Vec<_>>()
+        );
+
+        for pooled in &clients.clients {
+            pooled.healthy.store(false, Ordering::Relaxed);
+        }
+        assert_eq!(
+            vec![2, 0, 1],
+            (0..3).map(|_| picked(&clients)).collect::<Vec<_>>()
+        );
+
+        let in_flight = clients.in_flight.unwrap();
+        let _permits = (
+            in_flight.clone().try_acquire_owned().unwrap(),
+            in_flight.clone().try_acquire_owned().unwrap(),
+        );
+        assert!(in_flight.try_acquire_owned().is_err());
     }
 }
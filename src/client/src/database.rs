@@ -18,16 +18,16 @@ use api::v1::greptime_request::Request;
 use api::v1::query_request::Query;
 use api::v1::{
     AlterExpr, AuthHeader, CreateTableExpr, DdlRequest, DeleteRequests, DropTableExpr,
-    GreptimeRequest, InsertRequests, PromRangeQuery, QueryRequest, RequestHeader,
+    FlightMetadata, GreptimeRequest, InsertRequests, PromRangeQuery, QueryRequest, RequestHeader,
     RowInsertRequests, TruncateTableExpr,
 };
-use arrow_flight::Ticket;
+use arrow_flight::{FlightDescriptor, Ticket};
 use async_stream::stream;
 use common_error::ext::{BoxedError, ErrorExt};
-use common_grpc::flight::{FlightDecoder, FlightMessage};
+use common_grpc::flight::{FlightDecoder, FlightEncoder, FlightMessage};
 use common_query::Output;
 use common_recordbatch::error::ExternalSnafu;
-use common_recordbatch::RecordBatchStreamWrapper;
+use common_recordbatch::{RecordBatch, RecordBatchStreamWrapper};
 use common_telemetry::logging;
 use common_telemetry::tracing_context::W3cTrace;
 use futures_util::StreamExt;
@@ -146,6 +146,74 @@ impl Database {
         self.handle(Request::Deletes(request)).await
     }
 
+    /// Inserts record batches into the table through Flight `DoPut`, the batches must have
+    /// the same schema. Returns the number of affected rows.
+    ///
+    /// The server reads semantic types of columns from the metadata of the schema, see
+    /// `SEMANTIC_TYPE_KEY` in the server.
+    pub async fn bulk_insert(&self, table_name: &str, batches: Vec<RecordBatch>) -> Result<u32> {
+        let _timer = metrics::METRIC_GRPC_BULK_INSERT.start_timer();
+        let Some(first_batch) = batches.first() else {
+            return Ok(0);
+        };
+
+        // The first message carries the table name and the request header.
+        let mut encoder = FlightEncoder::default();
+        let mut schema_data = encoder.encode(FlightMessage::Schema(first_batch.schema.clone()));
+        let mut descriptor = FlightDescriptor::new_path(vec![table_name.to_string()]);
+        descriptor.cmd = self.request_header().encode_to_vec().into();
+        schema_data.flight_descriptor = Some(descriptor);
+        let mut flight_data = Vec::with_capacity(batches.len() + 1);
+        flight_data.push(schema_data);
+        flight_data.extend(
+            batches
+                .into_iter()
+                .map(|batch| encoder.encode(FlightMessage::Recordbatch(batch))),
+        );
+
+        let mut client = self.client.make_flight_client()?;
+        let response = client
+            .mut_inner()
+            .do_put(futures_util::stream::iter(flight_data))
+            .await
+            .map_err(|e| {
+                let tonic_code = e.code();
+                let e: error::Error = e.into();
+                let code = e.status_code();
+                let msg = e.to_string();
+                let error = Error::FlightPut {
+                    tonic_code,
+                    addr: client.addr().to_string(),
+                    source: BoxedError::new(ServerSnafu { code, msg }.build()),
+                };
+                logging::error!(
+                    "Failed to do Flight put, addr: {}, code: {}, source: {:?}",
+                    client.addr(),
+                    tonic_code,
+                    error
+                );
+                error
+            })?;
+
+        let mut results = response.into_inner();
+        let mut affected_rows = 0;
+        while let Some(result) = results.next().await {
+            let result = result?;
+            let metadata = FlightMetadata::decode(result.app_metadata.as_ref()).map_err(|e| {
+                IllegalFlightMessagesSnafu {
+                    reason: format!("Invalid metadata of PutResult: {e}"),
+                }
+                .build()
+            })?;
+            affected_rows += metadata
+                .affected_rows
+                .map(|rows| rows.value as usize)
+                .unwrap_or_default();
+        }
+
+        Ok(affected_rows as u32)
+    }
+
     async fn handle(&self, request: Request) -> Result<u32> {
         let mut client = self.client.make_database_client()?.inner;
         let request = self.to_rpc_request(request);
@@ -156,18 +224,22 @@ impl Database {
     #[inline]
     fn to_rpc_request(&self, request: Request) -> GreptimeRequest {
         GreptimeRequest {
-            header: Some(RequestHeader {
-                catalog: self.catalog.clone(),
-                schema: self.schema.clone(),
-                authorization: self.ctx.auth_header.clone(),
-                dbname: self.dbname.clone(),
-                // TODO(Taylor-lagrange): add client grpc tracing
-                tracing_context: W3cTrace::new(),
-            }),
+            header: Some(self.request_header()),
             request: Some(request),
         }
     }
 
+    fn request_header(&self) -> RequestHeader {
+        RequestHeader {
+            catalog: self.catalog.clone(),
+            schema: self.schema.clone(),
+            authorization: self.ctx.auth_header.clone(),
+            dbname: self.dbname.clone(),
+            // TODO(Taylor-lagrange): add client grpc tracing
+            tracing_context: W3cTrace::new(),
+        }
+    }
+
     pub async fn sql<S>(&self, sql: S) -> Result<Output>
     where
         S: AsRef<str>,
@@ -295,10 +367,12 @@ impl Database {
                 );
                 Ok(Output::AffectedRows(rows))
             }
-            FlightMessage::Recordbatch(_) => IllegalFlightMessagesSnafu {
-                reason: "The first flight message cannot be a RecordBatch message",
+            FlightMessage::Recordbatch(_) | FlightMessage::Metrics(_) => {
+                IllegalFlightMessagesSnafu {
+                    reason: "The first flight message cannot be a RecordBatch or Metrics message",
+                }
+                .fail()
             }
-            .fail(),
             FlightMessage::Schema(schema) => {
                 let stream = Box::pin(stream!({
                     while let Some(flight_message) = flight_message_stream.next().await {
@@ -13,6 +13,7 @@
 // limitations under the License.
 
 use std::any::Any;
+use std::time::Duration;
 
 use common_error::ext::{BoxedError, ErrorExt};
 use common_error::status_code::StatusCode;
@@ -35,6 +36,13 @@ pub enum Error {
         source: BoxedError,
     },
 
+    #[snafu(display("Failed to do Flight put, code: {}", tonic_code))]
+    FlightPut {
+        addr: String,
+        tonic_code: Code,
+        source: BoxedError,
+    },
+
     #[snafu(display("Failure occurs during handling request"))]
     HandleRequest {
         location: Location,
@@ -78,6 +86,9 @@ pub enum Error {
 
     #[snafu(display("Failed to send request with streaming: {}", err_msg))]
     ClientStreaming { err_msg: String, location: Location },
+
+    #[snafu(display("Too many in-flight requests to the datanode"))]
+    TooManyInFlightRequests { location: Location },
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
@@ -93,12 +104,14 @@ impl ErrorExt for Error {
 
             Error::Server { code, .. } => *code,
             Error::FlightGet { source, .. }
+            | Error::FlightPut { source, .. }
             | Error::HandleRequest { source, .. }
             | Error::RegionServer { source, .. } => source.status_code(),
             Error::CreateChannel { source, .. } | Error::ConvertFlightData { source, .. } => {
                 source.status_code()
             }
             Error::IllegalGrpcClientState { .. } => StatusCode::Unexpected,
+            Error::TooManyInFlightRequests { .. } => StatusCode::RateLimited,
         }
     }
 
@@ -142,4 +155,30 @@ impl Error {
             }
         )
     }
+
+    /// Returns the suggested duration to wait before retrying the request.
+    pub fn retry_after(&self) -> Option<Duration> {
+        self.status_code()
+            .retry_after_ms()
+            .map(Duration::from_millis)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_retry_after_from_status() {
+        let mut metadata = tonic::metadata::MetadataMap::new();
+        let code = StatusCode::RuntimeResourcesExhausted as u32;
+        metadata.insert(GREPTIME_ERROR_CODE, code.to_string().parse().unwrap());
+        let status =
+            Status::with_metadata(Code::ResourceExhausted, "write buffer is full", metadata);
+        let err = Error::from(status);
+        assert_eq!(Some(Duration::from_millis(1000)), err.retry_after());
+
+        let err = Error::from(Status::invalid_argument("bad request"));
+        assert_eq!(None, err.retry_after());
+    }
 }
@@ -24,6 +24,8 @@ lazy_static! {
         register_histogram!("grpc_insert", "grpc insert").unwrap();
     pub static ref METRIC_GRPC_DELETE: Histogram =
         register_histogram!("grpc_delete", "grpc delete").unwrap();
+    pub static ref METRIC_GRPC_BULK_INSERT: Histogram =
+        register_histogram!("grpc_bulk_insert", "grpc bulk insert").unwrap();
     pub static ref METRIC_GRPC_SQL: Histogram =
         register_histogram!("grpc_sql", "grpc sql").unwrap();
     pub static ref METRIC_GRPC_LOGICAL_PLAN: Histogram =
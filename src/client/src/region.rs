@@ -12,6 +12,10 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+
 use api::v1::region::{QueryRequest, RegionRequest, RegionResponse};
 use api::v1::ResponseHeader;
 use arrow_flight::Ticket;
@@ -22,22 +26,56 @@ use common_error::status_code::StatusCode;
 use common_grpc::flight::{FlightDecoder, FlightMessage};
 use common_meta::datanode_manager::{AffectedRows, Datanode};
 use common_meta::error::{self as meta_error, Result as MetaResult};
-use common_recordbatch::error::ExternalSnafu;
-use common_recordbatch::{RecordBatchStreamWrapper, SendableRecordBatchStream};
+use common_recordbatch::error::{ExternalSnafu, Result as RecordBatchResult};
+use common_recordbatch::{
+    RecordBatch, RecordBatchMetrics, RecordBatchStream, SendableRecordBatchStream,
+};
 use common_telemetry::error;
+use datatypes::schema::SchemaRef;
+use futures_util::stream::BoxStream;
+use futures_util::Stream;
 use prost::Message;
 use snafu::{location, Location, OptionExt, ResultExt};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
 use tokio_stream::StreamExt;
 
 use crate::error::{
     self, ConvertFlightDataSnafu, IllegalDatabaseResponseSnafu, IllegalFlightMessagesSnafu,
-    MissingFieldSnafu, Result, ServerSnafu,
+    MissingFieldSnafu, Result, ServerSnafu, TooManyInFlightRequestsSnafu,
 };
 use crate::{metrics, Client, Error};
 
+/// Record batch stream of a region query that reports metrics sent by the datanode.
+struct RegionQueryStream {
+    schema: SchemaRef,
+    stream: BoxStream<'static, RecordBatchResult<RecordBatch>>,
+    /// Metrics of the query, the datanode sends them after all record batches.
+    metrics: Arc<Mutex<Option<RecordBatchMetrics>>>,
+}
+
+impl RecordBatchStream for RegionQueryStream {
+    fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+
+    fn metrics(&self) -> Option<RecordBatchMetrics> {
+        self.metrics.lock().unwrap().clone()
+    }
+}
+
+impl Stream for RegionQueryStream {
+    type Item = RecordBatchResult<RecordBatch>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.stream.as_mut().poll_next(cx)
+    }
+}
+
 #[derive(Debug)]
 pub struct RegionRequester {
     client: Client,
+    /// Limits in-flight requests to the datanode.
+    in_flight: Option<Arc<Semaphore>>,
 }
 
 #[async_trait]
@@ -70,10 +108,35 @@ impl Datanode for RegionRequester {
 
 impl RegionRequester {
     pub fn new(client: Client) -> Self {
-        Self { client }
+        Self {
+            client,
+            in_flight: None,
+        }
+    }
+
+    /// Sets the semaphore to limit in-flight requests, a request fails if there is no
+    /// available permit.
+    pub fn with_in_flight_limit(mut self, in_flight: Option<Arc<Semaphore>>) -> Self {
+        self.in_flight = in_flight;
+        self
+    }
+
+    fn acquire_in_flight(&self) -> Result<Option<OwnedSemaphorePermit>> {
+        self.in_flight
+            .as_ref()
+            .map(|in_flight| {
+                in_flight
+                    .clone()
+                    .try_acquire_owned()
+                    .ok()
+                    .context(TooManyInFlightRequestsSnafu)
+            })
+            .transpose()
     }
 
     pub async fn do_get_inner(&self, ticket: Ticket) -> Result<SendableRecordBatchStream> {
+        // The permit is released once the stream is dropped.
+        let permit = self.acquire_in_flight()?;
         let mut flight_client = self.client.make_flight_client()?;
         let response = flight_client
             .mut_inner()
@@ -119,27 +182,35 @@ impl RegionRequester {
             .fail();
         };
 
+        let metrics = Arc::new(Mutex::new(None));
+        let stream_metrics = metrics.clone();
         let stream = Box::pin(stream!({
+            let _permit = permit;
             while let Some(flight_message) = flight_message_stream.next().await {
                 let flight_message = flight_message
                     .map_err(BoxedError::new)
                     .context(ExternalSnafu)?;
-                let FlightMessage::Recordbatch(record_batch) = flight_message else {
-                    yield IllegalFlightMessagesSnafu {
-                            reason: "A Schema message must be succeeded exclusively by a set of RecordBatch messages"
-                        }
-                        .fail()
-                        .map_err(BoxedError::new)
-                        .context(ExternalSnafu);
-                    break;
-                };
-                yield Ok(record_batch);
+                match flight_message {
+                    FlightMessage::Recordbatch(record_batch) => yield Ok(record_batch),
+                    FlightMessage::Metrics(metrics) => {
+                        *stream_metrics.lock().unwrap() = Some(metrics);
+                    }
+                    _ => {
+                        yield IllegalFlightMessagesSnafu {
+                                reason: "A Schema message must be succeeded exclusively by a set of RecordBatch messages"
+                            }
+                            .fail()
+                            .map_err(BoxedError::new)
+                            .context(ExternalSnafu);
+                        break;
+                    }
+                }
             }
         }));
-        let record_batch_stream = RecordBatchStreamWrapper {
+        let record_batch_stream = RegionQueryStream {
             schema,
             stream,
-            output_ordering: None,
+            metrics,
         };
         Ok(Box::pin(record_batch_stream))
     }
@@ -155,6 +226,7 @@ impl RegionRequester {
             .with_label_values(&[request_type.as_str()])
             .start_timer();
 
+        let _permit = self.acquire_in_flight()?;
         let mut client = self.client.raw_region_client()?;
 
         let RegionResponse {
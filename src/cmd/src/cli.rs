@@ -23,6 +23,7 @@ mod helper;
 // Wait for https://github.com/GreptimeTeam/greptimedb/issues/2373
 #[allow(unused)]
 mod repl;
+mod seed;
 // TODO(weny): Removes it
 #[allow(deprecated)]
 mod upgrade;
@@ -35,6 +36,7 @@ pub use repl::Repl;
 use upgrade::UpgradeCommand;
 
 use self::export::ExportCommand;
+use self::seed::SeedCommand;
 use crate::error::Result;
 use crate::options::{CliOptions, Options};
 use crate::App;
@@ -99,6 +101,7 @@ enum SubCommand {
     Upgrade(UpgradeCommand),
     Bench(BenchTableMetadataCommand),
     Export(ExportCommand),
+    Seed(SeedCommand),
 }
 
 impl SubCommand {
@@ -108,6 +111,7 @@ impl SubCommand {
             SubCommand::Upgrade(cmd) => cmd.build().await,
             SubCommand::Bench(cmd) => cmd.build().await,
             SubCommand::Export(cmd) => cmd.build().await,
+            SubCommand::Seed(cmd) => cmd.build().await,
         }
     }
 }
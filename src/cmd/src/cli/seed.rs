@@ -0,0 +1,653 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use clap::{Parser, ValueEnum};
+use client::api::v1::auth_header::AuthScheme;
+use client::api::v1::{Basic, SemanticType};
+use client::{Client, Database, DEFAULT_SCHEMA_NAME};
+use common_query::Output;
+use common_recordbatch::util::collect;
+use common_recordbatch::RecordBatch;
+use common_telemetry::info;
+use common_time::timestamp::TimeUnit;
+use common_time::Timestamp;
+use datatypes::prelude::{ConcreteDataType, DataType, Value, VectorRef};
+use datatypes::scalars::ScalarVector;
+use datatypes::schema::{ColumnSchema, Schema};
+use datatypes::vectors::StringVector;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use servers::grpc::flight::SEMANTIC_TYPE_KEY;
+use snafu::{ensure, OptionExt, ResultExt};
+
+use crate::cli::export::split_database;
+use crate::cli::{Instance, Tool};
+use crate::error::{
+    BulkInsertSnafu, CollectRecordBatchesSnafu, ConnectServerSnafu, EmptyResultSnafu,
+    IllegalConfigSnafu, NewRecordBatchSnafu, NotDataFromOutputSnafu, RequestDatabaseSnafu, Result,
+};
+
+/// Distribution of values of numeric fields.
+#[derive(Debug, Default, Clone, Copy, PartialEq, ValueEnum)]
+enum ValueDistribution {
+    /// Uniform in `[0, 100)`.
+    Uniform,
+    /// Normal with mean 50 and standard deviation 10.
+    Normal,
+    /// Random walk of each series, starting from 50.
+    #[default]
+    RandomWalk,
+}
+
+/// Generates synthetic data for an existing table and writes it through the bulk
+/// insert path.
+#[derive(Debug, Default, Parser)]
+pub struct SeedCommand {
+    /// Server address to connect
+    #[clap(long)]
+    addr: String,
+
+    /// The database of the table, e.g. "greptime-public".
+    #[clap(long, default_value = "greptime-public")]
+    database: String,
+
+    /// Name of the table to write.
+    #[clap(long)]
+    table: String,
+
+    /// Number of series to generate.
+    #[clap(long, default_value = "1000")]
+    series: usize,
+
+    /// Max number of distinct values of each tag column except the last one. The last
+    /// tag column has one value per series.
+    #[clap(long, default_value = "100")]
+    tag_cardinality: usize,
+
+    /// Skew of rows among series. The series `i` writes a row at each timestamp with
+    /// probability `(i + 1) ^ -skew`, 0 means every series writes every timestamp.
+    #[clap(long, default_value = "0")]
+    skew: f64,
+
+    /// Distribution of values of numeric fields.
+    #[clap(long, value_enum, default_value_t)]
+    distribution: ValueDistribution,
+
+    /// Start of the time range in RFC3339, defaults to one hour before the end.
+    #[clap(long)]
+    start: Option<String>,
+
+    /// End of the time range (exclusive) in RFC3339, defaults to now.
+    #[clap(long)]
+    end: Option<String>,
+
+    /// Interval between two rows of a series, e.g. 10s.
+    #[clap(long, default_value = "10s")]
+    interval: String,
+
+    /// Number of rows in each bulk insert.
+    #[clap(long, default_value = "10000")]
+    batch_size: usize,
+
+    /// Seed of the random generator, generates the same data for the same seed.
+    #[clap(long)]
+    seed: Option<u64>,
+
+    /// basic authentication for connecting to the server
+    #[clap(long)]
+    auth_basic: Option<String>,
+}
+
+impl SeedCommand {
+    pub async fn build(&self) -> Result<Instance> {
+        let client = Client::with_urls([self.addr.clone()]);
+        client
+            .health_check()
+            .await
+            .with_context(|_| ConnectServerSnafu {
+                addr: self.addr.clone(),
+            })?;
+        let (catalog, schema) = split_database(&self.database)?;
+        let mut database_client = Database::new(
+            catalog,
+            schema.unwrap_or(DEFAULT_SCHEMA_NAME.to_string()),
+            client,
+        );
+        if let Some(auth_basic) = &self.auth_basic {
+            let (username, password) = auth_basic.split_once(':').context(IllegalConfigSnafu {
+                msg: "auth_basic cannot be split by ':'".to_string(),
+            })?;
+            database_client.set_auth(AuthScheme::Basic(Basic {
+                username: username.to_string(),
+                password: password.to_string(),
+            }));
+        }
+
+        let end = match &self.end {
+            Some(end) => parse_time(end)?,
+            None => Utc::now().timestamp_millis(),
+        };
+        let start = match &self.start {
+            Some(start) => parse_time(start)?,
+            None => end - Duration::from_secs(3600).as_millis() as i64,
+        };
+        let interval = humantime::parse_duration(&self.interval)
+            .ok()
+            .filter(|interval| interval.as_millis() > 0)
+            .with_context(|| IllegalConfigSnafu {
+                msg: format!("invalid interval {}", self.interval),
+            })?;
+        let options = GenerateOptions {
+            series: self.series,
+            tag_cardinality: self.tag_cardinality,
+            skew: self.skew,
+            distribution: self.distribution,
+            start,
+            end,
+            interval: interval.as_millis() as i64,
+            batch_size: self.batch_size,
+            seed: self.seed.unwrap_or_else(rand::random),
+        };
+        options.validate()?;
+
+        Ok(Instance::new(Box::new(Seed {
+            client: database_client,
+            table: self.table.clone(),
+            options,
+        })))
+    }
+}
+
+pub struct Seed {
+    client: Database,
+    table: String,
+    options: GenerateOptions,
+}
+
+impl Seed {
+    /// Returns columns of the table.
+    async fn describe_table(&self) -> Result<Vec<SeedColumn>> {
+        let sql = format!("DESC TABLE {}", self.table);
+        let result = self
+            .client
+            .sql(&sql)
+            .await
+            .with_context(|_| RequestDatabaseSnafu { sql })?;
+        let Output::Stream(stream) = result else {
+            NotDataFromOutputSnafu.fail()?
+        };
+        let record_batch = collect(stream)
+            .await
+            .context(CollectRecordBatchesSnafu)?
+            .pop()
+            .context(EmptyResultSnafu)?;
+
+        // Columns of the output are "Column", "Type", "Key", "Null", "Default" and
+        // "Semantic Type".
+        let string_column = |idx: usize| {
+            record_batch
+                .column(idx)
+                .as_any()
+                .downcast_ref::<StringVector>()
+                .unwrap()
+        };
+        let names = string_column(0);
+        let types = string_column(1);
+        let semantic_types = string_column(5);
+        (0..record_batch.num_rows())
+            .map(|i| {
+                SeedColumn::try_new(
+                    names.get_data(i).unwrap(),
+                    types.get_data(i).unwrap(),
+                    semantic_types.get_data(i).unwrap(),
+                )
+            })
+            .collect()
+    }
+}
+
+#[async_trait]
+impl Tool for Seed {
+    async fn do_work(&self) -> Result<()> {
+        let columns = self.describe_table().await?;
+        let mut generator = DataGenerator::try_new(columns, self.options.clone())?;
+
+        let mut total_rows = 0;
+        while let Some(batch) = generator.next_batch()? {
+            total_rows +=
+                self.client
+                    .bulk_insert(&self.table, vec![batch])
+                    .await
+                    .context(BulkInsertSnafu { table: &self.table })? as usize;
+            info!("Written {} rows to table {}", total_rows, self.table);
+        }
+        info!(
+            "Finished seeding table {} with {} rows",
+            self.table, total_rows
+        );
+
+        Ok(())
+    }
+}
+
+/// Parses a RFC3339 time into milliseconds.
+fn parse_time(time: &str) -> Result<i64> {
+    let time = DateTime::parse_from_rfc3339(time)
+        .ok()
+        .with_context(|| IllegalConfigSnafu {
+            msg: format!("invalid time {time}, expect RFC3339"),
+        })?;
+    Ok(time.timestamp_millis())
+}
+
+/// Options to generate data.
+#[derive(Debug, Clone)]
+struct GenerateOptions {
+    series: usize,
+    tag_cardinality: usize,
+    skew: f64,
+    distribution: ValueDistribution,
+    /// Start of the time range in milliseconds.
+    start: i64,
+    /// End of the time range in milliseconds (exclusive).
+    end: i64,
+    /// Interval between rows of a series in milliseconds.
+    interval: i64,
+    batch_size: usize,
+    seed: u64,
+}
+
+impl GenerateOptions {
+    fn validate(&self) -> Result<()> {
+        ensure!(
+            self.series > 0 && self.tag_cardinality > 0 && self.batch_size > 0,
+            IllegalConfigSnafu {
+                msg: "series, tag_cardinality and batch_size must be positive",
+            }
+        );
+        ensure!(
+            self.skew >= 0.0,
+            IllegalConfigSnafu {
+                msg: format!("skew {} must not be negative", self.skew),
+            }
+        );
+        ensure!(
+            self.start < self.end,
+            IllegalConfigSnafu {
+                msg: "start must be earlier than end",
+            }
+        );
+        Ok(())
+    }
+}
+
+/// A column of the table to seed.
+#[derive(Debug, Clone, PartialEq)]
+struct SeedColumn {
+    name: String,
+    data_type: ConcreteDataType,
+    semantic_type: SemanticType,
+}
+
+impl SeedColumn {
+    /// Creates a column from the output of `DESC TABLE`.
+    fn try_new(name: &str, data_type: &str, semantic_type: &str) -> Result<SeedColumn> {
+        let unsupported = || IllegalConfigSnafu {
+            msg: format!("unsupported column {name} with type {data_type} {semantic_type}"),
+        };
+        let data_type = parse_data_type(data_type).with_context(unsupported)?;
+        let semantic_type = match semantic_type {
+            "TAG" if data_type.is_string() || data_type.is_numeric() => SemanticType::Tag,
+            "FIELD" => SemanticType::Field,
+            "TIMESTAMP" if data_type.is_timestamp() => SemanticType::Timestamp,
+            _ => return unsupported().fail(),
+        };
+
+        Ok(SeedColumn {
+            name: name.to_string(),
+            data_type,
+            semantic_type,
+        })
+    }
+
+    fn column_schema(&self) -> ColumnSchema {
+        let semantic_type = match self.semantic_type {
+            SemanticType::Tag => "TAG",
+            SemanticType::Field => "FIELD",
+            SemanticType::Timestamp => "TIMESTAMP",
+        };
+        let is_time_index = self.semantic_type == SemanticType::Timestamp;
+        ColumnSchema::new(&self.name, self.data_type.clone(), !is_time_index)
+            .with_metadata(HashMap::from([(
+                SEMANTIC_TYPE_KEY.to_string(),
+                semantic_type.to_string(),
+            )]))
+            .with_time_index(is_time_index)
+    }
+}
+
+/// Parses names of data types in the output of `DESC TABLE`.
+fn parse_data_type(name: &str) -> Option<ConcreteDataType> {
+    let data_type = match name {
+        "Boolean" => ConcreteDataType::boolean_datatype(),
+        "Int8" => ConcreteDataType::int8_datatype(),
+        "Int16" => ConcreteDataType::int16_datatype(),
+        "Int32" => ConcreteDataType::int32_datatype(),
+        "Int64" => ConcreteDataType::int64_datatype(),
+        "UInt8" => ConcreteDataType::uint8_datatype(),
+        "UInt16" => ConcreteDataType::uint16_datatype(),
+        "UInt32" => ConcreteDataType::uint32_datatype(),
+        "UInt64" => ConcreteDataType::uint64_datatype(),
+        "Float32" => ConcreteDataType::float32_datatype(),
+        "Float64" => ConcreteDataType::float64_datatype(),
+        "String" => ConcreteDataType::string_datatype(),
+        "TimestampSecond" => ConcreteDataType::timestamp_second_datatype(),
+        "TimestampMillisecond" => ConcreteDataType::timestamp_millisecond_datatype(),
+        "TimestampMicrosecond" => ConcreteDataType::timestamp_microsecond_datatype(),
+        "TimestampNanosecond" => ConcreteDataType::timestamp_nanosecond_datatype(),
+        _ => return None,
+    };
+    Some(data_type)
+}
+
+/// Generates rows of all series timestamp by timestamp.
+struct DataGenerator {
+    columns: Vec<SeedColumn>,
+    schema: Arc<Schema>,
+    options: GenerateOptions,
+    rng: StdRng,
+    /// Current values of the random walk of each series and column.
+    walk_values: Vec<f64>,
+    /// Timestamp in milliseconds of the next row.
+    next_ts: i64,
+    /// Series of the next row.
+    next_series: usize,
+}
+
+impl DataGenerator {
+    fn try_new(columns: Vec<SeedColumn>, options: GenerateOptions) -> Result<DataGenerator> {
+        ensure!(
+            columns
+                .iter()
+                .any(|column| column.semantic_type == SemanticType::Timestamp),
+            IllegalConfigSnafu {
+                msg: "table has no time index",
+            }
+        );
+        let schema = Arc::new(Schema::new(
+            columns.iter().map(SeedColumn::column_schema).collect(),
+        ));
+
+        Ok(DataGenerator {
+            walk_values: vec![50.0; options.series * columns.len()],
+            rng: StdRng::seed_from_u64(options.seed),
+            next_ts: options.start,
+            next_series: 0,
+            columns,
+            schema,
+            options,
+        })
+    }
+
+    /// Returns the next batch, or `None` if all rows are generated.
+    fn next_batch(&mut self) -> Result<Option<RecordBatch>> {
+        let mut builders: Vec<_> = self
+            .columns
+            .iter()
+            .map(|column| {
+                column
+                    .data_type
+                    .create_mutable_vector(self.options.batch_size)
+            })
+            .collect();
+        let mut num_rows = 0;
+        while num_rows < self.options.batch_size && self.next_ts < self.options.end {
+            let series = self.next_series;
+            if self.options.skew == 0.0
+                || self
+                    .rng
+                    .gen_bool((series as f64 + 1.0).powf(-self.options.skew))
+            {
+                for (column_idx, builder) in builders.iter_mut().enumerate() {
+                    let value = self.generate_value(series, column_idx);
+                    builder.push_value_ref(value.as_value_ref());
+                }
+                num_rows += 1;
+            }
+
+            self.next_series += 1;
+            if self.next_series == self.options.series {
+                self.next_series = 0;
+                self.next_ts += self.options.interval;
+            }
+        }
+        if num_rows == 0 {
+            return Ok(None);
+        }
+
+        let columns: Vec<VectorRef> = builders
+            .iter_mut()
+            .map(|builder| builder.to_vector())
+            .collect();
+        RecordBatch::new(self.schema.clone(), columns)
+            .map(Some)
+            .context(NewRecordBatchSnafu)
+    }
+
+    /// Generates the value of the column at `column_idx` for the next row of `series`.
+    fn generate_value(&mut self, series: usize, column_idx: usize) -> Value {
+        let column = &self.columns[column_idx];
+        match column.semantic_type {
+            SemanticType::Timestamp => timestamp_value(&column.data_type, self.next_ts),
+            SemanticType::Tag => {
+                let is_last_tag = self.columns[column_idx + 1..]
+                    .iter()
+                    .all(|c| c.semantic_type != SemanticType::Tag);
+                let id = if is_last_tag {
+                    series
+                } else {
+                    series % self.options.tag_cardinality
+                };
+                if column.data_type.is_string() {
+                    Value::from(format!("{}-{}", column.name, id))
+                } else {
+                    numeric_value(&column.data_type, id as f64)
+                }
+            }
+            SemanticType::Field => {
+                let data_type = column.data_type.clone();
+                if data_type.is_timestamp() {
+                    timestamp_value(&data_type, self.next_ts)
+                } else if data_type.is_string() {
+                    Value::from(format!("value-{}", self.rng.gen_range(0..100)))
+                } else if data_type.is_boolean() {
+                    Value::Boolean(self.rng.gen_bool(0.5))
+                } else {
+                    let value = self.generate_number(series, column_idx);
+                    numeric_value(&data_type, value)
+                }
+            }
+        }
+    }
+
+    /// Generates a number in the distribution.
+    fn generate_number(&mut self, series: usize, column_idx: usize) -> f64 {
+        match self.options.distribution {
+            ValueDistribution::Uniform => self.rng.gen_range(0.0..100.0),
+            ValueDistribution::Normal => 50.0 + 10.0 * self.standard_normal(),
+            ValueDistribution::RandomWalk => {
+                let step = self.standard_normal();
+                let value = &mut self.walk_values[series * self.columns.len() + column_idx];
+                *value += step;
+                *value
+            }
+        }
+    }
+
+    /// Samples the standard normal distribution by the Box-Muller transform.
+    fn standard_normal(&mut self) -> f64 {
+        let u1: f64 = 1.0 - self.rng.gen::<f64>();
+        let u2: f64 = self.rng.gen();
+        (-2.0 * u1.ln()).sqrt() * (2.0 * std::f64::consts::PI * u2).cos()
+    }
+}
+
+/// Converts the timestamp in milliseconds to a value of `data_type`.
+fn timestamp_value(data_type: &ConcreteDataType, millis: i64) -> Value {
+    // Safety: we check the type while creating the column.
+    let unit = data_type.as_timestamp().unwrap().unit();
+    let value = match unit {
+        TimeUnit::Second => millis / 1000,
+        TimeUnit::Millisecond => millis,
+        TimeUnit::Microsecond => millis * 1000,
+        TimeUnit::Nanosecond => millis * 1_000_000,
+    };
+    Value::Timestamp(Timestamp::new(value, unit))
+}
+
+/// Converts the number to a value of the numeric `data_type`.
+fn numeric_value(data_type: &ConcreteDataType, value: f64) -> Value {
+    match data_type {
+        ConcreteDataType::Int8(_) => Value::Int8(value.round() as i8),
+        ConcreteDataType::Int16(_) => Value::Int16(value.round() as i16),
+        ConcreteDataType::Int32(_) => Value::Int32(value.round() as i32),
+        ConcreteDataType::Int64(_) => Value::Int64(value.round() as i64),
+        ConcreteDataType::UInt8(_) => Value::UInt8(value.round() as u8),
+        ConcreteDataType::UInt16(_) => Value::UInt16(value.round() as u16),
+        ConcreteDataType::UInt32(_) => Value::UInt32(value.round() as u32),
+        ConcreteDataType::UInt64(_) => Value::UInt64(value.round() as u64),
+        ConcreteDataType::Float32(_) => Value::Float32((value as f32).into()),
+        ConcreteDataType::Float64(_) => Value::Float64(value.into()),
+        // Other types are rejected while creating columns.
+        _ => unreachable!(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn new_columns() -> Vec<SeedColumn> {
+        vec![
+            SeedColumn::try_new("host", "String", "TAG").unwrap(),
+            SeedColumn::try_new("idc", "String", "TAG").unwrap(),
+            SeedColumn::try_new("cpu", "Float64", "FIELD").unwrap(),
+            SeedColumn::try_new("count", "UInt32", "FIELD").unwrap(),
+            SeedColumn::try_new("ts", "TimestampMillisecond", "TIMESTAMP").unwrap(),
+        ]
+    }
+
+    fn new_options() -> GenerateOptions {
+        GenerateOptions {
+            series: 3,
+            tag_cardinality: 2,
+            skew: 0.0,
+            distribution: ValueDistribution::RandomWalk,
+            start: 0,
+            end: 30_000,
+            interval: 10_000,
+            batch_size: 4,
+            seed: 0,
+        }
+    }
+
+    #[test]
+    fn test_seed_column() {
+        let column = SeedColumn::try_new("ts", "TimestampSecond", "TIMESTAMP").unwrap();
+        assert_eq!(
+            ConcreteDataType::timestamp_second_datatype(),
+            column.data_type
+        );
+        assert!(column.column_schema().is_time_index());
+
+        assert!(SeedColumn::try_new("a", "Binary", "FIELD").is_err());
+        assert!(SeedColumn::try_new("a", "Boolean", "TAG").is_err());
+        assert!(SeedColumn::try_new("a", "String", "TIMESTAMP").is_err());
+    }
+
+    #[test]
+    fn test_generate_data() {
+        let mut generator = DataGenerator::try_new(new_columns(), new_options()).unwrap();
+        let mut batches = vec![];
+        while let Some(batch) = generator.next_batch().unwrap() {
+            batches.push(batch);
+        }
+        let num_rows: Vec<_> = batches.iter().map(|batch| batch.num_rows()).collect();
+        assert_eq!(vec![4, 4, 1], num_rows);
+
+        let batch = &batches[0];
+        let hosts: Vec<_> = (0..4).map(|i| batch.column(0).get(i)).collect();
+        let idcs: Vec<_> = (0..4).map(|i| batch.column(1).get(i)).collect();
+        let timestamps: Vec<_> = (0..4).map(|i| batch.column(4).get(i)).collect();
+        assert_eq!(
+            vec![
+                Value::from("host-0"),
+                Value::from("host-1"),
+                Value::from("host-0"),
+                Value::from("host-0"),
+            ],
+            hosts
+        );
+        assert_eq!(
+            vec![
+                Value::from("idc-0"),
+                Value::from("idc-1"),
+                Value::from("idc-2"),
+                Value::from("idc-0"),
+            ],
+            idcs
+        );
+        assert_eq!(
+            vec![
+                Value::Timestamp(Timestamp::new_millisecond(0)),
+                Value::Timestamp(Timestamp::new_millisecond(0)),
+                Value::Timestamp(Timestamp::new_millisecond(0)),
+                Value::Timestamp(Timestamp::new_millisecond(10_000)),
+            ],
+            timestamps
+        );
+        assert_eq!(0, batch.column(2).null_count());
+
+        // Generates the same data with the same seed.
+        let mut generator = DataGenerator::try_new(new_columns(), new_options()).unwrap();
+        let batch = generator.next_batch().unwrap().unwrap();
+        assert_eq!(batches[0], batch);
+    }
+
+    #[test]
+    fn test_generate_skewed_data() {
+        let options = GenerateOptions {
+            series: 100,
+            skew: 1.0,
+            end: 1_000_000,
+            batch_size: 100_000,
+            ..new_options()
+        };
+        let mut generator = DataGenerator::try_new(new_columns(), options).unwrap();
+        let batch = generator.next_batch().unwrap().unwrap();
+        // The first series writes all 100 timestamps, others write less.
+        let idcs = batch.column(1);
+        let first_series = (0..batch.num_rows())
+            .filter(|i| idcs.get(*i) == Value::from("idc-0"))
+            .count();
+        assert_eq!(100, first_series);
+        assert!(batch.num_rows() < 100 * 100);
+    }
+}
@@ -238,6 +238,19 @@ pub enum Error {
         error: std::io::Error,
     },
 
+    #[snafu(display("Failed to bulk insert into table {}", table))]
+    BulkInsert {
+        table: String,
+        source: client::error::Error,
+        location: Location,
+    },
+
+    #[snafu(display("Failed to create record batch"))]
+    NewRecordBatch {
+        source: common_recordbatch::error::Error,
+        location: Location,
+    },
+
     #[snafu(display("Other error"))]
     Other {
         source: BoxedError,
@@ -290,6 +303,8 @@ impl ErrorExt for Error {
 
             Error::SerdeJson { .. } | Error::FileIo { .. } => StatusCode::Unexpected,
 
+            Error::BulkInsert { source, .. } => source.status_code(),
+            Error::NewRecordBatch { source, .. } => source.status_code(),
             Error::Other { source, .. } => source.status_code(),
         }
     }
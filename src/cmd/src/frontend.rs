@@ -237,22 +237,38 @@ impl StartCommand {
             Arc::new(executor),
         );
 
-        let mut instance = FrontendBuilder::new(
-            meta_backend.clone(),
-            Arc::new(DatanodeClients::default()),
-            meta_client,
-        )
-        .with_cache_invalidator(meta_backend)
-        .with_plugin(plugins)
-        .with_heartbeat_task(heartbeat_task)
-        .try_build()
-        .await
-        .context(StartFrontendSnafu)?;
+        let client_opts = &opts.datanode.client;
+        let datanode_clients = Arc::new(DatanodeClients::with_pool_config(
+            client_opts.channel_config(),
+            client_opts.pool_config(),
+        ));
+
+        let mut instance =
+            FrontendBuilder::new(meta_backend.clone(), datanode_clients, meta_client)
+                .with_cache_invalidator(meta_backend)
+                .with_plugin(plugins)
+                .with_heartbeat_task(heartbeat_task)
+                .with_hedged_read(opts.hedged_read.clone())
+                .with_capture_dir(opts.capture_dir.clone())
+                .try_build()
+                .await
+                .context(StartFrontendSnafu)?;
 
         instance
             .build_export_metrics_task(&opts.export_metrics)
             .context(StartFrontendSnafu)?;
 
+        instance.build_pipeline_runner(&opts.pipeline);
+
+        instance
+            .build_query_rule(&opts.query_rule)
+            .context(StartFrontendSnafu)?;
+
+        instance
+            .build_write_spool(&opts.spool)
+            .await
+            .context(StartFrontendSnafu)?;
+
         instance
             .build_servers(opts)
             .await
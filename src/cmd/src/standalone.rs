@@ -21,6 +21,7 @@ use common_catalog::consts::MIN_USER_TABLE_ID;
 use common_config::{metadata_store_dir, KvBackendConfig, WalConfig};
 use common_meta::cache_invalidator::DummyCacheInvalidator;
 use common_meta::datanode_manager::DatanodeManagerRef;
+use common_meta::ddl::table_meta::DefaultTableMetadataAllocator;
 use common_meta::ddl::{DdlTaskExecutorRef, TableMetadataAllocatorRef};
 use common_meta::ddl_manager::DdlManager;
 use common_meta::key::{TableMetadataManager, TableMetadataManagerRef};
@@ -36,15 +37,20 @@ use datanode::datanode::{Datanode, DatanodeBuilder};
 use file_engine::config::EngineConfig as FileEngineConfig;
 use frontend::frontend::FrontendOptions;
 use frontend::instance::builder::FrontendBuilder;
-use frontend::instance::standalone::StandaloneTableMetadataAllocator;
-use frontend::instance::{FrontendInstance, Instance as FeInstance, StandaloneDatanodeManager};
+use frontend::instance::{
+    FrontendInstance, Instance as FeInstance, StandaloneDatanodeManager, StandaloneRegionCapturer,
+};
+use frontend::query_rule::QueryRuleOptions;
 use frontend::service_config::{
-    GrpcOptions, InfluxdbOptions, MysqlOptions, OpentsdbOptions, PostgresOptions, PromStoreOptions,
+    FlightSqlOptions, GrpcOptions, InfluxdbOptions, MqttOptions, MysqlOptions, OpentsdbOptions,
+    PostgresOptions, PromStoreOptions,
 };
 use mito2::config::MitoConfig;
 use serde::{Deserialize, Serialize};
 use servers::export_metrics::ExportMetricsOption;
 use servers::http::HttpOptions;
+use servers::pipeline::PipelineOptions;
+use servers::rate_limit::RateLimitOptions;
 use servers::tls::{TlsMode, TlsOption};
 use servers::Mode;
 use snafu::ResultExt;
@@ -101,9 +107,14 @@ pub struct StandaloneOptions {
     pub grpc: GrpcOptions,
     pub mysql: MysqlOptions,
     pub postgres: PostgresOptions,
+    pub flight_sql: FlightSqlOptions,
     pub opentsdb: OpentsdbOptions,
     pub influxdb: InfluxdbOptions,
     pub prom_store: PromStoreOptions,
+    pub mqtt: MqttOptions,
+    pub pipeline: PipelineOptions,
+    pub rate_limit: RateLimitOptions,
+    pub query_rule: QueryRuleOptions,
     pub wal: WalConfig,
     pub storage: StorageConfig,
     pub metadata_store: KvBackendConfig,
@@ -113,6 +124,7 @@ pub struct StandaloneOptions {
     /// Options for different store engines.
     pub region_engine: Vec<RegionEngineConfig>,
     pub export_metrics: ExportMetricsOption,
+    pub capture_dir: Option<String>,
 }
 
 impl Default for StandaloneOptions {
@@ -124,9 +136,14 @@ impl Default for StandaloneOptions {
             grpc: GrpcOptions::default(),
             mysql: MysqlOptions::default(),
             postgres: PostgresOptions::default(),
+            flight_sql: FlightSqlOptions::default(),
             opentsdb: OpentsdbOptions::default(),
             influxdb: InfluxdbOptions::default(),
             prom_store: PromStoreOptions::default(),
+            mqtt: MqttOptions::default(),
+            pipeline: PipelineOptions::default(),
+            rate_limit: RateLimitOptions::default(),
+            query_rule: QueryRuleOptions::default(),
             wal: WalConfig::default(),
             storage: StorageConfig::default(),
             metadata_store: KvBackendConfig::default(),
@@ -138,6 +155,7 @@ impl Default for StandaloneOptions {
                 RegionEngineConfig::Mito(MitoConfig::default()),
                 RegionEngineConfig::File(FileEngineConfig::default()),
             ],
+            capture_dir: None,
         }
     }
 }
@@ -150,14 +168,20 @@ impl StandaloneOptions {
             grpc: self.grpc,
             mysql: self.mysql,
             postgres: self.postgres,
+            flight_sql: self.flight_sql,
             opentsdb: self.opentsdb,
             influxdb: self.influxdb,
             prom_store: self.prom_store,
+            mqtt: self.mqtt,
+            pipeline: self.pipeline,
+            rate_limit: self.rate_limit,
+            query_rule: self.query_rule,
             meta_client: None,
             logging: self.logging,
             user_provider: self.user_provider,
             // Handle the export metrics task run by standalone to frontend for execution
             export_metrics: self.export_metrics,
+            capture_dir: self.capture_dir,
             ..Default::default()
         }
     }
@@ -397,7 +421,7 @@ impl StartCommand {
             common_meta::wal::WalConfig::default(),
             kv_backend.clone(),
         ));
-        let table_meta_allocator = Arc::new(StandaloneTableMetadataAllocator::new(
+        let table_meta_allocator = Arc::new(DefaultTableMetadataAllocator::new(
             table_id_sequence,
             wal_options_allocator.clone(),
         ));
@@ -412,6 +436,8 @@ impl StartCommand {
 
         let mut frontend = FrontendBuilder::new(kv_backend, datanode_manager, ddl_task_executor)
             .with_plugin(fe_plugins)
+            .with_region_capturer(Arc::new(StandaloneRegionCapturer(datanode.region_server())))
+            .with_capture_dir(opts.frontend.capture_dir.clone())
             .try_build()
             .await
             .context(StartFrontendSnafu)?;
@@ -420,6 +446,12 @@ impl StartCommand {
             .build_export_metrics_task(&opts.frontend.export_metrics)
             .context(StartFrontendSnafu)?;
 
+        frontend.build_pipeline_runner(&opts.frontend.pipeline);
+
+        frontend
+            .build_query_rule(&opts.frontend.query_rule)
+            .context(StartFrontendSnafu)?;
+
         frontend
             .build_servers(opts)
             .await
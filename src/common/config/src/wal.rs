@@ -13,12 +13,17 @@
 // limitations under the License.
 
 pub mod kafka;
+pub mod object_store;
 pub mod raft_engine;
 
 use serde::{Deserialize, Serialize};
 use serde_with::with_prefix;
 
-pub use crate::wal::kafka::{KafkaConfig, KafkaOptions as KafkaWalOptions, Topic as KafkaWalTopic};
+pub use crate::wal::kafka::{
+    KafkaClientSasl, KafkaClientTls, KafkaCompression, KafkaConfig,
+    KafkaOptions as KafkaWalOptions, KafkaSaslMechanism, Topic as KafkaWalTopic,
+};
+pub use crate::wal::object_store::ObjectStoreWalConfig;
 pub use crate::wal::raft_engine::RaftEngineConfig;
 
 /// An encoded wal options will be wrapped into a (WAL_OPTIONS_KEY, encoded wal options) key-value pair
@@ -33,6 +38,26 @@ pub enum WalConfig {
     RaftEngine(RaftEngineConfig),
     #[serde(rename = "kafka")]
     Kafka(KafkaConfig),
+    /// Discards all entries. Data in memtables is lost if the datanode crashes,
+    /// so it's only suitable for data that can be written again from the source.
+    #[serde(rename = "noop")]
+    Noop,
+    /// Uses both the raft-engine wal and the kafka wal. Each region uses the provider
+    /// recorded in its wal options.
+    #[serde(rename = "mixed")]
+    Mixed(MixedWalConfig),
+    /// Buffers entries in local segment files and uploads them to the object store.
+    /// Regions use the raft-engine wal options.
+    #[serde(rename = "object_store")]
+    ObjectStore(ObjectStoreWalConfig),
+}
+
+/// Config of the mixed wal.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(default)]
+pub struct MixedWalConfig {
+    pub raft_engine: RaftEngineConfig,
+    pub kafka: KafkaConfig,
 }
 
 impl Default for WalConfig {
@@ -62,14 +87,17 @@ mod tests {
     use std::time::Duration;
 
     use common_base::readable_size::ReadableSize;
-    use rskafka::client::partition::Compression as RsKafkaCompression;
 
-    use crate::wal::{KafkaConfig, KafkaWalOptions, WalOptions};
+    use crate::wal::{
+        KafkaClientSasl, KafkaClientTls, KafkaCompression, KafkaConfig, KafkaSaslMechanism,
+        KafkaWalOptions, MixedWalConfig, ObjectStoreWalConfig, WalConfig, WalOptions,
+    };
 
     #[test]
     fn test_serde_kafka_config() {
         let toml_str = r#"
             broker_endpoints = ["127.0.0.1:9090"]
+            compression = "zstd"
             max_batch_size = "4MB"
             linger = "200ms"
             max_wait_time = "100ms"
@@ -77,20 +105,104 @@ mod tests {
             backoff_max = "10s"
             backoff_base = 2
             backoff_deadline = "5mins"
+            prune_obsolete_records = true
         "#;
         let decoded: KafkaConfig = toml::from_str(toml_str).unwrap();
         let expected = KafkaConfig {
             broker_endpoints: vec!["127.0.0.1:9090".to_string()],
-            compression: RsKafkaCompression::default(),
+            compression: KafkaCompression::Zstd,
             max_batch_size: ReadableSize::mb(4),
             linger: Duration::from_millis(200),
             max_wait_time: Duration::from_millis(100),
+            replay_buffer_size: 64,
+            offset_index_interval: 1024,
             backoff_init: Duration::from_millis(500),
             backoff_max: Duration::from_secs(10),
             backoff_base: 2,
             backoff_deadline: Some(Duration::from_secs(60 * 5)),
+            prune_obsolete_records: true,
+            probe_topics_interval: Duration::from_secs(60),
+            recreate_missing_topics: false,
+            replication_factor: 3,
+            sasl: None,
+            tls: None,
+        };
+        assert_eq!(decoded, expected);
+    }
+
+    #[test]
+    fn test_serde_noop_wal_config() {
+        let toml_str = r#"
+            provider = "noop"
+        "#;
+        let decoded: WalConfig = toml::from_str(toml_str).unwrap();
+        assert_eq!(WalConfig::Noop, decoded);
+    }
+
+    #[test]
+    fn test_serde_mixed_wal_config() {
+        let toml_str = r#"
+            provider = "mixed"
+            [raft_engine]
+            file_size = "128MB"
+            [kafka]
+            broker_endpoints = ["127.0.0.1:9090"]
+        "#;
+        let decoded: WalConfig = toml::from_str(toml_str).unwrap();
+        let WalConfig::Mixed(MixedWalConfig { raft_engine, kafka }) = decoded else {
+            panic!("unexpected wal config {decoded:?}");
+        };
+        assert_eq!(ReadableSize::mb(128), raft_engine.file_size);
+        assert_eq!(vec!["127.0.0.1:9090".to_string()], kafka.broker_endpoints);
+    }
+
+    #[test]
+    fn test_serde_object_store_wal_config() {
+        let toml_str = r#"
+            provider = "object_store"
+            store = "S3"
+            segment_size = "16MB"
+            upload_interval = "500ms"
+        "#;
+        let decoded: WalConfig = toml::from_str(toml_str).unwrap();
+        let expected = ObjectStoreWalConfig {
+            store: Some("S3".to_string()),
+            segment_size: ReadableSize::mb(16),
+            upload_interval: Duration::from_millis(500),
+            ..Default::default()
+        };
+        assert_eq!(WalConfig::ObjectStore(expected), decoded);
+    }
+
+    #[test]
+    fn test_serde_kafka_config_with_auth() {
+        let toml_str = r#"
+            broker_endpoints = ["127.0.0.1:9093"]
+            [sasl]
+            type = "SCRAM-SHA-512"
+            username = "greptime"
+            password = "secret"
+            [tls]
+            server_ca_cert_path = "/path/to/ca.crt"
+        "#;
+        let decoded: KafkaConfig = toml::from_str(toml_str).unwrap();
+        let expected = KafkaConfig {
+            broker_endpoints: vec!["127.0.0.1:9093".to_string()],
+            sasl: Some(KafkaClientSasl {
+                mechanism: KafkaSaslMechanism::ScramSha512,
+                username: "greptime".to_string(),
+                password: "secret".to_string(),
+            }),
+            tls: Some(KafkaClientTls {
+                server_ca_cert_path: Some("/path/to/ca.crt".to_string()),
+                client_cert_path: None,
+                client_key_path: None,
+            }),
+            ..Default::default()
         };
         assert_eq!(decoded, expected);
+        // Doesn't expose the password.
+        assert!(!format!("{:?}", decoded).contains("secret"));
     }
 
     #[test]
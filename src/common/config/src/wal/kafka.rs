@@ -12,10 +12,12 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::fmt;
 use std::time::Duration;
 
 use common_base::readable_size::ReadableSize;
 use rskafka::client::partition::Compression as RsKafkaCompression;
+use rskafka::client::{Credentials, SaslConfig};
 use serde::{Deserialize, Serialize};
 
 /// Topic name prefix.
@@ -30,9 +32,7 @@ pub struct KafkaConfig {
     /// The broker endpoints of the Kafka cluster.
     pub broker_endpoints: Vec<String>,
     /// The compression algorithm used to compress log entries.
-    #[serde(skip)]
-    #[serde(default)]
-    pub compression: RsKafkaCompression,
+    pub compression: KafkaCompression,
     /// The maximum log size a kakfa batch producer could buffer.
     pub max_batch_size: ReadableSize,
     /// The linger duration of a kafka batch producer.
@@ -41,6 +41,14 @@ pub struct KafkaConfig {
     /// The maximum amount of time (in milliseconds) to wait for Kafka records to be returned.
     #[serde(with = "humantime_serde")]
     pub max_wait_time: Duration,
+    /// The max number of records buffered for each region while replaying the WAL.
+    /// Regions sharing a topic are replayed by one consumer, which waits for regions that
+    /// replay slowly if their buffers are full.
+    pub replay_buffer_size: usize,
+    /// Number of entries of a region between two persisted checkpoints mapping entry ids to
+    /// offsets of their records. Replay seeks to the last checkpoint before the required entry.
+    /// Disabled if it's zero.
+    pub offset_index_interval: u64,
     /// The initial backoff for kafka clients.
     #[serde(with = "humantime_serde")]
     pub backoff_init: Duration,
@@ -54,24 +62,130 @@ pub struct KafkaConfig {
     /// If it's None, the reconnecting won't terminate.
     #[serde(with = "humantime_serde")]
     pub backoff_deadline: Option<Duration>,
+    /// Whether to delete records that all regions of the topic have flushed.
+    pub prune_obsolete_records: bool,
+    /// Interval to check whether topics used by regions exist in the Kafka cluster.
+    /// Disabled if it's zero.
+    #[serde(with = "humantime_serde")]
+    pub probe_topics_interval: Duration,
+    /// Whether to re-create topics missing in the Kafka cluster, e.g. the cluster is rebuilt.
+    /// Records in the missing topics are lost, so regions may lose data not flushed yet.
+    pub recreate_missing_topics: bool,
+    /// The replication factor of re-created topics.
+    pub replication_factor: i16,
+    /// The SASL authentication of kafka clients. Disabled if it's None.
+    pub sasl: Option<KafkaClientSasl>,
+    /// The TLS configs of kafka clients. Disabled if it's None.
+    pub tls: Option<KafkaClientTls>,
 }
 
 impl Default for KafkaConfig {
     fn default() -> Self {
         Self {
             broker_endpoints: vec!["127.0.0.1:9090".to_string()],
-            compression: RsKafkaCompression::NoCompression,
+            compression: KafkaCompression::None,
             max_batch_size: ReadableSize::mb(4),
             linger: Duration::from_millis(200),
             max_wait_time: Duration::from_millis(100),
+            replay_buffer_size: 64,
+            offset_index_interval: 1024,
             backoff_init: Duration::from_millis(500),
             backoff_max: Duration::from_secs(10),
             backoff_base: 2,
             backoff_deadline: Some(Duration::from_secs(60 * 5)), // 5 mins
+            prune_obsolete_records: false,
+            probe_topics_interval: Duration::from_secs(60),
+            recreate_missing_topics: false,
+            replication_factor: 3,
+            sasl: None,
+            tls: None,
         }
     }
 }
 
+/// Compression algorithms of records produced by kafka clients.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum KafkaCompression {
+    /// Doesn't compress records.
+    #[default]
+    None,
+    Gzip,
+    Lz4,
+    Snappy,
+    Zstd,
+}
+
+impl From<KafkaCompression> for RsKafkaCompression {
+    fn from(compression: KafkaCompression) -> Self {
+        match compression {
+            KafkaCompression::None => RsKafkaCompression::NoCompression,
+            KafkaCompression::Gzip => RsKafkaCompression::Gzip,
+            KafkaCompression::Lz4 => RsKafkaCompression::Lz4,
+            KafkaCompression::Snappy => RsKafkaCompression::Snappy,
+            KafkaCompression::Zstd => RsKafkaCompression::Zstd,
+        }
+    }
+}
+
+/// SASL mechanisms supported by kafka clients.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum KafkaSaslMechanism {
+    #[serde(rename = "PLAIN")]
+    Plain,
+    #[serde(rename = "SCRAM-SHA-256")]
+    ScramSha256,
+    #[serde(rename = "SCRAM-SHA-512")]
+    ScramSha512,
+}
+
+/// SASL authentication of kafka clients.
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct KafkaClientSasl {
+    /// The SASL mechanism.
+    #[serde(rename = "type")]
+    pub mechanism: KafkaSaslMechanism,
+    /// The user name to authenticate.
+    pub username: String,
+    /// The password of the user.
+    pub password: String,
+}
+
+impl fmt::Debug for KafkaClientSasl {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("KafkaClientSasl")
+            .field("mechanism", &self.mechanism)
+            .field("username", &self.username)
+            .field("password", &"******")
+            .finish()
+    }
+}
+
+impl KafkaClientSasl {
+    /// Returns the SASL config of the rskafka client.
+    pub fn to_sasl_config(&self) -> SaslConfig {
+        let credentials = Credentials::new(self.username.clone(), self.password.clone());
+        match self.mechanism {
+            KafkaSaslMechanism::Plain => SaslConfig::Plain(credentials),
+            KafkaSaslMechanism::ScramSha256 => SaslConfig::ScramSha256(credentials),
+            KafkaSaslMechanism::ScramSha512 => SaslConfig::ScramSha512(credentials),
+        }
+    }
+}
+
+/// TLS configs of kafka clients.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct KafkaClientTls {
+    /// Path to the CA certificate in PEM format to verify brokers.
+    /// Certificates of the system are also trusted.
+    pub server_ca_cert_path: Option<String>,
+    /// Path to the client certificate in PEM format for mutual TLS.
+    pub client_cert_path: Option<String>,
+    /// Path to the client private key in PEM format for mutual TLS.
+    pub client_key_path: Option<String>,
+}
+
 /// Kafka wal options allocated to a region.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct KafkaOptions {
@@ -0,0 +1,55 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::time::Duration;
+
+use common_base::readable_size::ReadableSize;
+use serde::{Deserialize, Serialize};
+
+/// Configurations for the object store wal.
+///
+/// Entries are appended to segment files in a local directory first, and segments are
+/// uploaded to the object store once they are full or the upload interval elapses.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct ObjectStoreWalConfig {
+    /// Name of the storage provider to upload segments, the default storage if it's None.
+    pub store: Option<String>,
+    /// Path of segments in the object store.
+    pub root: String,
+    /// Local directory to buffer segments not uploaded yet, `{data_home}/wal_buffer/` if
+    /// it's None.
+    pub dir: Option<String>,
+    /// A segment is uploaded once its size exceeds `segment_size`.
+    pub segment_size: ReadableSize,
+    /// Interval to upload segments that are not full. Entries not uploaded are lost if
+    /// the local disk is lost.
+    #[serde(with = "humantime_serde")]
+    pub upload_interval: Duration,
+    /// Whether to sync local segments after every write.
+    pub sync_write: bool,
+}
+
+impl Default for ObjectStoreWalConfig {
+    fn default() -> Self {
+        Self {
+            store: None,
+            root: "wal/".to_string(),
+            dir: None,
+            segment_size: ReadableSize::mb(8),
+            upload_interval: Duration::from_secs(1),
+            sync_write: false,
+        }
+    }
+}
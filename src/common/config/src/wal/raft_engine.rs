@@ -34,6 +34,17 @@ pub struct RaftEngineConfig {
     pub read_batch_size: usize,
     // whether to sync log file after every write
     pub sync_write: bool,
+    // interval to sync log files if `sync_write` is false, no periodic sync if absent
+    #[serde(with = "humantime_serde")]
+    pub sync_period: Option<Duration>,
+    // size of bytes written since the last sync to trigger a sync if `sync_write` is false
+    pub sync_bytes: Option<ReadableSize>,
+    // log batches larger than the threshold are compressed
+    pub batch_compression_threshold: ReadableSize,
+    // number of threads to recover log files
+    pub recovery_threads: usize,
+    // size of blocks to read log files during recovery
+    pub recovery_read_block_size: ReadableSize,
 }
 
 impl Default for RaftEngineConfig {
@@ -45,6 +56,11 @@ impl Default for RaftEngineConfig {
             purge_interval: Duration::from_secs(600),
             read_batch_size: 128,
             sync_write: false,
+            sync_period: None,
+            sync_bytes: None,
+            batch_compression_threshold: ReadableSize::kb(8),
+            recovery_threads: 4,
+            recovery_read_block_size: ReadableSize::kb(16),
         }
     }
 }
@@ -140,6 +140,14 @@ pub enum Error {
         error: object_store::Error,
     },
 
+    #[snafu(display("Invalid file name pattern: {}", pattern))]
+    BuildRegex {
+        pattern: String,
+        location: Location,
+        #[snafu(source)]
+        error: regex::Error,
+    },
+
     #[snafu(display("Invalid connection: {}", msg))]
     InvalidConnection { msg: String, location: Location },
 
@@ -193,6 +201,7 @@ impl ErrorExt for Error {
             | UnsupportedCompressionType { .. }
             | UnsupportedFormat { .. }
             | InvalidConnection { .. }
+            | BuildRegex { .. }
             | InvalidUrl { .. }
             | EmptyHostPath { .. }
             | InferSchema { .. }
@@ -238,6 +247,7 @@ impl ErrorExt for Error {
             EmptyHostPath { location, .. } => Some(*location),
             InvalidUrl { location, .. } => Some(*location),
             InvalidConnection { location, .. } => Some(*location),
+            BuildRegex { location, .. } => Some(*location),
             UnsupportedCompressionType { location, .. } => Some(*location),
             UnsupportedFormat { location, .. } => Some(*location),
             WriteParquet { location, .. } => Some(*location),
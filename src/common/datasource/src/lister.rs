@@ -18,6 +18,8 @@ use regex::Regex;
 use snafu::ResultExt;
 
 use crate::error::{self, Result};
+use crate::util::{find_dir_and_filename, glob_to_regex, is_glob};
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Source {
     Filename(String),
@@ -89,3 +91,80 @@ impl Lister {
         }
     }
 }
+
+/// Lists files at the `url`, which is a file, a directory or a glob of file names in a
+/// directory like `s3://bucket/path/*.parquet`.
+///
+/// Names of files in a directory must also match the `pattern` if it's present. Returns
+/// paths of files.
+pub async fn list_files(
+    object_store: ObjectStore,
+    url: &str,
+    pattern: Option<Regex>,
+) -> Result<Vec<String>> {
+    let (dir, filename) = find_dir_and_filename(url);
+    let (source, regex, filter) = match filename {
+        Some(filename) if is_glob(&filename) => {
+            let glob = Regex::new(&glob_to_regex(&filename))
+                .context(error::BuildRegexSnafu { pattern: &filename })?;
+            (Source::Dir, Some(glob), pattern)
+        }
+        Some(filename) => (Source::Filename(filename), pattern, None),
+        None => (Source::Dir, pattern, None),
+    };
+
+    let entries = Lister::new(object_store, source, dir, regex).list().await?;
+    Ok(entries
+        .into_iter()
+        .filter(|entry| !entry.path().ends_with('/'))
+        .filter(|entry| filter.as_ref().map_or(true, |r| r.is_match(entry.name())))
+        .map(|entry| entry.path().to_string())
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use common_test_util::temp_dir::create_temp_dir;
+
+    use super::*;
+    use crate::object_store::fs::build_fs_backend;
+
+    #[tokio::test]
+    async fn test_list_files() {
+        let dir = create_temp_dir("list-files");
+        let root = format!("{}/", dir.path().to_str().unwrap());
+        for name in ["a_1.parquet", "a_2.parquet", "b_1.parquet", "a_1.csv"] {
+            std::fs::write(dir.path().join(name), b"").unwrap();
+        }
+        let object_store = build_fs_backend(&root).unwrap();
+
+        let list = |url: String, pattern: Option<&str>| {
+            let object_store = object_store.clone();
+            let pattern = pattern.map(|p| Regex::new(p).unwrap());
+            async move {
+                let mut files = list_files(object_store, &url, pattern).await.unwrap();
+                files.sort();
+                files
+            }
+        };
+
+        assert_eq!(
+            vec!["a_1.parquet", "a_2.parquet"],
+            list(format!("{root}a_*.parquet"), None).await
+        );
+        assert_eq!(
+            vec!["a_2.parquet"],
+            list(format!("{root}a_*.parquet"), Some("2")).await
+        );
+        assert_eq!(
+            vec!["a_1.csv", "a_1.parquet"],
+            list(format!("{root}a_1.*"), None).await
+        );
+        assert_eq!(
+            vec!["b_1.parquet"],
+            list(format!("{root}b_1.parquet"), None).await
+        );
+        assert_eq!(4, list(root.clone(), None).await.len());
+        assert_eq!(vec!["a_1.csv"], list(root.clone(), Some(".*\\.csv")).await);
+    }
+}
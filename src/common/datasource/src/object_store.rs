@@ -13,6 +13,8 @@
 // limitations under the License.
 
 pub mod fs;
+pub mod gcs;
+pub mod oss;
 pub mod s3;
 use std::collections::HashMap;
 
@@ -23,12 +25,23 @@ use snafu::{OptionExt, ResultExt};
 use url::{ParseError, Url};
 
 use self::fs::build_fs_backend;
-use self::s3::build_s3_backend;
+use self::gcs::{build_gcs_backend, is_supported_in_gcs};
+use self::oss::{build_oss_backend, is_supported_in_oss};
+use self::s3::{build_s3_backend, is_supported_in_s3};
 use crate::error::{self, Result};
 use crate::util::find_dir_and_filename;
 
 pub const FS_SCHEMA: &str = "FS";
 pub const S3_SCHEMA: &str = "S3";
+pub const GCS_SCHEMA: &str = "GCS";
+/// Schema of Google Cloud Storage URLs like `gs://bucket/path`.
+pub const GS_SCHEMA: &str = "GS";
+pub const OSS_SCHEMA: &str = "OSS";
+
+/// Returns whether the `key` is a connection option of any object store backend.
+pub fn is_supported_connection_option(key: &str) -> bool {
+    is_supported_in_s3(key) || is_supported_in_gcs(key) || is_supported_in_oss(key)
+}
 
 /// Returns `(schema, Option<host>, path)`
 pub fn parse_url(url: &str) -> Result<(String, Option<String>, String)> {
@@ -64,6 +77,18 @@ pub fn build_backend(url: &str, connection: &HashMap<String, String>) -> Result<
             })?;
             Ok(build_s3_backend(&host, &root, connection)?)
         }
+        GCS_SCHEMA | GS_SCHEMA => {
+            let host = host.context(error::EmptyHostPathSnafu {
+                url: url.to_string(),
+            })?;
+            Ok(build_gcs_backend(&host, &root, connection)?)
+        }
+        OSS_SCHEMA => {
+            let host = host.context(error::EmptyHostPathSnafu {
+                url: url.to_string(),
+            })?;
+            Ok(build_oss_backend(&host, &root, connection)?)
+        }
         FS_SCHEMA => Ok(build_fs_backend(&root)?),
 
         _ => error::UnsupportedBackendProtocolSnafu {
@@ -86,7 +111,7 @@ pub fn handle_windows_path(url: &str) -> Option<String> {
 
 #[cfg(test)]
 mod tests {
-    use super::handle_windows_path;
+    use super::*;
 
     #[test]
     fn test_handle_windows_path() {
@@ -97,4 +122,28 @@ mod tests {
         assert_eq!(handle_windows_path("https://google.com"), None);
         assert_eq!(handle_windows_path("s3://bucket/path/to"), None);
     }
+
+    #[test]
+    fn test_build_backend() {
+        let connection = HashMap::from([
+            (
+                "endpoint".to_string(),
+                "https://oss-cn-hangzhou.aliyuncs.com".to_string(),
+            ),
+            ("access_key_id".to_string(), "key_id".to_string()),
+            ("access_key_secret".to_string(), "key_secret".to_string()),
+        ]);
+        for url in ["gs://bucket/path/to/", "oss://bucket/path/to/"] {
+            let _ = build_backend(url, &connection).unwrap();
+        }
+        assert!(build_backend("hdfs://bucket/path/to/", &connection).is_err());
+    }
+
+    #[test]
+    fn test_is_supported_connection_option() {
+        assert!(is_supported_connection_option("secret_access_key"));
+        assert!(is_supported_connection_option("credential_path"));
+        assert!(is_supported_connection_option("access_key_secret"));
+        assert!(!is_supported_connection_option("format"));
+    }
 }
@@ -0,0 +1,85 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+
+use object_store::services::Gcs;
+use object_store::ObjectStore;
+use snafu::ResultExt;
+
+use crate::error::{self, Result};
+
+const ENDPOINT: &str = "endpoint";
+const SCOPE: &str = "scope";
+/// Path of the service account credential file.
+const CREDENTIAL_PATH: &str = "credential_path";
+/// Base64 encoded content of the service account credential file.
+const CREDENTIAL: &str = "credential";
+
+pub fn is_supported_in_gcs(key: &str) -> bool {
+    key == ENDPOINT || key == SCOPE || key == CREDENTIAL_PATH || key == CREDENTIAL
+}
+
+pub fn build_gcs_backend(
+    host: &str,
+    path: &str,
+    connection: &HashMap<String, String>,
+) -> Result<ObjectStore> {
+    let mut builder = Gcs::default();
+
+    let _ = builder.root(path).bucket(host);
+
+    if let Some(endpoint) = connection.get(ENDPOINT) {
+        let _ = builder.endpoint(endpoint);
+    }
+
+    if let Some(scope) = connection.get(SCOPE) {
+        let _ = builder.scope(scope);
+    }
+
+    if let Some(credential_path) = connection.get(CREDENTIAL_PATH) {
+        let _ = builder.credential_path(credential_path);
+    }
+
+    if let Some(credential) = connection.get(CREDENTIAL) {
+        let _ = builder.credential(credential);
+    }
+
+    Ok(ObjectStore::new(builder)
+        .context(error::BuildBackendSnafu)?
+        .layer(
+            object_store::layers::LoggingLayer::default()
+                // Print the expected error only in DEBUG level.
+                // See https://docs.rs/opendal/latest/opendal/layers/struct.LoggingLayer.html#method.with_error_level
+                .with_error_level(Some("debug"))
+                .expect("input error level must be valid"),
+        )
+        .layer(object_store::layers::TracingLayer)
+        .layer(object_store::layers::PrometheusMetricsLayer)
+        .finish())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_supported_in_gcs() {
+        assert!(is_supported_in_gcs(ENDPOINT));
+        assert!(is_supported_in_gcs(SCOPE));
+        assert!(is_supported_in_gcs(CREDENTIAL_PATH));
+        assert!(is_supported_in_gcs(CREDENTIAL));
+        assert!(!is_supported_in_gcs("foo"))
+    }
+}
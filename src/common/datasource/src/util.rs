@@ -27,6 +27,49 @@ pub fn find_dir_and_filename(path: &str) -> (String, Option<String>) {
     }
 }
 
+/// Returns true if the `filename` contains glob wildcards: `*`, `?` or `[`.
+pub fn is_glob(filename: &str) -> bool {
+    filename.contains(['*', '?', '['])
+}
+
+/// Converts the glob pattern of a file name to a regex that matches the whole name.
+///
+/// Supports `*` (any characters), `?` (one character) and character classes like
+/// `[abc]`, `[a-z]` and `[!abc]`. Other characters are matched literally.
+pub fn glob_to_regex(glob: &str) -> String {
+    let mut regex = String::with_capacity(glob.len() * 2 + 2);
+    regex.push('^');
+    let mut chars = glob.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '*' => regex.push_str(".*"),
+            '?' => regex.push('.'),
+            '[' => {
+                regex.push('[');
+                if chars.next_if_eq(&'!').is_some() {
+                    regex.push('^');
+                }
+                for c in chars.by_ref() {
+                    if c == ']' {
+                        break;
+                    }
+                    if c == '\\' || c == '[' {
+                        regex.push('\\');
+                    }
+                    regex.push(c);
+                }
+                regex.push(']');
+            }
+            c => {
+                let mut buf = [0; 4];
+                regex.push_str(&regex::escape(c.encode_utf8(&mut buf)));
+            }
+        }
+    }
+    regex.push('$');
+    regex
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -133,4 +176,30 @@ mod tests {
             assert_eq!(test.expected_filename, filename)
         }
     }
+
+    #[test]
+    fn test_glob_to_regex() {
+        assert!(is_glob("*.parquet"));
+        assert!(is_glob("file_?.csv"));
+        assert!(is_glob("file_[0-9].csv"));
+        assert!(!is_glob("file.csv"));
+
+        let tests = [
+            ("*.parquet", "a.parquet", true),
+            ("*.parquet", "a.parquet.bak", false),
+            ("*.parquet", "a.csv", false),
+            ("file_?.csv", "file_1.csv", true),
+            ("file_?.csv", "file_10.csv", false),
+            ("file_[0-9].csv", "file_5.csv", true),
+            ("file_[0-9].csv", "file_a.csv", false),
+            ("file_[!0-9].csv", "file_a.csv", true),
+            ("file_[!0-9].csv", "file_5.csv", false),
+            ("a+b(1).csv", "a+b(1).csv", true),
+            ("a+b(1).csv", "aab1.csv", false),
+        ];
+        for (glob, name, matched) in tests {
+            let regex = regex::Regex::new(&glob_to_regex(glob)).unwrap();
+            assert_eq!(matched, regex.is_match(name), "glob: {glob}, name: {name}");
+        }
+    }
 }
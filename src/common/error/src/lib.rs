@@ -21,5 +21,7 @@ pub mod status_code;
 
 pub const GREPTIME_ERROR_CODE: &str = "x-greptime-err-code";
 pub const GREPTIME_ERROR_MSG: &str = "x-greptime-err-msg";
+/// Header of the suggested time in milliseconds to wait before retrying the request.
+pub const GREPTIME_RETRY_AFTER_MS: &str = "x-greptime-retry-after-ms";
 
 pub use snafu;
@@ -35,6 +35,8 @@ pub enum StatusCode {
     InvalidArguments = 1004,
     /// The task is cancelled.
     Cancelled = 1005,
+    /// The task exceeds its deadline.
+    DeadlineExceeded = 1006,
     // ====== End of common status code ================
 
     // ====== Begin of SQL related status code =========
@@ -109,7 +111,8 @@ impl StatusCode {
             | StatusCode::RuntimeResourcesExhausted
             | StatusCode::Internal
             | StatusCode::RegionNotReady
-            | StatusCode::RegionBusy => true,
+            | StatusCode::RegionBusy
+            | StatusCode::RateLimited => true,
 
             StatusCode::Success
             | StatusCode::Unknown
@@ -117,6 +120,7 @@ impl StatusCode {
             | StatusCode::Unexpected
             | StatusCode::InvalidArguments
             | StatusCode::Cancelled
+            | StatusCode::DeadlineExceeded
             | StatusCode::InvalidSyntax
             | StatusCode::PlanQuery
             | StatusCode::EngineExecuteQuery
@@ -128,7 +132,6 @@ impl StatusCode {
             | StatusCode::TableColumnNotFound
             | StatusCode::TableColumnExists
             | StatusCode::DatabaseNotFound
-            | StatusCode::RateLimited
             | StatusCode::UserNotFound
             | StatusCode::UnsupportedPasswordType
             | StatusCode::UserPasswordMismatch
@@ -139,6 +142,20 @@ impl StatusCode {
         }
     }
 
+    /// Returns the suggested time in milliseconds that clients should wait before
+    /// retrying a request failed with this code.
+    ///
+    /// Returns `None` if clients shouldn't retry or there is no hint.
+    pub fn retry_after_ms(&self) -> Option<u64> {
+        match self {
+            // The server is under pressure, e.g. the write buffer is full.
+            StatusCode::RuntimeResourcesExhausted | StatusCode::RateLimited => Some(1000),
+            StatusCode::RegionNotReady => Some(1000),
+            StatusCode::RegionBusy => Some(100),
+            _ => None,
+        }
+    }
+
     /// Returns `true` if we should print an error log for an error with
     /// this status code.
     pub fn should_log_error(&self) -> bool {
@@ -154,6 +171,7 @@ impl StatusCode {
             StatusCode::Success
             | StatusCode::Unsupported
             | StatusCode::InvalidArguments
+            | StatusCode::DeadlineExceeded
             | StatusCode::InvalidSyntax
             | StatusCode::TableAlreadyExists
             | StatusCode::TableNotFound
@@ -185,6 +203,7 @@ impl StatusCode {
             v if v == StatusCode::Internal as u32 => Some(StatusCode::Internal),
             v if v == StatusCode::InvalidArguments as u32 => Some(StatusCode::InvalidArguments),
             v if v == StatusCode::Cancelled as u32 => Some(StatusCode::Cancelled),
+            v if v == StatusCode::DeadlineExceeded as u32 => Some(StatusCode::DeadlineExceeded),
             v if v == StatusCode::InvalidSyntax as u32 => Some(StatusCode::InvalidSyntax),
             v if v == StatusCode::PlanQuery as u32 => Some(StatusCode::PlanQuery),
             v if v == StatusCode::EngineExecuteQuery as u32 => Some(StatusCode::EngineExecuteQuery),
@@ -251,4 +270,24 @@ mod tests {
         assert!(!StatusCode::is_success(2));
         assert!(!StatusCode::is_success(3));
     }
+
+    #[test]
+    fn test_retry_after_ms() {
+        assert_eq!(
+            Some(1000),
+            StatusCode::RuntimeResourcesExhausted.retry_after_ms()
+        );
+        assert_eq!(Some(1000), StatusCode::RateLimited.retry_after_ms());
+        assert_eq!(Some(100), StatusCode::RegionBusy.retry_after_ms());
+        assert_eq!(None, StatusCode::InvalidArguments.retry_after_ms());
+        // Codes with hints should be retryable.
+        for code in [
+            StatusCode::RuntimeResourcesExhausted,
+            StatusCode::RateLimited,
+            StatusCode::RegionNotReady,
+            StatusCode::RegionBusy,
+        ] {
+            assert!(code.is_retryable());
+        }
+    }
 }
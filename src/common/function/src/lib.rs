@@ -18,3 +18,4 @@ pub mod system;
 pub mod function;
 pub mod function_registry;
 pub mod helper;
+pub mod wasm;
@@ -12,6 +12,8 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+mod approx_count_distinct;
+mod approx_percentile;
 mod argmax;
 mod argmin;
 mod diff;
@@ -23,6 +25,8 @@ mod scipy_stats_norm_pdf;
 
 use std::sync::Arc;
 
+pub use approx_count_distinct::ApproxCountDistinctAccumulatorCreator;
+pub use approx_percentile::ApproxPercentileAccumulatorCreator;
 pub use argmax::ArgmaxAccumulatorCreator;
 pub use argmin::ArgminAccumulatorCreator;
 use common_query::logical_plan::AggregateFunctionCreatorRef;
@@ -92,6 +96,12 @@ impl AggregateFunctions {
         register_aggr_func!("argmax", 1, ArgmaxAccumulatorCreator);
         register_aggr_func!("argmin", 1, ArgminAccumulatorCreator);
         register_aggr_func!("percentile", 2, PercentileAccumulatorCreator);
+        register_aggr_func!("approx_percentile", 2, ApproxPercentileAccumulatorCreator);
+        register_aggr_func!(
+            "approx_count_distinct",
+            1,
+            ApproxCountDistinctAccumulatorCreator
+        );
         register_aggr_func!("scipystatsnormcdf", 2, ScipyStatsNormCdfAccumulatorCreator);
         register_aggr_func!("scipystatsnormpdf", 2, ScipyStatsNormPdfAccumulatorCreator);
     }
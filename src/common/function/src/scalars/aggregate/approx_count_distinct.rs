@@ -0,0 +1,288 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
+
+use common_macro::{as_aggr_func_creator, AggrFuncTypeStore};
+use common_query::error::{BadAccumulatorImplSnafu, DowncastVectorSnafu, Result};
+use common_query::logical_plan::{Accumulator, AggregateFunctionCreator};
+use common_query::prelude::*;
+use datatypes::prelude::*;
+use datatypes::vectors::BinaryVector;
+use snafu::{ensure, OptionExt};
+
+/// Number of bits of the hash to select a register.
+const PRECISION: u32 = 14;
+/// Number of registers in a sketch. The standard error of the estimation is
+/// about `1.04 / sqrt(NUM_REGISTERS)`, which is 0.81%.
+const NUM_REGISTERS: usize = 1 << PRECISION;
+
+/// A HyperLogLog sketch to estimate the number of distinct values.
+///
+/// The registers are the state of the sketch. Sketches of different partitions
+/// or regions are merged by taking the maximum of each register, so merging
+/// gets the same result as building the sketch from all values.
+#[derive(Debug, Default)]
+pub struct HyperLogLog {
+    /// Registers of the sketch. It's empty until the sketch has values.
+    registers: Vec<u8>,
+}
+
+impl HyperLogLog {
+    /// Adds a value to the sketch.
+    pub fn add<T: Hash>(&mut self, value: &T) {
+        let mut hasher = SketchHasher::default();
+        value.hash(&mut hasher);
+        self.add_hash(hasher.finish());
+    }
+
+    fn add_hash(&mut self, hash: u64) {
+        if self.registers.is_empty() {
+            self.registers = vec![0; NUM_REGISTERS];
+        }
+        let index = (hash >> (64 - PRECISION)) as usize;
+        // Sets the last bit so the rank is at most `64 - PRECISION + 1`.
+        let remaining = (hash << PRECISION) | (1 << (PRECISION - 1));
+        let rank = remaining.leading_zeros() as u8 + 1;
+        self.registers[index] = self.registers[index].max(rank);
+    }
+
+    /// Merges registers of another sketch into this sketch.
+    pub fn merge(&mut self, registers: &[u8]) -> Result<()> {
+        if registers.is_empty() {
+            return Ok(());
+        }
+        ensure!(
+            registers.len() == NUM_REGISTERS,
+            BadAccumulatorImplSnafu {
+                err_msg: format!(
+                    "expect {} registers in HyperLogLog sketch, got {}",
+                    NUM_REGISTERS,
+                    registers.len()
+                ),
+            }
+        );
+        if self.registers.is_empty() {
+            self.registers = registers.to_vec();
+            return Ok(());
+        }
+        for (register, other) in self.registers.iter_mut().zip(registers) {
+            *register = (*register).max(*other);
+        }
+        Ok(())
+    }
+
+    /// Returns the estimated number of distinct values.
+    pub fn count(&self) -> u64 {
+        if self.registers.is_empty() {
+            return 0;
+        }
+        let m = NUM_REGISTERS as f64;
+        let alpha = 0.7213 / (1.0 + 1.079 / m);
+        let sum: f64 = self.registers.iter().map(|r| 2f64.powi(-(*r as i32))).sum();
+        let estimate = alpha * m * m / sum;
+        let zeros = self.registers.iter().filter(|r| **r == 0).count();
+        // Uses linear counting for small cardinalities.
+        if estimate <= 2.5 * m && zeros > 0 {
+            (m * (m / zeros as f64).ln()).round() as u64
+        } else {
+            estimate.round() as u64
+        }
+    }
+}
+
+/// A deterministic hasher, so sketches built on different nodes hash the same value
+/// to the same register.
+///
+/// It's the 64-bit FNV-1a hash with the finalizer of MurmurHash3 to spread bits.
+struct SketchHasher(u64);
+
+impl Default for SketchHasher {
+    fn default() -> Self {
+        SketchHasher(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl Hasher for SketchHasher {
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= *byte as u64;
+            self.0 = self.0.wrapping_mul(0x0000_0100_0000_01b3);
+        }
+    }
+
+    fn finish(&self) -> u64 {
+        let mut h = self.0;
+        h ^= h >> 33;
+        h = h.wrapping_mul(0xff51_afd7_ed55_8ccd);
+        h ^= h >> 33;
+        h = h.wrapping_mul(0xc4ce_b9fe_1a85_ec53);
+        h ^= h >> 33;
+        h
+    }
+}
+
+/// Accumulator of `approx_count_distinct`, which estimates the number of distinct
+/// non-null values by a [HyperLogLog] sketch.
+#[derive(Debug, Default)]
+pub struct ApproxCountDistinct {
+    sketch: HyperLogLog,
+}
+
+impl Accumulator for ApproxCountDistinct {
+    fn state(&self) -> Result<Vec<Value>> {
+        Ok(vec![Value::from(self.sketch.registers.clone())])
+    }
+
+    fn update_batch(&mut self, values: &[VectorRef]) -> Result<()> {
+        if values.is_empty() {
+            return Ok(());
+        }
+        ensure!(values.len() == 1, InvalidInputStateSnafu);
+
+        let column = &values[0];
+        for i in 0..column.len() {
+            let value = column.get(i);
+            if !value.is_null() {
+                self.sketch.add(&value);
+            }
+        }
+        Ok(())
+    }
+
+    fn merge_batch(&mut self, states: &[VectorRef]) -> Result<()> {
+        if states.is_empty() {
+            return Ok(());
+        }
+        ensure!(
+            states.len() == 1,
+            BadAccumulatorImplSnafu {
+                err_msg: "expect 1 state in `merge_batch`",
+            }
+        );
+
+        let sketches = &states[0];
+        let sketches = sketches
+            .as_any()
+            .downcast_ref::<BinaryVector>()
+            .with_context(|| DowncastVectorSnafu {
+                err_msg: format!(
+                    "expect BinaryVector, got vector type {}",
+                    sketches.vector_type_name()
+                ),
+            })?;
+        for registers in sketches.iter_data().flatten() {
+            self.sketch.merge(registers)?;
+        }
+        Ok(())
+    }
+
+    fn evaluate(&self) -> Result<Value> {
+        Ok(Value::from(self.sketch.count()))
+    }
+}
+
+#[as_aggr_func_creator]
+#[derive(Debug, Default, AggrFuncTypeStore)]
+pub struct ApproxCountDistinctAccumulatorCreator {}
+
+impl AggregateFunctionCreator for ApproxCountDistinctAccumulatorCreator {
+    fn creator(&self) -> AccumulatorCreatorFunction {
+        let creator: AccumulatorCreatorFunction = Arc::new(move |_types: &[ConcreteDataType]| {
+            Ok(Box::new(ApproxCountDistinct::default()))
+        });
+        creator
+    }
+
+    fn output_type(&self) -> Result<ConcreteDataType> {
+        let input_types = self.input_types()?;
+        ensure!(input_types.len() == 1, InvalidInputStateSnafu);
+        Ok(ConcreteDataType::uint64_datatype())
+    }
+
+    fn state_types(&self) -> Result<Vec<ConcreteDataType>> {
+        let input_types = self.input_types()?;
+        ensure!(input_types.len() == 1, InvalidInputStateSnafu);
+        Ok(vec![ConcreteDataType::binary_datatype()])
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use datatypes::vectors::{Int32Vector, StringVector};
+
+    use super::*;
+
+    fn assert_estimation(expected: u64, actual: u64) {
+        let error = (expected as f64 - actual as f64).abs() / expected as f64;
+        assert!(error < 0.05, "expected: {expected}, actual: {actual}");
+    }
+
+    #[test]
+    fn test_update_batch() {
+        let mut accumulator = ApproxCountDistinct::default();
+        accumulator.update_batch(&[]).unwrap();
+        assert_eq!(Value::from(0u64), accumulator.evaluate().unwrap());
+
+        let v: Vec<VectorRef> = vec![Arc::new(Int32Vector::from(vec![
+            Some(1),
+            None,
+            Some(2),
+            Some(1),
+        ]))];
+        accumulator.update_batch(&v).unwrap();
+        assert_eq!(Value::from(2u64), accumulator.evaluate().unwrap());
+
+        let mut accumulator = ApproxCountDistinct::default();
+        let v: Vec<VectorRef> = vec![Arc::new(Int32Vector::from_vec(
+            (0..100_000).map(|i| i % 50_000).collect(),
+        ))];
+        accumulator.update_batch(&v).unwrap();
+        let Value::UInt64(count) = accumulator.evaluate().unwrap() else {
+            unreachable!()
+        };
+        assert_estimation(50_000, count);
+    }
+
+    #[test]
+    fn test_merge_batch() {
+        // Builds sketches of two partitions with overlapped values.
+        let mut states = Vec::new();
+        for range in [0..60_000, 40_000..100_000] {
+            let mut accumulator = ApproxCountDistinct::default();
+            let v: Vec<VectorRef> = vec![Arc::new(StringVector::from(
+                range.map(|i| format!("host-{i}")).collect::<Vec<_>>(),
+            ))];
+            accumulator.update_batch(&v).unwrap();
+            let Value::Binary(state) = accumulator.state().unwrap().remove(0) else {
+                unreachable!()
+            };
+            states.push(state.to_vec());
+        }
+        // An empty partition.
+        states.push(Vec::new());
+
+        let mut accumulator = ApproxCountDistinct::default();
+        let v: Vec<VectorRef> = vec![Arc::new(BinaryVector::from(states))];
+        accumulator.merge_batch(&v).unwrap();
+        let Value::UInt64(count) = accumulator.evaluate().unwrap() else {
+            unreachable!()
+        };
+        assert_estimation(100_000, count);
+
+        let v: Vec<VectorRef> = vec![Arc::new(BinaryVector::from(vec![vec![1u8, 2, 3]]))];
+        assert!(accumulator.merge_batch(&v).is_err());
+    }
+}
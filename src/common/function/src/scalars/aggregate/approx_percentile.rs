@@ -0,0 +1,416 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+use common_macro::{as_aggr_func_creator, AggrFuncTypeStore};
+use common_query::error::{
+    self, BadAccumulatorImplSnafu, CreateAccumulatorSnafu, DowncastVectorSnafu,
+    InvalidInputColSnafu, Result,
+};
+use common_query::logical_plan::{Accumulator, AggregateFunctionCreator};
+use common_query::prelude::*;
+use datatypes::prelude::*;
+use datatypes::value::OrderedFloat;
+use datatypes::vectors::{BinaryVector, ConstantVector, Float64Vector, Helper};
+use datatypes::with_match_primitive_type_id;
+use num::NumCast;
+use snafu::{ensure, OptionExt, ResultExt};
+
+/// Compression of the t-digest. A larger compression keeps more centroids, which
+/// costs more memory but gets more accurate estimations.
+const COMPRESSION: f64 = 100.0;
+/// Number of values to buffer before merging them into centroids.
+const BUFFER_SIZE: usize = 512;
+/// Size of an encoded centroid.
+const CENTROID_SIZE: usize = 16;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Centroid {
+    mean: f64,
+    weight: f64,
+}
+
+/// A merging t-digest to estimate percentiles.
+///
+/// The digest keeps values in centroids, and centroids near the tails hold fewer
+/// values, so it estimates extreme percentiles more accurately. Digests of different
+/// partitions or regions are merged by merging their centroids.
+#[derive(Debug, Default, Clone)]
+pub struct TDigest {
+    /// Centroids sorted by their means.
+    centroids: Vec<Centroid>,
+    /// Centroids not merged into `centroids` yet.
+    buffer: Vec<Centroid>,
+}
+
+impl TDigest {
+    /// Adds a value to the digest.
+    pub fn add(&mut self, value: f64) {
+        if value.is_nan() {
+            return;
+        }
+        self.push(Centroid {
+            mean: value,
+            weight: 1.0,
+        });
+    }
+
+    fn push(&mut self, centroid: Centroid) {
+        self.buffer.push(centroid);
+        if self.buffer.len() >= BUFFER_SIZE {
+            self.compress();
+        }
+    }
+
+    /// Merges buffered centroids into sorted centroids.
+    fn compress(&mut self) {
+        if self.buffer.is_empty() {
+            return;
+        }
+        let mut centroids = std::mem::take(&mut self.centroids);
+        centroids.append(&mut self.buffer);
+        centroids.sort_unstable_by(|a, b| a.mean.total_cmp(&b.mean));
+
+        let total: f64 = centroids.iter().map(|c| c.weight).sum();
+        let mut merged = Vec::new();
+        let mut weight_so_far = 0.0;
+        let mut current = centroids[0];
+        for centroid in &centroids[1..] {
+            let weight = current.weight + centroid.weight;
+            let q = (weight_so_far + weight / 2.0) / total;
+            // Limits the size of centroids by their quantiles so centroids at the tails
+            // are smaller.
+            let limit = 4.0 * total * q * (1.0 - q) / COMPRESSION;
+            if weight <= limit {
+                current.mean += (centroid.mean - current.mean) * centroid.weight / weight;
+                current.weight = weight;
+            } else {
+                weight_so_far += current.weight;
+                merged.push(current);
+                current = *centroid;
+            }
+        }
+        merged.push(current);
+        self.centroids = merged;
+    }
+
+    /// Returns the estimated value at the quantile `q` in `[0, 1]`.
+    ///
+    /// Returns `None` if the digest is empty.
+    pub fn quantile(&self, q: f64) -> Option<f64> {
+        let mut digest = self.clone();
+        digest.compress();
+        let centroids = &digest.centroids;
+
+        let total: f64 = centroids.iter().map(|c| c.weight).sum();
+        let rank = q.clamp(0.0, 1.0) * total;
+        let mut cumulative = 0.0;
+        let mut prev: Option<(f64, f64)> = None;
+        for centroid in centroids {
+            // Assumes values of a centroid are around its mean, so the mean is at
+            // the center of its rank range.
+            let center = cumulative + centroid.weight / 2.0;
+            if rank < center {
+                let Some((prev_center, prev_mean)) = prev else {
+                    return Some(centroid.mean);
+                };
+                let fract = (rank - prev_center) / (center - prev_center);
+                return Some(prev_mean + (centroid.mean - prev_mean) * fract);
+            }
+            prev = Some((center, centroid.mean));
+            cumulative += centroid.weight;
+        }
+        prev.map(|(_, mean)| mean)
+    }
+
+    /// Encodes the digest into bytes.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut bytes =
+            Vec::with_capacity((self.centroids.len() + self.buffer.len()) * CENTROID_SIZE);
+        for centroid in self.centroids.iter().chain(&self.buffer) {
+            bytes.extend_from_slice(&centroid.mean.to_le_bytes());
+            bytes.extend_from_slice(&centroid.weight.to_le_bytes());
+        }
+        bytes
+    }
+
+    /// Merges the digest encoded in `bytes` into this digest.
+    pub fn merge(&mut self, bytes: &[u8]) -> Result<()> {
+        ensure!(
+            bytes.len() % CENTROID_SIZE == 0,
+            BadAccumulatorImplSnafu {
+                err_msg: format!("invalid t-digest of {} bytes", bytes.len()),
+            }
+        );
+        for chunk in bytes.chunks_exact(CENTROID_SIZE) {
+            // Safety: The chunk has 16 bytes.
+            let mean = f64::from_le_bytes(chunk[..8].try_into().unwrap());
+            let weight = f64::from_le_bytes(chunk[8..].try_into().unwrap());
+            self.push(Centroid { mean, weight });
+        }
+        Ok(())
+    }
+}
+
+/// Accumulator of `approx_percentile`, which estimates the percentile by a [TDigest].
+///
+/// Like `percentile`, the second argument is the percentile in `[0, 100]`.
+#[derive(Debug, Default)]
+pub struct ApproxPercentile<T>
+where
+    T: WrapperType,
+{
+    digest: TDigest,
+    p: Option<f64>,
+    _phantom: PhantomData<T>,
+}
+
+impl<T> Accumulator for ApproxPercentile<T>
+where
+    T: WrapperType,
+{
+    fn state(&self) -> Result<Vec<Value>> {
+        Ok(vec![Value::from(self.digest.encode()), self.p.into()])
+    }
+
+    fn update_batch(&mut self, values: &[VectorRef]) -> Result<()> {
+        if values.is_empty() {
+            return Ok(());
+        }
+        ensure!(values.len() == 2, InvalidInputStateSnafu);
+        ensure!(values[0].len() == values[1].len(), InvalidInputStateSnafu);
+
+        if values[0].len() == 0 {
+            return Ok(());
+        }
+
+        let column = &values[0];
+        let mut len = 1;
+        let column: &<T as Scalar>::VectorType = if column.is_const() {
+            len = column.len();
+            let column: &ConstantVector = unsafe { Helper::static_cast(column) };
+            unsafe { Helper::static_cast(column.inner()) }
+        } else {
+            unsafe { Helper::static_cast(column) }
+        };
+
+        let x = &values[1];
+        let x = Helper::check_get_scalar::<f64>(x).context(error::InvalidInputTypeSnafu {
+            err_msg: "expecting \"APPROX_PERCENTILE\" function's second argument to be float64",
+        })?;
+        let first = x.get(0);
+        ensure!(!first.is_null(), InvalidInputColSnafu);
+        for i in 1..x.len() {
+            ensure!(first == x.get(i), InvalidInputColSnafu);
+        }
+        let first = match first {
+            Value::Float64(OrderedFloat(v)) => v,
+            // unreachable because we have checked `first` is not null and is f64 above
+            _ => unreachable!(),
+        };
+        if let Some(p) = self.p {
+            ensure!(p == first, InvalidInputColSnafu);
+        } else {
+            self.p = Some(first);
+        };
+
+        (0..len).for_each(|_| {
+            for v in column.iter_data().flatten() {
+                self.digest.add(NumCast::from(v.into_native()).unwrap());
+            }
+        });
+        Ok(())
+    }
+
+    fn merge_batch(&mut self, states: &[VectorRef]) -> Result<()> {
+        if states.is_empty() {
+            return Ok(());
+        }
+        ensure!(
+            states.len() == 2,
+            BadAccumulatorImplSnafu {
+                err_msg: "expect 2 states in `merge_batch`",
+            }
+        );
+
+        let p = &states[1];
+        let p = p
+            .as_any()
+            .downcast_ref::<Float64Vector>()
+            .with_context(|| DowncastVectorSnafu {
+                err_msg: format!(
+                    "expect float64vector, got vector type {}",
+                    p.vector_type_name()
+                ),
+            })?;
+        if let Some(p) = p.iter_data().flatten().next() {
+            self.p = Some(p);
+        }
+
+        let digests = &states[0];
+        let digests = digests
+            .as_any()
+            .downcast_ref::<BinaryVector>()
+            .with_context(|| DowncastVectorSnafu {
+                err_msg: format!(
+                    "expect BinaryVector, got vector type {}",
+                    digests.vector_type_name()
+                ),
+            })?;
+        for digest in digests.iter_data().flatten() {
+            self.digest.merge(digest)?;
+        }
+        Ok(())
+    }
+
+    fn evaluate(&self) -> Result<Value> {
+        let Some(p) = self.p else {
+            return Ok(Value::Null);
+        };
+        Ok(self.digest.quantile(p / 100.0).into())
+    }
+}
+
+#[as_aggr_func_creator]
+#[derive(Debug, Default, AggrFuncTypeStore)]
+pub struct ApproxPercentileAccumulatorCreator {}
+
+impl AggregateFunctionCreator for ApproxPercentileAccumulatorCreator {
+    fn creator(&self) -> AccumulatorCreatorFunction {
+        let creator: AccumulatorCreatorFunction = Arc::new(move |types: &[ConcreteDataType]| {
+            let input_type = &types[0];
+            with_match_primitive_type_id!(
+                input_type.logical_type_id(),
+                |$S| {
+                    Ok(Box::new(ApproxPercentile::<<$S as LogicalPrimitiveType>::Wrapper>::default()))
+                },
+                {
+                    let err_msg = format!(
+                        "\"APPROX_PERCENTILE\" aggregate function not support data type {:?}",
+                        input_type.logical_type_id(),
+                    );
+                    CreateAccumulatorSnafu { err_msg }.fail()?
+                }
+            )
+        });
+        creator
+    }
+
+    fn output_type(&self) -> Result<ConcreteDataType> {
+        let input_types = self.input_types()?;
+        ensure!(input_types.len() == 2, InvalidInputStateSnafu);
+        Ok(ConcreteDataType::float64_datatype())
+    }
+
+    fn state_types(&self) -> Result<Vec<ConcreteDataType>> {
+        let input_types = self.input_types()?;
+        ensure!(input_types.len() == 2, InvalidInputStateSnafu);
+        Ok(vec![
+            ConcreteDataType::binary_datatype(),
+            ConcreteDataType::float64_datatype(),
+        ])
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use datatypes::vectors::Int32Vector;
+
+    use super::*;
+
+    fn percentile_args(values: Vec<i32>, p: f64) -> Vec<VectorRef> {
+        let len = values.len();
+        vec![
+            Arc::new(Int32Vector::from_vec(values)),
+            Arc::new(Float64Vector::from_vec(vec![p; len])),
+        ]
+    }
+
+    fn assert_estimation(expected: f64, actual: Value) {
+        let Value::Float64(OrderedFloat(actual)) = actual else {
+            unreachable!()
+        };
+        assert!(
+            (expected - actual).abs() <= expected.abs() * 0.01,
+            "expected: {expected}, actual: {actual}"
+        );
+    }
+
+    #[test]
+    fn test_update_batch() {
+        let mut accumulator = ApproxPercentile::<i32>::default();
+        accumulator.update_batch(&[]).unwrap();
+        assert_eq!(Value::Null, accumulator.evaluate().unwrap());
+
+        let mut accumulator = ApproxPercentile::<i32>::default();
+        accumulator
+            .update_batch(&percentile_args(vec![42], 50.0))
+            .unwrap();
+        assert_eq!(Value::from(42.0), accumulator.evaluate().unwrap());
+
+        // Values with nulls.
+        let mut accumulator = ApproxPercentile::<i32>::default();
+        let v: Vec<VectorRef> = vec![
+            Arc::new(Int32Vector::from(vec![Some(-2i32), None, Some(3), Some(4)])),
+            Arc::new(Float64Vector::from_vec(vec![100.0; 4])),
+        ];
+        accumulator.update_batch(&v).unwrap();
+        assert_eq!(Value::from(4.0), accumulator.evaluate().unwrap());
+
+        for (p, expected) in [(0.0, 1.0), (50.0, 5000.0), (99.0, 9900.0), (100.0, 10000.0)] {
+            let mut accumulator = ApproxPercentile::<i32>::default();
+            // Adds values in reverse order so the digest needs to sort them.
+            accumulator
+                .update_batch(&percentile_args((1..=10000).rev().collect(), p))
+                .unwrap();
+            assert_estimation(expected, accumulator.evaluate().unwrap());
+        }
+    }
+
+    #[test]
+    fn test_merge_batch() {
+        // Builds digests of three partitions.
+        let mut states: Vec<Vec<Value>> = Vec::new();
+        for range in [1..=3000, 3001..=8000, 8001..=10000] {
+            let mut accumulator = ApproxPercentile::<i32>::default();
+            accumulator
+                .update_batch(&percentile_args(range.collect(), 90.0))
+                .unwrap();
+            states.push(accumulator.state().unwrap());
+        }
+
+        let digests: Vec<_> = states
+            .iter()
+            .map(|state| match &state[0] {
+                Value::Binary(bytes) => bytes.to_vec(),
+                _ => unreachable!(),
+            })
+            .collect();
+        let mut accumulator = ApproxPercentile::<i32>::default();
+        let v: Vec<VectorRef> = vec![
+            Arc::new(BinaryVector::from(digests)),
+            Arc::new(Float64Vector::from_vec(vec![90.0; 3])),
+        ];
+        accumulator.merge_batch(&v).unwrap();
+        assert_estimation(9000.0, accumulator.evaluate().unwrap());
+
+        let v: Vec<VectorRef> = vec![
+            Arc::new(BinaryVector::from(vec![vec![1u8, 2, 3]])),
+            Arc::new(Float64Vector::from_vec(vec![90.0])),
+        ];
+        assert!(accumulator.merge_batch(&v).is_err());
+    }
+}
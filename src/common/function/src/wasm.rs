@@ -0,0 +1,439 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! User-defined functions in WebAssembly.
+//!
+//! A module must export a function whose parameters and result are numeric WASM
+//! types mapped from the SQL types of the UDF:
+//! - `BOOLEAN`, `(U)INT8`, `(U)INT16`, `(U)INT32` to `i32`
+//! - `(U)INT64` to `i64`
+//! - `FLOAT32` to `f32`
+//! - `FLOAT64` to `f64`
+//!
+//! The module can't import anything from the host. We instantiate the module for each
+//! batch and call the function for each row. Rows with any null argument output null.
+
+use std::fmt;
+use std::time::Duration;
+
+use common_query::error::{InvalidFuncArgsSnafu, Result, WasmUdfSnafu};
+use common_query::prelude::{Signature, Volatility};
+use datatypes::data_type::DataType;
+use datatypes::prelude::{ConcreteDataType, Value, ValueRef};
+use datatypes::vectors::VectorRef;
+use once_cell::sync::Lazy;
+use snafu::{ensure, OptionExt};
+use wasmtime::{
+    Config, Engine, ExternType, Instance, Module, Store, StoreLimits, StoreLimitsBuilder, Val,
+    ValType,
+};
+
+use crate::function::{Function, FunctionContext};
+
+/// Interval to increase the epoch of the engine.
+const EPOCH_TICK: Duration = Duration::from_millis(10);
+
+/// Engine to compile and run all WASM modules.
+static WASM_ENGINE: Lazy<Engine> = Lazy::new(|| {
+    let mut config = Config::new();
+    config.consume_fuel(true).epoch_interruption(true);
+    let engine = Engine::new(&config).expect("Failed to create WASM engine");
+
+    // Stores use the epoch to interrupt functions that run too long.
+    let ticker = engine.clone();
+    let _ = std::thread::Builder::new()
+        .name("wasm-epoch-ticker".to_string())
+        .spawn(move || loop {
+            std::thread::sleep(EPOCH_TICK);
+            ticker.increment_epoch();
+        })
+        .expect("Failed to spawn WASM epoch ticker");
+
+    engine
+});
+
+/// Limits of resources to evaluate a batch by a WASM UDF.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WasmLimits {
+    /// Fuel to consume, roughly the number of WASM instructions to execute.
+    pub fuel: u64,
+    /// Max size of the linear memory in bytes.
+    pub max_memory: usize,
+    /// Max duration to run.
+    pub timeout: Duration,
+}
+
+impl Default for WasmLimits {
+    fn default() -> Self {
+        Self {
+            fuel: 1_000_000_000,
+            max_memory: 64 * 1024 * 1024,
+            timeout: Duration::from_secs(5),
+        }
+    }
+}
+
+/// State of a store.
+struct StoreState {
+    limits: StoreLimits,
+}
+
+/// A UDF that executes a function exported by a WASM module.
+pub struct WasmFunction {
+    /// Name of the UDF.
+    name: String,
+    /// Name of the function exported by the module.
+    export: String,
+    arg_types: Vec<ConcreteDataType>,
+    return_type: ConcreteDataType,
+    module: Module,
+    limits: WasmLimits,
+}
+
+impl WasmFunction {
+    /// Compiles the WASM module in `binary` and validates the signature of the `export`
+    /// function.
+    pub fn try_new(
+        name: String,
+        export: String,
+        arg_types: Vec<ConcreteDataType>,
+        return_type: ConcreteDataType,
+        binary: &[u8],
+        limits: WasmLimits,
+    ) -> Result<WasmFunction> {
+        ensure!(
+            !arg_types.is_empty(),
+            InvalidFuncArgsSnafu {
+                err_msg: format!("WASM UDF {name} requires at least one argument"),
+            }
+        );
+        let mut params = Vec::with_capacity(arg_types.len());
+        for data_type in arg_types.iter().chain([&return_type]) {
+            let val_type = to_val_type(data_type).with_context(|| InvalidFuncArgsSnafu {
+                err_msg: format!("Unsupported type {data_type:?} in WASM UDF {name}"),
+            })?;
+            params.push(val_type);
+        }
+        // Safety: we push the type of the return value.
+        let result = params.pop().unwrap();
+
+        let module = Module::new(&WASM_ENGINE, binary).map_err(|e| {
+            WasmUdfSnafu {
+                msg: format!("failed to compile module of {name}, {e}"),
+            }
+            .build()
+        })?;
+        ensure!(
+            module.imports().next().is_none(),
+            WasmUdfSnafu {
+                msg: format!("module of {name} must not import anything"),
+            }
+        );
+        let Some(ExternType::Func(func_type)) = module.get_export(&export) else {
+            return WasmUdfSnafu {
+                msg: format!("module of {name} doesn't export function {export}"),
+            }
+            .fail();
+        };
+        ensure!(
+            func_type.params().eq(params.iter().copied())
+                && func_type.results().eq([result]),
+            WasmUdfSnafu {
+                msg: format!(
+                    "function {export} has signature {func_type:?}, expect params {params:?} and result {result:?}"
+                ),
+            }
+        );
+
+        Ok(WasmFunction {
+            name,
+            export,
+            arg_types,
+            return_type,
+            module,
+            limits,
+        })
+    }
+
+    /// Creates a store with limits.
+    fn new_store(&self) -> Result<Store<StoreState>> {
+        let limits = StoreLimitsBuilder::new()
+            .memory_size(self.limits.max_memory)
+            .instances(1)
+            .build();
+        let mut store = Store::new(&WASM_ENGINE, StoreState { limits });
+        store.limiter(|state| &mut state.limits);
+        store.add_fuel(self.limits.fuel).map_err(|e| {
+            WasmUdfSnafu {
+                msg: format!("failed to add fuel, {e}"),
+            }
+            .build()
+        })?;
+        let ticks = (self.limits.timeout.as_millis() / EPOCH_TICK.as_millis()).max(1);
+        store.set_epoch_deadline(ticks as u64);
+
+        Ok(store)
+    }
+}
+
+impl Function for WasmFunction {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn return_type(&self, _input_types: &[ConcreteDataType]) -> Result<ConcreteDataType> {
+        Ok(self.return_type.clone())
+    }
+
+    fn signature(&self) -> Signature {
+        Signature::exact(self.arg_types.clone(), Volatility::Immutable)
+    }
+
+    fn eval(&self, _func_ctx: FunctionContext, columns: &[VectorRef]) -> Result<VectorRef> {
+        ensure!(
+            columns.len() == self.arg_types.len(),
+            InvalidFuncArgsSnafu {
+                err_msg: format!(
+                    "The length of the args is not correct, expect {}, have: {}",
+                    self.arg_types.len(),
+                    columns.len()
+                ),
+            }
+        );
+
+        let num_rows = columns[0].len();
+        let mut builder = self.return_type.create_mutable_vector(num_rows);
+        let mut store = self.new_store()?;
+        let instance = Instance::new(&mut store, &self.module, &[]).map_err(|e| {
+            WasmUdfSnafu {
+                msg: format!("failed to instantiate module of {}, {e}", self.name),
+            }
+            .build()
+        })?;
+        // Safety: we validate the export while creating the function.
+        let func = instance.get_func(&mut store, &self.export).unwrap();
+
+        let mut params = vec![Val::I32(0); columns.len()];
+        let mut results = [Val::I32(0)];
+        for row in 0..num_rows {
+            let mut has_null = false;
+            for (param, column) in params.iter_mut().zip(columns) {
+                match to_val(column.get_ref(row)) {
+                    Some(val) => *param = val,
+                    None => {
+                        has_null = true;
+                        break;
+                    }
+                }
+            }
+            if has_null {
+                builder.push_null();
+                continue;
+            }
+
+            func.call(&mut store, &params, &mut results).map_err(|e| {
+                WasmUdfSnafu {
+                    msg: format!("failed to call {}, {e}", self.name),
+                }
+                .build()
+            })?;
+            let value = from_val(&results[0], &self.return_type).with_context(|| WasmUdfSnafu {
+                msg: format!("unexpected result {:?} of {}", results[0], self.name),
+            })?;
+            builder.push_value_ref(value.as_value_ref());
+        }
+
+        Ok(builder.to_vector())
+    }
+}
+
+impl fmt::Display for WasmFunction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name.to_ascii_uppercase())
+    }
+}
+
+/// Returns the WASM type of the `data_type`.
+fn to_val_type(data_type: &ConcreteDataType) -> Option<ValType> {
+    match data_type {
+        ConcreteDataType::Boolean(_)
+        | ConcreteDataType::Int8(_)
+        | ConcreteDataType::Int16(_)
+        | ConcreteDataType::Int32(_)
+        | ConcreteDataType::UInt8(_)
+        | ConcreteDataType::UInt16(_)
+        | ConcreteDataType::UInt32(_) => Some(ValType::I32),
+        ConcreteDataType::Int64(_) | ConcreteDataType::UInt64(_) => Some(ValType::I64),
+        ConcreteDataType::Float32(_) => Some(ValType::F32),
+        ConcreteDataType::Float64(_) => Some(ValType::F64),
+        _ => None,
+    }
+}
+
+/// Converts the `value` into a WASM value, returns `None` if the value is null.
+fn to_val(value: ValueRef) -> Option<Val> {
+    let val = match value {
+        ValueRef::Boolean(v) => Val::I32(v as i32),
+        ValueRef::Int8(v) => Val::I32(v as i32),
+        ValueRef::Int16(v) => Val::I32(v as i32),
+        ValueRef::Int32(v) => Val::I32(v),
+        ValueRef::UInt8(v) => Val::I32(v as i32),
+        ValueRef::UInt16(v) => Val::I32(v as i32),
+        ValueRef::UInt32(v) => Val::I32(v as i32),
+        ValueRef::Int64(v) => Val::I64(v),
+        ValueRef::UInt64(v) => Val::I64(v as i64),
+        ValueRef::Float32(v) => Val::F32(v.into_inner().to_bits()),
+        ValueRef::Float64(v) => Val::F64(v.into_inner().to_bits()),
+        // Other types are rejected by the signature.
+        _ => return None,
+    };
+    Some(val)
+}
+
+/// Converts the WASM value `val` into a value of `data_type`.
+fn from_val(val: &Val, data_type: &ConcreteDataType) -> Option<Value> {
+    let value = match (data_type, val) {
+        (ConcreteDataType::Boolean(_), Val::I32(v)) => Value::Boolean(*v != 0),
+        (ConcreteDataType::Int8(_), Val::I32(v)) => Value::Int8(*v as i8),
+        (ConcreteDataType::Int16(_), Val::I32(v)) => Value::Int16(*v as i16),
+        (ConcreteDataType::Int32(_), Val::I32(v)) => Value::Int32(*v),
+        (ConcreteDataType::UInt8(_), Val::I32(v)) => Value::UInt8(*v as u8),
+        (ConcreteDataType::UInt16(_), Val::I32(v)) => Value::UInt16(*v as u16),
+        (ConcreteDataType::UInt32(_), Val::I32(v)) => Value::UInt32(*v as u32),
+        (ConcreteDataType::Int64(_), Val::I64(v)) => Value::Int64(*v),
+        (ConcreteDataType::UInt64(_), Val::I64(v)) => Value::UInt64(*v as u64),
+        (ConcreteDataType::Float32(_), Val::F32(v)) => Value::Float32(f32::from_bits(*v).into()),
+        (ConcreteDataType::Float64(_), Val::F64(v)) => Value::Float64(f64::from_bits(*v).into()),
+        _ => return None,
+    };
+    Some(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use datatypes::vectors::{Float64Vector, Int64Vector};
+
+    use super::*;
+
+    const ADD: &str = r#"(module
+  (func (export "add") (param i64 f64) (result f64)
+    local.get 0
+    f64.convert_i64_s
+    local.get 1
+    f64.add))"#;
+
+    const LOOP: &str = r#"(module
+  (func (export "spin") (param i64) (result i64)
+    (loop $l (br $l))
+    local.get 0))"#;
+
+    fn new_add_function() -> WasmFunction {
+        WasmFunction::try_new(
+            "my_add".to_string(),
+            "add".to_string(),
+            vec![
+                ConcreteDataType::int64_datatype(),
+                ConcreteDataType::float64_datatype(),
+            ],
+            ConcreteDataType::float64_datatype(),
+            ADD.as_bytes(),
+            WasmLimits::default(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_wasm_function_eval() {
+        let function = new_add_function();
+        assert_eq!("my_add", function.name());
+        assert_eq!(
+            ConcreteDataType::float64_datatype(),
+            function.return_type(&[]).unwrap()
+        );
+
+        let columns: Vec<VectorRef> = vec![
+            Arc::new(Int64Vector::from(vec![Some(1), None, Some(3)])),
+            Arc::new(Float64Vector::from(vec![Some(0.5), Some(1.0), Some(-3.0)])),
+        ];
+        let result = function.eval(FunctionContext::default(), &columns).unwrap();
+        let expect: VectorRef = Arc::new(Float64Vector::from(vec![Some(1.5), None, Some(0.0)]));
+        assert_eq!(expect, result);
+    }
+
+    #[test]
+    fn test_invalid_wasm_function() {
+        let new_function = |export: &str, arg_types, return_type, binary: &str| {
+            WasmFunction::try_new(
+                "f".to_string(),
+                export.to_string(),
+                arg_types,
+                return_type,
+                binary.as_bytes(),
+                WasmLimits::default(),
+            )
+        };
+        let int64 = ConcreteDataType::int64_datatype;
+        let float64 = ConcreteDataType::float64_datatype;
+
+        // No argument.
+        assert!(new_function("add", vec![], float64(), ADD).is_err());
+        // Unsupported type.
+        assert!(new_function(
+            "add",
+            vec![int64(), ConcreteDataType::string_datatype()],
+            float64(),
+            ADD
+        )
+        .is_err());
+        // Export not found.
+        assert!(new_function("sub", vec![int64(), float64()], float64(), ADD).is_err());
+        // Signature mismatch.
+        assert!(new_function("add", vec![int64(), float64()], int64(), ADD).is_err());
+        // Invalid module.
+        assert!(new_function("add", vec![int64(), float64()], float64(), "invalid").is_err());
+    }
+
+    #[test]
+    fn test_wasm_function_limits() {
+        let new_spin = |limits| {
+            WasmFunction::try_new(
+                "spin".to_string(),
+                "spin".to_string(),
+                vec![ConcreteDataType::int64_datatype()],
+                ConcreteDataType::int64_datatype(),
+                LOOP.as_bytes(),
+                limits,
+            )
+            .unwrap()
+        };
+        let columns: Vec<VectorRef> = vec![Arc::new(Int64Vector::from_slice([1]))];
+
+        // Runs out of fuel.
+        let function = new_spin(WasmLimits {
+            fuel: 10_000,
+            ..Default::default()
+        });
+        assert!(function.eval(FunctionContext::default(), &columns).is_err());
+
+        // Timeout.
+        let function = new_spin(WasmLimits {
+            fuel: 1 << 40,
+            timeout: Duration::from_millis(50),
+            ..Default::default()
+        });
+        assert!(function.eval(FunctionContext::default(), &columns).is_err());
+    }
+}
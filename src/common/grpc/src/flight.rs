@@ -19,7 +19,7 @@ use api::v1::{AffectedRows, FlightMetadata};
 use arrow_flight::utils::flight_data_to_arrow_batch;
 use arrow_flight::{FlightData, SchemaAsIpc};
 use common_base::bytes::Bytes;
-use common_recordbatch::{RecordBatch, RecordBatches};
+use common_recordbatch::{RecordBatch, RecordBatchMetrics, RecordBatches};
 use datatypes::arrow;
 use datatypes::arrow::datatypes::Schema as ArrowSchema;
 use datatypes::arrow::ipc::{root_as_message, writer, MessageHeader};
@@ -30,8 +30,8 @@ use prost::Message;
 use snafu::{OptionExt, ResultExt};
 
 use crate::error::{
-    ConvertArrowSchemaSnafu, CreateRecordBatchSnafu, DecodeFlightDataSnafu, InvalidFlightDataSnafu,
-    Result,
+    ConvertArrowSchemaSnafu, CreateRecordBatchSnafu, DecodeFlightDataSnafu,
+    DecodeFlightMetricsSnafu, InvalidFlightDataSnafu, Result,
 };
 
 #[derive(Debug, Clone)]
@@ -39,6 +39,8 @@ pub enum FlightMessage {
     Schema(SchemaRef),
     Recordbatch(RecordBatch),
     AffectedRows(usize),
+    /// Metrics of the stream, sent after all record batches.
+    Metrics(RecordBatchMetrics),
 }
 
 pub struct FlightEncoder {
@@ -94,6 +96,21 @@ impl FlightEncoder {
                     data_body: ProstBytes::default(),
                 }
             }
+            FlightMessage::Metrics(metrics) => {
+                // The metadata is empty, we put metrics encoded in JSON into the body.
+                let metadata = FlightMetadata {
+                    affected_rows: None,
+                }
+                .encode_to_vec();
+                // Safety: Serializing metrics never fails.
+                let body = serde_json::to_vec(&metrics).unwrap();
+                FlightData {
+                    flight_descriptor: None,
+                    data_header: build_none_flight_msg().into(),
+                    app_metadata: metadata.into(),
+                    data_body: body.into(),
+                }
+            }
         }
     }
 }
@@ -119,6 +136,11 @@ impl FlightDecoder {
                 if let Some(AffectedRows { value }) = metadata.affected_rows {
                     return Ok(FlightMessage::AffectedRows(value as _));
                 }
+                if !flight_data.data_body.is_empty() {
+                    let metrics = serde_json::from_slice(&flight_data.data_body)
+                        .context(DecodeFlightMetricsSnafu)?;
+                    return Ok(FlightMessage::Metrics(metrics));
+                }
                 InvalidFlightDataSnafu {
                     reason: "Expecting FlightMetadata have some meaningful content.",
                 }
@@ -287,6 +309,26 @@ mod test {
         assert_eq!(actual_batch, batch2);
     }
 
+    #[test]
+    fn test_encode_decode_metrics() {
+        let metrics = RecordBatchMetrics {
+            counters: vec![("num_files".to_string(), 3)],
+            elapsed: vec![("scan_cost".to_string(), std::time::Duration::from_millis(5))],
+        };
+        let flight_data = FlightEncoder::default().encode(FlightMessage::Metrics(metrics.clone()));
+
+        let message = FlightDecoder::default().try_decode(flight_data).unwrap();
+        let FlightMessage::Metrics(actual) = message else {
+            unreachable!()
+        };
+        assert_eq!(metrics, actual);
+
+        // Affected rows don't carry a body.
+        let flight_data = FlightEncoder::default().encode(FlightMessage::AffectedRows(2));
+        let message = FlightDecoder::default().try_decode(flight_data).unwrap();
+        assert!(matches!(message, FlightMessage::AffectedRows(2)));
+    }
+
     #[test]
     fn test_flight_messages_to_recordbatches() {
         let schema = Arc::new(Schema::new(vec![ColumnSchema::new(
@@ -25,10 +25,13 @@ use crate::key::table_route::TableRouteValue;
 use crate::key::TableMetadataManagerRef;
 use crate::region_keeper::MemoryRegionKeeperRef;
 use crate::rpc::ddl::{CreateTableTask, SubmitDdlTaskRequest, SubmitDdlTaskResponse};
+use crate::rpc::procedure::ProcedureStateResponse;
 
 pub mod alter_table;
 pub mod create_table;
 pub mod drop_table;
+pub mod rebuild_table;
+pub mod table_meta;
 pub mod truncate_table;
 pub mod utils;
 
@@ -45,6 +48,14 @@ pub trait DdlTaskExecutor: Send + Sync {
         ctx: &ExecutorContext,
         request: SubmitDdlTaskRequest,
     ) -> Result<SubmitDdlTaskResponse>;
+
+    /// Queries the state of the procedure with `procedure_id`, returns `None` if the
+    /// procedure is not found.
+    async fn query_procedure_state(
+        &self,
+        ctx: &ExecutorContext,
+        procedure_id: &str,
+    ) -> Result<Option<ProcedureStateResponse>>;
 }
 
 pub type DdlTaskExecutorRef = Arc<dyn DdlTaskExecutor>;
@@ -109,7 +109,7 @@ impl DropTableProcedure {
             }
         );
 
-        self.data.state = DropTableState::RemoveMetadata;
+        self.data.state = DropTableState::DatanodeDropRegions;
 
         Ok(Status::executing(true))
     }
@@ -144,11 +144,9 @@ impl DropTableProcedure {
 
     /// Removes the table metadata.
     async fn on_remove_metadata(&mut self) -> Result<Status> {
-        // NOTES: If the meta server is crashed after the `RemoveMetadata`,
-        // Corresponding regions of this table on the Datanode will be closed automatically.
-        // Then any future dropping operation will fail.
-
-        // TODO(weny): Considers introducing a RegionStatus to indicate the region is dropping.
+        // NOTES: Regions are dropped before removing the metadata, so the table can
+        // still be found and dropped again if the meta server crashes in any step.
+        // Otherwise, the regions would be left on the Datanode without metadata.
 
         let table_metadata_manager = &self.context.table_metadata_manager;
         let table_info_value = &self.data.table_info_value;
@@ -182,12 +180,15 @@ impl DropTableProcedure {
             .invalidate_table_id(&ctx, self.data.table_id())
             .await?;
 
-        self.data.state = DropTableState::DatanodeDropRegions;
-
-        Ok(Status::executing(true))
+        Ok(Status::Done)
     }
 
-    pub async fn on_datanode_drop_regions(&self) -> Result<Status> {
+    /// Drops regions on Datanode, the regions that have been dropped are ignored so
+    /// this step can be retried.
+    pub async fn on_datanode_drop_regions(&mut self) -> Result<Status> {
+        // Prevents the regions from being operated, e.g., region failover, while they are dropping.
+        self.register_dropping_regions()?;
+
         let table_id = self.data.table_id();
 
         let region_routes = &self.data.region_routes();
@@ -235,7 +236,9 @@ impl DropTableProcedure {
             .into_iter()
             .collect::<Result<Vec<_>>>()?;
 
-        Ok(Status::Done)
+        self.data.state = DropTableState::RemoveMetadata;
+
+        Ok(Status::executing(true))
     }
 }
 
@@ -254,9 +257,9 @@ impl Procedure for DropTableProcedure {
 
         match self.data.state {
             DropTableState::Prepare => self.on_prepare().await,
+            DropTableState::DatanodeDropRegions => self.on_datanode_drop_regions().await,
             DropTableState::RemoveMetadata => self.on_remove_metadata().await,
             DropTableState::InvalidateTableCache => self.on_broadcast().await,
-            DropTableState::DatanodeDropRegions => self.on_datanode_drop_regions().await,
         }
         .map_err(handle_retry_error)
     }
@@ -323,10 +326,10 @@ impl DropTableData {
 pub enum DropTableState {
     /// Prepares to drop the table
     Prepare,
+    /// Drops regions on Datanode
+    DatanodeDropRegions,
     /// Removes metadata
     RemoveMetadata,
     /// Invalidates Table Cache
     InvalidateTableCache,
-    /// Drops regions on Datanode
-    DatanodeDropRegions,
 }
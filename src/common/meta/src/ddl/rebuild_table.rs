@@ -0,0 +1,1000 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Procedure to rebuild a table with a new primary key.
+//!
+//! The primary key of a region can't be changed in place, so the procedure:
+//! - creates a shadow region with the new primary key for each region of the table,
+//!   on the same datanode as the leader of the source region.
+//! - marks source regions in the table route so frontends also write to shadow regions.
+//! - backfills shadow regions by scanning source regions. Writes to a source region are
+//!   fenced while backfilling its shadow region, otherwise rows written during the scan
+//!   might be overwritten by older rows scanned before them as backfilled rows have
+//!   larger sequences.
+//! - swaps source regions with shadow regions in the table route and the table info
+//!   in one transaction.
+//! - drops source regions.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use api::helper::{vectors_to_rows, ColumnDataTypeWrapper};
+use api::v1::region::{
+    region_request, CreateRequest as PbCreateRegionRequest, DropRequest as PbDropRegionRequest,
+    InsertRequest as PbInsertRegionRequest, InsertRequests as PbInsertRegionRequests, QueryRequest,
+    RegionColumnDef, RegionRequest, RegionRequestHeader,
+};
+use api::v1::{ColumnDef, ColumnSchema as PbColumnSchema, Rows, SemanticType};
+use async_trait::async_trait;
+use common_config::WAL_OPTIONS_KEY;
+use common_error::ext::{BoxedError, ErrorExt};
+use common_error::status_code::StatusCode;
+use common_procedure::error::{ExternalSnafu, FromJsonSnafu, ToJsonSnafu};
+use common_procedure::{
+    Context as ProcedureContext, LockKey, Procedure, Result as ProcedureResult, Status,
+};
+use common_telemetry::tracing_context::TracingContext;
+use common_telemetry::{debug, info};
+use datatypes::schema::{ColumnSchema, Schema};
+use futures::future::join_all;
+use futures::TryStreamExt;
+use serde::{Deserialize, Serialize};
+use snafu::{ensure, OptionExt, ResultExt};
+use store_api::storage::{RegionId, RegionNumber};
+use strum::AsRefStr;
+use table::engine::TableReference;
+use table::metadata::{RawTableInfo, TableId};
+
+use crate::cache_invalidator::Context;
+use crate::ddl::utils::{handle_operate_region_error, handle_retry_error, region_storage_path};
+use crate::ddl::DdlContext;
+use crate::error::{self, Result};
+use crate::key::datanode_table::{DatanodeTableKey, RegionInfo};
+use crate::key::table_info::TableInfoValue;
+use crate::key::table_name::TableNameKey;
+use crate::key::table_route::TableRouteValue;
+use crate::key::DeserializedValueWithBytes;
+use crate::metrics;
+use crate::region_keeper::OperatingRegionGuard;
+use crate::rpc::ddl::RebuildTableTask;
+use crate::rpc::router::{find_leaders, RegionRoute};
+
+pub struct RebuildTableProcedure {
+    context: DdlContext,
+    data: RebuildTableData,
+    /// The guards of shadow regions and source regions.
+    operating_regions: Vec<OperatingRegionGuard>,
+}
+
+impl RebuildTableProcedure {
+    pub const TYPE_NAME: &'static str = "metasrv-procedure::RebuildTable";
+
+    pub fn new(
+        cluster_id: u64,
+        task: RebuildTableTask,
+        table_info_value: DeserializedValueWithBytes<TableInfoValue>,
+        table_route_value: DeserializedValueWithBytes<TableRouteValue>,
+        context: DdlContext,
+    ) -> Self {
+        Self {
+            context,
+            data: RebuildTableData::new(cluster_id, task, table_info_value, table_route_value),
+            operating_regions: vec![],
+        }
+    }
+
+    pub fn from_json(json: &str, context: DdlContext) -> ProcedureResult<Self> {
+        let data: RebuildTableData = serde_json::from_str(json).context(FromJsonSnafu)?;
+        let mut procedure = Self {
+            context,
+            data,
+            operating_regions: vec![],
+        };
+
+        if !matches!(procedure.data.state, RebuildTableState::Prepare) {
+            procedure
+                .register_operating_regions()
+                .map_err(BoxedError::new)
+                .context(ExternalSnafu)?;
+        }
+
+        Ok(procedure)
+    }
+
+    /// Checks whether the table exists and the new primary key is valid.
+    async fn on_prepare(&mut self) -> Result<Status> {
+        let table_ref = self.data.table_ref();
+        let table_name = table_ref.to_string();
+        let table_id = self
+            .context
+            .table_metadata_manager
+            .table_name_manager()
+            .get(TableNameKey::new(
+                table_ref.catalog,
+                table_ref.schema,
+                table_ref.table,
+            ))
+            .await?
+            .with_context(|| error::TableNotFoundSnafu {
+                table_name: table_name.clone(),
+            })?
+            .table_id();
+        ensure!(
+            table_id == self.data.table_id(),
+            error::TableNotFoundSnafu { table_name }
+        );
+        ensure!(
+            matches!(*self.data.table_route_value, TableRouteValue::Physical(_)),
+            error::UnsupportedSnafu {
+                operation: "rebuilding a logical table",
+            }
+        );
+
+        self.data.primary_key_indices()?;
+
+        // Allocates region numbers of shadow regions after existing regions.
+        let region_routes = self.data.region_routes();
+        let mut next_region_number = region_routes
+            .iter()
+            .map(|route| route.region.id.region_number())
+            .max()
+            .unwrap_or_default()
+            + 1;
+        let mut shadow_regions = BTreeMap::new();
+        for route in region_routes {
+            shadow_regions.insert(route.region.id.region_number(), next_region_number);
+            next_region_number += 1;
+        }
+
+        // Shadow regions reuse wal options of source regions.
+        let mut region_wal_options = HashMap::new();
+        for peer in find_leaders(region_routes) {
+            let datanode_table = self
+                .context
+                .table_metadata_manager
+                .datanode_table_manager()
+                .get(&DatanodeTableKey::new(peer.id, table_id))
+                .await?;
+            if let Some(datanode_table) = datanode_table {
+                region_wal_options.extend(datanode_table.region_info.region_wal_options);
+            }
+        }
+
+        self.data.shadow_regions = shadow_regions;
+        self.data.region_wal_options = region_wal_options;
+        self.data.state = RebuildTableState::CreateShadowRegions;
+
+        Ok(Status::executing(true))
+    }
+
+    /// Registers shadow regions and source regions so they won't be closed by others.
+    fn register_operating_regions(&mut self) -> Result<()> {
+        let table_id = self.data.table_id();
+        let mut operating_regions = Vec::with_capacity(self.data.shadow_regions.len() * 2);
+        for route in self.data.region_routes() {
+            let Some(leader) = &route.leader_peer else {
+                continue;
+            };
+            let source_region_id = route.region.id;
+            let shadow_region_id = RegionId::new(
+                table_id,
+                self.data.shadow_regions[&source_region_id.region_number()],
+            );
+            operating_regions.push((source_region_id, leader.id));
+            operating_regions.push((shadow_region_id, leader.id));
+        }
+
+        if self.operating_regions.len() == operating_regions.len() {
+            return Ok(());
+        }
+
+        let mut guards = Vec::with_capacity(operating_regions.len());
+        for (region_id, datanode_id) in operating_regions {
+            let guard = self
+                .context
+                .memory_region_keeper
+                .register(datanode_id, region_id)
+                .context(error::RegionOperatingRaceSnafu {
+                    region_id,
+                    peer_id: datanode_id,
+                })?;
+            guards.push(guard);
+        }
+        self.operating_regions = guards;
+
+        Ok(())
+    }
+
+    /// Builds the request to create the shadow region of `source_region_id`.
+    fn create_shadow_region_request(
+        &self,
+        source_region_id: RegionId,
+    ) -> Result<PbCreateRegionRequest> {
+        let table_info = self.data.table_info();
+        let column_schemas = &table_info.meta.schema.column_schemas;
+        let primary_key_indices = self.data.primary_key_indices()?;
+        let time_index = table_info.meta.schema.timestamp_index;
+
+        let column_defs = column_schemas
+            .iter()
+            .enumerate()
+            .map(|(i, column_schema)| {
+                let semantic_type = if time_index == Some(i) {
+                    SemanticType::Timestamp
+                } else if primary_key_indices.contains(&i) {
+                    SemanticType::Tag
+                } else {
+                    SemanticType::Field
+                };
+                Ok(RegionColumnDef {
+                    column_def: Some(to_column_def(column_schema, semantic_type)?),
+                    column_id: i as u32,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let primary_key = primary_key_indices.iter().map(|i| *i as u32).collect();
+
+        let mut options: HashMap<String, String> = (&table_info.meta.options).into();
+        if let Some(wal_options) = self
+            .data
+            .region_wal_options
+            .get(&source_region_id.region_number().to_string())
+        {
+            options.insert(WAL_OPTIONS_KEY.to_string(), wal_options.clone());
+        }
+
+        Ok(PbCreateRegionRequest {
+            region_id: self.data.shadow_region_id(source_region_id).as_u64(),
+            engine: table_info.meta.engine.clone(),
+            column_defs,
+            primary_key,
+            path: region_storage_path(&table_info.catalog_name, &table_info.schema_name),
+            options,
+        })
+    }
+
+    /// Creates shadow regions on datanodes of source regions.
+    async fn on_create_shadow_regions(&mut self) -> Result<Status> {
+        self.register_operating_regions()?;
+
+        let mut create_region_tasks = Vec::with_capacity(self.data.shadow_regions.len());
+        for route in self.data.region_routes() {
+            let Some(datanode) = route.leader_peer.clone() else {
+                continue;
+            };
+            let request = self.create_shadow_region_request(route.region.id)?;
+            debug!(
+                "Creating shadow region {} on Datanode {datanode:?}",
+                RegionId::from_u64(request.region_id)
+            );
+            let request = RegionRequest {
+                header: Some(RegionRequestHeader {
+                    tracing_context: TracingContext::from_current_span().to_w3c(),
+                    ..Default::default()
+                }),
+                body: Some(region_request::Body::Create(request)),
+            };
+            let requester = self.context.datanode_manager.datanode(&datanode).await;
+
+            create_region_tasks.push(async move {
+                if let Err(err) = requester.handle(request).await {
+                    return Err(handle_operate_region_error(datanode)(err));
+                }
+                Ok(())
+            });
+        }
+
+        join_all(create_region_tasks)
+            .await
+            .into_iter()
+            .collect::<Result<Vec<_>>>()?;
+
+        self.data.state = RebuildTableState::EnableDoubleWrite;
+
+        Ok(Status::executing(true))
+    }
+
+    /// Marks source regions in the table route so writes to them are also
+    /// written to shadow regions.
+    async fn on_enable_double_write(&mut self) -> Result<Status> {
+        let table_id = self.data.table_id();
+        let current_table_route_value = self.get_table_route_value().await?;
+        let primary_keys = &self.data.task.primary_keys;
+
+        let new_region_routes = current_table_route_value
+            .region_routes()
+            .iter()
+            .map(|route| {
+                let mut route = route.clone();
+                let shadow_region_id = self.data.shadow_region_id(route.region.id);
+                route
+                    .region
+                    .set_shadow_region(shadow_region_id, primary_keys);
+                route
+            })
+            .collect();
+
+        let region_info = self.source_region_info();
+        let region_options = region_info.region_options.clone();
+        let region_wal_options = region_info.region_wal_options.clone();
+        self.context
+            .table_metadata_manager
+            .update_table_route(
+                table_id,
+                region_info,
+                &current_table_route_value,
+                new_region_routes,
+                &region_options,
+                &region_wal_options,
+            )
+            .await?;
+
+        // Frontends start writing to shadow regions after reloading the table route.
+        self.context
+            .cache_invalidator
+            .invalidate_table_id(&Context::default(), table_id)
+            .await?;
+
+        info!("Enabled double write for rebuilding table {table_id}");
+
+        self.data.state = RebuildTableState::Backfill;
+
+        Ok(Status::executing(true))
+    }
+
+    /// Backfills one shadow region from its source region.
+    async fn on_backfill(&mut self) -> Result<Status> {
+        let next_region = self.data.region_routes().iter().find(|route| {
+            !self
+                .data
+                .backfilled_regions
+                .contains(&route.region.id.region_number())
+        });
+        let Some(route) = next_region.cloned() else {
+            self.data.state = RebuildTableState::SwapRegions;
+            return Ok(Status::executing(true));
+        };
+
+        let source_region_id = route.region.id;
+        self.set_write_fenced(source_region_id, true).await?;
+        let rows = self.backfill_region(&route).await?;
+        info!(
+            "Backfilled {rows} rows from region {source_region_id} to region {}",
+            self.data.shadow_region_id(source_region_id)
+        );
+        // Rows written after unfencing have larger sequences than backfilled rows.
+        self.set_write_fenced(source_region_id, false).await?;
+
+        self.data
+            .backfilled_regions
+            .push(source_region_id.region_number());
+
+        Ok(Status::executing(true))
+    }
+
+    /// Marks whether frontends should reject writes to the source region and waits
+    /// until frontends reload the table route.
+    async fn set_write_fenced(&self, region_id: RegionId, fenced: bool) -> Result<()> {
+        let table_id = self.data.table_id();
+        let current_table_route_value = self.get_table_route_value().await?;
+        let need_update = current_table_route_value
+            .region_routes()
+            .iter()
+            .any(|route| route.region.id == region_id && route.region.is_write_fenced() != fenced);
+
+        if need_update {
+            let new_region_routes = current_table_route_value
+                .region_routes()
+                .iter()
+                .map(|route| {
+                    let mut route = route.clone();
+                    if route.region.id == region_id {
+                        route.region.set_write_fenced(fenced);
+                    }
+                    route
+                })
+                .collect();
+            let region_info = self.source_region_info();
+            let region_options = region_info.region_options.clone();
+            let region_wal_options = region_info.region_wal_options.clone();
+            self.context
+                .table_metadata_manager
+                .update_table_route(
+                    table_id,
+                    region_info,
+                    &current_table_route_value,
+                    new_region_routes,
+                    &region_options,
+                    &region_wal_options,
+                )
+                .await?;
+        }
+
+        // Invalidates the cache even if the route is unchanged, as the procedure may
+        // be resumed after updating the route.
+        self.context
+            .cache_invalidator
+            .invalidate_table_id(&Context::default(), table_id)
+            .await?;
+
+        debug!("Set write fenced of region {region_id} to {fenced}");
+
+        Ok(())
+    }
+
+    /// Scans the source region of `route` and writes all rows to its shadow region.
+    ///
+    /// Returns the number of rows backfilled.
+    async fn backfill_region(&self, route: &RegionRoute) -> Result<usize> {
+        let source_region_id = route.region.id;
+        let shadow_region_id = self.data.shadow_region_id(source_region_id);
+        let datanode = route
+            .leader_peer
+            .clone()
+            .with_context(|| error::UnexpectedSnafu {
+                err_msg: format!("Leader of region {source_region_id} not found"),
+            })?;
+        let requester = self.context.datanode_manager.datanode(&datanode).await;
+        let tracing_context = TracingContext::from_current_span().to_w3c();
+
+        let request = QueryRequest {
+            header: Some(RegionRequestHeader {
+                tracing_context: tracing_context.clone(),
+                ..Default::default()
+            }),
+            region_id: source_region_id.as_u64(),
+            plan: self.data.task.scan_plan.clone(),
+        };
+        let mut stream = requester
+            .handle_query(request)
+            .await
+            .map_err(|e| handle_operate_region_error(datanode.clone())(e))?;
+
+        let time_index = self.data.time_index_name()?;
+        let mut row_schema = None;
+        let mut backfilled_rows = 0;
+        while let Some(batch) = stream
+            .try_next()
+            .await
+            .context(error::ReadRecordBatchSnafu {
+                region_id: source_region_id,
+            })?
+        {
+            if batch.num_rows() == 0 {
+                continue;
+            }
+            if row_schema.is_none() {
+                row_schema = Some(build_row_schema(
+                    &batch.schema,
+                    time_index,
+                    &self.data.task.primary_keys,
+                )?);
+            }
+
+            let rows = Rows {
+                schema: row_schema.clone().unwrap_or_default(),
+                rows: vectors_to_rows(batch.columns().iter(), batch.num_rows()),
+            };
+            let request = RegionRequest {
+                header: Some(RegionRequestHeader {
+                    tracing_context: tracing_context.clone(),
+                    ..Default::default()
+                }),
+                body: Some(region_request::Body::Inserts(PbInsertRegionRequests {
+                    requests: vec![PbInsertRegionRequest {
+                        region_id: shadow_region_id.as_u64(),
+                        rows: Some(rows),
+                    }],
+                })),
+            };
+            requester
+                .handle(request)
+                .await
+                .map_err(|e| handle_operate_region_error(datanode.clone())(e))?;
+            backfilled_rows += batch.num_rows();
+        }
+
+        Ok(backfilled_rows)
+    }
+
+    /// Replaces source regions with shadow regions in the table route and the table info.
+    async fn on_swap_regions(&mut self) -> Result<Status> {
+        let table_id = self.data.table_id();
+        let current_table_route_value = self.get_table_route_value().await?;
+        let swapped = current_table_route_value
+            .region_routes()
+            .iter()
+            .all(|route| {
+                !self
+                    .data
+                    .shadow_regions
+                    .contains_key(&route.region.id.region_number())
+            });
+
+        if !swapped {
+            let new_region_routes = current_table_route_value
+                .region_routes()
+                .iter()
+                .map(|route| {
+                    let mut route = route.clone();
+                    route.region.id = self.data.shadow_region_id(route.region.id);
+                    route.region.clear_shadow_region();
+                    route.region.set_write_fenced(false);
+                    route
+                })
+                .collect();
+            let new_table_info = self.data.build_new_table_info()?;
+
+            // The datanode table values store wal options of shadow regions.
+            let mut region_info = self.source_region_info();
+            region_info.region_wal_options = self
+                .data
+                .shadow_regions
+                .iter()
+                .filter_map(|(source, shadow)| {
+                    self.data
+                        .region_wal_options
+                        .get(&source.to_string())
+                        .map(|options| (shadow.to_string(), options.clone()))
+                })
+                .collect();
+            let region_options = region_info.region_options.clone();
+            let region_wal_options = region_info.region_wal_options.clone();
+
+            self.context
+                .table_metadata_manager
+                .update_table_info_and_route(
+                    &self.data.table_info_value,
+                    new_table_info,
+                    region_info,
+                    &current_table_route_value,
+                    new_region_routes,
+                    &region_options,
+                    &region_wal_options,
+                )
+                .await?;
+        }
+
+        info!("Swapped regions of table {table_id} with shadow regions");
+
+        self.data.state = RebuildTableState::InvalidateTableCache;
+
+        Ok(Status::executing(true))
+    }
+
+    /// Broadcasts the invalidating table cache instructions.
+    async fn on_broadcast(&mut self) -> Result<Status> {
+        let ctx = Context {
+            subject: Some("Invalidate table cache by rebuilding table".to_string()),
+        };
+
+        self.context
+            .cache_invalidator
+            .invalidate_table_id(&ctx, self.data.table_id())
+            .await?;
+
+        self.data.state = RebuildTableState::DropSourceRegions;
+
+        Ok(Status::executing(true))
+    }
+
+    /// Drops source regions on datanodes.
+    async fn on_drop_source_regions(&mut self) -> Result<Status> {
+        let mut drop_region_tasks = Vec::with_capacity(self.data.shadow_regions.len());
+        for route in self.data.region_routes() {
+            let Some(datanode) = route.leader_peer.clone() else {
+                continue;
+            };
+            let region_id = route.region.id;
+            debug!("Dropping source region {region_id} on Datanode {datanode:?}");
+
+            let request = RegionRequest {
+                header: Some(RegionRequestHeader {
+                    tracing_context: TracingContext::from_current_span().to_w3c(),
+                    ..Default::default()
+                }),
+                body: Some(region_request::Body::Drop(PbDropRegionRequest {
+                    region_id: region_id.as_u64(),
+                })),
+            };
+            let requester = self.context.datanode_manager.datanode(&datanode).await;
+
+            drop_region_tasks.push(async move {
+                if let Err(err) = requester.handle(request).await {
+                    if err.status_code() != StatusCode::RegionNotFound {
+                        return Err(handle_operate_region_error(datanode)(err));
+                    }
+                }
+                Ok(())
+            });
+        }
+
+        join_all(drop_region_tasks)
+            .await
+            .into_iter()
+            .collect::<Result<Vec<_>>>()?;
+
+        info!("Rebuilt table {}", self.data.table_ref());
+
+        Ok(Status::Done)
+    }
+
+    async fn get_table_route_value(&self) -> Result<DeserializedValueWithBytes<TableRouteValue>> {
+        let table_id = self.data.table_id();
+        self.context
+            .table_metadata_manager
+            .table_route_manager()
+            .get(table_id)
+            .await?
+            .context(error::TableRouteNotFoundSnafu { table_id })
+    }
+
+    fn source_region_info(&self) -> RegionInfo {
+        let table_info = self.data.table_info();
+        let region_options: HashMap<String, String> = (&table_info.meta.options).into();
+        RegionInfo {
+            engine: table_info.meta.engine.clone(),
+            region_storage_path: region_storage_path(
+                &table_info.catalog_name,
+                &table_info.schema_name,
+            ),
+            region_options,
+            region_wal_options: self.data.region_wal_options.clone(),
+        }
+    }
+}
+
+#[async_trait]
+impl Procedure for RebuildTableProcedure {
+    fn type_name(&self) -> &str {
+        Self::TYPE_NAME
+    }
+
+    async fn execute(&mut self, _ctx: &ProcedureContext) -> ProcedureResult<Status> {
+        let state = &self.data.state;
+
+        let _timer = metrics::METRIC_META_PROCEDURE_REBUILD_TABLE
+            .with_label_values(&[state.as_ref()])
+            .start_timer();
+
+        match state {
+            RebuildTableState::Prepare => self.on_prepare().await,
+            RebuildTableState::CreateShadowRegions => self.on_create_shadow_regions().await,
+            RebuildTableState::EnableDoubleWrite => self.on_enable_double_write().await,
+            RebuildTableState::Backfill => self.on_backfill().await,
+            RebuildTableState::SwapRegions => self.on_swap_regions().await,
+            RebuildTableState::InvalidateTableCache => self.on_broadcast().await,
+            RebuildTableState::DropSourceRegions => self.on_drop_source_regions().await,
+        }
+        .map_err(handle_retry_error)
+    }
+
+    fn dump(&self) -> ProcedureResult<String> {
+        serde_json::to_string(&self.data).context(ToJsonSnafu)
+    }
+
+    fn lock_key(&self) -> LockKey {
+        let table_ref = self.data.table_ref();
+        let key = common_catalog::format_full_table_name(
+            table_ref.catalog,
+            table_ref.schema,
+            table_ref.table,
+        );
+
+        LockKey::single(key)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, AsRefStr)]
+enum RebuildTableState {
+    /// Prepares to rebuild the table.
+    Prepare,
+    /// Creates shadow regions on datanodes.
+    CreateShadowRegions,
+    /// Updates the table route to write to shadow regions.
+    EnableDoubleWrite,
+    /// Copies data from source regions to shadow regions.
+    Backfill,
+    /// Replaces source regions with shadow regions in metadata.
+    SwapRegions,
+    /// Broadcasts the invalidating table cache instruction.
+    InvalidateTableCache,
+    /// Drops source regions on datanodes.
+    DropSourceRegions,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RebuildTableData {
+    state: RebuildTableState,
+    cluster_id: u64,
+    task: RebuildTableTask,
+    /// Table info value before rebuilding.
+    table_info_value: DeserializedValueWithBytes<TableInfoValue>,
+    /// Table route value before rebuilding.
+    table_route_value: DeserializedValueWithBytes<TableRouteValue>,
+    /// Region number of the shadow region of each source region.
+    shadow_regions: BTreeMap<RegionNumber, RegionNumber>,
+    /// Source regions that have been backfilled.
+    backfilled_regions: Vec<RegionNumber>,
+    /// Wal options of source regions.
+    /// Key: region number (in string representation). Value: the encoded wal options of the region.
+    region_wal_options: HashMap<String, String>,
+}
+
+impl RebuildTableData {
+    pub fn new(
+        cluster_id: u64,
+        task: RebuildTableTask,
+        table_info_value: DeserializedValueWithBytes<TableInfoValue>,
+        table_route_value: DeserializedValueWithBytes<TableRouteValue>,
+    ) -> Self {
+        Self {
+            state: RebuildTableState::Prepare,
+            cluster_id,
+            task,
+            table_info_value,
+            table_route_value,
+            shadow_regions: BTreeMap::new(),
+            backfilled_regions: Vec::new(),
+            region_wal_options: HashMap::new(),
+        }
+    }
+
+    fn table_ref(&self) -> TableReference {
+        self.task.table_ref()
+    }
+
+    fn table_id(&self) -> TableId {
+        self.table_info().ident.table_id
+    }
+
+    fn table_info(&self) -> &RawTableInfo {
+        &self.table_info_value.table_info
+    }
+
+    /// Returns routes of source regions.
+    fn region_routes(&self) -> &Vec<RegionRoute> {
+        self.table_route_value.region_routes()
+    }
+
+    fn shadow_region_id(&self, source_region_id: RegionId) -> RegionId {
+        RegionId::new(
+            source_region_id.table_id(),
+            self.shadow_regions[&source_region_id.region_number()],
+        )
+    }
+
+    fn time_index_name(&self) -> Result<&str> {
+        let schema = &self.table_info().meta.schema;
+        schema
+            .timestamp_index
+            .map(|i| schema.column_schemas[i].name.as_str())
+            .with_context(|| error::UnexpectedSnafu {
+                err_msg: format!("Time index of table {} not found", self.table_ref()),
+            })
+    }
+
+    /// Returns indices of the new primary key columns in the table schema.
+    fn primary_key_indices(&self) -> Result<Vec<usize>> {
+        let table_info = self.table_info();
+        let schema = &table_info.meta.schema;
+        let invalid = |reason: String| {
+            error::InvalidPrimaryKeySnafu {
+                table_name: self.table_ref().to_string(),
+                reason,
+            }
+            .fail()
+        };
+
+        let mut indices = Vec::with_capacity(self.task.primary_keys.len());
+        let mut names = HashSet::with_capacity(self.task.primary_keys.len());
+        for name in &self.task.primary_keys {
+            if !names.insert(name) {
+                return invalid(format!("duplicate column {name}"));
+            }
+            let Some(index) = schema.column_schemas.iter().position(|c| &c.name == name) else {
+                return invalid(format!("column {name} not found"));
+            };
+            if schema.timestamp_index == Some(index) {
+                return invalid(format!("column {name} is the time index"));
+            }
+            indices.push(index);
+        }
+        if indices == table_info.meta.primary_key_indices {
+            return invalid("the primary key is not changed".to_string());
+        }
+
+        Ok(indices)
+    }
+
+    /// Builds the table info with the new primary key and shadow regions.
+    fn build_new_table_info(&self) -> Result<RawTableInfo> {
+        let mut new_info = self.table_info().clone();
+        let primary_key_indices = self.primary_key_indices()?;
+        let time_index = new_info.meta.schema.timestamp_index;
+        new_info.meta.value_indices = (0..new_info.meta.schema.column_schemas.len())
+            .filter(|i| !primary_key_indices.contains(i) && time_index != Some(*i))
+            .collect();
+        new_info.meta.primary_key_indices = primary_key_indices;
+        new_info.meta.region_numbers = self.shadow_regions.values().copied().collect();
+        new_info.ident.version += 1;
+
+        Ok(new_info)
+    }
+}
+
+/// Converts the `column_schema` of the table to a column definition of the region.
+fn to_column_def(column_schema: &ColumnSchema, semantic_type: SemanticType) -> Result<ColumnDef> {
+    let (datatype, datatype_extension) =
+        ColumnDataTypeWrapper::try_from(column_schema.data_type.clone())
+            .context(error::ConvertColumnTypeSnafu {
+                column_name: &column_schema.name,
+            })?
+            .to_parts();
+    let default_constraint = match column_schema.default_constraint() {
+        None => vec![],
+        Some(v) => v
+            .clone()
+            .try_into()
+            .context(error::ConvertColumnDefaultConstraintSnafu {
+                column_name: &column_schema.name,
+            })?,
+    };
+
+    Ok(ColumnDef {
+        name: column_schema.name.clone(),
+        data_type: datatype as i32,
+        is_nullable: column_schema.is_nullable(),
+        default_constraint,
+        semantic_type: semantic_type as i32,
+        comment: String::new(),
+        datatype_extension,
+    })
+}
+
+/// Builds the schema of rows to write to the shadow region from the scanned `schema`.
+fn build_row_schema(
+    schema: &Schema,
+    time_index: &str,
+    primary_keys: &[String],
+) -> Result<Vec<PbColumnSchema>> {
+    schema
+        .column_schemas()
+        .iter()
+        .map(|column_schema| {
+            let (datatype, datatype_extension) =
+                ColumnDataTypeWrapper::try_from(column_schema.data_type.clone())
+                    .context(error::ConvertColumnTypeSnafu {
+                        column_name: &column_schema.name,
+                    })?
+                    .to_parts();
+            let semantic_type = if column_schema.name == time_index {
+                SemanticType::Timestamp
+            } else if primary_keys.contains(&column_schema.name) {
+                SemanticType::Tag
+            } else {
+                SemanticType::Field
+            };
+
+            Ok(PbColumnSchema {
+                column_name: column_schema.name.clone(),
+                datatype: datatype as i32,
+                semantic_type: semantic_type as i32,
+                datatype_extension,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use datatypes::prelude::ConcreteDataType;
+    use datatypes::schema::SchemaBuilder;
+
+    use super::*;
+    use crate::key::test_utils::new_test_table_info;
+    use crate::rpc::router::Region;
+
+    fn new_test_data(primary_keys: &[&str]) -> RebuildTableData {
+        let table_info: RawTableInfo = new_test_table_info(10, [1, 2].into_iter()).into();
+        let region_routes = table_info
+            .meta
+            .region_numbers
+            .iter()
+            .map(|n| RegionRoute {
+                region: Region::new_test(RegionId::new(10, *n)),
+                ..Default::default()
+            })
+            .collect();
+        let task = RebuildTableTask {
+            catalog: table_info.catalog_name.clone(),
+            schema: table_info.schema_name.clone(),
+            table: table_info.name.clone(),
+            table_id: 10,
+            primary_keys: primary_keys.iter().map(|k| k.to_string()).collect(),
+            scan_plan: vec![],
+        };
+        let mut data = RebuildTableData::new(
+            0,
+            task,
+            DeserializedValueWithBytes::from_inner(TableInfoValue::new(table_info)),
+            DeserializedValueWithBytes::from_inner(TableRouteValue::physical(region_routes)),
+        );
+        data.shadow_regions = [(1, 3), (2, 4)].into_iter().collect();
+        data
+    }
+
+    #[test]
+    fn test_primary_key_indices() {
+        let data = new_test_data(&["col2", "col1"]);
+        assert_eq!(vec![2, 0], data.primary_key_indices().unwrap());
+
+        for primary_keys in [
+            &["col1", "col1"][..],
+            &["unknown"][..],
+            &["ts"][..],
+            &["col1"][..],
+        ] {
+            let err = new_test_data(primary_keys)
+                .primary_key_indices()
+                .unwrap_err();
+            assert_eq!(StatusCode::InvalidArguments, err.status_code());
+        }
+    }
+
+    #[test]
+    fn test_build_new_table_info() {
+        let data = new_test_data(&["col2", "col1"]);
+        let new_info = data.build_new_table_info().unwrap();
+        let old_info = data.table_info();
+        assert_eq!(vec![2, 0], new_info.meta.primary_key_indices);
+        assert!(new_info.meta.value_indices.is_empty());
+        assert_eq!(vec![3, 4], new_info.meta.region_numbers);
+        assert_eq!(old_info.ident.version + 1, new_info.ident.version);
+        assert_eq!(
+            RegionId::new(10, 4),
+            data.shadow_region_id(RegionId::new(10, 2))
+        );
+    }
+
+    #[test]
+    fn test_build_row_schema() {
+        let schema = SchemaBuilder::try_from(vec![
+            ColumnSchema::new("a", ConcreteDataType::string_datatype(), true),
+            ColumnSchema::new("b", ConcreteDataType::float64_datatype(), true),
+            ColumnSchema::new(
+                "ts",
+                ConcreteDataType::timestamp_millisecond_datatype(),
+                false,
+            )
+            .with_time_index(true),
+        ])
+        .unwrap()
+        .build()
+        .unwrap();
+        let schema = Arc::new(schema);
+
+        let row_schema = build_row_schema(&schema, "ts", &["b".to_string()]).unwrap();
+        let semantic_types: Vec<_> = row_schema.iter().map(|c| c.semantic_type).collect();
+        assert_eq!(
+            vec![
+                SemanticType::Field as i32,
+                SemanticType::Tag as i32,
+                SemanticType::Timestamp as i32
+            ],
+            semantic_types
+        );
+    }
+}
@@ -0,0 +1,320 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use common_catalog::consts::METRIC_ENGINE;
+use common_telemetry::{debug, info};
+use snafu::ensure;
+use store_api::storage::{RegionId, RegionNumber, TableId, MAX_REGION_SEQ};
+
+use crate::ddl::{TableMetadata, TableMetadataAllocator, TableMetadataAllocatorContext};
+use crate::error::{Result, TooManyPartitionsSnafu, UnsupportedSnafu};
+use crate::key::table_route::{LogicalTableRouteValue, PhysicalTableRouteValue, TableRouteValue};
+use crate::peer::Peer;
+use crate::rpc::ddl::CreateTableTask;
+use crate::rpc::router::{Region, RegionRoute};
+use crate::sequence::SequenceRef;
+use crate::wal::{allocate_region_wal_options, WalOptionsAllocatorRef};
+
+/// Allocates peers of regions of new tables.
+#[async_trait]
+pub trait PeerAllocator: Send + Sync {
+    /// Allocates `regions` peers, the peer of the region `i` is the `i`-th peer.
+    async fn alloc(&self, ctx: &TableMetadataAllocatorContext, regions: usize)
+        -> Result<Vec<Peer>>;
+}
+
+pub type PeerAllocatorRef = Arc<dyn PeerAllocator>;
+
+/// [`PeerAllocator`] of the standalone mode, whose regions are all served by the
+/// embedded datanode, so peers are only placeholders.
+pub struct NoopPeerAllocator;
+
+#[async_trait]
+impl PeerAllocator for NoopPeerAllocator {
+    async fn alloc(
+        &self,
+        _ctx: &TableMetadataAllocatorContext,
+        regions: usize,
+    ) -> Result<Vec<Peer>> {
+        Ok(vec![Peer::default(); regions])
+    }
+}
+
+/// [`TableMetadataAllocator`] of both the standalone and the cluster mode, so tables
+/// are created with the same semantics in both modes. The modes only differ in how
+/// peers of regions are allocated.
+pub struct DefaultTableMetadataAllocator {
+    table_id_sequence: SequenceRef,
+    wal_options_allocator: WalOptionsAllocatorRef,
+    peer_allocator: PeerAllocatorRef,
+}
+
+impl DefaultTableMetadataAllocator {
+    pub fn new(
+        table_id_sequence: SequenceRef,
+        wal_options_allocator: WalOptionsAllocatorRef,
+    ) -> Self {
+        Self {
+            table_id_sequence,
+            wal_options_allocator,
+            peer_allocator: Arc::new(NoopPeerAllocator),
+        }
+    }
+
+    pub fn with_peer_allocator(self, peer_allocator: PeerAllocatorRef) -> Self {
+        Self {
+            peer_allocator,
+            ..self
+        }
+    }
+
+    async fn allocate_table_id(&self, task: &CreateTableTask) -> Result<TableId> {
+        let table_id = if let Some(table_id) = &task.create_table.table_id {
+            let table_id = table_id.id;
+
+            ensure!(
+                !self
+                    .table_id_sequence
+                    .min_max()
+                    .await
+                    .contains(&(table_id as u64)),
+                UnsupportedSnafu {
+                    operation: format!(
+                        "create table by id {} that is reserved in this node",
+                        table_id
+                    )
+                }
+            );
+
+            info!(
+                "Received explicitly allocated table id {}, will use it directly.",
+                table_id
+            );
+
+            table_id
+        } else {
+            self.table_id_sequence.next().await? as TableId
+        };
+        Ok(table_id)
+    }
+
+    async fn create_table_route(
+        &self,
+        ctx: &TableMetadataAllocatorContext,
+        table_id: TableId,
+        task: &CreateTableTask,
+    ) -> Result<TableRouteValue> {
+        if task.create_table.engine == METRIC_ENGINE {
+            return Ok(TableRouteValue::Logical(LogicalTableRouteValue {}));
+        }
+
+        let regions = task.partitions.len();
+        ensure!(
+            regions <= MAX_REGION_SEQ as usize,
+            TooManyPartitionsSnafu {
+                max: MAX_REGION_SEQ as usize,
+                actual: regions,
+            }
+        );
+
+        let peers = self.peer_allocator.alloc(ctx, regions).await?;
+        let region_routes = task
+            .partitions
+            .iter()
+            .zip(peers)
+            .enumerate()
+            .map(|(i, (partition, peer))| {
+                let region = Region {
+                    id: RegionId::new(table_id, i as RegionNumber),
+                    partition: Some(partition.clone().into()),
+                    ..Default::default()
+                };
+
+                RegionRoute {
+                    region,
+                    leader_peer: Some(peer),
+                    ..Default::default()
+                }
+            })
+            .collect::<Vec<_>>();
+        Ok(TableRouteValue::Physical(PhysicalTableRouteValue::new(
+            region_routes,
+        )))
+    }
+
+    fn create_wal_options(
+        &self,
+        table_route: &TableRouteValue,
+        table_options: &HashMap<String, String>,
+    ) -> Result<HashMap<RegionNumber, String>> {
+        match table_route {
+            TableRouteValue::Physical(x) => {
+                let region_numbers = x
+                    .region_routes
+                    .iter()
+                    .map(|route| route.region.id.region_number())
+                    .collect();
+                allocate_region_wal_options(
+                    region_numbers,
+                    table_options,
+                    &self.wal_options_allocator,
+                )
+            }
+            TableRouteValue::Logical(_) => Ok(HashMap::new()),
+        }
+    }
+}
+
+#[async_trait]
+impl TableMetadataAllocator for DefaultTableMetadataAllocator {
+    async fn create(
+        &self,
+        ctx: &TableMetadataAllocatorContext,
+        task: &CreateTableTask,
+    ) -> Result<TableMetadata> {
+        let table_id = self.allocate_table_id(task).await?;
+
+        let table_route = self.create_table_route(ctx, table_id, task).await?;
+
+        let region_wal_options =
+            self.create_wal_options(&table_route, &task.table_info.meta.options.extra_options)?;
+
+        debug!(
+            "Allocated region wal options {:?} for table {}",
+            region_wal_options, table_id
+        );
+
+        Ok(TableMetadata {
+            table_id,
+            table_route,
+            region_wal_options,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use api::v1::{CreateTableExpr, TableId as PbTableId};
+
+    use super::*;
+    use crate::key::test_utils::new_test_table_info;
+    use crate::kv_backend::memory::MemoryKvBackend;
+    use crate::kv_backend::KvBackendRef;
+    use crate::rpc::router::Partition;
+    use crate::sequence::SequenceBuilder;
+    use crate::wal::{WalConfig, WalOptionsAllocator};
+
+    struct MockPeerAllocator;
+
+    #[async_trait]
+    impl PeerAllocator for MockPeerAllocator {
+        async fn alloc(
+            &self,
+            _ctx: &TableMetadataAllocatorContext,
+            regions: usize,
+        ) -> Result<Vec<Peer>> {
+            Ok((0..regions as u64)
+                .map(|id| Peer::new(id, format!("datanode-{id}")))
+                .collect())
+        }
+    }
+
+    fn new_task(table_id: Option<TableId>, regions: usize) -> CreateTableTask {
+        let create_table = CreateTableExpr {
+            table_name: "test".to_string(),
+            engine: "mito".to_string(),
+            table_id: table_id.map(|id| PbTableId { id }),
+            ..Default::default()
+        };
+        let partitions = (0..regions)
+            .map(|_| Partition {
+                column_list: vec![],
+                value_list: vec![],
+            })
+            .collect();
+        let table_info = new_test_table_info(1024, 0..regions as u32).into();
+        CreateTableTask::new(create_table, partitions, table_info)
+    }
+
+    async fn new_allocator() -> DefaultTableMetadataAllocator {
+        let kv_backend = Arc::new(MemoryKvBackend::new()) as KvBackendRef;
+        let sequence = Arc::new(
+            SequenceBuilder::new("table_id", kv_backend.clone())
+                .initial(1024)
+                .step(10)
+                .build(),
+        );
+        let wal_options_allocator = WalOptionsAllocator::new(WalConfig::RaftEngine, kv_backend);
+        wal_options_allocator.start().await.unwrap();
+        DefaultTableMetadataAllocator::new(sequence, Arc::new(wal_options_allocator))
+    }
+
+    #[tokio::test]
+    async fn test_allocate_table_metadata() {
+        let ctx = TableMetadataAllocatorContext { cluster_id: 0 };
+        let allocator = new_allocator()
+            .await
+            .with_peer_allocator(Arc::new(MockPeerAllocator));
+
+        let metadata = allocator.create(&ctx, &new_task(None, 3)).await.unwrap();
+        assert_eq!(1024, metadata.table_id);
+        let TableRouteValue::Physical(route) = metadata.table_route else {
+            unreachable!()
+        };
+        let peers = route
+            .region_routes
+            .iter()
+            .map(|route| (route.region.id, route.leader_peer.as_ref().unwrap().id))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec![
+                (RegionId::new(1024, 0), 0),
+                (RegionId::new(1024, 1), 1),
+                (RegionId::new(1024, 2), 2)
+            ],
+            peers
+        );
+        assert_eq!(3, metadata.region_wal_options.len());
+
+        // Explicit table ids are used as is, unless they are reserved by the sequence.
+        let metadata = allocator
+            .create(&ctx, &new_task(Some(100), 1))
+            .await
+            .unwrap();
+        assert_eq!(100, metadata.table_id);
+        assert!(allocator
+            .create(&ctx, &new_task(Some(1025), 1))
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_noop_peer_allocator() {
+        let ctx = TableMetadataAllocatorContext { cluster_id: 0 };
+        let allocator = new_allocator().await;
+
+        let metadata = allocator.create(&ctx, &new_task(None, 2)).await.unwrap();
+        let TableRouteValue::Physical(route) = metadata.table_route else {
+            unreachable!()
+        };
+        assert!(route
+            .region_routes
+            .iter()
+            .all(|route| route.leader_peer == Some(Peer::default())));
+    }
+}
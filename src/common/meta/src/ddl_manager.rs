@@ -13,39 +13,44 @@
 // limitations under the License.
 
 use std::collections::HashMap;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 
 use common_procedure::{watcher, ProcedureId, ProcedureManagerRef, ProcedureWithId};
 use common_telemetry::tracing_context::{FutureExt, TracingContext};
 use common_telemetry::{info, tracing};
-use snafu::{OptionExt, ResultExt};
+use snafu::{ensure, OptionExt, ResultExt};
 use store_api::storage::RegionNumber;
+use tokio::sync::{Mutex as AsyncMutex, OwnedMutexGuard};
 
 use crate::cache_invalidator::CacheInvalidatorRef;
 use crate::datanode_manager::DatanodeManagerRef;
 use crate::ddl::alter_table::AlterTableProcedure;
 use crate::ddl::create_table::CreateTableProcedure;
 use crate::ddl::drop_table::DropTableProcedure;
+use crate::ddl::rebuild_table::RebuildTableProcedure;
 use crate::ddl::truncate_table::TruncateTableProcedure;
 use crate::ddl::{
     DdlContext, DdlTaskExecutor, ExecutorContext, TableMetadata, TableMetadataAllocatorContext,
     TableMetadataAllocatorRef,
 };
 use crate::error::{
-    self, RegisterProcedureLoaderSnafu, Result, SubmitProcedureSnafu, TableNotFoundSnafu,
-    WaitProcedureSnafu,
+    self, ParseProcedureIdSnafu, QueryProcedureSnafu, RegisterProcedureLoaderSnafu, Result,
+    SubmitProcedureSnafu, TableAlreadyExistsSnafu, TableNotFoundSnafu, WaitProcedureSnafu,
 };
 use crate::key::table_info::TableInfoValue;
 use crate::key::table_name::TableNameKey;
 use crate::key::table_route::TableRouteValue;
 use crate::key::{DeserializedValueWithBytes, TableMetadataManagerRef};
 use crate::region_keeper::MemoryRegionKeeperRef;
-use crate::rpc::ddl::DdlTask::{AlterTable, CreateTable, DropTable, TruncateTable};
+use crate::rpc::ddl::DdlTask::{AlterTable, CreateTable, DropTable, RebuildTable, TruncateTable};
 use crate::rpc::ddl::{
-    AlterTableTask, CreateTableTask, DropTableTask, SubmitDdlTaskRequest, SubmitDdlTaskResponse,
-    TruncateTableTask,
+    AlterTableTask, CreateTableTask, DropTableTask, RebuildTableTask, SubmitDdlTaskRequest,
+    SubmitDdlTaskResponse, TruncateTableTask,
 };
+use crate::rpc::procedure::ProcedureStateResponse;
 use crate::rpc::router::RegionRoute;
+use crate::table_name::TableName;
+
 pub type DdlManagerRef = Arc<DdlManager>;
 
 /// The [DdlManager] provides the ability to execute Ddl.
@@ -56,6 +61,7 @@ pub struct DdlManager {
     table_metadata_manager: TableMetadataManagerRef,
     table_metadata_allocator: TableMetadataAllocatorRef,
     memory_region_keeper: MemoryRegionKeeperRef,
+    creating_tables: CreatingTables,
 }
 
 impl DdlManager {
@@ -75,6 +81,7 @@ impl DdlManager {
             table_metadata_manager,
             table_metadata_allocator,
             memory_region_keeper,
+            creating_tables: CreatingTables::default(),
         };
         manager.register_loaders()?;
         Ok(manager)
@@ -150,6 +157,20 @@ impl DdlManager {
             )
             .context(RegisterProcedureLoaderSnafu {
                 type_name: TruncateTableProcedure::TYPE_NAME,
+            })?;
+
+        let context = self.create_context();
+
+        self.procedure_manager
+            .register_loader(
+                RebuildTableProcedure::TYPE_NAME,
+                Box::new(move |json| {
+                    let context = context.clone();
+                    RebuildTableProcedure::from_json(json, context).map(|p| Box::new(p) as _)
+                }),
+            )
+            .context(RegisterProcedureLoaderSnafu {
+                type_name: RebuildTableProcedure::TYPE_NAME,
             })
     }
 
@@ -242,6 +263,29 @@ impl DdlManager {
         self.submit_procedure(procedure_with_id).await
     }
 
+    #[tracing::instrument(skip_all)]
+    /// Submits and executes a rebuild table task.
+    pub async fn submit_rebuild_table_task(
+        &self,
+        cluster_id: u64,
+        rebuild_table_task: RebuildTableTask,
+        table_info_value: DeserializedValueWithBytes<TableInfoValue>,
+        table_route_value: DeserializedValueWithBytes<TableRouteValue>,
+    ) -> Result<ProcedureId> {
+        let context = self.create_context();
+        let procedure = RebuildTableProcedure::new(
+            cluster_id,
+            rebuild_table_task,
+            table_info_value,
+            table_route_value,
+            context,
+        );
+
+        let procedure_with_id = ProcedureWithId::with_random_id(Box::new(procedure));
+
+        self.submit_procedure(procedure_with_id).await
+    }
+
     async fn submit_procedure(&self, procedure_with_id: ProcedureWithId) -> Result<ProcedureId> {
         let procedure_id = procedure_with_id.id;
 
@@ -253,7 +297,7 @@ impl DdlManager {
 
         watcher::wait(&mut watcher)
             .await
-            .context(WaitProcedureSnafu)?;
+            .context(WaitProcedureSnafu { procedure_id })?;
 
         Ok(procedure_id)
     }
@@ -297,6 +341,42 @@ async fn handle_truncate_table_task(
     })
 }
 
+async fn handle_rebuild_table_task(
+    ddl_manager: &DdlManager,
+    cluster_id: u64,
+    rebuild_table_task: RebuildTableTask,
+) -> Result<SubmitDdlTaskResponse> {
+    let table_id = rebuild_table_task.table_id;
+    let table_metadata_manager = &ddl_manager.table_metadata_manager();
+    let table_ref = rebuild_table_task.table_ref();
+
+    let (table_info_value, table_route_value) =
+        table_metadata_manager.get_full_table_info(table_id).await?;
+
+    let table_info_value = table_info_value.with_context(|| error::TableInfoNotFoundSnafu {
+        table_name: table_ref.to_string(),
+    })?;
+
+    let table_route_value =
+        table_route_value.context(error::TableRouteNotFoundSnafu { table_id })?;
+
+    let id = ddl_manager
+        .submit_rebuild_table_task(
+            cluster_id,
+            rebuild_table_task,
+            table_info_value,
+            table_route_value,
+        )
+        .await?;
+
+    info!("Table: {table_id} is rebuilt via procedure_id {id:?}");
+
+    Ok(SubmitDdlTaskResponse {
+        key: id.to_string().into(),
+        ..Default::default()
+    })
+}
+
 async fn handle_alter_table_task(
     ddl_manager: &DdlManager,
     cluster_id: u64,
@@ -380,6 +460,37 @@ async fn handle_create_table_task(
     cluster_id: u64,
     mut create_table_task: CreateTableTask,
 ) -> Result<SubmitDdlTaskResponse> {
+    // Tasks creating the same table wait for the task in flight, then find the table created
+    // by it without allocating table ids or submitting procedures.
+    let _guard = ddl_manager
+        .creating_tables
+        .lock(create_table_task.table_name())
+        .await;
+
+    let table_ref = create_table_task.table_ref();
+    let existing = ddl_manager
+        .table_metadata_manager
+        .table_name_manager()
+        .get(TableNameKey::new(
+            table_ref.catalog,
+            table_ref.schema,
+            table_ref.table,
+        ))
+        .await?;
+    if let Some(existing) = existing {
+        ensure!(
+            create_table_task.create_table.create_if_not_exists,
+            TableAlreadyExistsSnafu {
+                table_name: table_ref.to_string(),
+            }
+        );
+
+        return Ok(SubmitDdlTaskResponse {
+            table_id: Some(existing.table_id()),
+            ..Default::default()
+        });
+    }
+
     let table_meta = ddl_manager
         .table_metadata_allocator
         .create(
@@ -413,6 +524,59 @@ async fn handle_create_table_task(
     })
 }
 
+/// Tracks tables being created, so create table tasks of the same table run one by one.
+#[derive(Default)]
+struct CreatingTables {
+    inner: Arc<Mutex<HashMap<TableName, Arc<AsyncMutex<()>>>>>,
+}
+
+impl CreatingTables {
+    /// Waits until no other task is creating the table, and returns a guard that blocks other
+    /// tasks creating the table until it's dropped.
+    async fn lock(&self, table_name: TableName) -> CreatingTableGuard {
+        let lock = self
+            .inner
+            .lock()
+            .unwrap()
+            .entry(table_name.clone())
+            .or_default()
+            .clone();
+        let guard = lock.lock_owned().await;
+
+        CreatingTableGuard {
+            table_name,
+            inner: self.inner.clone(),
+            _guard: guard,
+        }
+    }
+
+    #[cfg(test)]
+    fn len(&self) -> usize {
+        self.inner.lock().unwrap().len()
+    }
+}
+
+struct CreatingTableGuard {
+    table_name: TableName,
+    inner: Arc<Mutex<HashMap<TableName, Arc<AsyncMutex<()>>>>>,
+    _guard: OwnedMutexGuard<()>,
+}
+
+impl Drop for CreatingTableGuard {
+    fn drop(&mut self) {
+        let mut inner = self.inner.lock().unwrap();
+        // Removes the lock if it's only referenced by the map and this guard, i.e. no other
+        // task is waiting for it.
+        if inner
+            .get(&self.table_name)
+            .map(|lock| Arc::strong_count(lock) <= 2)
+            .unwrap_or(false)
+        {
+            let _ = inner.remove(&self.table_name);
+        }
+    }
+}
+
 #[async_trait::async_trait]
 impl DdlTaskExecutor for DdlManager {
     async fn submit_ddl_task(
@@ -442,34 +606,60 @@ impl DdlTaskExecutor for DdlManager {
                 TruncateTable(truncate_table_task) => {
                     handle_truncate_table_task(self, cluster_id, truncate_table_task).await
                 }
+                RebuildTable(rebuild_table_task) => {
+                    handle_rebuild_table_task(self, cluster_id, rebuild_table_task).await
+                }
             }
         }
         .trace(span)
         .await
     }
+
+    async fn query_procedure_state(
+        &self,
+        _ctx: &ExecutorContext,
+        procedure_id: &str,
+    ) -> Result<Option<ProcedureStateResponse>> {
+        let pid =
+            ProcedureId::parse_str(procedure_id).context(ParseProcedureIdSnafu { procedure_id })?;
+        let state = self
+            .procedure_manager
+            .procedure_state(pid)
+            .await
+            .context(QueryProcedureSnafu)?;
+
+        Ok(state.as_ref().map(ProcedureStateResponse::from))
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use std::sync::Arc;
+    use std::time::Duration;
 
     use common_procedure::local::LocalManager;
+    use common_procedure::ProcedureId;
 
-    use super::DdlManager;
+    use super::{CreatingTables, DdlManager};
     use crate::cache_invalidator::DummyCacheInvalidator;
     use crate::datanode_manager::{DatanodeManager, DatanodeRef};
     use crate::ddl::alter_table::AlterTableProcedure;
     use crate::ddl::create_table::CreateTableProcedure;
     use crate::ddl::drop_table::DropTableProcedure;
+    use crate::ddl::rebuild_table::RebuildTableProcedure;
     use crate::ddl::truncate_table::TruncateTableProcedure;
-    use crate::ddl::{TableMetadata, TableMetadataAllocator, TableMetadataAllocatorContext};
-    use crate::error::Result;
+    use crate::ddl::{
+        DdlTaskExecutor, ExecutorContext, TableMetadata, TableMetadataAllocator,
+        TableMetadataAllocatorContext,
+    };
+    use crate::error::{self, Result};
     use crate::key::TableMetadataManager;
     use crate::kv_backend::memory::MemoryKvBackend;
     use crate::peer::Peer;
     use crate::region_keeper::MemoryRegionKeeper;
     use crate::rpc::ddl::CreateTableTask;
     use crate::state_store::KvStateStore;
+    use crate::table_name::TableName;
 
     /// A dummy implemented [DatanodeManager].
     pub struct DummyDatanodeManager;
@@ -517,10 +707,70 @@ mod tests {
             AlterTableProcedure::TYPE_NAME,
             DropTableProcedure::TYPE_NAME,
             TruncateTableProcedure::TYPE_NAME,
+            RebuildTableProcedure::TYPE_NAME,
         ];
 
         for loader in expected_loaders {
             assert!(procedure_manager.contains_loader(loader));
         }
     }
+
+    #[tokio::test]
+    async fn test_query_procedure_state() {
+        let kv_backend = Arc::new(MemoryKvBackend::new());
+        let table_metadata_manager = Arc::new(TableMetadataManager::new(kv_backend.clone()));
+        let state_store = Arc::new(KvStateStore::new(kv_backend));
+        let procedure_manager = Arc::new(LocalManager::new(Default::default(), state_store));
+
+        let ddl_manager = DdlManager::try_new(
+            procedure_manager,
+            Arc::new(DummyDatanodeManager),
+            Arc::new(DummyCacheInvalidator),
+            table_metadata_manager,
+            Arc::new(DummyTableMetadataAllocator),
+            Arc::new(MemoryRegionKeeper::default()),
+        )
+        .unwrap();
+
+        let ctx = ExecutorContext::default();
+        let err = ddl_manager
+            .query_procedure_state(&ctx, "not-a-procedure-id")
+            .await
+            .unwrap_err();
+        assert!(matches!(err, error::Error::ParseProcedureId { .. }));
+
+        let state = ddl_manager
+            .query_procedure_state(&ctx, &ProcedureId::random().to_string())
+            .await
+            .unwrap();
+        assert!(state.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_creating_tables() {
+        let creating_tables = Arc::new(CreatingTables::default());
+        let table_name = TableName::new("greptime", "public", "foo");
+
+        let guard = creating_tables.lock(table_name.clone()).await;
+        // Other tables are not blocked.
+        let other = creating_tables
+            .lock(TableName::new("greptime", "public", "bar"))
+            .await;
+        drop(other);
+        assert_eq!(1, creating_tables.len());
+
+        let waiter = {
+            let creating_tables = creating_tables.clone();
+            let table_name = table_name.clone();
+            tokio::spawn(async move {
+                let _guard = creating_tables.lock(table_name).await;
+            })
+        };
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(!waiter.is_finished());
+
+        drop(guard);
+        waiter.await.unwrap();
+        assert_eq!(0, creating_tables.len());
+    }
 }
@@ -18,6 +18,7 @@ use common_config::wal::WalOptions;
 use common_error::ext::{BoxedError, ErrorExt};
 use common_error::status_code::StatusCode;
 use common_macro::stack_trace_debug;
+use common_procedure::ProcedureId;
 use serde_json::error::Error as JsonError;
 use snafu::{Location, Snafu};
 use store_api::storage::{RegionId, RegionNumber};
@@ -106,8 +107,30 @@ pub enum Error {
         location: Location,
     },
 
-    #[snafu(display("Failed to wait procedure done"))]
+    #[snafu(display("Too many partitions, max: {}, actual: {}", max, actual))]
+    TooManyPartitions {
+        max: usize,
+        actual: usize,
+        location: Location,
+    },
+
+    #[snafu(display("Failed to wait procedure done, procedure_id: {}", procedure_id))]
     WaitProcedure {
+        procedure_id: ProcedureId,
+        location: Location,
+        source: common_procedure::Error,
+    },
+
+    #[snafu(display("Invalid procedure id: {}", procedure_id))]
+    ParseProcedureId {
+        procedure_id: String,
+        location: Location,
+        #[snafu(source)]
+        error: common_procedure::ParseIdError,
+    },
+
+    #[snafu(display("Failed to query procedure state"))]
+    QueryProcedure {
         location: Location,
         source: common_procedure::Error,
     },
@@ -210,6 +233,12 @@ pub enum Error {
         location: Location,
     },
 
+    #[snafu(display("Pipeline already exists, pipeline: {}", pipeline))]
+    PipelineAlreadyExists {
+        pipeline: String,
+        location: Location,
+    },
+
     #[snafu(display("Catalog already exists, catalog: {}", catalog))]
     CatalogAlreadyExists { catalog: String, location: Location },
 
@@ -297,6 +326,13 @@ pub enum Error {
         location: Location,
     },
 
+    #[snafu(display("Invalid wal provider {} of the table: {}", provider, reason))]
+    InvalidWalProvider {
+        provider: String,
+        reason: String,
+        location: Location,
+    },
+
     #[snafu(display("Invalid number of topics {}", num_topics))]
     InvalidNumTopics {
         num_topics: usize,
@@ -321,6 +357,24 @@ pub enum Error {
         error: rskafka::client::error::Error,
     },
 
+    #[snafu(display("Failed to list Kafka topics"))]
+    ListKafkaTopics {
+        location: Location,
+        #[snafu(source)]
+        error: rskafka::client::error::Error,
+    },
+
+    #[snafu(display(
+        "Kafka wal topics are not created as configured, missing topics: {:?}, misconfigured topics: {:?}",
+        missing,
+        misconfigured
+    ))]
+    InvalidKafkaWalTopics {
+        missing: Vec<String>,
+        misconfigured: Vec<String>,
+        location: Location,
+    },
+
     #[snafu(display("Failed to create a Kafka wal topic"))]
     CreateKafkaWalTopic {
         location: Location,
@@ -330,6 +384,56 @@ pub enum Error {
 
     #[snafu(display("The topic pool is empty"))]
     EmptyTopicPool { location: Location },
+
+    #[snafu(display("Failed to read Kafka TLS file: {}", path))]
+    ReadKafkaTlsFile {
+        path: String,
+        location: Location,
+        #[snafu(source)]
+        error: std::io::Error,
+    },
+
+    #[snafu(display("Invalid Kafka TLS config, reason: {}", reason))]
+    InvalidKafkaTlsConfig { reason: String, location: Location },
+
+    #[snafu(display("Failed to build Kafka TLS config"))]
+    BuildKafkaTlsConfig {
+        location: Location,
+        #[snafu(source)]
+        error: rustls::Error,
+    },
+
+    #[snafu(display(
+        "Invalid primary key to rebuild table {}, reason: {}",
+        table_name,
+        reason
+    ))]
+    InvalidPrimaryKey {
+        table_name: String,
+        reason: String,
+        location: Location,
+    },
+
+    #[snafu(display("Failed to convert the data type of column {}", column_name))]
+    ConvertColumnType {
+        column_name: String,
+        location: Location,
+        source: api::error::Error,
+    },
+
+    #[snafu(display("Failed to convert the default constraint of column {}", column_name))]
+    ConvertColumnDefaultConstraint {
+        column_name: String,
+        location: Location,
+        source: datatypes::error::Error,
+    },
+
+    #[snafu(display("Failed to read record batch from region {}", region_id))]
+    ReadRecordBatch {
+        region_id: RegionId,
+        location: Location,
+        source: common_recordbatch::error::Error,
+    },
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
@@ -368,8 +472,10 @@ impl ErrorExt for Error {
             | EncodeWalOptions { .. }
             | BuildKafkaClient { .. }
             | BuildKafkaCtrlClient { .. }
+            | ListKafkaTopics { .. }
             | CreateKafkaWalTopic { .. }
-            | EmptyTopicPool { .. } => StatusCode::Unexpected,
+            | EmptyTopicPool { .. }
+            | BuildKafkaTlsConfig { .. } => StatusCode::Unexpected,
 
             SendMessage { .. }
             | GetKvCache { .. }
@@ -379,14 +485,20 @@ impl ErrorExt for Error {
             | RenameTable { .. }
             | Unsupported { .. } => StatusCode::Internal,
 
-            PrimaryKeyNotFound { .. } | EmptyKey { .. } | InvalidEngineType { .. } => {
-                StatusCode::InvalidArguments
-            }
+            PrimaryKeyNotFound { .. }
+            | ParseProcedureId { .. }
+            | EmptyKey { .. }
+            | InvalidEngineType { .. }
+            | InvalidPrimaryKey { .. }
+            | TooManyPartitions { .. } => StatusCode::InvalidArguments,
 
             TableNotFound { .. } => StatusCode::TableNotFound,
             TableAlreadyExists { .. } => StatusCode::TableAlreadyExists,
+            PipelineAlreadyExists { .. } => StatusCode::InvalidArguments,
 
-            SubmitProcedure { source, .. } | WaitProcedure { source, .. } => source.status_code(),
+            SubmitProcedure { source, .. }
+            | WaitProcedure { source, .. }
+            | QueryProcedure { source, .. } => source.status_code(),
             RegisterProcedureLoader { source, .. } => source.status_code(),
             External { source, .. } => source.status_code(),
             OperateDatanode { source, .. } => source.status_code(),
@@ -394,8 +506,15 @@ impl ErrorExt for Error {
             RetryLater { source, .. } => source.status_code(),
             InvalidCatalogValue { source, .. } => source.status_code(),
             ConvertAlterTableRequest { source, .. } => source.status_code(),
-
-            InvalidNumTopics { .. } => StatusCode::InvalidArguments,
+            ConvertColumnType { source, .. } => source.status_code(),
+            ConvertColumnDefaultConstraint { source, .. } => source.status_code(),
+            ReadRecordBatch { source, .. } => source.status_code(),
+
+            InvalidNumTopics { .. }
+            | InvalidWalProvider { .. }
+            | InvalidKafkaWalTopics { .. }
+            | ReadKafkaTlsFile { .. }
+            | InvalidKafkaTlsConfig { .. } => StatusCode::InvalidArguments,
         }
     }
 
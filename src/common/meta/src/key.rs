@@ -45,6 +45,7 @@
 
 pub mod catalog_name;
 pub mod datanode_table;
+pub mod pipeline;
 pub mod schema_name;
 pub mod table_info;
 pub mod table_name;
@@ -69,6 +70,7 @@ use common_catalog::consts::{
 use common_telemetry::warn;
 use datanode_table::{DatanodeTableKey, DatanodeTableManager, DatanodeTableValue};
 use lazy_static::lazy_static;
+use pipeline::PipelineValue;
 use regex::Regex;
 use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
@@ -101,6 +103,8 @@ pub const TABLE_NAME_KEY_PREFIX: &str = "__table_name";
 pub const CATALOG_NAME_KEY_PREFIX: &str = "__catalog_name";
 pub const SCHEMA_NAME_KEY_PREFIX: &str = "__schema_name";
 pub const TABLE_ROUTE_PREFIX: &str = "__table_route";
+pub const PIPELINE_KEY_PREFIX: &str = "__pipeline";
+pub const PIPELINE_OFFSET_KEY_PREFIX: &str = "__pipeline_offset";
 
 pub const CACHE_KEY_PREFIXES: [&str; 4] = [
     TABLE_NAME_KEY_PREFIX,
@@ -139,6 +143,14 @@ lazy_static! {
     .unwrap();
 }
 
+lazy_static! {
+    /// PIPELINE_KEY: {PIPELINE_KEY_PREFIX}/{catalog_name}/{schema_name}/{pipeline_name}
+    static ref PIPELINE_KEY_PATTERN: Regex = Regex::new(&format!(
+        "^{PIPELINE_KEY_PREFIX}/({NAME_PATTERN})/({NAME_PATTERN})/({NAME_PATTERN})$"
+    ))
+    .unwrap();
+}
+
 pub fn to_removed_key(key: &str) -> String {
     format!("{REMOVED_PREFIX}-{key}")
 }
@@ -646,6 +658,77 @@ impl TableMetadataManager {
         Ok(())
     }
 
+    /// Updates the table info and the table route in one transaction.
+    ///
+    /// It's used to swap the regions of a table atomically, e.g. after rebuilding the table.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn update_table_info_and_route(
+        &self,
+        current_table_info_value: &DeserializedValueWithBytes<TableInfoValue>,
+        new_table_info: RawTableInfo,
+        region_info: RegionInfo,
+        current_table_route_value: &DeserializedValueWithBytes<TableRouteValue>,
+        new_region_routes: Vec<RegionRoute>,
+        new_region_options: &HashMap<String, String>,
+        new_region_wal_options: &HashMap<String, String>,
+    ) -> Result<()> {
+        let table_id = current_table_info_value.table_info.ident.table_id;
+
+        // Updates table info.
+        let new_table_info_value = current_table_info_value.update(new_table_info);
+        let (update_table_info_txn, on_update_table_info_failure) = self
+            .table_info_manager()
+            .build_update_txn(table_id, current_table_info_value, &new_table_info_value)?;
+
+        // Updates the datanode table key value pairs.
+        let current_region_distribution =
+            region_distribution(current_table_route_value.region_routes())?;
+        let new_region_distribution = region_distribution(&new_region_routes)?;
+        let update_datanode_table_txn = self.datanode_table_manager().build_update_txn(
+            table_id,
+            region_info,
+            current_region_distribution,
+            new_region_distribution,
+            new_region_options,
+            new_region_wal_options,
+        )?;
+
+        // Updates the table_route.
+        let new_table_route_value = current_table_route_value.update(new_region_routes);
+        let (update_table_route_txn, on_update_table_route_failure) = self
+            .table_route_manager()
+            .build_update_txn(table_id, current_table_route_value, &new_table_route_value)?;
+
+        let txn = Txn::merge_all(vec![
+            update_table_info_txn,
+            update_datanode_table_txn,
+            update_table_route_txn,
+        ]);
+
+        let r = self.kv_backend.txn(txn).await?;
+
+        // Checks whether metadata was already updated.
+        if !r.succeeded {
+            let remote_table_info = on_update_table_info_failure(&r.responses)?
+                .context(error::UnexpectedSnafu {
+                    err_msg: "Reads the empty table info during the updating table info",
+                })?
+                .into_inner();
+            let op_name = "the updating table info";
+            ensure_values!(remote_table_info, new_table_info_value, op_name);
+
+            let remote_table_route = on_update_table_route_failure(&r.responses)?
+                .context(error::UnexpectedSnafu {
+                    err_msg: "Reads the empty table route during the updating table route",
+                })?
+                .into_inner();
+            let op_name = "the updating table route";
+            ensure_values!(remote_table_route, new_table_route_value, op_name);
+        }
+
+        Ok(())
+    }
+
     /// Updates the leader status of the [RegionRoute].
     pub async fn update_leader_region_status<F>(
         &self,
@@ -749,7 +832,8 @@ macro_rules! impl_optional_meta_value {
 impl_table_meta_value! {
     TableNameValue,
     TableInfoValue,
-    DatanodeTableValue
+    DatanodeTableValue,
+    PipelineValue
 }
 
 impl_optional_meta_value! {
@@ -1319,4 +1403,91 @@ mod tests {
             .await
             .is_err());
     }
+
+    #[tokio::test]
+    async fn test_update_table_info_and_route() {
+        let mem_kv = Arc::new(MemoryKvBackend::default());
+        let table_metadata_manager = TableMetadataManager::new(mem_kv);
+        let region_routes = vec![new_region_route(1, 1), new_region_route(2, 2)];
+        let table_info: RawTableInfo =
+            new_test_table_info(region_routes.iter().map(|r| r.region.id.region_number())).into();
+        let table_id = table_info.ident.table_id;
+        let region_info = RegionInfo {
+            engine: table_info.meta.engine.clone(),
+            region_storage_path: region_storage_path(
+                &table_info.catalog_name,
+                &table_info.schema_name,
+            ),
+            region_options: HashMap::new(),
+            region_wal_options: HashMap::new(),
+        };
+        create_physical_table_metadata(
+            &table_metadata_manager,
+            table_info.clone(),
+            region_routes.clone(),
+        )
+        .await
+        .unwrap();
+
+        let current_table_info_value =
+            DeserializedValueWithBytes::from_inner(TableInfoValue::new(table_info.clone()));
+        let current_table_route_value = DeserializedValueWithBytes::from_inner(
+            TableRouteValue::physical(region_routes.clone()),
+        );
+        let mut new_table_info = table_info.clone();
+        new_table_info.meta.region_numbers = vec![3, 4];
+        let new_region_routes = vec![new_region_route(3, 1), new_region_route(4, 2)];
+        for _ in 0..2 {
+            // Updating the same metadata again should be ok.
+            table_metadata_manager
+                .update_table_info_and_route(
+                    &current_table_info_value,
+                    new_table_info.clone(),
+                    region_info.clone(),
+                    &current_table_route_value,
+                    new_region_routes.clone(),
+                    &HashMap::new(),
+                    &HashMap::new(),
+                )
+                .await
+                .unwrap();
+        }
+
+        let updated_table_info = table_metadata_manager
+            .table_info_manager()
+            .get(table_id)
+            .await
+            .unwrap()
+            .unwrap()
+            .into_inner();
+        assert_eq!(updated_table_info.table_info, new_table_info);
+        let updated_route = table_metadata_manager
+            .table_route_manager()
+            .get(table_id)
+            .await
+            .unwrap()
+            .unwrap()
+            .into_inner();
+        assert_eq!(updated_route.region_routes(), &new_region_routes);
+        assert_datanode_table(&table_metadata_manager, table_id, &new_region_routes).await;
+
+        // The table info is changed by others.
+        let mut wrong_table_info = table_info.clone();
+        wrong_table_info.name = "wrong".to_string();
+        let wrong_table_info_value = DeserializedValueWithBytes::from_inner(
+            current_table_info_value.update(wrong_table_info),
+        );
+        assert!(table_metadata_manager
+            .update_table_info_and_route(
+                &wrong_table_info_value,
+                new_table_info,
+                region_info,
+                &current_table_route_value,
+                new_region_routes,
+                &HashMap::new(),
+                &HashMap::new(),
+            )
+            .await
+            .is_err());
+    }
 }
@@ -74,6 +74,11 @@ impl DatanodeTableKey {
         format!("{}/", Self::prefix(datanode_id))
     }
 
+    /// Returns the prefix of keys of all datanodes.
+    pub fn all_range_start_key() -> String {
+        format!("{}/", DATANODE_TABLE_KEY_PREFIX)
+    }
+
     pub fn strip_table_id(raw_key: &[u8]) -> Result<TableId> {
         let key = String::from_utf8(raw_key.to_vec()).map_err(|e| {
             InvalidTableMetadataSnafu {
@@ -163,6 +168,20 @@ impl DatanodeTableManager {
         Box::pin(stream.map(|kv| kv.map(|kv| kv.1)))
     }
 
+    /// Returns a stream of tables on all datanodes.
+    pub fn all_tables(&self) -> BoxStream<'static, Result<DatanodeTableValue>> {
+        let req = RangeRequest::new().with_prefix(DatanodeTableKey::all_range_start_key());
+
+        let stream = PaginationStream::new(
+            self.kv_backend.clone(),
+            req,
+            DEFAULT_PAGE_SIZE,
+            Arc::new(datanode_table_value_decoder),
+        );
+
+        Box::pin(stream.map(|kv| kv.map(|kv| kv.1)))
+    }
+
     /// Builds the create datanode table transactions. It only executes while the primary keys comparing successes.
     pub fn build_create_txn(
         &self,
@@ -0,0 +1,295 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Metadata of ingestion pipelines.
+//!
+//! A pipeline is stored under `__pipeline/{catalog}/{schema}/{pipeline}`, and the offsets it
+//! has consumed are stored under `__pipeline_offset/{catalog}/{schema}/{pipeline}/{partition}`.
+
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::sync::Arc;
+
+use futures::stream::BoxStream;
+use serde::{Deserialize, Serialize};
+use snafu::{ensure, OptionExt, ResultExt};
+
+use crate::error::{
+    self, Error, InvalidTableMetadataSnafu, PipelineAlreadyExistsSnafu, Result, SerdeJsonSnafu,
+};
+use crate::key::{
+    TableMetaKey, TableMetaValue, PIPELINE_KEY_PATTERN, PIPELINE_KEY_PREFIX,
+    PIPELINE_OFFSET_KEY_PREFIX,
+};
+use crate::kv_backend::KvBackendRef;
+use crate::range_stream::{PaginationStream, DEFAULT_PAGE_SIZE};
+use crate::rpc::store::{DeleteRangeRequest, PutRequest, RangeRequest};
+use crate::rpc::KeyValue;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PipelineKey {
+    pub catalog: String,
+    pub schema: String,
+    pub pipeline: String,
+}
+
+impl PipelineKey {
+    pub fn new(catalog: &str, schema: &str, pipeline: &str) -> Self {
+        Self {
+            catalog: catalog.to_string(),
+            schema: schema.to_string(),
+            pipeline: pipeline.to_string(),
+        }
+    }
+
+    /// Returns the prefix of keys of all pipelines.
+    pub fn range_start_key() -> String {
+        format!("{}/", PIPELINE_KEY_PREFIX)
+    }
+
+    /// Returns the prefix of offset keys of the pipeline.
+    fn offset_prefix(&self) -> String {
+        format!(
+            "{}/{}/{}/{}/",
+            PIPELINE_OFFSET_KEY_PREFIX, self.catalog, self.schema, self.pipeline
+        )
+    }
+
+    fn offset_key(&self, partition: i32) -> String {
+        format!("{}{}", self.offset_prefix(), partition)
+    }
+}
+
+impl Display for PipelineKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}/{}/{}/{}",
+            PIPELINE_KEY_PREFIX, self.catalog, self.schema, self.pipeline
+        )
+    }
+}
+
+impl TableMetaKey for PipelineKey {
+    fn as_raw_key(&self) -> Vec<u8> {
+        self.to_string().into_bytes()
+    }
+}
+
+impl TryFrom<&str> for PipelineKey {
+    type Error = Error;
+
+    fn try_from(s: &str) -> Result<Self> {
+        let captures = PIPELINE_KEY_PATTERN
+            .captures(s)
+            .context(InvalidTableMetadataSnafu {
+                err_msg: format!("Illegal PipelineKey format: '{s}'"),
+            })?;
+
+        // Safety: pass the regex check above
+        Ok(Self::new(
+            captures.get(1).unwrap().as_str(),
+            captures.get(2).unwrap().as_str(),
+            captures.get(3).unwrap().as_str(),
+        ))
+    }
+}
+
+/// Definition of a pipeline.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PipelineValue {
+    /// Type of the source, e.g. `kafka`.
+    pub source_type: String,
+    /// Options of the source.
+    pub source_options: HashMap<String, String>,
+    /// Name of the table to write, in the same schema as the pipeline.
+    pub sink_table: Option<String>,
+    /// Options of the transform applied to messages.
+    pub options: HashMap<String, String>,
+}
+
+/// Decodes `KeyValue` to (PipelineKey, PipelineValue).
+pub fn pipeline_decoder(kv: KeyValue) -> Result<(PipelineKey, PipelineValue)> {
+    let str = std::str::from_utf8(&kv.key).context(error::ConvertRawKeySnafu)?;
+    let key = PipelineKey::try_from(str)?;
+    let value = PipelineValue::try_from_raw_value(&kv.value)?;
+
+    Ok((key, value))
+}
+
+pub type PipelineManagerRef = Arc<PipelineManager>;
+
+/// Manages definitions and consumed offsets of pipelines.
+pub struct PipelineManager {
+    kv_backend: KvBackendRef,
+}
+
+impl PipelineManager {
+    pub fn new(kv_backend: KvBackendRef) -> Self {
+        Self { kv_backend }
+    }
+
+    /// Creates the pipeline. Returns false if the pipeline exists and `if_not_exists` is true.
+    pub async fn create(
+        &self,
+        key: &PipelineKey,
+        value: &PipelineValue,
+        if_not_exists: bool,
+    ) -> Result<bool> {
+        let created = self
+            .kv_backend
+            .put_conditionally(key.as_raw_key(), value.try_as_raw_value()?, true)
+            .await?;
+        ensure!(
+            created || if_not_exists,
+            PipelineAlreadyExistsSnafu {
+                pipeline: key.to_string(),
+            }
+        );
+
+        Ok(created)
+    }
+
+    pub async fn get(&self, key: &PipelineKey) -> Result<Option<PipelineValue>> {
+        self.kv_backend
+            .get(&key.as_raw_key())
+            .await?
+            .map(|kv| PipelineValue::try_from_raw_value(&kv.value))
+            .transpose()
+    }
+
+    /// Deletes the pipeline and its offsets. Returns false if the pipeline doesn't exist.
+    pub async fn delete(&self, key: &PipelineKey) -> Result<bool> {
+        let deleted = self
+            .kv_backend
+            .delete(&key.as_raw_key(), true)
+            .await?
+            .is_some();
+        let req = DeleteRangeRequest::new().with_prefix(key.offset_prefix().into_bytes());
+        let _ = self.kv_backend.delete_range(req).await?;
+
+        Ok(deleted)
+    }
+
+    /// Returns a stream of all pipelines.
+    pub fn pipelines(&self) -> BoxStream<'static, Result<(PipelineKey, PipelineValue)>> {
+        let req = RangeRequest::new().with_prefix(PipelineKey::range_start_key().into_bytes());
+        let stream = PaginationStream::new(
+            self.kv_backend.clone(),
+            req,
+            DEFAULT_PAGE_SIZE,
+            Arc::new(pipeline_decoder),
+        );
+
+        Box::pin(stream)
+    }
+
+    /// Returns the next offsets to consume of each partition of the pipeline.
+    pub async fn offsets(&self, key: &PipelineKey) -> Result<HashMap<i32, i64>> {
+        let prefix = key.offset_prefix();
+        let req = RangeRequest::new().with_prefix(prefix.as_bytes());
+        let resp = self.kv_backend.range(req).await?;
+
+        resp.kvs
+            .into_iter()
+            .map(|kv| {
+                let partition = std::str::from_utf8(&kv.key)
+                    .ok()
+                    .and_then(|k| k.strip_prefix(&prefix))
+                    .and_then(|p| p.parse().ok())
+                    .context(InvalidTableMetadataSnafu {
+                        err_msg: format!(
+                            "Illegal pipeline offset key: '{}'",
+                            String::from_utf8_lossy(&kv.key)
+                        ),
+                    })?;
+                let offset = serde_json::from_slice(&kv.value).context(SerdeJsonSnafu)?;
+                Ok((partition, offset))
+            })
+            .collect()
+    }
+
+    /// Saves the next offset to consume of the `partition`.
+    pub async fn save_offset(&self, key: &PipelineKey, partition: i32, offset: i64) -> Result<()> {
+        let req = PutRequest::new()
+            .with_key(key.offset_key(partition))
+            .with_value(serde_json::to_vec(&offset).context(SerdeJsonSnafu)?);
+        let _ = self.kv_backend.put(req).await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::TryStreamExt;
+
+    use super::*;
+    use crate::kv_backend::memory::MemoryKvBackend;
+
+    fn new_value(topic: &str) -> PipelineValue {
+        PipelineValue {
+            source_type: "kafka".to_string(),
+            source_options: HashMap::from([("topic".to_string(), topic.to_string())]),
+            sink_table: Some("events".to_string()),
+            options: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn test_serialization() {
+        let key = PipelineKey::new("my-catalog", "my-schema", "my-pipeline");
+        assert_eq!(
+            "__pipeline/my-catalog/my-schema/my-pipeline",
+            key.to_string()
+        );
+        let parsed = PipelineKey::try_from("__pipeline/my-catalog/my-schema/my-pipeline").unwrap();
+        assert_eq!(key, parsed);
+        assert!(PipelineKey::try_from("__pipeline/my-catalog/my-schema").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_pipeline_manager() {
+        let manager = PipelineManager::new(Arc::new(MemoryKvBackend::default()));
+        let key = PipelineKey::new("greptime", "public", "p1");
+        assert!(manager.create(&key, &new_value("t1"), false).await.unwrap());
+        assert!(!manager.create(&key, &new_value("t2"), true).await.unwrap());
+        assert!(manager.create(&key, &new_value("t2"), false).await.is_err());
+        assert_eq!(Some(new_value("t1")), manager.get(&key).await.unwrap());
+
+        let other = PipelineKey::new("greptime", "public", "p2");
+        assert!(manager
+            .create(&other, &new_value("t2"), false)
+            .await
+            .unwrap());
+        let pipelines = manager.pipelines().try_collect::<Vec<_>>().await.unwrap();
+        assert_eq!(
+            vec![(key.clone(), new_value("t1")), (other, new_value("t2"))],
+            pipelines
+        );
+
+        manager.save_offset(&key, 0, 10).await.unwrap();
+        manager.save_offset(&key, 1, 20).await.unwrap();
+        manager.save_offset(&key, 0, 11).await.unwrap();
+        assert_eq!(
+            HashMap::from([(0, 11), (1, 20)]),
+            manager.offsets(&key).await.unwrap()
+        );
+
+        assert!(manager.delete(&key).await.unwrap());
+        assert!(!manager.delete(&key).await.unwrap());
+        assert!(manager.get(&key).await.unwrap().is_none());
+        assert!(manager.offsets(&key).await.unwrap().is_empty());
+    }
+}
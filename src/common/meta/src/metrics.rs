@@ -50,4 +50,10 @@ lazy_static! {
         &["step"]
     )
     .unwrap();
+    pub static ref METRIC_META_PROCEDURE_REBUILD_TABLE: HistogramVec = register_histogram_vec!(
+        "meta_procedure_rebuild_table",
+        "meta procedure rebuild table",
+        &["step"]
+    )
+    .unwrap();
 }
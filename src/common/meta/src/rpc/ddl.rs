@@ -38,6 +38,7 @@ pub enum DdlTask {
     DropTable(DropTableTask),
     AlterTable(AlterTableTask),
     TruncateTable(TruncateTableTask),
+    RebuildTable(RebuildTableTask),
 }
 
 impl DdlTask {
@@ -82,6 +83,24 @@ impl DdlTask {
             table_id,
         })
     }
+
+    pub fn new_rebuild_table(
+        catalog: String,
+        schema: String,
+        table: String,
+        table_id: TableId,
+        primary_keys: Vec<String>,
+        scan_plan: Vec<u8>,
+    ) -> Self {
+        DdlTask::RebuildTable(RebuildTableTask {
+            catalog,
+            schema,
+            table,
+            table_id,
+            primary_keys,
+            scan_plan,
+        })
+    }
 }
 
 impl TryFrom<Task> for DdlTask {
@@ -135,6 +154,13 @@ impl TryFrom<SubmitDdlTaskRequest> for PbSubmitDdlTaskRequest {
                     table_id: Some(api::v1::TableId { id: task.table_id }),
                 }),
             }),
+            // TODO(agent): Submits the task to the metasrv once the protocol supports it.
+            DdlTask::RebuildTable(_) => {
+                return error::UnsupportedSnafu {
+                    operation: "submitting rebuild table task to metasrv",
+                }
+                .fail();
+            }
         };
 
         Ok(Self {
@@ -438,6 +464,37 @@ impl TryFrom<PbTruncateTableTask> for TruncateTableTask {
     }
 }
 
+/// A task to rebuild a table with a new primary key.
+#[derive(Debug, PartialEq, Serialize, Deserialize, Clone)]
+pub struct RebuildTableTask {
+    pub catalog: String,
+    pub schema: String,
+    pub table: String,
+    pub table_id: TableId,
+    /// Names of the new primary key columns.
+    pub primary_keys: Vec<String>,
+    /// The substrait plan to scan all columns of a region.
+    pub scan_plan: Vec<u8>,
+}
+
+impl RebuildTableTask {
+    pub fn table_ref(&self) -> TableReference {
+        TableReference {
+            catalog: &self.catalog,
+            schema: &self.schema,
+            table: &self.table,
+        }
+    }
+
+    pub fn table_name(&self) -> TableName {
+        TableName {
+            catalog_name: self.catalog.to_string(),
+            schema_name: self.schema.to_string(),
+            table_name: self.table.to_string(),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::sync::Arc;
@@ -0,0 +1,72 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use common_procedure::ProcedureState;
+use serde::Serialize;
+
+/// Status of a procedure.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum ProcedureStatus {
+    Running,
+    Done,
+    Retrying,
+    Failed,
+}
+
+/// State of a procedure queried by its id.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ProcedureStateResponse {
+    pub status: ProcedureStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl From<&ProcedureState> for ProcedureStateResponse {
+    fn from(state: &ProcedureState) -> Self {
+        let (status, error) = match state {
+            ProcedureState::Running => (ProcedureStatus::Running, None),
+            ProcedureState::Done => (ProcedureStatus::Done, None),
+            ProcedureState::Retrying { error } => {
+                (ProcedureStatus::Retrying, Some(error.to_string()))
+            }
+            ProcedureState::Failed { error } => (ProcedureStatus::Failed, Some(error.to_string())),
+        };
+        Self { status, error }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use common_error::mock::MockError;
+    use common_error::status_code::StatusCode;
+    use common_procedure::Error;
+
+    use super::*;
+
+    #[test]
+    fn test_procedure_state_response() {
+        let response = ProcedureStateResponse::from(&ProcedureState::Done);
+        assert_eq!(
+            r#"{"status":"Done"}"#,
+            serde_json::to_string(&response).unwrap()
+        );
+
+        let error = Arc::new(Error::external(MockError::new(StatusCode::Unexpected)));
+        let response = ProcedureStateResponse::from(&ProcedureState::failed(error));
+        assert_eq!(ProcedureStatus::Failed, response.status);
+        assert!(response.error.is_some());
+    }
+}
@@ -315,6 +315,15 @@ impl RegionRoutes {
     pub fn find_region_leader(&self, region_number: RegionNumber) -> Option<&Peer> {
         self.region_leader_map().get(&region_number).copied()
     }
+
+    /// Returns follower peers of the region.
+    pub fn find_region_followers(&self, region_number: RegionNumber) -> &[Peer] {
+        self.0
+            .iter()
+            .find(|route| route.region.id.region_number() == region_number)
+            .map(|route| route.follower_peers.as_slice())
+            .unwrap_or_default()
+    }
 }
 
 #[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq)]
@@ -325,6 +334,13 @@ pub struct Region {
     pub attrs: BTreeMap<String, String>,
 }
 
+/// Attribute key of the shadow region that also receives writes to the region.
+pub const SHADOW_REGION_ATTR: &str = "shadow_region";
+/// Attribute key of the primary key of the shadow region.
+pub const SHADOW_PRIMARY_KEY_ATTR: &str = "shadow_primary_key";
+/// Attribute key that marks the region doesn't accept writes from frontends.
+pub const WRITE_FENCED_ATTR: &str = "write_fenced";
+
 impl Region {
     #[cfg(any(test, feature = "testing"))]
     pub fn new_test(id: RegionId) -> Self {
@@ -333,6 +349,49 @@ impl Region {
             ..Default::default()
         }
     }
+
+    /// Returns the shadow region and its primary key if writes to this region
+    /// should also be written to a shadow region.
+    pub fn shadow_region(&self) -> Option<(RegionId, Vec<String>)> {
+        let region_id = self.attrs.get(SHADOW_REGION_ATTR)?.parse::<u64>().ok()?;
+        let primary_keys = serde_json::from_str(self.attrs.get(SHADOW_PRIMARY_KEY_ATTR)?).ok()?;
+
+        Some((RegionId::from_u64(region_id), primary_keys))
+    }
+
+    /// Sets the shadow region of this region.
+    pub fn set_shadow_region(&mut self, region_id: RegionId, primary_keys: &[String]) {
+        let _ = self.attrs.insert(
+            SHADOW_REGION_ATTR.to_string(),
+            region_id.as_u64().to_string(),
+        );
+        let _ = self.attrs.insert(
+            SHADOW_PRIMARY_KEY_ATTR.to_string(),
+            serde_json::to_string(primary_keys).unwrap(),
+        );
+    }
+
+    /// Removes the shadow region of this region.
+    pub fn clear_shadow_region(&mut self) {
+        let _ = self.attrs.remove(SHADOW_REGION_ATTR);
+        let _ = self.attrs.remove(SHADOW_PRIMARY_KEY_ATTR);
+    }
+
+    /// Returns true if frontends should reject writes to this region.
+    pub fn is_write_fenced(&self) -> bool {
+        self.attrs.contains_key(WRITE_FENCED_ATTR)
+    }
+
+    /// Sets whether frontends should reject writes to this region.
+    pub fn set_write_fenced(&mut self, fenced: bool) {
+        if fenced {
+            let _ = self
+                .attrs
+                .insert(WRITE_FENCED_ATTR.to_string(), "true".to_string());
+        } else {
+            let _ = self.attrs.remove(WRITE_FENCED_ATTR);
+        }
+    }
 }
 
 impl From<PbRegion> for Region {
@@ -428,6 +487,36 @@ impl From<PbPartition> for Partition {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_shadow_region() {
+        let mut region = Region::new_test(RegionId::new(1, 1));
+        assert!(region.shadow_region().is_none());
+
+        let primary_keys = vec!["b".to_string(), "a".to_string()];
+        region.set_shadow_region(RegionId::new(1, 3), &primary_keys);
+        assert_eq!(
+            Some((RegionId::new(1, 3), primary_keys)),
+            region.shadow_region()
+        );
+
+        region.clear_shadow_region();
+        assert!(region.shadow_region().is_none());
+        assert!(region.attrs.is_empty());
+    }
+
+    #[test]
+    fn test_write_fenced() {
+        let mut region = Region::new_test(RegionId::new(1, 1));
+        assert!(!region.is_write_fenced());
+
+        region.set_write_fenced(true);
+        assert!(region.is_write_fenced());
+
+        region.set_write_fenced(false);
+        assert!(!region.is_write_fenced());
+        assert!(region.attrs.is_empty());
+    }
+
     #[test]
     fn test_leader_is_downgraded() {
         let mut region_route = RegionRoute {
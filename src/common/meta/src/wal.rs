@@ -82,6 +82,7 @@ mod tests {
         let expected_kafka_config = KafkaConfig {
             broker_endpoints: vec!["127.0.0.1:9090".to_string()],
             num_topics: 32,
+            auto_create_topics: true,
             selector_type: KafkaTopicSelectorType::RoundRobin,
             topic_name_prefix: "greptimedb_wal_topic".to_string(),
             num_partitions: 1,
@@ -91,6 +92,8 @@ mod tests {
             backoff_max: Duration::from_secs(10),
             backoff_base: 2,
             backoff_deadline: Some(Duration::from_secs(60 * 5)),
+            sasl: None,
+            tls: None,
         };
         assert_eq!(wal_config, WalConfig::Kafka(expected_kafka_config));
     }
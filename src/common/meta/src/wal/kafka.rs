@@ -16,21 +16,34 @@ pub mod topic;
 pub mod topic_manager;
 pub mod topic_selector;
 
+use std::io::Cursor;
+use std::sync::Arc;
 use std::time::Duration;
 
+use common_config::wal::{KafkaClientSasl, KafkaClientTls};
+use rskafka::client::ClientBuilder;
+use rustls::{ClientConfig, RootCertStore};
 use serde::{Deserialize, Serialize};
+use snafu::{OptionExt, ResultExt};
 
+use crate::error::{
+    BuildKafkaTlsConfigSnafu, InvalidKafkaTlsConfigSnafu, ReadKafkaTlsFileSnafu, Result,
+};
 pub use crate::wal::kafka::topic::Topic;
 pub use crate::wal::kafka::topic_manager::TopicManager;
 use crate::wal::kafka::topic_selector::SelectorType as TopicSelectorType;
 
 /// Configurations for kafka wal.
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct KafkaConfig {
     /// The broker endpoints of the Kafka cluster.
     pub broker_endpoints: Vec<String>,
     /// Number of topics to be created upon start.
     pub num_topics: usize,
+    /// Whether to create missing topics upon start. If false, topics must be created in advance
+    /// and are only validated.
+    pub auto_create_topics: bool,
     /// The type of the topic selector with which to select a topic for a region.
     pub selector_type: TopicSelectorType,
     /// Topic name prefix.
@@ -56,6 +69,10 @@ pub struct KafkaConfig {
     /// If it's None, the reconnecting won't terminate.
     #[serde(with = "humantime_serde")]
     pub backoff_deadline: Option<Duration>,
+    /// The SASL authentication of kafka clients. Disabled if it's None.
+    pub sasl: Option<KafkaClientSasl>,
+    /// The TLS configs of kafka clients. Disabled if it's None.
+    pub tls: Option<KafkaClientTls>,
 }
 
 impl Default for KafkaConfig {
@@ -63,6 +80,7 @@ impl Default for KafkaConfig {
         Self {
             broker_endpoints: vec!["127.0.0.1:9090".to_string()],
             num_topics: 64,
+            auto_create_topics: true,
             selector_type: TopicSelectorType::RoundRobin,
             topic_name_prefix: "greptimedb_wal_topic".to_string(),
             num_partitions: 1,
@@ -72,6 +90,71 @@ impl Default for KafkaConfig {
             backoff_max: Duration::from_secs(10),
             backoff_base: 2,
             backoff_deadline: Some(Duration::from_secs(60 * 5)), // 5 mins
+            sasl: None,
+            tls: None,
         }
     }
 }
+
+/// Applies the `sasl` and `tls` configs to the kafka client `builder`.
+pub async fn with_client_auth(
+    mut builder: ClientBuilder,
+    sasl: Option<&KafkaClientSasl>,
+    tls: Option<&KafkaClientTls>,
+) -> Result<ClientBuilder> {
+    if let Some(sasl) = sasl {
+        builder = builder.sasl_config(sasl.to_sasl_config());
+    }
+    if let Some(tls) = tls {
+        builder = builder.tls_config(Arc::new(build_tls_config(tls).await?));
+    }
+    Ok(builder)
+}
+
+/// Builds the rustls client config from the `tls` configs.
+async fn build_tls_config(tls: &KafkaClientTls) -> Result<ClientConfig> {
+    let mut roots = RootCertStore::empty();
+    if let Some(path) = &tls.server_ca_cert_path {
+        for cert in read_certs(path).await? {
+            roots.add(cert).context(BuildKafkaTlsConfigSnafu)?;
+        }
+    }
+    // Native certs that fail to load are ignored.
+    let native_certs = rustls_native_certs::load_native_certs().unwrap_or_default();
+    let _ = roots.add_parsable_certificates(native_certs);
+    let builder = ClientConfig::builder().with_root_certificates(roots);
+
+    match (&tls.client_cert_path, &tls.client_key_path) {
+        (Some(cert_path), Some(key_path)) => {
+            let certs = read_certs(cert_path).await?;
+            let key_bytes = read_file(key_path).await?;
+            let key = rustls_pemfile::private_key(&mut Cursor::new(key_bytes))
+                .context(ReadKafkaTlsFileSnafu { path: key_path })?
+                .context(InvalidKafkaTlsConfigSnafu {
+                    reason: format!("no private key found in {}", key_path),
+                })?;
+            builder
+                .with_client_auth_cert(certs, key)
+                .context(BuildKafkaTlsConfigSnafu)
+        }
+        (None, None) => Ok(builder.with_no_client_auth()),
+        _ => InvalidKafkaTlsConfigSnafu {
+            reason: "client_cert_path and client_key_path must be set together",
+        }
+        .fail(),
+    }
+}
+
+async fn read_file(path: &str) -> Result<Vec<u8>> {
+    tokio::fs::read(path)
+        .await
+        .context(ReadKafkaTlsFileSnafu { path })
+}
+
+/// Reads certificates in PEM format from the file at `path`.
+async fn read_certs(path: &str) -> Result<Vec<rustls::pki_types::CertificateDer<'static>>> {
+    let bytes = read_file(path).await?;
+    rustls_pemfile::certs(&mut Cursor::new(bytes))
+        .collect::<std::result::Result<Vec<_>, _>>()
+        .context(ReadKafkaTlsFileSnafu { path })
+}
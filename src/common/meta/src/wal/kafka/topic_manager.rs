@@ -12,24 +12,30 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use std::time::Duration;
 
+use common_config::WalOptions;
 use common_telemetry::debug;
-use rskafka::client::ClientBuilder;
+use futures::TryStreamExt;
+use rskafka::client::{Client, ClientBuilder};
 use rskafka::BackoffConfig;
 use snafu::{ensure, ResultExt};
 
 use crate::error::{
     BuildKafkaClientSnafu, BuildKafkaCtrlClientSnafu, CreateKafkaWalTopicSnafu, DecodeJsonSnafu,
-    EncodeJsonSnafu, InvalidNumTopicsSnafu, Result,
+    EncodeJsonSnafu, InvalidKafkaWalTopicsSnafu, InvalidNumTopicsSnafu, ListKafkaTopicsSnafu,
+    Result,
 };
+use crate::key::datanode_table::DatanodeTableManager;
 use crate::kv_backend::KvBackendRef;
 use crate::rpc::store::PutRequest;
 use crate::wal::kafka::topic::Topic;
-use crate::wal::kafka::topic_selector::{RoundRobinTopicSelector, SelectorType, TopicSelectorRef};
-use crate::wal::kafka::KafkaConfig;
+use crate::wal::kafka::topic_selector::{
+    LeastRegionsTopicSelector, RoundRobinTopicSelector, SelectorType, TopicSelectorRef,
+};
+use crate::wal::kafka::{with_client_auth, KafkaConfig};
 
 const CREATED_TOPICS_KEY: &str = "__created_wal_topics/kafka/";
 
@@ -50,14 +56,15 @@ impl TopicManager {
             .map(|topic_id| format!("{}_{topic_id}", config.topic_name_prefix))
             .collect::<Vec<_>>();
 
-        let selector = match config.selector_type {
-            SelectorType::RoundRobin => RoundRobinTopicSelector::with_shuffle(),
+        let selector: TopicSelectorRef = match config.selector_type {
+            SelectorType::RoundRobin => Arc::new(RoundRobinTopicSelector::with_shuffle()),
+            SelectorType::LeastRegions => Arc::new(LeastRegionsTopicSelector::default()),
         };
 
         Self {
             config,
             topic_pool: topics,
-            topic_selector: Arc::new(selector),
+            topic_selector: selector,
             kv_backend,
         }
     }
@@ -65,6 +72,7 @@ impl TopicManager {
     /// Tries to initialize the topic manager.
     /// The initializer first tries to restore persisted topics from the kv backend.
     /// If not enough topics retrieved, the initializer will try to contact the Kafka cluster and request creating more topics.
+    /// If topic creation is disabled, the initializer only validates that all topics are created as configured.
     pub async fn start(&self) -> Result<()> {
         let num_topics = self.config.num_topics;
         ensure!(num_topics > 0, InvalidNumTopicsSnafu { num_topics });
@@ -72,6 +80,19 @@ impl TopicManager {
         // Topics should be created.
         let topics = &self.topic_pool;
 
+        if self.topic_selector.requires_region_counts() {
+            let region_counts = Self::count_regions(&self.kv_backend).await?;
+            debug!("Restored region counts of {} topics", region_counts.len());
+            self.topic_selector.set_region_counts(region_counts);
+        }
+
+        if !self.config.auto_create_topics {
+            self.validate_topics(topics).await?;
+            Self::persist_created_topics(topics, &self.kv_backend).await?;
+            debug!("Validated and persisted {} topics", topics.len());
+            return Ok(());
+        }
+
         // Topics already created.
         // There may have extra topics created but it's okay since those topics won't break topic allocation.
         let created_topics = Self::restore_created_topics(&self.kv_backend)
@@ -99,22 +120,47 @@ impl TopicManager {
         Ok(())
     }
 
-    /// Tries to create topics specified by indexes in `to_be_created`.
-    async fn try_create_topics(&self, topics: &[Topic], to_be_created: &[usize]) -> Result<()> {
-        // Builds an kafka controller client for creating topics.
+    /// Builds a kafka client with the configured backoff and authentication.
+    async fn build_client(&self) -> Result<Client> {
         let backoff_config = BackoffConfig {
             init_backoff: self.config.backoff_init,
             max_backoff: self.config.backoff_max,
             base: self.config.backoff_base as f64,
             deadline: self.config.backoff_deadline,
         };
-        let client = ClientBuilder::new(self.config.broker_endpoints.clone())
-            .backoff_config(backoff_config)
+        let builder =
+            ClientBuilder::new(self.config.broker_endpoints.clone()).backoff_config(backoff_config);
+        with_client_auth(builder, self.config.sasl.as_ref(), self.config.tls.as_ref())
+            .await?
             .build()
             .await
             .with_context(|_| BuildKafkaClientSnafu {
                 broker_endpoints: self.config.broker_endpoints.clone(),
-            })?
+            })
+    }
+
+    /// Validates that all `topics` exist in the Kafka cluster with the configured number of partitions.
+    // TODO(agent): also validate the replication factor and the retention once the kafka client exposes topic configs.
+    async fn validate_topics(&self, topics: &[Topic]) -> Result<()> {
+        let existing_topics = self
+            .build_client()
+            .await?
+            .list_topics()
+            .await
+            .context(ListKafkaTopicsSnafu)?
+            .into_iter()
+            .map(|topic| (topic.name, topic.partitions.len()))
+            .collect::<HashMap<_, _>>();
+
+        check_topics(topics, &existing_topics, self.config.num_partitions)
+    }
+
+    /// Tries to create topics specified by indexes in `to_be_created`.
+    async fn try_create_topics(&self, topics: &[Topic], to_be_created: &[usize]) -> Result<()> {
+        // Builds an kafka controller client for creating topics.
+        let client = self
+            .build_client()
+            .await?
             .controller_client()
             .context(BuildKafkaCtrlClientSnafu)?;
 
@@ -149,6 +195,26 @@ impl TopicManager {
             .collect()
     }
 
+    /// Counts regions assigned to each topic by the wal options in the datanode table metadata.
+    async fn count_regions(kv_backend: &KvBackendRef) -> Result<HashMap<Topic, usize>> {
+        let tables = DatanodeTableManager::new(kv_backend.clone())
+            .all_tables()
+            .try_collect::<Vec<_>>()
+            .await?;
+
+        let mut region_counts = HashMap::new();
+        for table in tables {
+            for wal_options in table.region_info.region_wal_options.values() {
+                let wal_options: WalOptions =
+                    serde_json::from_str(wal_options).context(DecodeJsonSnafu)?;
+                if let WalOptions::Kafka(kafka_wal_options) = wal_options {
+                    *region_counts.entry(kafka_wal_options.topic).or_default() += 1;
+                }
+            }
+        }
+        Ok(region_counts)
+    }
+
     async fn restore_created_topics(kv_backend: &KvBackendRef) -> Result<Vec<Topic>> {
         kv_backend
             .get(CREATED_TOPICS_KEY.as_bytes())
@@ -172,13 +238,45 @@ impl TopicManager {
     }
 }
 
+/// Checks `topics` against `existing_topics`, a map from names of topics in the Kafka cluster
+/// to their number of partitions.
+fn check_topics(
+    topics: &[Topic],
+    existing_topics: &HashMap<String, usize>,
+    num_partitions: i32,
+) -> Result<()> {
+    let mut missing = Vec::new();
+    let mut misconfigured = Vec::new();
+    for topic in topics {
+        match existing_topics.get(topic) {
+            None => missing.push(topic.clone()),
+            Some(partitions) if *partitions != num_partitions as usize => misconfigured.push(
+                format!("{topic} (expect {num_partitions} partitions, found {partitions})"),
+            ),
+            Some(_) => {}
+        }
+    }
+    ensure!(
+        missing.is_empty() && misconfigured.is_empty(),
+        InvalidKafkaWalTopicsSnafu {
+            missing,
+            misconfigured,
+        }
+    );
+
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use std::env;
 
+    use common_config::KafkaWalOptions;
     use common_telemetry::info;
 
     use super::*;
+    use crate::key::datanode_table::{DatanodeTableKey, DatanodeTableValue, RegionInfo};
+    use crate::key::{TableMetaKey, TableMetaValue};
     use crate::kv_backend::memory::MemoryKvBackend;
     use crate::kv_backend::{self};
 
@@ -207,6 +305,80 @@ mod tests {
         assert_eq!(topics, restored_topics);
     }
 
+    #[tokio::test]
+    async fn test_count_regions() {
+        let kv_backend = Arc::new(MemoryKvBackend::new()) as KvBackendRef;
+        let encode = |wal_options: &WalOptions| serde_json::to_string(wal_options).unwrap();
+        let kafka_wal_options = |topic: &str| {
+            encode(&WalOptions::Kafka(KafkaWalOptions {
+                topic: topic.to_string(),
+            }))
+        };
+        let tables = [
+            (
+                1,
+                HashMap::from([
+                    ("1".to_string(), kafka_wal_options("topic_0")),
+                    ("2".to_string(), kafka_wal_options("topic_1")),
+                ]),
+            ),
+            (
+                2,
+                HashMap::from([
+                    ("1".to_string(), kafka_wal_options("topic_0")),
+                    ("2".to_string(), encode(&WalOptions::RaftEngine)),
+                ]),
+            ),
+        ];
+        for (datanode_id, region_wal_options) in tables {
+            let value = DatanodeTableValue::new(
+                1024,
+                vec![1, 2],
+                RegionInfo {
+                    region_wal_options,
+                    ..Default::default()
+                },
+            );
+            kv_backend
+                .put(PutRequest {
+                    key: DatanodeTableKey::new(datanode_id, 1024).as_raw_key(),
+                    value: value.try_as_raw_value().unwrap(),
+                    prev_kv: false,
+                })
+                .await
+                .unwrap();
+        }
+
+        let region_counts = TopicManager::count_regions(&kv_backend).await.unwrap();
+        assert_eq!(
+            HashMap::from([("topic_0".to_string(), 2), ("topic_1".to_string(), 1)]),
+            region_counts
+        );
+    }
+
+    #[test]
+    fn test_check_topics() {
+        let topics = (0..3)
+            .map(|i| format!("greptimedb_wal_topic_{i}"))
+            .collect::<Vec<_>>();
+        let mut existing_topics = topics
+            .iter()
+            .map(|topic| (topic.clone(), 2))
+            .collect::<HashMap<_, _>>();
+        check_topics(&topics, &existing_topics, 2).unwrap();
+
+        let _ = existing_topics.remove("greptimedb_wal_topic_0");
+        let _ = existing_topics.insert("greptimedb_wal_topic_1".to_string(), 1);
+        let err = check_topics(&topics, &existing_topics, 2).unwrap_err();
+        let msg = err.to_string();
+        assert!(msg.contains("[\"greptimedb_wal_topic_0\"]"), "{msg}");
+        assert!(
+            msg.contains("greptimedb_wal_topic_1 (expect 2 partitions, found 1)"),
+            "{msg}"
+        );
+        assert!(!msg.contains("greptimedb_wal_topic_2"), "{msg}");
+    }
+
     #[tokio::test]
     async fn test_topic_manager() {
         let endpoints = env::var("GT_KAFKA_ENDPOINTS").unwrap_or_default();
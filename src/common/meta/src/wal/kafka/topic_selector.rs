@@ -12,8 +12,9 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::collections::HashMap;
 use std::sync::atomic::{AtomicUsize, Ordering};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 
 use rand::Rng;
 use serde::{Deserialize, Serialize};
@@ -28,12 +29,24 @@ pub enum SelectorType {
     #[default]
     #[serde(rename = "round_robin")]
     RoundRobin,
+    #[serde(rename = "least_regions")]
+    LeastRegions,
 }
 
 /// Controls topic selection.
 pub(crate) trait TopicSelector: Send + Sync {
     /// Selects a topic from the topic pool.
     fn select<'a>(&self, topic_pool: &'a [Topic]) -> Result<&'a Topic>;
+
+    /// Returns whether the selector needs the number of regions of each topic.
+    fn requires_region_counts(&self) -> bool {
+        false
+    }
+
+    /// Sets the number of regions already assigned to each topic.
+    fn set_region_counts(&self, region_counts: HashMap<Topic, usize>) {
+        let _ = region_counts;
+    }
 }
 
 /// Arc wrapper of TopicSelector.
@@ -64,6 +77,39 @@ impl TopicSelector for RoundRobinTopicSelector {
     }
 }
 
+/// A topic selector that selects the topic with the least regions, so regions spread evenly
+/// across topics even if the metasrv restarts or tables are dropped.
+///
+/// The region counts are restored from the metadata when the topic manager starts and are only
+/// maintained in memory afterwards, so regions dropped later are not taken into account until
+/// the next restart.
+#[derive(Default)]
+pub(crate) struct LeastRegionsTopicSelector {
+    region_counts: Mutex<HashMap<Topic, usize>>,
+}
+
+impl TopicSelector for LeastRegionsTopicSelector {
+    fn select<'a>(&self, topic_pool: &'a [Topic]) -> Result<&'a Topic> {
+        ensure!(!topic_pool.is_empty(), EmptyTopicPoolSnafu);
+        let mut region_counts = self.region_counts.lock().unwrap();
+        // Safety: the topic pool is not empty.
+        let topic = topic_pool
+            .iter()
+            .min_by_key(|topic| region_counts.get(*topic).copied().unwrap_or_default())
+            .unwrap();
+        *region_counts.entry(topic.clone()).or_default() += 1;
+        Ok(topic)
+    }
+
+    fn requires_region_counts(&self) -> bool {
+        true
+    }
+
+    fn set_region_counts(&self, region_counts: HashMap<Topic, usize>) {
+        *self.region_counts.lock().unwrap() = region_counts;
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -83,4 +129,20 @@ mod tests {
         let topic = selector.select(&topic_pool).unwrap();
         assert!(topic_pool.contains(topic));
     }
+
+    #[test]
+    fn test_least_regions_topic_selector() {
+        let topic_pool: Vec<_> = [0, 1, 2].into_iter().map(|v| v.to_string()).collect();
+        let selector = LeastRegionsTopicSelector::default();
+        selector.set_region_counts(HashMap::from([("0".to_string(), 2), ("2".to_string(), 1)]));
+
+        assert_eq!(selector.select(&topic_pool).unwrap(), "1");
+        // Topic 1 and 2 have 1 region, the first one is selected.
+        assert_eq!(selector.select(&topic_pool).unwrap(), "1");
+        assert_eq!(selector.select(&topic_pool).unwrap(), "2");
+        assert_eq!(selector.select(&topic_pool).unwrap(), "0");
+        assert_eq!(selector.select(&topic_pool).unwrap(), "1");
+
+        assert!(selector.select(&[]).is_err());
+    }
 }
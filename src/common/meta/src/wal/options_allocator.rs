@@ -16,10 +16,11 @@ use std::collections::HashMap;
 use std::sync::Arc;
 
 use common_config::{KafkaWalOptions, WalOptions};
-use snafu::ResultExt;
+use snafu::{ensure, ResultExt};
 use store_api::storage::RegionNumber;
+use table::requests::WAL_PROVIDER_KEY;
 
-use crate::error::{EncodeWalOptionsSnafu, Result};
+use crate::error::{EncodeWalOptionsSnafu, InvalidWalProviderSnafu, Result};
 use crate::kv_backend::KvBackendRef;
 use crate::wal::kafka::TopicManager as KafkaTopicManager;
 use crate::wal::WalConfig;
@@ -88,12 +89,36 @@ impl WalOptionsAllocator {
 }
 
 /// Allocates a wal options for each region. The allocated wal options is encoded immediately.
+///
+/// The wal provider of the regions is chosen by the `wal.provider` option of the table if
+/// present, otherwise the allocator's provider is used.
 pub fn allocate_region_wal_options(
     regions: Vec<RegionNumber>,
+    table_options: &HashMap<String, String>,
     wal_options_allocator: &WalOptionsAllocator,
 ) -> Result<HashMap<RegionNumber, String>> {
-    let wal_options = wal_options_allocator
-        .alloc_batch(regions.len())?
+    let wal_options = match table_options.get(WAL_PROVIDER_KEY).map(|s| s.as_str()) {
+        None => wal_options_allocator.alloc_batch(regions.len())?,
+        Some("raft_engine") => vec![WalOptions::RaftEngine; regions.len()],
+        Some("kafka") => {
+            ensure!(
+                matches!(wal_options_allocator, WalOptionsAllocator::Kafka(_)),
+                InvalidWalProviderSnafu {
+                    provider: "kafka",
+                    reason: "kafka wal is not configured in metasrv",
+                }
+            );
+            wal_options_allocator.alloc_batch(regions.len())?
+        }
+        Some(provider) => {
+            return InvalidWalProviderSnafu {
+                provider,
+                reason: "expect raft_engine or kafka",
+            }
+            .fail()
+        }
+    };
+    let wal_options = wal_options
         .into_iter()
         .map(|wal_options| {
             serde_json::to_string(&wal_options).context(EncodeWalOptionsSnafu { wal_options })
@@ -119,7 +144,8 @@ mod tests {
 
         let num_regions = 32;
         let regions = (0..num_regions).collect::<Vec<_>>();
-        let got = allocate_region_wal_options(regions.clone(), &allocator).unwrap();
+        let got =
+            allocate_region_wal_options(regions.clone(), &HashMap::new(), &allocator).unwrap();
 
         let encoded_wal_options = serde_json::to_string(&WalOptions::RaftEngine).unwrap();
         let expected = regions
@@ -128,4 +154,25 @@ mod tests {
             .collect();
         assert_eq!(got, expected);
     }
+
+    #[tokio::test]
+    async fn test_allocate_with_table_wal_provider() {
+        let kv_backend = Arc::new(MemoryKvBackend::new()) as KvBackendRef;
+        let allocator = WalOptionsAllocator::new(WalConfig::RaftEngine, kv_backend);
+        allocator.start().await.unwrap();
+
+        let regions = vec![0, 1];
+        let options = HashMap::from([(WAL_PROVIDER_KEY.to_string(), "raft_engine".to_string())]);
+        let got = allocate_region_wal_options(regions.clone(), &options, &allocator).unwrap();
+        let encoded_wal_options = serde_json::to_string(&WalOptions::RaftEngine).unwrap();
+        assert_eq!(2, got.len());
+        assert!(got.values().all(|v| *v == encoded_wal_options));
+
+        // Kafka is not configured.
+        let options = HashMap::from([(WAL_PROVIDER_KEY.to_string(), "kafka".to_string())]);
+        assert!(allocate_region_wal_options(regions.clone(), &options, &allocator).is_err());
+
+        let options = HashMap::from([(WAL_PROVIDER_KEY.to_string(), "unknown".to_string())]);
+        assert!(allocate_region_wal_options(regions, &options, &allocator).is_err());
+    }
 }
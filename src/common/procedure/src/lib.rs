@@ -25,7 +25,7 @@ pub mod watcher;
 
 pub use crate::error::{Error, Result};
 pub use crate::procedure::{
-    BoxedProcedure, Context, ContextProvider, LockKey, Procedure, ProcedureId, ProcedureManager,
-    ProcedureManagerRef, ProcedureState, ProcedureWithId, Status,
+    BoxedProcedure, Context, ContextProvider, LockKey, ParseIdError, Procedure, ProcedureId,
+    ProcedureManager, ProcedureManagerRef, ProcedureState, ProcedureWithId, Status,
 };
 pub use crate::watcher::Watcher;
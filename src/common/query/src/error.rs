@@ -38,6 +38,9 @@ pub enum Error {
         location: Location,
     },
 
+    #[snafu(display("Failed to execute WASM UDF: {}", msg))]
+    WasmUdf { msg: String, location: Location },
+
     #[snafu(display("Failed to create temporary recordbatch when eval Python UDF"))]
     UdfTempRecordBatch {
         location: Location,
@@ -174,6 +177,7 @@ impl ErrorExt for Error {
         match self {
             Error::UdfTempRecordBatch { .. }
             | Error::PyUdf { .. }
+            | Error::WasmUdf { .. }
             | Error::ExecuteFunction { .. }
             | Error::GenerateFunction { .. }
             | Error::CreateAccumulator { .. }
@@ -12,17 +12,19 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::collections::BTreeMap;
 use std::future::Future;
 use std::pin::Pin;
 use std::sync::Arc;
 use std::task::{Context, Poll};
+use std::time::Duration;
 
 use datafusion::arrow::compute::cast;
 use datafusion::arrow::datatypes::SchemaRef as DfSchemaRef;
 use datafusion::error::Result as DfResult;
 use datafusion::parquet::arrow::async_reader::{AsyncFileReader, ParquetRecordBatchStream};
-use datafusion::physical_plan::metrics::BaselineMetrics;
-use datafusion::physical_plan::RecordBatchStream as DfRecordBatchStream;
+use datafusion::physical_plan::metrics::{BaselineMetrics, MetricValue};
+use datafusion::physical_plan::{ExecutionPlan, RecordBatchStream as DfRecordBatchStream};
 use datafusion_common::DataFusionError;
 use datatypes::schema::{Schema, SchemaRef};
 use futures::ready;
@@ -30,8 +32,8 @@ use snafu::ResultExt;
 
 use crate::error::{self, Result};
 use crate::{
-    DfRecordBatch, DfSendableRecordBatchStream, RecordBatch, RecordBatchStream,
-    SendableRecordBatchStream, Stream,
+    DfRecordBatch, DfSendableRecordBatchStream, OrderOption, RecordBatch, RecordBatchMetrics,
+    RecordBatchStream, SendableRecordBatchStream, Stream,
 };
 
 type FutureStream =
@@ -216,6 +218,89 @@ impl Stream for RecordBatchStreamAdapter {
     }
 }
 
+/// A [RecordBatchStream] that reports metrics of leaf nodes in the plan it executes.
+///
+/// Leaf nodes are usually scans, so the metrics show how the storage engine reads
+/// data. Counters and elapsed time with the same name are summed up.
+pub struct PlanMetricsStream {
+    inner: SendableRecordBatchStream,
+    plan: Arc<dyn ExecutionPlan>,
+}
+
+impl PlanMetricsStream {
+    pub fn new(inner: SendableRecordBatchStream, plan: Arc<dyn ExecutionPlan>) -> Self {
+        Self { inner, plan }
+    }
+}
+
+impl RecordBatchStream for PlanMetricsStream {
+    fn schema(&self) -> SchemaRef {
+        self.inner.schema()
+    }
+
+    fn output_ordering(&self) -> Option<&[OrderOption]> {
+        self.inner.output_ordering()
+    }
+
+    fn metrics(&self) -> Option<RecordBatchMetrics> {
+        let mut counters = BTreeMap::new();
+        let mut elapsed = BTreeMap::new();
+        collect_leaf_metrics(&self.plan, &mut counters, &mut elapsed);
+        if counters.is_empty() && elapsed.is_empty() {
+            return None;
+        }
+
+        Some(RecordBatchMetrics {
+            counters: counters.into_iter().collect(),
+            elapsed: elapsed.into_iter().collect(),
+        })
+    }
+}
+
+impl Stream for PlanMetricsStream {
+    type Item = Result<RecordBatch>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.inner.as_mut().poll_next(cx)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+/// Adds named counters and elapsed time of leaf nodes in the `plan`.
+fn collect_leaf_metrics(
+    plan: &Arc<dyn ExecutionPlan>,
+    counters: &mut BTreeMap<String, usize>,
+    elapsed: &mut BTreeMap<String, Duration>,
+) {
+    let children = plan.children();
+    if !children.is_empty() {
+        for child in &children {
+            collect_leaf_metrics(child, counters, elapsed);
+        }
+        return;
+    }
+
+    let Some(metrics) = plan.metrics() else {
+        return;
+    };
+    for metric in metrics.aggregate_by_name().iter() {
+        match metric.value() {
+            MetricValue::Count { name, count } => {
+                *counters.entry(name.to_string()).or_default() += count.value();
+            }
+            MetricValue::Time { name, time } => {
+                *elapsed.entry(name.to_string()).or_default() +=
+                    Duration::from_nanos(time.value() as u64);
+            }
+            _ => (),
+        }
+    }
+}
+
 enum AsyncRecordBatchStreamAdapterState {
     Uninit(FutureStream),
     Ready(SendableRecordBatchStream),
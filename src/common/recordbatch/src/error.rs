@@ -107,6 +107,12 @@ pub enum Error {
         location: Location,
         source: datatypes::error::Error,
     },
+
+    #[snafu(display("Query is cancelled"))]
+    Cancelled { location: Location },
+
+    #[snafu(display("Query exceeds its deadline"))]
+    DeadlineExceeded { location: Location },
 }
 
 impl ErrorExt for Error {
@@ -124,6 +130,9 @@ impl ErrorExt for Error {
 
             Error::External { source, .. } => source.status_code(),
 
+            Error::Cancelled { .. } => StatusCode::Cancelled,
+            Error::DeadlineExceeded { .. } => StatusCode::DeadlineExceeded,
+
             Error::SchemaConversion { source, .. } | Error::CastVector { source, .. } => {
                 source.status_code()
             }
@@ -17,8 +17,10 @@ pub mod error;
 mod recordbatch;
 pub mod util;
 
+use std::future::Future;
 use std::pin::Pin;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 use datafusion::physical_plan::memory::MemoryStream;
 pub use datafusion::physical_plan::SendableRecordBatchStream as DfSendableRecordBatchStream;
@@ -31,7 +33,9 @@ use error::Result;
 use futures::task::{Context, Poll};
 use futures::{Stream, TryStreamExt};
 pub use recordbatch::RecordBatch;
+use serde::{Deserialize, Serialize};
 use snafu::{ensure, ResultExt};
+use tokio_util::sync::CancellationToken;
 
 pub trait RecordBatchStream: Stream<Item = Result<RecordBatch>> {
     fn schema(&self) -> SchemaRef;
@@ -39,6 +43,20 @@ pub trait RecordBatchStream: Stream<Item = Result<RecordBatch>> {
     fn output_ordering(&self) -> Option<&[OrderOption]> {
         None
     }
+
+    /// Returns metrics collected by the stream, e.g. metrics of the storage engine.
+    fn metrics(&self) -> Option<RecordBatchMetrics> {
+        None
+    }
+}
+
+/// Metrics collected while producing record batches.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecordBatchMetrics {
+    /// Named counters, e.g. number of files to scan.
+    pub counters: Vec<(String, usize)>,
+    /// Named elapsed time of stages.
+    pub elapsed: Vec<(String, Duration)>,
 }
 
 pub type SendableRecordBatchStream = Pin<Box<dyn RecordBatchStream + Send>>;
@@ -239,6 +257,74 @@ impl<S: Stream<Item = Result<RecordBatch>> + Unpin> Stream for RecordBatchStream
     }
 }
 
+/// A [RecordBatchStream] that stops once its query is cancelled or exceeds its deadline.
+///
+/// The inner stream is dropped after cancellation so the execution behind it
+/// stops as well.
+pub struct CancellableRecordBatchStream {
+    schema: SchemaRef,
+    inner: Option<SendableRecordBatchStream>,
+    cancelled: Pin<Box<dyn Future<Output = ()> + Send>>,
+    deadline: Option<Pin<Box<tokio::time::Sleep>>>,
+}
+
+impl CancellableRecordBatchStream {
+    pub fn new(inner: SendableRecordBatchStream, token: CancellationToken) -> Self {
+        Self {
+            schema: inner.schema(),
+            inner: Some(inner),
+            cancelled: Box::pin(async move { token.cancelled().await }),
+            deadline: None,
+        }
+    }
+
+    /// Stops the stream at the `deadline`.
+    pub fn with_deadline(mut self, deadline: Option<Instant>) -> Self {
+        self.deadline =
+            deadline.map(|deadline| Box::pin(tokio::time::sleep_until(deadline.into())));
+        self
+    }
+}
+
+impl RecordBatchStream for CancellableRecordBatchStream {
+    fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+
+    fn output_ordering(&self) -> Option<&[OrderOption]> {
+        self.inner
+            .as_ref()
+            .and_then(|inner| inner.output_ordering())
+    }
+
+    fn metrics(&self) -> Option<RecordBatchMetrics> {
+        self.inner.as_ref().and_then(|inner| inner.metrics())
+    }
+}
+
+impl Stream for CancellableRecordBatchStream {
+    type Item = Result<RecordBatch>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        if self.inner.is_none() {
+            return Poll::Ready(None);
+        }
+        if self.cancelled.as_mut().poll(cx).is_ready() {
+            self.inner = None;
+            return Poll::Ready(Some(error::CancelledSnafu.fail()));
+        }
+        if let Some(deadline) = self.deadline.as_mut() {
+            if deadline.as_mut().poll(cx).is_ready() {
+                self.inner = None;
+                return Poll::Ready(Some(error::DeadlineExceededSnafu.fail()));
+            }
+        }
+
+        // Safety: checked above.
+        self.inner.as_mut().unwrap().as_mut().poll_next(cx)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::sync::Arc;
@@ -329,4 +415,38 @@ mod tests {
         assert_eq!(collected[0], batch1);
         assert_eq!(collected[1], batch2);
     }
+
+    #[tokio::test]
+    async fn test_cancellable_recordbatch_stream() {
+        let column_a = ColumnSchema::new("a", ConcreteDataType::int32_datatype(), false);
+        let schema = Arc::new(Schema::new(vec![column_a]));
+        let v: VectorRef = Arc::new(Int32Vector::from_slice([1, 2]));
+        let batch = RecordBatch::new(schema.clone(), vec![v]).unwrap();
+        let batches = RecordBatches::try_new(schema, vec![batch.clone(), batch.clone()]).unwrap();
+
+        let token = CancellationToken::new();
+        let mut stream = CancellableRecordBatchStream::new(batches.as_stream(), token.clone());
+        assert_eq!(batch, stream.try_next().await.unwrap().unwrap());
+
+        token.cancel();
+        let err = stream.try_next().await.unwrap_err();
+        assert!(matches!(err, error::Error::Cancelled { .. }));
+        assert!(stream.try_next().await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_recordbatch_stream_exceeds_deadline() {
+        let column_a = ColumnSchema::new("a", ConcreteDataType::int32_datatype(), false);
+        let schema = Arc::new(Schema::new(vec![column_a]));
+        let v: VectorRef = Arc::new(Int32Vector::from_slice([1, 2]));
+        let batch = RecordBatch::new(schema.clone(), vec![v]).unwrap();
+        let batches = RecordBatches::try_new(schema, vec![batch]).unwrap();
+
+        let mut stream =
+            CancellableRecordBatchStream::new(batches.as_stream(), CancellationToken::new())
+                .with_deadline(Some(Instant::now()));
+        let err = stream.try_next().await.unwrap_err();
+        assert!(matches!(err, error::Error::DeadlineExceeded { .. }));
+        assert!(stream.try_next().await.unwrap().is_none());
+    }
 }
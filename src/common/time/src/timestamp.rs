@@ -368,8 +368,16 @@ impl FromStr for Timestamp {
     /// - `2022-09-20 14:16:43.012345Z` (Zulu timezone, without T)
     /// - `2022-09-20 14:16:43` (Zulu timezone, without T)
     /// - `2022-09-20 14:16:43.012345` (Zulu timezone, without T)
-    #[allow(deprecated)]
     fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Timestamp::from_str_with_timezone(s, None)
+    }
+}
+
+impl Timestamp {
+    /// Parses the timestamp string like [Timestamp::from_str], but a string without
+    /// explicit offset is in the given `timezone` instead of UTC.
+    #[allow(deprecated)]
+    pub fn from_str_with_timezone(s: &str, timezone: Option<&TimeZone>) -> Result<Self, Error> {
         // RFC3339 timestamp (with a T)
         let s = s.trim();
         if let Ok(ts) = DateTime::parse_from_rfc3339(s) {
@@ -386,32 +394,44 @@ impl FromStr for Timestamp {
         }
 
         if let Ok(ts) = NaiveDateTime::parse_from_str(s, "%Y-%m-%dT%H:%M:%S") {
-            return naive_datetime_to_timestamp(s, ts);
+            return naive_datetime_to_timestamp(s, ts, timezone);
         }
 
         if let Ok(ts) = NaiveDateTime::parse_from_str(s, "%Y-%m-%dT%H:%M:%S%.f") {
-            return naive_datetime_to_timestamp(s, ts);
+            return naive_datetime_to_timestamp(s, ts, timezone);
         }
 
         if let Ok(ts) = NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S") {
-            return naive_datetime_to_timestamp(s, ts);
+            return naive_datetime_to_timestamp(s, ts, timezone);
         }
 
         if let Ok(ts) = NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S%.f") {
-            return naive_datetime_to_timestamp(s, ts);
+            return naive_datetime_to_timestamp(s, ts, timezone);
         }
 
         ParseTimestampSnafu { raw: s }.fail()
     }
 }
 
-/// Converts the naive datetime (which has no specific timezone) to a
-/// nanosecond epoch timestamp in UTC.
+/// Converts the naive datetime (which has no specific timezone) in `timezone`, or UTC
+/// if it's absent, to a nanosecond epoch timestamp in UTC.
 fn naive_datetime_to_timestamp(
     s: &str,
     datetime: NaiveDateTime,
+    timezone: Option<&TimeZone>,
 ) -> crate::error::Result<Timestamp> {
-    Timestamp::from_chrono_datetime(Utc.from_utc_datetime(&datetime).naive_utc())
+    let utc = match timezone {
+        Some(TimeZone::Offset(offset)) => offset
+            .from_local_datetime(&datetime)
+            .earliest()
+            .map(|v| v.naive_utc()),
+        Some(TimeZone::Named(tz)) => tz
+            .from_local_datetime(&datetime)
+            .earliest()
+            .map(|v| v.naive_utc()),
+        None => Some(Utc.from_utc_datetime(&datetime).naive_utc()),
+    };
+    utc.and_then(Timestamp::from_chrono_datetime)
         .context(ParseTimestampSnafu { raw: s })
 }
 
@@ -787,6 +807,20 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_from_str_with_timezone() {
+        let tz = TimeZone::from_tz_string("+08:00").unwrap();
+        let ts = Timestamp::from_str_with_timezone("2020-09-08 13:42:29", tz.as_ref()).unwrap();
+        assert_eq!(Timestamp::from_str("2020-09-08 05:42:29Z").unwrap(), ts);
+        // The explicit offset wins.
+        let ts = Timestamp::from_str_with_timezone("2020-09-08 13:42:29Z", tz.as_ref()).unwrap();
+        assert_eq!(Timestamp::from_str("2020-09-08 13:42:29Z").unwrap(), ts);
+
+        let tz = TimeZone::from_tz_string("Asia/Shanghai").unwrap();
+        let ts = Timestamp::from_str_with_timezone("2020-09-08T13:42:29.042", tz.as_ref()).unwrap();
+        assert_eq!(Timestamp::from_str("2020-09-08 05:42:29.042Z").unwrap(), ts);
+    }
+
     #[test]
     fn test_to_iso8601_string() {
         std::env::set_var("TZ", "Asia/Shanghai");
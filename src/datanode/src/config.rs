@@ -244,6 +244,7 @@ pub struct DatanodeOptions {
     pub logging: LoggingOptions,
     pub enable_telemetry: bool,
     pub export_metrics: ExportMetricsOption,
+    pub request_limit: RequestLimitOptions,
 }
 
 impl Default for DatanodeOptions {
@@ -270,6 +271,36 @@ impl Default for DatanodeOptions {
             heartbeat: HeartbeatOptions::datanode_default(),
             enable_telemetry: true,
             export_metrics: ExportMetricsOption::default(),
+            request_limit: RequestLimitOptions::default(),
+        }
+    }
+}
+
+/// Limits of concurrent requests handled by the region server, 0 means unlimited.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RequestLimitOptions {
+    /// Max concurrent scans of all regions.
+    pub max_concurrent_scans: usize,
+    /// Max concurrent scans of each region.
+    pub max_concurrent_region_scans: usize,
+    /// Max concurrent writes of all regions.
+    pub max_concurrent_writes: usize,
+    /// Max concurrent writes of each region.
+    pub max_concurrent_region_writes: usize,
+    /// Max time a request waits for its turn before it's rejected.
+    #[serde(with = "humantime_serde")]
+    pub queue_timeout: Duration,
+}
+
+impl Default for RequestLimitOptions {
+    fn default() -> Self {
+        Self {
+            max_concurrent_scans: 0,
+            max_concurrent_region_scans: 0,
+            max_concurrent_writes: 0,
+            max_concurrent_region_writes: 0,
+            queue_timeout: Duration::from_secs(5),
         }
     }
 }
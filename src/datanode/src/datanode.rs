@@ -21,7 +21,7 @@ use std::sync::Arc;
 
 use catalog::memory::MemoryCatalogManager;
 use common_base::Plugins;
-use common_config::wal::{KafkaConfig, RaftEngineConfig};
+use common_config::wal::{KafkaConfig, ObjectStoreWalConfig, RaftEngineConfig};
 use common_config::{WalConfig, WAL_OPTIONS_KEY};
 use common_error::ext::BoxedError;
 use common_greptimedb_telemetry::GreptimeDBTelemetryTask;
@@ -35,7 +35,10 @@ use futures::future;
 use futures_util::future::try_join_all;
 use futures_util::TryStreamExt;
 use log_store::kafka::log_store::KafkaLogStore;
+use log_store::mixed::MixedLogStore;
+use log_store::object_store::log_store::ObjectStoreLogStore;
 use log_store::raft_engine::log_store::RaftEngineLogStore;
+use log_store::NoopLogStore;
 use meta_client::client::MetaClient;
 use metric_engine::engine::MetricEngine;
 use mito2::config::MitoConfig;
@@ -61,7 +64,7 @@ use crate::config::{DatanodeOptions, RegionEngineConfig};
 use crate::error::{
     CreateDirSnafu, GetMetadataSnafu, MissingKvBackendSnafu, MissingNodeIdSnafu, OpenLogStoreSnafu,
     ParseAddrSnafu, Result, RuntimeResourceSnafu, ShutdownInstanceSnafu, ShutdownServerSnafu,
-    StartServerSnafu,
+    StartServerSnafu, WalStoreNotFoundSnafu,
 };
 use crate::event_listener::{
     new_region_server_event_channel, NoopRegionServerEventListener, RegionServerEventListenerRef,
@@ -75,6 +78,10 @@ use crate::store;
 const OPEN_REGION_PARALLELISM: usize = 16;
 const REGION_SERVER_SERVICE_NAME: &str = "REGION_SERVER_SERVICE";
 const DATANODE_HTTP_SERVICE_NAME: &str = "DATANODE_HTTP_SERVICE";
+/// Default local directory of the object store wal, relative to the data home.
+const WAL_BUFFER_DIR: &str = "wal_buffer/";
+/// Default local directory of the journal of manifest commits, relative to the data home.
+const COMMIT_JOURNAL_DIR: &str = "commit_journal/";
 
 /// Datanode service.
 pub struct Datanode {
@@ -238,7 +245,9 @@ impl DatanodeBuilder {
             (Box::new(NoopRegionServerEventListener) as _, None)
         };
 
-        let region_server = self.new_region_server(region_event_listener).await?;
+        let region_server = self
+            .new_region_server(region_event_listener, kv_backend.clone())
+            .await?;
 
         let datanode_table_manager = DatanodeTableManager::new(kv_backend.clone());
         let table_values = datanode_table_manager
@@ -384,6 +393,7 @@ impl DatanodeBuilder {
     async fn new_region_server(
         &self,
         event_listener: RegionServerEventListenerRef,
+        kv_backend: KvBackendRef,
     ) -> Result<RegionServer> {
         let opts = &self.opts;
 
@@ -411,10 +421,11 @@ impl DatanodeBuilder {
             runtime,
             event_listener,
             table_provider_factory,
-        );
+        )
+        .with_request_limit(opts.request_limit.clone());
 
         let object_store_manager = Self::build_object_store_manager(opts).await?;
-        let engines = Self::build_store_engines(opts, object_store_manager).await?;
+        let engines = Self::build_store_engines(opts, object_store_manager, kv_backend).await?;
         for engine in engines {
             region_server.register_engine(engine);
         }
@@ -428,14 +439,19 @@ impl DatanodeBuilder {
     async fn build_store_engines(
         opts: &DatanodeOptions,
         object_store_manager: ObjectStoreManagerRef,
+        kv_backend: KvBackendRef,
     ) -> Result<Vec<RegionEngineRef>> {
         let mut engines = vec![];
         for engine in &opts.region_engine {
             match engine {
                 RegionEngineConfig::Mito(config) => {
-                    let mito_engine =
-                        Self::build_mito_engine(opts, object_store_manager.clone(), config.clone())
-                            .await?;
+                    let mito_engine = Self::build_mito_engine(
+                        opts,
+                        object_store_manager.clone(),
+                        config.clone(),
+                        kv_backend.clone(),
+                    )
+                    .await?;
 
                     let metric_engine = MetricEngine::new(mito_engine.clone());
                     engines.push(Arc::new(mito_engine) as _);
@@ -457,8 +473,15 @@ impl DatanodeBuilder {
     async fn build_mito_engine(
         opts: &DatanodeOptions,
         object_store_manager: ObjectStoreManagerRef,
-        config: MitoConfig,
+        mut config: MitoConfig,
+        kv_backend: KvBackendRef,
     ) -> Result<MitoEngine> {
+        if config.commit_journal_dir.is_empty() {
+            config.commit_journal_dir = format!(
+                "{}{COMMIT_JOURNAL_DIR}",
+                normalize_dir(&opts.storage.data_home)
+            );
+        }
         let mito_engine = match &opts.wal {
             WalConfig::RaftEngine(raft_engine_config) => MitoEngine::new(
                 config,
@@ -468,9 +491,36 @@ impl DatanodeBuilder {
             ),
             WalConfig::Kafka(kafka_config) => MitoEngine::new(
                 config,
-                Self::build_kafka_log_store(kafka_config).await?,
+                Self::build_kafka_log_store(kafka_config, kv_backend).await?,
                 object_store_manager,
             ),
+            WalConfig::Noop => {
+                warn!("WAL is disabled, data not flushed will be lost if the datanode crashes");
+                MitoEngine::new(config, Arc::new(NoopLogStore), object_store_manager)
+            }
+            WalConfig::ObjectStore(object_store_config) => MitoEngine::new(
+                config,
+                Self::build_object_store_log_store(
+                    &opts.storage.data_home,
+                    object_store_config,
+                    &object_store_manager,
+                )
+                .await?,
+                object_store_manager,
+            ),
+            WalConfig::Mixed(mixed_config) => {
+                let raft_engine = Self::build_raft_engine_log_store(
+                    &opts.storage.data_home,
+                    &mixed_config.raft_engine,
+                )
+                .await?;
+                let kafka = Self::build_kafka_log_store(&mixed_config.kafka, kv_backend).await?;
+                MitoEngine::new(
+                    config,
+                    Arc::new(MixedLogStore::new(raft_engine, kafka)),
+                    object_store_manager,
+                )
+            }
         };
         Ok(mito_engine)
     }
@@ -503,12 +553,57 @@ impl DatanodeBuilder {
     }
 
     /// Builds [KafkaLogStore].
-    async fn build_kafka_log_store(config: &KafkaConfig) -> Result<Arc<KafkaLogStore>> {
-        KafkaLogStore::try_new(config)
+    async fn build_kafka_log_store(
+        config: &KafkaConfig,
+        kv_backend: KvBackendRef,
+    ) -> Result<Arc<KafkaLogStore>> {
+        let log_store = KafkaLogStore::try_new(config)
             .await
             .map_err(Box::new)
-            .context(OpenLogStoreSnafu)
-            .map(Arc::new)
+            .context(OpenLogStoreSnafu)?;
+        let log_store = if config.offset_index_interval > 0 {
+            log_store.with_offset_index(kv_backend.clone())
+        } else {
+            log_store
+        };
+        let log_store = if config.prune_obsolete_records {
+            log_store.with_pruner(kv_backend)
+        } else {
+            log_store
+        };
+        Ok(Arc::new(log_store))
+    }
+
+    /// Builds [ObjectStoreLogStore].
+    async fn build_object_store_log_store(
+        data_home: &str,
+        config: &ObjectStoreWalConfig,
+        object_store_manager: &ObjectStoreManagerRef,
+    ) -> Result<Arc<ObjectStoreLogStore>> {
+        let object_store = match &config.store {
+            Some(name) => object_store_manager
+                .find(name)
+                .context(WalStoreNotFoundSnafu { name })?,
+            None => object_store_manager.default_object_store(),
+        };
+        let buffer_dir = match &config.dir {
+            Some(dir) => dir.clone(),
+            None => format!("{}{WAL_BUFFER_DIR}", normalize_dir(data_home)),
+        };
+
+        fs::create_dir_all(Path::new(&buffer_dir))
+            .await
+            .context(CreateDirSnafu { dir: &buffer_dir })?;
+        info!(
+            "Creating object store logstore with config: {:?} and buffer path: {}",
+            config, &buffer_dir
+        );
+        let log_store = ObjectStoreLogStore::try_new(buffer_dir, object_store.clone(), config)
+            .await
+            .map_err(Box::new)
+            .context(OpenLogStoreSnafu)?;
+
+        Ok(Arc::new(log_store))
     }
 
     /// Builds [ObjectStoreManager]
@@ -161,6 +161,9 @@ pub enum Error {
     #[snafu(display("Missing node id in Datanode config"))]
     MissingNodeId { location: Location },
 
+    #[snafu(display("Storage provider {} of the object store wal not found", name))]
+    WalStoreNotFound { name: String, location: Location },
+
     #[snafu(display("Missing required field: {}", name))]
     MissingRequiredField { name: String, location: Location },
 
@@ -222,6 +225,13 @@ pub enum Error {
         location: Location,
     },
 
+    #[snafu(display("Too many concurrent {} requests, region: {}", kind, region_id))]
+    RequestLimitExceeded {
+        region_id: RegionId,
+        kind: String,
+        location: Location,
+    },
+
     #[snafu(display("Region engine {} is not registered", name))]
     RegionEngineNotFound { name: String, location: Location },
 
@@ -288,6 +298,7 @@ impl ErrorExt for Error {
             | SchemaExists { .. }
             | DatabaseNotFound { .. }
             | MissingNodeId { .. }
+            | WalStoreNotFound { .. }
             | ColumnNoneDefaultValue { .. }
             | MissingWalDirConfig { .. }
             | MissingKvBackend { .. } => StatusCode::InvalidArguments,
@@ -309,6 +320,7 @@ impl ErrorExt for Error {
             RegionNotFound { .. } => StatusCode::RegionNotFound,
             RegionNotReady { .. } => StatusCode::RegionNotReady,
             RegionBusy { .. } => StatusCode::RegionBusy,
+            RequestLimitExceeded { .. } => StatusCode::RateLimited,
 
             StartServer { source, .. } | ShutdownServer { source, .. } => source.status_code(),
 
@@ -23,6 +23,7 @@ mod greptimedb_telemetry;
 pub mod heartbeat;
 pub mod metrics;
 pub mod region_server;
+mod request_limiter;
 mod store;
 #[cfg(test)]
 mod tests;
@@ -51,23 +51,28 @@ use servers::grpc::region_server::RegionServerHandler;
 use session::context::{QueryContextBuilder, QueryContextRef};
 use snafu::{OptionExt, ResultExt};
 use store_api::metadata::RegionMetadataRef;
-use store_api::region_engine::{RegionEngineRef, RegionRole, SetReadonlyResponse};
+use store_api::region_engine::{
+    RegionCaptureRequest, RegionEngineRef, RegionRole, SetReadonlyResponse,
+};
 use store_api::region_request::{AffectedRows, RegionCloseRequest, RegionRequest};
 use store_api::storage::{RegionId, ScanRequest};
 use substrait::{DFLogicalSubstraitConvertor, SubstraitPlan};
 use table::table::scan::StreamScanAdapter;
 use tonic::{Request, Response, Result as TonicResult};
 
+use crate::config::RequestLimitOptions;
 use crate::error::{
     self, BuildRegionRequestsSnafu, DecodeLogicalPlanSnafu, ExecuteLogicalPlanSnafu,
     GetRegionMetadataSnafu, HandleRegionRequestSnafu, RegionEngineNotFoundSnafu,
     RegionNotFoundSnafu, Result, StopRegionEngineSnafu, UnsupportedOutputSnafu,
 };
 use crate::event_listener::RegionServerEventListenerRef;
+use crate::request_limiter::{PermitStream, RequestKind, RequestLimiter};
 
 #[derive(Clone)]
 pub struct RegionServer {
     inner: Arc<RegionServerInner>,
+    request_limiter: Arc<RequestLimiter>,
 }
 
 pub struct RegionStat {
@@ -103,9 +108,16 @@ impl RegionServer {
                 event_listener,
                 table_provider_factory,
             )),
+            request_limiter: Arc::default(),
         }
     }
 
+    /// Limits concurrent scans and writes of regions.
+    pub fn with_request_limit(mut self, opts: RequestLimitOptions) -> Self {
+        self.request_limiter = Arc::new(RequestLimiter::new(opts));
+        self
+    }
+
     pub fn register_engine(&mut self, engine: RegionEngineRef) {
         self.inner.register_engine(engine);
     }
@@ -115,12 +127,34 @@ impl RegionServer {
         region_id: RegionId,
         request: RegionRequest,
     ) -> Result<AffectedRows> {
-        self.inner.handle_request(region_id, request).await
+        let _permit = match &request {
+            RegionRequest::Put(_) | RegionRequest::Delete(_) => Some(
+                self.request_limiter
+                    .acquire(region_id, RequestKind::Write)
+                    .await?,
+            ),
+            _ => None,
+        };
+        let deregister = matches!(request, RegionRequest::Close(_) | RegionRequest::Drop(_));
+
+        let result = self.inner.handle_request(region_id, request).await;
+        if deregister && result.is_ok() {
+            self.request_limiter.remove_region(region_id);
+        }
+        result
     }
 
     #[tracing::instrument(skip_all)]
     pub async fn handle_read(&self, request: QueryRequest) -> Result<SendableRecordBatchStream> {
-        self.inner.handle_read(request).await
+        let region_id = RegionId::from_u64(request.region_id);
+        let permit = self
+            .request_limiter
+            .acquire(region_id, RequestKind::Scan)
+            .await?;
+        let stream = self.inner.handle_read(request).await?;
+
+        // The permit is released once the scan is done.
+        Ok(Box::pin(PermitStream::new(stream, permit)))
     }
 
     pub fn opened_regions(&self) -> Vec<RegionStat> {
@@ -163,6 +197,26 @@ impl RegionServer {
         }
     }
 
+    /// Copies files of the region that a read needs to a local directory.
+    ///
+    /// Returns paths of copied files relative to the target directory.
+    pub async fn capture_region(
+        &self,
+        region_id: RegionId,
+        request: RegionCaptureRequest,
+    ) -> Result<Vec<String>> {
+        let engine = self
+            .inner
+            .region_map
+            .get(&region_id)
+            .with_context(|| RegionNotFoundSnafu { region_id })?
+            .clone();
+        engine
+            .capture_region(region_id, request)
+            .await
+            .with_context(|_| HandleRegionRequestSnafu { region_id })
+    }
+
     pub fn runtime(&self) -> Arc<Runtime> {
         self.inner.runtime.clone()
     }
@@ -246,7 +300,10 @@ impl FlightCraft for RegionServer {
             .trace(tracing_context.attach(info_span!("RegionServer::handle_read")))
             .await?;
 
-        let stream = Box::pin(FlightRecordBatchStream::new(result, tracing_context));
+        let stream = Box::pin(FlightRecordBatchStream::with_metrics(
+            result,
+            tracing_context,
+        ));
         Ok(Response::new(stream))
     }
 }
@@ -418,7 +475,8 @@ impl RegionServerInner {
             | RegionRequest::Flush(_)
             | RegionRequest::Compact(_)
             | RegionRequest::Truncate(_)
-            | RegionRequest::Catchup(_) => RegionChange::None,
+            | RegionRequest::Catchup(_)
+            | RegionRequest::DeleteRange(_) => RegionChange::None,
         };
 
         let engine = match self.get_engine(region_id, &region_change)? {
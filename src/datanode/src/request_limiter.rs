@@ -0,0 +1,248 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Admission control of requests to regions.
+
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use common_recordbatch::error::Result as RecordBatchResult;
+use common_recordbatch::{
+    OrderOption, RecordBatch, RecordBatchMetrics, RecordBatchStream, SendableRecordBatchStream,
+};
+use dashmap::DashMap;
+use datatypes::schema::SchemaRef;
+use futures_util::Stream;
+use snafu::ensure;
+use store_api::storage::RegionId;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+use crate::config::RequestLimitOptions;
+use crate::error::{RequestLimitExceededSnafu, Result};
+
+/// Kind of requests limited separately.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum RequestKind {
+    Scan,
+    Write,
+}
+
+impl RequestKind {
+    fn as_str(&self) -> &'static str {
+        match self {
+            RequestKind::Scan => "scan",
+            RequestKind::Write => "write",
+        }
+    }
+}
+
+/// Permits of a request, released on drop.
+pub(crate) struct RequestPermit {
+    _region: Option<OwnedSemaphorePermit>,
+    _global: Option<OwnedSemaphorePermit>,
+}
+
+/// Limits concurrent requests of one kind.
+#[derive(Debug)]
+struct KindLimiter {
+    global: Option<Arc<Semaphore>>,
+    /// Max concurrent requests of each region, unlimited if it's 0.
+    region_limit: usize,
+    regions: DashMap<RegionId, Arc<Semaphore>>,
+}
+
+impl KindLimiter {
+    fn new(global_limit: usize, region_limit: usize) -> Self {
+        Self {
+            global: (global_limit > 0).then(|| Arc::new(Semaphore::new(global_limit))),
+            region_limit,
+            regions: DashMap::new(),
+        }
+    }
+
+    fn region_semaphore(&self, region_id: RegionId) -> Option<Arc<Semaphore>> {
+        (self.region_limit > 0).then(|| {
+            self.regions
+                .entry(region_id)
+                .or_insert_with(|| Arc::new(Semaphore::new(self.region_limit)))
+                .clone()
+        })
+    }
+
+    async fn acquire(&self, region_id: RegionId) -> RequestPermit {
+        // Waits for the permit of the region first, so requests queued for a hot region
+        // don't hold global permits.
+        let region = match self.region_semaphore(region_id) {
+            // Safety: semaphores are never closed.
+            Some(semaphore) => Some(semaphore.acquire_owned().await.unwrap()),
+            None => None,
+        };
+        let global = match &self.global {
+            Some(semaphore) => Some(semaphore.clone().acquire_owned().await.unwrap()),
+            None => None,
+        };
+
+        RequestPermit {
+            _region: region,
+            _global: global,
+        }
+    }
+}
+
+/// Limits concurrent scans and writes of the region server, globally and per region.
+#[derive(Debug)]
+pub(crate) struct RequestLimiter {
+    opts: RequestLimitOptions,
+    scan: KindLimiter,
+    write: KindLimiter,
+}
+
+impl Default for RequestLimiter {
+    fn default() -> Self {
+        Self::new(RequestLimitOptions::default())
+    }
+}
+
+impl RequestLimiter {
+    pub(crate) fn new(opts: RequestLimitOptions) -> Self {
+        Self {
+            scan: KindLimiter::new(opts.max_concurrent_scans, opts.max_concurrent_region_scans),
+            write: KindLimiter::new(
+                opts.max_concurrent_writes,
+                opts.max_concurrent_region_writes,
+            ),
+            opts,
+        }
+    }
+
+    fn limiter(&self, kind: RequestKind) -> &KindLimiter {
+        match kind {
+            RequestKind::Scan => &self.scan,
+            RequestKind::Write => &self.write,
+        }
+    }
+
+    /// Waits for permits of the request, fails if they are not acquired in the queue timeout.
+    pub(crate) async fn acquire(
+        &self,
+        region_id: RegionId,
+        kind: RequestKind,
+    ) -> Result<RequestPermit> {
+        let permit = tokio::time::timeout(
+            self.opts.queue_timeout,
+            self.limiter(kind).acquire(region_id),
+        )
+        .await;
+        ensure!(
+            permit.is_ok(),
+            RequestLimitExceededSnafu {
+                region_id,
+                kind: kind.as_str(),
+            }
+        );
+
+        // Safety: checked above.
+        Ok(permit.unwrap())
+    }
+
+    /// Removes limits of the region.
+    pub(crate) fn remove_region(&self, region_id: RegionId) {
+        let _ = self.scan.regions.remove(&region_id);
+        let _ = self.write.regions.remove(&region_id);
+    }
+}
+
+/// A stream holding the permit of its scan until it's dropped.
+pub(crate) struct PermitStream {
+    inner: SendableRecordBatchStream,
+    _permit: RequestPermit,
+}
+
+impl PermitStream {
+    pub(crate) fn new(inner: SendableRecordBatchStream, permit: RequestPermit) -> Self {
+        Self {
+            inner,
+            _permit: permit,
+        }
+    }
+}
+
+impl RecordBatchStream for PermitStream {
+    fn schema(&self) -> SchemaRef {
+        self.inner.schema()
+    }
+
+    fn output_ordering(&self) -> Option<&[OrderOption]> {
+        self.inner.output_ordering()
+    }
+
+    fn metrics(&self) -> Option<RecordBatchMetrics> {
+        self.inner.metrics()
+    }
+}
+
+impl Stream for PermitStream {
+    type Item = RecordBatchResult<RecordBatch>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.inner.as_mut().poll_next(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::error::Error;
+
+    #[tokio::test]
+    async fn test_request_limiter() {
+        let limiter = RequestLimiter::new(RequestLimitOptions {
+            max_concurrent_scans: 2,
+            max_concurrent_region_scans: 1,
+            max_concurrent_writes: 0,
+            max_concurrent_region_writes: 0,
+            queue_timeout: Duration::from_millis(10),
+        });
+        let region_1 = RegionId::new(1, 1);
+        let region_2 = RegionId::new(1, 2);
+        let region_3 = RegionId::new(1, 3);
+
+        let permit_1 = limiter.acquire(region_1, RequestKind::Scan).await.unwrap();
+        // Exceeds the limit of the region.
+        let err = limiter
+            .acquire(region_1, RequestKind::Scan)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::RequestLimitExceeded { .. }));
+
+        let _permit_2 = limiter.acquire(region_2, RequestKind::Scan).await.unwrap();
+        // Exceeds the global limit.
+        assert!(limiter.acquire(region_3, RequestKind::Scan).await.is_err());
+        drop(permit_1);
+        let _permit_3 = limiter.acquire(region_3, RequestKind::Scan).await.unwrap();
+
+        // Writes are unlimited.
+        let _writes = futures_util::future::try_join_all(
+            (0..10).map(|_| limiter.acquire(region_1, RequestKind::Write)),
+        )
+        .await
+        .unwrap();
+
+        limiter.remove_region(region_1);
+        assert!(!limiter.scan.regions.contains_key(&region_1));
+    }
+}
@@ -30,7 +30,9 @@ use query::query_engine::DescribeResult;
 use query::QueryEngine;
 use session::context::QueryContextRef;
 use store_api::metadata::RegionMetadataRef;
-use store_api::region_engine::{RegionEngine, RegionRole, SetReadonlyResponse};
+use store_api::region_engine::{
+    RegionCaptureRequest, RegionEngine, RegionRole, SetReadonlyResponse,
+};
 use store_api::region_request::{AffectedRows, RegionRequest};
 use store_api::storage::{RegionId, ScanRequest};
 use table::TableRef;
@@ -74,6 +76,10 @@ impl QueryEngine for MockQueryEngine {
 
     fn register_function(&self, _func: FunctionRef) {}
 
+    fn register_catalog_function(&self, _catalog: &str, _func: FunctionRef) -> bool {
+        false
+    }
+
     fn read_table(&self, _table: TableRef) -> query::error::Result<DataFrame> {
         unimplemented!()
     }
@@ -177,4 +183,12 @@ impl RegionEngine for MockRegionEngine {
     fn role(&self, _region_id: RegionId) -> Option<RegionRole> {
         Some(RegionRole::Leader)
     }
+
+    async fn capture_region(
+        &self,
+        _region_id: RegionId,
+        _request: RegionCaptureRequest,
+    ) -> Result<Vec<String>, BoxedError> {
+        unimplemented!()
+    }
 }
@@ -143,6 +143,18 @@ impl ColumnSchema {
         self
     }
 
+    /// Set the nullablity to `false` of the column.
+    ///
+    /// Returns an error if the default constraint of the column might be null.
+    pub fn with_nullable_unset(mut self) -> Result<Self> {
+        if let Some(constraint) = &self.default_constraint {
+            constraint.validate(&self.data_type, false)?;
+        }
+
+        self.is_nullable = false;
+        Ok(self)
+    }
+
     /// Creates a new [`ColumnSchema`] with given metadata.
     pub fn with_metadata(mut self, metadata: Metadata) -> Self {
         self.metadata = metadata;
@@ -344,6 +356,21 @@ mod tests {
         assert!(Field::try_from(&column_schema).is_err());
     }
 
+    #[test]
+    fn test_column_schema_with_nullable_unset() {
+        let column_schema = ColumnSchema::new("test", ConcreteDataType::int32_datatype(), true)
+            .with_default_constraint(Some(ColumnDefaultConstraint::Value(Value::from(99))))
+            .unwrap()
+            .with_nullable_unset()
+            .unwrap();
+        assert!(!column_schema.is_nullable());
+
+        let column_schema = ColumnSchema::new("test", ConcreteDataType::int32_datatype(), true)
+            .with_default_constraint(Some(ColumnDefaultConstraint::null_value()))
+            .unwrap();
+        assert!(column_schema.with_nullable_unset().is_err());
+    }
+
     #[test]
     fn test_column_schema_invalid_default_constraint() {
         assert!(
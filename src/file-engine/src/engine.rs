@@ -23,7 +23,9 @@ use common_telemetry::{error, info};
 use object_store::ObjectStore;
 use snafu::{ensure, OptionExt};
 use store_api::metadata::RegionMetadataRef;
-use store_api::region_engine::{RegionEngine, RegionRole, SetReadonlyResponse};
+use store_api::region_engine::{
+    RegionCaptureRequest, RegionEngine, RegionRole, SetReadonlyResponse,
+};
 use store_api::region_request::{
     AffectedRows, RegionCloseRequest, RegionCreateRequest, RegionDropRequest, RegionOpenRequest,
     RegionRequest,
@@ -77,6 +79,7 @@ impl RegionEngine for FileRegionEngine {
             .context(RegionNotFoundSnafu { region_id })
             .map_err(BoxedError::new)?
             .query(request)
+            .await
             .map_err(BoxedError::new)
     }
 
@@ -119,6 +122,19 @@ impl RegionEngine for FileRegionEngine {
     fn role(&self, region_id: RegionId) -> Option<RegionRole> {
         self.inner.state(region_id)
     }
+
+    async fn capture_region(
+        &self,
+        _region_id: RegionId,
+        _request: RegionCaptureRequest,
+    ) -> Result<Vec<String>, BoxedError> {
+        // Files of external tables are not managed by the engine.
+        UnsupportedSnafu {
+            operation: "capture region",
+        }
+        .fail()
+        .map_err(BoxedError::new)
+    }
 }
 
 struct EngineInner {
@@ -106,6 +106,20 @@ pub enum Error {
         source: common_datasource::error::Error,
     },
 
+    #[snafu(display("Failed to list files"))]
+    ListFiles {
+        location: Location,
+        source: common_datasource::error::Error,
+    },
+
+    #[snafu(display("Invalid file pattern: {}", pattern))]
+    InvalidPattern {
+        pattern: String,
+        #[snafu(source)]
+        error: regex::Error,
+        location: Location,
+    },
+
     #[snafu(display("Failed to build csv config"))]
     BuildCsvConfig {
         #[snafu(source)]
@@ -196,11 +210,12 @@ impl ErrorExt for Error {
             | InvalidMetadata { .. }
             | ProjectionOutOfBounds { .. }
             | CreateDefault { .. }
-            | MissingColumnNoDefault { .. } => StatusCode::InvalidArguments,
+            | MissingColumnNoDefault { .. }
+            | InvalidPattern { .. } => StatusCode::InvalidArguments,
 
             RegionNotFound { .. } => StatusCode::RegionNotFound,
 
-            BuildBackend { source, .. } => source.status_code(),
+            BuildBackend { source, .. } | ListFiles { source, .. } => source.status_code(),
             BuildStreamAdapter { source, .. } => source.status_code(),
             ParseFileFormat { source, .. } => source.status_code(),
 
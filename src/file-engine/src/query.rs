@@ -19,6 +19,7 @@ use std::pin::Pin;
 use std::sync::Arc;
 use std::task::{Context, Poll};
 
+use common_datasource::lister::list_files;
 use common_datasource::object_store::build_backend;
 use common_error::ext::BoxedError;
 use common_query::prelude::Expr;
@@ -29,19 +30,24 @@ use datatypes::prelude::ConcreteDataType;
 use datatypes::schema::{ColumnSchema, Schema, SchemaRef};
 use datatypes::vectors::VectorRef;
 use futures::Stream;
+use object_store::ObjectStore;
+use regex::Regex;
 use snafu::{ensure, OptionExt, ResultExt};
 use store_api::storage::ScanRequest;
+use table::requests::{FILE_TABLE_LIST_ON_SCAN_KEY, FILE_TABLE_PATTERN_KEY};
 
 use self::file_stream::{CreateScanPlanContext, ScanPlanConfig};
 use crate::error::{
-    BuildBackendSnafu, CreateDefaultSnafu, ExtractColumnFromFilterSnafu,
-    MissingColumnNoDefaultSnafu, ProjectSchemaSnafu, ProjectionOutOfBoundsSnafu, Result,
+    BuildBackendSnafu, CreateDefaultSnafu, ExtractColumnFromFilterSnafu, InvalidPatternSnafu,
+    ListFilesSnafu, MissingColumnNoDefaultSnafu, ProjectSchemaSnafu, ProjectionOutOfBoundsSnafu,
+    Result,
 };
 use crate::region::FileRegion;
 
 impl FileRegion {
-    pub fn query(&self, request: ScanRequest) -> Result<SendableRecordBatchStream> {
+    pub async fn query(&self, request: ScanRequest) -> Result<SendableRecordBatchStream> {
         let store = build_backend(&self.url, &self.options).context(BuildBackendSnafu)?;
+        let files = self.files_to_scan(&store).await?;
 
         let file_projection = self.projection_pushdown_to_file(&request.projection)?;
         let file_filters = self.filters_pushdown_to_file(&request.filters)?;
@@ -52,7 +58,7 @@ impl FileRegion {
             &CreateScanPlanContext::default(),
             &ScanPlanConfig {
                 file_schema,
-                files: &self.file_options.files,
+                files: &files,
                 projection: file_projection.as_ref(),
                 filters: &file_filters,
                 limit: request.limit,
@@ -68,6 +74,28 @@ impl FileRegion {
         )))
     }
 
+    /// Returns files to scan. Lists the location again if `list_on_scan` is set,
+    /// otherwise uses files found when the table was created.
+    async fn files_to_scan(&self, store: &ObjectStore) -> Result<Vec<String>> {
+        let list_on_scan = self
+            .options
+            .get(FILE_TABLE_LIST_ON_SCAN_KEY)
+            .map(|v| v.eq_ignore_ascii_case("true"))
+            .unwrap_or(false);
+        if !list_on_scan {
+            return Ok(self.file_options.files.clone());
+        }
+
+        let pattern = self
+            .options
+            .get(FILE_TABLE_PATTERN_KEY)
+            .map(|pattern| Regex::new(pattern).context(InvalidPatternSnafu { pattern }))
+            .transpose()?;
+        list_files(store.clone(), &self.url, pattern)
+            .await
+            .context(ListFilesSnafu)
+    }
+
     fn projection_pushdown_to_file(
         &self,
         req_projection: &Option<Vec<usize>>,
@@ -0,0 +1,84 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Records queries and writes by their sources, i.e. the protocol, the user and the client.
+//!
+//! The audit log is written to the [AUDIT_LOG_TARGET] target at the debug level, it can be
+//! enabled by a log level like `info,audit=debug`.
+
+use common_telemetry::debug;
+use session::context::QueryContextRef;
+
+use crate::metrics::{METRIC_REQUESTS_BY_SOURCE, METRIC_WRITE_ROWS_BY_SOURCE};
+
+pub(crate) const AUDIT_LOG_TARGET: &str = "audit";
+
+/// Records a query, the `query` is logged so secrets in it must be redacted.
+pub(crate) fn record_query(query_ctx: &QueryContextRef, query: &str) {
+    let user = username(query_ctx);
+    METRIC_REQUESTS_BY_SOURCE
+        .with_label_values(&[
+            "query",
+            query_ctx.channel().as_str(),
+            &user,
+            query_ctx.client_name(),
+        ])
+        .inc();
+    debug!(
+        target: AUDIT_LOG_TARGET,
+        "query, {}, db: {}, query: {}",
+        source(query_ctx, &user),
+        query_ctx.get_db_string(),
+        query
+    );
+}
+
+/// Records a write of `rows` rows.
+pub(crate) fn record_write(query_ctx: &QueryContextRef, rows: usize) {
+    let user = username(query_ctx);
+    let labels = [query_ctx.channel().as_str(), &user, query_ctx.client_name()];
+    METRIC_REQUESTS_BY_SOURCE
+        .with_label_values(&["write", labels[0], labels[1], labels[2]])
+        .inc();
+    METRIC_WRITE_ROWS_BY_SOURCE
+        .with_label_values(&labels)
+        .inc_by(rows as u64);
+    debug!(
+        target: AUDIT_LOG_TARGET,
+        "write, {}, db: {}, rows: {}",
+        source(query_ctx, &user),
+        query_ctx.get_db_string(),
+        rows
+    );
+}
+
+fn username(query_ctx: &QueryContextRef) -> String {
+    query_ctx
+        .current_user()
+        .map(|user| user.username().to_string())
+        .unwrap_or_default()
+}
+
+fn source(query_ctx: &QueryContextRef, user: &str) -> String {
+    format!(
+        "channel: {}, client addr: {}, user: {}, user agent: {}",
+        query_ctx.channel(),
+        query_ctx
+            .client_addr()
+            .map(|addr| addr.to_string())
+            .unwrap_or_default(),
+        user,
+        query_ctx.user_agent().unwrap_or_default()
+    )
+}
@@ -14,6 +14,7 @@
 
 use std::any::Any;
 
+use common_base::readable_size::ReadableSize;
 use common_datasource::file_format::Format;
 use common_error::ext::{BoxedError, ErrorExt};
 use common_error::status_code::StatusCode;
@@ -264,6 +265,58 @@ pub enum Error {
     #[snafu(display("Invalid auth config"))]
     IllegalAuthConfig { source: auth::error::Error },
 
+    #[snafu(display("Failed to access write spool file: {}", path))]
+    AccessSpool {
+        path: String,
+        #[snafu(source)]
+        error: std::io::Error,
+        location: Location,
+    },
+
+    #[snafu(display("Corrupted write spool file: {}", path))]
+    CorruptedSpoolFile { path: String, location: Location },
+
+    #[snafu(display("Failed to decode write spool file: {}", path))]
+    DecodeSpoolFile {
+        path: String,
+        #[snafu(source)]
+        error: prost::DecodeError,
+        location: Location,
+    },
+
+    #[snafu(display("Write spool is full, max size: {}", max_size))]
+    SpoolFull {
+        max_size: ReadableSize,
+        location: Location,
+    },
+
+    #[snafu(display("Failed to read query rule file: {}", path))]
+    ReadQueryRuleFile {
+        path: String,
+        #[snafu(source)]
+        error: std::io::Error,
+        location: Location,
+    },
+
+    #[snafu(display("Failed to parse query rule file: {}", path))]
+    ParseQueryRuleFile {
+        path: String,
+        #[snafu(source)]
+        error: toml::de::Error,
+        location: Location,
+    },
+
+    #[snafu(display("Failed to watch query rule file: {}", path))]
+    WatchQueryRuleFile {
+        path: String,
+        #[snafu(source)]
+        error: notify::Error,
+        location: Location,
+    },
+
+    #[snafu(display("Query denied by rule: {}", reason))]
+    QueryRuleViolated { reason: String, location: Location },
+
     #[snafu(display("Failed to serialize options to TOML"))]
     TomlFormat {
         #[snafu(source)]
@@ -346,6 +399,17 @@ impl ErrorExt for Error {
             Error::StartScriptManager { source, .. } => source.status_code(),
 
             Error::TableOperation { source, .. } => source.status_code(),
+
+            Error::AccessSpool { .. } => StatusCode::StorageUnavailable,
+            Error::CorruptedSpoolFile { .. } | Error::DecodeSpoolFile { .. } => {
+                StatusCode::Unexpected
+            }
+            Error::SpoolFull { .. } => StatusCode::RuntimeResourcesExhausted,
+
+            Error::ReadQueryRuleFile { .. }
+            | Error::ParseQueryRuleFile { .. }
+            | Error::WatchQueryRuleFile { .. } => StatusCode::InvalidArguments,
+            Error::QueryRuleViolated { .. } => StatusCode::PermissionDenied,
         }
     }
 
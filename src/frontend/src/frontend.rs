@@ -18,14 +18,19 @@ use serde::{Deserialize, Serialize};
 use servers::export_metrics::ExportMetricsOption;
 use servers::heartbeat_options::HeartbeatOptions;
 use servers::http::HttpOptions;
+use servers::pipeline::PipelineOptions;
+use servers::rate_limit::RateLimitOptions;
 use servers::Mode;
 use snafu::prelude::*;
 
 use crate::error::{Result, TomlFormatSnafu};
+use crate::hedged_read::HedgedReadOptions;
+use crate::query_rule::QueryRuleOptions;
 use crate::service_config::{
-    DatanodeOptions, GrpcOptions, InfluxdbOptions, MysqlOptions, OpentsdbOptions, OtlpOptions,
-    PostgresOptions, PromStoreOptions,
+    DatanodeOptions, FlightSqlOptions, GrpcOptions, InfluxdbOptions, LokiOptions, MqttOptions,
+    MysqlOptions, OpentsdbOptions, OtlpOptions, PostgresOptions, PromStoreOptions,
 };
+use crate::spool::SpoolOptions;
 
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(default)]
@@ -37,15 +42,26 @@ pub struct FrontendOptions {
     pub grpc: GrpcOptions,
     pub mysql: MysqlOptions,
     pub postgres: PostgresOptions,
+    pub flight_sql: FlightSqlOptions,
     pub opentsdb: OpentsdbOptions,
     pub influxdb: InfluxdbOptions,
     pub prom_store: PromStoreOptions,
     pub otlp: OtlpOptions,
+    pub loki: LokiOptions,
+    pub mqtt: MqttOptions,
+    pub pipeline: PipelineOptions,
+    pub rate_limit: RateLimitOptions,
+    pub spool: SpoolOptions,
+    pub hedged_read: HedgedReadOptions,
+    pub query_rule: QueryRuleOptions,
     pub meta_client: Option<MetaClientOptions>,
     pub logging: LoggingOptions,
     pub datanode: DatanodeOptions,
     pub user_provider: Option<String>,
     pub export_metrics: ExportMetricsOption,
+    /// Directory `ADMIN capture_query` writes captures under, capturing queries is disabled
+    /// if it's absent.
+    pub capture_dir: Option<String>,
 }
 
 impl Default for FrontendOptions {
@@ -58,15 +74,24 @@ impl Default for FrontendOptions {
             grpc: GrpcOptions::default(),
             mysql: MysqlOptions::default(),
             postgres: PostgresOptions::default(),
+            flight_sql: FlightSqlOptions::default(),
             opentsdb: OpentsdbOptions::default(),
             influxdb: InfluxdbOptions::default(),
             prom_store: PromStoreOptions::default(),
             otlp: OtlpOptions::default(),
+            loki: LokiOptions::default(),
+            mqtt: MqttOptions::default(),
+            pipeline: PipelineOptions::default(),
+            rate_limit: RateLimitOptions::default(),
+            spool: SpoolOptions::default(),
+            hedged_read: HedgedReadOptions::default(),
+            query_rule: QueryRuleOptions::default(),
             meta_client: None,
             logging: LoggingOptions::default(),
             datanode: DatanodeOptions::default(),
             user_provider: None,
             export_metrics: ExportMetricsOption::default(),
+            capture_dir: None,
         }
     }
 }
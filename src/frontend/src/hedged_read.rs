@@ -0,0 +1,187 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Hedged reads to follower replicas.
+//!
+//! A query to a region is sent to its leader first. If the leader doesn't respond within
+//! a percentile of recent response latencies, the query is also sent to a follower and
+//! the first successful response is taken. Hedged queries are limited by a budget, which
+//! is a ratio of all queries, to limit the extra load.
+
+use std::sync::atomic::{AtomicI64, AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+/// Number of recent latencies to compute the percentile.
+const LATENCY_WINDOW: usize = 1024;
+/// Number of latencies recorded between two updates of the threshold.
+const THRESHOLD_UPDATE_INTERVAL: u64 = 64;
+/// Tokens consumed by a hedged query.
+const HEDGE_COST: i64 = 1000;
+/// Max hedged queries allowed in a burst.
+const MAX_BURST: i64 = 10;
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct HedgedReadOptions {
+    /// Whether to hedge queries to follower replicas.
+    pub enable: bool,
+    /// Percentile of recent latencies of leaders to wait before hedging, in (0, 1].
+    pub percentile: f64,
+    /// Min time to wait before hedging.
+    #[serde(with = "humantime_serde")]
+    pub min_delay: Duration,
+    /// Max ratio of hedged queries to all queries.
+    pub budget_ratio: f64,
+}
+
+impl Default for HedgedReadOptions {
+    fn default() -> Self {
+        Self {
+            enable: false,
+            percentile: 0.95,
+            min_delay: Duration::from_millis(10),
+            budget_ratio: 0.05,
+        }
+    }
+}
+
+/// Latencies of recent queries.
+#[derive(Debug)]
+struct LatencyWindow {
+    /// Latencies in microseconds.
+    samples: Vec<u64>,
+    next: usize,
+}
+
+/// Decides when to hedge queries.
+#[derive(Debug)]
+pub(crate) struct HedgePolicy {
+    percentile: f64,
+    min_delay: Duration,
+    /// Tokens added by every query, a hedged query costs [HEDGE_COST] tokens.
+    tokens_per_query: i64,
+    tokens: AtomicI64,
+    window: Mutex<LatencyWindow>,
+    recorded: AtomicU64,
+    /// Current threshold in microseconds.
+    threshold_us: AtomicU64,
+}
+
+impl HedgePolicy {
+    /// Returns the policy if hedged reads are enabled.
+    pub(crate) fn new(opts: &HedgedReadOptions) -> Option<Self> {
+        if !opts.enable {
+            return None;
+        }
+
+        Some(Self {
+            percentile: opts.percentile.clamp(0.0, 1.0),
+            min_delay: opts.min_delay,
+            tokens_per_query: (opts.budget_ratio.clamp(0.0, 1.0) * HEDGE_COST as f64) as i64,
+            tokens: AtomicI64::new(0),
+            window: Mutex::new(LatencyWindow {
+                samples: Vec::with_capacity(LATENCY_WINDOW),
+                next: 0,
+            }),
+            recorded: AtomicU64::new(0),
+            threshold_us: AtomicU64::new(opts.min_delay.as_micros() as u64),
+        })
+    }
+
+    /// Returns the time to wait for the leader before hedging, and adds tokens of the query
+    /// to the budget.
+    pub(crate) fn delay(&self) -> Duration {
+        let max_tokens = MAX_BURST * HEDGE_COST;
+        let _ = self
+            .tokens
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |tokens| {
+                Some((tokens + self.tokens_per_query).min(max_tokens))
+            });
+
+        Duration::from_micros(self.threshold_us.load(Ordering::Relaxed)).max(self.min_delay)
+    }
+
+    /// Tries to take the budget of a hedged query.
+    pub(crate) fn try_hedge(&self) -> bool {
+        self.tokens
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |tokens| {
+                (tokens >= HEDGE_COST).then_some(tokens - HEDGE_COST)
+            })
+            .is_ok()
+    }
+
+    /// Records the latency of a query to the leader.
+    pub(crate) fn record(&self, latency: Duration) {
+        let latency_us = latency.as_micros() as u64;
+        let mut window = self.window.lock().unwrap();
+        if window.samples.len() < LATENCY_WINDOW {
+            window.samples.push(latency_us);
+        } else {
+            let next = window.next;
+            window.samples[next] = latency_us;
+        }
+        window.next = (window.next + 1) % LATENCY_WINDOW;
+
+        if self.recorded.fetch_add(1, Ordering::Relaxed) % THRESHOLD_UPDATE_INTERVAL == 0 {
+            let mut samples = window.samples.clone();
+            drop(window);
+            samples.sort_unstable();
+            let idx = ((samples.len() as f64 * self.percentile).ceil() as usize)
+                .clamp(1, samples.len())
+                - 1;
+            self.threshold_us.store(samples[idx], Ordering::Relaxed);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hedge_policy() {
+        assert!(HedgePolicy::new(&HedgedReadOptions::default()).is_none());
+
+        let policy = HedgePolicy::new(&HedgedReadOptions {
+            enable: true,
+            percentile: 0.9,
+            min_delay: Duration::from_millis(1),
+            budget_ratio: 0.1,
+        })
+        .unwrap();
+        assert_eq!(Duration::from_millis(1), policy.delay());
+
+        for ms in 1..=(THRESHOLD_UPDATE_INTERVAL + 1) {
+            policy.record(Duration::from_millis(ms));
+        }
+        // The threshold is updated by the first and the last latencies.
+        assert_eq!(Duration::from_millis(59), policy.delay());
+
+        // Budget of 10% queries.
+        assert!(!policy.try_hedge());
+        for _ in 0..8 {
+            let _ = policy.delay();
+        }
+        assert!(policy.try_hedge());
+        assert!(!policy.try_hedge());
+        for _ in 0..1000 {
+            let _ = policy.delay();
+        }
+        let hedged = (0..100).filter(|_| policy.try_hedge()).count();
+        assert_eq!(MAX_BURST as usize, hedged);
+    }
+}
@@ -15,12 +15,16 @@
 pub mod builder;
 mod grpc;
 mod influxdb;
+mod loki;
+mod mqtt;
 mod opentsdb;
 mod otlp;
+mod pipeline;
 mod prom_store;
 mod region_query;
 mod script;
 pub mod standalone;
+mod upload;
 
 use std::sync::Arc;
 
@@ -40,6 +44,7 @@ use common_procedure::ProcedureManagerRef;
 use common_query::Output;
 use common_telemetry::error;
 use common_telemetry::logging::info;
+use common_time::range::TimestampRange;
 use log_store::raft_engine::RaftEngineBackend;
 use meta_client::client::{MetaClient, MetaClientBuilder};
 use meta_client::MetaClientOptions;
@@ -59,22 +64,26 @@ use servers::export_metrics::{ExportMetricsOption, ExportMetricsTask};
 use servers::interceptor::{
     PromQueryInterceptor, PromQueryInterceptorRef, SqlQueryInterceptor, SqlQueryInterceptorRef,
 };
+use servers::pipeline::{PipelineOptions, PipelineRunner};
 use servers::prometheus_handler::PrometheusHandler;
 use servers::query_handler::grpc::GrpcQueryHandler;
 use servers::query_handler::sql::SqlQueryHandler;
 use servers::query_handler::{
-    InfluxdbLineProtocolHandler, OpenTelemetryProtocolHandler, OpentsdbProtocolHandler,
-    PromStoreProtocolHandler, ScriptHandler,
+    InfluxdbLineProtocolHandler, LokiProtocolHandler, MqttProtocolHandler,
+    OpenTelemetryProtocolHandler, OpentsdbProtocolHandler, PromStoreProtocolHandler, ScriptHandler,
+    UploadHandler,
 };
 use servers::server::{start_server, ServerHandlers};
 use session::context::QueryContextRef;
 use snafu::prelude::*;
 use sql::dialect::Dialect;
 use sql::parser::ParserContext;
+use sql::statements::admin::Admin;
 use sql::statements::copy::CopyTable;
 use sql::statements::statement::Statement;
 use sqlparser::ast::ObjectName;
-pub use standalone::StandaloneDatanodeManager;
+pub use standalone::{StandaloneDatanodeManager, StandaloneRegionCapturer};
+use table::engine::TableReference;
 
 use crate::error::{
     self, Error, ExecLogicalPlanSnafu, ExecutePromqlSnafu, ExternalSnafu, ParseSqlSnafu,
@@ -83,9 +92,13 @@ use crate::error::{
 };
 use crate::frontend::{FrontendOptions, TomlSerializable};
 use crate::heartbeat::HeartbeatTask;
-use crate::metrics;
+use crate::query_rule::{
+    maybe_watch_rule_file, QueryRuleEngine, QueryRuleEngineRef, QueryRuleOptions,
+};
 use crate::script::ScriptExecutor;
 use crate::server::Services;
+use crate::spool::{SpoolOptions, WriteSpool, WriteSpoolRef};
+use crate::{audit, metrics};
 
 #[async_trait]
 pub trait FrontendInstance:
@@ -93,6 +106,9 @@ pub trait FrontendInstance:
     + SqlQueryHandler<Error = Error>
     + OpentsdbProtocolHandler
     + InfluxdbLineProtocolHandler
+    + MqttProtocolHandler
+    + LokiProtocolHandler
+    + UploadHandler
     + PromStoreProtocolHandler
     + OpenTelemetryProtocolHandler
     + ScriptHandler
@@ -119,6 +135,10 @@ pub struct Instance {
     inserter: InserterRef,
     deleter: DeleterRef,
     export_metrics_task: Option<ExportMetricsTask>,
+    kv_backend: KvBackendRef,
+    pipeline_runner: Option<PipelineRunner>,
+    write_spool: Option<WriteSpoolRef>,
+    query_rule: Option<QueryRuleEngineRef>,
 }
 
 impl Instance {
@@ -202,6 +222,29 @@ impl Instance {
         Ok(())
     }
 
+    /// Builds the runner of pipelines if pipelines are enabled on this frontend.
+    pub fn build_pipeline_runner(&mut self, opts: &PipelineOptions) {
+        self.pipeline_runner =
+            PipelineRunner::new(opts, self.kv_backend.clone(), Arc::new(self.clone()));
+    }
+
+    /// Opens the spool of row inserts if it's enabled.
+    pub async fn build_write_spool(&mut self, opts: &SpoolOptions) -> Result<()> {
+        self.write_spool = WriteSpool::open(opts).await?;
+        Ok(())
+    }
+
+    /// Builds the engine of query rules if any rule is configured.
+    pub fn build_query_rule(&mut self, opts: &QueryRuleOptions) -> Result<()> {
+        if opts.is_empty() {
+            return Ok(());
+        }
+        let query_rule = Arc::new(QueryRuleEngine::try_new(opts)?);
+        maybe_watch_rule_file(query_rule.clone())?;
+        self.query_rule = Some(query_rule);
+        Ok(())
+    }
+
     pub fn catalog_manager(&self) -> &CatalogManagerRef {
         &self.catalog_manager
     }
@@ -235,6 +278,14 @@ impl FrontendInstance for Instance {
             t.start()
         }
 
+        if let Some(runner) = self.pipeline_runner.as_ref() {
+            runner.start()
+        }
+
+        if let Some(spool) = self.write_spool.as_ref() {
+            spool.start(self.inserter.clone(), self.statement_executor.clone())
+        }
+
         futures::future::try_join_all(self.servers.iter().map(|(name, handler)| async move {
             info!("Starting service: {name}");
             start_server(handler).await
@@ -250,8 +301,38 @@ fn parse_stmt(sql: &str, dialect: &(dyn Dialect + Send + Sync)) -> Result<Vec<St
 }
 
 impl Instance {
-    async fn query_statement(&self, stmt: Statement, query_ctx: QueryContextRef) -> Result<Output> {
+    async fn query_statement(
+        &self,
+        mut stmt: Statement,
+        query_ctx: QueryContextRef,
+    ) -> Result<Output> {
         check_permission(self.plugins.clone(), &stmt, &query_ctx)?;
+        match &stmt {
+            Statement::Admin(Admin::CaptureQuery { query, .. }) => {
+                self.check_capture_permission(query, &query_ctx)?;
+            }
+            Statement::CreateFunction(_) => self.check_admin_permission(&query_ctx)?,
+            _ => {}
+        }
+
+        if let Some(query_rule) = &self.query_rule
+            && matches!(stmt, Statement::Query(_))
+        {
+            query_rule.rewrite_statement(&mut stmt, &query_ctx);
+            if query_rule.need_check_plan() {
+                let plan = self
+                    .statement_executor
+                    .plan(QueryStatement::Sql(stmt), query_ctx.clone())
+                    .await
+                    .context(TableOperationSnafu)?;
+                query_rule.check_plan(&self.query_engine, &plan)?;
+                return self
+                    .query_engine
+                    .execute(plan, query_ctx)
+                    .await
+                    .context(ExecLogicalPlanSnafu);
+            }
+        }
 
         let stmt = QueryStatement::Sql(stmt);
         self.statement_executor
@@ -259,6 +340,31 @@ impl Instance {
             .await
             .context(TableOperationSnafu)
     }
+
+    /// Checks whether the user can capture the `query`, which requires the admin permission
+    /// and the permission to run the query. Other checks of the query are done by
+    /// [check_permission].
+    fn check_capture_permission(&self, query: &str, query_ctx: &QueryContextRef) -> Result<()> {
+        self.check_admin_permission(query_ctx)?;
+        let checker_ref = self.plugins.get::<PermissionCheckerRef>();
+        let checker = checker_ref.as_ref();
+        for stmt in parse_stmt(query, query_ctx.sql_dialect())? {
+            let _ = checker
+                .check_permission(query_ctx.current_user(), PermissionReq::SqlStatement(&stmt))
+                .context(PermissionSnafu)?;
+        }
+        Ok(())
+    }
+
+    /// Checks whether the user has privileges of administrators.
+    fn check_admin_permission(&self, query_ctx: &QueryContextRef) -> Result<()> {
+        let checker_ref = self.plugins.get::<PermissionCheckerRef>();
+        let _ = checker_ref
+            .as_ref()
+            .check_permission(query_ctx.current_user(), PermissionReq::Admin)
+            .context(PermissionSnafu)?;
+        Ok(())
+    }
 }
 
 #[async_trait]
@@ -267,6 +373,7 @@ impl SqlQueryHandler for Instance {
 
     async fn do_query(&self, query: &str, query_ctx: QueryContextRef) -> Vec<Result<Output>> {
         let _timer = metrics::METRIC_HANDLE_SQL_ELAPSED.start_timer();
+        audit::record_query(&query_ctx, &sql::util::redact_sql_secrets(query));
         let query_interceptor_opt = self.plugins.get::<SqlQueryInterceptorRef<Error>>();
         let query_interceptor = query_interceptor_opt.as_ref();
         let query = match query_interceptor.pre_parsing(query, query_ctx.clone()) {
@@ -340,6 +447,7 @@ impl SqlQueryHandler for Instance {
         query: &PromQuery,
         query_ctx: QueryContextRef,
     ) -> Vec<Result<Output>> {
+        audit::record_query(&query_ctx, &query.query);
         // check will be done in prometheus handler's do_query
         let result = PrometheusHandler::do_query(self, query, query_ctx)
             .await
@@ -423,6 +531,29 @@ impl PrometheusHandler for Instance {
         Ok(interceptor.post_execute(output, query_ctx)?)
     }
 
+    async fn tag_values(
+        &self,
+        table_ref: TableReference<'_>,
+        column: &str,
+        range: Option<&TimestampRange>,
+        limit: usize,
+        query_ctx: QueryContextRef,
+    ) -> server_error::Result<Vec<String>> {
+        self.plugins
+            .get::<PermissionCheckerRef>()
+            .as_ref()
+            .check_permission(query_ctx.current_user(), PermissionReq::PromQuery)
+            .context(AuthSnafu)?;
+
+        self.statement_executor
+            .tag_values(table_ref, column, range, limit, query_ctx)
+            .await
+            .map_err(BoxedError::new)
+            .with_context(|_| ExecuteQuerySnafu {
+                query: format!("tag values of {column} in {table_ref}"),
+            })
+    }
+
     fn catalog_manager(&self) -> CatalogManagerRef {
         self.catalog_manager.clone()
     }
@@ -444,7 +575,11 @@ pub fn check_permission(
 
     match stmt {
         // These are executed by query engine, and will be checked there.
-        Statement::Query(_) | Statement::Explain(_) | Statement::Tql(_) | Statement::Delete(_) => {}
+        Statement::Query(_)
+        | Statement::Explain(_)
+        | Statement::Tql(_)
+        | Statement::Delete(_)
+        | Statement::Copy(sql::statements::copy::Copy::CopyQueryTo(_)) => {}
         // database ops won't be checked
         Statement::CreateDatabase(_) | Statement::ShowDatabases(_) => {}
         // show create table and alter are not supported yet
@@ -482,6 +617,22 @@ pub fn check_permission(
         Statement::TruncateTable(stmt) => {
             validate_param(stmt.table_name(), query_ctx)?;
         }
+        Statement::CreatePipeline(stmt) => {
+            validate_param(&stmt.name, query_ctx)?;
+        }
+        Statement::DropPipeline(stmt) => {
+            validate_param(stmt.name(), query_ctx)?;
+        }
+        Statement::CreateFunction(stmt) => {
+            validate_param(&stmt.name, query_ctx)?;
+        }
+        Statement::Admin(Admin::CaptureQuery { query, .. }) => {
+            for stmt in parse_stmt(query, query_ctx.sql_dialect())? {
+                check_permission(plugins.clone(), &stmt, query_ctx)?;
+            }
+        }
+        // other admin functions don't refer to any database
+        Statement::Admin(_) => {}
     }
     Ok(())
 }
@@ -607,6 +758,10 @@ mod tests {
 
         // test describe table
         let sql = "DESC TABLE {catalog}{schema}demo;";
+        replace_test(sql, plugins.clone(), &query_ctx);
+
+        // test statements captured by admin functions
+        let sql = "ADMIN capture_query('DROP TABLE {catalog}{schema}demo', 'q1');";
         replace_test(sql, plugins, &query_ctx);
     }
 }
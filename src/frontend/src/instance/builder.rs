@@ -21,15 +21,18 @@ use common_meta::cache_invalidator::{CacheInvalidatorRef, DummyCacheInvalidator}
 use common_meta::datanode_manager::DatanodeManagerRef;
 use common_meta::ddl::DdlTaskExecutorRef;
 use common_meta::kv_backend::KvBackendRef;
+use operator::capture::RegionCapturerRef;
 use operator::delete::Deleter;
 use operator::insert::Inserter;
 use operator::statement::StatementExecutor;
 use operator::table::TableMutationOperator;
 use partition::manager::PartitionRuleManager;
 use query::QueryEngineFactory;
+use servers::tail::{TailHub, TailHubRef};
 
 use crate::error::Result;
 use crate::heartbeat::HeartbeatTask;
+use crate::hedged_read::HedgedReadOptions;
 use crate::instance::region_query::FrontendRegionQueryHandler;
 use crate::instance::{Instance, StatementExecutorRef};
 use crate::script::ScriptExecutor;
@@ -41,6 +44,9 @@ pub struct FrontendBuilder {
     plugins: Option<Plugins>,
     ddl_task_executor: DdlTaskExecutorRef,
     heartbeat_task: Option<HeartbeatTask>,
+    hedged_read: HedgedReadOptions,
+    region_capturer: Option<RegionCapturerRef>,
+    capture_dir: Option<String>,
 }
 
 impl FrontendBuilder {
@@ -56,6 +62,9 @@ impl FrontendBuilder {
             plugins: None,
             ddl_task_executor,
             heartbeat_task: None,
+            hedged_read: HedgedReadOptions::default(),
+            region_capturer: None,
+            capture_dir: None,
         }
     }
 
@@ -80,6 +89,27 @@ impl FrontendBuilder {
         }
     }
 
+    pub fn with_hedged_read(self, hedged_read: HedgedReadOptions) -> Self {
+        Self {
+            hedged_read,
+            ..self
+        }
+    }
+
+    pub fn with_region_capturer(self, region_capturer: RegionCapturerRef) -> Self {
+        Self {
+            region_capturer: Some(region_capturer),
+            ..self
+        }
+    }
+
+    pub fn with_capture_dir(self, capture_dir: Option<String>) -> Self {
+        Self {
+            capture_dir,
+            ..self
+        }
+    }
+
     pub async fn try_build(self) -> Result<Instance> {
         let kv_backend = self.kv_backend;
         let datanode_manager = self.datanode_manager;
@@ -93,14 +123,21 @@ impl FrontendBuilder {
 
         let partition_manager = Arc::new(PartitionRuleManager::new(kv_backend.clone()));
 
-        let region_query_handler =
-            FrontendRegionQueryHandler::arc(partition_manager.clone(), datanode_manager.clone());
-
-        let inserter = Arc::new(Inserter::new(
-            catalog_manager.clone(),
+        let region_query_handler = FrontendRegionQueryHandler::arc(
             partition_manager.clone(),
             datanode_manager.clone(),
-        ));
+            &self.hedged_read,
+        );
+
+        let tail_hub = Arc::new(TailHub::default());
+        let inserter = Arc::new(
+            Inserter::new(
+                catalog_manager.clone(),
+                partition_manager.clone(),
+                datanode_manager.clone(),
+            )
+            .with_tail_hub(tail_hub.clone()),
+        );
         let deleter = Arc::new(Deleter::new(
             catalog_manager.clone(),
             partition_manager,
@@ -123,16 +160,24 @@ impl FrontendBuilder {
         let script_executor =
             Arc::new(ScriptExecutor::new(catalog_manager.clone(), query_engine.clone()).await?);
 
-        let statement_executor = Arc::new(StatementExecutor::new(
+        let mut statement_executor = StatementExecutor::new(
             catalog_manager.clone(),
             query_engine.clone(),
             self.ddl_task_executor,
-            kv_backend,
+            kv_backend.clone(),
             catalog_manager.clone(),
             inserter.clone(),
-        ));
+        );
+        if let Some(region_capturer) = self.region_capturer {
+            statement_executor = statement_executor.with_region_capturer(region_capturer);
+        }
+        if let Some(capture_dir) = self.capture_dir {
+            statement_executor = statement_executor.with_capture_dir(capture_dir);
+        }
+        let statement_executor = Arc::new(statement_executor);
 
         plugins.insert::<StatementExecutorRef>(statement_executor.clone());
+        plugins.insert::<TailHubRef>(tail_hub);
 
         Ok(Instance {
             catalog_manager,
@@ -145,6 +190,10 @@ impl FrontendBuilder {
             inserter,
             deleter,
             export_metrics_task: None,
+            kv_backend,
+            pipeline_runner: None,
+            write_spool: None,
+            query_rule: None,
         })
     }
 }
@@ -18,8 +18,10 @@ use api::v1::query_request::Query;
 use api::v1::{DeleteRequests, InsertRequests, RowDeleteRequests, RowInsertRequests};
 use async_trait::async_trait;
 use auth::{PermissionChecker, PermissionCheckerRef, PermissionReq};
+use common_error::ext::ErrorExt;
 use common_meta::table_name::TableName;
 use common_query::Output;
+use common_telemetry::logging::warn;
 use query::parser::PromQuery;
 use servers::interceptor::{GrpcQueryInterceptor, GrpcQueryInterceptorRef};
 use servers::query_handler::grpc::GrpcQueryHandler;
@@ -27,6 +29,7 @@ use servers::query_handler::sql::SqlQueryHandler;
 use session::context::QueryContextRef;
 use snafu::{ensure, OptionExt, ResultExt};
 
+use crate::audit;
 use crate::error::{
     Error, IncompleteGrpcRequestSnafu, NotSupportedSnafu, PermissionSnafu, Result,
     TableOperationSnafu,
@@ -49,8 +52,13 @@ impl GrpcQueryHandler for Instance {
             .context(PermissionSnafu)?;
 
         let output = match request {
-            Request::Inserts(requests) => self.handle_inserts(requests, ctx.clone()).await?,
-            Request::RowInserts(requests) => self.handle_row_inserts(requests, ctx.clone()).await?,
+            Request::Inserts(requests) => {
+                self.handle_inserts(requests, ctx.clone(), "grpc").await?
+            }
+            Request::RowInserts(requests) => {
+                self.handle_row_inserts(requests, ctx.clone(), "grpc")
+                    .await?
+            }
             Request::Deletes(requests) => self.handle_deletes(requests, ctx.clone()).await?,
             Request::RowDeletes(requests) => self.handle_row_deletes(requests, ctx.clone()).await?,
             Request::Query(query_request) => {
@@ -140,6 +148,12 @@ impl GrpcQueryHandler for Instance {
     }
 }
 
+fn record_write(ctx: &QueryContextRef, output: &Output) {
+    if let Output::AffectedRows(rows) = output {
+        audit::record_write(ctx, *rows);
+    }
+}
+
 fn fill_catalog_and_schema_from_context(ddl_expr: &mut DdlExpr, ctx: &QueryContextRef) {
     let catalog = ctx.current_catalog();
     let schema = ctx.current_schema();
@@ -173,26 +187,80 @@ fn fill_catalog_and_schema_from_context(ddl_expr: &mut DdlExpr, ctx: &QueryConte
 }
 
 impl Instance {
+    /// Handles inserts ingested by `protocol`, e.g. "grpc".
     pub async fn handle_inserts(
         &self,
         requests: InsertRequests,
         ctx: QueryContextRef,
+        protocol: &str,
     ) -> Result<Output> {
-        self.inserter
-            .handle_column_inserts(requests, ctx, self.statement_executor.as_ref())
+        let output = self
+            .inserter
+            .handle_column_inserts(
+                requests,
+                ctx.clone(),
+                protocol,
+                self.statement_executor.as_ref(),
+            )
             .await
-            .context(TableOperationSnafu)
+            .context(TableOperationSnafu)?;
+        record_write(&ctx, &output);
+        Ok(output)
     }
 
+    /// Handles row inserts ingested by `protocol`, e.g. "influxdb".
     pub async fn handle_row_inserts(
         &self,
         requests: RowInsertRequests,
         ctx: QueryContextRef,
+        protocol: &str,
+    ) -> Result<Output> {
+        let output = self.do_row_inserts(requests, ctx.clone(), protocol).await?;
+        record_write(&ctx, &output);
+        Ok(output)
+    }
+
+    async fn do_row_inserts(
+        &self,
+        requests: RowInsertRequests,
+        ctx: QueryContextRef,
+        protocol: &str,
     ) -> Result<Output> {
-        self.inserter
-            .handle_row_inserts(requests, ctx, self.statement_executor.as_ref())
+        let Some(spool) = &self.write_spool else {
+            return self
+                .inserter
+                .handle_row_inserts(requests, ctx, protocol, self.statement_executor.as_ref())
+                .await
+                .context(TableOperationSnafu);
+        };
+
+        // Spools the requests if there are inserts to replay, so they are written in order.
+        if !spool.is_empty().await {
+            return spool.append(&requests, &ctx, protocol).await;
+        }
+        match self
+            .inserter
+            .handle_row_inserts(
+                requests.clone(),
+                ctx.clone(),
+                protocol,
+                self.statement_executor.as_ref(),
+            )
             .await
-            .context(TableOperationSnafu)
+        {
+            Ok(output) => Ok(output),
+            Err(e) if e.status_code().is_retryable() => {
+                warn!(e; "Failed to insert rows, spool them to replay later");
+                match spool.append(&requests, &ctx, protocol).await {
+                    Ok(output) => Ok(output),
+                    Err(spool_error) => {
+                        warn!(spool_error; "Failed to spool rows");
+                        Err(e).context(TableOperationSnafu)
+                    }
+                }
+            }
+            Err(e) => Err(e).context(TableOperationSnafu),
+        }
     }
 
     pub async fn handle_deletes(
@@ -40,7 +40,7 @@ impl OpentsdbProtocolHandler for Instance {
 
         let (requests, _) = data_point_to_grpc_row_insert_requests(data_points)?;
         let output = self
-            .handle_row_inserts(requests, ctx)
+            .handle_row_inserts(requests, ctx, "opentsdb")
             .await
             .map_err(BoxedError::new)
             .context(servers::error::ExecuteGrpcQuerySnafu)?;
@@ -13,7 +13,10 @@
 // limitations under the License.
 
 use api::prom_store::remote::read_request::ResponseType;
-use api::prom_store::remote::{Query, QueryResult, ReadRequest, ReadResponse, WriteRequest};
+use api::prom_store::remote::{
+    ChunkedReadResponse, Query, QueryResult, ReadRequest, ReadResponse, TimeSeries, WriteRequest,
+};
+use api::v1::RowInsertRequests;
 use async_trait::async_trait;
 use auth::{PermissionChecker, PermissionCheckerRef, PermissionReq};
 use common_catalog::format_full_table_name;
@@ -23,7 +26,8 @@ use common_recordbatch::RecordBatches;
 use common_telemetry::logging;
 use prost::Message;
 use servers::error::{self, AuthSnafu, Result as ServerResult};
-use servers::prom_store::{self, Metrics};
+use servers::prom_store::chunk::{self, STREAMED_CONTENT_TYPE};
+use servers::prom_store::{self, Metrics, PromWriteOptions};
 use servers::query_handler::{PromStoreProtocolHandler, PromStoreResponse};
 use session::context::QueryContextRef;
 use snafu::{OptionExt, ResultExt};
@@ -35,12 +39,12 @@ use crate::error::{
 use crate::instance::Instance;
 use crate::metrics::PROM_STORE_REMOTE_WRITE_SAMPLES;
 
-const SAMPLES_RESPONSE_TYPE: i32 = ResponseType::Samples as i32;
-
 #[inline]
 fn is_supported(response_type: i32) -> bool {
-    // Only supports samples response right now
-    response_type == SAMPLES_RESPONSE_TYPE
+    matches!(
+        ResponseType::try_from(response_type),
+        Ok(ResponseType::Samples | ResponseType::StreamedXorChunks)
+    )
 }
 
 /// Negotiating the content type of the remote read response.
@@ -62,19 +66,23 @@ fn negotiate_response_type(accepted_response_types: &[i32]) -> ServerResult<Resp
             ),
         })?;
 
-    // It's safe to unwrap here, we known that it should be SAMPLES_RESPONSE_TYPE
+    // It's safe to unwrap here, we known that it's a supported response type
     Ok(ResponseType::try_from(*response_type).unwrap())
 }
 
-async fn to_query_result(table_name: &str, output: Output) -> ServerResult<QueryResult> {
+async fn to_timeseries(table_name: &str, output: Output) -> ServerResult<Vec<TimeSeries>> {
     let Output::Stream(stream) = output else {
         unreachable!()
     };
     let recordbatches = RecordBatches::try_collect(stream)
         .await
         .context(error::CollectRecordbatchSnafu)?;
+    prom_store::recordbatches_to_timeseries(table_name, recordbatches)
+}
+
+async fn to_query_result(table_name: &str, output: Output) -> ServerResult<QueryResult> {
     Ok(QueryResult {
-        timeseries: prom_store::recordbatches_to_timeseries(table_name, recordbatches)?,
+        timeseries: to_timeseries(table_name, output).await?,
     })
 }
 
@@ -143,19 +151,78 @@ impl Instance {
         }
         Ok(results)
     }
+
+    /// Removes columns that don't exist in tables from `requests`, so the write doesn't
+    /// add columns to existing tables. Requests to tables that don't exist are kept.
+    async fn remove_new_columns(
+        &self,
+        requests: &mut RowInsertRequests,
+        ctx: &QueryContextRef,
+    ) -> Result<()> {
+        let catalog_name = ctx.current_catalog();
+        let schema_name = ctx.current_schema();
+
+        for request in &mut requests.inserts {
+            let Some(rows) = request.rows.as_mut() else {
+                continue;
+            };
+            let Some(table) = self
+                .catalog_manager
+                .table(catalog_name, schema_name, &request.table_name)
+                .await
+                .context(CatalogSnafu)?
+            else {
+                continue;
+            };
+
+            let table_schema = table.schema();
+            let retained = rows
+                .schema
+                .iter()
+                .map(|column| {
+                    table_schema
+                        .column_schema_by_name(&column.column_name)
+                        .is_some()
+                })
+                .collect::<Vec<_>>();
+            if retained.iter().all(|retain| *retain) {
+                continue;
+            }
+
+            let mut retain_iter = retained.iter();
+            rows.schema.retain(|_| *retain_iter.next().unwrap());
+            for row in &mut rows.rows {
+                let mut retain_iter = retained.iter();
+                row.values.retain(|_| *retain_iter.next().unwrap_or(&false));
+            }
+        }
+
+        Ok(())
+    }
 }
 
 #[async_trait]
 impl PromStoreProtocolHandler for Instance {
-    async fn write(&self, request: WriteRequest, ctx: QueryContextRef) -> ServerResult<()> {
+    async fn write(
+        &self,
+        request: WriteRequest,
+        options: PromWriteOptions,
+        ctx: QueryContextRef,
+    ) -> ServerResult<()> {
         self.plugins
             .get::<PermissionCheckerRef>()
             .as_ref()
             .check_permission(ctx.current_user(), PermissionReq::PromStoreWrite)
             .context(AuthSnafu)?;
-        let (requests, samples) = prom_store::to_grpc_row_insert_requests(request)?;
+        let (mut requests, samples) = prom_store::to_row_insert_requests(request, &options.layout)?;
+        if !options.auto_add_columns {
+            self.remove_new_columns(&mut requests, &ctx)
+                .await
+                .map_err(BoxedError::new)
+                .context(error::ExecuteGrpcQuerySnafu)?;
+        }
         let _ = self
-            .handle_row_inserts(requests, ctx)
+            .handle_row_inserts(requests, ctx, "prom_store")
             .await
             .map_err(BoxedError::new)
             .context(error::ExecuteGrpcQuerySnafu)?;
@@ -198,10 +265,28 @@ impl PromStoreProtocolHandler for Instance {
                     body: prom_store::snappy_compress(&response.encode_to_vec())?,
                 })
             }
-            ResponseType::StreamedXorChunks => error::NotSupportedSnafu {
-                feat: "streamed remote read",
+            ResponseType::StreamedXorChunks => {
+                // Each frame contains a series, and results of queries are in the order
+                // of queries.
+                let mut body = Vec::new();
+                for (query_index, (table_name, output)) in results.into_iter().enumerate() {
+                    let timeseries = to_timeseries(&table_name, output).await?;
+                    for series in chunk::to_chunked_series(timeseries) {
+                        let response = ChunkedReadResponse {
+                            chunked_series: vec![series],
+                            query_index: query_index as i64,
+                        };
+                        chunk::encode_frame(&response, &mut body);
+                    }
+                }
+
+                // TODO(agent): write frames to the response while reading series.
+                Ok(PromStoreResponse {
+                    content_type: STREAMED_CONTENT_TYPE.to_string(),
+                    content_encoding: String::new(),
+                    body,
+                })
             }
-            .fail(),
         }
     }
 
@@ -13,33 +13,43 @@
 // limitations under the License.
 
 use std::sync::Arc;
+use std::time::Instant;
 
 use api::v1::region::QueryRequest;
 use async_trait::async_trait;
 use common_error::ext::BoxedError;
 use common_meta::datanode_manager::DatanodeManagerRef;
+use common_query::logical_plan::Expr;
 use common_recordbatch::SendableRecordBatchStream;
+use common_telemetry::logging::warn;
+use datafusion_expr::Expr as DfExpr;
 use partition::manager::PartitionRuleManagerRef;
 use query::error::{RegionQuerySnafu, Result as QueryResult};
 use query::region_query::RegionQueryHandler;
 use snafu::{OptionExt, ResultExt};
-use store_api::storage::RegionId;
+use store_api::storage::{RegionId, RegionNumber};
+use table::metadata::TableId;
 
 use crate::error::{FindDatanodeSnafu, FindTableRouteSnafu, RequestQuerySnafu, Result};
+use crate::hedged_read::{HedgePolicy, HedgedReadOptions};
+use crate::metrics::METRIC_HEDGED_READS;
 
 pub(crate) struct FrontendRegionQueryHandler {
     partition_manager: PartitionRuleManagerRef,
     datanode_manager: DatanodeManagerRef,
+    hedge_policy: Option<HedgePolicy>,
 }
 
 impl FrontendRegionQueryHandler {
     pub fn arc(
         partition_manager: PartitionRuleManagerRef,
         datanode_manager: DatanodeManagerRef,
+        hedged_read: &HedgedReadOptions,
     ) -> Arc<Self> {
         Arc::new(Self {
             partition_manager,
             datanode_manager,
+            hedge_policy: HedgePolicy::new(hedged_read),
         })
     }
 }
@@ -52,6 +62,26 @@ impl RegionQueryHandler for FrontendRegionQueryHandler {
             .map_err(BoxedError::new)
             .context(RegionQuerySnafu)
     }
+
+    async fn find_regions_by_filters(
+        &self,
+        table_id: TableId,
+        filters: &[DfExpr],
+    ) -> QueryResult<Option<Vec<RegionNumber>>> {
+        let filters = filters.iter().cloned().map(Expr::from).collect::<Vec<_>>();
+        let partition_rule = self
+            .partition_manager
+            .find_table_partition_rule(table_id)
+            .await
+            .map_err(BoxedError::new)
+            .context(RegionQuerySnafu)?;
+        let regions = self
+            .partition_manager
+            .find_regions_by_filters(partition_rule, &filters)
+            .map_err(BoxedError::new)
+            .context(RegionQuerySnafu)?;
+        Ok(Some(regions))
+    }
 }
 
 impl FrontendRegionQueryHandler {
@@ -70,12 +100,62 @@ impl FrontendRegionQueryHandler {
             .context(FindDatanodeSnafu {
                 region: region_id.region_number(),
             })?;
+        let follower = table_route
+            .find_region_followers(region_id.region_number())
+            .first();
 
         let client = self.datanode_manager.datanode(peer).await;
+        let (Some(policy), Some(follower)) = (&self.hedge_policy, follower) else {
+            return client
+                .handle_query(request)
+                .await
+                .context(RequestQuerySnafu);
+        };
 
-        client
-            .handle_query(request)
-            .await
-            .context(RequestQuerySnafu)
+        let delay = policy.delay();
+        let start = Instant::now();
+        let leader_query = client.handle_query(request.clone());
+        tokio::pin!(leader_query);
+        match tokio::time::timeout(delay, &mut leader_query).await {
+            Ok(result) => {
+                policy.record(start.elapsed());
+                return result.context(RequestQuerySnafu);
+            }
+            Err(_) if !policy.try_hedge() => {
+                let result = leader_query.await;
+                policy.record(start.elapsed());
+                return result.context(RequestQuerySnafu);
+            }
+            Err(_) => {}
+        }
+
+        METRIC_HEDGED_READS.with_label_values(&["issued"]).inc();
+        let follower_client = self.datanode_manager.datanode(follower).await;
+        let follower_query = follower_client.handle_query(request);
+        tokio::pin!(follower_query);
+        tokio::select! {
+            result = &mut leader_query => {
+                policy.record(start.elapsed());
+                match result {
+                    Ok(stream) => Ok(stream),
+                    Err(e) => {
+                        warn!(e; "Failed to query region {} on leader {:?}, wait for follower {:?}", region_id, peer, follower);
+                        follower_query.await.context(RequestQuerySnafu)
+                    }
+                }
+            }
+            result = &mut follower_query => {
+                match result {
+                    Ok(stream) => {
+                        METRIC_HEDGED_READS.with_label_values(&["won"]).inc();
+                        Ok(stream)
+                    }
+                    Err(e) => {
+                        warn!(e; "Failed to query region {} on follower {:?}, wait for leader {:?}", region_id, follower, peer);
+                        leader_query.await.context(RequestQuerySnafu)
+                    }
+                }
+            }
+        }
     }
 }
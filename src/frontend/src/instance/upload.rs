@@ -0,0 +1,63 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use api::v1::InsertRequests;
+use async_trait::async_trait;
+use auth::{PermissionChecker, PermissionCheckerRef, PermissionReq};
+use common_error::ext::BoxedError;
+use servers::error as server_error;
+use servers::error::{AuthSnafu, CatalogSnafu};
+use servers::query_handler::UploadHandler;
+use session::context::QueryContextRef;
+use snafu::ResultExt;
+use table::TableRef;
+
+use crate::instance::Instance;
+
+#[async_trait]
+impl UploadHandler for Instance {
+    async fn table(
+        &self,
+        table_name: &str,
+        ctx: QueryContextRef,
+    ) -> server_error::Result<Option<TableRef>> {
+        self.catalog_manager
+            .table(ctx.current_catalog(), ctx.current_schema(), table_name)
+            .await
+            .context(CatalogSnafu)
+    }
+
+    async fn insert(
+        &self,
+        requests: InsertRequests,
+        ctx: QueryContextRef,
+    ) -> server_error::Result<usize> {
+        self.plugins
+            .get::<PermissionCheckerRef>()
+            .as_ref()
+            .check_permission(ctx.current_user(), PermissionReq::Upload)
+            .context(AuthSnafu)?;
+
+        let output = self
+            .handle_inserts(requests, ctx, "upload")
+            .await
+            .map_err(BoxedError::new)
+            .context(servers::error::ExecuteGrpcQuerySnafu)?;
+
+        Ok(match output {
+            common_query::Output::AffectedRows(rows) => rows,
+            _ => unreachable!(),
+        })
+    }
+}
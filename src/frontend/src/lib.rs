@@ -13,12 +13,17 @@
 // limitations under the License.
 
 #![feature(assert_matches)]
+#![feature(let_chains)]
 
+mod audit;
 pub mod error;
 pub mod frontend;
 pub mod heartbeat;
+pub mod hedged_read;
 pub mod instance;
 pub(crate) mod metrics;
+pub mod query_rule;
 mod script;
 mod server;
 pub mod service_config;
+pub mod spool;
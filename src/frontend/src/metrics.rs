@@ -48,4 +48,41 @@ lazy_static! {
         "frontend otlp traces rows"
     )
     .unwrap();
+    /// Number of inserts in the write spool.
+    pub static ref METRIC_SPOOL_REQUESTS: IntGauge = register_int_gauge!(
+        "frontend_spool_requests",
+        "frontend spool requests"
+    )
+    .unwrap();
+    /// Total size of inserts in the write spool.
+    pub static ref METRIC_SPOOL_BYTES: IntGauge =
+        register_int_gauge!("frontend_spool_bytes", "frontend spool bytes").unwrap();
+    /// Events of spooled inserts: spooled, rejected, replayed, expired and failed.
+    pub static ref METRIC_SPOOL_EVENTS: IntCounterVec = register_int_counter_vec!(
+        "frontend_spool_events",
+        "frontend spool events",
+        &["event"]
+    )
+    .unwrap();
+    /// Queries and writes by their sources.
+    pub static ref METRIC_REQUESTS_BY_SOURCE: IntCounterVec = register_int_counter_vec!(
+        "frontend_requests_by_source",
+        "frontend requests by source",
+        &["kind", "channel", "user", "client"]
+    )
+    .unwrap();
+    /// Written rows by their sources.
+    pub static ref METRIC_WRITE_ROWS_BY_SOURCE: IntCounterVec = register_int_counter_vec!(
+        "frontend_write_rows_by_source",
+        "frontend write rows by source",
+        &["channel", "user", "client"]
+    )
+    .unwrap();
+    /// Hedged queries to follower replicas, `won` if the follower responds first.
+    pub static ref METRIC_HEDGED_READS: IntCounterVec = register_int_counter_vec!(
+        "frontend_hedged_reads",
+        "frontend hedged reads",
+        &["result"]
+    )
+    .unwrap();
 }
@@ -0,0 +1,449 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Rules enforcing operator policies on SQL queries.
+//!
+//! Rules are configured in the frontend options, or in a TOML file that is reloaded once
+//! it changes. Supported rules:
+//! - Deny queries scanning a table without time predicates bounding the scanned range.
+//! - Inject a default `LIMIT` into queries from interactive protocols.
+//! - Rewrite deprecated function names to their replacements.
+
+use std::collections::HashMap;
+use std::ops::ControlFlow;
+use std::path::Path;
+use std::sync::{mpsc, Arc};
+use std::time::Duration;
+
+use arc_swap::ArcSwap;
+use common_query::logical_plan::Expr;
+use common_telemetry::logging::{debug, error, info};
+use common_time::range::TimestampRange;
+use common_time::timestamp::TimeUnit;
+use datafusion::datasource::DefaultTableSource;
+use datafusion_expr::utils::split_conjunction;
+use datafusion_expr::{Expr as DfExpr, LogicalPlan as DfLogicalPlan};
+use notify::{EventKind, RecursiveMode, Watcher};
+use query::datafusion::DatafusionQueryEngine;
+use query::dist_plan::MergeScanLogicalPlan;
+use query::logical_optimizer::LogicalOptimizer;
+use query::plan::LogicalPlan;
+use query::QueryEngineRef;
+use serde::{Deserialize, Serialize};
+use session::context::QueryContextRef;
+use snafu::{ensure, ResultExt};
+use sql::statements::statement::Statement;
+use sqlparser::ast::{
+    visit_expressions_mut, Expr as SqlExpr, Ident, ObjectName, Value as SqlValue,
+};
+use table::predicate::TimeRangePredicateBuilder;
+use table::table::adapter::DfTableProviderAdapter;
+
+use crate::error::{
+    ParseQueryRuleFileSnafu, PlanStatementSnafu, QueryRuleViolatedSnafu, ReadQueryRuleFileSnafu,
+    Result, WatchQueryRuleFileSnafu,
+};
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct QueryRuleOptions {
+    /// Path of a TOML file of rules. Rules in the file replace rules in these options, and
+    /// are reloaded once the file changes.
+    pub file: Option<String>,
+    /// Denies queries scanning a table without time predicates, or whose time predicates
+    /// cover a range longer than `max_scan_range`.
+    #[serde(with = "humantime_serde")]
+    pub max_scan_range: Option<Duration>,
+    /// Limit injected into queries without a `LIMIT` clause from interactive channels.
+    pub default_limit: Option<u64>,
+    /// Channels of interactive queries, e.g. "mysql", "postgres" and "http".
+    pub interactive_channels: Vec<String>,
+    /// Deprecated function names and their replacements.
+    pub function_renames: HashMap<String, String>,
+}
+
+impl Default for QueryRuleOptions {
+    fn default() -> Self {
+        Self {
+            file: None,
+            max_scan_range: None,
+            default_limit: None,
+            interactive_channels: vec!["mysql".to_string(), "postgres".to_string()],
+            function_renames: HashMap::new(),
+        }
+    }
+}
+
+impl QueryRuleOptions {
+    /// Returns true if there is no rule to enforce.
+    pub fn is_empty(&self) -> bool {
+        self.file.is_none()
+            && self.max_scan_range.is_none()
+            && self.default_limit.is_none()
+            && self.function_renames.is_empty()
+    }
+}
+
+/// Enforces [QueryRuleOptions] on queries.
+#[derive(Debug)]
+pub struct QueryRuleEngine {
+    file: Option<String>,
+    rules: ArcSwap<QueryRuleOptions>,
+}
+
+pub type QueryRuleEngineRef = Arc<QueryRuleEngine>;
+
+impl QueryRuleEngine {
+    pub fn try_new(options: &QueryRuleOptions) -> Result<Self> {
+        let rules = match &options.file {
+            Some(file) => load_rules(file)?,
+            None => options.clone(),
+        };
+        Ok(Self {
+            file: options.file.clone(),
+            rules: ArcSwap::from_pointee(rules),
+        })
+    }
+
+    /// Reloads rules from the rule file, the current rules are kept if it fails.
+    pub fn reload(&self) -> Result<()> {
+        if let Some(file) = &self.file {
+            let rules = load_rules(file)?;
+            self.rules.store(Arc::new(rules));
+        }
+        Ok(())
+    }
+
+    /// Returns the current rules.
+    pub fn rules(&self) -> Arc<QueryRuleOptions> {
+        self.rules.load_full()
+    }
+
+    /// Returns true if plans of queries need to be checked by [QueryRuleEngine::check_plan].
+    pub fn need_check_plan(&self) -> bool {
+        self.rules.load().max_scan_range.is_some()
+    }
+
+    /// Rewrites a query statement, i.e. renames deprecated functions and injects the
+    /// default limit into queries from interactive channels.
+    pub fn rewrite_statement(&self, stmt: &mut Statement, query_ctx: &QueryContextRef) {
+        let Statement::Query(query) = stmt else {
+            return;
+        };
+        let rules = self.rules.load();
+
+        if !rules.function_renames.is_empty() {
+            let _ = visit_expressions_mut(&mut query.inner, |expr| {
+                if let SqlExpr::Function(func) = expr
+                    && let Some(name) = func.name.0.last()
+                    && let Some((_, new_name)) = rules
+                        .function_renames
+                        .iter()
+                        .find(|(old_name, _)| old_name.eq_ignore_ascii_case(&name.value))
+                {
+                    debug!("Rewrite function {} to {}", func.name, new_name);
+                    func.name = ObjectName(vec![Ident::new(new_name)]);
+                }
+                ControlFlow::<()>::Continue(())
+            });
+        }
+
+        if let Some(limit) = rules.default_limit
+            && query.inner.limit.is_none()
+            && rules
+                .interactive_channels
+                .iter()
+                .any(|channel| channel.eq_ignore_ascii_case(query_ctx.channel().as_str()))
+        {
+            query.inner.limit = Some(SqlExpr::Value(SqlValue::Number(limit.to_string(), false)));
+        }
+    }
+
+    /// Checks the plan of a query, i.e. denies scanning tables without time predicates
+    /// bounding the scanned range within `max_scan_range`.
+    ///
+    /// The plan is optimized to check predicates pushed down to table scans, the query
+    /// should still execute the original plan.
+    pub fn check_plan(&self, query_engine: &QueryEngineRef, plan: &LogicalPlan) -> Result<()> {
+        let Some(max_scan_range) = self.rules.load().max_scan_range else {
+            return Ok(());
+        };
+        let Some(engine) = query_engine
+            .as_any()
+            .downcast_ref::<DatafusionQueryEngine>()
+        else {
+            return Ok(());
+        };
+        let LogicalPlan::DfPlan(plan) = engine.optimize(plan).context(PlanStatementSnafu)?;
+
+        check_scan_range(&plan, vec![], max_scan_range)
+    }
+}
+
+fn load_rules(file: &str) -> Result<QueryRuleOptions> {
+    let content = std::fs::read_to_string(file).context(ReadQueryRuleFileSnafu { path: file })?;
+    toml::from_str(&content).context(ParseQueryRuleFileSnafu { path: file })
+}
+
+/// Checks time ranges of table scans in the plan, `filters` are predicates of filters
+/// above the plan.
+fn check_scan_range(
+    plan: &DfLogicalPlan,
+    mut filters: Vec<DfExpr>,
+    max_scan_range: Duration,
+) -> Result<()> {
+    match plan {
+        DfLogicalPlan::Filter(filter) => {
+            filters.extend(split_conjunction(&filter.predicate).into_iter().cloned());
+            check_scan_range(&filter.input, filters, max_scan_range)
+        }
+        // these plans don't change rows of the input
+        DfLogicalPlan::Projection(_)
+        | DfLogicalPlan::SubqueryAlias(_)
+        | DfLogicalPlan::Sort(_)
+        | DfLogicalPlan::Limit(_) => check_scan_range(plan.inputs()[0], filters, max_scan_range),
+        DfLogicalPlan::TableScan(scan) => {
+            let Some(table) = scan
+                .source
+                .as_any()
+                .downcast_ref::<DefaultTableSource>()
+                .and_then(|source| {
+                    source
+                        .table_provider
+                        .as_any()
+                        .downcast_ref::<DfTableProviderAdapter>()
+                })
+                .map(|adapter| adapter.table())
+            else {
+                return Ok(());
+            };
+            let schema = table.schema();
+            let Some((ts_col, ts_unit)) = schema.timestamp_column().and_then(|column| {
+                column
+                    .data_type
+                    .as_timestamp()
+                    .map(|ts_type| (&column.name, ts_type.unit()))
+            }) else {
+                return Ok(());
+            };
+
+            filters.extend(scan.filters.iter().cloned());
+            let filters = filters.into_iter().map(Expr::from).collect::<Vec<_>>();
+            let range = TimeRangePredicateBuilder::new(ts_col, ts_unit, &filters).build();
+            ensure!(
+                !exceeds_scan_range(&range, ts_unit, max_scan_range),
+                QueryRuleViolatedSnafu {
+                    reason: format!(
+                        "scanning table {} requires predicates on time index {} within {}",
+                        scan.table_name,
+                        ts_col,
+                        humantime_serde::re::humantime::format_duration(max_scan_range)
+                    ),
+                }
+            );
+            Ok(())
+        }
+        DfLogicalPlan::Extension(extension) => {
+            if let Some(merge_scan) = extension
+                .node
+                .as_any()
+                .downcast_ref::<MergeScanLogicalPlan>()
+            {
+                return check_scan_range(merge_scan.input(), filters, max_scan_range);
+            }
+            for input in plan.inputs() {
+                check_scan_range(input, vec![], max_scan_range)?;
+            }
+            Ok(())
+        }
+        _ => {
+            for input in plan.inputs() {
+                check_scan_range(input, vec![], max_scan_range)?;
+            }
+            Ok(())
+        }
+    }
+}
+
+/// Returns true if the `range` in `unit` is unbounded or longer than `max_scan_range`.
+fn exceeds_scan_range(range: &TimestampRange, unit: TimeUnit, max_scan_range: Duration) -> bool {
+    if range.is_empty() {
+        return false;
+    }
+    let (Some(start), Some(end)) = (range.start(), range.end()) else {
+        return true;
+    };
+    let range_nanos = (end.value() as i128 - start.value() as i128) * unit.factor() as i128;
+    range_nanos > max_scan_range.as_nanos() as i128
+}
+
+/// Watches the rule file and reloads rules once it changes.
+///
+/// The directory of the file is watched instead of the file, since the file may be
+/// replaced by editors or config management tools.
+pub fn maybe_watch_rule_file(engine: QueryRuleEngineRef) -> Result<()> {
+    let Some(file) = engine.file.clone() else {
+        return Ok(());
+    };
+
+    let (tx, rx) = mpsc::channel::<notify::Result<notify::Event>>();
+    let mut watcher =
+        notify::recommended_watcher(tx).context(WatchQueryRuleFileSnafu { path: &file })?;
+    let dir = Path::new(&file)
+        .parent()
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    watcher
+        .watch(dir, RecursiveMode::NonRecursive)
+        .context(WatchQueryRuleFileSnafu { path: &file })?;
+
+    let _handle = std::thread::Builder::new()
+        .name("query-rule-watcher".to_string())
+        .spawn(move || {
+            // Keeps the watcher alive while receiving events.
+            let _watcher = watcher;
+            while let Ok(event) = rx.recv() {
+                match event {
+                    Ok(event) => {
+                        if !matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_)) {
+                            continue;
+                        }
+                        debug!("Query rule file changed, event: {:?}", event);
+                        match engine.reload() {
+                            Ok(()) => info!("Reloaded query rules: {:?}", engine.rules()),
+                            // The file may be partially written, waits for the next event.
+                            Err(e) => error!(e; "Failed to reload query rules"),
+                        }
+                    }
+                    Err(e) => error!(e; "Failed to watch query rule file"),
+                }
+            }
+        })
+        .map_err(notify::Error::io)
+        .context(WatchQueryRuleFileSnafu { path: &file })?;
+    info!("Watching query rule file {}", file);
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use common_time::Timestamp;
+    use session::context::{Channel, QueryContextBuilder};
+    use sql::dialect::GreptimeDbDialect;
+    use sql::parser::ParserContext;
+
+    use super::*;
+
+    fn rewrite(engine: &QueryRuleEngine, sql: &str, channel: Channel) -> String {
+        let mut stmt = ParserContext::create_with_dialect(sql, &GreptimeDbDialect {})
+            .unwrap()
+            .remove(0);
+        let query_ctx = QueryContextBuilder::default().channel(channel).build();
+        engine.rewrite_statement(&mut stmt, &query_ctx);
+        let Statement::Query(query) = stmt else {
+            unreachable!()
+        };
+        query.inner.to_string()
+    }
+
+    #[test]
+    fn test_rewrite_statement() {
+        let options = QueryRuleOptions {
+            default_limit: Some(100),
+            function_renames: HashMap::from([("old_avg".to_string(), "avg".to_string())]),
+            ..Default::default()
+        };
+        let engine = QueryRuleEngine::try_new(&options).unwrap();
+
+        assert_eq!(
+            "SELECT avg(cpu) FROM monitor LIMIT 100",
+            rewrite(&engine, "SELECT OLD_AVG(cpu) FROM monitor", Channel::Mysql)
+        );
+        assert_eq!(
+            "SELECT cpu FROM monitor LIMIT 10",
+            rewrite(
+                &engine,
+                "SELECT cpu FROM monitor LIMIT 10",
+                Channel::Postgres
+            )
+        );
+        assert_eq!(
+            "SELECT avg(cpu) FROM monitor",
+            rewrite(&engine, "SELECT old_avg(cpu) FROM monitor", Channel::Grpc)
+        );
+    }
+
+    #[test]
+    fn test_reload_rule_file() {
+        let dir = common_test_util::temp_dir::create_temp_dir("query_rule");
+        let file = dir.path().join("rules.toml");
+        std::fs::write(&file, "default_limit = 10").unwrap();
+
+        let options = QueryRuleOptions {
+            file: Some(file.to_string_lossy().to_string()),
+            default_limit: Some(100),
+            ..Default::default()
+        };
+        let engine = QueryRuleEngine::try_new(&options).unwrap();
+        assert_eq!(Some(10), engine.rules().default_limit);
+        assert!(!engine.need_check_plan());
+
+        std::fs::write(&file, "max_scan_range = \"7d\"").unwrap();
+        engine.reload().unwrap();
+        assert_eq!(None, engine.rules().default_limit);
+        assert_eq!(
+            Some(Duration::from_secs(7 * 24 * 3600)),
+            engine.rules().max_scan_range
+        );
+        assert!(engine.need_check_plan());
+
+        // keeps current rules if the file is invalid
+        std::fs::write(&file, "max_scan_range = 1").unwrap();
+        assert!(engine.reload().is_err());
+        assert!(engine.need_check_plan());
+    }
+
+    #[test]
+    fn test_exceeds_scan_range() {
+        let max_scan_range = Duration::from_secs(3600);
+        let unit = TimeUnit::Millisecond;
+
+        assert!(exceeds_scan_range(
+            &TimestampRange::min_to_max(),
+            unit,
+            max_scan_range
+        ));
+        assert!(exceeds_scan_range(
+            &TimestampRange::from_start(Timestamp::new_millisecond(0)),
+            unit,
+            max_scan_range
+        ));
+        assert!(!exceeds_scan_range(
+            &TimestampRange::with_unit(0, 3_600_000, unit).unwrap(),
+            unit,
+            max_scan_range
+        ));
+        assert!(exceeds_scan_range(
+            &TimestampRange::with_unit(0, 3_600_001, unit).unwrap(),
+            unit,
+            max_scan_range
+        ));
+        assert!(!exceeds_scan_range(
+            &TimestampRange::empty(),
+            unit,
+            max_scan_range
+        ));
+    }
+}
@@ -19,15 +19,21 @@ use auth::UserProviderRef;
 use common_base::Plugins;
 use common_runtime::Builder as RuntimeBuilder;
 use servers::error::InternalIoSnafu;
+use servers::flight_sql::FlightSqlServer;
 use servers::grpc::{GrpcServer, GrpcServerConfig};
 use servers::http::HttpServerBuilder;
 use servers::metrics_handler::MetricsHandler;
+use servers::mqtt::MqttServer;
 use servers::mysql::server::{MysqlServer, MysqlSpawnConfig, MysqlSpawnRef};
 use servers::opentsdb::OpentsdbServer;
 use servers::postgres::PostgresServer;
 use servers::query_handler::grpc::ServerGrpcQueryHandlerAdapter;
 use servers::query_handler::sql::ServerSqlQueryHandlerAdapter;
+use servers::query_registry::{QueryRegistry, QueryRegistryRef};
+use servers::rate_limit::{RateLimiter, RateLimiterRef};
 use servers::server::{Server, ServerHandler, ServerHandlers};
+use servers::tail::TailHubRef;
+use servers::tls::{maybe_watch_tls_config, ReloadableTlsServerConfig};
 use snafu::ResultExt;
 
 use crate::error::{self, Result, StartServerSnafu};
@@ -50,9 +56,14 @@ impl Services {
         let opts: FrontendOptions = opts.into();
         let mut result = Vec::<ServerHandler>::with_capacity(plugins.len());
         let user_provider = plugins.get::<UserProviderRef>();
+        // Queries of HTTP and MySQL share the registry, so they can be killed from either one.
+        let query_registry: QueryRegistryRef = Arc::new(QueryRegistry::default());
+        let rate_limiter: Option<RateLimiterRef> = (!opts.rate_limit.rules.is_empty())
+            .then(|| Arc::new(RateLimiter::new(&opts.rate_limit)));
 
         {
             // Always init GRPC server
+            let otlp_enabled = opts.otlp.enable;
             let opts = &opts.grpc;
             let grpc_addr = parse_addr(&opts.addr)?;
 
@@ -68,7 +79,7 @@ impl Services {
                 max_recv_message_size: opts.max_recv_message_size.as_bytes() as usize,
                 max_send_message_size: opts.max_send_message_size.as_bytes() as usize,
             };
-            let grpc_server = GrpcServer::new(
+            let mut grpc_server = GrpcServer::new(
                 Some(grpc_config),
                 Some(ServerGrpcQueryHandlerAdapter::arc(instance.clone())),
                 Some(instance.clone()),
@@ -77,6 +88,12 @@ impl Services {
                 user_provider.clone(),
                 grpc_runtime,
             );
+            if otlp_enabled {
+                grpc_server = grpc_server.with_otlp_handler(instance.clone());
+            }
+            if let Some(rate_limiter) = rate_limiter.clone() {
+                grpc_server = grpc_server.with_rate_limiter(rate_limiter);
+            }
 
             result.push((Box::new(grpc_server), grpc_addr));
         }
@@ -89,12 +106,17 @@ impl Services {
             let mut http_server_builder = HttpServerBuilder::new(http_options.clone());
             let _ = http_server_builder
                 .with_sql_handler(ServerSqlQueryHandlerAdapter::arc(instance.clone()))
-                .with_grpc_handler(ServerGrpcQueryHandlerAdapter::arc(instance.clone()));
+                .with_grpc_handler(ServerGrpcQueryHandlerAdapter::arc(instance.clone()))
+                .with_query_registry(query_registry.clone());
 
             if let Some(user_provider) = user_provider.clone() {
                 let _ = http_server_builder.with_user_provider(user_provider);
             }
 
+            if let Some(rate_limiter) = rate_limiter.clone() {
+                let _ = http_server_builder.with_rate_limiter(rate_limiter);
+            }
+
             if opts.opentsdb.enable {
                 let _ = http_server_builder.with_opentsdb_handler(instance.clone());
             }
@@ -113,6 +135,16 @@ impl Services {
                 let _ = http_server_builder.with_otlp_handler(instance.clone());
             }
 
+            if opts.loki.enable {
+                let _ = http_server_builder.with_loki_handler(instance.clone());
+            }
+
+            let _ = http_server_builder.with_upload_handler(instance.clone());
+
+            if let Some(tail_hub) = plugins.get::<TailHubRef>() {
+                let _ = http_server_builder.with_tail_hub(tail_hub);
+            }
+
             let http_server = http_server_builder
                 .with_metrics_handler(MetricsHandler)
                 .with_script_handler(instance.clone())
@@ -134,21 +166,32 @@ impl Services {
                     .build()
                     .context(error::RuntimeResourceSnafu)?,
             );
+            let tls_server_config = Arc::new(
+                ReloadableTlsServerConfig::try_new(opts.tls.clone())
+                    .context(InternalIoSnafu)
+                    .context(StartServerSnafu)?,
+            );
+            maybe_watch_tls_config(tls_server_config.clone()).context(StartServerSnafu)?;
+
+            let mut spawn_ref = MysqlSpawnRef::new(
+                ServerSqlQueryHandlerAdapter::arc(instance.clone()),
+                user_provider.clone(),
+            )
+            .with_query_registry(query_registry.clone());
+            if let Some(rate_limiter) = rate_limiter.clone() {
+                spawn_ref = spawn_ref.with_rate_limiter(rate_limiter);
+            }
             let mysql_server = MysqlServer::create_server(
                 mysql_io_runtime,
-                Arc::new(MysqlSpawnRef::new(
-                    ServerSqlQueryHandlerAdapter::arc(instance.clone()),
-                    user_provider.clone(),
-                )),
-                Arc::new(MysqlSpawnConfig::new(
-                    opts.tls.should_force_tls(),
-                    opts.tls
-                        .setup()
-                        .context(InternalIoSnafu)
-                        .context(StartServerSnafu)?
-                        .map(Arc::new),
-                    opts.reject_no_database.unwrap_or(false),
-                )),
+                Arc::new(spawn_ref),
+                Arc::new(
+                    MysqlSpawnConfig::new(
+                        opts.tls.should_force_tls(),
+                        tls_server_config,
+                        opts.reject_no_database.unwrap_or(false),
+                    )
+                    .with_connection_options(opts.connection.clone()),
+                ),
             );
             result.push((mysql_server, mysql_addr));
         }
@@ -166,14 +209,35 @@ impl Services {
                     .context(error::RuntimeResourceSnafu)?,
             );
 
-            let pg_server = Box::new(PostgresServer::new(
+            let tls_server_config = Arc::new(
+                ReloadableTlsServerConfig::try_new(opts.tls.clone())
+                    .context(InternalIoSnafu)
+                    .context(StartServerSnafu)?,
+            );
+            maybe_watch_tls_config(tls_server_config.clone()).context(StartServerSnafu)?;
+
+            let pg_server = Box::new(
+                PostgresServer::new(
+                    ServerSqlQueryHandlerAdapter::arc(instance.clone()),
+                    tls_server_config,
+                    pg_io_runtime,
+                    user_provider.clone(),
+                )
+                .with_connection_options(opts.connection.clone()),
+            ) as Box<dyn Server>;
+
+            result.push((pg_server, pg_addr));
+        }
+
+        if opts.flight_sql.enable {
+            // Init Flight SQL server
+            let addr = parse_addr(&opts.flight_sql.addr)?;
+            let server = Box::new(FlightSqlServer::new(
                 ServerSqlQueryHandlerAdapter::arc(instance.clone()),
-                opts.tls.clone(),
-                pg_io_runtime,
                 user_provider.clone(),
             )) as Box<dyn Server>;
 
-            result.push((pg_server, pg_addr));
+            result.push((server, addr));
         }
 
         if opts.opentsdb.enable {
@@ -194,6 +258,29 @@ impl Services {
             result.push((server, addr));
         }
 
+        if opts.mqtt.enable {
+            // Init MQTT server
+            let opts = &opts.mqtt;
+            let addr = parse_addr(&opts.addr)?;
+
+            let io_runtime = Arc::new(
+                RuntimeBuilder::default()
+                    .worker_threads(opts.runtime_size)
+                    .thread_name("mqtt-io-handlers")
+                    .build()
+                    .context(error::RuntimeResourceSnafu)?,
+            );
+
+            let server = MqttServer::create_server(
+                instance.clone(),
+                user_provider.clone(),
+                opts.server_config(),
+                io_runtime,
+            );
+
+            result.push((server, addr));
+        }
+
         Ok(result
             .into_iter()
             .map(|(server, addr)| (server.name().to_string(), (server, addr)))
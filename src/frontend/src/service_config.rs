@@ -13,16 +13,22 @@
 // limitations under the License.
 
 pub mod datanode;
+pub mod flight_sql;
 pub mod grpc;
 pub mod influxdb;
+pub mod loki;
+pub mod mqtt;
 pub mod mysql;
 pub mod opentsdb;
 pub mod otlp;
 pub mod postgres;
 pub mod prom_store;
 
+pub use flight_sql::FlightSqlOptions;
 pub use grpc::GrpcOptions;
 pub use influxdb::InfluxdbOptions;
+pub use loki::LokiOptions;
+pub use mqtt::MqttOptions;
 pub use mysql::MysqlOptions;
 pub use opentsdb::OpentsdbOptions;
 pub use otlp::OtlpOptions;
@@ -14,21 +14,30 @@
 
 use std::time::Duration;
 
-use common_grpc::channel_manager;
+use client::client_manager::PoolConfig;
+use common_grpc::channel_manager::{self, ChannelConfig};
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
 pub struct DatanodeOptions {
-    client: DatanodeClientOptions,
+    pub client: DatanodeClientOptions,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct DatanodeClientOptions {
     #[serde(with = "humantime_serde")]
     pub timeout: Duration,
     #[serde(with = "humantime_serde")]
     pub connect_timeout: Duration,
     pub tcp_nodelay: bool,
+    /// Number of channels to each datanode.
+    pub channels_per_datanode: usize,
+    /// Max in-flight requests to each datanode, unlimited if it's 0.
+    pub max_in_flight_requests: usize,
+    /// Interval to check the health of channels.
+    #[serde(with = "humantime_serde")]
+    pub health_check_interval: Duration,
 }
 
 impl Default for DatanodeClientOptions {
@@ -39,6 +48,26 @@ impl Default for DatanodeClientOptions {
                 channel_manager::DEFAULT_GRPC_CONNECT_TIMEOUT_SECS,
             ),
             tcp_nodelay: true,
+            channels_per_datanode: 1,
+            max_in_flight_requests: 0,
+            health_check_interval: Duration::from_secs(10),
+        }
+    }
+}
+
+impl DatanodeClientOptions {
+    pub fn channel_config(&self) -> ChannelConfig {
+        ChannelConfig::new()
+            .timeout(self.timeout)
+            .connect_timeout(self.connect_timeout)
+            .tcp_nodelay(self.tcp_nodelay)
+    }
+
+    pub fn pool_config(&self) -> PoolConfig {
+        PoolConfig {
+            channels_per_peer: self.channels_per_datanode,
+            max_in_flight_per_peer: self.max_in_flight_requests,
+            health_check_interval: self.health_check_interval,
         }
     }
 }
@@ -0,0 +1,96 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use servers::mqtt::{MqttServerConfig, MqttTopicRule};
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct MqttOptions {
+    pub enable: bool,
+    pub addr: String,
+    pub runtime_size: usize,
+    /// Database to write.
+    pub db: String,
+    /// Max number of rows buffered by a connection before writing them.
+    pub batch_size: usize,
+    /// Interval to write buffered rows.
+    #[serde(with = "humantime_serde")]
+    pub flush_interval: Duration,
+    /// Rules to map topics to tables.
+    pub topics: Vec<MqttTopicRule>,
+}
+
+impl Default for MqttOptions {
+    fn default() -> Self {
+        Self {
+            enable: false,
+            addr: "127.0.0.1:1883".to_string(),
+            runtime_size: 2,
+            db: "public".to_string(),
+            batch_size: 1000,
+            flush_interval: Duration::from_secs(1),
+            topics: Vec::new(),
+        }
+    }
+}
+
+impl MqttOptions {
+    pub fn server_config(&self) -> MqttServerConfig {
+        MqttServerConfig {
+            topics: self.topics.clone(),
+            batch_size: self.batch_size,
+            flush_interval: self.flush_interval,
+            db: self.db.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use servers::mqtt::PayloadFormat;
+
+    use super::MqttOptions;
+
+    #[test]
+    fn test_mqtt_options() {
+        let default = MqttOptions::default();
+        assert!(!default.enable);
+        assert_eq!(default.addr, "127.0.0.1:1883");
+
+        let opts: MqttOptions = toml::from_str(
+            r#"
+enable = true
+flush_interval = "500ms"
+
+[[topics]]
+topic = "sensors/+/telemetry"
+table = "{1}"
+tags = ["device"]
+
+[[topics]]
+topic = "lines/#"
+format = "line_protocol"
+"#,
+        )
+        .unwrap();
+        assert!(opts.enable);
+        assert_eq!(500, opts.flush_interval.as_millis());
+        assert_eq!(2, opts.topics.len());
+        assert_eq!(PayloadFormat::Json, opts.topics[0].format);
+        assert_eq!(PayloadFormat::LineProtocol, opts.topics[1].format);
+    }
+}
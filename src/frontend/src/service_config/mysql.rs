@@ -13,6 +13,7 @@
 // limitations under the License.
 
 use serde::{Deserialize, Serialize};
+use servers::connection::ConnectionOptions;
 use servers::tls::TlsOption;
 
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
@@ -23,6 +24,8 @@ pub struct MysqlOptions {
     #[serde(default = "Default::default")]
     pub tls: TlsOption,
     pub reject_no_database: Option<bool>,
+    #[serde(default = "Default::default")]
+    pub connection: ConnectionOptions,
 }
 
 impl Default for MysqlOptions {
@@ -33,6 +36,7 @@ impl Default for MysqlOptions {
             runtime_size: 2,
             tls: TlsOption::default(),
             reject_no_database: None,
+            connection: ConnectionOptions::default(),
         }
     }
 }
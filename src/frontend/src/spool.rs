@@ -0,0 +1,642 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A bounded disk spool of row inserts.
+//!
+//! Row inserts that fail because datanodes or metasrv are temporarily unavailable are
+//! written to the spool and acknowledged. A background task replays spooled inserts in
+//! order once writes succeed again. Inserts arriving while the spool is not empty are
+//! spooled too, so they are never written before inserts spooled earlier.
+//!
+//! Acknowledged inserts may still not be written:
+//! - Inserts older than `max_age` are dropped.
+//! - Inserts that fail to replay with non-retryable errors, e.g. the table is dropped, or
+//!   can't be read are moved to the `failed` directory under the spool directory, so
+//!   operators can inspect them. The spool never removes files in the `failed` directory.
+
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use api::v1::RowInsertRequests;
+use auth::userinfo_by_name;
+use common_base::readable_size::ReadableSize;
+use common_error::ext::ErrorExt;
+use common_query::Output;
+use common_telemetry::logging::{error, info, warn};
+use common_time::util::current_time_millis;
+use common_time::TimeZone;
+use operator::insert::InserterRef;
+use operator::statement::StatementExecutor;
+use prost::Message;
+use serde::{Deserialize, Serialize};
+use session::context::{Channel, QueryContextBuilder, QueryContextRef};
+use snafu::{ensure, OptionExt, ResultExt};
+use tokio::fs;
+use tokio::io::AsyncWriteExt;
+use tokio::sync::Mutex;
+
+use crate::error::{
+    AccessSpoolSnafu, CorruptedSpoolFileSnafu, DecodeSpoolFileSnafu, Result, SpoolFullSnafu,
+};
+use crate::metrics::{METRIC_SPOOL_BYTES, METRIC_SPOOL_EVENTS, METRIC_SPOOL_REQUESTS};
+
+const SPOOL_FILE_EXTENSION: &str = "spool";
+const TMP_FILE_EXTENSION: &str = "tmp";
+/// Directory of spooled inserts that fail to replay.
+const FAILED_DIR: &str = "failed";
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct SpoolOptions {
+    /// Whether to spool row inserts that fail because the cluster is unavailable.
+    pub enable: bool,
+    /// Directory of spooled inserts.
+    pub dir: String,
+    /// Inserts are rejected once the total size of spooled inserts exceeds `max_size`.
+    pub max_size: ReadableSize,
+    /// Spooled inserts older than `max_age` are dropped instead of being replayed.
+    ///
+    /// Inserts that fail to replay with non-retryable errors are moved to the `failed`
+    /// directory under `dir` instead.
+    #[serde(with = "humantime_serde")]
+    pub max_age: Duration,
+    /// Interval to retry replaying spooled inserts.
+    #[serde(with = "humantime_serde")]
+    pub replay_interval: Duration,
+}
+
+impl Default for SpoolOptions {
+    fn default() -> Self {
+        Self {
+            enable: false,
+            dir: "/tmp/greptimedb/spool/".to_string(),
+            max_size: ReadableSize::mb(512),
+            max_age: Duration::from_secs(600),
+            replay_interval: Duration::from_secs(1),
+        }
+    }
+}
+
+/// Header of a spool file.
+///
+/// A spool file is `header length (u32, little endian) | JSON header | protobuf encoded
+/// RowInsertRequests`.
+#[derive(Debug, Serialize, Deserialize)]
+struct SpoolHeader {
+    catalog: String,
+    schema: String,
+    /// Time in milliseconds the insert is spooled.
+    created_ms: i64,
+    /// Protocol ingesting the insert, None for files spooled before it's recorded.
+    #[serde(default)]
+    protocol: Option<String>,
+    /// Name of the user issuing the insert.
+    #[serde(default)]
+    username: Option<String>,
+    /// Channel of the client issuing the insert.
+    #[serde(default)]
+    channel: Option<String>,
+    /// Time zone of the session issuing the insert.
+    #[serde(default)]
+    time_zone: Option<String>,
+}
+
+impl SpoolHeader {
+    /// Builds the context to replay the insert with.
+    fn query_context(&self) -> QueryContextRef {
+        let time_zone = self.time_zone.as_deref().and_then(|time_zone| {
+            match TimeZone::from_tz_string(time_zone) {
+                Ok(time_zone) => time_zone,
+                Err(e) => {
+                    warn!(e; "Ignore invalid time zone {} of spooled insert", time_zone);
+                    None
+                }
+            }
+        });
+        let ctx = QueryContextBuilder::default()
+            .current_catalog(self.catalog.clone())
+            .current_schema(self.schema.clone())
+            .time_zone(time_zone)
+            .channel(
+                self.channel
+                    .as_deref()
+                    .map(Channel::from_name)
+                    .unwrap_or(Channel::Unknown),
+            )
+            .build();
+        if let Some(username) = &self.username {
+            ctx.set_current_user(Some(userinfo_by_name(Some(username.clone()))));
+        }
+        ctx
+    }
+}
+
+#[derive(Debug)]
+struct SpooledInsert {
+    seq: u64,
+    size: u64,
+}
+
+#[derive(Debug, Default)]
+struct SpoolState {
+    inserts: VecDeque<SpooledInsert>,
+    /// Total size of spooled inserts in bytes.
+    size: u64,
+    next_seq: u64,
+}
+
+impl SpoolState {
+    fn push(&mut self, insert: SpooledInsert) {
+        self.size += insert.size;
+        self.next_seq = insert.seq + 1;
+        self.inserts.push_back(insert);
+        self.update_metrics();
+    }
+
+    fn pop(&mut self) -> Option<SpooledInsert> {
+        let insert = self.inserts.pop_front()?;
+        self.size -= insert.size;
+        self.update_metrics();
+        Some(insert)
+    }
+
+    fn update_metrics(&self) {
+        METRIC_SPOOL_REQUESTS.set(self.inserts.len() as i64);
+        METRIC_SPOOL_BYTES.set(self.size as i64);
+    }
+}
+
+pub type WriteSpoolRef = Arc<WriteSpool>;
+
+/// A disk spool of row inserts.
+#[derive(Debug)]
+pub struct WriteSpool {
+    dir: PathBuf,
+    max_size: u64,
+    max_age: Duration,
+    replay_interval: Duration,
+    state: Mutex<SpoolState>,
+}
+
+impl WriteSpool {
+    /// Opens the spool if it's enabled, inserts spooled by the last run are kept.
+    pub async fn open(opts: &SpoolOptions) -> Result<Option<WriteSpoolRef>> {
+        if !opts.enable {
+            return Ok(None);
+        }
+
+        let dir = PathBuf::from(&opts.dir);
+        fs::create_dir_all(&dir)
+            .await
+            .context(AccessSpoolSnafu { path: &opts.dir })?;
+
+        let mut inserts = Vec::new();
+        let mut entries = fs::read_dir(&dir)
+            .await
+            .context(AccessSpoolSnafu { path: &opts.dir })?;
+        while let Some(entry) = entries
+            .next_entry()
+            .await
+            .context(AccessSpoolSnafu { path: &opts.dir })?
+        {
+            let path = entry.path();
+            match path.extension().and_then(|ext| ext.to_str()) {
+                Some(SPOOL_FILE_EXTENSION) => {}
+                // Removes files that are not completely written.
+                Some(TMP_FILE_EXTENSION) => {
+                    fs::remove_file(&path).await.context(AccessSpoolSnafu {
+                        path: path.to_string_lossy(),
+                    })?;
+                    continue;
+                }
+                _ => continue,
+            }
+            let Some(seq) = path
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .and_then(|stem| stem.parse::<u64>().ok())
+            else {
+                continue;
+            };
+            let size = entry
+                .metadata()
+                .await
+                .context(AccessSpoolSnafu {
+                    path: path.to_string_lossy(),
+                })?
+                .len();
+            inserts.push(SpooledInsert { seq, size });
+        }
+        inserts.sort_unstable_by_key(|insert| insert.seq);
+
+        let mut state = SpoolState::default();
+        for insert in inserts {
+            state.push(insert);
+        }
+        state.update_metrics();
+        if !state.inserts.is_empty() {
+            info!(
+                "Opened write spool {} with {} inserts to replay",
+                opts.dir,
+                state.inserts.len()
+            );
+        }
+
+        Ok(Some(Arc::new(Self {
+            dir,
+            max_size: opts.max_size.as_bytes(),
+            max_age: opts.max_age,
+            replay_interval: opts.replay_interval,
+            state: Mutex::new(state),
+        })))
+    }
+
+    fn file_path(&self, seq: u64, extension: &str) -> PathBuf {
+        self.dir.join(format!("{seq:020}.{extension}"))
+    }
+
+    /// Returns true if there are no inserts to replay.
+    pub async fn is_empty(&self) -> bool {
+        self.state.lock().await.inserts.is_empty()
+    }
+
+    /// Spools the `requests` and returns the output to acknowledge.
+    ///
+    /// Returns an error if the spool is full.
+    pub async fn append(
+        &self,
+        requests: &RowInsertRequests,
+        ctx: &QueryContextRef,
+        protocol: &str,
+    ) -> Result<Output> {
+        let header = SpoolHeader {
+            catalog: ctx.current_catalog().to_string(),
+            schema: ctx.current_schema().to_string(),
+            created_ms: current_time_millis(),
+            protocol: Some(protocol.to_string()),
+            username: ctx.current_user().map(|user| user.username().to_string()),
+            channel: Some(ctx.channel().as_str().to_string()),
+            time_zone: ctx.time_zone().map(|time_zone| time_zone.to_string()),
+        };
+        // Safety: the header is always serializable.
+        let header = serde_json::to_vec(&header).unwrap();
+        let mut buf = Vec::with_capacity(4 + header.len() + requests.encoded_len());
+        buf.extend_from_slice(&(header.len() as u32).to_le_bytes());
+        buf.extend_from_slice(&header);
+        // Safety: encoding to a `Vec` never runs out of capacity.
+        requests.encode(&mut buf).unwrap();
+        let size = buf.len() as u64;
+
+        let mut state = self.state.lock().await;
+        if state.size + size > self.max_size {
+            METRIC_SPOOL_EVENTS.with_label_values(&["rejected"]).inc();
+            return SpoolFullSnafu {
+                max_size: ReadableSize(self.max_size),
+            }
+            .fail();
+        }
+
+        // Writes to a temporary file first, so a partially written file is never replayed.
+        let seq = state.next_seq;
+        let tmp_path = self.file_path(seq, TMP_FILE_EXTENSION);
+        let path = self.file_path(seq, SPOOL_FILE_EXTENSION);
+        let mut file = fs::File::create(&tmp_path)
+            .await
+            .context(AccessSpoolSnafu {
+                path: tmp_path.to_string_lossy(),
+            })?;
+        file.write_all(&buf).await.context(AccessSpoolSnafu {
+            path: tmp_path.to_string_lossy(),
+        })?;
+        file.sync_all().await.context(AccessSpoolSnafu {
+            path: tmp_path.to_string_lossy(),
+        })?;
+        fs::rename(&tmp_path, &path)
+            .await
+            .context(AccessSpoolSnafu {
+                path: path.to_string_lossy(),
+            })?;
+        state.push(SpooledInsert { seq, size });
+        METRIC_SPOOL_EVENTS.with_label_values(&["spooled"]).inc();
+
+        let rows = requests
+            .inserts
+            .iter()
+            .map(|r| r.rows.as_ref().map(|rows| rows.rows.len()).unwrap_or(0))
+            .sum();
+        Ok(Output::AffectedRows(rows))
+    }
+
+    /// Reads the oldest spooled insert.
+    async fn front(&self) -> Result<Option<(u64, SpoolHeader, RowInsertRequests)>> {
+        let Some(seq) = self
+            .state
+            .lock()
+            .await
+            .inserts
+            .front()
+            .map(|insert| insert.seq)
+        else {
+            return Ok(None);
+        };
+        let path = self.file_path(seq, SPOOL_FILE_EXTENSION);
+        let bytes = fs::read(&path).await.context(AccessSpoolSnafu {
+            path: path.to_string_lossy(),
+        })?;
+        let (header, requests) = decode_spool_file(&path, &bytes)?;
+        Ok(Some((seq, header, requests)))
+    }
+
+    /// Removes the oldest spooled insert.
+    async fn pop(&self, seq: u64) -> Result<()> {
+        let mut state = self.state.lock().await;
+        if state.inserts.front().map(|insert| insert.seq) != Some(seq) {
+            return Ok(());
+        }
+        let path = self.file_path(seq, SPOOL_FILE_EXTENSION);
+        fs::remove_file(&path).await.context(AccessSpoolSnafu {
+            path: path.to_string_lossy(),
+        })?;
+        let _ = state.pop();
+        Ok(())
+    }
+
+    /// Replays spooled inserts in order until the spool is empty or an insert fails with a
+    /// retryable error.
+    async fn replay(&self, inserter: &InserterRef, statement_executor: &StatementExecutor) {
+        loop {
+            let (seq, header, requests) = match self.front().await {
+                Ok(Some(front)) => front,
+                Ok(None) => return,
+                Err(e) => {
+                    error!(e; "Failed to read spooled insert");
+                    METRIC_SPOOL_EVENTS.with_label_values(&["failed"]).inc();
+                    self.fail_front().await;
+                    continue;
+                }
+            };
+
+            let age = current_time_millis().saturating_sub(header.created_ms);
+            if age > self.max_age.as_millis() as i64 {
+                warn!(
+                    "Drop spooled insert {} to {}.{}, age: {}ms",
+                    seq, header.catalog, header.schema, age
+                );
+                METRIC_SPOOL_EVENTS.with_label_values(&["expired"]).inc();
+                self.drop_front().await;
+                continue;
+            }
+
+            let ctx = header.query_context();
+            match inserter
+                .handle_row_inserts(
+                    requests,
+                    ctx,
+                    header.protocol.as_deref().unwrap_or("unknown"),
+                    statement_executor,
+                )
+                .await
+            {
+                Ok(_) => {
+                    METRIC_SPOOL_EVENTS.with_label_values(&["replayed"]).inc();
+                }
+                Err(e) if e.status_code().is_retryable() => {
+                    warn!(e; "Failed to replay spooled insert {}, retry later", seq);
+                    return;
+                }
+                Err(e) => {
+                    error!(e; "Failed to replay spooled insert {}", seq);
+                    METRIC_SPOOL_EVENTS.with_label_values(&["failed"]).inc();
+                    self.fail_front().await;
+                    continue;
+                }
+            }
+            if let Err(e) = self.pop(seq).await {
+                error!(e; "Failed to remove spooled insert {}", seq);
+                return;
+            }
+        }
+    }
+
+    async fn drop_front(&self) {
+        let mut state = self.state.lock().await;
+        if let Some(insert) = state.pop() {
+            let path = self.file_path(insert.seq, SPOOL_FILE_EXTENSION);
+            if let Err(e) = fs::remove_file(&path).await {
+                warn!(e; "Failed to remove spool file {}", path.display());
+            }
+        }
+    }
+
+    /// Moves the oldest spooled insert to the failed directory.
+    async fn fail_front(&self) {
+        let mut state = self.state.lock().await;
+        let Some(insert) = state.pop() else {
+            return;
+        };
+        let path = self.file_path(insert.seq, SPOOL_FILE_EXTENSION);
+        let failed_dir = self.dir.join(FAILED_DIR);
+        // Sequences restart from 0 once the spool is empty, so the time makes names unique.
+        let failed_path = failed_dir.join(format!(
+            "{}_{:020}.{SPOOL_FILE_EXTENSION}",
+            current_time_millis(),
+            insert.seq
+        ));
+        let res = match fs::create_dir_all(&failed_dir).await {
+            Ok(()) => fs::rename(&path, &failed_path).await,
+            Err(e) => Err(e),
+        };
+        match res {
+            Ok(()) => warn!(
+                "Moved spooled insert {} to {}",
+                insert.seq,
+                failed_path.display()
+            ),
+            Err(e) => error!(
+                e; "Failed to move spool file {} to {}",
+                path.display(),
+                failed_path.display()
+            ),
+        }
+    }
+
+    /// Starts the task to replay spooled inserts.
+    pub fn start(
+        self: &Arc<Self>,
+        inserter: InserterRef,
+        statement_executor: Arc<StatementExecutor>,
+    ) {
+        let spool = self.clone();
+        let _handle = common_runtime::spawn_bg(async move {
+            info!(
+                "Start replaying spooled inserts, interval: {:?}",
+                spool.replay_interval
+            );
+            let mut interval = tokio::time::interval(spool.replay_interval);
+            loop {
+                let _ = interval.tick().await;
+                spool.replay(&inserter, &statement_executor).await;
+            }
+        });
+    }
+}
+
+fn decode_spool_file(path: &Path, bytes: &[u8]) -> Result<(SpoolHeader, RowInsertRequests)> {
+    let path = path.to_string_lossy();
+    ensure!(bytes.len() >= 4, CorruptedSpoolFileSnafu { path: &*path });
+    // Safety: the length is checked above.
+    let header_len = u32::from_le_bytes(bytes[..4].try_into().unwrap()) as usize;
+    ensure!(
+        bytes.len() >= 4 + header_len,
+        CorruptedSpoolFileSnafu { path: &*path }
+    );
+    let header = serde_json::from_slice(&bytes[4..4 + header_len])
+        .ok()
+        .with_context(|| CorruptedSpoolFileSnafu { path: &*path })?;
+    let requests = RowInsertRequests::decode(&bytes[4 + header_len..])
+        .context(DecodeSpoolFileSnafu { path: &*path })?;
+    Ok((header, requests))
+}
+
+#[cfg(test)]
+mod tests {
+    use api::v1::{Row, RowInsertRequest, Rows};
+    use common_test_util::temp_dir::create_temp_dir;
+    use session::context::QueryContext;
+
+    use super::*;
+
+    fn new_requests(table: &str, rows: usize) -> RowInsertRequests {
+        RowInsertRequests {
+            inserts: vec![RowInsertRequest {
+                table_name: table.to_string(),
+                rows: Some(Rows {
+                    schema: vec![],
+                    rows: vec![Row { values: vec![] }; rows],
+                }),
+                ..Default::default()
+            }],
+        }
+    }
+
+    #[tokio::test]
+    async fn test_write_spool() {
+        let dir = create_temp_dir("write-spool");
+        let opts = SpoolOptions {
+            enable: true,
+            dir: dir.path().to_string_lossy().to_string(),
+            max_size: ReadableSize::kb(1),
+            ..Default::default()
+        };
+        assert!(WriteSpool::open(&SpoolOptions::default())
+            .await
+            .unwrap()
+            .is_none());
+
+        let spool = WriteSpool::open(&opts).await.unwrap().unwrap();
+        assert!(spool.is_empty().await);
+        let ctx = QueryContext::with("greptime", "db1");
+        for (table, rows) in [("t1", 2), ("t2", 3)] {
+            let output = spool
+                .append(&new_requests(table, rows), &ctx, "grpc")
+                .await
+                .unwrap();
+            assert!(matches!(output, Output::AffectedRows(n) if n == rows));
+        }
+        // The spool is full.
+        assert!(spool
+            .append(&new_requests("t3", 1000), &ctx, "grpc")
+            .await
+            .is_err());
+
+        // Spooled inserts are kept after reopening.
+        drop(spool);
+        let spool = WriteSpool::open(&opts).await.unwrap().unwrap();
+        let (seq, header, requests) = spool.front().await.unwrap().unwrap();
+        assert_eq!("db1", header.schema);
+        assert_eq!(new_requests("t1", 2), requests);
+        spool.pop(seq).await.unwrap();
+        let (seq, _, requests) = spool.front().await.unwrap().unwrap();
+        assert_eq!(new_requests("t2", 3), requests);
+        spool.pop(seq).await.unwrap();
+        assert!(spool.is_empty().await);
+        assert!(spool.front().await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_spooled_context() {
+        let dir = create_temp_dir("write-spool-context");
+        let opts = SpoolOptions {
+            enable: true,
+            dir: dir.path().to_string_lossy().to_string(),
+            ..Default::default()
+        };
+        let spool = WriteSpool::open(&opts).await.unwrap().unwrap();
+        let ctx = QueryContextBuilder::default()
+            .current_schema("db1".to_string())
+            .channel(Channel::Mysql)
+            .time_zone(TimeZone::from_tz_string("+08:00").unwrap())
+            .build();
+        ctx.set_current_user(Some(userinfo_by_name(Some("writer".to_string()))));
+        let _ = spool
+            .append(&new_requests("t1", 1), &ctx, "mysql")
+            .await
+            .unwrap();
+
+        let (_, header, _) = spool.front().await.unwrap().unwrap();
+        let replay_ctx = header.query_context();
+        assert_eq!(ctx.current_catalog(), replay_ctx.current_catalog());
+        assert_eq!("db1", replay_ctx.current_schema());
+        assert_eq!(Channel::Mysql, replay_ctx.channel());
+        assert_eq!(ctx.time_zone(), replay_ctx.time_zone());
+        assert_eq!("writer", replay_ctx.current_user().unwrap().username());
+    }
+
+    #[tokio::test]
+    async fn test_keep_failed_inserts() {
+        let dir = create_temp_dir("write-spool-failed");
+        let opts = SpoolOptions {
+            enable: true,
+            dir: dir.path().to_string_lossy().to_string(),
+            ..Default::default()
+        };
+        let spool = WriteSpool::open(&opts).await.unwrap().unwrap();
+        let ctx = QueryContext::with("greptime", "db1");
+        let _ = spool
+            .append(&new_requests("t1", 1), &ctx, "grpc")
+            .await
+            .unwrap();
+        spool.fail_front().await;
+        assert!(spool.is_empty().await);
+
+        // The failed insert is kept but not replayed after reopening.
+        let failed = std::fs::read_dir(dir.path().join(FAILED_DIR))
+            .unwrap()
+            .collect::<Vec<_>>();
+        assert_eq!(1, failed.len());
+        drop(spool);
+        let spool = WriteSpool::open(&opts).await.unwrap().unwrap();
+        assert!(spool.is_empty().await);
+    }
+
+    #[test]
+    fn test_decode_corrupted_spool_file() {
+        let path = Path::new("0.spool");
+        assert!(decode_spool_file(path, b"").is_err());
+        assert!(decode_spool_file(path, &[100, 0, 0, 0, 1]).is_err());
+        assert!(decode_spool_file(path, &[2, 0, 0, 0, b'{', b'}']).is_err());
+    }
+}
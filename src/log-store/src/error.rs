@@ -13,12 +13,15 @@
 // limitations under the License.
 
 use std::any::Any;
+use std::sync::Arc;
 
 use common_config::wal::KafkaWalTopic;
 use common_error::ext::ErrorExt;
 use common_macro::stack_trace_debug;
 use common_runtime::error::Error as RuntimeError;
 use snafu::{Location, Snafu};
+use store_api::logstore::entry::Id as EntryId;
+use store_api::logstore::LogStoreError;
 
 #[derive(Snafu)]
 #[snafu(visibility(pub))]
@@ -97,6 +100,12 @@ pub enum Error {
         error: rskafka::client::error::Error,
     },
 
+    #[snafu(display("Failed to set up authentication of Kafka clients"))]
+    SetupClientAuth {
+        location: Location,
+        source: common_meta::error::Error,
+    },
+
     #[snafu(display(
         "Failed to build a Kafka partition client, topic: {}, partition: {}",
         topic,
@@ -121,6 +130,28 @@ pub enum Error {
         error: String,
     },
 
+    #[snafu(display("Kafka topic {} not found, affected regions: {:?}", topic, region_ids))]
+    TopicNotFound {
+        topic: String,
+        region_ids: Vec<u64>,
+        location: Location,
+    },
+
+    #[snafu(display("Failed to list topics in the Kafka cluster"))]
+    ListTopics {
+        location: Location,
+        #[snafu(source)]
+        error: rskafka::client::error::Error,
+    },
+
+    #[snafu(display("Failed to create Kafka topic {}", topic))]
+    CreateTopic {
+        topic: String,
+        location: Location,
+        #[snafu(source)]
+        error: rskafka::client::error::Error,
+    },
+
     #[snafu(display("Failed to encode a record meta"))]
     EncodeMeta {
         location: Location,
@@ -141,6 +172,19 @@ pub enum Error {
     #[snafu(display("Missing required value in a record"))]
     MissingValue { location: Location },
 
+    #[snafu(display(
+        "Corrupted entry {:?} in region {}, reason: {}",
+        entry_id,
+        region_id,
+        reason
+    ))]
+    CorruptedEntry {
+        region_id: u64,
+        entry_id: Option<EntryId>,
+        reason: String,
+        location: Location,
+    },
+
     #[snafu(display("Cannot build a record from empty entries"))]
     EmptyEntries { location: Location },
 
@@ -164,11 +208,77 @@ pub enum Error {
         offset: i64,
         location: Location,
         #[snafu(source)]
+        error: Arc<rskafka::client::error::Error>,
+    },
+
+    #[snafu(display("Failed to get the latest offset from Kafka, topic: {}", topic))]
+    GetOffset {
+        topic: String,
+        location: Location,
+        #[snafu(source)]
         error: rskafka::client::error::Error,
     },
 
+    #[snafu(display("Failed to access the prune state of topic {}", topic))]
+    AccessPruneState {
+        topic: String,
+        location: Location,
+        source: common_meta::error::Error,
+    },
+
+    #[snafu(display("Invalid prune state, key: {}, value: {}", key, value))]
+    InvalidPruneState {
+        key: String,
+        value: String,
+        location: Location,
+    },
+
+    #[snafu(display(
+        "Failed to delete records from Kafka, topic: {}, offset: {}",
+        topic,
+        offset
+    ))]
+    DeleteRecords {
+        topic: String,
+        offset: i64,
+        location: Location,
+        #[snafu(source)]
+        error: rskafka::client::error::Error,
+    },
+
+    #[snafu(display("Failed to access the offset index of topic {}", topic))]
+    AccessOffsetIndex {
+        topic: String,
+        location: Location,
+        source: common_meta::error::Error,
+    },
+
+    #[snafu(display("Invalid offset index, key: {}", key))]
+    InvalidOffsetIndex {
+        key: String,
+        location: Location,
+        #[snafu(source)]
+        error: serde_json::Error,
+    },
+
     #[snafu(display("Failed to do a cast"))]
     Cast { location: Location },
+
+    #[snafu(display("Failed to access local wal segment {}", path))]
+    AccessLocalSegment {
+        path: String,
+        location: Location,
+        #[snafu(source)]
+        error: std::io::Error,
+    },
+
+    #[snafu(display("Failed to access object store, path: {}", path))]
+    AccessObjectStore {
+        path: String,
+        location: Location,
+        #[snafu(source)]
+        error: object_store::Error,
+    },
 }
 
 impl ErrorExt for Error {
@@ -177,4 +287,13 @@ impl ErrorExt for Error {
     }
 }
 
+impl LogStoreError for Error {
+    fn corrupted_entry_id(&self) -> Option<EntryId> {
+        match self {
+            Error::CorruptedEntry { entry_id, .. } => *entry_id,
+            _ => None,
+        }
+    }
+}
+
 pub type Result<T> = std::result::Result<T, Error>;
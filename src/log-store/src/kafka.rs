@@ -15,7 +15,10 @@
 mod client_manager;
 pub mod log_store;
 mod offset;
+mod offset_index;
+mod pruner;
 mod record_utils;
+mod replay;
 
 use common_meta::wal::KafkaWalTopic as Topic;
 use serde::{Deserialize, Serialize};
@@ -12,23 +12,34 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::collections::{BTreeSet, HashSet};
 use std::sync::Arc;
 
 use common_config::wal::{KafkaConfig, KafkaWalTopic as Topic};
+use common_meta::wal::kafka::with_client_auth;
+use common_telemetry::{error, info, warn};
 use dashmap::mapref::entry::Entry as DashMapEntry;
-use dashmap::DashMap;
+use dashmap::{DashMap, DashSet};
 use rskafka::client::partition::{PartitionClient, UnknownTopicHandling};
 use rskafka::client::producer::aggregator::RecordAggregator;
 use rskafka::client::producer::{BatchProducer, BatchProducerBuilder};
 use rskafka::client::{Client as RsKafkaClient, ClientBuilder};
 use rskafka::BackoffConfig;
-use snafu::ResultExt;
+use snafu::{ensure, ResultExt};
 
-use crate::error::{BuildClientSnafu, BuildPartitionClientSnafu, Result};
+use crate::error::{
+    BuildClientSnafu, BuildPartitionClientSnafu, CreateTopicSnafu, ListTopicsSnafu, Result,
+    SetupClientAuthSnafu, TopicNotFoundSnafu,
+};
 
 // Each topic only has one partition for now.
 // The `DEFAULT_PARTITION` refers to the index of the partition.
 const DEFAULT_PARTITION: i32 = 0;
+/// Number of partitions of a re-created topic.
+const NUM_PARTITIONS: i32 = 1;
+
+/// Timeout of creating a topic.
+const CREATE_TOPIC_TIMEOUT_MS: i32 = 30_000;
 
 /// Arc wrapper of ClientManager.
 pub(crate) type ClientManagerRef = Arc<ClientManager>;
@@ -48,7 +59,7 @@ impl Client {
     pub(crate) fn new(raw_client: Arc<PartitionClient>, config: &KafkaConfig) -> Self {
         let record_aggregator = RecordAggregator::new(config.max_batch_size.as_bytes() as usize);
         let batch_producer = BatchProducerBuilder::new(raw_client.clone())
-            .with_compression(config.compression)
+            .with_compression(config.compression.into())
             .with_linger(config.linger)
             .build(record_aggregator);
 
@@ -68,6 +79,10 @@ pub(crate) struct ClientManager {
     /// A pool maintaining a collection of clients.
     /// Key: a topic. Value: the associated client of the topic.
     client_pool: DashMap<Topic, Client>,
+    /// Regions using each topic, so errors of a topic can name the affected regions.
+    regions: DashMap<Topic, BTreeSet<u64>>,
+    /// Topics found missing in the Kafka cluster by the latest probe.
+    missing_topics: DashSet<Topic>,
 }
 
 impl ClientManager {
@@ -80,8 +95,11 @@ impl ClientManager {
             base: config.backoff_base as f64,
             deadline: config.backoff_deadline,
         };
-        let client = ClientBuilder::new(config.broker_endpoints.clone())
-            .backoff_config(backoff_config)
+        let builder =
+            ClientBuilder::new(config.broker_endpoints.clone()).backoff_config(backoff_config);
+        let client = with_client_auth(builder, config.sasl.as_ref(), config.tls.as_ref())
+            .await
+            .context(SetupClientAuthSnafu)?
             .build()
             .await
             .with_context(|_| BuildClientSnafu {
@@ -92,12 +110,45 @@ impl ClientManager {
             config: config.clone(),
             client_factory: client,
             client_pool: DashMap::new(),
+            regions: DashMap::new(),
+            missing_topics: DashSet::new(),
         })
     }
 
+    /// Starts a background task to probe topics periodically. The task stops once the
+    /// manager is dropped.
+    pub(crate) fn start_probe_task(self: &Arc<Self>) {
+        let interval = self.config.probe_topics_interval;
+        if interval.is_zero() {
+            return;
+        }
+        let manager = Arc::downgrade(self);
+        let _handle = common_runtime::spawn_bg(async move {
+            let mut ticker = tokio::time::interval(interval);
+            // The first tick completes immediately.
+            let _ = ticker.tick().await;
+            loop {
+                let _ = ticker.tick().await;
+                let Some(manager) = manager.upgrade() else {
+                    return;
+                };
+                if let Err(e) = manager.probe_topics().await {
+                    warn!(e; "Failed to probe Kafka topics");
+                }
+            }
+        });
+    }
+
     /// Gets the client associated with the topic. If the client does not exist, a new one will
     /// be created and returned.
     pub(crate) async fn get_or_insert(&self, topic: &Topic) -> Result<Client> {
+        ensure!(
+            !self.missing_topics.contains(topic),
+            TopicNotFoundSnafu {
+                topic,
+                region_ids: self.regions_of(topic),
+            }
+        );
         match self.client_pool.entry(topic.to_string()) {
             DashMapEntry::Occupied(entry) => Ok(entry.get().clone()),
             DashMapEntry::Vacant(entry) => {
@@ -107,7 +158,107 @@ impl ClientManager {
         }
     }
 
+    /// Records that the region uses the topic.
+    pub(crate) fn register_region(&self, topic: &Topic, region_id: u64) {
+        if let Some(regions) = self.regions.get(topic) {
+            if regions.contains(&region_id) {
+                return;
+            }
+        }
+        let _ = self
+            .regions
+            .entry(topic.clone())
+            .or_default()
+            .insert(region_id);
+    }
+
+    /// Records that the region no longer uses the topic.
+    pub(crate) fn unregister_region(&self, topic: &Topic, region_id: u64) {
+        if let Some(mut regions) = self.regions.get_mut(topic) {
+            let _ = regions.remove(&region_id);
+        }
+    }
+
+    fn regions_of(&self, topic: &Topic) -> Vec<u64> {
+        self.regions
+            .get(topic)
+            .map(|regions| regions.iter().copied().collect())
+            .unwrap_or_default()
+    }
+
+    /// Checks whether topics used by regions exist in the Kafka cluster. Missing topics are
+    /// re-created if `recreate_missing_topics` is enabled, otherwise accessing them fails
+    /// until they exist again.
+    pub(crate) async fn probe_topics(&self) -> Result<()> {
+        let existing_topics = self.list_topics().await?;
+        let topics = self
+            .regions
+            .iter()
+            .map(|entry| entry.key().clone())
+            .chain(self.client_pool.iter().map(|entry| entry.key().clone()))
+            .collect::<HashSet<_>>();
+        for topic in topics {
+            if existing_topics.contains(&topic) {
+                if self.missing_topics.remove(&topic).is_some() {
+                    info!("Kafka topic {} is available again", topic);
+                }
+                continue;
+            }
+            self.handle_missing_topic(&topic).await?;
+        }
+        Ok(())
+    }
+
+    async fn list_topics(&self) -> Result<HashSet<Topic>> {
+        Ok(self
+            .client_factory
+            .list_topics()
+            .await
+            .context(ListTopicsSnafu)?
+            .into_iter()
+            .map(|topic| topic.name)
+            .collect())
+    }
+
+    /// Re-creates the missing topic if allowed, otherwise marks it missing and returns
+    /// an error naming the topic and the affected regions.
+    async fn handle_missing_topic(&self, topic: &Topic) -> Result<()> {
+        let region_ids = self.regions_of(topic);
+        if !self.config.recreate_missing_topics {
+            error!(
+                "Kafka topic {} not found, affected regions: {:?}",
+                topic, region_ids
+            );
+            let _ = self.missing_topics.insert(topic.clone());
+            return TopicNotFoundSnafu { topic, region_ids }.fail();
+        }
+
+        warn!(
+            "Re-creating missing Kafka topic {}, affected regions: {:?}",
+            topic, region_ids
+        );
+        self.client_factory
+            .controller_client()
+            .context(CreateTopicSnafu { topic })?
+            .create_topic(
+                topic,
+                NUM_PARTITIONS,
+                self.config.replication_factor,
+                CREATE_TOPIC_TIMEOUT_MS,
+            )
+            .await
+            .context(CreateTopicSnafu { topic })?;
+        let _ = self.missing_topics.remove(topic);
+        Ok(())
+    }
+
     async fn try_create_client(&self, topic: &Topic) -> Result<Client> {
+        // Checks the topic before creating the client, as the client keeps retrying if the
+        // topic doesn't exist.
+        if !self.list_topics().await?.contains(topic) {
+            self.handle_missing_topic(topic).await?;
+        }
+
         // Sets to Retry to retry connecting if the kafka cluter replies with an UnknownTopic error.
         // That's because the topic is believed to exist as the metasrv is expected to create required topics upon start.
         // The reconnecting won't stop until succeed or a different error returns.
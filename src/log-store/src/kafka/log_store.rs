@@ -16,35 +16,135 @@ use std::collections::HashMap;
 use std::sync::Arc;
 
 use common_config::wal::{KafkaConfig, WalOptions};
-use futures_util::StreamExt;
-use rskafka::client::consumer::{StartOffset, StreamConsumerBuilder};
+use common_meta::kv_backend::KvBackendRef;
+use common_telemetry::{debug, info, warn};
+use rskafka::client::partition::{OffsetAt, PartitionClient};
+use snafu::ResultExt;
 use store_api::logstore::entry::Id as EntryId;
 use store_api::logstore::entry_stream::SendableEntryStream;
 use store_api::logstore::namespace::Id as NamespaceId;
 use store_api::logstore::{AppendBatchResponse, AppendResponse, LogStore};
 
-use crate::error::{Error, Result};
+use crate::error::{DeleteRecordsSnafu, Error, GetOffsetSnafu, Result};
 use crate::kafka::client_manager::{ClientManager, ClientManagerRef};
 use crate::kafka::offset::Offset;
-use crate::kafka::record_utils::{handle_consume_result, RecordProducer};
+use crate::kafka::offset_index::OffsetIndex;
+use crate::kafka::pruner::WalPruner;
+use crate::kafka::record_utils::RecordProducer;
+use crate::kafka::replay::{TopicReplayer, TopicReplayers};
 use crate::kafka::{EntryImpl, NamespaceImpl};
 
+/// Timeout of deleting records.
+const DELETE_RECORDS_TIMEOUT_MS: i32 = 30_000;
+
 /// A log store backed by Kafka.
 #[derive(Debug)]
 pub struct KafkaLogStore {
     config: KafkaConfig,
     /// Manages kafka clients through which the log store contact the Kafka cluster.
     client_manager: ClientManagerRef,
+    /// Deletes obsolete records of topics. Records are never deleted by the log store if it's None.
+    pruner: Option<WalPruner>,
+    /// Maps entry ids to offsets so replay seeks near the required entry. Replay starts from
+    /// the offset equal to the entry id if it's None.
+    offset_index: Option<OffsetIndex>,
+    /// Replays regions sharing a topic with one consumer.
+    replayers: TopicReplayers,
 }
 
 impl KafkaLogStore {
     /// Tries to create a Kafka log store.
     pub async fn try_new(config: &KafkaConfig) -> Result<Self> {
+        let client_manager = Arc::new(ClientManager::try_new(config).await?);
+        client_manager.start_probe_task();
         Ok(Self {
-            client_manager: Arc::new(ClientManager::try_new(config).await?),
+            client_manager,
             config: config.clone(),
+            pruner: None,
+            offset_index: None,
+            replayers: TopicReplayers::default(),
         })
     }
+
+    /// Enables pruning obsolete records. The `kv_backend` stores states of regions and
+    /// must be shared by all datanodes using the same topics.
+    pub fn with_pruner(mut self, kv_backend: KvBackendRef) -> Self {
+        self.pruner = Some(WalPruner::new(kv_backend));
+        self
+    }
+
+    /// Enables the offset index. The `kv_backend` persists checkpoints of regions so they are
+    /// available after the datanode restarts or regions migrate to other datanodes.
+    pub fn with_offset_index(mut self, kv_backend: KvBackendRef) -> Self {
+        self.offset_index = Some(OffsetIndex::new(
+            kv_backend,
+            self.config.offset_index_interval,
+        ));
+        self
+    }
+
+    /// Records that the record at `offset` contains the entry with `entry_id` of the region.
+    /// The index is only a hint for replay, so failing to update it doesn't fail the append.
+    async fn index_entry(&self, ns: &NamespaceImpl, entry_id: EntryId, offset: &Offset) {
+        let Some(offset_index) = &self.offset_index else {
+            return;
+        };
+        if let Err(e) = offset_index.on_append(ns, entry_id, offset.0).await {
+            warn!(
+                e; "Failed to update the offset index of region {} in topic {}",
+                ns.region_id, ns.topic
+            );
+        }
+    }
+
+    /// Returns the offset to start reading entries with ids `>= entry_id` of the region.
+    async fn seek_offset(&self, ns: &NamespaceImpl, entry_id: EntryId) -> Result<i64> {
+        // Entry ids never exceed offsets of their records, see `append_batch`.
+        let offset = Offset::try_from(entry_id)?.0;
+        let Some(offset_index) = &self.offset_index else {
+            return Ok(offset);
+        };
+        match offset_index.seek(ns, entry_id).await {
+            Ok(seek_offset) => {
+                Ok(seek_offset.map_or(offset, |seek_offset| seek_offset.max(offset)))
+            }
+            Err(e) => {
+                warn!(
+                    e; "Failed to seek the offset index of region {} in topic {}",
+                    ns.region_id, ns.topic
+                );
+                Ok(offset)
+            }
+        }
+    }
+
+    /// Registers regions of the `entries` in the pruner so their records won't be deleted
+    /// before they are flushed.
+    async fn register_entries(&self, entries: &[EntryImpl]) -> Result<()> {
+        let Some(pruner) = &self.pruner else {
+            return Ok(());
+        };
+        for entry in entries {
+            // The region requires all entries it appends until it flushes.
+            pruner.register(&entry.ns, 0).await?;
+        }
+        Ok(())
+    }
+
+    /// Returns the max size of a record. Entries are split into multiple records
+    /// if they are larger than this size.
+    fn max_record_size(&self) -> usize {
+        // The batch producer rejects records larger than the max batch size.
+        self.config.max_batch_size.as_bytes() as usize
+    }
+}
+
+/// Returns the offset of the next record to be written to the partition, a.k.a. the high watermark.
+async fn end_offset(client: &PartitionClient, topic: &str) -> Result<i64> {
+    client
+        .get_offset(OffsetAt::Latest)
+        .await
+        .context(GetOffsetSnafu { topic })
 }
 
 #[async_trait::async_trait]
@@ -69,11 +169,16 @@ impl LogStore for KafkaLogStore {
 
     /// Appends an entry to the log store and returns a response containing the entry id of the appended entry.
     async fn append(&self, entry: Self::Entry) -> Result<AppendResponse> {
-        let entry_id = RecordProducer::new(entry.ns.clone())
+        self.client_manager
+            .register_region(&entry.ns.topic, entry.ns.region_id);
+        self.register_entries(std::slice::from_ref(&entry)).await?;
+        let (ns, last_id) = (entry.ns.clone(), entry.id);
+        let offset = RecordProducer::new(ns.clone(), self.max_record_size())
             .with_entries(vec![entry])
             .produce(&self.client_manager)
-            .await
-            .map(TryInto::try_into)??;
+            .await?;
+        self.index_entry(&ns, last_id, &offset).await;
+        let entry_id = offset.try_into()?;
         Ok(AppendResponse {
             last_entry_id: entry_id,
         })
@@ -85,26 +190,41 @@ impl LogStore for KafkaLogStore {
         if entries.is_empty() {
             return Ok(AppendBatchResponse::default());
         }
+        self.register_entries(&entries).await?;
 
         // Groups entries by region id and pushes them to an associated record producer.
         let mut producers = HashMap::with_capacity(entries.len());
         for entry in entries {
             producers
                 .entry(entry.ns.region_id)
-                .or_insert(RecordProducer::new(entry.ns.clone()))
+                .or_insert_with(|| {
+                    self.client_manager
+                        .register_region(&entry.ns.topic, entry.ns.region_id);
+                    RecordProducer::new(entry.ns.clone(), self.max_record_size())
+                })
                 .push(entry);
         }
 
         // Builds a record from entries belong to a region and produces them to kafka server.
         let region_ids = producers.keys().cloned().collect::<Vec<_>>();
+        let last_entries = producers
+            .values()
+            .map(|producer| (producer.namespace().clone(), producer.last_entry_id()))
+            .collect::<Vec<_>>();
         let tasks = producers
             .into_values()
             .map(|producer| producer.produce(&self.client_manager))
             .collect::<Vec<_>>();
         // Each produce operation returns a kafka offset of the produced record.
-        // The offsets are then converted to entry ids.
-        let entry_ids = futures::future::try_join_all(tasks)
-            .await?
+        // The offsets are then converted to entry ids, so the next entry id of a region never
+        // exceeds the offset of the record containing the entry.
+        let offsets = futures::future::try_join_all(tasks).await?;
+        for ((ns, last_id), offset) in last_entries.iter().zip(&offsets) {
+            if let Some(last_id) = last_id {
+                self.index_entry(ns, *last_id, offset).await;
+            }
+        }
+        let entry_ids = offsets
             .into_iter()
             .map(TryInto::try_into)
             .collect::<Result<Vec<_>>>()?;
@@ -115,6 +235,9 @@ impl LogStore for KafkaLogStore {
 
     /// Creates a new `EntryStream` to asynchronously generates `Entry` with entry ids
     /// starting from `entry_id`. The generated entries will be filtered by the namespace.
+    ///
+    /// Regions reading the same topic concurrently share one consumer of the topic, so the
+    /// stream must be consumed until it ends or be dropped.
     async fn read(
         &self,
         ns: &Self::Namespace,
@@ -123,6 +246,11 @@ impl LogStore for KafkaLogStore {
         let topic = ns.topic.clone();
         let region_id = ns.region_id;
 
+        self.client_manager.register_region(&topic, region_id);
+        if let Some(pruner) = &self.pruner {
+            pruner.register(ns, entry_id).await?;
+        }
+
         // Gets the client associated with the topic.
         let client = self
             .client_manager
@@ -131,15 +259,38 @@ impl LogStore for KafkaLogStore {
             .raw_client
             .clone();
 
-        // Reads the entries starting from exactly the specified offset.
-        let offset = Offset::try_from(entry_id)?.0;
-        let mut stream_consumer = StreamConsumerBuilder::new(client, StartOffset::At(offset))
-            .with_max_batch_size(self.config.max_batch_size.as_bytes() as i32)
-            .with_max_wait_ms(self.config.max_wait_time.as_millis() as i32)
-            .build();
+        // Reads the entries starting from the offset of the entry, or a larger offset
+        // that the offset index knows contains no required entries before it.
+        let offset = self.seek_offset(ns, entry_id).await?;
+        // Records written after this point are not required by the reader, e.g. the region
+        // doesn't accept writes while replaying, so we stop at the current end offset instead
+        // of waiting for new records.
+        let end_offset = end_offset(&client, &topic).await?;
+        if offset >= end_offset {
+            debug!(
+                "No entries to read for region {} in topic {}, start offset: {}, end offset: {}",
+                region_id, topic, offset, end_offset
+            );
+            return Ok(Box::pin(futures::stream::empty()));
+        }
+
+        let replayer = self.replayers.get_or_insert_with(&topic, || {
+            TopicReplayer::new(
+                topic.clone(),
+                client,
+                self.config.max_batch_size.as_bytes() as i32,
+                self.config.max_wait_time.as_millis() as i32,
+            )
+        });
+        let mut receiver = replayer.subscribe(
+            region_id,
+            offset,
+            end_offset,
+            self.config.replay_buffer_size,
+        );
         let stream = async_stream::stream!({
-            while let Some(consume_result) = stream_consumer.next().await {
-                yield handle_consume_result(consume_result, &topic, region_id, offset);
+            while let Some(entries) = receiver.recv().await {
+                yield entries;
             }
         });
         Ok(Box::pin(stream))
@@ -147,9 +298,8 @@ impl LogStore for KafkaLogStore {
 
     /// Creates a namespace of the associated Namespace type.
     fn namespace(&self, ns_id: NamespaceId, wal_options: &WalOptions) -> Self::Namespace {
-        // Safety: upon start, the datanode checks the consistency of the wal providers in the wal config of the
-        // datanode and that of the metasrv. Therefore, the wal options passed into the kafka log store
-        // must be of type WalOptions::Kafka.
+        // Safety: regions are opened only if their wal options are supported by the log store,
+        // see `supports_wal_options`.
         let WalOptions::Kafka(kafka_options) = wal_options else {
             unreachable!()
         };
@@ -159,13 +309,42 @@ impl LogStore for KafkaLogStore {
         }
     }
 
+    fn supports_wal_options(&self, wal_options: &WalOptions) -> bool {
+        matches!(wal_options, WalOptions::Kafka(_))
+    }
+
+    /// Returns the id of the latest entry in the topic of the namespace. The topic may
+    /// contain entries of other regions.
+    async fn latest_entry_id(&self, ns: &Self::Namespace) -> Result<Option<EntryId>> {
+        let client = self
+            .client_manager
+            .get_or_insert(&ns.topic)
+            .await?
+            .raw_client
+            .clone();
+        let end_offset = end_offset(&client, &ns.topic).await?;
+        if end_offset == 0 {
+            return Ok(None);
+        }
+        EntryId::try_from(Offset(end_offset - 1)).map(Some)
+    }
+
     /// Creates a new `Namespace` from the given ref.
     async fn create_namespace(&self, _ns: &Self::Namespace) -> Result<()> {
         Ok(())
     }
 
     /// Deletes an existing `Namespace` specified by the given ref.
-    async fn delete_namespace(&self, _ns: &Self::Namespace) -> Result<()> {
+    async fn delete_namespace(&self, ns: &Self::Namespace) -> Result<()> {
+        self.client_manager
+            .unregister_region(&ns.topic, ns.region_id);
+        if let Some(pruner) = &self.pruner {
+            // The region no longer requires any records.
+            pruner.unregister(ns).await?;
+        }
+        if let Some(offset_index) = &self.offset_index {
+            offset_index.unregister(ns).await?;
+        }
         Ok(())
     }
 
@@ -177,7 +356,36 @@ impl LogStore for KafkaLogStore {
     /// Marks all entries with ids `<=entry_id` of the given `namespace` as obsolete,
     /// so that the log store can safely delete those entries. This method does not guarantee
     /// that the obsolete entries are deleted immediately.
-    async fn obsolete(&self, _ns: Self::Namespace, _entry_id: EntryId) -> Result<()> {
+    async fn obsolete(&self, ns: Self::Namespace, entry_id: EntryId) -> Result<()> {
+        if let Some(offset_index) = &self.offset_index {
+            offset_index.truncate(&ns, entry_id).await?;
+        }
+        let Some(pruner) = &self.pruner else {
+            return Ok(());
+        };
+        pruner.update(&ns, entry_id).await?;
+        let Some(offset) = pruner.prunable_offset(&ns.topic).await? else {
+            return Ok(());
+        };
+
+        let client = self
+            .client_manager
+            .get_or_insert(&ns.topic)
+            .await?
+            .raw_client
+            .clone();
+        client
+            .delete_records(offset, DELETE_RECORDS_TIMEOUT_MS)
+            .await
+            .context(DeleteRecordsSnafu {
+                topic: &ns.topic,
+                offset,
+            })?;
+        pruner.on_pruned(&ns.topic, offset);
+        info!(
+            "Deleted records before offset {} of topic {}",
+            offset, ns.topic
+        );
         Ok(())
     }
 
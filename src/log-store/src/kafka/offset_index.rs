@@ -0,0 +1,285 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Maps entry ids of regions to offsets of their records, so replay seeks near the first
+//! required entry instead of scanning from a coarse offset.
+//!
+//! Once a region appends `interval` entries after its last checkpoint, the index persists a
+//! new checkpoint of `(entry id, offset)` to the kv backend, where `offset` is the offset of the
+//! record containing the entry. Entries with larger ids are appended after the checkpoint, so
+//! reading entries after the checkpoint can start from the offset next to it.
+
+use std::fmt;
+
+use common_meta::kv_backend::KvBackendRef;
+use common_meta::rpc::store::PutRequest;
+use dashmap::DashMap;
+use serde::{Deserialize, Serialize};
+use snafu::ResultExt;
+use store_api::logstore::entry::Id as EntryId;
+
+use crate::error::{AccessOffsetIndexSnafu, InvalidOffsetIndexSnafu, Result};
+use crate::kafka::NamespaceImpl;
+
+/// Prefix of keys to store checkpoints of regions.
+const OFFSET_INDEX_KEY_PREFIX: &str = "__wal_offset_index";
+/// Max number of checkpoints kept for each region.
+const MAX_CHECKPOINTS: usize = 16;
+
+fn region_key(ns: &NamespaceImpl) -> String {
+    format!("{OFFSET_INDEX_KEY_PREFIX}/{}/{}", ns.topic, ns.region_id)
+}
+
+/// The record at `offset` contains the entry with `entry_id`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+struct Checkpoint {
+    entry_id: EntryId,
+    offset: i64,
+}
+
+/// Checkpoints of a region in ascending order of entry ids.
+type Checkpoints = Vec<Checkpoint>;
+
+/// Persists sparse mappings from entry ids of regions to offsets of their records.
+pub(crate) struct OffsetIndex {
+    kv_backend: KvBackendRef,
+    /// Number of entries between two checkpoints of a region.
+    interval: u64,
+    /// Checkpoints of regions loaded from the kv backend.
+    checkpoints: DashMap<String, Checkpoints>,
+}
+
+impl fmt::Debug for OffsetIndex {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OffsetIndex")
+            .field("kv_backend", &self.kv_backend.name())
+            .field("interval", &self.interval)
+            .finish()
+    }
+}
+
+impl OffsetIndex {
+    pub(crate) fn new(kv_backend: KvBackendRef, interval: u64) -> OffsetIndex {
+        OffsetIndex {
+            kv_backend,
+            interval: interval.max(1),
+            checkpoints: DashMap::new(),
+        }
+    }
+
+    /// Records that the record at `offset` contains the entry with `entry_id` of the region,
+    /// and persists it if the region has appended enough entries after its last checkpoint.
+    pub(crate) async fn on_append(
+        &self,
+        ns: &NamespaceImpl,
+        entry_id: EntryId,
+        offset: i64,
+    ) -> Result<()> {
+        let key = region_key(ns);
+        self.load(ns, &key).await?;
+
+        let checkpoints = {
+            let Some(mut checkpoints) = self.checkpoints.get_mut(&key) else {
+                return Ok(());
+            };
+            if checkpoints
+                .last()
+                .is_some_and(|last| entry_id < last.entry_id.saturating_add(self.interval))
+            {
+                return Ok(());
+            }
+            checkpoints.push(Checkpoint { entry_id, offset });
+            if checkpoints.len() > MAX_CHECKPOINTS {
+                let _ = checkpoints.remove(0);
+            }
+            checkpoints.clone()
+        };
+        self.persist(ns, key, &checkpoints).await
+    }
+
+    /// Returns the offset to start reading entries with ids `>= entry_id` of the region,
+    /// or `None` if the region has no checkpoint before the entry.
+    pub(crate) async fn seek(&self, ns: &NamespaceImpl, entry_id: EntryId) -> Result<Option<i64>> {
+        let key = region_key(ns);
+        self.load(ns, &key).await?;
+
+        let Some(checkpoints) = self.checkpoints.get(&key) else {
+            return Ok(None);
+        };
+        Ok(checkpoints
+            .iter()
+            .rev()
+            .find(|checkpoint| checkpoint.entry_id < entry_id)
+            .map(|checkpoint| checkpoint.offset + 1))
+    }
+
+    /// Removes checkpoints that are no longer useful after entries with ids `<= entry_id`
+    /// of the region become obsolete. Only the last checkpoint before them is kept.
+    pub(crate) async fn truncate(&self, ns: &NamespaceImpl, entry_id: EntryId) -> Result<()> {
+        let key = region_key(ns);
+        self.load(ns, &key).await?;
+
+        let checkpoints = {
+            let Some(mut checkpoints) = self.checkpoints.get_mut(&key) else {
+                return Ok(());
+            };
+            let num_obsolete = checkpoints
+                .iter()
+                .take_while(|checkpoint| checkpoint.entry_id <= entry_id)
+                .count();
+            if num_obsolete <= 1 {
+                return Ok(());
+            }
+            let _ = checkpoints.drain(..num_obsolete - 1);
+            checkpoints.clone()
+        };
+        self.persist(ns, key, &checkpoints).await
+    }
+
+    /// Removes checkpoints of the region.
+    pub(crate) async fn unregister(&self, ns: &NamespaceImpl) -> Result<()> {
+        let key = region_key(ns);
+        let _ = self
+            .kv_backend
+            .delete(key.as_bytes(), false)
+            .await
+            .context(AccessOffsetIndexSnafu { topic: &ns.topic })?;
+        let _ = self.checkpoints.remove(&key);
+        Ok(())
+    }
+
+    /// Loads checkpoints of the region from the kv backend if they aren't loaded.
+    async fn load(&self, ns: &NamespaceImpl, key: &str) -> Result<()> {
+        if self.checkpoints.contains_key(key) {
+            return Ok(());
+        }
+
+        let checkpoints = match self
+            .kv_backend
+            .get(key.as_bytes())
+            .await
+            .context(AccessOffsetIndexSnafu { topic: &ns.topic })?
+        {
+            Some(kv) => {
+                serde_json::from_slice(&kv.value).context(InvalidOffsetIndexSnafu { key })?
+            }
+            None => Vec::new(),
+        };
+        let _ = self
+            .checkpoints
+            .entry(key.to_string())
+            .or_insert(checkpoints);
+        Ok(())
+    }
+
+    async fn persist(
+        &self,
+        ns: &NamespaceImpl,
+        key: String,
+        checkpoints: &Checkpoints,
+    ) -> Result<()> {
+        let value =
+            serde_json::to_vec(checkpoints).context(InvalidOffsetIndexSnafu { key: &key })?;
+        let req = PutRequest::new().with_key(key).with_value(value);
+        let _ = self
+            .kv_backend
+            .put(req)
+            .await
+            .context(AccessOffsetIndexSnafu { topic: &ns.topic })?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use common_meta::kv_backend::memory::MemoryKvBackend;
+
+    use super::*;
+
+    fn new_namespace(region_id: u64, topic: &str) -> NamespaceImpl {
+        NamespaceImpl {
+            region_id,
+            topic: topic.to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_seek() {
+        let kv_backend: KvBackendRef = Arc::new(MemoryKvBackend::new());
+        let index = OffsetIndex::new(kv_backend.clone(), 10);
+        let ns = new_namespace(1, "test_topic");
+
+        assert_eq!(None, index.seek(&ns, 5).await.unwrap());
+        index.on_append(&ns, 0, 100).await.unwrap();
+        // Less than `interval` entries after the last checkpoint.
+        index.on_append(&ns, 5, 150).await.unwrap();
+        index.on_append(&ns, 12, 200).await.unwrap();
+
+        assert_eq!(None, index.seek(&ns, 0).await.unwrap());
+        assert_eq!(Some(101), index.seek(&ns, 1).await.unwrap());
+        assert_eq!(Some(101), index.seek(&ns, 12).await.unwrap());
+        assert_eq!(Some(201), index.seek(&ns, 13).await.unwrap());
+
+        // Loads checkpoints from the kv backend after restart.
+        let index = OffsetIndex::new(kv_backend, 10);
+        assert_eq!(Some(201), index.seek(&ns, 13).await.unwrap());
+        // Other regions don't have checkpoints.
+        assert_eq!(
+            None,
+            index
+                .seek(&new_namespace(2, "test_topic"), 13)
+                .await
+                .unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_truncate_and_unregister() {
+        let kv_backend: KvBackendRef = Arc::new(MemoryKvBackend::new());
+        let index = OffsetIndex::new(kv_backend.clone(), 1);
+        let ns = new_namespace(1, "test_topic");
+        for i in 0..5 {
+            index.on_append(&ns, i * 10, i as i64 * 100).await.unwrap();
+        }
+
+        index.truncate(&ns, 25).await.unwrap();
+        // Keeps the last checkpoint before the obsolete entries.
+        assert_eq!(Some(201), index.seek(&ns, 26).await.unwrap());
+        assert_eq!(None, index.seek(&ns, 20).await.unwrap());
+
+        let index = OffsetIndex::new(kv_backend.clone(), 1);
+        assert_eq!(Some(201), index.seek(&ns, 26).await.unwrap());
+        index.unregister(&ns).await.unwrap();
+        assert_eq!(None, index.seek(&ns, 26).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_max_checkpoints() {
+        let kv_backend: KvBackendRef = Arc::new(MemoryKvBackend::new());
+        let index = OffsetIndex::new(kv_backend, 1);
+        let ns = new_namespace(1, "test_topic");
+        for i in 0..MAX_CHECKPOINTS as u64 + 2 {
+            index.on_append(&ns, i, i as i64).await.unwrap();
+        }
+        assert_eq!(
+            MAX_CHECKPOINTS,
+            index.checkpoints.get(&region_key(&ns)).unwrap().len()
+        );
+        // The oldest checkpoints are removed.
+        assert_eq!(None, index.seek(&ns, 2).await.unwrap());
+        assert_eq!(Some(3), index.seek(&ns, 3).await.unwrap());
+    }
+}
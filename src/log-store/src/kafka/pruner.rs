@@ -0,0 +1,217 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Prunes records of topics that are obsolete for all regions.
+//!
+//! A topic is shared by regions on different datanodes, so a record can only be deleted
+//! after all regions of the topic have flushed it. Each region stores the id of the first
+//! entry it still requires in the metadata kv backend, which is shared by datanodes through
+//! the metasrv. Records before the minimum of these ids in a topic can be deleted.
+
+use std::fmt;
+
+use common_meta::kv_backend::KvBackendRef;
+use common_meta::rpc::store::{PutRequest, RangeRequest};
+use dashmap::{DashMap, DashSet};
+use snafu::{OptionExt, ResultExt};
+use store_api::logstore::entry::Id as EntryId;
+
+use crate::error::{AccessPruneStateSnafu, InvalidPruneStateSnafu, Result};
+use crate::kafka::{NamespaceImpl, Topic};
+
+/// Prefix of keys to store the first required entry ids of regions.
+const PRUNE_KEY_PREFIX: &str = "__wal_prune";
+
+fn topic_prefix(topic: &str) -> String {
+    format!("{PRUNE_KEY_PREFIX}/{topic}/")
+}
+
+fn region_key(ns: &NamespaceImpl) -> String {
+    format!("{}{}", topic_prefix(&ns.topic), ns.region_id)
+}
+
+/// Tracks the first required entry id of regions and computes offsets to prune.
+pub(crate) struct WalPruner {
+    kv_backend: KvBackendRef,
+    /// Keys of regions that have been registered in the kv backend.
+    registered: DashSet<String>,
+    /// Offsets before which records of topics have been deleted.
+    pruned_offsets: DashMap<Topic, i64>,
+}
+
+impl fmt::Debug for WalPruner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WalPruner")
+            .field("kv_backend", &self.kv_backend.name())
+            .field("pruned_offsets", &self.pruned_offsets)
+            .finish()
+    }
+}
+
+impl WalPruner {
+    pub(crate) fn new(kv_backend: KvBackendRef) -> WalPruner {
+        WalPruner {
+            kv_backend,
+            registered: DashSet::new(),
+            pruned_offsets: DashMap::new(),
+        }
+    }
+
+    /// Registers the region of the namespace if it isn't registered, so records
+    /// from `first_required` won't be pruned until the region updates its state.
+    pub(crate) async fn register(&self, ns: &NamespaceImpl, first_required: EntryId) -> Result<()> {
+        let key = region_key(ns);
+        if self.registered.contains(&key) {
+            return Ok(());
+        }
+
+        // Keeps the existing state, e.g. the state stored before the datanode restarts.
+        let _ = self
+            .kv_backend
+            .put_conditionally(
+                key.clone().into_bytes(),
+                first_required.to_string().into_bytes(),
+                true,
+            )
+            .await
+            .context(AccessPruneStateSnafu { topic: &ns.topic })?;
+        self.registered.insert(key);
+        Ok(())
+    }
+
+    /// Marks entries of the region with ids `<= entry_id` as obsolete.
+    pub(crate) async fn update(&self, ns: &NamespaceImpl, entry_id: EntryId) -> Result<()> {
+        let key = region_key(ns);
+        let req = PutRequest::new()
+            .with_key(key.clone())
+            .with_value((entry_id + 1).to_string());
+        let _ = self
+            .kv_backend
+            .put(req)
+            .await
+            .context(AccessPruneStateSnafu { topic: &ns.topic })?;
+        self.registered.insert(key);
+        Ok(())
+    }
+
+    /// Removes the state of the region so it won't block pruning the topic.
+    pub(crate) async fn unregister(&self, ns: &NamespaceImpl) -> Result<()> {
+        let key = region_key(ns);
+        let _ = self
+            .kv_backend
+            .delete(key.as_bytes(), false)
+            .await
+            .context(AccessPruneStateSnafu { topic: &ns.topic })?;
+        self.registered.remove(&key);
+        Ok(())
+    }
+
+    /// Returns the offset before which records of the `topic` are obsolete for all regions,
+    /// or `None` if the offset doesn't advance.
+    pub(crate) async fn prunable_offset(&self, topic: &Topic) -> Result<Option<i64>> {
+        let req = RangeRequest::new().with_prefix(topic_prefix(topic));
+        let resp = self
+            .kv_backend
+            .range(req)
+            .await
+            .context(AccessPruneStateSnafu { topic })?;
+
+        let mut min_required: Option<EntryId> = None;
+        for kv in resp.kvs {
+            let value = String::from_utf8_lossy(&kv.value);
+            let first_required =
+                value
+                    .parse::<EntryId>()
+                    .ok()
+                    .with_context(|| InvalidPruneStateSnafu {
+                        key: String::from_utf8_lossy(&kv.key),
+                        value: value.to_string(),
+                    })?;
+            min_required = Some(min_required.map_or(first_required, |min| min.min(first_required)));
+        }
+
+        let Some(offset) = min_required.and_then(|id| i64::try_from(id).ok()) else {
+            return Ok(None);
+        };
+        let pruned = self.pruned_offsets.get(topic).map(|offset| *offset);
+        if offset == 0 || pruned.is_some_and(|pruned| pruned >= offset) {
+            return Ok(None);
+        }
+        Ok(Some(offset))
+    }
+
+    /// Records that records of the `topic` before the `offset` are deleted.
+    pub(crate) fn on_pruned(&self, topic: &Topic, offset: i64) {
+        self.pruned_offsets.insert(topic.clone(), offset);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use common_meta::kv_backend::memory::MemoryKvBackend;
+
+    use super::*;
+
+    fn new_namespace(region_id: u64, topic: &str) -> NamespaceImpl {
+        NamespaceImpl {
+            region_id,
+            topic: topic.to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_prunable_offset() {
+        let kv_backend: KvBackendRef = Arc::new(MemoryKvBackend::new());
+        let pruner = WalPruner::new(kv_backend);
+        let topic = "test_topic".to_string();
+        let ns1 = new_namespace(1, &topic);
+        let ns2 = new_namespace(2, &topic);
+        // Regions of other topics don't block pruning.
+        let other = new_namespace(3, "other_topic");
+
+        assert_eq!(None, pruner.prunable_offset(&topic).await.unwrap());
+        pruner.register(&ns1, 0).await.unwrap();
+        pruner.register(&ns2, 5).await.unwrap();
+        pruner.register(&other, 0).await.unwrap();
+        // Region 1 requires all records.
+        assert_eq!(None, pruner.prunable_offset(&topic).await.unwrap());
+
+        pruner.update(&ns1, 10).await.unwrap();
+        assert_eq!(Some(5), pruner.prunable_offset(&topic).await.unwrap());
+        pruner.on_pruned(&topic, 5);
+        assert_eq!(None, pruner.prunable_offset(&topic).await.unwrap());
+
+        pruner.update(&ns2, 20).await.unwrap();
+        assert_eq!(Some(11), pruner.prunable_offset(&topic).await.unwrap());
+
+        pruner.unregister(&ns1).await.unwrap();
+        assert_eq!(Some(21), pruner.prunable_offset(&topic).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_register_keeps_existing_state() {
+        let kv_backend: KvBackendRef = Arc::new(MemoryKvBackend::new());
+        let topic = "test_topic".to_string();
+        let ns = new_namespace(1, &topic);
+        let pruner = WalPruner::new(kv_backend.clone());
+        pruner.update(&ns, 10).await.unwrap();
+
+        // Registers the region again after restart.
+        let pruner = WalPruner::new(kv_backend);
+        pruner.register(&ns, 0).await.unwrap();
+        assert_eq!(Some(11), pruner.prunable_offset(&topic).await.unwrap());
+    }
+}
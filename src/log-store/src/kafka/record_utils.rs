@@ -12,25 +12,27 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use common_config::wal::KafkaWalTopic as Topic;
-use rskafka::record::{Record, RecordAndOffset};
+use common_telemetry::{debug, warn};
+use rskafka::record::Record;
 use serde::{Deserialize, Serialize};
 use snafu::{ensure, OptionExt, ResultExt};
 
 use crate::error::{
-    ConsumeRecordSnafu, DecodeMetaSnafu, EmptyEntriesSnafu, EncodeMetaSnafu, GetClientSnafu,
+    CorruptedEntrySnafu, DecodeMetaSnafu, EmptyEntriesSnafu, EncodeMetaSnafu, GetClientSnafu,
     MissingKeySnafu, MissingValueSnafu, ProduceRecordSnafu, Result,
 };
 use crate::kafka::client_manager::ClientManagerRef;
 use crate::kafka::offset::Offset;
 use crate::kafka::{EntryId, EntryImpl, NamespaceImpl};
 
-type ConsumeResult = std::result::Result<(RecordAndOffset, i64), rskafka::client::error::Error>;
+/// Size reserved for the key and headers of a record.
+const RECORD_META_RESERVED_SIZE: usize = 4 * 1024;
 
 /// Record metadata which will be serialized/deserialized to/from the `key` of a Record.
 #[derive(Debug, Serialize, Deserialize, PartialEq)]
 struct RecordMeta {
     /// Meta version. Used for backward compatibility.
+    /// Version 1 records may carry a chunk of an entry.
     version: u32,
     /// The namespace of the entries wrapped in the record.
     ns: NamespaceImpl,
@@ -38,12 +40,27 @@ struct RecordMeta {
     entry_ids: Vec<EntryId>,
     /// entry_offsets[i] is the end offset (exclusive) of the data of the i-th entry in the record value.
     entry_offsets: Vec<usize>,
+    /// The chunk of the entry if the record only contains part of an entry.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    chunk: Option<ChunkMeta>,
+}
+
+/// Metadata of a chunk of an entry that is too large to fit into one record.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+struct ChunkMeta {
+    /// Index of the chunk, starting from 0.
+    index: u32,
+    /// Total number of chunks of the entry.
+    num_chunks: u32,
+    /// CRC32 checksum of the data of the whole entry.
+    checksum: u32,
 }
 
 impl RecordMeta {
     fn new(ns: NamespaceImpl, entries: &[EntryImpl]) -> Self {
         Self {
             version: 0,
+            chunk: None,
             ns,
             entry_ids: entries.iter().map(|entry| entry.id).collect(),
             entry_offsets: entries
@@ -58,20 +75,24 @@ impl RecordMeta {
     }
 }
 
-/// Produces a record to a kafka topic.
+/// Produces records to a kafka topic.
 pub(crate) struct RecordProducer {
     /// The namespace of the entries.
     ns: NamespaceImpl,
-    /// Entries are buffered before being built into a record.
+    /// Entries are buffered before being built into records.
     entries: Vec<EntryImpl>,
+    /// Max size of a record.
+    max_record_size: usize,
 }
 
 impl RecordProducer {
     /// Creates a new producer for producing entries with the given namespace.
-    pub(crate) fn new(ns: NamespaceImpl) -> Self {
+    /// Entries are split into multiple records if they exceed the `max_record_size`.
+    pub(crate) fn new(ns: NamespaceImpl, max_record_size: usize) -> Self {
         Self {
             ns,
             entries: Vec::new(),
+            max_record_size,
         }
     }
 
@@ -80,15 +101,23 @@ impl RecordProducer {
         Self { entries, ..self }
     }
 
+    /// Returns the namespace of the entries.
+    pub(crate) fn namespace(&self) -> &NamespaceImpl {
+        &self.ns
+    }
+
+    /// Returns the id of the last buffered entry.
+    pub(crate) fn last_entry_id(&self) -> Option<EntryId> {
+        self.entries.last().map(|entry| entry.id)
+    }
+
     /// Pushes an entry into the entry buffer.
     pub(crate) fn push(&mut self, entry: EntryImpl) {
         self.entries.push(entry);
     }
 
-    /// Produces the buffered entries to kafka sever as a kafka record.
-    /// Returns the kafka offset of the produced record.
-    // TODO(niebayes): since the total size of a region's entries may be way-too large,
-    // the producer may need to support splitting entries into multiple records.
+    /// Produces the buffered entries to kafka sever as kafka records.
+    /// Returns the kafka offset of the last produced record.
     pub(crate) async fn produce(self, client_manager: &ClientManagerRef) -> Result<Offset> {
         ensure!(!self.entries.is_empty(), EmptyEntriesSnafu);
 
@@ -103,18 +132,93 @@ impl RecordProducer {
                 }
                 .build()
             })?;
-        client
-            .producer
-            .produce(encode_to_record(self.ns.clone(), self.entries)?)
-            .await
-            .map(Offset)
-            .context(ProduceRecordSnafu {
-                topic: &self.ns.topic,
-            })
+        let records = encode_to_records(
+            self.ns.clone(),
+            self.entries,
+            self.max_record_size
+                .saturating_sub(RECORD_META_RESERVED_SIZE),
+        )?;
+        // Produces records one by one so chunks of an entry are in order.
+        let mut offset = 0;
+        for record in records {
+            offset = client
+                .producer
+                .produce(record)
+                .await
+                .context(ProduceRecordSnafu {
+                    topic: &self.ns.topic,
+                })?;
+        }
+        Ok(Offset(offset))
+    }
+}
+
+/// Encodes entries into records whose values are at most `max_value_size` bytes.
+///
+/// Entries larger than `max_value_size` are split into chunks, one record for each chunk.
+fn encode_to_records(
+    ns: NamespaceImpl,
+    entries: Vec<EntryImpl>,
+    max_value_size: usize,
+) -> Result<Vec<Record>> {
+    let max_value_size = max_value_size.max(1);
+    let mut records = Vec::new();
+    let mut group = Vec::new();
+    let mut group_size = 0;
+    for entry in entries {
+        if !group.is_empty() && group_size + entry.data.len() > max_value_size {
+            records.push(encode_to_record(ns.clone(), std::mem::take(&mut group))?);
+            group_size = 0;
+        }
+
+        if entry.data.len() > max_value_size {
+            records.extend(encode_to_chunk_records(ns.clone(), entry, max_value_size)?);
+        } else {
+            group_size += entry.data.len();
+            group.push(entry);
+        }
+    }
+    if !group.is_empty() {
+        records.push(encode_to_record(ns, group)?);
     }
+    Ok(records)
+}
+
+/// Splits the entry into chunks of at most `chunk_size` bytes and encodes each chunk into a record.
+fn encode_to_chunk_records(
+    ns: NamespaceImpl,
+    entry: EntryImpl,
+    chunk_size: usize,
+) -> Result<Vec<Record>> {
+    let chunks: Vec<_> = entry.data.chunks(chunk_size).collect();
+    let num_chunks = chunks.len() as u32;
+    let checksum = crc32fast::hash(&entry.data);
+    chunks
+        .into_iter()
+        .enumerate()
+        .map(|(index, chunk)| {
+            let meta = RecordMeta {
+                version: 1,
+                ns: ns.clone(),
+                entry_ids: vec![entry.id],
+                entry_offsets: vec![chunk.len()],
+                chunk: Some(ChunkMeta {
+                    index: index as u32,
+                    num_chunks,
+                    checksum,
+                }),
+            };
+            Ok(Record {
+                key: Some(serde_json::to_vec(&meta).context(EncodeMetaSnafu)?),
+                value: Some(chunk.to_vec()),
+                timestamp: rskafka::chrono::Utc::now(),
+                headers: Default::default(),
+            })
+        })
+        .collect()
 }
 
-fn encode_to_record(ns: NamespaceImpl, entries: Vec<EntryImpl>) -> Result<Record> {
+pub(crate) fn encode_to_record(ns: NamespaceImpl, entries: Vec<EntryImpl>) -> Result<Record> {
     let meta = RecordMeta::new(ns, &entries);
     let data = entries.into_iter().flat_map(|entry| entry.data).collect();
     Ok(Record {
@@ -125,10 +229,13 @@ fn encode_to_record(ns: NamespaceImpl, entries: Vec<EntryImpl>) -> Result<Record
     })
 }
 
-fn decode_from_record(record: Record) -> Result<Vec<EntryImpl>> {
-    let key = record.key.context(MissingKeySnafu)?;
+fn decode_meta(record: &Record) -> Result<RecordMeta> {
+    let key = record.key.as_ref().context(MissingKeySnafu)?;
+    serde_json::from_slice(key).context(DecodeMetaSnafu)
+}
+
+fn decode_from_record(meta: RecordMeta, record: Record) -> Result<Vec<EntryImpl>> {
     let value = record.value.context(MissingValueSnafu)?;
-    let meta: RecordMeta = serde_json::from_slice(&key).context(DecodeMetaSnafu)?;
 
     let mut entries = Vec::with_capacity(meta.entry_ids.len());
     let mut start_offset = 0;
@@ -144,36 +251,137 @@ fn decode_from_record(record: Record) -> Result<Vec<EntryImpl>> {
     Ok(entries)
 }
 
-/// Handles the result of a consume operation on a kafka topic.
-pub(crate) fn handle_consume_result(
-    result: ConsumeResult,
-    topic: &Topic,
+/// An entry whose chunks are partially read.
+struct PartialEntry {
+    id: EntryId,
+    ns: NamespaceImpl,
+    data: Vec<u8>,
+    next_index: u32,
+    num_chunks: u32,
+    checksum: u32,
+}
+
+/// Decodes entries of a region from records and reassembles entries split into chunks.
+pub(crate) struct EntryDecoder {
     region_id: u64,
-    offset: i64,
-) -> Result<Vec<EntryImpl>> {
-    match result {
-        Ok((record_and_offset, _)) => {
-            // Only produces entries belong to the region with the given region id.
-            // Since a record only contains entries from a single region, it suffices to check the first entry only.
-            let entries = decode_from_record(record_and_offset.record)?;
-            if let Some(entry) = entries.first()
-                && entry.id == region_id
-            {
-                Ok(entries)
-            } else {
-                Ok(vec![])
+    /// The entry being reassembled.
+    partial: Option<PartialEntry>,
+}
+
+impl EntryDecoder {
+    /// Creates a decoder for entries of the region with `region_id`.
+    pub(crate) fn new(region_id: u64) -> Self {
+        Self {
+            region_id,
+            partial: None,
+        }
+    }
+
+    /// Decodes entries from the record. Returns an empty vector if the record doesn't
+    /// belong to the region or only contains part of an entry.
+    pub(crate) fn decode(&mut self, record: Record) -> Result<Vec<EntryImpl>> {
+        let meta = decode_meta(&record)?;
+        // Only produces entries belong to the region with the given region id.
+        // Since a record only contains entries from a single region, it suffices to check the namespace only.
+        if meta.ns.region_id != self.region_id {
+            return Ok(vec![]);
+        }
+        let Some(chunk) = meta.chunk else {
+            self.discard_partial();
+            return decode_from_record(meta, record);
+        };
+
+        let entry_id = meta
+            .entry_ids
+            .first()
+            .copied()
+            .context(CorruptedEntrySnafu {
+                region_id: self.region_id,
+                entry_id: None,
+                reason: "missing entry id of the chunk",
+            })?;
+        let value = record.value.context(MissingValueSnafu)?;
+        if chunk.index == 0 {
+            self.discard_partial();
+            self.partial = Some(PartialEntry {
+                id: entry_id,
+                ns: meta.ns,
+                data: Vec::new(),
+                next_index: 0,
+                num_chunks: chunk.num_chunks,
+                checksum: chunk.checksum,
+            });
+        }
+        let Some(partial) = self.partial.as_mut() else {
+            // We may start reading from the middle of an entry, skips its remaining chunks.
+            debug!(
+                "Skip chunk {} of entry {} in region {}",
+                chunk.index, entry_id, self.region_id
+            );
+            return Ok(vec![]);
+        };
+        if partial.id != entry_id || partial.next_index != chunk.index {
+            let partial_id = partial.id;
+            let expect_index = partial.next_index;
+            // The partial entry can't be completed, so decoding starts over from the next entry.
+            self.partial = None;
+            return CorruptedEntrySnafu {
+                region_id: self.region_id,
+                entry_id: Some(partial_id),
+                reason: format!(
+                    "expect chunk {} of entry {}, but got chunk {} of entry {}",
+                    expect_index, partial_id, chunk.index, entry_id
+                ),
             }
+            .fail();
+        }
+        partial.data.extend_from_slice(&value);
+        partial.next_index += 1;
+        if partial.next_index < partial.num_chunks {
+            return Ok(vec![]);
+        }
+
+        // Safety: the partial entry exists.
+        let partial = self.partial.take().unwrap();
+        ensure!(
+            crc32fast::hash(&partial.data) == partial.checksum,
+            CorruptedEntrySnafu {
+                region_id: self.region_id,
+                entry_id: Some(partial.id),
+                reason: format!("checksum mismatch of entry {}", partial.id),
+            }
+        );
+        Ok(vec![EntryImpl {
+            data: partial.data,
+            id: partial.id,
+            ns: partial.ns,
+        }])
+    }
+
+    /// Discards the entry being reassembled.
+    ///
+    /// The producer may fail after writing some chunks of an entry and the entry is
+    /// never acknowledged, so remaining chunks of the entry never come. Its retries or
+    /// later entries start from a new record.
+    fn discard_partial(&mut self) {
+        if let Some(partial) = self.partial.take() {
+            warn!(
+                "Discard incomplete entry {} of region {}, received {} of {} chunks",
+                partial.id, self.region_id, partial.next_index, partial.num_chunks
+            );
         }
-        Err(e) => Err(e).context(ConsumeRecordSnafu {
-            topic,
-            region_id,
-            offset,
-        }),
     }
 }
 
+/// Returns the id of the region whose entries are in the record.
+pub(crate) fn record_region_id(record: &Record) -> Result<u64> {
+    decode_meta(record).map(|meta| meta.ns.region_id)
+}
+
 #[cfg(test)]
 mod tests {
+    use store_api::logstore::LogStoreError;
+
     use super::*;
 
     fn new_test_entry<D: AsRef<[u8]>>(data: D, entry_id: EntryId, ns: NamespaceImpl) -> EntryImpl {
@@ -213,7 +421,103 @@ mod tests {
             new_test_entry(b"33333", 3, ns.clone()),
         ];
         let record = encode_to_record(ns, entries.clone()).unwrap();
-        let decoded_entries = decode_from_record(record).unwrap();
+        let meta = decode_meta(&record).unwrap();
+        let decoded_entries = decode_from_record(meta, record).unwrap();
         assert_eq!(entries, decoded_entries);
     }
+
+    fn decode_records(decoder: &mut EntryDecoder, records: Vec<Record>) -> Result<Vec<EntryImpl>> {
+        let mut entries = Vec::new();
+        for record in records {
+            entries.extend(decoder.decode(record)?);
+        }
+        Ok(entries)
+    }
+
+    #[test]
+    fn test_encdec_chunked_records() {
+        let ns = NamespaceImpl {
+            region_id: 1,
+            topic: "test_topic".to_string(),
+        };
+        let entries = vec![
+            new_test_entry(b"111", 1, ns.clone()),
+            new_test_entry(b"2222222222", 2, ns.clone()),
+            new_test_entry(b"33", 3, ns.clone()),
+            new_test_entry(b"4", 4, ns.clone()),
+        ];
+        let records = encode_to_records(ns.clone(), entries.clone(), 4).unwrap();
+        // [1], [2, 2, 2], [3, 4]
+        assert_eq!(5, records.len());
+        let mut decoder = EntryDecoder::new(1);
+        assert_eq!(entries, decode_records(&mut decoder, records).unwrap());
+
+        // Ignores records of other regions.
+        let mut decoder = EntryDecoder::new(2);
+        let records = encode_to_records(ns, entries, 4).unwrap();
+        assert!(decode_records(&mut decoder, records).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_decode_partial_chunks() {
+        let ns = NamespaceImpl {
+            region_id: 1,
+            topic: "test_topic".to_string(),
+        };
+        let entries = vec![
+            new_test_entry(b"1111111111", 1, ns.clone()),
+            new_test_entry(b"2222222222", 2, ns.clone()),
+        ];
+        let mut records = encode_to_records(ns, entries.clone(), 4).unwrap();
+        assert_eq!(6, records.len());
+
+        // Skips chunks of the first entry if we start reading from its middle.
+        let mut decoder = EntryDecoder::new(1);
+        let decoded = decode_records(&mut decoder, records[1..].to_vec()).unwrap();
+        assert_eq!(&entries[1..], &decoded);
+
+        // A chunk is missing.
+        let mut decoder = EntryDecoder::new(1);
+        let mut missing = records.clone();
+        missing.remove(4);
+        let err = decode_records(&mut decoder, missing).unwrap_err();
+        assert_eq!(Some(2), err.corrupted_entry_id());
+
+        // The data is corrupted.
+        let mut decoder = EntryDecoder::new(1);
+        records[1].value.as_mut().unwrap()[0] = b'0';
+        let err = decode_records(&mut decoder, records[..3].to_vec()).unwrap_err();
+        assert_eq!(Some(1), err.corrupted_entry_id());
+        // The decoder continues to decode the next entry.
+        let decoded = decode_records(&mut decoder, records[3..].to_vec()).unwrap();
+        assert_eq!(&entries[1..], &decoded);
+    }
+
+    #[test]
+    fn test_decode_interrupted_chunks() {
+        let ns = NamespaceImpl {
+            region_id: 1,
+            topic: "test_topic".to_string(),
+        };
+        let entries = vec![
+            new_test_entry(b"1111111111", 1, ns.clone()),
+            new_test_entry(b"22", 2, ns.clone()),
+        ];
+        let chunks = encode_to_records(ns.clone(), entries[..1].to_vec(), 4).unwrap();
+        assert_eq!(3, chunks.len());
+        let record = encode_to_record(ns, entries[1..].to_vec()).unwrap();
+
+        // The producer fails after writing the first chunk, then retries the entry.
+        let mut records = vec![chunks[0].clone()];
+        records.extend(chunks.iter().cloned());
+        let mut decoder = EntryDecoder::new(1);
+        let decoded = decode_records(&mut decoder, records).unwrap();
+        assert_eq!(&entries[..1], &decoded);
+
+        // The producer gives up the entry after writing two chunks and writes the next entry.
+        let records = vec![chunks[0].clone(), chunks[1].clone(), record];
+        let mut decoder = EntryDecoder::new(1);
+        let decoded = decode_records(&mut decoder, records).unwrap();
+        assert_eq!(&entries[1..], &decoded);
+    }
 }
@@ -0,0 +1,356 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Replays regions sharing a topic with one consumer, so regions opened concurrently don't
+//! consume the same records repeatedly.
+//!
+//! Regions reading a topic subscribe to the [TopicReplayer] of the topic. The replayer
+//! consumes the topic from the min start offset of its subscribers and sends entries of each
+//! region to its subscriber through a bounded channel, so the memory used by the replay is
+//! bounded and the replayer waits for regions that replay slowly. Regions subscribing while
+//! the replayer is running join the replay if the records they require haven't been consumed,
+//! otherwise they are replayed in the next round. Each topic has its own replayer so topics
+//! are replayed in parallel.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+use common_config::wal::KafkaWalTopic as Topic;
+use common_telemetry::info;
+use futures_util::StreamExt;
+use rskafka::client::consumer::{StartOffset, StreamConsumerBuilder};
+use rskafka::client::partition::PartitionClient;
+use rskafka::record::Record;
+use snafu::ResultExt;
+use tokio::sync::mpsc;
+
+use crate::error::{ConsumeRecordSnafu, Result};
+use crate::kafka::record_utils::{record_region_id, EntryDecoder};
+use crate::kafka::EntryImpl;
+use crate::metrics;
+
+pub(crate) type EntryReceiver = mpsc::Receiver<Result<Vec<EntryImpl>>>;
+
+/// A region replaying records of a topic.
+struct Subscriber {
+    region_id: u64,
+    /// Offset of the first record to replay.
+    start_offset: i64,
+    /// The replay of the region finishes before this offset.
+    end_offset: i64,
+    decoder: EntryDecoder,
+    sender: mpsc::Sender<Result<Vec<EntryImpl>>>,
+}
+
+#[derive(Default)]
+struct ReplayState {
+    /// Subscribers waiting to join the replay.
+    pending: Vec<Subscriber>,
+    /// Whether a task is consuming the topic.
+    running: bool,
+}
+
+/// Replays records of a topic to the regions subscribing to it.
+pub(crate) struct TopicReplayer {
+    topic: Topic,
+    client: Arc<PartitionClient>,
+    max_batch_size: i32,
+    max_wait_ms: i32,
+    state: Mutex<ReplayState>,
+}
+
+impl fmt::Debug for TopicReplayer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TopicReplayer")
+            .field("topic", &self.topic)
+            .finish()
+    }
+}
+
+impl TopicReplayer {
+    pub(crate) fn new(
+        topic: Topic,
+        client: Arc<PartitionClient>,
+        max_batch_size: i32,
+        max_wait_ms: i32,
+    ) -> Self {
+        Self {
+            topic,
+            client,
+            max_batch_size,
+            max_wait_ms,
+            state: Mutex::new(ReplayState::default()),
+        }
+    }
+
+    /// Subscribes records in `[start_offset, end_offset)` of the region, and starts the
+    /// replay if the replayer isn't running. The region must keep receiving entries until
+    /// the channel is closed or drop the receiver, otherwise other regions of the topic wait
+    /// for it once its buffer is full.
+    pub(crate) fn subscribe(
+        self: &Arc<Self>,
+        region_id: u64,
+        start_offset: i64,
+        end_offset: i64,
+        buffer_size: usize,
+    ) -> EntryReceiver {
+        let (sender, receiver) = mpsc::channel(buffer_size.max(1));
+        let mut state = self.state.lock().unwrap();
+        state.pending.push(Subscriber {
+            region_id,
+            start_offset,
+            end_offset,
+            decoder: EntryDecoder::new(region_id),
+            sender,
+        });
+        if !state.running {
+            state.running = true;
+            let replayer = self.clone();
+            let _handle = common_runtime::spawn_bg(replayer.run());
+        }
+        receiver
+    }
+
+    /// Replays the topic until there are no pending subscribers.
+    async fn run(self: Arc<Self>) {
+        loop {
+            let subscribers = {
+                let mut state = self.state.lock().unwrap();
+                if state.pending.is_empty() {
+                    state.running = false;
+                    return;
+                }
+                std::mem::take(&mut state.pending)
+            };
+            self.replay(subscribers).await;
+        }
+    }
+
+    /// Consumes the topic from the min start offset of the `subscribers` until all of them
+    /// finish the replay.
+    async fn replay(&self, mut subscribers: Vec<Subscriber>) {
+        // Safety: the replayer runs only if there are subscribers.
+        let start_offset = subscribers.iter().map(|s| s.start_offset).min().unwrap();
+        info!(
+            "Start replaying topic {} from offset {} for {} regions",
+            self.topic,
+            start_offset,
+            subscribers.len()
+        );
+        let _timer = metrics::KAFKA_REPLAY_ELAPSED
+            .with_label_values(&[self.topic.as_str()])
+            .start_timer();
+        let lag = metrics::KAFKA_REPLAY_LAG.with_label_values(&[self.topic.as_str()]);
+        let records_total =
+            metrics::KAFKA_REPLAY_RECORDS_TOTAL.with_label_values(&[self.topic.as_str()]);
+        let bytes_total =
+            metrics::KAFKA_REPLAY_BYTES_TOTAL.with_label_values(&[self.topic.as_str()]);
+
+        let mut consumer =
+            StreamConsumerBuilder::new(self.client.clone(), StartOffset::At(start_offset))
+                .with_max_batch_size(self.max_batch_size)
+                .with_max_wait_ms(self.max_wait_ms)
+                .build();
+        let mut next_offset = start_offset;
+        loop {
+            {
+                let mut state = self.state.lock().unwrap();
+                join_pending(&mut state.pending, &mut subscribers, next_offset);
+            }
+            if subscribers.is_empty() {
+                break;
+            }
+            let Some(result) = consumer.next().await else {
+                break;
+            };
+            let (record_and_offset, high_watermark) = match result {
+                Ok(result) => result,
+                Err(e) => {
+                    let error = Arc::new(e);
+                    for subscriber in subscribers.drain(..) {
+                        let result = Err(error.clone()).context(ConsumeRecordSnafu {
+                            topic: &self.topic,
+                            region_id: subscriber.region_id,
+                            offset: subscriber.start_offset,
+                        });
+                        let _ = subscriber.sender.send(result).await;
+                    }
+                    break;
+                }
+            };
+
+            let offset = record_and_offset.offset;
+            records_total.inc();
+            bytes_total.inc_by(record_and_offset.record.approximate_size() as u64);
+            lag.set((high_watermark - offset - 1).max(0));
+            dispatch(&mut subscribers, record_and_offset.record, offset).await;
+            next_offset = offset + 1;
+        }
+        lag.set(0);
+        info!(
+            "Finish replaying topic {} from offset {} to {}",
+            self.topic, start_offset, next_offset
+        );
+    }
+}
+
+/// Moves pending subscribers that only require records after `next_offset` to `subscribers`.
+fn join_pending(
+    pending: &mut Vec<Subscriber>,
+    subscribers: &mut Vec<Subscriber>,
+    next_offset: i64,
+) {
+    let mut i = 0;
+    while i < pending.len() {
+        if pending[i].start_offset >= next_offset {
+            subscribers.push(pending.swap_remove(i));
+        } else {
+            i += 1;
+        }
+    }
+}
+
+/// Sends entries in the record to the subscribers of its region, and removes subscribers
+/// that finish the replay.
+async fn dispatch(subscribers: &mut Vec<Subscriber>, record: Record, offset: i64) {
+    // A record only contains entries of a region. The record is sent to all subscribers
+    // if it's corrupted, so every region can decide how to handle it.
+    let region_id = record_region_id(&record).ok();
+    let targets = subscribers
+        .iter()
+        .enumerate()
+        .filter(|(_, s)| offset >= s.start_offset && region_id.map_or(true, |id| id == s.region_id))
+        .map(|(i, _)| i)
+        .collect::<Vec<_>>();
+
+    let mut record = Some(record);
+    for (n, i) in targets.iter().enumerate() {
+        // Safety: the record is only taken by the last target.
+        let record = if n + 1 == targets.len() {
+            record.take().unwrap()
+        } else {
+            record.clone().unwrap()
+        };
+        let subscriber = &mut subscribers[*i];
+        let entries = subscriber.decoder.decode(record);
+        if matches!(&entries, Ok(entries) if entries.is_empty()) {
+            continue;
+        }
+        // The region stops replaying if the receiver is dropped.
+        let _ = subscriber.sender.send(entries).await;
+    }
+
+    subscribers.retain(|s| !s.sender.is_closed() && offset + 1 < s.end_offset);
+}
+
+/// Replayers of topics.
+#[derive(Debug, Default)]
+pub(crate) struct TopicReplayers {
+    replayers: Mutex<HashMap<Topic, Arc<TopicReplayer>>>,
+}
+
+impl TopicReplayers {
+    /// Returns the replayer of the topic, or creates one by `create` if it doesn't exist.
+    pub(crate) fn get_or_insert_with(
+        &self,
+        topic: &Topic,
+        create: impl FnOnce() -> TopicReplayer,
+    ) -> Arc<TopicReplayer> {
+        self.replayers
+            .lock()
+            .unwrap()
+            .entry(topic.clone())
+            .or_insert_with(|| Arc::new(create()))
+            .clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::kafka::record_utils::encode_to_record;
+    use crate::kafka::NamespaceImpl;
+
+    fn new_subscriber(
+        region_id: u64,
+        start_offset: i64,
+        end_offset: i64,
+    ) -> (Subscriber, EntryReceiver) {
+        let (sender, receiver) = mpsc::channel(8);
+        let subscriber = Subscriber {
+            region_id,
+            start_offset,
+            end_offset,
+            decoder: EntryDecoder::new(region_id),
+            sender,
+        };
+        (subscriber, receiver)
+    }
+
+    fn new_record(region_id: u64, entry_id: u64) -> Record {
+        let ns = NamespaceImpl {
+            region_id,
+            topic: "test_topic".to_string(),
+        };
+        let entry = EntryImpl {
+            data: b"hello".to_vec(),
+            id: entry_id,
+            ns: ns.clone(),
+        };
+        encode_to_record(ns, vec![entry]).unwrap()
+    }
+
+    #[test]
+    fn test_join_pending() {
+        let mut pending = vec![
+            new_subscriber(1, 5, 10).0,
+            new_subscriber(2, 10, 20).0,
+            new_subscriber(3, 3, 20).0,
+        ];
+        let mut subscribers = Vec::new();
+        join_pending(&mut pending, &mut subscribers, 5);
+        let mut joined = subscribers.iter().map(|s| s.region_id).collect::<Vec<_>>();
+        joined.sort_unstable();
+        assert_eq!(vec![1, 2], joined);
+        assert_eq!(3, pending[0].region_id);
+    }
+
+    #[tokio::test]
+    async fn test_dispatch() {
+        let (subscriber1, mut receiver1) = new_subscriber(1, 0, 4);
+        let (subscriber2, mut receiver2) = new_subscriber(2, 1, 10);
+        let mut subscribers = vec![subscriber1, subscriber2];
+
+        // The record of region 2 is before its start offset.
+        dispatch(&mut subscribers, new_record(2, 0), 0).await;
+        dispatch(&mut subscribers, new_record(1, 1), 1).await;
+        dispatch(&mut subscribers, new_record(2, 2), 2).await;
+        assert_eq!(2, subscribers.len());
+        // Region 1 finishes the replay.
+        dispatch(&mut subscribers, new_record(1, 3), 3).await;
+        assert_eq!(1, subscribers.len());
+        drop(subscribers);
+
+        let entries = receiver1.recv().await.unwrap().unwrap();
+        assert_eq!(1, entries[0].id);
+        let entries = receiver1.recv().await.unwrap().unwrap();
+        assert_eq!(3, entries[0].id);
+        assert!(receiver1.recv().await.is_none());
+
+        let entries = receiver2.recv().await.unwrap().unwrap();
+        assert_eq!(2, entries[0].id);
+        assert!(receiver2.recv().await.is_none());
+    }
+}
@@ -16,7 +16,10 @@
 
 pub mod error;
 pub mod kafka;
+pub mod metrics;
+pub mod mixed;
 mod noop;
+pub mod object_store;
 pub mod raft_engine;
 pub mod test_util;
 
@@ -0,0 +1,108 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use lazy_static::lazy_static;
+use prometheus::*;
+
+/// Type label.
+pub const TYPE_LABEL: &str = "type";
+/// Topic label.
+pub const TOPIC_LABEL: &str = "topic";
+
+lazy_static! {
+    /// Total size of raft-engine WAL files in bytes.
+    pub static ref RAFT_ENGINE_WAL_BYTES: IntGauge = register_int_gauge!(
+        "logstore_raft_engine_wal_bytes",
+        "logstore raft-engine wal bytes"
+    )
+    .unwrap();
+    /// Counter of bytes written to raft-engine WAL.
+    pub static ref RAFT_ENGINE_WRITE_BYTES_TOTAL: IntCounter = register_int_counter!(
+        "logstore_raft_engine_write_bytes_total",
+        "logstore raft-engine write bytes total"
+    )
+    .unwrap();
+    /// Elapsed time to append entries to raft-engine WAL.
+    pub static ref RAFT_ENGINE_APPEND_ELAPSED: HistogramVec = register_histogram_vec!(
+        "logstore_raft_engine_append_elapsed",
+        "logstore raft-engine append elapsed",
+        &[TYPE_LABEL]
+    )
+    .unwrap();
+    /// Elapsed time to sync raft-engine WAL files.
+    pub static ref RAFT_ENGINE_SYNC_ELAPSED: HistogramVec = register_histogram_vec!(
+        "logstore_raft_engine_sync_elapsed",
+        "logstore raft-engine sync elapsed",
+        &[TYPE_LABEL]
+    )
+    .unwrap();
+    /// Counter of raft-engine WAL purges.
+    pub static ref RAFT_ENGINE_PURGE_TOTAL: IntCounter = register_int_counter!(
+        "logstore_raft_engine_purge_total",
+        "logstore raft-engine purge total"
+    )
+    .unwrap();
+    /// Counter of failed raft-engine WAL purges.
+    pub static ref RAFT_ENGINE_PURGE_ERRORS_TOTAL: IntCounter = register_int_counter!(
+        "logstore_raft_engine_purge_errors_total",
+        "logstore raft-engine purge errors total"
+    )
+    .unwrap();
+    /// Elapsed time of a raft-engine WAL purge.
+    pub static ref RAFT_ENGINE_PURGE_ELAPSED: Histogram = register_histogram!(
+        "logstore_raft_engine_purge_elapsed",
+        "logstore raft-engine purge elapsed"
+    )
+    .unwrap();
+    /// Counter of bytes uploaded to the object store WAL.
+    pub static ref OBJECT_STORE_WAL_UPLOAD_BYTES_TOTAL: IntCounter = register_int_counter!(
+        "logstore_object_store_wal_upload_bytes_total",
+        "logstore object store wal upload bytes total"
+    )
+    .unwrap();
+    /// Elapsed time to upload a segment of the object store WAL.
+    pub static ref OBJECT_STORE_WAL_UPLOAD_ELAPSED: Histogram = register_histogram!(
+        "logstore_object_store_wal_upload_elapsed",
+        "logstore object store wal upload elapsed"
+    )
+    .unwrap();
+    /// Counter of records consumed to replay regions from Kafka topics.
+    pub static ref KAFKA_REPLAY_RECORDS_TOTAL: IntCounterVec = register_int_counter_vec!(
+        "logstore_kafka_replay_records_total",
+        "logstore kafka replay records total",
+        &[TOPIC_LABEL]
+    )
+    .unwrap();
+    /// Counter of bytes consumed to replay regions from Kafka topics.
+    pub static ref KAFKA_REPLAY_BYTES_TOTAL: IntCounterVec = register_int_counter_vec!(
+        "logstore_kafka_replay_bytes_total",
+        "logstore kafka replay bytes total",
+        &[TOPIC_LABEL]
+    )
+    .unwrap();
+    /// Number of records to consume before the replay of a topic catches up with its high watermark.
+    pub static ref KAFKA_REPLAY_LAG: IntGaugeVec = register_int_gauge_vec!(
+        "logstore_kafka_replay_lag",
+        "logstore kafka replay lag",
+        &[TOPIC_LABEL]
+    )
+    .unwrap();
+    /// Elapsed time of a round of replaying a Kafka topic.
+    pub static ref KAFKA_REPLAY_ELAPSED: HistogramVec = register_histogram_vec!(
+        "logstore_kafka_replay_elapsed",
+        "logstore kafka replay elapsed",
+        &[TOPIC_LABEL]
+    )
+    .unwrap();
+}
@@ -0,0 +1,298 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A log store that routes namespaces to the raft-engine log store or the kafka log store
+//! by their wal options.
+
+use std::sync::Arc;
+
+use common_config::wal::WalOptions;
+use futures::StreamExt;
+use store_api::logstore::entry::{Entry, Id as EntryId};
+use store_api::logstore::entry_stream::SendableEntryStream;
+use store_api::logstore::namespace::{Id as NamespaceId, Namespace};
+use store_api::logstore::{AppendBatchResponse, AppendResponse, LogStore};
+
+use crate::error::{Error, Result};
+use crate::kafka::log_store::KafkaLogStore;
+use crate::kafka::{EntryImpl as KafkaEntry, NamespaceImpl as KafkaNamespace};
+use crate::raft_engine::log_store::RaftEngineLogStore;
+use crate::raft_engine::protos::logstore::{
+    EntryImpl as RaftEngineEntry, NamespaceImpl as RaftEngineNamespace,
+};
+
+/// Namespace of the [MixedLogStore].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum MixedNamespace {
+    RaftEngine(RaftEngineNamespace),
+    Kafka(KafkaNamespace),
+}
+
+impl Namespace for MixedNamespace {
+    fn id(&self) -> NamespaceId {
+        match self {
+            MixedNamespace::RaftEngine(ns) => ns.id(),
+            MixedNamespace::Kafka(ns) => ns.id(),
+        }
+    }
+}
+
+/// Entry of the [MixedLogStore].
+#[derive(Debug, Clone, PartialEq)]
+pub enum MixedEntry {
+    RaftEngine(RaftEngineEntry),
+    Kafka(KafkaEntry),
+}
+
+impl Entry for MixedEntry {
+    type Error = Error;
+    type Namespace = MixedNamespace;
+
+    fn data(&self) -> &[u8] {
+        match self {
+            MixedEntry::RaftEngine(entry) => entry.data(),
+            MixedEntry::Kafka(entry) => entry.data(),
+        }
+    }
+
+    fn id(&self) -> EntryId {
+        match self {
+            MixedEntry::RaftEngine(entry) => entry.id(),
+            MixedEntry::Kafka(entry) => entry.id(),
+        }
+    }
+
+    fn namespace(&self) -> Self::Namespace {
+        match self {
+            MixedEntry::RaftEngine(entry) => MixedNamespace::RaftEngine(entry.namespace()),
+            MixedEntry::Kafka(entry) => MixedNamespace::Kafka(entry.namespace()),
+        }
+    }
+}
+
+/// A log store that writes regions with raft-engine wal options to the local raft-engine
+/// and regions with kafka wal options to kafka.
+///
+/// It allows tables to choose their wal provider by the `wal.provider` table option.
+#[derive(Debug)]
+pub struct MixedLogStore {
+    raft_engine: Arc<RaftEngineLogStore>,
+    kafka: Arc<KafkaLogStore>,
+}
+
+impl MixedLogStore {
+    pub fn new(raft_engine: Arc<RaftEngineLogStore>, kafka: Arc<KafkaLogStore>) -> Self {
+        Self { raft_engine, kafka }
+    }
+}
+
+#[async_trait::async_trait]
+impl LogStore for MixedLogStore {
+    type Error = Error;
+    type Namespace = MixedNamespace;
+    type Entry = MixedEntry;
+
+    async fn stop(&self) -> Result<()> {
+        self.raft_engine.stop().await?;
+        self.kafka.stop().await
+    }
+
+    async fn append(&self, entry: Self::Entry) -> Result<AppendResponse> {
+        match entry {
+            MixedEntry::RaftEngine(entry) => self.raft_engine.append(entry).await,
+            MixedEntry::Kafka(entry) => self.kafka.append(entry).await,
+        }
+    }
+
+    async fn append_batch(&self, entries: Vec<Self::Entry>) -> Result<AppendBatchResponse> {
+        let mut raft_engine_entries = Vec::new();
+        let mut kafka_entries = Vec::new();
+        for entry in entries {
+            match entry {
+                MixedEntry::RaftEngine(entry) => raft_engine_entries.push(entry),
+                MixedEntry::Kafka(entry) => kafka_entries.push(entry),
+            }
+        }
+
+        let mut response = AppendBatchResponse::default();
+        if !raft_engine_entries.is_empty() {
+            let raft_engine_response = self.raft_engine.append_batch(raft_engine_entries).await?;
+            response
+                .last_entry_ids
+                .extend(raft_engine_response.last_entry_ids);
+        }
+        if !kafka_entries.is_empty() {
+            let kafka_response = self.kafka.append_batch(kafka_entries).await?;
+            response
+                .last_entry_ids
+                .extend(kafka_response.last_entry_ids);
+        }
+        Ok(response)
+    }
+
+    async fn read(
+        &self,
+        ns: &Self::Namespace,
+        entry_id: EntryId,
+    ) -> Result<SendableEntryStream<'_, Self::Entry, Self::Error>> {
+        let stream = match ns {
+            MixedNamespace::RaftEngine(ns) => {
+                let stream = self.raft_engine.read(ns, entry_id).await?;
+                stream
+                    .map(|entries| {
+                        entries.map(|entries| {
+                            entries.into_iter().map(MixedEntry::RaftEngine).collect()
+                        })
+                    })
+                    .boxed()
+            }
+            MixedNamespace::Kafka(ns) => {
+                let stream = self.kafka.read(ns, entry_id).await?;
+                stream
+                    .map(|entries| {
+                        entries.map(|entries| entries.into_iter().map(MixedEntry::Kafka).collect())
+                    })
+                    .boxed()
+            }
+        };
+        Ok(stream)
+    }
+
+    async fn latest_entry_id(&self, ns: &Self::Namespace) -> Result<Option<EntryId>> {
+        match ns {
+            MixedNamespace::RaftEngine(ns) => self.raft_engine.latest_entry_id(ns).await,
+            MixedNamespace::Kafka(ns) => self.kafka.latest_entry_id(ns).await,
+        }
+    }
+
+    async fn create_namespace(&self, ns: &Self::Namespace) -> Result<()> {
+        match ns {
+            MixedNamespace::RaftEngine(ns) => self.raft_engine.create_namespace(ns).await,
+            MixedNamespace::Kafka(ns) => self.kafka.create_namespace(ns).await,
+        }
+    }
+
+    async fn delete_namespace(&self, ns: &Self::Namespace) -> Result<()> {
+        match ns {
+            MixedNamespace::RaftEngine(ns) => self.raft_engine.delete_namespace(ns).await,
+            MixedNamespace::Kafka(ns) => self.kafka.delete_namespace(ns).await,
+        }
+    }
+
+    async fn list_namespaces(&self) -> Result<Vec<Self::Namespace>> {
+        let mut namespaces = self
+            .raft_engine
+            .list_namespaces()
+            .await?
+            .into_iter()
+            .map(MixedNamespace::RaftEngine)
+            .collect::<Vec<_>>();
+        namespaces.extend(
+            self.kafka
+                .list_namespaces()
+                .await?
+                .into_iter()
+                .map(MixedNamespace::Kafka),
+        );
+        Ok(namespaces)
+    }
+
+    fn entry<D: AsRef<[u8]>>(
+        &self,
+        data: D,
+        entry_id: EntryId,
+        ns: Self::Namespace,
+    ) -> Self::Entry {
+        match ns {
+            MixedNamespace::RaftEngine(ns) => {
+                MixedEntry::RaftEngine(self.raft_engine.entry(data, entry_id, ns))
+            }
+            MixedNamespace::Kafka(ns) => MixedEntry::Kafka(self.kafka.entry(data, entry_id, ns)),
+        }
+    }
+
+    fn namespace(&self, ns_id: NamespaceId, wal_options: &WalOptions) -> Self::Namespace {
+        match wal_options {
+            WalOptions::RaftEngine => {
+                MixedNamespace::RaftEngine(self.raft_engine.namespace(ns_id, wal_options))
+            }
+            WalOptions::Kafka(_) => MixedNamespace::Kafka(self.kafka.namespace(ns_id, wal_options)),
+        }
+    }
+
+    async fn obsolete(&self, ns: Self::Namespace, entry_id: EntryId) -> Result<()> {
+        match ns {
+            MixedNamespace::RaftEngine(ns) => self.raft_engine.obsolete(ns, entry_id).await,
+            MixedNamespace::Kafka(ns) => self.kafka.obsolete(ns, entry_id).await,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use common_config::wal::{KafkaConfig, KafkaWalOptions, RaftEngineConfig};
+    use common_telemetry::info;
+    use common_test_util::temp_dir::create_temp_dir;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_route_by_wal_options() {
+        let endpoints = env::var("GT_KAFKA_ENDPOINTS").unwrap_or_default();
+        common_telemetry::init_default_ut_logging();
+
+        if endpoints.is_empty() {
+            info!("The endpoints is empty, skipping the test.");
+            return;
+        }
+
+        let dir = create_temp_dir("mixed-logstore-test");
+        let raft_engine = RaftEngineLogStore::try_new(
+            dir.path().to_str().unwrap().to_string(),
+            RaftEngineConfig::default(),
+        )
+        .await
+        .unwrap();
+        let kafka = KafkaLogStore::try_new(&KafkaConfig {
+            broker_endpoints: endpoints.split(',').map(|s| s.to_string()).collect(),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+        let logstore = MixedLogStore::new(Arc::new(raft_engine), Arc::new(kafka));
+
+        let ns = logstore.namespace(1, &WalOptions::RaftEngine);
+        assert!(matches!(ns, MixedNamespace::RaftEngine(_)));
+        let kafka_ns = logstore.namespace(
+            2,
+            &WalOptions::Kafka(KafkaWalOptions {
+                topic: "greptimedb_mixed_logstore_test".to_string(),
+            }),
+        );
+        assert!(matches!(kafka_ns, MixedNamespace::Kafka(_)));
+        assert_eq!(2, kafka_ns.id());
+
+        let entry = logstore.entry("hello".as_bytes(), 1, ns.clone());
+        let response = logstore.append_batch(vec![entry]).await.unwrap();
+        assert_eq!(Some(&1), response.last_entry_ids.get(&1));
+
+        let mut stream = logstore.read(&ns, 1).await.unwrap();
+        let entries = stream.next().await.unwrap().unwrap();
+        assert_eq!(1, entries.len());
+        assert!(matches!(entries[0], MixedEntry::RaftEngine(_)));
+        assert_eq!(b"hello", entries[0].data());
+    }
+}
@@ -12,6 +12,8 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::collections::HashMap;
+
 use common_config::wal::WalOptions;
 use store_api::logstore::entry::{Entry, Id as EntryId};
 use store_api::logstore::namespace::{Id as NamespaceId, Namespace};
@@ -19,19 +21,28 @@ use store_api::logstore::{AppendBatchResponse, AppendResponse, LogStore};
 
 use crate::error::{Error, Result};
 
-/// A noop log store which only for test
+/// A log store that acknowledges appends without persisting entries.
+///
+/// It's unsafe for data that can't be written again, as data not flushed is lost once the
+/// datanode restarts. Reading from the log store always returns no entries.
 #[derive(Debug, Default)]
 pub struct NoopLogStore;
 
+/// An entry without data, only its id is kept to acknowledge the append.
 #[derive(Debug, Default, Clone, PartialEq)]
-pub struct EntryImpl;
+pub struct EntryImpl {
+    id: EntryId,
+    ns: NamespaceImpl,
+}
 
 #[derive(Debug, Clone, Default, Eq, PartialEq, Hash)]
-pub struct NamespaceImpl;
+pub struct NamespaceImpl {
+    region_id: u64,
+}
 
 impl Namespace for NamespaceImpl {
     fn id(&self) -> NamespaceId {
-        0
+        self.region_id
     }
 }
 
@@ -44,11 +55,11 @@ impl Entry for EntryImpl {
     }
 
     fn id(&self) -> EntryId {
-        0
+        self.id
     }
 
     fn namespace(&self) -> Self::Namespace {
-        Default::default()
+        self.ns.clone()
     }
 }
 
@@ -62,12 +73,20 @@ impl LogStore for NoopLogStore {
         Ok(())
     }
 
-    async fn append(&self, mut _e: Self::Entry) -> Result<AppendResponse> {
-        Ok(AppendResponse::default())
+    async fn append(&self, e: Self::Entry) -> Result<AppendResponse> {
+        Ok(AppendResponse {
+            last_entry_id: e.id,
+        })
     }
 
-    async fn append_batch(&self, _e: Vec<Self::Entry>) -> Result<AppendBatchResponse> {
-        Ok(AppendBatchResponse::default())
+    /// Acknowledges the last entry id of each region, as if entries are written.
+    async fn append_batch(&self, entries: Vec<Self::Entry>) -> Result<AppendBatchResponse> {
+        let mut last_entry_ids = HashMap::new();
+        for entry in entries {
+            let last_entry_id = last_entry_ids.entry(entry.ns.region_id).or_insert(entry.id);
+            *last_entry_id = (*last_entry_id).max(entry.id);
+        }
+        Ok(AppendBatchResponse { last_entry_ids })
     }
 
     async fn read(
@@ -81,6 +100,10 @@ impl LogStore for NoopLogStore {
         )))))
     }
 
+    async fn latest_entry_id(&self, _ns: &Self::Namespace) -> Result<Option<EntryId>> {
+        Ok(None)
+    }
+
     async fn create_namespace(&self, _ns: &Self::Namespace) -> Result<()> {
         Ok(())
     }
@@ -100,15 +123,12 @@ impl LogStore for NoopLogStore {
         ns: Self::Namespace,
     ) -> Self::Entry {
         let _ = data;
-        let _ = entry_id;
-        let _ = ns;
-        EntryImpl
+        EntryImpl { id: entry_id, ns }
     }
 
     fn namespace(&self, ns_id: NamespaceId, wal_options: &WalOptions) -> Self::Namespace {
-        let _ = ns_id;
         let _ = wal_options;
-        NamespaceImpl
+        NamespaceImpl { region_id: ns_id }
     }
 
     async fn obsolete(
@@ -128,7 +148,7 @@ mod tests {
 
     #[test]
     fn test_mock_entry() {
-        let e = EntryImpl;
+        let e = EntryImpl::default();
         assert_eq!(0, e.data().len());
         assert_eq!(0, e.id());
     }
@@ -136,13 +156,30 @@ mod tests {
     #[tokio::test]
     async fn test_noop_logstore() {
         let store = NoopLogStore;
-        let e = store.entry("".as_bytes(), 1, NamespaceImpl);
-        let _ = store.append(e.clone()).await.unwrap();
+        let ns = store.namespace(1, &WalOptions::default());
+        let e = store.entry("".as_bytes(), 1, ns.clone());
+        assert_eq!(1, store.append(e.clone()).await.unwrap().last_entry_id);
         assert!(store.append_batch(vec![e]).await.is_ok());
-        store.create_namespace(&NamespaceImpl).await.unwrap();
+        store.create_namespace(&ns).await.unwrap();
         assert_eq!(0, store.list_namespaces().await.unwrap().len());
-        store.delete_namespace(&NamespaceImpl).await.unwrap();
-        assert_eq!(NamespaceImpl, store.namespace(0, &WalOptions::default()));
-        store.obsolete(NamespaceImpl, 1).await.unwrap();
+        store.delete_namespace(&ns).await.unwrap();
+        assert_eq!(1, ns.id());
+        store.obsolete(ns, 1).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_append_batch() {
+        let store = NoopLogStore;
+        let ns1 = store.namespace(1, &WalOptions::default());
+        let ns2 = store.namespace(2, &WalOptions::default());
+        let entries = vec![
+            store.entry("a".as_bytes(), 3, ns1.clone()),
+            store.entry("b".as_bytes(), 4, ns1),
+            store.entry("c".as_bytes(), 10, ns2),
+        ];
+        let resp = store.append_batch(entries).await.unwrap();
+        assert_eq!(2, resp.last_entry_ids.len());
+        assert_eq!(4, resp.last_entry_ids[&1]);
+        assert_eq!(10, resp.last_entry_ids[&2]);
     }
 }
@@ -0,0 +1,763 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+
+use common_config::wal::{ObjectStoreWalConfig, WalOptions};
+use common_runtime::{RepeatedTask, TaskFunction};
+use common_telemetry::{error, info, warn};
+use dashmap::DashMap;
+use object_store::util::{join_dir, join_path};
+use object_store::{ErrorKind, ObjectStore};
+use snafu::{ensure, ResultExt};
+use store_api::logstore::entry::Id as EntryId;
+use store_api::logstore::entry_stream::SendableEntryStream;
+use store_api::logstore::namespace::Id as NamespaceId;
+use store_api::logstore::{AppendBatchResponse, AppendResponse, LogStore, LogStoreError};
+use tokio::fs::{self, File, OpenOptions};
+use tokio::io::AsyncWriteExt;
+use tokio::sync::Mutex;
+
+use crate::error::{
+    AccessLocalSegmentSnafu, AccessObjectStoreSnafu, Error, IllegalStateSnafu, Result,
+    StartGcTaskSnafu, StopGcTaskSnafu,
+};
+use crate::metrics::{OBJECT_STORE_WAL_UPLOAD_BYTES_TOTAL, OBJECT_STORE_WAL_UPLOAD_ELAPSED};
+use crate::object_store::segment::{
+    decode_entries, encode_entry, is_local_segment, local_segment_name,
+    parse_uploaded_segment_name, uploaded_segment_name,
+};
+use crate::object_store::{EntryImpl, NamespaceImpl};
+
+/// A log store that appends entries to local segment files and uploads segments to the
+/// object store, so the wal is durable without running Kafka.
+///
+/// Each region has its own segments. Entries in the segment being written are lost if the
+/// local disk is lost before the segment is uploaded.
+pub struct ObjectStoreLogStore {
+    segments: Arc<Segments>,
+    upload_task: RepeatedTask<Error>,
+}
+
+impl std::fmt::Debug for ObjectStoreLogStore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ObjectStoreLogStore")
+            .field("root", &self.segments.root)
+            .field("dir", &self.segments.dir)
+            .field("started", &self.upload_task.started())
+            .finish()
+    }
+}
+
+impl ObjectStoreLogStore {
+    /// Tries to create a log store that buffers segments in the local `dir` and uploads them
+    /// to the `object_store`.
+    ///
+    /// Segments left in the local `dir` by the last run are uploaded before the log store
+    /// starts.
+    pub async fn try_new(
+        dir: String,
+        object_store: ObjectStore,
+        config: &ObjectStoreWalConfig,
+    ) -> Result<Self> {
+        let segments = Arc::new(Segments {
+            object_store,
+            root: join_dir(&config.root, ""),
+            dir,
+            segment_size: config.segment_size.as_bytes() as usize,
+            sync_write: config.sync_write,
+            regions: DashMap::new(),
+        });
+        segments.upload_local_segments().await?;
+
+        let upload_task = RepeatedTask::new(
+            config.upload_interval,
+            Box::new(UploadSegmentsFunction {
+                segments: segments.clone(),
+            }),
+        );
+        upload_task
+            .start(common_runtime::bg_runtime())
+            .context(StartGcTaskSnafu)?;
+
+        Ok(Self {
+            segments,
+            upload_task,
+        })
+    }
+}
+
+/// Uploads segments that are not full periodically.
+struct UploadSegmentsFunction {
+    segments: Arc<Segments>,
+}
+
+#[async_trait::async_trait]
+impl TaskFunction<Error> for UploadSegmentsFunction {
+    fn name(&self) -> &str {
+        "ObjectStoreLogStore-upload-task"
+    }
+
+    async fn call(&mut self) -> Result<()> {
+        if let Err(e) = self.segments.upload_all().await {
+            error!(e; "Failed to upload wal segments");
+        }
+        Ok(())
+    }
+}
+
+/// An uploaded segment.
+#[derive(Debug, Clone)]
+struct UploadedSegment {
+    /// Id of the first entry.
+    start: EntryId,
+    /// Id of the last entry.
+    end: EntryId,
+    path: String,
+}
+
+/// The local segment being written.
+struct ActiveSegment {
+    /// Id of the first entry.
+    start: EntryId,
+    /// Id of the last entry.
+    end: EntryId,
+    size: usize,
+    path: String,
+    file: File,
+}
+
+/// Segments of a region.
+#[derive(Default)]
+struct RegionSegments {
+    /// Uploaded segments sorted by entry ids, `None` if they are not listed from the object
+    /// store yet.
+    uploaded: Option<Vec<UploadedSegment>>,
+    active: Option<ActiveSegment>,
+}
+
+/// Manages segments of all regions.
+struct Segments {
+    object_store: ObjectStore,
+    /// Root of uploaded segments in the object store.
+    root: String,
+    /// Local directory of segments not uploaded yet.
+    dir: String,
+    /// Max size of a segment in bytes.
+    segment_size: usize,
+    sync_write: bool,
+    regions: DashMap<u64, Arc<Mutex<RegionSegments>>>,
+}
+
+impl Segments {
+    fn region(&self, region_id: u64) -> Arc<Mutex<RegionSegments>> {
+        self.regions.entry(region_id).or_default().clone()
+    }
+
+    fn uploaded_dir(&self, region_id: u64) -> String {
+        join_dir(&self.root, &region_id.to_string())
+    }
+
+    fn local_dir(&self, region_id: u64) -> String {
+        Path::new(&self.dir)
+            .join(region_id.to_string())
+            .to_string_lossy()
+            .to_string()
+    }
+
+    /// Returns uploaded segments of the region, listing them from the object store if they
+    /// are not listed yet.
+    async fn uploaded<'a>(
+        &self,
+        region_id: u64,
+        region: &'a mut RegionSegments,
+    ) -> Result<&'a mut Vec<UploadedSegment>> {
+        if region.uploaded.is_none() {
+            let dir = self.uploaded_dir(region_id);
+            let entries = match self.object_store.list(&dir).await {
+                Ok(entries) => entries,
+                Err(e) if e.kind() == ErrorKind::NotFound => Vec::new(),
+                Err(e) => return Err(e).context(AccessObjectStoreSnafu { path: dir }),
+            };
+            let mut uploaded = entries
+                .into_iter()
+                .filter_map(|entry| {
+                    let (start, end) = parse_uploaded_segment_name(entry.name())?;
+                    Some(UploadedSegment {
+                        start,
+                        end,
+                        path: entry.path().to_string(),
+                    })
+                })
+                .collect::<Vec<_>>();
+            uploaded.sort_unstable_by_key(|segment| (segment.start, segment.end));
+            region.uploaded = Some(uploaded);
+        }
+
+        // Safety: uploaded segments are listed above.
+        Ok(region.uploaded.as_mut().unwrap())
+    }
+
+    /// Appends entries to the active segment of the region, and uploads the segment if it's
+    /// full.
+    async fn append(&self, region_id: u64, entries: &[EntryImpl]) -> Result<EntryId> {
+        let mut buf = Vec::new();
+        for entry in entries {
+            encode_entry(entry.id, &entry.data, &mut buf);
+        }
+        // Safety: entries are not empty.
+        let first_id = entries.first().unwrap().id;
+        let last_id = entries.last().unwrap().id;
+
+        let region = self.region(region_id);
+        let mut region = region.lock().await;
+        if region.active.is_none() {
+            let dir = self.local_dir(region_id);
+            fs::create_dir_all(&dir)
+                .await
+                .context(AccessLocalSegmentSnafu { path: &dir })?;
+            let path = Path::new(&dir)
+                .join(local_segment_name(first_id))
+                .to_string_lossy()
+                .to_string();
+            let file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&path)
+                .await
+                .context(AccessLocalSegmentSnafu { path: &path })?;
+            region.active = Some(ActiveSegment {
+                start: first_id,
+                end: first_id,
+                size: 0,
+                path,
+                file,
+            });
+        }
+        // Safety: the active segment is created above.
+        let active = region.active.as_mut().unwrap();
+
+        active
+            .file
+            .write_all(&buf)
+            .await
+            .context(AccessLocalSegmentSnafu { path: &active.path })?;
+        if self.sync_write {
+            active
+                .file
+                .sync_data()
+                .await
+                .context(AccessLocalSegmentSnafu { path: &active.path })?;
+        }
+        active.end = last_id;
+        active.size += buf.len();
+
+        if active.size >= self.segment_size {
+            self.upload_active(region_id, &mut region).await?;
+        }
+        Ok(last_id)
+    }
+
+    /// Uploads the active segment of the region and removes the local file.
+    async fn upload_active(&self, region_id: u64, region: &mut RegionSegments) -> Result<()> {
+        let Some(active) = &mut region.active else {
+            return Ok(());
+        };
+        active
+            .file
+            .flush()
+            .await
+            .context(AccessLocalSegmentSnafu { path: &active.path })?;
+        let (start, end, local_path) = (active.start, active.end, active.path.clone());
+
+        let path = self.upload_file(region_id, &local_path, start, end).await?;
+        region.active = None;
+        fs::remove_file(&local_path)
+            .await
+            .context(AccessLocalSegmentSnafu { path: &local_path })?;
+        self.uploaded(region_id, region)
+            .await?
+            .push(UploadedSegment { start, end, path });
+        Ok(())
+    }
+
+    /// Uploads the local segment file at `local_path` with entries in `[start, end]`, and
+    /// returns the path of the uploaded segment.
+    async fn upload_file(
+        &self,
+        region_id: u64,
+        local_path: &str,
+        start: EntryId,
+        end: EntryId,
+    ) -> Result<String> {
+        let _timer = OBJECT_STORE_WAL_UPLOAD_ELAPSED.start_timer();
+        let bytes = fs::read(local_path)
+            .await
+            .context(AccessLocalSegmentSnafu { path: local_path })?;
+        let path = join_path(
+            &self.uploaded_dir(region_id),
+            &uploaded_segment_name(start, end),
+        );
+        let size = bytes.len();
+        self.object_store
+            .write(&path, bytes)
+            .await
+            .context(AccessObjectStoreSnafu { path: &path })?;
+        OBJECT_STORE_WAL_UPLOAD_BYTES_TOTAL.inc_by(size as u64);
+        Ok(path)
+    }
+
+    /// Uploads active segments of all regions.
+    async fn upload_all(&self) -> Result<()> {
+        let regions = self
+            .regions
+            .iter()
+            .map(|entry| (*entry.key(), entry.value().clone()))
+            .collect::<Vec<_>>();
+        let mut result = Ok(());
+        for (region_id, region) in regions {
+            let mut region = region.lock().await;
+            if let Err(e) = self.upload_active(region_id, &mut region).await {
+                warn!(e; "Failed to upload wal segment of region {}", region_id);
+                result = Err(e);
+            }
+        }
+        result
+    }
+
+    /// Uploads segments left in the local directory, e.g. by a crash.
+    async fn upload_local_segments(&self) -> Result<()> {
+        let mut region_dirs = match fs::read_dir(&self.dir).await {
+            Ok(dirs) => dirs,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+            Err(e) => return Err(e).context(AccessLocalSegmentSnafu { path: &self.dir }),
+        };
+        while let Some(region_dir) = region_dirs
+            .next_entry()
+            .await
+            .context(AccessLocalSegmentSnafu { path: &self.dir })?
+        {
+            let Some(region_id) = region_dir
+                .file_name()
+                .to_str()
+                .and_then(|name| name.parse::<u64>().ok())
+            else {
+                continue;
+            };
+            let dir = self.local_dir(region_id);
+            let mut files = fs::read_dir(&dir)
+                .await
+                .context(AccessLocalSegmentSnafu { path: &dir })?;
+            while let Some(file) = files
+                .next_entry()
+                .await
+                .context(AccessLocalSegmentSnafu { path: &dir })?
+            {
+                if !file.file_name().to_str().is_some_and(is_local_segment) {
+                    continue;
+                }
+                let local_path = file.path().to_string_lossy().to_string();
+                let bytes = fs::read(&local_path)
+                    .await
+                    .context(AccessLocalSegmentSnafu { path: &local_path })?;
+                let entries = decode_entries(region_id, &bytes)
+                    .into_iter()
+                    .collect::<Result<Vec<_>>>()?;
+                if let (Some((start, _)), Some((end, _))) = (entries.first(), entries.last()) {
+                    let path = self
+                        .upload_file(region_id, &local_path, *start, *end)
+                        .await?;
+                    info!("Uploaded wal segment {} to {}", local_path, path);
+                }
+                fs::remove_file(&local_path)
+                    .await
+                    .context(AccessLocalSegmentSnafu { path: &local_path })?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Reads entries of the region with ids `>= start_id`.
+    ///
+    /// Corrupted entries are returned as errors between batches of other entries.
+    async fn read(&self, region_id: u64, start_id: EntryId) -> Result<Vec<Result<Vec<EntryImpl>>>> {
+        let region = self.region(region_id);
+        let mut region = region.lock().await;
+
+        let mut segments = Vec::new();
+        for segment in self.uploaded(region_id, &mut region).await? {
+            if segment.end < start_id {
+                continue;
+            }
+            let bytes =
+                self.object_store
+                    .read(&segment.path)
+                    .await
+                    .context(AccessObjectStoreSnafu {
+                        path: &segment.path,
+                    })?;
+            segments.push(bytes);
+        }
+        if let Some(active) = &mut region.active {
+            active
+                .file
+                .flush()
+                .await
+                .context(AccessLocalSegmentSnafu { path: &active.path })?;
+            let bytes = fs::read(&active.path)
+                .await
+                .context(AccessLocalSegmentSnafu { path: &active.path })?;
+            segments.push(bytes);
+        }
+
+        let ns = NamespaceImpl { region_id };
+        // Segments uploaded again after a crash may overlap, so entries are deduplicated by ids.
+        let mut next_id = start_id;
+        let mut batches = Vec::with_capacity(segments.len());
+        for bytes in segments {
+            let mut entries = Vec::new();
+            for entry in decode_entries(region_id, &bytes) {
+                match entry {
+                    Ok((id, data)) => {
+                        if id < next_id {
+                            continue;
+                        }
+                        next_id = id + 1;
+                        entries.push(EntryImpl {
+                            data,
+                            id,
+                            ns: ns.clone(),
+                        });
+                    }
+                    Err(e) => {
+                        if e.corrupted_entry_id().is_some_and(|id| id < next_id) {
+                            continue;
+                        }
+                        // Yields the corrupted entry in place so readers can skip it.
+                        if !entries.is_empty() {
+                            batches.push(Ok(std::mem::take(&mut entries)));
+                        }
+                        batches.push(Err(e));
+                    }
+                }
+            }
+            if !entries.is_empty() {
+                batches.push(Ok(entries));
+            }
+        }
+        Ok(batches)
+    }
+
+    /// Returns the id of the last entry of the region.
+    async fn latest_entry_id(&self, region_id: u64) -> Result<Option<EntryId>> {
+        let region = self.region(region_id);
+        let mut region = region.lock().await;
+        if let Some(active) = &region.active {
+            return Ok(Some(active.end));
+        }
+        Ok(self
+            .uploaded(region_id, &mut region)
+            .await?
+            .iter()
+            .map(|segment| segment.end)
+            .max())
+    }
+
+    /// Deletes uploaded segments whose entries are all `<= entry_id`.
+    async fn obsolete(&self, region_id: u64, entry_id: EntryId) -> Result<()> {
+        let region = self.region(region_id);
+        let mut region = region.lock().await;
+        let uploaded = self.uploaded(region_id, &mut region).await?;
+        while let Some(segment) = uploaded.first() {
+            if segment.end > entry_id {
+                break;
+            }
+            self.object_store
+                .delete(&segment.path)
+                .await
+                .context(AccessObjectStoreSnafu {
+                    path: &segment.path,
+                })?;
+            let _ = uploaded.remove(0);
+        }
+        Ok(())
+    }
+
+    /// Deletes all segments of the region.
+    async fn delete(&self, region_id: u64) -> Result<()> {
+        let region = self.region(region_id);
+        let mut region = region.lock().await;
+        region.active = None;
+        let local_dir = self.local_dir(region_id);
+        match fs::remove_dir_all(&local_dir).await {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                return Err(e).context(AccessLocalSegmentSnafu { path: local_dir });
+            }
+            _ => {}
+        }
+        let uploaded_dir = self.uploaded_dir(region_id);
+        self.object_store
+            .remove_all(&uploaded_dir)
+            .await
+            .context(AccessObjectStoreSnafu { path: uploaded_dir })?;
+        region.uploaded = Some(Vec::new());
+        Ok(())
+    }
+
+    /// Lists regions with segments.
+    async fn list_regions(&self) -> Result<Vec<u64>> {
+        let mut regions = match self.object_store.list(&self.root).await {
+            Ok(entries) => entries
+                .into_iter()
+                .filter_map(|entry| entry.name().strip_suffix('/')?.parse::<u64>().ok())
+                .collect::<Vec<_>>(),
+            Err(e) if e.kind() == ErrorKind::NotFound => Vec::new(),
+            Err(e) => {
+                return Err(e).context(AccessObjectStoreSnafu { path: &self.root });
+            }
+        };
+        regions.extend(self.regions.iter().map(|entry| *entry.key()));
+        regions.sort_unstable();
+        regions.dedup();
+        Ok(regions)
+    }
+}
+
+#[async_trait::async_trait]
+impl LogStore for ObjectStoreLogStore {
+    type Error = Error;
+    type Namespace = NamespaceImpl;
+    type Entry = EntryImpl;
+
+    /// Stops the upload task and uploads all segments.
+    async fn stop(&self) -> Result<()> {
+        self.upload_task.stop().await.context(StopGcTaskSnafu)?;
+        self.segments.upload_all().await
+    }
+
+    async fn append(&self, entry: Self::Entry) -> Result<AppendResponse> {
+        ensure!(self.upload_task.started(), IllegalStateSnafu);
+        let region_id = entry.ns.region_id;
+        let last_entry_id = self.segments.append(region_id, &[entry]).await?;
+        Ok(AppendResponse { last_entry_id })
+    }
+
+    /// Appends entries of different regions to their segments concurrently.
+    async fn append_batch(&self, entries: Vec<Self::Entry>) -> Result<AppendBatchResponse> {
+        ensure!(self.upload_task.started(), IllegalStateSnafu);
+        let mut region_entries: HashMap<u64, Vec<EntryImpl>> = HashMap::new();
+        for entry in entries {
+            region_entries
+                .entry(entry.ns.region_id)
+                .or_default()
+                .push(entry);
+        }
+
+        let tasks = region_entries
+            .iter()
+            .map(|(region_id, entries)| async move {
+                let last_entry_id = self.segments.append(*region_id, entries).await?;
+                Ok::<_, Error>((*region_id, last_entry_id))
+            });
+        let last_entry_ids = futures::future::try_join_all(tasks)
+            .await?
+            .into_iter()
+            .collect();
+        Ok(AppendBatchResponse { last_entry_ids })
+    }
+
+    async fn read(
+        &self,
+        ns: &Self::Namespace,
+        entry_id: EntryId,
+    ) -> Result<SendableEntryStream<Self::Entry, Self::Error>> {
+        let batches = self.segments.read(ns.region_id, entry_id).await?;
+        Ok(Box::pin(futures::stream::iter(batches)))
+    }
+
+    async fn latest_entry_id(&self, ns: &Self::Namespace) -> Result<Option<EntryId>> {
+        self.segments.latest_entry_id(ns.region_id).await
+    }
+
+    async fn create_namespace(&self, _ns: &Self::Namespace) -> Result<()> {
+        Ok(())
+    }
+
+    async fn delete_namespace(&self, ns: &Self::Namespace) -> Result<()> {
+        self.segments.delete(ns.region_id).await
+    }
+
+    async fn list_namespaces(&self) -> Result<Vec<Self::Namespace>> {
+        Ok(self
+            .segments
+            .list_regions()
+            .await?
+            .into_iter()
+            .map(|region_id| NamespaceImpl { region_id })
+            .collect())
+    }
+
+    fn entry<D: AsRef<[u8]>>(
+        &self,
+        data: D,
+        entry_id: EntryId,
+        ns: Self::Namespace,
+    ) -> Self::Entry {
+        EntryImpl {
+            data: data.as_ref().to_vec(),
+            id: entry_id,
+            ns,
+        }
+    }
+
+    fn namespace(&self, ns_id: NamespaceId, _wal_options: &WalOptions) -> Self::Namespace {
+        NamespaceImpl { region_id: ns_id }
+    }
+
+    fn supports_wal_options(&self, wal_options: &WalOptions) -> bool {
+        matches!(wal_options, WalOptions::RaftEngine)
+    }
+
+    async fn obsolete(&self, ns: Self::Namespace, entry_id: EntryId) -> Result<()> {
+        self.segments.obsolete(ns.region_id, entry_id).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use common_base::readable_size::ReadableSize;
+    use common_test_util::temp_dir::create_temp_dir;
+    use futures::TryStreamExt;
+    use object_store::services::Fs;
+
+    use super::*;
+
+    fn new_object_store(root: &str) -> ObjectStore {
+        let mut builder = Fs::default();
+        let _ = builder.root(root);
+        ObjectStore::new(builder).unwrap().finish()
+    }
+
+    async fn new_log_store(dir: &str, object_store: ObjectStore) -> ObjectStoreLogStore {
+        let config = ObjectStoreWalConfig {
+            segment_size: ReadableSize(64),
+            upload_interval: Duration::from_secs(3600),
+            sync_write: true,
+            ..Default::default()
+        };
+        ObjectStoreLogStore::try_new(dir.to_string(), object_store, &config)
+            .await
+            .unwrap()
+    }
+
+    async fn read_ids(log_store: &ObjectStoreLogStore, region_id: u64, start: u64) -> Vec<u64> {
+        let ns = NamespaceImpl { region_id };
+        log_store
+            .read(&ns, start)
+            .await
+            .unwrap()
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap()
+            .into_iter()
+            .flatten()
+            .map(|entry| entry.id)
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_append_and_read() {
+        let dir = create_temp_dir("object-store-wal-test");
+        let store_dir = create_temp_dir("object-store-wal-store");
+        let object_store = new_object_store(store_dir.path().to_str().unwrap());
+        let log_store = new_log_store(dir.path().to_str().unwrap(), object_store.clone()).await;
+
+        let ns = NamespaceImpl { region_id: 1 };
+        for id in 0..10 {
+            let resp = log_store
+                .append(log_store.entry(vec![id as u8; 16], id, ns.clone()))
+                .await
+                .unwrap();
+            assert_eq!(id, resp.last_entry_id);
+        }
+        let entries = (0..3)
+            .map(|id| log_store.entry(b"x", id, NamespaceImpl { region_id: 2 }))
+            .collect();
+        let resp = log_store.append_batch(entries).await.unwrap();
+        assert_eq!(HashMap::from([(2, 2)]), resp.last_entry_ids);
+
+        // Full segments are uploaded.
+        assert!(!object_store.list("wal/1/").await.unwrap().is_empty());
+        assert_eq!(
+            (0..10).collect::<Vec<_>>(),
+            read_ids(&log_store, 1, 0).await
+        );
+        assert_eq!(
+            (5..10).collect::<Vec<_>>(),
+            read_ids(&log_store, 1, 5).await
+        );
+        assert_eq!(Some(9), log_store.latest_entry_id(&ns).await.unwrap());
+
+        log_store.obsolete(ns.clone(), 5).await.unwrap();
+        let ids = read_ids(&log_store, 1, 0).await;
+        assert!(ids.first().unwrap() > &0);
+        assert_eq!(&9, ids.last().unwrap());
+
+        let mut regions = log_store
+            .list_namespaces()
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|ns| ns.region_id)
+            .collect::<Vec<_>>();
+        regions.sort_unstable();
+        assert_eq!(vec![1, 2], regions);
+
+        log_store.delete_namespace(&ns).await.unwrap();
+        assert!(read_ids(&log_store, 1, 0).await.is_empty());
+        log_store.stop().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_upload_local_segments_on_open() {
+        let dir = create_temp_dir("object-store-wal-test");
+        let store_dir = create_temp_dir("object-store-wal-store");
+        let object_store = new_object_store(store_dir.path().to_str().unwrap());
+        let dir = dir.path().to_str().unwrap();
+
+        let log_store = new_log_store(dir, object_store.clone()).await;
+        let ns = NamespaceImpl { region_id: 1 };
+        for id in 0..2 {
+            let _ = log_store
+                .append(log_store.entry(b"x", id, ns.clone()))
+                .await
+                .unwrap();
+        }
+        // Simulates a crash, the segment is not uploaded.
+        drop(log_store);
+        assert!(object_store
+            .list("wal/1/")
+            .await
+            .map(|entries| entries.is_empty())
+            .unwrap_or(true));
+
+        let log_store = new_log_store(dir, object_store.clone()).await;
+        assert_eq!(vec![0, 1], read_ids(&log_store, 1, 0).await);
+        log_store.stop().await.unwrap();
+    }
+}
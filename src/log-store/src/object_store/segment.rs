@@ -0,0 +1,154 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Encoding of segments.
+//!
+//! A segment is a sequence of records, each record is an entry encoded as:
+//! `entry id (u64) | data length (u32) | CRC32 checksum of data (u32) | data`,
+//! integers are in little endian.
+
+use store_api::logstore::entry::Id as EntryId;
+
+use crate::error::{CorruptedEntrySnafu, Result};
+
+/// Size of the header of a record.
+const HEADER_SIZE: usize = 16;
+/// Suffix of segment files.
+const SEGMENT_SUFFIX: &str = ".seg";
+
+/// Appends the record of an entry to `buf`.
+pub(crate) fn encode_entry(id: EntryId, data: &[u8], buf: &mut Vec<u8>) {
+    buf.extend_from_slice(&id.to_le_bytes());
+    buf.extend_from_slice(&(data.len() as u32).to_le_bytes());
+    buf.extend_from_slice(&crc32fast::hash(data).to_le_bytes());
+    buf.extend_from_slice(data);
+}
+
+/// Decodes entries in the segment of the region.
+///
+/// An incomplete record at the end, which is left by a crash during writing the segment, is
+/// ignored. A record whose checksum mismatches is decoded as an error, and records after
+/// it are still decoded.
+pub(crate) fn decode_entries(region_id: u64, bytes: &[u8]) -> Vec<Result<(EntryId, Vec<u8>)>> {
+    let mut entries = Vec::new();
+    let mut remaining = bytes;
+    while remaining.len() >= HEADER_SIZE {
+        // Safety: the header has 16 bytes.
+        let id = u64::from_le_bytes(remaining[0..8].try_into().unwrap());
+        let len = u32::from_le_bytes(remaining[8..12].try_into().unwrap()) as usize;
+        let checksum = u32::from_le_bytes(remaining[12..16].try_into().unwrap());
+        let Some(data) = remaining.get(HEADER_SIZE..HEADER_SIZE + len) else {
+            break;
+        };
+        let entry = if crc32fast::hash(data) == checksum {
+            Ok((id, data.to_vec()))
+        } else {
+            CorruptedEntrySnafu {
+                region_id,
+                entry_id: Some(id),
+                reason: format!("checksum mismatch of entry {id}"),
+            }
+            .fail()
+        };
+        entries.push(entry);
+        remaining = &remaining[HEADER_SIZE + len..];
+    }
+    entries
+}
+
+/// Returns the name of an uploaded segment that contains entries in `[start, end]`.
+pub(crate) fn uploaded_segment_name(start: EntryId, end: EntryId) -> String {
+    format!("{start:020}_{end:020}{SEGMENT_SUFFIX}")
+}
+
+/// Parses the range of entry ids from the name of an uploaded segment.
+pub(crate) fn parse_uploaded_segment_name(name: &str) -> Option<(EntryId, EntryId)> {
+    let (start, end) = name.strip_suffix(SEGMENT_SUFFIX)?.split_once('_')?;
+    Some((start.parse().ok()?, end.parse().ok()?))
+}
+
+/// Returns the name of a local segment whose first entry is `start`.
+pub(crate) fn local_segment_name(start: EntryId) -> String {
+    format!("{start:020}{SEGMENT_SUFFIX}")
+}
+
+/// Returns true if the file is a local segment.
+pub(crate) fn is_local_segment(name: &str) -> bool {
+    name.strip_suffix(SEGMENT_SUFFIX)
+        .map(|start| start.parse::<EntryId>().is_ok())
+        .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use store_api::logstore::LogStoreError;
+
+    use super::*;
+
+    fn decode_all(bytes: &[u8]) -> Result<Vec<(EntryId, Vec<u8>)>> {
+        decode_entries(1, bytes).into_iter().collect()
+    }
+
+    #[test]
+    fn test_encode_decode_entries() {
+        let mut buf = Vec::new();
+        encode_entry(1, b"hello", &mut buf);
+        encode_entry(2, b"", &mut buf);
+        encode_entry(3, b"world", &mut buf);
+        let expected = vec![
+            (1, b"hello".to_vec()),
+            (2, Vec::new()),
+            (3, b"world".to_vec()),
+        ];
+        assert_eq!(expected, decode_all(&buf).unwrap());
+
+        // Incomplete records at the end are ignored.
+        for len in [buf.len() - 1, buf.len() - 6, buf.len() - 20] {
+            assert_eq!(expected[..2], decode_all(&buf[..len]).unwrap());
+        }
+
+        // Corrupted data.
+        let last = buf.len() - 1;
+        buf[last] ^= 0xff;
+        assert!(decode_all(&buf).is_err());
+
+        // Records after the corrupted one are still decoded.
+        let mut buf = Vec::new();
+        encode_entry(1, b"hello", &mut buf);
+        let offset = buf.len();
+        encode_entry(2, b"world", &mut buf);
+        encode_entry(3, b"!", &mut buf);
+        buf[offset + HEADER_SIZE] ^= 0xff;
+        let decoded = decode_entries(1, &buf);
+        assert_eq!(3, decoded.len());
+        assert_eq!((1, b"hello".to_vec()), *decoded[0].as_ref().unwrap());
+        assert_eq!(
+            Some(2),
+            decoded[1].as_ref().unwrap_err().corrupted_entry_id()
+        );
+        assert_eq!((3, b"!".to_vec()), *decoded[2].as_ref().unwrap());
+    }
+
+    #[test]
+    fn test_segment_name() {
+        let name = uploaded_segment_name(5, 100);
+        assert_eq!("00000000000000000005_00000000000000000100.seg", name);
+        assert_eq!(Some((5, 100)), parse_uploaded_segment_name(&name));
+        assert_eq!(None, parse_uploaded_segment_name("5.seg"));
+
+        let name = local_segment_name(5);
+        assert!(is_local_segment(&name));
+        assert!(!is_local_segment(&uploaded_segment_name(5, 100)));
+    }
+}
@@ -14,23 +14,30 @@
 
 use std::collections::HashMap;
 use std::fmt::{Debug, Formatter};
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 
 use async_stream::stream;
 use common_config::wal::{RaftEngineConfig, WalOptions};
 use common_runtime::{RepeatedTask, TaskFunction};
-use common_telemetry::{error, info};
+use common_telemetry::{error, info, warn};
 use raft_engine::{Config, Engine, LogBatch, MessageExt, ReadableSize, RecoveryMode};
-use snafu::{ensure, ResultExt};
+use snafu::{ensure, IntoError, ResultExt};
 use store_api::logstore::entry::{Entry, Id as EntryId};
 use store_api::logstore::entry_stream::SendableEntryStream;
 use store_api::logstore::namespace::{Id as NamespaceId, Namespace as NamespaceTrait};
-use store_api::logstore::{AppendBatchResponse, AppendResponse, LogStore};
+use store_api::logstore::{AppendBatchResponse, AppendResponse, LogStore, LogStoreError};
 
 use crate::error;
 use crate::error::{
-    AddEntryLogBatchSnafu, Error, FetchEntrySnafu, IllegalNamespaceSnafu, IllegalStateSnafu,
-    OverrideCompactedEntrySnafu, RaftEngineSnafu, Result, StartGcTaskSnafu, StopGcTaskSnafu,
+    AddEntryLogBatchSnafu, CorruptedEntrySnafu, Error, FetchEntrySnafu, IllegalNamespaceSnafu,
+    IllegalStateSnafu, OverrideCompactedEntrySnafu, RaftEngineSnafu, Result, StartGcTaskSnafu,
+    StopGcTaskSnafu,
+};
+use crate::metrics::{
+    RAFT_ENGINE_APPEND_ELAPSED, RAFT_ENGINE_PURGE_ELAPSED, RAFT_ENGINE_PURGE_ERRORS_TOTAL,
+    RAFT_ENGINE_PURGE_TOTAL, RAFT_ENGINE_SYNC_ELAPSED, RAFT_ENGINE_WAL_BYTES,
+    RAFT_ENGINE_WRITE_BYTES_TOTAL,
 };
 use crate::raft_engine::backend::SYSTEM_NAMESPACE;
 use crate::raft_engine::protos::logstore::{EntryImpl, NamespaceImpl as Namespace};
@@ -41,10 +48,16 @@ pub struct RaftEngineLogStore {
     config: RaftEngineConfig,
     engine: Arc<Engine>,
     gc_task: RepeatedTask<Error>,
+    /// Task to sync log files periodically, if `sync_period` is set.
+    sync_task: Option<RepeatedTask<Error>>,
+    /// Size of bytes written since the last sync.
+    unsynced_bytes: Arc<AtomicU64>,
 }
 
 pub struct PurgeExpiredFilesFunction {
     engine: Arc<Engine>,
+    /// Size in bytes to trigger purge.
+    purge_threshold: u64,
 }
 
 #[async_trait::async_trait]
@@ -54,6 +67,8 @@ impl TaskFunction<Error> for PurgeExpiredFilesFunction {
     }
 
     async fn call(&mut self) -> Result<()> {
+        let _timer = RAFT_ENGINE_PURGE_ELAPSED.start_timer();
+        RAFT_ENGINE_PURGE_TOTAL.inc();
         match self.engine.purge_expired_files().context(RaftEngineSnafu) {
             Ok(res) => {
                 // TODO(hl): the retval of purge_expired_files indicates the namespaces need to be compact,
@@ -64,36 +79,138 @@ impl TaskFunction<Error> for PurgeExpiredFilesFunction {
                 );
             }
             Err(e) => {
+                RAFT_ENGINE_PURGE_ERRORS_TOTAL.inc();
                 error!(e; "Failed to purge files in logstore");
             }
         }
 
+        let used_size = update_wal_size_metric(&self.engine);
+        if used_size > self.purge_threshold {
+            // Files are only purged after all namespaces in them are obsoleted.
+            warn!(
+                "Logstore size {} bytes still exceeds the purge threshold {} bytes after purge, some regions may fail to flush",
+                used_size, self.purge_threshold
+            );
+        }
+
         Ok(())
     }
 }
 
+pub struct SyncFunction {
+    engine: Arc<Engine>,
+    unsynced_bytes: Arc<AtomicU64>,
+}
+
+#[async_trait::async_trait]
+impl TaskFunction<Error> for SyncFunction {
+    fn name(&self) -> &str {
+        "RaftEngineLogStore-sync-task"
+    }
+
+    async fn call(&mut self) -> Result<()> {
+        if self.unsynced_bytes.load(Ordering::Relaxed) == 0 {
+            return Ok(());
+        }
+        sync_engine(&self.engine, &self.unsynced_bytes, "periodic")
+    }
+}
+
+/// Syncs log files of the engine and resets the size of unsynced bytes.
+fn sync_engine(engine: &Engine, unsynced_bytes: &AtomicU64, sync_type: &str) -> Result<()> {
+    let _timer = RAFT_ENGINE_SYNC_ELAPSED
+        .with_label_values(&[sync_type])
+        .start_timer();
+    // Bytes written before the sync are persisted after the sync.
+    let _ = unsynced_bytes.swap(0, Ordering::Relaxed);
+    engine.sync().context(RaftEngineSnafu)
+}
+
+/// Updates the WAL size metric and returns the total size of WAL files in bytes.
+fn update_wal_size_metric(engine: &Engine) -> u64 {
+    let used_size = engine.get_used_size() as u64;
+    RAFT_ENGINE_WAL_BYTES.set(used_size as i64);
+    used_size
+}
+
+/// Fetches entries in `[start, end)` of the namespace one by one to locate the corrupted
+/// entry, as the engine fails to fetch the whole range if any entry in it is corrupted.
+///
+/// Returns entries before the corrupted entry and the error of the corrupted entry.
+fn fetch_until_corrupted(
+    engine: &Engine,
+    ns_id: u64,
+    start: EntryId,
+    end: EntryId,
+) -> (Vec<EntryImpl>, Option<Error>) {
+    let mut entries = Vec::new();
+    for index in start..end {
+        if let Err(e) =
+            engine.fetch_entries_to::<MessageType>(ns_id, index, index + 1, None, &mut entries)
+        {
+            let error = match e {
+                raft_engine::Error::Corruption(reason) => CorruptedEntrySnafu {
+                    region_id: ns_id,
+                    entry_id: Some(index),
+                    reason,
+                }
+                .build(),
+                e => FetchEntrySnafu {
+                    ns: ns_id,
+                    start: index,
+                    end: index + 1,
+                    max_size: 1usize,
+                }
+                .into_error(e),
+            };
+            return (entries, Some(error));
+        }
+    }
+    (entries, None)
+}
+
 impl RaftEngineLogStore {
     pub async fn try_new(dir: String, config: RaftEngineConfig) -> Result<Self> {
         let raft_engine_config = Config {
             dir,
             purge_threshold: ReadableSize(config.purge_threshold.0),
             recovery_mode: RecoveryMode::TolerateTailCorruption,
-            batch_compression_threshold: ReadableSize::kb(8),
+            recovery_threads: config.recovery_threads,
+            recovery_read_block_size: ReadableSize(config.recovery_read_block_size.0),
+            batch_compression_threshold: ReadableSize(config.batch_compression_threshold.0),
             target_file_size: ReadableSize(config.file_size.0),
             ..Default::default()
         };
         let engine = Arc::new(Engine::open(raft_engine_config).context(RaftEngineSnafu)?);
+        let _ = update_wal_size_metric(&engine);
         let gc_task = RepeatedTask::new(
             config.purge_interval,
             Box::new(PurgeExpiredFilesFunction {
                 engine: engine.clone(),
+                purge_threshold: config.purge_threshold.0,
             }),
         );
 
+        let unsynced_bytes = Arc::new(AtomicU64::new(0));
+        let sync_task = config
+            .sync_period
+            .filter(|_| !config.sync_write)
+            .map(|sync_period| {
+                RepeatedTask::new(
+                    sync_period,
+                    Box::new(SyncFunction {
+                        engine: engine.clone(),
+                        unsynced_bytes: unsynced_bytes.clone(),
+                    }),
+                )
+            });
+
         let log_store = Self {
             config,
             engine,
             gc_task,
+            sync_task,
+            unsynced_bytes,
         };
         log_store.start()?;
         Ok(log_store)
@@ -106,7 +223,13 @@ impl RaftEngineLogStore {
     fn start(&self) -> Result<()> {
         self.gc_task
             .start(common_runtime::bg_runtime())
-            .context(StartGcTaskSnafu)
+            .context(StartGcTaskSnafu)?;
+        if let Some(sync_task) = &self.sync_task {
+            sync_task
+                .start(common_runtime::bg_runtime())
+                .context(StartGcTaskSnafu)?;
+        }
+        Ok(())
     }
 
     fn span(&self, namespace: &<Self as LogStore>::Namespace) -> (Option<u64>, Option<u64>) {
@@ -116,6 +239,36 @@ impl RaftEngineLogStore {
         )
     }
 
+    /// Writes the `batch` of entries to the engine.
+    ///
+    /// The write is synced if `sync_write` is enabled. Otherwise, log files are synced once
+    /// bytes written since the last sync exceed `sync_bytes`.
+    fn write(&self, batch: &mut LogBatch) -> Result<()> {
+        let written = if self.config.sync_write {
+            let _timer = RAFT_ENGINE_SYNC_ELAPSED
+                .with_label_values(&["write"])
+                .start_timer();
+            self.engine.write(batch, true)
+        } else {
+            self.engine.write(batch, false)
+        }
+        .context(RaftEngineSnafu)? as u64;
+        RAFT_ENGINE_WRITE_BYTES_TOTAL.inc_by(written);
+
+        if !self.config.sync_write {
+            let unsynced_bytes =
+                self.unsynced_bytes.fetch_add(written, Ordering::Relaxed) + written;
+            if let Some(sync_bytes) = self.config.sync_bytes
+                && unsynced_bytes >= sync_bytes.as_bytes()
+            {
+                sync_engine(&self.engine, &self.unsynced_bytes, "bytes")?;
+            }
+        }
+
+        let _ = update_wal_size_metric(&self.engine);
+        Ok(())
+    }
+
     /// Checks if entry does not override the min index of namespace.
     fn check_entry(&self, e: &EntryImpl) -> Result<()> {
         if cfg!(debug_assertions) {
@@ -151,12 +304,20 @@ impl LogStore for RaftEngineLogStore {
     type Entry = EntryImpl;
 
     async fn stop(&self) -> Result<()> {
+        if let Some(sync_task) = &self.sync_task {
+            sync_task.stop().await.context(StopGcTaskSnafu)?;
+            // Syncs bytes written after the last periodic sync.
+            sync_engine(&self.engine, &self.unsynced_bytes, "periodic")?;
+        }
         self.gc_task.stop().await.context(StopGcTaskSnafu)
     }
 
     /// Appends an entry to logstore. Currently the existence of the entry's namespace is not checked.
     async fn append(&self, e: Self::Entry) -> Result<AppendResponse> {
         ensure!(self.started(), IllegalStateSnafu);
+        let _timer = RAFT_ENGINE_APPEND_ELAPSED
+            .with_label_values(&["append"])
+            .start_timer();
         let entry_id = e.id;
         let namespace_id = e.namespace_id;
         let mut batch = LogBatch::with_capacity(1);
@@ -175,10 +336,7 @@ impl LogStore for RaftEngineLogStore {
             );
         }
 
-        let _ = self
-            .engine
-            .write(&mut batch, self.config.sync_write)
-            .context(RaftEngineSnafu)?;
+        self.write(&mut batch)?;
         Ok(AppendResponse {
             last_entry_id: entry_id,
         })
@@ -191,6 +349,9 @@ impl LogStore for RaftEngineLogStore {
         if entries.is_empty() {
             return Ok(AppendBatchResponse::default());
         }
+        let _timer = RAFT_ENGINE_APPEND_ELAPSED
+            .with_label_values(&["append_batch"])
+            .start_timer();
 
         // Records the last entry id for each region's entries.
         let mut last_entry_ids: HashMap<NamespaceId, EntryId> =
@@ -210,10 +371,7 @@ impl LogStore for RaftEngineLogStore {
                 .context(AddEntryLogBatchSnafu)?;
         }
 
-        let _ = self
-            .engine
-            .write(&mut batch, self.config.sync_write)
-            .context(RaftEngineSnafu)?;
+        self.write(&mut batch)?;
 
         Ok(AppendBatchResponse { last_entry_ids })
     }
@@ -266,6 +424,30 @@ impl LogStore for RaftEngineLogStore {
                             break;
                         }
                     }
+                    Err(Error::FetchEntry {
+                        error: raft_engine::Error::Corruption(_),
+                        ..
+                    }) => {
+                        let end = (start_index + max_batch_size as u64).min(last_index + 1);
+                        let (entries, error) =
+                            fetch_until_corrupted(&engine, ns.id, start_index, end);
+                        if !entries.is_empty() && tx.send(Ok(entries)).await.is_err() {
+                            break;
+                        }
+                        let Some(error) = error else {
+                            start_index = end;
+                            continue;
+                        };
+                        let Some(entry_id) = error.corrupted_entry_id() else {
+                            let _ = tx.send(Err(error)).await;
+                            break;
+                        };
+                        // Continues to read entries after the corrupted entry.
+                        start_index = entry_id + 1;
+                        if tx.send(Err(error)).await.is_err() {
+                            break;
+                        }
+                    }
                     Err(e) => {
                         let _ = tx.send(Err(e)).await;
                         break;
@@ -282,6 +464,11 @@ impl LogStore for RaftEngineLogStore {
         Ok(Box::pin(s))
     }
 
+    async fn latest_entry_id(&self, ns: &Self::Namespace) -> Result<Option<EntryId>> {
+        ensure!(self.started(), IllegalStateSnafu);
+        Ok(self.engine.last_index(ns.id()))
+    }
+
     async fn create_namespace(&self, ns: &Self::Namespace) -> Result<()> {
         ensure!(
             ns.id != SYSTEM_NAMESPACE,
@@ -356,6 +543,10 @@ impl LogStore for RaftEngineLogStore {
         }
     }
 
+    fn supports_wal_options(&self, wal_options: &WalOptions) -> bool {
+        matches!(wal_options, WalOptions::RaftEngine)
+    }
+
     async fn obsolete(&self, ns: Self::Namespace, entry_id: EntryId) -> Result<()> {
         ensure!(self.started(), IllegalStateSnafu);
         let obsoleted = self.engine.compact_to(ns.id(), entry_id + 1);
@@ -568,6 +759,7 @@ mod tests {
             before_purge, after_purge
         );
         assert!(before_purge > after_purge);
+        assert!(crate::metrics::RAFT_ENGINE_PURGE_TOTAL.get() > 0);
     }
 
     #[tokio::test]
@@ -591,6 +783,55 @@ mod tests {
         assert_eq!(101, vec.first().unwrap().id);
     }
 
+    #[tokio::test]
+    async fn test_latest_entry_id() {
+        let dir = create_temp_dir("raft-engine-logstore-test");
+        let logstore = new_test_log_store(&dir).await;
+
+        let namespace = Namespace::with_id(42);
+        assert_eq!(None, logstore.latest_entry_id(&namespace).await.unwrap());
+        for id in 0..10 {
+            let entry = Entry::create(id, namespace.id(), b"x".to_vec());
+            let _ = logstore.append(entry).await.unwrap();
+        }
+        assert_eq!(Some(9), logstore.latest_entry_id(&namespace).await.unwrap());
+        assert_eq!(
+            None,
+            logstore
+                .latest_entry_id(&Namespace::with_id(43))
+                .await
+                .unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_sync_bytes() {
+        let dir = create_temp_dir("raft-engine-logstore-test");
+        let config = RaftEngineConfig {
+            sync_bytes: Some(ReadableSize::kb(8)),
+            sync_period: Some(Duration::from_secs(3600)),
+            ..Default::default()
+        };
+        let logstore =
+            RaftEngineLogStore::try_new(dir.path().to_str().unwrap().to_string(), config)
+                .await
+                .unwrap();
+
+        let namespace = Namespace::with_id(42);
+        let entry = Entry::create(0, namespace.id(), [b'x'; 4096].to_vec());
+        let _ = logstore.append(entry).await.unwrap();
+        assert!(logstore.unsynced_bytes.load(Ordering::Relaxed) >= 4096);
+
+        let entry = Entry::create(1, namespace.id(), [b'x'; 4096].to_vec());
+        let _ = logstore.append(entry).await.unwrap();
+        assert_eq!(0, logstore.unsynced_bytes.load(Ordering::Relaxed));
+
+        let entry = Entry::create(2, namespace.id(), b"x".to_vec());
+        let _ = logstore.append(entry).await.unwrap();
+        logstore.stop().await.unwrap();
+        assert_eq!(0, logstore.unsynced_bytes.load(Ordering::Relaxed));
+    }
+
     #[tokio::test]
     async fn test_append_batch() {
         common_telemetry::init_default_ut_logging();
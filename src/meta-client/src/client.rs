@@ -27,6 +27,7 @@ use common_meta::ddl::{DdlTaskExecutor, ExecutorContext};
 use common_meta::error::{self as meta_error, Result as MetaResult};
 use common_meta::rpc::ddl::{SubmitDdlTaskRequest, SubmitDdlTaskResponse};
 use common_meta::rpc::lock::{LockRequest, LockResponse, UnlockRequest};
+use common_meta::rpc::procedure::ProcedureStateResponse;
 use common_meta::rpc::store::{
     BatchDeleteRequest, BatchDeleteResponse, BatchGetRequest, BatchGetResponse, BatchPutRequest,
     BatchPutResponse, CompareAndPutRequest, CompareAndPutResponse, DeleteRangeRequest,
@@ -191,6 +192,18 @@ impl DdlTaskExecutor for MetaClient {
             .map_err(BoxedError::new)
             .context(meta_error::ExternalSnafu)
     }
+
+    async fn query_procedure_state(
+        &self,
+        _ctx: &ExecutorContext,
+        _procedure_id: &str,
+    ) -> MetaResult<Option<ProcedureStateResponse>> {
+        // TODO(agent): query the procedure state from metasrv once it provides the rpc.
+        meta_error::UnsupportedSnafu {
+            operation: "query procedure state from metasrv",
+        }
+        .fail()
+    }
 }
 
 impl MetaClient {
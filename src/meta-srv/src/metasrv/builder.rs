@@ -21,6 +21,7 @@ use common_base::Plugins;
 use common_catalog::consts::MIN_USER_TABLE_ID;
 use common_grpc::channel_manager::ChannelConfig;
 use common_meta::datanode_manager::DatanodeManagerRef;
+use common_meta::ddl::table_meta::DefaultTableMetadataAllocator;
 use common_meta::ddl::TableMetadataAllocatorRef;
 use common_meta::ddl_manager::{DdlManager, DdlManagerRef};
 use common_meta::distributed_time_constants;
@@ -62,7 +63,7 @@ use crate::selector::lease_based::LeaseBasedSelector;
 use crate::service::mailbox::MailboxRef;
 use crate::service::store::cached_kv::{CheckLeader, LeaderCachedKvBackend};
 use crate::state::State;
-use crate::table_meta_alloc::MetaSrvTableMetadataAllocator;
+use crate::table_meta_alloc::MetaSrvPeerAllocator;
 
 // TODO(fys): try use derive_builder macro
 pub struct MetaSrvBuilder {
@@ -216,12 +217,13 @@ impl MetaSrvBuilder {
                     .step(10)
                     .build(),
             );
-            Arc::new(MetaSrvTableMetadataAllocator::new(
-                selector_ctx.clone(),
-                selector.clone(),
-                sequence.clone(),
-                wal_options_allocator.clone(),
-            ))
+            Arc::new(
+                DefaultTableMetadataAllocator::new(sequence, wal_options_allocator.clone())
+                    .with_peer_allocator(Arc::new(MetaSrvPeerAllocator::new(
+                        selector_ctx.clone(),
+                        selector.clone(),
+                    ))),
+            )
         });
 
         let opening_region_keeper = Arc::new(MemoryRegionKeeper::default());
@@ -28,7 +28,7 @@ use common_catalog::consts::MITO2_ENGINE;
 use common_meta::datanode_manager::DatanodeManagerRef;
 use common_meta::ddl::alter_table::AlterTableProcedure;
 use common_meta::ddl::create_table::*;
-use common_meta::ddl::drop_table::DropTableProcedure;
+use common_meta::ddl::drop_table::{DropTableProcedure, DropTableState};
 use common_meta::key::table_info::TableInfoValue;
 use common_meta::key::table_route::TableRouteValue;
 use common_meta::key::DeserializedValueWithBytes;
@@ -244,7 +244,7 @@ async fn test_on_datanode_drop_regions() {
     let region_routes = test_data::new_region_routes();
     let datanode_manager = new_datanode_manager(&region_server, &region_routes).await;
 
-    let procedure = DropTableProcedure::new(
+    let mut procedure = DropTableProcedure::new(
         1,
         drop_table_task,
         DeserializedValueWithBytes::from_inner(TableRouteValue::physical(region_routes)),
@@ -275,7 +275,12 @@ async fn test_on_datanode_drop_regions() {
     });
 
     let status = procedure.on_datanode_drop_regions().await.unwrap();
-    assert!(matches!(status, Status::Done));
+    assert!(matches!(status, Status::Executing { persist: true }));
+    assert!(matches!(
+        procedure.data.state,
+        DropTableState::RemoveMetadata
+    ));
+    assert_eq!(3, procedure.dropping_regions.len());
 
     handle.await.unwrap();
 
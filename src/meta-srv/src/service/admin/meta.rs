@@ -19,7 +19,7 @@ use common_meta::key::table_name::TableNameKey;
 use common_meta::key::TableMetadataManagerRef;
 use futures::TryStreamExt;
 use snafu::ResultExt;
-use store_api::storage::{RegionId, TableId};
+use store_api::storage::TableId;
 use tonic::codegen::http;
 
 use crate::error;
@@ -140,6 +140,7 @@ impl HttpHandler for TableHandler {
             return util::to_text_response(
                 r#"
             - GET /table?region_ids=1,2,3,4,5
+            - GET /table?region_ids=foo.bar.baz#1,foo.bar.baz#2
             - GET /table?table_ids=1,2,3,4,5
             - GET /table?catalog=foo&schema=bar&table=baz
             "#,
@@ -169,16 +170,11 @@ impl HttpHandler for TableHandler {
 impl TableHandler {
     async fn extract_table_ids(&self, params: &HashMap<String, String>) -> Result<Vec<TableId>> {
         if let Some(ids) = params.get("region_ids") {
-            let table_ids = ids
-                .split(',')
-                .map(|x| {
-                    x.parse::<u64>()
-                        .map(|y| RegionId::from_u64(y).table_id())
-                        .context(ParseNumSnafu {
-                            err_msg: format!("invalid region id: {x}"),
-                        })
-                })
-                .collect::<Result<Vec<_>>>()?;
+            let mut table_ids = Vec::new();
+            for id in util::split_region_ids(ids) {
+                let region_id = util::parse_region_id(&self.table_metadata_manager, id).await?;
+                table_ids.push(region_id.table_id());
+            }
 
             return Ok(table_ids);
         }
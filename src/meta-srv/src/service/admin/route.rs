@@ -18,7 +18,7 @@ use common_catalog::format_full_table_name;
 use common_meta::key::table_name::TableNameKey;
 use common_meta::key::TableMetadataManagerRef;
 use snafu::{OptionExt, ResultExt};
-use store_api::storage::{RegionId, TableId};
+use store_api::storage::TableId;
 use tonic::codegen::http;
 
 use super::{util, HttpHandler};
@@ -43,6 +43,8 @@ impl HttpHandler for RouteHandler {
             return util::to_text_response(
                 r#"
             - GET /table?region_id=123
+            - GET /table?region_id=123(0, 123)
+            - GET /table?region_id=foo.bar.baz#1
             - GET /table?table_id=456
             - GET /table?catalog=foo&schema=bar&table=baz
             "#,
@@ -69,13 +71,8 @@ impl HttpHandler for RouteHandler {
 impl RouteHandler {
     async fn extract_table_id(&self, params: &HashMap<String, String>) -> Result<TableId> {
         if let Some(id) = params.get("region_id") {
-            let table_id = id
-                .parse::<u64>()
-                .map(|x| RegionId::from_u64(x).table_id())
-                .context(ParseNumSnafu {
-                    err_msg: format!("invalid region id: {id}"),
-                })?;
-            return Ok(table_id);
+            let region_id = util::parse_region_id(&self.table_metadata_manager, id).await?;
+            return Ok(region_id.table_id());
         }
 
         if let Some(id) = params.get("table_id") {
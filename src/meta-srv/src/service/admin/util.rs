@@ -14,10 +14,18 @@
 
 use std::collections::HashMap;
 
+use common_catalog::consts::{DEFAULT_CATALOG_NAME, DEFAULT_SCHEMA_NAME};
+use common_catalog::format_full_table_name;
+use common_meta::key::table_name::TableNameKey;
+use common_meta::key::TableMetadataManagerRef;
 use snafu::{OptionExt, ResultExt};
+use store_api::storage::{RegionId, RegionNumber};
 use tonic::codegen::http;
 
-use crate::error::{self, MissingRequiredParameterSnafu, ParseNumSnafu, Result};
+use crate::error::{
+    self, InvalidArgumentsSnafu, MissingRequiredParameterSnafu, ParseNumSnafu, Result,
+    TableMetadataManagerSnafu, TableNotFoundSnafu,
+};
 
 pub fn extract_cluster_id(params: &HashMap<String, String>) -> Result<u64> {
     params
@@ -31,6 +39,75 @@ pub fn extract_cluster_id(params: &HashMap<String, String>) -> Result<u64> {
         })
 }
 
+/// Parses a region id from the `input`.
+///
+/// Besides the forms [RegionId] can be parsed from, e.g. `4294967297` or `4294967297(1, 1)`,
+/// it also accepts `<table_name>#<region_number>`, where the table name can be `table`,
+/// `schema.table` or `catalog.schema.table`.
+pub async fn parse_region_id(
+    table_metadata_manager: &TableMetadataManagerRef,
+    input: &str,
+) -> Result<RegionId> {
+    let Some((table_name, region_number)) = input.trim().rsplit_once('#') else {
+        return input
+            .parse::<RegionId>()
+            .ok()
+            .context(InvalidArgumentsSnafu {
+                err_msg: format!("invalid region id: {input}"),
+            });
+    };
+
+    let region_number = region_number
+        .parse::<RegionNumber>()
+        .context(ParseNumSnafu {
+            err_msg: format!("invalid region number in region id: {input}"),
+        })?;
+    let (catalog, schema, table) = match table_name.split('.').collect::<Vec<_>>()[..] {
+        [table] => (DEFAULT_CATALOG_NAME, DEFAULT_SCHEMA_NAME, table),
+        [schema, table] => (DEFAULT_CATALOG_NAME, schema, table),
+        [catalog, schema, table] => (catalog, schema, table),
+        _ => {
+            return InvalidArgumentsSnafu {
+                err_msg: format!("invalid table name in region id: {input}"),
+            }
+            .fail()
+        }
+    };
+
+    let table_id = table_metadata_manager
+        .table_name_manager()
+        .get(TableNameKey::new(catalog, schema, table))
+        .await
+        .context(TableMetadataManagerSnafu)?
+        .map(|x| x.table_id())
+        .context(TableNotFoundSnafu {
+            name: format_full_table_name(catalog, schema, table),
+        })?;
+
+    Ok(RegionId::new(table_id, region_number))
+}
+
+/// Splits comma separated region ids, ignoring commas inside the parentheses of
+/// region ids like `4294967297(1, 1)`.
+pub fn split_region_ids(ids: &str) -> Vec<&str> {
+    let mut result = Vec::new();
+    let mut depth = 0;
+    let mut start = 0;
+    for (i, c) in ids.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            ',' if depth == 0 => {
+                result.push(ids[start..i].trim());
+                start = i + 1;
+            }
+            _ => (),
+        }
+    }
+    result.push(ids[start..].trim());
+    result
+}
+
 pub fn get_value<'a>(params: &'a HashMap<String, String>, key: &str) -> Result<&'a String> {
     params
         .get(key)
@@ -44,3 +121,67 @@ pub fn to_text_response(text: &str) -> Result<http::Response<String>> {
         .body(text.to_string())
         .context(error::InvalidHttpBodySnafu)
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use common_meta::key::table_route::TableRouteValue;
+    use common_meta::key::test_utils::new_test_table_info;
+    use common_meta::key::TableMetadataManager;
+    use common_meta::kv_backend::memory::MemoryKvBackend;
+
+    use super::*;
+
+    #[test]
+    fn test_split_region_ids() {
+        assert_eq!(vec!["1"], split_region_ids("1"));
+        assert_eq!(vec!["1", "2", "3"], split_region_ids("1, 2,3"));
+        assert_eq!(
+            vec!["4294967297(1, 1)", "t#2"],
+            split_region_ids("4294967297(1, 1),t#2")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_parse_region_id() {
+        let table_metadata_manager = Arc::new(TableMetadataManager::new(Arc::new(
+            MemoryKvBackend::default(),
+        )));
+        let table_info = new_test_table_info(1024, vec![2]).into();
+        table_metadata_manager
+            .create_table_metadata(
+                table_info,
+                TableRouteValue::physical(vec![]),
+                HashMap::new(),
+            )
+            .await
+            .unwrap();
+
+        let expected = RegionId::new(1024, 2);
+        for input in [
+            "4398046511106",
+            "4398046511106(1024, 2)",
+            "mytable#2",
+            "public.mytable#2",
+            "greptime.public.mytable#2",
+        ] {
+            assert_eq!(
+                expected,
+                parse_region_id(&table_metadata_manager, input)
+                    .await
+                    .unwrap(),
+                "input: {input}"
+            );
+        }
+
+        for input in ["abc", "mytable#a", "foo#1", "a.b.c.d#1"] {
+            assert!(
+                parse_region_id(&table_metadata_manager, input)
+                    .await
+                    .is_err(),
+                "input: {input}"
+            );
+        }
+    }
+}
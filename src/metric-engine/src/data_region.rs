@@ -110,6 +110,7 @@ impl DataRegion {
                 Ok(AddColumn {
                     column_metadata: c,
                     location: None,
+                    backfill: false,
                 })
             })
             .collect::<Result<_>>()?;
@@ -31,13 +31,16 @@ use common_recordbatch::SendableRecordBatchStream;
 use mito2::engine::MitoEngine;
 use store_api::metadata::RegionMetadataRef;
 use store_api::metric_engine_consts::METRIC_ENGINE_NAME;
-use store_api::region_engine::{RegionEngine, RegionRole, SetReadonlyResponse};
+use store_api::region_engine::{
+    RegionCaptureRequest, RegionEngine, RegionRole, SetReadonlyResponse,
+};
 use store_api::region_request::{AffectedRows, RegionRequest};
 use store_api::storage::{RegionId, ScanRequest};
 use tokio::sync::RwLock;
 
 use self::state::MetricEngineState;
 use crate::data_region::DataRegion;
+use crate::error::UnsupportedRegionRequestSnafu;
 use crate::metadata_region::MetadataRegion;
 
 /// Fixed random state for generating tsid
@@ -133,6 +136,10 @@ impl RegionEngine for MetricEngine {
             RegionRequest::Truncate(_) => todo!(),
             /// It always Ok(0), all data is latest.
             RegionRequest::Catchup(_) => Ok(0),
+            request @ RegionRequest::DeleteRange(_) => UnsupportedRegionRequestSnafu {
+                request: request.type_name(),
+            }
+            .fail(),
         };
 
         result.map_err(BoxedError::new)
@@ -189,6 +196,17 @@ impl RegionEngine for MetricEngine {
     fn role(&self, region_id: RegionId) -> Option<RegionRole> {
         todo!()
     }
+
+    async fn capture_region(
+        &self,
+        region_id: RegionId,
+        request: RegionCaptureRequest,
+    ) -> Result<Vec<String>, BoxedError> {
+        self.inner
+            .capture_region(region_id, request)
+            .await
+            .map_err(BoxedError::new)
+    }
 }
 
 impl MetricEngine {
@@ -217,8 +235,11 @@ struct MetricEngineInner {
 mod test {
     use std::collections::HashMap;
 
+    use common_time::Timestamp;
     use store_api::metric_engine_consts::PHYSICAL_TABLE_METADATA_KEY;
-    use store_api::region_request::{RegionCloseRequest, RegionOpenRequest};
+    use store_api::region_request::{
+        RegionCloseRequest, RegionDeleteRangeRequest, RegionOpenRequest,
+    };
 
     use super::*;
     use crate::test_util::TestEnv;
@@ -279,4 +300,24 @@ mod test {
             .await
             .unwrap();
     }
+
+    #[tokio::test]
+    async fn test_delete_range_unsupported() {
+        let env = TestEnv::new().await;
+        env.init_metric_region().await;
+        let engine = env.metric();
+
+        let err = engine
+            .handle_request(
+                env.default_logical_region_id(),
+                RegionRequest::DeleteRange(RegionDeleteRangeRequest {
+                    start: Timestamp::new_millisecond(0),
+                    end: Timestamp::new_millisecond(1000),
+                    tag_filters: vec![],
+                }),
+            )
+            .await
+            .unwrap_err();
+        assert_eq!(StatusCode::Unsupported, err.status_code());
+    }
 }
@@ -146,6 +146,7 @@ mod test {
                         ),
                     },
                     location: None,
+                    backfill: false,
                 }],
             },
         };
@@ -12,6 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::path::Path;
 use std::sync::Arc;
 
 use api::v1::SemanticType;
@@ -22,7 +23,7 @@ use datafusion::logical_expr;
 use snafu::{OptionExt, ResultExt};
 use store_api::metadata::{RegionMetadata, RegionMetadataBuilder, RegionMetadataRef};
 use store_api::metric_engine_consts::DATA_SCHEMA_TABLE_ID_COLUMN_NAME;
-use store_api::region_engine::RegionEngine;
+use store_api::region_engine::{RegionCaptureRequest, RegionEngine};
 use store_api::storage::consts::ReservedColumnId;
 use store_api::storage::{RegionId, ScanRequest};
 
@@ -32,6 +33,11 @@ use crate::error::{
 };
 use crate::utils;
 
+/// Directory to capture the data region to.
+const CAPTURE_DATA_DIR: &str = "data";
+/// Directory to capture the metadata region to.
+const CAPTURE_METADATA_DIR: &str = "metadata";
+
 impl MetricEngineInner {
     #[tracing::instrument(skip_all)]
     pub async fn read_region(
@@ -104,6 +110,58 @@ impl MetricEngineInner {
         }
     }
 
+    /// Captures files of the data region and the metadata region behind the region.
+    ///
+    /// Files of the data region are copied to the `data` directory under the target
+    /// directory and files of the metadata region to the `metadata` directory.
+    pub async fn capture_region(
+        &self,
+        region_id: RegionId,
+        request: RegionCaptureRequest,
+    ) -> Result<Vec<String>> {
+        let is_physical_region = self
+            .state
+            .read()
+            .await
+            .physical_regions()
+            .contains_key(&region_id);
+        let physical_region_id = if is_physical_region {
+            region_id
+        } else {
+            self.get_physical_region_id(region_id).await?
+        };
+
+        let target_dir = Path::new(&request.target_dir);
+        let regions = [
+            (
+                CAPTURE_DATA_DIR,
+                utils::to_data_region_id(physical_region_id),
+                request.filters.clone(),
+            ),
+            // Rows in the metadata region are not related to the time range of the read.
+            (
+                CAPTURE_METADATA_DIR,
+                utils::to_metadata_region_id(physical_region_id),
+                vec![],
+            ),
+        ];
+        let mut copied = Vec::new();
+        for (dir, region_id, filters) in regions {
+            let request = RegionCaptureRequest {
+                filters,
+                target_dir: target_dir.join(dir).to_string_lossy().to_string(),
+            };
+            let files = self
+                .mito
+                .capture_region(region_id, request)
+                .await
+                .context(MitoReadOperationSnafu)?;
+            copied.extend(files.into_iter().map(|file| format!("{dir}/{file}")));
+        }
+
+        Ok(copied)
+    }
+
     async fn get_physical_region_id(&self, logical_region_id: RegionId) -> Result<RegionId> {
         let state = &self.state.read().await;
         state
@@ -138,6 +196,10 @@ impl MetricEngineInner {
             .filters
             .push(self.table_id_filter(logical_region_id));
 
+        // The primary key of the physical region is different from the logical region,
+        // so the prefix of the logical primary key doesn't apply to it.
+        request.pk_prefix = None;
+
         Ok(request)
     }
 
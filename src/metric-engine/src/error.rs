@@ -141,6 +141,9 @@ pub enum Error {
     #[snafu(display("Alter request to physical region is forbidden"))]
     ForbiddenPhysicalAlter { location: Location },
 
+    #[snafu(display("Unsupported region request: {}", request))]
+    UnsupportedRegionRequest { request: String, location: Location },
+
     #[snafu(display("Invalid region metadata"))]
     InvalidMetadata {
         source: store_api::metadata::MetadataError,
@@ -160,7 +163,9 @@ impl ErrorExt for Error {
             | ConflictRegionOption { .. }
             | ColumnTypeMismatch { .. } => StatusCode::InvalidArguments,
 
-            ForbiddenPhysicalAlter { .. } => StatusCode::Unsupported,
+            ForbiddenPhysicalAlter { .. } | UnsupportedRegionRequest { .. } => {
+                StatusCode::Unsupported
+            }
 
             MissingInternalColumn { .. }
             | DeserializeSemanticType { .. }
@@ -323,6 +323,7 @@ impl MetadataRegion {
             filters: vec![],
             output_ordering: None,
             limit: None,
+            pk_prefix: None,
         };
         let record_batch_stream = self
             .mito
@@ -366,6 +367,7 @@ impl MetadataRegion {
             filters: vec![filter_expr.into()],
             output_ordering: None,
             limit: None,
+            pk_prefix: None,
         }
     }
 
@@ -493,6 +495,7 @@ mod test {
             filters: vec![expected_filter_expr.into()],
             output_ordering: None,
             limit: None,
+            pk_prefix: None,
         };
         let actual_scan_request = MetadataRegion::build_read_request(key);
         assert_eq!(actual_scan_request, expected_scan_request);
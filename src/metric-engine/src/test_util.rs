@@ -173,6 +173,7 @@ pub fn alter_logical_region_add_tag_columns(
                 ),
             },
             location: None,
+            backfill: false,
         });
     }
     RegionAlterRequest {
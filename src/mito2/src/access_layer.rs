@@ -18,18 +18,24 @@ use object_store::{util, ObjectStore};
 use snafu::ResultExt;
 use store_api::metadata::RegionMetadataRef;
 
-use crate::error::{DeleteSstSnafu, Result};
+use crate::error::{DeleteSstSnafu, OpenDalSnafu, Result};
 use crate::read::Source;
+use crate::sst::dictionary::SharedDictionaryRef;
 use crate::sst::file::{FileHandle, FileId};
 use crate::sst::parquet::reader::ParquetReaderBuilder;
 use crate::sst::parquet::writer::ParquetWriter;
 
 pub type AccessLayerRef = Arc<AccessLayer>;
 
+/// Directory under the region directory to stage external files.
+const STAGING_DIR: &str = "staging/";
+
 /// A layer to access SST files under the same directory.
 pub struct AccessLayer {
     region_dir: String,
     object_store: ObjectStore,
+    /// Dictionary shared by SSTs of the region.
+    shared_dictionary: SharedDictionaryRef,
 }
 
 impl std::fmt::Debug for AccessLayer {
@@ -46,9 +52,16 @@ impl AccessLayer {
         AccessLayer {
             region_dir: region_dir.into(),
             object_store,
+            shared_dictionary: Default::default(),
         }
     }
 
+    /// Sets the dictionary shared by SSTs of the region.
+    pub(crate) fn with_shared_dictionary(mut self, dictionary: SharedDictionaryRef) -> AccessLayer {
+        self.shared_dictionary = dictionary;
+        self
+    }
+
     /// Returns the directory of the region.
     pub fn region_dir(&self) -> &str {
         &self.region_dir
@@ -59,6 +72,11 @@ impl AccessLayer {
         &self.object_store
     }
 
+    /// Returns the dictionary shared by SSTs of the region.
+    pub(crate) fn shared_dictionary(&self) -> &SharedDictionaryRef {
+        &self.shared_dictionary
+    }
+
     /// Deletes a SST file with given file id.
     pub(crate) async fn delete_sst(&self, file_id: FileId) -> Result<()> {
         let path = self.sst_file_path(&file_id.as_parquet());
@@ -68,9 +86,19 @@ impl AccessLayer {
             .context(DeleteSstSnafu { file_id })
     }
 
+    /// Returns whether the SST file with given file id exists.
+    pub(crate) async fn is_sst_exist(&self, file_id: FileId) -> Result<bool> {
+        let path = self.sst_file_path(&file_id.as_parquet());
+        self.object_store
+            .is_exist(&path)
+            .await
+            .context(OpenDalSnafu)
+    }
+
     /// Returns a reader builder for specific `file`.
     pub(crate) fn read_sst(&self, file: FileHandle) -> ParquetReaderBuilder {
         ParquetReaderBuilder::new(self.region_dir.clone(), file, self.object_store.clone())
+            .shared_dictionary(Some(self.shared_dictionary.clone()))
     }
 
     /// Returns a new parquet writer to write the SST for specific `file_id`.
@@ -83,6 +111,44 @@ impl AccessLayer {
     ) -> ParquetWriter {
         let path = self.sst_file_path(&file_id.as_parquet());
         ParquetWriter::new(path, metadata, source, self.object_store.clone())
+            .shared_dictionary(Some(self.shared_dictionary.clone()))
+    }
+
+    /// Returns the directory to stage external SST files before ingesting them.
+    pub fn staging_dir(&self) -> String {
+        util::join_dir(&self.region_dir, STAGING_DIR)
+    }
+
+    /// Returns the path of the staged file `file_name`.
+    pub(crate) fn staged_file_path(&self, file_name: &str) -> String {
+        util::join_path(&self.staging_dir(), file_name)
+    }
+
+    /// Copies the staged file `file_name` to the SST file of `file_id`.
+    ///
+    /// Returns the size of the installed SST file.
+    pub(crate) async fn install_staged_sst(&self, file_name: &str, file_id: FileId) -> Result<u64> {
+        let staged_path = self.staged_file_path(file_name);
+        let sst_path = self.sst_file_path(&file_id.as_parquet());
+        self.object_store
+            .copy(&staged_path, &sst_path)
+            .await
+            .context(OpenDalSnafu)?;
+        let meta = self
+            .object_store
+            .stat(&sst_path)
+            .await
+            .context(OpenDalSnafu)?;
+
+        Ok(meta.content_length())
+    }
+
+    /// Deletes the staged file `file_name`.
+    pub(crate) async fn delete_staged_file(&self, file_name: &str) -> Result<()> {
+        self.object_store
+            .delete(&self.staged_file_path(file_name))
+            .await
+            .context(OpenDalSnafu)
     }
 
     /// Returns the `file_path` for the `file_name` in the object store.
@@ -12,13 +12,14 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+mod backfill;
 mod output;
 mod picker;
 #[cfg(test)]
 mod test_util;
 mod twcs;
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use std::time::Instant;
 
@@ -30,6 +31,7 @@ use store_api::storage::RegionId;
 use tokio::sync::mpsc::{self, Sender};
 
 use crate::access_layer::AccessLayerRef;
+use crate::compaction::backfill::BackfillPicker;
 use crate::compaction::twcs::TwcsPicker;
 use crate::config::MitoConfig;
 use crate::error::{
@@ -40,6 +42,7 @@ use crate::region::options::CompactionOptions;
 use crate::region::version::{VersionControlRef, VersionRef};
 use crate::request::{OptionOutputTx, OutputTx, WorkerRequest};
 use crate::schedule::scheduler::SchedulerRef;
+use crate::sst::file::FileId;
 use crate::sst::file_purger::FilePurgerRef;
 
 /// Region compaction request.
@@ -55,6 +58,11 @@ pub struct CompactionRequest {
     pub(crate) start_time: Instant,
     /// Buffering threshold while writing SST files.
     pub(crate) sst_write_buffer_size: ReadableSize,
+    /// Files to rewrite to backfill new columns.
+    ///
+    /// The request rewrites these files instead of compacting the region if
+    /// it isn't empty.
+    pub(crate) files_to_backfill: HashSet<FileId>,
 }
 
 impl CompactionRequest {
@@ -128,6 +136,40 @@ impl CompactionScheduler {
         self.schedule_compaction_request(request)
     }
 
+    /// Schedules a task to rewrite `files` to backfill new columns.
+    ///
+    /// If the region is compacting, files are rewritten after the current task.
+    pub(crate) fn schedule_backfill(
+        &mut self,
+        region_id: RegionId,
+        files: impl IntoIterator<Item = FileId>,
+        version_control: &VersionControlRef,
+        access_layer: &AccessLayerRef,
+        file_purger: &FilePurgerRef,
+        engine_config: Arc<MitoConfig>,
+    ) -> Result<()> {
+        if let Some(status) = self.region_status.get_mut(&region_id) {
+            // Region is compacting. Add files to the pending backfill list.
+            status.pending_backfill.extend(files);
+            return Ok(());
+        }
+
+        let mut status = CompactionStatus::new(
+            region_id,
+            version_control.clone(),
+            access_layer.clone(),
+            file_purger.clone(),
+        );
+        status.pending_backfill.extend(files);
+        let request = status.new_compaction_request(
+            self.request_sender.clone(),
+            OptionOutputTx::none(),
+            engine_config,
+        );
+        self.region_status.insert(region_id, status);
+        self.schedule_compaction_request(request)
+    }
+
     /// Notifies the scheduler that the compaction job is finished successfully.
     pub(crate) fn on_compaction_finished(
         &mut self,
@@ -186,7 +228,11 @@ impl CompactionScheduler {
     ///
     /// If the region has nothing to compact, it removes the region from the status map.
     fn schedule_compaction_request(&mut self, request: CompactionRequest) -> Result<()> {
-        let picker = compaction_options_to_picker(&request.current_version.options.compaction);
+        let picker = if request.files_to_backfill.is_empty() {
+            compaction_options_to_picker(&request.current_version.options.compaction)
+        } else {
+            Arc::new(BackfillPicker) as Arc<_>
+        };
         let region_id = request.region_id();
         debug!(
             "Pick compaction strategy {:?} for region: {}",
@@ -271,6 +317,8 @@ struct CompactionStatus {
     ///
     /// For simplicity, we merge all pending compaction requests into one.
     pending_compaction: Option<PendingCompaction>,
+    /// Files pending to rewrite to backfill new columns.
+    pending_backfill: HashSet<FileId>,
 }
 
 impl CompactionStatus {
@@ -287,6 +335,7 @@ impl CompactionStatus {
             access_layer,
             file_purger,
             pending_compaction: None,
+            pending_backfill: HashSet::new(),
         }
     }
 
@@ -308,7 +357,7 @@ impl CompactionStatus {
 
     /// Creates a new compaction request for compaction picker.
     ///
-    /// It consumes all pending compaction waiters.
+    /// It consumes all pending compaction waiters and files to backfill.
     fn new_compaction_request(
         &mut self,
         request_sender: Sender<WorkerRequest>,
@@ -325,6 +374,7 @@ impl CompactionStatus {
             file_purger: self.file_purger.clone(),
             start_time,
             sst_write_buffer_size: engine_config.sst_write_buffer_size,
+            files_to_backfill: std::mem::take(&mut self.pending_backfill),
         };
 
         if let Some(pending) = self.pending_compaction.take() {
@@ -513,4 +563,82 @@ mod tests {
             .pending_compaction
             .is_some());
     }
+
+    #[tokio::test]
+    async fn test_schedule_backfill() {
+        let job_scheduler = Arc::new(VecScheduler::default());
+        let env = SchedulerEnv::new().scheduler(job_scheduler.clone());
+        let (tx, _rx) = mpsc::channel(4);
+        let mut scheduler = env.mock_compaction_scheduler(tx);
+        let mut builder = VersionControlBuilder::new();
+        let purger = builder.file_purger();
+        let region_id = builder.region_id();
+
+        // No file to backfill.
+        let version_control = Arc::new(builder.build());
+        scheduler
+            .schedule_backfill(
+                region_id,
+                [FileId::random()],
+                &version_control,
+                &env.access_layer,
+                &purger,
+                Arc::new(MitoConfig::default()),
+            )
+            .unwrap();
+        assert!(scheduler.region_status.is_empty());
+        assert_eq!(0, job_scheduler.num_jobs());
+
+        // Only one file, the backfill picker still rewrites it.
+        let version_control = Arc::new(builder.push_l0_file(0, 1000).build());
+        let file_ids: Vec<_> = version_control.current().version.ssts.levels()[0]
+            .files
+            .keys()
+            .copied()
+            .collect();
+        scheduler
+            .schedule_backfill(
+                region_id,
+                file_ids.clone(),
+                &version_control,
+                &env.access_layer,
+                &purger,
+                Arc::new(MitoConfig::default()),
+            )
+            .unwrap();
+        assert_eq!(1, scheduler.region_status.len());
+        assert_eq!(1, job_scheduler.num_jobs());
+
+        // The region is compacting.
+        scheduler
+            .schedule_backfill(
+                region_id,
+                file_ids,
+                &version_control,
+                &env.access_layer,
+                &purger,
+                Arc::new(MitoConfig::default()),
+            )
+            .unwrap();
+        assert_eq!(1, job_scheduler.num_jobs());
+        assert_eq!(
+            1,
+            scheduler
+                .region_status
+                .get(&region_id)
+                .unwrap()
+                .pending_backfill
+                .len()
+        );
+
+        // Schedules the pending backfill.
+        scheduler.on_compaction_finished(region_id, Arc::new(MitoConfig::default()));
+        assert_eq!(2, job_scheduler.num_jobs());
+        assert!(scheduler
+            .region_status
+            .get(&region_id)
+            .unwrap()
+            .pending_backfill
+            .is_empty());
+    }
 }
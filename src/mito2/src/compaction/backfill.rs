@@ -0,0 +1,85 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use common_telemetry::info;
+
+use crate::compaction::output::CompactionOutput;
+use crate::compaction::picker::{CompactionTask, Picker};
+use crate::compaction::twcs::TwcsCompactionTask;
+use crate::compaction::CompactionRequest;
+use crate::sst::file::FileId;
+
+/// `BackfillPicker` rewrites SST files written before columns to backfill are added.
+///
+/// Each file is rewritten with the latest region metadata to a new file in the same
+/// level so default values of new columns are stored in the new file.
+#[derive(Debug)]
+pub(crate) struct BackfillPicker;
+
+impl Picker for BackfillPicker {
+    fn pick(&self, req: CompactionRequest) -> Option<Box<dyn CompactionTask>> {
+        let CompactionRequest {
+            current_version,
+            access_layer,
+            request_sender,
+            waiters,
+            file_purger,
+            start_time,
+            sst_write_buffer_size,
+            files_to_backfill,
+        } = req;
+
+        let region_id = current_version.metadata.region_id;
+        // Files that were removed by other compactions don't need to rewrite as the
+        // compaction outputs are written by the latest metadata.
+        let outputs: Vec<_> = current_version
+            .ssts
+            .levels()
+            .iter()
+            .flat_map(|level| level.files().map(move |file| (level.level, file)))
+            .filter(|(_, file)| files_to_backfill.contains(&file.file_id()) && !file.compacting())
+            .map(|(level, file)| CompactionOutput {
+                output_file_id: FileId::random(),
+                output_level: level,
+                inputs: vec![file.clone()],
+            })
+            .collect();
+        if outputs.is_empty() {
+            for waiter in waiters {
+                waiter.send(Ok(0));
+            }
+            return None;
+        }
+
+        info!("Backfill {} files in region {}", outputs.len(), region_id);
+
+        let task = TwcsCompactionTask {
+            region_id,
+            schema: current_version.metadata.clone(),
+            sst_layer: access_layer,
+            outputs,
+            expired_ssts: Vec::new(),
+            sst_write_buffer_size,
+            compaction_time_window: None,
+            range_tombstones: current_version.range_tombstones.clone(),
+            resolved_tombstones: Vec::new(),
+            append_mode: current_version.options.append_mode,
+            request_sender,
+            waiters,
+            file_purger,
+            start_time,
+        };
+        Some(Box::new(task))
+    }
+}
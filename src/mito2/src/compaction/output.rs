@@ -19,6 +19,7 @@ use store_api::storage::RegionId;
 use crate::access_layer::AccessLayerRef;
 use crate::error;
 use crate::read::projection::ProjectionMapper;
+use crate::read::range_delete::RangeTombstone;
 use crate::read::seq_scan::SeqScan;
 use crate::read::{BoxedBatchReader, Source};
 use crate::sst::file::{FileHandle, FileId, FileMeta, Level};
@@ -40,8 +41,17 @@ impl CompactionOutput {
         schema: RegionMetadataRef,
         sst_layer: AccessLayerRef,
         sst_write_buffer_size: ReadableSize,
+        range_tombstones: &[RangeTombstone],
+        append_mode: bool,
     ) -> error::Result<Option<FileMeta>> {
-        let reader = build_sst_reader(schema.clone(), sst_layer.clone(), &self.inputs).await?;
+        let reader = build_sst_reader(
+            schema.clone(),
+            sst_layer.clone(),
+            &self.inputs,
+            range_tombstones,
+            append_mode,
+        )
+        .await?;
 
         let opts = WriteOptions {
             write_buffer_size: sst_write_buffer_size,
@@ -63,6 +73,7 @@ impl CompactionOutput {
                     time_range,
                     level: self.output_level,
                     file_size,
+                    sequence: None,
                 }
             },
         );
@@ -76,11 +87,17 @@ async fn build_sst_reader(
     schema: RegionMetadataRef,
     sst_layer: AccessLayerRef,
     inputs: &[FileHandle],
+    range_tombstones: &[RangeTombstone],
+    append_mode: bool,
 ) -> error::Result<BoxedBatchReader> {
     SeqScan::new(sst_layer, ProjectionMapper::all(&schema)?)
         .with_files(inputs.to_vec())
+        // Removes rows deleted by range tombstones.
+        .with_range_tombstones(range_tombstones)
         // We ignore file not found error during compaction.
         .with_ignore_file_not_found(true)
+        // Keeps duplicate rows of append-only regions.
+        .with_dedup(!append_mode)
         .build_reader()
         .await
 }
@@ -12,7 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, HashSet};
 use std::fmt::{Debug, Formatter};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
@@ -34,6 +34,7 @@ use crate::compaction::CompactionRequest;
 use crate::error;
 use crate::error::CompactRegionSnafu;
 use crate::metrics::{COMPACTION_FAILURE_COUNT, COMPACTION_STAGE_ELAPSED};
+use crate::read::range_delete::RangeTombstone;
 use crate::request::{
     BackgroundNotify, CompactionFailed, CompactionFinished, OutputTx, WorkerRequest,
 };
@@ -76,14 +77,25 @@ impl TwcsPicker {
     /// Builds compaction output from files.
     /// For active writing window, we allow for at most `max_active_window_files` files to alleviate
     /// fragmentation. For other windows, we allow at most 1 file at each window.
+    /// Windows with files that overlap `tombstones` are always compacted to remove deleted rows.
     fn build_output(
         &self,
         time_windows: &BTreeMap<i64, Vec<FileHandle>>,
         active_window: Option<i64>,
+        tombstones: &[RangeTombstone],
     ) -> Vec<CompactionOutput> {
         let mut output = vec![];
         for (window, files) in time_windows {
-            if let Some(active_window) = active_window
+            if files
+                .iter()
+                .any(|file| tombstones.iter().any(|tombstone| tombstone.overlaps(file)))
+            {
+                output.push(CompactionOutput {
+                    output_file_id: FileId::random(),
+                    output_level: 1,
+                    inputs: files.clone(),
+                });
+            } else if let Some(active_window) = active_window
                 && *window == active_window
             {
                 if files.len() > self.max_active_window_files {
@@ -126,6 +138,7 @@ impl Picker for TwcsPicker {
             file_purger,
             start_time,
             sst_write_buffer_size,
+            files_to_backfill: _,
         } = req;
 
         let region_metadata = current_version.metadata.clone();
@@ -158,7 +171,15 @@ impl Picker for TwcsPicker {
         let active_window = find_latest_window_in_seconds(levels[0].files(), time_window_size);
         // Assign files to windows
         let windows = assign_to_windows(levels.iter().flat_map(LevelMeta::files), time_window_size);
-        let outputs = self.build_output(&windows, active_window);
+        // Rows deleted by these tombstones are all in SSTs, so we can remove the
+        // tombstones once files they overlap are compacted.
+        let flushed_tombstones: Vec<_> = current_version
+            .range_tombstones
+            .iter()
+            .filter(|tombstone| tombstone.sequence <= current_version.flushed_sequence)
+            .cloned()
+            .collect();
+        let outputs = self.build_output(&windows, active_window, &flushed_tombstones);
 
         if outputs.is_empty() && expired_ssts.is_empty() {
             // Nothing to compact, we are done. Notifies all waiters as we consume the compaction request.
@@ -167,6 +188,8 @@ impl Picker for TwcsPicker {
             }
             return None;
         }
+        let resolved_tombstones =
+            resolve_tombstones(flushed_tombstones, levels, &outputs, &expired_ssts);
         let task = TwcsCompactionTask {
             region_id,
             schema: region_metadata,
@@ -175,6 +198,9 @@ impl Picker for TwcsPicker {
             expired_ssts,
             sst_write_buffer_size,
             compaction_time_window: Some(time_window_size),
+            range_tombstones: current_version.range_tombstones.clone(),
+            resolved_tombstones,
+            append_mode: current_version.options.append_mode,
             request_sender,
             waiters,
             file_purger,
@@ -184,6 +210,31 @@ impl Picker for TwcsPicker {
     }
 }
 
+/// Returns tombstones that don't overlap any file after compacting `outputs` and
+/// removing `expired_ssts`.
+fn resolve_tombstones(
+    tombstones: Vec<RangeTombstone>,
+    levels: &[LevelMeta],
+    outputs: &[CompactionOutput],
+    expired_ssts: &[FileHandle],
+) -> Vec<RangeTombstone> {
+    let removed: HashSet<_> = outputs
+        .iter()
+        .flat_map(|output| output.inputs.iter())
+        .chain(expired_ssts)
+        .map(FileHandle::file_id)
+        .collect();
+    tombstones
+        .into_iter()
+        .filter(|tombstone| {
+            levels
+                .iter()
+                .flat_map(LevelMeta::files)
+                .all(|file| removed.contains(&file.file_id()) || !tombstone.overlaps(file))
+        })
+        .collect()
+}
+
 /// Assigns files to windows with predefined window size (in seconds) by their max timestamps.
 fn assign_to_windows<'a>(
     files: impl Iterator<Item = &'a FileHandle>,
@@ -234,6 +285,13 @@ pub(crate) struct TwcsCompactionTask {
     pub expired_ssts: Vec<FileHandle>,
     pub sst_write_buffer_size: ReadableSize,
     pub compaction_time_window: Option<i64>,
+    /// Tombstones to remove deleted rows from the output.
+    pub range_tombstones: Vec<RangeTombstone>,
+    /// Tombstones to remove from the region after compaction.
+    pub resolved_tombstones: Vec<RangeTombstone>,
+    /// Whether the region is append-only. Rows of an append-only region
+    /// aren't deduplicated.
+    pub append_mode: bool,
     pub file_purger: FilePurgerRef,
     /// Request sender to notify the worker.
     pub(crate) request_sender: mpsc::Sender<WorkerRequest>,
@@ -279,6 +337,8 @@ impl TwcsCompactionTask {
             let schema = self.schema.clone();
             let sst_layer = self.sst_layer.clone();
             let sst_write_buffer_size = self.sst_write_buffer_size;
+            let range_tombstones = self.range_tombstones.clone();
+            let append_mode = self.append_mode;
             compacted_inputs.extend(output.inputs.iter().map(FileHandle::meta));
 
             info!(
@@ -296,7 +356,14 @@ impl TwcsCompactionTask {
             // TODO(hl): Maybe spawn to runtime to exploit in-job parallelism.
             futs.push(async move {
                 output
-                    .build(region_id, schema, sst_layer, sst_write_buffer_size)
+                    .build(
+                        region_id,
+                        schema,
+                        sst_layer,
+                        sst_write_buffer_size,
+                        &range_tombstones,
+                        append_mode,
+                    )
                     .await
             });
         }
@@ -375,6 +442,7 @@ impl CompactionTask for TwcsCompactionTask {
                     compaction_time_window: self
                         .compaction_time_window
                         .map(|seconds| Duration::from_secs(seconds as u64)),
+                    resolved_tombstones: std::mem::take(&mut self.resolved_tombstones),
                     start_time: self.start_time,
                 })
             }
@@ -575,7 +643,7 @@ mod tests {
             let windows = assign_to_windows(self.input_files.iter(), self.window_size);
             let active_window =
                 find_latest_window_in_seconds(self.input_files.iter(), self.window_size);
-            let output = TwcsPicker::new(4, 1, None).build_output(&windows, active_window);
+            let output = TwcsPicker::new(4, 1, None).build_output(&windows, active_window, &[]);
 
             let output = output
                 .iter()
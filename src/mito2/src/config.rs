@@ -26,6 +26,8 @@ const DEFAULT_MAX_BG_JOB: usize = 4;
 const MULTIPART_UPLOAD_MINIMUM_SIZE: ReadableSize = ReadableSize::mb(5);
 /// Default channel size for parallel scan task.
 const DEFAULT_SCAN_CHANNEL_SIZE: usize = 32;
+/// Default interval to retry purging data of a dropped region.
+const DEFAULT_PURGE_RETRY_INTERVAL: Duration = Duration::from_secs(5 * 60);
 
 /// Configuration for [MitoEngine](crate::engine::MitoEngine).
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
@@ -78,6 +80,30 @@ pub struct MitoConfig {
     pub scan_parallelism: usize,
     /// Capacity of the channel to send data from parallel scan tasks to the main task (default 32).
     pub parallel_scan_channel_size: usize,
+    /// Whether to disable pruning SSTs, memtables, row groups and pages when scanning
+    /// (default false). Only for verifying whether a wrong result is caused by pruning.
+    pub scan_disable_pruning: bool,
+    /// Whether to scan regions again without pruning in background and log mismatched
+    /// row counts (default false). It doubles the cost of each scan.
+    pub scan_verify_pruning: bool,
+    /// Max number of regions to open concurrently (default: number of cpu cores).
+    /// Sets to 0 to use the default value.
+    pub open_region_parallelism: usize,
+    /// Policy to handle corrupted WAL entries while replaying the WAL (default fail).
+    pub wal_corruption_policy: WalCorruptionPolicy,
+    /// Local directory of the journal of manifest commits, which repairs flushes and
+    /// compactions interrupted by a crash when opening regions. Empty to disable it.
+    pub commit_journal_dir: String,
+
+    // Drop configs:
+    /// Delay to purge data of a dropped region (default 5 min). The data is still kept
+    /// in the region dir within the delay.
+    #[serde(with = "humantime_serde")]
+    pub dropped_region_purge_delay: Duration,
+    /// Interval to retry purging data of a dropped region if there are SST files in use
+    /// or the object store fails (default 5 min).
+    #[serde(with = "humantime_serde")]
+    pub dropped_region_purge_retry_interval: Duration,
 }
 
 impl Default for MitoConfig {
@@ -98,6 +124,13 @@ impl Default for MitoConfig {
             sst_write_buffer_size: ReadableSize::mb(8),
             scan_parallelism: divide_num_cpus(4),
             parallel_scan_channel_size: DEFAULT_SCAN_CHANNEL_SIZE,
+            scan_disable_pruning: false,
+            scan_verify_pruning: false,
+            open_region_parallelism: divide_num_cpus(1),
+            wal_corruption_policy: WalCorruptionPolicy::default(),
+            commit_journal_dir: String::new(),
+            dropped_region_purge_delay: Duration::from_secs(5 * 60),
+            dropped_region_purge_retry_interval: DEFAULT_PURGE_RETRY_INTERVAL,
         }
     }
 }
@@ -149,9 +182,39 @@ impl MitoConfig {
                 self.parallel_scan_channel_size
             );
         }
+
+        // Use default value if `open_region_parallelism` is 0.
+        if self.open_region_parallelism == 0 {
+            self.open_region_parallelism = divide_num_cpus(1);
+        }
+
+        if self.dropped_region_purge_retry_interval.is_zero() {
+            self.dropped_region_purge_retry_interval = DEFAULT_PURGE_RETRY_INTERVAL;
+            warn!(
+                "Sanitize dropped region purge retry interval to {:?}",
+                self.dropped_region_purge_retry_interval
+            );
+        }
     }
 }
 
+/// Policy to handle corrupted WAL entries while replaying the WAL.
+///
+/// It applies to entries that fail to decode and entries the log store reports as corrupted.
+#[derive(Debug, Default, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum WalCorruptionPolicy {
+    /// Fails to open the region.
+    #[default]
+    Fail,
+    /// Skips corrupted entries and replays the rest.
+    Skip,
+    /// Stops replaying at the first corrupted entry.
+    ///
+    /// Rows in entries after the corrupted entry are lost.
+    TruncateAtCorruption,
+}
+
 /// Divide cpu num by a non-zero `divisor` and returns at least 1.
 fn divide_num_cpus(divisor: usize) -> usize {
     debug_assert!(divisor > 0);
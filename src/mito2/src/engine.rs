@@ -19,6 +19,8 @@ mod alter_test;
 #[cfg(test)]
 mod basic_test;
 #[cfg(test)]
+mod capture_test;
+#[cfg(test)]
 mod catchup_test;
 #[cfg(test)]
 mod close_test;
@@ -27,9 +29,15 @@ mod compaction_test;
 #[cfg(test)]
 mod create_test;
 #[cfg(test)]
+mod delete_range_test;
+#[cfg(test)]
 mod drop_test;
 #[cfg(test)]
 mod flush_test;
+#[cfg(test)]
+mod ingest_test;
+#[cfg(test)]
+mod kv_test;
 #[cfg(any(test, feature = "test"))]
 pub mod listener;
 #[cfg(test)]
@@ -41,33 +49,57 @@ mod projection_test;
 #[cfg(test)]
 mod prune_test;
 #[cfg(test)]
+mod scan_test;
+#[cfg(test)]
 mod set_readonly_test;
 #[cfg(test)]
 mod truncate_test;
+#[cfg(test)]
+mod verify_test;
 
 use std::sync::Arc;
 
 use async_trait::async_trait;
 use common_error::ext::BoxedError;
 use common_recordbatch::SendableRecordBatchStream;
+use common_telemetry::{info, warn};
+use futures::stream::{FuturesUnordered, StreamExt};
 use object_store::manager::ObjectStoreManagerRef;
-use snafu::{OptionExt, ResultExt};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use snafu::{ensure, OptionExt, ResultExt};
 use store_api::logstore::LogStore;
 use store_api::metadata::RegionMetadataRef;
-use store_api::region_engine::{RegionEngine, RegionRole, SetReadonlyResponse};
-use store_api::region_request::{AffectedRows, RegionRequest};
+use store_api::region_engine::{
+    RegionCaptureRequest, RegionEngine, RegionRole, SetReadonlyResponse,
+};
+use store_api::region_request::{
+    AffectedRows, RegionAlterRequest, RegionOpenRequest, RegionRequest,
+};
 use store_api::storage::{RegionId, ScanRequest};
 
 use crate::config::MitoConfig;
-use crate::error::{RecvSnafu, RegionNotFoundSnafu, Result};
-use crate::metrics::HANDLE_REQUEST_ELAPSED;
+use crate::error::{Error, RecvSnafu, RegionNotFoundSnafu, RegionReadonlySnafu, Result};
+use crate::ingest::load_staged_sst;
+use crate::manifest::action::{RegionKvEdit, RegionMetaAction, RegionMetaActionList};
+use crate::metrics::{HANDLE_REQUEST_ELAPSED, PRUNING_MISMATCH_TOTAL};
+use crate::purge_job::PurgeJob;
+use crate::read::prune_verify::check_pruning;
 use crate::read::scan_region::{ScanParallism, ScanRegion, Scanner};
-use crate::region::RegionUsage;
-use crate::request::WorkerRequest;
+use crate::region::kv::RegionKvKey;
+use crate::region::verify::{self, RegionVerifyReport};
+use crate::region::{capture, RegionUsage};
+use crate::request::{IngestFiles, ValidatedAlter, WorkerRequest};
+use crate::sst::file::{FileId, FileMeta};
+use crate::sst::file_purger::PurgeRequest;
+use crate::validate::validate_alteration;
 use crate::worker::WorkerGroup;
 
 pub const MITO_ENGINE_NAME: &str = "mito";
 
+/// Max times to validate a region again if it is modified during validation.
+const MAX_VALIDATION_RETRY: usize = 3;
+
 /// Region engine implementation for timeseries data.
 #[derive(Clone)]
 pub struct MitoEngine {
@@ -93,6 +125,11 @@ impl MitoEngine {
         self.inner.workers.is_region_exists(region_id)
     }
 
+    /// Returns jobs to purge data of dropped regions.
+    pub fn purge_jobs(&self) -> Vec<PurgeJob> {
+        self.inner.workers.purge_jobs()
+    }
+
     /// Returns the region disk/memory usage information.
     pub async fn get_region_usage(&self, region_id: RegionId) -> Result<RegionUsage> {
         let region = self
@@ -104,6 +141,124 @@ impl MitoEngine {
         Ok(region.region_usage().await)
     }
 
+    /// Returns previous metadata kept in the manifest of the region, ordered by
+    /// schema version. The current metadata is not included.
+    ///
+    /// This is useful for debugging schema drift between SSTs and the region.
+    pub async fn region_metadata_history(
+        &self,
+        region_id: RegionId,
+    ) -> Result<Vec<RegionMetadataRef>> {
+        let region = self
+            .inner
+            .workers
+            .get_region(region_id)
+            .context(RegionNotFoundSnafu { region_id })?;
+
+        let manifest = region.manifest_manager.manifest().await;
+        Ok(manifest.metadata_history.clone())
+    }
+
+    /// Scans the region and validates invariants of its memtables and SSTs.
+    ///
+    /// It reads all rows of the region so it might be slow for large regions.
+    pub async fn verify_region(&self, region_id: RegionId) -> Result<RegionVerifyReport> {
+        let region = self
+            .inner
+            .workers
+            .get_region(region_id)
+            .context(RegionNotFoundSnafu { region_id })?;
+
+        verify::verify_region(&region).await
+    }
+
+    /// Returns the value of the `key` in the engine-private state of the region.
+    pub async fn get_region_kv<T: DeserializeOwned>(
+        &self,
+        region_id: RegionId,
+        key: &RegionKvKey<T>,
+    ) -> Result<Option<T>> {
+        let region = self
+            .inner
+            .workers
+            .get_region(region_id)
+            .context(RegionNotFoundSnafu { region_id })?;
+
+        let manifest = region.manifest_manager.manifest().await;
+        key.get(&manifest)
+    }
+
+    /// Puts the `value` of the `key` to the engine-private state of the region.
+    ///
+    /// The value is durable once this method returns. Only writable regions can
+    /// update their states.
+    pub async fn put_region_kv<T: Serialize>(
+        &self,
+        region_id: RegionId,
+        key: &RegionKvKey<T>,
+        value: &T,
+    ) -> Result<()> {
+        let edit = key.put_edit(region_id, value)?;
+        self.inner.update_region_kv(region_id, edit).await
+    }
+
+    /// Deletes the `key` from the engine-private state of the region.
+    pub async fn delete_region_kv<T>(
+        &self,
+        region_id: RegionId,
+        key: &RegionKvKey<T>,
+    ) -> Result<()> {
+        self.inner
+            .update_region_kv(region_id, key.delete_edit())
+            .await
+    }
+
+    /// Returns the directory to stage external SST files for the region.
+    pub fn staging_dir(&self, region_id: RegionId) -> Result<String> {
+        let region = self
+            .inner
+            .workers
+            .get_region(region_id)
+            .context(RegionNotFoundSnafu { region_id })?;
+
+        Ok(region.access_layer.staging_dir())
+    }
+
+    /// Ingests SST files under the staging directory of the region.
+    ///
+    /// Files should be parquet files in the SST format. The engine validates these
+    /// files and adds them to the region in one manifest edit, so either all files
+    /// or none of them are visible. Data ingested doesn't go through the WAL and
+    /// memtables. Rows in ingested files overwrite rows written before the ingestion,
+    /// as the region assigns files new sequences instead of sequences stored in them.
+    /// Staged files are removed after they are ingested.
+    ///
+    /// Returns the number of rows ingested.
+    pub async fn ingest_staged_files(
+        &self,
+        region_id: RegionId,
+        file_names: &[String],
+    ) -> Result<AffectedRows> {
+        self.inner.ingest_staged_files(region_id, file_names).await
+    }
+
+    /// Opens regions in batch.
+    ///
+    /// Workers open regions concurrently in background, and the number of regions
+    /// opening at the same time is limited by
+    /// [open_region_parallelism](MitoConfig::open_region_parallelism). The `progress`
+    /// callback is invoked each time a region finishes opening, either successfully
+    /// or not.
+    ///
+    /// Returns the result of each region in the order that they finish.
+    pub async fn open_regions(
+        &self,
+        requests: Vec<(RegionId, RegionOpenRequest)>,
+        progress: impl Fn(OpenRegionsProgress),
+    ) -> Vec<(RegionId, Result<AffectedRows>)> {
+        self.inner.open_regions(requests, progress).await
+    }
+
     /// Returns a scanner to scan for `request`.
     fn scanner(&self, region_id: RegionId, request: ScanRequest) -> Result<Scanner> {
         self.inner.handle_query(region_id, request)
@@ -115,6 +270,17 @@ impl MitoEngine {
     }
 }
 
+/// Progress of opening regions in batch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OpenRegionsProgress {
+    /// Id of the region that just finished opening.
+    pub region_id: RegionId,
+    /// Number of regions finished opening.
+    pub finished: usize,
+    /// Total number of regions to open.
+    pub total: usize,
+}
+
 /// Inner struct of [MitoEngine].
 struct EngineInner {
     /// Region workers group.
@@ -164,12 +330,210 @@ impl EngineInner {
             .with_label_values(&[request.type_name()])
             .start_timer();
 
+        let request = match request {
+            RegionRequest::Alter(request) if request.kind.need_validation() => {
+                return self.alter_with_validation(region_id, request).await;
+            }
+            request => request,
+        };
+
         let (request, receiver) = WorkerRequest::try_from_region_request(region_id, request)?;
         self.workers.submit_to_worker(region_id, request).await?;
 
         receiver.await.context(RecvSnafu)?
     }
 
+    /// Submits all open `requests` to workers and waits for their results.
+    async fn open_regions(
+        &self,
+        requests: Vec<(RegionId, RegionOpenRequest)>,
+        progress: impl Fn(OpenRegionsProgress),
+    ) -> Vec<(RegionId, Result<AffectedRows>)> {
+        let total = requests.len();
+        let mut results = Vec::with_capacity(total);
+        let mut receivers = FuturesUnordered::new();
+        for (region_id, request) in requests {
+            let submitted = async {
+                let (request, receiver) = WorkerRequest::try_from_region_request(
+                    region_id,
+                    RegionRequest::Open(request),
+                )?;
+                self.workers.submit_to_worker(region_id, request).await?;
+                Ok(receiver)
+            }
+            .await;
+            match submitted {
+                Ok(receiver) => receivers.push(async move {
+                    (region_id, receiver.await.context(RecvSnafu).and_then(|r| r))
+                }),
+                Err(e) => {
+                    // Regions failed to submit are also finished.
+                    results.push((region_id, Err(e)));
+                    progress(OpenRegionsProgress {
+                        region_id,
+                        finished: results.len(),
+                        total,
+                    });
+                }
+            }
+        }
+
+        while let Some((region_id, result)) = receivers.next().await {
+            results.push((region_id, result));
+            progress(OpenRegionsProgress {
+                region_id,
+                finished: results.len(),
+                total,
+            });
+        }
+
+        results
+    }
+
+    /// Applies the key-value `edit` to the manifest of the region.
+    async fn update_region_kv(&self, region_id: RegionId, edit: RegionKvEdit) -> Result<()> {
+        let region = self
+            .workers
+            .get_region(region_id)
+            .context(RegionNotFoundSnafu { region_id })?;
+        ensure!(region.is_writable(), RegionReadonlySnafu { region_id });
+
+        region
+            .manifest_manager
+            .update(RegionMetaActionList::with_action(RegionMetaAction::Kv(
+                edit,
+            )))
+            .await?;
+
+        Ok(())
+    }
+
+    /// Validates existing data of the region and then submits the alter `request`
+    /// to the worker.
+    ///
+    /// Validation scans the region without blocking the worker, so we validate
+    /// the region again if it is modified during validation.
+    async fn alter_with_validation(
+        &self,
+        region_id: RegionId,
+        request: RegionAlterRequest,
+    ) -> Result<AffectedRows> {
+        let region = self
+            .workers
+            .get_region(region_id)
+            .context(RegionNotFoundSnafu { region_id })?;
+        // The worker validates the request so we only check the role here.
+        ensure!(region.is_writable(), RegionReadonlySnafu { region_id });
+
+        let mut retry = 0;
+        loop {
+            let validated_sequence =
+                validate_alteration(&region, &request.kind, Some(self.workers.cache_manager()))
+                    .await?;
+
+            let (worker_request, receiver) = WorkerRequest::new_validated_alter(
+                region_id,
+                ValidatedAlter {
+                    request: request.clone(),
+                    validated_sequence,
+                },
+            );
+            self.workers
+                .submit_to_worker(region_id, worker_request)
+                .await?;
+            match receiver.await.context(RecvSnafu)? {
+                Err(Error::ValidationConflict { .. }) if retry < MAX_VALIDATION_RETRY => {
+                    retry += 1;
+                    info!(
+                        "Region {} is modified during validation, retry: {}",
+                        region_id, retry
+                    );
+                }
+                res => return res,
+            }
+        }
+    }
+
+    /// Validates and copies staged files into the region, then submits them to the worker.
+    async fn ingest_staged_files(
+        &self,
+        region_id: RegionId,
+        file_names: &[String],
+    ) -> Result<AffectedRows> {
+        let region = self
+            .workers
+            .get_region(region_id)
+            .context(RegionNotFoundSnafu { region_id })?;
+        ensure!(region.is_writable(), RegionReadonlySnafu { region_id });
+        if file_names.is_empty() {
+            return Ok(0);
+        }
+
+        // Validates all files before copying them.
+        let metadata = region.metadata();
+        let mut staged_ssts = Vec::with_capacity(file_names.len());
+        for file_name in file_names {
+            let staged = load_staged_sst(&region.access_layer, file_name, &metadata).await?;
+            staged_ssts.push(staged);
+        }
+
+        let mut file_metas: Vec<FileMeta> = Vec::with_capacity(staged_ssts.len());
+        let mut num_rows = 0;
+        for staged in &staged_ssts {
+            let file_id = FileId::random();
+            let file_size = match region
+                .access_layer
+                .install_staged_sst(&staged.file_name, file_id)
+                .await
+            {
+                Ok(size) => size,
+                Err(e) => {
+                    // Removes files installed.
+                    for file_meta in &file_metas {
+                        region.file_purger.send_request(PurgeRequest {
+                            region_id,
+                            file_id: file_meta.file_id,
+                        });
+                    }
+                    return Err(e);
+                }
+            };
+            file_metas.push(FileMeta {
+                region_id,
+                file_id,
+                time_range: staged.time_range,
+                level: 0,
+                file_size,
+                // The worker assigns the sequence.
+                sequence: None,
+            });
+            num_rows += staged.num_rows;
+        }
+
+        let (request, receiver) = WorkerRequest::new_ingest(
+            region_id,
+            IngestFiles {
+                file_metas,
+                num_rows,
+            },
+        );
+        self.workers.submit_to_worker(region_id, request).await?;
+        let rows = receiver.await.context(RecvSnafu)??;
+
+        for staged in &staged_ssts {
+            if let Err(e) = region
+                .access_layer
+                .delete_staged_file(&staged.file_name)
+                .await
+            {
+                warn!(e; "Failed to delete staged file {}, region: {}",
+                    staged.file_name, region_id);
+            }
+        }
+
+        Ok(rows)
+    }
+
     /// Handles the scan `request` and returns a [Scanner] for the `request`.
     fn handle_query(&self, region_id: RegionId, request: ScanRequest) -> Result<Scanner> {
         // Reading a region doesn't need to go through the region worker thread.
@@ -191,11 +555,42 @@ impl EngineInner {
             request,
             Some(cache_manager),
         )
-        .with_parallelism(scan_parallelism);
+        .with_parallelism(scan_parallelism)
+        .with_disable_pruning(self.config.scan_disable_pruning);
 
         scan_region.scanner()
     }
 
+    /// Spawns a task to check whether pruning by filters of the `request` skips rows
+    /// matching the filters in the region.
+    fn verify_pruning(&self, region_id: RegionId, request: &ScanRequest) {
+        if request.filters.is_empty() {
+            // Nothing to prune.
+            return;
+        }
+        let Some(region) = self.workers.get_region(region_id) else {
+            return;
+        };
+        let version = region.version();
+        let access_layer = region.access_layer.clone();
+        let cache_manager = self.workers.cache_manager();
+        let filters = request.filters.clone();
+
+        common_runtime::spawn_read(async move {
+            match check_pruning(version, access_layer, filters.clone(), Some(cache_manager)).await {
+                Ok(check) if !check.is_consistent() => {
+                    PRUNING_MISMATCH_TOTAL.inc();
+                    warn!(
+                        "Pruning changes scan results of region {}, filters: {:?}, rows with pruning: {}, rows without pruning: {}",
+                        region_id, filters, check.pruned_rows, check.unpruned_rows
+                    );
+                }
+                Ok(_) => {}
+                Err(e) => warn!(e; "Failed to verify pruning of region {}", region_id),
+            }
+        });
+    }
+
     /// Set writable mode for a region.
     fn set_writable(&self, region_id: RegionId, writable: bool) -> Result<()> {
         let region = self
@@ -251,6 +646,9 @@ impl RegionEngine for MitoEngine {
         region_id: RegionId,
         request: ScanRequest,
     ) -> std::result::Result<SendableRecordBatchStream, BoxedError> {
+        if self.inner.config.scan_verify_pruning {
+            self.inner.verify_pruning(region_id, &request);
+        }
         self.scanner(region_id, request)
             .map_err(BoxedError::new)?
             .scan()
@@ -303,6 +701,23 @@ impl RegionEngine for MitoEngine {
     fn role(&self, region_id: RegionId) -> Option<RegionRole> {
         self.inner.role(region_id)
     }
+
+    async fn capture_region(
+        &self,
+        region_id: RegionId,
+        request: RegionCaptureRequest,
+    ) -> Result<Vec<String>, BoxedError> {
+        let region = self
+            .inner
+            .workers
+            .get_region(region_id)
+            .context(RegionNotFoundSnafu { region_id })
+            .map_err(BoxedError::new)?;
+
+        capture::capture_region(&region, &request)
+            .await
+            .map_err(BoxedError::new)
+    }
 }
 
 // Tests methods.
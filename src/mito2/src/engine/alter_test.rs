@@ -16,20 +16,28 @@ use std::collections::HashMap;
 
 use api::v1::value::ValueData;
 use api::v1::{ColumnDataType, Row, Rows, SemanticType};
+use bytes::Bytes;
+use common_error::ext::ErrorExt;
+use common_error::status_code::StatusCode;
 use common_recordbatch::RecordBatches;
+use datatypes::arrow::array::Int64Array;
 use datatypes::prelude::ConcreteDataType;
-use datatypes::schema::ColumnSchema;
-use store_api::metadata::ColumnMetadata;
+use datatypes::schema::{ColumnDefaultConstraint, ColumnSchema};
+use datatypes::value::Value;
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use store_api::metadata::{ColumnMetadata, RegionMetadataRef};
 use store_api::region_engine::RegionEngine;
 use store_api::region_request::{
-    AddColumn, AddColumnLocation, AlterKind, RegionAlterRequest, RegionOpenRequest, RegionRequest,
+    AddColumn, AddColumnLocation, AlterKind, RegionAlterRequest, RegionCompactRequest,
+    RegionOpenRequest, RegionPutRequest, RegionRequest,
 };
 use store_api::storage::{RegionId, ScanRequest};
 
 use crate::config::MitoConfig;
 use crate::engine::MitoEngine;
 use crate::test_util::{
-    build_rows, build_rows_for_key, put_rows, rows_schema, CreateRequestBuilder, TestEnv,
+    build_rows, build_rows_for_key, flush_region, put_rows, rows_schema, CreateRequestBuilder,
+    TestEnv,
 };
 
 async fn scan_check_after_alter(engine: &MitoEngine, region_id: RegionId, expected: &str) {
@@ -57,6 +65,7 @@ fn add_tag1() -> RegionAlterRequest {
                     column_id: 3,
                 },
                 location: Some(AddColumnLocation::First),
+                backfill: false,
             }],
         },
     }
@@ -297,3 +306,278 @@ async fn test_alter_region_retry() {
     assert_eq!(1, version_data.version.flushed_entry_id);
     assert_eq!(2, version_data.version.flushed_sequence);
 }
+
+#[tokio::test]
+async fn test_alter_region_metadata_history() {
+    let mut env = TestEnv::new();
+    let engine = env.create_engine(MitoConfig::default()).await;
+
+    let region_id = RegionId::new(1, 1);
+    let request = CreateRequestBuilder::new().build();
+    let region_dir = request.region_dir.clone();
+    engine
+        .handle_request(region_id, RegionRequest::Create(request))
+        .await
+        .unwrap();
+    assert!(engine
+        .region_metadata_history(region_id)
+        .await
+        .unwrap()
+        .is_empty());
+
+    let request = add_tag1();
+    engine
+        .handle_request(region_id, RegionRequest::Alter(request))
+        .await
+        .unwrap();
+
+    let check_history = |history: Vec<RegionMetadataRef>| {
+        assert_eq!(1, history.len());
+        assert_eq!(0, history[0].schema_version);
+        assert!(history[0].column_by_name("tag_1").is_none());
+    };
+    check_history(engine.region_metadata_history(region_id).await.unwrap());
+
+    // The history is persisted in the manifest.
+    let engine = env.reopen_engine(engine, MitoConfig::default()).await;
+    engine
+        .handle_request(
+            region_id,
+            RegionRequest::Open(RegionOpenRequest {
+                engine: String::new(),
+                region_dir,
+                options: HashMap::default(),
+                skip_wal_replay: false,
+            }),
+        )
+        .await
+        .unwrap();
+    check_history(engine.region_metadata_history(region_id).await.unwrap());
+    let metadata = engine.get_metadata(region_id).await.unwrap();
+    assert_eq!(1, metadata.schema_version);
+}
+
+#[tokio::test]
+async fn test_alter_region_backfill() {
+    common_telemetry::init_default_ut_logging();
+
+    let mut env = TestEnv::new();
+    let engine = env.create_engine(MitoConfig::default()).await;
+    let object_store = env.get_object_store().unwrap();
+
+    let region_id = RegionId::new(1, 1);
+    let request = CreateRequestBuilder::new().build();
+
+    let column_schemas = rows_schema(&request);
+    engine
+        .handle_request(region_id, RegionRequest::Create(request))
+        .await
+        .unwrap();
+
+    let rows = Rows {
+        schema: column_schemas,
+        rows: build_rows(0, 3),
+    };
+    put_rows(&engine, region_id, rows).await;
+    flush_region(&engine, region_id, None).await;
+    let region = engine.get_region(region_id).unwrap();
+    let old_file = region.version().ssts.levels()[0]
+        .files()
+        .next()
+        .unwrap()
+        .file_id();
+
+    let request = RegionAlterRequest {
+        schema_version: 0,
+        kind: AlterKind::AddColumns {
+            columns: vec![AddColumn {
+                column_metadata: ColumnMetadata {
+                    column_schema: ColumnSchema::new(
+                        "field_1",
+                        ConcreteDataType::int64_datatype(),
+                        true,
+                    )
+                    .with_default_constraint(Some(ColumnDefaultConstraint::Value(Value::Int64(10))))
+                    .unwrap(),
+                    semantic_type: SemanticType::Field,
+                    column_id: 3,
+                },
+                location: None,
+                backfill: true,
+            }],
+        },
+    };
+    engine
+        .handle_request(region_id, RegionRequest::Alter(request))
+        .await
+        .unwrap();
+    // Waits for the backfill task.
+    engine
+        .handle_request(region_id, RegionRequest::Compact(RegionCompactRequest {}))
+        .await
+        .unwrap();
+
+    // The old file is rewritten.
+    let version = region.version();
+    let files: Vec<_> = version.ssts.levels()[0].files().cloned().collect();
+    assert_eq!(1, files.len());
+    assert_ne!(old_file, files[0].file_id());
+
+    // The default value is stored in the new file.
+    let data = object_store
+        .read(&files[0].file_path(region.region_dir()))
+        .await
+        .unwrap();
+    let mut reader = ParquetRecordBatchReaderBuilder::try_new(Bytes::from(data))
+        .unwrap()
+        .build()
+        .unwrap();
+    let batch = reader.next().unwrap().unwrap();
+    let column = batch
+        .column_by_name("field_1")
+        .unwrap()
+        .as_any()
+        .downcast_ref::<Int64Array>()
+        .unwrap();
+    assert_eq!(&Int64Array::from(vec![10, 10, 10]), column);
+
+    let expected = "\
++-------+---------+---------------------+---------+
+| tag_0 | field_0 | ts                  | field_1 |
++-------+---------+---------------------+---------+
+| 0     | 0.0     | 1970-01-01T00:00:00 | 10      |
+| 1     | 1.0     | 1970-01-01T00:00:01 | 10      |
+| 2     | 2.0     | 1970-01-01T00:00:02 | 10      |
++-------+---------+---------------------+---------+";
+    scan_check_after_alter(&engine, region_id, expected).await;
+}
+
+fn set_field0_not_null() -> RegionAlterRequest {
+    RegionAlterRequest {
+        schema_version: 0,
+        kind: AlterKind::SetNotNull {
+            names: vec!["field_0".to_string()],
+        },
+    }
+}
+
+/// Builds rows whose field_0 is null.
+fn build_rows_with_null_field(start: usize, end: usize) -> Vec<Row> {
+    (start..end)
+        .map(|i| Row {
+            values: vec![
+                api::v1::Value {
+                    value_data: Some(ValueData::StringValue(i.to_string())),
+                },
+                api::v1::Value { value_data: None },
+                api::v1::Value {
+                    value_data: Some(ValueData::TimestampMillisecondValue(i as i64 * 1000)),
+                },
+            ],
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn test_alter_region_set_not_null() {
+    common_telemetry::init_default_ut_logging();
+
+    let mut env = TestEnv::new();
+    let engine = env.create_engine(MitoConfig::default()).await;
+
+    let region_id = RegionId::new(1, 1);
+    let request = CreateRequestBuilder::new().build();
+
+    let column_schemas = rows_schema(&request);
+    engine
+        .handle_request(region_id, RegionRequest::Create(request))
+        .await
+        .unwrap();
+
+    let rows = Rows {
+        schema: column_schemas.clone(),
+        rows: build_rows(0, 3),
+    };
+    put_rows(&engine, region_id, rows).await;
+    flush_region(&engine, region_id, None).await;
+    // Validates data in both memtables and SSTs.
+    let rows = Rows {
+        schema: column_schemas.clone(),
+        rows: build_rows(3, 5),
+    };
+    put_rows(&engine, region_id, rows).await;
+
+    engine
+        .handle_request(region_id, RegionRequest::Alter(set_field0_not_null()))
+        .await
+        .unwrap();
+
+    let region = engine.get_region(region_id).unwrap();
+    let metadata = region.metadata();
+    assert_eq!(1, metadata.schema_version);
+    assert!(!metadata
+        .column_by_name("field_0")
+        .unwrap()
+        .column_schema
+        .is_nullable());
+
+    // Puts null to the column.
+    let rows = Rows {
+        schema: column_schemas,
+        rows: build_rows_with_null_field(5, 6),
+    };
+    let err = engine
+        .handle_request(region_id, RegionRequest::Put(RegionPutRequest { rows }))
+        .await
+        .unwrap_err();
+    assert_eq!(StatusCode::InvalidArguments, err.status_code());
+}
+
+#[tokio::test]
+async fn test_alter_region_set_not_null_with_nulls() {
+    common_telemetry::init_default_ut_logging();
+
+    let mut env = TestEnv::new();
+    let engine = env.create_engine(MitoConfig::default()).await;
+
+    let region_id = RegionId::new(1, 1);
+    let request = CreateRequestBuilder::new().build();
+
+    let column_schemas = rows_schema(&request);
+    engine
+        .handle_request(region_id, RegionRequest::Create(request))
+        .await
+        .unwrap();
+
+    let rows = Rows {
+        schema: column_schemas.clone(),
+        rows: build_rows(0, 3),
+    };
+    put_rows(&engine, region_id, rows).await;
+    let rows = Rows {
+        schema: column_schemas,
+        rows: build_rows_with_null_field(3, 5),
+    };
+    put_rows(&engine, region_id, rows).await;
+    flush_region(&engine, region_id, None).await;
+
+    let err = engine
+        .handle_request(region_id, RegionRequest::Alter(set_field0_not_null()))
+        .await
+        .unwrap_err();
+    assert_eq!(StatusCode::InvalidArguments, err.status_code());
+    let msg = err.output_msg();
+    assert!(msg.contains("contains null values"), "{msg}");
+    assert!(msg.contains("tag_0=3"), "{msg}");
+    assert!(msg.contains("tag_0=4"), "{msg}");
+
+    // The region isn't altered.
+    let region = engine.get_region(region_id).unwrap();
+    let metadata = region.metadata();
+    assert_eq!(0, metadata.schema_version);
+    assert!(metadata
+        .column_by_name("field_0")
+        .unwrap()
+        .column_schema
+        .is_nullable());
+}
@@ -80,6 +80,58 @@ async fn test_write_to_region() {
     put_rows(&engine, region_id, rows).await;
 }
 
+#[tokio::test]
+async fn test_write_with_noop_log_store() {
+    let mut env = TestEnv::with_prefix("write-noop-wal");
+    let engine = env
+        .create_engine_with_noop_log_store(MitoConfig::default())
+        .await;
+
+    let region_id = RegionId::new(1, 1);
+    let request = CreateRequestBuilder::new().build();
+    let region_dir = request.region_dir.clone();
+
+    let column_schemas = rows_schema(&request);
+    engine
+        .handle_request(region_id, RegionRequest::Create(request))
+        .await
+        .unwrap();
+
+    let rows = Rows {
+        schema: column_schemas.clone(),
+        rows: build_rows(0, 20),
+    };
+    put_rows(&engine, region_id, rows).await;
+    let rows = Rows {
+        schema: column_schemas.clone(),
+        rows: build_rows(20, 42),
+    };
+    put_rows(&engine, region_id, rows).await;
+
+    let region = engine.get_region(region_id).unwrap();
+    let VersionControlData {
+        committed_sequence,
+        last_entry_id,
+        ..
+    } = region.version_control.current();
+    assert_eq!(42, committed_sequence);
+    assert_eq!(2, last_entry_id);
+
+    flush_region(&engine, region_id, None).await;
+    // Rows not flushed are lost after reopening the region.
+    let rows = Rows {
+        schema: column_schemas,
+        rows: build_rows(42, 50),
+    };
+    put_rows(&engine, region_id, rows).await;
+    reopen_region(&engine, region_id, region_dir, false).await;
+
+    let request = ScanRequest::default();
+    let stream = engine.handle_query(region_id, request).await.unwrap();
+    let batches = RecordBatches::try_collect(stream).await.unwrap();
+    assert_eq!(42, batches.iter().map(|b| b.num_rows()).sum::<usize>());
+}
+
 #[tokio::test]
 async fn test_region_replay() {
     common_telemetry::init_default_ut_logging();
@@ -0,0 +1,84 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use api::v1::Rows;
+use common_query::prelude::Expr;
+use common_test_util::temp_dir::create_temp_dir;
+use datafusion_common::ScalarValue;
+use datafusion_expr::{col, lit};
+use store_api::region_engine::{RegionCaptureRequest, RegionEngine};
+use store_api::region_request::RegionRequest;
+use store_api::storage::RegionId;
+
+use crate::config::MitoConfig;
+use crate::test_util::{
+    build_rows, flush_region, put_rows, rows_schema, CreateRequestBuilder, TestEnv,
+};
+
+#[tokio::test]
+async fn test_engine_capture_region() {
+    let mut env = TestEnv::with_prefix("capture-region");
+    let engine = env.create_engine(MitoConfig::default()).await;
+
+    let region_id = RegionId::new(1, 1);
+    let request = CreateRequestBuilder::new().build();
+    let column_schemas = rows_schema(&request);
+    engine
+        .handle_request(region_id, RegionRequest::Create(request))
+        .await
+        .unwrap();
+
+    for (start, end) in [(0, 5), (10, 15)] {
+        let rows = Rows {
+            schema: column_schemas.clone(),
+            rows: build_rows(start, end),
+        };
+        put_rows(&engine, region_id, rows).await;
+        flush_region(&engine, region_id, None).await;
+    }
+
+    // Only the second SST overlaps with the filter.
+    let dir = create_temp_dir("capture-target");
+    let filter = col("ts").gt_eq(lit(ScalarValue::TimestampMillisecond(Some(10000), None)));
+    let files = engine
+        .capture_region(
+            region_id,
+            RegionCaptureRequest {
+                filters: vec![Expr::from(filter)],
+                target_dir: dir.path().to_str().unwrap().to_string(),
+            },
+        )
+        .await
+        .unwrap();
+    let ssts: Vec<_> = files.iter().filter(|f| f.ends_with(".parquet")).collect();
+    assert_eq!(1, ssts.len());
+    assert!(files.iter().any(|f| f.starts_with("manifest/")));
+    for file in &files {
+        assert!(dir.path().join(file).exists(), "{file} not found");
+    }
+
+    // Captures all SSTs without filters.
+    let dir = create_temp_dir("capture-target-all");
+    let files = engine
+        .capture_region(
+            region_id,
+            RegionCaptureRequest {
+                filters: vec![],
+                target_dir: dir.path().to_str().unwrap().to_string(),
+            },
+        )
+        .await
+        .unwrap();
+    assert_eq!(2, files.iter().filter(|f| f.ends_with(".parquet")).count());
+}
@@ -0,0 +1,218 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use api::v1::Rows;
+use common_error::ext::ErrorExt;
+use common_error::status_code::StatusCode;
+use common_recordbatch::RecordBatches;
+use common_time::Timestamp;
+use datatypes::value::Value;
+use store_api::region_engine::RegionEngine;
+use store_api::region_request::{RegionCompactRequest, RegionDeleteRangeRequest, RegionRequest};
+use store_api::storage::{RegionId, ScanRequest};
+
+use crate::config::MitoConfig;
+use crate::engine::MitoEngine;
+use crate::test_util::{
+    build_rows, flush_region, put_rows, reopen_region, rows_schema, CreateRequestBuilder, TestEnv,
+};
+
+async fn delete_range(
+    engine: &MitoEngine,
+    region_id: RegionId,
+    start: i64,
+    end: i64,
+    tag_filters: Vec<(String, Value)>,
+) {
+    let rows = engine
+        .handle_request(
+            region_id,
+            RegionRequest::DeleteRange(RegionDeleteRangeRequest {
+                start: Timestamp::new_millisecond(start),
+                end: Timestamp::new_millisecond(end),
+                tag_filters,
+            }),
+        )
+        .await
+        .unwrap();
+    assert_eq!(0, rows);
+}
+
+async fn scan_region(engine: &MitoEngine, region_id: RegionId) -> String {
+    let stream = engine
+        .handle_query(region_id, ScanRequest::default())
+        .await
+        .unwrap();
+    let batches = RecordBatches::try_collect(stream).await.unwrap();
+    batches.pretty_print().unwrap()
+}
+
+#[tokio::test]
+async fn test_engine_delete_range() {
+    let mut env = TestEnv::with_prefix("delete-range");
+    let engine = env.create_engine(MitoConfig::default()).await;
+
+    let region_id = RegionId::new(1, 1);
+    let request = CreateRequestBuilder::new().build();
+    let region_dir = request.region_dir.clone();
+    let column_schemas = rows_schema(&request);
+    engine
+        .handle_request(region_id, RegionRequest::Create(request))
+        .await
+        .unwrap();
+
+    // Rows in SSTs and memtables.
+    let rows = Rows {
+        schema: column_schemas.clone(),
+        rows: build_rows(0, 5),
+    };
+    put_rows(&engine, region_id, rows).await;
+    flush_region(&engine, region_id, None).await;
+    let rows = Rows {
+        schema: column_schemas.clone(),
+        rows: build_rows(5, 8),
+    };
+    put_rows(&engine, region_id, rows).await;
+
+    delete_range(&engine, region_id, 1000, 6000, Vec::new()).await;
+    // Rows written after deletion are visible.
+    let rows = Rows {
+        schema: column_schemas,
+        rows: build_rows(3, 4),
+    };
+    put_rows(&engine, region_id, rows).await;
+
+    let expected = "\
++-------+---------+---------------------+
+| tag_0 | field_0 | ts                  |
++-------+---------+---------------------+
+| 0     | 0.0     | 1970-01-01T00:00:00 |
+| 3     | 3.0     | 1970-01-01T00:00:03 |
+| 6     | 6.0     | 1970-01-01T00:00:06 |
+| 7     | 7.0     | 1970-01-01T00:00:07 |
++-------+---------+---------------------+";
+    assert_eq!(expected, scan_region(&engine, region_id).await);
+
+    // Deletes rows with a specific tag.
+    delete_range(
+        &engine,
+        region_id,
+        0,
+        10000,
+        vec![("tag_0".to_string(), Value::String("7".into()))],
+    )
+    .await;
+    let expected = "\
++-------+---------+---------------------+
+| tag_0 | field_0 | ts                  |
++-------+---------+---------------------+
+| 0     | 0.0     | 1970-01-01T00:00:00 |
+| 3     | 3.0     | 1970-01-01T00:00:03 |
+| 6     | 6.0     | 1970-01-01T00:00:06 |
++-------+---------+---------------------+";
+    assert_eq!(expected, scan_region(&engine, region_id).await);
+
+    // Tombstones are persisted.
+    reopen_region(&engine, region_id, region_dir, true).await;
+    assert_eq!(expected, scan_region(&engine, region_id).await);
+    flush_region(&engine, region_id, None).await;
+    assert_eq!(expected, scan_region(&engine, region_id).await);
+}
+
+#[tokio::test]
+async fn test_engine_compact_delete_range() {
+    let mut env = TestEnv::with_prefix("compact-delete-range");
+    let engine = env.create_engine(MitoConfig::default()).await;
+
+    let region_id = RegionId::new(1, 1);
+    let request = CreateRequestBuilder::new().build();
+    let region_dir = request.region_dir.clone();
+    let column_schemas = rows_schema(&request);
+    engine
+        .handle_request(region_id, RegionRequest::Create(request))
+        .await
+        .unwrap();
+
+    for (start, end) in [(0, 5), (5, 8)] {
+        let rows = Rows {
+            schema: column_schemas.clone(),
+            rows: build_rows(start, end),
+        };
+        put_rows(&engine, region_id, rows).await;
+        flush_region(&engine, region_id, None).await;
+    }
+    delete_range(&engine, region_id, 1000, 3000, Vec::new()).await;
+    let region = engine.get_region(region_id).unwrap();
+    assert_eq!(1, region.version().range_tombstones.len());
+
+    // Compaction removes deleted rows and the tombstone.
+    let output = engine
+        .handle_request(region_id, RegionRequest::Compact(RegionCompactRequest {}))
+        .await
+        .unwrap();
+    assert_eq!(0, output);
+    let scanner = engine.scanner(region_id, ScanRequest::default()).unwrap();
+    assert_eq!(1, scanner.num_files());
+    assert!(region.version().range_tombstones.is_empty());
+
+    let expected = "\
++-------+---------+---------------------+
+| tag_0 | field_0 | ts                  |
++-------+---------+---------------------+
+| 0     | 0.0     | 1970-01-01T00:00:00 |
+| 3     | 3.0     | 1970-01-01T00:00:03 |
+| 4     | 4.0     | 1970-01-01T00:00:04 |
+| 5     | 5.0     | 1970-01-01T00:00:05 |
+| 6     | 6.0     | 1970-01-01T00:00:06 |
+| 7     | 7.0     | 1970-01-01T00:00:07 |
++-------+---------+---------------------+";
+    assert_eq!(expected, scan_region(&engine, region_id).await);
+
+    reopen_region(&engine, region_id, region_dir, true).await;
+    let region = engine.get_region(region_id).unwrap();
+    assert!(region.version().range_tombstones.is_empty());
+    assert_eq!(expected, scan_region(&engine, region_id).await);
+}
+
+#[tokio::test]
+async fn test_engine_delete_range_invalid_tag() {
+    let mut env = TestEnv::with_prefix("delete-range-invalid");
+    let engine = env.create_engine(MitoConfig::default()).await;
+
+    let region_id = RegionId::new(1, 1);
+    let request = CreateRequestBuilder::new().build();
+    engine
+        .handle_request(region_id, RegionRequest::Create(request))
+        .await
+        .unwrap();
+
+    for tag_filters in [
+        vec![("unknown".to_string(), Value::String("a".into()))],
+        vec![("field_0".to_string(), Value::Float64(1.0.into()))],
+        vec![("tag_0".to_string(), Value::Int64(1))],
+    ] {
+        let err = engine
+            .handle_request(
+                region_id,
+                RegionRequest::DeleteRange(RegionDeleteRangeRequest {
+                    start: Timestamp::new_millisecond(0),
+                    end: Timestamp::new_millisecond(1000),
+                    tag_filters,
+                }),
+            )
+            .await
+            .unwrap_err();
+        assert_eq!(StatusCode::InvalidArguments, err.status_code());
+    }
+}
@@ -24,6 +24,7 @@ use store_api::storage::RegionId;
 use crate::config::MitoConfig;
 use crate::engine::listener::DropListener;
 use crate::engine::MitoEngine;
+use crate::purge_job::PurgeJobState;
 use crate::test_util::{
     build_rows_for_key, flush_region, put_rows, rows_schema, CreateRequestBuilder, TestEnv,
 };
@@ -77,11 +78,20 @@ async fn test_engine_drop_region() {
         .unwrap();
     assert!(!engine.is_region_exists(region_id));
 
+    let jobs = engine.purge_jobs();
+    assert_eq!(1, jobs.len());
+    assert_eq!(region_id, jobs[0].region_id);
+    assert_eq!(region_dir, jobs[0].region_dir);
+
     // Wait for drop task.
     listener.wait().await;
 
     let object_store = env.get_object_store().unwrap();
     assert!(!object_store.is_exist(&region_dir).await.unwrap());
+    let jobs = engine.purge_jobs();
+    assert_eq!(PurgeJobState::Done, jobs[0].state);
+    assert!(jobs[0].attempts >= 1);
+    assert_eq!(0, jobs[0].remaining_files);
 }
 
 #[tokio::test]
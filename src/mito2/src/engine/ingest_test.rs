@@ -0,0 +1,289 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Ingest tests for mito engine.
+
+use api::v1::value::ValueData;
+use api::v1::{Row, Rows, Value};
+use common_recordbatch::RecordBatches;
+use object_store::util::join_path;
+use object_store::ObjectStore;
+use store_api::region_engine::RegionEngine;
+use store_api::region_request::RegionRequest;
+use store_api::storage::{RegionId, ScanRequest};
+
+use crate::config::MitoConfig;
+use crate::engine::MitoEngine;
+use crate::test_util::{
+    build_rows, build_rows_for_key, flush_region, put_rows, reopen_region, rows_schema,
+    CreateRequestBuilder, TestEnv,
+};
+
+/// Flushes the source region and copies its SST file to the staging
+/// directory of the target region as `file_name`.
+async fn stage_sst_from_region(
+    engine: &MitoEngine,
+    object_store: &ObjectStore,
+    source_id: RegionId,
+    target_id: RegionId,
+    file_name: &str,
+) {
+    flush_region(engine, source_id, None).await;
+
+    let region = engine.get_region(source_id).unwrap();
+    let version = region.version();
+    let file = version.ssts.levels()[0].files().next().unwrap().clone();
+    let data = object_store
+        .read(&file.file_path(region.region_dir()))
+        .await
+        .unwrap();
+
+    let staging_dir = engine.staging_dir(target_id).unwrap();
+    object_store
+        .write(&join_path(&staging_dir, file_name), data)
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_ingest_staged_files() {
+    let mut env = TestEnv::with_prefix("ingest-staged");
+    let engine = env.create_engine(MitoConfig::default()).await;
+    let object_store = env.get_object_store().unwrap();
+
+    let source_id = RegionId::new(1, 1);
+    let request = CreateRequestBuilder::new().region_dir("source").build();
+    let column_schemas = rows_schema(&request);
+    engine
+        .handle_request(source_id, RegionRequest::Create(request))
+        .await
+        .unwrap();
+    let target_id = RegionId::new(2, 1);
+    let request = CreateRequestBuilder::new().region_dir("target").build();
+    engine
+        .handle_request(target_id, RegionRequest::Create(request))
+        .await
+        .unwrap();
+
+    let rows = Rows {
+        schema: column_schemas,
+        rows: build_rows(0, 3),
+    };
+    put_rows(&engine, source_id, rows).await;
+    stage_sst_from_region(&engine, &object_store, source_id, target_id, "a.parquet").await;
+
+    let rows = engine
+        .ingest_staged_files(target_id, &["a.parquet".to_string()])
+        .await
+        .unwrap();
+    assert_eq!(3, rows);
+    let staging_dir = engine.staging_dir(target_id).unwrap();
+    assert!(!object_store
+        .is_exist(&join_path(&staging_dir, "a.parquet"))
+        .await
+        .unwrap());
+
+    let expected = "\
++-------+---------+---------------------+
+| tag_0 | field_0 | ts                  |
++-------+---------+---------------------+
+| 0     | 0.0     | 1970-01-01T00:00:00 |
+| 1     | 1.0     | 1970-01-01T00:00:01 |
+| 2     | 2.0     | 1970-01-01T00:00:02 |
++-------+---------+---------------------+";
+    let stream = engine
+        .handle_query(target_id, ScanRequest::default())
+        .await
+        .unwrap();
+    let batches = RecordBatches::try_collect(stream).await.unwrap();
+    assert_eq!(expected, batches.pretty_print().unwrap());
+
+    // Ingested files are persisted in the manifest.
+    reopen_region(&engine, target_id, "target".to_string(), true).await;
+    let stream = engine
+        .handle_query(target_id, ScanRequest::default())
+        .await
+        .unwrap();
+    let batches = RecordBatches::try_collect(stream).await.unwrap();
+    assert_eq!(expected, batches.pretty_print().unwrap());
+}
+
+async fn scan_region(engine: &MitoEngine, region_id: RegionId) -> String {
+    let stream = engine
+        .handle_query(region_id, ScanRequest::default())
+        .await
+        .unwrap();
+    let batches = RecordBatches::try_collect(stream).await.unwrap();
+    batches.pretty_print().unwrap()
+}
+
+#[tokio::test]
+async fn test_ingest_overwrite_rows() {
+    let mut env = TestEnv::with_prefix("ingest-overwrite");
+    let engine = env.create_engine(MitoConfig::default()).await;
+    let object_store = env.get_object_store().unwrap();
+
+    let source_id = RegionId::new(1, 1);
+    let request = CreateRequestBuilder::new().region_dir("source").build();
+    let column_schemas = rows_schema(&request);
+    engine
+        .handle_request(source_id, RegionRequest::Create(request))
+        .await
+        .unwrap();
+    let target_id = RegionId::new(2, 1);
+    let request = CreateRequestBuilder::new().region_dir("target").build();
+    engine
+        .handle_request(target_id, RegionRequest::Create(request))
+        .await
+        .unwrap();
+
+    // Sequences in the file are less than sequences in the target region.
+    let rows = Rows {
+        schema: column_schemas.clone(),
+        rows: build_rows_for_key("a", 0, 3, 0),
+    };
+    put_rows(&engine, source_id, rows).await;
+    stage_sst_from_region(&engine, &object_store, source_id, target_id, "a.parquet").await;
+    for _ in 0..5 {
+        let rows = Rows {
+            schema: column_schemas.clone(),
+            rows: build_rows_for_key("a", 0, 3, 10),
+        };
+        put_rows(&engine, target_id, rows).await;
+    }
+
+    // Rows ingested overwrite rows written before.
+    engine
+        .ingest_staged_files(target_id, &["a.parquet".to_string()])
+        .await
+        .unwrap();
+    let region = engine.get_region(target_id).unwrap();
+    assert!(region.version().memtables.is_empty());
+    assert_eq!(16, region.version_control.current().committed_sequence);
+    let expected = "\
++-------+---------+---------------------+
+| tag_0 | field_0 | ts                  |
++-------+---------+---------------------+
+| a     | 0.0     | 1970-01-01T00:00:00 |
+| a     | 1.0     | 1970-01-01T00:00:01 |
+| a     | 2.0     | 1970-01-01T00:00:02 |
++-------+---------+---------------------+";
+    assert_eq!(expected, scan_region(&engine, target_id).await);
+
+    // Rows written after the ingestion overwrite rows ingested.
+    let rows = Rows {
+        schema: column_schemas.clone(),
+        rows: build_rows_for_key("a", 1, 2, 20),
+    };
+    put_rows(&engine, target_id, rows).await;
+    let expected = "\
++-------+---------+---------------------+
+| tag_0 | field_0 | ts                  |
++-------+---------+---------------------+
+| a     | 0.0     | 1970-01-01T00:00:00 |
+| a     | 20.0    | 1970-01-01T00:00:01 |
+| a     | 2.0     | 1970-01-01T00:00:02 |
++-------+---------+---------------------+";
+    assert_eq!(expected, scan_region(&engine, target_id).await);
+
+    // Sequences keep increasing after reopening the region.
+    reopen_region(&engine, target_id, "target".to_string(), true).await;
+    assert_eq!(expected, scan_region(&engine, target_id).await);
+    let rows = Rows {
+        schema: column_schemas,
+        rows: build_rows_for_key("a", 2, 3, 30),
+    };
+    put_rows(&engine, target_id, rows).await;
+    let expected = "\
++-------+---------+---------------------+
+| tag_0 | field_0 | ts                  |
++-------+---------+---------------------+
+| a     | 0.0     | 1970-01-01T00:00:00 |
+| a     | 20.0    | 1970-01-01T00:00:01 |
+| a     | 30.0    | 1970-01-01T00:00:02 |
++-------+---------+---------------------+";
+    assert_eq!(expected, scan_region(&engine, target_id).await);
+}
+
+#[tokio::test]
+async fn test_ingest_invalid_files() {
+    let mut env = TestEnv::with_prefix("ingest-invalid");
+    let engine = env.create_engine(MitoConfig::default()).await;
+    let object_store = env.get_object_store().unwrap();
+
+    let source_id = RegionId::new(1, 1);
+    let request = CreateRequestBuilder::new()
+        .region_dir("source")
+        .tag_num(2)
+        .build();
+    let column_schemas = rows_schema(&request);
+    engine
+        .handle_request(source_id, RegionRequest::Create(request))
+        .await
+        .unwrap();
+    let target_id = RegionId::new(2, 1);
+    let request = CreateRequestBuilder::new().region_dir("target").build();
+    engine
+        .handle_request(target_id, RegionRequest::Create(request))
+        .await
+        .unwrap();
+
+    let rows = Rows {
+        schema: column_schemas,
+        rows: build_rows_for_tags(0, 3),
+    };
+    put_rows(&engine, source_id, rows).await;
+    stage_sst_from_region(&engine, &object_store, source_id, target_id, "a.parquet").await;
+    // Stage a file that is not a parquet file.
+    let staging_dir = engine.staging_dir(target_id).unwrap();
+    object_store
+        .write(&join_path(&staging_dir, "b.parquet"), "invalid")
+        .await
+        .unwrap();
+
+    // The source region has more tags.
+    engine
+        .ingest_staged_files(target_id, &["a.parquet".to_string()])
+        .await
+        .unwrap_err();
+    engine
+        .ingest_staged_files(target_id, &["b.parquet".to_string()])
+        .await
+        .unwrap_err();
+
+    let region = engine.get_region(target_id).unwrap();
+    assert_eq!(0, region.version().ssts.levels()[0].files().count());
+}
+
+/// Builds rows with two tags.
+fn build_rows_for_tags(start: usize, end: usize) -> Vec<Row> {
+    (start..end)
+        .map(|i| Row {
+            values: vec![
+                Value {
+                    value_data: Some(ValueData::StringValue(i.to_string())),
+                },
+                Value {
+                    value_data: Some(ValueData::StringValue(i.to_string())),
+                },
+                Value {
+                    value_data: Some(ValueData::F64Value(i as f64)),
+                },
+                Value {
+                    value_data: Some(ValueData::TimestampMillisecondValue(i as i64 * 1000)),
+                },
+            ],
+        })
+        .collect()
+}
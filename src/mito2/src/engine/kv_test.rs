@@ -0,0 +1,92 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use common_error::ext::ErrorExt;
+use common_error::status_code::StatusCode;
+use serde::{Deserialize, Serialize};
+use store_api::region_engine::RegionEngine;
+use store_api::region_request::RegionRequest;
+use store_api::storage::RegionId;
+
+use crate::config::MitoConfig;
+use crate::region::kv::{RegionKvKey, MAX_REGION_KV_VALUE_SIZE};
+use crate::test_util::{reopen_region, CreateRequestBuilder, TestEnv};
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Window {
+    start: i64,
+    end: i64,
+}
+
+const WINDOW_KEY: RegionKvKey<Window> = RegionKvKey::new("test.window");
+const NAME_KEY: RegionKvKey<String> = RegionKvKey::new("test.name");
+
+#[tokio::test]
+async fn test_region_kv() {
+    let mut env = TestEnv::with_prefix("region-kv");
+    let engine = env.create_engine(MitoConfig::default()).await;
+
+    let region_id = RegionId::new(1, 1);
+    let request = CreateRequestBuilder::new().build();
+    let region_dir = request.region_dir.clone();
+    engine
+        .handle_request(region_id, RegionRequest::Create(request))
+        .await
+        .unwrap();
+
+    assert_eq!(
+        None,
+        engine.get_region_kv(region_id, &WINDOW_KEY).await.unwrap()
+    );
+    engine
+        .put_region_kv(region_id, &WINDOW_KEY, &Window { start: 1, end: 2 })
+        .await
+        .unwrap();
+    engine
+        .put_region_kv(region_id, &NAME_KEY, &"a".to_string())
+        .await
+        .unwrap();
+    // Overwrites the value.
+    engine
+        .put_region_kv(region_id, &WINDOW_KEY, &Window { start: 3, end: 4 })
+        .await
+        .unwrap();
+    engine.delete_region_kv(region_id, &NAME_KEY).await.unwrap();
+
+    // States are persisted in the manifest.
+    let engine = env.reopen_engine(engine, MitoConfig::default()).await;
+    reopen_region(&engine, region_id, region_dir, false).await;
+    assert_eq!(
+        Some(Window { start: 3, end: 4 }),
+        engine.get_region_kv(region_id, &WINDOW_KEY).await.unwrap()
+    );
+    assert_eq!(
+        None,
+        engine.get_region_kv(region_id, &NAME_KEY).await.unwrap()
+    );
+
+    // The region is readonly after reopening.
+    let err = engine
+        .put_region_kv(region_id, &NAME_KEY, &"b".to_string())
+        .await
+        .unwrap_err();
+    assert_eq!(StatusCode::RegionReadonly, err.status_code());
+
+    engine.set_writable(region_id, true).unwrap();
+    let err = engine
+        .put_region_kv(region_id, &NAME_KEY, &"b".repeat(MAX_REGION_KV_VALUE_SIZE))
+        .await
+        .unwrap_err();
+    assert_eq!(StatusCode::InvalidArguments, err.status_code());
+}
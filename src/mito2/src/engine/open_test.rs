@@ -319,3 +319,153 @@ async fn test_open_region_skip_wal_replay() {
 +-------+---------+---------------------+";
     assert_eq!(expected, batches.pretty_print().unwrap());
 }
+
+#[tokio::test]
+async fn test_engine_open_regions() {
+    let mut env = TestEnv::with_prefix("open-regions");
+    let engine = env.create_engine(MitoConfig::default()).await;
+
+    let mut region_dirs = Vec::new();
+    for i in 0..3 {
+        let region_id = RegionId::new(1, i);
+        let request = CreateRequestBuilder::new()
+            .region_dir(&format!("test-{i}"))
+            .build();
+        region_dirs.push((region_id, request.region_dir.clone()));
+        engine
+            .handle_request(region_id, RegionRequest::Create(request))
+            .await
+            .unwrap();
+    }
+
+    let engine = env.reopen_engine(engine, MitoConfig::default()).await;
+    let open_request = |region_dir: &str| RegionOpenRequest {
+        engine: String::new(),
+        region_dir: region_dir.to_string(),
+        options: HashMap::default(),
+        skip_wal_replay: false,
+    };
+    let mut requests: Vec<_> = region_dirs
+        .iter()
+        .map(|(region_id, region_dir)| (*region_id, open_request(region_dir)))
+        .collect();
+    // Opens the same region twice.
+    requests.push((region_dirs[0].0, open_request(&region_dirs[0].1)));
+    // Opens a region that doesn't exist.
+    let missing_id = RegionId::new(1, 100);
+    requests.push((missing_id, open_request("empty")));
+
+    let progress = std::sync::Mutex::new(Vec::new());
+    let results = engine
+        .open_regions(requests, |p| progress.lock().unwrap().push(p))
+        .await;
+
+    assert_eq!(5, results.len());
+    for (region_id, result) in results {
+        if region_id == missing_id {
+            let err = result.unwrap_err();
+            assert_eq!(StatusCode::RegionNotFound, err.status_code());
+        } else {
+            result.unwrap();
+            assert!(engine.is_region_exists(region_id));
+        }
+    }
+    assert!(!engine.is_region_exists(missing_id));
+
+    let progress = progress.into_inner().unwrap();
+    let finished: Vec<_> = progress.iter().map(|p| (p.finished, p.total)).collect();
+    assert_eq!(vec![(1, 5), (2, 5), (3, 5), (4, 5), (5, 5)], finished);
+}
+
+#[tokio::test]
+async fn test_engine_reject_ddl_while_opening() {
+    let mut env = TestEnv::with_prefix("reject-ddl-while-opening");
+    let engine = env.create_engine(MitoConfig::default()).await;
+
+    let region_id = RegionId::new(1, 1);
+    let request = CreateRequestBuilder::new().build();
+    let region_dir = request.region_dir.clone();
+    engine
+        .handle_request(region_id, RegionRequest::Create(request))
+        .await
+        .unwrap();
+    let engine = env.reopen_engine(engine, MitoConfig::default()).await;
+
+    // The close request is sent after the open request, so the worker handles it
+    // before the region is opened.
+    let (opened, closed) = futures::join!(
+        engine.handle_request(
+            region_id,
+            RegionRequest::Open(RegionOpenRequest {
+                engine: String::new(),
+                region_dir,
+                options: HashMap::default(),
+                skip_wal_replay: false,
+            }),
+        ),
+        engine.handle_request(region_id, RegionRequest::Close(RegionCloseRequest {})),
+    );
+    opened.unwrap();
+    let err = closed.unwrap_err();
+    assert_eq!(StatusCode::RegionNotReady, err.status_code());
+    assert!(engine.is_region_exists(region_id));
+}
+
+#[tokio::test]
+async fn test_engine_reopen_skip_flushed_entries() {
+    let mut env = TestEnv::with_prefix("reopen-skip-flushed");
+    let engine = env.create_engine(MitoConfig::default()).await;
+
+    let region_id = RegionId::new(1, 1);
+    let request = CreateRequestBuilder::new().build();
+    let region_dir = request.region_dir.clone();
+    let column_schemas = rows_schema(&request);
+    engine
+        .handle_request(region_id, RegionRequest::Create(request))
+        .await
+        .unwrap();
+
+    let rows = Rows {
+        schema: column_schemas.clone(),
+        rows: build_rows(0, 3),
+    };
+    put_rows(&engine, region_id, rows).await;
+    flush_region(&engine, region_id, None).await;
+
+    // Reopens the engine so the region replays the WAL.
+    let engine = env.reopen_engine(engine, MitoConfig::default()).await;
+    engine
+        .handle_request(
+            region_id,
+            RegionRequest::Open(RegionOpenRequest {
+                engine: String::new(),
+                region_dir,
+                options: HashMap::default(),
+                skip_wal_replay: false,
+            }),
+        )
+        .await
+        .unwrap();
+
+    // All entries are flushed so we don't replay any of them.
+    let region = engine.get_region(region_id).unwrap();
+    let version_data = region.version_control.current();
+    assert!(version_data.version.memtables.mutable.is_empty());
+    assert_eq!(
+        version_data.version.flushed_entry_id,
+        version_data.last_entry_id
+    );
+
+    let request = ScanRequest::default();
+    let stream = engine.handle_query(region_id, request).await.unwrap();
+    let batches = RecordBatches::try_collect(stream).await.unwrap();
+    let expected = "\
++-------+---------+---------------------+
+| tag_0 | field_0 | ts                  |
++-------+---------+---------------------+
+| 0     | 0.0     | 1970-01-01T00:00:00 |
+| 1     | 1.0     | 1970-01-01T00:00:01 |
+| 2     | 2.0     | 1970-01-01T00:00:02 |
++-------+---------+---------------------+";
+    assert_eq!(expected, batches.pretty_print().unwrap());
+}
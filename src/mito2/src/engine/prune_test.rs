@@ -15,14 +15,16 @@
 use api::v1::Rows;
 use common_query::logical_plan::DfExpr;
 use common_query::prelude::Expr;
-use common_recordbatch::RecordBatches;
+use common_recordbatch::{RecordBatchStream, RecordBatches};
 use datafusion_common::ScalarValue;
 use datafusion_expr::{col, lit};
+use futures::TryStreamExt;
 use store_api::region_engine::RegionEngine;
 use store_api::region_request::RegionRequest;
 use store_api::storage::{RegionId, ScanRequest};
 
 use crate::config::MitoConfig;
+use crate::read::prune_verify::{check_pruning, PruningCheck};
 use crate::test_util::{
     build_rows, flush_region, put_rows, rows_schema, CreateRequestBuilder, TestEnv,
 };
@@ -115,6 +117,26 @@ async fn test_prune_tag() {
     .await;
 }
 
+#[tokio::test]
+async fn test_prune_tag_prefix() {
+    // prune result: row group 0&1, rows without the prefix are also removed.
+    check_prune_row_groups(
+        col("tag_0").like(lit("1%")),
+        "\
++-------+---------+---------------------+
+| tag_0 | field_0 | ts                  |
++-------+---------+---------------------+
+| 1     | 1.0     | 1970-01-01T00:00:01 |
+| 10    | 10.0    | 1970-01-01T00:00:10 |
+| 11    | 11.0    | 1970-01-01T00:00:11 |
+| 12    | 12.0    | 1970-01-01T00:00:12 |
+| 13    | 13.0    | 1970-01-01T00:00:13 |
+| 14    | 14.0    | 1970-01-01T00:00:14 |
++-------+---------+---------------------+",
+    )
+    .await;
+}
+
 #[tokio::test]
 async fn test_prune_tag_and_field() {
     common_telemetry::init_default_ut_logging();
@@ -266,3 +288,145 @@ async fn test_prune_memtable_complex_expr() {
 +-------+---------+---------------------+";
     assert_eq!(expected, batches.pretty_print().unwrap());
 }
+
+#[tokio::test]
+async fn test_scan_metrics() {
+    let mut env = TestEnv::new();
+    let engine = env.create_engine(MitoConfig::default()).await;
+
+    let region_id = RegionId::new(1, 1);
+    let request = CreateRequestBuilder::new().build();
+
+    let column_schemas = rows_schema(&request);
+
+    engine
+        .handle_request(region_id, RegionRequest::Create(request))
+        .await
+        .unwrap();
+
+    put_rows(
+        &engine,
+        region_id,
+        Rows {
+            schema: column_schemas.clone(),
+            rows: build_rows(0, 15),
+        },
+    )
+    .await;
+    flush_region(&engine, region_id, Some(5)).await;
+
+    // Only row group 2 is read.
+    let expr = col("tag_0").gt(lit(ScalarValue::Utf8(Some("4".to_string()))));
+    let mut stream = engine
+        .handle_query(
+            region_id,
+            ScanRequest {
+                filters: vec![Expr::from(expr)],
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+    let mut num_rows = 0;
+    while let Some(batch) = stream.try_next().await.unwrap() {
+        num_rows += batch.num_rows();
+    }
+    assert_eq!(5, num_rows);
+
+    let metrics = stream.metrics().unwrap();
+    let counter = |name: &str| {
+        metrics
+            .counters
+            .iter()
+            .find(|(counter, _)| counter == name)
+            .unwrap()
+            .1
+    };
+    assert_eq!(1, counter("num_files"));
+    assert_eq!(3, counter("num_row_groups"));
+    assert_eq!(2, counter("num_pruned_row_groups"));
+    // SSTs in tests only have one page in each row group.
+    assert_eq!(0, counter("num_pruned_pages"));
+    assert_eq!(5, counter("num_output_rows"));
+}
+
+#[tokio::test]
+async fn test_disable_pruning() {
+    let mut env = TestEnv::new();
+    let engine = env
+        .create_engine(MitoConfig {
+            scan_disable_pruning: true,
+            ..Default::default()
+        })
+        .await;
+
+    let region_id = RegionId::new(1, 1);
+    let request = CreateRequestBuilder::new().build();
+
+    let column_schemas = rows_schema(&request);
+
+    engine
+        .handle_request(region_id, RegionRequest::Create(request))
+        .await
+        .unwrap();
+
+    // 5 ~ 10 in SST
+    put_rows(
+        &engine,
+        region_id,
+        Rows {
+            schema: column_schemas.clone(),
+            rows: build_rows(5, 10),
+        },
+    )
+    .await;
+    flush_region(&engine, region_id, Some(5)).await;
+
+    // 20 ~ 30 in memtable
+    put_rows(
+        &engine,
+        region_id,
+        Rows {
+            schema: column_schemas.clone(),
+            rows: build_rows(20, 30),
+        },
+    )
+    .await;
+
+    // The memtable isn't pruned so the scan returns all rows.
+    let stream = engine
+        .handle_query(
+            region_id,
+            ScanRequest {
+                filters: vec![time_range_expr(0, 20)],
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+    let batches = RecordBatches::try_collect(stream).await.unwrap();
+    let num_rows: usize = batches.iter().map(|batch| batch.num_rows()).sum();
+    assert_eq!(15, num_rows);
+
+    // Rows matching filters are the same with and without pruning.
+    let region = engine.get_region(region_id).unwrap();
+    let check = check_pruning(
+        region.version(),
+        region.access_layer.clone(),
+        vec![
+            time_range_expr(0, 20),
+            Expr::from(col("tag_0").lt(lit("8"))),
+        ],
+        None,
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        PruningCheck {
+            pruned_rows: 3,
+            unpruned_rows: 3,
+        },
+        check
+    );
+    assert!(check.is_consistent());
+}
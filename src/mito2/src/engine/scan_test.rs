@@ -0,0 +1,365 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Tests for scanning regions in specific orders or with limits.
+
+use api::v1::value::ValueData;
+use api::v1::{Row, Rows, Value};
+use common_query::prelude::Expr;
+use common_recordbatch::{OrderOption, RecordBatchStream, RecordBatches};
+use datafusion_expr::{col, lit};
+use datatypes::arrow::compute::SortOptions;
+use store_api::region_engine::RegionEngine;
+use store_api::region_request::RegionRequest;
+use store_api::storage::{PrimaryKeyPrefix, RegionId, ScanRequest};
+
+use crate::config::MitoConfig;
+use crate::read::scan_region::Scanner;
+use crate::test_util::{
+    build_rows, flush_region, put_rows, rows_schema, CreateRequestBuilder, TestEnv,
+};
+
+/// Builds rows for a region without tags.
+fn build_rows_without_tags(start: usize, end: usize, value_start: usize) -> Vec<Row> {
+    (start..end)
+        .enumerate()
+        .map(|(idx, ts)| Row {
+            values: vec![
+                Value {
+                    value_data: Some(ValueData::F64Value((value_start + idx) as f64)),
+                },
+                Value {
+                    value_data: Some(ValueData::TimestampMillisecondValue(ts as i64 * 1000)),
+                },
+            ],
+        })
+        .collect()
+}
+
+fn ts_desc_request() -> ScanRequest {
+    ScanRequest {
+        output_ordering: Some(vec![OrderOption {
+            name: "ts".to_string(),
+            options: SortOptions {
+                descending: true,
+                nulls_first: true,
+            },
+        }]),
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn test_reverse_scan() {
+    for parallelism in [1, 2] {
+        let mut env = TestEnv::new();
+        let engine = env
+            .create_engine(MitoConfig {
+                scan_parallelism: parallelism,
+                ..Default::default()
+            })
+            .await;
+
+        let region_id = RegionId::new(1, 1);
+        let request = CreateRequestBuilder::new().tag_num(0).field_num(1).build();
+        let column_schemas = rows_schema(&request);
+        engine
+            .handle_request(region_id, RegionRequest::Create(request))
+            .await
+            .unwrap();
+
+        let rows = Rows {
+            schema: column_schemas.clone(),
+            rows: build_rows_without_tags(0, 3, 0),
+        };
+        put_rows(&engine, region_id, rows).await;
+        flush_region(&engine, region_id, None).await;
+        // Overwrites ts 2 in the memtable.
+        let rows = Rows {
+            schema: column_schemas,
+            rows: build_rows_without_tags(2, 5, 10),
+        };
+        put_rows(&engine, region_id, rows).await;
+
+        let stream = engine
+            .handle_query(region_id, ts_desc_request())
+            .await
+            .unwrap();
+        assert_eq!(
+            "ts",
+            stream.output_ordering().unwrap()[0].name,
+            "parallelism: {parallelism}"
+        );
+        let batches = RecordBatches::try_collect(stream).await.unwrap();
+        let expected = "\
++---------+---------------------+
+| field_0 | ts                  |
++---------+---------------------+
+| 12.0    | 1970-01-01T00:00:04 |
+| 11.0    | 1970-01-01T00:00:03 |
+| 10.0    | 1970-01-01T00:00:02 |
+| 1.0     | 1970-01-01T00:00:01 |
+| 0.0     | 1970-01-01T00:00:00 |
++---------+---------------------+";
+        assert_eq!(
+            expected,
+            batches.pretty_print().unwrap(),
+            "parallelism: {parallelism}"
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_reverse_scan_with_primary_key() {
+    let mut env = TestEnv::new();
+    let engine = env.create_engine(MitoConfig::default()).await;
+
+    let region_id = RegionId::new(1, 1);
+    let request = CreateRequestBuilder::new().build();
+    engine
+        .handle_request(region_id, RegionRequest::Create(request))
+        .await
+        .unwrap();
+
+    // We don't reverse the scan if the region has primary key.
+    let stream = engine
+        .handle_query(region_id, ts_desc_request())
+        .await
+        .unwrap();
+    assert!(stream.output_ordering().is_none());
+}
+
+#[tokio::test]
+async fn test_scan_with_limit() {
+    for parallelism in [1, 2] {
+        let mut env = TestEnv::new();
+        let engine = env
+            .create_engine(MitoConfig {
+                scan_parallelism: parallelism,
+                ..Default::default()
+            })
+            .await;
+
+        let region_id = RegionId::new(1, 1);
+        let request = CreateRequestBuilder::new().tag_num(0).field_num(1).build();
+        let column_schemas = rows_schema(&request);
+        engine
+            .handle_request(region_id, RegionRequest::Create(request))
+            .await
+            .unwrap();
+
+        let rows = Rows {
+            schema: column_schemas.clone(),
+            rows: build_rows_without_tags(0, 3, 0),
+        };
+        put_rows(&engine, region_id, rows).await;
+        flush_region(&engine, region_id, None).await;
+        let rows = Rows {
+            schema: column_schemas,
+            rows: build_rows_without_tags(3, 6, 3),
+        };
+        put_rows(&engine, region_id, rows).await;
+
+        let request = ScanRequest {
+            limit: Some(4),
+            ..Default::default()
+        };
+        let stream = engine.handle_query(region_id, request).await.unwrap();
+        let batches = RecordBatches::try_collect(stream).await.unwrap();
+        let expected = "\
+| 0.0     | 1970-01-01T00:00:00 |
+| 1.0     | 1970-01-01T00:00:01 |
+| 2.0     | 1970-01-01T00:00:02 |
+| 3.0     | 1970-01-01T00:00:03 |
++---------+---------------------+";
+        assert_eq!(
+            expected,
+            batches.pretty_print().unwrap(),
+            "parallelism: {parallelism}"
+        );
+
+        let request = ScanRequest {
+            limit: Some(2),
+            ..ts_desc_request()
+        };
+        let stream = engine.handle_query(region_id, request).await.unwrap();
+        let batches = RecordBatches::try_collect(stream).await.unwrap();
+        let expected = "\
++---------+---------------------+
+| field_0 | ts                  |
++---------+---------------------+
+| 5.0     | 1970-01-01T00:00:05 |
+| 4.0     | 1970-01-01T00:00:04 |
++---------+---------------------+";
+        assert_eq!(
+            expected,
+            batches.pretty_print().unwrap(),
+            "parallelism: {parallelism}"
+        );
+
+        // Doesn't push down the limit if the request has filters.
+        let request = ScanRequest {
+            filters: vec![Expr::from(col("field_0").gt_eq(lit(0.0)))],
+            limit: Some(2),
+            ..Default::default()
+        };
+        let stream = engine.handle_query(region_id, request).await.unwrap();
+        let batches = RecordBatches::try_collect(stream).await.unwrap();
+        assert_eq!(6, batches.iter().map(|b| b.num_rows()).sum::<usize>());
+    }
+}
+
+#[tokio::test]
+async fn test_unordered_scan_append_mode() {
+    let mut env = TestEnv::new();
+    let engine = env
+        .create_engine(MitoConfig {
+            scan_parallelism: 2,
+            ..Default::default()
+        })
+        .await;
+
+    let region_id = RegionId::new(1, 1);
+    let request = CreateRequestBuilder::new()
+        .tag_num(0)
+        .field_num(1)
+        .insert_option("append_mode", "true")
+        .build();
+    let column_schemas = rows_schema(&request);
+    engine
+        .handle_request(region_id, RegionRequest::Create(request))
+        .await
+        .unwrap();
+
+    let rows = Rows {
+        schema: column_schemas.clone(),
+        rows: build_rows_without_tags(0, 3, 0),
+    };
+    put_rows(&engine, region_id, rows).await;
+    flush_region(&engine, region_id, None).await;
+    // Writes rows with the same timestamps again.
+    let rows = Rows {
+        schema: column_schemas.clone(),
+        rows: build_rows_without_tags(1, 4, 10),
+    };
+    put_rows(&engine, region_id, rows).await;
+    // Writes a row with the same timestamp to the memtable.
+    let rows = Rows {
+        schema: column_schemas,
+        rows: build_rows_without_tags(1, 2, 20),
+    };
+    put_rows(&engine, region_id, rows).await;
+
+    let scanner = engine.scanner(region_id, ScanRequest::default()).unwrap();
+    assert!(matches!(scanner, Scanner::Unordered(_)));
+    let stream = scanner.scan().await.unwrap();
+    let unordered = RecordBatches::try_collect(stream).await.unwrap();
+    // Rows in the memtable and the SST are not deduplicated.
+    assert_eq!(7, unordered.iter().map(|b| b.num_rows()).sum::<usize>());
+
+    // Still uses the sequential scan if the request requires ordered output.
+    let scanner = engine.scanner(region_id, ts_desc_request()).unwrap();
+    assert!(matches!(scanner, Scanner::Seq(_)));
+    let stream = scanner.scan().await.unwrap();
+    let ordered = RecordBatches::try_collect(stream).await.unwrap();
+    // The sequential scan doesn't deduplicate rows of an append-only region either.
+    assert_eq!(sorted_lines(&unordered), sorted_lines(&ordered));
+    let expected = "\
+| 0.0     | 1970-01-01T00:00:00 |
+| 1.0     | 1970-01-01T00:00:01 |
+| 10.0    | 1970-01-01T00:00:01 |
+| 11.0    | 1970-01-01T00:00:02 |
+| 12.0    | 1970-01-01T00:00:03 |
+| 2.0     | 1970-01-01T00:00:02 |
+| 20.0    | 1970-01-01T00:00:01 |";
+    assert_eq!(expected, sorted_lines(&ordered).join("\n"));
+}
+
+/// Returns rows of the pretty printed `batches` in sorted order.
+fn sorted_lines(batches: &RecordBatches) -> Vec<String> {
+    let mut lines: Vec<_> = batches
+        .pretty_print()
+        .unwrap()
+        .lines()
+        // Skips the header.
+        .skip(3)
+        .filter(|line| !line.starts_with('+'))
+        .map(|line| line.to_string())
+        .collect();
+    lines.sort_unstable();
+    lines
+}
+
+#[tokio::test]
+async fn test_scan_with_pk_prefix() {
+    let mut env = TestEnv::new();
+    let engine = env.create_engine(MitoConfig::default()).await;
+
+    let region_id = RegionId::new(1, 1);
+    let request = CreateRequestBuilder::new().build();
+    let column_schemas = rows_schema(&request);
+    engine
+        .handle_request(region_id, RegionRequest::Create(request))
+        .await
+        .unwrap();
+
+    let rows = Rows {
+        schema: column_schemas.clone(),
+        rows: build_rows(0, 15),
+    };
+    put_rows(&engine, region_id, rows).await;
+    flush_region(&engine, region_id, None).await;
+    let rows = Rows {
+        schema: column_schemas,
+        rows: build_rows(15, 25),
+    };
+    put_rows(&engine, region_id, rows).await;
+
+    let request = ScanRequest {
+        pk_prefix: Some(PrimaryKeyPrefix {
+            values: Vec::new(),
+            string_prefix: Some("1".to_string()),
+        }),
+        ..Default::default()
+    };
+    let stream = engine.handle_query(region_id, request).await.unwrap();
+    let batches = RecordBatches::try_collect(stream).await.unwrap();
+    let expected = "\
++-------+---------+---------------------+
+| tag_0 | field_0 | ts                  |
++-------+---------+---------------------+
+| 1     | 1.0     | 1970-01-01T00:00:01 |
+| 10    | 10.0    | 1970-01-01T00:00:10 |
+| 11    | 11.0    | 1970-01-01T00:00:11 |
+| 12    | 12.0    | 1970-01-01T00:00:12 |
+| 13    | 13.0    | 1970-01-01T00:00:13 |
+| 14    | 14.0    | 1970-01-01T00:00:14 |
+| 15    | 15.0    | 1970-01-01T00:00:15 |
+| 16    | 16.0    | 1970-01-01T00:00:16 |
+| 17    | 17.0    | 1970-01-01T00:00:17 |
+| 18    | 18.0    | 1970-01-01T00:00:18 |
+| 19    | 19.0    | 1970-01-01T00:00:19 |
++-------+---------+---------------------+";
+    assert_eq!(expected, batches.pretty_print().unwrap());
+
+    // The prefix has more columns than the primary key.
+    let request = ScanRequest {
+        pk_prefix: Some(PrimaryKeyPrefix {
+            values: vec![datatypes::value::Value::from("1")],
+            string_prefix: Some("1".to_string()),
+        }),
+        ..Default::default()
+    };
+    assert!(engine.handle_query(region_id, request).await.is_err());
+}
@@ -0,0 +1,67 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use api::v1::Rows;
+use store_api::region_engine::RegionEngine;
+use store_api::region_request::RegionRequest;
+use store_api::storage::{RegionId, ScanRequest};
+
+use crate::config::MitoConfig;
+use crate::region::verify::VerifyIssue;
+use crate::test_util::{
+    build_rows, flush_region, put_rows, rows_schema, CreateRequestBuilder, TestEnv,
+};
+
+#[tokio::test]
+async fn test_engine_verify_region() {
+    let mut env = TestEnv::with_prefix("verify-region");
+    let engine = env.create_engine(MitoConfig::default()).await;
+
+    let region_id = RegionId::new(1, 1);
+    let request = CreateRequestBuilder::new().build();
+    let column_schemas = rows_schema(&request);
+    engine
+        .handle_request(region_id, RegionRequest::Create(request))
+        .await
+        .unwrap();
+
+    let rows = Rows {
+        schema: column_schemas.clone(),
+        rows: build_rows(0, 5),
+    };
+    put_rows(&engine, region_id, rows).await;
+    flush_region(&engine, region_id, None).await;
+    let rows = Rows {
+        schema: column_schemas,
+        rows: build_rows(3, 8),
+    };
+    put_rows(&engine, region_id, rows).await;
+
+    let report = engine.verify_region(region_id).await.unwrap();
+    assert!(report.is_healthy(), "{report}");
+    assert_eq!(1, report.num_memtables);
+    assert_eq!(1, report.num_files);
+    assert_eq!(10, report.num_rows);
+
+    // Removes the SST file.
+    let scanner = engine.scanner(region_id, ScanRequest::default()).unwrap();
+    let file_id = scanner.file_ids()[0];
+    let region = engine.get_region(region_id).unwrap();
+    region.access_layer.delete_sst(file_id).await.unwrap();
+
+    let report = engine.verify_region(region_id).await.unwrap();
+    assert!(!report.is_healthy());
+    assert_eq!(vec![VerifyIssue::FileNotFound { file_id }], report.issues);
+    assert_eq!(5, report.num_rows);
+}
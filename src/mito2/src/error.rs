@@ -15,6 +15,7 @@
 use std::any::Any;
 use std::sync::Arc;
 
+use common_config::wal::WalOptions;
 use common_datasource::compression::CompressionType;
 use common_error::ext::{BoxedError, ErrorExt};
 use common_error::status_code::StatusCode;
@@ -53,6 +54,14 @@ pub enum Error {
         error: object_store::Error,
     },
 
+    #[snafu(display("Failed to access the commit journal, path: {}", path))]
+    CommitJournal {
+        path: String,
+        location: Location,
+        #[snafu(source)]
+        error: std::io::Error,
+    },
+
     #[snafu(display("Fail to compress object by {}, path: {}", compress_type, path))]
     CompressObject {
         compress_type: CompressionType,
@@ -216,14 +225,55 @@ pub enum Error {
         source: BoxedError,
     },
 
-    #[snafu(display("Failed to decode WAL entry, region_id: {}", region_id))]
+    #[snafu(display(
+        "WAL entry is corrupted in the log store, region_id: {}, entry_id: {}",
+        region_id,
+        entry_id
+    ))]
+    ReadCorruptedWal {
+        region_id: RegionId,
+        entry_id: u64,
+        location: Location,
+        source: BoxedError,
+    },
+
+    #[snafu(display(
+        "Failed to decode WAL entry, region_id: {}, entry_id: {}",
+        region_id,
+        entry_id
+    ))]
     DecodeWal {
         region_id: RegionId,
+        entry_id: u64,
         location: Location,
         #[snafu(source)]
         error: DecodeError,
     },
 
+    #[snafu(display(
+        "WAL entry is corrupted, region_id: {}, entry_id: {}, reason: {}",
+        region_id,
+        entry_id,
+        reason
+    ))]
+    CorruptedWalEntry {
+        region_id: RegionId,
+        entry_id: u64,
+        reason: String,
+        location: Location,
+    },
+
+    #[snafu(display(
+        "WAL options {:?} of region {} are not supported by the log store",
+        wal_options,
+        region_id
+    ))]
+    UnsupportedWalOptions {
+        region_id: RegionId,
+        wal_options: WalOptions,
+        location: Location,
+    },
+
     #[snafu(display("Failed to delete WAL, region_id: {}", region_id))]
     DeleteWal {
         region_id: RegionId,
@@ -321,6 +371,13 @@ pub enum Error {
         location: Location,
     },
 
+    #[snafu(display("Failed to open region {}", region_id))]
+    OpenRegion {
+        region_id: RegionId,
+        source: Arc<Error>,
+        location: Location,
+    },
+
     #[snafu(display("Failed to flush region {}", region_id))]
     FlushRegion {
         region_id: RegionId,
@@ -409,6 +466,51 @@ pub enum Error {
         location: Location,
     },
 
+    #[snafu(display("Invalid file {} to ingest, reason: {}", path, reason))]
+    InvalidIngestFile {
+        path: String,
+        reason: String,
+        location: Location,
+    },
+
+    #[snafu(display(
+        "Column {} in region {} contains null values, samples: {}",
+        column,
+        region_id,
+        samples
+    ))]
+    ColumnContainsNull {
+        region_id: RegionId,
+        column: String,
+        samples: String,
+        location: Location,
+    },
+
+    #[snafu(display("Region {} is opening", region_id))]
+    RegionOpening {
+        region_id: RegionId,
+        location: Location,
+    },
+
+    #[snafu(display("Region {} is modified while validating data", region_id))]
+    ValidationConflict {
+        region_id: RegionId,
+        location: Location,
+    },
+
+    #[snafu(display("Failed to read record batch"))]
+    ReadRecordBatch {
+        source: common_recordbatch::error::Error,
+        location: Location,
+    },
+
+    #[snafu(display("Failed to evaluate filters on scanned rows"))]
+    EvaluateFilter {
+        #[snafu(source)]
+        error: datafusion_common::DataFusionError,
+        location: Location,
+    },
+
     #[snafu(display("Failed to read arrow record batch from parquet file {}", path))]
     ArrowReader {
         path: String,
@@ -426,6 +528,16 @@ impl Error {
         matches!(self, Error::FillDefault { .. })
     }
 
+    /// Returns the id of the WAL entry if the error is caused by a corrupted entry.
+    pub(crate) fn corrupted_wal_entry_id(&self) -> Option<u64> {
+        match self {
+            Error::ReadCorruptedWal { entry_id, .. }
+            | Error::DecodeWal { entry_id, .. }
+            | Error::CorruptedWalEntry { entry_id, .. } => Some(*entry_id),
+            _ => None,
+        }
+    }
+
     /// Returns true if the file is not found on the object store.
     pub(crate) fn is_object_not_found(&self) -> bool {
         match self {
@@ -444,7 +556,8 @@ impl ErrorExt for Error {
             | ReadParquet { .. }
             | WriteWal { .. }
             | ReadWal { .. }
-            | DeleteWal { .. } => StatusCode::StorageUnavailable,
+            | DeleteWal { .. }
+            | CommitJournal { .. } => StatusCode::StorageUnavailable,
             CompressObject { .. }
             | DecompressObject { .. }
             | SerdeJson { .. }
@@ -467,7 +580,9 @@ impl ErrorExt for Error {
             | WorkerStopped { .. }
             | Recv { .. }
             | EncodeWal { .. }
-            | DecodeWal { .. } => StatusCode::Internal,
+            | ReadCorruptedWal { .. }
+            | DecodeWal { .. }
+            | CorruptedWalEntry { .. } => StatusCode::Internal,
             WriteBuffer { source, .. } => source.status_code(),
             WriteGroup { source, .. } => source.status_code(),
             FieldTypeMismatch { source, .. } => source.status_code(),
@@ -484,18 +599,23 @@ impl ErrorExt for Error {
             InvalidSchedulerState { .. } => StatusCode::InvalidArguments,
             StopScheduler { .. } => StatusCode::Internal,
             DeleteSst { .. } => StatusCode::StorageUnavailable,
-            FlushRegion { source, .. } => source.status_code(),
+            OpenRegion { source, .. } | FlushRegion { source, .. } => source.status_code(),
             RegionDropped { .. } => StatusCode::Cancelled,
             RegionClosed { .. } => StatusCode::Cancelled,
             RegionTruncated { .. } => StatusCode::Cancelled,
-            RejectWrite { .. } => StatusCode::StorageUnavailable,
+            RejectWrite { .. } => StatusCode::RuntimeResourcesExhausted,
             CompactRegion { source, .. } => source.status_code(),
-            CompatReader { .. } => StatusCode::Unexpected,
+            CompatReader { .. } | EvaluateFilter { .. } => StatusCode::Unexpected,
             InvalidRegionRequest { source, .. } => source.status_code(),
             RegionReadonly { .. } => StatusCode::RegionReadonly,
-            JsonOptions { .. } => StatusCode::InvalidArguments,
+            JsonOptions { .. } | UnsupportedWalOptions { .. } => StatusCode::InvalidArguments,
             EmptyRegionDir { .. } | EmptyManifestDir { .. } => StatusCode::RegionNotFound,
             ArrowReader { .. } => StatusCode::StorageUnavailable,
+            InvalidIngestFile { .. } => StatusCode::InvalidArguments,
+            ColumnContainsNull { .. } => StatusCode::InvalidArguments,
+            RegionOpening { .. } => StatusCode::RegionNotReady,
+            ValidationConflict { .. } => StatusCode::RegionBusy,
+            ReadRecordBatch { source, .. } => source.status_code(),
         }
     }
 
@@ -174,6 +174,8 @@ pub enum FlushReason {
     Manual,
     /// Flush to alter table.
     Alter,
+    /// Flush to ingest files.
+    Ingest,
 }
 
 impl FlushReason {
@@ -325,6 +327,7 @@ impl RegionFlushTask {
                 time_range: sst_info.time_range,
                 level: 0,
                 file_size: sst_info.file_size,
+                sequence: None,
             });
         }
 
@@ -0,0 +1,266 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Utilities to ingest external SST files into a region.
+//!
+//! Users first put parquet files in the SST format into the staging directory
+//! of a region (See [AccessLayer::staging_dir()]) and then ask the engine to
+//! ingest them. The engine validates these files, copies them into the region
+//! and adds them to the manifest in one edit.
+
+use api::v1::SemanticType;
+use common_time::Timestamp;
+use parquet::arrow::async_reader::AsyncFileReader;
+use parquet::file::metadata::ParquetMetaData;
+use parquet::file::statistics::Statistics;
+use snafu::{ensure, OptionExt, ResultExt};
+use store_api::metadata::RegionMetadata;
+use tokio::io::BufReader;
+
+use crate::access_layer::AccessLayer;
+use crate::error::{InvalidIngestFileSnafu, OpenDalSnafu, ReadParquetSnafu, Result};
+use crate::sst::file::FileTimeRange;
+use crate::sst::parquet::reader::ParquetReaderBuilder;
+
+/// A staged file that is valid to ingest.
+#[derive(Debug)]
+pub(crate) struct StagedSst {
+    /// Name of the file under the staging directory.
+    pub(crate) file_name: String,
+    /// Time range of the file.
+    pub(crate) time_range: FileTimeRange,
+    /// Number of rows in the file.
+    pub(crate) num_rows: usize,
+}
+
+/// Loads the staged file `file_name` and validates it against the `expected` metadata.
+pub(crate) async fn load_staged_sst(
+    access_layer: &AccessLayer,
+    file_name: &str,
+    expected: &RegionMetadata,
+) -> Result<StagedSst> {
+    let path = access_layer.staged_file_path(file_name);
+    let reader = access_layer
+        .object_store()
+        .reader(&path)
+        .await
+        .context(OpenDalSnafu)?;
+    let mut reader = BufReader::new(reader);
+    let parquet_meta = reader
+        .get_metadata()
+        .await
+        .context(ReadParquetSnafu { path: &path })?;
+
+    let key_value_meta = parquet_meta.file_metadata().key_value_metadata();
+    let file_meta = ParquetReaderBuilder::get_region_metadata(&path, key_value_meta)?;
+    validate_file_metadata(&path, &file_meta, expected)?;
+
+    let num_rows = parquet_meta.file_metadata().num_rows() as usize;
+    ensure!(
+        num_rows > 0,
+        InvalidIngestFileSnafu {
+            path: &path,
+            reason: "file is empty",
+        }
+    );
+    let time_range = file_time_range(&path, &parquet_meta, &file_meta)?;
+
+    Ok(StagedSst {
+        file_name: file_name.to_string(),
+        time_range,
+        num_rows,
+    })
+}
+
+/// Checks whether the SST written with `file_meta` can be read by a region with `expected` metadata.
+///
+/// The time index must be the same. The primary key of the file must be a prefix of
+/// the region's primary key so readers can fill defaults for tags added later.
+fn validate_file_metadata(
+    path: &str,
+    file_meta: &RegionMetadata,
+    expected: &RegionMetadata,
+) -> Result<()> {
+    let file_ts = file_meta.time_index_column();
+    let expected_ts = expected.time_index_column();
+    ensure!(
+        file_ts.column_id == expected_ts.column_id
+            && file_ts.column_schema.data_type == expected_ts.column_schema.data_type,
+        InvalidIngestFileSnafu {
+            path,
+            reason: format!(
+                "time index {} mismatches time index {} of region {}",
+                file_ts.column_schema.name, expected_ts.column_schema.name, expected.region_id
+            ),
+        }
+    );
+    ensure!(
+        expected.primary_key.starts_with(&file_meta.primary_key),
+        InvalidIngestFileSnafu {
+            path,
+            reason: format!(
+                "primary key {:?} is not a prefix of primary key {:?} of region {}",
+                file_meta.primary_key, expected.primary_key, expected.region_id
+            ),
+        }
+    );
+
+    for column in &file_meta.column_metadatas {
+        let Some(expected_column) = expected.column_by_id(column.column_id) else {
+            // Fields dropped by the region are ignored while reading.
+            ensure!(
+                column.semantic_type == SemanticType::Field,
+                InvalidIngestFileSnafu {
+                    path,
+                    reason: format!(
+                        "column {} not found in region {}",
+                        column.column_schema.name, expected.region_id
+                    ),
+                }
+            );
+            continue;
+        };
+        ensure!(
+            expected_column.semantic_type == column.semantic_type
+                && expected_column.column_schema.data_type == column.column_schema.data_type,
+            InvalidIngestFileSnafu {
+                path,
+                reason: format!(
+                    "column {} has type {:?} {:?}, but region {} expects {:?} {:?}",
+                    column.column_schema.name,
+                    column.semantic_type,
+                    column.column_schema.data_type,
+                    expected.region_id,
+                    expected_column.semantic_type,
+                    expected_column.column_schema.data_type,
+                ),
+            }
+        );
+    }
+
+    Ok(())
+}
+
+/// Computes the time range of the file from statistics of the time index column.
+fn file_time_range(
+    path: &str,
+    parquet_meta: &ParquetMetaData,
+    file_meta: &RegionMetadata,
+) -> Result<FileTimeRange> {
+    let time_index = file_meta.time_index_column();
+    let unit = time_index
+        .column_schema
+        .data_type
+        .as_timestamp()
+        .expect("Time index must have timestamp-compatible type")
+        .unit();
+    let column_index = parquet_meta
+        .file_metadata()
+        .schema_descr()
+        .columns()
+        .iter()
+        .position(|column| column.name() == time_index.column_schema.name)
+        .with_context(|| InvalidIngestFileSnafu {
+            path,
+            reason: format!("time index {} not found", time_index.column_schema.name),
+        })?;
+
+    let mut range: Option<(i64, i64)> = None;
+    for row_group in parquet_meta.row_groups() {
+        let (min, max) = match row_group.column(column_index).statistics() {
+            Some(Statistics::Int64(stats)) if stats.has_min_max_set() => {
+                (*stats.min(), *stats.max())
+            }
+            _ => {
+                return InvalidIngestFileSnafu {
+                    path,
+                    reason: "missing statistics of the time index",
+                }
+                .fail();
+            }
+        };
+        range = Some(match range {
+            Some((lower, upper)) => (lower.min(min), upper.max(max)),
+            None => (min, max),
+        });
+    }
+    let (min, max) = range.with_context(|| InvalidIngestFileSnafu {
+        path,
+        reason: "file has no row group",
+    })?;
+
+    Ok((Timestamp::new(min, unit), Timestamp::new(max, unit)))
+}
+
+#[cfg(test)]
+mod tests {
+    use datatypes::prelude::ConcreteDataType;
+    use datatypes::schema::ColumnSchema;
+    use store_api::metadata::{ColumnMetadata, RegionMetadataBuilder};
+    use store_api::storage::RegionId;
+
+    use super::*;
+    use crate::test_util::meta_util::TestRegionMetadataBuilder;
+
+    #[test]
+    fn test_validate_same_metadata() {
+        let metadata = TestRegionMetadataBuilder::default().build();
+        validate_file_metadata("test", &metadata, &metadata).unwrap();
+
+        // Files from other regions with the same schema are allowed.
+        let mut file_meta = metadata.clone();
+        file_meta.region_id = RegionId::new(1024, 1);
+        validate_file_metadata("test", &file_meta, &metadata).unwrap();
+    }
+
+    #[test]
+    fn test_validate_primary_key() {
+        let expected = TestRegionMetadataBuilder::default().build();
+        let mut builder = RegionMetadataBuilder::from_existing(expected.clone());
+        builder.push_column_metadata(ColumnMetadata {
+            column_schema: ColumnSchema::new("k1", ConcreteDataType::string_datatype(), true),
+            semantic_type: SemanticType::Tag,
+            column_id: 100,
+        });
+        let mut primary_key = expected.primary_key.clone();
+        primary_key.push(100);
+        builder.primary_key(primary_key);
+        let more_tags = builder.build().unwrap();
+
+        // The file has more tags than the region.
+        validate_file_metadata("test", &more_tags, &expected).unwrap_err();
+        // The region has more tags than the file.
+        validate_file_metadata("test", &expected, &more_tags).unwrap();
+    }
+
+    #[test]
+    fn test_validate_column_type() {
+        let expected = TestRegionMetadataBuilder::default().build();
+        let file_meta = TestRegionMetadataBuilder::default()
+            .ts_name("other_ts")
+            .build();
+        // Columns are matched by id so the name of the time index doesn't matter.
+        validate_file_metadata("test", &file_meta, &expected).unwrap();
+
+        let file_meta = TestRegionMetadataBuilder::default().num_fields(2).build();
+        // The field `v1` is not in the region.
+        validate_file_metadata("test", &file_meta, &expected).unwrap();
+        // The region has a field `v1` that the file doesn't have.
+        validate_file_metadata("test", &expected, &file_meta).unwrap();
+
+        let file_meta = TestRegionMetadataBuilder::default().num_tags(0).build();
+        // The column `v0` in the file is the tag `k0` of the region.
+        validate_file_metadata("test", &file_meta, &expected).unwrap_err();
+    }
+}
@@ -30,9 +30,11 @@ pub mod config;
 pub mod engine;
 pub mod error;
 pub mod flush;
+mod ingest;
 pub mod manifest;
 pub mod memtable;
 mod metrics;
+pub mod purge_job;
 pub mod read;
 pub mod region;
 mod region_write_ctx;
@@ -40,6 +42,7 @@ pub mod request;
 mod row_converter;
 pub(crate) mod schedule;
 pub mod sst;
+mod validate;
 pub mod wal;
 mod worker;
 
@@ -151,6 +154,7 @@ mod worker;
 ///     +Option&lt;Timestamp, Timestamp&gt; time_range
 ///     +Level level
 ///     +u64 file_size
+///     +Option&lt;SequenceNumber&gt; sequence
 /// }
 /// VersionControl o-- Version
 /// Version o-- RegionMetadata
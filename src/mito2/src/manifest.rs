@@ -15,6 +15,7 @@
 //! manifest storage
 
 pub mod action;
+pub(crate) mod journal;
 pub mod manager;
 pub mod storage;
 #[cfg(test)]
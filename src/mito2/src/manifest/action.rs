@@ -14,7 +14,7 @@
 
 //! Defines [RegionMetaAction] related structs and [RegionCheckpoint].
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::time::Duration;
 
 use serde::{Deserialize, Serialize};
@@ -24,9 +24,13 @@ use store_api::metadata::RegionMetadataRef;
 use store_api::storage::{RegionId, SequenceNumber};
 
 use crate::error::{RegionMetadataNotFoundSnafu, Result, SerdeJsonSnafu, Utf8Snafu};
+use crate::read::range_delete::RangeTombstone;
 use crate::sst::file::{FileId, FileMeta};
 use crate::wal::EntryId;
 
+/// Max number of previous metadata versions kept in the manifest.
+pub(crate) const MAX_METADATA_HISTORY: usize = 16;
+
 /// Actions that can be applied to region manifest.
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
 pub enum RegionMetaAction {
@@ -38,6 +42,12 @@ pub enum RegionMetaAction {
     Remove(RegionRemove),
     /// Truncate the region.
     Truncate(RegionTruncate),
+    /// Edit engine-private key-values of the region.
+    Kv(RegionKvEdit),
+    /// Delete rows in a time range of the region.
+    DeleteRange(RangeTombstone),
+    /// Remove range tombstones whose deleted rows are removed from SSTs.
+    RemoveRangeTombstones(Vec<RangeTombstone>),
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
@@ -71,6 +81,15 @@ pub struct RegionTruncate {
     pub truncated_sequence: SequenceNumber,
 }
 
+/// Edits engine-private key-values of the region.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct RegionKvEdit {
+    /// Key-values to put. Values are encoded in JSON.
+    pub puts: BTreeMap<String, String>,
+    /// Keys to delete.
+    pub deletes: Vec<String>,
+}
+
 /// The region manifest data.
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
 pub struct RegionManifest {
@@ -89,17 +108,31 @@ pub struct RegionManifest {
     /// Inferred compaction time window.
     #[serde(with = "humantime_serde")]
     pub compaction_time_window: Option<Duration>,
+    /// Previous metadata of the region, ordered by schema version.
+    ///
+    /// At most [MAX_METADATA_HISTORY] versions are kept.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub metadata_history: Vec<RegionMetadataRef>,
+    /// Engine-private key-values of the region. Values are encoded in JSON.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub kv: BTreeMap<String, String>,
+    /// Tombstones of deleted time ranges, ordered by sequence.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub range_tombstones: Vec<RangeTombstone>,
 }
 
 #[derive(Debug, Default)]
 pub struct RegionManifestBuilder {
     metadata: Option<RegionMetadataRef>,
+    metadata_history: Vec<RegionMetadataRef>,
     files: HashMap<FileId, FileMeta>,
     flushed_entry_id: EntryId,
     flushed_sequence: SequenceNumber,
     manifest_version: ManifestVersion,
     truncated_entry_id: Option<EntryId>,
     compaction_time_window: Option<Duration>,
+    kv: BTreeMap<String, String>,
+    range_tombstones: Vec<RangeTombstone>,
 }
 
 impl RegionManifestBuilder {
@@ -114,6 +147,9 @@ impl RegionManifestBuilder {
                 flushed_sequence: s.flushed_sequence,
                 truncated_entry_id: s.truncated_entry_id,
                 compaction_time_window: s.compaction_time_window,
+                metadata_history: s.metadata_history,
+                kv: s.kv,
+                range_tombstones: s.range_tombstones,
             }
         } else {
             Default::default()
@@ -121,6 +157,15 @@ impl RegionManifestBuilder {
     }
 
     pub fn apply_change(&mut self, manifest_version: ManifestVersion, change: RegionChange) {
+        if let Some(old) = self.metadata.take() {
+            // Only keeps metadata of different schema versions.
+            if old.schema_version != change.metadata.schema_version {
+                self.metadata_history.push(old);
+                if self.metadata_history.len() > MAX_METADATA_HISTORY {
+                    self.metadata_history.remove(0);
+                }
+            }
+        }
         self.metadata = Some(change.metadata);
         self.manifest_version = manifest_version;
     }
@@ -150,6 +195,35 @@ impl RegionManifestBuilder {
         self.flushed_sequence = truncate.truncated_sequence;
         self.truncated_entry_id = Some(truncate.truncated_entry_id);
         self.files.clear();
+        // All data deleted by tombstones are truncated.
+        self.range_tombstones.clear();
+    }
+
+    pub fn apply_delete_range(
+        &mut self,
+        manifest_version: ManifestVersion,
+        tombstone: RangeTombstone,
+    ) {
+        self.manifest_version = manifest_version;
+        self.range_tombstones.push(tombstone);
+    }
+
+    pub fn apply_remove_range_tombstones(
+        &mut self,
+        manifest_version: ManifestVersion,
+        tombstones: Vec<RangeTombstone>,
+    ) {
+        self.manifest_version = manifest_version;
+        self.range_tombstones
+            .retain(|tombstone| !tombstones.contains(tombstone));
+    }
+
+    pub fn apply_kv(&mut self, manifest_version: ManifestVersion, edit: RegionKvEdit) {
+        self.manifest_version = manifest_version;
+        for key in edit.deletes {
+            self.kv.remove(&key);
+        }
+        self.kv.extend(edit.puts);
     }
 
     /// Check if the builder keeps a [RegionMetadata](store_api::metadata::RegionMetadata).
@@ -167,6 +241,9 @@ impl RegionManifestBuilder {
             manifest_version: self.manifest_version,
             truncated_entry_id: self.truncated_entry_id,
             compaction_time_window: self.compaction_time_window,
+            metadata_history: self.metadata_history,
+            kv: self.kv,
+            range_tombstones: self.range_tombstones,
         })
     }
 }
@@ -235,7 +312,10 @@ impl RegionMetaActionList {
 #[cfg(test)]
 mod tests {
 
+    use std::sync::Arc;
+
     use super::*;
+    use crate::manifest::tests::utils::basic_region_metadata;
 
     #[test]
     fn test_encode_decode_action_list() {
@@ -293,6 +373,72 @@ mod tests {
         // TODO(ruihang): port this test case
     }
 
+    fn metadata_with_version(schema_version: u64) -> RegionMetadataRef {
+        let mut metadata = basic_region_metadata();
+        metadata.schema_version = schema_version;
+        Arc::new(metadata)
+    }
+
+    #[test]
+    fn test_region_manifest_metadata_history() {
+        let mut builder = RegionManifestBuilder::default();
+        builder.apply_change(
+            0,
+            RegionChange {
+                metadata: metadata_with_version(0),
+            },
+        );
+        let manifest = builder.try_build().unwrap();
+        assert!(manifest.metadata_history.is_empty());
+
+        let mut builder = RegionManifestBuilder::with_checkpoint(Some(manifest));
+        for version in 1..=(MAX_METADATA_HISTORY as u64 + 1) {
+            builder.apply_change(
+                version,
+                RegionChange {
+                    metadata: metadata_with_version(version),
+                },
+            );
+        }
+        // Changes with the same schema version don't add history.
+        builder.apply_change(
+            100,
+            RegionChange {
+                metadata: metadata_with_version(MAX_METADATA_HISTORY as u64 + 1),
+            },
+        );
+        let manifest = builder.try_build().unwrap();
+        assert_eq!(MAX_METADATA_HISTORY, manifest.metadata_history.len());
+        let versions: Vec<_> = manifest
+            .metadata_history
+            .iter()
+            .map(|metadata| metadata.schema_version)
+            .collect();
+        let expect: Vec<_> = (1..=MAX_METADATA_HISTORY as u64).collect();
+        assert_eq!(expect, versions);
+        assert_eq!(
+            MAX_METADATA_HISTORY as u64 + 1,
+            manifest.metadata.schema_version
+        );
+    }
+
+    #[test]
+    fn test_region_manifest_without_history() {
+        let mut builder = RegionManifestBuilder::default();
+        builder.apply_change(
+            0,
+            RegionChange {
+                metadata: metadata_with_version(0),
+            },
+        );
+        let manifest = builder.try_build().unwrap();
+        let mut value = serde_json::to_value(&manifest).unwrap();
+        // Manifests written by older versions don't have the history.
+        value.as_object_mut().unwrap().remove("metadata_history");
+        let decoded: RegionManifest = serde_json::from_value(value).unwrap();
+        assert_eq!(manifest, decoded);
+    }
+
     #[test]
     fn test_encode_decode_region_checkpoint() {
         // TODO(ruihang): port this test case
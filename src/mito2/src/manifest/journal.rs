@@ -0,0 +1,225 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Local journal of manifest commits of flushes and compactions.
+//!
+//! A flush or compaction records the actions to commit in the journal before updating
+//! the manifest and removes the record after the update. If the datanode crashes in
+//! between, the record is still there when the region is opened again, and the opener
+//! repairs the half-committed operation by comparing the record with the manifest.
+
+use std::io::ErrorKind;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use common_telemetry::{info, warn};
+use serde::{Deserialize, Serialize};
+use snafu::ResultExt;
+use store_api::manifest::ManifestVersion;
+use store_api::storage::RegionId;
+use tokio::fs;
+use tokio::io::AsyncWriteExt;
+
+use crate::access_layer::AccessLayer;
+use crate::config::MitoConfig;
+use crate::error::{CommitJournalSnafu, Result, SerdeJsonSnafu};
+use crate::manifest::action::{RegionMetaAction, RegionMetaActionList};
+use crate::manifest::manager::RegionManifestManager;
+
+pub(crate) type CommitJournalRef = Arc<CommitJournal>;
+
+/// A commit of a region recorded in the journal.
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+struct JournalEntry {
+    region_id: RegionId,
+    /// Version of the manifest before the commit.
+    manifest_version: ManifestVersion,
+    /// Actions to commit to the manifest.
+    actions: RegionMetaActionList,
+}
+
+/// Journal of manifest commits in a local directory, one file for each region.
+///
+/// A region has at most one commit in progress as its worker commits flushes and
+/// compactions one by one.
+#[derive(Debug)]
+pub(crate) struct CommitJournal {
+    dir: PathBuf,
+}
+
+impl CommitJournal {
+    /// Returns a journal in the local directory `dir`, the directory is created on
+    /// the first commit.
+    pub(crate) fn new(dir: impl Into<PathBuf>) -> CommitJournal {
+        CommitJournal { dir: dir.into() }
+    }
+
+    /// Returns the journal in the `commit_journal_dir` of the config, or `None` if the
+    /// directory isn't set.
+    pub(crate) fn from_config(config: &MitoConfig) -> Option<CommitJournalRef> {
+        (!config.commit_journal_dir.is_empty())
+            .then(|| Arc::new(CommitJournal::new(&config.commit_journal_dir)))
+    }
+
+    fn entry_path(&self, region_id: RegionId) -> PathBuf {
+        self.dir.join(format!("{}.json", region_id.as_u64()))
+    }
+
+    /// Records the `actions` to commit to the manifest of the region at `manifest_version`.
+    pub(crate) async fn begin(
+        &self,
+        region_id: RegionId,
+        manifest_version: ManifestVersion,
+        actions: &RegionMetaActionList,
+    ) -> Result<()> {
+        let entry = JournalEntry {
+            region_id,
+            manifest_version,
+            actions: actions.clone(),
+        };
+        let data = serde_json::to_vec(&entry).context(SerdeJsonSnafu)?;
+
+        let path = self.entry_path(region_id);
+        let tmp_path = path.with_extension("tmp");
+        let context = || CommitJournalSnafu {
+            path: path.display().to_string(),
+        };
+        fs::create_dir_all(&self.dir).await.with_context(context)?;
+        let mut file = fs::File::create(&tmp_path).await.with_context(context)?;
+        file.write_all(&data).await.with_context(context)?;
+        file.sync_all().await.with_context(context)?;
+        // Renames the file at last so a crash never leaves a partial record.
+        fs::rename(&tmp_path, &path).await.with_context(context)
+    }
+
+    /// Removes the record of the region after its commit is done or abandoned.
+    pub(crate) async fn finish(&self, region_id: RegionId) -> Result<()> {
+        let path = self.entry_path(region_id);
+        match fs::remove_file(&path).await {
+            Err(e) if e.kind() != ErrorKind::NotFound => Err(e).context(CommitJournalSnafu {
+                path: path.display().to_string(),
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Returns the commit of the region in the journal.
+    async fn pending(&self, region_id: RegionId) -> Result<Option<JournalEntry>> {
+        let path = self.entry_path(region_id);
+        let data = match fs::read(&path).await {
+            Ok(data) => data,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(e) => {
+                return Err(e).context(CommitJournalSnafu {
+                    path: path.display().to_string(),
+                })
+            }
+        };
+        let entry = serde_json::from_slice(&data).context(SerdeJsonSnafu)?;
+        Ok(Some(entry))
+    }
+
+    /// Repairs the half-committed operation of the region recorded in the journal.
+    ///
+    /// The commit is applied to the manifest again if the manifest is still at the
+    /// version before the commit and all the files to add exist. Otherwise, the commit
+    /// is either done or can't be repaired, the data of an abandoned flush is still in
+    /// the WAL as the WAL is only obsoleted after the manifest is updated.
+    pub(crate) async fn recover(
+        &self,
+        region_id: RegionId,
+        manifest_manager: &RegionManifestManager,
+        access_layer: &AccessLayer,
+    ) -> Result<()> {
+        let Some(entry) = self.pending(region_id).await? else {
+            return Ok(());
+        };
+
+        let manifest_version = manifest_manager.manifest().await.manifest_version;
+        if manifest_version == entry.manifest_version {
+            let mut files_exist = true;
+            for action in &entry.actions.actions {
+                let RegionMetaAction::Edit(edit) = action else {
+                    continue;
+                };
+                for file in &edit.files_to_add {
+                    files_exist &= access_layer.is_sst_exist(file.file_id).await?;
+                }
+            }
+
+            if files_exist {
+                let version = manifest_manager.update(entry.actions).await?;
+                info!(
+                    "Repaired the half-committed operation of region {}, manifest version: {}",
+                    region_id, version
+                );
+            } else {
+                warn!(
+                    "Abandon the half-committed operation of region {} as its files are missing",
+                    region_id
+                );
+            }
+        } else if manifest_version < entry.manifest_version {
+            warn!(
+                "Ignore the commit of region {} at manifest version {} newer than the manifest version {}",
+                region_id, entry.manifest_version, manifest_version
+            );
+        }
+
+        self.finish(region_id).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use common_test_util::temp_dir::create_temp_dir;
+
+    use super::*;
+    use crate::manifest::action::RegionEdit;
+
+    #[tokio::test]
+    async fn test_commit_journal() {
+        let dir = create_temp_dir("journal");
+        let journal = CommitJournal::new(dir.path().join("journal"));
+        let region_id = RegionId::new(1, 1);
+        assert!(journal.pending(region_id).await.unwrap().is_none());
+        // Finishing a region without commits is fine.
+        journal.finish(region_id).await.unwrap();
+
+        let actions = RegionMetaActionList::with_action(RegionMetaAction::Edit(RegionEdit {
+            files_to_add: vec![],
+            files_to_remove: vec![],
+            compaction_time_window: None,
+            flushed_entry_id: Some(10),
+            flushed_sequence: Some(20),
+        }));
+        journal.begin(region_id, 3, &actions).await.unwrap();
+        assert_eq!(
+            Some(JournalEntry {
+                region_id,
+                manifest_version: 3,
+                actions,
+            }),
+            journal.pending(region_id).await.unwrap()
+        );
+        assert!(journal
+            .pending(RegionId::new(1, 2))
+            .await
+            .unwrap()
+            .is_none());
+
+        journal.finish(region_id).await.unwrap();
+        assert!(journal.pending(region_id).await.unwrap().is_none());
+    }
+}
@@ -301,6 +301,15 @@ impl RegionManifestManagerInner {
                     RegionMetaAction::Truncate(action) => {
                         manifest_builder.apply_truncate(manifest_version, action);
                     }
+                    RegionMetaAction::Kv(action) => {
+                        manifest_builder.apply_kv(manifest_version, action);
+                    }
+                    RegionMetaAction::DeleteRange(action) => {
+                        manifest_builder.apply_delete_range(manifest_version, action);
+                    }
+                    RegionMetaAction::RemoveRangeTombstones(action) => {
+                        manifest_builder.apply_remove_range_tombstones(manifest_version, action);
+                    }
                 }
             }
         }
@@ -355,6 +364,15 @@ impl RegionManifestManagerInner {
                 RegionMetaAction::Truncate(action) => {
                     manifest_builder.apply_truncate(version, action);
                 }
+                RegionMetaAction::Kv(action) => {
+                    manifest_builder.apply_kv(version, action);
+                }
+                RegionMetaAction::DeleteRange(action) => {
+                    manifest_builder.apply_delete_range(version, action);
+                }
+                RegionMetaAction::RemoveRangeTombstones(action) => {
+                    manifest_builder.apply_remove_range_tombstones(version, action);
+                }
             }
         }
         let new_manifest = manifest_builder.try_build()?;
@@ -437,6 +455,15 @@ impl RegionManifestManagerInner {
                     RegionMetaAction::Truncate(action) => {
                         manifest_builder.apply_truncate(version, action);
                     }
+                    RegionMetaAction::Kv(action) => {
+                        manifest_builder.apply_kv(version, action);
+                    }
+                    RegionMetaAction::DeleteRange(action) => {
+                        manifest_builder.apply_delete_range(version, action);
+                    }
+                    RegionMetaAction::RemoveRangeTombstones(action) => {
+                        manifest_builder.apply_remove_range_tombstones(version, action);
+                    }
                 }
             }
             last_version = version;
@@ -93,7 +93,10 @@ pub type MemtableRef = Arc<dyn Memtable>;
 /// Builder to build a new [Memtable].
 pub trait MemtableBuilder: Send + Sync + fmt::Debug {
     /// Builds a new memtable instance.
-    fn build(&self, metadata: &RegionMetadataRef) -> MemtableRef;
+    ///
+    /// The memtable only keeps the latest row for the same primary key and timestamp
+    /// if `dedup` is true.
+    fn build(&self, metadata: &RegionMetadataRef, dedup: bool) -> MemtableRef;
 }
 
 pub type MemtableBuilderRef = Arc<dyn MemtableBuilder>;
@@ -73,12 +73,13 @@ impl TimeSeriesMemtableBuilder {
 }
 
 impl MemtableBuilder for TimeSeriesMemtableBuilder {
-    fn build(&self, metadata: &RegionMetadataRef) -> MemtableRef {
+    fn build(&self, metadata: &RegionMetadataRef, dedup: bool) -> MemtableRef {
         let id = self.id.fetch_add(1, Ordering::Relaxed);
         Arc::new(TimeSeriesMemtable::new(
             metadata.clone(),
             id,
             self.write_buffer_manager.clone(),
+            dedup,
         ))
     }
 }
@@ -92,6 +93,8 @@ pub struct TimeSeriesMemtable {
     alloc_tracker: AllocTracker,
     max_timestamp: AtomicI64,
     min_timestamp: AtomicI64,
+    /// Whether to only keep the latest row for the same timestamp in a series.
+    dedup: bool,
 }
 
 impl TimeSeriesMemtable {
@@ -99,6 +102,7 @@ impl TimeSeriesMemtable {
         region_metadata: RegionMetadataRef,
         id: MemtableId,
         write_buffer_manager: Option<WriteBufferManagerRef>,
+        dedup: bool,
     ) -> Self {
         let row_codec = Arc::new(McmpRowCodec::new(
             region_metadata
@@ -115,6 +119,7 @@ impl TimeSeriesMemtable {
             alloc_tracker: AllocTracker::new(write_buffer_manager),
             max_timestamp: AtomicI64::new(i64::MIN),
             min_timestamp: AtomicI64::new(i64::MAX),
+            dedup,
         }
     }
 
@@ -227,7 +232,7 @@ impl Memtable for TimeSeriesMemtable {
                 .collect()
         };
 
-        Box::new(self.series_set.iter_series(projection, filters))
+        Box::new(self.series_set.iter_series(projection, filters, self.dedup))
     }
 
     fn is_empty(&self) -> bool {
@@ -304,7 +309,12 @@ impl SeriesSet {
     }
 
     /// Iterates all series in [SeriesSet].
-    fn iter_series(&self, projection: HashSet<ColumnId>, predicate: Option<Predicate>) -> Iter {
+    fn iter_series(
+        &self,
+        projection: HashSet<ColumnId>,
+        predicate: Option<Predicate>,
+        dedup: bool,
+    ) -> Iter {
         let (primary_key_builders, primary_key_schema) =
             primary_key_builders(&self.region_metadata, 1);
 
@@ -321,6 +331,7 @@ impl SeriesSet {
             pk_schema: primary_key_schema,
             primary_key_builders,
             codec: self.codec.clone(),
+            dedup,
             metrics: Metrics::default(),
         }
     }
@@ -373,6 +384,8 @@ struct Iter {
     pk_schema: arrow::datatypes::SchemaRef,
     primary_key_builders: Vec<Box<dyn MutableVector>>,
     codec: Arc<McmpRowCodec>,
+    /// Whether to dedup rows with the same timestamp in each series.
+    dedup: bool,
     metrics: Metrics,
 }
 
@@ -427,8 +440,9 @@ impl Iterator for Iter {
             self.last_key = Some(primary_key.clone());
 
             let values = series.compact(&self.metadata);
-            let batch =
-                values.and_then(|v| v.to_batch(primary_key, &self.metadata, &self.projection));
+            let batch = values.and_then(|v| {
+                v.to_batch(primary_key, &self.metadata, &self.projection, self.dedup)
+            });
 
             // Update metrics.
             self.metrics.num_batches += 1;
@@ -665,12 +679,13 @@ struct Values {
 
 impl Values {
     /// Converts [Values] to `Batch`, sorts the batch according to `timestamp, sequence` desc and
-    /// keeps only the latest row for the same timestamp.
+    /// keeps only the latest row for the same timestamp if `dedup` is true.
     pub fn to_batch(
         &self,
         primary_key: &[u8],
         metadata: &RegionMetadataRef,
         projection: &HashSet<ColumnId>,
+        dedup: bool,
     ) -> Result<Batch> {
         let builder = BatchBuilder::with_required_columns(
             primary_key.to_vec(),
@@ -691,7 +706,7 @@ impl Values {
             .collect();
 
         let mut batch = builder.with_fields(fields).build()?;
-        batch.sort_and_dedup()?;
+        batch.sort(dedup)?;
         Ok(batch)
     }
 
@@ -906,7 +921,12 @@ mod tests {
         };
 
         let batch = values
-            .to_batch(b"test", &schema, &[0, 1, 2, 3, 4].into_iter().collect())
+            .to_batch(
+                b"test",
+                &schema,
+                &[0, 1, 2, 3, 4].into_iter().collect(),
+                true,
+            )
             .unwrap();
         check_value(
             &batch,
@@ -1072,7 +1092,7 @@ mod tests {
         common_telemetry::init_default_ut_logging();
         let schema = schema_for_test();
         let kvs = build_key_values(&schema, "hello".to_string(), 42, 100);
-        let memtable = TimeSeriesMemtable::new(schema, 42, None);
+        let memtable = TimeSeriesMemtable::new(schema, 42, None, true);
         memtable.write(&kvs).unwrap();
 
         let expected_ts = kvs
@@ -1113,7 +1133,7 @@ mod tests {
         common_telemetry::init_default_ut_logging();
         let schema = schema_for_test();
         let kvs = build_key_values(&schema, "hello".to_string(), 42, 100);
-        let memtable = TimeSeriesMemtable::new(schema, 42, None);
+        let memtable = TimeSeriesMemtable::new(schema, 42, None, true);
         memtable.write(&kvs).unwrap();
 
         let iter = memtable.iter(Some(&[3]), None);
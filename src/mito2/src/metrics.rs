@@ -119,6 +119,9 @@ lazy_static! {
     /// Counter of filtered rows during merge.
     pub static ref MERGE_FILTER_ROWS_TOTAL: IntCounterVec =
         register_int_counter_vec!("mito_merge_filter_rows_total", "mito merge filter rows total", &[TYPE_LABEL]).unwrap();
+    /// Counter of scans whose results change if pruning is disabled.
+    pub static ref PRUNING_MISMATCH_TOTAL: IntCounter =
+        register_int_counter!("mito_pruning_mismatch_total", "mito pruning mismatch total").unwrap();
     // ------- End of query metrics.
 
     // Cache related metrics.
@@ -0,0 +1,158 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Tracks background jobs to purge data of dropped regions.
+
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+use common_time::util::current_time_millis;
+use store_api::storage::RegionId;
+
+/// State of a purge job.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PurgeJobState {
+    /// The job is waiting for the next attempt.
+    Pending,
+    /// The job is removing files of the region.
+    Running,
+    /// The region dir is removed.
+    Done,
+    /// The job gives up after too many attempts.
+    Failed,
+}
+
+/// Progress of a job to purge data of a dropped region.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PurgeJob {
+    pub region_id: RegionId,
+    pub region_dir: String,
+    pub state: PurgeJobState,
+    /// Timestamp in millis when the region is dropped.
+    pub dropped_at: i64,
+    /// Number of attempts to remove the region dir.
+    pub attempts: u64,
+    /// Number of SST files left in the region dir after the last attempt.
+    pub remaining_files: usize,
+    /// Error of the last failed attempt.
+    pub last_error: Option<String>,
+}
+
+/// Tracks purge jobs of an engine. Finished jobs are kept until the engine restarts.
+#[derive(Debug, Default)]
+pub(crate) struct PurgeJobTracker {
+    jobs: RwLock<HashMap<RegionId, PurgeJob>>,
+}
+
+pub(crate) type PurgeJobTrackerRef = Arc<PurgeJobTracker>;
+
+impl PurgeJobTracker {
+    /// Adds a pending job for the dropped region, replacing the previous one.
+    pub(crate) fn add_job(&self, region_id: RegionId, region_dir: String) {
+        let job = PurgeJob {
+            region_id,
+            region_dir,
+            state: PurgeJobState::Pending,
+            dropped_at: current_time_millis(),
+            attempts: 0,
+            remaining_files: 0,
+            last_error: None,
+        };
+        let mut jobs = self.jobs.write().unwrap();
+        jobs.insert(region_id, job);
+    }
+
+    /// Marks the job is running a new attempt.
+    pub(crate) fn on_attempt_begin(&self, region_id: RegionId) {
+        self.update(region_id, |job| {
+            job.state = PurgeJobState::Running;
+            job.attempts += 1;
+        });
+    }
+
+    /// Updates the job by the result of the attempt, `remaining_files` is 0 if the
+    /// region dir is removed.
+    pub(crate) fn on_attempt_end(&self, region_id: RegionId, result: Result<usize, String>) {
+        self.update(region_id, |job| match result {
+            Ok(0) => {
+                job.state = PurgeJobState::Done;
+                job.remaining_files = 0;
+            }
+            Ok(remaining_files) => {
+                job.state = PurgeJobState::Pending;
+                job.remaining_files = remaining_files;
+            }
+            Err(error) => {
+                job.state = PurgeJobState::Pending;
+                job.last_error = Some(error);
+            }
+        });
+    }
+
+    /// Marks the job failed.
+    pub(crate) fn on_job_failed(&self, region_id: RegionId) {
+        self.update(region_id, |job| job.state = PurgeJobState::Failed);
+    }
+
+    /// Returns all jobs, ordered by region id.
+    pub(crate) fn jobs(&self) -> Vec<PurgeJob> {
+        let jobs = self.jobs.read().unwrap();
+        let mut jobs = jobs.values().cloned().collect::<Vec<_>>();
+        jobs.sort_unstable_by_key(|job| job.region_id.as_u64());
+        jobs
+    }
+
+    fn update(&self, region_id: RegionId, f: impl FnOnce(&mut PurgeJob)) {
+        let mut jobs = self.jobs.write().unwrap();
+        if let Some(job) = jobs.get_mut(&region_id) {
+            f(job);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_purge_job_tracker() {
+        let tracker = PurgeJobTracker::default();
+        let region_id = RegionId::new(1, 1);
+        tracker.add_job(region_id, "data/1_0000000001".to_string());
+        let job = &tracker.jobs()[0];
+        assert_eq!(PurgeJobState::Pending, job.state);
+        assert_eq!(0, job.attempts);
+
+        tracker.on_attempt_begin(region_id);
+        assert_eq!(PurgeJobState::Running, tracker.jobs()[0].state);
+        tracker.on_attempt_end(region_id, Err("timeout".to_string()));
+        tracker.on_attempt_begin(region_id);
+        tracker.on_attempt_end(region_id, Ok(3));
+        let job = &tracker.jobs()[0];
+        assert_eq!(PurgeJobState::Pending, job.state);
+        assert_eq!(2, job.attempts);
+        assert_eq!(3, job.remaining_files);
+        assert_eq!(Some("timeout"), job.last_error.as_deref());
+
+        tracker.on_attempt_begin(region_id);
+        tracker.on_attempt_end(region_id, Ok(0));
+        let job = &tracker.jobs()[0];
+        assert_eq!(PurgeJobState::Done, job.state);
+        assert_eq!(0, job.remaining_files);
+
+        // Updating a job that doesn't exist is a no-op.
+        tracker.on_job_failed(RegionId::new(1, 2));
+        assert_eq!(1, tracker.jobs().len());
+    }
+}
@@ -16,9 +16,14 @@
 
 pub mod compat;
 pub mod merge;
+pub(crate) mod pk_prefix;
 pub mod projection;
+pub(crate) mod prune_verify;
+pub(crate) mod range_delete;
+pub(crate) mod reverse;
 pub(crate) mod scan_region;
 pub(crate) mod seq_scan;
+pub(crate) mod unordered_scan;
 
 use std::collections::HashSet;
 use std::sync::Arc;
@@ -321,6 +326,13 @@ impl Batch {
     /// row for the same timestamp. It doesn't consider op type as sequence
     /// should already provide uniqueness for a row.
     pub fn sort_and_dedup(&mut self) -> Result<()> {
+        self.sort(true)
+    }
+
+    /// Sorts rows in the batch by timestamp, sequence desc.
+    ///
+    /// Only keeps the latest row for the same timestamp if `dedup` is true.
+    pub fn sort(&mut self, dedup: bool) -> Result<()> {
         // If building a converter each time is costly, we may allow passing a
         // converter.
         let converter = RowConverter::new(vec![
@@ -343,19 +355,30 @@ impl Batch {
         let mut to_sort: Vec<_> = rows.iter().enumerate().collect();
         to_sort.sort_unstable_by(|left, right| left.1.cmp(&right.1));
 
-        // Dedup by timestamps.
-        to_sort.dedup_by(|left, right| {
-            debug_assert_eq!(18, left.1.as_ref().len());
-            debug_assert_eq!(18, right.1.as_ref().len());
-            let (left_key, right_key) = (left.1.as_ref(), right.1.as_ref());
-            // We only compare the timestamp part and ignore sequence.
-            left_key[..TIMESTAMP_KEY_LEN] == right_key[..TIMESTAMP_KEY_LEN]
-        });
+        if dedup {
+            // Dedup by timestamps.
+            to_sort.dedup_by(|left, right| {
+                debug_assert_eq!(18, left.1.as_ref().len());
+                debug_assert_eq!(18, right.1.as_ref().len());
+                let (left_key, right_key) = (left.1.as_ref(), right.1.as_ref());
+                // We only compare the timestamp part and ignore sequence.
+                left_key[..TIMESTAMP_KEY_LEN] == right_key[..TIMESTAMP_KEY_LEN]
+            });
+        }
 
         let indices = UInt32Vector::from_iter_values(to_sort.iter().map(|v| v.0 as u32));
         self.take_in_place(&indices)
     }
 
+    /// Reverses rows in the batch.
+    ///
+    /// Rows are ordered by timestamp desc after reversing a sorted batch.
+    pub fn reverse(&mut self) -> Result<()> {
+        let num_rows = self.num_rows() as u32;
+        let indices = UInt32Vector::from_iter_values((0..num_rows).rev());
+        self.take_in_place(&indices)
+    }
+
     /// Returns ids of fields in the [Batch] after applying the `projection`.
     pub(crate) fn projected_fields(
         metadata: &RegionMetadata,
@@ -961,6 +984,28 @@ mod tests {
         assert!(batch.is_empty());
     }
 
+    #[test]
+    fn test_reverse() {
+        let mut batch = new_batch(
+            &[1, 2, 3],
+            &[11, 12, 13],
+            &[OpType::Put, OpType::Delete, OpType::Put],
+            &[21, 22, 23],
+        );
+        batch.reverse().unwrap();
+        let expect = new_batch(
+            &[3, 2, 1],
+            &[13, 12, 11],
+            &[OpType::Put, OpType::Delete, OpType::Put],
+            &[23, 22, 21],
+        );
+        assert_eq!(expect, batch);
+
+        let mut batch = new_batch(&[], &[], &[], &[]);
+        batch.reverse().unwrap();
+        assert!(batch.is_empty());
+    }
+
     #[test]
     fn test_sort_and_dedup() {
         let mut batch = new_batch(
@@ -1002,4 +1047,23 @@ mod tests {
         let expect = new_batch(&[1, 2], &[1, 6], &[OpType::Put, OpType::Put], &[23, 22]);
         assert_eq!(expect, batch);
     }
+
+    #[test]
+    fn test_sort_without_dedup() {
+        let mut batch = new_batch(
+            &[2, 3, 1, 2],
+            &[1, 2, 3, 4],
+            &[OpType::Put, OpType::Put, OpType::Put, OpType::Put],
+            &[21, 22, 23, 24],
+        );
+        batch.sort(false).unwrap();
+        // It keeps both timestamp 2 and orders them by sequence desc.
+        let expect = new_batch(
+            &[1, 2, 2, 3],
+            &[3, 4, 1, 2],
+            &[OpType::Put, OpType::Put, OpType::Put, OpType::Put],
+            &[23, 24, 21, 22],
+        );
+        assert_eq!(expect, batch);
+    }
 }
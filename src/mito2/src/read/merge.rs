@@ -35,6 +35,12 @@ use crate::read::{Batch, BatchReader, BoxedBatchReader, Source};
 /// ignore op type as sequence is already unique).
 /// 2. Batch doesn't have duplicate elements (elements with the same primary key and time index).
 /// 3. Batches from sources **must** not be empty.
+///
+/// In reverse mode, sources must yield batches and rows in reverse order, that is, ordered
+/// by primary key desc, time index desc. The reader also outputs batches in reverse order.
+///
+/// If dedup is disabled, the reader outputs all rows with the same primary key and time
+/// index and batches from sources may contain duplicate elements.
 pub struct MergeReader {
     /// Holds [Node]s whose key range of current batch **is** overlapped with the merge window.
     /// Each node yields batches from a `source`.
@@ -48,6 +54,12 @@ pub struct MergeReader {
     cold: BinaryHeap<Node>,
     /// Batch to output.
     output_batch: Option<Batch>,
+    /// Whether to merge batches in reverse order.
+    reverse: bool,
+    /// Whether to only keep the row with the maximum sequence for duplicate elements.
+    dedup: bool,
+    /// Maximum number of rows to output. `None` means no limit.
+    limit: Option<usize>,
     /// Local metrics.
     metrics: Metrics,
 }
@@ -56,6 +68,12 @@ pub struct MergeReader {
 impl BatchReader for MergeReader {
     async fn next_batch(&mut self) -> Result<Option<Batch>> {
         let start = Instant::now();
+        if self.reach_limit() {
+            // Stops fetching batches from sources once we output enough rows.
+            self.metrics.scan_cost += start.elapsed();
+            return Ok(None);
+        }
+
         while !self.hot.is_empty() && self.output_batch.is_none() {
             if self.hot.len() == 1 {
                 // No need to do merge sort if only one batch in the hot heap.
@@ -68,7 +86,13 @@ impl BatchReader for MergeReader {
             }
         }
 
-        if let Some(batch) = self.output_batch.take() {
+        if let Some(mut batch) = self.output_batch.take() {
+            if let Some(limit) = self.limit {
+                let remaining = limit - self.metrics.num_output_rows;
+                if batch.num_rows() > remaining {
+                    batch = batch.slice(0, remaining);
+                }
+            }
             self.metrics.scan_cost += start.elapsed();
             self.metrics.num_output_rows += batch.num_rows();
             Ok(Some(batch))
@@ -99,13 +123,19 @@ impl Drop for MergeReader {
 impl MergeReader {
     /// Creates and initializes a new [MergeReader].
     pub async fn new(sources: Vec<Source>) -> Result<MergeReader> {
+        Self::new_with_order(sources, false).await
+    }
+
+    /// Creates and initializes a new [MergeReader] that merges batches in reverse
+    /// order if `reverse` is true.
+    pub async fn new_with_order(sources: Vec<Source>, reverse: bool) -> Result<MergeReader> {
         let start = Instant::now();
         let mut metrics = Metrics::default();
 
         let mut cold = BinaryHeap::with_capacity(sources.len());
         let hot = BinaryHeap::with_capacity(sources.len());
         for source in sources {
-            let node = Node::new(source, &mut metrics).await?;
+            let node = Node::new(source, reverse, &mut metrics).await?;
             if !node.is_eof() {
                 // Ensure `cold` don't have eof nodes.
                 cold.push(node);
@@ -116,6 +146,9 @@ impl MergeReader {
             hot,
             cold,
             output_batch: None,
+            reverse,
+            dedup: true,
+            limit: None,
             metrics,
         };
         // Initializes the reader.
@@ -125,6 +158,26 @@ impl MergeReader {
         Ok(reader)
     }
 
+    /// Returns true if the reader has output rows up to the limit.
+    fn reach_limit(&self) -> bool {
+        self.limit
+            .map(|limit| self.metrics.num_output_rows >= limit)
+            .unwrap_or(false)
+    }
+
+    /// Returns the number of rows the reader outputs.
+    pub(crate) fn num_output_rows(&self) -> usize {
+        self.metrics.num_output_rows
+    }
+
+    /// Returns the time spent in merging, excluding the time to fetch batches
+    /// from sources.
+    pub(crate) fn merge_cost(&self) -> Duration {
+        self.metrics
+            .scan_cost
+            .saturating_sub(self.metrics.fetch_cost)
+    }
+
     /// Moves nodes in `cold` heap, whose key range is overlapped with current merge
     /// window to `hot` heap.
     fn refill_hot(&mut self) {
@@ -161,8 +214,9 @@ impl MergeReader {
         // Pop hottest node.
         let mut top_node = self.hot.pop().unwrap();
         let top = top_node.current_batch();
-        // Min timestamp and its sequence in the next batch.
-        let next_min_ts = {
+        // First timestamp in the next batch. It is the min timestamp of the next batch
+        // or the max timestamp in reverse mode.
+        let next_first_ts = {
             let next_node = self.hot.peek().unwrap();
             let next = next_node.current_batch();
             // top and next have overlapping rows so they must have same primary keys.
@@ -173,14 +227,33 @@ impl MergeReader {
 
         // Safety: Batches in the heap is not empty, so we can use unwrap here.
         let timestamps = top.timestamps_native().unwrap();
+        if !self.dedup {
+            // Outputs all rows not after the first timestamp of the next node, including
+            // duplicate timestamps. The top node has at least one such row as it is the
+            // hottest node.
+            let pos = if self.reverse {
+                timestamps.partition_point(|ts| *ts >= next_first_ts.value())
+            } else {
+                timestamps.partition_point(|ts| *ts <= next_first_ts.value())
+            };
+            Self::maybe_output_batch(top.slice(0, pos), &mut self.output_batch, &mut self.metrics)?;
+            top_node.skip_rows(pos, &mut self.metrics).await?;
+            return self.reheap(top_node);
+        }
         // Binary searches the timestamp in the top batch.
         // Safety: Batches should have the same timestamp resolution so we can compare the native
         // value directly.
-        match timestamps.binary_search(&next_min_ts.value()) {
+        let search_result = if self.reverse {
+            // Timestamps are in descending order.
+            timestamps.binary_search_by(|probe| next_first_ts.value().cmp(probe))
+        } else {
+            timestamps.binary_search(&next_first_ts.value())
+        };
+        match search_result {
             Ok(pos) => {
                 // They have duplicate timestamps. Outputs timestamps before the duplicated timestamp.
                 // Batch itself doesn't contain duplicate timestamps so timestamps before `pos`
-                // must be less than `next_min_ts`.
+                // must be before `next_first_ts`.
                 Self::maybe_output_batch(
                     top.slice(0, pos),
                     &mut self.output_batch,
@@ -190,7 +263,7 @@ impl MergeReader {
                 top_node.skip_rows(pos, &mut self.metrics).await?;
                 // The merge window should contain this timestamp so only nodes in the hot heap
                 // have this timestamp.
-                self.filter_first_duplicate_timestamp_in_hot(top_node, next_min_ts)
+                self.filter_first_duplicate_timestamp_in_hot(top_node, next_first_ts)
                     .await?;
             }
             Err(pos) => {
@@ -315,12 +388,23 @@ impl MergeReader {
 }
 
 /// Builder to build and initialize a [MergeReader].
-#[derive(Default)]
 pub struct MergeReaderBuilder {
     /// Input sources.
     ///
     /// All source must yield batches with the same schema.
     sources: Vec<Source>,
+    /// Whether to merge batches in reverse order.
+    reverse: bool,
+    /// Whether to dedup duplicate elements.
+    dedup: bool,
+    /// Maximum number of rows to output.
+    limit: Option<usize>,
+}
+
+impl Default for MergeReaderBuilder {
+    fn default() -> MergeReaderBuilder {
+        MergeReaderBuilder::from_sources(Vec::new())
+    }
 }
 
 impl MergeReaderBuilder {
@@ -331,7 +415,36 @@ impl MergeReaderBuilder {
 
     /// Creates a builder from sources.
     pub fn from_sources(sources: Vec<Source>) -> MergeReaderBuilder {
-        MergeReaderBuilder { sources }
+        MergeReaderBuilder {
+            sources,
+            reverse: false,
+            dedup: true,
+            limit: None,
+        }
+    }
+
+    /// Sets whether to merge batches in reverse order.
+    ///
+    /// Sources must yield batches in reverse order if `reverse` is true.
+    pub fn reverse(&mut self, reverse: bool) -> &mut Self {
+        self.reverse = reverse;
+        self
+    }
+
+    /// Sets whether to only keep the row with the maximum sequence for duplicate elements.
+    ///
+    /// Defaults to true.
+    pub fn dedup(&mut self, dedup: bool) -> &mut Self {
+        self.dedup = dedup;
+        self
+    }
+
+    /// Sets the maximum number of rows to output.
+    ///
+    /// The reader stops reading sources once it outputs `limit` rows.
+    pub fn limit(&mut self, limit: Option<usize>) -> &mut Self {
+        self.limit = limit;
+        self
     }
 
     /// Pushes a batch reader to sources.
@@ -349,7 +462,10 @@ impl MergeReaderBuilder {
     /// Builds and initializes the reader, then resets the builder.
     pub async fn build(&mut self) -> Result<MergeReader> {
         let sources = mem::take(&mut self.sources);
-        MergeReader::new(sources).await
+        let mut reader = MergeReader::new_with_order(sources, self.reverse).await?;
+        reader.dedup = self.dedup;
+        reader.limit = self.limit;
+        Ok(reader)
     }
 }
 
@@ -382,13 +498,15 @@ struct Node {
     ///
     /// `None` means the `source` has reached EOF.
     current_batch: Option<CompareFirst>,
+    /// Whether the source yields batches in reverse order.
+    reverse: bool,
 }
 
 impl Node {
     /// Initialize a node.
     ///
     /// It tries to fetch one batch from the `source`.
-    async fn new(mut source: Source, metrics: &mut Metrics) -> Result<Node> {
+    async fn new(mut source: Source, reverse: bool, metrics: &mut Metrics) -> Result<Node> {
         // Ensures batch is not empty.
         let start = Instant::now();
         let current_batch = source.next_batch().await?.map(CompareFirst);
@@ -398,6 +516,7 @@ impl Node {
         Ok(Node {
             source,
             current_batch,
+            reverse,
         })
     }
 
@@ -453,11 +572,16 @@ impl Node {
         // We only compare pk and timestamp so nodes in the cold
         // heap don't have overlapping timestamps with the hottest node
         // in the hot heap.
-        self.primary_key().cmp(other.primary_key()).then_with(|| {
+        let ordering = self.primary_key().cmp(other.primary_key()).then_with(|| {
             self.current_batch()
                 .first_timestamp()
                 .cmp(&other.current_batch().last_timestamp())
-        }) == Ordering::Greater
+        });
+        if self.reverse {
+            ordering == Ordering::Less
+        } else {
+            ordering == Ordering::Greater
+        }
     }
 
     /// Skips first `num_to_skip` rows from node's current batch. If current batch is empty it fetches
@@ -498,9 +622,16 @@ impl PartialOrd for Node {
 
 impl Ord for Node {
     fn cmp(&self, other: &Node) -> Ordering {
-        // The std binary heap is a max heap, but we want the nodes are ordered in
-        // ascend order, so we compare the nodes in reverse order.
-        other.current_batch.cmp(&self.current_batch)
+        if self.reverse {
+            // Nodes are ordered in descend order in reverse mode. Nodes with the same
+            // key are not ordered by sequence desc but the reader removes duplicate
+            // rows by comparing their sequences.
+            self.current_batch.cmp(&other.current_batch)
+        } else {
+            // The std binary heap is a max heap, but we want the nodes are ordered in
+            // ascend order, so we compare the nodes in reverse order.
+            other.current_batch.cmp(&self.current_batch)
+        }
     }
 }
 
@@ -901,4 +1032,193 @@ mod tests {
             .collect();
         check_reader_result(&mut reader, &expect).await;
     }
+
+    #[tokio::test]
+    async fn test_merge_without_dedup() {
+        let reader1 = VecBatchReader::new(&[new_batch(
+            b"k1",
+            &[1, 2],
+            &[11, 12],
+            &[OpType::Put, OpType::Put],
+            &[21, 22],
+        )]);
+        let reader2 = VecBatchReader::new(&[new_batch(
+            b"k1",
+            &[2, 3],
+            &[13, 14],
+            &[OpType::Put, OpType::Put],
+            &[23, 24],
+        )]);
+        let mut reader = MergeReaderBuilder::new()
+            .push_batch_reader(Box::new(reader1))
+            .push_batch_reader(Box::new(reader2))
+            .dedup(false)
+            .build()
+            .await
+            .unwrap();
+        // Keeps both rows with timestamp 2.
+        check_reader_result(
+            &mut reader,
+            &[
+                new_batch(
+                    b"k1",
+                    &[1, 2],
+                    &[11, 12],
+                    &[OpType::Put, OpType::Put],
+                    &[21, 22],
+                ),
+                new_batch(
+                    b"k1",
+                    &[2, 3],
+                    &[13, 14],
+                    &[OpType::Put, OpType::Put],
+                    &[23, 24],
+                ),
+            ],
+        )
+        .await;
+    }
+
+    #[tokio::test]
+    async fn test_merge_limit() {
+        let reader1 = VecBatchReader::new(&[
+            new_batch(
+                b"k1",
+                &[1, 2],
+                &[11, 12],
+                &[OpType::Put, OpType::Delete],
+                &[21, 22],
+            ),
+            new_batch(
+                b"k2",
+                &[1, 2],
+                &[11, 12],
+                &[OpType::Put, OpType::Put],
+                &[21, 22],
+            ),
+        ]);
+        let reader2 = VecBatchReader::new(&[new_batch(
+            b"k1",
+            &[3, 4],
+            &[13, 14],
+            &[OpType::Put, OpType::Put],
+            &[23, 24],
+        )]);
+        let mut reader = MergeReaderBuilder::new()
+            .push_batch_reader(Box::new(reader1))
+            .push_batch_iter(Box::new(reader2))
+            .limit(Some(2))
+            .build()
+            .await
+            .unwrap();
+        // Deleted rows are not counted and the last batch is truncated.
+        check_reader_result(
+            &mut reader,
+            &[
+                new_batch(b"k1", &[1], &[11], &[OpType::Put], &[21]),
+                new_batch(b"k1", &[3], &[13], &[OpType::Put], &[23]),
+            ],
+        )
+        .await;
+        assert_eq!(2, reader.metrics.num_output_rows);
+
+        let reader1 = VecBatchReader::new(&[new_batch(
+            b"k1",
+            &[1, 2],
+            &[11, 12],
+            &[OpType::Put, OpType::Put],
+            &[21, 22],
+        )]);
+        let mut reader = MergeReaderBuilder::new()
+            .push_batch_reader(Box::new(reader1))
+            .limit(Some(0))
+            .build()
+            .await
+            .unwrap();
+        assert!(reader.next_batch().await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_merge_reverse() {
+        // Batches and rows are in reverse order.
+        let reader1 = VecBatchReader::new(&[
+            new_batch(
+                b"k2",
+                &[3, 2],
+                &[13, 12],
+                &[OpType::Put, OpType::Put],
+                &[23, 22],
+            ),
+            new_batch(
+                b"k1",
+                &[7, 4],
+                &[17, 14],
+                &[OpType::Put, OpType::Put],
+                &[27, 24],
+            ),
+            new_batch(
+                b"k1",
+                &[2, 1],
+                &[12, 11],
+                &[OpType::Put, OpType::Put],
+                &[22, 21],
+            ),
+        ]);
+        let reader2 = VecBatchReader::new(&[
+            new_batch(
+                b"k1",
+                &[5, 4],
+                &[25, 24],
+                &[OpType::Put, OpType::Delete],
+                &[35, 34],
+            ),
+            new_batch(b"k1", &[1], &[21], &[OpType::Put], &[31]),
+        ]);
+        let mut reader = MergeReaderBuilder::new()
+            .push_batch_reader(Box::new(reader1))
+            .push_batch_iter(Box::new(reader2))
+            .reverse(true)
+            .build()
+            .await
+            .unwrap();
+
+        let mut batches = Vec::new();
+        while let Some(batch) = reader.next_batch().await.unwrap() {
+            batches.push(batch);
+        }
+        // Batches are ordered by primary key desc.
+        assert!(batches
+            .windows(2)
+            .all(|w| w[0].primary_key() >= w[1].primary_key()));
+        let concat_key = |key: &[u8]| {
+            let batches = batches
+                .iter()
+                .filter(|b| b.primary_key() == key)
+                .cloned()
+                .collect();
+            Batch::concat(batches).unwrap()
+        };
+        assert_eq!(
+            new_batch(
+                b"k2",
+                &[3, 2],
+                &[13, 12],
+                &[OpType::Put, OpType::Put],
+                &[23, 22],
+            ),
+            concat_key(b"k2")
+        );
+        assert_eq!(
+            new_batch(
+                b"k1",
+                &[7, 5, 2, 1],
+                &[17, 25, 12, 21],
+                &[OpType::Put, OpType::Put, OpType::Put, OpType::Put],
+                &[27, 35, 22, 31],
+            ),
+            concat_key(b"k1")
+        );
+        assert_eq!(2, reader.metrics.num_duplicate_rows);
+        assert_eq!(1, reader.metrics.num_deleted_rows);
+    }
 }
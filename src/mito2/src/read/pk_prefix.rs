@@ -0,0 +1,318 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Filter to only read primary keys starting with a prefix.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use datatypes::value::Value;
+use parquet::file::metadata::RowGroupMetaData;
+use parquet::file::statistics::Statistics;
+use snafu::ensure;
+use store_api::metadata::RegionMetadata;
+use store_api::storage::{ColumnId, PrimaryKeyPrefix};
+
+use crate::error::{InvalidRequestSnafu, Result};
+use crate::read::{Batch, BatchReader, Source};
+use crate::row_converter::{McmpRowCodec, RowCodec, SortField};
+
+/// Filter to select primary keys starting with a [PrimaryKeyPrefix].
+///
+/// Primary keys are encoded by a memcomparable codec so keys starting with the
+/// prefix are contiguous in the order of encoded keys. The first of them is not
+/// less than the encoded prefix, and any key after the encoded prefix that doesn't
+/// match is greater than all the matched keys.
+pub(crate) struct PrimaryKeyPrefixFilter {
+    /// Ids of the primary key columns the prefix constrains.
+    column_ids: Vec<ColumnId>,
+    /// Values of the leading primary key columns.
+    values: Vec<Value>,
+    /// Prefix of the string column next to `values`.
+    string_prefix: Option<String>,
+    /// Codec of the primary key columns the prefix constrains.
+    codec: McmpRowCodec,
+    /// Encoded prefix. Keys starting with the prefix are not less than it.
+    lower_bound: Vec<u8>,
+}
+
+pub(crate) type PrimaryKeyPrefixFilterRef = Arc<PrimaryKeyPrefixFilter>;
+
+impl PrimaryKeyPrefixFilter {
+    /// Creates a filter for the region with specific `metadata`.
+    ///
+    /// Returns `None` if the prefix is empty.
+    pub(crate) fn new(
+        metadata: &RegionMetadata,
+        prefix: &PrimaryKeyPrefix,
+    ) -> Result<Option<PrimaryKeyPrefixFilter>> {
+        if prefix.is_empty() {
+            return Ok(None);
+        }
+
+        let num_columns = prefix.values.len() + usize::from(prefix.string_prefix.is_some());
+        ensure!(
+            num_columns <= metadata.primary_key.len(),
+            InvalidRequestSnafu {
+                region_id: metadata.region_id,
+                reason: format!(
+                    "primary key prefix has {} columns but the primary key only has {}",
+                    num_columns,
+                    metadata.primary_key.len()
+                ),
+            }
+        );
+        let columns: Vec<_> = metadata.primary_key_columns().take(num_columns).collect();
+        for (column, value) in columns.iter().zip(&prefix.values) {
+            ensure!(
+                !value.is_null() && value.data_type() == column.column_schema.data_type,
+                InvalidRequestSnafu {
+                    region_id: metadata.region_id,
+                    reason: format!(
+                        "invalid value {:?} in primary key prefix for column {}",
+                        value, column.column_schema.name
+                    ),
+                }
+            );
+        }
+        let mut bound_values = prefix.values.clone();
+        if let Some(string_prefix) = &prefix.string_prefix {
+            // Safety: We checked the number of columns.
+            let column = columns.last().unwrap();
+            ensure!(
+                column.column_schema.data_type.is_string(),
+                InvalidRequestSnafu {
+                    region_id: metadata.region_id,
+                    reason: format!(
+                        "column {} in primary key prefix is not a string",
+                        column.column_schema.name
+                    ),
+                }
+            );
+            bound_values.push(Value::from(string_prefix.as_str()));
+        }
+
+        let codec = McmpRowCodec::new(
+            columns
+                .iter()
+                .map(|c| SortField::new(c.column_schema.data_type.clone()))
+                .collect(),
+        );
+        let lower_bound = codec.encode(bound_values.iter().map(|v| v.as_value_ref()))?;
+
+        Ok(Some(PrimaryKeyPrefixFilter {
+            column_ids: columns.iter().map(|c| c.column_id).collect(),
+            values: prefix.values.clone(),
+            string_prefix: prefix.string_prefix.clone(),
+            codec,
+            lower_bound,
+        }))
+    }
+
+    /// Returns true if the encoded `primary_key` starts with the prefix.
+    pub(crate) fn matches(&self, primary_key: &[u8]) -> Result<bool> {
+        let values = self
+            .codec
+            .decode_leftmost(primary_key, self.codec.num_fields())?;
+        if values[..self.values.len()] != self.values[..] {
+            return Ok(false);
+        }
+        let Some(string_prefix) = &self.string_prefix else {
+            return Ok(true);
+        };
+        match values.last() {
+            Some(Value::String(s)) => Ok(s.as_utf8().starts_with(string_prefix.as_str())),
+            _ => Ok(false),
+        }
+    }
+
+    /// Returns false if no primary key in the range `[min, max]` starts with the prefix.
+    pub(crate) fn may_match_range(&self, min: &[u8], max: &[u8]) -> Result<bool> {
+        if max < self.lower_bound.as_slice() {
+            return Ok(false);
+        }
+        if min >= self.lower_bound.as_slice() && !self.matches(min)? {
+            // The min key is after all keys starting with the prefix.
+            return Ok(false);
+        }
+        Ok(true)
+    }
+
+    /// Returns true if the primary key of the SST with specific `metadata` also starts
+    /// with columns this filter constrains, so the filter can prune the SST by its keys.
+    pub(crate) fn is_compatible_with(&self, metadata: &RegionMetadata) -> bool {
+        metadata.primary_key.starts_with(&self.column_ids)
+    }
+
+    /// Returns false if the row group has no primary key starting with the prefix.
+    ///
+    /// `column_idx` is the index of the primary key column in the SST.
+    pub(crate) fn row_group_may_match(
+        &self,
+        row_group: &RowGroupMetaData,
+        column_idx: usize,
+    ) -> bool {
+        match row_group.column(column_idx).statistics() {
+            Some(Statistics::ByteArray(stats)) if stats.has_min_max_set() => self
+                .may_match_range(stats.min_bytes(), stats.max_bytes())
+                .unwrap_or(true),
+            _ => true,
+        }
+    }
+}
+
+/// Reader that only returns batches whose primary keys start with the prefix.
+pub(crate) struct PrimaryKeyPrefixReader {
+    source: Source,
+    filter: PrimaryKeyPrefixFilterRef,
+}
+
+impl PrimaryKeyPrefixReader {
+    /// Creates a new reader to filter the `source`.
+    pub(crate) fn new(source: Source, filter: PrimaryKeyPrefixFilterRef) -> PrimaryKeyPrefixReader {
+        PrimaryKeyPrefixReader { source, filter }
+    }
+}
+
+#[async_trait]
+impl BatchReader for PrimaryKeyPrefixReader {
+    async fn next_batch(&mut self) -> Result<Option<Batch>> {
+        while let Some(batch) = self.source.next_batch().await? {
+            if self.filter.matches(batch.primary_key())? {
+                return Ok(Some(batch));
+            }
+        }
+
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use api::v1::OpType;
+
+    use super::*;
+    use crate::test_util::sst_util::{new_primary_key, new_source, sst_region_metadata};
+    use crate::test_util::{check_reader_result, new_batch};
+
+    fn new_filter(values: &[&str], string_prefix: Option<&str>) -> PrimaryKeyPrefixFilter {
+        let prefix = PrimaryKeyPrefix {
+            values: values.iter().map(|v| Value::from(*v)).collect(),
+            string_prefix: string_prefix.map(|s| s.to_string()),
+        };
+        PrimaryKeyPrefixFilter::new(&sst_region_metadata(), &prefix)
+            .unwrap()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_invalid_prefix() {
+        let metadata = sst_region_metadata();
+        let prefix = PrimaryKeyPrefix::default();
+        assert!(PrimaryKeyPrefixFilter::new(&metadata, &prefix)
+            .unwrap()
+            .is_none());
+
+        let prefix = PrimaryKeyPrefix {
+            values: vec![Value::from("a"), Value::from("b")],
+            string_prefix: Some("c".to_string()),
+        };
+        assert!(PrimaryKeyPrefixFilter::new(&metadata, &prefix).is_err());
+
+        let prefix = PrimaryKeyPrefix {
+            values: vec![Value::Int64(1)],
+            string_prefix: None,
+        };
+        assert!(PrimaryKeyPrefixFilter::new(&metadata, &prefix).is_err());
+    }
+
+    #[test]
+    fn test_prefix_matches() {
+        let filter = new_filter(&["a"], Some("b"));
+        assert!(filter.matches(&new_primary_key(&["a", "b"])).unwrap());
+        assert!(filter.matches(&new_primary_key(&["a", "bcd"])).unwrap());
+        assert!(!filter.matches(&new_primary_key(&["a", "cb"])).unwrap());
+        assert!(!filter.matches(&new_primary_key(&["ab", "b"])).unwrap());
+
+        let filter = new_filter(&[], Some("ab"));
+        assert!(filter.matches(&new_primary_key(&["abc", "x"])).unwrap());
+        assert!(!filter.matches(&new_primary_key(&["a", "x"])).unwrap());
+    }
+
+    #[test]
+    fn test_may_match_range() {
+        let filter = new_filter(&["b"], Some("x"));
+        let check = |min: &[&str], max: &[&str]| {
+            filter
+                .may_match_range(&new_primary_key(min), &new_primary_key(max))
+                .unwrap()
+        };
+        assert!(check(&["a", "a"], &["c", "a"]));
+        assert!(check(&["b", "xy"], &["b", "xz"]));
+        assert!(check(&["b", "a"], &["b", "x"]));
+        // All keys are less than the prefix.
+        assert!(!check(&["a", "a"], &["b", "w"]));
+        // All keys are greater than the prefix.
+        assert!(!check(&["b", "y"], &["c", "a"]));
+        assert!(!check(&["c", "x"], &["d", "x"]));
+    }
+
+    #[tokio::test]
+    async fn test_pk_prefix_reader() {
+        let source = new_source(&[
+            new_batch(
+                &new_primary_key(&["a", "b"]),
+                &[1, 2],
+                &[11, 12],
+                &[OpType::Put, OpType::Put],
+                &[21, 22],
+            ),
+            new_batch(
+                &new_primary_key(&["b", "a"]),
+                &[1],
+                &[13],
+                &[OpType::Put],
+                &[23],
+            ),
+            new_batch(
+                &new_primary_key(&["b", "c"]),
+                &[2],
+                &[14],
+                &[OpType::Put],
+                &[24],
+            ),
+        ]);
+        let mut reader = PrimaryKeyPrefixReader::new(source, Arc::new(new_filter(&["b"], None)));
+        check_reader_result(
+            &mut reader,
+            &[
+                new_batch(
+                    &new_primary_key(&["b", "a"]),
+                    &[1],
+                    &[13],
+                    &[OpType::Put],
+                    &[23],
+                ),
+                new_batch(
+                    &new_primary_key(&["b", "c"]),
+                    &[2],
+                    &[14],
+                    &[OpType::Put],
+                    &[24],
+                ),
+            ],
+        )
+        .await;
+    }
+}
@@ -44,8 +44,9 @@ pub struct ProjectionMapper {
     metadata: RegionMetadataRef,
     /// Maps column in [RecordBatch] to index in [Batch].
     batch_indices: Vec<BatchIndex>,
-    /// Output record batch contains tags.
-    has_tags: bool,
+    /// Number of leftmost primary key columns to decode. The output record batch
+    /// doesn't contain tags if it is 0.
+    num_decoded_tags: usize,
     /// Decoder for primary key.
     codec: McmpRowCodec,
     /// Schema for converted [RecordBatch].
@@ -99,7 +100,7 @@ impl ProjectionMapper {
             .collect();
         // For each projected column, compute its index in batches.
         let mut batch_indices = Vec::with_capacity(projection.len());
-        let mut has_tags = false;
+        let mut num_decoded_tags = 0;
         for idx in &projection {
             // Safety: idx is valid.
             let column = &metadata.column_metadatas[*idx];
@@ -108,8 +109,8 @@ impl ProjectionMapper {
                 SemanticType::Tag => {
                     // Safety: It is a primary key column.
                     let index = metadata.primary_key_index(column.column_id).unwrap();
-                    // We need to output a tag.
-                    has_tags = true;
+                    // We need to decode the primary key until this tag.
+                    num_decoded_tags = num_decoded_tags.max(index + 1);
                     // We always read all primary key so the column always exists and the tag
                     // index is always valid.
                     BatchIndex::Tag(index)
@@ -127,7 +128,7 @@ impl ProjectionMapper {
         Ok(ProjectionMapper {
             metadata: metadata.clone(),
             batch_indices,
-            has_tags,
+            num_decoded_tags,
             codec,
             output_schema,
             column_ids,
@@ -175,10 +176,11 @@ impl ProjectionMapper {
             .zip(batch.fields())
             .all(|(id, batch_col)| *id == batch_col.column_id));
 
-        // Skips decoding pk if we don't need to output it.
-        let pk_values = if self.has_tags {
+        // Skips decoding pk if we don't need to output it and only decodes tags
+        // until the last projected one.
+        let pk_values = if self.num_decoded_tags > 0 {
             self.codec
-                .decode(batch.primary_key())
+                .decode_leftmost(batch.primary_key(), self.num_decoded_tags)
                 .map_err(BoxedError::new)
                 .context(ExternalSnafu)?
         } else {
@@ -387,4 +389,34 @@ mod tests {
 +----+----+";
         assert_eq!(expect, print_record_batch(record_batch));
     }
+
+    #[test]
+    fn test_projection_mapper_without_tags() {
+        let metadata = Arc::new(
+            TestRegionMetadataBuilder::default()
+                .num_tags(2)
+                .num_fields(2)
+                .build(),
+        );
+        // Columns ts, v0
+        let mapper = ProjectionMapper::new(&metadata, [0, 3].into_iter()).unwrap();
+        assert_eq!(0, mapper.num_decoded_tags);
+
+        // The mapper never decodes the primary key so an invalid key doesn't matter.
+        let mut batch = new_batch(0, &[1, 2], &[(3, 3)], 2);
+        batch.set_primary_key(b"invalid".to_vec());
+        let record_batch = mapper.convert(&batch, None).unwrap();
+        let expect = "\
++---------------------+----+
+| ts                  | v0 |
++---------------------+----+
+| 1970-01-01T00:00:00 | 3  |
+| 1970-01-01T00:00:01 | 3  |
++---------------------+----+";
+        assert_eq!(expect, print_record_batch(record_batch));
+
+        // Columns k0, v0
+        let mapper = ProjectionMapper::new(&metadata, [1, 3].into_iter()).unwrap();
+        assert_eq!(1, mapper.num_decoded_tags);
+    }
 }
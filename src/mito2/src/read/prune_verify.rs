@@ -0,0 +1,124 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Verifies pruning by comparing scans with and without pruning.
+//!
+//! Pruning only skips data that can't match the filters, so the number of rows
+//! matching the filters must be the same whether pruning is enabled or not.
+
+use common_query::prelude::Expr;
+use common_recordbatch::SendableRecordBatchStream;
+use datafusion::common::ToDFSchema;
+use datafusion::optimizer::utils::conjunction;
+use datafusion::physical_expr::create_physical_expr;
+use datafusion::physical_expr::execution_props::ExecutionProps;
+use datafusion_common::cast::as_boolean_array;
+use futures::TryStreamExt;
+use snafu::ResultExt;
+use store_api::storage::ScanRequest;
+
+use crate::access_layer::AccessLayerRef;
+use crate::cache::CacheManagerRef;
+use crate::error::{EvaluateFilterSnafu, ReadRecordBatchSnafu, Result};
+use crate::read::scan_region::ScanRegion;
+use crate::region::version::VersionRef;
+
+/// Number of rows matching filters, read with and without pruning.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct PruningCheck {
+    /// Rows matching filters in the scan with pruning.
+    pub(crate) pruned_rows: usize,
+    /// Rows matching filters in the scan without pruning.
+    pub(crate) unpruned_rows: usize,
+}
+
+impl PruningCheck {
+    /// Returns true if pruning doesn't skip any row matching filters.
+    pub(crate) fn is_consistent(&self) -> bool {
+        self.pruned_rows == self.unpruned_rows
+    }
+}
+
+/// Scans the `version` with and without pruning and counts rows matching `filters`.
+pub(crate) async fn check_pruning(
+    version: VersionRef,
+    access_layer: AccessLayerRef,
+    filters: Vec<Expr>,
+    cache_manager: Option<CacheManagerRef>,
+) -> Result<PruningCheck> {
+    let request = ScanRequest {
+        filters: filters.clone(),
+        ..Default::default()
+    };
+
+    let mut counts = [0; 2];
+    for (count, disable_pruning) in counts.iter_mut().zip([false, true]) {
+        let stream = ScanRegion::new(
+            version.clone(),
+            access_layer.clone(),
+            request.clone(),
+            cache_manager.clone(),
+        )
+        .with_disable_pruning(disable_pruning)
+        .scanner()?
+        .scan()
+        .await?;
+        *count = count_matched_rows(stream, &filters).await?;
+    }
+
+    Ok(PruningCheck {
+        pruned_rows: counts[0],
+        unpruned_rows: counts[1],
+    })
+}
+
+/// Counts rows in the `stream` matching all `filters`.
+async fn count_matched_rows(
+    mut stream: SendableRecordBatchStream,
+    filters: &[Expr],
+) -> Result<usize> {
+    let arrow_schema = stream.schema().arrow_schema().clone();
+    let filter = match conjunction(filters.iter().map(|filter| filter.df_expr().clone())) {
+        Some(expr) => {
+            let df_schema = arrow_schema
+                .clone()
+                .to_dfschema_ref()
+                .context(EvaluateFilterSnafu)?;
+            let filter =
+                create_physical_expr(&expr, &df_schema, &arrow_schema, &ExecutionProps::new())
+                    .context(EvaluateFilterSnafu)?;
+            Some(filter)
+        }
+        None => None,
+    };
+
+    let mut rows = 0;
+    while let Some(batch) = stream.try_next().await.context(ReadRecordBatchSnafu)? {
+        let batch = batch.df_record_batch();
+        let Some(filter) = &filter else {
+            rows += batch.num_rows();
+            continue;
+        };
+
+        let matched = filter
+            .evaluate(batch)
+            .context(EvaluateFilterSnafu)?
+            .into_array(batch.num_rows());
+        rows += as_boolean_array(&matched)
+            .context(EvaluateFilterSnafu)?
+            .true_count();
+    }
+
+    Ok(rows)
+}
@@ -0,0 +1,287 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Range tombstones and the reader to remove rows deleted by them.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use datatypes::value::Value;
+use datatypes::vectors::BooleanVector;
+use serde::{Deserialize, Serialize};
+use store_api::metadata::RegionMetadata;
+use store_api::storage::{ColumnId, SequenceNumber};
+
+use crate::error::Result;
+use crate::read::{Batch, BatchReader, Source};
+use crate::row_converter::{McmpRowCodec, RowCodec, SortField};
+use crate::sst::file::FileHandle;
+
+/// A tombstone that deletes rows in a time range.
+///
+/// It only deletes rows written before it, so rows whose sequences are greater than
+/// the sequence of the tombstone are still visible.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct RangeTombstone {
+    /// Inclusive start of the time index in the unit of the time index.
+    pub start: i64,
+    /// Exclusive end of the time index in the unit of the time index.
+    pub end: i64,
+    /// Ids and values of tags to match. Matches all rows in the range if it's empty.
+    pub tags: Vec<(ColumnId, Value)>,
+    /// Deletes rows whose sequences are less than or equal to this sequence.
+    pub sequence: SequenceNumber,
+}
+
+impl RangeTombstone {
+    /// Returns true if the tombstone may delete rows in the `file`.
+    pub(crate) fn overlaps(&self, file: &FileHandle) -> bool {
+        let (start, end) = file.time_range();
+        start.value() < self.end && end.value() >= self.start
+    }
+}
+
+/// A [RangeTombstone] whose tags are resolved to indices in the primary key.
+struct ResolvedTombstone {
+    start: i64,
+    end: i64,
+    tags: Vec<(usize, Value)>,
+    sequence: SequenceNumber,
+}
+
+/// Filter to remove rows deleted by range tombstones.
+pub(crate) struct RangeDeleteFilter {
+    tombstones: Vec<ResolvedTombstone>,
+    /// Codec to decode primary keys. Only used if some tombstones have tags.
+    codec: McmpRowCodec,
+}
+
+pub(crate) type RangeDeleteFilterRef = Arc<RangeDeleteFilter>;
+
+impl RangeDeleteFilter {
+    /// Creates a filter for batches of the region with specific `metadata`.
+    ///
+    /// Returns `None` if no tombstone may delete rows in the region.
+    pub(crate) fn new(
+        metadata: &RegionMetadata,
+        tombstones: &[RangeTombstone],
+    ) -> Option<RangeDeleteFilter> {
+        let tombstones: Vec<_> = tombstones
+            .iter()
+            .filter_map(|tombstone| {
+                let tags = tombstone
+                    .tags
+                    .iter()
+                    .map(|(column_id, value)| {
+                        // A tombstone matches nothing if the tag isn't in the primary key.
+                        let index = metadata.primary_key_index(*column_id)?;
+                        Some((index, value.clone()))
+                    })
+                    .collect::<Option<Vec<_>>>()?;
+                Some(ResolvedTombstone {
+                    start: tombstone.start,
+                    end: tombstone.end,
+                    tags,
+                    sequence: tombstone.sequence,
+                })
+            })
+            .collect();
+        if tombstones.is_empty() {
+            return None;
+        }
+
+        let codec = McmpRowCodec::new(
+            metadata
+                .primary_key_columns()
+                .map(|c| SortField::new(c.column_schema.data_type.clone()))
+                .collect(),
+        );
+        Some(RangeDeleteFilter { tombstones, codec })
+    }
+
+    /// Removes rows deleted by tombstones from the `batch`.
+    pub(crate) fn filter(&self, batch: &mut Batch) -> Result<()> {
+        let Some(timestamps) = batch.timestamps_native() else {
+            return Ok(());
+        };
+        let sequences = batch.sequences().as_arrow().values();
+
+        let mut primary_key = None;
+        // Whether to keep each row. `None` if we keep all rows.
+        let mut keep: Option<Vec<bool>> = None;
+        for tombstone in &self.tombstones {
+            if !tombstone.tags.is_empty() {
+                if primary_key.is_none() {
+                    primary_key = Some(self.codec.decode(batch.primary_key())?);
+                }
+                // Safety: The primary key is decoded.
+                let values = primary_key.as_ref().unwrap();
+                if !tombstone
+                    .tags
+                    .iter()
+                    .all(|(index, value)| values.get(*index) == Some(value))
+                {
+                    continue;
+                }
+            }
+
+            for (i, (ts, sequence)) in timestamps.iter().zip(sequences.iter()).enumerate() {
+                if *ts >= tombstone.start && *ts < tombstone.end && *sequence <= tombstone.sequence
+                {
+                    keep.get_or_insert_with(|| vec![true; timestamps.len()])[i] = false;
+                }
+            }
+        }
+
+        if let Some(keep) = keep {
+            batch.filter(&BooleanVector::from(keep))?;
+        }
+        Ok(())
+    }
+}
+
+/// Reader that removes rows deleted by range tombstones from the source.
+pub(crate) struct RangeDeleteReader {
+    source: Source,
+    filter: RangeDeleteFilterRef,
+}
+
+impl RangeDeleteReader {
+    /// Creates a new reader to filter the `source`.
+    pub(crate) fn new(source: Source, filter: RangeDeleteFilterRef) -> RangeDeleteReader {
+        RangeDeleteReader { source, filter }
+    }
+}
+
+#[async_trait]
+impl BatchReader for RangeDeleteReader {
+    async fn next_batch(&mut self) -> Result<Option<Batch>> {
+        while let Some(mut batch) = self.source.next_batch().await? {
+            self.filter.filter(&mut batch)?;
+            if !batch.is_empty() {
+                return Ok(Some(batch));
+            }
+        }
+
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use api::v1::OpType;
+
+    use super::*;
+    use crate::test_util::sst_util::{new_primary_key, new_source, sst_region_metadata};
+    use crate::test_util::{check_reader_result, new_batch};
+
+    fn new_tombstone(start: i64, end: i64, sequence: SequenceNumber) -> RangeTombstone {
+        RangeTombstone {
+            start,
+            end,
+            tags: Vec::new(),
+            sequence,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_range_delete_reader() {
+        let metadata = sst_region_metadata();
+        let filter = RangeDeleteFilter::new(
+            &metadata,
+            &[new_tombstone(2, 4, 12), new_tombstone(5, 6, 20)],
+        )
+        .unwrap();
+        let source = new_source(&[
+            new_batch(
+                &new_primary_key(&["a", "b"]),
+                &[1, 2, 3, 4],
+                &[11, 12, 13, 14],
+                &[OpType::Put, OpType::Put, OpType::Put, OpType::Put],
+                &[21, 22, 23, 24],
+            ),
+            new_batch(
+                &new_primary_key(&["a", "c"]),
+                &[5],
+                &[15],
+                &[OpType::Put],
+                &[25],
+            ),
+        ]);
+        let mut reader = RangeDeleteReader::new(source, Arc::new(filter));
+        check_reader_result(
+            &mut reader,
+            &[new_batch(
+                &new_primary_key(&["a", "b"]),
+                &[1, 3, 4],
+                &[11, 13, 14],
+                &[OpType::Put, OpType::Put, OpType::Put],
+                &[21, 23, 24],
+            )],
+        )
+        .await;
+    }
+
+    #[tokio::test]
+    async fn test_range_delete_with_tags() {
+        let metadata = sst_region_metadata();
+        let tombstone = RangeTombstone {
+            start: 0,
+            end: 10,
+            tags: vec![(1, Value::String("b".into()))],
+            sequence: 20,
+        };
+        let filter = RangeDeleteFilter::new(&metadata, &[tombstone]).unwrap();
+        let source = new_source(&[
+            new_batch(
+                &new_primary_key(&["a", "b"]),
+                &[1, 2],
+                &[11, 12],
+                &[OpType::Put, OpType::Put],
+                &[21, 22],
+            ),
+            new_batch(
+                &new_primary_key(&["a", "c"]),
+                &[1],
+                &[13],
+                &[OpType::Put],
+                &[23],
+            ),
+        ]);
+        let mut reader = RangeDeleteReader::new(source, Arc::new(filter));
+        check_reader_result(
+            &mut reader,
+            &[new_batch(
+                &new_primary_key(&["a", "c"]),
+                &[1],
+                &[13],
+                &[OpType::Put],
+                &[23],
+            )],
+        )
+        .await;
+    }
+
+    #[test]
+    fn test_unknown_tag() {
+        let metadata = sst_region_metadata();
+        let tombstone = RangeTombstone {
+            start: 0,
+            end: 10,
+            tags: vec![(100, Value::String("b".into()))],
+            sequence: 20,
+        };
+        assert!(RangeDeleteFilter::new(&metadata, &[tombstone]).is_none());
+    }
+}
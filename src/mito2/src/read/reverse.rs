@@ -0,0 +1,109 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Reader to read batches in reverse order.
+
+use async_trait::async_trait;
+
+use crate::error::Result;
+use crate::read::{Batch, BatchReader, Source};
+
+/// Reader that returns batches from a sorted source in reverse order.
+///
+/// It reads all batches from the source on the first call to `next_batch()`, so it
+/// should only wrap sources whose data is already in memory, e.g. memtables.
+pub(crate) struct ReverseReader {
+    /// Source to read. `None` if all batches are read into `batches`.
+    source: Option<Source>,
+    /// Batches read from the source.
+    batches: Vec<Batch>,
+}
+
+impl ReverseReader {
+    /// Creates a new reader to reverse the `source`.
+    pub(crate) fn new(source: Source) -> ReverseReader {
+        ReverseReader {
+            source: Some(source),
+            batches: Vec::new(),
+        }
+    }
+}
+
+#[async_trait]
+impl BatchReader for ReverseReader {
+    async fn next_batch(&mut self) -> Result<Option<Batch>> {
+        if let Some(mut source) = self.source.take() {
+            while let Some(batch) = source.next_batch().await? {
+                self.batches.push(batch);
+            }
+        }
+
+        let Some(mut batch) = self.batches.pop() else {
+            return Ok(None);
+        };
+        batch.reverse()?;
+        Ok(Some(batch))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use api::v1::OpType;
+
+    use super::*;
+    use crate::test_util::{check_reader_result, new_batch, VecBatchReader};
+
+    #[tokio::test]
+    async fn test_reverse_reader() {
+        let source = VecBatchReader::new(&[
+            new_batch(
+                b"k1",
+                &[1, 2],
+                &[11, 12],
+                &[OpType::Put, OpType::Put],
+                &[21, 22],
+            ),
+            new_batch(b"k1", &[3], &[13], &[OpType::Delete], &[23]),
+            new_batch(
+                b"k2",
+                &[1, 2],
+                &[11, 12],
+                &[OpType::Put, OpType::Put],
+                &[31, 32],
+            ),
+        ]);
+        let mut reader = ReverseReader::new(Source::Reader(Box::new(source)));
+        check_reader_result(
+            &mut reader,
+            &[
+                new_batch(
+                    b"k2",
+                    &[2, 1],
+                    &[12, 11],
+                    &[OpType::Put, OpType::Put],
+                    &[32, 31],
+                ),
+                new_batch(b"k1", &[3], &[13], &[OpType::Delete], &[23]),
+                new_batch(
+                    b"k1",
+                    &[2, 1],
+                    &[12, 11],
+                    &[OpType::Put, OpType::Put],
+                    &[22, 21],
+                ),
+            ],
+        )
+        .await;
+    }
+}
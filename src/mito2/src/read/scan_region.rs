@@ -14,17 +14,21 @@
 
 //! Scans a region according to the scan request.
 
-use common_recordbatch::SendableRecordBatchStream;
+use std::sync::Arc;
+
+use common_recordbatch::{OrderOption, SendableRecordBatchStream};
 use common_telemetry::debug;
 use common_time::range::TimestampRange;
-use store_api::storage::ScanRequest;
-use table::predicate::{Predicate, TimeRangePredicateBuilder};
+use store_api::storage::{PrimaryKeyPrefix, ScanRequest};
+use table::predicate::{Predicate, PrimaryKeyPrefixBuilder, TimeRangePredicateBuilder};
 
 use crate::access_layer::AccessLayerRef;
 use crate::cache::CacheManagerRef;
 use crate::error::Result;
+use crate::read::pk_prefix::PrimaryKeyPrefixFilter;
 use crate::read::projection::ProjectionMapper;
 use crate::read::seq_scan::SeqScan;
+use crate::read::unordered_scan::UnorderedScan;
 use crate::region::version::VersionRef;
 use crate::sst::file::FileHandle;
 
@@ -32,6 +36,8 @@ use crate::sst::file::FileHandle;
 pub(crate) enum Scanner {
     /// Sequential scan.
     Seq(SeqScan),
+    /// Unordered scan.
+    Unordered(UnorderedScan),
     // TODO(yingwen): Support windowed scan and chained scan.
 }
 
@@ -40,6 +46,7 @@ impl Scanner {
     pub(crate) async fn scan(&self) -> Result<SendableRecordBatchStream> {
         match self {
             Scanner::Seq(seq_scan) => seq_scan.build_stream().await,
+            Scanner::Unordered(unordered_scan) => unordered_scan.build_stream().await,
         }
     }
 }
@@ -50,6 +57,7 @@ impl Scanner {
     pub(crate) fn num_files(&self) -> usize {
         match self {
             Scanner::Seq(seq_scan) => seq_scan.num_files(),
+            Scanner::Unordered(unordered_scan) => unordered_scan.num_files(),
         }
     }
 
@@ -57,6 +65,7 @@ impl Scanner {
     pub(crate) fn num_memtables(&self) -> usize {
         match self {
             Scanner::Seq(seq_scan) => seq_scan.num_memtables(),
+            Scanner::Unordered(unordered_scan) => unordered_scan.num_memtables(),
         }
     }
 
@@ -64,6 +73,7 @@ impl Scanner {
     pub(crate) fn file_ids(&self) -> Vec<crate::sst::file::FileId> {
         match self {
             Scanner::Seq(seq_scan) => seq_scan.file_ids(),
+            Scanner::Unordered(unordered_scan) => unordered_scan.file_ids(),
         }
     }
 }
@@ -81,10 +91,12 @@ impl Scanner {
 ///     -ScanRequest request
 ///     ~scanner() Scanner
 ///     ~seq_scan() SeqScan
+///     ~unordered_scan() UnorderedScan
 /// }
 /// class Scanner {
 ///     <<enumeration>>
 ///     SeqScan
+///     UnorderedScan
 ///     +scan() SendableRecordBatchStream
 /// }
 /// class SeqScan {
@@ -102,6 +114,8 @@ impl Scanner {
 /// ScanRegion -- Scanner
 /// ScanRegion o-- ScanRequest
 /// Scanner o-- SeqScan
+/// Scanner o-- UnorderedScan
+/// UnorderedScan o-- SeqScan
 /// Scanner -- SendableRecordBatchStream
 /// SeqScan o-- ProjectionMapper
 /// SeqScan -- SendableRecordBatchStream
@@ -117,6 +131,8 @@ pub(crate) struct ScanRegion {
     cache_manager: Option<CacheManagerRef>,
     /// Parallelism to scan.
     parallelism: ScanParallism,
+    /// Disables pruning by filters.
+    disable_pruning: bool,
 }
 
 impl ScanRegion {
@@ -133,6 +149,7 @@ impl ScanRegion {
             request,
             cache_manager,
             parallelism: ScanParallism::default(),
+            disable_pruning: false,
         }
     }
 
@@ -143,14 +160,46 @@ impl ScanRegion {
         self
     }
 
+    /// Sets whether to disable pruning. The scan reads all SSTs, memtables, row groups
+    /// and pages if pruning is disabled.
+    #[must_use]
+    pub(crate) fn with_disable_pruning(mut self, disable_pruning: bool) -> Self {
+        self.disable_pruning = disable_pruning;
+        self
+    }
+
     /// Returns a [Scanner] to scan the region.
     pub(crate) fn scanner(self) -> Result<Scanner> {
-        self.seq_scan().map(Scanner::Seq)
+        if self.use_unordered_scan() {
+            self.unordered_scan().map(Scanner::Unordered)
+        } else {
+            self.seq_scan().map(Scanner::Seq)
+        }
+    }
+
+    /// Scan unordered.
+    pub(crate) fn unordered_scan(self) -> Result<UnorderedScan> {
+        self.seq_scan().map(UnorderedScan::new)
+    }
+
+    /// Returns true if we can scan the region without merging sources.
+    ///
+    /// The unordered scan doesn't merge sources so we only use it for append-only
+    /// regions, whose rows aren't deduplicated, if the request doesn't require ordered
+    /// output.
+    fn use_unordered_scan(&self) -> bool {
+        self.version.options.append_mode
+            && self.request.output_ordering.is_none()
+            && self.parallelism.allow_parallel_scan()
     }
 
     /// Scan sequentially.
     pub(crate) fn seq_scan(self) -> Result<SeqScan> {
-        let time_range = self.build_time_range_predicate();
+        let time_range = if self.disable_pruning {
+            TimestampRange::min_to_max()
+        } else {
+            self.build_time_range_predicate()
+        };
 
         let ssts = &self.version.ssts;
         let mut total_ssts = 0;
@@ -194,24 +243,102 @@ impl ScanRegion {
             total_ssts
         );
 
-        let predicate = Predicate::new(self.request.filters.clone());
+        let predicate =
+            (!self.disable_pruning).then(|| Predicate::new(self.request.filters.clone()));
         // The mapper always computes projected column ids as the schema of SSTs may change.
         let mapper = match &self.request.projection {
             Some(p) => ProjectionMapper::new(&self.version.metadata, p.iter().copied())?,
             None => ProjectionMapper::all(&self.version.metadata)?,
         };
 
+        let pk_prefix_filter = match self.pk_prefix() {
+            Some(prefix) => PrimaryKeyPrefixFilter::new(&self.version.metadata, &prefix)?,
+            None => None,
+        };
+
+        let reverse_ordering = self.reverse_ordering();
+        let reverse = reverse_ordering.is_some();
+        if reverse {
+            // Reads newest files first.
+            files.sort_unstable_by(|a, b| b.time_range().1.cmp(&a.time_range().1));
+        }
+        // Only reports the ordering if the output contains the time index.
+        let output_ordering = reverse_ordering.filter(|ordering| {
+            mapper
+                .output_schema()
+                .column_index_by_name(&ordering.name)
+                .is_some()
+        });
+
         let seq_scan = SeqScan::new(self.access_layer.clone(), mapper)
-            .with_time_range(Some(time_range))
-            .with_predicate(Some(predicate))
+            .with_time_range((!self.disable_pruning).then_some(time_range))
+            .with_predicate(predicate)
             .with_memtables(memtables)
             .with_files(files)
             .with_cache(self.cache_manager)
-            .with_parallelism(self.parallelism);
+            .with_parallelism(self.parallelism)
+            .with_reverse(reverse)
+            .with_dedup(!self.version.options.append_mode)
+            .with_output_ordering(output_ordering.map(|ordering| vec![ordering]))
+            .with_limit(self.scan_limit())
+            .with_range_tombstones(&self.version.range_tombstones)
+            .with_pk_prefix_filter(pk_prefix_filter.map(Arc::new));
 
         Ok(seq_scan)
     }
 
+    /// Returns the requested ordering if we can scan the region in reverse order to
+    /// satisfy it.
+    ///
+    /// Now we only scan regions without primary key in reverse order so the output is
+    /// ordered by time index desc. A reversed scan over a region with primary keys
+    /// returns keys in desc order, which doesn't satisfy `time index desc`, as we
+    /// don't merge series by time index.
+    ///
+    /// The reversed scan reads each memtable into memory before returning the first
+    /// batch (see [ReverseReader](crate::read::reverse::ReverseReader)), so it
+    /// costs more memory than a normal scan if memtables are large. SSTs are still
+    /// read by row groups.
+    fn reverse_ordering(&self) -> Option<OrderOption> {
+        let ordering = self.request.output_ordering.as_ref()?.first()?;
+        let metadata = &self.version.metadata;
+        let time_index = metadata.time_index_column();
+        if metadata.primary_key.is_empty()
+            && ordering.name == time_index.column_schema.name
+            && ordering.options.descending
+        {
+            Some(ordering.clone())
+        } else {
+            None
+        }
+    }
+
+    /// Returns the number of rows the scan needs to return.
+    ///
+    /// The scan doesn't evaluate filters exactly so we can only push down the limit
+    /// if the request has no filter.
+    fn scan_limit(&self) -> Option<usize> {
+        if self.request.filters.is_empty() {
+            self.request.limit
+        } else {
+            None
+        }
+    }
+
+    /// Returns the prefix of primary keys to scan.
+    ///
+    /// Extracts the prefix from filters, e.g. `tag_0 = 'a' AND tag_1 LIKE 'b%'`, if the
+    /// request doesn't specify it.
+    fn pk_prefix(&self) -> Option<PrimaryKeyPrefix> {
+        if self.request.pk_prefix.is_some() {
+            return self.request.pk_prefix.clone();
+        }
+        if self.disable_pruning {
+            return None;
+        }
+        PrimaryKeyPrefixBuilder::new(&self.version.metadata, &self.request.filters).build()
+    }
+
     /// Build time range predicate from filters.
     fn build_time_range_predicate(&self) -> TimestampRange {
         let time_index = self.version.metadata.time_index_column();
@@ -243,7 +370,7 @@ impl ScanParallism {
 }
 
 /// Returns true if the time range of a SST `file` matches the `predicate`.
-fn file_in_range(file: &FileHandle, predicate: &TimestampRange) -> bool {
+pub(crate) fn file_in_range(file: &FileHandle, predicate: &TimestampRange) -> bool {
     if predicate == &TimestampRange::min_to_max() {
         return true;
     }
@@ -14,15 +14,23 @@
 
 //! Sequential scan.
 
-use std::sync::Arc;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
 use std::time::{Duration, Instant};
 
 use async_stream::try_stream;
 use common_error::ext::BoxedError;
 use common_recordbatch::error::ExternalSnafu;
-use common_recordbatch::{RecordBatch, RecordBatchStreamWrapper, SendableRecordBatchStream};
+use common_recordbatch::{
+    OrderOption, RecordBatch, RecordBatchMetrics, RecordBatchStream, SendableRecordBatchStream,
+};
 use common_telemetry::{debug, error};
 use common_time::range::TimestampRange;
+use datatypes::schema::SchemaRef;
+use futures::stream::BoxStream;
+use futures::{Stream, StreamExt};
 use snafu::ResultExt;
 use table::predicate::Predicate;
 use tokio::sync::{mpsc, Semaphore};
@@ -34,15 +42,21 @@ use crate::error::Result;
 use crate::memtable::MemtableRef;
 use crate::metrics::READ_STAGE_ELAPSED;
 use crate::read::compat::{self, CompatReader};
-use crate::read::merge::MergeReaderBuilder;
+use crate::read::merge::{MergeReader, MergeReaderBuilder};
+use crate::read::pk_prefix::{PrimaryKeyPrefixFilterRef, PrimaryKeyPrefixReader};
 use crate::read::projection::ProjectionMapper;
+use crate::read::range_delete::{
+    RangeDeleteFilter, RangeDeleteFilterRef, RangeDeleteReader, RangeTombstone,
+};
+use crate::read::reverse::ReverseReader;
 use crate::read::scan_region::ScanParallism;
 use crate::read::{BatchReader, BoxedBatchReader, BoxedBatchStream, Source};
 use crate::sst::file::FileHandle;
 
 /// Scans a region and returns rows in a sorted sequence.
 ///
-/// The output order is always `order by primary key, time index`.
+/// The output order is `order by primary key, time index` or `order by primary key desc,
+/// time index desc` if the scan is reversed.
 pub struct SeqScan {
     /// Region SST access layer.
     access_layer: AccessLayerRef,
@@ -62,6 +76,18 @@ pub struct SeqScan {
     ignore_file_not_found: bool,
     /// Parallelism to scan data.
     parallelism: ScanParallism,
+    /// Whether to scan data in reverse order.
+    reverse: bool,
+    /// Whether to dedup rows with the same primary key and time index.
+    dedup: bool,
+    /// Ordering of the output to report.
+    output_ordering: Option<Vec<OrderOption>>,
+    /// Maximum number of rows to return.
+    limit: Option<usize>,
+    /// Filter to remove rows deleted by range tombstones.
+    range_delete_filter: Option<RangeDeleteFilterRef>,
+    /// Filter to only read primary keys starting with a prefix.
+    pk_prefix_filter: Option<PrimaryKeyPrefixFilterRef>,
 }
 
 impl SeqScan {
@@ -78,6 +104,12 @@ impl SeqScan {
             cache_manager: None,
             ignore_file_not_found: false,
             parallelism: ScanParallism::default(),
+            reverse: false,
+            dedup: true,
+            output_ordering: None,
+            limit: None,
+            range_delete_filter: None,
+            pk_prefix_filter: None,
         }
     }
 
@@ -130,6 +162,52 @@ impl SeqScan {
         self
     }
 
+    /// Sets whether to scan data in reverse order.
+    #[must_use]
+    pub(crate) fn with_reverse(mut self, reverse: bool) -> Self {
+        self.reverse = reverse;
+        self
+    }
+
+    /// Sets whether to dedup rows with the same primary key and time index.
+    #[must_use]
+    pub(crate) fn with_dedup(mut self, dedup: bool) -> Self {
+        self.dedup = dedup;
+        self
+    }
+
+    /// Sets the ordering of the output stream.
+    #[must_use]
+    pub(crate) fn with_output_ordering(mut self, ordering: Option<Vec<OrderOption>>) -> Self {
+        self.output_ordering = ordering;
+        self
+    }
+
+    /// Sets the maximum number of rows to return.
+    #[must_use]
+    pub(crate) fn with_limit(mut self, limit: Option<usize>) -> Self {
+        self.limit = limit;
+        self
+    }
+
+    /// Sets range tombstones to remove deleted rows.
+    #[must_use]
+    pub(crate) fn with_range_tombstones(mut self, tombstones: &[RangeTombstone]) -> Self {
+        self.range_delete_filter =
+            RangeDeleteFilter::new(self.mapper.metadata(), tombstones).map(Arc::new);
+        self
+    }
+
+    /// Sets the filter to only read primary keys starting with a prefix.
+    #[must_use]
+    pub(crate) fn with_pk_prefix_filter(
+        mut self,
+        filter: Option<PrimaryKeyPrefixFilterRef>,
+    ) -> Self {
+        self.pk_prefix_filter = filter;
+        self
+    }
+
     /// Builds a stream for the query.
     pub async fn build_stream(&self) -> Result<SendableRecordBatchStream> {
         let start = Instant::now();
@@ -137,9 +215,9 @@ impl SeqScan {
         let use_parallel = self.use_parallel_reader();
         // Scans all memtables and SSTs. Builds a merge reader to merge results.
         let mut reader = if use_parallel {
-            self.build_parallel_reader().await?
+            self.build_parallel_reader(&mut metrics).await?
         } else {
-            self.build_reader().await?
+            self.build_merge_reader(&mut metrics).await?
         };
         let elapsed = start.elapsed();
         metrics.build_reader_cost = elapsed;
@@ -149,6 +227,8 @@ impl SeqScan {
         let mapper = self.mapper.clone();
         let cache_manager = self.cache_manager.clone();
         let parallelism = self.parallelism.parallelism;
+        let shared_metrics = Arc::new(Mutex::new(metrics.clone()));
+        let stream_metrics = shared_metrics.clone();
         let stream = try_stream! {
             let cache = cache_manager.as_ref().map(|cache| cache.as_ref());
             while let Some(batch) =
@@ -156,6 +236,8 @@ impl SeqScan {
             {
                 yield batch;
             }
+            metrics.num_output_rows = reader.num_output_rows();
+            metrics.merge_cost = reader.merge_cost();
 
             debug!(
                 "Seq scan finished, region_id: {:?}, metrics: {:?}, use_parallel: {}, parallelism: {}",
@@ -163,28 +245,57 @@ impl SeqScan {
             );
             // Update metrics.
             READ_STAGE_ELAPSED.with_label_values(&["total"]).observe(metrics.scan_cost.as_secs_f64());
+            *stream_metrics.lock().unwrap() = metrics;
         };
-        let stream = Box::pin(RecordBatchStreamWrapper::new(
-            self.mapper.output_schema(),
-            Box::pin(stream),
-        ));
+        let stream = Box::pin(SeqScanStream {
+            schema: self.mapper.output_schema(),
+            stream: Box::pin(stream),
+            metrics: shared_metrics,
+            output_ordering: self.output_ordering.clone(),
+        });
 
         Ok(stream)
     }
 
+    /// Returns the mapper to convert batches.
+    pub(crate) fn mapper(&self) -> &Arc<ProjectionMapper> {
+        &self.mapper
+    }
+
+    /// Returns the cache of the scan.
+    pub(crate) fn cache_manager(&self) -> Option<&CacheManagerRef> {
+        self.cache_manager.as_ref()
+    }
+
+    /// Returns the parallelism of the scan.
+    pub(crate) fn parallelism(&self) -> ScanParallism {
+        self.parallelism
+    }
+
     /// Builds a [BoxedBatchReader] from sequential scan.
     pub async fn build_reader(&self) -> Result<BoxedBatchReader> {
+        let mut metrics = Metrics::default();
+        Ok(Box::new(self.build_merge_reader(&mut metrics).await?))
+    }
+
+    /// Builds a [MergeReader] to merge all memtables and SSTs.
+    async fn build_merge_reader(&self, metrics: &mut Metrics) -> Result<MergeReader> {
         // Scans all memtables and SSTs. Builds a merge reader to merge results.
-        let sources = self.build_sources().await?;
+        let sources = self.build_sources(metrics).await?;
         let mut builder = MergeReaderBuilder::from_sources(sources);
-        Ok(Box::new(builder.build().await?))
+        builder
+            .reverse(self.reverse)
+            .dedup(self.dedup)
+            .limit(self.limit)
+            .build()
+            .await
     }
 
-    /// Builds a [BoxedBatchReader] that can scan memtables and SSTs in parallel.
-    async fn build_parallel_reader(&self) -> Result<BoxedBatchReader> {
+    /// Builds a [MergeReader] that can scan memtables and SSTs in parallel.
+    async fn build_parallel_reader(&self, metrics: &mut Metrics) -> Result<MergeReader> {
         assert!(self.parallelism.allow_parallel_scan());
         // Scall all memtables and SSTs.
-        let sources = self.build_sources().await?;
+        let sources = self.build_sources(metrics).await?;
         let semaphore = Arc::new(Semaphore::new(self.parallelism.parallelism));
         // Spawn a task for each source.
         let sources = sources
@@ -195,16 +306,29 @@ impl SeqScan {
             })
             .collect();
         let mut builder = MergeReaderBuilder::from_sources(sources);
-        Ok(Box::new(builder.build().await?))
+        builder
+            .reverse(self.reverse)
+            .dedup(self.dedup)
+            .limit(self.limit)
+            .build()
+            .await
     }
 
     /// Builds and returns sources to read.
-    async fn build_sources(&self) -> Result<Vec<Source>> {
+    pub(crate) async fn build_sources(&self, metrics: &mut Metrics) -> Result<Vec<Source>> {
         let mut sources = Vec::with_capacity(self.memtables.len() + self.files.len());
         for mem in &self.memtables {
             let iter = mem.iter(Some(self.mapper.column_ids()), self.predicate.clone());
-            sources.push(Source::Iter(iter));
+            if self.reverse {
+                // Memtable iterators can't iterate backward, so the reader buffers all
+                // batches of the memtable.
+                let reader = ReverseReader::new(Source::Iter(iter));
+                sources.push(Source::Reader(Box::new(reader)));
+            } else {
+                sources.push(Source::Iter(iter));
+            }
         }
+        metrics.num_memtables += self.memtables.len();
         for file in &self.files {
             let maybe_reader = self
                 .access_layer
@@ -213,6 +337,9 @@ impl SeqScan {
                 .time_range(self.time_range)
                 .projection(Some(self.mapper.column_ids().to_vec()))
                 .cache(self.cache_manager.clone())
+                .reverse(self.reverse)
+                .pk_prefix_filter(self.pk_prefix_filter.clone())
+                .pruned_pages_counter(Some(metrics.num_pruned_pages.clone()))
                 .build()
                 .await;
             let reader = match maybe_reader {
@@ -226,6 +353,10 @@ impl SeqScan {
                     }
                 }
             };
+            let num_row_groups = reader.num_row_groups();
+            metrics.num_files += 1;
+            metrics.num_row_groups += num_row_groups;
+            metrics.num_pruned_row_groups += num_row_groups - reader.num_read_row_groups();
             if compat::has_same_columns(self.mapper.metadata(), reader.metadata()) {
                 sources.push(Source::Reader(Box::new(reader)));
             } else {
@@ -237,6 +368,28 @@ impl SeqScan {
             }
         }
 
+        if let Some(filter) = &self.pk_prefix_filter {
+            // Memtables and SSTs may still return keys without the prefix.
+            sources = sources
+                .into_iter()
+                .map(|source| {
+                    let reader = PrimaryKeyPrefixReader::new(source, filter.clone());
+                    Source::Reader(Box::new(reader))
+                })
+                .collect();
+        }
+        if let Some(filter) = &self.range_delete_filter {
+            // Removes deleted rows before merging so the merge reader won't count
+            // them into the limit.
+            sources = sources
+                .into_iter()
+                .map(|source| {
+                    let reader = RangeDeleteReader::new(source, filter.clone());
+                    Source::Reader(Box::new(reader))
+                })
+                .collect();
+        }
+
         Ok(sources)
     }
 
@@ -259,7 +412,10 @@ impl SeqScan {
                 };
                 match maybe_batch {
                     Ok(Some(batch)) => {
-                        let _ = sender.send(Ok(batch)).await;
+                        if sender.send(Ok(batch)).await.is_err() {
+                            // The receiver is dropped, e.g. the query reaches its limit.
+                            break;
+                        }
                     }
                     Ok(None) => break,
                     Err(e) => {
@@ -303,14 +459,90 @@ impl SeqScan {
 }
 
 /// Metrics for [SeqScan].
-#[derive(Debug, Default)]
-struct Metrics {
+#[derive(Debug, Default, Clone)]
+pub(crate) struct Metrics {
     /// Duration to build the reader.
     build_reader_cost: Duration,
     /// Duration to scan data.
     scan_cost: Duration,
     /// Duration to convert batches.
     convert_cost: Duration,
+    /// Duration to merge batches, excluding the time to read sources.
+    merge_cost: Duration,
+    /// Number of memtables to scan.
+    num_memtables: usize,
+    /// Number of SST files to scan.
+    num_files: usize,
+    /// Number of row groups in SST files to scan.
+    num_row_groups: usize,
+    /// Number of row groups pruned by min-max statistics.
+    num_pruned_row_groups: usize,
+    /// Number of pages pruned by the time range.
+    ///
+    /// SST readers prune pages and update it while scanning.
+    num_pruned_pages: Arc<AtomicUsize>,
+    /// Number of rows after deduplication.
+    num_output_rows: usize,
+}
+
+impl Metrics {
+    /// Converts the metrics into [RecordBatchMetrics].
+    fn to_record_batch_metrics(&self) -> RecordBatchMetrics {
+        RecordBatchMetrics {
+            counters: vec![
+                ("num_memtables".to_string(), self.num_memtables),
+                ("num_files".to_string(), self.num_files),
+                ("num_row_groups".to_string(), self.num_row_groups),
+                (
+                    "num_pruned_row_groups".to_string(),
+                    self.num_pruned_row_groups,
+                ),
+                (
+                    "num_pruned_pages".to_string(),
+                    self.num_pruned_pages.load(Ordering::Relaxed),
+                ),
+                ("num_output_rows".to_string(), self.num_output_rows),
+            ],
+            elapsed: vec![
+                ("build_reader_cost".to_string(), self.build_reader_cost),
+                ("scan_cost".to_string(), self.scan_cost),
+                ("convert_cost".to_string(), self.convert_cost),
+                ("merge_cost".to_string(), self.merge_cost),
+            ],
+        }
+    }
+}
+
+/// Record batch stream of a [SeqScan] that reports scan metrics.
+struct SeqScanStream {
+    schema: SchemaRef,
+    stream: BoxStream<'static, common_recordbatch::error::Result<RecordBatch>>,
+    /// Metrics of the scan, updated when the scan is finished.
+    metrics: Arc<Mutex<Metrics>>,
+    /// Ordering of the output.
+    output_ordering: Option<Vec<OrderOption>>,
+}
+
+impl Stream for SeqScanStream {
+    type Item = common_recordbatch::error::Result<RecordBatch>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.stream.poll_next_unpin(cx)
+    }
+}
+
+impl RecordBatchStream for SeqScanStream {
+    fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+
+    fn output_ordering(&self) -> Option<&[OrderOption]> {
+        self.output_ordering.as_deref()
+    }
+
+    fn metrics(&self) -> Option<RecordBatchMetrics> {
+        Some(self.metrics.lock().unwrap().to_record_batch_metrics())
+    }
 }
 
 #[cfg(test)]
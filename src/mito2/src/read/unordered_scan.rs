@@ -0,0 +1,171 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Unordered scan.
+
+use std::sync::Arc;
+use std::time::Instant;
+
+use common_error::ext::BoxedError;
+use common_recordbatch::error::ExternalSnafu;
+use common_recordbatch::{RecordBatch, RecordBatchStreamWrapper, SendableRecordBatchStream};
+use common_telemetry::debug;
+use snafu::ResultExt;
+use tokio::sync::{mpsc, Semaphore};
+use tokio_stream::wrappers::ReceiverStream;
+
+use crate::cache::CacheManagerRef;
+use crate::error::Result;
+use crate::metrics::READ_STAGE_ELAPSED;
+use crate::read::projection::ProjectionMapper;
+use crate::read::seq_scan::{Metrics, SeqScan};
+use crate::read::Source;
+
+/// Scans a region without keeping the order of rows.
+///
+/// It reads all memtables and SSTs concurrently and outputs batches without merging
+/// them, so rows from different sources are not deduplicated. Deleted rows are only
+/// removed within each batch. It is only suitable for append-only regions.
+pub struct UnorderedScan {
+    /// Input to scan. We reuse the [SeqScan] to build the sources to read.
+    input: SeqScan,
+}
+
+impl UnorderedScan {
+    /// Creates a new [UnorderedScan] to scan the targets of the `input`.
+    pub(crate) fn new(input: SeqScan) -> UnorderedScan {
+        UnorderedScan { input }
+    }
+
+    /// Builds a stream for the query.
+    pub async fn build_stream(&self) -> Result<SendableRecordBatchStream> {
+        let start = Instant::now();
+        let mut metrics = Metrics::default();
+        let sources = self.input.build_sources(&mut metrics).await?;
+        let parallelism = self.input.parallelism();
+        debug!(
+            "Unordered scan region {}, sources: {}, parallelism: {}, build reader cost: {:?}, metrics: {:?}",
+            self.input.mapper().metadata().region_id,
+            sources.len(),
+            parallelism.parallelism,
+            start.elapsed(),
+            metrics,
+        );
+
+        // Use at least one task to read sources.
+        let semaphore = Arc::new(Semaphore::new(parallelism.parallelism.max(1)));
+        let (sender, receiver) = mpsc::channel(parallelism.channel_size.max(1));
+        for source in sources {
+            Self::spawn_scan_task(
+                source,
+                self.input.mapper().clone(),
+                self.input.cache_manager().cloned(),
+                semaphore.clone(),
+                sender.clone(),
+            );
+        }
+
+        let stream = RecordBatchStreamWrapper::new(
+            self.input.mapper().output_schema(),
+            ReceiverStream::new(receiver),
+        );
+
+        Ok(Box::pin(stream))
+    }
+
+    /// Reads the `input` source in another task and sends converted record batches to the `sender`.
+    fn spawn_scan_task(
+        mut input: Source,
+        mapper: Arc<ProjectionMapper>,
+        cache_manager: Option<CacheManagerRef>,
+        semaphore: Arc<Semaphore>,
+        sender: mpsc::Sender<common_recordbatch::error::Result<RecordBatch>>,
+    ) {
+        tokio::spawn(async move {
+            let start = Instant::now();
+            loop {
+                // We release the permit before sending result to avoid the task waiting on
+                // the channel with the permit holded.
+                let maybe_batch = {
+                    // Safety: We never close the semaphore.
+                    let _permit = semaphore.acquire().await.unwrap();
+                    Self::fetch_record_batch(&mut input, &mapper, cache_manager.as_ref()).await
+                };
+                match maybe_batch {
+                    Ok(Some(batch)) => {
+                        if sender.send(Ok(batch)).await.is_err() {
+                            // The receiver is dropped, e.g. the query reaches its limit.
+                            break;
+                        }
+                    }
+                    Ok(None) => break,
+                    Err(e) => {
+                        let _ = sender.send(Err(e)).await;
+                        break;
+                    }
+                }
+            }
+            READ_STAGE_ELAPSED
+                .with_label_values(&["unordered_scan_source"])
+                .observe(start.elapsed().as_secs_f64());
+        });
+    }
+
+    /// Fetches the next non-empty batch from the source and converts it into a record batch.
+    async fn fetch_record_batch(
+        input: &mut Source,
+        mapper: &ProjectionMapper,
+        cache_manager: Option<&CacheManagerRef>,
+    ) -> common_recordbatch::error::Result<Option<RecordBatch>> {
+        loop {
+            let Some(mut batch) = input
+                .next_batch()
+                .await
+                .map_err(BoxedError::new)
+                .context(ExternalSnafu)?
+            else {
+                return Ok(None);
+            };
+
+            batch
+                .filter_deleted()
+                .map_err(BoxedError::new)
+                .context(ExternalSnafu)?;
+            if batch.is_empty() {
+                continue;
+            }
+
+            let cache = cache_manager.map(|cache| cache.as_ref());
+            return mapper.convert(&batch, cache).map(Some);
+        }
+    }
+}
+
+#[cfg(test)]
+impl UnorderedScan {
+    /// Returns number of memtables to scan.
+    pub(crate) fn num_memtables(&self) -> usize {
+        self.input.num_memtables()
+    }
+
+    /// Returns number of SST files to scan.
+    pub(crate) fn num_files(&self) -> usize {
+        self.input.num_files()
+    }
+
+    /// Returns SST file ids to scan.
+    pub(crate) fn file_ids(&self) -> Vec<crate::sst::file::FileId> {
+        self.input.file_ids()
+    }
+}
@@ -14,8 +14,11 @@
 
 //! Mito region.
 
+pub(crate) mod capture;
+pub mod kv;
 pub(crate) mod opener;
 pub mod options;
+pub mod verify;
 pub(crate) mod version;
 
 use std::collections::HashMap;
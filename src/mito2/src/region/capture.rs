@@ -0,0 +1,98 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Captures files of a region to reproduce a read offline.
+//!
+//! The capture contains all manifest files of the region and the SSTs that a read
+//! with the same filters would scan. Files are laid out like the region directory so
+//! the captured directory can be opened as the region in another engine. Data in
+//! memtables is not captured, callers should flush the region first if it matters.
+
+use common_telemetry::info;
+use futures::TryStreamExt;
+use object_store::services::Fs;
+use object_store::{util, ObjectStore};
+use snafu::ResultExt;
+use store_api::region_engine::RegionCaptureRequest;
+use table::predicate::TimeRangePredicateBuilder;
+
+use crate::error::{OpenDalSnafu, Result};
+use crate::read::scan_region::file_in_range;
+use crate::region::MitoRegionRef;
+
+/// Directory of manifest files in the captured directory.
+const MANIFEST_DIR: &str = "manifest/";
+
+/// Copies the manifest and SSTs of the `region` that the read needs to the target directory.
+///
+/// Returns paths of copied files relative to the target directory.
+pub(crate) async fn capture_region(
+    region: &MitoRegionRef,
+    request: &RegionCaptureRequest,
+) -> Result<Vec<String>> {
+    let mut builder = Fs::default();
+    let _ = builder.root(&request.target_dir);
+    let target = ObjectStore::new(builder).context(OpenDalSnafu)?.finish();
+    let source = region.access_layer.object_store();
+    let mut copied = Vec::new();
+
+    let manifest_store = region.manifest_manager.store().await;
+    if let Some(mut lister) = manifest_store.manifest_lister().await? {
+        while let Some(entry) = lister.try_next().await.context(OpenDalSnafu)? {
+            if entry.path().ends_with('/') {
+                continue;
+            }
+            let path = util::join_path(MANIFEST_DIR, entry.name());
+            copy_file(source, &target, entry.path(), &path).await?;
+            copied.push(path);
+        }
+    }
+
+    let version = region.version();
+    let time_index = version.metadata.time_index_column();
+    let unit = time_index
+        .column_schema
+        .data_type
+        .as_timestamp()
+        .expect("Time index must have timestamp-compatible type")
+        .unit();
+    let time_range =
+        TimeRangePredicateBuilder::new(&time_index.column_schema.name, unit, &request.filters)
+            .build();
+    for level in version.ssts.levels() {
+        for file in level.files() {
+            if !file_in_range(file, &time_range) {
+                continue;
+            }
+            let path = file.file_id().as_parquet();
+            let file_path = file.file_path(region.access_layer.region_dir());
+            copy_file(source, &target, &file_path, &path).await?;
+            copied.push(path);
+        }
+    }
+
+    info!(
+        "Captured {} files of region {} to {}",
+        copied.len(),
+        region.region_id,
+        request.target_dir
+    );
+
+    Ok(copied)
+}
+
+async fn copy_file(source: &ObjectStore, target: &ObjectStore, from: &str, to: &str) -> Result<()> {
+    let data = source.read(from).await.context(OpenDalSnafu)?;
+    target.write(to, data).await.context(OpenDalSnafu)
+}
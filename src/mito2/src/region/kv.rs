@@ -0,0 +1,103 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Engine-private key-value state of a region.
+//!
+//! Features that need to persist small states for a region can store them
+//! in the region's manifest instead of managing their own files. The state is
+//! durable once the manifest is updated and is removed with the region.
+
+use std::marker::PhantomData;
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use snafu::{ensure, ResultExt};
+use store_api::storage::RegionId;
+
+use crate::error::{InvalidRequestSnafu, Result, SerdeJsonSnafu};
+use crate::manifest::action::{RegionKvEdit, RegionManifest};
+
+/// Max size of an encoded value.
+///
+/// The manifest keeps all key-values of the region so they should be small.
+pub const MAX_REGION_KV_VALUE_SIZE: usize = 64 * 1024;
+
+/// A typed key of the region key-value state.
+///
+/// Values of the key are encoded in JSON.
+#[derive(Debug)]
+pub struct RegionKvKey<T> {
+    name: &'static str,
+    _phantom: PhantomData<fn() -> T>,
+}
+
+impl<T> RegionKvKey<T> {
+    /// Returns a new key with specific `name`.
+    ///
+    /// Names should be prefixed by the feature they belong to, e.g. `dedup.window`.
+    pub const fn new(name: &'static str) -> RegionKvKey<T> {
+        RegionKvKey {
+            name,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Returns the name of the key.
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Returns an edit to delete the key.
+    pub(crate) fn delete_edit(&self) -> RegionKvEdit {
+        RegionKvEdit {
+            puts: Default::default(),
+            deletes: vec![self.name.to_string()],
+        }
+    }
+}
+
+impl<T: DeserializeOwned> RegionKvKey<T> {
+    /// Gets the value of the key from the `manifest`.
+    pub(crate) fn get(&self, manifest: &RegionManifest) -> Result<Option<T>> {
+        manifest
+            .kv
+            .get(self.name)
+            .map(|value| serde_json::from_str(value).context(SerdeJsonSnafu))
+            .transpose()
+    }
+}
+
+impl<T: Serialize> RegionKvKey<T> {
+    /// Returns an edit to put the `value` of the key.
+    pub(crate) fn put_edit(&self, region_id: RegionId, value: &T) -> Result<RegionKvEdit> {
+        let value = serde_json::to_string(value).context(SerdeJsonSnafu)?;
+        ensure!(
+            value.len() <= MAX_REGION_KV_VALUE_SIZE,
+            InvalidRequestSnafu {
+                region_id,
+                reason: format!(
+                    "value of key {} is too large, size: {}, max: {}",
+                    self.name,
+                    value.len(),
+                    MAX_REGION_KV_VALUE_SIZE
+                ),
+            }
+        );
+
+        Ok(RegionKvEdit {
+            puts: [(self.name.to_string(), value)].into(),
+            deletes: Vec::new(),
+        })
+    }
+}
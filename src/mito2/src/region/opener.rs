@@ -31,8 +31,12 @@ use store_api::storage::{ColumnId, RegionId};
 
 use crate::access_layer::AccessLayer;
 use crate::cache::CacheManagerRef;
-use crate::config::MitoConfig;
-use crate::error::{EmptyRegionDirSnafu, ObjectStoreNotFoundSnafu, RegionCorruptedSnafu, Result};
+use crate::config::{MitoConfig, WalCorruptionPolicy};
+use crate::error::{
+    EmptyRegionDirSnafu, ObjectStoreNotFoundSnafu, RegionCorruptedSnafu, Result,
+    UnsupportedWalOptionsSnafu,
+};
+use crate::manifest::journal::CommitJournalRef;
 use crate::manifest::manager::{RegionManifestManager, RegionManifestOptions};
 use crate::manifest::storage::manifest_compress_type;
 use crate::memtable::MemtableBuilderRef;
@@ -42,6 +46,7 @@ use crate::region::MitoRegion;
 use crate::region_write_ctx::RegionWriteCtx;
 use crate::request::OptionOutputTx;
 use crate::schedule::scheduler::SchedulerRef;
+use crate::sst::dictionary::SharedDictionary;
 use crate::sst::file_purger::LocalFilePurger;
 use crate::wal::{EntryId, Wal};
 
@@ -56,6 +61,7 @@ pub(crate) struct RegionOpener {
     options: Option<RegionOptions>,
     cache_manager: Option<CacheManagerRef>,
     skip_wal_replay: bool,
+    commit_journal: Option<CommitJournalRef>,
 }
 
 impl RegionOpener {
@@ -77,6 +83,7 @@ impl RegionOpener {
             options: None,
             cache_manager: None,
             skip_wal_replay: false,
+            commit_journal: None,
         }
     }
 
@@ -104,6 +111,12 @@ impl RegionOpener {
         self
     }
 
+    /// Sets the journal to repair the commit interrupted by a crash.
+    pub(crate) fn commit_journal(mut self, commit_journal: Option<CommitJournalRef>) -> Self {
+        self.commit_journal = commit_journal;
+        self
+    }
+
     /// Sets the `skip_wal_replay`.
     pub(crate) fn skip_wal_replay(mut self, skip: bool) -> Self {
         self.skip_wal_replay = skip;
@@ -122,6 +135,14 @@ impl RegionOpener {
         wal: &Wal<S>,
     ) -> Result<MitoRegion> {
         let region_id = self.region_id;
+        let wal_options = &self.options.as_ref().unwrap().wal_options;
+        ensure!(
+            wal.supports_wal_options(wal_options),
+            UnsupportedWalOptionsSnafu {
+                region_id,
+                wal_options: wal_options.clone(),
+            }
+        );
 
         // Tries to open the region.
         match self.maybe_open(config, wal).await {
@@ -162,13 +183,19 @@ impl RegionOpener {
         let manifest_manager =
             RegionManifestManager::new(metadata.clone(), region_manifest_options).await?;
 
-        let mutable = self.memtable_builder.build(&metadata);
+        let mutable = self.memtable_builder.build(&metadata, !options.append_mode);
+        let shared_dictionary = Arc::new(SharedDictionary::new(
+            options.shared_dictionary_columns.clone(),
+        ));
 
         let version = VersionBuilder::new(metadata, mutable)
             .options(options)
             .build();
         let version_control = Arc::new(VersionControl::new(version));
-        let access_layer = Arc::new(AccessLayer::new(self.region_dir, object_store));
+        let access_layer = Arc::new(
+            AccessLayer::new(self.region_dir, object_store)
+                .with_shared_dictionary(shared_dictionary),
+        );
 
         Ok(MitoRegion {
             region_id,
@@ -226,6 +253,13 @@ impl RegionOpener {
     ) -> Result<Option<MitoRegion>> {
         let region_options = self.options.as_ref().unwrap().clone();
         let wal_options = region_options.wal_options.clone();
+        ensure!(
+            wal.supports_wal_options(&wal_options),
+            UnsupportedWalOptionsSnafu {
+                region_id: self.region_id,
+                wal_options: wal_options.clone(),
+            }
+        );
 
         let region_manifest_options = self.manifest_options(config, &region_options)?;
         let Some(manifest_manager) = RegionManifestManager::open(region_manifest_options).await?
@@ -233,24 +267,46 @@ impl RegionOpener {
             return Ok(None);
         };
 
-        let manifest = manifest_manager.manifest().await;
-        let metadata = manifest.metadata.clone();
-
         let region_id = self.region_id;
         let object_store = self.object_store(&region_options.storage)?.clone();
-        let access_layer = Arc::new(AccessLayer::new(self.region_dir.clone(), object_store));
+        let shared_dictionary = Arc::new(SharedDictionary::new(
+            region_options.shared_dictionary_columns.clone(),
+        ));
+        let access_layer = Arc::new(
+            AccessLayer::new(self.region_dir.clone(), object_store)
+                .with_shared_dictionary(shared_dictionary.clone()),
+        );
+        // Only repairs the commit if we are going to replay the WAL. Regions skipping the
+        // WAL replay are followers and mustn't update the manifest.
+        if let Some(journal) = self
+            .commit_journal
+            .as_ref()
+            .filter(|_| !self.skip_wal_replay)
+        {
+            journal
+                .recover(region_id, &manifest_manager, &access_layer)
+                .await?;
+        }
+
+        let manifest = manifest_manager.manifest().await;
+        let metadata = manifest.metadata.clone();
+        // Loads the dictionary after repairing the commit as the commit may add values to it.
+        shared_dictionary.load(&manifest)?;
         let file_purger = Arc::new(LocalFilePurger::new(
             self.scheduler.clone(),
             access_layer.clone(),
             self.cache_manager.clone(),
         ));
-        let mutable = self.memtable_builder.build(&metadata);
+        let mutable = self
+            .memtable_builder
+            .build(&metadata, !region_options.append_mode);
         let version = VersionBuilder::new(metadata, mutable)
             .add_files(file_purger.clone(), manifest.files.values().cloned())
             .flushed_entry_id(manifest.flushed_entry_id)
             .flushed_sequence(manifest.flushed_sequence)
             .truncated_entry_id(manifest.truncated_entry_id)
             .compaction_time_window(manifest.compaction_time_window)
+            .range_tombstones(manifest.range_tombstones.clone())
             .options(region_options)
             .build();
         let flushed_entry_id = version.flushed_entry_id;
@@ -262,8 +318,24 @@ impl RegionOpener {
                 region_id,
                 flushed_entry_id,
                 &version_control,
+                config.wal_corruption_policy,
             )
             .await?;
+
+            // Entries before the flushed entry id are useless now. We notify the log store
+            // to obsolete them in case we failed to do so after the last flush. It's fine
+            // to ignore the error as the log store can still obsolete them later.
+            if flushed_entry_id > 0 {
+                if let Err(e) = wal
+                    .obsolete(region_id, flushed_entry_id, &wal_options)
+                    .await
+                {
+                    warn!(
+                        e; "Failed to obsolete WAL entries of region {} up to {}",
+                        region_id, flushed_entry_id
+                    );
+                }
+            }
         } else {
             info!("Skip the WAL replay for region: {}", region_id);
         }
@@ -370,15 +442,51 @@ pub(crate) async fn replay_memtable<S: LogStore>(
     region_id: RegionId,
     flushed_entry_id: EntryId,
     version_control: &VersionControlRef,
+    corruption_policy: WalCorruptionPolicy,
 ) -> Result<EntryId> {
     let mut rows_replayed = 0;
+    let mut entries_skipped = 0;
+    let mut entries_corrupted = 0;
     // Last entry id should start from flushed entry id since there might be no
     // data in the WAL.
     let mut last_entry_id = flushed_entry_id;
     let mut region_write_ctx = RegionWriteCtx::new(region_id, version_control, wal_options.clone());
-    let mut wal_stream = wal.scan(region_id, flushed_entry_id, wal_options)?;
+    // Entries whose ids `<= flushed_entry_id` are already persisted in SSTs.
+    let mut wal_stream = wal.scan(region_id, flushed_entry_id + 1, wal_options)?;
     while let Some(res) = wal_stream.next().await {
-        let (entry_id, entry) = res?;
+        let (entry_id, entry) = match res {
+            Ok(res) => res,
+            Err(e) => {
+                let Some(entry_id) = e.corrupted_wal_entry_id() else {
+                    return Err(e);
+                };
+                if entry_id <= flushed_entry_id {
+                    warn!(e; "Ignore corrupted WAL entry {} of region {} as it is flushed", entry_id, region_id);
+                    entries_skipped += 1;
+                    continue;
+                }
+                match corruption_policy {
+                    WalCorruptionPolicy::Fail => return Err(e),
+                    WalCorruptionPolicy::Skip => {
+                        warn!(e; "Skip corrupted WAL entry {} of region {}", entry_id, region_id);
+                        entries_corrupted += 1;
+                        last_entry_id = last_entry_id.max(entry_id);
+                        continue;
+                    }
+                    WalCorruptionPolicy::TruncateAtCorruption => {
+                        warn!(e; "Stop replaying WAL of region {} at corrupted entry {}", region_id, entry_id);
+                        entries_corrupted += 1;
+                        break;
+                    }
+                }
+            }
+        };
+        if entry_id <= flushed_entry_id {
+            // The log store may return entries before the start id, e.g. entries
+            // of the same batch.
+            entries_skipped += 1;
+            continue;
+        }
         last_entry_id = last_entry_id.max(entry_id);
         for mutation in entry.mutations {
             rows_replayed += mutation
@@ -395,8 +503,8 @@ pub(crate) async fn replay_memtable<S: LogStore>(
     region_write_ctx.write_memtable();
 
     info!(
-        "Replay WAL for region: {}, rows recovered: {}, last entry id: {}",
-        region_id, rows_replayed, last_entry_id
+        "Replay WAL for region: {}, rows recovered: {}, entries skipped: {}, entries corrupted: {}, flushed entry id: {}, last entry id: {}",
+        region_id, rows_replayed, entries_skipped, entries_corrupted, flushed_entry_id, last_entry_id
     );
     Ok(last_entry_id)
 }
@@ -23,12 +23,14 @@ use serde::Deserialize;
 use serde_json::Value;
 use serde_with::{serde_as, with_prefix, DisplayFromStr};
 use snafu::ResultExt;
+use table::requests::SHARED_DICTIONARY_COLUMNS_KEY;
 
 use crate::error::{Error, JsonOptionsSnafu, Result};
 
 /// Options that affect the entire region.
 ///
 /// Users need to specify the options while creating/opening a region.
+#[serde_as]
 #[derive(Debug, Default, Clone, PartialEq, Eq, Deserialize)]
 #[serde(default)]
 pub struct RegionOptions {
@@ -41,6 +43,15 @@ pub struct RegionOptions {
     pub storage: Option<String>,
     /// Wal options.
     pub wal_options: WalOptions,
+    /// Whether the region only appends rows.
+    ///
+    /// An append-only region keeps all rows with the same primary key and timestamp.
+    /// Scans that don't require ordered output don't merge rows of the region.
+    #[serde_as(as = "DisplayFromStr")]
+    pub append_mode: bool,
+    /// String fields whose values are encoded by a dictionary shared by all SSTs
+    /// of the region.
+    pub shared_dictionary_columns: Vec<String>,
 }
 
 impl TryFrom<&HashMap<String, String>> for RegionOptions {
@@ -65,11 +76,26 @@ impl TryFrom<&HashMap<String, String>> for RegionOptions {
             },
         )?;
 
+        // Column names are case sensitive so we don't get them from the lowercase json.
+        let shared_dictionary_columns = options_map
+            .get(SHARED_DICTIONARY_COLUMNS_KEY)
+            .map(|columns| {
+                columns
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|column| !column.is_empty())
+                    .map(str::to_string)
+                    .collect()
+            })
+            .unwrap_or_default();
+
         Ok(RegionOptions {
             ttl: options.ttl,
             compaction,
             storage: options.storage,
             wal_options,
+            append_mode: options.append_mode,
+            shared_dictionary_columns,
         })
     }
 }
@@ -134,6 +160,7 @@ impl Default for TwcsOptions {
 
 /// We need to define a new struct without enum fields as `#[serde(default)]` does not
 /// support external tagging.
+#[serde_as]
 #[derive(Debug, Deserialize)]
 #[serde(default)]
 struct RegionOptionsWithoutEnum {
@@ -141,6 +168,8 @@ struct RegionOptionsWithoutEnum {
     #[serde(with = "humantime_serde")]
     ttl: Option<Duration>,
     storage: Option<String>,
+    #[serde_as(as = "DisplayFromStr")]
+    append_mode: bool,
 }
 
 impl Default for RegionOptionsWithoutEnum {
@@ -149,6 +178,7 @@ impl Default for RegionOptionsWithoutEnum {
         RegionOptionsWithoutEnum {
             ttl: options.ttl,
             storage: options.storage,
+            append_mode: options.append_mode,
         }
     }
 }
@@ -215,6 +245,20 @@ mod tests {
         assert_eq!(expect, options);
     }
 
+    #[test]
+    fn test_with_append_mode() {
+        let map = make_map(&[("append_mode", "true")]);
+        let options = RegionOptions::try_from(&map).unwrap();
+        let expect = RegionOptions {
+            append_mode: true,
+            ..Default::default()
+        };
+        assert_eq!(expect, options);
+
+        let map = make_map(&[("append_mode", "invalid")]);
+        assert!(RegionOptions::try_from(&map).is_err());
+    }
+
     #[test]
     fn test_without_compaction_type() {
         // If `compaction.type` is not provided, we ignore all compaction
@@ -283,6 +327,8 @@ mod tests {
             ("compaction.twcs.time_window", "2h"),
             ("compaction.type", "twcs"),
             ("storage", "S3"),
+            ("append_mode", "true"),
+            (SHARED_DICTIONARY_COLUMNS_KEY, "level, Status"),
             (
                 WAL_OPTIONS_KEY,
                 &serde_json::to_string(&wal_options).unwrap(),
@@ -298,6 +344,8 @@ mod tests {
             }),
             storage: Some("s3".to_string()),
             wal_options,
+            append_mode: true,
+            shared_dictionary_columns: vec!["level".to_string(), "Status".to_string()],
         };
         assert_eq!(expect, options);
     }
@@ -0,0 +1,343 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Consistency checker of a region.
+//!
+//! The checker reads every memtable and SST of a region and validates invariants
+//! the read path relies on. It is useful to confirm the region is healthy after
+//! failover or manual repair.
+
+use std::cmp::Ordering;
+use std::fmt;
+
+use common_telemetry::info;
+use store_api::storage::{ColumnId, RegionId, SequenceNumber};
+
+use crate::error::Result;
+use crate::memtable::MemtableId;
+use crate::read::{Batch, Source};
+use crate::region::MitoRegionRef;
+use crate::sst::file::{FileHandle, FileId};
+
+/// Max number of issues to keep in a report.
+const MAX_REPORT_ISSUES: usize = 100;
+
+/// A memtable or SST file of a region.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VerifySource {
+    Memtable(MemtableId),
+    File(FileId),
+}
+
+impl fmt::Display for VerifySource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VerifySource::Memtable(id) => write!(f, "memtable {id}"),
+            VerifySource::File(id) => write!(f, "file {id}"),
+        }
+    }
+}
+
+/// A violated invariant of a region.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VerifyIssue {
+    /// A file in the manifest doesn't exist in the object store.
+    FileNotFound { file_id: FileId },
+    /// Rows are not sorted by primary key, timestamp and sequence in desc order.
+    UnsortedRows {
+        source: VerifySource,
+        timestamp: i64,
+        sequence: SequenceNumber,
+    },
+    /// Rows with the same primary key, timestamp and sequence.
+    DuplicateRows {
+        source: VerifySource,
+        timestamp: i64,
+        sequence: SequenceNumber,
+    },
+    /// A row whose sequence is greater than the committed sequence of the region.
+    SequenceOutOfRange {
+        source: VerifySource,
+        sequence: SequenceNumber,
+        committed_sequence: SequenceNumber,
+    },
+    /// Time range of rows in a file doesn't match the range in the manifest.
+    TimeRangeMismatch {
+        file_id: FileId,
+        expected: (i64, i64),
+        actual: Option<(i64, i64)>,
+    },
+}
+
+impl fmt::Display for VerifyIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VerifyIssue::FileNotFound { file_id } => write!(f, "file {file_id} not found"),
+            VerifyIssue::UnsortedRows {
+                source,
+                timestamp,
+                sequence,
+            } => write!(
+                f,
+                "unsorted row in {source}, timestamp: {timestamp}, sequence: {sequence}"
+            ),
+            VerifyIssue::DuplicateRows {
+                source,
+                timestamp,
+                sequence,
+            } => write!(
+                f,
+                "duplicate row in {source}, timestamp: {timestamp}, sequence: {sequence}"
+            ),
+            VerifyIssue::SequenceOutOfRange {
+                source,
+                sequence,
+                committed_sequence,
+            } => write!(
+                f,
+                "sequence {sequence} in {source} is greater than committed sequence {committed_sequence}"
+            ),
+            VerifyIssue::TimeRangeMismatch {
+                file_id,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "time range of file {file_id} is {actual:?}, expect {expected:?}"
+            ),
+        }
+    }
+}
+
+/// Report of verifying a region.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RegionVerifyReport {
+    pub region_id: RegionId,
+    /// Number of memtables verified.
+    pub num_memtables: usize,
+    /// Number of SST files verified.
+    pub num_files: usize,
+    /// Number of rows verified.
+    pub num_rows: usize,
+    /// Total number of issues found.
+    pub num_issues: usize,
+    /// Issues found. At most [MAX_REPORT_ISSUES] issues are kept.
+    pub issues: Vec<VerifyIssue>,
+}
+
+impl RegionVerifyReport {
+    fn new(region_id: RegionId) -> RegionVerifyReport {
+        RegionVerifyReport {
+            region_id,
+            num_memtables: 0,
+            num_files: 0,
+            num_rows: 0,
+            num_issues: 0,
+            issues: Vec::new(),
+        }
+    }
+
+    /// Returns true if no issue is found.
+    pub fn is_healthy(&self) -> bool {
+        self.num_issues == 0
+    }
+
+    fn add_issue(&mut self, issue: VerifyIssue) {
+        self.num_issues += 1;
+        if self.issues.len() < MAX_REPORT_ISSUES {
+            self.issues.push(issue);
+        }
+    }
+}
+
+impl fmt::Display for RegionVerifyReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "region {}: {} memtables, {} files, {} rows, {} issues",
+            self.region_id, self.num_memtables, self.num_files, self.num_rows, self.num_issues
+        )?;
+        for issue in &self.issues {
+            writeln!(f, "  {issue}")?;
+        }
+        if self.issues.len() < self.num_issues {
+            writeln!(f, "  ... {} more", self.num_issues - self.issues.len())?;
+        }
+        Ok(())
+    }
+}
+
+/// Verifies invariants of the `region`.
+pub(crate) async fn verify_region(region: &MitoRegionRef) -> Result<RegionVerifyReport> {
+    let version_data = region.version_control.current();
+    let version = &version_data.version;
+    let committed_sequence = version_data.committed_sequence;
+    // We only need the primary key, timestamp and sequence of rows.
+    let projection = vec![version.metadata.time_index_column().column_id];
+
+    let mut report = RegionVerifyReport::new(region.region_id);
+    for memtable in version.memtables.list_memtables() {
+        let source = Source::Iter(memtable.iter(Some(&projection), None));
+        let mut checker = SourceChecker::new(VerifySource::Memtable(memtable.id()));
+        checker
+            .check(source, committed_sequence, &mut report)
+            .await?;
+        report.num_memtables += 1;
+    }
+
+    for file in version.ssts.levels().iter().flat_map(|level| level.files()) {
+        verify_file(region, file, &projection, committed_sequence, &mut report).await?;
+        report.num_files += 1;
+    }
+
+    info!("Verified region {}, report: {}", region.region_id, report);
+
+    Ok(report)
+}
+
+/// Verifies the `file` exists and its rows match the file meta.
+async fn verify_file(
+    region: &MitoRegionRef,
+    file: &FileHandle,
+    projection: &[ColumnId],
+    committed_sequence: SequenceNumber,
+    report: &mut RegionVerifyReport,
+) -> Result<()> {
+    let reader = match region
+        .access_layer
+        .read_sst(file.clone())
+        .projection(Some(projection.to_vec()))
+        .build()
+        .await
+    {
+        Ok(reader) => reader,
+        Err(e) if e.is_object_not_found() => {
+            report.add_issue(VerifyIssue::FileNotFound {
+                file_id: file.file_id(),
+            });
+            return Ok(());
+        }
+        Err(e) => return Err(e),
+    };
+
+    let mut checker = SourceChecker::new(VerifySource::File(file.file_id()));
+    checker
+        .check(Source::Reader(Box::new(reader)), committed_sequence, report)
+        .await?;
+
+    let (start, end) = file.time_range();
+    let expected = (start.value(), end.value());
+    if checker.time_range != Some(expected) {
+        report.add_issue(VerifyIssue::TimeRangeMismatch {
+            file_id: file.file_id(),
+            expected,
+            actual: checker.time_range,
+        });
+    }
+    Ok(())
+}
+
+/// Checks rows of a source.
+struct SourceChecker {
+    source: VerifySource,
+    /// Primary key, timestamp and sequence of the last row.
+    last: Option<(Vec<u8>, i64, SequenceNumber)>,
+    /// Min and max timestamps of rows.
+    time_range: Option<(i64, i64)>,
+}
+
+impl SourceChecker {
+    fn new(source: VerifySource) -> SourceChecker {
+        SourceChecker {
+            source,
+            last: None,
+            time_range: None,
+        }
+    }
+
+    async fn check(
+        &mut self,
+        mut source: Source,
+        committed_sequence: SequenceNumber,
+        report: &mut RegionVerifyReport,
+    ) -> Result<()> {
+        while let Some(batch) = source.next_batch().await? {
+            self.check_batch(&batch, committed_sequence, report);
+            report.num_rows += batch.num_rows();
+        }
+        Ok(())
+    }
+
+    fn check_batch(
+        &mut self,
+        batch: &Batch,
+        committed_sequence: SequenceNumber,
+        report: &mut RegionVerifyReport,
+    ) {
+        let Some(timestamps) = batch.timestamps_native() else {
+            return;
+        };
+        let sequences = batch.sequences().as_arrow().values();
+        let primary_key = batch.primary_key();
+        // Rows in a batch have the same primary key so we only compare the key
+        // of the first row with the last row of the previous batch.
+        let mut prev = self.last.as_ref().map(|(last_key, last_ts, last_seq)| {
+            (last_key.as_slice().cmp(primary_key), *last_ts, *last_seq)
+        });
+        for (&timestamp, &sequence) in timestamps.iter().zip(sequences.iter()) {
+            if sequence > committed_sequence {
+                report.add_issue(VerifyIssue::SequenceOutOfRange {
+                    source: self.source,
+                    sequence,
+                    committed_sequence,
+                });
+            }
+
+            if let Some((key_ordering, prev_ts, prev_seq)) = prev {
+                // Rows are sorted by primary key and timestamp in asc order, then
+                // sequence in desc order.
+                let ordering = key_ordering
+                    .then(prev_ts.cmp(&timestamp))
+                    .then(sequence.cmp(&prev_seq));
+                match ordering {
+                    Ordering::Less => (),
+                    Ordering::Equal => report.add_issue(VerifyIssue::DuplicateRows {
+                        source: self.source,
+                        timestamp,
+                        sequence,
+                    }),
+                    Ordering::Greater => report.add_issue(VerifyIssue::UnsortedRows {
+                        source: self.source,
+                        timestamp,
+                        sequence,
+                    }),
+                }
+            }
+            prev = Some((Ordering::Equal, timestamp, sequence));
+        }
+
+        // Safety: The batch isn't empty since it has timestamps.
+        let (min, max) = (
+            *timestamps.iter().min().unwrap(),
+            *timestamps.iter().max().unwrap(),
+        );
+        self.time_range = Some(match self.time_range {
+            Some((start, end)) => (start.min(min), end.max(max)),
+            None => (min, max),
+        });
+        if let Some((_, last_ts, last_seq)) = prev {
+            self.last = Some((primary_key.to_vec(), last_ts, last_seq));
+        }
+    }
+}
@@ -32,6 +32,7 @@ use store_api::storage::SequenceNumber;
 use crate::manifest::action::RegionEdit;
 use crate::memtable::version::{MemtableVersion, MemtableVersionRef};
 use crate::memtable::{MemtableBuilderRef, MemtableId, MemtableRef};
+use crate::read::range_delete::RangeTombstone;
 use crate::region::options::RegionOptions;
 use crate::sst::file::FileMeta;
 use crate::sst::file_purger::FilePurgerRef;
@@ -81,7 +82,7 @@ impl VersionControl {
         if version.memtables.mutable.is_empty() {
             return;
         }
-        let new_mutable = builder.build(&version.metadata);
+        let new_mutable = builder.build(&version.metadata, !version.options.append_mode);
         // Safety: Immutable memtable is None.
         let new_memtables = version.memtables.freeze_mutable(new_mutable).unwrap();
         // Create a new version with memtable switched.
@@ -114,10 +115,36 @@ impl VersionControl {
         version_data.version = new_version;
     }
 
+    /// Adds a range tombstone to current version.
+    pub(crate) fn add_range_tombstone(&self, tombstone: RangeTombstone) {
+        let version = self.current().version;
+        let new_version = Arc::new(
+            VersionBuilder::from_version(version)
+                .add_range_tombstone(tombstone)
+                .build(),
+        );
+
+        let mut version_data = self.data.write().unwrap();
+        version_data.version = new_version;
+    }
+
+    /// Removes range tombstones from current version.
+    pub(crate) fn remove_range_tombstones(&self, tombstones: &[RangeTombstone]) {
+        let version = self.current().version;
+        let new_version = Arc::new(
+            VersionBuilder::from_version(version)
+                .remove_range_tombstones(tombstones)
+                .build(),
+        );
+
+        let mut version_data = self.data.write().unwrap();
+        version_data.version = new_version;
+    }
+
     /// Mark all opened files as deleted and set the delete marker in [VersionControlData]
     pub(crate) fn mark_dropped(&self, memtable_builder: &MemtableBuilderRef) {
         let version = self.current().version;
-        let new_mutable = memtable_builder.build(&version.metadata);
+        let new_mutable = memtable_builder.build(&version.metadata, !version.options.append_mode);
 
         let mut data = self.data.write().unwrap();
         data.is_dropped = true;
@@ -133,8 +160,8 @@ impl VersionControl {
     /// It replaces existing mutable memtable with a memtable that uses the
     /// new schema. Memtables of the version must be empty.
     pub(crate) fn alter_schema(&self, metadata: RegionMetadataRef, builder: &MemtableBuilderRef) {
-        let new_mutable = builder.build(&metadata);
         let version = self.current().version;
+        let new_mutable = builder.build(&metadata, !version.options.append_mode);
         debug_assert!(version.memtables.mutable.is_empty());
         debug_assert!(version.memtables.immutables().is_empty());
         let new_version = Arc::new(
@@ -157,12 +184,13 @@ impl VersionControl {
     ) {
         let version = self.current().version;
 
-        let new_mutable = memtable_builder.build(&version.metadata);
+        let new_mutable = memtable_builder.build(&version.metadata, !version.options.append_mode);
         let new_version = Arc::new(
             VersionBuilder::new(version.metadata.clone(), new_mutable)
                 .flushed_entry_id(truncated_entry_id)
                 .flushed_sequence(truncated_sequence)
                 .truncated_entry_id(Some(truncated_entry_id))
+                .options(version.options.clone())
                 .build(),
         );
 
@@ -217,6 +245,8 @@ pub(crate) struct Version {
     pub(crate) compaction_time_window: Option<Duration>,
     /// Options of the region.
     pub(crate) options: RegionOptions,
+    /// Tombstones of deleted time ranges.
+    pub(crate) range_tombstones: Vec<RangeTombstone>,
 }
 
 pub(crate) type VersionRef = Arc<Version>;
@@ -231,6 +261,7 @@ pub(crate) struct VersionBuilder {
     truncated_entry_id: Option<EntryId>,
     compaction_time_window: Option<Duration>,
     options: RegionOptions,
+    range_tombstones: Vec<RangeTombstone>,
 }
 
 impl VersionBuilder {
@@ -245,6 +276,7 @@ impl VersionBuilder {
             truncated_entry_id: None,
             compaction_time_window: None,
             options: RegionOptions::default(),
+            range_tombstones: Vec::new(),
         }
     }
 
@@ -259,6 +291,7 @@ impl VersionBuilder {
             truncated_entry_id: version.truncated_entry_id,
             compaction_time_window: version.compaction_time_window,
             options: version.options.clone(),
+            range_tombstones: version.range_tombstones.clone(),
         }
     }
 
@@ -304,6 +337,25 @@ impl VersionBuilder {
         self
     }
 
+    /// Sets range tombstones.
+    pub(crate) fn range_tombstones(mut self, tombstones: Vec<RangeTombstone>) -> Self {
+        self.range_tombstones = tombstones;
+        self
+    }
+
+    /// Adds a range tombstone.
+    pub(crate) fn add_range_tombstone(mut self, tombstone: RangeTombstone) -> Self {
+        self.range_tombstones.push(tombstone);
+        self
+    }
+
+    /// Removes range tombstones.
+    pub(crate) fn remove_range_tombstones(mut self, tombstones: &[RangeTombstone]) -> Self {
+        self.range_tombstones
+            .retain(|tombstone| !tombstones.contains(tombstone));
+        self
+    }
+
     /// Apply edit to the builder.
     pub(crate) fn apply_edit(mut self, edit: RegionEdit, file_purger: FilePurgerRef) -> Self {
         if let Some(entry_id) = edit.flushed_entry_id {
@@ -359,6 +411,7 @@ impl VersionBuilder {
             truncated_entry_id: self.truncated_entry_id,
             compaction_time_window: self.compaction_time_window,
             options: self.options,
+            range_tombstones: self.range_tombstones,
         }
     }
 }
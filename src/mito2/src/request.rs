@@ -33,8 +33,8 @@ use store_api::metadata::{ColumnMetadata, RegionMetadata};
 use store_api::region_engine::SetReadonlyResponse;
 use store_api::region_request::{
     AffectedRows, RegionAlterRequest, RegionCatchupRequest, RegionCloseRequest,
-    RegionCompactRequest, RegionCreateRequest, RegionDropRequest, RegionFlushRequest,
-    RegionOpenRequest, RegionRequest, RegionTruncateRequest,
+    RegionCompactRequest, RegionCreateRequest, RegionDeleteRangeRequest, RegionDropRequest,
+    RegionFlushRequest, RegionOpenRequest, RegionRequest, RegionTruncateRequest,
 };
 use store_api::storage::{RegionId, SequenceNumber};
 use tokio::sync::oneshot::{self, Receiver, Sender};
@@ -45,6 +45,8 @@ use crate::error::{
 };
 use crate::memtable::MemtableId;
 use crate::metrics::COMPACTION_ELAPSED_TOTAL;
+use crate::read::range_delete::RangeTombstone;
+use crate::region::MitoRegionRef;
 use crate::sst::file::FileMeta;
 use crate::sst::file_purger::{FilePurgerRef, PurgeRequest};
 use crate::wal::EntryId;
@@ -555,11 +557,50 @@ impl WorkerRequest {
                 sender: sender.into(),
                 request: DdlRequest::Catchup(v),
             }),
+            RegionRequest::DeleteRange(v) => WorkerRequest::Ddl(SenderDdlRequest {
+                region_id,
+                sender: sender.into(),
+                request: DdlRequest::DeleteRange(v),
+            }),
         };
 
         Ok((worker_request, receiver))
     }
 
+    /// Creates a request to ingest files into the region.
+    pub(crate) fn new_ingest(
+        region_id: RegionId,
+        request: IngestFiles,
+    ) -> (WorkerRequest, Receiver<Result<AffectedRows>>) {
+        let (sender, receiver) = oneshot::channel();
+
+        (
+            WorkerRequest::Ddl(SenderDdlRequest {
+                region_id,
+                sender: sender.into(),
+                request: DdlRequest::Ingest(request),
+            }),
+            receiver,
+        )
+    }
+
+    /// Creates a request to alter the region after validating its data.
+    pub(crate) fn new_validated_alter(
+        region_id: RegionId,
+        request: ValidatedAlter,
+    ) -> (WorkerRequest, Receiver<Result<AffectedRows>>) {
+        let (sender, receiver) = oneshot::channel();
+
+        (
+            WorkerRequest::Ddl(SenderDdlRequest {
+                region_id,
+                sender: sender.into(),
+                request: DdlRequest::ValidatedAlter(request),
+            }),
+            receiver,
+        )
+    }
+
     pub(crate) fn new_set_readonly_gracefully(
         region_id: RegionId,
     ) -> (WorkerRequest, Receiver<SetReadonlyResponse>) {
@@ -584,6 +625,27 @@ pub(crate) enum DdlRequest {
     Compact(RegionCompactRequest),
     Truncate(RegionTruncateRequest),
     Catchup(RegionCatchupRequest),
+    DeleteRange(RegionDeleteRangeRequest),
+    Ingest(IngestFiles),
+    ValidatedAlter(ValidatedAlter),
+}
+
+/// Request to add ingested SST files to a region.
+#[derive(Debug)]
+pub(crate) struct IngestFiles {
+    /// Meta of SSTs already copied into the region directory.
+    pub(crate) file_metas: Vec<FileMeta>,
+    /// Number of rows in these files.
+    pub(crate) num_rows: usize,
+}
+
+/// Request to alter a region whose data has been validated.
+#[derive(Debug)]
+pub(crate) struct ValidatedAlter {
+    /// The alter request.
+    pub(crate) request: RegionAlterRequest,
+    /// Committed sequence of the data validated.
+    pub(crate) validated_sequence: SequenceNumber,
 }
 
 /// Sender and Ddl request.
@@ -608,6 +670,10 @@ pub(crate) enum BackgroundNotify {
     CompactionFinished(CompactionFinished),
     /// Compaction has failed.
     CompactionFailed(CompactionFailed),
+    /// Region is opened.
+    RegionOpened(RegionOpened),
+    /// Failed to open the region.
+    RegionOpenFailed(RegionOpenFailed),
 }
 
 /// Notifies a flush job is finished.
@@ -665,6 +731,20 @@ pub(crate) struct FlushFailed {
     pub(crate) err: Arc<Error>,
 }
 
+/// Notifies a region is opened in background.
+#[derive(Debug)]
+pub(crate) struct RegionOpened {
+    /// The opened region.
+    pub(crate) region: MitoRegionRef,
+}
+
+/// Notifies opening a region in background is failed.
+#[derive(Debug)]
+pub(crate) struct RegionOpenFailed {
+    /// The error source of the failure.
+    pub(crate) err: Arc<Error>,
+}
+
 /// Notifies a compaction job has finished.
 #[derive(Debug)]
 pub(crate) struct CompactionFinished {
@@ -680,6 +760,8 @@ pub(crate) struct CompactionFinished {
     pub(crate) file_purger: FilePurgerRef,
     /// Inferred Compaction time window.
     pub(crate) compaction_time_window: Option<Duration>,
+    /// Range tombstones that no longer overlap any file after compaction.
+    pub(crate) resolved_tombstones: Vec<RangeTombstone>,
     /// Start time of compaction task.
     pub(crate) start_time: Instant,
 }
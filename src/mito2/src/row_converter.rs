@@ -232,6 +232,17 @@ impl McmpRowCodec {
     pub fn estimated_size(&self) -> usize {
         self.fields.iter().map(|f| f.estimated_size()).sum()
     }
+
+    /// Decodes the leftmost `num_fields` values from bytes and skips remaining fields.
+    pub fn decode_leftmost(&self, bytes: &[u8], num_fields: usize) -> Result<Vec<Value>> {
+        let mut deserializer = Deserializer::new(bytes);
+        let mut values = Vec::with_capacity(num_fields);
+        for f in self.fields.iter().take(num_fields) {
+            let value = f.deserialize(&mut deserializer)?;
+            values.push(value);
+        }
+        Ok(values)
+    }
 }
 
 impl RowCodec for McmpRowCodec {
@@ -257,13 +268,7 @@ impl RowCodec for McmpRowCodec {
     }
 
     fn decode(&self, bytes: &[u8]) -> Result<Vec<Value>> {
-        let mut deserializer = Deserializer::new(bytes);
-        let mut values = Vec::with_capacity(self.fields.len());
-        for f in &self.fields {
-            let value = f.deserialize(&mut deserializer)?;
-            values.push(value);
-        }
-        Ok(values)
+        self.decode_leftmost(bytes, self.fields.len())
     }
 }
 
@@ -304,6 +309,28 @@ mod tests {
         assert_eq!(&values, &decoded as &[Value]);
     }
 
+    #[test]
+    fn test_decode_leftmost() {
+        let encoder = McmpRowCodec::new(vec![
+            SortField::new(ConcreteDataType::string_datatype()),
+            SortField::new(ConcreteDataType::int64_datatype()),
+            SortField::new(ConcreteDataType::string_datatype()),
+        ]);
+        let values = [
+            Value::String("abc".into()),
+            Value::Int64(128),
+            Value::String("def".into()),
+        ];
+        let value_ref = values.iter().map(|v| v.as_value_ref()).collect::<Vec<_>>();
+        let result = encoder.encode(value_ref.iter().cloned()).unwrap();
+
+        assert!(encoder.decode_leftmost(&result, 0).unwrap().is_empty());
+        let decoded = encoder.decode_leftmost(&result, 2).unwrap();
+        assert_eq!(&values[..2], &decoded as &[Value]);
+        let decoded = encoder.decode_leftmost(&result, 5).unwrap();
+        assert_eq!(&values, &decoded as &[Value]);
+    }
+
     #[test]
     fn test_memcmp_timestamp() {
         check_encode_and_decode(
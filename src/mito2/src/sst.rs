@@ -14,6 +14,7 @@
 
 //! Sorted strings tables.
 
+pub(crate) mod dictionary;
 pub mod file;
 pub mod file_purger;
 pub mod parquet;
@@ -0,0 +1,301 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Dictionaries of string field columns shared by SSTs in a region.
+//!
+//! SSTs store values of the designated columns as `u32` codes instead of building
+//! their own dictionaries. The region persists the dictionaries in its manifest
+//! with the SSTs referencing them. Dictionaries are append-only so codes are stable
+//! across SSTs.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::{Arc, RwLock};
+
+use datatypes::arrow::array::{Array, ArrayRef, StringArray, UInt32Array};
+use snafu::{OptionExt, ResultExt};
+use store_api::metadata::RegionMetadata;
+use store_api::storage::ColumnId;
+
+use crate::error::{InvalidBatchSnafu, InvalidRecordBatchSnafu, Result, SerdeJsonSnafu};
+use crate::manifest::action::{RegionKvEdit, RegionManifest};
+use crate::region::kv::RegionKvKey;
+
+/// Key of the dictionaries in the region manifest.
+pub(crate) const SHARED_DICTIONARY_KEY: RegionKvKey<BTreeMap<ColumnId, Vec<String>>> =
+    RegionKvKey::new("sst.shared_dictionary");
+
+/// Max number of values in the dictionary of a column.
+///
+/// New SSTs don't encode a column whose dictionary is full as the column
+/// probably has a high cardinality.
+pub(crate) const MAX_DICTIONARY_VALUES: usize = 4096;
+
+pub(crate) type SharedDictionaryRef = Arc<SharedDictionary>;
+
+/// Dictionaries shared by SSTs in a region.
+#[derive(Debug, Default)]
+pub(crate) struct SharedDictionary {
+    /// Names of columns to encode.
+    columns: Vec<String>,
+    inner: RwLock<DictionaryInner>,
+}
+
+#[derive(Debug, Default)]
+struct DictionaryInner {
+    /// Dictionaries of encoded columns.
+    dictionaries: HashMap<ColumnId, ColumnDictionary>,
+    /// Number of values in all dictionaries.
+    num_values: usize,
+    /// Number of values persisted in the manifest.
+    num_persisted: usize,
+}
+
+#[derive(Debug, Default)]
+struct ColumnDictionary {
+    /// Values ordered by codes.
+    values: Vec<String>,
+    /// Value to its code.
+    codes: HashMap<String, u32>,
+}
+
+impl ColumnDictionary {
+    fn from_values(values: Vec<String>) -> ColumnDictionary {
+        let codes = values
+            .iter()
+            .enumerate()
+            .map(|(code, value)| (value.clone(), code as u32))
+            .collect();
+        ColumnDictionary { values, codes }
+    }
+
+    /// Returns the code of the `value`, adds the value to the dictionary if it doesn't exist.
+    fn get_or_insert(&mut self, value: &str) -> u32 {
+        if let Some(code) = self.codes.get(value) {
+            return *code;
+        }
+
+        let code = self.values.len() as u32;
+        self.values.push(value.to_string());
+        let _ = self.codes.insert(value.to_string(), code);
+        code
+    }
+}
+
+impl SharedDictionary {
+    /// Returns empty dictionaries to encode `columns`.
+    pub(crate) fn new(columns: Vec<String>) -> SharedDictionary {
+        SharedDictionary {
+            columns,
+            inner: RwLock::default(),
+        }
+    }
+
+    /// Loads dictionaries persisted in the `manifest`.
+    pub(crate) fn load(&self, manifest: &RegionManifest) -> Result<()> {
+        let dictionaries = SHARED_DICTIONARY_KEY.get(manifest)?.unwrap_or_default();
+        let num_values = dictionaries.values().map(Vec::len).sum();
+
+        let mut inner = self.inner.write().unwrap();
+        inner.dictionaries = dictionaries
+            .into_iter()
+            .map(|(column_id, values)| (column_id, ColumnDictionary::from_values(values)))
+            .collect();
+        inner.num_values = num_values;
+        inner.num_persisted = num_values;
+        Ok(())
+    }
+
+    /// Returns ids of columns to encode in a new SST of `metadata`.
+    ///
+    /// Only string fields whose dictionaries aren't full are encoded.
+    pub(crate) fn columns_to_encode(&self, metadata: &RegionMetadata) -> HashSet<ColumnId> {
+        if self.columns.is_empty() {
+            return HashSet::new();
+        }
+
+        let inner = self.inner.read().unwrap();
+        metadata
+            .field_columns()
+            .filter(|column| {
+                self.columns.contains(&column.column_schema.name)
+                    && column.column_schema.data_type.is_string()
+                    && inner
+                        .dictionaries
+                        .get(&column.column_id)
+                        .map_or(true, |dictionary| {
+                            dictionary.values.len() < MAX_DICTIONARY_VALUES
+                        })
+            })
+            .map(|column| column.column_id)
+            .collect()
+    }
+
+    /// Encodes string `array` of the column to codes.
+    ///
+    /// Values not in the dictionary are added to it. An SST encoding the column
+    /// keeps adding values even if the dictionary becomes full.
+    pub(crate) fn encode(&self, column_id: ColumnId, array: &ArrayRef) -> Result<ArrayRef> {
+        let strings = array
+            .as_any()
+            .downcast_ref::<StringArray>()
+            .with_context(|| InvalidBatchSnafu {
+                reason: format!(
+                    "column {} to encode should not be {:?}",
+                    column_id,
+                    array.data_type()
+                ),
+            })?;
+
+        let mut inner = self.inner.write().unwrap();
+        let DictionaryInner {
+            dictionaries,
+            num_values,
+            ..
+        } = &mut *inner;
+        let dictionary = dictionaries.entry(column_id).or_default();
+        let num_values_before = dictionary.values.len();
+        let codes: UInt32Array = strings
+            .iter()
+            .map(|value| value.map(|value| dictionary.get_or_insert(value)))
+            .collect();
+        *num_values += dictionary.values.len() - num_values_before;
+
+        Ok(Arc::new(codes))
+    }
+
+    /// Decodes `array` of codes of the column to strings.
+    pub(crate) fn decode(&self, column_id: ColumnId, array: &ArrayRef) -> Result<ArrayRef> {
+        let codes = array
+            .as_any()
+            .downcast_ref::<UInt32Array>()
+            .with_context(|| InvalidRecordBatchSnafu {
+                reason: format!(
+                    "codes of column {} should not be {:?}",
+                    column_id,
+                    array.data_type()
+                ),
+            })?;
+
+        let inner = self.inner.read().unwrap();
+        let dictionary =
+            inner
+                .dictionaries
+                .get(&column_id)
+                .with_context(|| InvalidRecordBatchSnafu {
+                    reason: format!("dictionary of column {} not found", column_id),
+                })?;
+        let values = codes
+            .iter()
+            .map(|code| {
+                code.map(|code| {
+                    dictionary
+                        .values
+                        .get(code as usize)
+                        .map(String::as_str)
+                        .with_context(|| InvalidRecordBatchSnafu {
+                            reason: format!("code {} of column {} not found", code, column_id),
+                        })
+                })
+                .transpose()
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Arc::new(StringArray::from(values)))
+    }
+
+    /// Returns an edit to persist the dictionaries and the number of values in it if
+    /// some values aren't persisted.
+    ///
+    /// The size of the dictionaries is bounded by [MAX_DICTIONARY_VALUES] so the
+    /// edit doesn't check the size limit of region key-values.
+    pub(crate) fn pending_edit(&self) -> Result<Option<(RegionKvEdit, usize)>> {
+        let inner = self.inner.read().unwrap();
+        if inner.num_values == inner.num_persisted {
+            return Ok(None);
+        }
+
+        let dictionaries: BTreeMap<_, _> = inner
+            .dictionaries
+            .iter()
+            .map(|(column_id, dictionary)| (*column_id, &dictionary.values))
+            .collect();
+        let value = serde_json::to_string(&dictionaries).context(SerdeJsonSnafu)?;
+        let edit = RegionKvEdit {
+            puts: [(SHARED_DICTIONARY_KEY.name().to_string(), value)].into(),
+            deletes: Vec::new(),
+        };
+
+        Ok(Some((edit, inner.num_values)))
+    }
+
+    /// Marks `num_values` values as persisted.
+    pub(crate) fn mark_persisted(&self, num_values: usize) {
+        let mut inner = self.inner.write().unwrap();
+        inner.num_persisted = inner.num_persisted.max(num_values);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn new_string_array(values: &[Option<&str>]) -> ArrayRef {
+        Arc::new(StringArray::from(values.to_vec()))
+    }
+
+    #[test]
+    fn test_encode_decode() {
+        let dictionary = SharedDictionary::new(vec!["status".to_string()]);
+        let array = new_string_array(&[Some("ok"), None, Some("error"), Some("ok")]);
+        let codes = dictionary.encode(1, &array).unwrap();
+        let expect: ArrayRef = Arc::new(UInt32Array::from(vec![Some(0), None, Some(1), Some(0)]));
+        assert_eq!(&expect, &codes);
+        assert_eq!(&array, &dictionary.decode(1, &codes).unwrap());
+
+        // Codes are stable.
+        let array = new_string_array(&[Some("error"), Some("timeout")]);
+        let codes = dictionary.encode(1, &array).unwrap();
+        let expect: ArrayRef = Arc::new(UInt32Array::from(vec![1, 2]));
+        assert_eq!(&expect, &codes);
+
+        // Unknown code and column.
+        let codes: ArrayRef = Arc::new(UInt32Array::from(vec![3]));
+        assert!(dictionary.decode(1, &codes).is_err());
+        assert!(dictionary.decode(2, &codes).is_err());
+    }
+
+    #[test]
+    fn test_pending_edit() {
+        let dictionary = SharedDictionary::new(vec!["status".to_string()]);
+        assert!(dictionary.pending_edit().unwrap().is_none());
+
+        let array = new_string_array(&[Some("ok"), Some("error")]);
+        dictionary.encode(1, &array).unwrap();
+        let (edit, num_values) = dictionary.pending_edit().unwrap().unwrap();
+        assert_eq!(2, num_values);
+        assert_eq!(
+            r#"{"1":["ok","error"]}"#,
+            edit.puts[SHARED_DICTIONARY_KEY.name()]
+        );
+
+        // Values added after taking the edit are still pending.
+        let array = new_string_array(&[Some("timeout")]);
+        dictionary.encode(1, &array).unwrap();
+        dictionary.mark_persisted(num_values);
+        let (_, num_values) = dictionary.pending_edit().unwrap().unwrap();
+        assert_eq!(3, num_values);
+        dictionary.mark_persisted(num_values);
+        assert!(dictionary.pending_edit().unwrap().is_none());
+    }
+}
@@ -23,7 +23,7 @@ use common_time::Timestamp;
 use object_store::util::join_path;
 use serde::{Deserialize, Serialize};
 use snafu::{ResultExt, Snafu};
-use store_api::storage::RegionId;
+use store_api::storage::{RegionId, SequenceNumber};
 use uuid::Uuid;
 
 use crate::sst::file_purger::{FilePurgerRef, PurgeRequest};
@@ -90,6 +90,11 @@ pub struct FileMeta {
     pub level: Level,
     /// Size of the file.
     pub file_size: u64,
+    /// Sequence of all rows in the file, which overrides sequences stored in the file.
+    ///
+    /// Only files ingested from outside have it. See [crate::ingest].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sequence: Option<SequenceNumber>,
 }
 
 /// Handle to a SST file.
@@ -231,6 +236,7 @@ mod tests {
             time_range: FileTimeRange::default(),
             level,
             file_size: 0,
+            sequence: None,
         }
     }
 
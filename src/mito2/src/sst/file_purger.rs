@@ -136,6 +136,7 @@ mod tests {
                     time_range: FileTimeRange::default(),
                     level: 0,
                     file_size: 4096,
+                    sequence: None,
                 },
                 file_purger,
             );
@@ -14,11 +14,13 @@
 
 //! SST in parquet format.
 
+mod dictionary_filter;
 mod format;
 mod page_reader;
 pub mod reader;
 pub mod row_group;
 mod stats;
+mod time_filter;
 pub mod writer;
 
 use common_base::readable_size::ReadableSize;
@@ -27,6 +29,8 @@ use crate::sst::file::FileTimeRange;
 
 /// Key of metadata in parquet SST.
 pub const PARQUET_METADATA_KEY: &str = "greptime:metadata";
+/// Key of ids of columns encoded by the shared dictionary in parquet SST.
+pub const PARQUET_SHARED_DICTIONARY_KEY: &str = "greptime:shared_dictionary_columns";
 const DEFAULT_WRITE_BUFFER_SIZE: ReadableSize = ReadableSize::mb(8);
 /// Default batch size to read parquet files.
 pub(crate) const DEFAULT_READ_BATCH_SIZE: usize = 1024;
@@ -67,6 +71,8 @@ mod tests {
 
     use api::v1::OpType;
     use common_time::Timestamp;
+    use datafusion_expr::{col, lit};
+    use table::predicate::Predicate;
 
     use super::*;
     use crate::cache::{CacheManager, PageKey};
@@ -137,6 +143,47 @@ mod tests {
         .await;
     }
 
+    #[tokio::test]
+    async fn test_read_reverse() {
+        let mut env = TestEnv::new();
+        let object_store = env.init_object_store_manager();
+        let handle = sst_file_handle(0, 1000);
+        let file_path = handle.file_path(FILE_DIR);
+        let metadata = Arc::new(sst_region_metadata());
+        let source = new_source(&[
+            new_batch_by_range(&["a", "d"], 0, 60),
+            new_batch_by_range(&["b", "f"], 0, 40),
+            new_batch_by_range(&["b", "h"], 100, 200),
+        ]);
+        // Use a small row group size for test.
+        let write_opts = WriteOptions {
+            row_group_size: 50,
+            ..Default::default()
+        };
+        let mut writer = ParquetWriter::new(file_path, metadata, source, object_store.clone());
+        writer.write_all(&write_opts).await.unwrap().unwrap();
+
+        let reversed_batch = |tags: &[&str], start, end| {
+            let mut batch = new_batch_by_range(tags, start, end);
+            batch.reverse().unwrap();
+            batch
+        };
+        let builder = ParquetReaderBuilder::new(FILE_DIR.to_string(), handle.clone(), object_store)
+            .reverse(true);
+        let mut reader = builder.build().await.unwrap();
+        check_reader_result(
+            &mut reader,
+            &[
+                reversed_batch(&["b", "h"], 150, 200),
+                reversed_batch(&["b", "h"], 100, 150),
+                reversed_batch(&["b", "f"], 0, 40),
+                reversed_batch(&["a", "d"], 50, 60),
+                reversed_batch(&["a", "d"], 0, 50),
+            ],
+        )
+        .await;
+    }
+
     #[tokio::test]
     async fn test_read_with_cache() {
         let mut env = TestEnv::new();
@@ -195,4 +242,40 @@ mod tests {
         };
         assert!(cache.as_ref().unwrap().get_pages(&page_key).is_none());
     }
+
+    #[tokio::test]
+    async fn test_read_with_dictionary_filter() {
+        let mut env = TestEnv::new();
+        let object_store = env.init_object_store_manager();
+        let handle = sst_file_handle(0, 1000);
+        let file_path = handle.file_path(FILE_DIR);
+        let metadata = Arc::new(sst_region_metadata());
+        let source = new_source(&[
+            new_batch_by_range(&["a", "d"], 0, 60),
+            new_batch_by_range(&["b", "f"], 0, 40),
+            new_batch_by_range(&["b", "h"], 100, 200),
+        ]);
+        // Use a small row group size for test.
+        let write_opts = WriteOptions {
+            row_group_size: 50,
+            ..Default::default()
+        };
+        let mut writer = ParquetWriter::new(file_path, metadata, source, object_store.clone());
+        writer.write_all(&write_opts).await.unwrap().unwrap();
+
+        // The last two row groups only contain the key (b, h).
+        let predicate = Predicate::new(vec![col("tag_1").not_eq(lit("h")).into()]);
+        let builder = ParquetReaderBuilder::new(FILE_DIR.to_string(), handle.clone(), object_store)
+            .predicate(Some(predicate));
+        let mut reader = builder.build().await.unwrap();
+        check_reader_result(
+            &mut reader,
+            &[
+                new_batch_by_range(&["a", "d"], 0, 50),
+                new_batch_by_range(&["a", "d"], 50, 60),
+                new_batch_by_range(&["b", "f"], 0, 40),
+            ],
+        )
+        .await;
+    }
 }
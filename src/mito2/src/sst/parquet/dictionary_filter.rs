@@ -0,0 +1,284 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Filter to prune row groups by dictionaries of primary keys.
+
+use std::sync::Arc;
+
+use bytes::{Buf, Bytes};
+use common_telemetry::warn;
+use datafusion::physical_plan::PhysicalExpr;
+use datafusion_common::ScalarValue;
+use datafusion_expr::ColumnarValue;
+use datatypes::arrow::array::{Array, BooleanArray};
+use datatypes::arrow::datatypes::{Field, Schema, SchemaRef};
+use datatypes::arrow::record_batch::RecordBatch;
+use datatypes::prelude::{ConcreteDataType, DataType, MutableVector, Vector};
+use parquet::arrow::arrow_reader::RowGroups;
+use parquet::basic::Encoding;
+use parquet::column::page::Page;
+use parquet::errors::{ParquetError, Result};
+use store_api::metadata::RegionMetadata;
+use table::predicate::Predicate;
+
+use crate::row_converter::{McmpRowCodec, RowCodec, SortField};
+use crate::sst::parquet::row_group::InMemoryRowGroup;
+
+/// Filter to prune row groups by evaluating predicates on tags against distinct
+/// primary keys in the dictionary of the primary key column.
+///
+/// Min-max statistics can't prune row groups by filters like `tag != 'a'` or
+/// `tag ~ 'a.*'` but the dictionary has all primary keys in a row group, so we
+/// skip the row group if no key in the dictionary matches the filters.
+pub(crate) struct PrimaryKeyDictionaryFilter {
+    /// Codec to decode primary keys.
+    codec: McmpRowCodec,
+    /// Schema of the record batch of decoded primary keys.
+    schema: SchemaRef,
+    /// Data types of primary key columns.
+    data_types: Vec<ConcreteDataType>,
+    /// Physical exprs that only reference primary key columns.
+    exprs: Vec<Arc<dyn PhysicalExpr>>,
+}
+
+impl PrimaryKeyDictionaryFilter {
+    /// Creates a filter for the SST with specific `metadata`.
+    ///
+    /// Returns `None` if no filter in the `predicate` only references primary key columns.
+    pub(crate) fn new(
+        metadata: &RegionMetadata,
+        predicate: &Predicate,
+    ) -> Option<PrimaryKeyDictionaryFilter> {
+        if metadata.primary_key.is_empty() {
+            return None;
+        }
+
+        let fields: Vec<_> = metadata
+            .primary_key_columns()
+            .map(|column| {
+                Field::new(
+                    column.column_schema.name.clone(),
+                    column.column_schema.data_type.as_arrow_type(),
+                    column.column_schema.is_nullable(),
+                )
+            })
+            .collect();
+        let schema = Arc::new(Schema::new(fields));
+        // Filters referencing other columns fail to convert.
+        let exprs = predicate.to_physical_exprs(&schema).ok()?;
+        if exprs.is_empty() {
+            return None;
+        }
+
+        let data_types: Vec<_> = metadata
+            .primary_key_columns()
+            .map(|column| column.column_schema.data_type.clone())
+            .collect();
+        let codec = McmpRowCodec::new(
+            data_types
+                .iter()
+                .map(|data_type| SortField::new(data_type.clone()))
+                .collect(),
+        );
+        Some(PrimaryKeyDictionaryFilter {
+            codec,
+            schema,
+            data_types,
+            exprs,
+        })
+    }
+
+    /// Returns false if no primary key in the `row_group` matches the filters.
+    ///
+    /// `column_idx` is the index of the primary key column and the column must be
+    /// fetched in the row group.
+    pub(crate) fn row_group_may_match(
+        &self,
+        row_group: &InMemoryRowGroup,
+        column_idx: usize,
+    ) -> Result<bool> {
+        let Some(dictionary) = read_dictionary(row_group, column_idx)? else {
+            return Ok(true);
+        };
+        match self.build_record_batch(&dictionary) {
+            Some(batch) => Ok(self.may_match(&batch)),
+            None => Ok(true),
+        }
+    }
+
+    /// Decodes primary keys into a record batch.
+    fn build_record_batch(&self, primary_keys: &[Bytes]) -> Option<RecordBatch> {
+        let mut builders: Vec<_> = self
+            .data_types
+            .iter()
+            .map(|data_type| data_type.create_mutable_vector(primary_keys.len()))
+            .collect();
+        for primary_key in primary_keys {
+            let values = match self.codec.decode(primary_key) {
+                Ok(values) => values,
+                Err(e) => {
+                    warn!(e; "Failed to decode primary key in dictionary");
+                    return None;
+                }
+            };
+            for (builder, value) in builders.iter_mut().zip(&values) {
+                builder.push_value_ref(value.as_value_ref());
+            }
+        }
+        let columns = builders
+            .iter_mut()
+            .map(|builder| builder.to_vector().to_arrow_array())
+            .collect();
+        RecordBatch::try_new(self.schema.clone(), columns).ok()
+    }
+
+    /// Returns false if any filter doesn't match all primary keys in the `batch`.
+    fn may_match(&self, batch: &RecordBatch) -> bool {
+        for expr in &self.exprs {
+            let Ok(result) = expr.evaluate(batch) else {
+                continue;
+            };
+            let matched = match result {
+                ColumnarValue::Array(array) => {
+                    let Some(array) = array.as_any().downcast_ref::<BooleanArray>() else {
+                        continue;
+                    };
+                    // Null results are unknown so we treat them as matched.
+                    array.null_count() > 0 || array.true_count() > 0
+                }
+                ColumnarValue::Scalar(ScalarValue::Boolean(v)) => v.unwrap_or(true),
+                ColumnarValue::Scalar(_) => true,
+            };
+            if !matched {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/// Reads values in the dictionary page of the column at `column_idx`.
+///
+/// Returns `None` if the column has no dictionary or the dictionary doesn't
+/// contain all values, e.g. the writer falls back to plain encoding because the
+/// dictionary is too large.
+fn read_dictionary(row_group: &InMemoryRowGroup, column_idx: usize) -> Result<Option<Vec<Bytes>>> {
+    let Some(page_reader) = row_group.column_chunks(column_idx)?.next() else {
+        return Ok(None);
+    };
+    let mut dictionary = None;
+    for page in page_reader? {
+        match page? {
+            Page::DictionaryPage {
+                buf, num_values, ..
+            } => {
+                dictionary = Some(decode_plain_byte_array(
+                    Bytes::copy_from_slice(buf.as_ref()),
+                    num_values as usize,
+                )?);
+            }
+            Page::DataPage { encoding, .. } | Page::DataPageV2 { encoding, .. } => {
+                if !matches!(
+                    encoding,
+                    Encoding::RLE_DICTIONARY | Encoding::PLAIN_DICTIONARY
+                ) {
+                    // The writer falls back to other encodings.
+                    return Ok(None);
+                }
+            }
+        }
+    }
+
+    Ok(dictionary)
+}
+
+/// Decodes `num_values` binary values encoded in the plain encoding.
+fn decode_plain_byte_array(mut buf: Bytes, num_values: usize) -> Result<Vec<Bytes>> {
+    let mut values = Vec::with_capacity(num_values);
+    for _ in 0..num_values {
+        if buf.remaining() < 4 {
+            return Err(ParquetError::EOF(
+                "Unexpected end of dictionary page".to_string(),
+            ));
+        }
+        let len = buf.get_u32_le() as usize;
+        if buf.remaining() < len {
+            return Err(ParquetError::EOF(
+                "Unexpected end of dictionary page".to_string(),
+            ));
+        }
+        values.push(buf.split_to(len));
+    }
+    Ok(values)
+}
+
+#[cfg(test)]
+mod tests {
+    use datafusion_expr::{col, lit, Expr as DfExpr};
+
+    use super::*;
+    use crate::test_util::sst_util::{new_primary_key, sst_region_metadata};
+
+    fn new_filter(exprs: Vec<DfExpr>) -> Option<PrimaryKeyDictionaryFilter> {
+        let predicate = Predicate::new(exprs.into_iter().map(Into::into).collect());
+        PrimaryKeyDictionaryFilter::new(&sst_region_metadata(), &predicate)
+    }
+
+    fn check_may_match(filter: &PrimaryKeyDictionaryFilter, keys: &[[&str; 2]]) -> bool {
+        let keys: Vec<_> = keys
+            .iter()
+            .map(|key| Bytes::from(new_primary_key(key)))
+            .collect();
+        let batch = filter.build_record_batch(&keys).unwrap();
+        filter.may_match(&batch)
+    }
+
+    #[test]
+    fn test_new_dictionary_filter() {
+        assert!(new_filter(vec![]).is_none());
+        // Filters on fields.
+        assert!(new_filter(vec![col("field_0").gt(lit(1u64))]).is_none());
+        assert!(new_filter(vec![col("tag_0").not_eq(lit("a"))]).is_some());
+    }
+
+    #[test]
+    fn test_dictionary_may_match() {
+        let filter = new_filter(vec![col("tag_0").not_eq(lit("a"))]).unwrap();
+        assert!(!check_may_match(&filter, &[["a", "b"], ["a", "c"]]));
+        assert!(check_may_match(&filter, &[["a", "b"], ["b", "c"]]));
+
+        let filter = new_filter(vec![
+            col("tag_1").like(lit("x%")),
+            col("tag_0").in_list(vec![lit("a"), lit("b")], false),
+        ])
+        .unwrap();
+        assert!(!check_may_match(&filter, &[["a", "b"], ["c", "x"]]));
+        assert!(check_may_match(&filter, &[["a", "b"], ["b", "xy"]]));
+    }
+
+    #[test]
+    fn test_decode_plain_byte_array() {
+        let mut buf = Vec::new();
+        for value in ["a", "", "bcd"] {
+            buf.extend_from_slice(&(value.len() as u32).to_le_bytes());
+            buf.extend_from_slice(value.as_bytes());
+        }
+        let values = decode_plain_byte_array(Bytes::from(buf.clone()), 3).unwrap();
+        assert_eq!(
+            vec![Bytes::from("a"), Bytes::from(""), Bytes::from("bcd")],
+            values
+        );
+        assert!(decode_plain_byte_array(Bytes::from(buf), 4).is_err());
+    }
+}
@@ -25,8 +25,11 @@
 //! ```
 //!
 //! We stores fields in the same order as [RegionMetadata::field_columns()](store_api::metadata::RegionMetadata::field_columns()).
+//!
+//! Fields encoded by the [SharedDictionary](crate::sst::dictionary::SharedDictionary)
+//! store codes of their values. Type: uint32
 
-use std::collections::{HashMap, VecDeque};
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::sync::Arc;
 
 use api::v1::SemanticType;
@@ -45,13 +48,14 @@ use store_api::metadata::{ColumnMetadata, RegionMetadata, RegionMetadataRef};
 use store_api::storage::consts::{
     OP_TYPE_COLUMN_NAME, PRIMARY_KEY_COLUMN_NAME, SEQUENCE_COLUMN_NAME,
 };
-use store_api::storage::ColumnId;
+use store_api::storage::{ColumnId, SequenceNumber};
 
 use crate::error::{
     ConvertVectorSnafu, InvalidBatchSnafu, InvalidRecordBatchSnafu, NewRecordBatchSnafu, Result,
 };
 use crate::read::{Batch, BatchBuilder, BatchColumn};
 use crate::row_converter::{McmpRowCodec, RowCodec, SortField};
+use crate::sst::dictionary::SharedDictionaryRef;
 
 /// Number of columns that have fixed positions.
 ///
@@ -63,6 +67,10 @@ pub(crate) struct WriteFormat {
     metadata: RegionMetadataRef,
     /// SST file schema.
     arrow_schema: SchemaRef,
+    /// Dictionary to encode fields in `encoded_columns`.
+    shared_dictionary: Option<SharedDictionaryRef>,
+    /// Ids of fields to encode.
+    encoded_columns: HashSet<ColumnId>,
 }
 
 impl WriteFormat {
@@ -72,9 +80,23 @@ impl WriteFormat {
         WriteFormat {
             metadata,
             arrow_schema,
+            shared_dictionary: None,
+            encoded_columns: HashSet::new(),
         }
     }
 
+    /// Encodes fields in `columns` by the shared `dictionary`.
+    pub(crate) fn with_shared_dictionary(
+        mut self,
+        dictionary: SharedDictionaryRef,
+        columns: HashSet<ColumnId>,
+    ) -> WriteFormat {
+        self.arrow_schema = to_encoded_arrow_schema(&self.arrow_schema, &self.metadata, &columns);
+        self.shared_dictionary = Some(dictionary);
+        self.encoded_columns = columns;
+        self
+    }
+
     /// Gets the arrow schema to store in parquet.
     pub(crate) fn arrow_schema(&self) -> SchemaRef {
         self.arrow_schema.clone()
@@ -99,7 +121,14 @@ impl WriteFormat {
                 }
             );
 
-            columns.push(column.data.to_arrow_array());
+            let array = column.data.to_arrow_array();
+            if let Some(dictionary) = &self.shared_dictionary
+                && self.encoded_columns.contains(&column.column_id)
+            {
+                columns.push(dictionary.encode(column.column_id, &array)?);
+            } else {
+                columns.push(array);
+            }
         }
         // Add time index column.
         columns.push(batch.timestamps().to_arrow_array());
@@ -119,6 +148,12 @@ pub(crate) struct ReadFormat {
     arrow_schema: SchemaRef,
     // Field column id to its index in `schema` (SST schema).
     field_id_to_index: HashMap<ColumnId, usize>,
+    /// Dictionary to decode fields in `encoded_columns`.
+    shared_dictionary: Option<SharedDictionaryRef>,
+    /// Ids of fields encoded by the shared dictionary.
+    encoded_columns: HashSet<ColumnId>,
+    /// Sequence to replace sequences stored in the file.
+    sequence: Option<SequenceNumber>,
 }
 
 impl ReadFormat {
@@ -135,9 +170,32 @@ impl ReadFormat {
             metadata,
             arrow_schema,
             field_id_to_index,
+            shared_dictionary: None,
+            encoded_columns: HashSet::new(),
+            sequence: None,
         }
     }
 
+    /// Replaces sequences of all rows by `sequence` if it isn't `None`.
+    pub(crate) fn with_sequence(mut self, sequence: Option<SequenceNumber>) -> ReadFormat {
+        self.sequence = sequence;
+        self
+    }
+
+    /// Decodes fields in `columns` by the shared `dictionary`.
+    ///
+    /// The `dictionary` must not be `None` if `columns` is not empty.
+    pub(crate) fn with_shared_dictionary(
+        mut self,
+        dictionary: Option<SharedDictionaryRef>,
+        columns: HashSet<ColumnId>,
+    ) -> ReadFormat {
+        self.arrow_schema = to_encoded_arrow_schema(&self.arrow_schema, &self.metadata, &columns);
+        self.shared_dictionary = dictionary;
+        self.encoded_columns = columns;
+        self
+    }
+
     /// Gets the arrow schema of the SST file.
     ///
     /// This schema is computed from the region metadata but should be the same
@@ -238,10 +296,17 @@ impl ReadFormat {
             let dict_key = keys.value(*start);
             let primary_key = pk_values.value(dict_key.into()).to_vec();
 
+            let sequences = match self.sequence {
+                Some(sequence) => {
+                    Arc::new(UInt64Array::from_value(sequence, rows_in_batch)) as ArrayRef
+                }
+                None => sequence_array.slice(*start, rows_in_batch),
+            };
+
             let mut builder = BatchBuilder::new(primary_key);
             builder
                 .timestamps_array(ts_array.slice(*start, rows_in_batch))?
-                .sequences_array(sequence_array.slice(*start, rows_in_batch))?
+                .sequences_array(sequences)?
                 .op_types_array(op_type_array.slice(*start, rows_in_batch))?;
             // Push all fields
             for batch_column in &field_batch_columns {
@@ -268,6 +333,10 @@ impl ReadFormat {
         match column.semantic_type {
             SemanticType::Tag => self.tag_values(row_groups, column, true),
             SemanticType::Field => {
+                // Statistics of codes are meaningless.
+                if self.encoded_columns.contains(&column_id) {
+                    return None;
+                }
                 let index = self.field_id_to_index.get(&column_id)?;
                 Self::column_values(row_groups, column, *index, true)
             }
@@ -288,6 +357,9 @@ impl ReadFormat {
         match column.semantic_type {
             SemanticType::Tag => self.tag_values(row_groups, column, false),
             SemanticType::Field => {
+                if self.encoded_columns.contains(&column_id) {
+                    return None;
+                }
                 let index = self.field_id_to_index.get(&column_id)?;
                 Self::column_values(row_groups, column, *index, false)
             }
@@ -326,13 +398,26 @@ impl ReadFormat {
             .zip(record_batch.schema().fields())
             .take(record_batch.num_columns() - FIXED_POS_COLUMN_NUM) // Take all field columns.
             .map(|(array, field)| {
-                let vector = Helper::try_into_vector(array.clone()).context(ConvertVectorSnafu)?;
                 let column = self
                     .metadata
                     .column_by_name(field.name())
                     .with_context(|| InvalidRecordBatchSnafu {
                         reason: format!("column {} not found in metadata", field.name()),
                     })?;
+                let array = if self.encoded_columns.contains(&column.column_id) {
+                    self.shared_dictionary
+                        .as_ref()
+                        .with_context(|| InvalidRecordBatchSnafu {
+                            reason: format!(
+                                "shared dictionary is required to decode column {}",
+                                field.name()
+                            ),
+                        })?
+                        .decode(column.column_id, array)?
+                } else {
+                    array.clone()
+                };
+                let vector = Helper::try_into_vector(array).context(ConvertVectorSnafu)?;
 
                 Ok(BatchColumn {
                     column_id: column.column_id,
@@ -475,12 +560,12 @@ impl ReadFormat {
     }
 
     /// Field index of the primary key.
-    fn primary_key_position(&self) -> usize {
+    pub(crate) fn primary_key_position(&self) -> usize {
         self.arrow_schema.fields.len() - 3
     }
 
     /// Field index of the time index.
-    fn time_index_position(&self) -> usize {
+    pub(crate) fn time_index_position(&self) -> usize {
         self.arrow_schema.fields.len() - FIXED_POS_COLUMN_NUM
     }
 }
@@ -509,6 +594,31 @@ fn to_sst_arrow_schema(metadata: &RegionMetadata) -> SchemaRef {
     Arc::new(Schema::new(fields))
 }
 
+/// Returns the arrow schema whose fields in `encoded_columns` store codes of the
+/// [SharedDictionary](crate::sst::dictionary::SharedDictionary).
+fn to_encoded_arrow_schema(
+    schema: &SchemaRef,
+    metadata: &RegionMetadata,
+    encoded_columns: &HashSet<ColumnId>,
+) -> SchemaRef {
+    if encoded_columns.is_empty() {
+        return schema.clone();
+    }
+
+    let fields = Fields::from_iter(schema.fields().iter().map(|field| {
+        let is_encoded = metadata
+            .column_by_name(field.name())
+            .is_some_and(|column| encoded_columns.contains(&column.column_id));
+        if is_encoded {
+            Arc::new(field.as_ref().clone().with_data_type(ArrowDataType::UInt32))
+        } else {
+            field.clone()
+        }
+    }));
+
+    Arc::new(Schema::new(fields))
+}
+
 /// Compute offsets of different primary keys in the array.
 fn primary_key_offsets(pk_dict_array: &DictionaryArray<UInt16Type>) -> Result<Vec<usize>> {
     if pk_dict_array.is_empty() {
@@ -563,15 +673,20 @@ fn new_primary_key_array(primary_key: &[u8], num_rows: usize) -> ArrayRef {
 #[cfg(test)]
 mod tests {
     use api::v1::OpType;
-    use datatypes::arrow::array::{Int64Array, TimestampMillisecondArray, UInt64Array, UInt8Array};
+    use datatypes::arrow::array::{
+        Int64Array, TimestampMillisecondArray, UInt32Array, UInt64Array, UInt8Array,
+    };
     use datatypes::arrow::datatypes::TimeUnit;
     use datatypes::prelude::ConcreteDataType;
     use datatypes::schema::ColumnSchema;
-    use datatypes::vectors::{Int64Vector, TimestampMillisecondVector, UInt64Vector, UInt8Vector};
+    use datatypes::vectors::{
+        Int64Vector, StringVector, TimestampMillisecondVector, UInt64Vector, UInt8Vector,
+    };
     use store_api::metadata::{ColumnMetadata, RegionMetadataBuilder};
     use store_api::storage::RegionId;
 
     use super::*;
+    use crate::sst::dictionary::SharedDictionary;
 
     const TEST_SEQUENCE: u64 = 1;
     const TEST_OP_TYPE: u8 = OpType::Put as u8;
@@ -803,4 +918,95 @@ mod tests {
             batches.into_iter().collect::<Vec<_>>(),
         );
     }
+
+    #[test]
+    fn test_convert_with_sequence() {
+        let metadata = build_test_region_metadata();
+        let read_format = ReadFormat::new(metadata).with_sequence(Some(100));
+
+        let columns: Vec<ArrayRef> = vec![
+            Arc::new(Int64Array::from(vec![1, 1, 10, 10])), // field1
+            Arc::new(Int64Array::from(vec![2, 2, 11, 11])), // field0
+            Arc::new(TimestampMillisecondArray::from(vec![1, 2, 11, 12])), // ts
+            build_test_pk_array(&[(b"one".to_vec(), 2), (b"two".to_vec(), 2)]), // primary key
+            Arc::new(UInt64Array::from(vec![TEST_SEQUENCE; 4])), // sequence
+            Arc::new(UInt8Array::from(vec![TEST_OP_TYPE; 4])), // op type
+        ];
+        let arrow_schema = build_test_arrow_schema();
+        let record_batch = RecordBatch::try_new(arrow_schema, columns).unwrap();
+        let mut batches = VecDeque::new();
+        read_format
+            .convert_record_batch(&record_batch, &mut batches)
+            .unwrap();
+
+        assert_eq!(2, batches.len());
+        for batch in batches {
+            assert_eq!(
+                vec![100, 100],
+                batch.sequences().as_arrow().values().to_vec()
+            );
+        }
+    }
+
+    #[test]
+    fn test_convert_with_shared_dictionary() {
+        let mut builder = RegionMetadataBuilder::new(RegionId::new(1, 1));
+        builder
+            .push_column_metadata(ColumnMetadata {
+                column_schema: ColumnSchema::new("tag0", ConcreteDataType::int64_datatype(), true),
+                semantic_type: SemanticType::Tag,
+                column_id: 1,
+            })
+            .push_column_metadata(ColumnMetadata {
+                column_schema: ColumnSchema::new(
+                    "field0",
+                    ConcreteDataType::string_datatype(),
+                    true,
+                ),
+                semantic_type: SemanticType::Field,
+                column_id: 2,
+            })
+            .push_column_metadata(ColumnMetadata {
+                column_schema: ColumnSchema::new(
+                    "ts",
+                    ConcreteDataType::timestamp_millisecond_datatype(),
+                    false,
+                ),
+                semantic_type: SemanticType::Timestamp,
+                column_id: 3,
+            })
+            .primary_key(vec![1]);
+        let metadata = Arc::new(builder.build().unwrap());
+        let dictionary = Arc::new(SharedDictionary::new(vec!["field0".to_string()]));
+        let encoded_columns = dictionary.columns_to_encode(&metadata);
+        assert_eq!(HashSet::from([2]), encoded_columns);
+
+        let timestamps = Arc::new(TimestampMillisecondVector::from_values([1, 2, 3]));
+        let sequences = Arc::new(UInt64Vector::from_vec(vec![TEST_SEQUENCE; 3]));
+        let op_types = Arc::new(UInt8Vector::from_vec(vec![TEST_OP_TYPE; 3]));
+        let fields = vec![BatchColumn {
+            column_id: 2,
+            data: Arc::new(StringVector::from(vec![Some("ok"), None, Some("ok")])),
+        }];
+        let batch =
+            BatchBuilder::with_required_columns(b"one".to_vec(), timestamps, sequences, op_types)
+                .with_fields(fields)
+                .build()
+                .unwrap();
+
+        let write_format = WriteFormat::new(metadata.clone())
+            .with_shared_dictionary(dictionary.clone(), encoded_columns.clone());
+        let record_batch = write_format.convert_batch(&batch).unwrap();
+        let expect: ArrayRef = Arc::new(UInt32Array::from(vec![Some(0), None, Some(0)]));
+        assert_eq!(&expect, record_batch.column(0));
+
+        let read_format =
+            ReadFormat::new(metadata).with_shared_dictionary(Some(dictionary), encoded_columns);
+        assert_eq!(write_format.arrow_schema(), *read_format.arrow_schema());
+        let mut batches = VecDeque::new();
+        read_format
+            .convert_record_batch(&record_batch, &mut batches)
+            .unwrap();
+        assert_eq!(vec![batch], batches.into_iter().collect::<Vec<_>>());
+    }
 }
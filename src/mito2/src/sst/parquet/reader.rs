@@ -15,6 +15,7 @@
 //! Parquet reader.
 
 use std::collections::{HashSet, VecDeque};
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 
@@ -23,12 +24,14 @@ use common_telemetry::debug;
 use common_time::range::TimestampRange;
 use datatypes::arrow::record_batch::RecordBatch;
 use object_store::ObjectStore;
-use parquet::arrow::arrow_reader::ParquetRecordBatchReader;
+use parquet::arrow::arrow_reader::{
+    ArrowReaderMetadata, ArrowReaderOptions, ParquetRecordBatchReader,
+};
 use parquet::arrow::async_reader::AsyncFileReader;
 use parquet::arrow::{parquet_to_arrow_field_levels, FieldLevels, ProjectionMask};
 use parquet::file::metadata::ParquetMetaData;
 use parquet::format::KeyValue;
-use snafu::{OptionExt, ResultExt};
+use snafu::{ensure, OptionExt, ResultExt};
 use store_api::metadata::{RegionMetadata, RegionMetadataRef};
 use store_api::storage::ColumnId;
 use table::predicate::Predicate;
@@ -37,15 +40,21 @@ use tokio::io::BufReader;
 use crate::cache::CacheManagerRef;
 use crate::error::{
     ArrowReaderSnafu, InvalidMetadataSnafu, InvalidParquetSnafu, OpenDalSnafu, ReadParquetSnafu,
-    Result,
+    Result, SerdeJsonSnafu,
 };
 use crate::metrics::{READ_ROWS_TOTAL, READ_STAGE_ELAPSED};
+use crate::read::pk_prefix::PrimaryKeyPrefixFilterRef;
 use crate::read::{Batch, BatchReader};
+use crate::sst::dictionary::SharedDictionaryRef;
 use crate::sst::file::FileHandle;
+use crate::sst::parquet::dictionary_filter::PrimaryKeyDictionaryFilter;
 use crate::sst::parquet::format::ReadFormat;
 use crate::sst::parquet::row_group::InMemoryRowGroup;
 use crate::sst::parquet::stats::RowGroupPruningStats;
-use crate::sst::parquet::{DEFAULT_READ_BATCH_SIZE, PARQUET_METADATA_KEY};
+use crate::sst::parquet::time_filter::TimeRangeFilter;
+use crate::sst::parquet::{
+    DEFAULT_READ_BATCH_SIZE, PARQUET_METADATA_KEY, PARQUET_SHARED_DICTIONARY_KEY,
+};
 
 /// Parquet SST reader builder.
 pub struct ParquetReaderBuilder {
@@ -64,6 +73,14 @@ pub struct ParquetReaderBuilder {
     projection: Option<Vec<ColumnId>>,
     /// Manager that caches SST data.
     cache_manager: Option<CacheManagerRef>,
+    /// Whether to read the SST in reverse order.
+    reverse: bool,
+    /// Dictionary to decode fields encoded by it.
+    shared_dictionary: Option<SharedDictionaryRef>,
+    /// Filter to prune row groups without primary keys starting with a prefix.
+    pk_prefix_filter: Option<PrimaryKeyPrefixFilterRef>,
+    /// Counter of pages pruned by the time range.
+    pruned_pages: Option<Arc<AtomicUsize>>,
 }
 
 impl ParquetReaderBuilder {
@@ -81,6 +98,10 @@ impl ParquetReaderBuilder {
             time_range: None,
             projection: None,
             cache_manager: None,
+            reverse: false,
+            shared_dictionary: None,
+            pk_prefix_filter: None,
+            pruned_pages: None,
         }
     }
 
@@ -110,6 +131,48 @@ impl ParquetReaderBuilder {
         self
     }
 
+    /// Sets whether to read the SST in reverse order.
+    ///
+    /// The reader reads row groups from the last one and returns batches and
+    /// rows in reverse order if `reverse` is true.
+    pub fn reverse(mut self, reverse: bool) -> ParquetReaderBuilder {
+        self.reverse = reverse;
+        self
+    }
+
+    /// Attaches the shared dictionary of the region to the builder.
+    pub(crate) fn shared_dictionary(
+        mut self,
+        dictionary: Option<SharedDictionaryRef>,
+    ) -> ParquetReaderBuilder {
+        self.shared_dictionary = dictionary;
+        self
+    }
+
+    /// Attaches the filter of the primary key prefix to the builder.
+    ///
+    /// The reader only uses it to prune row groups so it may still return
+    /// keys without the prefix.
+    pub(crate) fn pk_prefix_filter(
+        mut self,
+        filter: Option<PrimaryKeyPrefixFilterRef>,
+    ) -> ParquetReaderBuilder {
+        self.pk_prefix_filter = filter;
+        self
+    }
+
+    /// Attaches the counter of pages pruned by the time range to the builder.
+    ///
+    /// The reader prunes pages while reading each row group so the counter is
+    /// only complete after the scan.
+    pub(crate) fn pruned_pages_counter(
+        mut self,
+        counter: Option<Arc<AtomicUsize>>,
+    ) -> ParquetReaderBuilder {
+        self.pruned_pages = counter;
+        self
+    }
+
     /// Builds and initializes a [ParquetReader].
     ///
     /// This needs to perform IO operation.
@@ -129,6 +192,14 @@ impl ParquetReaderBuilder {
         // Decodes region metadata.
         let key_value_meta = parquet_meta.file_metadata().key_value_metadata();
         let region_meta = Self::get_region_metadata(&file_path, key_value_meta)?;
+        let encoded_columns = Self::get_shared_dictionary_columns(key_value_meta)?;
+        ensure!(
+            encoded_columns.is_empty() || self.shared_dictionary.is_some(),
+            InvalidParquetSnafu {
+                file: &file_path,
+                reason: "shared dictionary is required to read the file",
+            }
+        );
         // Computes column ids to read.
         let column_ids: HashSet<_> = self
             .projection
@@ -141,10 +212,12 @@ impl ParquetReaderBuilder {
                     .map(|c| c.column_id)
                     .collect()
             });
-        let read_format = ReadFormat::new(Arc::new(region_meta));
+        let read_format = ReadFormat::new(Arc::new(region_meta))
+            .with_shared_dictionary(self.shared_dictionary.clone(), encoded_columns)
+            .with_sequence(self.file_handle.meta().sequence);
 
         // Prunes row groups by metadata.
-        let row_groups: VecDeque<_> = if let Some(predicate) = &self.predicate {
+        let mut row_groups: VecDeque<_> = if let Some(predicate) = &self.predicate {
             let stats =
                 RowGroupPruningStats::new(parquet_meta.row_groups(), &read_format, column_ids);
 
@@ -157,6 +230,35 @@ impl ParquetReaderBuilder {
         } else {
             (0..parquet_meta.num_row_groups()).collect()
         };
+        // Prunes row groups by the time range, the reader also prunes pages by it.
+        let time_filter = self.time_range.map(|time_range| {
+            let unit = read_format
+                .metadata()
+                .time_index_column()
+                .column_schema
+                .data_type
+                .as_timestamp()
+                .expect("Time index must have timestamp-compatible type")
+                .unit();
+            TimeRangeFilter::new(read_format.time_index_position(), unit, time_range)
+        });
+        if let Some(time_filter) = &time_filter {
+            row_groups.retain(|idx| time_filter.row_group_in_range(parquet_meta.row_group(*idx)));
+        }
+        // Prunes row groups by min-max primary keys.
+        if let Some(pk_prefix_filter) = &self.pk_prefix_filter
+            && pk_prefix_filter.is_compatible_with(read_format.metadata())
+        {
+            let column_idx = read_format.primary_key_position();
+            row_groups.retain(|idx| {
+                pk_prefix_filter.row_group_may_match(parquet_meta.row_group(*idx), column_idx)
+            });
+        }
+
+        // Filter to prune row groups by primary keys in the dictionary.
+        let dictionary_filter = self.predicate.as_ref().and_then(|predicate| {
+            PrimaryKeyDictionaryFilter::new(read_format.metadata(), predicate)
+        });
 
         // Computes the projection mask.
         let parquet_schema_desc = parquet_meta.file_metadata().schema_descr();
@@ -182,6 +284,10 @@ impl ParquetReaderBuilder {
             projection: projection_mask,
             field_levels,
             cache_manager: self.cache_manager.clone(),
+            time_filter,
+            dictionary_filter,
+            primary_key_position: read_format.primary_key_position(),
+            pruned_pages: self.pruned_pages.clone(),
         };
 
         let metrics = Metrics {
@@ -196,12 +302,13 @@ impl ParquetReaderBuilder {
             reader_builder,
             current_reader: None,
             batches: VecDeque::new(),
+            reverse: self.reverse,
             metrics,
         })
     }
 
     /// Decodes region metadata from key value.
-    fn get_region_metadata(
+    pub(crate) fn get_region_metadata(
         file_path: &str,
         key_value_meta: Option<&Vec<KeyValue>>,
     ) -> Result<RegionMetadata> {
@@ -227,6 +334,25 @@ impl ParquetReaderBuilder {
         RegionMetadata::from_json(json).context(InvalidMetadataSnafu)
     }
 
+    /// Decodes ids of columns encoded by the shared dictionary from key value.
+    fn get_shared_dictionary_columns(
+        key_value_meta: Option<&Vec<KeyValue>>,
+    ) -> Result<HashSet<ColumnId>> {
+        let Some(value) = key_value_meta
+            .and_then(|key_values| {
+                key_values
+                    .iter()
+                    .find(|kv| kv.key == PARQUET_SHARED_DICTIONARY_KEY)
+            })
+            .and_then(|kv| kv.value.as_ref())
+        else {
+            return Ok(HashSet::new());
+        };
+
+        let column_ids: Vec<ColumnId> = serde_json::from_str(value).context(SerdeJsonSnafu)?;
+        Ok(column_ids.into_iter().collect())
+    }
+
     /// Reads parquet metadata of specific file.
     async fn read_parquet_metadata(
         &self,
@@ -240,11 +366,13 @@ impl ParquetReaderBuilder {
             return Ok(metadata);
         }
 
-        // Cache miss, get from the reader.
-        let metadata = reader
-            .get_metadata()
+        // Cache miss, get from the reader. Also loads the page index to prune pages.
+        let options = ArrowReaderOptions::new().with_page_index(true);
+        let metadata = ArrowReaderMetadata::load_async(reader, options)
             .await
-            .context(ReadParquetSnafu { path: file_path })?;
+            .context(ReadParquetSnafu { path: file_path })?
+            .metadata()
+            .clone();
         // Cache the metadata.
         if let Some(cache) = &self.cache_manager {
             cache.put_parquet_meta_data(
@@ -263,6 +391,8 @@ impl ParquetReaderBuilder {
 struct Metrics {
     /// Number of row groups to read.
     read_row_groups: usize,
+    /// Number of row groups pruned by dictionaries of primary keys.
+    dictionary_pruned_row_groups: usize,
     /// Duration to build the parquet reader.
     build_cost: Duration,
     /// Duration to scan the reader.
@@ -293,6 +423,14 @@ struct RowGroupReaderBuilder {
     field_levels: FieldLevels,
     /// Cache.
     cache_manager: Option<CacheManagerRef>,
+    /// Filter to prune pages.
+    time_filter: Option<TimeRangeFilter>,
+    /// Filter to prune row groups by dictionaries of primary keys.
+    dictionary_filter: Option<PrimaryKeyDictionaryFilter>,
+    /// Index of the primary key column in the parquet file.
+    primary_key_position: usize,
+    /// Counter of pages pruned by the time range.
+    pruned_pages: Option<Arc<AtomicUsize>>,
 }
 
 impl RowGroupReaderBuilder {
@@ -302,7 +440,23 @@ impl RowGroupReaderBuilder {
     }
 
     /// Builds a [ParquetRecordBatchReader] to read the row group at `row_group_idx`.
-    async fn build(&mut self, row_group_idx: usize) -> Result<ParquetRecordBatchReader> {
+    ///
+    /// Returns `None` if the dictionary filter prunes the row group.
+    async fn build(&mut self, row_group_idx: usize) -> Result<Option<ParquetRecordBatchReader>> {
+        let selection = self
+            .time_filter
+            .as_ref()
+            .and_then(|time_filter| time_filter.select_pages(&self.parquet_meta, row_group_idx))
+            .map(|(selection, num_pruned)| {
+                if let Some(pruned_pages) = &self.pruned_pages {
+                    pruned_pages.fetch_add(num_pruned, Ordering::Relaxed);
+                }
+                selection
+            });
+        // The cache needs all pages of a column so we only fetch selected pages if the cache
+        // is disabled.
+        let fetch_selection = selection.as_ref().filter(|_| self.cache_manager.is_none());
+
         let mut row_group = InMemoryRowGroup::create(
             self.file_handle.region_id(),
             self.file_handle.file_id(),
@@ -312,25 +466,47 @@ impl RowGroupReaderBuilder {
             &self.file_path,
             self.object_store.clone(),
         );
+        if let Some(dictionary_filter) = &self.dictionary_filter {
+            // Fetches the whole primary key column to read its dictionary. We won't
+            // fetch the column again.
+            let parquet_schema_desc = self.parquet_meta.file_metadata().schema_descr();
+            let pk_projection =
+                ProjectionMask::roots(parquet_schema_desc, [self.primary_key_position]);
+            row_group
+                .fetch(&pk_projection, None)
+                .await
+                .context(ReadParquetSnafu {
+                    path: &self.file_path,
+                })?;
+            let may_match = dictionary_filter
+                .row_group_may_match(&row_group, self.primary_key_position)
+                .context(ReadParquetSnafu {
+                    path: &self.file_path,
+                })?;
+            if !may_match {
+                return Ok(None);
+            }
+        }
         // Fetches data into memory.
         row_group
-            .fetch(&self.projection, None)
+            .fetch(&self.projection, fetch_selection)
             .await
             .context(ReadParquetSnafu {
                 path: &self.file_path,
             })?;
 
         // Builds the parquet reader.
-        // Now the row selection is None.
-        ParquetRecordBatchReader::try_new_with_row_groups(
+        let reader = ParquetRecordBatchReader::try_new_with_row_groups(
             &self.field_levels,
             &row_group,
             DEFAULT_READ_BATCH_SIZE,
-            None,
+            selection,
         )
         .context(ReadParquetSnafu {
             path: &self.file_path,
-        })
+        })?;
+
+        Ok(Some(reader))
     }
 }
 
@@ -351,6 +527,8 @@ pub struct ParquetReader {
     current_reader: Option<ParquetRecordBatchReader>,
     /// Buffered batches to return.
     batches: VecDeque<Batch>,
+    /// Whether to read the SST in reverse order.
+    reverse: bool,
     /// Local metrics.
     metrics: Metrics,
 }
@@ -358,6 +536,10 @@ pub struct ParquetReader {
 #[async_trait]
 impl BatchReader for ParquetReader {
     async fn next_batch(&mut self) -> Result<Option<Batch>> {
+        if self.reverse {
+            return self.next_batch_reverse().await;
+        }
+
         let start = Instant::now();
         if let Some(batch) = self.batches.pop_front() {
             self.metrics.scan_cost += start.elapsed();
@@ -414,6 +596,53 @@ impl ParquetReader {
         self.read_format.metadata()
     }
 
+    /// Returns the number of row groups in the SST.
+    pub(crate) fn num_row_groups(&self) -> usize {
+        self.reader_builder.parquet_meta.num_row_groups()
+    }
+
+    /// Returns the number of row groups to read after pruning.
+    pub(crate) fn num_read_row_groups(&self) -> usize {
+        self.metrics.read_row_groups
+    }
+
+    /// Returns the next batch in reverse order.
+    ///
+    /// Batches in a row group are sorted so we read the whole row group and
+    /// return its batches from the last one.
+    async fn next_batch_reverse(&mut self) -> Result<Option<Batch>> {
+        let start = Instant::now();
+        while self.batches.is_empty() {
+            let Some(row_group_idx) = self.row_groups.pop_back() else {
+                self.metrics.scan_cost += start.elapsed();
+                return Ok(None);
+            };
+
+            let Some(row_group_reader) = self.reader_builder.build(row_group_idx).await? else {
+                self.metrics.dictionary_pruned_row_groups += 1;
+                continue;
+            };
+            let mut converted = VecDeque::new();
+            for record_batch in row_group_reader {
+                let record_batch = record_batch.context(ArrowReaderSnafu {
+                    path: self.reader_builder.file_path(),
+                })?;
+                self.metrics.num_record_batches += 1;
+                self.read_format
+                    .convert_record_batch(&record_batch, &mut converted)?;
+                self.batches.append(&mut converted);
+            }
+            self.metrics.num_batches += self.batches.len();
+        }
+
+        // Safety: batches is not empty.
+        let mut batch = self.batches.pop_back().unwrap();
+        batch.reverse()?;
+        self.metrics.scan_cost += start.elapsed();
+        self.metrics.num_rows += batch.num_rows();
+        Ok(Some(batch))
+    }
+
     /// Tries to fetch next [RecordBatch] from the reader.
     ///
     /// If the reader is exhausted, reads next row group.
@@ -433,7 +662,10 @@ impl ParquetReader {
 
         // No more items in current row group, reads next row group.
         while let Some(row_group_idx) = self.row_groups.pop_front() {
-            let mut row_group_reader = self.reader_builder.build(row_group_idx).await?;
+            let Some(mut row_group_reader) = self.reader_builder.build(row_group_idx).await? else {
+                self.metrics.dictionary_pruned_row_groups += 1;
+                continue;
+            };
             let Some(record_batch) =
                 row_group_reader
                     .next()
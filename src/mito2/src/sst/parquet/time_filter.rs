@@ -0,0 +1,189 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Prunes row groups and pages of an SST by the time range to read.
+
+use common_time::range::TimestampRange;
+use common_time::timestamp::TimeUnit;
+use common_time::Timestamp;
+use parquet::arrow::arrow_reader::{RowSelection, RowSelector};
+use parquet::file::metadata::{ParquetMetaData, RowGroupMetaData};
+use parquet::file::page_index::index::Index;
+use parquet::file::statistics::Statistics;
+
+/// Filter of the time index column.
+pub(crate) struct TimeRangeFilter {
+    /// Index of the time index column in the parquet schema.
+    column_idx: usize,
+    /// Unit of the time index.
+    unit: TimeUnit,
+    /// Time range to read.
+    time_range: TimestampRange,
+}
+
+impl TimeRangeFilter {
+    pub(crate) fn new(column_idx: usize, unit: TimeUnit, time_range: TimestampRange) -> Self {
+        Self {
+            column_idx,
+            unit,
+            time_range,
+        }
+    }
+
+    /// Returns false if the row group has no rows in the time range.
+    pub(crate) fn row_group_in_range(&self, row_group: &RowGroupMetaData) -> bool {
+        match row_group.column(self.column_idx).statistics() {
+            Some(Statistics::Int64(stats)) if stats.has_min_max_set() => {
+                self.in_range(*stats.min(), *stats.max())
+            }
+            _ => true,
+        }
+    }
+
+    /// Selects pages of the row group that may have rows in the time range
+    /// by the page index. Returns the selection and the number of pruned pages.
+    ///
+    /// Returns `None` if the SST has no page index or all pages are selected.
+    pub(crate) fn select_pages(
+        &self,
+        parquet_meta: &ParquetMetaData,
+        row_group_idx: usize,
+    ) -> Option<(RowSelection, usize)> {
+        let column_index = parquet_meta
+            .column_index()?
+            .get(row_group_idx)?
+            .get(self.column_idx)?;
+        let Index::INT64(index) = column_index else {
+            return None;
+        };
+        let page_locations = parquet_meta
+            .offset_index()?
+            .get(row_group_idx)?
+            .get(self.column_idx)?;
+        if index.indexes.len() != page_locations.len() {
+            return None;
+        }
+
+        let num_rows = parquet_meta.row_group(row_group_idx).num_rows() as usize;
+        let mut num_pruned = 0;
+        let selectors: Vec<_> = index
+            .indexes
+            .iter()
+            .zip(page_locations)
+            .enumerate()
+            .map(|(i, (page, location))| {
+                let start = location.first_row_index as usize;
+                let end = page_locations
+                    .get(i + 1)
+                    .map(|next| next.first_row_index as usize)
+                    .unwrap_or(num_rows);
+                let selected = match (page.min, page.max) {
+                    (Some(min), Some(max)) => self.in_range(min, max),
+                    _ => true,
+                };
+                if selected {
+                    RowSelector::select(end - start)
+                } else {
+                    num_pruned += 1;
+                    RowSelector::skip(end - start)
+                }
+            })
+            .collect();
+
+        (num_pruned > 0).then(|| (RowSelection::from(selectors), num_pruned))
+    }
+
+    fn in_range(&self, min: i64, max: i64) -> bool {
+        let range = TimestampRange::new_inclusive(
+            Some(Timestamp::new(min, self.unit)),
+            Some(Timestamp::new(max, self.unit)),
+        );
+        range.intersects(&self.time_range)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use bytes::Bytes;
+    use datatypes::arrow::array::TimestampMillisecondArray;
+    use datatypes::arrow::datatypes::{DataType, Field, Schema, TimeUnit as ArrowTimeUnit};
+    use datatypes::arrow::record_batch::RecordBatch;
+    use parquet::arrow::ArrowWriter;
+    use parquet::file::properties::WriterProperties;
+    use parquet::file::reader::FileReader;
+    use parquet::file::serialized_reader::{ReadOptionsBuilder, SerializedFileReader};
+
+    use super::*;
+
+    /// Writes timestamps `[0, 10)` in pages of 2 rows.
+    fn new_parquet_meta() -> ParquetMetaData {
+        let schema = Arc::new(Schema::new(vec![Field::new(
+            "ts",
+            DataType::Timestamp(ArrowTimeUnit::Millisecond, None),
+            false,
+        )]));
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![Arc::new(TimestampMillisecondArray::from_iter_values(0..10))],
+        )
+        .unwrap();
+        let props = WriterProperties::builder()
+            .set_data_page_row_count_limit(2)
+            .set_write_batch_size(2)
+            .build();
+        let mut buffer = Vec::new();
+        let mut writer = ArrowWriter::try_new(&mut buffer, schema, Some(props)).unwrap();
+        writer.write(&batch).unwrap();
+        writer.close().unwrap();
+
+        let options = ReadOptionsBuilder::new().with_page_index().build();
+        let reader = SerializedFileReader::new_with_options(Bytes::from(buffer), options).unwrap();
+        reader.metadata().clone()
+    }
+
+    fn new_filter(start: i64, end: i64) -> TimeRangeFilter {
+        TimeRangeFilter::new(
+            0,
+            TimeUnit::Millisecond,
+            TimestampRange::with_unit(start, end, TimeUnit::Millisecond).unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_row_group_in_range() {
+        let parquet_meta = new_parquet_meta();
+        let row_group = parquet_meta.row_group(0);
+        assert!(new_filter(5, 20).row_group_in_range(row_group));
+        assert!(!new_filter(10, 20).row_group_in_range(row_group));
+    }
+
+    #[test]
+    fn test_select_pages() {
+        let parquet_meta = new_parquet_meta();
+        // Pages: [0, 1], [2, 3], [4, 5], [6, 7], [8, 9], the time range is [3, 6).
+        let (selection, num_pruned) = new_filter(3, 6).select_pages(&parquet_meta, 0).unwrap();
+        let expect = RowSelection::from(vec![
+            RowSelector::skip(2),
+            RowSelector::select(4),
+            RowSelector::skip(4),
+        ]);
+        assert_eq!(expect, selection);
+        assert_eq!(3, num_pruned);
+
+        // All pages are selected.
+        assert!(new_filter(0, 20).select_pages(&parquet_meta, 0).is_none());
+    }
+}
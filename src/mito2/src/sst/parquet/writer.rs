@@ -26,10 +26,13 @@ use snafu::ResultExt;
 use store_api::metadata::RegionMetadataRef;
 use store_api::storage::consts::SEQUENCE_COLUMN_NAME;
 
-use crate::error::{InvalidMetadataSnafu, Result, WriteBufferSnafu};
+use crate::error::{InvalidMetadataSnafu, Result, SerdeJsonSnafu, WriteBufferSnafu};
 use crate::read::{Batch, Source};
+use crate::sst::dictionary::SharedDictionaryRef;
 use crate::sst::parquet::format::WriteFormat;
-use crate::sst::parquet::{SstInfo, WriteOptions, PARQUET_METADATA_KEY};
+use crate::sst::parquet::{
+    SstInfo, WriteOptions, PARQUET_METADATA_KEY, PARQUET_SHARED_DICTIONARY_KEY,
+};
 
 /// Parquet SST writer.
 pub struct ParquetWriter {
@@ -40,6 +43,8 @@ pub struct ParquetWriter {
     /// Region metadata of the source and the target SST.
     metadata: RegionMetadataRef,
     object_store: ObjectStore,
+    /// Dictionary to encode designated string fields.
+    shared_dictionary: Option<SharedDictionaryRef>,
 }
 
 impl ParquetWriter {
@@ -55,19 +60,45 @@ impl ParquetWriter {
             source,
             metadata,
             object_store,
+            shared_dictionary: None,
         }
     }
 
+    /// Attaches the shared dictionary to the writer.
+    pub(crate) fn shared_dictionary(
+        mut self,
+        dictionary: Option<SharedDictionaryRef>,
+    ) -> ParquetWriter {
+        self.shared_dictionary = dictionary;
+        self
+    }
+
     /// Iterates source and writes all rows to Parquet file.
     ///
     /// Returns the [SstInfo] if the SST is written.
     pub async fn write_all(&mut self, opts: &WriteOptions) -> Result<Option<SstInfo>> {
         let json = self.metadata.to_json().context(InvalidMetadataSnafu)?;
-        let key_value_meta = KeyValue::new(PARQUET_METADATA_KEY.to_string(), json);
+        let mut key_value_meta = vec![KeyValue::new(PARQUET_METADATA_KEY.to_string(), json)];
+
+        let mut write_format = WriteFormat::new(self.metadata.clone());
+        if let Some(dictionary) = &self.shared_dictionary {
+            let encoded_columns = dictionary.columns_to_encode(&self.metadata);
+            if !encoded_columns.is_empty() {
+                let mut column_ids: Vec<_> = encoded_columns.iter().copied().collect();
+                column_ids.sort_unstable();
+                let json = serde_json::to_string(&column_ids).context(SerdeJsonSnafu)?;
+                key_value_meta.push(KeyValue::new(
+                    PARQUET_SHARED_DICTIONARY_KEY.to_string(),
+                    json,
+                ));
+                write_format =
+                    write_format.with_shared_dictionary(dictionary.clone(), encoded_columns);
+            }
+        }
 
         // TODO(yingwen): Find and set proper column encoding for internal columns: op type and tsid.
         let props_builder = WriterProperties::builder()
-            .set_key_value_metadata(Some(vec![key_value_meta]))
+            .set_key_value_metadata(Some(key_value_meta))
             .set_compression(Compression::ZSTD(ZstdLevel::default()))
             .set_encoding(Encoding::PLAIN)
             .set_max_row_group_size(opts.row_group_size);
@@ -75,7 +106,6 @@ impl ParquetWriter {
         let props_builder = Self::customize_column_config(props_builder, &self.metadata);
         let writer_props = props_builder.build();
 
-        let write_format = WriteFormat::new(self.metadata.clone());
         let mut buffered_writer = BufferedWriter::try_new(
             self.file_path.clone(),
             self.object_store.clone(),
@@ -36,6 +36,7 @@ use datatypes::prelude::ConcreteDataType;
 use datatypes::schema::ColumnSchema;
 use log_store::raft_engine::log_store::RaftEngineLogStore;
 use log_store::test_util::log_store_util;
+use log_store::NoopLogStore;
 use object_store::manager::{ObjectStoreManager, ObjectStoreManagerRef};
 use object_store::services::Fs;
 use object_store::ObjectStore;
@@ -139,6 +140,13 @@ impl TestEnv {
         MitoEngine::new(config, logstore, object_store_manager)
     }
 
+    /// Creates a new engine with specific config that doesn't write WAL.
+    pub async fn create_engine_with_noop_log_store(&mut self, config: MitoConfig) -> MitoEngine {
+        let object_store_manager = Arc::new(self.create_object_store_manager());
+        self.object_store_manager = Some(object_store_manager.clone());
+        MitoEngine::new(config, Arc::new(NoopLogStore), object_store_manager)
+    }
+
     /// Creates a new engine with specific config and existing logstore and object store manager.
     pub async fn create_follower_engine(&mut self, config: MitoConfig) -> MitoEngine {
         let logstore = self.logstore.as_ref().unwrap().clone();
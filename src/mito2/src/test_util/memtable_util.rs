@@ -77,7 +77,7 @@ pub(crate) struct EmptyMemtableBuilder {
 }
 
 impl MemtableBuilder for EmptyMemtableBuilder {
-    fn build(&self, _metadata: &RegionMetadataRef) -> MemtableRef {
+    fn build(&self, _metadata: &RegionMetadataRef, _dedup: bool) -> MemtableRef {
         Arc::new(EmptyMemtable::new(
             self.next_id.fetch_add(1, Ordering::Relaxed),
         ))
@@ -0,0 +1,153 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Validates existing data of a region before altering it.
+//!
+//! Some alterations, e.g. setting a column to not null, are only valid if existing
+//! data satisfies the new schema. The engine scans the region outside the worker
+//! and remembers the committed sequence of the data it validates. The worker then
+//! only applies the alteration if no more data is written to the region.
+
+use std::collections::HashSet;
+
+use common_recordbatch::SendableRecordBatchStream;
+use futures::TryStreamExt;
+use snafu::{ensure, ResultExt};
+use store_api::metadata::RegionMetadata;
+use store_api::region_request::AlterKind;
+use store_api::storage::{ScanRequest, SequenceNumber};
+
+use crate::cache::CacheManagerRef;
+use crate::error::{ColumnContainsNullSnafu, ReadRecordBatchSnafu, Result};
+use crate::read::scan_region::ScanRegion;
+use crate::region::MitoRegionRef;
+
+/// Maximum number of rows with null values to report.
+const MAX_NULL_SAMPLES: usize = 5;
+
+/// Validates existing data of the `region` against the alter `kind`.
+///
+/// Returns the committed sequence of the data validated.
+pub(crate) async fn validate_alteration(
+    region: &MitoRegionRef,
+    kind: &AlterKind,
+    cache_manager: Option<CacheManagerRef>,
+) -> Result<SequenceNumber> {
+    // Gets the sequence before scanning the region so we can find out
+    // whether there are new writes after validation.
+    let version_data = region.version_control.current();
+    let committed_sequence = version_data.committed_sequence;
+    let version = version_data.version;
+
+    match kind {
+        AlterKind::SetNotNull { names } => {
+            let Some(request) = not_null_scan_request(&version.metadata, names) else {
+                return Ok(committed_sequence);
+            };
+            let metadata = version.metadata.clone();
+            let scanner =
+                ScanRegion::new(version, region.access_layer.clone(), request, cache_manager)
+                    .scanner()?;
+            let stream = scanner.scan().await?;
+            check_not_null(&metadata, stream, names).await?;
+        }
+        AlterKind::AddColumns { .. } | AlterKind::DropColumns { .. } => (),
+    }
+
+    Ok(committed_sequence)
+}
+
+/// Returns a request to scan key columns and nullable columns in `names`.
+///
+/// Returns `None` if all columns are already not null.
+fn not_null_scan_request(metadata: &RegionMetadata, names: &[String]) -> Option<ScanRequest> {
+    let names: HashSet<_> = names.iter().collect();
+    let nullable: Vec<_> = metadata
+        .column_metadatas
+        .iter()
+        .filter(|col| names.contains(&col.column_schema.name) && col.column_schema.is_nullable())
+        .collect();
+    if nullable.is_empty() {
+        return None;
+    }
+
+    // Reads keys to locate rows with null values.
+    let mut projection: Vec<_> = metadata
+        .primary_key_columns()
+        .chain(std::iter::once(metadata.time_index_column()))
+        .chain(nullable)
+        .filter_map(|col| {
+            metadata
+                .schema
+                .column_index_by_name(&col.column_schema.name)
+        })
+        .collect();
+    projection.sort_unstable();
+    projection.dedup();
+
+    Some(ScanRequest {
+        projection: Some(projection),
+        ..Default::default()
+    })
+}
+
+/// Returns an error with some sample rows if columns in `names` contain null values.
+async fn check_not_null(
+    metadata: &RegionMetadata,
+    mut stream: SendableRecordBatchStream,
+    names: &[String],
+) -> Result<()> {
+    let key_names: Vec<_> = metadata
+        .primary_key_columns()
+        .chain(std::iter::once(metadata.time_index_column()))
+        .map(|col| col.column_schema.name.as_str())
+        .collect();
+
+    while let Some(batch) = stream.try_next().await.context(ReadRecordBatchSnafu)? {
+        for name in names {
+            let Some(index) = batch.schema.column_index_by_name(name) else {
+                continue;
+            };
+            let column = batch.column(index);
+            if column.null_count() == 0 {
+                continue;
+            }
+
+            let samples: Vec<_> = (0..column.len())
+                .filter(|row| column.is_null(*row))
+                .take(MAX_NULL_SAMPLES)
+                .map(|row| {
+                    let keys: Vec<_> = key_names
+                        .iter()
+                        .filter_map(|key| {
+                            let key_index = batch.schema.column_index_by_name(key)?;
+                            Some(format!("{}={}", key, batch.column(key_index).get(row)))
+                        })
+                        .collect();
+                    format!("({})", keys.join(", "))
+                })
+                .collect();
+            ensure!(
+                samples.is_empty(),
+                ColumnContainsNullSnafu {
+                    region_id: metadata.region_id,
+                    column: name,
+                    samples: samples.join(", "),
+                }
+            );
+        }
+    }
+
+    Ok(())
+}
@@ -19,19 +19,20 @@ use std::mem;
 use std::sync::Arc;
 
 use api::v1::WalEntry;
-use async_stream::try_stream;
+use async_stream::stream;
 use common_config::wal::WalOptions;
 use common_error::ext::BoxedError;
 use futures::stream::BoxStream;
 use futures::StreamExt;
 use prost::Message;
-use snafu::ResultExt;
+use snafu::{ensure, ResultExt};
 use store_api::logstore::entry::Entry;
-use store_api::logstore::{AppendBatchResponse, LogStore};
+use store_api::logstore::{AppendBatchResponse, LogStore, LogStoreError};
 use store_api::storage::RegionId;
 
 use crate::error::{
-    DecodeWalSnafu, DeleteWalSnafu, EncodeWalSnafu, ReadWalSnafu, Result, WriteWalSnafu,
+    CorruptedWalEntrySnafu, DecodeWalSnafu, DeleteWalSnafu, EncodeWalSnafu, ReadCorruptedWalSnafu,
+    ReadWalSnafu, Result, WriteWalSnafu,
 };
 
 /// WAL entry id.
@@ -39,15 +40,33 @@ pub type EntryId = store_api::logstore::entry::Id;
 /// A stream that yields tuple of WAL entry id and corresponding entry.
 pub type WalEntryStream<'a> = BoxStream<'a, Result<(EntryId, WalEntry)>>;
 
+/// Marker of an entry with checksum.
+///
+/// An encoded [WalEntry] never starts with this byte as its only field is
+/// `mutations` (field number 1), so we can distinguish entries written before
+/// checksums were added.
+const CHECKSUM_MARKER: u8 = 0xff;
+/// Size of the header of an entry with checksum: the marker and a crc32 (little endian)
+/// of the encoded [WalEntry].
+const CHECKSUM_HEADER_SIZE: usize = 5;
+
 /// Write ahead log.
 ///
 /// All regions in the engine shares the same WAL instance.
-#[derive(Debug, Clone)]
+#[derive(Debug)]
 pub struct Wal<S> {
     /// The underlying log store.
     store: Arc<S>,
 }
 
+impl<S> Clone for Wal<S> {
+    fn clone(&self) -> Self {
+        Self {
+            store: self.store.clone(),
+        }
+    }
+}
+
 impl<S> Wal<S> {
     /// Creates a new [Wal] from the log store.
     pub fn new(store: Arc<S>) -> Self {
@@ -66,29 +85,58 @@ impl<S: LogStore> Wal<S> {
         }
     }
 
+    /// Returns true if the log store supports the `wal_options`.
+    pub fn supports_wal_options(&self, wal_options: &WalOptions) -> bool {
+        self.store.supports_wal_options(wal_options)
+    }
+
     /// Scan entries of specific region starting from `start_id` (inclusive).
+    ///
+    /// The stream yields an error for each corrupted entry, whether the log store or the
+    /// decoder detects the corruption, and continues to yield remaining entries, so callers
+    /// can decide how to handle corruption. It stops after yielding any other error that
+    /// fails to read the log store.
     pub fn scan<'a>(
         &'a self,
         region_id: RegionId,
         start_id: EntryId,
         wal_options: &'a WalOptions,
     ) -> Result<WalEntryStream> {
-        let stream = try_stream!({
+        let stream = stream!({
             let namespace = self.store.namespace(region_id.into(), wal_options);
-            let mut stream = self
+            let mut stream = match self
                 .store
                 .read(&namespace, start_id)
                 .await
                 .map_err(BoxedError::new)
-                .context(ReadWalSnafu { region_id })?;
+                .context(ReadWalSnafu { region_id })
+            {
+                Ok(stream) => stream,
+                Err(e) => {
+                    yield Err(e);
+                    return;
+                }
+            };
 
             while let Some(entries) = stream.next().await {
-                let entries = entries
-                    .map_err(BoxedError::new)
-                    .context(ReadWalSnafu { region_id })?;
+                let entries = match entries {
+                    Ok(entries) => entries,
+                    Err(e) => {
+                        if let Some(entry_id) = e.corrupted_entry_id() {
+                            // The log store continues to yield entries after the corrupted entry.
+                            yield Err(BoxedError::new(e)).context(ReadCorruptedWalSnafu {
+                                region_id,
+                                entry_id,
+                            });
+                            continue;
+                        }
+                        yield Err(BoxedError::new(e)).context(ReadWalSnafu { region_id });
+                        return;
+                    }
+                };
 
                 for entry in entries {
-                    yield decode_entry(region_id, entry)?;
+                    yield decode_entry(region_id, entry);
                 }
             }
         });
@@ -110,18 +158,71 @@ impl<S: LogStore> Wal<S> {
             .map_err(BoxedError::new)
             .context(DeleteWalSnafu { region_id })
     }
+
+    /// Deletes the namespace of the region after the region is dropped.
+    pub async fn delete_namespace(
+        &self,
+        region_id: RegionId,
+        wal_options: &WalOptions,
+    ) -> Result<()> {
+        let namespace = self.store.namespace(region_id.into(), wal_options);
+        self.store
+            .delete_namespace(&namespace)
+            .await
+            .map_err(BoxedError::new)
+            .context(DeleteWalSnafu { region_id })
+    }
 }
 
 /// Decode Wal entry from log store.
 fn decode_entry<E: Entry>(region_id: RegionId, entry: E) -> Result<(EntryId, WalEntry)> {
     let entry_id = entry.id();
-    let data = entry.data();
+    let mut data = entry.data();
+
+    if data.first() == Some(&CHECKSUM_MARKER) {
+        ensure!(
+            data.len() >= CHECKSUM_HEADER_SIZE,
+            CorruptedWalEntrySnafu {
+                region_id,
+                entry_id,
+                reason: format!("entry size {} is less than header size", data.len()),
+            }
+        );
+        let expect = u32::from_le_bytes(data[1..CHECKSUM_HEADER_SIZE].try_into().unwrap());
+        data = &data[CHECKSUM_HEADER_SIZE..];
+        let actual = crc32fast::hash(data);
+        ensure!(
+            expect == actual,
+            CorruptedWalEntrySnafu {
+                region_id,
+                entry_id,
+                reason: format!("checksum mismatch, expect: {expect}, actual: {actual}"),
+            }
+        );
+    }
 
-    let wal_entry = WalEntry::decode(data).context(DecodeWalSnafu { region_id })?;
+    let wal_entry = WalEntry::decode(data).context(DecodeWalSnafu {
+        region_id,
+        entry_id,
+    })?;
 
     Ok((entry_id, wal_entry))
 }
 
+/// Encodes the `wal_entry` with a checksum header into `buf`.
+fn encode_entry(region_id: RegionId, wal_entry: &WalEntry, buf: &mut Vec<u8>) -> Result<()> {
+    buf.clear();
+    buf.push(CHECKSUM_MARKER);
+    buf.extend_from_slice(&[0; CHECKSUM_HEADER_SIZE - 1]);
+    wal_entry
+        .encode(buf)
+        .context(EncodeWalSnafu { region_id })?;
+    let checksum = crc32fast::hash(&buf[CHECKSUM_HEADER_SIZE..]);
+    buf[1..CHECKSUM_HEADER_SIZE].copy_from_slice(&checksum.to_le_bytes());
+
+    Ok(())
+}
+
 /// WAL batch writer.
 pub struct WalWriter<S: LogStore> {
     /// Log store of the WAL.
@@ -151,10 +252,7 @@ impl<S: LogStore> WalWriter<S> {
             .clone();
 
         // Encode wal entry to log store entry.
-        self.entry_encode_buf.clear();
-        wal_entry
-            .encode(&mut self.entry_encode_buf)
-            .context(EncodeWalSnafu { region_id })?;
+        encode_entry(region_id, wal_entry, &mut self.entry_encode_buf)?;
         let entry = self
             .store
             .entry(&self.entry_encode_buf, entry_id, namespace);
@@ -189,6 +287,7 @@ mod tests {
     use store_api::storage::SequenceNumber;
 
     use super::*;
+    use crate::error::Error;
 
     struct WalEnv {
         _wal_dir: TempDir,
@@ -390,4 +489,94 @@ mod tests {
         let actual: Vec<_> = stream.try_collect().await.unwrap();
         check_entries(&entries[2..], 3, &actual);
     }
+
+    #[tokio::test]
+    async fn test_decode_corrupted_entry() {
+        let env = WalEnv::new().await;
+        let log_store = env.log_store.clone().unwrap();
+        let wal_options = WalOptions::default();
+        let region_id = RegionId::new(1, 1);
+        let namespace = log_store.namespace(region_id.into(), &wal_options);
+
+        let entries = sample_entries();
+        let mut buf = Vec::new();
+        encode_entry(region_id, &entries[0], &mut buf).unwrap();
+        let entry = log_store.entry(&buf, 1, namespace.clone());
+        assert_eq!(
+            (1, entries[0].clone()),
+            decode_entry(region_id, entry).unwrap()
+        );
+
+        // Flips a byte of the payload.
+        let last = buf.len() - 1;
+        buf[last] ^= 0x1;
+        let entry = log_store.entry(&buf, 2, namespace.clone());
+        let err = decode_entry(region_id, entry).unwrap_err();
+        assert!(
+            matches!(err, Error::CorruptedWalEntry { .. }),
+            "unexpected err: {err}"
+        );
+        assert_eq!(Some(2), err.corrupted_wal_entry_id());
+
+        // Truncated header.
+        let entry = log_store.entry(&buf[..2], 3, namespace);
+        let err = decode_entry(region_id, entry).unwrap_err();
+        assert_eq!(Some(3), err.corrupted_wal_entry_id());
+    }
+
+    #[tokio::test]
+    async fn test_decode_entry_without_checksum() {
+        let env = WalEnv::new().await;
+        let log_store = env.log_store.clone().unwrap();
+        let region_id = RegionId::new(1, 1);
+        let namespace = log_store.namespace(region_id.into(), &WalOptions::default());
+
+        let entries = sample_entries();
+        let buf = entries[1].encode_to_vec();
+        let entry = log_store.entry(&buf, 1, namespace);
+        assert_eq!(
+            (1, entries[1].clone()),
+            decode_entry(region_id, entry).unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_scan_skips_corrupted_entry() {
+        let env = WalEnv::new().await;
+        let log_store = env.log_store.clone().unwrap();
+        let wal = env.new_wal();
+        let wal_options = WalOptions::default();
+        let region_id = RegionId::new(1, 1);
+        let namespace = log_store.namespace(region_id.into(), &wal_options);
+
+        let entries = sample_entries();
+        let mut writer = wal.writer();
+        writer
+            .add_entry(region_id, 1, &entries[0], &wal_options)
+            .unwrap();
+        writer.write_to_wal().await.unwrap();
+        // Writes a corrupted entry.
+        let mut buf = Vec::new();
+        encode_entry(region_id, &entries[1], &mut buf).unwrap();
+        buf[CHECKSUM_HEADER_SIZE] ^= 0x1;
+        log_store
+            .append_batch(vec![log_store.entry(&buf, 2, namespace)])
+            .await
+            .unwrap();
+        let mut writer = wal.writer();
+        writer
+            .add_entry(region_id, 3, &entries[2], &wal_options)
+            .unwrap();
+        writer.write_to_wal().await.unwrap();
+
+        let stream = wal.scan(region_id, 1, &wal_options).unwrap();
+        let actual: Vec<_> = stream.collect().await;
+        assert_eq!(3, actual.len());
+        assert_eq!((1, entries[0].clone()), *actual[0].as_ref().unwrap());
+        assert_eq!(
+            Some(2),
+            actual[1].as_ref().unwrap_err().corrupted_wal_entry_id()
+        );
+        assert_eq!((3, entries[2].clone()), *actual[2].as_ref().unwrap());
+    }
 }
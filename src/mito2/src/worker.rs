@@ -19,8 +19,10 @@ mod handle_catchup;
 mod handle_close;
 mod handle_compaction;
 mod handle_create;
+mod handle_delete_range;
 mod handle_drop;
 mod handle_flush;
+mod handle_ingest;
 mod handle_open;
 mod handle_truncate;
 mod handle_write;
@@ -40,21 +42,25 @@ use store_api::logstore::LogStore;
 use store_api::region_engine::SetReadonlyResponse;
 use store_api::storage::RegionId;
 use tokio::sync::mpsc::{Receiver, Sender};
-use tokio::sync::{mpsc, oneshot, Mutex};
+use tokio::sync::{mpsc, oneshot, Mutex, Semaphore};
 
 use crate::cache::{CacheManager, CacheManagerRef};
 use crate::compaction::CompactionScheduler;
 use crate::config::MitoConfig;
-use crate::error::{JoinSnafu, Result, WorkerStoppedSnafu};
+use crate::error::{JoinSnafu, RegionOpeningSnafu, Result, WorkerStoppedSnafu};
 use crate::flush::{FlushScheduler, WriteBufferManagerImpl, WriteBufferManagerRef};
+use crate::manifest::action::{RegionMetaAction, RegionMetaActionList};
+use crate::manifest::journal::{CommitJournal, CommitJournalRef};
 use crate::memtable::time_series::TimeSeriesMemtableBuilder;
 use crate::memtable::MemtableBuilderRef;
+use crate::purge_job::{PurgeJob, PurgeJobTracker, PurgeJobTrackerRef};
 use crate::region::{MitoRegionRef, RegionMap, RegionMapRef};
 use crate::request::{
     BackgroundNotify, DdlRequest, SenderDdlRequest, SenderWriteRequest, WorkerRequest,
 };
 use crate::schedule::scheduler::{LocalScheduler, SchedulerRef};
 use crate::wal::Wal;
+use crate::worker::handle_open::OpeningRegions;
 
 /// Identifier for a worker.
 pub(crate) type WorkerId = u32;
@@ -105,6 +111,8 @@ pub(crate) struct WorkerGroup {
     scheduler: SchedulerRef,
     /// Cache.
     cache_manager: CacheManagerRef,
+    /// Jobs to purge data of dropped regions.
+    purge_jobs: PurgeJobTrackerRef,
 }
 
 impl WorkerGroup {
@@ -125,6 +133,9 @@ impl WorkerGroup {
             config.vector_cache_size.as_bytes(),
             config.page_cache_size.as_bytes(),
         ));
+        let open_semaphore = Arc::new(Semaphore::new(config.open_region_parallelism));
+        let purge_jobs = Arc::new(PurgeJobTracker::default());
+        let commit_journal = CommitJournal::from_config(&config);
 
         let workers = (0..config.num_workers)
             .map(|id| {
@@ -137,6 +148,9 @@ impl WorkerGroup {
                     scheduler: scheduler.clone(),
                     listener: WorkerListener::default(),
                     cache_manager: cache_manager.clone(),
+                    open_semaphore: open_semaphore.clone(),
+                    purge_jobs: purge_jobs.clone(),
+                    commit_journal: commit_journal.clone(),
                 }
                 .start()
             })
@@ -146,6 +160,7 @@ impl WorkerGroup {
             workers,
             scheduler,
             cache_manager,
+            purge_jobs,
         }
     }
 
@@ -187,6 +202,11 @@ impl WorkerGroup {
         self.cache_manager.clone()
     }
 
+    /// Returns jobs to purge data of dropped regions.
+    pub(crate) fn purge_jobs(&self) -> Vec<PurgeJob> {
+        self.purge_jobs.jobs()
+    }
+
     /// Get worker for specific `region_id`.
     fn worker(&self, region_id: RegionId) -> &RegionWorker {
         let mut hasher = DefaultHasher::new();
@@ -222,6 +242,9 @@ impl WorkerGroup {
             config.vector_cache_size.as_bytes(),
             config.page_cache_size.as_bytes(),
         ));
+        let open_semaphore = Arc::new(Semaphore::new(config.open_region_parallelism));
+        let purge_jobs = Arc::new(PurgeJobTracker::default());
+        let commit_journal = CommitJournal::from_config(&config);
 
         let workers = (0..config.num_workers)
             .map(|id| {
@@ -234,6 +257,9 @@ impl WorkerGroup {
                     scheduler: scheduler.clone(),
                     listener: WorkerListener::new(listener.clone()),
                     cache_manager: cache_manager.clone(),
+                    open_semaphore: open_semaphore.clone(),
+                    purge_jobs: purge_jobs.clone(),
+                    commit_journal: commit_journal.clone(),
                 }
                 .start()
             })
@@ -243,6 +269,7 @@ impl WorkerGroup {
             workers,
             scheduler,
             cache_manager,
+            purge_jobs,
         }
     }
 }
@@ -261,6 +288,9 @@ struct WorkerStarter<S> {
     scheduler: SchedulerRef,
     listener: WorkerListener,
     cache_manager: CacheManagerRef,
+    open_semaphore: Arc<Semaphore>,
+    purge_jobs: PurgeJobTrackerRef,
+    commit_journal: Option<CommitJournalRef>,
 }
 
 impl<S: LogStore> WorkerStarter<S> {
@@ -290,6 +320,10 @@ impl<S: LogStore> WorkerStarter<S> {
             stalled_requests: StalledRequests::default(),
             listener: self.listener,
             cache_manager: self.cache_manager,
+            open_semaphore: self.open_semaphore,
+            opening_regions: OpeningRegions::default(),
+            purge_jobs: self.purge_jobs,
+            commit_journal: self.commit_journal,
         };
         let handle = common_runtime::spawn_write(async move {
             worker_thread.run().await;
@@ -446,6 +480,14 @@ struct RegionWorkerLoop<S> {
     listener: WorkerListener,
     /// Cache.
     cache_manager: CacheManagerRef,
+    /// Semaphore to limit the number of regions to open concurrently in the engine.
+    open_semaphore: Arc<Semaphore>,
+    /// Regions that are opening in background.
+    opening_regions: OpeningRegions,
+    /// Jobs to purge data of dropped regions.
+    purge_jobs: PurgeJobTrackerRef,
+    /// Local journal of manifest commits, `None` if it's disabled.
+    commit_journal: Option<CommitJournalRef>,
 }
 
 impl<S: LogStore> RegionWorkerLoop<S> {
@@ -527,10 +569,28 @@ impl<S: LogStore> RegionWorkerLoop<S> {
         }
 
         for ddl in ddl_requests {
+            if !matches!(ddl.request, DdlRequest::Open(_))
+                && self.opening_regions.is_opening(ddl.region_id)
+            {
+                // The region is inserted after it is opened, so other DDL requests can't
+                // see the region until then. Clients should retry them later.
+                ddl.sender.send(
+                    RegionOpeningSnafu {
+                        region_id: ddl.region_id,
+                    }
+                    .fail(),
+                );
+                continue;
+            }
+
             let res = match ddl.request {
                 DdlRequest::Create(req) => self.handle_create_request(ddl.region_id, req).await,
                 DdlRequest::Drop(_) => self.handle_drop_request(ddl.region_id).await,
-                DdlRequest::Open(req) => self.handle_open_request(ddl.region_id, req).await,
+                DdlRequest::Open(req) => {
+                    self.handle_open_request(ddl.region_id, req, ddl.sender)
+                        .await;
+                    continue;
+                }
                 DdlRequest::Close(_) => self.handle_close_request(ddl.region_id).await,
                 DdlRequest::Alter(req) => {
                     self.handle_alter_request(ddl.region_id, req, ddl.sender)
@@ -548,6 +608,19 @@ impl<S: LogStore> RegionWorkerLoop<S> {
                 }
                 DdlRequest::Truncate(_) => self.handle_truncate_request(ddl.region_id).await,
                 DdlRequest::Catchup(req) => self.handle_catchup_request(ddl.region_id, req).await,
+                DdlRequest::DeleteRange(req) => {
+                    self.handle_delete_range_request(ddl.region_id, req).await
+                }
+                DdlRequest::Ingest(req) => {
+                    self.handle_ingest_request(ddl.region_id, req, ddl.sender)
+                        .await;
+                    continue;
+                }
+                DdlRequest::ValidatedAlter(req) => {
+                    self.handle_validated_alter_request(ddl.region_id, req, ddl.sender)
+                        .await;
+                    continue;
+                }
             };
 
             ddl.sender.send(res);
@@ -565,6 +638,10 @@ impl<S: LogStore> RegionWorkerLoop<S> {
                 self.handle_compaction_finished(region_id, req).await
             }
             BackgroundNotify::CompactionFailed(req) => self.handle_compaction_failure(req).await,
+            BackgroundNotify::RegionOpened(req) => self.handle_region_opened(region_id, req).await,
+            BackgroundNotify::RegionOpenFailed(req) => {
+                self.handle_region_open_failed(region_id, req)
+            }
         }
     }
 
@@ -598,6 +675,57 @@ impl<S> RegionWorkerLoop<S> {
 
         self.regions.clear();
     }
+
+    /// Commits the actions of a flush or compaction to the manifest of the region.
+    ///
+    /// The commit also persists new values of the shared dictionary, so SSTs in the
+    /// commit never reference values not in the manifest.
+    async fn commit_manifest(
+        &self,
+        region: &MitoRegionRef,
+        mut action_list: RegionMetaActionList,
+    ) -> Result<()> {
+        let shared_dictionary = region.access_layer.shared_dictionary();
+        let pending_dictionary = shared_dictionary.pending_edit()?;
+        if let Some((edit, _)) = &pending_dictionary {
+            action_list.actions.push(RegionMetaAction::Kv(edit.clone()));
+        }
+
+        self.commit_actions(region, action_list).await?;
+        if let Some((_, num_values)) = pending_dictionary {
+            shared_dictionary.mark_persisted(num_values);
+        }
+        Ok(())
+    }
+
+    /// Commits the actions to the manifest of the region.
+    ///
+    /// The commit is recorded in the journal until the manifest is updated, so the
+    /// region can repair it on open if the datanode crashes in between.
+    async fn commit_actions(
+        &self,
+        region: &MitoRegionRef,
+        action_list: RegionMetaActionList,
+    ) -> Result<()> {
+        let Some(journal) = &self.commit_journal else {
+            return region
+                .manifest_manager
+                .update(action_list)
+                .await
+                .map(|_| ());
+        };
+
+        let manifest_version = region.manifest_manager.manifest().await.manifest_version;
+        journal
+            .begin(region.region_id, manifest_version, &action_list)
+            .await?;
+        let result = region.manifest_manager.update(action_list).await;
+        // The flush or compaction fails if the update fails, so there is nothing to repair.
+        if let Err(e) = journal.finish(region.region_id).await {
+            warn!(e; "Failed to finish the commit of region {} in the journal", region.region_id);
+        }
+        result.map(|_| ())
+    }
 }
 
 /// Wrapper that only calls event listener in tests.
@@ -22,16 +22,45 @@ use store_api::metadata::{RegionMetadata, RegionMetadataBuilder, RegionMetadataR
 use store_api::region_request::RegionAlterRequest;
 use store_api::storage::RegionId;
 
-use crate::error::{InvalidMetadataSnafu, InvalidRegionRequestSnafu, Result};
+use crate::error::{
+    InvalidMetadataSnafu, InvalidRegionRequestSnafu, Result, ValidationConflictSnafu,
+};
 use crate::flush::FlushReason;
 use crate::manifest::action::{RegionChange, RegionMetaAction, RegionMetaActionList};
 use crate::memtable::MemtableBuilderRef;
 use crate::region::version::Version;
 use crate::region::MitoRegionRef;
-use crate::request::{DdlRequest, OptionOutputTx, SenderDdlRequest};
+use crate::request::{DdlRequest, OptionOutputTx, SenderDdlRequest, ValidatedAlter};
 use crate::worker::RegionWorkerLoop;
 
 impl<S> RegionWorkerLoop<S> {
+    /// Alters the region if no data is written after it is validated.
+    pub(crate) async fn handle_validated_alter_request(
+        &mut self,
+        region_id: RegionId,
+        request: ValidatedAlter,
+        mut sender: OptionOutputTx,
+    ) {
+        let Some(region) = self.regions.writable_region_or(region_id, &mut sender) else {
+            return;
+        };
+
+        // Writes are stalled once the alter request is pending so the region
+        // can't receive data we haven't validated if the sequence is unchanged.
+        let committed_sequence = region.version_control.current().committed_sequence;
+        if committed_sequence != request.validated_sequence {
+            info!(
+                "Region {} is modified after validation, validated sequence: {}, committed sequence: {}",
+                region_id, request.validated_sequence, committed_sequence
+            );
+            sender.send(ValidationConflictSnafu { region_id }.fail());
+            return;
+        }
+
+        self.handle_alter_request(region_id, request.request, sender)
+            .await;
+    }
+
     pub(crate) async fn handle_alter_request(
         &mut self,
         region_id: RegionId,
@@ -101,6 +130,7 @@ impl<S> RegionWorkerLoop<S> {
         }
 
         // Now we can alter the region directly.
+        let need_backfill = request.kind.need_backfill();
         if let Err(e) =
             alter_region_schema(&region, &version, request, &self.memtable_builder).await
         {
@@ -118,6 +148,28 @@ impl<S> RegionWorkerLoop<S> {
 
         // Notifies waiters.
         sender.send(Ok(0));
+
+        if need_backfill {
+            // Memtables are empty so all files are written before the alteration.
+            let files = version
+                .ssts
+                .levels()
+                .iter()
+                .flat_map(|level| level.files().map(|file| file.file_id()));
+            if let Err(e) = self.compaction_scheduler.schedule_backfill(
+                region_id,
+                files,
+                &region.version_control,
+                &region.access_layer,
+                &region.file_purger,
+                self.config.clone(),
+            ) {
+                warn!(
+                    "Failed to schedule backfill after alteration, region: {}, err: {}",
+                    region_id, e
+                );
+            }
+        }
     }
 }
 
@@ -73,6 +73,7 @@ impl<S: LogStore> RegionWorkerLoop<S> {
             region_id,
             flushed_entry_id,
             &region.version_control,
+            self.config.wal_corruption_policy,
         )
         .await?;
         if let Some(expected_last_entry_id) = request.entry_id {
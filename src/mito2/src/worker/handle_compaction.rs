@@ -71,9 +71,20 @@ impl<S: LogStore> RegionWorkerLoop<S> {
                 flushed_entry_id: None,
                 flushed_sequence: None,
             };
-            let action_list =
-                RegionMetaActionList::with_action(RegionMetaAction::Edit(edit.clone()));
-            if let Err(e) = region.manifest_manager.update(action_list).await {
+            let resolved_tombstones = std::mem::take(&mut request.resolved_tombstones);
+            let mut actions = vec![RegionMetaAction::Edit(edit.clone())];
+            if !resolved_tombstones.is_empty() {
+                info!(
+                    "Remove {} resolved range tombstones from region {}",
+                    resolved_tombstones.len(),
+                    region_id
+                );
+                actions.push(RegionMetaAction::RemoveRangeTombstones(
+                    resolved_tombstones.clone(),
+                ));
+            }
+            let action_list = RegionMetaActionList::new(actions);
+            if let Err(e) = self.commit_manifest(&region, action_list).await {
                 error!(e; "Failed to update manifest, region: {}", region_id);
                 manifest_timer.stop_and_discard();
                 request.on_failure(e);
@@ -84,6 +95,11 @@ impl<S: LogStore> RegionWorkerLoop<S> {
             region
                 .version_control
                 .apply_edit(edit, &[], region.file_purger.clone());
+            if !resolved_tombstones.is_empty() {
+                region
+                    .version_control
+                    .remove_range_tombstones(&resolved_tombstones);
+            }
         }
         // compaction finished.
         request.on_success();
@@ -0,0 +1,136 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Handling delete range requests.
+
+use api::v1::SemanticType;
+use common_telemetry::info;
+use datatypes::value::Value;
+use snafu::{ensure, OptionExt};
+use store_api::logstore::LogStore;
+use store_api::metadata::RegionMetadata;
+use store_api::region_request::{AffectedRows, RegionDeleteRangeRequest};
+use store_api::storage::{ColumnId, RegionId};
+
+use crate::error::{InvalidRequestSnafu, Result};
+use crate::manifest::action::{RegionMetaAction, RegionMetaActionList};
+use crate::read::range_delete::RangeTombstone;
+use crate::worker::RegionWorkerLoop;
+
+impl<S: LogStore> RegionWorkerLoop<S> {
+    /// Deletes rows in a time range by adding a range tombstone to the region.
+    ///
+    /// The tombstone only deletes rows written before the request. Rows are removed
+    /// physically while compacting files in the range.
+    ///
+    /// Always returns 0 affected rows as the region doesn't count rows in the range.
+    pub(crate) async fn handle_delete_range_request(
+        &mut self,
+        region_id: RegionId,
+        request: RegionDeleteRangeRequest,
+    ) -> Result<AffectedRows> {
+        let region = self.regions.writable_region(region_id)?;
+
+        let version_data = region.version_control.current();
+        let metadata = &version_data.version.metadata;
+        let tags = resolve_tag_filters(metadata, &request)?;
+
+        let unit = metadata
+            .time_index_column()
+            .column_schema
+            .data_type
+            .as_timestamp()
+            .expect("Time index must have timestamp-compatible type")
+            .unit();
+        let (Some(start), Some(end)) = (
+            request.start.convert_to_ceil(unit),
+            request.end.convert_to_ceil(unit),
+        ) else {
+            return InvalidRequestSnafu {
+                region_id,
+                reason: format!(
+                    "time range [{:?}, {:?}) overflows the time index",
+                    request.start, request.end
+                ),
+            }
+            .fail();
+        };
+        let sequence = version_data.committed_sequence;
+        if start.value() >= end.value() || sequence == 0 {
+            // Nothing to delete.
+            return Ok(0);
+        }
+
+        info!(
+            "Delete range [{}, {}) in region {}, tags: {:?}, sequence: {}",
+            start.value(),
+            end.value(),
+            region_id,
+            tags,
+            sequence
+        );
+
+        let tombstone = RangeTombstone {
+            start: start.value(),
+            end: end.value(),
+            tags,
+            sequence,
+        };
+        let action_list =
+            RegionMetaActionList::with_action(RegionMetaAction::DeleteRange(tombstone.clone()));
+        region.manifest_manager.update(action_list).await?;
+
+        region.version_control.add_range_tombstone(tombstone);
+
+        Ok(0)
+    }
+}
+
+/// Resolves names of tag filters in the `request` to column ids.
+fn resolve_tag_filters(
+    metadata: &RegionMetadata,
+    request: &RegionDeleteRangeRequest,
+) -> Result<Vec<(ColumnId, Value)>> {
+    request
+        .tag_filters
+        .iter()
+        .map(|(name, value)| {
+            let column = metadata
+                .column_by_name(name)
+                .with_context(|| InvalidRequestSnafu {
+                    region_id: metadata.region_id,
+                    reason: format!("unknown column {name}"),
+                })?;
+            ensure!(
+                column.semantic_type == SemanticType::Tag,
+                InvalidRequestSnafu {
+                    region_id: metadata.region_id,
+                    reason: format!("column {name} is not a tag"),
+                }
+            );
+            ensure!(
+                !value.is_null() && value.data_type() == column.column_schema.data_type,
+                InvalidRequestSnafu {
+                    region_id: metadata.region_id,
+                    reason: format!(
+                        "invalid value {:?} for tag {name}, expect type {:?}",
+                        value, column.column_schema.data_type
+                    ),
+                }
+            );
+
+            Ok((column.column_id, value.clone()))
+        })
+        .collect()
+}
@@ -21,19 +21,20 @@ use futures::TryStreamExt;
 use object_store::util::join_path;
 use object_store::{EntryMode, ObjectStore};
 use snafu::ResultExt;
+use store_api::logstore::LogStore;
 use store_api::region_request::AffectedRows;
 use store_api::storage::RegionId;
 use tokio::time::sleep;
 
 use crate::error::{OpenDalSnafu, Result};
 use crate::metrics::REGION_COUNT;
+use crate::purge_job::PurgeJobTrackerRef;
 use crate::region::RegionMapRef;
 use crate::worker::{RegionWorkerLoop, DROPPING_MARKER_FILE};
 
-const GC_TASK_INTERVAL_SEC: u64 = 5 * 60; // 5 minutes
 const MAX_RETRY_TIMES: u64 = 288; // 24 hours (5m * 288)
 
-impl<S> RegionWorkerLoop<S> {
+impl<S: LogStore> RegionWorkerLoop<S> {
     pub(crate) async fn handle_drop_request(
         &mut self,
         region_id: RegionId,
@@ -69,23 +70,39 @@ impl<S> RegionWorkerLoop<S> {
 
         REGION_COUNT.dec();
 
-        // detach a background task to delete the region dir
+        // The log store may keep states of the region, e.g. states to prune shared
+        // kafka topics. Entries of a dropped region are never read so we only log the error.
+        if let Err(e) = self
+            .wal
+            .delete_namespace(region_id, &region.wal_options)
+            .await
+        {
+            warn!(e; "Failed to delete wal namespace of region {}", region_id);
+        }
+
+        // detach a tracked background job to delete the region dir
         let region_dir = region.access_layer.region_dir().to_owned();
-        let object_store = region.access_layer.object_store().clone();
-        let dropping_regions = self.dropping_regions.clone();
+        self.purge_jobs.add_job(region_id, region_dir.clone());
+        let task = LaterDropTask {
+            region_id,
+            region_path: region_dir,
+            object_store: region.access_layer.object_store().clone(),
+            dropping_regions: self.dropping_regions.clone(),
+            purge_jobs: self.purge_jobs.clone(),
+            delay: self.config.dropped_region_purge_delay,
+            retry_interval: self.config.dropped_region_purge_retry_interval,
+        };
         let listener = self.listener.clone();
         common_runtime::spawn_bg(async move {
-            let gc_duration = listener
-                .on_later_drop_begin(region_id)
-                .unwrap_or(Duration::from_secs(GC_TASK_INTERVAL_SEC));
-            let removed = later_drop_task(
-                region_id,
-                region_dir,
-                object_store,
-                dropping_regions,
-                gc_duration,
-            )
-            .await;
+            let task = match listener.on_later_drop_begin(region_id) {
+                Some(gc_duration) => LaterDropTask {
+                    delay: gc_duration,
+                    retry_interval: gc_duration,
+                    ..task
+                },
+                None => task,
+            };
+            let removed = task.run().await;
             listener.on_later_drop_end(region_id, removed);
         });
 
@@ -94,53 +111,73 @@ impl<S> RegionWorkerLoop<S> {
 }
 
 /// Background GC task to remove the entire region path once it find there is no
-/// parquet file left. Returns whether the path is removed.
+/// parquet file left.
 ///
 /// This task will keep running until finished. Any resource captured by it will
 /// not be released before then. Be sure to only pass weak reference if something
 /// is depended on ref-count mechanism.
-async fn later_drop_task(
+struct LaterDropTask {
     region_id: RegionId,
     region_path: String,
     object_store: ObjectStore,
     dropping_regions: RegionMapRef,
-    gc_duration: Duration,
-) -> bool {
-    for _ in 0..MAX_RETRY_TIMES {
-        sleep(gc_duration).await;
-        let result = remove_region_dir_once(&region_path, &object_store).await;
-        match result {
-            Err(err) => {
-                warn!(
-                    "Error occurs during trying to GC region dir {}: {}",
-                    region_path, err
-                );
-            }
-            Ok(true) => {
-                dropping_regions.remove_region(region_id);
-                info!("Region {} is dropped", region_path);
-                return true;
+    purge_jobs: PurgeJobTrackerRef,
+    /// Delay before the first attempt.
+    delay: Duration,
+    /// Interval between attempts.
+    retry_interval: Duration,
+}
+
+impl LaterDropTask {
+    /// Runs the task and returns whether the path is removed.
+    async fn run(self) -> bool {
+        let region_id = self.region_id;
+        sleep(self.delay).await;
+        for _ in 0..MAX_RETRY_TIMES {
+            self.purge_jobs.on_attempt_begin(region_id);
+            let result = remove_region_dir_once(&self.region_path, &self.object_store).await;
+            match result {
+                Err(err) => {
+                    warn!(
+                        "Error occurs during trying to GC region dir {}: {}",
+                        self.region_path, err
+                    );
+                    self.purge_jobs
+                        .on_attempt_end(region_id, Err(err.to_string()));
+                }
+                Ok(0) => {
+                    self.dropping_regions.remove_region(region_id);
+                    self.purge_jobs.on_attempt_end(region_id, Ok(0));
+                    info!("Region {} is dropped", self.region_path);
+                    return true;
+                }
+                Ok(remaining_files) => {
+                    self.purge_jobs
+                        .on_attempt_end(region_id, Ok(remaining_files));
+                }
             }
-            Ok(false) => (),
+            sleep(self.retry_interval).await;
         }
-    }
 
-    warn!(
-        "Failed to GC region dir {} after {} retries, giving up",
-        region_path, MAX_RETRY_TIMES
-    );
+        warn!(
+            "Failed to GC region dir {} after {} retries, giving up",
+            self.region_path, MAX_RETRY_TIMES
+        );
+        self.purge_jobs.on_job_failed(region_id);
 
-    false
+        false
+    }
 }
 
 // TODO(ruihang): place the marker in a separate dir
-/// Removes region dir if there is no parquet files, returns whether the directory is removed.
+/// Removes region dir if there is no parquet files, returns the number of parquet files
+/// left, the directory is removed if it returns 0.
 pub(crate) async fn remove_region_dir_once(
     region_path: &str,
     object_store: &ObjectStore,
-) -> Result<bool> {
-    // list all files under the given region path to check if there are un-deleted parquet files
-    let mut has_parquet_file = false;
+) -> Result<usize> {
+    // list all files under the given region path to count un-deleted parquet files
+    let mut parquet_files = 0;
     // record all paths that neither ends with .parquet nor the marker file
     let mut files_to_remove_first = vec![];
     let mut files = object_store
@@ -149,8 +186,7 @@ pub(crate) async fn remove_region_dir_once(
         .context(OpenDalSnafu)?;
     while let Some(file) = files.try_next().await.context(OpenDalSnafu)? {
         if file.path().ends_with(".parquet") {
-            has_parquet_file = true;
-            break;
+            parquet_files += 1;
         } else if !file.path().ends_with(DROPPING_MARKER_FILE) {
             let meta = file.metadata();
             if meta.mode() == EntryMode::FILE {
@@ -159,7 +195,7 @@ pub(crate) async fn remove_region_dir_once(
         }
     }
 
-    if !has_parquet_file {
+    if parquet_files == 0 {
         // no parquet file found, delete the region path
         // first delete all files other than the marker
         object_store
@@ -171,8 +207,7 @@ pub(crate) async fn remove_region_dir_once(
             .remove_all(region_path)
             .await
             .context(OpenDalSnafu)?;
-        Ok(true)
-    } else {
-        Ok(false)
     }
+
+    Ok(parquet_files)
 }
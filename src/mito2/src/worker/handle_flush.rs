@@ -182,7 +182,7 @@ impl<S: LogStore> RegionWorkerLoop<S> {
             flushed_sequence: Some(request.flushed_sequence),
         };
         let action_list = RegionMetaActionList::with_action(RegionMetaAction::Edit(edit.clone()));
-        if let Err(e) = region.manifest_manager.update(action_list).await {
+        if let Err(e) = self.commit_manifest(&region, action_list).await {
             error!(e; "Failed to write manifest, region: {}", region_id);
             request.on_failure(e);
             return;
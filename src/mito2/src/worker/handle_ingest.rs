@@ -0,0 +1,143 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Handling ingest related requests.
+
+use common_telemetry::{error, info, warn};
+use store_api::storage::RegionId;
+
+use crate::flush::FlushReason;
+use crate::manifest::action::{RegionEdit, RegionMetaAction, RegionMetaActionList};
+use crate::request::{DdlRequest, IngestFiles, OptionOutputTx, SenderDdlRequest};
+use crate::sst::file_purger::PurgeRequest;
+use crate::worker::RegionWorkerLoop;
+
+impl<S> RegionWorkerLoop<S> {
+    /// Adds ingested files to the region.
+    ///
+    /// Each file gets a new sequence greater than sequences of existing data, so
+    /// rows in the file overwrite rows written before and are overwritten by rows
+    /// written after the ingestion. The region flushes memtables first so all rows
+    /// with smaller sequences are in SSTs before we bump the flushed sequence.
+    ///
+    /// Files are removed if we fail to add them to the manifest.
+    pub(crate) async fn handle_ingest_request(
+        &mut self,
+        region_id: RegionId,
+        mut request: IngestFiles,
+        sender: OptionOutputTx,
+    ) {
+        let region = match self.regions.writable_region(region_id) {
+            Ok(region) => region,
+            Err(e) => {
+                // If the region is closed or dropped, the files are
+                // unreachable and removed with the region directory.
+                sender.send(Err(e));
+                return;
+            }
+        };
+
+        let version_data = region.version_control.current();
+        if !version_data.version.memtables.is_empty() {
+            info!("Flush region: {} before ingesting files", region_id);
+
+            let task = self.new_flush_task(&region, FlushReason::Ingest, None, self.config.clone());
+            if let Err(e) =
+                self.flush_scheduler
+                    .schedule_flush(region.region_id, &region.version_control, task)
+            {
+                for file in &request.file_metas {
+                    region.file_purger.send_request(PurgeRequest {
+                        region_id: file.region_id,
+                        file_id: file.file_id,
+                    });
+                }
+                sender.send(Err(e));
+                return;
+            }
+
+            // Safety: We have requested flush.
+            self.flush_scheduler
+                .add_ddl_request_to_pending(SenderDdlRequest {
+                    region_id,
+                    sender,
+                    request: DdlRequest::Ingest(request),
+                });
+            return;
+        }
+
+        // Files ingested later overwrite files ingested before.
+        let mut sequence = version_data.committed_sequence;
+        for file in &mut request.file_metas {
+            sequence += 1;
+            file.sequence = Some(sequence);
+        }
+
+        info!(
+            "Ingest {} files with {} rows to region {}, sequences: ({}, {}]",
+            request.file_metas.len(),
+            request.num_rows,
+            region_id,
+            version_data.committed_sequence,
+            sequence
+        );
+
+        // All rows with smaller sequences are flushed, so we can bump the flushed sequence
+        // to recover the committed sequence after reopening the region.
+        let edit = RegionEdit {
+            files_to_add: request.file_metas,
+            files_to_remove: Vec::new(),
+            compaction_time_window: None,
+            flushed_entry_id: None,
+            flushed_sequence: Some(sequence),
+        };
+        let action_list = RegionMetaActionList::with_action(RegionMetaAction::Edit(edit.clone()));
+        if let Err(e) = region.manifest_manager.update(action_list).await {
+            error!(e; "Failed to write manifest, region: {}", region_id);
+            for file in &edit.files_to_add {
+                region.file_purger.send_request(PurgeRequest {
+                    region_id: file.region_id,
+                    file_id: file.file_id,
+                });
+            }
+            sender.send(Err(e));
+            return;
+        }
+
+        // Apply edit to region's version.
+        region
+            .version_control
+            .apply_edit(edit, &[], region.file_purger.clone());
+        region
+            .version_control
+            .set_sequence_and_entry_id(sequence, version_data.last_entry_id);
+
+        sender.send(Ok(request.num_rows));
+
+        // Ingested files are in level 0 so we might need to compact them.
+        if let Err(e) = self.compaction_scheduler.schedule_compaction(
+            region.region_id,
+            &region.version_control,
+            &region.access_layer,
+            &region.file_purger,
+            OptionOutputTx::none(),
+            self.config.clone(),
+        ) {
+            warn!(
+                "Failed to schedule compaction after ingest, region: {}, err: {}",
+                region.region_id, e
+            );
+        }
+    }
+}
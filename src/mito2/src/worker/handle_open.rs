@@ -14,30 +14,186 @@
 
 //! Handling open request.
 
+use std::collections::HashMap;
 use std::sync::Arc;
 
-use common_telemetry::info;
+use common_telemetry::{error, info, warn};
 use object_store::util::join_path;
 use snafu::{OptionExt, ResultExt};
 use store_api::logstore::LogStore;
-use store_api::region_request::{AffectedRows, RegionOpenRequest};
+use store_api::region_request::RegionOpenRequest;
 use store_api::storage::RegionId;
 
-use crate::error::{ObjectStoreNotFoundSnafu, OpenDalSnafu, RegionNotFoundSnafu, Result};
+use crate::error::{
+    ObjectStoreNotFoundSnafu, OpenDalSnafu, OpenRegionSnafu, RegionNotFoundSnafu, Result,
+};
 use crate::metrics::REGION_COUNT;
 use crate::region::opener::RegionOpener;
+use crate::request::{
+    BackgroundNotify, OptionOutputTx, RegionOpenFailed, RegionOpened, WorkerRequest,
+};
 use crate::worker::handle_drop::remove_region_dir_once;
 use crate::worker::{RegionWorkerLoop, DROPPING_MARKER_FILE};
 
+/// Regions that are opening in background and senders waiting for them.
+#[derive(Default)]
+pub(crate) struct OpeningRegions {
+    regions: HashMap<RegionId, Vec<OptionOutputTx>>,
+}
+
+impl OpeningRegions {
+    /// Adds the `sender` to waiters of the region if the region is opening.
+    ///
+    /// Returns the `sender` back if the region isn't opening.
+    fn wait(&mut self, region_id: RegionId, sender: OptionOutputTx) -> Option<OptionOutputTx> {
+        match self.regions.get_mut(&region_id) {
+            Some(senders) => {
+                senders.push(sender);
+                None
+            }
+            None => Some(sender),
+        }
+    }
+
+    /// Returns true if the region is opening.
+    pub(crate) fn is_opening(&self, region_id: RegionId) -> bool {
+        self.regions.contains_key(&region_id)
+    }
+
+    /// Marks the region as opening.
+    fn insert(&mut self, region_id: RegionId, sender: OptionOutputTx) {
+        self.regions.insert(region_id, vec![sender]);
+    }
+
+    /// Removes the region and returns senders waiting for it.
+    fn remove(&mut self, region_id: RegionId) -> Vec<OptionOutputTx> {
+        self.regions.remove(&region_id).unwrap_or_default()
+    }
+}
+
 impl<S: LogStore> RegionWorkerLoop<S> {
+    /// Opens the region in background.
+    ///
+    /// Opening a region loads its manifest and replays its WAL, which may take a long
+    /// time. We open regions in background tasks so the worker can open multiple regions
+    /// concurrently. The `sender` is notified after the region is opened.
     pub(crate) async fn handle_open_request(
         &mut self,
         region_id: RegionId,
         request: RegionOpenRequest,
-    ) -> Result<AffectedRows> {
+        sender: OptionOutputTx,
+    ) {
         if self.regions.is_region_exists(region_id) {
-            return Ok(0);
+            sender.send(Ok(0));
+            return;
+        }
+        let Some(sender) = self.opening_regions.wait(region_id, sender) else {
+            // The region is already opening.
+            return;
+        };
+
+        let opener = match self.new_region_opener(region_id, request).await {
+            Ok(opener) => opener,
+            Err(e) => {
+                sender.send(Err(e));
+                return;
+            }
+        };
+
+        info!("Try to open region {}", region_id);
+
+        self.opening_regions.insert(region_id, sender);
+        let config = self.config.clone();
+        let wal = self.wal.clone();
+        let semaphore = self.open_semaphore.clone();
+        let request_sender = self.sender.clone();
+        common_runtime::spawn_bg(async move {
+            let result = {
+                // Safety: We never close the semaphore.
+                let _permit = semaphore.acquire().await.unwrap();
+                opener.open(&config, &wal).await
+            };
+            let notify = match result {
+                Ok(region) => BackgroundNotify::RegionOpened(RegionOpened {
+                    region: Arc::new(region),
+                }),
+                Err(e) => {
+                    error!(e; "Failed to open region {}", region_id);
+                    BackgroundNotify::RegionOpenFailed(RegionOpenFailed { err: Arc::new(e) })
+                }
+            };
+            if request_sender
+                .send(WorkerRequest::Background { region_id, notify })
+                .await
+                .is_err()
+            {
+                warn!(
+                    "Failed to send open result of region {} to the worker",
+                    region_id
+                );
+            }
+        });
+    }
+
+    /// Handles the region opened in background.
+    pub(crate) async fn handle_region_opened(
+        &mut self,
+        region_id: RegionId,
+        request: RegionOpened,
+    ) {
+        let senders = self.opening_regions.remove(region_id);
+        // Other DDL requests are rejected while opening, but we still check the region
+        // isn't created or being dropped before inserting it.
+        if self.regions.is_region_exists(region_id)
+            || self.dropping_regions.is_region_exists(region_id)
+        {
+            warn!(
+                "Region {} is created or dropped while opening, ignore the opened region",
+                region_id
+            );
+            if let Err(e) = request.region.stop().await {
+                error!(e; "Failed to stop opened region {}", region_id);
+            }
+            let exists = self.regions.is_region_exists(region_id);
+            for sender in senders {
+                if exists {
+                    sender.send(Ok(0));
+                } else {
+                    sender.send(RegionNotFoundSnafu { region_id }.fail());
+                }
+            }
+            return;
         }
+
+        info!("Region {} is opened", region_id);
+
+        REGION_COUNT.inc();
+
+        // Insert the MitoRegion into the RegionMap.
+        self.regions.insert_region(request.region);
+
+        for sender in senders {
+            sender.send(Ok(0));
+        }
+    }
+
+    /// Handles the failure of opening a region in background.
+    pub(crate) fn handle_region_open_failed(
+        &mut self,
+        region_id: RegionId,
+        request: RegionOpenFailed,
+    ) {
+        for sender in self.opening_regions.remove(region_id) {
+            sender.send(Err(request.err.clone()).context(OpenRegionSnafu { region_id }));
+        }
+    }
+
+    /// Returns a [RegionOpener] to open the region.
+    async fn new_region_opener(
+        &self,
+        region_id: RegionId,
+        request: RegionOpenRequest,
+    ) -> Result<RegionOpener> {
         let object_store = if let Some(storage_name) = request.options.get("storage") {
             self.object_store_manager
                 .find(storage_name)
@@ -55,14 +211,15 @@ impl<S: LogStore> RegionWorkerLoop<S> {
                 .context(OpenDalSnafu)?
         {
             let result = remove_region_dir_once(&request.region_dir, object_store).await;
-            info!("Region {} is dropped, result: {:?}", region_id, result);
+            info!(
+                "Region {} is dropped, remaining parquet files: {:?}",
+                region_id, result
+            );
             return RegionNotFoundSnafu { region_id }.fail();
         }
 
-        info!("Try to open region {}", region_id);
-
         // Open region from specific region dir.
-        let region = RegionOpener::new(
+        RegionOpener::new(
             region_id,
             &request.region_dir,
             self.memtable_builder.clone(),
@@ -70,18 +227,8 @@ impl<S: LogStore> RegionWorkerLoop<S> {
             self.scheduler.clone(),
         )
         .skip_wal_replay(request.skip_wal_replay)
-        .parse_options(request.options)?
-        .cache(Some(self.cache_manager.clone()))
-        .open(&self.config, &self.wal)
-        .await?;
-
-        info!("Region {} is opened", region_id);
-
-        REGION_COUNT.inc();
-
-        // Insert the MitoRegion into the RegionMap.
-        self.regions.insert_region(Arc::new(region));
-
-        Ok(0)
+        .commit_journal(self.commit_journal.clone())
+        .parse_options(request.options)
+        .map(|opener| opener.cache(Some(self.cache_manager.clone())))
     }
 }
@@ -0,0 +1,35 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use common_error::ext::BoxedError;
+use store_api::region_engine::RegionCaptureRequest;
+use store_api::storage::RegionId;
+
+pub type RegionCapturerRef = Arc<dyn RegionCapturer>;
+
+/// Copies files of regions to reproduce reads offline, used by `ADMIN capture_query`.
+#[async_trait]
+pub trait RegionCapturer: Send + Sync {
+    /// Copies the manifest and SSTs of the region that the read needs to a local directory.
+    ///
+    /// Returns paths of copied files relative to the target directory.
+    async fn capture_region(
+        &self,
+        region_id: RegionId,
+        request: RegionCaptureRequest,
+    ) -> Result<Vec<String>, BoxedError>;
+}
@@ -27,12 +27,14 @@ use futures_util::future;
 use partition::manager::PartitionRuleManagerRef;
 use session::context::QueryContextRef;
 use snafu::{ensure, OptionExt, ResultExt};
+use store_api::storage::RegionId;
 use table::requests::DeleteRequest as TableDeleteRequest;
 use table::TableRef;
 
 use crate::error::{
-    CatalogSnafu, FindRegionLeaderSnafu, InvalidDeleteRequestSnafu, JoinTaskSnafu,
-    MissingTimeIndexColumnSnafu, RequestDeletesSnafu, Result, TableNotFoundSnafu,
+    CatalogSnafu, FindRegionLeaderSnafu, FindShadowRegionSnafu, InvalidDeleteRequestSnafu,
+    JoinTaskSnafu, MissingTimeIndexColumnSnafu, NotSupportedSnafu, RequestDeletesSnafu, Result,
+    TableNotFoundSnafu,
 };
 use crate::region_req_factory::RegionRequestFactory;
 use crate::req_convert::delete::{ColumnToRow, RowToRegion, TableToRegion};
@@ -155,9 +157,22 @@ impl Deleter {
         let mut deletes: HashMap<Peer, RegionDeleteRequests> = HashMap::new();
 
         for req in requests.requests {
+            let region_id = RegionId::from_u64(req.region_id);
+            // Rows to delete may not contain all columns of the new primary key, so we
+            // can't write them to the shadow region.
+            ensure!(
+                self.partition_manager
+                    .find_shadow_region(region_id)
+                    .await
+                    .context(FindShadowRegionSnafu { region_id })?
+                    .is_none(),
+                NotSupportedSnafu {
+                    feat: "deleting from a table being rebuilt",
+                }
+            );
             let peer = self
                 .partition_manager
-                .find_region_leader(req.region_id.into())
+                .find_region_leader(region_id)
                 .await
                 .context(FindRegionLeaderSnafu)?;
             deletes.entry(peer).or_default().requests.push(req);
@@ -23,6 +23,7 @@ use datatypes::arrow::error::ArrowError;
 use datatypes::value::Value;
 use servers::define_into_tonic_status;
 use snafu::{Location, Snafu};
+use store_api::storage::RegionId;
 
 #[derive(Snafu)]
 #[snafu(visibility(pub))]
@@ -150,6 +151,19 @@ pub enum Error {
         location: Location,
     },
 
+    #[snafu(display("Failed to find shadow region of region {}", region_id))]
+    FindShadowRegion {
+        region_id: RegionId,
+        source: partition::error::Error,
+        location: Location,
+    },
+
+    #[snafu(display("Writes to region {} are fenced, try again later", region_id))]
+    RegionWriteFenced {
+        region_id: RegionId,
+        location: Location,
+    },
+
     #[snafu(display("Failed to create table info"))]
     CreateTableInfo {
         location: Location,
@@ -211,6 +225,12 @@ pub enum Error {
         location: Location,
     },
 
+    #[snafu(display("Failed to encode DataFusion logical plan"))]
+    EncodeSubstraitLogicalPlan {
+        location: Location,
+        source: substrait::error::Error,
+    },
+
     #[snafu(display("Failed to convert AlterExpr to AlterRequest"))]
     AlterExprToRequest {
         location: Location,
@@ -344,6 +364,62 @@ pub enum Error {
         error: object_store::Error,
     },
 
+    #[snafu(display("Capturing queries is disabled, the capture directory is not configured"))]
+    CaptureDisabled { location: Location },
+
+    #[snafu(display("Invalid capture location: {}, reason: {}", capture_location, reason))]
+    InvalidCaptureLocation {
+        capture_location: String,
+        reason: String,
+        location: Location,
+    },
+
+    #[snafu(display("Failed to access capture path: {}", path))]
+    AccessCapturePath {
+        path: String,
+        location: Location,
+        #[snafu(source)]
+        error: std::io::Error,
+    },
+
+    #[snafu(display("Failed to capture region {}", region_id))]
+    CaptureRegion {
+        region_id: RegionId,
+        location: Location,
+        source: BoxedError,
+    },
+
+    #[snafu(display("Failed to write object to path: {}", path))]
+    WriteObject {
+        path: String,
+        location: Location,
+        #[snafu(source)]
+        error: object_store::Error,
+    },
+
+    #[snafu(display("Failed to split record batch by column: {}", column))]
+    SplitRecordBatch {
+        column: String,
+        #[snafu(source)]
+        error: ArrowError,
+        location: Location,
+    },
+
+    #[snafu(display("Column {} of table {} is not a tag", column, table))]
+    NotTagColumn {
+        column: String,
+        table: String,
+        location: Location,
+    },
+
+    #[snafu(display("Failed to format values of tag: {}", column))]
+    FormatTagValue {
+        column: String,
+        #[snafu(source)]
+        error: ArrowError,
+        location: Location,
+    },
+
     #[snafu(display("Failed to read record batch"))]
     ReadDfRecordBatch {
         #[snafu(source)]
@@ -424,12 +500,57 @@ pub enum Error {
         location: Location,
     },
 
+    #[snafu(display("Invalid pipeline"))]
+    InvalidPipeline {
+        source: servers::error::Error,
+        location: Location,
+    },
+
+    #[snafu(display("Pipeline not found: {}", pipeline))]
+    PipelineNotFound {
+        pipeline: String,
+        location: Location,
+    },
+
+    #[snafu(display("Function {} already exists in catalog {}", name, catalog))]
+    FunctionExists {
+        catalog: String,
+        name: String,
+        location: Location,
+    },
+
+    #[snafu(display("Failed to create function {}", name))]
+    CreateFunction {
+        name: String,
+        source: common_query::error::Error,
+        location: Location,
+    },
+
     #[snafu(display("Failed to read record batch"))]
     ReadRecordBatch {
         source: common_recordbatch::error::Error,
         location: Location,
     },
 
+    #[snafu(display("Failed to build record batch"))]
+    BuildRecordBatch {
+        source: common_recordbatch::error::Error,
+        location: Location,
+    },
+
+    #[snafu(display("Failed to query procedure state: {}", procedure_id))]
+    QueryProcedureState {
+        procedure_id: String,
+        location: Location,
+        source: common_meta::error::Error,
+    },
+
+    #[snafu(display("Procedure not found: {}", procedure_id))]
+    ProcedureNotFound {
+        procedure_id: String,
+        location: Location,
+    },
+
     #[snafu(display("Failed to build column vectors"))]
     BuildColumnVectors {
         source: common_recordbatch::error::Error,
@@ -497,6 +618,7 @@ impl ErrorExt for Error {
             | Error::SchemaNotFound { .. }
             | Error::SchemaExists { .. }
             | Error::ColumnNotFound { .. }
+            | Error::NotTagColumn { .. }
             | Error::BuildRegex { .. }
             | Error::InvalidSchema { .. }
             | Error::PrepareImmutableTable { .. }
@@ -507,7 +629,9 @@ impl ErrorExt for Error {
             | Error::InvalidPartitionColumns { .. }
             | Error::PrepareFileTable { .. }
             | Error::InferFileTableSchema { .. }
-            | Error::SchemaIncompatible { .. } => StatusCode::InvalidArguments,
+            | Error::SchemaIncompatible { .. }
+            | Error::PipelineNotFound { .. }
+            | Error::ProcedureNotFound { .. } => StatusCode::InvalidArguments,
 
             Error::TableAlreadyExists { .. } => StatusCode::TableAlreadyExists,
 
@@ -515,6 +639,11 @@ impl ErrorExt for Error {
 
             Error::TableMetadataManager { source, .. } => source.status_code(),
 
+            Error::InvalidPipeline { source, .. } => source.status_code(),
+
+            Error::CreateFunction { source, .. } => source.status_code(),
+            Error::FunctionExists { .. } => StatusCode::InvalidArguments,
+
             Error::ConvertSqlValue { source, .. } | Error::ParseSql { source, .. } => {
                 source.status_code()
             }
@@ -546,7 +675,9 @@ impl ErrorExt for Error {
             | Error::BuildTableMeta { .. }
             | Error::MissingInsertBody { .. } => StatusCode::Internal,
 
-            Error::EncodeJson { .. } => StatusCode::Unexpected,
+            Error::EncodeJson { .. }
+            | Error::SplitRecordBatch { .. }
+            | Error::FormatTagValue { .. } => StatusCode::Unexpected,
 
             Error::TableNotFound { .. } => StatusCode::TableNotFound,
 
@@ -571,29 +702,42 @@ impl ErrorExt for Error {
 
             Error::AlterExprToRequest { source, .. } => source.status_code(),
 
+            Error::EncodeSubstraitLogicalPlan { source, .. } => source.status_code(),
+
             Error::External { source, .. } => source.status_code(),
             Error::DeserializePartition { source, .. }
             | Error::FindTablePartitionRule { source, .. }
             | Error::SplitInsert { source, .. }
             | Error::SplitDelete { source, .. }
-            | Error::FindRegionLeader { source, .. } => source.status_code(),
+            | Error::FindRegionLeader { source, .. }
+            | Error::FindShadowRegion { source, .. } => source.status_code(),
+
+            Error::RegionWriteFenced { .. } => StatusCode::RegionBusy,
 
             Error::UnrecognizedTableOption { .. } => StatusCode::InvalidArguments,
 
-            Error::ReadObject { .. } | Error::ReadParquet { .. } | Error::ReadOrc { .. } => {
-                StatusCode::StorageUnavailable
-            }
+            Error::ReadObject { .. }
+            | Error::ReadParquet { .. }
+            | Error::ReadOrc { .. }
+            | Error::WriteObject { .. } => StatusCode::StorageUnavailable,
 
             Error::ListObjects { source, .. }
             | Error::ParseUrl { source, .. }
             | Error::BuildBackend { source, .. } => source.status_code(),
 
-            Error::ExecuteDdl { source, .. } => source.status_code(),
-            Error::InvalidCopyParameter { .. } => StatusCode::InvalidArguments,
+            Error::CaptureRegion { source, .. } => source.status_code(),
+            Error::CaptureDisabled { .. } => StatusCode::Unsupported,
+            Error::InvalidCaptureLocation { .. } => StatusCode::InvalidArguments,
+            Error::AccessCapturePath { .. } => StatusCode::StorageUnavailable,
 
-            Error::ReadRecordBatch { source, .. } | Error::BuildColumnVectors { source, .. } => {
+            Error::ExecuteDdl { source, .. } | Error::QueryProcedureState { source, .. } => {
                 source.status_code()
             }
+            Error::InvalidCopyParameter { .. } => StatusCode::InvalidArguments,
+
+            Error::ReadRecordBatch { source, .. }
+            | Error::BuildColumnVectors { source, .. }
+            | Error::BuildRecordBatch { source, .. } => source.status_code(),
 
             Error::ColumnDefaultValue { source, .. } => source.status_code(),
         }
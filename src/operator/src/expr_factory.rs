@@ -394,6 +394,13 @@ pub(crate) fn to_alter_expr(
                 name: name.value.to_string(),
             }],
         }),
+        AlterTableOperation::ModifyPrimaryKey { .. } => {
+            // Modifying the primary key rebuilds the table, which is not an alter expr.
+            return NotSupportedSnafu {
+                feat: "MODIFY PRIMARY KEY in alter expr",
+            }
+            .fail();
+        }
         AlterTableOperation::RenameTable { new_table_name } => Kind::RenameTable(RenameTable {
             new_table_name: new_table_name.to_string(),
         }),
@@ -16,12 +16,17 @@ use std::collections::HashMap;
 use std::sync::Arc;
 
 use api::v1::alter_expr::Kind;
-use api::v1::region::{InsertRequests as RegionInsertRequests, RegionRequestHeader};
+use api::v1::region::{
+    InsertRequest as RegionInsertRequest, InsertRequests as RegionInsertRequests,
+    RegionRequestHeader,
+};
 use api::v1::{
     AlterExpr, ColumnSchema, CreateTableExpr, InsertRequests, RowInsertRequest, RowInsertRequests,
+    SemanticType,
 };
 use catalog::CatalogManagerRef;
 use common_catalog::consts::default_engine;
+use common_catalog::format_full_table_name;
 use common_grpc_expr::util::{extract_new_columns, ColumnExpr};
 use common_meta::datanode_manager::{AffectedRows, DatanodeManagerRef};
 use common_meta::peer::Peer;
@@ -32,16 +37,20 @@ use datatypes::schema::Schema;
 use futures_util::future;
 use meter_macros::write_meter;
 use partition::manager::PartitionRuleManagerRef;
+use prost::Message;
+use servers::tail::TailHubRef;
 use session::context::QueryContextRef;
 use snafu::prelude::*;
 use sql::statements::insert::Insert;
+use store_api::storage::RegionId;
 use table::engine::TableReference;
 use table::requests::InsertRequest as TableInsertRequest;
 use table::TableRef;
 
 use crate::error::{
-    CatalogSnafu, FindNewColumnsOnInsertionSnafu, FindRegionLeaderSnafu, InvalidInsertRequestSnafu,
-    JoinTaskSnafu, RequestInsertsSnafu, Result, TableNotFoundSnafu,
+    CatalogSnafu, FindNewColumnsOnInsertionSnafu, FindRegionLeaderSnafu, FindShadowRegionSnafu,
+    InvalidInsertRequestSnafu, JoinTaskSnafu, RegionWriteFencedSnafu, RequestInsertsSnafu, Result,
+    TableNotFoundSnafu,
 };
 use crate::expr_factory::CreateExprFactory;
 use crate::region_req_factory::RegionRequestFactory;
@@ -52,6 +61,8 @@ pub struct Inserter {
     catalog_manager: CatalogManagerRef,
     partition_manager: PartitionRuleManagerRef,
     datanode_manager: DatanodeManagerRef,
+    /// Hub to publish written rows to tail subscribers.
+    tail_hub: Option<TailHubRef>,
 }
 
 pub type InserterRef = Arc<Inserter>;
@@ -66,6 +77,14 @@ impl Inserter {
             catalog_manager,
             partition_manager,
             datanode_manager,
+            tail_hub: None,
+        }
+    }
+
+    pub fn with_tail_hub(self, tail_hub: TailHubRef) -> Self {
+        Self {
+            tail_hub: Some(tail_hub),
+            ..self
         }
     }
 
@@ -73,17 +92,21 @@ impl Inserter {
         &self,
         requests: InsertRequests,
         ctx: QueryContextRef,
+        protocol: &str,
         statement_executor: &StatementExecutor,
     ) -> Result<Output> {
         let row_inserts = ColumnToRow::convert(requests)?;
-        self.handle_row_inserts(row_inserts, ctx, statement_executor)
+        self.handle_row_inserts(row_inserts, ctx, protocol, statement_executor)
             .await
     }
 
+    /// Handles row inserts ingested by `protocol`, e.g. "influxdb", which labels the
+    /// per-table ingest metrics.
     pub async fn handle_row_inserts(
         &self,
         mut requests: RowInsertRequests,
         ctx: QueryContextRef,
+        protocol: &str,
         statement_executor: &StatementExecutor,
     ) -> Result<Output> {
         // remove empty requests
@@ -95,8 +118,15 @@ impl Inserter {
         });
         validate_column_count_match(&requests)?;
 
-        self.create_or_alter_tables_on_demand(&requests, &ctx, statement_executor)
+        self.create_or_alter_tables_on_demand(&requests, &ctx, protocol, statement_executor)
             .await?;
+        let ingest_stats = TableIngestStats::collect(&requests, &ctx);
+        // Only keeps a copy of requests if someone is tailing rows.
+        let tail_requests = self
+            .tail_hub
+            .as_ref()
+            .filter(|hub| hub.has_subscribers())
+            .map(|_| requests.clone());
         let inserts = RowToRegion::new(
             self.catalog_manager.as_ref(),
             self.partition_manager.as_ref(),
@@ -106,6 +136,12 @@ impl Inserter {
         .await?;
 
         let affected_rows = self.do_request(inserts, &ctx).await?;
+        for stats in ingest_stats {
+            stats.record(protocol);
+        }
+        if let (Some(hub), Some(requests)) = (&self.tail_hub, tail_requests) {
+            hub.publish(ctx.current_catalog(), ctx.current_schema(), &requests);
+        }
         Ok(Output::AffectedRows(affected_rows as _))
     }
 
@@ -158,45 +194,91 @@ impl Inserter {
             dbname: ctx.get_db_string(),
         });
 
-        let tasks = self
-            .group_requests_by_peer(requests)
-            .await?
+        let (inserts, shadow_inserts) = self.group_requests_by_peer(requests).await?;
+        let spawn_insert = |peer: Peer, inserts: RegionInsertRequests| {
+            let request = request_factory.build_insert(inserts);
+            let datanode_manager = self.datanode_manager.clone();
+            common_runtime::spawn_write(async move {
+                datanode_manager
+                    .datanode(&peer)
+                    .await
+                    .handle(request)
+                    .await
+                    .context(RequestInsertsSnafu)
+            })
+        };
+        let tasks = inserts
             .into_iter()
-            .map(|(peer, inserts)| {
-                let request = request_factory.build_insert(inserts);
-                let datanode_manager = self.datanode_manager.clone();
-                common_runtime::spawn_write(async move {
-                    datanode_manager
-                        .datanode(&peer)
-                        .await
-                        .handle(request)
-                        .await
-                        .context(RequestInsertsSnafu)
-                })
-            });
-        let results = future::try_join_all(tasks).await.context(JoinTaskSnafu)?;
+            .map(|(peer, inserts)| spawn_insert(peer, inserts))
+            .collect::<Vec<_>>();
+        let shadow_tasks = shadow_inserts
+            .into_iter()
+            .map(|(peer, inserts)| spawn_insert(peer, inserts))
+            .collect::<Vec<_>>();
+        let (results, shadow_results) = future::try_join(
+            future::try_join_all(tasks),
+            future::try_join_all(shadow_tasks),
+        )
+        .await
+        .context(JoinTaskSnafu)?;
 
+        // Rows written to shadow regions are duplicates, so they are not counted.
+        for result in shadow_results {
+            let _ = result?;
+        }
         let affected_rows = results.into_iter().sum::<Result<u64>>()?;
         crate::metrics::DIST_INGEST_ROW_COUNT.inc_by(affected_rows);
         Ok(affected_rows)
     }
 
+    /// Groups requests by the leader peer of their regions.
+    ///
+    /// Also returns the requests to the shadow regions of regions being rebuilt, grouped
+    /// by peer. A shadow region is always placed on the peer of its source region.
     async fn group_requests_by_peer(
         &self,
         requests: RegionInsertRequests,
-    ) -> Result<HashMap<Peer, RegionInsertRequests>> {
+    ) -> Result<(
+        HashMap<Peer, RegionInsertRequests>,
+        HashMap<Peer, RegionInsertRequests>,
+    )> {
         let mut inserts: HashMap<Peer, RegionInsertRequests> = HashMap::new();
+        let mut shadow_inserts: HashMap<Peer, RegionInsertRequests> = HashMap::new();
 
         for req in requests.requests {
+            let region_id = RegionId::from_u64(req.region_id);
+            // Rows written while backfilling the shadow region might be overwritten
+            // by rows scanned before them.
+            ensure!(
+                !self
+                    .partition_manager
+                    .is_write_fenced(region_id)
+                    .await
+                    .context(FindShadowRegionSnafu { region_id })?,
+                RegionWriteFencedSnafu { region_id }
+            );
             let peer = self
                 .partition_manager
-                .find_region_leader(req.region_id.into())
+                .find_region_leader(region_id)
                 .await
                 .context(FindRegionLeaderSnafu)?;
+            if let Some((shadow_region_id, primary_keys)) = self
+                .partition_manager
+                .find_shadow_region(region_id)
+                .await
+                .context(FindShadowRegionSnafu { region_id })?
+            {
+                let shadow_req = build_shadow_request(&req, shadow_region_id, &primary_keys);
+                shadow_inserts
+                    .entry(peer.clone())
+                    .or_default()
+                    .requests
+                    .push(shadow_req);
+            }
             inserts.entry(peer).or_default().requests.push(req);
         }
 
-        Ok(inserts)
+        Ok((inserts, shadow_inserts))
     }
 
     // check if tables already exist:
@@ -206,6 +288,7 @@ impl Inserter {
         &self,
         requests: &RowInsertRequests,
         ctx: &QueryContextRef,
+        protocol: &str,
         statement_executor: &StatementExecutor,
     ) -> Result<()> {
         // TODO(jeremy): create and alter in batch?
@@ -213,13 +296,24 @@ impl Inserter {
             let catalog = ctx.current_catalog();
             let schema = ctx.current_schema();
             let table = self.get_table(catalog, schema, &req.table_name).await?;
-            match table {
+            let kind = match table {
                 Some(table) => {
                     validate_request_with_table(req, &table)?;
-                    self.alter_table_on_demand(req, table, ctx, statement_executor)
-                        .await?
+                    let altered = self
+                        .alter_table_on_demand(req, table, ctx, statement_executor)
+                        .await?;
+                    altered.then_some("alter")
                 }
-                None => self.create_table(req, ctx, statement_executor).await?,
+                None => {
+                    self.create_table(req, ctx, statement_executor).await?;
+                    Some("create")
+                }
+            };
+            if let Some(kind) = kind {
+                let table_name = format_full_table_name(catalog, schema, &req.table_name);
+                crate::metrics::TABLE_INGEST_SCHEMA_CHANGES
+                    .with_label_values(&[&table_name, protocol, kind])
+                    .inc();
             }
         }
 
@@ -238,13 +332,14 @@ impl Inserter {
             .context(CatalogSnafu)
     }
 
+    /// Adds columns of the request missing in the table, returns whether the table is altered.
     async fn alter_table_on_demand(
         &self,
         req: &RowInsertRequest,
         table: TableRef,
         ctx: &QueryContextRef,
         statement_executor: &StatementExecutor,
-    ) -> Result<()> {
+    ) -> Result<bool> {
         let catalog_name = ctx.current_catalog();
         let schema_name = ctx.current_schema();
         let table_name = table.table_info().name.clone();
@@ -254,7 +349,7 @@ impl Inserter {
         let add_columns = extract_new_columns(&table.schema(), column_exprs)
             .context(FindNewColumnsOnInsertionSnafu)?;
         let Some(add_columns) = add_columns else {
-            return Ok(());
+            return Ok(false);
         };
 
         info!(
@@ -277,7 +372,7 @@ impl Inserter {
                     "Successfully added new columns to table: {}.{}.{}",
                     catalog_name, schema_name, table_name
                 );
-                Ok(())
+                Ok(true)
             }
             Err(err) => {
                 error!(
@@ -330,6 +425,73 @@ impl Inserter {
     }
 }
 
+/// Rows and encoded bytes of row inserts to a table.
+struct TableIngestStats {
+    table_name: String,
+    rows: u64,
+    bytes: u64,
+}
+
+impl TableIngestStats {
+    fn collect(requests: &RowInsertRequests, ctx: &QueryContextRef) -> Vec<TableIngestStats> {
+        requests
+            .inserts
+            .iter()
+            .filter_map(|req| {
+                let rows = req.rows.as_ref()?;
+                Some(TableIngestStats {
+                    table_name: format_full_table_name(
+                        ctx.current_catalog(),
+                        ctx.current_schema(),
+                        &req.table_name,
+                    ),
+                    rows: rows.rows.len() as u64,
+                    bytes: rows.encoded_len() as u64,
+                })
+            })
+            .collect()
+    }
+
+    fn record(&self, protocol: &str) {
+        let labels = [self.table_name.as_str(), protocol];
+        crate::metrics::TABLE_INGEST_ROWS
+            .with_label_values(&labels)
+            .inc_by(self.rows);
+        crate::metrics::TABLE_INGEST_BYTES
+            .with_label_values(&labels)
+            .inc_by(self.bytes);
+        if self.rows > 0 {
+            crate::metrics::TABLE_INGEST_AVG_ROW_BYTES
+                .with_label_values(&labels)
+                .set((self.bytes / self.rows) as i64);
+        }
+    }
+}
+
+/// Builds the request to write the same rows to the shadow region, whose primary key
+/// consists of `primary_keys`.
+fn build_shadow_request(
+    req: &RegionInsertRequest,
+    shadow_region_id: RegionId,
+    primary_keys: &[String],
+) -> RegionInsertRequest {
+    let mut shadow_req = req.clone();
+    shadow_req.region_id = shadow_region_id.as_u64();
+    if let Some(rows) = &mut shadow_req.rows {
+        for column in &mut rows.schema {
+            if column.semantic_type == SemanticType::Timestamp as i32 {
+                continue;
+            }
+            column.semantic_type = if primary_keys.contains(&column.column_name) {
+                SemanticType::Tag as i32
+            } else {
+                SemanticType::Field as i32
+            };
+        }
+    }
+    shadow_req
+}
+
 fn validate_column_count_match(requests: &RowInsertRequests) -> Result<()> {
     for request in &requests.inserts {
         let rows = request.rows.as_ref().unwrap();
@@ -389,8 +551,11 @@ fn build_create_table_expr(
 
 #[cfg(test)]
 mod tests {
+    use api::v1::value::ValueData;
+    use api::v1::{Row, Rows, Value};
     use datatypes::prelude::{ConcreteDataType, Value as DtValue};
     use datatypes::schema::{ColumnDefaultConstraint, ColumnSchema as DtColumnSchema};
+    use session::context::QueryContext;
 
     use super::*;
 
@@ -434,4 +599,54 @@ mod tests {
         // Neither of the above cases.
         assert!(validate_required_columns(request_schema, &schema).is_err());
     }
+
+    #[test]
+    fn test_table_ingest_stats() {
+        let rows = Rows {
+            schema: vec![ColumnSchema {
+                column_name: "host".to_string(),
+                ..Default::default()
+            }],
+            rows: vec![
+                Row {
+                    values: vec![Value {
+                        value_data: Some(ValueData::StringValue("a".to_string())),
+                    }],
+                },
+                Row {
+                    values: vec![Value {
+                        value_data: Some(ValueData::StringValue("bcd".to_string())),
+                    }],
+                },
+            ],
+        };
+        let bytes = rows.encoded_len() as u64;
+        let requests = RowInsertRequests {
+            inserts: vec![RowInsertRequest {
+                table_name: "monitor".to_string(),
+                rows: Some(rows),
+            }],
+        };
+
+        let stats = TableIngestStats::collect(&requests, &QueryContext::arc());
+        assert_eq!(1, stats.len());
+        assert_eq!("greptime.public.monitor", stats[0].table_name);
+        assert_eq!(2, stats[0].rows);
+        assert_eq!(bytes, stats[0].bytes);
+
+        stats[0].record("influxdb");
+        let labels = ["greptime.public.monitor", "influxdb"];
+        assert_eq!(
+            2,
+            crate::metrics::TABLE_INGEST_ROWS
+                .with_label_values(&labels)
+                .get()
+        );
+        assert_eq!(
+            (bytes / 2) as i64,
+            crate::metrics::TABLE_INGEST_AVG_ROW_BYTES
+                .with_label_values(&labels)
+                .get()
+        );
+    }
 }
@@ -12,6 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+pub mod capture;
 pub mod delete;
 pub mod error;
 pub mod expr_factory;
@@ -22,4 +22,32 @@ lazy_static! {
         register_int_counter!("table_operator_ingest_rows", "table operator ingest rows").unwrap();
     pub static ref DIST_DELETE_ROW_COUNT: IntCounter =
         register_int_counter!("table_operator_delete_rows", "table operator delete rows").unwrap();
+    /// Rows ingested by row inserts, partitioned by table and source protocol.
+    pub static ref TABLE_INGEST_ROWS: IntCounterVec = register_int_counter_vec!(
+        "table_operator_table_ingest_rows",
+        "table operator table ingest rows",
+        &["table", "protocol"]
+    )
+    .unwrap();
+    /// Encoded bytes of rows ingested by row inserts.
+    pub static ref TABLE_INGEST_BYTES: IntCounterVec = register_int_counter_vec!(
+        "table_operator_table_ingest_bytes",
+        "table operator table ingest bytes",
+        &["table", "protocol"]
+    )
+    .unwrap();
+    /// Average encoded bytes of rows of the last row insert to a table.
+    pub static ref TABLE_INGEST_AVG_ROW_BYTES: IntGaugeVec = register_int_gauge_vec!(
+        "table_operator_table_ingest_avg_row_bytes",
+        "table operator table ingest average row bytes",
+        &["table", "protocol"]
+    )
+    .unwrap();
+    /// Tables created or altered on demand by row inserts, `kind` is "create" or "alter".
+    pub static ref TABLE_INGEST_SCHEMA_CHANGES: IntCounterVec = register_int_counter_vec!(
+        "table_operator_table_ingest_schema_changes",
+        "table operator table ingest schema changes",
+        &["table", "protocol", "kind"]
+    )
+    .unwrap();
 }
@@ -12,15 +12,21 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+mod admin;
 mod backup;
+mod copy_query_to;
 mod copy_table_from;
 mod copy_table_to;
 mod ddl;
 mod describe;
 mod dml;
+mod function;
+mod pipeline;
 mod show;
+mod tag_values;
 mod tql;
 
+use std::path::PathBuf;
 use std::str::FromStr;
 use std::sync::Arc;
 
@@ -28,6 +34,7 @@ use catalog::CatalogManagerRef;
 use common_error::ext::BoxedError;
 use common_meta::cache_invalidator::CacheInvalidatorRef;
 use common_meta::ddl::DdlTaskExecutorRef;
+use common_meta::key::pipeline::{PipelineManager, PipelineManagerRef};
 use common_meta::key::{TableMetadataManager, TableMetadataManagerRef};
 use common_meta::kv_backend::KvBackendRef;
 use common_meta::table_name::TableName;
@@ -50,6 +57,7 @@ use table::engine::TableReference;
 use table::requests::{CopyDatabaseRequest, CopyDirection, CopyTableRequest};
 use table::TableRef;
 
+use crate::capture::RegionCapturerRef;
 use crate::error::{
     self, CatalogSnafu, ExecLogicalPlanSnafu, ExternalSnafu, InvalidSqlSnafu, PlanStatementSnafu,
     Result, TableNotFoundSnafu,
@@ -65,8 +73,12 @@ pub struct StatementExecutor {
     ddl_executor: DdlTaskExecutorRef,
     table_metadata_manager: TableMetadataManagerRef,
     partition_manager: PartitionRuleManagerRef,
+    pipeline_manager: PipelineManagerRef,
     cache_invalidator: CacheInvalidatorRef,
     inserter: InserterRef,
+    region_capturer: Option<RegionCapturerRef>,
+    /// Root directory of locations of `ADMIN capture_query`.
+    capture_dir: Option<PathBuf>,
 }
 
 impl StatementExecutor {
@@ -83,12 +95,31 @@ impl StatementExecutor {
             query_engine,
             ddl_executor: ddl_task_executor,
             table_metadata_manager: Arc::new(TableMetadataManager::new(kv_backend.clone())),
-            partition_manager: Arc::new(PartitionRuleManager::new(kv_backend)),
+            partition_manager: Arc::new(PartitionRuleManager::new(kv_backend.clone())),
+            pipeline_manager: Arc::new(PipelineManager::new(kv_backend)),
             cache_invalidator,
             inserter,
+            region_capturer: None,
+            capture_dir: None,
         }
     }
 
+    /// Sets the capturer to copy region files in `ADMIN capture_query`.
+    ///
+    /// Region files are not captured if the capturer is absent.
+    pub fn with_region_capturer(mut self, region_capturer: RegionCapturerRef) -> Self {
+        self.region_capturer = Some(region_capturer);
+        self
+    }
+
+    /// Sets the directory `ADMIN capture_query` writes captures under.
+    ///
+    /// Capturing queries is disabled if the directory is absent.
+    pub fn with_capture_dir(mut self, capture_dir: impl Into<PathBuf>) -> Self {
+        self.capture_dir = Some(capture_dir.into());
+        self
+    }
+
     #[tracing::instrument(skip_all)]
     pub async fn execute_stmt(
         &self,
@@ -124,13 +155,15 @@ impl StatementExecutor {
                         .copy_table_to(req, query_ctx)
                         .await
                         .map(Output::AffectedRows),
-                    CopyDirection::Import => self
-                        .copy_table_from(req, query_ctx)
-                        .await
-                        .map(Output::AffectedRows),
+                    CopyDirection::Import => self.copy_table_from(req, query_ctx).await,
                 }
             }
 
+            Statement::Copy(sql::statements::copy::Copy::CopyQueryTo(arg)) => self
+                .copy_query_to(arg, query_ctx)
+                .await
+                .map(Output::AffectedRows),
+
             Statement::Copy(sql::statements::copy::Copy::CopyDatabase(arg)) => {
                 self.copy_database(to_copy_database_request(arg, &query_ctx)?)
                     .await
@@ -161,6 +194,11 @@ impl StatementExecutor {
                 let table_name = TableName::new(catalog, schema, table);
                 self.truncate_table(table_name).await
             }
+            Statement::CreatePipeline(stmt) => self.create_pipeline(stmt, query_ctx).await,
+            Statement::DropPipeline(stmt) => self.drop_pipeline(stmt, query_ctx).await,
+            Statement::CreateFunction(stmt) => self.create_function(stmt, query_ctx).await,
+
+            Statement::Admin(admin) => self.execute_admin(admin, query_ctx).await,
 
             Statement::CreateDatabase(stmt) => {
                 self.create_database(
@@ -0,0 +1,373 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+
+use common_datasource::object_store::fs::build_fs_backend;
+use common_meta::ddl::ExecutorContext;
+use common_query::logical_plan::Expr;
+use common_query::Output;
+use common_recordbatch::RecordBatches;
+use common_telemetry::{info, tracing, warn};
+use datafusion::datasource::DefaultTableSource;
+use datafusion_expr::{or, Expr as DfExpr, LogicalPlan as DfLogicalPlan, TableScan};
+use datatypes::prelude::ConcreteDataType;
+use datatypes::schema::{ColumnSchema, Schema};
+use datatypes::vectors::StringVector;
+use object_store::ObjectStore;
+use query::parser::{QueryLanguageParser, QueryStatement};
+use query::plan::LogicalPlan;
+use session::context::QueryContextRef;
+use snafu::{ensure, OptionExt, ResultExt};
+use sql::statements::admin::Admin;
+use sql::statements::statement::Statement;
+use store_api::region_engine::RegionCaptureRequest;
+use table::metadata::{RawTableInfo, TableType};
+use table::table::adapter::DfTableProviderAdapter;
+use table::TableRef;
+
+use crate::error::{
+    AccessCapturePathSnafu, BuildBackendSnafu, BuildRecordBatchSnafu, CaptureDisabledSnafu,
+    CaptureRegionSnafu, EncodeJsonSnafu, FindTablePartitionRuleSnafu, InvalidCaptureLocationSnafu,
+    InvalidSqlSnafu, ParseQuerySnafu, ProcedureNotFoundSnafu, QueryProcedureStateSnafu,
+    ReadRecordBatchSnafu, Result, UnexpectedSnafu, WriteObjectSnafu,
+};
+use crate::statement::StatementExecutor;
+
+const PROCEDURE_STATE_COLUMN: &str = "procedure_state";
+const CAPTURED_FILE_COLUMN: &str = "file";
+
+/// File of the captured query.
+const CAPTURE_QUERY_FILE: &str = "query.sql";
+/// File of the session context to run the captured query.
+const CAPTURE_CONTEXT_FILE: &str = "context.json";
+/// File of the plan of the captured query.
+const CAPTURE_PLAN_FILE: &str = "plan.txt";
+/// Directory of metadata of tables the captured query reads.
+const CAPTURE_TABLES_DIR: &str = "tables/";
+/// Directory of files of regions the captured query reads.
+const CAPTURE_REGIONS_DIR: &str = "regions/";
+
+impl StatementExecutor {
+    #[tracing::instrument(skip_all)]
+    pub(super) async fn execute_admin(
+        &self,
+        admin: Admin,
+        query_ctx: QueryContextRef,
+    ) -> Result<Output> {
+        match admin {
+            Admin::ProcedureState(procedure_id) => self.procedure_state(&procedure_id).await,
+            Admin::CaptureQuery { query, location } => {
+                self.capture_query(&query, &location, query_ctx).await
+            }
+        }
+    }
+
+    /// Returns the state of the procedure in json, e.g. `{"status":"Done"}`.
+    async fn procedure_state(&self, procedure_id: &str) -> Result<Output> {
+        let state = self
+            .ddl_executor
+            .query_procedure_state(&ExecutorContext::default(), procedure_id)
+            .await
+            .context(QueryProcedureStateSnafu { procedure_id })?
+            .context(ProcedureNotFoundSnafu { procedure_id })?;
+        let state = serde_json::to_string(&state).context(EncodeJsonSnafu)?;
+
+        let schema = Arc::new(Schema::new(vec![ColumnSchema::new(
+            PROCEDURE_STATE_COLUMN,
+            ConcreteDataType::string_datatype(),
+            false,
+        )]));
+        let column = Arc::new(StringVector::from(vec![state])) as _;
+        let records =
+            RecordBatches::try_from_columns(schema, vec![column]).context(BuildRecordBatchSnafu)?;
+        Ok(Output::RecordBatches(records))
+    }
+
+    /// Captures files to reproduce the `query` offline to the local directory `location`,
+    /// which is relative to the capture directory.
+    ///
+    /// The capture contains the query, its plan, metadata of tables it reads, and the
+    /// manifests and SSTs of regions the query scans. Region files are only captured if
+    /// a [RegionCapturer](crate::capture::RegionCapturer) is set. Returns paths of
+    /// captured files relative to the `location`.
+    async fn capture_query(
+        &self,
+        query: &str,
+        location: &str,
+        query_ctx: QueryContextRef,
+    ) -> Result<Output> {
+        let stmt = QueryLanguageParser::parse_sql(query).context(ParseQuerySnafu)?;
+        ensure!(
+            matches!(stmt, QueryStatement::Sql(Statement::Query(_))),
+            InvalidSqlSnafu {
+                err_msg: format!("only queries can be captured, actual: {query}"),
+            }
+        );
+        let capture_dir = self.capture_dir.as_ref().context(CaptureDisabledSnafu)?;
+        let capture_path = resolve_capture_location(capture_dir, location).await?;
+        let object_store =
+            build_fs_backend(&capture_path.to_string_lossy()).context(BuildBackendSnafu)?;
+        let mut files = Vec::new();
+
+        write_capture_file(&object_store, CAPTURE_QUERY_FILE, query.as_bytes().to_vec()).await?;
+        files.push(CAPTURE_QUERY_FILE.to_string());
+        let context = serde_json::json!({
+            "catalog": query_ctx.current_catalog(),
+            "schema": query_ctx.current_schema(),
+        });
+        let context = serde_json::to_vec_pretty(&context).context(EncodeJsonSnafu)?;
+        write_capture_file(&object_store, CAPTURE_CONTEXT_FILE, context).await?;
+        files.push(CAPTURE_CONTEXT_FILE.to_string());
+
+        let plan = self.plan(stmt, query_ctx.clone()).await?;
+        let explain = self.explain_query(query, query_ctx).await?;
+        write_capture_file(&object_store, CAPTURE_PLAN_FILE, explain.into_bytes()).await?;
+        files.push(CAPTURE_PLAN_FILE.to_string());
+
+        let LogicalPlan::DfPlan(plan) = plan;
+        for scan in collect_table_scans(&plan) {
+            let table_info = scan.table.table_info();
+            let table_id = table_info.table_id();
+            let table_name = table_info.full_table_name();
+            let region_routes = self
+                .partition_manager
+                .find_table_route(table_id)
+                .await
+                .context(FindTablePartitionRuleSnafu {
+                    table_name: &table_name,
+                })?;
+            let metadata = serde_json::json!({
+                "table_info": RawTableInfo::from((*table_info).clone()),
+                "region_routes": &region_routes.0,
+            });
+            let metadata = serde_json::to_vec_pretty(&metadata).context(EncodeJsonSnafu)?;
+            let path = format!("{CAPTURE_TABLES_DIR}{table_name}.json");
+            write_capture_file(&object_store, &path, metadata).await?;
+            files.push(path);
+
+            let Some(capturer) = &self.region_capturer else {
+                warn!("Region files of table {table_name} are not captured, no region capturer");
+                continue;
+            };
+            let filters = scan
+                .filter
+                .map(|filter| vec![Expr::from(filter)])
+                .unwrap_or_default();
+            for route in &region_routes.0 {
+                let region_id = route.region.id;
+                let region_dir = format!("{CAPTURE_REGIONS_DIR}{}/", region_id.as_u64());
+                let request = RegionCaptureRequest {
+                    filters: filters.clone(),
+                    target_dir: capture_path.join(&region_dir).to_string_lossy().to_string(),
+                };
+                let region_files = capturer
+                    .capture_region(region_id, request)
+                    .await
+                    .context(CaptureRegionSnafu { region_id })?;
+                files.extend(
+                    region_files
+                        .into_iter()
+                        .map(|file| format!("{region_dir}{file}")),
+                );
+            }
+        }
+
+        info!(
+            "Captured query {query} to {}, files: {}",
+            capture_path.display(),
+            files.len()
+        );
+
+        let schema = Arc::new(Schema::new(vec![ColumnSchema::new(
+            CAPTURED_FILE_COLUMN,
+            ConcreteDataType::string_datatype(),
+            false,
+        )]));
+        let column = Arc::new(StringVector::from(files)) as _;
+        let records =
+            RecordBatches::try_from_columns(schema, vec![column]).context(BuildRecordBatchSnafu)?;
+        Ok(Output::RecordBatches(records))
+    }
+
+    /// Returns the output of `EXPLAIN` the `query`.
+    async fn explain_query(&self, query: &str, query_ctx: QueryContextRef) -> Result<String> {
+        let stmt =
+            QueryLanguageParser::parse_sql(&format!("EXPLAIN {query}")).context(ParseQuerySnafu)?;
+        let output = self.plan_exec(stmt, query_ctx).await?;
+        let records = match output {
+            Output::Stream(stream) => RecordBatches::try_collect(stream)
+                .await
+                .context(ReadRecordBatchSnafu)?,
+            Output::RecordBatches(records) => records,
+            Output::AffectedRows(_) => {
+                return UnexpectedSnafu {
+                    violated: "Expected records of explain",
+                }
+                .fail()
+            }
+        };
+        records.pretty_print().context(ReadRecordBatchSnafu)
+    }
+}
+
+/// A table scanned by a query.
+struct CapturedScan {
+    table: TableRef,
+    /// Filter of rows to read from the table, `None` if the query reads all rows.
+    filter: Option<DfExpr>,
+}
+
+/// Collects tables scanned by the `plan`.
+///
+/// Only a filter right above a scan is considered as the filter of the scan, other
+/// filters may not apply to all rows the scan reads. Filters of scans of the same
+/// table are combined with `OR`.
+fn collect_table_scans(plan: &DfLogicalPlan) -> Vec<CapturedScan> {
+    let mut scans = Vec::new();
+    visit_table_scans(plan, &mut scans);
+    scans
+}
+
+fn visit_table_scans(plan: &DfLogicalPlan, scans: &mut Vec<CapturedScan>) {
+    let (scan, filter) = match plan {
+        DfLogicalPlan::Filter(filter) => match filter.input.as_ref() {
+            DfLogicalPlan::TableScan(scan) => (scan, Some(&filter.predicate)),
+            _ => return visit_inputs(plan, scans),
+        },
+        DfLogicalPlan::TableScan(scan) => (scan, None),
+        _ => return visit_inputs(plan, scans),
+    };
+    let Some(table) = scan_table(scan) else {
+        return;
+    };
+
+    let table_id = table.table_info().table_id();
+    match scans
+        .iter_mut()
+        .find(|s| s.table.table_info().table_id() == table_id)
+    {
+        Some(existing) => {
+            existing.filter = match (existing.filter.take(), filter) {
+                (Some(left), Some(right)) => Some(or(left, right.clone())),
+                _ => None,
+            };
+        }
+        None => scans.push(CapturedScan {
+            table,
+            filter: filter.cloned(),
+        }),
+    }
+}
+
+fn visit_inputs(plan: &DfLogicalPlan, scans: &mut Vec<CapturedScan>) {
+    for input in plan.inputs() {
+        visit_table_scans(input, scans);
+    }
+}
+
+/// Returns the base table the `scan` reads.
+fn scan_table(scan: &TableScan) -> Option<TableRef> {
+    let source = scan.source.as_any().downcast_ref::<DefaultTableSource>()?;
+    let provider = source
+        .table_provider
+        .as_any()
+        .downcast_ref::<DfTableProviderAdapter>()?;
+    let table = provider.table();
+    (table.table_type() == TableType::Base).then_some(table)
+}
+
+async fn write_capture_file(object_store: &ObjectStore, path: &str, data: Vec<u8>) -> Result<()> {
+    object_store
+        .write(path, data)
+        .await
+        .context(WriteObjectSnafu { path })
+}
+
+/// Resolves the path of the capture `location` under the `capture_dir`.
+///
+/// The location must be a relative path without `..`, and must not escape the capture
+/// directory through symbolic links.
+async fn resolve_capture_location(capture_dir: &Path, location: &str) -> Result<PathBuf> {
+    let relative = Path::new(location);
+    ensure!(
+        relative
+            .components()
+            .all(|component| matches!(component, Component::Normal(_)))
+            && relative.components().next().is_some(),
+        InvalidCaptureLocationSnafu {
+            capture_location: location,
+            reason: "expect a relative path without `.` or `..`",
+        }
+    );
+
+    let root = tokio::fs::canonicalize(capture_dir)
+        .await
+        .context(AccessCapturePathSnafu {
+            path: capture_dir.to_string_lossy(),
+        })?;
+    let path = root.join(relative);
+    // Checks the nearest existing ancestor as the location may not exist yet.
+    let mut existing = path.as_path();
+    while !tokio::fs::try_exists(existing)
+        .await
+        .context(AccessCapturePathSnafu {
+            path: existing.to_string_lossy(),
+        })?
+    {
+        // Safety: the root exists, so there is always an existing ancestor.
+        existing = existing.parent().unwrap();
+    }
+    let existing = tokio::fs::canonicalize(existing)
+        .await
+        .context(AccessCapturePathSnafu {
+            path: existing.to_string_lossy(),
+        })?;
+    ensure!(
+        existing.starts_with(&root),
+        InvalidCaptureLocationSnafu {
+            capture_location: location,
+            reason: "the location is outside of the capture directory",
+        }
+    );
+
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_resolve_capture_location() {
+        let capture_dir = std::env::temp_dir();
+        let root = capture_dir.canonicalize().unwrap();
+        assert_eq!(
+            root.join("q1/capture"),
+            resolve_capture_location(&capture_dir, "q1/capture")
+                .await
+                .unwrap()
+        );
+
+        for location in ["", ".", "../q1", "q1/../../q2", "/tmp/q1"] {
+            assert!(
+                resolve_capture_location(&capture_dir, location)
+                    .await
+                    .is_err(),
+                "location: {location}"
+            );
+        }
+    }
+}
@@ -0,0 +1,510 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::hash_map::Entry;
+use std::collections::{BTreeMap, HashMap};
+use std::time::Duration;
+
+use chrono::NaiveDateTime;
+use common_datasource::file_format::Format;
+use common_datasource::object_store::build_backend;
+use common_query::Output;
+use common_recordbatch::adapter::DfRecordBatchStreamAdapter;
+use common_runtime::JoinHandle;
+use common_telemetry::{info, tracing};
+use datafusion::physical_plan::stream::RecordBatchStreamAdapter;
+use datafusion::physical_plan::SendableRecordBatchStream as DfSendableRecordBatchStream;
+use datatypes::arrow::array::{AsArray, UInt32Array};
+use datatypes::arrow::compute::{cast, take};
+use datatypes::arrow::datatypes::{DataType, Int64Type, TimeUnit};
+use datatypes::arrow::record_batch::RecordBatch as DfRecordBatch;
+use datatypes::arrow::util::display::array_value_to_string;
+use futures::channel::mpsc;
+use futures::{SinkExt, StreamExt};
+use object_store::ObjectStore;
+use query::parser::QueryStatement;
+use serde::Serialize;
+use session::context::QueryContextRef;
+use snafu::{ensure, OptionExt, ResultExt};
+use sql::statements::copy::CopyQueryToArgument;
+use sql::statements::statement::Statement;
+
+use crate::error::{self, ExecLogicalPlanSnafu, Result};
+use crate::statement::copy_table_to::{object_store_connection, stream_to_file};
+use crate::statement::StatementExecutor;
+
+/// Option to partition files by values of a column.
+const COPY_QUERY_PARTITION_BY_KEY: &str = "partition_by";
+/// Option to partition files by time windows of the partition column, e.g. '1d'.
+pub(crate) const COPY_PARTITION_INTERVAL_KEY: &str = "partition_interval";
+/// Format of the start of a time window in partition paths.
+const TIME_WINDOW_FORMAT: &str = "%Y%m%dT%H%M%SZ";
+/// Name of the manifest, which is written after all files are written.
+const MANIFEST_FILE: &str = "_manifest.json";
+/// Name of files in a partition.
+const PART_FILE_PREFIX: &str = "part-0";
+/// Partition value of nulls.
+const NULL_PARTITION: &str = "__null__";
+/// Max number of batches buffered for each partition.
+const PARTITION_CHANNEL_SIZE: usize = 4;
+
+/// Manifest of exported files.
+#[derive(Debug, Serialize)]
+struct CopyManifest {
+    rows: usize,
+    files: Vec<ExportedFile>,
+}
+
+#[derive(Debug, Serialize)]
+struct ExportedFile {
+    /// Path relative to the location.
+    path: String,
+    rows: usize,
+}
+
+/// How to partition exported files.
+pub(crate) struct PartitionSpec {
+    /// Name of the column to partition by.
+    pub(crate) column: String,
+    /// Partitions rows by time windows of the timestamp column if set, otherwise by values
+    /// of the column.
+    pub(crate) interval: Option<Duration>,
+}
+
+/// Parses the time window of partitions in the `with` options.
+pub(crate) fn parse_partition_interval(with: &HashMap<String, String>) -> Result<Option<Duration>> {
+    let Some(value) = with.get(COPY_PARTITION_INTERVAL_KEY) else {
+        return Ok(None);
+    };
+    let interval = humantime::parse_duration(value)
+        .ok()
+        .filter(|interval| interval.as_millis() > 0)
+        .with_context(|| error::InvalidCopyParameterSnafu {
+            key: COPY_PARTITION_INTERVAL_KEY,
+            value,
+        })?;
+    Ok(Some(interval))
+}
+
+impl StatementExecutor {
+    /// Writes results of the query to files under the location, which must be a directory.
+    ///
+    /// Results are written while the query is running. If `partition_by` is set, rows
+    /// of each value of the column are written to the `column=value/` sub-directory.
+    /// The manifest is written after all files are written, so a reader can check it to
+    /// know whether the export is completed.
+    #[tracing::instrument(skip_all)]
+    pub(crate) async fn copy_query_to(
+        &self,
+        arg: CopyQueryToArgument,
+        query_ctx: QueryContextRef,
+    ) -> Result<usize> {
+        ensure!(
+            arg.location.ends_with('/'),
+            error::InvalidCopyParameterSnafu {
+                key: "location",
+                value: arg.location,
+            }
+        );
+        let format = Format::try_from(&arg.with.map).context(error::ParseFileFormatSnafu)?;
+        let interval = parse_partition_interval(&arg.with.map)?;
+        let partition = match arg.with.get(COPY_QUERY_PARTITION_BY_KEY) {
+            Some(column) => Some(PartitionSpec {
+                column: column.clone(),
+                interval,
+            }),
+            None => {
+                ensure!(
+                    interval.is_none(),
+                    error::InvalidCopyParameterSnafu {
+                        key: COPY_PARTITION_INTERVAL_KEY,
+                        value: "requires partition_by",
+                    }
+                );
+                None
+            }
+        };
+
+        let plan = self
+            .plan(
+                QueryStatement::Sql(Statement::Query(arg.query)),
+                query_ctx.clone(),
+            )
+            .await?;
+        let output = self
+            .query_engine
+            .execute(plan, query_ctx)
+            .await
+            .context(ExecLogicalPlanSnafu)?;
+        let stream = match output {
+            Output::Stream(stream) => stream,
+            Output::RecordBatches(record_batches) => record_batches.as_stream(),
+            Output::AffectedRows(_) => {
+                return error::UnexpectedSnafu {
+                    violated: "Expected records of the query",
+                }
+                .fail()
+            }
+        };
+        let stream = Box::pin(DfRecordBatchStreamAdapter::new(stream));
+
+        let connection = object_store_connection(&arg.with.map, &arg.connection.map);
+        let object_store =
+            build_backend(&arg.location, &connection).context(error::BuildBackendSnafu)?;
+        info!(
+            "Copy query to: {}, partition by: {:?}, interval: {:?}",
+            arg.location,
+            partition.as_ref().map(|partition| &partition.column),
+            interval
+        );
+        write_to_directory(stream, partition.as_ref(), &format, object_store).await
+    }
+}
+
+/// Writes the `stream` to files under the directory of the `object_store`, then writes
+/// the manifest of the files.
+///
+/// Returns number of rows written.
+pub(crate) async fn write_to_directory(
+    stream: DfSendableRecordBatchStream,
+    partition: Option<&PartitionSpec>,
+    format: &Format,
+    object_store: ObjectStore,
+) -> Result<usize> {
+    let files = match partition {
+        Some(partition) => {
+            write_partitions(stream, partition, format, object_store.clone()).await?
+        }
+        None => {
+            let path = format!("{PART_FILE_PREFIX}{}", format.suffix());
+            let rows = stream_to_file(stream, format, object_store.clone(), &path).await?;
+            vec![ExportedFile { path, rows }]
+        }
+    };
+
+    let manifest = CopyManifest {
+        rows: files.iter().map(|file| file.rows).sum(),
+        files,
+    };
+    let content = serde_json::to_vec(&manifest).context(error::EncodeJsonSnafu)?;
+    object_store
+        .write(MANIFEST_FILE, content)
+        .await
+        .context(error::WriteObjectSnafu {
+            path: MANIFEST_FILE,
+        })?;
+
+    Ok(manifest.rows)
+}
+
+/// A partition file being written.
+struct PartitionWriter {
+    path: String,
+    sender: mpsc::Sender<datafusion_common::Result<DfRecordBatch>>,
+    handle: JoinHandle<Result<usize>>,
+}
+
+/// Writes rows of each partition to a file.
+async fn write_partitions(
+    mut stream: DfSendableRecordBatchStream,
+    partition: &PartitionSpec,
+    format: &Format,
+    object_store: ObjectStore,
+) -> Result<Vec<ExportedFile>> {
+    let column = partition.column.as_str();
+    let schema = stream.schema();
+    let column_idx = schema
+        .index_of(column)
+        .ok()
+        .context(error::InvalidCopyParameterSnafu {
+            key: COPY_QUERY_PARTITION_BY_KEY,
+            value: column,
+        })?;
+    if partition.interval.is_some() {
+        ensure!(
+            matches!(
+                schema.field(column_idx).data_type(),
+                DataType::Timestamp(..)
+            ),
+            error::InvalidCopyParameterSnafu {
+                key: COPY_QUERY_PARTITION_BY_KEY,
+                value: format!("{column} is not a timestamp column"),
+            }
+        );
+    }
+
+    let mut writers: HashMap<String, PartitionWriter> = HashMap::new();
+    let mut result = Ok(());
+    while let Some(batch) = stream.next().await {
+        let split = batch
+            .context(error::ReadDfRecordBatchSnafu)
+            .and_then(|batch| split_by_column(&batch, column_idx, column, partition.interval));
+        let split = match split {
+            Ok(split) => split,
+            Err(e) => {
+                result = Err(e);
+                break;
+            }
+        };
+
+        for (value, batch) in split {
+            let writer = match writers.entry(value) {
+                Entry::Occupied(entry) => entry.into_mut(),
+                Entry::Vacant(entry) => {
+                    let path = format!(
+                        "{column}={}/{PART_FILE_PREFIX}{}",
+                        entry.key(),
+                        format.suffix()
+                    );
+                    let writer =
+                        spawn_partition_writer(path, schema.clone(), *format, object_store.clone());
+                    entry.insert(writer)
+                }
+            };
+            // The writer stops receiving on failure, its error is returned by the join handle.
+            let _ = writer.sender.send(Ok(batch)).await;
+        }
+    }
+
+    // Waits for all writers even if the query fails, so no file is written after returning.
+    let mut files = Vec::with_capacity(writers.len());
+    for PartitionWriter {
+        path,
+        sender,
+        handle,
+    } in writers.into_values()
+    {
+        drop(sender);
+        match handle.await.context(error::JoinTaskSnafu).and_then(|r| r) {
+            Ok(rows) => files.push(ExportedFile { path, rows }),
+            Err(e) => {
+                if result.is_ok() {
+                    result = Err(e);
+                }
+            }
+        }
+    }
+    result?;
+
+    files.sort_unstable_by(|a, b| a.path.cmp(&b.path));
+    Ok(files)
+}
+
+fn spawn_partition_writer(
+    path: String,
+    schema: datatypes::arrow::datatypes::SchemaRef,
+    format: Format,
+    object_store: ObjectStore,
+) -> PartitionWriter {
+    let (sender, receiver) = mpsc::channel(PARTITION_CHANNEL_SIZE);
+    let stream = Box::pin(RecordBatchStreamAdapter::new(schema, receiver));
+    let file_path = path.clone();
+    let handle = common_runtime::spawn_bg(async move {
+        stream_to_file(stream, &format, object_store, &file_path).await
+    });
+
+    PartitionWriter {
+        path,
+        sender,
+        handle,
+    }
+}
+
+/// Splits the `batch` by values of the column at `column_idx`, or by time windows of the
+/// column if `interval` is set.
+fn split_by_column(
+    batch: &DfRecordBatch,
+    column_idx: usize,
+    column_name: &str,
+    interval: Option<Duration>,
+) -> Result<Vec<(String, DfRecordBatch)>> {
+    let column = match interval {
+        // Timestamps in milliseconds, so windows are computed regardless of the time unit.
+        Some(_) => cast(
+            &cast(
+                batch.column(column_idx),
+                &DataType::Timestamp(TimeUnit::Millisecond, None),
+            )
+            .context(error::SplitRecordBatchSnafu {
+                column: column_name,
+            })?,
+            &DataType::Int64,
+        )
+        .context(error::SplitRecordBatchSnafu {
+            column: column_name,
+        })?,
+        None => batch.column(column_idx).clone(),
+    };
+    let mut partitions: BTreeMap<String, Vec<u32>> = BTreeMap::new();
+    for row in 0..batch.num_rows() {
+        let value = if column.is_null(row) {
+            NULL_PARTITION.to_string()
+        } else if let Some(interval) = interval {
+            let millis = column.as_primitive::<Int64Type>().value(row);
+            time_window_start(millis, interval)
+        } else {
+            array_value_to_string(&column, row)
+                .map(|value| escape_partition_value(&value))
+                .context(error::SplitRecordBatchSnafu {
+                    column: column_name,
+                })?
+        };
+        partitions.entry(value).or_default().push(row as u32);
+    }
+
+    if partitions.len() == 1 {
+        let value = partitions.into_keys().next().unwrap();
+        return Ok(vec![(value, batch.clone())]);
+    }
+
+    partitions
+        .into_iter()
+        .map(|(value, rows)| {
+            let indices = UInt32Array::from(rows);
+            let columns = batch
+                .columns()
+                .iter()
+                .map(|array| take(array, &indices, None))
+                .collect::<std::result::Result<Vec<_>, _>>()
+                .and_then(|columns| DfRecordBatch::try_new(batch.schema(), columns))
+                .context(error::SplitRecordBatchSnafu {
+                    column: column_name,
+                })?;
+            Ok((value, columns))
+        })
+        .collect()
+}
+
+/// Returns the formatted start of the time window of length `interval` containing `millis`.
+fn time_window_start(millis: i64, interval: Duration) -> String {
+    let interval = interval.as_millis() as i64;
+    let start = millis.div_euclid(interval) * interval;
+    NaiveDateTime::from_timestamp_millis(start)
+        .map(|start| start.format(TIME_WINDOW_FORMAT).to_string())
+        .unwrap_or_else(|| start.to_string())
+}
+
+/// Escapes characters that can't be in a directory name.
+fn escape_partition_value(value: &str) -> String {
+    value.replace('%', "%25").replace('/', "%2F")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use datatypes::arrow::array::{Int64Array, StringArray, TimestampSecondArray};
+    use datatypes::arrow::datatypes::{DataType, Field, Schema};
+
+    use super::*;
+
+    #[test]
+    fn test_split_by_column() {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("date", DataType::Utf8, true),
+            Field::new("v", DataType::Int64, false),
+        ]));
+        let batch = DfRecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(StringArray::from(vec![
+                    Some("2024-01-02"),
+                    Some("2024/01/01"),
+                    None,
+                    Some("2024-01-02"),
+                ])),
+                Arc::new(Int64Array::from(vec![1, 2, 3, 4])),
+            ],
+        )
+        .unwrap();
+
+        let split = split_by_column(&batch, 0, "date", None).unwrap();
+        let split = split
+            .into_iter()
+            .map(|(value, batch)| {
+                let values = batch
+                    .column(1)
+                    .as_any()
+                    .downcast_ref::<Int64Array>()
+                    .unwrap()
+                    .values()
+                    .to_vec();
+                (value, values)
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec![
+                ("2024%2F01%2F01".to_string(), vec![2]),
+                ("2024-01-02".to_string(), vec![1, 4]),
+                (NULL_PARTITION.to_string(), vec![3]),
+            ],
+            split
+        );
+
+        // A single partition.
+        let batch = batch.slice(0, 1);
+        let split = split_by_column(&batch, 0, "date", None).unwrap();
+        assert_eq!(1, split.len());
+        assert_eq!(batch, split[0].1);
+    }
+
+    #[test]
+    fn test_split_by_time_window() {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("ts", DataType::Timestamp(TimeUnit::Second, None), true),
+            Field::new("v", DataType::Int64, false),
+        ]));
+        let batch = DfRecordBatch::try_new(
+            schema,
+            vec![
+                // 2024-01-01T00:00:00, 2024-01-01T23:59:59, 2024-01-02T00:00:00
+                Arc::new(TimestampSecondArray::from(vec![
+                    Some(1704067200),
+                    Some(1704153599),
+                    Some(1704153600),
+                    None,
+                ])),
+                Arc::new(Int64Array::from(vec![1, 2, 3, 4])),
+            ],
+        )
+        .unwrap();
+
+        let split = split_by_column(&batch, 0, "ts", Some(Duration::from_secs(86400))).unwrap();
+        let split = split
+            .into_iter()
+            .map(|(value, batch)| (value, batch.num_rows()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec![
+                ("20240101T000000Z".to_string(), 2),
+                ("20240102T000000Z".to_string(), 1),
+                (NULL_PARTITION.to_string(), 1),
+            ],
+            split
+        );
+    }
+
+    #[test]
+    fn test_parse_partition_interval() {
+        let mut with = HashMap::new();
+        assert_eq!(None, parse_partition_interval(&with).unwrap());
+        let _ = with.insert(COPY_PARTITION_INTERVAL_KEY.to_string(), "1h".to_string());
+        assert_eq!(
+            Some(Duration::from_secs(3600)),
+            parse_partition_interval(&with).unwrap()
+        );
+        let _ = with.insert(COPY_PARTITION_INTERVAL_KEY.to_string(), "0s".to_string());
+        assert!(parse_partition_interval(&with).is_err());
+    }
+}
@@ -25,9 +25,10 @@ use common_datasource::file_format::orc::{
 use common_datasource::file_format::{FileFormat, Format};
 use common_datasource::lister::{Lister, Source};
 use common_datasource::object_store::{build_backend, parse_url};
-use common_datasource::util::find_dir_and_filename;
+use common_datasource::util::{find_dir_and_filename, glob_to_regex, is_glob};
+use common_query::Output;
 use common_recordbatch::adapter::ParquetRecordBatchStreamAdapter;
-use common_recordbatch::DfSendableRecordBatchStream;
+use common_recordbatch::{DfSendableRecordBatchStream, RecordBatches};
 use common_telemetry::{debug, tracing};
 use datafusion::datasource::listing::PartitionedFile;
 use datafusion::datasource::object_store::ObjectStoreUrl;
@@ -36,21 +37,36 @@ use datafusion::parquet::arrow::ParquetRecordBatchStreamBuilder;
 use datafusion::physical_plan::metrics::ExecutionPlanMetricsSet;
 use datatypes::arrow::compute::can_cast_types;
 use datatypes::arrow::datatypes::{Schema, SchemaRef};
-use datatypes::vectors::Helper;
-use futures_util::StreamExt;
+use datatypes::prelude::ConcreteDataType;
+use datatypes::schema::{ColumnSchema, Schema as GtSchema};
+use datatypes::vectors::{Helper, StringVector, UInt64Vector, VectorRef};
+use futures_util::{StreamExt, TryStreamExt};
 use object_store::{Entry, EntryMode, ObjectStore};
 use regex::Regex;
 use session::context::QueryContextRef;
-use snafu::ResultExt;
+use snafu::{OptionExt, ResultExt};
 use table::engine::TableReference;
 use table::requests::{CopyTableRequest, InsertRequest};
 use tokio::io::BufReader;
 
-use crate::error::{self, IntoVectorsSnafu, Result};
+use crate::error::{self, BuildRecordBatchSnafu, IntoVectorsSnafu, Result};
 use crate::statement::StatementExecutor;
 
 const DEFAULT_BATCH_SIZE: usize = 8192;
 
+/// Option to map column names in files to column names in the table.
+const COPY_FROM_COLUMN_MAPPING_KEY: &str = "column_mapping";
+/// Option to count rows of each file without importing them.
+const COPY_FROM_DRY_RUN_KEY: &str = "dry_run";
+/// Option of the number of files to import concurrently.
+const COPY_FROM_PARALLELISM_KEY: &str = "parallelism";
+/// Imports files one by one by default, so rows with the same key in later files
+/// always overwrite rows in earlier files.
+const DEFAULT_COPY_FROM_PARALLELISM: usize = 1;
+
+const DRY_RUN_FILE_COLUMN: &str = "file";
+const DRY_RUN_ROWS_COLUMN: &str = "rows";
+
 impl StatementExecutor {
     async fn list_copy_from_entries(
         &self,
@@ -69,15 +85,28 @@ impl StatementExecutor {
             .transpose()
             .context(error::BuildRegexSnafu)?;
 
-        let source = if let Some(filename) = filename {
-            Source::Filename(filename)
-        } else {
-            Source::Dir
+        // Lists the directory and matches names with the glob if the file name has wildcards,
+        // the `PATTERN` option further filters the matched names.
+        let (source, list_regex, filter_regex) = match filename {
+            Some(filename) if is_glob(&filename) => {
+                let glob = Regex::new(&glob_to_regex(&filename)).context(error::BuildRegexSnafu)?;
+                (Source::Dir, Some(glob), regex)
+            }
+            Some(filename) => (Source::Filename(filename), regex, None),
+            None => (Source::Dir, regex, None),
         };
 
-        let lister = Lister::new(object_store.clone(), source.clone(), dir.to_string(), regex);
+        let lister = Lister::new(
+            object_store.clone(),
+            source.clone(),
+            dir.to_string(),
+            list_regex,
+        );
 
-        let entries = lister.list().await.context(error::ListObjectsSnafu)?;
+        let mut entries = lister.list().await.context(error::ListObjectsSnafu)?;
+        if let Some(regex) = filter_regex {
+            entries.retain(|entry| regex.is_match(entry.name()));
+        }
         debug!("Copy from dir: {dir:?}, {source:?}, entries: {entries:?}");
         Ok((object_store, entries))
     }
@@ -235,12 +264,16 @@ impl StatementExecutor {
         }
     }
 
+    /// Imports files at the location to the table.
+    ///
+    /// Returns the number of rows inserted, or the number of rows of each file without
+    /// inserting them in dry run mode.
     #[tracing::instrument(skip_all)]
     pub async fn copy_table_from(
         &self,
         req: CopyTableRequest,
         query_ctx: QueryContextRef,
-    ) -> Result<usize> {
+    ) -> Result<Output> {
         let table_ref = TableReference {
             catalog: &req.catalog_name,
             schema: &req.schema_name,
@@ -249,12 +282,24 @@ impl StatementExecutor {
         let table = self.get_table(&table_ref).await?;
 
         let format = Format::try_from(&req.with).context(error::ParseFileFormatSnafu)?;
+        let options = CopyFromOptions::try_from(&req.with)?;
+        let table_schema = table.schema().arrow_schema().clone();
+        if let Some(column) = options
+            .column_mapping
+            .values()
+            .find(|column| table_schema.column_with_name(column).is_none())
+        {
+            return error::InvalidCopyParameterSnafu {
+                key: COPY_FROM_COLUMN_MAPPING_KEY,
+                value: format!("column {column} not found in table"),
+            }
+            .fail();
+        }
 
         let (object_store, entries) = self.list_copy_from_entries(&req).await?;
 
+        // Checks schemas of all files before importing any of them.
         let mut files = Vec::with_capacity(entries.len());
-        let table_schema = table.schema().arrow_schema().clone();
-
         for entry in entries.iter() {
             if entry.metadata().mode() != EntryMode::FILE {
                 continue;
@@ -264,7 +309,11 @@ impl StatementExecutor {
                 .infer_schema(&format, object_store.clone(), path)
                 .await?;
             let (file_schema_projection, table_schema_projection, compat_schema) =
-                generated_schema_projection_and_compatible_file_schema(&file_schema, &table_schema);
+                generated_schema_projection_and_compatible_file_schema(
+                    &file_schema,
+                    &table_schema,
+                    &options.column_mapping,
+                );
 
             let projected_file_schema = Arc::new(
                 file_schema
@@ -279,74 +328,211 @@ impl StatementExecutor {
 
             ensure_schema_compatible(&projected_file_schema, &projected_table_schema)?;
 
-            files.push((
-                Arc::new(compat_schema),
+            files.push(CopyFromFile {
+                path: path.to_string(),
+                schema: Arc::new(compat_schema),
                 file_schema_projection,
                 projected_table_schema,
-                path,
-            ))
+            })
         }
 
-        let mut rows_inserted = 0;
-        for (schema, file_schema_projection, projected_table_schema, path) in files {
-            let mut stream = self
-                .build_read_stream(
-                    &format,
-                    object_store.clone(),
-                    path,
-                    schema,
-                    file_schema_projection,
-                )
-                .await?;
-
-            let fields = projected_table_schema
-                .fields()
-                .iter()
-                .map(|f| f.name().to_string())
-                .collect::<Vec<_>>();
+        let paths = files.iter().map(|f| f.path.clone()).collect::<Vec<_>>();
+        let rows = futures::stream::iter(files.into_iter().map(|file| {
+            self.copy_file_from(
+                &req,
+                &format,
+                object_store.clone(),
+                file,
+                options.dry_run,
+                query_ctx.clone(),
+            )
+        }))
+        .buffered(options.parallelism)
+        .try_collect::<Vec<_>>()
+        .await?;
+
+        if !options.dry_run {
+            return Ok(Output::AffectedRows(rows.iter().sum()));
+        }
 
-            // TODO(hl): make this configurable through options.
-            let pending_mem_threshold = ReadableSize::mb(32).as_bytes();
-            let mut pending_mem_size = 0;
-            let mut pending = vec![];
+        let schema = Arc::new(GtSchema::new(vec![
+            ColumnSchema::new(
+                DRY_RUN_FILE_COLUMN,
+                ConcreteDataType::string_datatype(),
+                false,
+            ),
+            ColumnSchema::new(
+                DRY_RUN_ROWS_COLUMN,
+                ConcreteDataType::uint64_datatype(),
+                false,
+            ),
+        ]));
+        let rows = rows.into_iter().map(|r| r as u64).collect::<Vec<_>>();
+        let columns: Vec<VectorRef> = vec![
+            Arc::new(StringVector::from(paths)),
+            Arc::new(UInt64Vector::from_vec(rows)),
+        ];
+        let records =
+            RecordBatches::try_from_columns(schema, columns).context(BuildRecordBatchSnafu)?;
+        Ok(Output::RecordBatches(records))
+    }
 
+    /// Imports the file to the table, returns the number of rows inserted.
+    ///
+    /// Only reads the file and returns the number of rows in it if `dry_run` is true.
+    async fn copy_file_from(
+        &self,
+        req: &CopyTableRequest,
+        format: &Format,
+        object_store: ObjectStore,
+        file: CopyFromFile,
+        dry_run: bool,
+        query_ctx: QueryContextRef,
+    ) -> Result<usize> {
+        let mut stream = self
+            .build_read_stream(
+                format,
+                object_store,
+                &file.path,
+                file.schema,
+                file.file_schema_projection,
+            )
+            .await?;
+
+        if dry_run {
+            let mut rows = 0;
             while let Some(r) = stream.next().await {
-                let record_batch = r.context(error::ReadDfRecordBatchSnafu)?;
-                let vectors =
-                    Helper::try_into_vectors(record_batch.columns()).context(IntoVectorsSnafu)?;
-
-                pending_mem_size += vectors.iter().map(|v| v.memory_size()).sum::<usize>();
-
-                let columns_values = fields
-                    .iter()
-                    .cloned()
-                    .zip(vectors)
-                    .collect::<HashMap<_, _>>();
-
-                pending.push(self.inserter.handle_table_insert(
-                    InsertRequest {
-                        catalog_name: req.catalog_name.to_string(),
-                        schema_name: req.schema_name.to_string(),
-                        table_name: req.table_name.to_string(),
-                        columns_values,
-                    },
-                    query_ctx.clone(),
-                ));
-
-                if pending_mem_size as u64 >= pending_mem_threshold {
-                    rows_inserted += batch_insert(&mut pending, &mut pending_mem_size).await?;
-                }
+                rows += r.context(error::ReadDfRecordBatchSnafu)?.num_rows();
             }
+            return Ok(rows);
+        }
+
+        let fields = file
+            .projected_table_schema
+            .fields()
+            .iter()
+            .map(|f| f.name().to_string())
+            .collect::<Vec<_>>();
+
+        // TODO(hl): make this configurable through options.
+        let pending_mem_threshold = ReadableSize::mb(32).as_bytes();
+        let mut pending_mem_size = 0;
+        let mut pending = vec![];
+        let mut rows_inserted = 0;
+
+        while let Some(r) = stream.next().await {
+            let record_batch = r.context(error::ReadDfRecordBatchSnafu)?;
+            let vectors =
+                Helper::try_into_vectors(record_batch.columns()).context(IntoVectorsSnafu)?;
+
+            pending_mem_size += vectors.iter().map(|v| v.memory_size()).sum::<usize>();
 
-            if !pending.is_empty() {
+            let columns_values = fields
+                .iter()
+                .cloned()
+                .zip(vectors)
+                .collect::<HashMap<_, _>>();
+
+            pending.push(self.inserter.handle_table_insert(
+                InsertRequest {
+                    catalog_name: req.catalog_name.to_string(),
+                    schema_name: req.schema_name.to_string(),
+                    table_name: req.table_name.to_string(),
+                    columns_values,
+                },
+                query_ctx.clone(),
+            ));
+
+            if pending_mem_size as u64 >= pending_mem_threshold {
                 rows_inserted += batch_insert(&mut pending, &mut pending_mem_size).await?;
             }
         }
 
+        if !pending.is_empty() {
+            rows_inserted += batch_insert(&mut pending, &mut pending_mem_size).await?;
+        }
+
         Ok(rows_inserted)
     }
 }
 
+/// A file to import and how to read it.
+struct CopyFromFile {
+    path: String,
+    /// Schema to read the file, fields the table has are converted to types of the table.
+    schema: SchemaRef,
+    file_schema_projection: Vec<usize>,
+    /// Columns of the table to import the projected columns of the file to.
+    projected_table_schema: SchemaRef,
+}
+
+/// Options of `COPY FROM` in `WITH`.
+#[derive(Debug, PartialEq, Eq)]
+struct CopyFromOptions {
+    /// Maps columns names in files to column names in the table.
+    column_mapping: HashMap<String, String>,
+    /// Only counts rows of files if it's true.
+    dry_run: bool,
+    /// Max number of files to import concurrently.
+    parallelism: usize,
+}
+
+impl TryFrom<&HashMap<String, String>> for CopyFromOptions {
+    type Error = error::Error;
+
+    fn try_from(with: &HashMap<String, String>) -> Result<Self> {
+        let column_mapping = match with.get(COPY_FROM_COLUMN_MAPPING_KEY) {
+            Some(value) => {
+                parse_column_mapping(value).with_context(|| error::InvalidCopyParameterSnafu {
+                    key: COPY_FROM_COLUMN_MAPPING_KEY,
+                    value,
+                })?
+            }
+            None => HashMap::new(),
+        };
+        let dry_run = match with.get(COPY_FROM_DRY_RUN_KEY) {
+            Some(value) => value.to_lowercase().parse::<bool>().ok().with_context(|| {
+                error::InvalidCopyParameterSnafu {
+                    key: COPY_FROM_DRY_RUN_KEY,
+                    value,
+                }
+            })?,
+            None => false,
+        };
+        let parallelism = match with.get(COPY_FROM_PARALLELISM_KEY) {
+            Some(value) => value
+                .parse::<usize>()
+                .ok()
+                .filter(|p| *p > 0)
+                .with_context(|| error::InvalidCopyParameterSnafu {
+                    key: COPY_FROM_PARALLELISM_KEY,
+                    value,
+                })?,
+            None => DEFAULT_COPY_FROM_PARALLELISM,
+        };
+
+        Ok(Self {
+            column_mapping,
+            dry_run,
+            parallelism,
+        })
+    }
+}
+
+/// Parses mappings like `file_col1:table_col1,file_col2:table_col2`.
+///
+/// Returns `None` if the mapping is malformed.
+fn parse_column_mapping(value: &str) -> Option<HashMap<String, String>> {
+    value
+        .split(',')
+        .map(|pair| {
+            let (from, to) = pair.split_once(':')?;
+            let (from, to) = (from.trim(), to.trim());
+            (!from.is_empty() && !to.is_empty()).then(|| (from.to_string(), to.to_string()))
+        })
+        .collect()
+}
+
 /// Executes all pending inserts all at once, drain pending requests and reset pending bytes.
 async fn batch_insert(
     pending: &mut Vec<impl Future<Output = Result<usize>>>,
@@ -380,20 +566,29 @@ fn ensure_schema_compatible(from: &SchemaRef, to: &SchemaRef) -> Result<()> {
 }
 
 /// Allows the file schema is a subset of table
+///
+/// A column of the file matches the column of the table with the same name, or the name
+/// in the `column_mapping`.
 fn generated_schema_projection_and_compatible_file_schema(
     file: &SchemaRef,
     table: &SchemaRef,
+    column_mapping: &HashMap<String, String>,
 ) -> (Vec<usize>, Vec<usize>, Schema) {
     let mut file_projection = Vec::with_capacity(file.fields.len());
     let mut table_projection = Vec::with_capacity(file.fields.len());
     let mut compatible_fields = file.fields.iter().cloned().collect::<Vec<_>>();
     for (file_idx, file_field) in file.fields.iter().enumerate() {
-        if let Some((table_idx, table_field)) = table.fields.find(file_field.name()) {
+        let name = column_mapping
+            .get(file_field.name())
+            .unwrap_or(file_field.name());
+        if let Some((table_idx, table_field)) = table.fields.find(name) {
             file_projection.push(file_idx);
             table_projection.push(table_idx);
 
             // Safety: the compatible_fields has same length as file schema
-            compatible_fields[file_idx] = table_field.clone();
+            // Keeps the name of the file column as readers may look up columns by name.
+            compatible_fields[file_idx] =
+                Arc::new(table_field.as_ref().clone().with_name(file_field.name()));
         }
     }
 
@@ -542,6 +737,81 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_copy_from_options() {
+        let options = CopyFromOptions::try_from(&HashMap::new()).unwrap();
+        assert_eq!(
+            CopyFromOptions {
+                column_mapping: HashMap::new(),
+                dry_run: false,
+                parallelism: DEFAULT_COPY_FROM_PARALLELISM,
+            },
+            options
+        );
+
+        let with = HashMap::from([
+            (
+                COPY_FROM_COLUMN_MAPPING_KEY.to_string(),
+                "host_name:host, value :val".to_string(),
+            ),
+            (COPY_FROM_DRY_RUN_KEY.to_string(), "TRUE".to_string()),
+            (COPY_FROM_PARALLELISM_KEY.to_string(), "4".to_string()),
+        ]);
+        let options = CopyFromOptions::try_from(&with).unwrap();
+        assert_eq!(
+            CopyFromOptions {
+                column_mapping: HashMap::from([
+                    ("host_name".to_string(), "host".to_string()),
+                    ("value".to_string(), "val".to_string()),
+                ]),
+                dry_run: true,
+                parallelism: 4,
+            },
+            options
+        );
+
+        for (key, value) in [
+            (COPY_FROM_COLUMN_MAPPING_KEY, "host_name"),
+            (COPY_FROM_COLUMN_MAPPING_KEY, "host_name:"),
+            (COPY_FROM_DRY_RUN_KEY, "yes"),
+            (COPY_FROM_PARALLELISM_KEY, "0"),
+        ] {
+            let with = HashMap::from([(key.to_string(), value.to_string())]);
+            assert!(CopyFromOptions::try_from(&with).is_err(), "{key}={value}");
+        }
+    }
+
+    #[test]
+    fn test_schema_projection_with_column_mapping() {
+        let file_schema = make_test_schema(&[
+            Field::new("host_name", DataType::Utf8, true),
+            Field::new("cpu", DataType::Float32, true),
+            Field::new("unknown", DataType::Utf8, true),
+        ]);
+        let table_schema = make_test_schema(&[
+            Field::new("host", DataType::Utf8, true),
+            Field::new("cpu", DataType::Float64, true),
+        ]);
+        let mapping = HashMap::from([("host_name".to_string(), "host".to_string())]);
+
+        let (fp, tp, compat) = generated_schema_projection_and_compatible_file_schema(
+            &file_schema,
+            &table_schema,
+            &mapping,
+        );
+        assert_eq!(vec![0, 1], fp);
+        assert_eq!(vec![0, 1], tp);
+        // Keeps names of the file but uses types of the table.
+        assert_eq!(
+            Schema::new(vec![
+                Field::new("host_name", DataType::Utf8, true),
+                Field::new("cpu", DataType::Float64, true),
+                Field::new("unknown", DataType::Utf8, true),
+            ]),
+            compat
+        );
+    }
+
     fn make_test_schema(v: &[Field]) -> Arc<Schema> {
         Arc::new(Schema::new(v.to_vec()))
     }
@@ -564,8 +834,11 @@ mod tests {
             Field::new("c2", DataType::Int16, true),
         ]);
 
-        let (_, tp, _) =
-            generated_schema_projection_and_compatible_file_schema(&file_schema0, &table_schema);
+        let (_, tp, _) = generated_schema_projection_and_compatible_file_schema(
+            &file_schema0,
+            &table_schema,
+            &HashMap::new(),
+        );
 
         assert_eq!(table_schema.project(&tp).unwrap(), *compat_schema);
     }
@@ -608,8 +881,11 @@ mod tests {
         ];
 
         for test in tests {
-            let (fp, tp, _) =
-                generated_schema_projection_and_compatible_file_schema(test.0, test.1);
+            let (fp, tp, _) = generated_schema_projection_and_compatible_file_schema(
+                test.0,
+                test.1,
+                &HashMap::new(),
+            );
             assert_eq!(test.0.project(&fp).unwrap(), test.1.project(&tp).unwrap());
         }
     }
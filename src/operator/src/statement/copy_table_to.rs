@@ -12,6 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::collections::HashMap;
 use std::sync::Arc;
 
 use common_base::readable_size::ReadableSize;
@@ -19,69 +20,73 @@ use common_datasource::file_format::csv::stream_to_csv;
 use common_datasource::file_format::json::stream_to_json;
 use common_datasource::file_format::parquet::stream_to_parquet;
 use common_datasource::file_format::Format;
-use common_datasource::object_store::{build_backend, parse_url};
+use common_datasource::object_store::{build_backend, is_supported_connection_option, parse_url};
 use common_datasource::util::find_dir_and_filename;
 use common_query::Output;
 use common_recordbatch::adapter::DfRecordBatchStreamAdapter;
-use common_recordbatch::SendableRecordBatchStream;
 use common_telemetry::{debug, tracing};
 use datafusion::datasource::DefaultTableSource;
+use datafusion::physical_plan::SendableRecordBatchStream as DfSendableRecordBatchStream;
 use datafusion_common::TableReference as DfTableReference;
 use datafusion_expr::LogicalPlanBuilder;
 use object_store::ObjectStore;
 use query::plan::LogicalPlan;
 use session::context::QueryContextRef;
-use snafu::{OptionExt, ResultExt};
+use snafu::{ensure, OptionExt, ResultExt};
 use table::engine::TableReference;
 use table::requests::CopyTableRequest;
 use table::table::adapter::DfTableProviderAdapter;
 
 use crate::error::{self, BuildDfLogicalPlanSnafu, ExecLogicalPlanSnafu, Result};
+use crate::statement::copy_query_to::{
+    parse_partition_interval, write_to_directory, PartitionSpec, COPY_PARTITION_INTERVAL_KEY,
+};
 use crate::statement::StatementExecutor;
 
 // The buffer size should be greater than 5MB (minimum multipart upload size).
 /// Buffer size to flush data to object stores.
 const WRITE_BUFFER_THRESHOLD: ReadableSize = ReadableSize::mb(8);
 
-impl StatementExecutor {
-    async fn stream_to_file(
-        &self,
-        stream: SendableRecordBatchStream,
-        format: &Format,
-        object_store: ObjectStore,
-        path: &str,
-    ) -> Result<usize> {
-        let threshold = WRITE_BUFFER_THRESHOLD.as_bytes() as usize;
-
-        match format {
-            Format::Csv(_) => stream_to_csv(
-                Box::pin(DfRecordBatchStreamAdapter::new(stream)),
-                object_store,
-                path,
-                threshold,
-            )
+/// Returns options to connect to the object store, which are the CONNECTION options and
+/// connection options like credentials in the WITH options. CONNECTION options take
+/// precedence.
+pub(crate) fn object_store_connection(
+    with: &HashMap<String, String>,
+    connection: &HashMap<String, String>,
+) -> HashMap<String, String> {
+    with.iter()
+        .filter(|(key, _)| is_supported_connection_option(key))
+        .chain(connection.iter())
+        .map(|(key, value)| (key.clone(), value.clone()))
+        .collect()
+}
+
+/// Writes the `stream` to the file at `path` in the `format`.
+///
+/// Returns number of rows written.
+pub(crate) async fn stream_to_file(
+    stream: DfSendableRecordBatchStream,
+    format: &Format,
+    object_store: ObjectStore,
+    path: &str,
+) -> Result<usize> {
+    let threshold = WRITE_BUFFER_THRESHOLD.as_bytes() as usize;
+
+    match format {
+        Format::Csv(_) => stream_to_csv(stream, object_store, path, threshold)
             .await
             .context(error::WriteStreamToFileSnafu { path }),
-            Format::Json(_) => stream_to_json(
-                Box::pin(DfRecordBatchStreamAdapter::new(stream)),
-                object_store,
-                path,
-                threshold,
-            )
+        Format::Json(_) => stream_to_json(stream, object_store, path, threshold)
             .await
             .context(error::WriteStreamToFileSnafu { path }),
-            Format::Parquet(_) => stream_to_parquet(
-                Box::pin(DfRecordBatchStreamAdapter::new(stream)),
-                object_store,
-                path,
-                threshold,
-            )
+        Format::Parquet(_) => stream_to_parquet(stream, object_store, path, threshold)
             .await
             .context(error::WriteStreamToFileSnafu { path }),
-            _ => error::UnsupportedFormatSnafu { format: *format }.fail(),
-        }
+        _ => error::UnsupportedFormatSnafu { format: *format }.fail(),
     }
+}
 
+impl StatementExecutor {
     #[tracing::instrument(skip_all)]
     pub(crate) async fn copy_table_to(
         &self,
@@ -92,6 +97,11 @@ impl StatementExecutor {
         let table = self.get_table(&table_ref).await?;
         let table_id = table.table_info().table_id();
         let format = Format::try_from(&req.with).context(error::ParseFileFormatSnafu)?;
+        let interval = parse_partition_interval(&req.with)?;
+        let timestamp_column = table
+            .schema()
+            .timestamp_column()
+            .map(|column| column.name.clone());
 
         let df_table_ref = DfTableReference::from(table_ref);
 
@@ -132,18 +142,69 @@ impl StatementExecutor {
             _ => unreachable!(),
         };
 
+        let stream = Box::pin(DfRecordBatchStreamAdapter::new(stream));
+        let connection = object_store_connection(&req.with, &req.connection);
+
+        // Writes files of time windows under the location.
+        if let Some(interval) = interval {
+            ensure!(
+                req.location.ends_with('/'),
+                error::InvalidCopyParameterSnafu {
+                    key: "location",
+                    value: req.location,
+                }
+            );
+            let column = timestamp_column.context(error::InvalidCopyParameterSnafu {
+                key: COPY_PARTITION_INTERVAL_KEY,
+                value: "requires a table with time index",
+            })?;
+            let object_store =
+                build_backend(&req.location, &connection).context(error::BuildBackendSnafu)?;
+            debug!(
+                "Copy table: {table_id} to location: {}, interval: {interval:?}",
+                req.location
+            );
+            let partition = PartitionSpec {
+                column,
+                interval: Some(interval),
+            };
+            return write_to_directory(stream, Some(&partition), &format, object_store).await;
+        }
+
         let (_schema, _host, path) = parse_url(&req.location).context(error::ParseUrlSnafu)?;
         let (_, filename) = find_dir_and_filename(&path);
         let filename = filename.context(error::UnexpectedSnafu {
             violated: format!("Expected filename, path: {path}"),
         })?;
         let object_store =
-            build_backend(&req.location, &req.connection).context(error::BuildBackendSnafu)?;
+            build_backend(&req.location, &connection).context(error::BuildBackendSnafu)?;
         debug!("Copy table: {table_id} to path: {path}");
-        let rows_copied = self
-            .stream_to_file(stream, &format, object_store, &filename)
-            .await?;
+        let rows_copied = stream_to_file(stream, &format, object_store, &filename).await?;
 
         Ok(rows_copied)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_object_store_connection() {
+        let with = HashMap::from([
+            ("format".to_string(), "csv".to_string()),
+            ("access_key_id".to_string(), "with_id".to_string()),
+            ("secret_access_key".to_string(), "with_key".to_string()),
+        ]);
+        let connection = HashMap::from([("access_key_id".to_string(), "id".to_string())]);
+
+        let options = object_store_connection(&with, &connection);
+        assert_eq!(
+            HashMap::from([
+                ("access_key_id".to_string(), "id".to_string()),
+                ("secret_access_key".to_string(), "with_key".to_string()),
+            ]),
+            options
+        );
+    }
+}
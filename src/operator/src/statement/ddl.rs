@@ -21,6 +21,7 @@ use catalog::CatalogManagerRef;
 use chrono::Utc;
 use common_catalog::consts::{DEFAULT_CATALOG_NAME, DEFAULT_SCHEMA_NAME};
 use common_catalog::format_full_table_name;
+use common_error::ext::BoxedError;
 use common_meta::cache_invalidator::Context;
 use common_meta::ddl::ExecutorContext;
 use common_meta::key::schema_name::{SchemaNameKey, SchemaNameValue};
@@ -30,6 +31,9 @@ use common_meta::rpc::router::{Partition, Partition as MetaPartition};
 use common_meta::table_name::TableName;
 use common_query::Output;
 use common_telemetry::{info, tracing};
+use datafusion::datasource::DefaultTableSource;
+use datafusion_common::TableReference as DfTableReference;
+use datafusion_expr::LogicalPlanBuilder;
 use datatypes::prelude::ConcreteDataType;
 use datatypes::schema::RawSchema;
 use lazy_static::lazy_static;
@@ -38,23 +42,27 @@ use regex::Regex;
 use session::context::QueryContextRef;
 use snafu::{ensure, IntoError, OptionExt, ResultExt};
 use sql::ast::Value as SqlValue;
-use sql::statements::alter::AlterTable;
+use sql::statements::alter::{AlterTable, AlterTableOperation};
 use sql::statements::create::{CreateExternalTable, CreateTable, Partitions};
 use sql::statements::sql_value_to_value;
 use sql::MAXVALUE;
+use substrait::{DFLogicalSubstraitConvertor, SubstraitPlan};
 use table::dist_table::DistTable;
+use table::engine::TableReference;
 use table::metadata::{self, RawTableInfo, RawTableMeta, TableId, TableInfo, TableType};
 use table::requests::{AlterKind, AlterTableRequest, TableOptions};
+use table::table::adapter::DfTableProviderAdapter;
 use table::TableRef;
 
 use super::StatementExecutor;
 use crate::error::{
-    self, AlterExprToRequestSnafu, CatalogSnafu, ColumnDataTypeSnafu, ColumnNotFoundSnafu,
-    DeserializePartitionSnafu, InvalidPartitionColumnsSnafu, ParseSqlSnafu, Result,
-    SchemaNotFoundSnafu, TableMetadataManagerSnafu, TableNotFoundSnafu,
+    self, AlterExprToRequestSnafu, BuildDfLogicalPlanSnafu, CatalogSnafu, ColumnDataTypeSnafu,
+    ColumnNotFoundSnafu, DeserializePartitionSnafu, InvalidPartitionColumnsSnafu, ParseSqlSnafu,
+    Result, SchemaNotFoundSnafu, TableMetadataManagerSnafu, TableNotFoundSnafu,
     UnrecognizedTableOptionSnafu,
 };
 use crate::expr_factory;
+use crate::table::table_idents_to_full_name;
 
 lazy_static! {
     static ref NAME_PATTERN_REG: Regex = Regex::new(&format!("^{NAME_PATTERN}$")).unwrap();
@@ -266,10 +274,95 @@ impl StatementExecutor {
         alter_table: AlterTable,
         query_ctx: QueryContextRef,
     ) -> Result<Output> {
+        if let AlterTableOperation::ModifyPrimaryKey { columns } = alter_table.alter_operation() {
+            let (catalog, schema, table) =
+                table_idents_to_full_name(alter_table.table_name(), query_ctx)
+                    .map_err(BoxedError::new)
+                    .context(error::ExternalSnafu)?;
+            let primary_keys = columns.iter().map(|c| c.value.clone()).collect();
+            return self
+                .modify_primary_key(TableName::new(catalog, schema, table), primary_keys)
+                .await;
+        }
+
         let expr = expr_factory::to_alter_expr(alter_table, query_ctx)?;
         self.alter_table_inner(expr).await
     }
 
+    /// Modifies the primary key of the table by rebuilding all its regions.
+    #[tracing::instrument(skip_all)]
+    pub async fn modify_primary_key(
+        &self,
+        table_name: TableName,
+        primary_keys: Vec<String>,
+    ) -> Result<Output> {
+        let table = self
+            .catalog_manager
+            .table(
+                &table_name.catalog_name,
+                &table_name.schema_name,
+                &table_name.table_name,
+            )
+            .await
+            .context(CatalogSnafu)?
+            .with_context(|| TableNotFoundSnafu {
+                table_name: table_name.to_string(),
+            })?;
+        let table_id = table.table_info().table_id();
+
+        // The plan scans all rows of a region, it is sent to each source region to backfill
+        // the rebuilt regions.
+        let table_ref = TableReference::full(
+            &table_name.catalog_name,
+            &table_name.schema_name,
+            &table_name.table_name,
+        );
+        let df_table_ref = DfTableReference::from(table_ref);
+        let table_provider = Arc::new(DfTableProviderAdapter::new(table));
+        let table_source = Arc::new(DefaultTableSource::new(table_provider));
+        let plan = LogicalPlanBuilder::scan(df_table_ref.to_owned_reference(), table_source, None)
+            .context(BuildDfLogicalPlanSnafu)?
+            .build()
+            .context(BuildDfLogicalPlanSnafu)?;
+        let scan_plan = DFLogicalSubstraitConvertor
+            .encode(&plan)
+            .context(error::EncodeSubstraitLogicalPlanSnafu)?;
+
+        info!(
+            "Modifying primary key of table {} to {:?}",
+            table_name, primary_keys
+        );
+
+        let req = SubmitDdlTaskRequest {
+            task: DdlTask::new_rebuild_table(
+                table_name.catalog_name.to_string(),
+                table_name.schema_name.to_string(),
+                table_name.table_name.to_string(),
+                table_id,
+                primary_keys,
+                scan_plan.to_vec(),
+            ),
+        };
+
+        self.ddl_executor
+            .submit_ddl_task(&ExecutorContext::default(), req)
+            .await
+            .context(error::ExecuteDdlSnafu)?;
+
+        // Invalidates local cache ASAP.
+        self.cache_invalidator
+            .invalidate_table_id(&Context::default(), table_id)
+            .await
+            .context(error::InvalidateTableCacheSnafu)?;
+
+        self.cache_invalidator
+            .invalidate_table_name(&Context::default(), table_name)
+            .await
+            .context(error::InvalidateTableCacheSnafu)?;
+
+        Ok(Output::AffectedRows(0))
+    }
+
     pub async fn alter_table_inner(&self, expr: AlterExpr) -> Result<Output> {
         let catalog_name = if expr.catalog_name.is_empty() {
             DEFAULT_CATALOG_NAME
@@ -609,7 +702,8 @@ fn find_partition_entries(
                 let v = match v {
                     SqlValue::Number(n, _) if n == MAXVALUE => PartitionBound::MaxValue,
                     _ => PartitionBound::Value(
-                        sql_value_to_value(column_name, data_type, v).context(ParseSqlSnafu)?,
+                        sql_value_to_value(column_name, data_type, v, None)
+                            .context(ParseSqlSnafu)?,
                     ),
                 };
                 values.push(v);
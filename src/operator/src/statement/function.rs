@@ -0,0 +1,204 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use common_base::readable_size::ReadableSize;
+use common_datasource::object_store::{build_backend, parse_url};
+use common_datasource::util::find_dir_and_filename;
+use common_function::function_registry::FUNCTION_REGISTRY;
+use common_function::wasm::{WasmFunction, WasmLimits};
+use common_query::Output;
+use common_telemetry::{info, tracing};
+use session::context::QueryContextRef;
+use snafu::{ensure, OptionExt, ResultExt};
+use sql::statements::create::CreateFunction;
+use sql::statements::sql_data_type_to_concrete_data_type;
+
+use crate::error::{
+    BuildBackendSnafu, CreateFunctionSnafu, FunctionExistsSnafu, InvalidSqlSnafu, ParseSqlSnafu,
+    ParseUrlSnafu, ReadObjectSnafu, Result,
+};
+use crate::statement::StatementExecutor;
+
+/// Language of WASM UDFs.
+const WASM_LANGUAGE: &str = "wasm";
+/// Option of the name of the function exported by the module, defaults to the UDF name.
+const ENTRY_KEY: &str = "entry";
+/// Option of the fuel to evaluate a batch.
+const FUEL_KEY: &str = "fuel";
+/// Option of the max size of the linear memory.
+const MEMORY_LIMIT_KEY: &str = "memory_limit";
+/// Option of the max duration to evaluate a batch.
+const TIMEOUT_KEY: &str = "timeout";
+
+impl StatementExecutor {
+    /// Loads the module in the location of the function and registers the function
+    /// to the current catalog of the query engine. Functions in the catalog can't be replaced.
+    // TODO(agent): persist functions so other frontends and restarted frontends can load them.
+    #[tracing::instrument(skip_all)]
+    pub async fn create_function(
+        &self,
+        stmt: CreateFunction,
+        query_ctx: QueryContextRef,
+    ) -> Result<Output> {
+        ensure!(
+            stmt.language == WASM_LANGUAGE,
+            InvalidSqlSnafu {
+                err_msg: format!("unsupported function language {}", stmt.language),
+            }
+        );
+        // Functions are registered to catalogs of the query engine so they are not in any schema.
+        let name = match &stmt.name.0[..] {
+            [name] => name.value.clone(),
+            _ => {
+                return InvalidSqlSnafu {
+                    err_msg: "expect function name to be <function>, functions are not in schemas",
+                }
+                .fail()
+            }
+        };
+        ensure!(
+            FUNCTION_REGISTRY.get_function(&name).is_none()
+                && FUNCTION_REGISTRY.get_aggr_function(&name).is_none(),
+            InvalidSqlSnafu {
+                err_msg: format!("function {name} is a builtin function"),
+            }
+        );
+
+        let arg_types = stmt
+            .args
+            .iter()
+            .map(sql_data_type_to_concrete_data_type)
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .context(ParseSqlSnafu)?;
+        let return_type =
+            sql_data_type_to_concrete_data_type(&stmt.return_type).context(ParseSqlSnafu)?;
+        let export = stmt
+            .options
+            .get(ENTRY_KEY)
+            .cloned()
+            .unwrap_or_else(|| name.clone());
+        let limits = parse_wasm_limits(&stmt.options.map)?;
+
+        let binary = read_module(&stmt.location, &stmt.connection.map).await?;
+        let function = WasmFunction::try_new(
+            name.clone(),
+            export,
+            arg_types,
+            return_type,
+            &binary,
+            limits,
+        )
+        .context(CreateFunctionSnafu { name: &name })?;
+        let catalog = query_ctx.current_catalog();
+        ensure!(
+            self.query_engine
+                .register_catalog_function(catalog, Arc::new(function)),
+            FunctionExistsSnafu { catalog, name }
+        );
+
+        info!(
+            "Created WASM function {} in catalog {} from {}",
+            name, catalog, stmt.location
+        );
+
+        Ok(Output::AffectedRows(0))
+    }
+}
+
+/// Reads the WASM module at `location`.
+async fn read_module(location: &str, connection: &HashMap<String, String>) -> Result<Vec<u8>> {
+    let (_schema, _host, path) = parse_url(location).context(ParseUrlSnafu)?;
+    let (_dir, filename) = find_dir_and_filename(&path);
+    let filename = filename.with_context(|| InvalidSqlSnafu {
+        err_msg: format!("expect location {location} to be a file"),
+    })?;
+    let object_store = build_backend(location, connection).context(BuildBackendSnafu)?;
+
+    object_store
+        .read(&filename)
+        .await
+        .context(ReadObjectSnafu { path: location })
+}
+
+/// Parses limits of the function in the `options`.
+fn parse_wasm_limits(options: &HashMap<String, String>) -> Result<WasmLimits> {
+    let mut limits = WasmLimits::default();
+    for (key, value) in options {
+        let invalid_value = || InvalidSqlSnafu {
+            err_msg: format!("invalid value {value} of function option {key}"),
+        };
+        match key.as_str() {
+            ENTRY_KEY => {}
+            FUEL_KEY => limits.fuel = u64::from_str(value).ok().with_context(invalid_value)?,
+            MEMORY_LIMIT_KEY => {
+                let size = ReadableSize::from_str(value)
+                    .ok()
+                    .with_context(invalid_value)?;
+                limits.max_memory = size.as_bytes() as usize;
+            }
+            TIMEOUT_KEY => {
+                limits.timeout = humantime::parse_duration(value)
+                    .ok()
+                    .with_context(invalid_value)?
+            }
+            _ => {
+                return InvalidSqlSnafu {
+                    err_msg: format!("unknown function option {key}"),
+                }
+                .fail()
+            }
+        }
+    }
+
+    Ok(limits)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_wasm_limits() {
+        assert_eq!(
+            WasmLimits::default(),
+            parse_wasm_limits(&HashMap::new()).unwrap()
+        );
+
+        let options = HashMap::from([
+            ("entry".to_string(), "f".to_string()),
+            ("fuel".to_string(), "1000".to_string()),
+            ("memory_limit".to_string(), "1MiB".to_string()),
+            ("timeout".to_string(), "100ms".to_string()),
+        ]);
+        assert_eq!(
+            WasmLimits {
+                fuel: 1000,
+                max_memory: 1024 * 1024,
+                timeout: Duration::from_millis(100),
+            },
+            parse_wasm_limits(&options).unwrap()
+        );
+
+        let options = HashMap::from([("fuel".to_string(), "-1".to_string())]);
+        assert!(parse_wasm_limits(&options).is_err());
+        let options = HashMap::from([("unknown".to_string(), "1".to_string())]);
+        assert!(parse_wasm_limits(&options).is_err());
+    }
+}
@@ -0,0 +1,105 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use common_meta::key::pipeline::{PipelineKey, PipelineValue};
+use common_meta::key::schema_name::SchemaNameKey;
+use common_query::Output;
+use common_telemetry::tracing;
+use servers::pipeline::Pipeline;
+use session::context::QueryContextRef;
+use snafu::{ensure, ResultExt};
+use sql::statements::create::CreatePipeline;
+use sql::statements::drop::DropPipeline;
+
+use crate::error::{
+    InvalidPipelineSnafu, InvalidSqlSnafu, PipelineNotFoundSnafu, Result, SchemaNotFoundSnafu,
+    TableMetadataManagerSnafu,
+};
+use crate::statement::StatementExecutor;
+use crate::table::table_idents_to_full_name;
+
+impl StatementExecutor {
+    #[tracing::instrument(skip_all)]
+    pub async fn create_pipeline(
+        &self,
+        stmt: CreatePipeline,
+        query_ctx: QueryContextRef,
+    ) -> Result<Output> {
+        let (catalog, schema, pipeline) = table_idents_to_full_name(&stmt.name, query_ctx)?;
+        let exists = self
+            .table_metadata_manager
+            .schema_manager()
+            .exists(SchemaNameKey::new(&catalog, &schema))
+            .await
+            .context(TableMetadataManagerSnafu)?;
+        ensure!(
+            exists,
+            SchemaNotFoundSnafu {
+                schema_info: &schema,
+            }
+        );
+
+        // Sink tables are always in the schema of the pipeline.
+        let sink_table = match stmt.sink_table.as_ref().map(|name| &name.0[..]) {
+            None => None,
+            Some([table]) => Some(table.value.clone()),
+            Some(_) => {
+                return InvalidSqlSnafu {
+                    err_msg: "expect sink table name to be <table>, the table is in the schema of the pipeline",
+                }
+                .fail()
+            }
+        };
+
+        let key = PipelineKey::new(&catalog, &schema, &pipeline);
+        let value = PipelineValue {
+            source_type: stmt.source_type,
+            source_options: stmt.source_options.map,
+            sink_table,
+            options: stmt.options.map,
+        };
+        let _ = Pipeline::try_new(key.clone(), &value).context(InvalidPipelineSnafu)?;
+
+        let _ = self
+            .pipeline_manager
+            .create(&key, &value, stmt.if_not_exists)
+            .await
+            .context(TableMetadataManagerSnafu)?;
+
+        Ok(Output::AffectedRows(0))
+    }
+
+    #[tracing::instrument(skip_all)]
+    pub async fn drop_pipeline(
+        &self,
+        stmt: DropPipeline,
+        query_ctx: QueryContextRef,
+    ) -> Result<Output> {
+        let (catalog, schema, pipeline) = table_idents_to_full_name(stmt.name(), query_ctx)?;
+        let key = PipelineKey::new(&catalog, &schema, &pipeline);
+        let deleted = self
+            .pipeline_manager
+            .delete(&key)
+            .await
+            .context(TableMetadataManagerSnafu)?;
+        ensure!(
+            deleted || stmt.drop_if_exists(),
+            PipelineNotFoundSnafu {
+                pipeline: format!("{catalog}.{schema}.{pipeline}"),
+            }
+        );
+
+        Ok(Output::AffectedRows(0))
+    }
+}
@@ -0,0 +1,191 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::BTreeSet;
+use std::sync::Arc;
+
+use common_query::logical_plan::build_filter_from_timestamp;
+use common_query::Output;
+use common_recordbatch::adapter::DfRecordBatchStreamAdapter;
+use common_telemetry::tracing;
+use common_time::range::TimestampRange;
+use datafusion::datasource::DefaultTableSource;
+use datafusion::physical_plan::SendableRecordBatchStream as DfSendableRecordBatchStream;
+use datafusion_common::{Column, TableReference as DfTableReference};
+use datafusion_expr::{Expr, LogicalPlanBuilder};
+use datatypes::arrow::util::display::array_value_to_string;
+use futures::StreamExt;
+use query::plan::LogicalPlan;
+use session::context::QueryContextRef;
+use snafu::{ensure, OptionExt, ResultExt};
+use table::engine::TableReference;
+use table::table::adapter::DfTableProviderAdapter;
+
+use crate::error::{self, BuildDfLogicalPlanSnafu, ExecLogicalPlanSnafu, Result};
+use crate::statement::StatementExecutor;
+
+impl StatementExecutor {
+    /// Returns sorted distinct values of the tag `column` in the time `range`.
+    ///
+    /// Only the tag and the time index are scanned, and the scan stops once `limit` values
+    /// are found, so they aren't always the smallest values of the tag.
+    #[tracing::instrument(skip_all)]
+    pub async fn tag_values(
+        &self,
+        table_ref: TableReference<'_>,
+        column: &str,
+        range: Option<&TimestampRange>,
+        limit: usize,
+        query_ctx: QueryContextRef,
+    ) -> Result<Vec<String>> {
+        let table = self.get_table(&table_ref).await?;
+        let schema = table.schema();
+        let column_idx =
+            schema
+                .column_index_by_name(column)
+                .with_context(|| error::ColumnNotFoundSnafu {
+                    msg: format!("{column} in table {table_ref}"),
+                })?;
+        ensure!(
+            table
+                .table_info()
+                .meta
+                .primary_key_indices
+                .contains(&column_idx),
+            error::NotTagColumnSnafu {
+                column,
+                table: table_ref.to_string(),
+            }
+        );
+        if limit == 0 {
+            return Ok(vec![]);
+        }
+
+        let filter = schema
+            .timestamp_column()
+            .and_then(|c| build_filter_from_timestamp(&c.name, range));
+        let table_provider = Arc::new(DfTableProviderAdapter::new(table));
+        let table_source = Arc::new(DefaultTableSource::new(table_provider));
+        let mut builder = LogicalPlanBuilder::scan(
+            DfTableReference::from(table_ref).to_owned_reference(),
+            table_source,
+            None,
+        )
+        .context(BuildDfLogicalPlanSnafu)?;
+        if let Some(filter) = filter {
+            builder = builder
+                .filter(filter.df_expr().clone())
+                .context(BuildDfLogicalPlanSnafu)?;
+        }
+        // The projection is pushed down to the scan by the optimizer.
+        let plan = builder
+            .project(vec![Expr::Column(Column::from_name(column))])
+            .context(BuildDfLogicalPlanSnafu)?
+            .build()
+            .context(BuildDfLogicalPlanSnafu)?;
+
+        let output = self
+            .query_engine
+            .execute(LogicalPlan::DfPlan(plan), query_ctx)
+            .await
+            .context(ExecLogicalPlanSnafu)?;
+        let stream = match output {
+            Output::Stream(stream) => stream,
+            Output::RecordBatches(record_batches) => record_batches.as_stream(),
+            Output::AffectedRows(_) => {
+                return error::UnexpectedSnafu {
+                    violated: "Expected records of the scan",
+                }
+                .fail()
+            }
+        };
+
+        collect_values(
+            Box::pin(DfRecordBatchStreamAdapter::new(stream)),
+            column,
+            limit,
+        )
+        .await
+    }
+}
+
+/// Collects distinct non-null values of the first column, stops reading the `stream`
+/// once there are `limit` values.
+async fn collect_values(
+    mut stream: DfSendableRecordBatchStream,
+    column: &str,
+    limit: usize,
+) -> Result<Vec<String>> {
+    let mut values = BTreeSet::new();
+    while let Some(batch) = stream.next().await {
+        let batch = batch.context(error::ReadDfRecordBatchSnafu)?;
+        let array = batch.column(0);
+        for row in 0..batch.num_rows() {
+            if array.is_null(row) {
+                continue;
+            }
+            let value =
+                array_value_to_string(array, row).context(error::FormatTagValueSnafu { column })?;
+            let _ = values.insert(value);
+            if values.len() >= limit {
+                return Ok(values.into_iter().collect());
+            }
+        }
+    }
+
+    Ok(values.into_iter().collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use datafusion::physical_plan::stream::RecordBatchStreamAdapter;
+    use datatypes::arrow::array::StringArray;
+    use datatypes::arrow::datatypes::{DataType, Field, Schema};
+    use datatypes::arrow::record_batch::RecordBatch as DfRecordBatch;
+
+    use super::*;
+
+    fn values_stream(batches: Vec<Vec<Option<&str>>>) -> DfSendableRecordBatchStream {
+        let schema = Arc::new(Schema::new(vec![Field::new("host", DataType::Utf8, true)]));
+        let batches = batches
+            .into_iter()
+            .map(|values| {
+                DfRecordBatch::try_new(schema.clone(), vec![Arc::new(StringArray::from(values))])
+                    .map_err(Into::into)
+            })
+            .collect::<Vec<_>>();
+        Box::pin(RecordBatchStreamAdapter::new(
+            schema,
+            futures::stream::iter(batches),
+        ))
+    }
+
+    #[tokio::test]
+    async fn test_collect_values() {
+        let batches = vec![
+            vec![Some("b"), None, Some("a"), Some("b")],
+            vec![Some("d"), Some("c")],
+        ];
+        let values = collect_values(values_stream(batches.clone()), "host", 10)
+            .await
+            .unwrap();
+        assert_eq!(vec!["a", "b", "c", "d"], values);
+
+        // Stops at the first value of the second batch.
+        let values = collect_values(values_stream(batches), "host", 3)
+            .await
+            .unwrap();
+        assert_eq!(vec!["a", "b", "d"], values);
+    }
+}
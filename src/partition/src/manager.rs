@@ -217,6 +217,31 @@ impl PartitionRuleManager {
         Ok(peer.clone())
     }
 
+    /// Finds the shadow region of the given region and the primary key of the shadow region.
+    ///
+    /// Returns `None` if the region isn't being rebuilt.
+    pub async fn find_shadow_region(
+        &self,
+        region_id: RegionId,
+    ) -> Result<Option<(RegionId, Vec<String>)>> {
+        let table_route = self.find_table_route(region_id.table_id()).await?;
+        Ok(table_route
+            .0
+            .iter()
+            .find(|route| route.region.id == region_id)
+            .and_then(|route| route.region.shadow_region()))
+    }
+
+    /// Returns true if writes to the given region are fenced, e.g., while the region
+    /// is backfilling its shadow region.
+    pub async fn is_write_fenced(&self, region_id: RegionId) -> Result<bool> {
+        let table_route = self.find_table_route(region_id.table_id()).await?;
+        Ok(table_route
+            .0
+            .iter()
+            .any(|route| route.region.id == region_id && route.region.is_write_fenced()))
+    }
+
     pub async fn split_rows(
         &self,
         table_id: TableId,
@@ -119,6 +119,17 @@ pub enum Error {
     #[snafu(display("Invalid function argument for {}", fn_name))]
     FunctionInvalidArgument { fn_name: String, location: Location },
 
+    #[snafu(display("Invalid label name: {}", label))]
+    InvalidLabelName { label: String, location: Location },
+
+    #[snafu(display("Invalid regular expression: {}", regex))]
+    InvalidRegularExpression {
+        regex: String,
+        #[snafu(source)]
+        error: regex::Error,
+        location: Location,
+    },
+
     #[snafu(display(
         "Attempt to combine two tables with different column sets, left: {:?}, right: {:?}",
         left,
@@ -146,6 +157,8 @@ impl ErrorExt for Error {
             | ColumnNotFound { .. }
             | Deserialize { .. }
             | FunctionInvalidArgument { .. }
+            | InvalidLabelName { .. }
+            | InvalidRegularExpression { .. }
             | UnsupportedVectorMatch { .. }
             | CombineTableColumnMismatch { .. }
             | DataFusionPlanning { .. }
@@ -30,3 +30,13 @@ pub use range_manipulate::{RangeManipulate, RangeManipulateExec, RangeManipulate
 pub use series_divide::{SeriesDivide, SeriesDivideExec, SeriesDivideStream};
 
 pub(crate) type Millisecond = <TimestampMillisecondType as ArrowPrimitiveType>::Native;
+
+/// Bit pattern of the NaN value Prometheus writes to mark a series as stale.
+///
+/// Refer to <https://github.com/prometheus/prometheus/blob/main/model/value/value.go>.
+pub const STALE_NAN_BITS: u64 = 0x7ff0000000000002;
+
+/// Returns true if the `value` is a staleness marker. Other NaN values are normal samples.
+pub fn is_stale_marker(value: f64) -> bool {
+    value.to_bits() == STALE_NAN_BITS
+}
@@ -20,9 +20,8 @@ use std::time::Instant;
 
 use common_recordbatch::RecordBatch as GtRecordBatch;
 use common_telemetry::warn;
-use datafusion::arrow::array::AsArray;
-use datafusion::arrow::compute::{self, concat_batches, SortOptions};
-use datafusion::arrow::datatypes::{DataType, Float64Type, SchemaRef};
+use datafusion::arrow::compute::{concat_batches, SortOptions};
+use datafusion::arrow::datatypes::{DataType, SchemaRef};
 use datafusion::arrow::record_batch::RecordBatch;
 use datafusion::common::{DFSchema, DFSchemaRef};
 use datafusion::error::{DataFusionError, Result as DataFusionResult};
@@ -47,13 +46,15 @@ use futures::{ready, Stream, StreamExt};
 /// type, and samples on other tag columns:
 /// - `le` will become a [ListArray] of [f64]. With each bucket bound parsed
 /// - `field` will become a [ListArray] of [f64]
-/// - other columns will be sampled every `bucket_num` element, but their types won't change.
+/// - other columns will be sampled once per bucket group, but their types won't change.
 ///
-/// Due to the folding or sampling, the output rows number will become `input_rows` / `bucket_num`.
+/// A bucket group is the consecutive rows sharing the same tags and timestamp, ended by
+/// its `+Inf` bucket. Series are not required to have the same bucket set. Buckets in
+/// one group with the same upper bound (e.g. `le="1"` and `le="1.0"`) are merged by
+/// summing their counters. Groups without a `+Inf` bucket are evaluated to `NaN`.
 ///
 /// # Requirement
 /// - Input should be sorted on `<tag list>, ts, le ASC`.
-/// - The value set of `le` should be same. I.e., buckets of every series should be same.
 ///
 /// [1]: https://prometheus.io/docs/concepts/metric_types/#histogram
 #[derive(Debug, PartialEq, Hash, Eq)]
@@ -324,7 +325,6 @@ impl ExecutionPlan for HistogramFoldExec {
             field_column_index: self.field_column_index,
             quantile: self.quantile,
             normal_indices: normal_indices.into_iter().collect(),
-            bucket_size: None,
             input_buffer: vec![],
             input,
             output_schema,
@@ -398,7 +398,6 @@ pub struct HistogramFoldStream {
     quantile: f64,
     /// Columns need not folding. This indices is based on input schema
     normal_indices: Vec<usize>,
-    bucket_size: Option<usize>,
     /// Expected output batch size
     batch_size: usize,
     output_schema: SchemaRef,
@@ -439,7 +438,11 @@ impl Stream for HistogramFoldStream {
                     self.metric.elapsed_compute().add_elapsed(timer);
                     break Poll::Ready(Some(result));
                 }
-                None => break Poll::Ready(self.take_output_buf()?.map(Ok)),
+                None => {
+                    // the input is exhausted, fold the last bucket group
+                    self.fold_buf(true)?;
+                    break Poll::Ready(self.take_output_buf()?.map(Ok));
+                }
             }
         };
         self.metric.record_poll(poll)
@@ -452,17 +455,8 @@ impl HistogramFoldStream {
         &mut self,
         input: RecordBatch,
     ) -> DataFusionResult<Option<DataFusionResult<RecordBatch>>> {
-        let Some(bucket_num) = self.calculate_bucket_num(&input)? else {
-            return Ok(None);
-        };
-
-        if self.input_buffered_rows + input.num_rows() < bucket_num {
-            // not enough rows to fold
-            self.push_input_buf(input);
-            return Ok(None);
-        }
-
-        self.fold_buf(bucket_num, input)?;
+        self.push_input_buf(input);
+        self.fold_buf(false)?;
         if self.output_buffered_rows >= self.batch_size {
             return Ok(self.take_output_buf()?.map(Ok));
         }
@@ -493,78 +487,120 @@ impl HistogramFoldStream {
         Ok(builders)
     }
 
-    fn calculate_bucket_num(&mut self, batch: &RecordBatch) -> DataFusionResult<Option<usize>> {
-        if let Some(size) = self.bucket_size {
-            return Ok(Some(size));
-        }
-
-        let inf_pos = self.find_positive_inf(batch)?;
-        if inf_pos == batch.num_rows() {
-            // no positive inf found, append to buffer and wait for next batch
-            self.push_input_buf(batch.clone());
-            return Ok(None);
+    /// Fold complete bucket groups from input buffer and put results to output buffer.
+    ///
+    /// The last group in the buffer is kept for the next batch as its remaining buckets
+    /// may not arrive yet, unless `flush` is set.
+    fn fold_buf(&mut self, flush: bool) -> DataFusionResult<()> {
+        if self.input_buffered_rows == 0 {
+            return Ok(());
         }
-
-        // else we found the positive inf.
-        // calculate the bucket size
-        let bucket_size = inf_pos + self.input_buffered_rows + 1;
-        Ok(Some(bucket_size))
-    }
-
-    /// Fold record batches from input buffer and put to output buffer
-    fn fold_buf(&mut self, bucket_num: usize, input: RecordBatch) -> DataFusionResult<()> {
-        self.push_input_buf(input);
         // TODO(ruihang): this concat is avoidable.
         let batch = concat_batches(&self.input.schema(), self.input_buffer.drain(..).as_ref())?;
-        let mut remaining_rows = self.input_buffered_rows;
-        let mut cursor = 0;
+        let num_rows = batch.num_rows();
 
         let gt_schema = GtSchema::try_from(self.input.schema()).unwrap();
         let batch = GtRecordBatch::try_from_df_record_batch(Arc::new(gt_schema), batch).unwrap();
 
-        while remaining_rows >= bucket_num {
-            // "sample" normal columns
-            for normal_index in &self.normal_indices {
-                let val = batch.column(*normal_index).get(cursor);
-                self.output_buffer[*normal_index].push_value_ref(val.as_value_ref());
-            }
-            // "fold" `le` and field columns
-            let le_array = batch.column(self.le_column_index);
-            let field_array = batch.column(self.field_column_index);
-            let mut bucket = vec![];
-            let mut counters = vec![];
-            for bias in 0..bucket_num {
-                let le_str_val = le_array.get(cursor + bias);
-                let le_str_val_ref = le_str_val.as_value_ref();
-                let le_str = le_str_val_ref
-                    .as_string()
-                    .unwrap()
-                    .expect("le column should not be nullable");
-                let le = le_str.parse::<f64>().unwrap();
-                bucket.push(le);
-
-                let counter = field_array
-                    .get(cursor + bias)
-                    .as_value_ref()
-                    .as_f64()
-                    .unwrap()
-                    .expect("field column should not be nullable");
-                counters.push(counter);
+        // unparsable `le` is treated as null and its row will be ignored
+        let le_array = batch.column(self.le_column_index);
+        let les = (0..num_rows)
+            .map(|cursor| {
+                let le_str_val = le_array.get(cursor);
+                let le_str = le_str_val.as_value_ref().as_string().ok().flatten()?;
+                le_str.parse::<f64>().ok()
+            })
+            .collect::<Vec<_>>();
+
+        let mut group_start = 0;
+        for cursor in 1..=num_rows {
+            if cursor == num_rows {
+                if !flush {
+                    break;
+                }
+            } else if !self.is_group_end(&batch, &les, cursor) {
+                continue;
             }
-            let result = Self::evaluate_row(self.quantile, &bucket, &counters)?;
-            self.output_buffer[self.field_column_index].push_value_ref(ValueRef::from(result));
-            cursor += bucket_num;
-            remaining_rows -= bucket_num;
-            self.output_buffered_rows += 1;
+            self.fold_group(&batch, &les, group_start, cursor)?;
+            group_start = cursor;
         }
 
-        let remaining_input_batch = batch.into_df_record_batch().slice(cursor, remaining_rows);
+        let remaining_input_batch = batch
+            .into_df_record_batch()
+            .slice(group_start, num_rows - group_start);
         self.input_buffered_rows = remaining_input_batch.num_rows();
         self.input_buffer.push(remaining_input_batch);
 
         Ok(())
     }
 
+    /// Whether the bucket group ends before the row at `cursor`. I.e., the previous row
+    /// is the last `+Inf` bucket, or the tags and timestamp are changed.
+    fn is_group_end(&self, batch: &GtRecordBatch, les: &[Option<f64>], cursor: usize) -> bool {
+        if les[cursor - 1] == Some(f64::INFINITY) && les[cursor] != Some(f64::INFINITY) {
+            return true;
+        }
+        self.normal_indices.iter().any(|normal_index| {
+            let column = batch.column(*normal_index);
+            column.get(cursor - 1) != column.get(cursor)
+        })
+    }
+
+    /// Fold rows in `[start, end)` of the batch into one output row.
+    fn fold_group(
+        &mut self,
+        batch: &GtRecordBatch,
+        les: &[Option<f64>],
+        start: usize,
+        end: usize,
+    ) -> DataFusionResult<()> {
+        // "sample" normal columns
+        for normal_index in &self.normal_indices {
+            let val = batch.column(*normal_index).get(start);
+            self.output_buffer[*normal_index].push_value_ref(val.as_value_ref());
+        }
+
+        // "fold" `le` and field columns
+        let field_array = batch.column(self.field_column_index);
+        let mut bucket: Vec<f64> = Vec::with_capacity(end - start);
+        let mut counters: Vec<f64> = Vec::with_capacity(end - start);
+        for (cursor, le) in les.iter().enumerate().take(end).skip(start) {
+            let counter = field_array
+                .get(cursor)
+                .as_value_ref()
+                .as_f64()
+                .ok()
+                .flatten();
+            let (Some(le), Some(counter)) = (le, counter) else {
+                continue;
+            };
+            // merge buckets with the same upper bound, which may come from different series
+            if bucket.last() == Some(le) {
+                *counters.last_mut().unwrap() += counter;
+            } else {
+                bucket.push(*le);
+                counters.push(counter);
+            }
+        }
+        // counters can be non-monotonic because of precision loss or inconsistent
+        // scrapes. Fix them like Prometheus does.
+        for i in 1..counters.len() {
+            if counters[i] < counters[i - 1] {
+                counters[i] = counters[i - 1];
+            }
+        }
+
+        let result = if bucket.last() == Some(&f64::INFINITY) {
+            Self::evaluate_row(self.quantile, &bucket, &counters)?
+        } else {
+            f64::NAN
+        };
+        self.output_buffer[self.field_column_index].push_value_ref(ValueRef::from(result));
+        self.output_buffered_rows += 1;
+
+        Ok(())
+    }
+
     fn push_input_buf(&mut self, batch: RecordBatch) {
         self.input_buffered_rows += batch.num_rows();
         self.input_buffer.push(batch);
@@ -597,43 +633,6 @@ impl HistogramFoldStream {
             .map_err(DataFusionError::ArrowError)
     }
 
-    /// Find the first `+Inf` which indicates the end of the bucket group
-    ///
-    /// If the return value equals to batch's num_rows means the it's not found
-    /// in this batch
-    fn find_positive_inf(&self, batch: &RecordBatch) -> DataFusionResult<usize> {
-        // fuse this function. It should not be called when the
-        // bucket size is already know.
-        if let Some(bucket_size) = self.bucket_size {
-            return Ok(bucket_size);
-        }
-        let string_le_array = batch.column(self.le_column_index);
-        let float_le_array = compute::cast(&string_le_array, &DataType::Float64).map_err(|e| {
-            DataFusionError::Execution(format!(
-                "cannot cast {} array to float64 array: {:?}",
-                string_le_array.data_type(),
-                e
-            ))
-        })?;
-        let le_as_f64_array = float_le_array
-            .as_primitive_opt::<Float64Type>()
-            .ok_or_else(|| {
-                DataFusionError::Execution(format!(
-                    "expect a float64 array, but found {}",
-                    float_le_array.data_type()
-                ))
-            })?;
-        for (i, v) in le_as_f64_array.iter().enumerate() {
-            if let Some(v) = v
-                && v == f64::INFINITY
-            {
-                return Ok(i);
-            }
-        }
-
-        Ok(batch.num_rows())
-    }
-
     /// Evaluate the field column and return the result
     fn evaluate_row(quantile: f64, bucket: &[f64], counter: &[f64]) -> DataFusionResult<f64> {
         // check bucket
@@ -801,6 +800,79 @@ mod test {
         assert_eq!(result_literal, expected);
     }
 
+    #[tokio::test]
+    async fn fold_different_buckets() {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("host", DataType::Utf8, true),
+            Field::new("le", DataType::Utf8, true),
+            Field::new("val", DataType::Float64, true),
+        ]));
+        // host_1 and host_2 have different bucket sets. host_2 contains two series
+        // whose buckets should be merged. host_3 has no `+Inf` bucket.
+        let data_1 = RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(StringArray::from(vec![
+                    "host_1", "host_1", "host_1", "host_2", "host_2",
+                ])) as _,
+                Arc::new(StringArray::from(vec!["1", "2", "+Inf", "1", "2"])) as _,
+                Arc::new(Float64Array::from(vec![1.0, 3.0, 4.0, 1.0, 1.0])) as _,
+            ],
+        )
+        .unwrap();
+        let data_2 = RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(StringArray::from(vec![
+                    "host_2", "host_2", "host_2", "host_3", "host_3",
+                ])) as _,
+                Arc::new(StringArray::from(vec!["2.0", "+Inf", "+inf", "1", "2"])) as _,
+                Arc::new(Float64Array::from(vec![2.0, 2.0, 2.0, 1.0, 2.0])) as _,
+            ],
+        )
+        .unwrap();
+        let memory_exec =
+            Arc::new(MemoryExec::try_new(&[vec![data_1, data_2]], schema, None).unwrap());
+        let output_schema = Arc::new(
+            (*HistogramFold::convert_schema(
+                &Arc::new(memory_exec.schema().to_dfschema().unwrap()),
+                "le",
+            )
+            .unwrap()
+            .as_ref())
+            .clone()
+            .into(),
+        );
+        let fold_exec = Arc::new(HistogramFoldExec {
+            le_column_index: 1,
+            field_column_index: 2,
+            quantile: 0.5,
+            ts_column_index: 9999, // not exist but doesn't matter
+            input: memory_exec,
+            output_schema,
+            metric: ExecutionPlanMetricsSet::new(),
+        });
+
+        let session_context = SessionContext::default();
+        let result = datafusion::physical_plan::collect(fold_exec, session_context.task_ctx())
+            .await
+            .unwrap();
+        let result_literal = datatypes::arrow::util::pretty::pretty_format_batches(&result)
+            .unwrap()
+            .to_string();
+
+        let expected = String::from(
+            "+--------+-----+
+| host   | val |
++--------+-----+
+| host_1 | 1.5 |
+| host_2 | 1.5 |
+| host_3 | NaN |
++--------+-----+",
+        );
+        assert_eq!(result_literal, expected);
+    }
+
     #[test]
     fn confirm_schema() {
         let input_schema = Schema::new(vec![
@@ -39,7 +39,7 @@ use prost::Message;
 use snafu::ResultExt;
 
 use crate::error::{DeserializeSnafu, Result};
-use crate::extension_plan::Millisecond;
+use crate::extension_plan::{is_stale_marker, Millisecond};
 
 /// Manipulate the input record batch to make it suitable for Instant Operator.
 ///
@@ -361,9 +361,9 @@ impl InstantManipulateStream {
                 match curr.cmp(&expected_ts) {
                     Ordering::Equal => {
                         if let Some(field_column) = &field_column
-                            && field_column.value(cursor).is_nan()
+                            && is_stale_marker(field_column.value(cursor))
                         {
-                            // ignore the NaN value
+                            // ignore the staleness marker
                         } else {
                             take_indices.push(cursor as u64);
                             aligned_ts.push(expected_ts);
@@ -395,9 +395,9 @@ impl InstantManipulateStream {
                     if prev_ts + self.lookback_delta >= expected_ts {
                         // only use the point in the time range
                         if let Some(field_column) = &field_column
-                            && field_column.value(prev_cursor).is_nan()
+                            && is_stale_marker(field_column.value(prev_cursor))
                         {
-                            // if the newest value is NaN, it means the value is stale, so we should not use it
+                            // if the newest value is a staleness marker, the series is stale, so we should not use it
                             continue;
                         }
                         // use this point
@@ -406,9 +406,9 @@ impl InstantManipulateStream {
                     }
                 }
             } else if let Some(field_column) = &field_column
-                && field_column.value(cursor).is_nan()
+                && is_stale_marker(field_column.value(cursor))
             {
-                // if the newest value is NaN, it means the value is stale, so we should not use it
+                // if the newest value is a staleness marker, the series is stale, so we should not use it
             } else {
                 // use this point
                 take_indices.push(cursor as u64);
@@ -455,6 +455,7 @@ mod test {
     use datatypes::arrow_array::StringArray;
 
     use super::*;
+    use crate::extension_plan::STALE_NAN_BITS;
 
     const TIME_INDEX_COLUMN: &str = "timestamp";
 
@@ -489,10 +490,22 @@ mod test {
         contains_nan: bool,
     ) {
         let memory_exec = if contains_nan {
-            Arc::new(prepare_test_data_with_nan())
+            prepare_test_data_with_nan(f64::from_bits(STALE_NAN_BITS))
         } else {
-            Arc::new(prepare_test_data())
+            prepare_test_data()
         };
+        let result_literal = do_manipulate(memory_exec, start, end, lookback_delta, interval).await;
+        assert_eq!(result_literal, expected);
+    }
+
+    async fn do_manipulate(
+        memory_exec: MemoryExec,
+        start: Millisecond,
+        end: Millisecond,
+        lookback_delta: Millisecond,
+        interval: Millisecond,
+    ) -> String {
+        let memory_exec = Arc::new(memory_exec);
         let normalize_exec = Arc::new(InstantManipulateExec {
             start,
             end,
@@ -507,11 +520,9 @@ mod test {
         let result = datafusion::physical_plan::collect(normalize_exec, session_context.task_ctx())
             .await
             .unwrap();
-        let result_literal = datatypes::arrow::util::pretty::pretty_format_batches(&result)
+        datatypes::arrow::util::pretty::pretty_format_batches(&result)
             .unwrap()
-            .to_string();
-
-        assert_eq!(result_literal, expected);
+            .to_string()
     }
 
     #[tokio::test]
@@ -749,7 +760,7 @@ mod test {
         do_normalize_test(190_000, 300_000, 30_000, 10_000, expected, false).await;
     }
 
-    fn prepare_test_data_with_nan() -> MemoryExec {
+    fn prepare_test_data_with_nan(nan: f64) -> MemoryExec {
         let schema = Arc::new(Schema::new(vec![
             Field::new(TIME_INDEX_COLUMN, TimestampMillisecondType::DATA_TYPE, true),
             Field::new("value", DataType::Float64, true),
@@ -757,8 +768,7 @@ mod test {
         let timestamp_column = Arc::new(TimestampMillisecondArray::from(vec![
             0, 30_000, 60_000, 90_000, 120_000, // every 30s
         ])) as _;
-        let field_column =
-            Arc::new(Float64Array::from(vec![0.0, f64::NAN, 6.0, f64::NAN, 12.0])) as _;
+        let field_column = Arc::new(Float64Array::from(vec![0.0, nan, 6.0, nan, 12.0])) as _;
         let data =
             RecordBatch::try_new(schema.clone(), vec![timestamp_column, field_column]).unwrap();
 
@@ -796,6 +806,36 @@ mod test {
         do_normalize_test(1, 300_001, 10_000, 10_000, expected, true).await;
     }
 
+    #[tokio::test]
+    async fn lookback_10s_interval_10s_with_normal_nan() {
+        // Only staleness markers make the series stale.
+        let expected = String::from(
+            "+---------------------+-------+\
+            \n| timestamp           | value |\
+            \n+---------------------+-------+\
+            \n| 1970-01-01T00:00:00 | 0.0   |\
+            \n| 1970-01-01T00:00:10 | 0.0   |\
+            \n| 1970-01-01T00:00:30 | NaN   |\
+            \n| 1970-01-01T00:00:40 | NaN   |\
+            \n| 1970-01-01T00:01:00 | 6.0   |\
+            \n| 1970-01-01T00:01:10 | 6.0   |\
+            \n| 1970-01-01T00:01:30 | NaN   |\
+            \n| 1970-01-01T00:01:40 | NaN   |\
+            \n| 1970-01-01T00:02:00 | 12.0  |\
+            \n| 1970-01-01T00:02:10 | 12.0  |\
+            \n+---------------------+-------+",
+        );
+        let result = do_manipulate(
+            prepare_test_data_with_nan(f64::NAN),
+            0,
+            300_000,
+            10_000,
+            10_000,
+        )
+        .await;
+        assert_eq!(result, expected);
+    }
+
     #[tokio::test]
     async fn ultra_large_range() {
         let expected = String::from(
@@ -39,7 +39,7 @@ use prost::Message;
 use snafu::ResultExt;
 
 use crate::error::{DeserializeSnafu, Result};
-use crate::extension_plan::Millisecond;
+use crate::extension_plan::{is_stale_marker, Millisecond};
 
 /// Normalize the input record batch. Notice that for simplicity, this method assumes
 /// the input batch only contains sample points from one time series.
@@ -47,7 +47,7 @@ use crate::extension_plan::Millisecond;
 /// Roughly speaking, this method does these things:
 /// - bias sample's timestamp by offset
 /// - sort the record batch based on timestamp column
-/// - remove NaN values (optional)
+/// - remove staleness markers (optional)
 #[derive(Debug, PartialEq, Eq, Hash)]
 pub struct SeriesNormalize {
     offset: Millisecond,
@@ -285,13 +285,12 @@ impl SeriesNormalizeStream {
             return Ok(ordered_batch);
         }
 
-        // TODO(ruihang): consider the "special NaN"
-        // filter out NaN
+        // filter out staleness markers, other NaN values are normal samples
         let mut filter = vec![true; input.num_rows()];
         for column in ordered_batch.columns() {
             if let Some(float_column) = column.as_any().downcast_ref::<Float64Array>() {
                 for (i, flag) in filter.iter_mut().enumerate() {
-                    if float_column.value(i).is_nan() {
+                    if is_stale_marker(float_column.value(i)) {
                         *flag = false;
                     }
                 }
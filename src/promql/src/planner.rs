@@ -12,20 +12,23 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::collections::{BTreeSet, HashSet, VecDeque};
+use std::collections::{BTreeMap, BTreeSet, HashSet, VecDeque};
 use std::str::FromStr;
 use std::sync::Arc;
-use std::time::UNIX_EPOCH;
+use std::time::{Duration, UNIX_EPOCH};
 
 use async_recursion::async_recursion;
 use catalog::table_source::DfTableSourceProvider;
 use datafusion::common::{DFSchemaRef, OwnedTableReference, Result as DfResult};
 use datafusion::datasource::DefaultTableSource;
-use datafusion::logical_expr::expr::{AggregateFunction, Alias, ScalarFunction, ScalarUDF};
+use datafusion::logical_expr::expr::{
+    AggregateFunction, Alias, ScalarFunction, ScalarUDF, WindowFunction as WindowFunctionExpr,
+};
 use datafusion::logical_expr::expr_rewriter::normalize_cols;
 use datafusion::logical_expr::{
-    AggregateFunction as AggregateFunctionEnum, BinaryExpr, BuiltinScalarFunction, Cast, Extension,
-    LogicalPlan, LogicalPlanBuilder, Operator, ScalarUDF as ScalarUdfDef,
+    AggregateFunction as AggregateFunctionEnum, BinaryExpr, BuiltInWindowFunction,
+    BuiltinScalarFunction, Cast, Extension, LogicalPlan, LogicalPlanBuilder, Operator,
+    ScalarUDF as ScalarUdfDef, WindowFrame, WindowFunction,
 };
 use datafusion::optimizer::utils;
 use datafusion::prelude as df_prelude;
@@ -35,8 +38,8 @@ use datafusion::sql::TableReference;
 use datatypes::arrow::datatypes::DataType as ArrowDataType;
 use promql_parser::label::{MatchOp, Matcher, Matchers, METRIC_NAME};
 use promql_parser::parser::{
-    token, AggregateExpr, BinModifier, BinaryExpr as PromBinaryExpr, Call, EvalStmt,
-    Expr as PromExpr, Function, LabelModifier, MatrixSelector, NumberLiteral, Offset, ParenExpr,
+    token, AggregateExpr, AtModifier, BinModifier, BinaryExpr as PromBinaryExpr, Call, EvalStmt,
+    Expr as PromExpr, LabelModifier, MatrixSelector, NumberLiteral, Offset, ParenExpr,
     StringLiteral, SubqueryExpr, TokenType, UnaryExpr, VectorMatchCardinality, VectorSelector,
 };
 use snafu::{ensure, OptionExt, ResultExt};
@@ -44,19 +47,20 @@ use table::table::adapter::DfTableProviderAdapter;
 
 use crate::error::{
     CatalogSnafu, ColumnNotFoundSnafu, CombineTableColumnMismatchSnafu, DataFusionPlanningSnafu,
-    ExpectRangeSelectorSnafu, FunctionInvalidArgumentSnafu, MultipleMetricMatchersSnafu,
-    MultipleVectorSnafu, NoMetricMatcherSnafu, Result, TableNameNotFoundSnafu,
-    TimeIndexNotFoundSnafu, UnexpectedPlanExprSnafu, UnexpectedTokenSnafu, UnknownTableSnafu,
-    UnsupportedExprSnafu, UnsupportedVectorMatchSnafu, ValueNotFoundSnafu, ZeroRangeSelectorSnafu,
+    ExpectRangeSelectorSnafu, FunctionInvalidArgumentSnafu, InvalidLabelNameSnafu,
+    InvalidRegularExpressionSnafu, MultipleMetricMatchersSnafu, MultipleVectorSnafu,
+    NoMetricMatcherSnafu, Result, TableNameNotFoundSnafu, TimeIndexNotFoundSnafu,
+    UnexpectedPlanExprSnafu, UnexpectedTokenSnafu, UnknownTableSnafu, UnsupportedExprSnafu,
+    UnsupportedVectorMatchSnafu, ValueNotFoundSnafu, ZeroRangeSelectorSnafu,
 };
 use crate::extension_plan::{
     build_special_time_expr, EmptyMetric, HistogramFold, InstantManipulate, Millisecond,
     RangeManipulate, SeriesDivide, SeriesNormalize,
 };
 use crate::functions::{
-    AbsentOverTime, AvgOverTime, Changes, CountOverTime, Delta, Deriv, HoltWinters, IDelta,
-    Increase, LastOverTime, MaxOverTime, MinOverTime, PredictLinear, PresentOverTime,
-    QuantileOverTime, Rate, Resets, StddevOverTime, StdvarOverTime, SumOverTime,
+    AvgOverTime, Changes, CountOverTime, Delta, Deriv, HoltWinters, IDelta, Increase, LastOverTime,
+    MaxOverTime, MinOverTime, PredictLinear, PresentOverTime, QuantileOverTime, Rate, Resets,
+    StddevOverTime, StdvarOverTime, SumOverTime,
 };
 
 /// `time()` function in PromQL.
@@ -65,6 +69,18 @@ const SPECIAL_TIME_FUNCTION: &str = "time";
 const SPECIAL_HISTOGRAM_QUANTILE: &str = "histogram_quantile";
 /// `le` column for conventional histogram.
 const LE_COLUMN_NAME: &str = "le";
+/// `absent` function in PromQL.
+const SPECIAL_ABSENT: &str = "absent";
+/// `absent_over_time` function in PromQL.
+const SPECIAL_ABSENT_OVER_TIME: &str = "absent_over_time";
+/// Alias of the input's time index column when planning `absent`.
+const ABSENT_INPUT_TIME_COLUMN: &str = "__absent_input_time";
+/// `label_replace` function in PromQL.
+const SPECIAL_LABEL_REPLACE: &str = "label_replace";
+/// `label_join` function in PromQL.
+const SPECIAL_LABEL_JOIN: &str = "label_join";
+/// Rank of each sample in its group when planning `topk` and `bottomk`.
+const TOPK_RANK_COLUMN: &str = "__topk_rank";
 
 const DEFAULT_TIME_INDEX_COLUMN: &str = "time";
 
@@ -74,6 +90,9 @@ const DEFAULT_FIELD_COLUMN: &str = "value";
 /// Special modifier to project field columns under multi-field mode
 const FIELD_COLUMN_MATCHER: &str = "__field__";
 
+/// Time index column of steps that results pinned by the `@` modifier are repeated at.
+const AT_STEP_TIME_COLUMN: &str = "__at_step_time";
+
 #[derive(Default, Debug, Clone)]
 struct PromPlannerContext {
     // query parameters
@@ -81,6 +100,10 @@ struct PromPlannerContext {
     end: Millisecond,
     interval: Millisecond,
     lookback_delta: Millisecond,
+    /// Start and end of the whole query. `start` and `end` above change when planning
+    /// subqueries and expressions with `@` modifiers but these don't.
+    query_start: Millisecond,
+    query_end: Millisecond,
 
     // planner states
     table_name: Option<String>,
@@ -94,11 +117,15 @@ struct PromPlannerContext {
 
 impl PromPlannerContext {
     fn from_eval_stmt(stmt: &EvalStmt) -> Self {
+        let start = stmt.start.duration_since(UNIX_EPOCH).unwrap().as_millis() as _;
+        let end = stmt.end.duration_since(UNIX_EPOCH).unwrap().as_millis() as _;
         Self {
-            start: stmt.start.duration_since(UNIX_EPOCH).unwrap().as_millis() as _,
-            end: stmt.end.duration_since(UNIX_EPOCH).unwrap().as_millis() as _,
+            start,
+            end,
             interval: stmt.interval.as_millis() as _,
             lookback_delta: stmt.lookback_delta.as_millis() as _,
+            query_start: start,
+            query_end: end,
             ..Default::default()
         }
     }
@@ -139,16 +166,26 @@ impl PromPlanner {
 
     #[async_recursion]
     pub async fn prom_expr_to_plan(&mut self, prom_expr: PromExpr) -> Result<LogicalPlan> {
+        if let Some(at) = Self::at_modifier(&prom_expr) {
+            let timestamp = self.at_timestamp(at);
+            let mut prom_expr = prom_expr;
+            Self::remove_at_modifier(&mut prom_expr);
+            return self.pinned_expr_to_plan(prom_expr, timestamp).await;
+        }
+
         let res = match &prom_expr {
             PromExpr::Aggregate(AggregateExpr {
                 op,
                 expr,
-                // TODO(ruihang): support param
-                param: _param,
+                param,
                 modifier,
             }) => {
                 let input = self.prom_expr_to_plan(*expr.clone()).await?;
 
+                if matches!(op.id(), token::T_TOPK | token::T_BOTTOMK) {
+                    return self.topk_to_plan(input, *op, param, modifier);
+                }
+
                 // calculate columns to group by
                 // Need to append time index column into group by columns
                 let group_exprs = self.agg_modifier_to_col(input.schema(), modifier)?;
@@ -355,10 +392,13 @@ impl PromPlanner {
                 }
             }
             PromExpr::Paren(ParenExpr { expr }) => self.prom_expr_to_plan(*expr.clone()).await?,
-            PromExpr::Subquery(SubqueryExpr { .. }) => UnsupportedExprSnafu {
-                name: "Prom Subquery",
-            }
-            .fail()?,
+            PromExpr::Subquery(SubqueryExpr {
+                expr,
+                offset,
+                range,
+                step,
+                ..
+            }) => self.subquery_to_plan(expr, offset, range, step).await?,
             PromExpr::NumberLiteral(NumberLiteral { val }) => {
                 self.ctx.time_index_column = Some(DEFAULT_TIME_INDEX_COLUMN.to_string());
                 self.ctx.field_columns = vec![DEFAULT_FIELD_COLUMN.to_string()];
@@ -399,11 +439,12 @@ impl PromPlanner {
                     ),
                 })
             }
+            // The `@` modifier is handled by `pinned_expr_to_plan()`.
             PromExpr::VectorSelector(VectorSelector {
                 name,
                 offset,
                 matchers,
-                at: _,
+                ..
             }) => {
                 let matchers = self.preprocess_label_matchers(matchers, name)?;
                 self.setup_context().await?;
@@ -499,6 +540,9 @@ impl PromPlanner {
                             fn_name: SPECIAL_HISTOGRAM_QUANTILE.to_string(),
                         })?
                         .clone();
+                    // `le` is folded and not a tag column of the output anymore
+                    self.ctx.tag_columns.retain(|col| col != LE_COLUMN_NAME);
+                    self.ctx.field_columns = vec![field_column.clone()];
 
                     return Ok(LogicalPlan::Extension(Extension {
                         node: Arc::new(
@@ -514,6 +558,14 @@ impl PromPlanner {
                     }));
                 }
 
+                if func.name == SPECIAL_ABSENT || func.name == SPECIAL_ABSENT_OVER_TIME {
+                    return self.absent_to_plan(func.name, &args.args).await;
+                }
+
+                if func.name == SPECIAL_LABEL_REPLACE || func.name == SPECIAL_LABEL_JOIN {
+                    return self.label_function_to_plan(func.name, &args.args).await;
+                }
+
                 let args = self.create_function_args(&args.args)?;
                 let input = if let Some(prom_expr) = args.input {
                     self.prom_expr_to_plan(prom_expr).await?
@@ -534,17 +586,7 @@ impl PromPlanner {
                         ),
                     })
                 };
-                let mut func_exprs = self.create_function_expr(func, args.literals)?;
-                func_exprs.insert(0, self.create_time_index_column_expr()?);
-                func_exprs.extend_from_slice(&self.create_tag_column_exprs()?);
-
-                LogicalPlanBuilder::from(input)
-                    .project(func_exprs)
-                    .context(DataFusionPlanningSnafu)?
-                    .filter(self.create_empty_values_filter_expr()?)
-                    .context(DataFusionPlanningSnafu)?
-                    .build()
-                    .context(DataFusionPlanningSnafu)?
+                self.function_to_plan(input, func.name, args.literals)?
             }
             PromExpr::Extension(promql_parser::parser::ast::Extension { expr }) => {
                 let children = expr.children();
@@ -630,11 +672,7 @@ impl PromPlanner {
         let table_name = self.ctx.table_name.clone().unwrap();
 
         // make filter exprs
-        let offset_duration = match offset {
-            Some(Offset::Pos(duration)) => duration.as_millis() as Millisecond,
-            Some(Offset::Neg(duration)) => -(duration.as_millis() as Millisecond),
-            None => 0,
-        };
+        let offset_duration = offset_to_millis(offset);
         let range_ms = self.ctx.range.unwrap_or_default();
         let mut scan_filters = self.matchers_to_expr(label_matchers.clone())?;
         scan_filters.push(self.create_time_index_column_expr()?.gt_eq(DfExpr::Literal(
@@ -766,6 +804,212 @@ impl PromPlanner {
         Ok(logical_plan)
     }
 
+    /// Returns the `@` modifier that pins the evaluation time of `expr`. It's the modifier
+    /// of a vector selector, or of the range vector argument of a function call as range
+    /// vectors are only evaluated by functions.
+    fn at_modifier(expr: &PromExpr) -> Option<&AtModifier> {
+        match expr {
+            PromExpr::VectorSelector(vs) => vs.at.as_ref(),
+            PromExpr::Call(Call { args, .. }) => {
+                args.args.iter().find_map(|arg| match arg.as_ref() {
+                    PromExpr::MatrixSelector(MatrixSelector { vs, .. }) => vs.at.as_ref(),
+                    PromExpr::Subquery(SubqueryExpr { at, .. }) => at.as_ref(),
+                    _ => None,
+                })
+            }
+            _ => None,
+        }
+    }
+
+    /// Removes the modifier returned by [Self::at_modifier] from `expr`.
+    fn remove_at_modifier(expr: &mut PromExpr) {
+        match expr {
+            PromExpr::VectorSelector(vs) => vs.at = None,
+            PromExpr::Call(Call { args, .. }) => {
+                for arg in args.args.iter_mut() {
+                    match arg.as_mut() {
+                        PromExpr::MatrixSelector(MatrixSelector { vs, .. }) => vs.at = None,
+                        PromExpr::Subquery(SubqueryExpr { at, .. }) => *at = None,
+                        _ => {}
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Returns the timestamp in millisecond that the `@` modifier pins. `start()` and
+    /// `end()` always refer to the whole query even inside subqueries.
+    fn at_timestamp(&self, at: &AtModifier) -> Millisecond {
+        match at {
+            AtModifier::Start => self.ctx.query_start,
+            AtModifier::End => self.ctx.query_end,
+            AtModifier::At(time) => match time.duration_since(UNIX_EPOCH) {
+                Ok(duration) => duration.as_millis() as Millisecond,
+                Err(e) => -(e.duration().as_millis() as Millisecond),
+            },
+        }
+    }
+
+    /// Plans `expr` evaluated only at `timestamp` and repeats its result at every step,
+    /// which is how the `@` modifier works.
+    async fn pinned_expr_to_plan(
+        &mut self,
+        expr: PromExpr,
+        timestamp: Millisecond,
+    ) -> Result<LogicalPlan> {
+        let (start, end) = (self.ctx.start, self.ctx.end);
+        self.ctx.start = timestamp;
+        self.ctx.end = timestamp;
+        let plan = self.prom_expr_to_plan(expr).await;
+        self.ctx.start = start;
+        self.ctx.end = end;
+
+        self.broadcast_to_steps(plan?)
+    }
+
+    /// Repeats samples of the `input`, which only has samples at a single timestamp, at
+    /// every step between start and end.
+    fn broadcast_to_steps(&self, input: LogicalPlan) -> Result<LogicalPlan> {
+        let time_index =
+            self.ctx
+                .time_index_column
+                .clone()
+                .with_context(|| TimeIndexNotFoundSnafu {
+                    table: self.ctx.table_name.clone().unwrap_or_default(),
+                })?;
+        let steps = LogicalPlan::Extension(Extension {
+            node: Arc::new(
+                EmptyMetric::new(
+                    self.ctx.start,
+                    self.ctx.end,
+                    self.ctx.interval,
+                    AT_STEP_TIME_COLUMN.to_string(),
+                    DEFAULT_FIELD_COLUMN.to_string(),
+                    None,
+                )
+                .context(DataFusionPlanningSnafu)?,
+            ),
+        });
+
+        let input_exprs = input
+            .schema()
+            .fields()
+            .iter()
+            .filter(|field| field.name() != &time_index)
+            .map(|field| DfExpr::Column(field.qualified_column()))
+            .collect::<Vec<_>>();
+        let mut exprs =
+            vec![DfExpr::Column(Column::from_name(AT_STEP_TIME_COLUMN)).alias(&time_index)];
+        exprs.extend(input_exprs.iter().cloned());
+
+        let input = LogicalPlanBuilder::from(input)
+            .project(input_exprs)
+            .context(DataFusionPlanningSnafu)?
+            .build()
+            .context(DataFusionPlanningSnafu)?;
+        LogicalPlanBuilder::from(steps)
+            .cross_join(input)
+            .context(DataFusionPlanningSnafu)?
+            .project(exprs)
+            .context(DataFusionPlanningSnafu)?
+            .sort(self.create_tag_and_time_index_column_sort_exprs()?)
+            .context(DataFusionPlanningSnafu)?
+            .build()
+            .context(DataFusionPlanningSnafu)
+    }
+
+    /// Plans the subquery `expr[range:step]` as a range vector.
+    ///
+    /// The inner `expr` is evaluated at every `step` aligned to the epoch, and samples
+    /// within `range` before each step of the query are gathered like a matrix selector.
+    /// `step` defaults to the interval of the query.
+    async fn subquery_to_plan(
+        &mut self,
+        expr: &PromExpr,
+        offset: &Option<Offset>,
+        range: &Duration,
+        step: &Option<Duration>,
+    ) -> Result<LogicalPlan> {
+        ensure!(!range.is_zero(), ZeroRangeSelectorSnafu);
+        let range_ms = range.as_millis() as Millisecond;
+        let step_ms = step
+            .map(|step| step.as_millis() as Millisecond)
+            .filter(|step| *step > 0)
+            .unwrap_or(self.ctx.interval);
+        let offset_ms = offset_to_millis(offset);
+
+        let (start, end, interval) = (self.ctx.start, self.ctx.end, self.ctx.interval);
+        // The first step after `start - offset - range` and the last step before `end - offset`.
+        self.ctx.start = ((start - offset_ms - range_ms).div_euclid(step_ms) + 1) * step_ms;
+        self.ctx.end = (end - offset_ms).div_euclid(step_ms) * step_ms;
+        self.ctx.interval = step_ms;
+        let inner = self.prom_expr_to_plan(expr.clone()).await;
+        self.ctx.start = start;
+        self.ctx.end = end;
+        self.ctx.interval = interval;
+        let mut inner = inner?;
+
+        let time_index =
+            self.ctx
+                .time_index_column
+                .clone()
+                .with_context(|| TimeIndexNotFoundSnafu {
+                    table: self.ctx.table_name.clone().unwrap_or_default(),
+                })?;
+        if offset_ms != 0 {
+            // Moves samples of the inner query forward by the offset.
+            let exprs = inner
+                .schema()
+                .fields()
+                .iter()
+                .map(|field| {
+                    let column = DfExpr::Column(field.qualified_column());
+                    if field.name() == &time_index {
+                        let offset = DfExpr::Literal(ScalarValue::new_interval_mdn(
+                            0,
+                            0,
+                            offset_ms * 1_000_000,
+                        ));
+                        (column + offset).alias(&time_index)
+                    } else {
+                        column
+                    }
+                })
+                .collect::<Vec<_>>();
+            inner = LogicalPlanBuilder::from(inner)
+                .project(exprs)
+                .context(DataFusionPlanningSnafu)?
+                .build()
+                .context(DataFusionPlanningSnafu)?;
+        }
+
+        let sort_plan = LogicalPlanBuilder::from(inner)
+            .sort(self.create_tag_and_time_index_column_sort_exprs()?)
+            .context(DataFusionPlanningSnafu)?
+            .build()
+            .context(DataFusionPlanningSnafu)?;
+        let divide_plan = LogicalPlan::Extension(Extension {
+            node: Arc::new(SeriesDivide::new(self.ctx.tag_columns.clone(), sort_plan)),
+        });
+
+        self.ctx.range = Some(range_ms);
+        let manipulate = RangeManipulate::new(
+            self.ctx.start,
+            self.ctx.end,
+            self.ctx.interval,
+            range_ms,
+            time_index,
+            self.ctx.field_columns.clone(),
+            divide_plan,
+        )
+        .context(DataFusionPlanningSnafu)?;
+
+        Ok(LogicalPlan::Extension(Extension {
+            node: Arc::new(manipulate),
+        }))
+    }
+
     /// Convert [LabelModifier] to [Column] exprs for aggregation.
     /// Timestamp column and tag columns will be included.
     ///
@@ -966,12 +1210,366 @@ impl PromPlanner {
         Ok(result)
     }
 
+    /// Applies function `func_name` on value columns of the `input` plan and removes rows
+    /// whose results are all null.
+    fn function_to_plan(
+        &mut self,
+        input: LogicalPlan,
+        func_name: &str,
+        other_input_exprs: Vec<DfExpr>,
+    ) -> Result<LogicalPlan> {
+        let mut func_exprs = self.create_function_expr(func_name, other_input_exprs)?;
+        func_exprs.insert(0, self.create_time_index_column_expr()?);
+        func_exprs.extend_from_slice(&self.create_tag_column_exprs()?);
+
+        LogicalPlanBuilder::from(input)
+            .project(func_exprs)
+            .context(DataFusionPlanningSnafu)?
+            .filter(self.create_empty_values_filter_expr()?)
+            .context(DataFusionPlanningSnafu)?
+            .build()
+            .context(DataFusionPlanningSnafu)
+    }
+
+    /// Plans `absent()` and `absent_over_time()`.
+    ///
+    /// The result has a sample with value 1 at each step where the input has no sample.
+    /// Like Prometheus, labels of the result come from equality matchers of the input
+    /// selector.
+    ///
+    /// # Side Effects
+    ///
+    /// This method will reset [PromPlannerContext]'s columns to the result's columns.
+    async fn absent_to_plan(
+        &mut self,
+        fn_name: &str,
+        args: &[Box<PromExpr>],
+    ) -> Result<LogicalPlan> {
+        ensure!(
+            args.len() == 1,
+            FunctionInvalidArgumentSnafu {
+                fn_name: fn_name.to_string(),
+            }
+        );
+        let input_expr = args[0].as_ref().clone();
+        let labels = Self::absent_labels(&input_expr);
+
+        let input = self.prom_expr_to_plan(input_expr).await?;
+        let input = if fn_name == SPECIAL_ABSENT_OVER_TIME {
+            // `present_over_time` returns null if the range is empty.
+            self.function_to_plan(input, "present_over_time", vec![])?
+        } else {
+            LogicalPlanBuilder::from(input)
+                .filter(self.create_empty_values_filter_expr()?)
+                .context(DataFusionPlanningSnafu)?
+                .build()
+                .context(DataFusionPlanningSnafu)?
+        };
+        // Steps where the input has samples.
+        let present_steps = LogicalPlanBuilder::from(input)
+            .project(vec![self
+                .create_time_index_column_expr()?
+                .alias(ABSENT_INPUT_TIME_COLUMN)])
+            .context(DataFusionPlanningSnafu)?
+            .distinct()
+            .context(DataFusionPlanningSnafu)?
+            .build()
+            .context(DataFusionPlanningSnafu)?;
+
+        let all_steps = LogicalPlan::Extension(Extension {
+            node: Arc::new(
+                EmptyMetric::new(
+                    self.ctx.start,
+                    self.ctx.end,
+                    self.ctx.interval,
+                    DEFAULT_TIME_INDEX_COLUMN.to_string(),
+                    DEFAULT_FIELD_COLUMN.to_string(),
+                    Some(df_prelude::lit(1.0)),
+                )
+                .context(DataFusionPlanningSnafu)?,
+            ),
+        });
+        let mut exprs = vec![
+            DfExpr::Column(Column::from_name(DEFAULT_TIME_INDEX_COLUMN)),
+            DfExpr::Column(Column::from_name(DEFAULT_FIELD_COLUMN)),
+        ];
+        exprs.extend(
+            labels
+                .iter()
+                .map(|(name, value)| df_prelude::lit(value.clone()).alias(name)),
+        );
+        let plan = LogicalPlanBuilder::from(all_steps)
+            .join(
+                present_steps,
+                JoinType::LeftAnti,
+                (
+                    vec![Column::from_name(DEFAULT_TIME_INDEX_COLUMN)],
+                    vec![Column::from_name(ABSENT_INPUT_TIME_COLUMN)],
+                ),
+                None,
+            )
+            .context(DataFusionPlanningSnafu)?
+            .project(exprs)
+            .context(DataFusionPlanningSnafu)?
+            .build()
+            .context(DataFusionPlanningSnafu)?;
+
+        self.ctx.time_index_column = Some(DEFAULT_TIME_INDEX_COLUMN.to_string());
+        self.ctx.field_columns = vec![DEFAULT_FIELD_COLUMN.to_string()];
+        self.ctx.tag_columns = labels.into_keys().collect();
+        self.ctx.table_name = Some(String::new());
+        self.ctx.range = None;
+
+        Ok(plan)
+    }
+
+    /// Plan `label_replace` and `label_join`. The destination label is computed from the
+    /// source labels of each series, and is appended to the tag columns if the input doesn't
+    /// have it. Labels not present in the input are treated as empty strings.
+    ///
+    /// # Side effect
+    ///
+    /// This method will add the destination label to the tag columns in ctx.
+    async fn label_function_to_plan(
+        &mut self,
+        fn_name: &str,
+        args: &[Box<PromExpr>],
+    ) -> Result<LogicalPlan> {
+        let expected_args = if fn_name == SPECIAL_LABEL_REPLACE {
+            args.len() == 5
+        } else {
+            args.len() >= 3
+        };
+        ensure!(expected_args, FunctionInvalidArgumentSnafu { fn_name });
+        let labels = args[1..]
+            .iter()
+            .map(|arg| Self::try_build_string_literal(arg))
+            .collect::<Option<Vec<_>>>()
+            .context(FunctionInvalidArgumentSnafu { fn_name })?;
+
+        let input = self.prom_expr_to_plan(args[0].as_ref().clone()).await?;
+
+        let dst_label = &labels[0];
+        Self::ensure_valid_label_name(dst_label)?;
+        ensure!(
+            !self.ctx.field_columns.contains(dst_label)
+                && self.ctx.time_index_column.as_ref() != Some(dst_label),
+            InvalidLabelNameSnafu { label: dst_label }
+        );
+        let dst_expr = if fn_name == SPECIAL_LABEL_REPLACE {
+            let (replacement, src_label, regex) = (&labels[1], &labels[2], &labels[3]);
+            Self::ensure_valid_label_name(src_label)?;
+            // the regex is fully anchored, like Prometheus does
+            let pattern = format!("^(?:{regex})$");
+            let _ = regex::Regex::new(&pattern).context(InvalidRegularExpressionSnafu { regex })?;
+
+            let src_expr = self.create_label_value_expr(src_label);
+            let is_match = DfExpr::BinaryExpr(BinaryExpr {
+                left: Box::new(src_expr.clone()),
+                op: Operator::RegexMatch,
+                right: Box::new(df_prelude::lit(pattern.clone())),
+            });
+            let replaced = DfExpr::ScalarFunction(ScalarFunction {
+                fun: BuiltinScalarFunction::RegexpReplace,
+                args: vec![
+                    src_expr,
+                    df_prelude::lit(pattern),
+                    df_prelude::lit(replacement.clone()),
+                ],
+            });
+            // series not matching the regex are returned unchanged
+            df_prelude::when(is_match, replaced)
+                .otherwise(self.create_label_value_expr(dst_label))
+                .context(DataFusionPlanningSnafu)?
+        } else {
+            let (separator, src_labels) = (&labels[1], &labels[2..]);
+            let mut args = Vec::with_capacity(src_labels.len() + 1);
+            args.push(df_prelude::lit(separator.clone()));
+            for src_label in src_labels {
+                Self::ensure_valid_label_name(src_label)?;
+                args.push(self.create_label_value_expr(src_label));
+            }
+            if src_labels.is_empty() {
+                df_prelude::lit(String::new())
+            } else {
+                DfExpr::ScalarFunction(ScalarFunction {
+                    fun: BuiltinScalarFunction::ConcatWithSeparator,
+                    args,
+                })
+            }
+        };
+
+        if !self.ctx.tag_columns.contains(dst_label) {
+            self.ctx.tag_columns.push(dst_label.clone());
+        }
+        let mut exprs = Vec::with_capacity(self.ctx.tag_columns.len() + 1);
+        for tag in &self.ctx.tag_columns {
+            if tag == dst_label {
+                exprs.push(dst_expr.clone().alias(dst_label));
+            } else {
+                exprs.push(DfExpr::Column(Column::from_name(tag)));
+            }
+        }
+        exprs.push(self.create_time_index_column_expr()?);
+        exprs.extend(
+            self.ctx
+                .field_columns
+                .iter()
+                .map(|col| DfExpr::Column(Column::from_name(col))),
+        );
+
+        let mut builder = LogicalPlanBuilder::from(input)
+            .project(exprs)
+            .context(DataFusionPlanningSnafu)?;
+        // qualify the destination label like other columns
+        if let Some(table_name) = &self.ctx.table_name
+            && !table_name.is_empty()
+        {
+            builder = builder
+                .alias(table_name.clone())
+                .context(DataFusionPlanningSnafu)?;
+        }
+        builder.build().context(DataFusionPlanningSnafu)
+    }
+
+    /// Returns the value of given label, or an empty string if the label doesn't exist.
+    fn create_label_value_expr(&self, label: &str) -> DfExpr {
+        if self.ctx.tag_columns.iter().any(|tag| tag == label) {
+            DfExpr::ScalarFunction(ScalarFunction {
+                fun: BuiltinScalarFunction::Coalesce,
+                args: vec![
+                    DfExpr::Column(Column::from_name(label)),
+                    df_prelude::lit(String::new()),
+                ],
+            })
+        } else {
+            df_prelude::lit(String::new())
+        }
+    }
+
+    /// Ensure the label name matches `[a-zA-Z_][a-zA-Z0-9_]*`. The metric name can't be
+    /// changed as it is the table name.
+    fn ensure_valid_label_name(label: &str) -> Result<()> {
+        let mut chars = label.chars();
+        let is_valid = chars
+            .next()
+            .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+            && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+            && label != METRIC_NAME;
+        ensure!(is_valid, InvalidLabelNameSnafu { label });
+        Ok(())
+    }
+
+    /// Plan `topk` and `bottomk`. Samples are ranked by the first field column in each group
+    /// and time index, and only the first `k` samples are kept. Unlike other aggregations, the
+    /// result keeps all labels of the input series.
+    fn topk_to_plan(
+        &mut self,
+        input: LogicalPlan,
+        op: TokenType,
+        param: &Option<Box<PromExpr>>,
+        modifier: &Option<LabelModifier>,
+    ) -> Result<LogicalPlan> {
+        let fn_name = format!("{op:?}");
+        let k = param
+            .as_ref()
+            .and_then(|param| Self::try_build_float_literal(param))
+            .context(FunctionInvalidArgumentSnafu { fn_name: &fn_name })?;
+        // TODO(agent): samples are only ranked by the first field under multi-field mode
+        let field_column = self
+            .ctx
+            .field_columns
+            .first()
+            .with_context(|| ValueNotFoundSnafu {
+                table: self.ctx.table_name.clone().unwrap_or_default(),
+            })?
+            .clone();
+
+        // partition by the group labels but keep all labels of the input in output
+        let tag_columns = self.ctx.tag_columns.clone();
+        let partition_exprs = self.agg_modifier_to_col(input.schema(), modifier)?;
+        self.ctx.tag_columns = tag_columns;
+
+        let is_bottomk = op.id() == token::T_BOTTOMK;
+        let value = DfExpr::Column(Column::from_name(field_column));
+        // NaN is always ranked last, like Prometheus does
+        let is_nan = DfExpr::ScalarFunction(ScalarFunction {
+            fun: BuiltinScalarFunction::Isnan,
+            args: vec![value.clone()],
+        });
+        let rank = DfExpr::WindowFunction(WindowFunctionExpr::new(
+            WindowFunction::BuiltInWindowFunction(BuiltInWindowFunction::RowNumber),
+            vec![],
+            partition_exprs,
+            vec![is_nan.sort(true, false), value.sort(is_bottomk, false)],
+            WindowFrame::new(true),
+        ))
+        .alias(TOPK_RANK_COLUMN);
+        // `k` less than 1 results in an empty result
+        let k = if k >= 1.0 { k as u64 } else { 0 };
+
+        let mut project_exprs = self.create_tag_column_exprs()?;
+        project_exprs.push(self.create_time_index_column_expr()?);
+        project_exprs.extend(
+            self.ctx
+                .field_columns
+                .iter()
+                .map(|col| DfExpr::Column(Column::from_name(col))),
+        );
+
+        LogicalPlanBuilder::from(input)
+            .filter(self.create_empty_values_filter_expr()?)
+            .context(DataFusionPlanningSnafu)?
+            .window(vec![rank])
+            .context(DataFusionPlanningSnafu)?
+            .filter(DfExpr::Column(Column::from_name(TOPK_RANK_COLUMN)).lt_eq(df_prelude::lit(k)))
+            .context(DataFusionPlanningSnafu)?
+            .project(project_exprs)
+            .context(DataFusionPlanningSnafu)?
+            .sort(self.create_tag_and_time_index_column_sort_exprs()?)
+            .context(DataFusionPlanningSnafu)?
+            .build()
+            .context(DataFusionPlanningSnafu)
+    }
+
+    /// Returns labels of the result of `absent()` from the equality matchers of the input
+    /// selector. Labels matched by more than one equality matcher are ambiguous and ignored.
+    fn absent_labels(expr: &PromExpr) -> BTreeMap<String, String> {
+        let matchers = match expr {
+            PromExpr::VectorSelector(vs) => &vs.matchers,
+            PromExpr::MatrixSelector(MatrixSelector { vs, .. }) => &vs.matchers,
+            _ => return BTreeMap::new(),
+        };
+
+        let mut labels = BTreeMap::new();
+        let mut duplicated = HashSet::new();
+        for matcher in &matchers.matchers {
+            if !matches!(matcher.op, MatchOp::Equal)
+                || matcher.name == METRIC_NAME
+                || matcher.name == FIELD_COLUMN_MATCHER
+                // These names are taken by the time index and value columns of the result.
+                || matcher.name == DEFAULT_TIME_INDEX_COLUMN
+                || matcher.name == DEFAULT_FIELD_COLUMN
+            {
+                continue;
+            }
+            if labels
+                .insert(matcher.name.clone(), matcher.value.clone())
+                .is_some()
+            {
+                duplicated.insert(matcher.name.clone());
+            }
+        }
+        labels.retain(|name, _| !duplicated.contains(name));
+        labels
+    }
+
     /// # Side Effects
     ///
     /// This method will update [PromPlannerContext]'s value fields.
     fn create_function_expr(
         &mut self,
-        func: &Function,
+        func_name: &str,
         other_input_exprs: Vec<DfExpr>,
     ) -> Result<Vec<DfExpr>> {
         // TODO(ruihang): check function args list
@@ -980,7 +1578,7 @@ impl PromPlanner {
         // TODO(ruihang): set this according to in-param list
         let field_column_pos = 0;
         let mut exprs = Vec::with_capacity(self.ctx.field_columns.len());
-        let scalar_func = match func.name {
+        let scalar_func = match func_name {
             "increase" => ScalarFunc::ExtrapolateUdf(Increase::scalar_udf(
                 self.ctx.range.context(ExpectRangeSelectorSnafu)?,
             )),
@@ -1001,7 +1599,6 @@ impl PromPlanner {
             "sum_over_time" => ScalarFunc::Udf(SumOverTime::scalar_udf()),
             "count_over_time" => ScalarFunc::Udf(CountOverTime::scalar_udf()),
             "last_over_time" => ScalarFunc::Udf(LastOverTime::scalar_udf()),
-            "absent_over_time" => ScalarFunc::Udf(AbsentOverTime::scalar_udf()),
             "present_over_time" => ScalarFunc::Udf(PresentOverTime::scalar_udf()),
             "stddev_over_time" => ScalarFunc::Udf(StddevOverTime::scalar_udf()),
             "stdvar_over_time" => ScalarFunc::Udf(StdvarOverTime::scalar_udf()),
@@ -1128,9 +1725,9 @@ impl PromPlanner {
                 ScalarFunc::GeneratedExpr
             }
             _ => ScalarFunc::DataFusionBuiltin(
-                BuiltinScalarFunction::from_str(func.name).map_err(|_| {
+                BuiltinScalarFunction::from_str(func_name).map_err(|_| {
                     UnsupportedExprSnafu {
-                        name: func.name.to_string(),
+                        name: func_name.to_string(),
                     }
                     .build()
                 })?,
@@ -1266,12 +1863,10 @@ impl PromPlanner {
             token::T_GROUP => AggregateFunctionEnum::Grouping,
             token::T_STDDEV => AggregateFunctionEnum::StddevPop,
             token::T_STDVAR => AggregateFunctionEnum::VariancePop,
-            token::T_TOPK | token::T_BOTTOMK | token::T_COUNT_VALUES | token::T_QUANTILE => {
-                UnsupportedExprSnafu {
-                    name: format!("{op:?}"),
-                }
-                .fail()?
+            token::T_COUNT_VALUES | token::T_QUANTILE => UnsupportedExprSnafu {
+                name: format!("{op:?}"),
             }
+            .fail()?,
             _ => UnexpectedTokenSnafu { token: op }.fail()?,
         };
 
@@ -1372,6 +1967,15 @@ impl PromPlanner {
         }
     }
 
+    /// Try to build a [String] from [PromExpr].
+    fn try_build_string_literal(expr: &PromExpr) -> Option<String> {
+        match expr {
+            PromExpr::StringLiteral(StringLiteral { val }) => Some(val.clone()),
+            PromExpr::Paren(ParenExpr { expr }) => Self::try_build_string_literal(expr),
+            _ => None,
+        }
+    }
+
     /// Try to build a [f64] from [PromExpr].
     fn try_build_float_literal(expr: &PromExpr) -> Option<f64> {
         match expr {
@@ -1712,6 +2316,15 @@ enum ScalarFunc {
     GeneratedExpr,
 }
 
+/// Returns the offset in millisecond, a negative offset moves forward in time.
+fn offset_to_millis(offset: &Option<Offset>) -> Millisecond {
+    match offset {
+        Some(Offset::Pos(duration)) => duration.as_millis() as Millisecond,
+        Some(Offset::Neg(duration)) => -(duration.as_millis() as Millisecond),
+        None => 0,
+    }
+}
+
 #[cfg(test)]
 mod test {
     use std::time::{Duration, UNIX_EPOCH};
@@ -1836,10 +2449,67 @@ mod test {
         do_single_instant_function_call("abs", "abs").await;
     }
 
+    async fn do_absent_function_call(query: &str) -> LogicalPlan {
+        let prom_expr = parser::parse(query).unwrap();
+        let eval_stmt = EvalStmt {
+            expr: prom_expr,
+            start: UNIX_EPOCH,
+            end: UNIX_EPOCH
+                .checked_add(Duration::from_secs(100_000))
+                .unwrap(),
+            interval: Duration::from_secs(5),
+            lookback_delta: Duration::from_secs(1),
+        };
+
+        let table_provider = build_test_table_provider("some_metric".to_string(), 2, 1).await;
+        PromPlanner::stmt_to_plan(table_provider, eval_stmt)
+            .await
+            .unwrap()
+    }
+
     #[tokio::test]
-    #[should_panic]
     async fn single_absent() {
-        do_single_instant_function_call("absent", "").await;
+        let plan =
+            do_absent_function_call("absent(some_metric{tag_0=\"foo\", tag_1!=\"bar\"})").await;
+        let fields = plan
+            .schema()
+            .fields()
+            .iter()
+            .map(|field| field.name().clone())
+            .collect::<Vec<_>>();
+        assert_eq!(vec!["time", "value", "tag_0"], fields);
+        let plan = plan.display_indent_schema().to_string();
+        assert!(plan.contains("LeftAnti Join"), "{plan}");
+        assert!(plan.contains("EmptyMetric"), "{plan}");
+    }
+
+    #[tokio::test]
+    async fn absent_with_duplicated_labels() {
+        let plan =
+            do_absent_function_call("absent(some_metric{tag_0=\"foo\", tag_0=\"bar\"})").await;
+        let fields = plan
+            .schema()
+            .fields()
+            .iter()
+            .map(|field| field.name().clone())
+            .collect::<Vec<_>>();
+        assert_eq!(vec!["time", "value"], fields);
+    }
+
+    #[tokio::test]
+    async fn single_absent_over_time() {
+        let plan =
+            do_absent_function_call("absent_over_time(some_metric{tag_0=\"foo\"}[5m])").await;
+        let fields = plan
+            .schema()
+            .fields()
+            .iter()
+            .map(|field| field.name().clone())
+            .collect::<Vec<_>>();
+        assert_eq!(vec!["time", "value", "tag_0"], fields);
+        let plan = plan.display_indent_schema().to_string();
+        assert!(plan.contains("prom_present_over_time"), "{plan}");
+        assert!(plan.contains("PromRangeManipulate"), "{plan}");
     }
 
     #[tokio::test]
@@ -2458,4 +3128,144 @@ mod test {
             assert!(plan.is_err(), "case: {:?}", case);
         }
     }
+
+    async fn plan_query(query: &str) -> String {
+        try_plan_query(query)
+            .await
+            .unwrap()
+            .display_indent_schema()
+            .to_string()
+    }
+
+    async fn try_plan_query(query: &str) -> Result<LogicalPlan> {
+        let prom_expr = parser::parse(query).unwrap();
+        let eval_stmt = EvalStmt {
+            expr: prom_expr,
+            start: UNIX_EPOCH,
+            end: UNIX_EPOCH
+                .checked_add(Duration::from_secs(100_000))
+                .unwrap(),
+            interval: Duration::from_secs(5),
+            lookback_delta: Duration::from_secs(1),
+        };
+
+        let table_provider = build_test_table_provider("some_metric".to_string(), 1, 1).await;
+        PromPlanner::stmt_to_plan(table_provider, eval_stmt).await
+    }
+
+    #[tokio::test]
+    async fn at_modifier() {
+        // Evaluates the selector at 100s and repeats the result at every step.
+        let plan = plan_query("some_metric @ 100").await;
+        assert!(plan.contains("CrossJoin"), "{plan}");
+        assert!(
+            plan.contains("EmptyMetric: range=[0..100000000], interval=[5000]"),
+            "{plan}"
+        );
+        assert!(
+            plan.contains("PromInstantManipulate: range=[100000..100000]"),
+            "{plan}"
+        );
+
+        // `end()` refers to the end of the query.
+        let plan = plan_query("rate(some_metric[5m] @ end())").await;
+        assert!(plan.contains("CrossJoin"), "{plan}");
+        assert!(
+            plan.contains("PromRangeManipulate: req range=[100000000..100000000]"),
+            "{plan}"
+        );
+
+        let plan = plan_query("sum(some_metric @ start() offset 10s)").await;
+        assert!(
+            plan.contains("PromInstantManipulate: range=[0..0]"),
+            "{plan}"
+        );
+        assert!(
+            plan.contains("PromSeriesNormalize: offset=[10000]"),
+            "{plan}"
+        );
+    }
+
+    #[tokio::test]
+    async fn subquery() {
+        let plan = plan_query("max_over_time(rate(some_metric[5m])[30m:1m])").await;
+        // The inner query is evaluated every minute.
+        assert!(
+            plan.contains("PromRangeManipulate: req range=[-1740000..99960000], interval=[60000], eval range=[300000]"),
+            "{plan}"
+        );
+        assert!(
+            plan.contains("PromRangeManipulate: req range=[0..100000000], interval=[5000], eval range=[1800000]"),
+            "{plan}"
+        );
+
+        // The step defaults to the interval of the query.
+        let plan = plan_query("max_over_time(some_metric[1m:] offset 1m)").await;
+        assert!(
+            plan.contains("PromInstantManipulate: range=[-115000..99940000], lookback=[1000], interval=[5000]"),
+            "{plan}"
+        );
+        assert!(
+            plan.contains("PromRangeManipulate: req range=[0..100000000], interval=[5000], eval range=[60000]"),
+            "{plan}"
+        );
+    }
+
+    #[tokio::test]
+    async fn label_replace() {
+        let plan = plan_query(r#"label_replace(some_metric, "foo", "$1", "tag_0", "(.*)")"#).await;
+        assert!(plan.contains("regexp_replace"), "{plan}");
+        assert!(plan.contains("foo:Utf8"), "{plan}");
+
+        // replace an existing label
+        let plan =
+            plan_query(r#"label_replace(some_metric, "tag_0", "$1", "tag_0", "(.*)")"#).await;
+        assert!(plan.contains("regexp_replace"), "{plan}");
+        assert!(!plan.contains("foo"), "{plan}");
+
+        let bad_cases = [
+            // invalid regex
+            r#"label_replace(some_metric, "foo", "$1", "tag_0", "(.*")"#,
+            // invalid label name
+            r#"label_replace(some_metric, "0foo", "$1", "tag_0", "(.*)")"#,
+            r#"label_replace(some_metric, "__name__", "$1", "tag_0", "(.*)")"#,
+            // field column is not a label
+            r#"label_replace(some_metric, "field_0", "$1", "tag_0", "(.*)")"#,
+            // not enough arguments
+            r#"label_replace(some_metric, "foo", "$1", "tag_0")"#,
+        ];
+        for case in bad_cases {
+            assert!(try_plan_query(case).await.is_err(), "case: {case}");
+        }
+    }
+
+    #[tokio::test]
+    async fn label_join() {
+        let plan =
+            plan_query(r#"label_join(some_metric, "foo", ",", "tag_0", "nonexistent")"#).await;
+        assert!(plan.contains("concat_ws"), "{plan}");
+        assert!(plan.contains("foo:Utf8"), "{plan}");
+
+        assert!(try_plan_query(r#"label_join(some_metric, "foo")"#)
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn topk_bottomk() {
+        let plan = plan_query("topk(2, some_metric)").await;
+        assert!(plan.contains("WindowAggr"), "{plan}");
+        assert!(plan.contains("ROW_NUMBER()"), "{plan}");
+        assert!(plan.contains("__topk_rank <= UInt64(2)"), "{plan}");
+        // all labels are kept
+        assert!(plan.contains("tag_0:Utf8"), "{plan}");
+
+        let plan = plan_query("bottomk(1, some_metric) by (tag_0)").await;
+        assert!(plan.contains("PARTITION BY [some_metric.tag_0"), "{plan}");
+        assert!(plan.contains("__topk_rank <= UInt64(1)"), "{plan}");
+
+        assert!(try_plan_query("topk(scalar(some_metric), some_metric)")
+            .await
+            .is_err());
+    }
 }
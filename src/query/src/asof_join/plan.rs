@@ -0,0 +1,741 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::any::Any;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use arrow::array::{Array, ArrayRef, TimestampNanosecondArray, UInt32Array, UInt32Builder};
+use arrow::compute::{self, concat_batches};
+use arrow_schema::{DataType, Field, Schema, SchemaRef, TimeUnit};
+use common_query::DfPhysicalPlan;
+use common_recordbatch::DfSendableRecordBatchStream;
+use datafusion::common::Statistics;
+use datafusion::error::Result as DfResult;
+use datafusion::physical_plan::metrics::{BaselineMetrics, ExecutionPlanMetricsSet, MetricsSet};
+use datafusion::physical_plan::stream::RecordBatchStreamAdapter;
+use datafusion::physical_plan::{
+    DisplayAs, DisplayFormatType, ExecutionPlan, Partitioning, SendableRecordBatchStream,
+};
+use datafusion_common::{Column, DFSchemaRef, DataFusionError};
+use datafusion_expr::utils::split_conjunction;
+use datafusion_expr::{
+    BinaryExpr, Expr, JoinType, LogicalPlan, Operator, UserDefinedLogicalNodeCore,
+};
+use datafusion_physical_expr::{Distribution, PhysicalSortExpr};
+use datatypes::arrow::record_batch::RecordBatch;
+use datatypes::arrow::row::{RowConverter, SortField};
+use futures::{stream, StreamExt, TryStreamExt};
+
+/// ASOF join joins each row of the left input to the right row with the same keys and
+/// the nearest time satisfying the time condition. For example, the condition
+/// `left.ts >= right.ts` joins the latest right row at or before the left row.
+#[derive(Debug, PartialEq, Eq, Hash)]
+pub struct AsofJoin {
+    left: Arc<LogicalPlan>,
+    right: Arc<LogicalPlan>,
+    /// Inner or left join.
+    join_type: JoinType,
+    /// Equal keys of the left and right inputs.
+    on: Vec<(Column, Column)>,
+    /// Time column of the left input.
+    left_time: Column,
+    /// Time column of the right input.
+    right_time: Column,
+    /// Operator comparing the left time to the right time.
+    op: Operator,
+    schema: DFSchemaRef,
+}
+
+impl AsofJoin {
+    pub const fn name() -> &'static str {
+        "AsofJoin"
+    }
+
+    /// Creates an ASOF join from the `condition` of a join. The condition must be a
+    /// conjunction of equal keys and exactly one comparison between time columns.
+    pub fn try_new(
+        left: Arc<LogicalPlan>,
+        right: Arc<LogicalPlan>,
+        join_type: JoinType,
+        condition: &Expr,
+        schema: DFSchemaRef,
+    ) -> DfResult<Self> {
+        if !matches!(join_type, JoinType::Inner | JoinType::Left) {
+            return Err(DataFusionError::Plan(format!(
+                "ASOF JOIN doesn't support {join_type} join"
+            )));
+        }
+
+        let mut on = Vec::new();
+        let mut time = None;
+        for expr in split_conjunction(condition) {
+            let Expr::BinaryExpr(BinaryExpr {
+                left: lhs,
+                op,
+                right: rhs,
+            }) = expr
+            else {
+                return Err(invalid_condition(expr));
+            };
+            let (Expr::Column(lhs), Expr::Column(rhs)) = (lhs.as_ref(), rhs.as_ref()) else {
+                return Err(invalid_condition(expr));
+            };
+            // Normalizes the expr to `left_column op right_column`.
+            let (left_column, op, right_column) = if left.schema().index_of_column(lhs).is_ok()
+                && right.schema().index_of_column(rhs).is_ok()
+            {
+                (lhs.clone(), *op, rhs.clone())
+            } else if left.schema().index_of_column(rhs).is_ok()
+                && right.schema().index_of_column(lhs).is_ok()
+            {
+                let op = op.swap().ok_or_else(|| invalid_condition(expr))?;
+                (rhs.clone(), op, lhs.clone())
+            } else {
+                return Err(invalid_condition(expr));
+            };
+
+            match op {
+                Operator::Eq => on.push((left_column, right_column)),
+                Operator::Gt | Operator::GtEq | Operator::Lt | Operator::LtEq if time.is_none() => {
+                    time = Some((left_column, op, right_column));
+                }
+                _ => return Err(invalid_condition(expr)),
+            }
+        }
+        let (left_time, op, right_time) = time.ok_or_else(|| {
+            DataFusionError::Plan("ASOF JOIN requires a comparison between time columns".into())
+        })?;
+
+        Ok(Self {
+            left,
+            right,
+            join_type,
+            on,
+            left_time,
+            right_time,
+            op,
+            schema,
+        })
+    }
+
+    pub fn to_execution_plan(
+        &self,
+        left: Arc<dyn ExecutionPlan>,
+        right: Arc<dyn ExecutionPlan>,
+    ) -> DfResult<Arc<dyn ExecutionPlan>> {
+        let left_schema = self.left.schema();
+        let right_schema = self.right.schema();
+        let on = self
+            .on
+            .iter()
+            .map(|(left_key, right_key)| {
+                Ok((
+                    left_schema.index_of_column(left_key)?,
+                    right_schema.index_of_column(right_key)?,
+                ))
+            })
+            .collect::<DfResult<Vec<_>>>()?;
+        let left_time = left_schema.index_of_column(&self.left_time)?;
+        let right_time = right_schema.index_of_column(&self.right_time)?;
+
+        Ok(Arc::new(AsofJoinExec::new(
+            left,
+            right,
+            self.join_type,
+            on,
+            left_time,
+            right_time,
+            self.op,
+        )))
+    }
+}
+
+fn invalid_condition(expr: &Expr) -> DataFusionError {
+    DataFusionError::Plan(format!("Unsupported ASOF JOIN condition: {expr}"))
+}
+
+impl UserDefinedLogicalNodeCore for AsofJoin {
+    fn name(&self) -> &str {
+        Self::name()
+    }
+
+    fn inputs(&self) -> Vec<&LogicalPlan> {
+        vec![&self.left, &self.right]
+    }
+
+    fn schema(&self) -> &DFSchemaRef {
+        &self.schema
+    }
+
+    fn expressions(&self) -> Vec<Expr> {
+        self.on
+            .iter()
+            .flat_map(|(left_key, right_key)| [left_key, right_key])
+            .chain([&self.left_time, &self.right_time])
+            .map(|column| Expr::Column(column.clone()))
+            .collect()
+    }
+
+    fn fmt_for_explain(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "AsofJoin: type={}, on=[{}], time={} {} {}",
+            self.join_type,
+            self.on
+                .iter()
+                .map(|(left_key, right_key)| format!("({left_key}, {right_key})"))
+                .collect::<Vec<_>>()
+                .join(", "),
+            self.left_time,
+            self.op,
+            self.right_time,
+        )
+    }
+
+    fn from_template(&self, _exprs: &[Expr], inputs: &[LogicalPlan]) -> Self {
+        assert_eq!(inputs.len(), 2);
+
+        Self {
+            left: Arc::new(inputs[0].clone()),
+            right: Arc::new(inputs[1].clone()),
+            join_type: self.join_type,
+            on: self.on.clone(),
+            left_time: self.left_time.clone(),
+            right_time: self.right_time.clone(),
+            op: self.op,
+            schema: self.schema.clone(),
+        }
+    }
+}
+
+/// Executes ASOF join by loading the right input into memory, grouping its rows by keys
+/// and sorting them by time, then streams the left input and looks up the nearest right
+/// row of each left row.
+#[derive(Debug)]
+pub struct AsofJoinExec {
+    left: Arc<dyn ExecutionPlan>,
+    right: Arc<dyn ExecutionPlan>,
+    join_type: JoinType,
+    /// Indices of equal keys in the left and right inputs.
+    on: Vec<(usize, usize)>,
+    left_time: usize,
+    right_time: usize,
+    op: Operator,
+    schema: SchemaRef,
+    metric: ExecutionPlanMetricsSet,
+}
+
+impl AsofJoinExec {
+    fn new(
+        left: Arc<dyn ExecutionPlan>,
+        right: Arc<dyn ExecutionPlan>,
+        join_type: JoinType,
+        on: Vec<(usize, usize)>,
+        left_time: usize,
+        right_time: usize,
+        op: Operator,
+    ) -> Self {
+        // Fields of the right input are nullable in left join.
+        let fields = left
+            .schema()
+            .fields()
+            .iter()
+            .map(|field| Field::clone(field))
+            .chain(right.schema().fields().iter().map(|field| {
+                Field::clone(field)
+                    .with_nullable(field.is_nullable() || join_type == JoinType::Left)
+            }))
+            .collect::<Vec<_>>();
+
+        Self {
+            left,
+            right,
+            join_type,
+            on,
+            left_time,
+            right_time,
+            op,
+            schema: Arc::new(Schema::new(fields)),
+            metric: ExecutionPlanMetricsSet::new(),
+        }
+    }
+}
+
+impl DisplayAs for AsofJoinExec {
+    fn fmt_as(&self, t: DisplayFormatType, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match t {
+            DisplayFormatType::Default | DisplayFormatType::Verbose => {
+                let left_schema = self.left.schema();
+                let right_schema = self.right.schema();
+                write!(
+                    f,
+                    "AsofJoinExec: type={}, on=[{}], time={}@{} {} {}@{}",
+                    self.join_type,
+                    self.on
+                        .iter()
+                        .map(|(left_key, right_key)| format!(
+                            "({}@{}, {}@{})",
+                            left_schema.field(*left_key).name(),
+                            left_key,
+                            right_schema.field(*right_key).name(),
+                            right_key
+                        ))
+                        .collect::<Vec<_>>()
+                        .join(", "),
+                    left_schema.field(self.left_time).name(),
+                    self.left_time,
+                    self.op,
+                    right_schema.field(self.right_time).name(),
+                    self.right_time,
+                )
+            }
+        }
+    }
+}
+
+impl ExecutionPlan for AsofJoinExec {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+
+    fn output_partitioning(&self) -> Partitioning {
+        Partitioning::UnknownPartitioning(1)
+    }
+
+    fn required_input_distribution(&self) -> Vec<Distribution> {
+        vec![Distribution::SinglePartition, Distribution::SinglePartition]
+    }
+
+    fn output_ordering(&self) -> Option<&[PhysicalSortExpr]> {
+        None
+    }
+
+    fn children(&self) -> Vec<Arc<dyn DfPhysicalPlan>> {
+        vec![self.left.clone(), self.right.clone()]
+    }
+
+    fn with_new_children(
+        self: Arc<Self>,
+        children: Vec<Arc<dyn DfPhysicalPlan>>,
+    ) -> DfResult<Arc<dyn DfPhysicalPlan>> {
+        assert_eq!(children.len(), 2);
+        Ok(Arc::new(Self {
+            left: children[0].clone(),
+            right: children[1].clone(),
+            join_type: self.join_type,
+            on: self.on.clone(),
+            left_time: self.left_time,
+            right_time: self.right_time,
+            op: self.op,
+            schema: self.schema.clone(),
+            metric: self.metric.clone(),
+        }))
+    }
+
+    fn execute(
+        &self,
+        partition: usize,
+        context: Arc<common_query::physical_plan::TaskContext>,
+    ) -> DfResult<DfSendableRecordBatchStream> {
+        let metric = BaselineMetrics::new(&self.metric, partition);
+        let right = self.right.execute(partition, context.clone())?;
+        let left = self.left.execute(partition, context)?;
+
+        // Keys of both inputs are cast to the types of the left keys so they are encoded
+        // in the same way.
+        let left_schema = self.left.schema();
+        let key_types: Vec<_> = self
+            .on
+            .iter()
+            .map(|(left_key, _)| left_schema.field(*left_key).data_type().clone())
+            .collect();
+        let left_keys = KeyEncoder::try_new(
+            self.on.iter().map(|(left_key, _)| *left_key).collect(),
+            key_types.clone(),
+        )?;
+        let right_keys = KeyEncoder::try_new(
+            self.on.iter().map(|(_, right_key)| *right_key).collect(),
+            key_types,
+        )?;
+        let right_time = self.right_time;
+        let mut prober = Prober {
+            schema: self.schema.clone(),
+            join_type: self.join_type,
+            op: self.op,
+            left_keys,
+            left_time: self.left_time,
+            right_batch: RecordBatch::new_empty(self.right.schema()),
+            groups: HashMap::new(),
+            metric,
+        };
+
+        let stream = stream::once(async move {
+            prober.load_right(right, right_keys, right_time).await?;
+            Ok::<_, DataFusionError>(left.map(move |batch| prober.probe(&batch?)))
+        })
+        .try_flatten();
+
+        Ok(Box::pin(RecordBatchStreamAdapter::new(
+            self.schema.clone(),
+            stream,
+        )))
+    }
+
+    fn metrics(&self) -> Option<MetricsSet> {
+        Some(self.metric.clone_inner())
+    }
+
+    fn statistics(&self) -> Statistics {
+        Statistics::default()
+    }
+}
+
+/// Encodes join keys of rows into comparable bytes.
+struct KeyEncoder {
+    /// Indices of key columns.
+    keys: Vec<usize>,
+    /// Types to cast the key columns to.
+    key_types: Vec<DataType>,
+    converter: RowConverter,
+}
+
+impl KeyEncoder {
+    fn try_new(keys: Vec<usize>, key_types: Vec<DataType>) -> DfResult<Self> {
+        let converter = RowConverter::new(
+            key_types
+                .iter()
+                .map(|data_type| SortField::new(data_type.clone()))
+                .collect(),
+        )?;
+        Ok(Self {
+            keys,
+            key_types,
+            converter,
+        })
+    }
+
+    /// Returns keys of rows in the `batch`. The key is `None` if it contains null as null
+    /// never equals to others.
+    fn encode(&mut self, batch: &RecordBatch) -> DfResult<Vec<Option<Vec<u8>>>> {
+        if self.keys.is_empty() {
+            return Ok(vec![Some(Vec::new()); batch.num_rows()]);
+        }
+
+        let arrays = self
+            .keys
+            .iter()
+            .zip(&self.key_types)
+            .map(|(key, data_type)| compute::cast(batch.column(*key), data_type))
+            .collect::<Result<Vec<_>, _>>()?;
+        let rows = self.converter.convert_columns(&arrays)?;
+        Ok((0..batch.num_rows())
+            .map(|i| {
+                (!arrays.iter().any(|array| array.is_null(i)))
+                    .then(|| rows.row(i).as_ref().to_vec())
+            })
+            .collect())
+    }
+}
+
+/// Casts the time column to nanosecond timestamps so times of both inputs are comparable.
+fn time_array(array: &ArrayRef) -> DfResult<TimestampNanosecondArray> {
+    let array = compute::cast(array, &DataType::Timestamp(TimeUnit::Nanosecond, None))?;
+    array
+        .as_any()
+        .downcast_ref::<TimestampNanosecondArray>()
+        .cloned()
+        .ok_or_else(|| DataFusionError::Internal("Failed to cast time column".into()))
+}
+
+/// Finds the row in the `group` sorted by time that satisfies `time op row_time` with
+/// the nearest time.
+fn find_row(group: &[(i64, u32)], time: i64, op: Operator) -> Option<u32> {
+    let index = match op {
+        // The latest row at or before the time.
+        Operator::GtEq => group.partition_point(|(t, _)| *t <= time).checked_sub(1)?,
+        Operator::Gt => group.partition_point(|(t, _)| *t < time).checked_sub(1)?,
+        // The earliest row at or after the time.
+        Operator::LtEq => group.partition_point(|(t, _)| *t < time),
+        Operator::Lt => group.partition_point(|(t, _)| *t <= time),
+        _ => return None,
+    };
+    group.get(index).map(|(_, row)| *row)
+}
+
+/// Joins batches of the left input to rows of the right input.
+struct Prober {
+    schema: SchemaRef,
+    join_type: JoinType,
+    op: Operator,
+    left_keys: KeyEncoder,
+    left_time: usize,
+    /// All rows of the right input.
+    right_batch: RecordBatch,
+    /// Times and indices of rows in the `right_batch` grouped by keys, sorted by time.
+    groups: HashMap<Vec<u8>, Vec<(i64, u32)>>,
+    metric: BaselineMetrics,
+}
+
+impl Prober {
+    async fn load_right(
+        &mut self,
+        mut right: SendableRecordBatchStream,
+        mut right_keys: KeyEncoder,
+        right_time: usize,
+    ) -> DfResult<()> {
+        let mut batches = Vec::new();
+        while let Some(batch) = right.next().await {
+            batches.push(batch?);
+        }
+
+        let _timer = self.metric.elapsed_compute().timer();
+        let batch = concat_batches(&right.schema(), &batches)?;
+        let keys = right_keys.encode(&batch)?;
+        let times = time_array(batch.column(right_time))?;
+        for (row, key) in keys.into_iter().enumerate() {
+            if let Some(key) = key
+                && times.is_valid(row)
+            {
+                self.groups
+                    .entry(key)
+                    .or_default()
+                    .push((times.value(row), row as u32));
+            }
+        }
+        // Rows from time-ordered scans are already sorted so sorting them is cheap.
+        for group in self.groups.values_mut() {
+            group.sort_by_key(|(time, _)| *time);
+        }
+        self.right_batch = batch;
+
+        Ok(())
+    }
+
+    fn probe(&mut self, batch: &RecordBatch) -> DfResult<RecordBatch> {
+        let _timer = self.metric.elapsed_compute().timer();
+        let keys = self.left_keys.encode(batch)?;
+        let times = time_array(batch.column(self.left_time))?;
+
+        let mut left_indices = UInt32Builder::with_capacity(batch.num_rows());
+        let mut right_indices = UInt32Builder::with_capacity(batch.num_rows());
+        for (row, key) in keys.iter().enumerate() {
+            let matched = key
+                .as_ref()
+                .filter(|_| times.is_valid(row))
+                .and_then(|key| self.groups.get(key))
+                .and_then(|group| find_row(group, times.value(row), self.op));
+            match matched {
+                Some(right_row) => {
+                    left_indices.append_value(row as u32);
+                    right_indices.append_value(right_row);
+                }
+                None if self.join_type == JoinType::Left => {
+                    left_indices.append_value(row as u32);
+                    right_indices.append_null();
+                }
+                None => {}
+            }
+        }
+        let left_indices: UInt32Array = left_indices.finish();
+        let right_indices: UInt32Array = right_indices.finish();
+
+        let columns = batch
+            .columns()
+            .iter()
+            .map(|column| compute::take(column, &left_indices, None))
+            .chain(
+                self.right_batch
+                    .columns()
+                    .iter()
+                    .map(|column| compute::take(column, &right_indices, None)),
+            )
+            .collect::<Result<Vec<_>, _>>()?;
+        let output = RecordBatch::try_new(self.schema.clone(), columns)?;
+        self.metric.record_output(output.num_rows());
+        Ok(output)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use arrow::array::{Float64Array, StringArray, TimestampMillisecondArray};
+    use datafusion::physical_plan::memory::MemoryExec;
+    use datafusion::prelude::SessionContext;
+    use datafusion_expr::{col, LogicalPlanBuilder};
+
+    use super::*;
+
+    fn new_memory_exec(
+        hosts: Vec<&str>,
+        times: Vec<i64>,
+        values: Option<Vec<f64>>,
+    ) -> Arc<dyn ExecutionPlan> {
+        let mut fields = vec![
+            Field::new("host", DataType::Utf8, true),
+            Field::new("ts", DataType::Timestamp(TimeUnit::Millisecond, None), true),
+        ];
+        let mut columns: Vec<ArrayRef> = vec![
+            Arc::new(StringArray::from(hosts)),
+            Arc::new(TimestampMillisecondArray::from(times)),
+        ];
+        if let Some(values) = values {
+            fields.push(Field::new("value", DataType::Float64, true));
+            columns.push(Arc::new(Float64Array::from(values)));
+        }
+        let schema = Arc::new(Schema::new(fields));
+        let batch = RecordBatch::try_new(schema.clone(), columns).unwrap();
+        Arc::new(MemoryExec::try_new(&[vec![batch]], schema, None).unwrap())
+    }
+
+    async fn do_asof_join_test(join_type: JoinType, op: Operator, expected: &str) {
+        // Events.
+        let left = new_memory_exec(vec!["a", "a", "b", "c"], vec![5, 15, 5, 5], None);
+        // Metrics.
+        let right = new_memory_exec(
+            vec!["a", "b", "a", "a"],
+            vec![10, 10, 0, 20],
+            Some(vec![2.0, 4.0, 1.0, 3.0]),
+        );
+        let exec = Arc::new(AsofJoinExec::new(
+            left,
+            right,
+            join_type,
+            vec![(0, 0)],
+            1,
+            1,
+            op,
+        ));
+
+        let session_context = SessionContext::default();
+        let result = datafusion::physical_plan::collect(exec, session_context.task_ctx())
+            .await
+            .unwrap();
+        let result_literal = arrow::util::pretty::pretty_format_batches(&result)
+            .unwrap()
+            .to_string();
+        assert_eq!(expected, result_literal);
+    }
+
+    #[tokio::test]
+    async fn test_asof_join_backward() {
+        let expected = "\
++------+-------------------------+------+-------------------------+-------+
+| host | ts                      | host | ts                      | value |
++------+-------------------------+------+-------------------------+-------+
+| a    | 1970-01-01T00:00:00.005 | a    | 1970-01-01T00:00:00     | 1.0   |
+| a    | 1970-01-01T00:00:00.015 | a    | 1970-01-01T00:00:00.010 | 2.0   |
++------+-------------------------+------+-------------------------+-------+";
+        do_asof_join_test(JoinType::Inner, Operator::GtEq, expected).await;
+    }
+
+    #[tokio::test]
+    async fn test_asof_join_left() {
+        let expected = "\
++------+-------------------------+------+-------------------------+-------+
+| host | ts                      | host | ts                      | value |
++------+-------------------------+------+-------------------------+-------+
+| a    | 1970-01-01T00:00:00.005 | a    | 1970-01-01T00:00:00     | 1.0   |
+| a    | 1970-01-01T00:00:00.015 | a    | 1970-01-01T00:00:00.010 | 2.0   |
+| b    | 1970-01-01T00:00:00.005 |      |                         |       |
+| c    | 1970-01-01T00:00:00.005 |      |                         |       |
++------+-------------------------+------+-------------------------+-------+";
+        do_asof_join_test(JoinType::Left, Operator::GtEq, expected).await;
+    }
+
+    #[tokio::test]
+    async fn test_asof_join_forward() {
+        let expected = "\
++------+-------------------------+------+-------------------------+-------+
+| host | ts                      | host | ts                      | value |
++------+-------------------------+------+-------------------------+-------+
+| a    | 1970-01-01T00:00:00.005 | a    | 1970-01-01T00:00:00.010 | 2.0   |
+| a    | 1970-01-01T00:00:00.015 | a    | 1970-01-01T00:00:00.020 | 3.0   |
+| b    | 1970-01-01T00:00:00.005 | b    | 1970-01-01T00:00:00.010 | 4.0   |
++------+-------------------------+------+-------------------------+-------+";
+        do_asof_join_test(JoinType::Inner, Operator::LtEq, expected).await;
+    }
+
+    #[test]
+    fn test_find_row() {
+        let group = [(0, 0), (10, 1), (10, 2), (20, 3)];
+        assert_eq!(None, find_row(&group, -1, Operator::GtEq));
+        assert_eq!(Some(0), find_row(&group, 5, Operator::GtEq));
+        assert_eq!(Some(2), find_row(&group, 10, Operator::GtEq));
+        assert_eq!(Some(0), find_row(&group, 10, Operator::Gt));
+        assert_eq!(Some(1), find_row(&group, 10, Operator::LtEq));
+        assert_eq!(Some(3), find_row(&group, 10, Operator::Lt));
+        assert_eq!(None, find_row(&group, 21, Operator::LtEq));
+    }
+
+    #[test]
+    fn test_asof_join_condition() {
+        let schema = Schema::new(vec![
+            Field::new("host", DataType::Utf8, true),
+            Field::new("ts", DataType::Timestamp(TimeUnit::Millisecond, None), true),
+        ]);
+        let left = LogicalPlanBuilder::scan_empty(Some("a"), &schema, None)
+            .unwrap()
+            .build()
+            .unwrap();
+        let right = LogicalPlanBuilder::scan_empty(Some("b"), &schema, None)
+            .unwrap()
+            .build()
+            .unwrap();
+        let join_schema = Arc::new(left.schema().join(right.schema()).unwrap());
+        let try_new = |condition: Expr| {
+            AsofJoin::try_new(
+                Arc::new(left.clone()),
+                Arc::new(right.clone()),
+                JoinType::Inner,
+                &condition,
+                join_schema.clone(),
+            )
+        };
+
+        let join = try_new(
+            col("b.ts")
+                .lt_eq(col("a.ts"))
+                .and(col("a.host").eq(col("b.host"))),
+        )
+        .unwrap();
+        assert_eq!(
+            vec![(Column::from("a.host"), Column::from("b.host"))],
+            join.on
+        );
+        assert_eq!(Column::from("a.ts"), join.left_time);
+        assert_eq!(Operator::GtEq, join.op);
+        assert_eq!(Column::from("b.ts"), join.right_time);
+
+        // No time condition.
+        assert!(try_new(col("a.host").eq(col("b.host"))).is_err());
+        // Multiple time conditions.
+        assert!(try_new(col("a.ts").gt(col("b.ts")).and(col("a.ts").lt(col("b.ts")))).is_err());
+        // Not a column.
+        assert!(try_new(
+            col("a.ts")
+                .gt_eq(col("b.ts"))
+                .and(
+                    col("a.host").eq(Expr::Literal(datafusion_common::ScalarValue::Utf8(Some(
+                        "x".to_string()
+                    ))))
+                )
+        )
+        .is_err());
+    }
+}
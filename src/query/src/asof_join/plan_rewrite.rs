@@ -0,0 +1,187 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+
+use arrow_schema::DataType;
+use datafusion_common::{DataFusionError, Result as DFResult};
+use datafusion_expr::expr::ScalarUDF as ScalarUDFExpr;
+use datafusion_expr::{
+    create_udf, Analyze, Explain, Expr, Extension, Join, LogicalPlan, LogicalPlanBuilder,
+    ScalarUDF, Volatility,
+};
+use snafu::ResultExt;
+use sql::statements::ASOF_JOIN_FUNCTION;
+
+use crate::asof_join::plan::AsofJoin;
+use crate::error::{DataFusionSnafu, Result};
+
+/// Returns the function wrapping the condition of ASOF joins.
+///
+/// The parser wraps the condition of `ASOF JOIN` with this function and [AsofJoinRewriter]
+/// rewrites such joins to [AsofJoin], so the function itself is never evaluated.
+pub fn asof_join_udf() -> ScalarUDF {
+    create_udf(
+        ASOF_JOIN_FUNCTION,
+        vec![DataType::Boolean],
+        Arc::new(DataType::Boolean),
+        Volatility::Immutable,
+        Arc::new(|_| {
+            Err(DataFusionError::Plan(format!(
+                "{ASOF_JOIN_FUNCTION} can only be used as the condition of ASOF JOIN"
+            )))
+        }),
+    )
+}
+
+/// Rewrites joins whose condition is wrapped by [ASOF_JOIN_FUNCTION] to [AsofJoin].
+pub struct AsofJoinRewriter;
+
+impl AsofJoinRewriter {
+    pub fn rewrite(plan: LogicalPlan) -> Result<LogicalPlan> {
+        match Self::rewrite_plan(&plan).context(DataFusionSnafu)? {
+            Some(new_plan) => Ok(new_plan),
+            None => Ok(plan),
+        }
+    }
+
+    /// Returns the rewritten plan or `None` if the plan doesn't contain ASOF joins.
+    fn rewrite_plan(plan: &LogicalPlan) -> DFResult<Option<LogicalPlan>> {
+        let inputs = plan.inputs();
+        let new_inputs = inputs
+            .iter()
+            .map(|input| Self::rewrite_plan(input))
+            .collect::<DFResult<Vec<_>>>()?;
+        let no_new_inputs = new_inputs.iter().all(Option::is_none);
+        let inputs: Vec<LogicalPlan> = new_inputs
+            .into_iter()
+            .zip(inputs)
+            .map(|(new_input, input)| new_input.unwrap_or_else(|| input.clone()))
+            .collect();
+
+        if let LogicalPlan::Join(join) = plan
+            && let Some(condition) = asof_join_condition(join)
+        {
+            if !join.on.is_empty() {
+                return Err(DataFusionError::Plan(
+                    "ASOF JOIN doesn't support equal keys outside the ON condition".into(),
+                ));
+            }
+            let asof_join = AsofJoin::try_new(
+                Arc::new(inputs[0].clone()),
+                Arc::new(inputs[1].clone()),
+                join.join_type,
+                condition,
+                join.schema.clone(),
+            )?;
+            return Ok(Some(LogicalPlan::Extension(Extension {
+                node: Arc::new(asof_join),
+            })));
+        }
+
+        if no_new_inputs {
+            return Ok(None);
+        }
+        // Like `RangePlanRewriter`, rebuilds `Analyze` and `Explain` by the builder as
+        // `with_new_inputs` doesn't support them.
+        let plan = match plan {
+            LogicalPlan::Analyze(Analyze { verbose, .. }) => {
+                LogicalPlanBuilder::from(inputs[0].clone())
+                    .explain(*verbose, true)?
+                    .build()?
+            }
+            LogicalPlan::Explain(Explain { verbose, .. }) => {
+                LogicalPlanBuilder::from(inputs[0].clone())
+                    .explain(*verbose, false)?
+                    .build()?
+            }
+            _ => plan.with_new_inputs(&inputs)?,
+        };
+        Ok(Some(plan))
+    }
+}
+
+/// Returns the condition of the join if it is an ASOF join.
+fn asof_join_condition(join: &Join) -> Option<&Expr> {
+    match &join.filter {
+        Some(Expr::ScalarUDF(ScalarUDFExpr { fun, args }))
+            if fun.name == ASOF_JOIN_FUNCTION && args.len() == 1 =>
+        {
+            Some(&args[0])
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use arrow_schema::{Field, Schema, TimeUnit};
+    use datafusion_expr::{col, JoinType};
+
+    use super::*;
+
+    fn new_scan(name: &str) -> LogicalPlanBuilder {
+        let schema = Schema::new(vec![
+            Field::new("host", DataType::Utf8, true),
+            Field::new("ts", DataType::Timestamp(TimeUnit::Millisecond, None), true),
+        ]);
+        LogicalPlanBuilder::scan_empty(Some(name), &schema, None).unwrap()
+    }
+
+    #[test]
+    fn test_rewrite_asof_join() {
+        let condition = col("a.host")
+            .eq(col("b.host"))
+            .and(col("a.ts").gt_eq(col("b.ts")));
+        let plan = new_scan("a")
+            .join_on(
+                new_scan("b").build().unwrap(),
+                JoinType::Inner,
+                [Expr::ScalarUDF(ScalarUDFExpr::new(
+                    Arc::new(asof_join_udf()),
+                    vec![condition],
+                ))],
+            )
+            .unwrap()
+            .project([col("a.ts"), col("b.host")])
+            .unwrap()
+            .build()
+            .unwrap();
+
+        let plan = AsofJoinRewriter::rewrite(plan).unwrap();
+        let expected = "\
+Projection: a.ts, b.host\
+\n  AsofJoin: type=Inner, on=[(a.host, b.host)], time=a.ts >= b.ts\
+\n    TableScan: a\
+\n    TableScan: b";
+        assert_eq!(expected, plan.display_indent().to_string());
+    }
+
+    #[test]
+    fn test_rewrite_without_asof_join() {
+        let plan = new_scan("a")
+            .join_on(
+                new_scan("b").build().unwrap(),
+                JoinType::Inner,
+                [col("a.ts").eq(col("b.ts"))],
+            )
+            .unwrap()
+            .build()
+            .unwrap();
+        let expected = plan.display_indent().to_string();
+
+        let plan = AsofJoinRewriter::rewrite(plan).unwrap();
+        assert_eq!(expected, plan.display_indent().to_string());
+    }
+}
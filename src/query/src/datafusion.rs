@@ -30,9 +30,10 @@ use common_function::scalars::udf::create_udf;
 use common_query::physical_plan::{DfPhysicalPlanAdapter, PhysicalPlan, PhysicalPlanAdapter};
 use common_query::prelude::ScalarUdf;
 use common_query::Output;
-use common_recordbatch::adapter::RecordBatchStreamAdapter;
+use common_recordbatch::adapter::{PlanMetricsStream, RecordBatchStreamAdapter};
 use common_recordbatch::{
-    EmptyRecordBatchStream, RecordBatch, RecordBatches, SendableRecordBatchStream,
+    CancellableRecordBatchStream, EmptyRecordBatchStream, RecordBatch, RecordBatches,
+    SendableRecordBatchStream,
 };
 use common_telemetry::tracing;
 use datafusion::common::Column;
@@ -60,6 +61,7 @@ use crate::error::{
 };
 use crate::executor::QueryExecutor;
 use crate::logical_optimizer::LogicalOptimizer;
+use crate::optimizer::deferred_now::{defer_now, resolve_now};
 use crate::physical_optimizer::PhysicalOptimizer;
 use crate::physical_planner::PhysicalPlanner;
 use crate::physical_wrapper::PhysicalPlanWrapperRef;
@@ -86,6 +88,15 @@ impl DatafusionQueryEngine {
     ) -> Result<Output> {
         let mut ctx = QueryEngineContext::new(self.state.session_state(), query_ctx.clone());
 
+        let plan = match plan {
+            LogicalPlan::DfPlan(df_plan) => LogicalPlan::DfPlan(
+                resolve_now(
+                    df_plan,
+                    ctx.state().execution_props().query_execution_start_time,
+                )
+                .context(DataFusionSnafu)?,
+            ),
+        };
         // `create_physical_plan` will optimize logical plan internally
         let physical_plan = self.create_physical_plan(&mut ctx, &plan).await?;
         let optimized_physical_plan = self.optimize_physical_plan(&mut ctx, physical_plan)?;
@@ -243,6 +254,13 @@ impl QueryEngine for DatafusionQueryEngine {
     }
 
     async fn describe(&self, plan: LogicalPlan) -> Result<DescribeResult> {
+        // The plan may be executed later, e.g. by prepared statements, so `now()`
+        // is evaluated by the execution instead of the optimizer.
+        let plan = match plan {
+            LogicalPlan::DfPlan(df_plan) => {
+                LogicalPlan::DfPlan(defer_now(df_plan).context(DataFusionSnafu)?)
+            }
+        };
         let optimised_plan = self.optimize(&plan)?;
         Ok(DescribeResult {
             schema: optimised_plan.schema()?,
@@ -278,6 +296,10 @@ impl QueryEngine for DatafusionQueryEngine {
         self.state.register_udf(create_udf(func));
     }
 
+    fn register_catalog_function(&self, catalog: &str, func: FunctionRef) -> bool {
+        self.state.register_catalog_function(catalog, func)
+    }
+
     fn read_table(&self, table: TableRef) -> Result<DataFrame> {
         Ok(DataFrame::DataFusion(
             self.state
@@ -402,13 +424,13 @@ impl QueryExecutor for DatafusionQueryEngine {
         let _timer = metrics::METRIC_EXEC_PLAN_ELAPSED.start_timer();
         let task_ctx = ctx.build_task_ctx();
 
-        match plan.output_partitioning().partition_count() {
-            0 => Ok(Box::pin(EmptyRecordBatchStream::new(plan.schema()))),
-            1 => Ok(plan
+        let stream: SendableRecordBatchStream = match plan.output_partitioning().partition_count() {
+            0 => Box::pin(EmptyRecordBatchStream::new(plan.schema())),
+            1 => plan
                 .execute(0, task_ctx)
                 .context(error::ExecutePhysicalPlanSnafu)
                 .map_err(BoxedError::new)
-                .context(QueryExecutionSnafu))?,
+                .context(QueryExecutionSnafu)?,
             _ => {
                 // merge into a single partition
                 let plan =
@@ -424,9 +446,22 @@ impl QueryExecutor for DatafusionQueryEngine {
                     .context(error::ConvertDfRecordBatchStreamSnafu)
                     .map_err(BoxedError::new)
                     .context(QueryExecutionSnafu)?;
-                Ok(Box::pin(stream))
+                Box::pin(stream)
             }
-        }
+        };
+
+        // Reports metrics of scans so the caller can collect them after the execution,
+        // e.g. the frontend collects metrics of regions.
+        let stream = Box::pin(PlanMetricsStream::new(
+            stream,
+            Arc::new(DfPhysicalPlanAdapter(plan.clone())),
+        ));
+
+        let query_ctx = ctx.query_ctx();
+        Ok(Box::pin(
+            CancellableRecordBatchStream::new(stream, query_ctx.cancellation_token().clone())
+                .with_deadline(query_ctx.deadline()),
+        ))
     }
 }
 
@@ -509,6 +544,7 @@ mod tests {
 
     use catalog::RegisterTableRequest;
     use common_catalog::consts::{DEFAULT_CATALOG_NAME, DEFAULT_SCHEMA_NAME, NUMBERS_TABLE_ID};
+    use common_function::scalars::math::PowFunction;
     use common_query::Output;
     use common_recordbatch::{util, RecordBatch};
     use datafusion::prelude::{col, lit};
@@ -731,4 +767,15 @@ mod tests {
 +---------+";
         assert_eq!(record_batches.pretty_print().unwrap(), expected);
     }
+
+    #[tokio::test]
+    async fn test_register_catalog_function() {
+        let engine = create_test_engine().await;
+
+        assert!(engine.register_catalog_function(DEFAULT_CATALOG_NAME, Arc::new(PowFunction)));
+        // Functions in a catalog can't be replaced.
+        assert!(!engine.register_catalog_function(DEFAULT_CATALOG_NAME, Arc::new(PowFunction)));
+        // Functions of different catalogs can have the same name.
+        assert!(engine.register_catalog_function("other_catalog", Arc::new(PowFunction)));
+    }
 }
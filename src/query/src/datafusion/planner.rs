@@ -41,6 +41,8 @@ pub struct DfContextProviderAdapter {
     session_state: SessionState,
     tables: HashMap<String, Arc<dyn TableSource>>,
     table_provider: DfTableSourceProvider,
+    /// Catalog of the query to look up functions registered to the catalog.
+    catalog: String,
 }
 
 impl DfContextProviderAdapter {
@@ -67,6 +69,7 @@ impl DfContextProviderAdapter {
             session_state,
             tables,
             table_provider,
+            catalog: query_ctx.current_catalog().to_string(),
         })
     }
 }
@@ -104,7 +107,9 @@ impl ContextProvider for DfContextProviderAdapter {
     }
 
     fn get_function_meta(&self, name: &str) -> Option<Arc<ScalarUDF>> {
-        self.session_state.scalar_functions().get(name).cloned()
+        self.engine_state
+            .catalog_function(&self.catalog, name)
+            .or_else(|| self.session_state.scalar_functions().get(name).cloned())
     }
 
     fn get_aggregate_meta(&self, name: &str) -> Option<Arc<AggregateUDF>> {
@@ -148,6 +148,16 @@ impl PlanRewriter {
         if DFLogicalSubstraitConvertor.encode(plan).is_err() {
             return true;
         }
+        // A staged sort must be right above the merge scan to merge sorted regions, only a
+        // limit, which keeps the order, can be pushed down over it.
+        if self
+            .stage
+            .iter()
+            .any(|stage| matches!(stage, LogicalPlan::Sort(_)))
+            && !matches!(plan, LogicalPlan::Limit(_))
+        {
+            return true;
+        }
 
         match Categorizer::check_plan(plan, self.partition_cols.clone()) {
             Commutativity::Commutative => {}
@@ -67,9 +67,8 @@ impl Categorizer {
                     return Commutativity::Commutative;
                 }
 
-                // sort plan needs to consider column priority
-                // We can implement a merge-sort on partial ordered data
-                Commutativity::Unimplemented
+                // Each region sorts its rows, then sorted rows of regions are merged.
+                Commutativity::PartialCommutative
             }
             LogicalPlan::Join(_) => Commutativity::NonCommutative,
             LogicalPlan::CrossJoin(_) => Commutativity::NonCommutative,
@@ -214,4 +213,17 @@ mod test {
             Commutativity::Commutative
         ));
     }
+
+    #[test]
+    fn sort_on_partitions() {
+        let plan = LogicalPlan::Sort(Sort {
+            expr: vec![],
+            input: Arc::new(LogicalPlanBuilder::empty(false).build().unwrap()),
+            fetch: Some(10),
+        });
+        assert!(matches!(
+            Categorizer::check_plan(&plan, Some(vec!["host".to_string()])),
+            Commutativity::PartialCommutative
+        ));
+    }
 }
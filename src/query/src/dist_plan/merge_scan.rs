@@ -25,7 +25,8 @@ use common_query::physical_plan::TaskContext;
 use common_recordbatch::adapter::DfRecordBatchStreamAdapter;
 use common_recordbatch::error::ExternalSnafu;
 use common_recordbatch::{
-    DfSendableRecordBatchStream, RecordBatch, RecordBatchStreamWrapper, SendableRecordBatchStream,
+    DfSendableRecordBatchStream, RecordBatch, RecordBatchStream, RecordBatchStreamWrapper,
+    SendableRecordBatchStream,
 };
 use common_telemetry::tracing;
 use common_telemetry::tracing_context::TracingContext;
@@ -41,6 +42,7 @@ use futures_util::StreamExt;
 use greptime_proto::v1::region::{QueryRequest, RegionRequestHeader};
 use snafu::ResultExt;
 use store_api::storage::RegionId;
+use table::table::metrics::record_stream_metrics;
 use tokio::time::Instant;
 
 use crate::error::ConvertSchemaSnafu;
@@ -122,6 +124,9 @@ pub struct MergeScanExec {
     arrow_schema: ArrowSchemaRef,
     region_query_handler: RegionQueryHandlerRef,
     metric: ExecutionPlanMetricsSet,
+    /// Order of rows from each region. If it's set, each region is an output partition
+    /// so they can be merged by the order, otherwise regions are read one by one.
+    output_ordering: Option<Vec<PhysicalSortExpr>>,
 }
 
 impl std::fmt::Debug for MergeScanExec {
@@ -130,6 +135,7 @@ impl std::fmt::Debug for MergeScanExec {
             .field("table", &self.table)
             .field("regions", &self.regions)
             .field("schema", &self.schema)
+            .field("output_ordering", &self.output_ordering)
             .finish()
     }
 }
@@ -153,15 +159,34 @@ impl MergeScanExec {
             arrow_schema: arrow_schema_without_metadata,
             region_query_handler,
             metric: ExecutionPlanMetricsSet::new(),
+            output_ordering: None,
         })
     }
 
+    /// Sets the order of rows returned by each region.
+    pub fn with_output_ordering(mut self, output_ordering: Vec<PhysicalSortExpr>) -> Self {
+        // A merge scan has at least one partition.
+        if !self.regions.is_empty() && !output_ordering.is_empty() {
+            self.output_ordering = Some(output_ordering);
+        }
+        self
+    }
+
     #[tracing::instrument(skip_all)]
-    pub fn to_stream(&self, context: Arc<TaskContext>) -> Result<SendableRecordBatchStream> {
+    pub fn to_stream(
+        &self,
+        context: Arc<TaskContext>,
+        partition: usize,
+    ) -> Result<SendableRecordBatchStream> {
         let substrait_plan = self.substrait_plan.to_vec();
-        let regions = self.regions.clone();
+        let regions = if self.output_ordering.is_some() {
+            vec![self.regions[partition]]
+        } else {
+            self.regions.clone()
+        };
         let region_query_handler = self.region_query_handler.clone();
         let metric = MergeScanMetric::new(&self.metric);
+        let metrics_set = self.metric.clone();
         let schema = Self::arrow_schema_to_schema(self.schema())?;
 
         let dbname = context.task_id().unwrap_or_default();
@@ -214,6 +239,10 @@ impl MergeScanExec {
                     poll_timer = Instant::now();
                 }
                 METRIC_MERGE_SCAN_POLL_ELAPSED.observe(poll_duration.as_secs_f64());
+                // Records metrics of the region so `EXPLAIN ANALYZE` can show them.
+                if let Some(region_metrics) = stream.metrics() {
+                    record_stream_metrics(&metrics_set, partition, &region_metrics);
+                }
             }
         }));
 
@@ -254,11 +283,15 @@ impl ExecutionPlan for MergeScanExec {
     }
 
     fn output_partitioning(&self) -> Partitioning {
-        Partitioning::UnknownPartitioning(1)
+        if self.output_ordering.is_some() {
+            Partitioning::UnknownPartitioning(self.regions.len())
+        } else {
+            Partitioning::UnknownPartitioning(1)
+        }
     }
 
     fn output_ordering(&self) -> Option<&[PhysicalSortExpr]> {
-        None
+        self.output_ordering.as_deref()
     }
 
     fn children(&self) -> Vec<Arc<dyn ExecutionPlan>> {
@@ -276,11 +309,11 @@ impl ExecutionPlan for MergeScanExec {
 
     fn execute(
         &self,
-        _partition: usize,
+        partition: usize,
         context: Arc<TaskContext>,
     ) -> Result<DfSendableRecordBatchStream> {
         Ok(Box::pin(DfRecordBatchStreamAdapter::new(
-            self.to_stream(context)?,
+            self.to_stream(context, partition)?,
         )))
     }
 
@@ -299,7 +332,15 @@ impl DisplayAs for MergeScanExec {
         for region_id in self.regions.iter() {
             write!(f, "{}, ", region_id)?;
         }
-        write!(f, "]")
+        write!(f, "]")?;
+        if let Some(output_ordering) = &self.output_ordering {
+            write!(f, ", output_ordering=[")?;
+            for sort_expr in output_ordering {
+                write!(f, "{}, ", sort_expr)?;
+            }
+            write!(f, "]")?;
+        }
+        Ok(())
     }
 }
 
@@ -16,10 +16,12 @@
 
 use std::sync::Arc;
 
+use arrow_schema::{Schema as ArrowSchema, SortOptions};
 use async_trait::async_trait;
 use catalog::CatalogManagerRef;
 use common_catalog::consts::{DEFAULT_CATALOG_NAME, DEFAULT_SCHEMA_NAME};
 use common_meta::table_name::TableName;
+use common_telemetry::{debug, warn};
 use datafusion::common::Result;
 use datafusion::datasource::DefaultTableSource;
 use datafusion::execution::context::SessionState;
@@ -27,8 +29,10 @@ use datafusion::physical_plan::ExecutionPlan;
 use datafusion::physical_planner::{ExtensionPlanner, PhysicalPlanner};
 use datafusion_common::tree_node::{Transformed, TreeNode, TreeNodeVisitor, VisitRecursion};
 use datafusion_common::TableReference;
-use datafusion_expr::{LogicalPlan, UserDefinedLogicalNode};
+use datafusion_expr::expr::Sort as SortExpr;
+use datafusion_expr::{Expr, LogicalPlan, UserDefinedLogicalNode};
 use datafusion_optimizer::analyzer::Analyzer;
+use datafusion_physical_expr::PhysicalSortExpr;
 use snafu::{OptionExt, ResultExt};
 use store_api::storage::RegionId;
 use substrait::{DFLogicalSubstraitConvertor, SubstraitPlan};
@@ -38,6 +42,7 @@ use table::table::adapter::DfTableProviderAdapter;
 use crate::dist_plan::merge_scan::{MergeScanExec, MergeScanLogicalPlan};
 use crate::error;
 use crate::error::{CatalogSnafu, TableNotFoundSnafu};
+use crate::optimizer::deferred_now::resolve_now;
 use crate::region_query::RegionQueryHandlerRef;
 
 pub struct DistExtensionPlanner {
@@ -71,7 +76,11 @@ impl ExtensionPlanner for DistExtensionPlanner {
             return Ok(None);
         };
 
-        let input_plan = merge_scan.input();
+        // Resolves `now()` before passing down the plan so all regions see the same time.
+        let input_plan = &resolve_now(
+            merge_scan.input().clone(),
+            session_state.execution_props().query_execution_start_time,
+        )?;
         let fallback = |logical_plan| async move {
             planner
                 .create_physical_plan(logical_plan, session_state)
@@ -90,7 +99,7 @@ impl ExtensionPlanner for DistExtensionPlanner {
             return fallback(&optimized_plan).await;
         };
 
-        let Ok(regions) = self.get_regions(&table_name).await else {
+        let Ok(regions) = self.get_regions(&table_name, &optimized_plan).await else {
             // no peers found, going to execute them locally
             return fallback(&optimized_plan).await;
         };
@@ -103,13 +112,15 @@ impl ExtensionPlanner for DistExtensionPlanner {
             .encode(&amended_plan)
             .context(error::EncodeSubstraitLogicalPlanSnafu)?
             .into();
+        let output_ordering = Self::output_ordering(planner, &optimized_plan, session_state)?;
         let merge_scan_plan = MergeScanExec::new(
             table_name,
             regions,
             substrait_plan,
             &schema,
             self.region_query_handler.clone(),
-        )?;
+        )?
+        .with_output_ordering(output_ordering);
         Ok(Some(Arc::new(merge_scan_plan) as _))
     }
 }
@@ -122,12 +133,57 @@ impl DistExtensionPlanner {
         Ok(extractor.table_name)
     }
 
+    /// Returns the order of rows from each region, which are sorted if the root of the
+    /// `plan` pushed down to regions is a sort.
+    fn output_ordering(
+        planner: &dyn PhysicalPlanner,
+        plan: &LogicalPlan,
+        session_state: &SessionState,
+    ) -> Result<Vec<PhysicalSortExpr>> {
+        let sort = match plan {
+            LogicalPlan::Sort(sort) => sort,
+            LogicalPlan::Limit(limit) => match limit.input.as_ref() {
+                LogicalPlan::Sort(sort) => sort,
+                _ => return Ok(vec![]),
+            },
+            _ => return Ok(vec![]),
+        };
+
+        let df_schema = plan.schema();
+        let schema = ArrowSchema::from(df_schema.as_ref());
+        let mut output_ordering = Vec::with_capacity(sort.expr.len());
+        for expr in &sort.expr {
+            let Expr::Sort(SortExpr {
+                expr,
+                asc,
+                nulls_first,
+            }) = expr
+            else {
+                return Ok(vec![]);
+            };
+            output_ordering.push(PhysicalSortExpr {
+                expr: planner.create_physical_expr(expr, df_schema, &schema, session_state)?,
+                options: SortOptions {
+                    descending: !asc,
+                    nulls_first: *nulls_first,
+                },
+            });
+        }
+        Ok(output_ordering)
+    }
+
     /// Apply the fully resolved table name to the TableScan plan
     fn plan_with_full_table_name(plan: LogicalPlan, name: &TableName) -> Result<LogicalPlan> {
         plan.transform(&|plan| TableNameRewriter::rewrite_table_name(plan, name))
     }
 
-    async fn get_regions(&self, table_name: &TableName) -> Result<Vec<RegionId>> {
+    /// Gets regions of the table to query, regions are pruned by filters of the scan in
+    /// the `plan` if possible.
+    async fn get_regions(
+        &self,
+        table_name: &TableName,
+        plan: &LogicalPlan,
+    ) -> Result<Vec<RegionId>> {
         let table = self
             .catalog_manager
             .table(
@@ -140,7 +196,45 @@ impl DistExtensionPlanner {
             .with_context(|| TableNotFoundSnafu {
                 table: table_name.to_string(),
             })?;
-        Ok(table.table_info().region_ids())
+        let table_info = table.table_info();
+        let regions = table_info.region_ids();
+
+        let mut extractor = ScanFilterExtractor::default();
+        let _ = plan.visit(&mut extractor)?;
+        // Only a plan of a single table is pruned.
+        if extractor.scans != 1 || extractor.filters.is_empty() {
+            return Ok(regions);
+        }
+        let table_id = table_info.table_id();
+        match self
+            .region_query_handler
+            .find_regions_by_filters(table_id, &extractor.filters)
+            .await
+        {
+            Ok(Some(region_numbers)) => {
+                let pruned = regions
+                    .iter()
+                    .filter(|region| region_numbers.contains(&region.region_number()))
+                    .copied()
+                    .collect::<Vec<_>>();
+                // Queries all regions if the filters can't match any region.
+                if pruned.is_empty() {
+                    Ok(regions)
+                } else {
+                    debug!(
+                        "Pruned regions of table {table_name} from {} to {}",
+                        regions.len(),
+                        pruned.len()
+                    );
+                    Ok(pruned)
+                }
+            }
+            Ok(None) => Ok(regions),
+            Err(e) => {
+                warn!(e; "Failed to prune regions of table {table_name}");
+                Ok(regions)
+            }
+        }
     }
 
     // TODO(ruihang): find a more elegant way to optimize input logical plan
@@ -222,6 +316,25 @@ impl TreeNodeVisitor for TableNameExtractor {
     }
 }
 
+/// Visitor to collect filters pushed down to table scans.
+#[derive(Default)]
+struct ScanFilterExtractor {
+    scans: usize,
+    filters: Vec<Expr>,
+}
+
+impl TreeNodeVisitor for ScanFilterExtractor {
+    type N = LogicalPlan;
+
+    fn pre_visit(&mut self, node: &Self::N) -> Result<VisitRecursion> {
+        if let LogicalPlan::TableScan(scan) = node {
+            self.scans += 1;
+            self.filters.extend(scan.filters.iter().cloned());
+        }
+        Ok(VisitRecursion::Continue)
+    }
+}
+
 struct TableNameRewriter;
 
 impl TableNameRewriter {
@@ -12,6 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+pub mod deferred_now;
 pub mod order_hint;
 pub mod string_normalization;
 pub mod type_conversion;
@@ -0,0 +1,196 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Evaluates `now()` at the start of the execution.
+//!
+//! DataFusion folds `now()` into a literal while optimizing a plan, so a plan optimized
+//! before its execution, e.g. the plan of a prepared statement, keeps the time when it's
+//! optimized. [defer_now] replaces `now()` with a function the optimizer doesn't fold, and
+//! [resolve_now] replaces both of them with the start time of the execution. Resolving
+//! `now()` before dispatching sub-plans also makes all regions see the same time.
+
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use datafusion_common::tree_node::{Transformed, TreeNode, VisitRecursion};
+use datafusion_common::{DataFusionError, Result, ScalarValue};
+use datafusion_expr::expr::{ScalarFunction, ScalarUDF};
+use datafusion_expr::{
+    create_udf, BuiltinScalarFunction, Expr, LogicalPlan, ScalarUDF as DfScalarUDF, Volatility,
+};
+use datatypes::arrow::datatypes::{DataType, TimeUnit};
+use once_cell::sync::Lazy;
+
+/// Time zone of the result of `now()`.
+const NOW_TIME_ZONE: &str = "+00:00";
+
+/// A volatile `now()` so the optimizer doesn't fold it.
+static DEFERRED_NOW: Lazy<Arc<DfScalarUDF>> = Lazy::new(|| {
+    Arc::new(create_udf(
+        "now",
+        vec![],
+        Arc::new(DataType::Timestamp(
+            TimeUnit::Nanosecond,
+            Some(NOW_TIME_ZONE.into()),
+        )),
+        Volatility::Volatile,
+        Arc::new(|_| {
+            Err(DataFusionError::Internal(
+                "deferred now() should be resolved before the execution".to_string(),
+            ))
+        }),
+    ))
+});
+
+/// Replaces `now()` in the `plan` with the deferred `now()`.
+pub fn defer_now(plan: LogicalPlan) -> Result<LogicalPlan> {
+    let deferred = Expr::ScalarUDF(ScalarUDF {
+        fun: DEFERRED_NOW.clone(),
+        args: vec![],
+    });
+    replace_now(plan, &is_builtin_now, &deferred)
+}
+
+/// Replaces both `now()` and the deferred `now()` in the `plan` with `now`.
+pub fn resolve_now(plan: LogicalPlan, now: DateTime<Utc>) -> Result<LogicalPlan> {
+    let now = Expr::Literal(ScalarValue::TimestampNanosecond(
+        now.timestamp_nanos_opt(),
+        Some(NOW_TIME_ZONE.into()),
+    ));
+    replace_now(
+        plan,
+        &|expr| is_builtin_now(expr) || is_deferred_now(expr),
+        &now,
+    )
+}
+
+fn is_builtin_now(expr: &Expr) -> bool {
+    matches!(
+        expr,
+        Expr::ScalarFunction(ScalarFunction {
+            fun: BuiltinScalarFunction::Now,
+            ..
+        })
+    )
+}
+
+fn is_deferred_now(expr: &Expr) -> bool {
+    matches!(expr, Expr::ScalarUDF(ScalarUDF { fun, .. }) if Arc::ptr_eq(fun, &DEFERRED_NOW))
+}
+
+fn contains(expr: &Expr, is_now: &impl Fn(&Expr) -> bool) -> bool {
+    let mut found = false;
+    let _ = expr.apply(&mut |expr| {
+        if is_now(expr) {
+            found = true;
+            return Ok(VisitRecursion::Stop);
+        }
+        Ok(VisitRecursion::Continue)
+    });
+    found
+}
+
+fn replace_now(
+    plan: LogicalPlan,
+    is_now: &impl Fn(&Expr) -> bool,
+    replacement: &Expr,
+) -> Result<LogicalPlan> {
+    plan.transform(&|plan| {
+        let exprs = plan.expressions();
+        if !exprs.iter().any(|expr| contains(expr, is_now)) {
+            return Ok(Transformed::No(plan));
+        }
+
+        let is_projection = matches!(plan, LogicalPlan::Projection(_));
+        let exprs = exprs
+            .into_iter()
+            .map(|expr| {
+                let name = expr.display_name()?;
+                let new_expr = expr.transform(&|expr| {
+                    if is_now(&expr) {
+                        Ok(Transformed::Yes(replacement.clone()))
+                    } else {
+                        Ok(Transformed::No(expr))
+                    }
+                })?;
+                // Keeps names of output columns.
+                if is_projection && new_expr.display_name()? != name {
+                    Ok(new_expr.alias(name))
+                } else {
+                    Ok(new_expr)
+                }
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let inputs = plan.inputs().into_iter().cloned().collect::<Vec<_>>();
+        plan.with_new_exprs(exprs, &inputs).map(Transformed::Yes)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use datafusion_expr::{col, lit, now, LogicalPlanBuilder};
+    use datatypes::arrow::datatypes::{Field, Schema};
+
+    use super::*;
+
+    fn build_plan() -> LogicalPlan {
+        let schema = Schema::new(vec![Field::new(
+            "ts",
+            DataType::Timestamp(TimeUnit::Millisecond, None),
+            false,
+        )]);
+        LogicalPlanBuilder::scan_empty(Some("t"), &schema, None)
+            .unwrap()
+            .filter(col("ts").gt_eq(now()))
+            .unwrap()
+            .project(vec![col("ts"), now()])
+            .unwrap()
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_defer_and_resolve_now() {
+        let plan = build_plan();
+        let names = plan.schema().field_names();
+
+        let deferred = defer_now(plan).unwrap();
+        assert_eq!(names, deferred.schema().field_names());
+        let mut has_builtin_now = false;
+        let _ = deferred.apply(&mut |plan| {
+            has_builtin_now |= plan
+                .expressions()
+                .iter()
+                .any(|e| contains(e, &is_builtin_now));
+            Ok(VisitRecursion::Continue)
+        });
+        assert!(!has_builtin_now);
+
+        let start = Utc::now();
+        let resolved = resolve_now(deferred, start).unwrap();
+        assert_eq!(names, resolved.schema().field_names());
+        let expected = lit(ScalarValue::TimestampNanosecond(
+            start.timestamp_nanos_opt(),
+            Some(NOW_TIME_ZONE.into()),
+        ));
+        let LogicalPlan::Projection(projection) = &resolved else {
+            unreachable!()
+        };
+        assert_eq!(expected.clone().alias("now()"), projection.expr[1]);
+        let LogicalPlan::Filter(filter) = projection.input.as_ref() else {
+            unreachable!()
+        };
+        assert_eq!(col("ts").gt_eq(expected), filter.predicate);
+    }
+}
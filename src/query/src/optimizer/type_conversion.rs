@@ -12,9 +12,8 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::str::FromStr;
-
 use common_time::timestamp::{TimeUnit, Timestamp};
+use common_time::TimeZone;
 use datafusion::config::ConfigOptions;
 use datafusion_common::tree_node::{Transformed, TreeNode, TreeNodeRewriter};
 use datafusion_common::{DFSchemaRef, DataFusionError, Result, ScalarValue};
@@ -34,11 +33,18 @@ use datatypes::arrow::datatypes::DataType;
 pub struct TypeConversionRule;
 
 impl AnalyzerRule for TypeConversionRule {
-    fn analyze(&self, plan: LogicalPlan, _config: &ConfigOptions) -> Result<LogicalPlan> {
+    fn analyze(&self, plan: LogicalPlan, config: &ConfigOptions) -> Result<LogicalPlan> {
+        // The time zone of the query, for string literals of timestamps without offset.
+        let time_zone = config
+            .execution
+            .time_zone
+            .as_deref()
+            .and_then(|tz| TimeZone::from_tz_string(tz).ok().flatten());
         plan.transform(&|plan| match plan {
             LogicalPlan::Filter(filter) => {
                 let mut converter = TypeConverter {
                     schema: filter.input.schema().clone(),
+                    time_zone: time_zone.clone(),
                 };
                 let rewritten = filter.predicate.clone().rewrite(&mut converter)?;
                 Ok(Transformed::Yes(LogicalPlan::Filter(Filter::try_new(
@@ -56,6 +62,7 @@ impl AnalyzerRule for TypeConversionRule {
             }) => {
                 let mut converter = TypeConverter {
                     schema: projected_schema.clone(),
+                    time_zone: time_zone.clone(),
                 };
                 let rewrite_filters = filters
                     .into_iter()
@@ -86,6 +93,7 @@ impl AnalyzerRule for TypeConversionRule {
             | LogicalPlan::Analyze { .. } => {
                 let mut converter = TypeConverter {
                     schema: plan.schema().clone(),
+                    time_zone: time_zone.clone(),
                 };
                 let inputs = plan.inputs().into_iter().cloned().collect::<Vec<_>>();
                 let expr = plan
@@ -117,6 +125,7 @@ impl AnalyzerRule for TypeConversionRule {
 
 struct TypeConverter {
     schema: DFSchemaRef,
+    time_zone: Option<TimeZone>,
 }
 
 impl TypeConverter {
@@ -129,9 +138,15 @@ impl TypeConverter {
         None
     }
 
-    fn cast_scalar_value(value: &ScalarValue, target_type: &DataType) -> Result<ScalarValue> {
+    fn cast_scalar_value(
+        &self,
+        value: &ScalarValue,
+        target_type: &DataType,
+    ) -> Result<ScalarValue> {
         match (target_type, value) {
-            (DataType::Timestamp(_, _), ScalarValue::Utf8(Some(v))) => string_to_timestamp_ms(v),
+            (DataType::Timestamp(_, _), ScalarValue::Utf8(Some(v))) => {
+                string_to_timestamp_ms(v, self.time_zone.as_ref())
+            }
             (DataType::Boolean, ScalarValue::Utf8(Some(v))) => match v.to_lowercase().as_str() {
                 "true" => Ok(ScalarValue::Boolean(Some(true))),
                 "false" => Ok(ScalarValue::Boolean(Some(false))),
@@ -167,7 +182,7 @@ impl TypeConverter {
 
         match (left, right) {
             (Expr::Column(col), Expr::Literal(value)) => {
-                let casted_right = Self::cast_scalar_value(value, target_type)?;
+                let casted_right = self.cast_scalar_value(value, target_type)?;
                 if casted_right.is_null() {
                     return Err(DataFusionError::Plan(format!(
                         "column:{col:?}. Casting value:{value:?} to {target_type:?} is invalid",
@@ -176,7 +191,7 @@ impl TypeConverter {
                 Ok((left.clone(), Expr::Literal(casted_right)))
             }
             (Expr::Literal(value), Expr::Column(col)) => {
-                let casted_left = Self::cast_scalar_value(value, target_type)?;
+                let casted_left = self.cast_scalar_value(value, target_type)?;
                 if casted_left.is_null() {
                     return Err(DataFusionError::Plan(format!(
                         "column:{col:?}. Casting value:{value:?} to {target_type:?} is invalid",
@@ -273,8 +288,9 @@ fn timestamp_to_timestamp_ms_expr(val: i64, unit: TimeUnit) -> Expr {
     Expr::Literal(ScalarValue::TimestampMillisecond(Some(timestamp), None))
 }
 
-fn string_to_timestamp_ms(string: &str) -> Result<ScalarValue> {
-    let ts = Timestamp::from_str(string).map_err(|e| DataFusionError::External(Box::new(e)))?;
+fn string_to_timestamp_ms(string: &str, time_zone: Option<&TimeZone>) -> Result<ScalarValue> {
+    let ts = Timestamp::from_str_with_timezone(string, time_zone)
+        .map_err(|e| DataFusionError::External(Box::new(e)))?;
 
     let value = Some(ts.value());
     let scalar = match ts.unit() {
@@ -301,13 +317,19 @@ mod tests {
     #[test]
     fn test_string_to_timestamp_ms() {
         assert_eq!(
-            string_to_timestamp_ms("2022-02-02 19:00:00+08:00").unwrap(),
+            string_to_timestamp_ms("2022-02-02 19:00:00+08:00", None).unwrap(),
             ScalarValue::TimestampSecond(Some(1643799600), None)
         );
         assert_eq!(
-            string_to_timestamp_ms("2009-02-13 23:31:30Z").unwrap(),
+            string_to_timestamp_ms("2009-02-13 23:31:30Z", None).unwrap(),
             ScalarValue::TimestampSecond(Some(1234567890), None)
         );
+
+        let time_zone = TimeZone::from_tz_string("+08:00").unwrap();
+        assert_eq!(
+            string_to_timestamp_ms("2022-02-02 19:00:00", time_zone.as_ref()).unwrap(),
+            ScalarValue::TimestampSecond(Some(1643799600), None)
+        );
     }
 
     #[test]
@@ -363,7 +385,10 @@ mod tests {
             )
             .unwrap(),
         );
-        let mut converter = TypeConverter { schema };
+        let mut converter = TypeConverter {
+            schema,
+            time_zone: None,
+        };
 
         assert_eq!(
             Expr::Column(Column::from_name("ts")).gt(Expr::Literal(ScalarValue::TimestampSecond(
@@ -395,7 +420,10 @@ mod tests {
             )
             .unwrap(),
         );
-        let mut converter = TypeConverter { schema };
+        let mut converter = TypeConverter {
+            schema,
+            time_zone: None,
+        };
 
         assert_eq!(
             Expr::Column(Column::from_name(col_name))
@@ -26,6 +26,7 @@ use session::context::QueryContextRef;
 use snafu::ResultExt;
 use sql::statements::statement::Statement;
 
+use crate::asof_join::plan_rewrite::AsofJoinRewriter;
 use crate::error::{PlanSqlSnafu, QueryPlanSnafu, Result, SqlSnafu};
 use crate::parser::QueryStatement;
 use crate::plan::LogicalPlan;
@@ -84,6 +85,7 @@ impl DfLogicalPlanner {
         let plan = RangePlanRewriter::new(table_provider)
             .rewrite(result)
             .await?;
+        let plan = AsofJoinRewriter::rewrite(plan)?;
         Ok(LogicalPlan::DfPlan(plan))
     }
 
@@ -70,6 +70,12 @@ pub trait QueryEngine: Send + Sync {
 
     fn register_function(&self, func: FunctionRef);
 
+    /// Registers a function only visible to queries in the `catalog`.
+    ///
+    /// Returns false and keeps the existing function if the catalog already has a function
+    /// with the same name.
+    fn register_catalog_function(&self, catalog: &str, func: FunctionRef) -> bool;
+
     /// Create a DataFrame from a table.
     fn read_table(&self, table: TableRef) -> Result<DataFrame>;
 }
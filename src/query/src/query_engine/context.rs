@@ -24,7 +24,12 @@ pub struct QueryEngineContext {
 }
 
 impl QueryEngineContext {
-    pub fn new(state: SessionState, query_ctx: QueryContextRef) -> Self {
+    pub fn new(mut state: SessionState, query_ctx: QueryContextRef) -> Self {
+        // Passes the time zone of the session to DataFusion, so the analyzer parses
+        // timestamp literals in it.
+        if let Some(time_zone) = query_ctx.time_zone() {
+            state.config_mut().options_mut().execution.time_zone = Some(time_zone.to_string());
+        }
         Self { state, query_ctx }
     }
 
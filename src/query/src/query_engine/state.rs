@@ -12,6 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::collections::hash_map::Entry;
 use std::collections::HashMap;
 use std::fmt;
 use std::sync::{Arc, RwLock};
@@ -20,6 +21,8 @@ use async_trait::async_trait;
 use catalog::CatalogManagerRef;
 use common_base::Plugins;
 use common_function::scalars::aggregate::AggregateFunctionMetaRef;
+use common_function::scalars::udf::create_udf;
+use common_function::scalars::FunctionRef;
 use common_query::physical_plan::SessionContext;
 use common_query::prelude::ScalarUdf;
 use datafusion::catalog::MemoryCatalogList;
@@ -27,6 +30,7 @@ use datafusion::dataframe::DataFrame;
 use datafusion::error::Result as DfResult;
 use datafusion::execution::context::{QueryPlanner, SessionConfig, SessionState};
 use datafusion::execution::runtime_env::RuntimeEnv;
+use datafusion::physical_plan::udf::ScalarUDF;
 use datafusion::physical_plan::ExecutionPlan;
 use datafusion::physical_planner::{DefaultPhysicalPlanner, ExtensionPlanner, PhysicalPlanner};
 use datafusion_expr::LogicalPlan as DfLogicalPlan;
@@ -38,6 +42,8 @@ use substrait::extension_serializer::ExtensionSerializer;
 use table::table::adapter::DfTableProviderAdapter;
 use table::TableRef;
 
+use crate::asof_join::plan_rewrite::asof_join_udf;
+use crate::asof_join::planner::AsofJoinPlanner;
 use crate::dist_plan::{DistExtensionPlanner, DistPlannerAnalyzer};
 use crate::optimizer::order_hint::OrderHintRule;
 use crate::optimizer::string_normalization::StringNormalizationRule;
@@ -57,6 +63,8 @@ pub struct QueryEngineState {
     catalog_manager: CatalogManagerRef,
     table_mutation_handler: Option<TableMutationHandlerRef>,
     aggregate_functions: Arc<RwLock<HashMap<String, AggregateFunctionMetaRef>>>,
+    /// Functions only visible to queries in a catalog, keyed by catalogs and function names.
+    catalog_functions: Arc<RwLock<HashMap<String, HashMap<String, Arc<ScalarUDF>>>>>,
     plugins: Plugins,
 }
 
@@ -104,12 +112,14 @@ impl QueryEngineState {
         .with_optimizer_rules(optimizer.rules);
 
         let df_context = SessionContext::new_with_state(session_state);
+        df_context.register_udf(asof_join_udf());
 
         Self {
             df_context,
             catalog_manager: catalog_list,
             table_mutation_handler,
             aggregate_functions: Arc::new(RwLock::new(HashMap::new())),
+            catalog_functions: Arc::new(RwLock::new(HashMap::new())),
             plugins,
         }
     }
@@ -124,6 +134,36 @@ impl QueryEngineState {
         self.df_context.register_udf(udf.into_df_udf());
     }
 
+    /// Registers a function only visible to queries in the `catalog`.
+    ///
+    /// Returns false and keeps the existing function if the catalog already has a function
+    /// with the same name.
+    pub fn register_catalog_function(&self, catalog: &str, func: FunctionRef) -> bool {
+        let name = func.name().to_string();
+        let mut catalog_functions = self.catalog_functions.write().unwrap();
+        match catalog_functions
+            .entry(catalog.to_string())
+            .or_default()
+            .entry(name)
+        {
+            Entry::Occupied(_) => false,
+            Entry::Vacant(entry) => {
+                let _ = entry.insert(Arc::new(create_udf(func).into_df_udf()));
+                true
+            }
+        }
+    }
+
+    /// Returns the function registered to the `catalog` by [Self::register_catalog_function].
+    pub fn catalog_function(&self, catalog: &str, function_name: &str) -> Option<Arc<ScalarUDF>> {
+        self.catalog_functions
+            .read()
+            .unwrap()
+            .get(catalog)
+            .and_then(|functions| functions.get(function_name))
+            .cloned()
+    }
+
     pub fn aggregate_function(&self, function_name: &str) -> Option<AggregateFunctionMetaRef> {
         self.aggregate_functions
             .read()
@@ -202,8 +242,11 @@ impl DfQueryPlanner {
         catalog_manager: CatalogManagerRef,
         region_query_handler: Option<RegionQueryHandlerRef>,
     ) -> Self {
-        let mut planners: Vec<Arc<dyn ExtensionPlanner + Send + Sync>> =
-            vec![Arc::new(PromExtensionPlanner), Arc::new(RangeSelectPlanner)];
+        let mut planners: Vec<Arc<dyn ExtensionPlanner + Send + Sync>> = vec![
+            Arc::new(PromExtensionPlanner),
+            Arc::new(RangeSelectPlanner),
+            Arc::new(AsofJoinPlanner),
+        ];
         if let Some(region_query_handler) = region_query_handler {
             planners.push(Arc::new(DistExtensionPlanner::new(
                 catalog_manager,
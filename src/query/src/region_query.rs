@@ -17,12 +17,27 @@ use std::sync::Arc;
 use api::v1::region::QueryRequest;
 use async_trait::async_trait;
 use common_recordbatch::SendableRecordBatchStream;
+use datafusion_expr::Expr;
+use store_api::storage::RegionNumber;
+use table::metadata::TableId;
 
 use crate::error::Result;
 
 #[async_trait]
 pub trait RegionQueryHandler: Send + Sync {
     async fn do_get(&self, request: QueryRequest) -> Result<SendableRecordBatchStream>;
+
+    /// Finds regions of the table that may have rows satisfying all the `filters`.
+    ///
+    /// Returns `None` if regions can't be pruned by the `filters`, then all regions
+    /// of the table are queried.
+    async fn find_regions_by_filters(
+        &self,
+        _table_id: TableId,
+        _filters: &[Expr],
+    ) -> Result<Option<Vec<RegionNumber>>> {
+        Ok(None)
+    }
 }
 
 pub type RegionQueryHandlerRef = Arc<dyn RegionQueryHandler>;
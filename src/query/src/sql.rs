@@ -22,9 +22,8 @@ use common_catalog::consts::{
     SEMANTIC_TYPE_FIELD, SEMANTIC_TYPE_PRIMARY_KEY, SEMANTIC_TYPE_TIME_INDEX,
 };
 use common_datasource::file_format::{infer_schemas, FileFormat, Format};
-use common_datasource::lister::{Lister, Source};
+use common_datasource::lister::list_files;
 use common_datasource::object_store::build_backend;
-use common_datasource::util::find_dir_and_filename;
 use common_query::Output;
 use common_recordbatch::{RecordBatch, RecordBatches};
 use common_time::Timestamp;
@@ -360,36 +359,20 @@ pub async fn prepare_file_table_files(
             name: FILE_TABLE_LOCATION_KEY,
         })?;
 
-    let (dir, filename) = find_dir_and_filename(url);
-    let source = if let Some(filename) = filename {
-        Source::Filename(filename)
-    } else {
-        Source::Dir
-    };
     let regex = options
         .get(FILE_TABLE_PATTERN_KEY)
         .map(|x| Regex::new(x))
         .transpose()
         .context(error::BuildRegexSnafu)?;
     let object_store = build_backend(url, options).context(error::BuildBackendSnafu)?;
-    let lister = Lister::new(object_store.clone(), source, dir, regex);
+    // Files are only listed once here unless the table sets `list_on_scan`.
     // If we scan files in a directory every time the database restarts,
     // then it might lead to a potential undefined behavior:
     // If a user adds a file with an incompatible schema to that directory,
     // it will make the external table unavailable.
-    let files = lister
-        .list()
+    let files = list_files(object_store.clone(), url, regex)
         .await
-        .context(error::ListObjectsSnafu)?
-        .into_iter()
-        .filter_map(|entry| {
-            if entry.path().ends_with('/') {
-                None
-            } else {
-                Some(entry.path().to_string())
-            }
-        })
-        .collect::<Vec<_>>();
+        .context(error::ListObjectsSnafu)?;
     Ok((object_store, files))
 }
 
@@ -21,6 +21,7 @@ use table::TableRef;
 use crate::parser::QueryLanguageParser;
 use crate::{QueryEngineFactory, QueryEngineRef};
 
+mod approx_aggregate_test;
 mod argmax_test;
 mod argmin_test;
 mod mean_test;
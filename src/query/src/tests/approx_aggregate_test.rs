@@ -0,0 +1,66 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+
+use common_recordbatch::RecordBatch;
+use datatypes::prelude::*;
+use datatypes::schema::{ColumnSchema, Schema};
+use datatypes::vectors::Int32Vector;
+use table::test_util::MemTable;
+
+use super::new_query_engine_with_table;
+use crate::tests::{exec_selection, function};
+use crate::QueryEngine;
+
+fn create_approx_engine() -> Arc<dyn QueryEngine> {
+    let column_schemas = vec![ColumnSchema::new(
+        "number",
+        ConcreteDataType::int32_datatype(),
+        true,
+    )];
+    let columns: Vec<VectorRef> = vec![Arc::new(Int32Vector::from(vec![
+        Some(3),
+        Some(6),
+        None,
+        Some(8),
+        Some(10),
+        Some(6),
+    ]))];
+
+    let schema = Arc::new(Schema::new(column_schemas));
+    let table = MemTable::table("approx_numbers", RecordBatch::new(schema, columns).unwrap());
+    new_query_engine_with_table(table)
+}
+
+#[tokio::test]
+async fn test_approx_percentile() {
+    let engine = create_approx_engine();
+    for (p, expected) in [(0.0, 3.0), (50.0, 6.0), (100.0, 10.0)] {
+        let sql =
+            format!("select APPROX_PERCENTILE(number, {p:.1}) as percentile from approx_numbers");
+        let result = exec_selection(engine.clone(), &sql).await;
+        let value = function::get_value_from_batches("percentile", result);
+        assert_eq!(Value::from(expected), value, "p: {p}");
+    }
+}
+
+#[tokio::test]
+async fn test_approx_count_distinct() {
+    let engine = create_approx_engine();
+    let sql = "select APPROX_COUNT_DISTINCT(number) as distinct_count from approx_numbers";
+    let result = exec_selection(engine, sql).await;
+    let value = function::get_value_from_batches("distinct_count", result);
+    assert_eq!(Value::from(4u64), value);
+}
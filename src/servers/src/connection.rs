@@ -0,0 +1,334 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Management of client connections of the MySQL and Postgres servers, so leaked client
+//! connections don't exhaust server resources.
+//!
+//! A server rejects new connections once it has `max_connections` connections, closes
+//! connections idle for `idle_timeout`, and enables TCP keepalive of connections so the
+//! connections of crashed clients are detected. Setting an option to zero disables it.
+
+use std::io;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+
+use common_telemetry::logging::{error, warn};
+use pin_project::pin_project;
+use serde::{Deserialize, Serialize};
+use socket2::{SockRef, TcpKeepalive};
+use tokio::io::{AsyncRead, ReadBuf};
+use tokio::net::TcpStream;
+
+use crate::metrics::{
+    METRIC_IDLE_CONNECTIONS, METRIC_IDLE_TIMEOUT_CONNECTIONS, METRIC_REJECTED_CONNECTIONS,
+};
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct ConnectionOptions {
+    /// Max number of connections, new connections are rejected if it's reached.
+    pub max_connections: usize,
+    /// Closes connections that have been idle, i.e. waiting for requests, for this long.
+    #[serde(with = "humantime_serde")]
+    pub idle_timeout: Duration,
+    /// Idle time of a connection before TCP keepalive probes are sent.
+    #[serde(with = "humantime_serde")]
+    pub keepalive: Duration,
+}
+
+/// Admits and tracks connections of a server, it's shared by all connections of the server.
+pub struct ConnectionManager {
+    protocol: &'static str,
+    options: ConnectionOptions,
+    active: Arc<AtomicUsize>,
+}
+
+impl ConnectionManager {
+    pub fn new(protocol: &'static str, options: ConnectionOptions) -> Self {
+        Self {
+            protocol,
+            options,
+            active: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
+    /// Admits a new connection, returns None if the connection should be rejected. The
+    /// connection is counted until the permit is dropped.
+    pub(crate) fn admit(&self, stream: &TcpStream) -> Option<ConnectionPermit> {
+        let max_connections = self.options.max_connections;
+        let admitted = self
+            .active
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |active| {
+                (max_connections == 0 || active < max_connections).then_some(active + 1)
+            })
+            .is_ok();
+        if !admitted {
+            METRIC_REJECTED_CONNECTIONS
+                .with_label_values(&[self.protocol])
+                .inc();
+            warn!(
+                "Reject {} connection from {:?}, max connections {} reached",
+                self.protocol,
+                stream.peer_addr().ok(),
+                max_connections
+            );
+            return None;
+        }
+
+        if !self.options.keepalive.is_zero() {
+            let keepalive = TcpKeepalive::new().with_time(self.options.keepalive);
+            if let Err(e) = SockRef::from(stream).set_tcp_keepalive(&keepalive) {
+                error!(e; "Failed to set TCP keepalive");
+            }
+        }
+
+        Some(ConnectionPermit {
+            active: self.active.clone(),
+        })
+    }
+
+    /// Returns a new activity to track whether an admitted connection is idle.
+    pub(crate) fn activity(&self) -> Arc<ConnectionActivity> {
+        Arc::new(ConnectionActivity::new(self.protocol))
+    }
+
+    /// Resolves once the connection has been idle for the idle timeout, never resolves if
+    /// the idle timeout is disabled.
+    pub(crate) async fn wait_idle_timeout(&self, activity: &ConnectionActivity) {
+        let idle_timeout = self.options.idle_timeout;
+        if idle_timeout.is_zero() {
+            return futures::future::pending().await;
+        }
+
+        loop {
+            let wait = match activity.idle_for() {
+                Some(idle) if idle >= idle_timeout => break,
+                Some(idle) => idle_timeout - idle,
+                None => idle_timeout,
+            };
+            tokio::time::sleep(wait).await;
+        }
+
+        METRIC_IDLE_TIMEOUT_CONNECTIONS
+            .with_label_values(&[self.protocol])
+            .inc();
+    }
+}
+
+/// A connection admitted by [`ConnectionManager`].
+pub(crate) struct ConnectionPermit {
+    active: Arc<AtomicUsize>,
+}
+
+impl Drop for ConnectionPermit {
+    fn drop(&mut self) {
+        let _ = self.active.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+/// Tracks whether a connection is idle, i.e. waiting for requests of the client.
+pub(crate) struct ConnectionActivity {
+    protocol: &'static str,
+    created: Instant,
+    idle: AtomicBool,
+    /// Millis since `created` when the connection became idle or busy.
+    last_active_ms: AtomicU64,
+}
+
+impl ConnectionActivity {
+    fn new(protocol: &'static str) -> Self {
+        METRIC_IDLE_CONNECTIONS.with_label_values(&[protocol]).inc();
+        Self {
+            protocol,
+            created: Instant::now(),
+            idle: AtomicBool::new(true),
+            last_active_ms: AtomicU64::new(0),
+        }
+    }
+
+    pub(crate) fn busy(&self) {
+        if self.idle.swap(false, Ordering::AcqRel) {
+            self.touch();
+            METRIC_IDLE_CONNECTIONS
+                .with_label_values(&[self.protocol])
+                .dec();
+        }
+    }
+
+    pub(crate) fn idle(&self) {
+        if !self.idle.swap(true, Ordering::AcqRel) {
+            self.touch();
+            METRIC_IDLE_CONNECTIONS
+                .with_label_values(&[self.protocol])
+                .inc();
+        }
+    }
+
+    /// Marks the connection busy until the returned guard is dropped.
+    pub(crate) fn busy_guard(&self) -> BusyGuard<'_> {
+        self.busy();
+        BusyGuard { activity: self }
+    }
+
+    /// Returns how long the connection has been idle, or None if it's busy.
+    fn idle_for(&self) -> Option<Duration> {
+        if !self.idle.load(Ordering::Acquire) {
+            return None;
+        }
+        let last_active = Duration::from_millis(self.last_active_ms.load(Ordering::Acquire));
+        Some(self.created.elapsed().saturating_sub(last_active))
+    }
+
+    fn touch(&self) {
+        self.last_active_ms
+            .store(self.created.elapsed().as_millis() as u64, Ordering::Release);
+    }
+}
+
+impl Drop for ConnectionActivity {
+    fn drop(&mut self) {
+        if self.idle.load(Ordering::Acquire) {
+            METRIC_IDLE_CONNECTIONS
+                .with_label_values(&[self.protocol])
+                .dec();
+        }
+    }
+}
+
+pub(crate) struct BusyGuard<'a> {
+    activity: &'a ConnectionActivity,
+}
+
+impl Drop for BusyGuard<'_> {
+    fn drop(&mut self) {
+        self.activity.idle();
+    }
+}
+
+/// Reader of a connection, the connection is idle while the reader is waiting for data.
+#[pin_project]
+pub(crate) struct ActivityReader<R> {
+    #[pin]
+    inner: R,
+    activity: Arc<ConnectionActivity>,
+}
+
+impl<R> ActivityReader<R> {
+    pub(crate) fn new(inner: R, activity: Arc<ConnectionActivity>) -> Self {
+        Self { inner, activity }
+    }
+}
+
+impl<R: AsyncRead> AsyncRead for ActivityReader<R> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let this = self.project();
+        let poll = this.inner.poll_read(cx, buf);
+        if poll.is_pending() {
+            this.activity.idle();
+        } else {
+            this.activity.busy();
+        }
+        poll
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::io::AsyncReadExt;
+    use tokio::net::TcpListener;
+
+    use super::*;
+
+    async fn connect(listener: &TcpListener) -> (TcpStream, TcpStream) {
+        let addr = listener.local_addr().unwrap();
+        let (client, server) = tokio::join!(TcpStream::connect(addr), listener.accept());
+        (client.unwrap(), server.unwrap().0)
+    }
+
+    #[tokio::test]
+    async fn test_max_connections() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let manager = ConnectionManager::new(
+            "test",
+            ConnectionOptions {
+                max_connections: 1,
+                keepalive: Duration::from_secs(60),
+                ..Default::default()
+            },
+        );
+
+        let (_c1, s1) = connect(&listener).await;
+        let (_c2, s2) = connect(&listener).await;
+        let permit = manager.admit(&s1).unwrap();
+        assert!(manager.admit(&s2).is_none());
+        drop(permit);
+        assert!(manager.admit(&s2).is_some());
+    }
+
+    #[tokio::test]
+    async fn test_idle_timeout() {
+        let manager = ConnectionManager::new(
+            "test",
+            ConnectionOptions {
+                idle_timeout: Duration::from_millis(100),
+                ..Default::default()
+            },
+        );
+        let activity = manager.activity();
+
+        // A busy connection never times out.
+        let guard = activity.busy_guard();
+        assert!(tokio::time::timeout(
+            Duration::from_millis(300),
+            manager.wait_idle_timeout(&activity)
+        )
+        .await
+        .is_err());
+        drop(guard);
+
+        tokio::time::timeout(
+            Duration::from_millis(300),
+            manager.wait_idle_timeout(&activity),
+        )
+        .await
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_activity_reader() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let (client, server) = connect(&listener).await;
+        let activity = Arc::new(ConnectionActivity::new("test"));
+        let mut reader = ActivityReader::new(server, activity.clone());
+
+        let mut buf = [0u8; 4];
+        assert!(
+            tokio::time::timeout(Duration::from_millis(50), reader.read(&mut buf))
+                .await
+                .is_err()
+        );
+        assert!(activity.idle_for().is_some());
+
+        client.try_write(b"ping").unwrap();
+        assert_eq!(4, reader.read(&mut buf).await.unwrap());
+        assert!(activity.idle_for().is_none());
+    }
+}
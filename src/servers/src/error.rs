@@ -116,6 +116,12 @@ pub enum Error {
         source: BoxedError,
     },
 
+    #[snafu(display("Failed to parse SQL"))]
+    ParseSql {
+        source: sql::error::Error,
+        location: Location,
+    },
+
     #[snafu(display("Failed to describe statement"))]
     DescribeStatement { source: BoxedError },
 
@@ -193,6 +199,62 @@ pub enum Error {
         location: Location,
     },
 
+    #[snafu(display("Invalid MQTT packet, reason: {}", reason))]
+    InvalidMqttPacket { reason: String, location: Location },
+
+    #[snafu(display("Invalid MQTT payload of topic {}, reason: {}", topic, reason))]
+    InvalidMqttPayload {
+        topic: String,
+        reason: String,
+        location: Location,
+    },
+
+    #[snafu(display("Invalid MQTT JSON payload of topic {}", topic))]
+    MqttJsonPayload {
+        topic: String,
+        #[snafu(source)]
+        error: serde_json::error::Error,
+        location: Location,
+    },
+
+    #[snafu(display("Invalid pipeline {}, reason: {}", pipeline, reason))]
+    InvalidPipeline {
+        pipeline: String,
+        reason: String,
+        location: Location,
+    },
+
+    #[snafu(display("Invalid pipeline message, reason: {}", reason))]
+    InvalidPipelineMessage { reason: String, location: Location },
+
+    #[snafu(display("Invalid JSON pipeline message"))]
+    PipelineJsonMessage {
+        #[snafu(source)]
+        error: serde_json::error::Error,
+        location: Location,
+    },
+
+    #[snafu(display("Failed to decode protobuf pipeline message"))]
+    DecodePipelineProtobuf {
+        #[snafu(source)]
+        error: prost::DecodeError,
+        location: Location,
+    },
+
+    #[snafu(display("Failed to access metadata of pipelines"))]
+    PipelineMetadata {
+        source: common_meta::error::Error,
+        location: Location,
+    },
+
+    #[snafu(display("Failed to consume Kafka topic {}", topic))]
+    ConsumeKafka {
+        topic: String,
+        #[snafu(source)]
+        error: rskafka::client::error::Error,
+        location: Location,
+    },
+
     #[snafu(display("Failed to decode prometheus remote request"))]
     DecodePromRemoteRequest {
         location: Location,
@@ -207,6 +269,23 @@ pub enum Error {
         error: prost::DecodeError,
     },
 
+    #[snafu(display("Failed to decode Loki push request"))]
+    DecodeLokiRequest {
+        location: Location,
+        #[snafu(source)]
+        error: prost::DecodeError,
+    },
+
+    #[snafu(display("Invalid JSON Loki push request"))]
+    LokiJsonRequest {
+        location: Location,
+        #[snafu(source)]
+        error: serde_json::error::Error,
+    },
+
+    #[snafu(display("Invalid Loki push request, reason: {}", reason))]
+    InvalidLokiRequest { reason: String, location: Location },
+
     #[snafu(display("Failed to decompress prometheus remote request"))]
     DecompressPromRemoteRequest {
         location: Location,
@@ -244,6 +323,64 @@ pub enum Error {
         location: Location,
     },
 
+    #[snafu(display("Failed to decode Flight data"))]
+    DecodeFlightData {
+        location: Location,
+        source: common_grpc::error::Error,
+    },
+
+    #[snafu(display("Invalid bulk insert request, reason: {}", reason))]
+    InvalidBulkInsert { reason: String, location: Location },
+
+    #[snafu(display("Failed to watch file {}", path))]
+    FileWatch {
+        path: String,
+        #[snafu(source)]
+        error: notify::Error,
+        location: Location,
+    },
+
+    #[snafu(display("Invalid upload request, reason: {}", reason))]
+    InvalidUpload { reason: String, location: Location },
+
+    #[snafu(display("Table not found: {}", table_name))]
+    TableNotFound {
+        table_name: String,
+        location: Location,
+    },
+
+    #[snafu(display(
+        "Too many bad rows in the uploaded file, max bad rows: {}, last error: {}",
+        max_bad_rows,
+        last_error
+    ))]
+    TooManyBadRows {
+        max_bad_rows: usize,
+        last_error: String,
+        location: Location,
+    },
+
+    #[snafu(display("Failed to read the uploaded Parquet file"))]
+    ReadParquet {
+        #[snafu(source)]
+        error: parquet::errors::ParquetError,
+        location: Location,
+    },
+
+    #[snafu(display("Failed to convert the uploaded file to the table schema"))]
+    ConvertUpload {
+        #[snafu(source)]
+        error: datatypes::arrow::error::ArrowError,
+        location: Location,
+    },
+
+    #[snafu(display("Unsupported data type of column: {}", column))]
+    ColumnDataType {
+        column: String,
+        location: Location,
+        source: api::error::Error,
+    },
+
     #[snafu(display("Tls is required for {}, plain connection is rejected", server))]
     TlsRequired { server: String },
 
@@ -412,6 +549,20 @@ pub enum Error {
         location: Location,
     },
 
+    #[snafu(display("Failed to encode record batches to Arrow IPC"))]
+    EncodeArrowIpc {
+        #[snafu(source)]
+        error: datatypes::arrow::error::ArrowError,
+        location: Location,
+    },
+
+    #[snafu(display("Failed to encode record batches to CSV"))]
+    EncodeCsv {
+        #[snafu(source)]
+        error: datatypes::arrow::error::ArrowError,
+        location: Location,
+    },
+
     #[snafu(display("Failed to decode url"))]
     UrlDecode {
         #[snafu(source)]
@@ -421,6 +572,33 @@ pub enum Error {
 
     #[snafu(display("Failed to convert Mysql value, error: {}", err_msg))]
     MysqlValueConversion { err_msg: String, location: Location },
+
+    #[snafu(display("Query {} is cancelled", id))]
+    QueryCancelled { id: u32, location: Location },
+
+    #[snafu(display("Unknown query id: {}", id))]
+    QueryNotFound { id: u32, location: Location },
+
+    #[snafu(display("Statement timeout"))]
+    StatementTimeout { location: Location },
+
+    #[snafu(display("Invalid statement timeout: {}", value))]
+    InvalidStatementTimeout { value: String, location: Location },
+
+    #[snafu(display("Invalid time zone: {}", value))]
+    InvalidTimeZone {
+        value: String,
+        location: Location,
+        source: common_time::error::Error,
+    },
+
+    #[snafu(display("Too many {} requests, user: {}, catalog: {}", protocol, user, catalog))]
+    RateLimited {
+        protocol: String,
+        user: String,
+        catalog: String,
+        location: Location,
+    },
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
@@ -431,6 +609,7 @@ impl ErrorExt for Error {
         match self {
             Internal { .. }
             | InternalIo { .. }
+            | FileWatch { .. }
             | TokioIo { .. }
             | StartHttp { .. }
             | StartGrpc { .. }
@@ -456,6 +635,9 @@ impl ErrorExt for Error {
             | ExecuteGrpcRequest { source, .. }
             | CheckDatabaseValidity { source, .. } => source.status_code(),
 
+            PipelineMetadata { source, .. } => source.status_code(),
+            ConsumeKafka { .. } => StatusCode::StorageUnavailable,
+
             NotSupported { .. }
             | InvalidParameter { .. }
             | InvalidQuery { .. }
@@ -463,20 +645,44 @@ impl ErrorExt for Error {
             | ConnResetByPeer { .. }
             | InvalidOpentsdbLine { .. }
             | InvalidOpentsdbJsonRequest { .. }
+            | InvalidMqttPacket { .. }
+            | InvalidMqttPayload { .. }
+            | MqttJsonPayload { .. }
+            | InvalidPipeline { .. }
+            | InvalidPipelineMessage { .. }
+            | PipelineJsonMessage { .. }
+            | DecodePipelineProtobuf { .. }
             | DecodePromRemoteRequest { .. }
             | DecodeOtlpRequest { .. }
             | CompressPromRemoteRequest { .. }
             | DecompressPromRemoteRequest { .. }
             | InvalidPromRemoteRequest { .. }
+            | DecodeLokiRequest { .. }
+            | LokiJsonRequest { .. }
+            | InvalidLokiRequest { .. }
             | InvalidExportMetricsConfig { .. }
             | InvalidFlightTicket { .. }
+            | DecodeFlightData { .. }
+            | InvalidBulkInsert { .. }
+            | InvalidUpload { .. }
+            | TooManyBadRows { .. }
+            | ReadParquet { .. }
+            | ConvertUpload { .. }
+            | ColumnDataType { .. }
             | InvalidPrepareStatement { .. }
             | DataFrame { .. }
             | PreparedStmtTypeMismatch { .. }
             | TimePrecision { .. }
             | UrlDecode { .. }
             | IncompatibleSchema { .. }
-            | MysqlValueConversion { .. } => StatusCode::InvalidArguments,
+            | MysqlValueConversion { .. }
+            | QueryNotFound { .. }
+            | InvalidStatementTimeout { .. }
+            | InvalidTimeZone { .. } => StatusCode::InvalidArguments,
+
+            QueryCancelled { .. } => StatusCode::Cancelled,
+            StatementTimeout { .. } => StatusCode::DeadlineExceeded,
+            RateLimited { .. } => StatusCode::RateLimited,
 
             InfluxdbLinesWrite { source, .. }
             | PromSeriesWrite { source, .. }
@@ -486,6 +692,7 @@ impl ErrorExt for Error {
             TlsRequired { .. } => StatusCode::Unknown,
             Auth { source, .. } => source.status_code(),
             DescribeStatement { source } => source.status_code(),
+            ParseSql { source, .. } => source.status_code(),
 
             NotFoundAuthHeader { .. } | NotFoundInfluxAuth { .. } => StatusCode::AuthHeaderNotFound,
             InvisibleASCII { .. }
@@ -495,6 +702,7 @@ impl ErrorExt for Error {
             | InvalidUtf8Value { .. } => StatusCode::InvalidAuthHeader,
 
             DatabaseNotFound { .. } => StatusCode::DatabaseNotFound,
+            TableNotFound { .. } => StatusCode::TableNotFound,
             #[cfg(feature = "mem-prof")]
             DumpProfileData { source, .. } => source.status_code(),
             InvalidFlushArgument { .. } => StatusCode::InvalidArguments,
@@ -521,7 +729,7 @@ impl ErrorExt for Error {
 
             ConvertScalarValue { source, .. } => source.status_code(),
 
-            ToJson { .. } => StatusCode::Internal,
+            ToJson { .. } | EncodeArrowIpc { .. } | EncodeCsv { .. } => StatusCode::Internal,
         }
     }
 
@@ -542,6 +750,7 @@ pub fn status_to_tonic_code(status_code: StatusCode) -> Code {
         | StatusCode::EngineExecuteQuery => Code::Internal,
         StatusCode::InvalidArguments | StatusCode::InvalidSyntax => Code::InvalidArgument,
         StatusCode::Cancelled => Code::Cancelled,
+        StatusCode::DeadlineExceeded => Code::DeadlineExceeded,
         StatusCode::TableAlreadyExists
         | StatusCode::TableColumnExists
         | StatusCode::RegionAlreadyExists => Code::AlreadyExists,
@@ -569,16 +778,21 @@ macro_rules! define_into_tonic_status {
     ($Error: ty) => {
         impl From<$Error> for tonic::Status {
             fn from(err: $Error) -> Self {
-                use common_error::{GREPTIME_ERROR_CODE, GREPTIME_ERROR_MSG};
+                use common_error::{
+                    GREPTIME_ERROR_CODE, GREPTIME_ERROR_MSG, GREPTIME_RETRY_AFTER_MS,
+                };
                 use tonic::codegen::http::{HeaderMap, HeaderValue};
                 use tonic::metadata::MetadataMap;
 
-                let mut headers = HeaderMap::<HeaderValue>::with_capacity(2);
+                let mut headers = HeaderMap::<HeaderValue>::with_capacity(3);
 
                 // If either of the status_code or error msg cannot convert to valid HTTP header value
                 // (which is a very rare case), just ignore. Client will use Tonic status code and message.
                 let status_code = err.status_code();
                 headers.insert(GREPTIME_ERROR_CODE, HeaderValue::from(status_code as u32));
+                if let Some(retry_after_ms) = status_code.retry_after_ms() {
+                    headers.insert(GREPTIME_RETRY_AFTER_MS, HeaderValue::from(retry_after_ms));
+                }
                 let root_error = err.output_msg();
 
                 if let Ok(err_msg) = HeaderValue::from_bytes(root_error.as_bytes()) {
@@ -607,6 +821,23 @@ impl From<std::io::Error> for Error {
 impl IntoResponse for Error {
     fn into_response(self) -> Response {
         let error_msg = self.output_msg();
+        if let Some(retry_after_ms) = self.status_code().retry_after_ms() {
+            // Asks the client to back off, e.g. the write buffer of the datanode is full.
+            logging::debug!("Failed to handle HTTP request, err: {:?}", self);
+            let body = Json(json!({
+                "error": error_msg,
+                "retry_after_ms": retry_after_ms,
+            }));
+            // The `Retry-After` header is in seconds.
+            let retry_after_secs = retry_after_ms.div_ceil(1000);
+            return (
+                HttpStatusCode::TOO_MANY_REQUESTS,
+                [(http::header::RETRY_AFTER, retry_after_secs.to_string())],
+                body,
+            )
+                .into_response();
+        }
+
         let status = match self {
             Error::InfluxdbLineProtocol { .. }
             | Error::InfluxdbLinesWrite { .. }
@@ -617,8 +848,17 @@ impl IntoResponse for Error {
             | Error::DecodeOtlpRequest { .. }
             | Error::DecompressPromRemoteRequest { .. }
             | Error::InvalidPromRemoteRequest { .. }
+            | Error::DecodeLokiRequest { .. }
+            | Error::LokiJsonRequest { .. }
+            | Error::InvalidLokiRequest { .. }
             | Error::InvalidQuery { .. }
+            | Error::InvalidUpload { .. }
+            | Error::TooManyBadRows { .. }
+            | Error::ReadParquet { .. }
+            | Error::ConvertUpload { .. }
+            | Error::ColumnDataType { .. }
             | Error::TimePrecision { .. } => HttpStatusCode::BAD_REQUEST,
+            Error::TableNotFound { .. } => HttpStatusCode::NOT_FOUND,
             _ => {
                 logging::error!(self; "Failed to handle HTTP request");
 
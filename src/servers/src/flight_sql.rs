@@ -0,0 +1,115 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Arrow Flight SQL server, so BI tools and ADBC/JDBC drivers can fetch results as Arrow
+//! record batches.
+//!
+//! The server serves the same `FlightService` as the gRPC server with a different protocol,
+//! so it listens on its own address. Statements, prepared statements without parameters and
+//! updates are supported, metadata commands like `GetTables` aren't supported yet.
+
+mod service;
+
+use std::net::SocketAddr;
+
+use arrow_flight::flight_service_server::FlightServiceServer;
+use async_trait::async_trait;
+use auth::UserProviderRef;
+use common_telemetry::logging::{error, info};
+use futures::FutureExt;
+use snafu::{ensure, ResultExt};
+use tokio::net::TcpListener;
+use tokio::sync::oneshot::{self, Sender};
+use tokio::sync::Mutex;
+use tonic::transport::server::TcpIncoming;
+
+use crate::error::{AlreadyStartedSnafu, Result, TcpBindSnafu, TcpIncomingSnafu};
+pub use crate::flight_sql::service::FlightSqlServiceImpl;
+use crate::query_handler::sql::ServerSqlQueryHandlerRef;
+use crate::server::Server;
+
+pub const FLIGHT_SQL_SERVER: &str = "FLIGHT_SQL_SERVER";
+
+pub struct FlightSqlServer {
+    service: FlightSqlServiceImpl,
+    shutdown_tx: Mutex<Option<Sender<()>>>,
+}
+
+impl FlightSqlServer {
+    pub fn new(
+        query_handler: ServerSqlQueryHandlerRef,
+        user_provider: Option<UserProviderRef>,
+    ) -> Self {
+        Self {
+            service: FlightSqlServiceImpl::new(query_handler, user_provider),
+            shutdown_tx: Mutex::new(None),
+        }
+    }
+}
+
+#[async_trait]
+impl Server for FlightSqlServer {
+    async fn shutdown(&self) -> Result<()> {
+        let mut shutdown_tx = self.shutdown_tx.lock().await;
+        if let Some(tx) = shutdown_tx.take() {
+            if tx.send(()).is_err() {
+                info!("Receiver dropped, the Flight SQL server has already existed");
+            }
+        }
+        info!("Shutdown Flight SQL server");
+
+        Ok(())
+    }
+
+    async fn start(&self, addr: SocketAddr) -> Result<SocketAddr> {
+        let (tx, rx) = oneshot::channel();
+        let (incoming, addr) = {
+            let mut shutdown_tx = self.shutdown_tx.lock().await;
+            ensure!(
+                shutdown_tx.is_none(),
+                AlreadyStartedSnafu {
+                    server: "Flight SQL"
+                }
+            );
+
+            let listener = TcpListener::bind(addr)
+                .await
+                .context(TcpBindSnafu { addr })?;
+            let addr = listener.local_addr().context(TcpBindSnafu { addr })?;
+            let incoming =
+                TcpIncoming::from_listener(listener, true, None).context(TcpIncomingSnafu)?;
+            info!("Flight SQL server is bound to {}", addr);
+
+            *shutdown_tx = Some(tx);
+
+            (incoming, addr)
+        };
+
+        let builder = tonic::transport::Server::builder()
+            .add_service(FlightServiceServer::new(self.service.clone()));
+        let _handle = common_runtime::spawn_bg(async move {
+            if let Err(e) = builder
+                .serve_with_incoming_shutdown(incoming, rx.map(drop))
+                .await
+            {
+                error!(e; "Flight SQL server exited with error");
+            }
+        });
+        Ok(addr)
+    }
+
+    fn name(&self) -> &str {
+        FLIGHT_SQL_SERVER
+    }
+}
@@ -0,0 +1,386 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use api::v1::auth_header::AuthScheme as GrpcAuthScheme;
+use api::v1::{AuthHeader, Basic, RequestHeader};
+use arrow_flight::flight_service_server::FlightService;
+use arrow_flight::sql::server::{FlightSqlService, PeekableFlightDataStream};
+use arrow_flight::sql::{
+    ActionClosePreparedStatementRequest, ActionCreatePreparedStatementRequest,
+    ActionCreatePreparedStatementResult, CommandPreparedStatementQuery, CommandStatementQuery,
+    CommandStatementUpdate, ProstMessageExt, SqlInfo, TicketStatementQuery,
+};
+use arrow_flight::{
+    FlightDescriptor, FlightEndpoint, FlightInfo, HandshakeRequest, HandshakeResponse, IpcMessage,
+    SchemaAsIpc, Ticket,
+};
+use async_trait::async_trait;
+use auth::UserProviderRef;
+use common_query::Output;
+use common_telemetry::tracing_context::TracingContext;
+use datatypes::arrow::datatypes::Schema as ArrowSchema;
+use datatypes::arrow::ipc::writer::IpcWriteOptions;
+use futures::Stream;
+use moka::sync::Cache;
+use prost::Message;
+use secrecy::ExposeSecret;
+use session::context::QueryContextRef;
+use snafu::{ensure, OptionExt, ResultExt};
+use sql::dialect::GreptimeDbDialect;
+use sql::parser::ParserContext;
+use tonic::metadata::{MetadataMap, MetadataValue};
+use tonic::{Request, Response, Status, Streaming};
+
+use crate::error::{
+    InvalidAuthorizationHeaderSnafu, InvalidPrepareStatementSnafu, InvalidQuerySnafu,
+    ParseSqlSnafu, Result,
+};
+use crate::grpc::flight::to_flight_data_stream;
+use crate::grpc::greptime_handler::{auth, create_query_context, RequestOrigin};
+use crate::grpc::TonicResult;
+use crate::http::authorize::AuthScheme;
+use crate::http::header::GREPTIME_DB_NAME_HEADER_NAME;
+use crate::query_handler::sql::ServerSqlQueryHandlerRef;
+
+/// Max number of prepared statements kept by the server.
+const PREPARED_STATEMENT_CAPACITY: u64 = 4096;
+/// Prepared statements not used in this duration are closed.
+const PREPARED_STATEMENT_IDLE: Duration = Duration::from_secs(30 * 60);
+
+/// Flight SQL service executing queries by the SQL query handler.
+///
+/// Prepared statements are kept by the server instead of sessions, they are closed by
+/// clients or expire after being idle for a while.
+#[derive(Clone)]
+pub struct FlightSqlServiceImpl {
+    query_handler: ServerSqlQueryHandlerRef,
+    user_provider: Option<UserProviderRef>,
+    /// Queries of prepared statements by their handles.
+    prepared_statements: Cache<String, String>,
+    next_handle: Arc<AtomicU64>,
+}
+
+impl FlightSqlServiceImpl {
+    pub fn new(
+        query_handler: ServerSqlQueryHandlerRef,
+        user_provider: Option<UserProviderRef>,
+    ) -> Self {
+        Self {
+            query_handler,
+            user_provider,
+            prepared_statements: Cache::builder()
+                .max_capacity(PREPARED_STATEMENT_CAPACITY)
+                .time_to_idle(PREPARED_STATEMENT_IDLE)
+                .build(),
+            next_handle: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    /// Authenticates the request and creates the query context of it.
+    ///
+    /// Takes the header and the origin instead of the request as streaming requests aren't
+    /// [Sync].
+    async fn query_context(
+        &self,
+        (header, origin): (RequestHeader, RequestOrigin),
+    ) -> Result<QueryContextRef> {
+        let query_ctx = create_query_context(Some(&header), origin);
+        let user_info = auth(self.user_provider.clone(), Some(&header), &query_ctx).await?;
+        query_ctx.set_current_user(user_info);
+        Ok(query_ctx)
+    }
+
+    /// Returns the schema of results of the `query`, the schema is empty if the query
+    /// doesn't return rows.
+    async fn describe(&self, query: &str, query_ctx: QueryContextRef) -> Result<ArrowSchema> {
+        let mut stmts = ParserContext::create_with_dialect(query, &GreptimeDbDialect {})
+            .context(ParseSqlSnafu)?;
+        ensure!(
+            stmts.len() == 1,
+            InvalidQuerySnafu {
+                reason: format!("expect exactly one statement, got {}", stmts.len()),
+            }
+        );
+        let schema = self
+            .query_handler
+            .do_describe(stmts.remove(0), query_ctx)
+            .await?
+            .map(|result| result.schema.arrow_schema().as_ref().clone())
+            .unwrap_or_else(ArrowSchema::empty);
+        Ok(schema)
+    }
+
+    async fn execute(&self, query: &str, query_ctx: QueryContextRef) -> Result<Output> {
+        let mut outputs = self.query_handler.do_query(query, query_ctx).await;
+        ensure!(
+            outputs.len() == 1,
+            InvalidQuerySnafu {
+                reason: format!("expect exactly one statement, got {}", outputs.len()),
+            }
+        );
+        outputs.remove(0)
+    }
+
+    fn prepared_query(&self, handle: &[u8]) -> Result<String> {
+        let handle = String::from_utf8_lossy(handle);
+        self.prepared_statements
+            .get(handle.as_ref())
+            .with_context(|| InvalidPrepareStatementSnafu {
+                err_msg: format!("prepared statement {handle} not found"),
+            })
+    }
+
+    async fn flight_info(
+        &self,
+        query: &str,
+        ticket: Ticket,
+        request: Request<FlightDescriptor>,
+    ) -> TonicResult<Response<FlightInfo>> {
+        let source = request_source(&request)?;
+        let query_ctx = self.query_context(source).await?;
+        let schema = self.describe(query, query_ctx).await?;
+        let info = FlightInfo::new()
+            .try_with_schema(&schema)
+            .map_err(|e| Status::internal(e.to_string()))?
+            .with_endpoint(FlightEndpoint::new().with_ticket(ticket))
+            .with_descriptor(request.into_inner());
+        Ok(Response::new(info))
+    }
+
+    async fn do_get_query(
+        &self,
+        query: &str,
+        request: Request<Ticket>,
+    ) -> TonicResult<Response<<Self as FlightService>::DoGetStream>> {
+        let source = request_source(&request)?;
+        let query_ctx = self.query_context(source).await?;
+        let output = self.execute(query, query_ctx).await?;
+        Ok(Response::new(to_flight_data_stream(
+            output,
+            TracingContext::new(),
+        )))
+    }
+}
+
+#[async_trait]
+impl FlightSqlService for FlightSqlServiceImpl {
+    type FlightService = FlightSqlServiceImpl;
+
+    /// Authenticates the user by the basic authorization header, the credentials are
+    /// returned as a bearer token for following requests.
+    async fn do_handshake(
+        &self,
+        request: Request<Streaming<HandshakeRequest>>,
+    ) -> TonicResult<Response<Pin<Box<dyn Stream<Item = TonicResult<HandshakeResponse>> + Send>>>>
+    {
+        let source = request_source(&request)?;
+        let _ = self.query_context(source).await?;
+        let token = request
+            .metadata()
+            .get(axum::http::header::AUTHORIZATION.as_str())
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.split_once(' '))
+            .map(|(_, credentials)| credentials.to_string());
+
+        let output = futures::stream::once(async {
+            Ok(HandshakeResponse {
+                protocol_version: 0,
+                payload: Default::default(),
+            })
+        });
+        let mut response: Response<Pin<Box<dyn Stream<Item = _> + Send>>> =
+            Response::new(Box::pin(output));
+        if let Some(token) = token {
+            let value = MetadataValue::try_from(format!("Bearer {token}"))
+                .map_err(|_| Status::from(InvalidAuthorizationHeaderSnafu.build()))?;
+            let _ = response
+                .metadata_mut()
+                .insert(axum::http::header::AUTHORIZATION.as_str(), value);
+        }
+        Ok(response)
+    }
+
+    async fn get_flight_info_statement(
+        &self,
+        query: CommandStatementQuery,
+        request: Request<FlightDescriptor>,
+    ) -> TonicResult<Response<FlightInfo>> {
+        let ticket = TicketStatementQuery {
+            statement_handle: query.query.clone().into(),
+        };
+        let ticket = Ticket::new(ticket.as_any().encode_to_vec());
+        self.flight_info(&query.query, ticket, request).await
+    }
+
+    async fn get_flight_info_prepared_statement(
+        &self,
+        cmd: CommandPreparedStatementQuery,
+        request: Request<FlightDescriptor>,
+    ) -> TonicResult<Response<FlightInfo>> {
+        let query = self.prepared_query(&cmd.prepared_statement_handle)?;
+        let ticket = Ticket::new(cmd.as_any().encode_to_vec());
+        self.flight_info(&query, ticket, request).await
+    }
+
+    async fn do_get_statement(
+        &self,
+        ticket: TicketStatementQuery,
+        request: Request<Ticket>,
+    ) -> TonicResult<Response<<Self as FlightService>::DoGetStream>> {
+        let query = String::from_utf8(ticket.statement_handle.to_vec())
+            .map_err(|_| Status::invalid_argument("Invalid Flight ticket"))?;
+        self.do_get_query(&query, request).await
+    }
+
+    async fn do_get_prepared_statement(
+        &self,
+        cmd: CommandPreparedStatementQuery,
+        request: Request<Ticket>,
+    ) -> TonicResult<Response<<Self as FlightService>::DoGetStream>> {
+        let query = self.prepared_query(&cmd.prepared_statement_handle)?;
+        self.do_get_query(&query, request).await
+    }
+
+    async fn do_put_statement_update(
+        &self,
+        cmd: CommandStatementUpdate,
+        request: Request<PeekableFlightDataStream>,
+    ) -> TonicResult<i64> {
+        let source = request_source(&request)?;
+        let query_ctx = self.query_context(source).await?;
+        match self.execute(&cmd.query, query_ctx).await? {
+            Output::AffectedRows(rows) => Ok(rows as i64),
+            Output::Stream(_) | Output::RecordBatches(_) => Err(Status::invalid_argument(
+                "expect a statement without results, use a query instead",
+            )),
+        }
+    }
+
+    async fn do_action_create_prepared_statement(
+        &self,
+        query: ActionCreatePreparedStatementRequest,
+        request: Request<arrow_flight::Action>,
+    ) -> TonicResult<ActionCreatePreparedStatementResult> {
+        let source = request_source(&request)?;
+        let query_ctx = self.query_context(source).await?;
+        let schema = self.describe(&query.query, query_ctx).await?;
+        let IpcMessage(dataset_schema) = SchemaAsIpc::new(&schema, &IpcWriteOptions::default())
+            .try_into()
+            .map_err(|e: datatypes::arrow::error::ArrowError| Status::internal(e.to_string()))?;
+
+        let handle = self.next_handle.fetch_add(1, Ordering::Relaxed).to_string();
+        self.prepared_statements.insert(handle.clone(), query.query);
+
+        Ok(ActionCreatePreparedStatementResult {
+            prepared_statement_handle: handle.into(),
+            dataset_schema,
+            // Parameters aren't supported.
+            parameter_schema: Default::default(),
+        })
+    }
+
+    async fn do_action_close_prepared_statement(
+        &self,
+        query: ActionClosePreparedStatementRequest,
+        _request: Request<arrow_flight::Action>,
+    ) -> TonicResult<()> {
+        let handle = String::from_utf8_lossy(&query.prepared_statement_handle);
+        self.prepared_statements.invalidate(handle.as_ref());
+        Ok(())
+    }
+
+    async fn register_sql_info(&self, _id: i32, _result: &SqlInfo) {}
+}
+
+/// Returns the header and the origin of the `request`.
+fn request_source<T>(request: &Request<T>) -> Result<(RequestHeader, RequestOrigin)> {
+    Ok((
+        request_header(request.metadata())?,
+        RequestOrigin::new(request),
+    ))
+}
+
+/// Converts the metadata of a request to the header of gRPC requests.
+///
+/// Both the basic authorization and the bearer token returned by the handshake are accepted.
+fn request_header(metadata: &MetadataMap) -> Result<RequestHeader> {
+    let dbname = metadata
+        .get(GREPTIME_DB_NAME_HEADER_NAME.as_str())
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default()
+        .to_string();
+    let authorization = match metadata.get(axum::http::header::AUTHORIZATION.as_str()) {
+        Some(value) => {
+            let value = value
+                .to_str()
+                .ok()
+                .context(InvalidAuthorizationHeaderSnafu)?;
+            let value = match value.split_once(' ') {
+                Some((scheme, token)) if scheme.eq_ignore_ascii_case("bearer") => {
+                    format!("Basic {token}")
+                }
+                _ => value.to_string(),
+            };
+            let AuthScheme::Basic(username, password) = AuthScheme::try_from(value.as_str())?;
+            Some(AuthHeader {
+                auth_scheme: Some(GrpcAuthScheme::Basic(Basic {
+                    username,
+                    password: password.expose_secret().clone(),
+                })),
+            })
+        }
+        None => None,
+    };
+
+    Ok(RequestHeader {
+        dbname,
+        authorization,
+        ..Default::default()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_request_header() {
+        let header = request_header(&MetadataMap::new()).unwrap();
+        assert!(header.dbname.is_empty());
+        assert!(header.authorization.is_none());
+
+        // base64 of "user:pwd"
+        for value in ["Basic dXNlcjpwd2Q=", "Bearer dXNlcjpwd2Q="] {
+            let mut metadata = MetadataMap::new();
+            let _ = metadata.insert("x-greptime-db-name", MetadataValue::from_static("db"));
+            let _ = metadata.insert("authorization", MetadataValue::from_static(value));
+            let header = request_header(&metadata).unwrap();
+            assert_eq!("db", header.dbname);
+            let Some(GrpcAuthScheme::Basic(basic)) = header.authorization.unwrap().auth_scheme
+            else {
+                unreachable!()
+            };
+            assert_eq!(("user", "pwd"), (&*basic.username, &*basic.password));
+        }
+
+        let mut metadata = MetadataMap::new();
+        let _ = metadata.insert("authorization", MetadataValue::from_static("Bearer token"));
+        assert!(request_header(&metadata).is_err());
+    }
+}
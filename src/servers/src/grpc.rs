@@ -15,6 +15,7 @@
 mod database;
 pub mod flight;
 pub mod greptime_handler;
+pub mod otlp;
 pub mod prom_query_gateway;
 pub mod region_server;
 
@@ -40,6 +41,7 @@ use common_runtime::Runtime;
 use common_telemetry::logging::info;
 use common_telemetry::{error, warn};
 use futures::FutureExt;
+use opentelemetry_proto::tonic::collector::metrics::v1::metrics_service_server::MetricsServiceServer;
 use snafu::{ensure, OptionExt, ResultExt};
 use tokio::net::TcpListener;
 use tokio::sync::oneshot::{self, Receiver, Sender};
@@ -49,6 +51,7 @@ use tonic::{Request, Response, Status};
 use tonic_reflection::server::{ServerReflection, ServerReflectionServer};
 
 use self::flight::{FlightCraftRef, FlightCraftWrapper};
+use self::otlp::OtlpMetricsService;
 use self::prom_query_gateway::PrometheusGatewayService;
 use self::region_server::{RegionServerHandlerRef, RegionServerRequestHandler};
 use crate::error::{
@@ -58,9 +61,10 @@ use crate::grpc::database::DatabaseService;
 use crate::grpc::greptime_handler::GreptimeRequestHandler;
 use crate::prometheus_handler::PrometheusHandlerRef;
 use crate::query_handler::grpc::ServerGrpcQueryHandlerRef;
+use crate::query_handler::OpenTelemetryProtocolHandlerRef;
 use crate::server::Server;
 
-type TonicResult<T> = std::result::Result<T, Status>;
+pub(crate) type TonicResult<T> = std::result::Result<T, Status>;
 
 pub struct GrpcServer {
     config: GrpcServerConfig,
@@ -81,6 +85,8 @@ pub struct GrpcServer {
     flight_handler: Option<FlightCraftRef>,
     /// Handler for [RegionServer].
     region_server_handler: Option<RegionServerRequestHandler>,
+    /// Handler for OTLP/gRPC metrics ([OtlpMetricsService]). Only present for frontend server.
+    otlp_handler: Option<OpenTelemetryProtocolHandlerRef>,
 }
 
 /// Grpc Server configuration
@@ -125,9 +131,24 @@ impl GrpcServer {
             prometheus_handler,
             flight_handler,
             region_server_handler,
+            otlp_handler: None,
         }
     }
 
+    /// Serves OTLP metrics by the `handler`.
+    pub fn with_otlp_handler(mut self, handler: OpenTelemetryProtocolHandlerRef) -> Self {
+        self.otlp_handler = Some(handler);
+        self
+    }
+
+    /// Limits requests of the [DatabaseService] service and Arrow Flight.
+    pub fn with_rate_limiter(mut self, rate_limiter: RateLimiterRef) -> Self {
+        self.database_handler = self
+            .database_handler
+            .map(|handler| handler.with_rate_limiter(rate_limiter));
+        self
+    }
+
     #[cfg(feature = "testing")]
     pub fn create_flight_service(&self) -> FlightServiceServer<impl FlightService> {
         FlightServiceServer::new(FlightCraftWrapper(self.flight_handler.clone().unwrap()))
@@ -245,6 +266,16 @@ impl Server for GrpcServer {
             builder = builder
                 .add_service(self.create_prom_query_gateway_service(prometheus_handler.clone()))
         }
+        if let Some(otlp_handler) = &self.otlp_handler {
+            builder = builder.add_service(
+                MetricsServiceServer::new(OtlpMetricsService::new(
+                    otlp_handler.clone(),
+                    self.user_provider.clone(),
+                ))
+                .max_decoding_message_size(max_recv_message_size)
+                .max_encoding_message_size(max_send_message_size),
+            )
+        }
         if let Some(flight_handler) = &self.flight_handler {
             builder = builder.add_service(
                 FlightServiceServer::new(FlightCraftWrapper(flight_handler.clone()))
@@ -21,7 +21,7 @@ use common_query::Output;
 use futures::StreamExt;
 use tonic::{Request, Response, Status, Streaming};
 
-use crate::grpc::greptime_handler::GreptimeRequestHandler;
+use crate::grpc::greptime_handler::{GreptimeRequestHandler, RequestOrigin};
 use crate::grpc::TonicResult;
 
 pub(crate) struct DatabaseService {
@@ -40,8 +40,9 @@ impl GreptimeDatabase for DatabaseService {
         &self,
         request: Request<GreptimeRequest>,
     ) -> TonicResult<Response<GreptimeResponse>> {
+        let origin = RequestOrigin::new(&request);
         let request = request.into_inner();
-        let output = self.handler.handle_request(request).await?;
+        let output = self.handler.handle_request(request, origin).await?;
         let message = match output {
             Output::AffectedRows(rows) => GreptimeResponse {
                 header: Some(ResponseHeader {
@@ -65,10 +66,11 @@ impl GreptimeDatabase for DatabaseService {
     ) -> Result<Response<GreptimeResponse>, Status> {
         let mut affected_rows = 0;
 
+        let origin = RequestOrigin::new(&request);
         let mut stream = request.into_inner();
         while let Some(request) = stream.next().await {
             let request = request?;
-            let output = self.handler.handle_request(request).await?;
+            let output = self.handler.handle_request(request, origin.clone()).await?;
             match output {
                 Output::AffectedRows(rows) => affected_rows += rows,
                 Output::Stream(_) | Output::RecordBatches(_) => {
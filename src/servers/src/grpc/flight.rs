@@ -12,29 +12,33 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+pub(crate) mod bulk_insert;
 mod stream;
 
 use std::pin::Pin;
 use std::sync::Arc;
 
-use api::v1::GreptimeRequest;
+use api::v1::greptime_request::Request as GreptimeRequestKind;
+use api::v1::{AffectedRows, FlightMetadata, GreptimeRequest, InsertRequests};
 use arrow_flight::flight_service_server::FlightService;
 use arrow_flight::{
     Action, ActionType, Criteria, Empty, FlightData, FlightDescriptor, FlightInfo,
     HandshakeRequest, HandshakeResponse, PutResult, SchemaResult, Ticket,
 };
 use async_trait::async_trait;
-use common_grpc::flight::{FlightEncoder, FlightMessage};
+use common_grpc::flight::{FlightDecoder, FlightEncoder, FlightMessage};
 use common_query::Output;
 use common_telemetry::tracing_context::TracingContext;
-use futures::Stream;
+use futures::{Stream, StreamExt};
 use prost::Message;
-use snafu::ResultExt;
+use snafu::{OptionExt, ResultExt};
 use tonic::{Request, Response, Status, Streaming};
 
 use crate::error;
+pub use crate::grpc::flight::bulk_insert::SEMANTIC_TYPE_KEY;
+use crate::grpc::flight::bulk_insert::{to_insert_request, BulkInsertTarget};
 pub use crate::grpc::flight::stream::FlightRecordBatchStream;
-use crate::grpc::greptime_handler::GreptimeRequestHandler;
+use crate::grpc::greptime_handler::{GreptimeRequestHandler, RequestOrigin};
 use crate::grpc::TonicResult;
 
 pub type TonicStream<T> = Pin<Box<dyn Stream<Item = TonicResult<T>> + Send + Sync + 'static>>;
@@ -46,6 +50,13 @@ pub trait FlightCraft: Send + Sync + 'static {
         &self,
         request: Request<Ticket>,
     ) -> TonicResult<Response<TonicStream<FlightData>>>;
+
+    async fn do_put(
+        &self,
+        _request: Request<Streaming<FlightData>>,
+    ) -> TonicResult<Response<TonicStream<PutResult>>> {
+        Err(Status::unimplemented("Not yet implemented"))
+    }
 }
 
 pub type FlightCraftRef = Arc<dyn FlightCraft>;
@@ -66,6 +77,13 @@ impl FlightCraft for FlightCraftRef {
     ) -> TonicResult<Response<TonicStream<FlightData>>> {
         (**self).do_get(request).await
     }
+
+    async fn do_put(
+        &self,
+        request: Request<Streaming<FlightData>>,
+    ) -> TonicResult<Response<TonicStream<PutResult>>> {
+        (**self).do_put(request).await
+    }
 }
 
 #[async_trait]
@@ -112,9 +130,9 @@ impl<T: FlightCraft> FlightService for FlightCraftWrapper<T> {
 
     async fn do_put(
         &self,
-        _: Request<Streaming<FlightData>>,
+        request: Request<Streaming<FlightData>>,
     ) -> TonicResult<Response<Self::DoPutStream>> {
-        Err(Status::unimplemented("Not yet implemented"))
+        self.0.do_put(request).await
     }
 
     type DoExchangeStream = TonicStream<FlightData>;
@@ -148,19 +166,86 @@ impl FlightCraft for GreptimeRequestHandler {
         &self,
         request: Request<Ticket>,
     ) -> TonicResult<Response<TonicStream<FlightData>>> {
+        let origin = RequestOrigin::new(&request);
         let ticket = request.into_inner().ticket;
         let request =
             GreptimeRequest::decode(ticket.as_ref()).context(error::InvalidFlightTicketSnafu)?;
 
-        let output = self.handle_request(request).await?;
+        let output = self.handle_request(request, origin).await?;
 
         let stream: Pin<Box<dyn Stream<Item = Result<FlightData, Status>> + Send + Sync>> =
             to_flight_data_stream(output, TracingContext::new());
         Ok(Response::new(stream))
     }
+
+    /// Inserts the record batches in the stream, see the `bulk_insert` module for the protocol.
+    /// Returns a [PutResult] with the affected rows for each record batch.
+    async fn do_put(
+        &self,
+        request: Request<Streaming<FlightData>>,
+    ) -> TonicResult<Response<TonicStream<PutResult>>> {
+        let origin = RequestOrigin::new(&request);
+        let mut stream = request.into_inner();
+        let mut decoder = FlightDecoder::default();
+        let mut target = None;
+        let mut results = vec![];
+
+        while let Some(flight_data) = stream.next().await {
+            let flight_data = flight_data?;
+            if target.is_none() {
+                let descriptor = flight_data.flight_descriptor.as_ref().context(
+                    error::InvalidBulkInsertSnafu {
+                        reason: "expect a FlightDescriptor in the first FlightData",
+                    },
+                )?;
+                target = Some(BulkInsertTarget::try_from_descriptor(descriptor)?);
+            }
+            let target = target.as_ref().unwrap();
+
+            let batch = match decoder
+                .try_decode(flight_data)
+                .context(error::DecodeFlightDataSnafu)?
+            {
+                FlightMessage::Schema(_) => continue,
+                FlightMessage::Recordbatch(batch) => batch,
+                FlightMessage::AffectedRows(_) | FlightMessage::Metrics(_) => {
+                    return Err(error::InvalidBulkInsertSnafu {
+                        reason: "expect record batches",
+                    }
+                    .build()
+                    .into())
+                }
+            };
+            if batch.num_rows() == 0 {
+                continue;
+            }
+
+            let request = GreptimeRequest {
+                header: target.header.clone(),
+                request: Some(GreptimeRequestKind::Inserts(InsertRequests {
+                    inserts: vec![to_insert_request(&target.table_name, &batch)?],
+                })),
+            };
+            let affected_rows = match self.handle_request(request, origin.clone()).await? {
+                Output::AffectedRows(rows) => rows,
+                _ => return Err(Status::internal("Expect affected rows of inserts")),
+            };
+            let metadata = FlightMetadata {
+                affected_rows: Some(AffectedRows {
+                    value: affected_rows as _,
+                }),
+            };
+            results.push(Ok(PutResult {
+                app_metadata: metadata.encode_to_vec().into(),
+            }));
+        }
+
+        let stream = tokio_stream::iter(results);
+        Ok(Response::new(Box::pin(stream)))
+    }
 }
 
-fn to_flight_data_stream(
+pub(crate) fn to_flight_data_stream(
     output: Output,
     tracing_context: TracingContext,
 ) -> TonicStream<FlightData> {
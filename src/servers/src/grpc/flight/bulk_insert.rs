@@ -0,0 +1,222 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Bulk insert through Flight `DoPut`, so writers can send Arrow IPC payloads instead of
+//! row-based protobuf.
+//!
+//! The first [FlightData] must carry a [FlightDescriptor] whose path is the table name and
+//! whose cmd is an optional encoded [RequestHeader] for the database and authorization.
+//! Every record batch in the stream is inserted as a columnar [InsertRequest].
+
+use api::helper::{push_vals, ColumnDataTypeWrapper};
+use api::v1::{Column, InsertRequest, RequestHeader, SemanticType};
+use arrow_flight::FlightDescriptor;
+use common_recordbatch::RecordBatch;
+use datatypes::schema::ColumnSchema;
+use prost::Message;
+use snafu::{OptionExt, ResultExt};
+
+use crate::error::{ColumnDataTypeSnafu, InvalidBulkInsertSnafu, Result};
+
+/// Key in the metadata of an Arrow field to specify the semantic type of the column,
+/// the value is one of `TAG`, `FIELD` and `TIMESTAMP`.
+pub const SEMANTIC_TYPE_KEY: &str = "greptime:semantic_type";
+
+/// Target of a bulk insert parsed from the [FlightDescriptor].
+#[derive(Debug, PartialEq)]
+pub(crate) struct BulkInsertTarget {
+    pub(crate) table_name: String,
+    pub(crate) header: Option<RequestHeader>,
+}
+
+impl BulkInsertTarget {
+    pub(crate) fn try_from_descriptor(descriptor: &FlightDescriptor) -> Result<Self> {
+        let table_name = match &descriptor.path[..] {
+            [table_name] if !table_name.is_empty() => table_name.clone(),
+            _ => {
+                return InvalidBulkInsertSnafu {
+                    reason: "expect the path of FlightDescriptor to be the table name",
+                }
+                .fail()
+            }
+        };
+        let header = if descriptor.cmd.is_empty() {
+            None
+        } else {
+            let header = RequestHeader::decode(descriptor.cmd.as_ref()).map_err(|e| {
+                InvalidBulkInsertSnafu {
+                    reason: format!("invalid request header in FlightDescriptor: {e}"),
+                }
+                .build()
+            })?;
+            Some(header)
+        };
+
+        Ok(Self { table_name, header })
+    }
+}
+
+/// Converts the record batch to a columnar [InsertRequest] of `table_name`.
+pub(crate) fn to_insert_request(table_name: &str, batch: &RecordBatch) -> Result<InsertRequest> {
+    let column_schemas = batch.schema.column_schemas();
+    let mut semantic_types = column_schemas
+        .iter()
+        .map(semantic_type)
+        .collect::<Result<Vec<_>>>()?;
+    // Uses the first timestamp column as the time index if no column is specified.
+    if !semantic_types.contains(&SemanticType::Timestamp) {
+        let index = column_schemas
+            .iter()
+            .position(|column| column.data_type.is_timestamp())
+            .context(InvalidBulkInsertSnafu {
+                reason: "expect a timestamp column",
+            })?;
+        semantic_types[index] = SemanticType::Timestamp;
+    }
+
+    build_insert_request(table_name, batch, semantic_types)
+}
+
+/// Converts the record batch to a columnar [InsertRequest] of `table_name` with the
+/// `semantic_types` of its columns.
+pub(crate) fn build_insert_request(
+    table_name: &str,
+    batch: &RecordBatch,
+    semantic_types: Vec<SemanticType>,
+) -> Result<InsertRequest> {
+    let column_schemas = batch.schema.column_schemas();
+    let row_count = batch.num_rows();
+    let columns = column_schemas
+        .iter()
+        .zip(semantic_types)
+        .zip(batch.columns())
+        .map(|((column_schema, semantic_type), vector)| {
+            let (datatype, datatype_extension) =
+                ColumnDataTypeWrapper::try_from(column_schema.data_type.clone())
+                    .context(ColumnDataTypeSnafu {
+                        column: &column_schema.name,
+                    })?
+                    .to_parts();
+            let mut column = Column {
+                column_name: column_schema.name.clone(),
+                semantic_type: semantic_type as i32,
+                datatype: datatype as i32,
+                datatype_extension,
+                ..Default::default()
+            };
+            push_vals(&mut column, 0, vector.clone());
+            Ok(column)
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(InsertRequest {
+        table_name: table_name.to_string(),
+        columns,
+        row_count: row_count as u32,
+    })
+}
+
+fn semantic_type(column_schema: &ColumnSchema) -> Result<SemanticType> {
+    if let Some(value) = column_schema.metadata().get(SEMANTIC_TYPE_KEY) {
+        let semantic_type = match value.to_uppercase().as_str() {
+            "TAG" => SemanticType::Tag,
+            "FIELD" => SemanticType::Field,
+            "TIMESTAMP" => SemanticType::Timestamp,
+            _ => {
+                return InvalidBulkInsertSnafu {
+                    reason: format!(
+                        "invalid semantic type {value} of column {}",
+                        column_schema.name
+                    ),
+                }
+                .fail()
+            }
+        };
+        return Ok(semantic_type);
+    }
+
+    if column_schema.is_time_index() {
+        Ok(SemanticType::Timestamp)
+    } else {
+        Ok(SemanticType::Field)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::sync::Arc;
+
+    use datatypes::prelude::ConcreteDataType;
+    use datatypes::schema::Schema;
+    use datatypes::vectors::{StringVector, TimestampMillisecondVector, VectorRef};
+
+    use super::*;
+
+    #[test]
+    fn test_bulk_insert_target() {
+        let descriptor = FlightDescriptor::new_path(vec!["foo".to_string()]);
+        let target = BulkInsertTarget::try_from_descriptor(&descriptor).unwrap();
+        assert_eq!("foo", target.table_name);
+        assert!(target.header.is_none());
+
+        let header = RequestHeader {
+            dbname: "greptime-public".to_string(),
+            ..Default::default()
+        };
+        let mut descriptor = FlightDescriptor::new_path(vec!["foo".to_string()]);
+        descriptor.cmd = header.encode_to_vec().into();
+        let target = BulkInsertTarget::try_from_descriptor(&descriptor).unwrap();
+        assert_eq!(Some(header), target.header);
+
+        let descriptor = FlightDescriptor::new_path(vec!["a".to_string(), "b".to_string()]);
+        assert!(BulkInsertTarget::try_from_descriptor(&descriptor).is_err());
+    }
+
+    #[test]
+    fn test_to_insert_request() {
+        let host =
+            ColumnSchema::new("host", ConcreteDataType::string_datatype(), true).with_metadata(
+                HashMap::from([(SEMANTIC_TYPE_KEY.to_string(), "tag".to_string())]),
+            );
+        let ts = ColumnSchema::new(
+            "ts",
+            ConcreteDataType::timestamp_millisecond_datatype(),
+            false,
+        );
+        let schema = Arc::new(Schema::new(vec![host, ts]));
+        let columns: Vec<VectorRef> = vec![
+            Arc::new(StringVector::from(vec![Some("a"), None])),
+            Arc::new(TimestampMillisecondVector::from_vec(vec![1, 2])),
+        ];
+        let batch = RecordBatch::new(schema, columns).unwrap();
+
+        let request = to_insert_request("foo", &batch).unwrap();
+        assert_eq!("foo", request.table_name);
+        assert_eq!(2, request.row_count);
+        let host = &request.columns[0];
+        assert_eq!(SemanticType::Tag as i32, host.semantic_type);
+        assert_eq!(
+            vec!["a".to_string()],
+            host.values.as_ref().unwrap().string_values
+        );
+        assert_eq!(vec![0b10], host.null_mask);
+        let ts = &request.columns[1];
+        assert_eq!(SemanticType::Timestamp as i32, ts.semantic_type);
+        assert_eq!(
+            vec![1, 2],
+            ts.values.as_ref().unwrap().timestamp_millisecond_values
+        );
+    }
+}
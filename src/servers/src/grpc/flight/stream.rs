@@ -42,9 +42,28 @@ pub struct FlightRecordBatchStream {
 
 impl FlightRecordBatchStream {
     pub fn new(recordbatches: SendableRecordBatchStream, tracing_context: TracingContext) -> Self {
+        Self::new_inner(recordbatches, tracing_context, false)
+    }
+
+    /// Creates a stream that also sends metrics of `recordbatches` after all
+    /// record batches, so the receiver can collect metrics of the execution.
+    ///
+    /// Only clients that can decode [FlightMessage::Metrics] should receive it.
+    pub fn with_metrics(
+        recordbatches: SendableRecordBatchStream,
+        tracing_context: TracingContext,
+    ) -> Self {
+        Self::new_inner(recordbatches, tracing_context, true)
+    }
+
+    fn new_inner(
+        recordbatches: SendableRecordBatchStream,
+        tracing_context: TracingContext,
+        send_metrics: bool,
+    ) -> Self {
         let (tx, rx) = mpsc::channel::<TonicResult<FlightMessage>>(1);
         let join_handle = common_runtime::spawn_read(async move {
-            Self::flight_data_stream(recordbatches, tx)
+            Self::flight_data_stream(recordbatches, tx, send_metrics)
                 .trace(tracing_context.attach(info_span!("flight_data_stream")))
                 .await
         });
@@ -59,6 +78,7 @@ impl FlightRecordBatchStream {
     async fn flight_data_stream(
         mut recordbatches: SendableRecordBatchStream,
         mut tx: Sender<TonicResult<FlightMessage>>,
+        send_metrics: bool,
     ) {
         let schema = recordbatches.schema();
         if let Err(e) = tx.send(Ok(FlightMessage::Schema(schema))).await {
@@ -83,6 +103,15 @@ impl FlightRecordBatchStream {
                 }
             }
         }
+
+        if !send_metrics {
+            return;
+        }
+        if let Some(metrics) = recordbatches.metrics() {
+            if let Err(e) = tx.send(Ok(FlightMessage::Metrics(metrics))).await {
+                warn!("stop sending Flight data, err: {e}");
+            }
+        }
     }
 }
 
@@ -14,6 +14,7 @@
 
 //! Handler for Greptime Database service. It's implemented by frontend.
 
+use std::net::SocketAddr;
 use std::sync::Arc;
 use std::time::Instant;
 
@@ -28,19 +29,22 @@ use common_error::status_code::StatusCode;
 use common_query::Output;
 use common_runtime::Runtime;
 use common_telemetry::logging;
-use session::context::{QueryContextBuilder, QueryContextRef};
+use session::context::{Channel, QueryContextBuilder, QueryContextRef};
 use snafu::{OptionExt, ResultExt};
+use tonic::Request;
 
 use crate::error::Error::UnsupportedAuthScheme;
 use crate::error::{AuthSnafu, InvalidQuerySnafu, JoinTaskSnafu, NotFoundAuthHeaderSnafu, Result};
 use crate::metrics::{METRIC_AUTH_FAILURE, METRIC_SERVER_GRPC_DB_REQUEST_TIMER};
 use crate::query_handler::grpc::ServerGrpcQueryHandlerRef;
+use crate::rate_limit::RateLimiterRef;
 
 #[derive(Clone)]
 pub struct GreptimeRequestHandler {
     handler: ServerGrpcQueryHandlerRef,
     user_provider: Option<UserProviderRef>,
     runtime: Arc<Runtime>,
+    rate_limiter: Option<RateLimiterRef>,
 }
 
 impl GreptimeRequestHandler {
@@ -53,18 +57,32 @@ impl GreptimeRequestHandler {
             handler,
             user_provider,
             runtime,
+            rate_limiter: None,
         }
     }
 
-    pub(crate) async fn handle_request(&self, request: GreptimeRequest) -> Result<Output> {
+    /// Limits requests after they are authenticated.
+    pub fn with_rate_limiter(mut self, rate_limiter: RateLimiterRef) -> Self {
+        self.rate_limiter = Some(rate_limiter);
+        self
+    }
+
+    pub(crate) async fn handle_request(
+        &self,
+        request: GreptimeRequest,
+        origin: RequestOrigin,
+    ) -> Result<Output> {
         let query = request.request.context(InvalidQuerySnafu {
             reason: "Expecting non-empty GreptimeRequest.",
         })?;
 
         let header = request.header.as_ref();
-        let query_ctx = create_query_context(header);
+        let query_ctx = create_query_context(header, origin);
         let user_info = auth(self.user_provider.clone(), header, &query_ctx).await?;
         query_ctx.set_current_user(user_info);
+        if let Some(rate_limiter) = &self.rate_limiter {
+            rate_limiter.check(&query_ctx)?;
+        }
 
         let handler = self.handler.clone();
         let request_type = request_type(&query).to_string();
@@ -138,7 +156,30 @@ pub(crate) async fn auth(
     })
 }
 
-pub(crate) fn create_query_context(header: Option<&RequestHeader>) -> QueryContextRef {
+/// Where a gRPC request comes from.
+#[derive(Debug, Default, Clone)]
+pub(crate) struct RequestOrigin {
+    client_addr: Option<SocketAddr>,
+    user_agent: Option<String>,
+}
+
+impl RequestOrigin {
+    pub(crate) fn new<T>(request: &Request<T>) -> Self {
+        Self {
+            client_addr: request.remote_addr(),
+            user_agent: request
+                .metadata()
+                .get(axum::http::header::USER_AGENT.as_str())
+                .and_then(|value| value.to_str().ok())
+                .map(|value| value.to_string()),
+        }
+    }
+}
+
+pub(crate) fn create_query_context(
+    header: Option<&RequestHeader>,
+    origin: RequestOrigin,
+) -> QueryContextRef {
     let (catalog, schema) = header
         .map(|header| {
             // We provide dbname field in newer versions of protos/sdks
@@ -165,6 +206,9 @@ pub(crate) fn create_query_context(header: Option<&RequestHeader>) -> QueryConte
     QueryContextBuilder::default()
         .current_catalog(catalog.to_string())
         .current_schema(schema.to_string())
+        .channel(Channel::Grpc)
+        .client_addr(origin.client_addr)
+        .user_agent(origin.user_agent)
         .build()
 }
 
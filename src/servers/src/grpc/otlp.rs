@@ -0,0 +1,140 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! OTLP/gRPC metrics receiver, so the OpenTelemetry Collector can export metrics by
+//! either OTLP/HTTP or OTLP/gRPC.
+//!
+//! Like the HTTP API, the database is selected by the `x-greptime-db-name` metadata and
+//! the user is authenticated by the `authorization` metadata.
+
+use api::v1::auth_header::AuthScheme as GrpcAuthScheme;
+use api::v1::{AuthHeader, Basic, RequestHeader};
+use async_trait::async_trait;
+use auth::UserProviderRef;
+use opentelemetry_proto::tonic::collector::metrics::v1::metrics_service_server::MetricsService;
+use opentelemetry_proto::tonic::collector::metrics::v1::{
+    ExportMetricsServiceRequest, ExportMetricsServiceResponse,
+};
+use secrecy::ExposeSecret;
+use snafu::OptionExt;
+use tonic::metadata::MetadataMap;
+use tonic::{Request, Response};
+
+use crate::error::{InvalidAuthorizationHeaderSnafu, Result};
+use crate::grpc::greptime_handler::{auth, create_query_context, RequestOrigin};
+use crate::grpc::TonicResult;
+use crate::http::authorize::AuthScheme;
+use crate::http::header::GREPTIME_DB_NAME_HEADER_NAME;
+use crate::query_handler::OpenTelemetryProtocolHandlerRef;
+
+pub struct OtlpMetricsService {
+    handler: OpenTelemetryProtocolHandlerRef,
+    user_provider: Option<UserProviderRef>,
+}
+
+impl OtlpMetricsService {
+    pub fn new(
+        handler: OpenTelemetryProtocolHandlerRef,
+        user_provider: Option<UserProviderRef>,
+    ) -> Self {
+        Self {
+            handler,
+            user_provider,
+        }
+    }
+}
+
+#[async_trait]
+impl MetricsService for OtlpMetricsService {
+    async fn export(
+        &self,
+        request: Request<ExportMetricsServiceRequest>,
+    ) -> TonicResult<Response<ExportMetricsServiceResponse>> {
+        let header = request_header(request.metadata())?;
+        let query_ctx = create_query_context(Some(&header), RequestOrigin::new(&request));
+        let user_info = auth(self.user_provider.clone(), Some(&header), &query_ctx).await?;
+        query_ctx.set_current_user(user_info);
+
+        let response = self
+            .handler
+            .metrics(request.into_inner(), query_ctx)
+            .await?;
+        Ok(Response::new(response))
+    }
+}
+
+/// Converts the metadata of a request to the header of gRPC requests.
+fn request_header(metadata: &MetadataMap) -> Result<RequestHeader> {
+    let dbname = metadata
+        .get(GREPTIME_DB_NAME_HEADER_NAME.as_str())
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default()
+        .to_string();
+    let authorization = match metadata.get(axum::http::header::AUTHORIZATION.as_str()) {
+        Some(value) => {
+            let value = value
+                .to_str()
+                .ok()
+                .context(InvalidAuthorizationHeaderSnafu)?;
+            let AuthScheme::Basic(username, password) = AuthScheme::try_from(value)?;
+            Some(AuthHeader {
+                auth_scheme: Some(GrpcAuthScheme::Basic(Basic {
+                    username,
+                    password: password.expose_secret().clone(),
+                })),
+            })
+        }
+        None => None,
+    };
+
+    Ok(RequestHeader {
+        dbname,
+        authorization,
+        ..Default::default()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use tonic::metadata::MetadataValue;
+
+    use super::*;
+
+    #[test]
+    fn test_request_header() {
+        let header = request_header(&MetadataMap::new()).unwrap();
+        assert!(header.dbname.is_empty());
+        assert!(header.authorization.is_none());
+
+        let mut metadata = MetadataMap::new();
+        let _ = metadata.insert(
+            "x-greptime-db-name",
+            MetadataValue::from_static("greptime-db"),
+        );
+        // base64 of "user:pwd"
+        let _ = metadata.insert(
+            "authorization",
+            MetadataValue::from_static("Basic dXNlcjpwd2Q="),
+        );
+        let header = request_header(&metadata).unwrap();
+        assert_eq!("greptime-db", header.dbname);
+        let Some(GrpcAuthScheme::Basic(basic)) = header.authorization.unwrap().auth_scheme else {
+            unreachable!()
+        };
+        assert_eq!(("user", "pwd"), (&*basic.username, &*basic.password));
+
+        let _ = metadata.insert("authorization", MetadataValue::from_static("Bearer token"));
+        assert!(request_header(&metadata).is_err());
+    }
+}
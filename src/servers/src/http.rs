@@ -13,9 +13,11 @@
 // limitations under the License.
 
 pub mod authorize;
+pub mod file_result;
 pub mod handler;
 pub mod header;
 pub mod influxdb;
+pub mod loki;
 pub mod mem_prof;
 pub mod opentsdb;
 pub mod otlp;
@@ -23,6 +25,9 @@ pub mod pprof;
 pub mod prom_store;
 pub mod prometheus;
 pub mod script;
+pub mod stream_result;
+pub mod tail;
+pub mod upload;
 
 #[cfg(feature = "dashboard")]
 mod dashboard;
@@ -30,6 +35,7 @@ pub mod influxdb_result_v1;
 
 use std::fmt::Display;
 use std::net::SocketAddr;
+use std::sync::Arc;
 use std::time::{Duration, Instant};
 
 use aide::axum::{routing as apirouting, ApiRouter, IntoApiResponse};
@@ -53,10 +59,12 @@ use common_time::timestamp::TimeUnit;
 use common_time::Timestamp;
 use datatypes::data_type::DataType;
 use futures::FutureExt;
+use hyper::server::accept;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use snafu::{ensure, ResultExt};
+use tokio::net::TcpListener;
 use tokio::sync::oneshot::{self, Sender};
 use tokio::sync::Mutex;
 use tower::timeout::TimeoutLayer;
@@ -65,11 +73,14 @@ use tower_http::trace::TraceLayer;
 
 use self::authorize::AuthState;
 use crate::configurator::ConfiguratorRef;
-use crate::error::{AlreadyStartedSnafu, Error, Result, StartHttpSnafu, ToJsonSnafu};
+use crate::error::{
+    AlreadyStartedSnafu, Error, InternalIoSnafu, Result, StartHttpSnafu, TcpBindSnafu, ToJsonSnafu,
+};
 use crate::http::influxdb::{influxdb_health, influxdb_ping, influxdb_write_v1, influxdb_write_v2};
 use crate::http::influxdb_result_v1::InfluxdbV1Response;
 use crate::http::prometheus::{
-    format_query, instant_query, label_values_query, labels_query, range_query, series_query,
+    format_query, instant_query, label_values_query, labels_query, parse_query, range_query,
+    series_query,
 };
 use crate::metrics::{
     HTTP_TRACK_METRICS, METRIC_HTTP_REQUESTS_ELAPSED, METRIC_HTTP_REQUESTS_TOTAL,
@@ -79,10 +90,14 @@ use crate::prometheus_handler::PrometheusHandlerRef;
 use crate::query_handler::grpc::ServerGrpcQueryHandlerRef;
 use crate::query_handler::sql::ServerSqlQueryHandlerRef;
 use crate::query_handler::{
-    InfluxdbLineProtocolHandlerRef, OpenTelemetryProtocolHandlerRef, OpentsdbProtocolHandlerRef,
-    PromStoreProtocolHandlerRef, ScriptHandlerRef,
+    InfluxdbLineProtocolHandlerRef, LokiProtocolHandlerRef, OpenTelemetryProtocolHandlerRef,
+    OpentsdbProtocolHandlerRef, PromStoreProtocolHandlerRef, ScriptHandlerRef, UploadHandlerRef,
 };
+use crate::query_registry::QueryRegistryRef;
+use crate::rate_limit::{self, RateLimiterRef};
 use crate::server::Server;
+use crate::tail::TailHubRef;
+use crate::tls::{maybe_watch_tls_config, tls_incoming, ReloadableTlsServerConfig, TlsOption};
 
 pub const HTTP_API_VERSION: &str = "v1";
 pub const HTTP_API_PREFIX: &str = "/v1/";
@@ -91,6 +106,8 @@ const DEFAULT_BODY_LIMIT: ReadableSize = ReadableSize::mb(64);
 
 // TODO(fys): This is a temporary workaround, it will be improved later
 pub static PUBLIC_APIS: [&str; 2] = ["/v1/influxdb/ping", "/v1/influxdb/health"];
+/// Path of the influxdb v2 write API under the influxdb APIs.
+pub(crate) const INFLUXDB_V2_WRITE_PATH: &str = "/api/v2/write";
 
 #[derive(Default)]
 pub struct HttpServer {
@@ -102,12 +119,17 @@ pub struct HttpServer {
     prom_handler: Option<PromStoreProtocolHandlerRef>,
     prometheus_handler: Option<PrometheusHandlerRef>,
     otlp_handler: Option<OpenTelemetryProtocolHandlerRef>,
+    loki_handler: Option<LokiProtocolHandlerRef>,
+    upload_handler: Option<UploadHandlerRef>,
     script_handler: Option<ScriptHandlerRef>,
+    tail_hub: Option<TailHubRef>,
     shutdown_tx: Mutex<Option<Sender<()>>>,
     user_provider: Option<UserProviderRef>,
     metrics_handler: Option<MetricsHandler>,
     greptime_config_options: Option<String>,
     plugins: Plugins,
+    query_registry: QueryRegistryRef,
+    rate_limiter: Option<RateLimiterRef>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -122,6 +144,9 @@ pub struct HttpOptions {
     pub disable_dashboard: bool,
 
     pub body_limit: ReadableSize,
+
+    /// Serves HTTPS if TLS is enabled.
+    pub tls: TlsOption,
 }
 
 impl Default for HttpOptions {
@@ -131,6 +156,7 @@ impl Default for HttpOptions {
             timeout: Duration::from_secs(30),
             disable_dashboard: false,
             body_limit: DEFAULT_BODY_LIMIT,
+            tls: TlsOption::default(),
         }
     }
 }
@@ -253,6 +279,9 @@ pub struct GreptimedbV1Response {
     output: Vec<JsonOutput>,
     #[serde(skip_serializing_if = "Option::is_none")]
     execution_time_ms: Option<u64>,
+    /// Suggested time in milliseconds to wait before retrying the request.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    retry_after_ms: Option<u64>,
 }
 
 impl GreptimedbV1Response {
@@ -269,6 +298,7 @@ impl GreptimedbV1Response {
             code: code as u32,
             output: vec![],
             execution_time_ms: None,
+            retry_after_ms: code.retry_after_ms(),
         }
     }
 
@@ -278,6 +308,7 @@ impl GreptimedbV1Response {
             code: error_code as u32,
             output: vec![],
             execution_time_ms: None,
+            retry_after_ms: error_code.retry_after_ms(),
         }
     }
 
@@ -287,6 +318,7 @@ impl GreptimedbV1Response {
             code: StatusCode::Success as u32,
             output,
             execution_time_ms: None,
+            retry_after_ms: None,
         }
     }
 
@@ -497,6 +529,7 @@ async fn serve_docs() -> Html<String> {
 pub struct ApiState {
     pub sql_handler: ServerSqlQueryHandlerRef,
     pub script_handler: Option<ScriptHandlerRef>,
+    pub query_registry: QueryRegistryRef,
 }
 
 #[derive(Clone)]
@@ -521,12 +554,17 @@ impl HttpServerBuilder {
                 prom_handler: None,
                 prometheus_handler: None,
                 otlp_handler: None,
+                loki_handler: None,
+                upload_handler: None,
                 user_provider: None,
                 script_handler: None,
+                tail_hub: None,
                 metrics_handler: None,
                 shutdown_tx: Mutex::new(None),
                 greptime_config_options: None,
                 plugins: Default::default(),
+                query_registry: Default::default(),
+                rate_limiter: None,
             },
         }
     }
@@ -571,6 +609,21 @@ impl HttpServerBuilder {
         self
     }
 
+    pub fn with_loki_handler(&mut self, handler: LokiProtocolHandlerRef) -> &mut Self {
+        let _ = self.inner.loki_handler.get_or_insert(handler);
+        self
+    }
+
+    pub fn with_upload_handler(&mut self, handler: UploadHandlerRef) -> &mut Self {
+        let _ = self.inner.upload_handler.get_or_insert(handler);
+        self
+    }
+
+    pub fn with_tail_hub(&mut self, hub: TailHubRef) -> &mut Self {
+        let _ = self.inner.tail_hub.get_or_insert(hub);
+        self
+    }
+
     pub fn with_user_provider(&mut self, user_provider: UserProviderRef) -> &mut Self {
         let _ = self.inner.user_provider.get_or_insert(user_provider);
         self
@@ -586,6 +639,16 @@ impl HttpServerBuilder {
         self
     }
 
+    pub fn with_query_registry(&mut self, query_registry: QueryRegistryRef) -> &mut Self {
+        self.inner.query_registry = query_registry;
+        self
+    }
+
+    pub fn with_rate_limiter(&mut self, rate_limiter: RateLimiterRef) -> &mut Self {
+        let _ = self.inner.rate_limiter.get_or_insert(rate_limiter);
+        self
+    }
+
     pub fn with_greptime_config_options(&mut self, opts: String) -> &mut Self {
         self.inner.greptime_config_options = Some(opts);
         self
@@ -619,6 +682,7 @@ impl HttpServer {
                 .route_sql(ApiState {
                     sql_handler,
                     script_handler: self.script_handler.clone(),
+                    query_registry: self.query_registry.clone(),
                 })
                 .finish_api(&mut api)
                 .layer(Extension(api.clone()));
@@ -660,6 +724,27 @@ impl HttpServer {
             );
         }
 
+        if let Some(loki_handler) = self.loki_handler.clone() {
+            router = router.nest(
+                &format!("/{HTTP_API_VERSION}/loki"),
+                self.route_loki(loki_handler),
+            );
+        }
+
+        if let Some(upload_handler) = self.upload_handler.clone() {
+            router = router.nest(
+                &format!("/{HTTP_API_VERSION}/tables"),
+                self.route_upload(upload_handler),
+            );
+        }
+
+        if let Some(tail_hub) = self.tail_hub.clone() {
+            router = router.route(
+                &format!("/{HTTP_API_VERSION}/stream"),
+                routing::get(tail::tail).with_state(tail_hub),
+            );
+        }
+
         if let Some(metrics_handler) = self.metrics_handler {
             router = router.nest("", self.route_metrics(metrics_handler));
         }
@@ -723,6 +808,11 @@ impl HttpServer {
                     .layer(middleware::from_fn_with_state(
                         AuthState::new(self.user_provider.clone()),
                         authorize::check_http_auth,
+                    ))
+                    // Limits requests after auth, so they are limited by their users.
+                    .layer(middleware::from_fn_with_state(
+                        self.rate_limiter.clone(),
+                        rate_limit::check_http_rate_limit,
                     )),
             )
             // Handlers for debug, we don't expect a timeout.
@@ -750,14 +840,16 @@ impl HttpServer {
         ApiRouter::new()
             .api_route(
                 "/sql",
-                apirouting::get_with(handler::sql, handler::sql_docs)
-                    .post_with(handler::sql, handler::sql_docs),
+                apirouting::get_with(handler::sql_with_format, handler::sql_docs)
+                    .post_with(handler::sql_with_format, handler::sql_docs),
             )
             .api_route(
                 "/promql",
                 apirouting::get_with(handler::promql, handler::sql_docs)
                     .post_with(handler::promql, handler::sql_docs),
             )
+            .api_route("/kill", apirouting::post(handler::kill))
+            .route("/queries", apirouting::get(handler::queries))
             .api_route("/scripts", apirouting::post(script::scripts))
             .api_route("/run-script", apirouting::post(script::run_script))
             .route("/private/api.json", apirouting::get(serve_api))
@@ -771,6 +863,7 @@ impl HttpServer {
                 "/format_query",
                 routing::post(format_query).get(format_query),
             )
+            .route("/parse_query", routing::post(parse_query).get(parse_query))
             .route("/query", routing::post(instant_query).get(instant_query))
             .route("/query_range", routing::post(range_query).get(range_query))
             .route("/labels", routing::post(labels_query).get(labels_query))
@@ -792,7 +885,7 @@ impl HttpServer {
     fn route_influxdb<S>(&self, influxdb_handler: InfluxdbLineProtocolHandlerRef) -> Router<S> {
         Router::new()
             .route("/write", routing::post(influxdb_write_v1))
-            .route("/api/v2/write", routing::post(influxdb_write_v2))
+            .route(INFLUXDB_V2_WRITE_PATH, routing::post(influxdb_write_v2))
             .route("/ping", routing::get(influxdb_ping))
             .route("/health", routing::get(influxdb_health))
             .with_state(influxdb_handler)
@@ -811,6 +904,18 @@ impl HttpServer {
             .with_state(otlp_handler)
     }
 
+    fn route_loki<S>(&self, loki_handler: LokiProtocolHandlerRef) -> Router<S> {
+        Router::new()
+            .route("/api/v1/push", routing::post(loki::push))
+            .with_state(loki_handler)
+    }
+
+    fn route_upload<S>(&self, upload_handler: UploadHandlerRef) -> Router<S> {
+        Router::new()
+            .route("/:table/upload", routing::post(upload::upload))
+            .with_state(upload_handler)
+    }
+
     fn route_config<S>(&self, state: GreptimeOptionsConfigState) -> ApiRouter<S> {
         ApiRouter::new()
             .route("/config", apirouting::get(handler::config))
@@ -865,7 +970,7 @@ impl Server for HttpServer {
 
     async fn start(&self, listening: SocketAddr) -> Result<SocketAddr> {
         let (tx, rx) = oneshot::channel();
-        let server = {
+        let (app, tls_server_config) = {
             let mut shutdown_tx = self.shutdown_tx.lock().await;
             ensure!(
                 shutdown_tx.is_none(),
@@ -877,14 +982,36 @@ impl Server for HttpServer {
                 app = configurator.config_http(app);
             }
             let app = self.build(app);
-            let server = axum::Server::bind(&listening)
-                .tcp_nodelay(true)
-                .serve(app.into_make_service());
-
+            let tls_server_config = Arc::new(
+                ReloadableTlsServerConfig::try_new(self.options.tls.clone())
+                    .context(InternalIoSnafu)?,
+            );
+            maybe_watch_tls_config(tls_server_config.clone())?;
             *shutdown_tx = Some(tx);
 
-            server
+            (app, tls_server_config)
         };
+        let make_service = app.into_make_service_with_connect_info::<SocketAddr>();
+
+        if tls_server_config.get_server_config().is_some() {
+            let listener = TcpListener::bind(listening)
+                .await
+                .context(TcpBindSnafu { addr: listening })?;
+            let listening = listener
+                .local_addr()
+                .context(TcpBindSnafu { addr: listening })?;
+            info!("HTTPS server is bound to {}", listening);
+
+            let incoming = accept::from_stream(tls_incoming(listener, tls_server_config));
+            let server = axum::Server::builder(incoming).serve(make_service);
+            let graceful = server.with_graceful_shutdown(rx.map(drop));
+            graceful.await.context(StartHttpSnafu)?;
+            return Ok(listening);
+        }
+
+        let server = axum::Server::bind(&listening)
+            .tcp_nodelay(true)
+            .serve(make_service);
         let listening = server.local_addr();
         info!("HTTP server is bound to {}", listening);
 
@@ -12,30 +12,34 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::net::SocketAddr;
+use std::time::{Duration, Instant};
+
 use ::auth::UserProviderRef;
-use axum::extract::State;
+use axum::extract::{ConnectInfo, State};
 use axum::http::{self, Request, StatusCode};
 use axum::middleware::Next;
 use axum::response::{IntoResponse, Response};
 use axum::Json;
 use base64::prelude::BASE64_STANDARD;
 use base64::Engine;
-use common_catalog::consts::DEFAULT_SCHEMA_NAME;
-use common_catalog::parse_catalog_and_schema_from_db_string;
 use common_error::ext::ErrorExt;
 use common_telemetry::warn;
+use common_time::TimeZone;
 use headers::Header;
 use secrecy::SecretString;
-use session::context::QueryContext;
+use session::context::{Channel, QueryContextBuilder};
+use session::database::parse_database;
 use snafu::{ensure, OptionExt, ResultExt};
 
-use super::header::GreptimeDbName;
+use super::header::{GreptimeDbName, GREPTIME_TIMEOUT_HEADER_NAME, GREPTIME_TIMEZONE_HEADER_NAME};
 use super::{JsonResponse, ResponseFormat, PUBLIC_APIS};
 use crate::error::{
     self, InvalidAuthorizationHeaderSnafu, InvalidParameterSnafu, InvisibleASCIISnafu,
     NotFoundInfluxAuthSnafu, Result, UnsupportedAuthSchemeSnafu, UrlDecodeSnafu,
 };
-use crate::http::HTTP_API_PREFIX;
+use crate::http::{HTTP_API_PREFIX, INFLUXDB_V2_WRITE_PATH};
+use crate::statement_timeout::parse_statement_timeout;
 
 /// AuthState is a holder state for [`UserProviderRef`]
 /// during [`check_http_auth`] function in axum's middleware
@@ -56,9 +60,35 @@ pub async fn inner_auth<B>(
 ) -> std::result::Result<Request<B>, Response> {
     // 1. prepare
     let (catalog, schema) = extract_catalog_and_schema(&req);
-    let query_ctx = QueryContext::with(catalog, schema);
     let need_auth = need_auth(&req);
     let is_influxdb = req.uri().path().contains("influxdb");
+    let options = extract_timeout(&req)
+        .and_then(|timeout| extract_time_zone(&req).map(|time_zone| (timeout, time_zone)));
+    let (timeout, time_zone) = match options {
+        Ok(v) => v,
+        Err(e) => {
+            let (_, body) = err_response(is_influxdb, e);
+            return Err((StatusCode::BAD_REQUEST, body).into_response());
+        }
+    };
+    let query_ctx = QueryContextBuilder::default()
+        .current_catalog(catalog.to_string())
+        .current_schema(schema.to_string())
+        .deadline(timeout.map(|timeout| Instant::now() + timeout))
+        .time_zone(time_zone)
+        .channel(Channel::Http)
+        .client_addr(
+            req.extensions()
+                .get::<ConnectInfo<SocketAddr>>()
+                .map(|info| info.0),
+        )
+        .user_agent(
+            req.headers()
+                .get(http::header::USER_AGENT)
+                .and_then(|value| value.to_str().ok())
+                .map(|value| value.to_string()),
+        )
+        .build();
 
     // 2. check if auth is needed
     let user_provider = if let Some(user_provider) = user_provider.filter(|_| need_auth) {
@@ -117,7 +147,7 @@ pub async fn check_http_auth<B>(
     }
 }
 
-fn err_response(is_influxdb: bool, err: impl ErrorExt) -> impl IntoResponse {
+fn err_response(is_influxdb: bool, err: impl ErrorExt) -> (StatusCode, Json<JsonResponse>) {
     let format = if is_influxdb {
         ResponseFormat::InfluxdbV1
     } else {
@@ -134,14 +164,40 @@ fn extract_catalog_and_schema<B>(request: &Request<B>) -> (&str, &str) {
         .headers()
         .get(GreptimeDbName::name())
         // eat this invalid ascii error and give user the final IllegalParam error
-        .and_then(|header| header.to_str().ok())
-        .or_else(|| {
-            let query = request.uri().query().unwrap_or_default();
-            extract_db_from_query(query)
-        })
-        .unwrap_or(DEFAULT_SCHEMA_NAME);
-
-    parse_catalog_and_schema_from_db_string(dbname)
+        .and_then(|header| header.to_str().ok());
+    let query = request.uri().query().unwrap_or_default();
+    if dbname.is_none() && request.uri().path().ends_with(INFLUXDB_V2_WRITE_PATH) {
+        // The organization and bucket of influxdb v2 are the catalog and schema.
+        if let Some(bucket) = extract_param_from_query(query, "bucket") {
+            return match extract_param_from_query(query, "org") {
+                Some(org) => (org, bucket),
+                None => parse_database(Some(bucket)),
+            };
+        }
+    }
+
+    parse_database(dbname.or_else(|| extract_db_from_query(query)))
+}
+
+fn extract_timeout<B>(request: &Request<B>) -> Result<Option<Duration>> {
+    let Some(header) = request.headers().get(&GREPTIME_TIMEOUT_HEADER_NAME) else {
+        return Ok(None);
+    };
+    let value = header
+        .to_str()
+        .ok()
+        .context(error::InvalidStatementTimeoutSnafu {
+            value: format!("{header:?}"),
+        })?;
+    parse_statement_timeout(value)
+}
+
+fn extract_time_zone<B>(request: &Request<B>) -> Result<Option<TimeZone>> {
+    let Some(header) = request.headers().get(&GREPTIME_TIMEZONE_HEADER_NAME) else {
+        return Ok(None);
+    };
+    let value = String::from_utf8_lossy(header.as_bytes());
+    TimeZone::from_tz_string(&value).context(error::InvalidTimeZoneSnafu { value })
 }
 
 fn get_influxdb_credentials<B>(request: &Request<B>) -> Result<Option<(Username, Password)>> {
@@ -153,16 +209,17 @@ fn get_influxdb_credentials<B>(request: &Request<B>) -> Result<Option<(Username,
             .context(InvisibleASCIISnafu)?
             .split_once(' ')
             .context(InvalidAuthorizationHeaderSnafu)?;
-        ensure!(
-            auth_scheme.to_lowercase() == "token",
-            UnsupportedAuthSchemeSnafu { name: auth_scheme }
-        );
-
-        let (username, password) = credential
-            .split_once(':')
-            .context(InvalidAuthorizationHeaderSnafu)?;
-
-        Ok(Some((username.to_string(), password.to_string().into())))
+        match auth_scheme.to_lowercase().as_str() {
+            // the token is `username:password`
+            "token" => {
+                let (username, password) = credential
+                    .split_once(':')
+                    .context(InvalidAuthorizationHeaderSnafu)?;
+                Ok(Some((username.to_string(), password.to_string().into())))
+            }
+            "basic" => decode_basic(credential).map(Some),
+            _ => UnsupportedAuthSchemeSnafu { name: auth_scheme }.fail(),
+        }
     } else {
         // try v1
         let Some(query_str) = request.uri().query() else {
@@ -268,9 +325,16 @@ fn need_auth<B>(req: &Request<B>) -> bool {
 }
 
 fn extract_db_from_query(query: &str) -> Option<&str> {
+    extract_param_from_query(query, "db")
+}
+
+/// Returns the first value of the `key` in the `query`, `None` if it's empty.
+fn extract_param_from_query<'a>(query: &'a str, key: &str) -> Option<&'a str> {
     for pair in query.split('&') {
-        if let Some(db) = pair.strip_prefix("db=") {
-            return if db.is_empty() { None } else { Some(db) };
+        if let Some((k, value)) = pair.split_once('=') {
+            if k == key {
+                return if value.is_empty() { None } else { Some(value) };
+            }
         }
     }
     None
@@ -322,6 +386,48 @@ mod tests {
         assert!(need_auth(&req));
     }
 
+    #[test]
+    fn test_extract_timeout() {
+        let req = Request::builder().body(()).unwrap();
+        assert!(extract_timeout(&req).unwrap().is_none());
+
+        let req = Request::builder()
+            .header(&GREPTIME_TIMEOUT_HEADER_NAME, "30s")
+            .body(())
+            .unwrap();
+        assert_eq!(
+            Some(Duration::from_secs(30)),
+            extract_timeout(&req).unwrap()
+        );
+
+        let req = Request::builder()
+            .header(&GREPTIME_TIMEOUT_HEADER_NAME, "soon")
+            .body(())
+            .unwrap();
+        assert!(extract_timeout(&req).is_err());
+    }
+
+    #[test]
+    fn test_extract_time_zone() {
+        let req = Request::builder().body(()).unwrap();
+        assert!(extract_time_zone(&req).unwrap().is_none());
+
+        let req = Request::builder()
+            .header(&GREPTIME_TIMEZONE_HEADER_NAME, "+08:00")
+            .body(())
+            .unwrap();
+        assert_eq!(
+            "+08:00",
+            extract_time_zone(&req).unwrap().unwrap().to_string()
+        );
+
+        let req = Request::builder()
+            .header(&GREPTIME_TIMEZONE_HEADER_NAME, "Mars/Olympus")
+            .body(())
+            .unwrap();
+        assert!(extract_time_zone(&req).is_err());
+    }
+
     #[test]
     fn test_decode_basic() {
         // base64encode("username:password") == "dXNlcm5hbWU6cGFzc3dvcmQ="
@@ -412,6 +518,47 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_influxdb_v2_db() {
+        let req = mock_http_request(
+            None,
+            Some("http://localhost/v1/influxdb/api/v2/write?org=greptime&bucket=tomcat"),
+        )
+        .unwrap();
+        assert_eq!(("greptime", "tomcat"), extract_catalog_and_schema(&req));
+
+        let req = mock_http_request(
+            None,
+            Some("http://localhost/v1/influxdb/api/v2/write?bucket=greptime-tomcat"),
+        )
+        .unwrap();
+        assert_eq!(("greptime", "tomcat"), extract_catalog_and_schema(&req));
+
+        // The bucket is ignored by other APIs.
+        let req = mock_http_request(
+            None,
+            Some("http://localhost/v1/influxdb/write?bucket=tomcat&db=public"),
+        )
+        .unwrap();
+        assert_eq!(("greptime", "public"), extract_catalog_and_schema(&req));
+    }
+
+    #[test]
+    fn test_influxdb_credentials() {
+        let req = mock_http_request(Some("Token username:password"), None).unwrap();
+        let (username, pwd) = get_influxdb_credentials(&req).unwrap().unwrap();
+        assert_eq!("username", username);
+        assert_eq!("password", pwd.expose_secret());
+
+        let req = mock_http_request(Some("Basic dXNlcm5hbWU6cGFzc3dvcmQ="), None).unwrap();
+        let (username, pwd) = get_influxdb_credentials(&req).unwrap().unwrap();
+        assert_eq!("username", username);
+        assert_eq!("password", pwd.expose_secret());
+
+        let req = mock_http_request(Some("Token password"), None).unwrap();
+        assert!(get_influxdb_credentials(&req).is_err());
+    }
+
     #[test]
     fn test_extract_user() {
         assert_matches!(extract_influxdb_user_from_query(""), (None, None));
@@ -0,0 +1,205 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Results of SQL queries over HTTP in file formats, i.e. CSV and Arrow IPC.
+
+use axum::http::header;
+use axum::response::{IntoResponse, Json, Response};
+use common_query::Output;
+use common_recordbatch::util;
+use datatypes::arrow::csv::WriterBuilder;
+use datatypes::arrow::ipc::writer::FileWriter;
+use datatypes::arrow::record_batch::RecordBatch as DfRecordBatch;
+use datatypes::schema::SchemaRef;
+use snafu::ResultExt;
+
+use crate::error::{
+    CollectRecordbatchSnafu, EncodeArrowIpcSnafu, EncodeCsvSnafu, NotSupportedSnafu, Result,
+};
+use crate::http::{JsonResponse, ResponseFormat};
+
+pub const CSV_CONTENT_TYPE: &str = "text/csv; charset=utf-8";
+pub const ARROW_FILE_CONTENT_TYPE: &str = "application/vnd.apache.arrow.file";
+
+/// File formats of query results.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileFormat {
+    /// CSV with a header line of column names.
+    Csv,
+    /// Arrow IPC file format.
+    Arrow,
+}
+
+impl FileFormat {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "csv" => Some(FileFormat::Csv),
+            "arrow" => Some(FileFormat::Arrow),
+            _ => None,
+        }
+    }
+
+    fn content_type(&self) -> &'static str {
+        match self {
+            FileFormat::Csv => CSV_CONTENT_TYPE,
+            FileFormat::Arrow => ARROW_FILE_CONTENT_TYPE,
+        }
+    }
+}
+
+/// Encodes the only output in `outputs` in the `format`.
+///
+/// Failures are responded in the `greptimedb_v1` json format.
+pub async fn file_response(outputs: Vec<Result<Output>>, format: FileFormat) -> Response {
+    let encoded = collect_output(outputs)
+        .await
+        .and_then(|(schema, batches)| match format {
+            FileFormat::Csv => encode_csv(&schema, &batches),
+            FileFormat::Arrow => encode_arrow(&schema, &batches),
+        });
+
+    match encoded {
+        Ok(body) => ([(header::CONTENT_TYPE, format.content_type())], body).into_response(),
+        Err(e) => Json(JsonResponse::with_error(e, ResponseFormat::GreptimedbV1)).into_response(),
+    }
+}
+
+async fn collect_output(
+    mut outputs: Vec<Result<Output>>,
+) -> Result<(SchemaRef, Vec<DfRecordBatch>)> {
+    if outputs.len() != 1 {
+        return NotSupportedSnafu {
+            feat: "results of multiple statements in file formats",
+        }
+        .fail();
+    }
+    let (schema, batches) = match outputs.remove(0)? {
+        Output::Stream(stream) => {
+            let schema = stream.schema();
+            let batches = util::collect(stream)
+                .await
+                .context(CollectRecordbatchSnafu)?;
+            (schema, batches)
+        }
+        Output::RecordBatches(recordbatches) => (recordbatches.schema(), recordbatches.take()),
+        Output::AffectedRows(_) => {
+            return NotSupportedSnafu {
+                feat: "affected rows in file formats",
+            }
+            .fail();
+        }
+    };
+    let batches = batches
+        .into_iter()
+        .map(|batch| batch.into_df_record_batch())
+        .collect();
+    Ok((schema, batches))
+}
+
+fn encode_csv(schema: &SchemaRef, batches: &[DfRecordBatch]) -> Result<Vec<u8>> {
+    let mut writer = WriterBuilder::new().has_headers(true).build(Vec::new());
+    if batches.is_empty() {
+        // Writes the header only.
+        let empty = DfRecordBatch::new_empty(schema.arrow_schema().clone());
+        writer.write(&empty).context(EncodeCsvSnafu)?;
+    }
+    for batch in batches {
+        writer.write(batch).context(EncodeCsvSnafu)?;
+    }
+    Ok(writer.into_inner())
+}
+
+fn encode_arrow(schema: &SchemaRef, batches: &[DfRecordBatch]) -> Result<Vec<u8>> {
+    let mut writer =
+        FileWriter::try_new(Vec::new(), schema.arrow_schema()).context(EncodeArrowIpcSnafu)?;
+    for batch in batches {
+        writer.write(batch).context(EncodeArrowIpcSnafu)?;
+    }
+    writer.finish().context(EncodeArrowIpcSnafu)?;
+    writer.into_inner().context(EncodeArrowIpcSnafu)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+    use std::sync::Arc;
+
+    use common_recordbatch::{RecordBatch, RecordBatches};
+    use datatypes::arrow::ipc::reader::FileReader;
+    use datatypes::prelude::ConcreteDataType;
+    use datatypes::schema::{ColumnSchema, Schema};
+    use datatypes::vectors::{StringVector, UInt32Vector};
+
+    use super::*;
+
+    fn new_output(num_batches: u32) -> Output {
+        let schema = Arc::new(Schema::new(vec![
+            ColumnSchema::new("n", ConcreteDataType::uint32_datatype(), false),
+            ColumnSchema::new("s", ConcreteDataType::string_datatype(), true),
+        ]));
+        let batches = (0..num_batches)
+            .map(|i| {
+                RecordBatch::new(
+                    schema.clone(),
+                    vec![
+                        Arc::new(UInt32Vector::from_slice([i])) as _,
+                        Arc::new(StringVector::from(vec![format!("s,{i}")])) as _,
+                    ],
+                )
+                .unwrap()
+            })
+            .collect();
+        Output::RecordBatches(RecordBatches::try_new(schema, batches).unwrap())
+    }
+
+    async fn encode(output: Output, format: FileFormat) -> Vec<u8> {
+        let (schema, batches) = collect_output(vec![Ok(output)]).await.unwrap();
+        match format {
+            FileFormat::Csv => encode_csv(&schema, &batches).unwrap(),
+            FileFormat::Arrow => encode_arrow(&schema, &batches).unwrap(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_encode_csv() {
+        let csv = encode(new_output(2), FileFormat::Csv).await;
+        assert_eq!(
+            "n,s\n0,\"s,0\"\n1,\"s,1\"\n",
+            String::from_utf8(csv).unwrap()
+        );
+
+        // Only the header.
+        let csv = encode(new_output(0), FileFormat::Csv).await;
+        assert_eq!("n,s\n", String::from_utf8(csv).unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_encode_arrow() {
+        let arrow = encode(new_output(2), FileFormat::Arrow).await;
+        let reader = FileReader::try_new(Cursor::new(arrow), None).unwrap();
+        assert_eq!(2, reader.num_batches());
+        let num_rows = reader.map(|batch| batch.unwrap().num_rows()).sum::<usize>();
+        assert_eq!(2, num_rows);
+    }
+
+    #[tokio::test]
+    async fn test_unsupported_output() {
+        assert!(collect_output(vec![Ok(Output::AffectedRows(1))])
+            .await
+            .is_err());
+        assert!(collect_output(vec![Ok(new_output(1)), Ok(new_output(1))])
+            .await
+            .is_err());
+    }
+}
@@ -20,23 +20,28 @@ use aide::transform::TransformOperation;
 use axum::extract::{Json, Query, State};
 use axum::response::{IntoResponse, Response};
 use axum::{Extension, Form};
-use common_error::ext::ErrorExt;
 use common_error::status_code::StatusCode;
+use common_query::Output;
 use query::parser::PromQuery;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use session::context::QueryContextRef;
+use session::database::resolve_database;
 
+use crate::http::file_result::{file_response, FileFormat};
+use crate::http::stream_result::{stream_response, StreamFormat};
 use crate::http::{ApiState, Epoch, GreptimeOptionsConfigState, JsonResponse, ResponseFormat};
 use crate::metrics_handler::MetricsHandler;
 use crate::query_handler::sql::ServerSqlQueryHandlerRef;
+use crate::query_registry::{QueryInfo, RunningQueryGuard};
 
 #[derive(Debug, Default, Serialize, Deserialize, JsonSchema)]
 pub struct SqlQuery {
     pub db: Option<String>,
     pub sql: Option<String>,
-    // (Optional) result format: [`gerptimedb_v1`, `influxdb_v1`],
-    // the default value is `greptimedb_v1`
+    // (Optional) result format: [`gerptimedb_v1`, `influxdb_v1`, `ndjson`, `arrow-stream`,
+    // `csv`, `arrow`], the default value is `greptimedb_v1`. Results in `ndjson` and
+    // `arrow-stream` are streamed instead of being buffered.
     pub format: Option<String>,
     // Returns epoch timestamps with the specified precision.
     // Both u and µ indicate microseconds.
@@ -83,7 +88,13 @@ pub async fn sql(
             return Json(resp);
         }
 
-        JsonResponse::from_output(sql_handler.do_query(sql, query_ctx).await, format, epoch).await
+        // Keeps the query registered until its output is collected.
+        let guard = register_query(&state, sql, &query_ctx);
+        let outputs = guard
+            .run(async { Ok(sql_handler.do_query(sql, query_ctx).await) })
+            .await
+            .unwrap_or_else(|e| vec![Err(e)]);
+        JsonResponse::from_output(outputs, format, epoch).await
     } else {
         JsonResponse::with_error_message(
             "sql parameter is required.".to_string(),
@@ -95,6 +106,76 @@ pub async fn sql(
     Json(resp.with_execution_time(start.elapsed().as_millis()))
 }
 
+/// Formats of results that aren't json.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RawFormat {
+    Stream(StreamFormat),
+    File(FileFormat),
+}
+
+impl RawFormat {
+    fn parse(s: &str) -> Option<Self> {
+        StreamFormat::parse(s)
+            .map(RawFormat::Stream)
+            .or_else(|| FileFormat::parse(s).map(RawFormat::File))
+    }
+}
+
+/// Handler to execute sql, the result is in json unless the format is a raw format
+#[axum_macros::debug_handler]
+pub async fn sql_with_format(
+    State(state): State<ApiState>,
+    Query(query_params): Query<SqlQuery>,
+    Extension(query_ctx): Extension<QueryContextRef>,
+    Form(form_params): Form<SqlQuery>,
+) -> Response {
+    let raw_format = query_params
+        .format
+        .as_ref()
+        .or(form_params.format.as_ref())
+        .and_then(|s| RawFormat::parse(&s.to_lowercase()));
+    let Some(raw_format) = raw_format else {
+        return sql(
+            State(state),
+            Query(query_params),
+            Extension(query_ctx),
+            Form(form_params),
+        )
+        .await
+        .into_response();
+    };
+
+    let sql_handler = &state.sql_handler;
+    let db = query_ctx.get_db_string();
+    let _timer = crate::metrics::METRIC_HTTP_SQL_ELAPSED
+        .with_label_values(&[db.as_str()])
+        .start_timer();
+
+    let format = ResponseFormat::GreptimedbV1;
+    let Some(sql) = query_params.sql.or(form_params.sql) else {
+        return Json(JsonResponse::with_error_message(
+            "sql parameter is required.".to_string(),
+            StatusCode::InvalidArguments,
+            format,
+        ))
+        .into_response();
+    };
+    if let Some(resp) = validate_schema(sql_handler.clone(), query_ctx.clone(), format).await {
+        return Json(resp).into_response();
+    }
+
+    // Keeps the query registered until its output is encoded.
+    let guard = register_query(&state, &sql, &query_ctx);
+    let outputs = guard
+        .run(async { Ok(sql_handler.do_query(&sql, query_ctx).await) })
+        .await
+        .unwrap_or_else(|e| vec![Err(e)]);
+    match raw_format {
+        RawFormat::Stream(format) => stream_response(outputs, format, guard),
+        RawFormat::File(format) => file_response(outputs, format).await,
+    }
+}
+
 #[derive(Debug, Default, Serialize, Deserialize, JsonSchema)]
 pub struct PromqlQuery {
     pub query: String,
@@ -139,17 +220,61 @@ pub async fn promql(
         return Json(resp);
     }
 
+    // Keeps the query registered until its output is collected.
+    let guard = register_query(&state, &params.query, &query_ctx);
     let prom_query = params.into();
-    let resp = JsonResponse::from_output(
-        sql_handler.do_promql_query(&prom_query, query_ctx).await,
-        ResponseFormat::GreptimedbV1,
-        None,
-    )
-    .await;
+    let outputs = guard
+        .run(async { Ok(sql_handler.do_promql_query(&prom_query, query_ctx).await) })
+        .await
+        .unwrap_or_else(|e| vec![Err(e)]);
+    let resp = JsonResponse::from_output(outputs, ResponseFormat::GreptimedbV1, None).await;
 
     Json(resp.with_execution_time(exec_start.elapsed().as_millis()))
 }
 
+/// Registers the query of an HTTP request under a new id.
+fn register_query(state: &ApiState, query: &str, query_ctx: &QueryContextRef) -> RunningQueryGuard {
+    let registry = &state.query_registry;
+    registry.register(registry.next_id(), query, query_ctx, "http")
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, JsonSchema)]
+pub struct KillQuery {
+    pub id: Option<u32>,
+}
+
+/// Handler to cancel a running query
+#[axum_macros::debug_handler]
+pub async fn kill(
+    State(state): State<ApiState>,
+    Query(query_params): Query<KillQuery>,
+    Form(form_params): Form<KillQuery>,
+) -> Json<JsonResponse> {
+    let format = ResponseFormat::GreptimedbV1;
+    let resp = match query_params.id.or(form_params.id) {
+        Some(id) => {
+            let output = state
+                .query_registry
+                .kill(id)
+                .map(|_| Output::AffectedRows(0));
+            JsonResponse::from_output(vec![output], format, None).await
+        }
+        None => JsonResponse::with_error_message(
+            "id parameter is required.".to_string(),
+            StatusCode::InvalidArguments,
+            format,
+        ),
+    };
+
+    Json(resp)
+}
+
+/// Handler to list running queries
+#[axum_macros::debug_handler]
+pub async fn queries(State(state): State<ApiState>) -> Json<Vec<QueryInfo>> {
+    Json(state.query_registry.list())
+}
+
 pub(crate) fn sql_docs(op: TransformOperation) -> TransformOperation {
     op.response::<200, Json<JsonResponse>>()
 }
@@ -224,24 +349,10 @@ async fn validate_schema(
     query_ctx: QueryContextRef,
     format: ResponseFormat,
 ) -> Option<JsonResponse> {
-    match sql_handler
-        .is_valid_schema(query_ctx.current_catalog(), query_ctx.current_schema())
+    // The user is authorized to access the database while authenticating.
+    let db = query_ctx.get_db_string();
+    resolve_database(sql_handler.as_ref(), None, Some(&db), None)
         .await
-    {
-        Ok(false) => Some(JsonResponse::with_error_message(
-            format!("Database not found: {}", query_ctx.get_db_string()),
-            StatusCode::DatabaseNotFound,
-            format,
-        )),
-        Err(e) => Some(JsonResponse::with_error_message(
-            format!(
-                "Error checking database: {}, {}",
-                query_ctx.get_db_string(),
-                e.output_msg(),
-            ),
-            StatusCode::Internal,
-            format,
-        )),
-        _ => None,
-    }
+        .err()
+        .map(|e| JsonResponse::with_error(e, format))
 }
@@ -15,6 +15,14 @@
 use headers::{Header, HeaderName, HeaderValue};
 
 pub static GREPTIME_DB_NAME_HEADER_NAME: HeaderName = HeaderName::from_static("x-greptime-db-name");
+/// Header of the statement timeout of a request, see [crate::statement_timeout].
+pub static GREPTIME_TIMEOUT_HEADER_NAME: HeaderName = HeaderName::from_static("x-greptime-timeout");
+/// Header of the time zone of a request, timestamp literals without offset are in it.
+pub static GREPTIME_TIMEZONE_HEADER_NAME: HeaderName =
+    HeaderName::from_static("x-greptime-timezone");
+/// Header of the table to write logs of a request, see [crate::http::loki].
+pub static GREPTIME_LOG_TABLE_NAME_HEADER_NAME: HeaderName =
+    HeaderName::from_static("x-greptime-log-table-name");
 
 pub struct GreptimeDbName(Option<String>);
 
@@ -57,16 +57,16 @@ pub async fn influxdb_write_v1(
     influxdb_write(&db, precision, lines, handler, query_ctx).await
 }
 
+/// Writes lines by the influxdb v2 API, the `org` and `bucket` are resolved as the
+/// catalog and schema of the `query_ctx`.
 #[axum_macros::debug_handler]
 pub async fn influxdb_write_v2(
     State(handler): State<InfluxdbLineProtocolHandlerRef>,
-    Query(mut params): Query<HashMap<String, String>>,
+    Query(params): Query<HashMap<String, String>>,
     Extension(query_ctx): Extension<QueryContextRef>,
     lines: String,
 ) -> Result<impl IntoResponse> {
-    let db = params
-        .remove("bucket")
-        .unwrap_or_else(|| DEFAULT_SCHEMA_NAME.to_string());
+    let db = query_ctx.get_db_string();
 
     let precision = params
         .get("precision")
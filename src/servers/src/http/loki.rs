@@ -0,0 +1,69 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use axum::extract::{RawBody, State};
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::response::IntoResponse;
+use axum::Extension;
+use session::context::QueryContextRef;
+use snafu::ResultExt;
+
+use crate::error::{self, Result};
+use crate::http::header::GREPTIME_LOG_TABLE_NAME_HEADER_NAME;
+use crate::loki::{self, LOKI_TABLE_NAME};
+use crate::query_handler::LokiProtocolHandlerRef;
+
+/// Handles Loki push requests in protobuf or JSON, the request is in JSON if its content
+/// type is `application/json`.
+///
+/// Logs are written to the table in the `x-greptime-log-table-name` header, or the
+/// [LOKI_TABLE_NAME] table if the header is absent.
+#[axum_macros::debug_handler]
+pub async fn push(
+    State(handler): State<LokiProtocolHandlerRef>,
+    Extension(query_ctx): Extension<QueryContextRef>,
+    headers: HeaderMap,
+    RawBody(body): RawBody,
+) -> Result<impl IntoResponse> {
+    let db = query_ctx.get_db_string();
+    let _timer = crate::metrics::METRIC_HTTP_LOKI_PUSH_ELAPSED
+        .with_label_values(&[db.as_str()])
+        .start_timer();
+
+    let body = hyper::body::to_bytes(body)
+        .await
+        .context(error::HyperSnafu)?;
+    let streams = if is_json(&headers) {
+        loki::decode_json(&body)?
+    } else {
+        loki::decode_protobuf(&body)?
+    };
+    let table_name = headers
+        .get(&GREPTIME_LOG_TABLE_NAME_HEADER_NAME)
+        .and_then(|value| value.to_str().ok())
+        .filter(|value| !value.is_empty())
+        .unwrap_or(LOKI_TABLE_NAME);
+    let (requests, _) = loki::to_grpc_insert_requests(table_name, streams)?;
+
+    let _ = handler.insert(requests, query_ctx).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+fn is_json(headers: &HeaderMap) -> bool {
+    headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .map(|value| value.trim_start().starts_with("application/json"))
+        .unwrap_or(false)
+}
@@ -14,7 +14,7 @@
 
 use api::prom_store::remote::{ReadRequest, WriteRequest};
 use axum::extract::{Query, RawBody, State};
-use axum::http::{header, StatusCode};
+use axum::http::{header, HeaderValue, StatusCode};
 use axum::response::IntoResponse;
 use axum::Extension;
 use common_catalog::consts::DEFAULT_SCHEMA_NAME;
@@ -26,7 +26,7 @@ use session::context::QueryContextRef;
 use snafu::prelude::*;
 
 use crate::error::{self, Result};
-use crate::prom_store::snappy_decompress;
+use crate::prom_store::{snappy_decompress, MetricLayout, PromWriteOptions};
 use crate::query_handler::{PromStoreProtocolHandlerRef, PromStoreResponse};
 
 #[derive(Debug, Serialize, Deserialize, JsonSchema)]
@@ -42,13 +42,64 @@ impl Default for DatabaseQuery {
     }
 }
 
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct RemoteWriteQuery {
+    pub db: Option<String>,
+    /// Layout of tables to write, `single_field` (default) or `multi_field`.
+    pub layout: Option<String>,
+    /// Table to write in the `multi_field` layout.
+    pub table: Option<String>,
+    /// Whether to add columns for new labels and metrics to existing tables (default true).
+    pub auto_add_columns: Option<bool>,
+}
+
+impl Default for RemoteWriteQuery {
+    fn default() -> RemoteWriteQuery {
+        Self {
+            db: Some(DEFAULT_SCHEMA_NAME.to_string()),
+            layout: None,
+            table: None,
+            auto_add_columns: None,
+        }
+    }
+}
+
+impl RemoteWriteQuery {
+    fn write_options(&self) -> Result<PromWriteOptions> {
+        let layout = match self.layout.as_deref() {
+            None | Some("single_field") => MetricLayout::SingleField,
+            Some("multi_field") => {
+                let table = self
+                    .table
+                    .clone()
+                    .context(error::InvalidPromRemoteRequestSnafu {
+                        msg: "table is required in the multi_field layout",
+                    })?;
+                MetricLayout::MultiField { table }
+            }
+            Some(layout) => {
+                return error::InvalidPromRemoteRequestSnafu {
+                    msg: format!("unknown layout: {layout}"),
+                }
+                .fail();
+            }
+        };
+
+        Ok(PromWriteOptions {
+            layout,
+            auto_add_columns: self.auto_add_columns.unwrap_or(true),
+        })
+    }
+}
+
 #[axum_macros::debug_handler]
 pub async fn remote_write(
     State(handler): State<PromStoreProtocolHandlerRef>,
-    Query(params): Query<DatabaseQuery>,
+    Query(params): Query<RemoteWriteQuery>,
     Extension(query_ctx): Extension<QueryContextRef>,
     RawBody(body): RawBody,
 ) -> Result<(StatusCode, ())> {
+    let options = params.write_options()?;
     let request = decode_remote_write_request(body).await?;
     let db = params.db.clone().unwrap_or_default();
 
@@ -56,20 +107,22 @@ pub async fn remote_write(
         .with_label_values(&[db.as_str()])
         .start_timer();
 
-    handler.write(request, query_ctx).await?;
+    handler.write(request, options, query_ctx).await?;
     Ok((StatusCode::NO_CONTENT, ()))
 }
 
 impl IntoResponse for PromStoreResponse {
     fn into_response(self) -> axum::response::Response {
-        (
-            [
-                (header::CONTENT_TYPE, self.content_type),
-                (header::CONTENT_ENCODING, self.content_encoding),
-            ],
-            self.body,
-        )
-            .into_response()
+        let mut response = ([(header::CONTENT_TYPE, self.content_type)], self.body).into_response();
+        // The streamed response isn't encoded.
+        if !self.content_encoding.is_empty() {
+            if let Ok(value) = HeaderValue::from_str(&self.content_encoding) {
+                let _ = response
+                    .headers_mut()
+                    .insert(header::CONTENT_ENCODING, value);
+            }
+        }
+        response
     }
 }
 
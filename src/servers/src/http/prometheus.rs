@@ -14,34 +14,41 @@
 
 //! prom supply the prometheus HTTP API Server compliance
 use std::collections::{BTreeMap, HashMap, HashSet};
+use std::time::UNIX_EPOCH;
 
 use axum::extract::{Path, Query, State};
 use axum::{Extension, Form, Json};
 use catalog::CatalogManagerRef;
-use common_catalog::consts::DEFAULT_SCHEMA_NAME;
-use common_catalog::parse_catalog_and_schema_from_db_string;
+use chrono::DateTime;
 use common_error::ext::ErrorExt;
 use common_error::status_code::StatusCode;
 use common_query::Output;
 use common_recordbatch::RecordBatches;
+use common_time::range::TimestampRange;
 use common_time::util::{current_time_rfc3339, yesterday_rfc3339};
+use common_time::Timestamp;
 use datatypes::prelude::ConcreteDataType;
 use datatypes::scalars::ScalarVector;
 use datatypes::vectors::{Float64Vector, StringVector, TimestampMillisecondVector};
-use promql_parser::label::METRIC_NAME;
+use promql_parser::label::{Labels, MatchOp, METRIC_NAME};
 use promql_parser::parser::{
-    AggregateExpr, BinaryExpr, Call, Expr as PromqlExpr, MatrixSelector, ParenExpr, SubqueryExpr,
-    UnaryExpr, ValueType, VectorSelector,
+    AggregateExpr, AtModifier, BinaryExpr, Call, Expr as PromqlExpr, LabelModifier, MatrixSelector,
+    NumberLiteral, Offset, ParenExpr, StringLiteral, SubqueryExpr, UnaryExpr, ValueType,
+    VectorMatchCardinality, VectorSelector,
 };
 use query::parser::{PromQuery, DEFAULT_LOOKBACK_STRING};
 use schemars::JsonSchema;
 use serde::de::{self, MapAccess, Visitor};
 use serde::{Deserialize, Serialize};
+use serde_json::json;
 use session::context::QueryContextRef;
+use session::database::parse_database;
 use snafu::{Location, OptionExt, ResultExt};
+use table::engine::TableReference;
 
 use crate::error::{
-    CollectRecordbatchSnafu, Error, InternalSnafu, InvalidQuerySnafu, Result, UnexpectedResultSnafu,
+    CatalogSnafu, CollectRecordbatchSnafu, Error, InternalSnafu, InvalidQuerySnafu, Result,
+    UnexpectedResultSnafu,
 };
 use crate::prom_store::{FIELD_COLUMN_NAME, METRIC_NAME_LABEL, TIMESTAMP_COLUMN_NAME};
 use crate::prometheus_handler::PrometheusHandlerRef;
@@ -71,6 +78,7 @@ pub enum PrometheusResponse {
     Series(Vec<HashMap<String, String>>),
     LabelValues(Vec<String>),
     FormatQuery(String),
+    ParseQuery(serde_json::Value),
 }
 
 impl Default for PrometheusResponse {
@@ -318,6 +326,253 @@ pub async fn format_query(
     }
 }
 
+#[axum_macros::debug_handler]
+pub async fn parse_query(
+    State(_handler): State<PrometheusHandlerRef>,
+    Query(params): Query<InstantQuery>,
+    Extension(_query_ctx): Extension<QueryContextRef>,
+    Form(form_params): Form<InstantQuery>,
+) -> Json<PrometheusJsonResponse> {
+    let _timer = crate::metrics::METRIC_HTTP_PROMQL_PARSE_QUERY_ELAPSED.start_timer();
+
+    let query = params.query.or(form_params.query).unwrap_or_default();
+    match promql_parser::parser::parse(&query) {
+        Ok(expr) => PrometheusJsonResponse::success(PrometheusResponse::ParseQuery(
+            promql_expr_to_ast_json(&expr),
+        )),
+        Err(reason) => {
+            let err = InvalidQuerySnafu { reason }.build();
+            PrometheusJsonResponse::error(err.status_code().to_string(), err.output_msg())
+        }
+    }
+}
+
+/// Translates the PromQL expression to the JSON AST returned by Prometheus' `parse_query` API.
+fn promql_expr_to_ast_json(expr: &PromqlExpr) -> serde_json::Value {
+    match expr {
+        PromqlExpr::Aggregate(AggregateExpr {
+            op,
+            expr,
+            param,
+            modifier,
+        }) => {
+            let (grouping, without) = match modifier {
+                Some(LabelModifier::Include(labels)) => (labels_to_vec(labels), false),
+                Some(LabelModifier::Exclude(labels)) => (labels_to_vec(labels), true),
+                None => (vec![], false),
+            };
+            json!({
+                "type": "aggregation",
+                "op": op.to_string(),
+                "expr": promql_expr_to_ast_json(expr),
+                "param": param.as_ref().map(|param| promql_expr_to_ast_json(param)),
+                "grouping": grouping,
+                "without": without,
+            })
+        }
+        PromqlExpr::Unary(UnaryExpr { expr }) => json!({
+            "type": "unaryExpr",
+            "op": "-",
+            "expr": promql_expr_to_ast_json(expr),
+        }),
+        PromqlExpr::Binary(BinaryExpr {
+            op,
+            lhs,
+            rhs,
+            modifier,
+        }) => {
+            let matching = match modifier {
+                Some(modifier) => {
+                    let (card, include) = match &modifier.card {
+                        VectorMatchCardinality::OneToOne => ("one-to-one", vec![]),
+                        VectorMatchCardinality::ManyToOne(labels) => {
+                            ("many-to-one", labels_to_vec(labels))
+                        }
+                        VectorMatchCardinality::OneToMany(labels) => {
+                            ("one-to-many", labels_to_vec(labels))
+                        }
+                        VectorMatchCardinality::ManyToMany => ("many-to-many", vec![]),
+                    };
+                    let (labels, on) = match &modifier.matching {
+                        Some(LabelModifier::Include(labels)) => (labels_to_vec(labels), true),
+                        Some(LabelModifier::Exclude(labels)) => (labels_to_vec(labels), false),
+                        None => (vec![], false),
+                    };
+                    json!({
+                        "card": card,
+                        "labels": labels,
+                        "on": on,
+                        "include": include,
+                    })
+                }
+                // Prometheus always matches two vectors one-to-one by default.
+                None if matches!(lhs.value_type(), ValueType::Vector)
+                    && matches!(rhs.value_type(), ValueType::Vector) =>
+                {
+                    json!({
+                        "card": "one-to-one",
+                        "labels": [],
+                        "on": false,
+                        "include": [],
+                    })
+                }
+                None => serde_json::Value::Null,
+            };
+            json!({
+                "type": "binaryExpr",
+                "op": op.to_string(),
+                "lhs": promql_expr_to_ast_json(lhs),
+                "rhs": promql_expr_to_ast_json(rhs),
+                "matching": matching,
+                "bool": modifier.as_ref().map(|m| m.return_bool).unwrap_or(false),
+            })
+        }
+        PromqlExpr::Paren(ParenExpr { expr }) => json!({
+            "type": "parenExpr",
+            "expr": promql_expr_to_ast_json(expr),
+        }),
+        PromqlExpr::Subquery(SubqueryExpr {
+            expr,
+            offset,
+            at,
+            range,
+            step,
+        }) => {
+            let (timestamp, start_or_end) = at_modifier_to_json(at);
+            json!({
+                "type": "subquery",
+                "expr": promql_expr_to_ast_json(expr),
+                "range": range.as_millis() as i64,
+                "offset": offset_to_millis(offset),
+                "step": step.map(|step| step.as_millis() as i64).unwrap_or_default(),
+                "timestamp": timestamp,
+                "startOrEnd": start_or_end,
+            })
+        }
+        PromqlExpr::NumberLiteral(NumberLiteral { val }) => {
+            // Formats special values like Go does.
+            let val = if val.is_nan() {
+                "NaN".to_string()
+            } else if val.is_infinite() {
+                if val.is_sign_positive() {
+                    "+Inf"
+                } else {
+                    "-Inf"
+                }
+                .to_string()
+            } else {
+                val.to_string()
+            };
+            json!({
+                "type": "numberLiteral",
+                "val": val,
+            })
+        }
+        PromqlExpr::StringLiteral(StringLiteral { val }) => json!({
+            "type": "stringLiteral",
+            "val": val,
+        }),
+        PromqlExpr::VectorSelector(vs) => {
+            let (timestamp, start_or_end) = at_modifier_to_json(&vs.at);
+            json!({
+                "type": "vectorSelector",
+                "name": vs.name.clone().unwrap_or_default(),
+                "offset": offset_to_millis(&vs.offset),
+                "matchers": matchers_to_json(vs),
+                "timestamp": timestamp,
+                "startOrEnd": start_or_end,
+            })
+        }
+        PromqlExpr::MatrixSelector(MatrixSelector { vs, range }) => {
+            let (timestamp, start_or_end) = at_modifier_to_json(&vs.at);
+            json!({
+                "type": "matrixSelector",
+                "name": vs.name.clone().unwrap_or_default(),
+                "range": range.as_millis() as i64,
+                "offset": offset_to_millis(&vs.offset),
+                "matchers": matchers_to_json(vs),
+                "timestamp": timestamp,
+                "startOrEnd": start_or_end,
+            })
+        }
+        PromqlExpr::Call(Call { func, args }) => json!({
+            "type": "call",
+            "func": {
+                "name": func.name,
+                "argTypes": func.arg_types.iter().map(|t| t.to_string()).collect::<Vec<_>>(),
+                "variadic": i32::from(func.variadic),
+                "returnType": func.return_type.to_string(),
+            },
+            "args": args
+                .args
+                .iter()
+                .map(|arg| promql_expr_to_ast_json(arg))
+                .collect::<Vec<_>>(),
+        }),
+        // Extensions like `EXPLAIN` are not part of PromQL.
+        PromqlExpr::Extension(_) => serde_json::Value::Null,
+    }
+}
+
+fn labels_to_vec(labels: &Labels) -> Vec<String> {
+    labels.labels.iter().cloned().collect()
+}
+
+fn offset_to_millis(offset: &Option<Offset>) -> i64 {
+    match offset {
+        Some(Offset::Pos(duration)) => duration.as_millis() as i64,
+        Some(Offset::Neg(duration)) => -(duration.as_millis() as i64),
+        None => 0,
+    }
+}
+
+/// Returns the `timestamp` and `startOrEnd` fields of the `@` modifier.
+fn at_modifier_to_json(at: &Option<AtModifier>) -> (Option<i64>, Option<&'static str>) {
+    match at {
+        Some(AtModifier::At(time)) => {
+            let millis = match time.duration_since(UNIX_EPOCH) {
+                Ok(duration) => duration.as_millis() as i64,
+                Err(e) => -(e.duration().as_millis() as i64),
+            };
+            (Some(millis), None)
+        }
+        Some(AtModifier::Start) => (None, Some("start")),
+        Some(AtModifier::End) => (None, Some("end")),
+        None => (None, None),
+    }
+}
+
+fn matchers_to_json(vs: &VectorSelector) -> Vec<serde_json::Value> {
+    // Prometheus keeps the metric name as a matcher.
+    let name_matcher = vs
+        .name
+        .as_ref()
+        .filter(|_| vs.matchers.find_matcher(METRIC_NAME).is_none())
+        .map(|name| {
+            json!({
+                "type": "=",
+                "name": METRIC_NAME,
+                "value": name,
+            })
+        });
+    name_matcher
+        .into_iter()
+        .chain(vs.matchers.matchers.iter().map(|matcher| {
+            let op = match matcher.op {
+                MatchOp::Equal => "=",
+                MatchOp::NotEqual => "!=",
+                MatchOp::Re(_) => "=~",
+                MatchOp::NotRe(_) => "!~",
+            };
+            json!({
+                "type": op,
+                "name": matcher.name,
+                "value": matcher.value,
+            })
+        }))
+        .collect()
+}
+
 #[derive(Debug, Default, Serialize, Deserialize, JsonSchema)]
 pub struct InstantQuery {
     query: Option<String>,
@@ -444,8 +699,7 @@ pub async fn labels_query(
 ) -> Json<PrometheusJsonResponse> {
     let _timer = crate::metrics::METRIC_HTTP_PROMQL_LABEL_QUERY_ELAPSED.start_timer();
 
-    let db = &params.db.unwrap_or(DEFAULT_SCHEMA_NAME.to_string());
-    let (catalog, schema) = parse_catalog_and_schema_from_db_string(db);
+    let (catalog, schema) = parse_database(params.db.as_deref());
 
     let mut queries = params.matches.0;
     if queries.is_empty() {
@@ -475,6 +729,35 @@ pub async fn labels_query(
     let _ = labels.insert(METRIC_NAME.to_string());
 
     for query in queries {
+        // Reads the columns from the table schema instead of evaluating the query if it
+        // only selects a metric.
+        if let Some(metric) = selected_metric(&query) {
+            match handler
+                .catalog_manager()
+                .table(catalog, schema, &metric)
+                .await
+            {
+                Ok(Some(table)) => {
+                    labels.extend(
+                        table
+                            .schema()
+                            .column_schemas()
+                            .iter()
+                            .map(|column| column.name.clone()),
+                    );
+                    continue;
+                }
+                // Prometheus won't report error if querying nonexist metric
+                Ok(None) => continue,
+                Err(e) => {
+                    return PrometheusJsonResponse::error(
+                        e.status_code().to_string(),
+                        e.output_msg(),
+                    )
+                }
+            }
+        }
+
         let prom_query = PromQuery {
             query,
             start: start.clone(),
@@ -515,6 +798,7 @@ async fn get_all_column_names(
     let table_names = manager.table_names(catalog, schema).await?;
 
     let mut labels = HashSet::new();
+    let _ = labels.insert(METRIC_NAME.to_string());
     for table_name in table_names {
         let Some(table) = manager.table(catalog, schema, &table_name).await? else {
             continue;
@@ -524,6 +808,8 @@ async fn get_all_column_names(
             labels.insert(column.name.to_string());
         }
     }
+    let _ = labels.remove(TIMESTAMP_COLUMN_NAME);
+    let _ = labels.remove(FIELD_COLUMN_NAME);
 
     let mut labels_vec = labels.into_iter().collect::<Vec<_>>();
     labels_vec.sort_unstable();
@@ -699,6 +985,7 @@ pub struct LabelValueQuery {
     #[serde(flatten)]
     matches: Matches,
     db: Option<String>,
+    limit: Option<usize>,
 }
 
 #[axum_macros::debug_handler]
@@ -710,8 +997,7 @@ pub async fn label_values_query(
 ) -> Json<PrometheusJsonResponse> {
     let _timer = crate::metrics::METRIC_HTTP_PROMQL_LABEL_VALUE_QUERY_ELAPSED.start_timer();
 
-    let db = &params.db.unwrap_or(DEFAULT_SCHEMA_NAME.to_string());
-    let (catalog, schema) = parse_catalog_and_schema_from_db_string(db);
+    let (catalog, schema) = parse_database(params.db.as_deref());
 
     if label_name == METRIC_NAME_LABEL {
         let mut table_names = match handler.catalog_manager().table_names(catalog, schema).await {
@@ -721,20 +1007,76 @@ pub async fn label_values_query(
             }
         };
         table_names.sort_unstable();
+        if let Some(limit) = params.limit {
+            table_names.truncate(limit);
+        }
         return PrometheusJsonResponse::success(PrometheusResponse::LabelValues(table_names));
     }
 
+    let start = params.start.unwrap_or_else(yesterday_rfc3339);
+    let end = params.end.unwrap_or_else(current_time_rfc3339);
+    let range = parse_prom_time(&start)
+        .zip(parse_prom_time(&end))
+        .map(|(start, end)| TimestampRange::new_inclusive(Some(start), Some(end)));
+    let limit = params.limit.unwrap_or(usize::MAX);
+
     let queries = params.matches.0;
     if queries.is_empty() {
-        return PrometheusJsonResponse::error("Invalid argument", "match[] parameter is required");
+        // Collects the label values of all metrics, e.g. for Grafana's `label_values(label)`.
+        let Some(range) = &range else {
+            return PrometheusJsonResponse::error(
+                "Invalid argument",
+                format!("invalid time range, start: {start}, end: {end}"),
+            );
+        };
+        return match all_tag_values(
+            &handler,
+            catalog,
+            schema,
+            &label_name,
+            range,
+            limit,
+            query_ctx,
+        )
+        .await
+        {
+            Ok(values) => PrometheusJsonResponse::success(PrometheusResponse::LabelValues(values)),
+            Err(err) => {
+                PrometheusJsonResponse::error(err.status_code().to_string(), err.output_msg())
+            }
+        };
     }
 
-    let start = params.start.unwrap_or_else(yesterday_rfc3339);
-    let end = params.end.unwrap_or_else(current_time_rfc3339);
-
     let mut label_values = HashSet::new();
 
     for query in queries {
+        // Scans the tag instead of evaluating the query if it only selects a metric.
+        if let (Some(range), Some(metric)) = (&range, selected_metric(&query)) {
+            let catalog_manager = handler.catalog_manager();
+            if is_tag_column(&catalog_manager, catalog, schema, &metric, &label_name).await {
+                let table_ref = TableReference::full(catalog, schema, &metric);
+                match handler
+                    .tag_values(
+                        table_ref,
+                        &label_name,
+                        Some(range),
+                        limit,
+                        query_ctx.clone(),
+                    )
+                    .await
+                {
+                    Ok(values) => label_values.extend(values),
+                    Err(err) => {
+                        return PrometheusJsonResponse::error(
+                            err.status_code().to_string(),
+                            err.output_msg(),
+                        )
+                    }
+                }
+                continue;
+            }
+        }
+
         let prom_query = PromQuery {
             query,
             start: start.clone(),
@@ -758,9 +1100,90 @@ pub async fn label_values_query(
 
     let mut label_values: Vec<_> = label_values.into_iter().collect();
     label_values.sort();
+    label_values.truncate(limit);
     PrometheusJsonResponse::success(PrometheusResponse::LabelValues(label_values))
 }
 
+/// Parses a time of the API, which is either a RFC 3339 time or a unix timestamp in seconds.
+fn parse_prom_time(time: &str) -> Option<Timestamp> {
+    if let Ok(time) = DateTime::parse_from_rfc3339(time) {
+        return Some(Timestamp::new_millisecond(time.timestamp_millis()));
+    }
+    let secs = time.parse::<f64>().ok()?;
+    Some(Timestamp::new_millisecond((secs * 1000.0) as i64))
+}
+
+/// Returns the metric if the `query` only selects a metric, e.g. `up` or `{__name__="up"}`.
+fn selected_metric(query: &str) -> Option<String> {
+    let Ok(PromqlExpr::VectorSelector(vs)) = promql_parser::parser::parse(query) else {
+        return None;
+    };
+    if vs.offset.is_some() || vs.at.is_some() {
+        return None;
+    }
+    let name = vs.name.or(vs.matchers.find_matcher(METRIC_NAME))?;
+    vs.matchers
+        .matchers
+        .iter()
+        .all(|matcher| {
+            matcher.name == METRIC_NAME
+                && matches!(matcher.op, MatchOp::Equal)
+                && matcher.value == name
+        })
+        .then_some(name)
+}
+
+/// Returns sorted distinct values of the tag `label` of all tables in the schema.
+async fn all_tag_values(
+    handler: &PrometheusHandlerRef,
+    catalog: &str,
+    schema: &str,
+    label: &str,
+    range: &TimestampRange,
+    limit: usize,
+    query_ctx: QueryContextRef,
+) -> Result<Vec<String>> {
+    let catalog_manager = handler.catalog_manager();
+    let table_names = catalog_manager
+        .table_names(catalog, schema)
+        .await
+        .context(CatalogSnafu)?;
+
+    let mut values = HashSet::new();
+    for table_name in table_names {
+        if !is_tag_column(&catalog_manager, catalog, schema, &table_name, label).await {
+            continue;
+        }
+        let table_ref = TableReference::full(catalog, schema, &table_name);
+        let table_values = handler
+            .tag_values(table_ref, label, Some(range), limit, query_ctx.clone())
+            .await?;
+        values.extend(table_values);
+    }
+
+    let mut values: Vec<_> = values.into_iter().collect();
+    values.sort_unstable();
+    values.truncate(limit);
+    Ok(values)
+}
+
+/// Returns whether the `column` is a tag of the table.
+async fn is_tag_column(
+    manager: &CatalogManagerRef,
+    catalog: &str,
+    schema: &str,
+    table: &str,
+    column: &str,
+) -> bool {
+    let Ok(Some(table)) = manager.table(catalog, schema, table).await else {
+        return false;
+    };
+    table
+        .schema()
+        .column_index_by_name(column)
+        .is_some_and(|idx| table.table_info().meta.primary_key_indices.contains(&idx))
+}
+
 async fn retrieve_label_values(
     result: Result<Output>,
     label_name: &str,
@@ -853,7 +1276,10 @@ pub async fn series_query(
 
     let mut series = Vec::new();
     for query in queries {
-        let table_name = query.clone();
+        let table_name = retrieve_metric_name_and_result_type(&query)
+            .ok()
+            .and_then(|(metric_name, _)| metric_name)
+            .unwrap_or_default();
         let prom_query = PromQuery {
             query,
             start: start.clone(),
@@ -869,3 +1295,28 @@ pub async fn series_query(
     }
     PrometheusJsonResponse::success(PrometheusResponse::Series(series))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_prom_time() {
+        let expected = Some(Timestamp::new_millisecond(1_700_000_000_500));
+        assert_eq!(expected, parse_prom_time("1700000000.5"));
+        assert_eq!(expected, parse_prom_time("2023-11-14T22:13:20.5Z"));
+        assert_eq!(None, parse_prom_time("yesterday"));
+    }
+
+    #[test]
+    fn test_selected_metric() {
+        assert_eq!(Some("up".to_string()), selected_metric("up"));
+        assert_eq!(
+            Some("up".to_string()),
+            selected_metric(r#"{__name__="up"}"#)
+        );
+        assert_eq!(None, selected_metric(r#"up{job="api"}"#));
+        assert_eq!(None, selected_metric("up offset 5m"));
+        assert_eq!(None, selected_metric("rate(up[5m])"));
+    }
+}
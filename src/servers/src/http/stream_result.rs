@@ -0,0 +1,296 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Streaming results of SQL queries over HTTP.
+//!
+//! Unlike the json formats, the result isn't buffered. Each record batch is encoded and
+//! sent to the client once it's produced by the query.
+
+use std::future::ready;
+
+use axum::body::StreamBody;
+use axum::http::header;
+use axum::response::{IntoResponse, Json, Response};
+use bytes::Bytes;
+use common_error::ext::ErrorExt;
+use common_error::status_code::StatusCode;
+use common_query::Output;
+use common_recordbatch::{RecordBatch, SendableRecordBatchStream};
+use datatypes::arrow::ipc::writer::StreamWriter;
+use datatypes::data_type::DataType;
+use futures::stream::{self, BoxStream};
+use futures::StreamExt;
+use serde_json::{json, Value};
+use snafu::ResultExt;
+
+use crate::error::{CollectRecordbatchSnafu, EncodeArrowIpcSnafu, Result, ToJsonSnafu};
+use crate::http::{ColumnSchema, JsonResponse, ResponseFormat, Schema};
+use crate::query_registry::RunningQueryGuard;
+
+pub const NDJSON_CONTENT_TYPE: &str = "application/x-ndjson";
+pub const ARROW_STREAM_CONTENT_TYPE: &str = "application/vnd.apache.arrow.stream";
+
+/// Formats to stream results of queries.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StreamFormat {
+    /// Newline delimited json. The first line of a query result is its schema, and
+    /// each following line is a row. A failure is reported by a line of the error.
+    Ndjson,
+    /// Arrow IPC streaming format. Only a single query is supported.
+    ArrowStream,
+}
+
+impl StreamFormat {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "ndjson" => Some(StreamFormat::Ndjson),
+            "arrow-stream" => Some(StreamFormat::ArrowStream),
+            _ => None,
+        }
+    }
+
+    fn content_type(&self) -> &'static str {
+        match self {
+            StreamFormat::Ndjson => NDJSON_CONTENT_TYPE,
+            StreamFormat::ArrowStream => ARROW_STREAM_CONTENT_TYPE,
+        }
+    }
+}
+
+/// Streams the `outputs` in the `format`.
+///
+/// The `guard` keeps the query registered until the response body is dropped.
+pub fn stream_response(
+    outputs: Vec<Result<Output>>,
+    format: StreamFormat,
+    guard: RunningQueryGuard,
+) -> Response {
+    let chunks = match format {
+        StreamFormat::Ndjson => ndjson_chunks(outputs),
+        StreamFormat::ArrowStream => match arrow_stream_chunks(outputs) {
+            Ok(chunks) => chunks,
+            Err(e) => {
+                return Json(JsonResponse::with_error(e, ResponseFormat::GreptimedbV1))
+                    .into_response()
+            }
+        },
+    };
+    let chunks = chunks.map(move |chunk| {
+        // Moves the guard into the body.
+        let _ = &guard;
+        chunk
+    });
+
+    (
+        [(header::CONTENT_TYPE, format.content_type())],
+        StreamBody::new(chunks),
+    )
+        .into_response()
+}
+
+/// Encodes the `outputs` to lines, stops at the first error.
+fn ndjson_chunks(outputs: Vec<Result<Output>>) -> BoxStream<'static, Result<Bytes>> {
+    stream::iter(outputs)
+        .flat_map(|output| match output {
+            Ok(Output::AffectedRows(rows)) => {
+                stream::once(ready(json_line(&json!({ "affectedrows": rows })))).boxed()
+            }
+            Ok(Output::RecordBatches(recordbatches)) => ndjson_records(recordbatches.as_stream()),
+            Ok(Output::Stream(stream)) => ndjson_records(stream),
+            Err(e) => stream::once(ready(Err(e))).boxed(),
+        })
+        .scan(false, |failed, line| {
+            if *failed {
+                return ready(None);
+            }
+            let line = line.unwrap_or_else(|e| {
+                *failed = true;
+                error_line(&e)
+            });
+            ready(Some(Ok(line)))
+        })
+        .boxed()
+}
+
+fn ndjson_records(stream: SendableRecordBatchStream) -> BoxStream<'static, Result<Bytes>> {
+    let schema = Schema::new(
+        stream
+            .schema()
+            .column_schemas()
+            .iter()
+            .map(|cs| ColumnSchema::new(cs.name.clone(), cs.data_type.name()))
+            .collect(),
+    );
+    let schema_line = json_line(&json!({ "schema": schema }));
+
+    stream::once(ready(schema_line))
+        .chain(stream.map(|batch| {
+            batch
+                .context(CollectRecordbatchSnafu)
+                .and_then(|batch| rows_lines(&batch))
+        }))
+        .boxed()
+}
+
+/// Encodes rows of the `batch` to lines of json arrays.
+fn rows_lines(batch: &RecordBatch) -> Result<Bytes> {
+    let mut buf = Vec::new();
+    for row in batch.rows() {
+        let row = row
+            .into_iter()
+            .map(Value::try_from)
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .context(ToJsonSnafu)?;
+        serde_json::to_writer(&mut buf, &row).context(ToJsonSnafu)?;
+        buf.push(b'\n');
+    }
+    Ok(buf.into())
+}
+
+fn json_line(value: &Value) -> Result<Bytes> {
+    let mut buf = serde_json::to_vec(value).context(ToJsonSnafu)?;
+    buf.push(b'\n');
+    Ok(buf.into())
+}
+
+fn error_line(error: &crate::error::Error) -> Bytes {
+    let line = json!({
+        "code": error.status_code() as u32,
+        "error": error.output_msg(),
+    });
+    // Serializing a json value never fails.
+    json_line(&line).unwrap_or_default()
+}
+
+/// Encodes the only output in `outputs` to the Arrow IPC streaming format.
+///
+/// A failure while streaming aborts the response, so the client sees an incomplete stream.
+fn arrow_stream_chunks(
+    mut outputs: Vec<Result<Output>>,
+) -> Result<BoxStream<'static, Result<Bytes>>> {
+    if outputs.len() != 1 {
+        return crate::error::NotSupportedSnafu {
+            feat: "streaming results of multiple statements in arrow-stream format",
+        }
+        .fail();
+    }
+    let stream = match outputs.remove(0)? {
+        Output::Stream(stream) => stream,
+        Output::RecordBatches(recordbatches) => recordbatches.as_stream(),
+        Output::AffectedRows(_) => {
+            return crate::error::NotSupportedSnafu {
+                feat: "streaming affected rows in arrow-stream format",
+            }
+            .fail();
+        }
+    };
+
+    let writer = StreamWriter::try_new(Vec::new(), stream.schema().arrow_schema())
+        .context(EncodeArrowIpcSnafu)?;
+    // Appends a `None` to finish the stream after all batches are written.
+    let chunks = stream
+        .map(Some)
+        .chain(stream::once(ready(None)))
+        .scan(writer, |writer, batch| {
+            let written = match batch {
+                Some(Ok(batch)) => writer
+                    .write(batch.df_record_batch())
+                    .context(EncodeArrowIpcSnafu),
+                Some(Err(e)) => Err(e).context(CollectRecordbatchSnafu),
+                None => writer.finish().context(EncodeArrowIpcSnafu),
+            };
+            let chunk = written.map(|_| Bytes::from(std::mem::take(writer.get_mut())));
+            ready(Some(chunk))
+        });
+    Ok(chunks.boxed())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use common_recordbatch::RecordBatches;
+    use datatypes::arrow::ipc::reader::StreamReader;
+    use datatypes::prelude::ConcreteDataType;
+    use datatypes::schema::{ColumnSchema as DtColumnSchema, Schema as DtSchema};
+    use datatypes::vectors::{StringVector, UInt32Vector};
+
+    use super::*;
+    use crate::error::Error;
+
+    fn new_output() -> Output {
+        let schema = Arc::new(DtSchema::new(vec![
+            DtColumnSchema::new("n", ConcreteDataType::uint32_datatype(), false),
+            DtColumnSchema::new("s", ConcreteDataType::string_datatype(), true),
+        ]));
+        let batches = (0..2)
+            .map(|i| {
+                RecordBatch::new(
+                    schema.clone(),
+                    vec![
+                        Arc::new(UInt32Vector::from_slice([i])) as _,
+                        Arc::new(StringVector::from(vec![format!("s{i}")])) as _,
+                    ],
+                )
+                .unwrap()
+            })
+            .collect();
+        Output::RecordBatches(RecordBatches::try_new(schema, batches).unwrap())
+    }
+
+    async fn collect(chunks: BoxStream<'static, Result<Bytes>>) -> Vec<u8> {
+        chunks.map(|chunk| chunk.unwrap().to_vec()).concat().await
+    }
+
+    #[tokio::test]
+    async fn test_ndjson_chunks() {
+        let outputs = vec![
+            Ok(Output::AffectedRows(3)),
+            Ok(new_output()),
+            Err(Error::NotSupported {
+                feat: "test".to_string(),
+            }),
+            Ok(Output::AffectedRows(1)),
+        ];
+        let body = collect(ndjson_chunks(outputs)).await;
+        let lines = String::from_utf8(body).unwrap();
+        let lines = lines.lines().collect::<Vec<_>>();
+        let error_line = format!(
+            r#"{{"code":{},"error":"Not supported: test"}}"#,
+            StatusCode::InvalidArguments as u32
+        );
+        // Stops at the error.
+        assert_eq!(
+            vec![
+                r#"{"affectedrows":3}"#,
+                r#"{"schema":{"column_schemas":[{"data_type":"UInt32","name":"n"},{"data_type":"String","name":"s"}]}}"#,
+                r#"[0,"s0"]"#,
+                r#"[1,"s1"]"#,
+                &error_line,
+            ],
+            lines
+        );
+    }
+
+    #[tokio::test]
+    async fn test_arrow_stream_chunks() {
+        let body = collect(arrow_stream_chunks(vec![Ok(new_output())]).unwrap()).await;
+        let reader = StreamReader::try_new(body.as_slice(), None).unwrap();
+        let num_rows = reader.map(|batch| batch.unwrap().num_rows()).sum::<usize>();
+        assert_eq!(2, num_rows);
+
+        assert!(arrow_stream_chunks(vec![Ok(Output::AffectedRows(1))]).is_err());
+        assert!(arrow_stream_chunks(vec![Ok(new_output()), Ok(new_output())]).is_err());
+    }
+}
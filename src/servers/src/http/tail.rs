@@ -0,0 +1,97 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use axum::extract::ws::{close_code, CloseFrame, Message, WebSocket, WebSocketUpgrade};
+use axum::extract::State;
+use axum::response::Response;
+use axum::Extension;
+use common_telemetry::{debug, warn};
+use session::context::QueryContextRef;
+
+use crate::tail::{TailFilter, TailHubRef};
+
+/// Handler of the websocket to tail newly ingested rows.
+///
+/// The client sends a [TailFilter] in JSON as the first message, then the server sends
+/// matched rows in [TailRows](crate::tail::TailRows) until the socket is closed.
+#[axum_macros::debug_handler]
+pub async fn tail(
+    State(hub): State<TailHubRef>,
+    Extension(query_ctx): Extension<QueryContextRef>,
+    ws: WebSocketUpgrade,
+) -> Response {
+    ws.on_upgrade(move |socket| handle_socket(socket, hub, query_ctx))
+}
+
+async fn handle_socket(mut socket: WebSocket, hub: TailHubRef, query_ctx: QueryContextRef) {
+    let filter = match socket.recv().await {
+        Some(Ok(Message::Text(text))) => match serde_json::from_str::<TailFilter>(&text) {
+            Ok(filter) => filter,
+            Err(e) => {
+                close_with_error(socket, format!("Invalid filter: {e}")).await;
+                return;
+            }
+        },
+        Some(Ok(Message::Close(_))) | None => return,
+        Some(Ok(_)) => {
+            close_with_error(socket, "Expect a filter in text message".to_string()).await;
+            return;
+        }
+        Some(Err(e)) => {
+            debug!("Failed to receive tail filter, error: {e}");
+            return;
+        }
+    };
+
+    let mut subscription = hub.subscribe(
+        query_ctx.current_catalog(),
+        query_ctx.current_schema(),
+        filter,
+    );
+    loop {
+        tokio::select! {
+            rows = subscription.recv() => {
+                let Some(rows) = rows else {
+                    break;
+                };
+                let text = match serde_json::to_string(&rows) {
+                    Ok(text) => text,
+                    Err(e) => {
+                        warn!("Failed to serialize tail rows, error: {e}");
+                        continue;
+                    }
+                };
+                if socket.send(Message::Text(text)).await.is_err() {
+                    break;
+                }
+            }
+            message = socket.recv() => {
+                match message {
+                    Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                    // Pings are answered by axum and other messages are ignored.
+                    Some(Ok(_)) => (),
+                }
+            }
+        }
+    }
+}
+
+async fn close_with_error(mut socket: WebSocket, reason: String) {
+    let _ = socket
+        .send(Message::Close(Some(CloseFrame {
+            code: close_code::INVALID,
+            reason: reason.into(),
+        })))
+        .await;
+}
@@ -0,0 +1,189 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use axum::body::Body;
+use axum::extract::{FromRequest, Multipart, Path, Query, State};
+use axum::http::{header, Request};
+use axum::response::IntoResponse;
+use axum::{Extension, Json};
+use bytes::Bytes;
+use serde::{Deserialize, Serialize};
+use session::context::QueryContextRef;
+use snafu::{ensure, OptionExt, ResultExt};
+
+use crate::error::{self, InvalidUploadSnafu, Result, TableNotFoundSnafu};
+use crate::query_handler::UploadHandlerRef;
+use crate::upload::{self, UploadFormat, UploadOptions};
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct UploadQuery {
+    /// `csv` or `parquet`, inferred from the file name of a multipart upload if absent.
+    pub format: Option<String>,
+    pub header: Option<bool>,
+    pub delimiter: Option<char>,
+    pub max_bad_rows: Option<usize>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UploadResponse {
+    pub rows: usize,
+    pub bad_rows: usize,
+}
+
+/// Uploads a CSV or Parquet file into the table, the file is either the body of the request
+/// or the first file of a `multipart/form-data` request.
+///
+/// The whole file is buffered and decoded in memory before any rows are written, so the
+/// file size is limited by the `http.body_limit` option and memory usage of an upload is
+/// several times the file size. Parquet files can't be streamed as their metadata is at
+/// the end of the file. Split large files into multiple uploads.
+#[axum_macros::debug_handler]
+pub async fn upload(
+    State(handler): State<UploadHandlerRef>,
+    Path(table_name): Path<String>,
+    Query(params): Query<UploadQuery>,
+    Extension(query_ctx): Extension<QueryContextRef>,
+    request: Request<Body>,
+) -> Result<impl IntoResponse> {
+    let db = query_ctx.get_db_string();
+    let _timer = crate::metrics::METRIC_HTTP_UPLOAD_ELAPSED
+        .with_label_values(&[db.as_str()])
+        .start_timer();
+
+    let (file_name, body) = if is_multipart(&request) {
+        read_multipart(request).await?
+    } else {
+        let body = hyper::body::to_bytes(request.into_body())
+            .await
+            .context(error::HyperSnafu)?;
+        (None, body)
+    };
+    let options = upload_options(&params, file_name.as_deref())?;
+
+    let table = handler
+        .table(&table_name, query_ctx.clone())
+        .await?
+        .context(TableNotFoundSnafu {
+            table_name: &table_name,
+        })?;
+    let table_info = table.table_info();
+    let decoded = upload::to_insert_requests(&table_info, body, &options)?;
+
+    let rows = handler.insert(decoded.requests, query_ctx).await?;
+    Ok(Json(UploadResponse {
+        rows,
+        bad_rows: decoded.bad_rows,
+    }))
+}
+
+fn upload_options(params: &UploadQuery, file_name: Option<&str>) -> Result<UploadOptions> {
+    let mut options = UploadOptions::default();
+    if let Some(format) = &params.format {
+        options.format = format.parse()?;
+    } else if let Some(extension) = file_name.and_then(|name| name.rsplit_once('.')) {
+        options.format = extension.1.parse()?;
+    }
+    if let Some(has_header) = params.header {
+        options.has_header = has_header;
+    }
+    if let Some(delimiter) = params.delimiter {
+        ensure!(
+            delimiter.is_ascii(),
+            InvalidUploadSnafu {
+                reason: format!("delimiter {delimiter} is not an ASCII character"),
+            }
+        );
+        options.delimiter = delimiter as u8;
+    }
+    if let Some(max_bad_rows) = params.max_bad_rows {
+        options.max_bad_rows = max_bad_rows;
+    }
+    Ok(options)
+}
+
+fn is_multipart(request: &Request<Body>) -> bool {
+    request
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .map(|value| value.trim_start().starts_with("multipart/form-data"))
+        .unwrap_or(false)
+}
+
+/// Reads the file name and content of the first file in the multipart request.
+async fn read_multipart(request: Request<Body>) -> Result<(Option<String>, Bytes)> {
+    let mut multipart = Multipart::from_request(request, &()).await.map_err(|e| {
+        InvalidUploadSnafu {
+            reason: e.to_string(),
+        }
+        .build()
+    })?;
+    while let Some(field) = multipart.next_field().await.map_err(|e| {
+        InvalidUploadSnafu {
+            reason: e.to_string(),
+        }
+        .build()
+    })? {
+        let Some(file_name) = field.file_name().map(|name| name.to_string()) else {
+            continue;
+        };
+        let body = field.bytes().await.map_err(|e| {
+            InvalidUploadSnafu {
+                reason: e.to_string(),
+            }
+            .build()
+        })?;
+        return Ok((Some(file_name), body));
+    }
+
+    InvalidUploadSnafu {
+        reason: "no file in the multipart request",
+    }
+    .fail()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_upload_options() {
+        let params = UploadQuery::default();
+        let options = upload_options(&params, Some("cpu.parquet")).unwrap();
+        assert_eq!(UploadFormat::Parquet, options.format);
+
+        let params = UploadQuery {
+            format: Some("CSV".to_string()),
+            header: Some(false),
+            delimiter: Some('\t'),
+            max_bad_rows: Some(10),
+        };
+        let options = upload_options(&params, Some("cpu.parquet")).unwrap();
+        assert_eq!(
+            UploadOptions {
+                format: UploadFormat::Csv,
+                has_header: false,
+                delimiter: b'\t',
+                max_bad_rows: 10,
+            },
+            options
+        );
+
+        let params = UploadQuery {
+            delimiter: Some('é'),
+            ..Default::default()
+        };
+        assert!(upload_options(&params, None).is_err());
+    }
+}
@@ -34,60 +34,72 @@ impl TryFrom<InfluxdbRequest> for RowInsertRequests {
     type Error = Error;
 
     fn try_from(value: InfluxdbRequest) -> Result<Self, Self::Error> {
-        let lines = parse_lines(&value.lines)
-            .collect::<influxdb_line_protocol::Result<Vec<_>>>()
-            .context(InfluxdbLineProtocolSnafu)?;
-
         let mut multi_table_data = MultiTableData::new();
+        let _ = write_lines(&value.lines, value.precision, &mut multi_table_data)?;
 
-        for line in &lines {
-            let table_name = line.series.measurement.as_str();
-            let tags = &line.series.tag_set;
-            let fields = &line.field_set;
-            let ts = line.timestamp;
-            // tags.len + fields.len + timestamp(+1)
-            let num_columns = tags.as_ref().map(|x| x.len()).unwrap_or(0) + fields.len() + 1;
-
-            let table_data = multi_table_data.get_or_default_table_data(table_name, num_columns, 0);
-            let mut one_row = table_data.alloc_one_row();
-
-            // tags
-            if let Some(tags) = tags {
-                let kvs = tags.iter().map(|(k, v)| (k.to_string(), v.as_str()));
-                row_writer::write_tags(table_data, kvs, &mut one_row)?;
-            }
+        Ok(multi_table_data.into_row_insert_requests().0)
+    }
+}
 
-            // fields
-            let fields = fields.iter().map(|(k, v)| {
-                let (datatype, value) = match v {
-                    FieldValue::I64(v) => (ColumnDataType::Int64, ValueData::I64Value(*v)),
-                    FieldValue::U64(v) => (ColumnDataType::Uint64, ValueData::U64Value(*v)),
-                    FieldValue::F64(v) => (ColumnDataType::Float64, ValueData::F64Value(*v)),
-                    FieldValue::String(v) => (
-                        ColumnDataType::String,
-                        ValueData::StringValue(v.to_string()),
-                    ),
-                    FieldValue::Boolean(v) => (ColumnDataType::Boolean, ValueData::BoolValue(*v)),
-                };
-                (k.to_string(), datatype, value)
-            });
-            row_writer::write_fields(table_data, fields, &mut one_row)?;
-
-            // timestamp
-            let precision = unwrap_or_default_precision(value.precision);
-            row_writer::write_ts_precision(
-                table_data,
-                INFLUXDB_TIMESTAMP_COLUMN_NAME,
-                ts,
-                precision,
-                &mut one_row,
-            )?;
-
-            table_data.add_row(one_row);
+/// Parses line protocol `lines` and writes rows into `multi_table_data`.
+///
+/// Returns the number of rows written.
+pub(crate) fn write_lines(
+    lines: &str,
+    precision: Option<Precision>,
+    multi_table_data: &mut MultiTableData,
+) -> Result<usize, Error> {
+    let lines = parse_lines(lines)
+        .collect::<influxdb_line_protocol::Result<Vec<_>>>()
+        .context(InfluxdbLineProtocolSnafu)?;
+
+    for line in &lines {
+        let table_name = line.series.measurement.as_str();
+        let tags = &line.series.tag_set;
+        let fields = &line.field_set;
+        let ts = line.timestamp;
+        // tags.len + fields.len + timestamp(+1)
+        let num_columns = tags.as_ref().map(|x| x.len()).unwrap_or(0) + fields.len() + 1;
+
+        let table_data = multi_table_data.get_or_default_table_data(table_name, num_columns, 0);
+        let mut one_row = table_data.alloc_one_row();
+
+        // tags
+        if let Some(tags) = tags {
+            let kvs = tags.iter().map(|(k, v)| (k.to_string(), v.as_str()));
+            row_writer::write_tags(table_data, kvs, &mut one_row)?;
         }
 
-        Ok(multi_table_data.into_row_insert_requests().0)
+        // fields
+        let fields = fields.iter().map(|(k, v)| {
+            let (datatype, value) = match v {
+                FieldValue::I64(v) => (ColumnDataType::Int64, ValueData::I64Value(*v)),
+                FieldValue::U64(v) => (ColumnDataType::Uint64, ValueData::U64Value(*v)),
+                FieldValue::F64(v) => (ColumnDataType::Float64, ValueData::F64Value(*v)),
+                FieldValue::String(v) => (
+                    ColumnDataType::String,
+                    ValueData::StringValue(v.to_string()),
+                ),
+                FieldValue::Boolean(v) => (ColumnDataType::Boolean, ValueData::BoolValue(*v)),
+            };
+            (k.to_string(), datatype, value)
+        });
+        row_writer::write_fields(table_data, fields, &mut one_row)?;
+
+        // timestamp
+        let precision = unwrap_or_default_precision(precision);
+        row_writer::write_ts_precision(
+            table_data,
+            INFLUXDB_TIMESTAMP_COLUMN_NAME,
+            ts,
+            precision,
+            &mut one_row,
+        )?;
+
+        table_data.add_row(one_row);
     }
+
+    Ok(lines.len())
 }
 
 #[inline]
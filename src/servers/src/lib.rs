@@ -21,27 +21,37 @@ use query::plan::LogicalPlan;
 use serde::{Deserialize, Serialize};
 
 pub mod configurator;
+pub mod connection;
 pub mod error;
 pub mod export_metrics;
+pub mod flight_sql;
 pub mod grpc;
 pub mod heartbeat_options;
 pub mod http;
 pub mod influxdb;
 pub mod interceptor;
 pub mod line_writer;
+pub mod loki;
 mod metrics;
 pub mod metrics_handler;
+pub mod mqtt;
 pub mod mysql;
 pub mod opentsdb;
 pub mod otlp;
+pub mod pipeline;
 pub mod postgres;
 pub mod prom_store;
 pub mod prometheus_handler;
 pub mod query_handler;
+pub mod query_registry;
+pub mod rate_limit;
 mod row_writer;
 pub mod server;
 mod shutdown;
+pub mod statement_timeout;
+pub mod tail;
 pub mod tls;
+pub mod upload;
 
 #[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq)]
 #[serde(rename_all = "lowercase")]
@@ -0,0 +1,496 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Loki push API, so log agents like Promtail and Grafana Agent can ship logs to GreptimeDB.
+//!
+//! A push request contains streams of log entries. Labels of a stream are written as tags,
+//! the line of an entry is written as the `line` field and structured metadata of an entry
+//! is written as the `structured_metadata` field in JSON. All streams of a request are
+//! written to the same table.
+//!
+//! See <https://grafana.com/docs/loki/latest/reference/api/#push-log-entries-to-loki> for
+//! the protobuf and JSON formats of push requests.
+
+use std::collections::BTreeMap;
+
+use api::v1::value::ValueData;
+use api::v1::{ColumnDataType, RowInsertRequests};
+use common_grpc::writer::Precision;
+use prost::Message;
+use snafu::{ensure, OptionExt, ResultExt};
+
+use crate::error::{DecodeLokiRequestSnafu, InvalidLokiRequestSnafu, LokiJsonRequestSnafu, Result};
+use crate::prom_store::snappy_decompress;
+use crate::row_writer::{self, MultiTableData};
+
+/// Table to write logs if the request doesn't specify one.
+pub const LOKI_TABLE_NAME: &str = "loki_logs";
+pub const LOKI_TIMESTAMP: &str = "greptime_timestamp";
+pub const LOKI_LINE: &str = "line";
+pub const LOKI_STRUCTURED_METADATA: &str = "structured_metadata";
+
+/// Tags, the timestamp, the line and the structured metadata.
+const APPROXIMATE_COLUMN_COUNT: usize = 8;
+
+/// Push request in protobuf, it's compatible with `logproto.PushRequest` of Loki.
+#[derive(Clone, PartialEq, Message)]
+pub struct PushRequest {
+    #[prost(message, repeated, tag = "1")]
+    pub streams: Vec<StreamAdapter>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct StreamAdapter {
+    /// Labels in the Prometheus format, e.g. `{job="varlogs", host="host1"}`.
+    #[prost(string, tag = "1")]
+    pub labels: String,
+    #[prost(message, repeated, tag = "2")]
+    pub entries: Vec<EntryAdapter>,
+    #[prost(uint64, tag = "3")]
+    pub hash: u64,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct EntryAdapter {
+    #[prost(message, optional, tag = "1")]
+    pub timestamp: Option<Timestamp>,
+    #[prost(string, tag = "2")]
+    pub line: String,
+    #[prost(message, repeated, tag = "3")]
+    pub structured_metadata: Vec<LabelPairAdapter>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct LabelPairAdapter {
+    #[prost(string, tag = "1")]
+    pub name: String,
+    #[prost(string, tag = "2")]
+    pub value: String,
+}
+
+/// Same as `google.protobuf.Timestamp`.
+#[derive(Clone, PartialEq, Message)]
+pub struct Timestamp {
+    #[prost(int64, tag = "1")]
+    pub seconds: i64,
+    #[prost(int32, tag = "2")]
+    pub nanos: i32,
+}
+
+/// A stream of log entries sharing the same labels.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LokiStream {
+    pub labels: Vec<(String, String)>,
+    pub entries: Vec<LokiEntry>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LokiEntry {
+    /// Timestamp in nanoseconds.
+    pub ts: i64,
+    pub line: String,
+    pub structured_metadata: BTreeMap<String, String>,
+}
+
+/// Decodes a snappy compressed protobuf push request.
+pub fn decode_protobuf(body: &[u8]) -> Result<Vec<LokiStream>> {
+    let buf = snappy_decompress(body)?;
+    let request = PushRequest::decode(&buf[..]).context(DecodeLokiRequestSnafu)?;
+    request
+        .streams
+        .into_iter()
+        .map(|stream| {
+            let labels = parse_labels(&stream.labels)?;
+            let entries = stream
+                .entries
+                .into_iter()
+                .map(|entry| {
+                    let ts = entry
+                        .timestamp
+                        .map(|ts| ts.seconds * 1_000_000_000 + ts.nanos as i64)
+                        .unwrap_or_default();
+                    LokiEntry {
+                        ts,
+                        line: entry.line,
+                        structured_metadata: entry
+                            .structured_metadata
+                            .into_iter()
+                            .map(|pair| (pair.name, pair.value))
+                            .collect(),
+                    }
+                })
+                .collect();
+            Ok(LokiStream { labels, entries })
+        })
+        .collect()
+}
+
+/// Decodes a JSON push request like
+/// `{"streams": [{"stream": {"job": "varlogs"}, "values": [["<ns>", "<line>"]]}]}`.
+///
+/// A value may have structured metadata as the third element.
+pub fn decode_json(body: &[u8]) -> Result<Vec<LokiStream>> {
+    let request: serde_json::Value = serde_json::from_slice(body).context(LokiJsonRequestSnafu)?;
+    let streams = request
+        .get("streams")
+        .and_then(|streams| streams.as_array())
+        .context(InvalidLokiRequestSnafu {
+            reason: "expect an array of streams",
+        })?;
+
+    streams
+        .iter()
+        .map(|stream| {
+            let labels = match stream.get("stream") {
+                Some(serde_json::Value::Object(labels)) => labels
+                    .iter()
+                    .map(|(name, value)| Ok((name.clone(), json_string(value, "label")?)))
+                    .collect::<Result<Vec<_>>>()?,
+                Some(serde_json::Value::Null) | None => vec![],
+                Some(_) => {
+                    return InvalidLokiRequestSnafu {
+                        reason: "expect labels of a stream to be an object",
+                    }
+                    .fail()
+                }
+            };
+            let values = stream
+                .get("values")
+                .and_then(|values| values.as_array())
+                .context(InvalidLokiRequestSnafu {
+                    reason: "expect an array of values in a stream",
+                })?;
+            let entries = values.iter().map(json_entry).collect::<Result<Vec<_>>>()?;
+            Ok(LokiStream { labels, entries })
+        })
+        .collect()
+}
+
+fn json_entry(value: &serde_json::Value) -> Result<LokiEntry> {
+    let value = value
+        .as_array()
+        .filter(|value| value.len() == 2 || value.len() == 3)
+        .context(InvalidLokiRequestSnafu {
+            reason: "expect a value to be an array of the timestamp, the line and optional structured metadata",
+        })?;
+    let ts = json_string(&value[0], "timestamp")?;
+    let ts = ts
+        .parse::<i64>()
+        .ok()
+        .with_context(|| InvalidLokiRequestSnafu {
+            reason: format!("invalid timestamp in nanoseconds: {ts}"),
+        })?;
+    let line = json_string(&value[1], "line")?;
+    let structured_metadata = match value.get(2) {
+        Some(serde_json::Value::Object(metadata)) => metadata
+            .iter()
+            .map(|(name, value)| Ok((name.clone(), json_string(value, "structured metadata")?)))
+            .collect::<Result<_>>()?,
+        Some(serde_json::Value::Null) | None => BTreeMap::new(),
+        Some(_) => {
+            return InvalidLokiRequestSnafu {
+                reason: "expect structured metadata to be an object",
+            }
+            .fail()
+        }
+    };
+
+    Ok(LokiEntry {
+        ts,
+        line,
+        structured_metadata,
+    })
+}
+
+fn json_string(value: &serde_json::Value, what: &str) -> Result<String> {
+    value
+        .as_str()
+        .map(|s| s.to_string())
+        .with_context(|| InvalidLokiRequestSnafu {
+            reason: format!("expect {what} to be a string, got {value}"),
+        })
+}
+
+/// Parses labels in the Prometheus format, e.g. `{job="varlogs", host="host1"}`.
+pub fn parse_labels(labels: &str) -> Result<Vec<(String, String)>> {
+    let invalid = |reason: &str| {
+        InvalidLokiRequestSnafu {
+            reason: format!("invalid labels {labels}, {reason}"),
+        }
+        .build()
+    };
+
+    let inner = labels
+        .trim()
+        .strip_prefix('{')
+        .and_then(|s| s.strip_suffix('}'))
+        .ok_or_else(|| invalid("expect labels in braces"))?;
+
+    let mut result = Vec::new();
+    let mut chars = inner.chars().peekable();
+    loop {
+        while chars.next_if(|c| c.is_whitespace() || *c == ',').is_some() {}
+        if chars.peek().is_none() {
+            break;
+        }
+
+        let mut name = String::new();
+        while let Some(c) = chars.next_if(|c| c.is_alphanumeric() || *c == '_') {
+            name.push(c);
+        }
+        ensure!(
+            !name.is_empty(),
+            InvalidLokiRequestSnafu {
+                reason: format!("invalid labels {labels}, expect a label name"),
+            }
+        );
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+        if chars.next() != Some('=') {
+            return Err(invalid("expect '=' after a label name"));
+        }
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+        if chars.next() != Some('"') {
+            return Err(invalid("expect a quoted label value"));
+        }
+
+        let mut value = String::new();
+        loop {
+            match chars.next() {
+                Some('"') => break,
+                Some('\\') => match chars.next() {
+                    Some('n') => value.push('\n'),
+                    Some('t') => value.push('\t'),
+                    Some(c) => value.push(c),
+                    None => return Err(invalid("unterminated label value")),
+                },
+                Some(c) => value.push(c),
+                None => return Err(invalid("unterminated label value")),
+            }
+        }
+        result.push((name, value));
+    }
+
+    Ok(result)
+}
+
+/// Converts streams to row insert requests of the `table_name`, returns the requests and
+/// the number of rows in them.
+pub fn to_grpc_insert_requests(
+    table_name: &str,
+    streams: Vec<LokiStream>,
+) -> Result<(RowInsertRequests, usize)> {
+    let num_rows = streams.iter().map(|stream| stream.entries.len()).sum();
+    let mut multi_table_writer = MultiTableData::default();
+    let table_data = multi_table_writer.get_or_default_table_data(
+        table_name,
+        APPROXIMATE_COLUMN_COUNT,
+        num_rows,
+    );
+
+    for stream in streams {
+        for entry in stream.entries {
+            let mut row = table_data.alloc_one_row();
+            row_writer::write_tags(table_data, stream.labels.iter().cloned(), &mut row)?;
+            row_writer::write_fields(
+                table_data,
+                std::iter::once((
+                    LOKI_LINE.to_string(),
+                    ColumnDataType::String,
+                    ValueData::StringValue(entry.line),
+                )),
+                &mut row,
+            )?;
+            if !entry.structured_metadata.is_empty() {
+                // A map of strings is always serializable.
+                let metadata = serde_json::to_string(&entry.structured_metadata).unwrap();
+                row_writer::write_fields(
+                    table_data,
+                    std::iter::once((
+                        LOKI_STRUCTURED_METADATA.to_string(),
+                        ColumnDataType::String,
+                        ValueData::StringValue(metadata),
+                    )),
+                    &mut row,
+                )?;
+            }
+            row_writer::write_ts_precision(
+                table_data,
+                LOKI_TIMESTAMP,
+                Some(entry.ts),
+                Precision::Nanosecond,
+                &mut row,
+            )?;
+            table_data.add_row(row);
+        }
+    }
+
+    Ok(multi_table_writer.into_row_insert_requests())
+}
+
+#[cfg(test)]
+mod tests {
+    use api::v1::SemanticType;
+
+    use super::*;
+    use crate::prom_store::snappy_compress;
+
+    fn labels(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_labels() {
+        assert!(parse_labels("{}").unwrap().is_empty());
+        assert_eq!(
+            labels(&[("job", "varlogs"), ("host", "host1")]),
+            parse_labels(r#"{job="varlogs", host="host1"}"#).unwrap()
+        );
+        assert_eq!(
+            labels(&[("msg", "a \"b\",}")]),
+            parse_labels(r#" { msg = "a \"b\",}" } "#).unwrap()
+        );
+
+        assert!(parse_labels(r#"job="varlogs""#).is_err());
+        assert!(parse_labels(r#"{job=varlogs}"#).is_err());
+        assert!(parse_labels(r#"{job="varlogs}"#).is_err());
+        assert!(parse_labels(r#"{="varlogs"}"#).is_err());
+    }
+
+    #[test]
+    fn test_decode_protobuf() {
+        let request = PushRequest {
+            streams: vec![StreamAdapter {
+                labels: r#"{job="varlogs"}"#.to_string(),
+                entries: vec![EntryAdapter {
+                    timestamp: Some(Timestamp {
+                        seconds: 1,
+                        nanos: 2,
+                    }),
+                    line: "hello".to_string(),
+                    structured_metadata: vec![LabelPairAdapter {
+                        name: "trace_id".to_string(),
+                        value: "abc".to_string(),
+                    }],
+                }],
+                hash: 0,
+            }],
+        };
+        let body = snappy_compress(&request.encode_to_vec()).unwrap();
+        let streams = decode_protobuf(&body).unwrap();
+        assert_eq!(
+            vec![LokiStream {
+                labels: labels(&[("job", "varlogs")]),
+                entries: vec![LokiEntry {
+                    ts: 1_000_000_002,
+                    line: "hello".to_string(),
+                    structured_metadata: BTreeMap::from([(
+                        "trace_id".to_string(),
+                        "abc".to_string()
+                    )]),
+                }],
+            }],
+            streams
+        );
+
+        assert!(decode_protobuf(b"invalid").is_err());
+    }
+
+    #[test]
+    fn test_decode_json() {
+        let body = r#"{"streams": [{"stream": {"job": "varlogs"}, "values": [["1000", "hello"], ["2000", "world", {"trace_id": "abc"}]]}]}"#;
+        let streams = decode_json(body.as_bytes()).unwrap();
+        assert_eq!(1, streams.len());
+        assert_eq!(labels(&[("job", "varlogs")]), streams[0].labels);
+        assert_eq!(
+            vec![
+                LokiEntry {
+                    ts: 1000,
+                    line: "hello".to_string(),
+                    structured_metadata: BTreeMap::new(),
+                },
+                LokiEntry {
+                    ts: 2000,
+                    line: "world".to_string(),
+                    structured_metadata: BTreeMap::from([(
+                        "trace_id".to_string(),
+                        "abc".to_string()
+                    )]),
+                },
+            ],
+            streams[0].entries
+        );
+
+        for body in [
+            r#"{}"#,
+            r#"{"streams": [{"stream": {"job": "varlogs"}}]}"#,
+            r#"{"streams": [{"stream": {"job": 1}, "values": []}]}"#,
+            r#"{"streams": [{"values": [["abc", "hello"]]}]}"#,
+            r#"{"streams": [{"values": [["1000"]]}]}"#,
+        ] {
+            assert!(decode_json(body.as_bytes()).is_err(), "{body}");
+        }
+    }
+
+    #[test]
+    fn test_to_grpc_insert_requests() {
+        let streams = vec![
+            LokiStream {
+                labels: labels(&[("job", "varlogs")]),
+                entries: vec![LokiEntry {
+                    ts: 1_000_000,
+                    line: "hello".to_string(),
+                    structured_metadata: BTreeMap::new(),
+                }],
+            },
+            LokiStream {
+                labels: labels(&[("host", "host1")]),
+                entries: vec![LokiEntry {
+                    ts: 2_000_000,
+                    line: "world".to_string(),
+                    structured_metadata: BTreeMap::from([(
+                        "trace_id".to_string(),
+                        "abc".to_string(),
+                    )]),
+                }],
+            },
+        ];
+        let (requests, rows) = to_grpc_insert_requests(LOKI_TABLE_NAME, streams).unwrap();
+        assert_eq!(2, rows);
+        assert_eq!(1, requests.inserts.len());
+        let insert = &requests.inserts[0];
+        assert_eq!(LOKI_TABLE_NAME, insert.table_name);
+
+        let rows = insert.rows.as_ref().unwrap();
+        let columns = rows
+            .schema
+            .iter()
+            .map(|c| (c.column_name.as_str(), c.semantic_type))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec![
+                ("job", SemanticType::Tag as i32),
+                (LOKI_LINE, SemanticType::Field as i32),
+                (LOKI_TIMESTAMP, SemanticType::Timestamp as i32),
+                ("host", SemanticType::Tag as i32),
+                (LOKI_STRUCTURED_METADATA, SemanticType::Field as i32),
+            ],
+            columns
+        );
+        // Rows are padded to the same number of columns.
+        assert!(rows.rows.iter().all(|row| row.values.len() == 5));
+    }
+}
@@ -22,7 +22,8 @@ use hyper::Body;
 use lazy_static::lazy_static;
 use prometheus::{
     register_histogram, register_histogram_vec, register_int_counter, register_int_counter_vec,
-    register_int_gauge, Histogram, HistogramVec, IntCounter, IntCounterVec, IntGauge,
+    register_int_gauge, register_int_gauge_vec, Histogram, HistogramVec, IntCounter, IntCounterVec,
+    IntGauge, IntGaugeVec,
 };
 use tonic::body::BoxBody;
 use tower::{Layer, Service};
@@ -40,6 +41,8 @@ pub(crate) const METRIC_POSTGRES_SIMPLE_QUERY: &str = "simple";
 pub(crate) const METRIC_POSTGRES_EXTENDED_QUERY: &str = "extended";
 pub(crate) const METRIC_METHOD_LABEL: &str = "method";
 pub(crate) const METRIC_PATH_LABEL: &str = "path";
+pub(crate) const METRIC_RESULT_LABEL: &str = "result";
+pub(crate) const METRIC_PIPELINE_LABEL: &str = "pipeline";
 
 lazy_static! {
     pub static ref METRIC_ERROR_COUNTER: IntCounterVec =
@@ -57,6 +60,12 @@ lazy_static! {
         &[METRIC_DB_LABEL]
     )
     .unwrap();
+    pub static ref METRIC_RATE_LIMITED_REQUESTS: IntCounterVec = register_int_counter_vec!(
+        "servers_rate_limited_requests_total",
+        "servers rate limited requests total",
+        &[METRIC_PROTOCOL_LABEL]
+    )
+    .unwrap();
     pub static ref METRIC_AUTH_FAILURE: IntCounterVec = register_int_counter_vec!(
         "servers_auth_failure_count",
         "servers auth failure count",
@@ -95,6 +104,18 @@ lazy_static! {
             &[METRIC_DB_LABEL]
         )
         .unwrap();
+    pub static ref METRIC_HTTP_LOKI_PUSH_ELAPSED: HistogramVec = register_histogram_vec!(
+        "servers_http_loki_push_elapsed",
+        "servers http loki push elapsed",
+        &[METRIC_DB_LABEL]
+    )
+    .unwrap();
+    pub static ref METRIC_HTTP_UPLOAD_ELAPSED: HistogramVec = register_histogram_vec!(
+        "servers_http_upload_elapsed",
+        "servers http upload elapsed",
+        &[METRIC_DB_LABEL]
+    )
+    .unwrap();
     pub static ref METRIC_TCP_OPENTSDB_LINE_WRITE_ELAPSED: Histogram = register_histogram!(
         "servers_opentsdb_line_write_elapsed",
         "servers opentsdb line write elapsed"
@@ -105,6 +126,11 @@ lazy_static! {
         "servers http promql format query elapsed"
     )
     .unwrap();
+    pub static ref METRIC_HTTP_PROMQL_PARSE_QUERY_ELAPSED: Histogram = register_histogram!(
+        "servers_http_promql_parse_query_elapsed",
+        "servers http promql parse query elapsed"
+    )
+    .unwrap();
     pub static ref METRIC_HTTP_PROMQL_INSTANT_QUERY_ELAPSED: Histogram = register_histogram!(
         "servers_http_promql_instant_query_elapsed",
         "servers http promql instant query elapsed"
@@ -135,6 +161,24 @@ lazy_static! {
         "servers mysql connection count"
     )
     .unwrap();
+    pub static ref METRIC_IDLE_CONNECTIONS: IntGaugeVec = register_int_gauge_vec!(
+        "servers_idle_connection_count",
+        "servers idle connection count",
+        &[METRIC_PROTOCOL_LABEL]
+    )
+    .unwrap();
+    pub static ref METRIC_REJECTED_CONNECTIONS: IntCounterVec = register_int_counter_vec!(
+        "servers_rejected_connections_total",
+        "servers rejected connections total",
+        &[METRIC_PROTOCOL_LABEL]
+    )
+    .unwrap();
+    pub static ref METRIC_IDLE_TIMEOUT_CONNECTIONS: IntCounterVec = register_int_counter_vec!(
+        "servers_idle_timeout_connections_total",
+        "servers idle timeout connections total",
+        &[METRIC_PROTOCOL_LABEL]
+    )
+    .unwrap();
     pub static ref METRIC_MYSQL_QUERY_TIMER: HistogramVec = register_histogram_vec!(
         "servers_mysql_query_elapsed",
         "servers mysql query elapsed",
@@ -201,6 +245,32 @@ lazy_static! {
     .unwrap();
     pub static ref HTTP_TRACK_METRICS: HistogramVec =
         register_histogram_vec!("http_track_metrics", "http track metrics", &["tag"]).unwrap();
+    pub static ref METRIC_MQTT_MESSAGES: IntCounterVec = register_int_counter_vec!(
+        "servers_mqtt_messages",
+        "servers mqtt messages",
+        &[METRIC_RESULT_LABEL]
+    )
+    .unwrap();
+    pub static ref METRIC_MQTT_FLUSH_ELAPSED: Histogram =
+        register_histogram!("servers_mqtt_flush_elapsed", "servers mqtt flush elapsed").unwrap();
+    pub static ref METRIC_PIPELINE_MESSAGES: IntCounterVec = register_int_counter_vec!(
+        "servers_pipeline_messages",
+        "servers pipeline messages",
+        &[METRIC_PIPELINE_LABEL, METRIC_RESULT_LABEL]
+    )
+    .unwrap();
+    pub static ref METRIC_PIPELINE_FLUSH_ELAPSED: Histogram = register_histogram!(
+        "servers_pipeline_flush_elapsed",
+        "servers pipeline flush elapsed"
+    )
+    .unwrap();
+    pub static ref METRIC_TAIL_SUBSCRIBERS: IntGauge =
+        register_int_gauge!("servers_tail_subscribers", "servers tail subscribers").unwrap();
+    pub static ref METRIC_TAIL_DROPPED_BATCHES: IntCounter = register_int_counter!(
+        "servers_tail_dropped_batches",
+        "servers tail dropped batches"
+    )
+    .unwrap();
 }
 
 // Based on https://github.com/hyperium/tonic/blob/master/examples/src/tower/server.rs
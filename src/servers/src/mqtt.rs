@@ -0,0 +1,156 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! MQTT ingestion server.
+//!
+//! Devices connect to the server as an MQTT 3.1.1 broker and publish messages with QoS 0 or 1.
+//! Messages are mapped to tables by [MqttTopicRule]s. The server doesn't deliver messages to
+//! subscribers.
+
+pub mod codec;
+pub mod connection;
+mod handler;
+pub mod payload;
+
+use std::future::Future;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use auth::UserProviderRef;
+use common_runtime::Runtime;
+use common_telemetry::logging::error;
+use futures::StreamExt;
+use tokio::sync::broadcast;
+
+use crate::error::Result;
+use crate::mqtt::connection::Connection;
+use crate::mqtt::handler::Handler;
+pub use crate::mqtt::payload::{MqttTopicRule, PayloadFormat};
+use crate::query_handler::MqttProtocolHandlerRef;
+use crate::server::{AbortableStream, BaseTcpServer, Server};
+use crate::shutdown::Shutdown;
+
+pub const MQTT_SERVER: &str = "MQTT_SERVER";
+
+#[derive(Debug, Clone)]
+pub struct MqttServerConfig {
+    /// Rules to map topics to tables, the first matched rule is used.
+    pub topics: Vec<MqttTopicRule>,
+    /// Max number of rows buffered by a connection before writing them.
+    pub batch_size: usize,
+    /// Interval to write buffered rows.
+    pub flush_interval: Duration,
+    /// Database to write.
+    pub db: String,
+}
+
+pub struct MqttServer {
+    base_server: BaseTcpServer,
+    query_handler: MqttProtocolHandlerRef,
+    user_provider: Option<UserProviderRef>,
+    config: Arc<MqttServerConfig>,
+
+    /// Broadcasts a shutdown signal to all active connections.
+    notify_shutdown: Option<broadcast::Sender<()>>,
+}
+
+impl MqttServer {
+    pub fn create_server(
+        query_handler: MqttProtocolHandlerRef,
+        user_provider: Option<UserProviderRef>,
+        config: MqttServerConfig,
+        io_runtime: Arc<Runtime>,
+    ) -> Box<dyn Server> {
+        let (notify_shutdown, _) = broadcast::channel(1);
+
+        Box::new(MqttServer {
+            base_server: BaseTcpServer::create_server("MQTT", io_runtime),
+            query_handler,
+            user_provider,
+            config: Arc::new(config),
+            notify_shutdown: Some(notify_shutdown),
+        })
+    }
+
+    fn accept(
+        &self,
+        io_runtime: Arc<Runtime>,
+        stream: AbortableStream,
+    ) -> impl Future<Output = ()> {
+        let query_handler = self.query_handler.clone();
+        let user_provider = self.user_provider.clone();
+        let config = self.config.clone();
+        let notify_shutdown = self
+            .notify_shutdown
+            .clone()
+            .expect("`notify_shutdown` must be present when accepting connection!");
+        stream.for_each(move |stream| {
+            let io_runtime = io_runtime.clone();
+            let query_handler = query_handler.clone();
+            let user_provider = user_provider.clone();
+            let config = config.clone();
+            let shutdown = Shutdown::new(notify_shutdown.subscribe());
+            async move {
+                match stream {
+                    Ok(stream) => {
+                        if let Err(e) = stream.set_nodelay(true) {
+                            error!(e; "Failed to set TCP nodelay");
+                        }
+                        let connection = Connection::new(stream);
+                        let mut handler = Handler::new(
+                            query_handler,
+                            user_provider,
+                            config,
+                            connection,
+                            shutdown,
+                        );
+
+                        let _handle = io_runtime.spawn(async move {
+                            if let Err(e) = handler.run().await {
+                                error!(e; "Unexpected error when handling MQTT connection");
+                            }
+                        });
+                    }
+                    Err(error) => error!("Broken pipe: {}", error), // IoError doesn't impl ErrorExt.
+                };
+            }
+        })
+    }
+}
+
+#[async_trait]
+impl Server for MqttServer {
+    async fn shutdown(&self) -> Result<()> {
+        if let Some(tx) = &self.notify_shutdown {
+            let _ = tx.send(());
+        }
+        self.base_server.shutdown().await?;
+        Ok(())
+    }
+
+    async fn start(&self, listening: SocketAddr) -> Result<SocketAddr> {
+        let (stream, addr) = self.base_server.bind(listening).await?;
+
+        let io_runtime = self.base_server.io_runtime();
+        let join_handle = common_runtime::spawn_read(self.accept(io_runtime, stream));
+        self.base_server.start_with(join_handle).await?;
+        Ok(addr)
+    }
+
+    fn name(&self) -> &str {
+        MQTT_SERVER
+    }
+}
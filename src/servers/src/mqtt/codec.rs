@@ -0,0 +1,450 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Encoder and decoder of the MQTT 3.1.1 packets the server needs.
+
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+use snafu::ensure;
+
+use crate::error::{InvalidMqttPacketSnafu, Result};
+
+/// Max size of a packet (excluding the fixed header).
+pub const MAX_PACKET_SIZE: usize = 8 * 1024 * 1024;
+
+/// Protocol level of MQTT 3.1.1.
+pub const PROTOCOL_LEVEL: u8 = 4;
+
+const CONNECT: u8 = 1;
+const CONNACK: u8 = 2;
+const PUBLISH: u8 = 3;
+const PUBACK: u8 = 4;
+const SUBSCRIBE: u8 = 8;
+const SUBACK: u8 = 9;
+const UNSUBSCRIBE: u8 = 10;
+const UNSUBACK: u8 = 11;
+const PINGREQ: u8 = 12;
+const PINGRESP: u8 = 13;
+const DISCONNECT: u8 = 14;
+
+/// Return code of a CONNACK packet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConnectReturnCode {
+    Accepted = 0,
+    UnacceptableProtocolVersion = 1,
+    BadUsernameOrPassword = 4,
+    NotAuthorized = 5,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Connect {
+    pub protocol_level: u8,
+    pub client_id: String,
+    /// Keep alive interval in seconds. 0 means no keep alive.
+    pub keep_alive: u16,
+    pub username: Option<String>,
+    pub password: Option<Bytes>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Publish {
+    pub qos: u8,
+    pub topic: String,
+    /// Packet id, only present when `qos > 0`.
+    pub packet_id: Option<u16>,
+    pub payload: Bytes,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Packet {
+    Connect(Connect),
+    ConnAck(ConnectReturnCode),
+    Publish(Publish),
+    PubAck(u16),
+    /// Packet id and number of topic filters.
+    Subscribe(u16, usize),
+    /// Packet id and number of topic filters, all subscriptions are rejected.
+    SubAck(u16, usize),
+    Unsubscribe(u16),
+    UnsubAck(u16),
+    PingReq,
+    PingResp,
+    Disconnect,
+}
+
+impl Packet {
+    /// Decodes a packet from `buf`.
+    ///
+    /// Returns `Ok(None)` if the `buf` doesn't contain a whole packet. Bytes of the packet
+    /// are consumed on success.
+    pub fn decode(buf: &mut BytesMut) -> Result<Option<Packet>> {
+        let Some((header_len, remaining_len)) = decode_fixed_header(buf)? else {
+            return Ok(None);
+        };
+        if buf.len() < header_len + remaining_len {
+            return Ok(None);
+        }
+
+        let first_byte = buf[0];
+        buf.advance(header_len);
+        let mut body = buf.split_to(remaining_len).freeze();
+        let packet_type = first_byte >> 4;
+        let flags = first_byte & 0x0f;
+
+        let packet = match packet_type {
+            CONNECT => Packet::Connect(decode_connect(&mut body)?),
+            CONNACK => {
+                ensure_remaining(&body, 2)?;
+                let code = match body[1] {
+                    0 => ConnectReturnCode::Accepted,
+                    1 => ConnectReturnCode::UnacceptableProtocolVersion,
+                    4 => ConnectReturnCode::BadUsernameOrPassword,
+                    5 => ConnectReturnCode::NotAuthorized,
+                    code => {
+                        return InvalidMqttPacketSnafu {
+                            reason: format!("unknown connect return code {code}"),
+                        }
+                        .fail()
+                    }
+                };
+                Packet::ConnAck(code)
+            }
+            PUBLISH => {
+                let qos = (flags >> 1) & 0x03;
+                ensure!(
+                    qos < 3,
+                    InvalidMqttPacketSnafu {
+                        reason: "invalid QoS 3",
+                    }
+                );
+                let topic = decode_string(&mut body)?;
+                let packet_id = if qos > 0 {
+                    Some(decode_u16(&mut body)?)
+                } else {
+                    None
+                };
+                Packet::Publish(Publish {
+                    qos,
+                    topic,
+                    packet_id,
+                    payload: body,
+                })
+            }
+            PUBACK => Packet::PubAck(decode_u16(&mut body)?),
+            SUBSCRIBE => {
+                let packet_id = decode_u16(&mut body)?;
+                let mut num_topics = 0;
+                while body.has_remaining() {
+                    let _ = decode_string(&mut body)?;
+                    ensure_remaining(&body, 1)?;
+                    body.advance(1);
+                    num_topics += 1;
+                }
+                Packet::Subscribe(packet_id, num_topics)
+            }
+            SUBACK => {
+                let packet_id = decode_u16(&mut body)?;
+                Packet::SubAck(packet_id, body.remaining())
+            }
+            UNSUBSCRIBE => Packet::Unsubscribe(decode_u16(&mut body)?),
+            UNSUBACK => Packet::UnsubAck(decode_u16(&mut body)?),
+            PINGREQ => Packet::PingReq,
+            PINGRESP => Packet::PingResp,
+            DISCONNECT => Packet::Disconnect,
+            _ => {
+                return InvalidMqttPacketSnafu {
+                    reason: format!("unsupported packet type {packet_type}"),
+                }
+                .fail()
+            }
+        };
+
+        Ok(Some(packet))
+    }
+
+    /// Encodes the packet into `buf`.
+    pub fn encode(&self, buf: &mut BytesMut) {
+        let mut body = BytesMut::new();
+        let first_byte = match self {
+            Packet::Connect(connect) => {
+                put_string(&mut body, "MQTT");
+                body.put_u8(connect.protocol_level);
+                let mut connect_flags = 0x02; // Clean session.
+                if connect.username.is_some() {
+                    connect_flags |= 0x80;
+                }
+                if connect.password.is_some() {
+                    connect_flags |= 0x40;
+                }
+                body.put_u8(connect_flags);
+                body.put_u16(connect.keep_alive);
+                put_string(&mut body, &connect.client_id);
+                if let Some(username) = &connect.username {
+                    put_string(&mut body, username);
+                }
+                if let Some(password) = &connect.password {
+                    body.put_u16(password.len() as u16);
+                    body.put_slice(password);
+                }
+                CONNECT << 4
+            }
+            Packet::ConnAck(code) => {
+                body.put_u8(0);
+                body.put_u8(*code as u8);
+                CONNACK << 4
+            }
+            Packet::Publish(publish) => {
+                put_string(&mut body, &publish.topic);
+                if let Some(packet_id) = publish.packet_id {
+                    body.put_u16(packet_id);
+                }
+                body.put_slice(&publish.payload);
+                (PUBLISH << 4) | (publish.qos << 1)
+            }
+            Packet::PubAck(packet_id) => {
+                body.put_u16(*packet_id);
+                PUBACK << 4
+            }
+            Packet::Subscribe(packet_id, num_topics) => {
+                body.put_u16(*packet_id);
+                for _ in 0..*num_topics {
+                    put_string(&mut body, "#");
+                    body.put_u8(0);
+                }
+                (SUBSCRIBE << 4) | 0x02
+            }
+            Packet::SubAck(packet_id, num_topics) => {
+                body.put_u16(*packet_id);
+                for _ in 0..*num_topics {
+                    // Failure.
+                    body.put_u8(0x80);
+                }
+                SUBACK << 4
+            }
+            Packet::Unsubscribe(packet_id) => {
+                body.put_u16(*packet_id);
+                (UNSUBSCRIBE << 4) | 0x02
+            }
+            Packet::UnsubAck(packet_id) => {
+                body.put_u16(*packet_id);
+                UNSUBACK << 4
+            }
+            Packet::PingReq => PINGREQ << 4,
+            Packet::PingResp => PINGRESP << 4,
+            Packet::Disconnect => DISCONNECT << 4,
+        };
+
+        buf.put_u8(first_byte);
+        encode_remaining_length(body.len(), buf);
+        buf.put_slice(&body);
+    }
+}
+
+/// Decodes the fixed header, returns the length of the header and the remaining length.
+fn decode_fixed_header(buf: &[u8]) -> Result<Option<(usize, usize)>> {
+    let mut remaining_len = 0;
+    // The remaining length takes at most 4 bytes.
+    for i in 0..4 {
+        let Some(byte) = buf.get(i + 1) else {
+            return Ok(None);
+        };
+        remaining_len |= ((byte & 0x7f) as usize) << (7 * i);
+        if byte & 0x80 == 0 {
+            ensure!(
+                remaining_len <= MAX_PACKET_SIZE,
+                InvalidMqttPacketSnafu {
+                    reason: format!("packet size {remaining_len} exceeds {MAX_PACKET_SIZE}"),
+                }
+            );
+            return Ok(Some((i + 2, remaining_len)));
+        }
+    }
+
+    InvalidMqttPacketSnafu {
+        reason: "malformed remaining length",
+    }
+    .fail()
+}
+
+fn encode_remaining_length(mut len: usize, buf: &mut BytesMut) {
+    loop {
+        let mut byte = (len % 128) as u8;
+        len /= 128;
+        if len > 0 {
+            byte |= 0x80;
+        }
+        buf.put_u8(byte);
+        if len == 0 {
+            break;
+        }
+    }
+}
+
+fn decode_connect(body: &mut Bytes) -> Result<Connect> {
+    let protocol_name = decode_string(body)?;
+    ensure!(
+        protocol_name == "MQTT" || protocol_name == "MQIsdp",
+        InvalidMqttPacketSnafu {
+            reason: format!("unknown protocol name {protocol_name}"),
+        }
+    );
+    ensure_remaining(body, 4)?;
+    let protocol_level = body.get_u8();
+    let connect_flags = body.get_u8();
+    let keep_alive = body.get_u16();
+    if protocol_level != PROTOCOL_LEVEL {
+        // The server responds with an unacceptable protocol version so the rest is ignored.
+        return Ok(Connect {
+            protocol_level,
+            client_id: String::new(),
+            keep_alive,
+            username: None,
+            password: None,
+        });
+    }
+
+    let client_id = decode_string(body)?;
+    // Skips the will topic and will message.
+    if connect_flags & 0x04 != 0 {
+        let _ = decode_string(body)?;
+        let _ = decode_bytes(body)?;
+    }
+    let username = if connect_flags & 0x80 != 0 {
+        Some(decode_string(body)?)
+    } else {
+        None
+    };
+    let password = if connect_flags & 0x40 != 0 {
+        Some(decode_bytes(body)?)
+    } else {
+        None
+    };
+
+    Ok(Connect {
+        protocol_level,
+        client_id,
+        keep_alive,
+        username,
+        password,
+    })
+}
+
+fn ensure_remaining(buf: &Bytes, len: usize) -> Result<()> {
+    ensure!(
+        buf.remaining() >= len,
+        InvalidMqttPacketSnafu {
+            reason: "unexpected end of packet",
+        }
+    );
+    Ok(())
+}
+
+fn decode_u16(buf: &mut Bytes) -> Result<u16> {
+    ensure_remaining(buf, 2)?;
+    Ok(buf.get_u16())
+}
+
+fn decode_bytes(buf: &mut Bytes) -> Result<Bytes> {
+    let len = decode_u16(buf)? as usize;
+    ensure_remaining(buf, len)?;
+    Ok(buf.split_to(len))
+}
+
+fn decode_string(buf: &mut Bytes) -> Result<String> {
+    let bytes = decode_bytes(buf)?;
+    String::from_utf8(bytes.to_vec()).map_err(|_| {
+        InvalidMqttPacketSnafu {
+            reason: "invalid UTF-8 string",
+        }
+        .build()
+    })
+}
+
+fn put_string(buf: &mut BytesMut, s: &str) {
+    buf.put_u16(s.len() as u16);
+    buf.put_slice(s.as_bytes());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check_round_trip(packet: Packet) {
+        let mut buf = BytesMut::new();
+        packet.encode(&mut buf);
+        let encoded_len = buf.len();
+
+        // A partial packet is not decoded.
+        let mut partial = BytesMut::from(&buf[..encoded_len - 1]);
+        assert_eq!(None, Packet::decode(&mut partial).unwrap());
+        assert_eq!(encoded_len - 1, partial.len());
+
+        assert_eq!(Some(packet), Packet::decode(&mut buf).unwrap());
+        assert!(buf.is_empty());
+    }
+
+    #[test]
+    fn test_packet_round_trip() {
+        check_round_trip(Packet::Connect(Connect {
+            protocol_level: PROTOCOL_LEVEL,
+            client_id: "device-1".to_string(),
+            keep_alive: 60,
+            username: Some("greptime".to_string()),
+            password: Some(Bytes::from_static(b"greptime")),
+        }));
+        check_round_trip(Packet::ConnAck(ConnectReturnCode::NotAuthorized));
+        check_round_trip(Packet::Publish(Publish {
+            qos: 1,
+            topic: "sensors/1/temperature".to_string(),
+            packet_id: Some(10),
+            payload: Bytes::from(vec![b'x'; 300]),
+        }));
+        check_round_trip(Packet::Publish(Publish {
+            qos: 0,
+            topic: "sensors".to_string(),
+            packet_id: None,
+            payload: Bytes::from_static(b"{}"),
+        }));
+        check_round_trip(Packet::PubAck(10));
+        check_round_trip(Packet::Subscribe(3, 2));
+        check_round_trip(Packet::SubAck(3, 2));
+        check_round_trip(Packet::Unsubscribe(4));
+        check_round_trip(Packet::UnsubAck(4));
+        check_round_trip(Packet::PingReq);
+        check_round_trip(Packet::PingResp);
+        check_round_trip(Packet::Disconnect);
+    }
+
+    #[test]
+    fn test_decode_invalid_packet() {
+        // Packet type 0 is reserved.
+        let mut buf = BytesMut::from(&[0x00, 0x00][..]);
+        assert!(Packet::decode(&mut buf).is_err());
+
+        // Remaining length takes more than 4 bytes.
+        let mut buf = BytesMut::from(&[PINGREQ << 4, 0xff, 0xff, 0xff, 0xff, 0x01][..]);
+        assert!(Packet::decode(&mut buf).is_err());
+
+        // QoS 3.
+        let mut buf = BytesMut::new();
+        Packet::Publish(Publish {
+            qos: 1,
+            topic: "a".to_string(),
+            packet_id: Some(1),
+            payload: Bytes::new(),
+        })
+        .encode(&mut buf);
+        buf[0] |= 0x06;
+        assert!(Packet::decode(&mut buf).is_err());
+    }
+}
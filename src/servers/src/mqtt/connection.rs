@@ -0,0 +1,70 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use bytes::BytesMut;
+use snafu::ResultExt;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufWriter};
+
+use crate::error::{self, Result};
+use crate::mqtt::codec::Packet;
+
+/// A connection that reads and writes MQTT packets.
+#[derive(Debug)]
+pub struct Connection<S: AsyncWrite + AsyncRead + Unpin> {
+    stream: BufWriter<S>,
+    buffer: BytesMut,
+    write_buffer: BytesMut,
+}
+
+impl<S: AsyncWrite + AsyncRead + Unpin> Connection<S> {
+    pub fn new(stream: S) -> Connection<S> {
+        Connection {
+            stream: BufWriter::new(stream),
+            buffer: BytesMut::with_capacity(4 * 1024),
+            write_buffer: BytesMut::new(),
+        }
+    }
+
+    /// Reads one packet from the underlying stream.
+    ///
+    /// Returns `None` if the stream is closed between packets. It's cancel safe as
+    /// data read is kept in the buffer.
+    pub async fn read_packet(&mut self) -> Result<Option<Packet>> {
+        loop {
+            if let Some(packet) = Packet::decode(&mut self.buffer)? {
+                return Ok(Some(packet));
+            }
+
+            if self.stream.read_buf(&mut self.buffer).await? == 0 {
+                if self.buffer.is_empty() {
+                    return Ok(None);
+                } else {
+                    return error::ConnResetByPeerSnafu {}.fail();
+                }
+            }
+        }
+    }
+
+    /// Writes the packet and flushes the stream.
+    pub async fn write_packet(&mut self, packet: &Packet) -> Result<()> {
+        self.write_buffer.clear();
+        packet.encode(&mut self.write_buffer);
+        self.stream
+            .write_all(&self.write_buffer)
+            .await
+            .context(error::InternalIoSnafu)?;
+        self.stream.flush().await.context(error::InternalIoSnafu)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,416 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use auth::{Identity, Password, UserProviderRef};
+use common_telemetry::{debug, warn};
+use session::context::{QueryContext, QueryContextRef};
+use snafu::ResultExt;
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::time::MissedTickBehavior;
+
+use crate::error::{AuthSnafu, Result};
+use crate::metrics::{METRIC_MQTT_FLUSH_ELAPSED, METRIC_MQTT_MESSAGES};
+use crate::mqtt::codec::{Connect, ConnectReturnCode, Packet, Publish, PROTOCOL_LEVEL};
+use crate::mqtt::connection::Connection;
+use crate::mqtt::payload::find_rule;
+use crate::mqtt::MqttServerConfig;
+use crate::query_handler::MqttProtocolHandlerRef;
+use crate::row_writer::MultiTableData;
+use crate::shutdown::Shutdown;
+
+/// Per-connection handler. Reads messages published to the connection, buffers rows parsed
+/// from them and writes rows to the [MqttProtocolHandlerRef] in batches.
+///
+/// Messages with QoS 1 are acknowledged after their rows are written, so clients resend them
+/// if the connection is closed before that.
+pub(crate) struct Handler<S: AsyncWrite + AsyncRead + Unpin> {
+    query_handler: MqttProtocolHandlerRef,
+    user_provider: Option<UserProviderRef>,
+    config: Arc<MqttServerConfig>,
+    connection: Connection<S>,
+    shutdown: Shutdown,
+    /// Rows not written yet.
+    buffer: MultiTableData,
+    buffered_rows: usize,
+    /// Ids of QoS 1 messages whose rows are buffered.
+    pending_acks: Vec<u16>,
+}
+
+impl<S: AsyncWrite + AsyncRead + Unpin> Handler<S> {
+    pub(crate) fn new(
+        query_handler: MqttProtocolHandlerRef,
+        user_provider: Option<UserProviderRef>,
+        config: Arc<MqttServerConfig>,
+        connection: Connection<S>,
+        shutdown: Shutdown,
+    ) -> Self {
+        Self {
+            query_handler,
+            user_provider,
+            config,
+            connection,
+            shutdown,
+            buffer: MultiTableData::new(),
+            buffered_rows: 0,
+            pending_acks: Vec::new(),
+        }
+    }
+
+    pub(crate) async fn run(&mut self) -> Result<()> {
+        // The first packet must be a CONNECT packet.
+        let connect = tokio::select! {
+            packet = self.connection.read_packet() => match packet? {
+                Some(Packet::Connect(connect)) => connect,
+                Some(packet) => {
+                    debug!("Expect a CONNECT packet, got {:?}", packet);
+                    return Ok(());
+                }
+                None => return Ok(()),
+            },
+            _ = self.shutdown.recv() => return Ok(()),
+        };
+        let Some(ctx) = self.connect(&connect).await? else {
+            return Ok(());
+        };
+
+        // The client is disconnected if no packet is received in 1.5 times of the keep alive.
+        let keep_alive = Duration::from_millis(connect.keep_alive as u64 * 1500);
+        let mut last_active = Instant::now();
+        let mut flush_interval = tokio::time::interval(self.config.flush_interval);
+        flush_interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        while !self.shutdown.is_shutdown() {
+            let packet = tokio::select! {
+                packet = self.connection.read_packet() => packet?,
+                _ = flush_interval.tick() => {
+                    if !keep_alive.is_zero() && last_active.elapsed() > keep_alive {
+                        debug!("MQTT client {} is inactive, closing connection", connect.client_id);
+                        break;
+                    }
+                    self.flush(&ctx).await?;
+                    continue;
+                }
+                _ = self.shutdown.recv() => break,
+            };
+            last_active = Instant::now();
+
+            match packet {
+                Some(Packet::Publish(publish)) => self.handle_publish(publish, &ctx).await?,
+                Some(Packet::PingReq) => self.connection.write_packet(&Packet::PingResp).await?,
+                Some(Packet::Subscribe(packet_id, num_topics)) => {
+                    // Only publishing is supported.
+                    self.connection
+                        .write_packet(&Packet::SubAck(packet_id, num_topics))
+                        .await?
+                }
+                Some(Packet::Unsubscribe(packet_id)) => {
+                    self.connection
+                        .write_packet(&Packet::UnsubAck(packet_id))
+                        .await?
+                }
+                Some(Packet::Disconnect) | None => break,
+                Some(packet) => {
+                    debug!("Unexpected MQTT packet {:?}", packet);
+                    break;
+                }
+            }
+        }
+
+        self.flush(&ctx).await
+    }
+
+    /// Authenticates the client and replies the CONNACK packet.
+    ///
+    /// Returns `None` if the connection is refused.
+    async fn connect(&mut self, connect: &Connect) -> Result<Option<QueryContextRef>> {
+        if connect.protocol_level != PROTOCOL_LEVEL {
+            self.connection
+                .write_packet(&Packet::ConnAck(
+                    ConnectReturnCode::UnacceptableProtocolVersion,
+                ))
+                .await?;
+            return Ok(None);
+        }
+
+        let ctx = QueryContext::with_db_name(Some(&self.config.db));
+        if let Some(user_provider) = &self.user_provider {
+            let (Some(username), Some(password)) = (&connect.username, &connect.password) else {
+                self.connection
+                    .write_packet(&Packet::ConnAck(ConnectReturnCode::NotAuthorized))
+                    .await?;
+                return Ok(None);
+            };
+            let password = String::from_utf8_lossy(password).to_string();
+            let user_info = match user_provider
+                .auth(
+                    Identity::UserId(username, None),
+                    Password::PlainText(password.into()),
+                    ctx.current_catalog(),
+                    ctx.current_schema(),
+                )
+                .await
+                .context(AuthSnafu)
+            {
+                Ok(user_info) => user_info,
+                Err(e) => {
+                    warn!(e; "Failed to authenticate MQTT client {}", connect.client_id);
+                    self.connection
+                        .write_packet(&Packet::ConnAck(ConnectReturnCode::BadUsernameOrPassword))
+                        .await?;
+                    return Ok(None);
+                }
+            };
+            ctx.set_current_user(Some(user_info));
+        }
+
+        self.connection
+            .write_packet(&Packet::ConnAck(ConnectReturnCode::Accepted))
+            .await?;
+        Ok(Some(ctx))
+    }
+
+    async fn handle_publish(&mut self, publish: Publish, ctx: &QueryContextRef) -> Result<()> {
+        let Some(rule) = find_rule(&self.config.topics, &publish.topic) else {
+            debug!("No rule matches MQTT topic {}", publish.topic);
+            METRIC_MQTT_MESSAGES.with_label_values(&["no_rule"]).inc();
+            return self.ack(publish.packet_id).await;
+        };
+        match rule.write_payload(&publish.topic, &publish.payload, &mut self.buffer) {
+            Ok(rows) => {
+                METRIC_MQTT_MESSAGES.with_label_values(&["ok"]).inc();
+                self.buffered_rows += rows;
+                if let Some(packet_id) = publish.packet_id {
+                    self.pending_acks.push(packet_id);
+                }
+                if self.buffered_rows >= self.config.batch_size {
+                    self.flush(ctx).await?;
+                }
+                Ok(())
+            }
+            Err(e) => {
+                // Invalid messages are dropped, the client can't fix them by resending.
+                warn!(e; "Failed to parse MQTT message of topic {}", publish.topic);
+                METRIC_MQTT_MESSAGES.with_label_values(&["invalid"]).inc();
+                self.ack(publish.packet_id).await
+            }
+        }
+    }
+
+    /// Writes buffered rows and acknowledges messages of them.
+    async fn flush(&mut self, ctx: &QueryContextRef) -> Result<()> {
+        if self.buffered_rows > 0 {
+            let _timer = METRIC_MQTT_FLUSH_ELAPSED.start_timer();
+            let buffer = std::mem::take(&mut self.buffer);
+            self.buffered_rows = 0;
+            let (requests, _) = buffer.into_row_insert_requests();
+            // Closes the connection on failure so the client resends unacknowledged messages.
+            let _ = self.query_handler.insert(requests, ctx.clone()).await?;
+        }
+
+        for packet_id in std::mem::take(&mut self.pending_acks) {
+            self.connection
+                .write_packet(&Packet::PubAck(packet_id))
+                .await?;
+        }
+        Ok(())
+    }
+
+    async fn ack(&mut self, packet_id: Option<u16>) -> Result<()> {
+        match packet_id {
+            // Acknowledges after buffered messages to keep the order.
+            Some(packet_id) if self.buffered_rows > 0 => self.pending_acks.push(packet_id),
+            Some(packet_id) => {
+                self.connection
+                    .write_packet(&Packet::PubAck(packet_id))
+                    .await?
+            }
+            None => (),
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use api::v1::RowInsertRequests;
+    use async_trait::async_trait;
+    use bytes::Bytes;
+    use tokio::io::DuplexStream;
+    use tokio::sync::broadcast;
+
+    use super::*;
+    use crate::mqtt::payload::MqttTopicRule;
+    use crate::query_handler::MqttProtocolHandler;
+
+    #[derive(Default)]
+    struct DummyHandler {
+        requests: Mutex<Vec<RowInsertRequests>>,
+    }
+
+    #[async_trait]
+    impl MqttProtocolHandler for DummyHandler {
+        async fn insert(
+            &self,
+            requests: RowInsertRequests,
+            _ctx: QueryContextRef,
+        ) -> Result<usize> {
+            let rows = requests
+                .inserts
+                .iter()
+                .map(|r| r.rows.as_ref().map(|r| r.rows.len()).unwrap_or(0))
+                .sum();
+            self.requests.lock().unwrap().push(requests);
+            Ok(rows)
+        }
+    }
+
+    fn new_publish(qos: u8, packet_id: u16, topic: &str, payload: &'static [u8]) -> Packet {
+        Packet::Publish(Publish {
+            qos,
+            topic: topic.to_string(),
+            packet_id: (qos > 0).then_some(packet_id),
+            payload: Bytes::from_static(payload),
+        })
+    }
+
+    fn start_handler(
+        batch_size: usize,
+    ) -> (
+        Arc<DummyHandler>,
+        Connection<DuplexStream>,
+        broadcast::Sender<()>,
+        tokio::task::JoinHandle<Result<()>>,
+    ) {
+        let query_handler = Arc::new(DummyHandler::default());
+        let config = Arc::new(MqttServerConfig {
+            topics: vec![MqttTopicRule {
+                topic: "sensors/+".to_string(),
+                table: "{1}".to_string(),
+                ..Default::default()
+            }],
+            batch_size,
+            // Never flushes by interval in tests.
+            flush_interval: Duration::from_secs(3600),
+            db: "public".to_string(),
+        });
+        let (client, server) = tokio::io::duplex(4096);
+        let (notify_shutdown, _) = broadcast::channel(1);
+        let mut handler = Handler::new(
+            query_handler.clone(),
+            None,
+            config,
+            Connection::new(server),
+            Shutdown::new(notify_shutdown.subscribe()),
+        );
+        let join_handle = tokio::spawn(async move { handler.run().await });
+
+        (
+            query_handler,
+            Connection::new(client),
+            notify_shutdown,
+            join_handle,
+        )
+    }
+
+    fn connect_packet() -> Packet {
+        Packet::Connect(Connect {
+            protocol_level: PROTOCOL_LEVEL,
+            client_id: "test".to_string(),
+            keep_alive: 0,
+            username: None,
+            password: None,
+        })
+    }
+
+    #[tokio::test]
+    async fn test_publish_in_batch() {
+        let (query_handler, mut client, _notify_shutdown, join_handle) = start_handler(2);
+
+        client.write_packet(&connect_packet()).await.unwrap();
+        assert_eq!(
+            Some(Packet::ConnAck(ConnectReturnCode::Accepted)),
+            client.read_packet().await.unwrap()
+        );
+
+        client
+            .write_packet(&new_publish(1, 1, "sensors/a", br#"{"v": 1}"#))
+            .await
+            .unwrap();
+        // Invalid messages and messages without rules are acknowledged but not written.
+        client
+            .write_packet(&new_publish(1, 2, "sensors/a", b"invalid"))
+            .await
+            .unwrap();
+        client
+            .write_packet(&new_publish(0, 0, "other", br#"{"v": 1}"#))
+            .await
+            .unwrap();
+        client
+            .write_packet(&new_publish(1, 3, "sensors/b", br#"{"v": 2}"#))
+            .await
+            .unwrap();
+
+        // Messages are acknowledged after the batch is written.
+        for id in 1..=3 {
+            assert_eq!(
+                Some(Packet::PubAck(id)),
+                client.read_packet().await.unwrap()
+            );
+        }
+        {
+            let requests = query_handler.requests.lock().unwrap();
+            assert_eq!(1, requests.len());
+            assert_eq!(2, requests[0].inserts.len());
+        }
+
+        // Buffered rows are written on disconnect.
+        client
+            .write_packet(&new_publish(0, 0, "sensors/a", br#"{"v": 3}"#))
+            .await
+            .unwrap();
+        client.write_packet(&Packet::PingReq).await.unwrap();
+        assert_eq!(Some(Packet::PingResp), client.read_packet().await.unwrap());
+        client.write_packet(&Packet::Disconnect).await.unwrap();
+        join_handle.await.unwrap().unwrap();
+        assert_eq!(2, query_handler.requests.lock().unwrap().len());
+    }
+
+    #[tokio::test]
+    async fn test_reject_protocol_version() {
+        let (_query_handler, mut client, _notify_shutdown, join_handle) = start_handler(1);
+
+        client
+            .write_packet(&Packet::Connect(Connect {
+                protocol_level: 5,
+                client_id: "test".to_string(),
+                keep_alive: 0,
+                username: None,
+                password: None,
+            }))
+            .await
+            .unwrap();
+        assert_eq!(
+            Some(Packet::ConnAck(
+                ConnectReturnCode::UnacceptableProtocolVersion
+            )),
+            client.read_packet().await.unwrap()
+        );
+        join_handle.await.unwrap().unwrap();
+    }
+}
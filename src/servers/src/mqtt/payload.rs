@@ -0,0 +1,295 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Maps MQTT messages to rows.
+
+use serde::{Deserialize, Serialize};
+use snafu::{ensure, OptionExt, ResultExt};
+
+use crate::error::{InvalidMqttPayloadSnafu, MqttJsonPayloadSnafu, Result};
+use crate::influxdb;
+use crate::row_writer::{self, MultiTableData};
+
+/// Format of payloads of MQTT messages.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PayloadFormat {
+    /// A JSON object or an array of JSON objects, each object is a row.
+    #[default]
+    Json,
+    /// InfluxDB line protocol, measurements are table names.
+    LineProtocol,
+}
+
+/// Rule to map messages of topics to a table.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MqttTopicRule {
+    /// Topic filter of the rule, `+` and `#` wildcards are supported.
+    pub topic: String,
+    /// Template of the table name, `{n}` is replaced by the n-th level (starting from 0)
+    /// of the topic. Ignored by line protocol payloads.
+    pub table: String,
+    pub format: PayloadFormat,
+    /// Keys of JSON objects written as tags, other keys are written as fields.
+    pub tags: Vec<String>,
+    /// Key of the timestamp in milliseconds of JSON objects, the receive time is used
+    /// if the key is absent.
+    pub timestamp_key: String,
+}
+
+impl Default for MqttTopicRule {
+    fn default() -> Self {
+        Self {
+            topic: "#".to_string(),
+            table: String::new(),
+            format: PayloadFormat::default(),
+            tags: Vec::new(),
+            timestamp_key: "ts".to_string(),
+        }
+    }
+}
+
+impl MqttTopicRule {
+    /// Returns true if the `topic` matches the topic filter of the rule.
+    pub fn matches(&self, topic: &str) -> bool {
+        let mut filter_levels = self.topic.split('/');
+        let mut topic_levels = topic.split('/');
+        loop {
+            match (filter_levels.next(), topic_levels.next()) {
+                (Some("#"), _) => return true,
+                (Some("+"), Some(_)) => (),
+                (Some(filter), Some(level)) if filter == level => (),
+                (None, None) => return true,
+                _ => return false,
+            }
+        }
+    }
+
+    /// Returns the name of the table to write messages of the `topic`.
+    fn table_name(&self, topic: &str) -> String {
+        let mut table = self.table.clone();
+        for (i, level) in topic.split('/').enumerate() {
+            table = table.replace(&format!("{{{i}}}"), level);
+        }
+        table
+    }
+
+    /// Parses the `payload` of a message of the `topic` and writes rows into `data`.
+    ///
+    /// Returns the number of rows written.
+    pub(crate) fn write_payload(
+        &self,
+        topic: &str,
+        payload: &[u8],
+        data: &mut MultiTableData,
+    ) -> Result<usize> {
+        match self.format {
+            PayloadFormat::Json => self.write_json(topic, payload, data),
+            PayloadFormat::LineProtocol => {
+                let lines = std::str::from_utf8(payload)
+                    .ok()
+                    .context(InvalidMqttPayloadSnafu {
+                        topic,
+                        reason: "invalid UTF-8 lines",
+                    })?;
+                influxdb::write_lines(lines, None, data)
+            }
+        }
+    }
+
+    fn write_json(&self, topic: &str, payload: &[u8], data: &mut MultiTableData) -> Result<usize> {
+        let table_name = self.table_name(topic);
+        ensure!(
+            !table_name.is_empty(),
+            InvalidMqttPayloadSnafu {
+                topic,
+                reason: "empty table name",
+            }
+        );
+        let value: serde_json::Value =
+            serde_json::from_slice(payload).context(MqttJsonPayloadSnafu { topic })?;
+        let objects = match value {
+            serde_json::Value::Array(values) => values,
+            value => vec![value],
+        };
+
+        for object in &objects {
+            let object = object.as_object().context(InvalidMqttPayloadSnafu {
+                topic,
+                reason: "expect JSON objects",
+            })?;
+            let ts = object
+                .get(&self.timestamp_key)
+                .map(|value| {
+                    value.as_i64().context(InvalidMqttPayloadSnafu {
+                        topic,
+                        reason: format!("timestamp {value} is not an integer"),
+                    })
+                })
+                .transpose()?;
+            let table_data = data.get_or_default_table_data(&table_name, object.len(), 0);
+            row_writer::write_json_object(table_data, object, &self.tags, &self.timestamp_key, ts)?;
+        }
+
+        Ok(objects.len())
+    }
+}
+
+/// Returns the first rule that matches the `topic`.
+pub(crate) fn find_rule<'a>(rules: &'a [MqttTopicRule], topic: &str) -> Option<&'a MqttTopicRule> {
+    rules.iter().find(|rule| rule.matches(topic))
+}
+
+#[cfg(test)]
+mod tests {
+    use api::v1::{ColumnDataType, Rows, SemanticType};
+
+    use super::*;
+
+    fn new_rule(topic: &str, table: &str) -> MqttTopicRule {
+        MqttTopicRule {
+            topic: topic.to_string(),
+            table: table.to_string(),
+            tags: vec!["device".to_string()],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_topic_matches() {
+        let rule = new_rule("sensors/+/temperature", "");
+        assert!(rule.matches("sensors/1/temperature"));
+        assert!(!rule.matches("sensors/1/humidity"));
+        assert!(!rule.matches("sensors/1/temperature/max"));
+        assert!(!rule.matches("sensors/temperature"));
+
+        let rule = new_rule("sensors/#", "");
+        assert!(rule.matches("sensors"));
+        assert!(rule.matches("sensors/1/temperature"));
+        assert!(!rule.matches("devices/1"));
+
+        let rules = vec![new_rule("a/b", "first"), new_rule("a/+", "second")];
+        assert_eq!("second", find_rule(&rules, "a/c").unwrap().table);
+        assert_eq!("first", find_rule(&rules, "a/b").unwrap().table);
+        assert!(find_rule(&rules, "b").is_none());
+    }
+
+    #[test]
+    fn test_table_name() {
+        let rule = new_rule("factory/+/+", "{1}_{2}");
+        assert_eq!(
+            "line1_temperature",
+            rule.table_name("factory/line1/temperature")
+        );
+        let rule = new_rule("#", "metrics");
+        assert_eq!("metrics", rule.table_name("a/b"));
+    }
+
+    fn into_rows(data: MultiTableData) -> (String, Rows) {
+        let (mut requests, _) = data.into_row_insert_requests();
+        assert_eq!(1, requests.inserts.len());
+        let request = requests.inserts.pop().unwrap();
+        (request.table_name, request.rows.unwrap())
+    }
+
+    #[test]
+    fn test_write_json() {
+        let rule = new_rule("sensors/+", "{1}");
+        let mut data = MultiTableData::new();
+        let payload = br#"[
+            {"device": "d1", "temperature": 20.5, "count": 3, "ts": 1000},
+            {"device": "d2", "temperature": 21.0, "ok": true, "ts": 2000}
+        ]"#;
+        assert_eq!(
+            2,
+            rule.write_payload("sensors/room", payload, &mut data)
+                .unwrap()
+        );
+        assert_eq!(
+            1,
+            rule.write_payload("sensors/room", br#"{"device": "d3"}"#, &mut data)
+                .unwrap()
+        );
+
+        let (table, rows) = into_rows(data);
+        assert_eq!("room", table);
+        assert_eq!(3, rows.rows.len());
+        let column = |name: &str| {
+            rows.schema
+                .iter()
+                .find(|c| c.column_name == name)
+                .unwrap()
+                .clone()
+        };
+        assert_eq!(SemanticType::Tag as i32, column("device").semantic_type);
+        assert_eq!(
+            ColumnDataType::Float64 as i32,
+            column("temperature").datatype
+        );
+        assert_eq!(ColumnDataType::Int64 as i32, column("count").datatype);
+        assert_eq!(ColumnDataType::Boolean as i32, column("ok").datatype);
+        assert_eq!(SemanticType::Timestamp as i32, column("ts").semantic_type);
+        // All rows are padded to the same length.
+        assert!(rows
+            .rows
+            .iter()
+            .all(|row| row.values.len() == rows.schema.len()));
+    }
+
+    #[test]
+    fn test_write_invalid_json() {
+        let rule = new_rule("sensors/+", "{1}");
+        let mut data = MultiTableData::new();
+        assert!(rule
+            .write_payload("sensors/room", b"not json", &mut data)
+            .is_err());
+        assert!(rule
+            .write_payload("sensors/room", b"[1]", &mut data)
+            .is_err());
+        assert!(rule
+            .write_payload("sensors/room", br#"{"ts": "now"}"#, &mut data)
+            .is_err());
+        // The table name is empty.
+        assert!(rule.write_payload("sensors", b"{}", &mut data).is_err());
+    }
+
+    #[test]
+    fn test_write_line_protocol() {
+        let rule = MqttTopicRule {
+            format: PayloadFormat::LineProtocol,
+            ..Default::default()
+        };
+        let mut data = MultiTableData::new();
+        let payload = b"monitor,host=h1 cpu=1.0 1663840496100023100\nmonitor,host=h2 cpu=2.0";
+        assert_eq!(2, rule.write_payload("any", payload, &mut data).unwrap());
+
+        let (table, rows) = into_rows(data);
+        assert_eq!("monitor", table);
+        assert_eq!(2, rows.rows.len());
+    }
+
+    #[test]
+    fn test_deserialize_rule() {
+        let rule: MqttTopicRule =
+            serde_json::from_str(r#"{"topic": "sensors/+", "table": "{1}", "tags": ["device"]}"#)
+                .unwrap();
+        assert_eq!(PayloadFormat::Json, rule.format);
+        assert_eq!("ts", rule.timestamp_key);
+
+        let rule: MqttTopicRule =
+            serde_json::from_str(r#"{"topic": "lines", "format": "line_protocol"}"#).unwrap();
+        assert_eq!(PayloadFormat::LineProtocol, rule.format);
+    }
+}
@@ -15,23 +15,24 @@
 //! Use regex to filter out some MySQL federated components' emitted statements.
 //! Inspired by Databend's "[mysql_federated.rs](https://github.com/datafuselabs/databend/blob/ac706bf65845e6895141c96c0a10bad6fdc2d367/src/query/service/src/servers/mysql/mysql_federated.rs)".
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::env;
 use std::sync::Arc;
 
 use common_query::Output;
 use common_recordbatch::RecordBatches;
 use common_time::timezone::system_time_zone_name;
-use common_time::TimeZone;
 use datatypes::prelude::ConcreteDataType;
 use datatypes::schema::{ColumnSchema, Schema};
 use datatypes::vectors::StringVector;
 use once_cell::sync::Lazy;
 use regex::bytes::RegexSet;
 use regex::Regex;
-use session::context::QueryContextRef;
+use session::context::{QueryContextRef, SessionVariables};
 use session::SessionRef;
 
+use crate::statement_timeout;
+
 static SELECT_VAR_PATTERN: Lazy<Regex> = Lazy::new(|| Regex::new("(?i)^(SELECT @@(.*))").unwrap());
 static MYSQL_CONN_JAVA_PATTERN: Lazy<Regex> =
     Lazy::new(|| Regex::new("(?i)^(/\\* mysql-connector-j(.*))").unwrap());
@@ -55,9 +56,17 @@ static SELECT_TIME_DIFF_FUNC_PATTERN: Lazy<Regex> =
 static SHOW_SQL_MODE_PATTERN: Lazy<Regex> =
     Lazy::new(|| Regex::new("(?i)^(SHOW VARIABLES LIKE 'sql_mode'(.*))").unwrap());
 
-// Time zone settings
-static SET_TIME_ZONE_PATTERN: Lazy<Regex> =
-    Lazy::new(|| Regex::new(r"(?i)^SET TIME_ZONE\s*=\s*'(\S+)'").unwrap());
+// Session variables like "SET time_zone = '+08:00'" or "SET @@session.max_execution_time = 1000".
+static SET_VARIABLE_PATTERN: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r#"(?i)^\s*SET\s+(?:SESSION\s+|LOCAL\s+)?((?:@@(?:SESSION\.|LOCAL\.)?)?[a-z_][a-z0-9_]*)\s*=\s*('[^']*'|"[^"]*"|[^\s,;'"]+)\s*;?\s*$"#,
+    )
+    .unwrap()
+});
+
+static SHOW_VARIABLES_LIKE_PATTERN: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)^\s*SHOW\s+(?:SESSION\s+)?VARIABLES\s+LIKE\s+'([^']*)'\s*;?\s*$").unwrap()
+});
 
 static OTHER_NOT_SUPPORTED_STMT: Lazy<RegexSet> = Lazy::new(|| {
     RegexSet::new([
@@ -161,20 +170,29 @@ fn select_function(name: &str, value: &str) -> RecordBatches {
 // | Variable_name | Value |
 // | xx            | yy    |
 fn show_variables(name: &str, value: &str) -> RecordBatches {
+    show_variable_rows(vec![(name.to_string(), value.to_string())])
+}
+
+fn show_variable_rows(rows: Vec<(String, String)>) -> RecordBatches {
     let schema = Arc::new(Schema::new(vec![
         ColumnSchema::new("Variable_name", ConcreteDataType::string_datatype(), true),
         ColumnSchema::new("Value", ConcreteDataType::string_datatype(), true),
     ]));
+    let (names, values): (Vec<_>, Vec<_>) = rows.into_iter().unzip();
     let columns = vec![
-        Arc::new(StringVector::from(vec![name])) as _,
-        Arc::new(StringVector::from(vec![value])) as _,
+        Arc::new(StringVector::from(names)) as _,
+        Arc::new(StringVector::from(values)) as _,
     ];
     RecordBatches::try_from_columns(schema, columns)
         // unwrap is safe because the schema and data are definitely able to form a recordbatch, they are all string type
         .unwrap()
 }
 
-fn select_variable(query: &str, query_context: QueryContextRef) -> Option<Output> {
+fn select_variable(
+    query: &str,
+    query_context: QueryContextRef,
+    session: &SessionRef,
+) -> Option<Output> {
     let mut fields = vec![];
     let mut values = vec![];
 
@@ -205,9 +223,9 @@ fn select_variable(query: &str, query_context: QueryContextRef) -> Option<Output
                 .map(|tz| tz.to_string())
                 .unwrap_or_else(|| "".to_owned()),
             "system_time_zone" => system_time_zone_name(),
-            _ => VAR_VALUES
-                .get(var_as[0])
-                .map(|v| v.to_string())
+            name => session
+                .variable(name)
+                .or_else(|| VAR_VALUES.get(name).map(|v| v.to_string()))
                 .unwrap_or_else(|| "0".to_owned()),
         };
 
@@ -242,37 +260,98 @@ fn select_variable(query: &str, query_context: QueryContextRef) -> Option<Output
     Some(Output::RecordBatches(batches))
 }
 
-fn check_select_variable(query: &str, query_context: QueryContextRef) -> Option<Output> {
+fn check_select_variable(
+    query: &str,
+    query_context: QueryContextRef,
+    session: &SessionRef,
+) -> Option<Output> {
     if [&SELECT_VAR_PATTERN, &MYSQL_CONN_JAVA_PATTERN]
         .iter()
         .any(|r| r.is_match(query))
     {
-        select_variable(query, query_context)
+        select_variable(query, query_context, session)
     } else {
         None
     }
 }
 
-fn check_show_variables(query: &str) -> Option<Output> {
+// Variables shown by "SHOW VARIABLES": the defaults overridden by the ones set in the session.
+fn session_variables(query_ctx: &QueryContextRef, session: &SessionRef) -> Vec<(String, String)> {
+    let mut variables = VAR_VALUES
+        .iter()
+        .filter(|(name, _)| !name.starts_with("session."))
+        .map(|(name, value)| (name.to_string(), value.to_string()))
+        .collect::<BTreeMap<_, _>>();
+    let _ = variables.insert("system_time_zone".to_string(), system_time_zone_name());
+    let _ = variables.insert(
+        SessionVariables::TIME_ZONE.to_string(),
+        query_ctx
+            .time_zone()
+            .map(|tz| tz.to_string())
+            .unwrap_or_default(),
+    );
+    let _ = variables.insert(
+        SessionVariables::MAX_EXECUTION_TIME.to_string(),
+        "0".to_string(),
+    );
+    variables.extend(session.variables());
+    variables.into_iter().collect()
+}
+
+// Converts the pattern of "LIKE" to a regex, '%' matches any characters and '_' matches one.
+fn like_to_regex(pattern: &str) -> Option<Regex> {
+    let mut regex = String::from("(?i)^");
+    for c in pattern.chars() {
+        match c {
+            '%' => regex.push_str(".*"),
+            '_' => regex.push('.'),
+            c => regex.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    regex.push('$');
+    Regex::new(&regex).ok()
+}
+
+fn check_show_variables(
+    query: &str,
+    query_ctx: &QueryContextRef,
+    session: &SessionRef,
+) -> Option<Output> {
     let recordbatches = if SHOW_SQL_MODE_PATTERN.is_match(query) {
         Some(show_variables("sql_mode", "ONLY_FULL_GROUP_BY STRICT_TRANS_TABLES NO_ZERO_IN_DATE NO_ZERO_DATE ERROR_FOR_DIVISION_BY_ZERO NO_ENGINE_SUBSTITUTION"))
     } else if SHOW_LOWER_CASE_PATTERN.is_match(query) {
         Some(show_variables("lower_case_table_names", "0"))
-    } else if SHOW_COLLATION_PATTERN.is_match(query) || SHOW_VARIABLES_PATTERN.is_match(query) {
+    } else if SHOW_COLLATION_PATTERN.is_match(query) {
         Some(show_variables("", ""))
+    } else if SHOW_VARIABLES_PATTERN.is_match(query) {
+        let mut variables = session_variables(query_ctx, session);
+        if let Some(pattern) = SHOW_VARIABLES_LIKE_PATTERN
+            .captures(query)
+            .and_then(|captures| like_to_regex(captures.get(1)?.as_str()))
+        {
+            variables.retain(|(name, _)| pattern.is_match(name));
+        }
+        Some(show_variable_rows(variables))
     } else {
         None
     };
     recordbatches.map(Output::RecordBatches)
 }
 
-// TODO(sunng87): extract this to use sqlparser for more variables
-fn check_set_variables(query: &str, session: SessionRef) -> Option<Output> {
-    if let Some(captures) = SET_TIME_ZONE_PATTERN.captures(query) {
-        // get the capture
-        let tz = captures.get(1).unwrap();
-        if let Ok(timezone) = TimeZone::from_tz_string(tz.as_str()) {
-            session.set_time_zone(timezone);
+fn check_set_variables(query: &str, session: &SessionRef) -> Option<Output> {
+    if let Some(value) = statement_timeout::match_set_statement_timeout(query) {
+        if let Ok(timeout) = statement_timeout::parse_statement_timeout(value) {
+            session.set_statement_timeout(timeout);
+            return Some(Output::AffectedRows(0));
+        }
+    }
+
+    if let Some(captures) = SET_VARIABLE_PATTERN.captures(query) {
+        // Both groups are mandatory in the pattern.
+        let name = captures.get(1).unwrap().as_str();
+        let value = captures.get(2).unwrap().as_str();
+        // An invalid value falls through, so the query fails as usual.
+        if session.set_variable(name, value).is_ok() {
             return Some(Output::AffectedRows(0));
         }
     }
@@ -316,10 +395,10 @@ pub(crate) fn check(
     }
 
     // First to check the query is like "select @@variables".
-    check_select_variable(query, query_ctx.clone())
+    check_select_variable(query, query_ctx.clone(), &session)
         // Then to check "show variables like ...".
-        .or_else(|| check_show_variables(query))
-        .or_else(|| check_set_variables(query, session.clone()))
+        .or_else(|| check_show_variables(query, &query_ctx, &session))
+        .or_else(|| check_set_variables(query, &session))
         // Last check
         .or_else(|| check_others(query, query_ctx))
 }
@@ -331,6 +410,8 @@ fn get_version() -> String {
 #[cfg(test)]
 mod test {
 
+    use std::time::Duration;
+
     use session::context::{Channel, QueryContext};
     use session::Session;
 
@@ -402,13 +483,15 @@ mod test {
 +--------------------------+----------------------+--------------------------+-----------------------+----------------------+------------------+----------------------+--------------+---------------------+---------+------------------------+--------------------+-------------------+--------------------+----------+------------------+-----------+-----------------------+---------------+";
         test(query, expected);
 
-        let query = "show variables";
+        let query = "show variables like '%_timeout'";
         let expected = "\
-+---------------+-------+
-| Variable_name | Value |
-+---------------+-------+
-|               |       |
-+---------------+-------+";
++---------------------+----------+
+| Variable_name       | Value    |
++---------------------+----------+
+| interactive_timeout | 31536000 |
+| net_write_timeout   | 31536000 |
+| wait_timeout        | 31536000 |
++---------------------+----------+";
         test(query, expected);
 
         let query = "show variables like 'lower_case_table_names'";
@@ -467,4 +550,87 @@ mod test {
             _ => unreachable!(),
         }
     }
+
+    #[test]
+    fn test_set_statement_timeout() {
+        let session = Arc::new(Session::new(None, Channel::Mysql));
+        let output = check(
+            "SET statement_timeout = '30s'",
+            QueryContext::arc(),
+            session.clone(),
+        );
+        assert!(matches!(output, Some(Output::AffectedRows(0))));
+        assert_eq!(Some(Duration::from_secs(30)), session.statement_timeout());
+        assert!(session.new_query_context().deadline().is_some());
+
+        let _ = check(
+            "SET statement_timeout = 0",
+            QueryContext::arc(),
+            session.clone(),
+        );
+        assert!(session.statement_timeout().is_none());
+        assert!(session.new_query_context().deadline().is_none());
+    }
+
+    #[test]
+    fn test_set_variables() {
+        let session = Arc::new(Session::new(None, Channel::Mysql));
+        for query in [
+            "SET time_zone = '+08:00'",
+            "SET @@session.max_execution_time = 1500;",
+            "SET SESSION autocommit = 1",
+        ] {
+            let output = check(query, QueryContext::arc(), session.clone());
+            assert!(matches!(output, Some(Output::AffectedRows(0))), "{query}");
+        }
+        assert_eq!(
+            Some(Duration::from_millis(1500)),
+            session.statement_timeout()
+        );
+
+        // Invalid values aren't swallowed.
+        let output = check(
+            "SET max_execution_time = 'soon'",
+            QueryContext::arc(),
+            session.clone(),
+        );
+        assert!(output.is_none());
+
+        let query_context = session.new_query_context();
+        let output = check(
+            "select @@autocommit, @@max_execution_time",
+            query_context.clone(),
+            session.clone(),
+        );
+        match output.unwrap() {
+            Output::RecordBatches(r) => {
+                let expected = "\
++--------------+----------------------+
+| @@autocommit | @@max_execution_time |
++--------------+----------------------+
+| 1            | 1500                 |
++--------------+----------------------+";
+                assert_eq!(r.pretty_print().unwrap(), expected);
+            }
+            _ => unreachable!(),
+        }
+
+        let output = check(
+            "SHOW VARIABLES LIKE 'time_zone'",
+            query_context,
+            session.clone(),
+        );
+        match output.unwrap() {
+            Output::RecordBatches(r) => {
+                let expected = "\
++---------------+--------+
+| Variable_name | Value  |
++---------------+--------+
+| time_zone     | +08:00 |
++---------------+--------+";
+                assert_eq!(r.pretty_print().unwrap(), expected);
+            }
+            _ => unreachable!(),
+        }
+    }
 }
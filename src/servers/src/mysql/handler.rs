@@ -13,6 +13,7 @@
 // limitations under the License.
 
 use std::collections::HashMap;
+use std::future::Future;
 use std::net::SocketAddr;
 use std::sync::atomic::{AtomicU32, Ordering};
 use std::sync::Arc;
@@ -21,8 +22,8 @@ use std::time::Duration;
 use ::auth::{Identity, Password, UserProviderRef};
 use async_trait::async_trait;
 use chrono::{NaiveDate, NaiveDateTime};
-use common_catalog::parse_catalog_and_schema_from_db_string;
 use common_error::ext::ErrorExt;
+use common_error::status_code::StatusCode;
 use common_query::Output;
 use common_telemetry::{debug, error, logging, tracing, warn};
 use datatypes::prelude::ConcreteDataType;
@@ -36,6 +37,7 @@ use query::plan::LogicalPlan;
 use query::query_engine::DescribeResult;
 use rand::RngCore;
 use session::context::{Channel, QueryContextRef};
+use session::database::resolve_database;
 use session::{Session, SessionRef};
 use snafu::{ensure, ResultExt};
 use sql::dialect::MySqlDialect;
@@ -51,6 +53,8 @@ use crate::mysql::helper::{
 use crate::mysql::writer;
 use crate::mysql::writer::create_mysql_column;
 use crate::query_handler::sql::ServerSqlQueryHandlerRef;
+use crate::query_registry::{QueryRegistryRef, RunningQueryGuard};
+use crate::rate_limit::RateLimiterRef;
 use crate::SqlPlan;
 
 // An intermediate shim for executing MySQL queries.
@@ -61,12 +65,18 @@ pub struct MysqlInstanceShim {
     user_provider: Option<UserProviderRef>,
     prepared_stmts: Arc<RwLock<HashMap<u32, SqlPlan>>>,
     prepared_stmts_counter: AtomicU32,
+    query_registry: QueryRegistryRef,
+    /// Id of the connection, queries of the connection are registered under the id.
+    conn_id: u32,
+    rate_limiter: Option<RateLimiterRef>,
 }
 
 impl MysqlInstanceShim {
     pub fn create(
         query_handler: ServerSqlQueryHandlerRef,
         user_provider: Option<UserProviderRef>,
+        query_registry: QueryRegistryRef,
+        rate_limiter: Option<RateLimiterRef>,
         client_addr: SocketAddr,
     ) -> MysqlInstanceShim {
         // init a random salt
@@ -89,6 +99,17 @@ impl MysqlInstanceShim {
             user_provider,
             prepared_stmts: Default::default(),
             prepared_stmts_counter: AtomicU32::new(1),
+            conn_id: query_registry.next_id(),
+            query_registry,
+            rate_limiter,
+        }
+    }
+
+    /// Returns an error if the query exceeds the rate limit.
+    fn check_rate_limit(&self, query_ctx: &QueryContextRef) -> Result<()> {
+        match &self.rate_limiter {
+            Some(rate_limiter) => rate_limiter.check(query_ctx),
+            None => Ok(()),
         }
     }
 
@@ -119,6 +140,23 @@ impl MysqlInstanceShim {
         }
     }
 
+    /// Runs the query until it's done or killed.
+    async fn run_query(
+        &self,
+        query: &str,
+        query_ctx: &QueryContextRef,
+        outputs: impl Future<Output = Vec<Result<Output>>>,
+    ) -> (Vec<Result<Output>>, RunningQueryGuard) {
+        let guard = self
+            .query_registry
+            .register(self.conn_id, query, query_ctx, "mysql");
+        let outputs = guard
+            .run(async { Ok(outputs.await) })
+            .await
+            .unwrap_or_else(|e| vec![Err(e)]);
+        (outputs, guard)
+    }
+
     /// Describe the statement
     async fn do_describe(
         &self,
@@ -147,6 +185,10 @@ impl MysqlInstanceShim {
 impl<W: AsyncWrite + Send + Sync + Unpin> AsyncMysqlShim<W> for MysqlInstanceShim {
     type Error = error::Error;
 
+    fn connect_id(&self) -> u32 {
+        self.conn_id
+    }
+
     fn salt(&self) -> [u8; 20] {
         self.salt
     }
@@ -261,6 +303,10 @@ impl<W: AsyncWrite + Send + Sync + Unpin> AsyncMysqlShim<W> for MysqlInstanceShi
         let _timer = crate::metrics::METRIC_MYSQL_QUERY_TIMER
             .with_label_values(&[crate::metrics::METRIC_MYSQL_BINQUERY, db.as_str()])
             .start_timer();
+        if let Err(e) = self.check_rate_limit(&query_ctx) {
+            writer::write_output(w, query_ctx, vec![Err(e)]).await?;
+            return Ok(());
+        }
 
         let params: Vec<ParamValue> = p.into_iter().collect();
         let sql_plan = match self.plan(stmt_id) {
@@ -275,7 +321,8 @@ impl<W: AsyncWrite + Send + Sync + Unpin> AsyncMysqlShim<W> for MysqlInstanceShi
             Some(sql_plan) => sql_plan,
         };
 
-        let outputs = match sql_plan.plan {
+        // Keeps the query registered until its output is written.
+        let (outputs, _guard) = match sql_plan.plan {
             Some(plan) => {
                 let param_types = plan
                     .get_param_types()
@@ -308,15 +355,19 @@ impl<W: AsyncWrite + Send + Sync + Unpin> AsyncMysqlShim<W> for MysqlInstanceShi
                 };
 
                 logging::debug!("Mysql execute prepared plan: {}", plan.display_indent());
-                vec![
-                    self.do_exec_plan(&sql_plan.query, plan, query_ctx.clone())
-                        .await,
-                ]
+                self.run_query(&sql_plan.query, &query_ctx, async {
+                    vec![
+                        self.do_exec_plan(&sql_plan.query, plan, query_ctx.clone())
+                            .await,
+                    ]
+                })
+                .await
             }
             None => {
                 let query = replace_params(params, sql_plan.query);
                 logging::debug!("Mysql execute replaced query: {}", query);
-                self.do_query(&query, query_ctx.clone()).await
+                self.run_query(&query, &query_ctx, self.do_query(&query, query_ctx.clone()))
+                    .await
             }
         };
 
@@ -343,43 +394,58 @@ impl<W: AsyncWrite + Send + Sync + Unpin> AsyncMysqlShim<W> for MysqlInstanceShi
         let _timer = crate::metrics::METRIC_MYSQL_QUERY_TIMER
             .with_label_values(&[crate::metrics::METRIC_MYSQL_TEXTQUERY, db.as_str()])
             .start_timer();
-        let outputs = self.do_query(query, query_ctx.clone()).await;
+        if let Err(e) = self.check_rate_limit(&query_ctx) {
+            writer::write_output(writer, query_ctx, vec![Err(e)]).await?;
+            return Ok(());
+        }
+        if let Some(id) = helper::parse_kill_query(query) {
+            let output = self
+                .query_registry
+                .kill(id)
+                .map(|_| Output::AffectedRows(0));
+            writer::write_output(writer, query_ctx, vec![output]).await?;
+            return Ok(());
+        }
+
+        // Keeps the query registered until its output is written.
+        let (outputs, _guard) = self
+            .run_query(query, &query_ctx, self.do_query(query, query_ctx.clone()))
+            .await;
         writer::write_output(writer, query_ctx, outputs).await?;
         Ok(())
     }
 
     async fn on_init<'a>(&'a mut self, database: &'a str, w: InitWriter<'a, W>) -> Result<()> {
-        let (catalog, schema) = parse_catalog_and_schema_from_db_string(database);
-
-        if !self.query_handler.is_valid_schema(catalog, schema).await? {
-            return w
-                .error(
-                    ErrorKind::ER_WRONG_DB_NAME,
-                    format!("Unknown database '{}'", database).as_bytes(),
-                )
-                .await
-                .map_err(|e| e.into());
-        }
-
-        let user_info = &self.session.user_info();
-
-        if let Some(schema_validator) = &self.user_provider {
-            if let Err(e) = schema_validator.authorize(catalog, schema, user_info).await {
-                METRIC_AUTH_FAILURE
-                    .with_label_values(&[e.status_code().as_ref()])
-                    .inc();
+        let user_info = self.session.user_info();
+        let resolved = resolve_database(
+            self.query_handler.as_ref(),
+            self.user_provider.as_ref(),
+            Some(database),
+            Some(&user_info),
+        )
+        .await;
+        let (catalog, schema) = match resolved {
+            Ok(resolved) => resolved,
+            Err(e) => {
+                let kind = match e.status_code() {
+                    StatusCode::DatabaseNotFound => ErrorKind::ER_WRONG_DB_NAME,
+                    StatusCode::AccessDenied => {
+                        METRIC_AUTH_FAILURE
+                            .with_label_values(&[e.status_code().as_ref()])
+                            .inc();
+                        ErrorKind::ER_DBACCESS_DENIED_ERROR
+                    }
+                    _ => ErrorKind::ER_INTERNAL_ERROR,
+                };
                 return w
-                    .error(
-                        ErrorKind::ER_DBACCESS_DENIED_ERROR,
-                        e.output_msg().as_bytes(),
-                    )
+                    .error(kind, e.output_msg().as_bytes())
                     .await
                     .map_err(|e| e.into());
             }
-        }
+        };
 
-        self.session.set_catalog(catalog.into());
-        self.session.set_schema(schema.into());
+        self.session.set_catalog(catalog);
+        self.session.set_schema(schema);
 
         w.ok().await.map_err(|e| e.into())
     }
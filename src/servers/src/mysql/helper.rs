@@ -20,13 +20,25 @@ use common_query::prelude::ScalarValue;
 use datatypes::prelude::ConcreteDataType;
 use datatypes::value::{self, Value};
 use itertools::Itertools;
+use once_cell::sync::Lazy;
 use opensrv_mysql::{to_naive_datetime, ParamValue, ValueInner};
+use regex::Regex;
 use snafu::ResultExt;
 use sql::ast::{visit_expressions_mut, Expr, Value as ValueExpr, VisitMut};
 use sql::statements::statement::Statement;
 
 use crate::error::{self, Result};
 
+static KILL_QUERY_PATTERN: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?i)^\s*KILL\s+QUERY\s+(\d+)\s*;?\s*$").unwrap());
+
+/// Returns the id in a `KILL QUERY <id>` statement.
+pub fn parse_kill_query(query: &str) -> Option<u32> {
+    KILL_QUERY_PATTERN
+        .captures(query)
+        .and_then(|captures| captures[1].parse().ok())
+}
+
 /// Returns the placeholder string "$i".
 pub fn format_placeholder(i: usize) -> String {
     format!("${}", i)
@@ -207,6 +219,15 @@ mod tests {
 
     use super::*;
 
+    #[test]
+    fn test_parse_kill_query() {
+        assert_eq!(Some(12), parse_kill_query("KILL QUERY 12"));
+        assert_eq!(Some(3), parse_kill_query(" kill  query 3; "));
+        assert_eq!(None, parse_kill_query("KILL 3"));
+        assert_eq!(None, parse_kill_query("KILL QUERY abc"));
+        assert_eq!(None, parse_kill_query("SELECT 'KILL QUERY 3'"));
+    }
+
     #[test]
     fn test_format_placeholder() {
         assert_eq!("$1", format_placeholder(1));
@@ -30,10 +30,16 @@ use tokio::io::BufWriter;
 use tokio::net::TcpStream;
 use tokio_rustls::rustls::ServerConfig;
 
+use crate::connection::{
+    ActivityReader, ConnectionActivity, ConnectionManager, ConnectionOptions, ConnectionPermit,
+};
 use crate::error::{Error, Result};
 use crate::mysql::handler::MysqlInstanceShim;
 use crate::query_handler::sql::ServerSqlQueryHandlerRef;
+use crate::query_registry::QueryRegistryRef;
+use crate::rate_limit::RateLimiterRef;
 use crate::server::{AbortableStream, BaseTcpServer, Server};
+use crate::tls::ReloadableTlsServerConfigRef;
 
 // Default size of ResultSet write buffer: 100KB
 const DEFAULT_RESULT_SET_WRITE_BUFFER_SIZE: usize = 100 * 1024;
@@ -43,6 +49,8 @@ const DEFAULT_RESULT_SET_WRITE_BUFFER_SIZE: usize = 100 * 1024;
 pub struct MysqlSpawnRef {
     query_handler: ServerSqlQueryHandlerRef,
     user_provider: Option<UserProviderRef>,
+    query_registry: QueryRegistryRef,
+    rate_limiter: Option<RateLimiterRef>,
 }
 
 impl MysqlSpawnRef {
@@ -53,15 +61,36 @@ impl MysqlSpawnRef {
         MysqlSpawnRef {
             query_handler,
             user_provider,
+            query_registry: Default::default(),
+            rate_limiter: None,
         }
     }
 
+    /// Sets the registry to cancel queries, queries are registered under ids of their connections.
+    pub fn with_query_registry(mut self, query_registry: QueryRegistryRef) -> Self {
+        self.query_registry = query_registry;
+        self
+    }
+
+    /// Limits queries of connections, queries exceeding the rate limit fail with
+    /// `ER_CON_COUNT_ERROR` (1040).
+    pub fn with_rate_limiter(mut self, rate_limiter: RateLimiterRef) -> Self {
+        self.rate_limiter = Some(rate_limiter);
+        self
+    }
+
     fn query_handler(&self) -> ServerSqlQueryHandlerRef {
         self.query_handler.clone()
     }
     fn user_provider(&self) -> Option<UserProviderRef> {
         self.user_provider.clone()
     }
+    fn query_registry(&self) -> QueryRegistryRef {
+        self.query_registry.clone()
+    }
+    fn rate_limiter(&self) -> Option<RateLimiterRef> {
+        self.rate_limiter.clone()
+    }
 }
 
 /// [`MysqlSpawnConfig`] stores config values
@@ -69,26 +98,35 @@ impl MysqlSpawnRef {
 pub struct MysqlSpawnConfig {
     // tls config
     force_tls: bool,
-    tls: Option<Arc<ServerConfig>>,
+    tls: ReloadableTlsServerConfigRef,
     // other shim config
     reject_no_database: bool,
+    connection: ConnectionManager,
 }
 
 impl MysqlSpawnConfig {
     pub fn new(
         force_tls: bool,
-        tls: Option<Arc<ServerConfig>>,
+        tls: ReloadableTlsServerConfigRef,
         reject_no_database: bool,
     ) -> MysqlSpawnConfig {
         MysqlSpawnConfig {
             force_tls,
             tls,
             reject_no_database,
+            connection: ConnectionManager::new("mysql", ConnectionOptions::default()),
         }
     }
 
+    /// Limits connections, closes idle connections and enables TCP keepalive of connections.
+    pub fn with_connection_options(mut self, options: ConnectionOptions) -> Self {
+        self.connection = ConnectionManager::new("mysql", options);
+        self
+    }
+
+    /// Returns the latest TLS config, so reloaded certificates apply to new connections.
     fn tls(&self) -> Option<Arc<ServerConfig>> {
-        self.tls.clone()
+        self.tls.get_server_config()
     }
 }
 
@@ -137,11 +175,15 @@ impl MysqlServer {
                 match tcp_stream {
                     Err(error) => warn!("Broken pipe: {}", error), // IoError doesn't impl ErrorExt.
                     Ok(io_stream) => {
+                        let Some(permit) = spawn_config.connection.admit(&io_stream) else {
+                            return;
+                        };
                         if let Err(e) = io_stream.set_nodelay(true) {
                             error!(e; "Failed to set TCP nodelay");
                         }
                         if let Err(error) =
-                            Self::handle(io_stream, io_runtime, spawn_ref, spawn_config).await
+                            Self::handle(io_stream, permit, io_runtime, spawn_ref, spawn_config)
+                                .await
                         {
                             warn!("Unexpected error when handling TcpStream {}", error);
                         };
@@ -153,6 +195,7 @@ impl MysqlServer {
 
     async fn handle(
         stream: TcpStream,
+        permit: ConnectionPermit,
         io_runtime: Arc<Runtime>,
         spawn_ref: Arc<MysqlSpawnRef>,
         spawn_config: Arc<MysqlSpawnConfig>,
@@ -160,12 +203,21 @@ impl MysqlServer {
         info!("MySQL connection coming from: {}", stream.peer_addr()?);
         let _handle = io_runtime.spawn(async move {
             crate::metrics::METRIC_MYSQL_CONNECTIONS.inc();
-            if let Err(e)  = Self::do_handle(stream, spawn_ref, spawn_config).await {
-                // TODO(LFC): Write this error to client as well, in MySQL text protocol.
-                // Looks like we have to expose opensrv-mysql's `PacketWriter`?
-                warn!(e; "Internal error occurred during query exec, server actively close the channel to let client try next time")
+            let activity = spawn_config.connection.activity();
+            tokio::select! {
+                result = Self::do_handle(stream, activity.clone(), spawn_ref, spawn_config.clone()) => {
+                    if let Err(e) = result {
+                        // TODO(LFC): Write this error to client as well, in MySQL text protocol.
+                        // Looks like we have to expose opensrv-mysql's `PacketWriter`?
+                        warn!(e; "Internal error occurred during query exec, server actively close the channel to let client try next time")
+                    }
+                }
+                _ = spawn_config.connection.wait_idle_timeout(&activity) => {
+                    info!("Close idle MySQL connection");
+                }
             }
             crate::metrics::METRIC_MYSQL_CONNECTIONS.dec();
+            drop(permit);
         });
 
         Ok(())
@@ -173,15 +225,19 @@ impl MysqlServer {
 
     async fn do_handle(
         stream: TcpStream,
+        activity: Arc<ConnectionActivity>,
         spawn_ref: Arc<MysqlSpawnRef>,
         spawn_config: Arc<MysqlSpawnConfig>,
     ) -> Result<()> {
         let mut shim = MysqlInstanceShim::create(
             spawn_ref.query_handler(),
             spawn_ref.user_provider(),
+            spawn_ref.query_registry(),
+            spawn_ref.rate_limiter(),
             stream.peer_addr()?,
         );
-        let (mut r, w) = stream.into_split();
+        let (r, w) = stream.into_split();
+        let mut r = ActivityReader::new(r, activity);
         let mut w = BufWriter::with_capacity(DEFAULT_RESULT_SET_WRITE_BUFFER_SIZE, w);
 
         let ops = spawn_config.as_ref().into();
@@ -15,6 +15,7 @@
 use std::ops::Deref;
 
 use common_error::ext::ErrorExt;
+use common_error::status_code::StatusCode;
 use common_query::Output;
 use common_recordbatch::{RecordBatch, SendableRecordBatchStream};
 use common_telemetry::{debug, error};
@@ -228,7 +229,10 @@ impl<'a, W: AsyncWrite + Unpin> MysqlResultWriter<'a, W> {
             debug!("Failed to handle mysql query, error: {error:?}");
         }
 
-        let kind = ErrorKind::ER_INTERNAL_ERROR;
+        let kind = match error.status_code() {
+            StatusCode::RateLimited => ErrorKind::ER_CON_COUNT_ERROR,
+            _ => ErrorKind::ER_INTERNAL_ERROR,
+        };
         let error = error.output_msg();
         w.error(kind, error.as_bytes()).await?;
         Ok(())
@@ -78,6 +78,7 @@ pub fn write_span_to_row(writer: &mut TableData, span: TraceSpan) -> Result<()>
             ("trace_id", span.trace_id),
             ("span_id", span.span_id),
             ("parent_span_id", span.parent_span_id),
+            ("service_name", span.service_name),
         ]
         .into_iter()
         .map(|(col, val)| (col.to_string(), val));
@@ -121,8 +122,18 @@ pub fn write_span_to_row(writer: &mut TableData, span: TraceSpan) -> Result<()>
             )
         });
 
+        let duration_nano = span
+            .end_in_nanosecond
+            .saturating_sub(span.start_in_nanosecond);
+        let duration_iter = std::iter::once((
+            "duration_nano".to_string(),
+            ColumnDataType::Uint64,
+            ValueData::U64Value(duration_nano),
+        ));
+
         row_writer::write_fields(writer, str_fields_iter, &mut row)?;
         row_writer::write_fields(writer, time_fields_iter, &mut row)?;
+        row_writer::write_fields(writer, duration_iter, &mut row)?;
         row_writer::write_fields(writer, span.uplifted_span_attributes.into_iter(), &mut row)?;
     }
 
@@ -23,7 +23,10 @@ use opentelemetry_proto::tonic::trace::v1::span::{Event, Link};
 use opentelemetry_proto::tonic::trace::v1::{Span, Status};
 use serde::Serialize;
 
-use super::attributes::Attributes;
+use super::attributes::{Attributes, OtlpAnyValue};
+
+/// Resource attribute of the service that emits spans.
+const SERVICE_NAME_KEY: &str = "service.name";
 
 #[derive(Debug, Clone)]
 pub struct TraceSpan {
@@ -31,6 +34,7 @@ pub struct TraceSpan {
     pub trace_id: String,
     pub span_id: String,
     pub parent_span_id: String,
+    pub service_name: String,
 
     // the following are fields
     pub resource_attributes: Attributes, // TODO(yuanbohan): Map in the future
@@ -148,10 +152,12 @@ pub fn parse_span(
 ) -> TraceSpan {
     let (span_status_code, span_status_message) = status_to_string(&span.status);
     let span_kind = span.kind().as_str_name().into();
+    let service_name = service_name(&resource_attrs);
     TraceSpan {
         trace_id: bytes_to_hex_string(&span.trace_id),
         span_id: bytes_to_hex_string(&span.span_id),
         parent_span_id: bytes_to_hex_string(&span.parent_span_id),
+        service_name,
 
         resource_attributes: Attributes::from(resource_attrs),
         trace_state: span.trace_state,
@@ -175,6 +181,16 @@ pub fn parse_span(
     }
 }
 
+/// Returns the `service.name` of the resource, which is empty if it's absent.
+pub fn service_name(resource_attrs: &[KeyValue]) -> String {
+    resource_attrs
+        .iter()
+        .find(|attr| attr.key == SERVICE_NAME_KEY)
+        .and_then(|attr| attr.value.as_ref())
+        .map(|value| OtlpAnyValue::from(value).to_string())
+        .unwrap_or_default()
+}
+
 pub fn bytes_to_hex_string(bs: &[u8]) -> String {
     bs.iter().map(|b| format!("{:02x}", b)).join("")
 }
@@ -188,9 +204,11 @@ pub fn status_to_string(status: &Option<Status>) -> (String, String) {
 
 #[cfg(test)]
 mod tests {
+    use opentelemetry_proto::tonic::common::v1::any_value::Value;
+    use opentelemetry_proto::tonic::common::v1::{AnyValue, KeyValue};
     use opentelemetry_proto::tonic::trace::v1::Status;
 
-    use crate::otlp::trace::span::{bytes_to_hex_string, status_to_string};
+    use crate::otlp::trace::span::{bytes_to_hex_string, service_name, status_to_string};
 
     #[test]
     fn test_bytes_to_hex_string() {
@@ -220,4 +238,25 @@ mod tests {
             status_to_string(&Some(status)),
         );
     }
+
+    #[test]
+    fn test_service_name() {
+        assert_eq!("", service_name(&[]));
+
+        let attrs = vec![
+            KeyValue {
+                key: "host.name".into(),
+                value: Some(AnyValue {
+                    value: Some(Value::StringValue("host".into())),
+                }),
+            },
+            KeyValue {
+                key: "service.name".into(),
+                value: Some(AnyValue {
+                    value: Some(Value::StringValue("frontend".into())),
+                }),
+            },
+        ];
+        assert_eq!("frontend", service_name(&attrs));
+    }
 }
@@ -0,0 +1,422 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Ingestion pipelines.
+//!
+//! A pipeline consumes messages from a source, e.g. a Kafka topic, transforms them to rows
+//! and writes the rows to tables. Pipelines are created by `CREATE PIPELINE` and stored in
+//! the metadata along with the offsets they have consumed, so a [PipelineRunner] resumes them
+//! after restarts.
+
+mod kafka;
+mod transform;
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use common_meta::key::pipeline::{PipelineKey, PipelineManager, PipelineManagerRef, PipelineValue};
+use common_meta::kv_backend::KvBackendRef;
+use common_runtime::JoinHandle;
+use common_telemetry::logging::{error, info};
+use futures::TryStreamExt;
+use humantime_serde::re::humantime;
+use serde::{Deserialize, Serialize};
+use snafu::{ensure, OptionExt, ResultExt};
+
+use crate::error::{InvalidPipelineSnafu, PipelineMetadataSnafu, Result};
+use crate::pipeline::kafka::KafkaSource;
+pub use crate::pipeline::transform::{MessageFormat, Transform};
+use crate::query_handler::PipelineHandlerRef;
+
+pub const KAFKA_SOURCE: &str = "kafka";
+
+const TAGS_KEY: &str = "tags";
+const TIMESTAMP_KEY_KEY: &str = "timestamp_key";
+const RENAME_KEY: &str = "rename";
+const EXCLUDE_KEY: &str = "exclude";
+const BATCH_SIZE_KEY: &str = "batch_size";
+const FLUSH_INTERVAL_KEY: &str = "flush_interval";
+
+/// Interval to retry a pipeline after it fails.
+const RETRY_INTERVAL: Duration = Duration::from_secs(5);
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct PipelineOptions {
+    /// Whether to run pipelines on this node. Pipelines should be enabled on only one
+    /// node, otherwise messages are written multiple times.
+    pub enable: bool,
+    /// Interval to load pipelines from the metadata.
+    #[serde(with = "humantime_serde")]
+    pub sync_interval: Duration,
+}
+
+impl Default for PipelineOptions {
+    fn default() -> Self {
+        Self {
+            enable: false,
+            sync_interval: Duration::from_secs(10),
+        }
+    }
+}
+
+/// A validated pipeline.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Pipeline {
+    key: PipelineKey,
+    source: KafkaSource,
+    format: MessageFormat,
+    /// Table to write JSON messages.
+    sink_table: Option<String>,
+    transform: Transform,
+    /// Max number of rows buffered by a partition before writing them.
+    batch_size: usize,
+    /// Interval to write buffered rows.
+    flush_interval: Duration,
+}
+
+impl Pipeline {
+    /// Validates the definition of the pipeline.
+    pub fn try_new(key: PipelineKey, value: &PipelineValue) -> Result<Self> {
+        let pipeline = key.pipeline.as_str();
+        ensure!(
+            value.source_type == KAFKA_SOURCE,
+            InvalidPipelineSnafu {
+                pipeline,
+                reason: format!("unsupported source {}", value.source_type),
+            }
+        );
+        let source = KafkaSource::try_new(pipeline, &value.source_options)?;
+        let format = match value.source_options.get(KafkaSource::FORMAT_KEY) {
+            None => MessageFormat::default(),
+            Some(format) => MessageFormat::parse(format).with_context(|| InvalidPipelineSnafu {
+                pipeline,
+                reason: format!("invalid format {format}, expect json, line_protocol or protobuf"),
+            })?,
+        };
+        ensure!(
+            (format == MessageFormat::Json) == value.sink_table.is_some(),
+            InvalidPipelineSnafu {
+                pipeline,
+                reason:
+                    "json messages require a sink table, other formats name tables by themselves",
+            }
+        );
+
+        let options = &value.options;
+        if let Some(key) = options.keys().find(|key| {
+            ![
+                TAGS_KEY,
+                TIMESTAMP_KEY_KEY,
+                RENAME_KEY,
+                EXCLUDE_KEY,
+                BATCH_SIZE_KEY,
+                FLUSH_INTERVAL_KEY,
+            ]
+            .contains(&key.as_str())
+        }) {
+            return InvalidPipelineSnafu {
+                pipeline,
+                reason: format!("unknown option {key}"),
+            }
+            .fail();
+        }
+
+        let mut transform = Transform::default();
+        if let Some(tags) = options.get(TAGS_KEY) {
+            transform.tags = split_list(tags);
+        }
+        if let Some(timestamp_key) = options.get(TIMESTAMP_KEY_KEY) {
+            transform.timestamp_key = timestamp_key.clone();
+        }
+        if let Some(exclude) = options.get(EXCLUDE_KEY) {
+            transform.exclude = split_list(exclude);
+        }
+        if let Some(rename) = options.get(RENAME_KEY) {
+            transform.rename = split_list(rename)
+                .into_iter()
+                .map(|pair| {
+                    pair.split_once(':')
+                        .map(|(from, to)| (from.to_string(), to.to_string()))
+                        .with_context(|| InvalidPipelineSnafu {
+                            pipeline,
+                            reason: format!("invalid rename {pair}, expect from:to"),
+                        })
+                })
+                .collect::<Result<_>>()?;
+        }
+        ensure!(
+            format == MessageFormat::Json || transform == Transform::default(),
+            InvalidPipelineSnafu {
+                pipeline,
+                reason: "transforms are only supported by json messages",
+            }
+        );
+
+        let batch_size = options
+            .get(BATCH_SIZE_KEY)
+            .map(|s| s.parse::<usize>().ok().filter(|size| *size > 0))
+            .unwrap_or(Some(1000))
+            .with_context(|| InvalidPipelineSnafu {
+                pipeline,
+                reason: format!("{BATCH_SIZE_KEY} should be a positive integer"),
+            })?;
+        let flush_interval = options
+            .get(FLUSH_INTERVAL_KEY)
+            .map(|s| {
+                s.parse::<humantime::Duration>()
+                    .ok()
+                    .map(Duration::from)
+                    .filter(|interval| !interval.is_zero())
+            })
+            .unwrap_or(Some(Duration::from_secs(1)))
+            .with_context(|| InvalidPipelineSnafu {
+                pipeline,
+                reason: format!("{FLUSH_INTERVAL_KEY} should be a positive duration"),
+            })?;
+
+        Ok(Self {
+            key,
+            source,
+            format,
+            sink_table: value.sink_table.clone(),
+            transform,
+            batch_size,
+            flush_interval,
+        })
+    }
+
+    fn name(&self) -> String {
+        format!(
+            "{}.{}.{}",
+            self.key.catalog, self.key.schema, self.key.pipeline
+        )
+    }
+
+    /// Runs the pipeline until it's aborted, retrying on errors.
+    async fn run(self, manager: PipelineManagerRef, handler: PipelineHandlerRef) {
+        loop {
+            match kafka::consume(&self, &manager, &handler).await {
+                Ok(()) => info!("Pipeline {} reaches the end of the source", self.name()),
+                Err(e) => error!(e; "Failed to run pipeline {}", self.name()),
+            }
+            tokio::time::sleep(RETRY_INTERVAL).await;
+        }
+    }
+}
+
+/// Splits a comma separated list.
+fn split_list(s: &str) -> Vec<String> {
+    s.split(',')
+        .map(|item| item.trim())
+        .filter(|item| !item.is_empty())
+        .map(|item| item.to_string())
+        .collect()
+}
+
+/// Runs pipelines stored in the metadata.
+#[derive(Clone)]
+pub struct PipelineRunner {
+    options: PipelineOptions,
+    manager: PipelineManagerRef,
+    handler: PipelineHandlerRef,
+}
+
+impl PipelineRunner {
+    /// Returns `None` if pipelines are not enabled.
+    pub fn new(
+        options: &PipelineOptions,
+        kv_backend: KvBackendRef,
+        handler: PipelineHandlerRef,
+    ) -> Option<Self> {
+        if !options.enable {
+            return None;
+        }
+
+        Some(Self {
+            options: options.clone(),
+            manager: Arc::new(PipelineManager::new(kv_backend)),
+            handler,
+        })
+    }
+
+    /// Starts a background task that periodically loads pipelines from the metadata,
+    /// starts new pipelines and stops dropped or changed pipelines.
+    pub fn start(&self) {
+        let mut interval = tokio::time::interval(self.options.sync_interval);
+        let manager = self.manager.clone();
+        let handler = self.handler.clone();
+        let _handle = common_runtime::spawn_bg(async move {
+            info!("Start pipeline runner");
+            let mut running = HashMap::new();
+            loop {
+                let _ = interval.tick().await;
+                if let Err(e) = sync_pipelines(&manager, &handler, &mut running).await {
+                    error!(e; "Failed to sync pipelines");
+                }
+            }
+        });
+    }
+}
+
+struct RunningPipeline {
+    value: PipelineValue,
+    /// `None` if the pipeline is invalid.
+    handle: Option<JoinHandle<()>>,
+}
+
+async fn sync_pipelines(
+    manager: &PipelineManagerRef,
+    handler: &PipelineHandlerRef,
+    running: &mut HashMap<PipelineKey, RunningPipeline>,
+) -> Result<()> {
+    let pipelines = manager
+        .pipelines()
+        .try_collect::<HashMap<_, _>>()
+        .await
+        .context(PipelineMetadataSnafu)?;
+
+    running.retain(|key, running| {
+        let keep = pipelines.get(key) == Some(&running.value);
+        if !keep {
+            info!("Stop pipeline {}", key);
+            if let Some(handle) = &running.handle {
+                handle.abort();
+            }
+        }
+        keep
+    });
+
+    for (key, value) in pipelines {
+        if running.contains_key(&key) {
+            continue;
+        }
+        let handle = match Pipeline::try_new(key.clone(), &value) {
+            Ok(pipeline) => {
+                info!("Start pipeline {}", key);
+                Some(common_runtime::spawn_bg(
+                    pipeline.run(manager.clone(), handler.clone()),
+                ))
+            }
+            Err(e) => {
+                error!(e; "Invalid pipeline {}", key);
+                None
+            }
+        };
+        let _ = running.insert(key, RunningPipeline { value, handle });
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn new_value(format: &str, sink_table: Option<&str>) -> PipelineValue {
+        PipelineValue {
+            source_type: KAFKA_SOURCE.to_string(),
+            source_options: HashMap::from([
+                (
+                    "broker_endpoints".to_string(),
+                    "127.0.0.1:9092, 127.0.0.1:9093".to_string(),
+                ),
+                ("topic".to_string(), "events".to_string()),
+                ("format".to_string(), format.to_string()),
+            ]),
+            sink_table: sink_table.map(|s| s.to_string()),
+            options: HashMap::new(),
+        }
+    }
+
+    fn new_key() -> PipelineKey {
+        PipelineKey::new("greptime", "public", "p")
+    }
+
+    #[test]
+    fn test_create_pipeline() {
+        let mut value = new_value("json", Some("events"));
+        value.options = HashMap::from([
+            ("tags".to_string(), "host,region".to_string()),
+            ("rename".to_string(), "hostname:host".to_string()),
+            ("batch_size".to_string(), "10".to_string()),
+            ("flush_interval".to_string(), "5s".to_string()),
+        ]);
+        let pipeline = Pipeline::try_new(new_key(), &value).unwrap();
+        assert_eq!(
+            vec!["127.0.0.1:9092".to_string(), "127.0.0.1:9093".to_string()],
+            pipeline.source.broker_endpoints
+        );
+        assert_eq!(MessageFormat::Json, pipeline.format);
+        assert_eq!(vec!["host", "region"], pipeline.transform.tags);
+        assert_eq!(
+            vec![("hostname".to_string(), "host".to_string())],
+            pipeline.transform.rename
+        );
+        assert_eq!("ts", pipeline.transform.timestamp_key);
+        assert_eq!(10, pipeline.batch_size);
+        assert_eq!(Duration::from_secs(5), pipeline.flush_interval);
+
+        let pipeline = Pipeline::try_new(new_key(), &new_value("protobuf", None)).unwrap();
+        assert_eq!(MessageFormat::Protobuf, pipeline.format);
+        assert_eq!(1000, pipeline.batch_size);
+    }
+
+    #[test]
+    fn test_create_invalid_pipeline() {
+        let mut invalid_values = vec![
+            new_value("csv", Some("events")),
+            // Json requires a sink table.
+            new_value("json", None),
+            new_value("line_protocol", Some("events")),
+        ];
+
+        let mut value = new_value("json", Some("events"));
+        value.source_type = "pulsar".to_string();
+        invalid_values.push(value);
+
+        let mut value = new_value("json", Some("events"));
+        let _ = value.source_options.remove("topic");
+        invalid_values.push(value);
+
+        let mut value = new_value("json", Some("events"));
+        let _ = value
+            .source_options
+            .insert("start_offset".to_string(), "middle".to_string());
+        invalid_values.push(value);
+
+        for (key, option) in [
+            ("unknown", "1"),
+            ("rename", "a"),
+            ("batch_size", "0"),
+            ("flush_interval", "soon"),
+        ] {
+            let mut value = new_value("json", Some("events"));
+            value.options = HashMap::from([(key.to_string(), option.to_string())]);
+            invalid_values.push(value);
+        }
+
+        let mut value = new_value("line_protocol", None);
+        value.options = HashMap::from([("tags".to_string(), "host".to_string())]);
+        invalid_values.push(value);
+
+        for value in invalid_values {
+            assert!(
+                Pipeline::try_new(new_key(), &value).is_err(),
+                "{value:?} should be invalid"
+            );
+        }
+    }
+}
@@ -0,0 +1,249 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Kafka source of pipelines.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use common_meta::key::pipeline::PipelineManagerRef;
+use common_telemetry::logging::warn;
+use futures::StreamExt;
+use rskafka::client::consumer::{StartOffset, StreamConsumerBuilder};
+use rskafka::client::partition::UnknownTopicHandling;
+use rskafka::client::{Client, ClientBuilder};
+use session::context::{QueryContext, QueryContextRef};
+use snafu::{OptionExt, ResultExt};
+use tokio::time::MissedTickBehavior;
+
+use crate::error::{ConsumeKafkaSnafu, InvalidPipelineSnafu, PipelineMetadataSnafu, Result};
+use crate::metrics::{METRIC_PIPELINE_FLUSH_ELAPSED, METRIC_PIPELINE_MESSAGES};
+use crate::pipeline::transform::{write_message, Batch};
+use crate::pipeline::Pipeline;
+use crate::query_handler::PipelineHandlerRef;
+
+const BROKER_ENDPOINTS_KEY: &str = "broker_endpoints";
+const TOPIC_KEY: &str = "topic";
+const START_OFFSET_KEY: &str = "start_offset";
+/// Max time in milliseconds to wait for new records in a fetch request.
+const MAX_WAIT_MS: i32 = 500;
+
+/// Where to start consuming partitions without consumed offsets.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum StartFrom {
+    #[default]
+    Earliest,
+    Latest,
+}
+
+/// Options of a Kafka source.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct KafkaSource {
+    pub(crate) broker_endpoints: Vec<String>,
+    pub(crate) topic: String,
+    pub(crate) start_from: StartFrom,
+}
+
+impl KafkaSource {
+    /// Key of the option of the message format.
+    pub(crate) const FORMAT_KEY: &'static str = "format";
+
+    pub(crate) fn try_new(pipeline: &str, options: &HashMap<String, String>) -> Result<Self> {
+        let invalid = |reason: String| InvalidPipelineSnafu { pipeline, reason }.build();
+
+        if let Some(key) = options.keys().find(|key| {
+            ![
+                BROKER_ENDPOINTS_KEY,
+                TOPIC_KEY,
+                START_OFFSET_KEY,
+                Self::FORMAT_KEY,
+            ]
+            .contains(&key.as_str())
+        }) {
+            return Err(invalid(format!("unknown kafka option {key}")));
+        }
+
+        let broker_endpoints = options
+            .get(BROKER_ENDPOINTS_KEY)
+            .map(|s| super::split_list(s))
+            .filter(|endpoints| !endpoints.is_empty())
+            .with_context(|| InvalidPipelineSnafu {
+                pipeline,
+                reason: format!("kafka option {BROKER_ENDPOINTS_KEY} is required"),
+            })?;
+        let topic = options
+            .get(TOPIC_KEY)
+            .filter(|topic| !topic.is_empty())
+            .with_context(|| InvalidPipelineSnafu {
+                pipeline,
+                reason: format!("kafka option {TOPIC_KEY} is required"),
+            })?
+            .clone();
+        let start_from = match options.get(START_OFFSET_KEY).map(|s| s.as_str()) {
+            None | Some("earliest") => StartFrom::Earliest,
+            Some("latest") => StartFrom::Latest,
+            Some(other) => {
+                return Err(invalid(format!(
+                    "invalid {START_OFFSET_KEY} {other}, expect earliest or latest"
+                )))
+            }
+        };
+
+        Ok(Self {
+            broker_endpoints,
+            topic,
+            start_from,
+        })
+    }
+}
+
+/// Consumes all partitions of the topic and writes rows until an error occurs.
+pub(crate) async fn consume(
+    pipeline: &Pipeline,
+    manager: &PipelineManagerRef,
+    handler: &PipelineHandlerRef,
+) -> Result<()> {
+    let source = &pipeline.source;
+    let client = ClientBuilder::new(source.broker_endpoints.clone())
+        .build()
+        .await
+        .context(ConsumeKafkaSnafu {
+            topic: &source.topic,
+        })?;
+    let partitions = client
+        .list_topics()
+        .await
+        .context(ConsumeKafkaSnafu {
+            topic: &source.topic,
+        })?
+        .into_iter()
+        .find(|topic| topic.name == source.topic)
+        .with_context(|| InvalidPipelineSnafu {
+            pipeline: pipeline.name(),
+            reason: format!("kafka topic {} not found", source.topic),
+        })?
+        .partitions;
+    let offsets = manager
+        .offsets(&pipeline.key)
+        .await
+        .context(PipelineMetadataSnafu)?;
+
+    let ctx = QueryContext::with(&pipeline.key.catalog, &pipeline.key.schema);
+    let tasks = partitions.into_iter().map(|partition| {
+        consume_partition(
+            &client,
+            pipeline,
+            partition,
+            offsets.get(&partition).copied(),
+            manager,
+            handler,
+            ctx.clone(),
+        )
+    });
+    let _ = futures::future::try_join_all(tasks).await?;
+
+    Ok(())
+}
+
+async fn consume_partition(
+    client: &Client,
+    pipeline: &Pipeline,
+    partition: i32,
+    offset: Option<i64>,
+    manager: &PipelineManagerRef,
+    handler: &PipelineHandlerRef,
+    ctx: QueryContextRef,
+) -> Result<()> {
+    let topic = &pipeline.source.topic;
+    let partition_client = client
+        .partition_client(topic.as_str(), partition, UnknownTopicHandling::Retry)
+        .await
+        .context(ConsumeKafkaSnafu { topic })?;
+    let start_offset = match (offset, pipeline.source.start_from) {
+        (Some(offset), _) => StartOffset::At(offset),
+        (None, StartFrom::Earliest) => StartOffset::Earliest,
+        (None, StartFrom::Latest) => StartOffset::Latest,
+    };
+    let mut stream = StreamConsumerBuilder::new(Arc::new(partition_client), start_offset)
+        .with_max_wait_ms(MAX_WAIT_MS)
+        .build();
+
+    let pipeline_name = pipeline.name();
+    let mut batch = Batch::default();
+    let mut interval = tokio::time::interval(pipeline.flush_interval);
+    interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    loop {
+        tokio::select! {
+            record = stream.next() => {
+                let Some(record) = record else {
+                    break;
+                };
+                let (record, _high_watermark) = record.context(ConsumeKafkaSnafu { topic })?;
+                if let Some(payload) = &record.record.value {
+                    match write_message(
+                        pipeline.format,
+                        pipeline.sink_table.as_deref(),
+                        &pipeline.transform,
+                        payload,
+                        &mut batch,
+                    ) {
+                        Ok(_) => METRIC_PIPELINE_MESSAGES
+                            .with_label_values(&[&pipeline_name, "ok"])
+                            .inc(),
+                        Err(e) => {
+                            METRIC_PIPELINE_MESSAGES
+                                .with_label_values(&[&pipeline_name, "invalid"])
+                                .inc();
+                            warn!(e; "Skip invalid message of pipeline {}, partition: {}, offset: {}", pipeline_name, partition, record.offset);
+                        }
+                    }
+                }
+                batch.next_offset = Some(record.offset + 1);
+                if batch.rows >= pipeline.batch_size {
+                    flush(pipeline, partition, &mut batch, manager, handler, &ctx).await?;
+                }
+            }
+            _ = interval.tick() => {
+                flush(pipeline, partition, &mut batch, manager, handler, &ctx).await?;
+            }
+        }
+    }
+
+    flush(pipeline, partition, &mut batch, manager, handler, &ctx).await
+}
+
+/// Writes rows in the batch and checkpoints the offset of the next message to consume.
+async fn flush(
+    pipeline: &Pipeline,
+    partition: i32,
+    batch: &mut Batch,
+    manager: &PipelineManagerRef,
+    handler: &PipelineHandlerRef,
+    ctx: &QueryContextRef,
+) -> Result<()> {
+    let Some(next_offset) = batch.next_offset else {
+        return Ok(());
+    };
+
+    let _timer = METRIC_PIPELINE_FLUSH_ELAPSED.start_timer();
+    let has_rows = batch.rows > 0;
+    let requests = batch.take();
+    if has_rows {
+        let _ = handler.insert(requests, ctx.clone()).await?;
+    }
+    manager
+        .save_offset(&pipeline.key, partition, next_offset)
+        .await
+        .context(PipelineMetadataSnafu)
+}
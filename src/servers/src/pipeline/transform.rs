@@ -0,0 +1,331 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Decodes and transforms pipeline messages to rows.
+
+use api::v1::{RowInsertRequest, RowInsertRequests};
+use prost::Message;
+use snafu::{OptionExt, ResultExt};
+
+use crate::error::{
+    DecodePipelineProtobufSnafu, InvalidPipelineMessageSnafu, PipelineJsonMessageSnafu, Result,
+};
+use crate::influxdb;
+use crate::row_writer::{self, MultiTableData};
+
+/// Format of messages consumed by pipelines.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum MessageFormat {
+    /// A JSON object or an array of JSON objects, each object is a row of the sink table.
+    #[default]
+    Json,
+    /// InfluxDB line protocol, measurements are table names.
+    LineProtocol,
+    /// Protobuf encoded `RowInsertRequests`.
+    Protobuf,
+}
+
+impl MessageFormat {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "json" => Some(MessageFormat::Json),
+            "line_protocol" => Some(MessageFormat::LineProtocol),
+            "protobuf" => Some(MessageFormat::Protobuf),
+            _ => None,
+        }
+    }
+}
+
+/// Transform applied to JSON objects before writing them as rows.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Transform {
+    /// Keys to drop.
+    pub exclude: Vec<String>,
+    /// Keys to rename, applied after dropping keys.
+    pub rename: Vec<(String, String)>,
+    /// Keys written as tags, after renaming.
+    pub tags: Vec<String>,
+    /// Key of the timestamp in milliseconds, after renaming. The time the message
+    /// is decoded is used if the key is absent.
+    pub timestamp_key: String,
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Self {
+            exclude: Vec::new(),
+            rename: Vec::new(),
+            tags: Vec::new(),
+            timestamp_key: "ts".to_string(),
+        }
+    }
+}
+
+impl Transform {
+    fn apply(&self, object: &mut serde_json::Map<String, serde_json::Value>) {
+        for key in &self.exclude {
+            let _ = object.remove(key);
+        }
+        for (from, to) in &self.rename {
+            if let Some(value) = object.remove(from) {
+                let _ = object.insert(to.clone(), value);
+            }
+        }
+    }
+}
+
+/// Rows decoded from messages but not written yet.
+#[derive(Default)]
+pub(crate) struct Batch {
+    data: MultiTableData,
+    requests: Vec<RowInsertRequest>,
+    /// Number of rows in the batch.
+    pub(crate) rows: usize,
+    /// Offset of the next message to consume, `None` if no message is consumed since the
+    /// batch is taken.
+    pub(crate) next_offset: Option<i64>,
+}
+
+impl Batch {
+    /// Takes rows in the batch and resets the batch.
+    pub(crate) fn take(&mut self) -> RowInsertRequests {
+        let batch = std::mem::take(self);
+        let (mut requests, _) = batch.data.into_row_insert_requests();
+        requests.inserts.extend(batch.requests);
+        requests
+    }
+}
+
+/// Decodes the `payload` and writes rows into the `batch`.
+///
+/// Returns the number of rows written.
+pub(crate) fn write_message(
+    format: MessageFormat,
+    sink_table: Option<&str>,
+    transform: &Transform,
+    payload: &[u8],
+    batch: &mut Batch,
+) -> Result<usize> {
+    let rows = match format {
+        MessageFormat::Json => {
+            // Safety: the sink table of JSON pipelines is checked on creation.
+            write_json(sink_table.unwrap(), transform, payload, &mut batch.data)?
+        }
+        MessageFormat::LineProtocol => {
+            let lines = std::str::from_utf8(payload)
+                .ok()
+                .context(InvalidPipelineMessageSnafu {
+                    reason: "invalid UTF-8 lines",
+                })?;
+            influxdb::write_lines(lines, None, &mut batch.data)?
+        }
+        MessageFormat::Protobuf => {
+            let requests =
+                RowInsertRequests::decode(payload).context(DecodePipelineProtobufSnafu)?;
+            let rows = requests
+                .inserts
+                .iter()
+                .map(|r| r.rows.as_ref().map(|rows| rows.rows.len()).unwrap_or(0))
+                .sum();
+            batch.requests.extend(requests.inserts);
+            rows
+        }
+    };
+    batch.rows += rows;
+    Ok(rows)
+}
+
+fn write_json(
+    table_name: &str,
+    transform: &Transform,
+    payload: &[u8],
+    data: &mut MultiTableData,
+) -> Result<usize> {
+    let value: serde_json::Value =
+        serde_json::from_slice(payload).context(PipelineJsonMessageSnafu)?;
+    let objects = match value {
+        serde_json::Value::Array(values) => values,
+        value => vec![value],
+    };
+
+    let mut rows = 0;
+    for object in objects {
+        let serde_json::Value::Object(mut object) = object else {
+            return InvalidPipelineMessageSnafu {
+                reason: "expect JSON objects",
+            }
+            .fail();
+        };
+        transform.apply(&mut object);
+        let ts = object
+            .get(&transform.timestamp_key)
+            .map(|value| {
+                value.as_i64().context(InvalidPipelineMessageSnafu {
+                    reason: format!("timestamp {value} is not an integer"),
+                })
+            })
+            .transpose()?;
+        let table_data = data.get_or_default_table_data(table_name, object.len(), 0);
+        row_writer::write_json_object(
+            table_data,
+            &object,
+            &transform.tags,
+            &transform.timestamp_key,
+            ts,
+        )?;
+        rows += 1;
+    }
+
+    Ok(rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use api::v1::value::ValueData;
+    use api::v1::{ColumnDataType, ColumnSchema, Row, Rows, SemanticType, Value};
+
+    use super::*;
+
+    #[test]
+    fn test_write_json_message() {
+        let transform = Transform {
+            exclude: vec!["debug".to_string()],
+            rename: vec![("hostname".to_string(), "host".to_string())],
+            tags: vec!["host".to_string()],
+            timestamp_key: "time".to_string(),
+        };
+        let mut batch = Batch::default();
+        let payload = br#"[
+            {"hostname": "h1", "cpu": 0.5, "debug": "x", "time": 1000},
+            {"hostname": "h2", "cpu": 0.7, "time": 2000}
+        ]"#;
+        assert_eq!(
+            2,
+            write_message(
+                MessageFormat::Json,
+                Some("metrics"),
+                &transform,
+                payload,
+                &mut batch
+            )
+            .unwrap()
+        );
+        assert_eq!(2, batch.rows);
+
+        let mut requests = batch.take();
+        assert_eq!(0, batch.rows);
+        assert_eq!(1, requests.inserts.len());
+        let request = requests.inserts.pop().unwrap();
+        assert_eq!("metrics", request.table_name);
+        let rows = request.rows.unwrap();
+        assert_eq!(2, rows.rows.len());
+        let names = rows
+            .schema
+            .iter()
+            .map(|c| (c.column_name.as_str(), c.semantic_type))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec![
+                ("host", SemanticType::Tag as i32),
+                ("cpu", SemanticType::Field as i32),
+                ("time", SemanticType::Timestamp as i32)
+            ],
+            names
+        );
+    }
+
+    #[test]
+    fn test_write_invalid_message() {
+        let transform = Transform::default();
+        let mut batch = Batch::default();
+        for payload in [&b"not json"[..], b"[1]", br#"{"ts": "now"}"#] {
+            assert!(write_message(
+                MessageFormat::Json,
+                Some("t"),
+                &transform,
+                payload,
+                &mut batch
+            )
+            .is_err());
+        }
+        assert!(write_message(
+            MessageFormat::Protobuf,
+            None,
+            &transform,
+            b"\xff\xff",
+            &mut batch
+        )
+        .is_err());
+        assert_eq!(0, batch.rows);
+    }
+
+    #[test]
+    fn test_write_line_protocol_and_protobuf_messages() {
+        let transform = Transform::default();
+        let mut batch = Batch::default();
+        let payload = b"monitor,host=h1 cpu=1.0 1663840496100023100\nmonitor,host=h2 cpu=2.0";
+        assert_eq!(
+            2,
+            write_message(
+                MessageFormat::LineProtocol,
+                None,
+                &transform,
+                payload,
+                &mut batch
+            )
+            .unwrap()
+        );
+
+        let requests = RowInsertRequests {
+            inserts: vec![RowInsertRequest {
+                table_name: "events".to_string(),
+                rows: Some(Rows {
+                    schema: vec![ColumnSchema {
+                        column_name: "ts".to_string(),
+                        datatype: ColumnDataType::TimestampMillisecond as i32,
+                        semantic_type: SemanticType::Timestamp as i32,
+                        ..Default::default()
+                    }],
+                    rows: vec![Row {
+                        values: vec![Value {
+                            value_data: Some(ValueData::TimestampMillisecondValue(1)),
+                        }],
+                    }],
+                }),
+                ..Default::default()
+            }],
+        };
+        assert_eq!(
+            1,
+            write_message(
+                MessageFormat::Protobuf,
+                None,
+                &transform,
+                &requests.encode_to_vec(),
+                &mut batch
+            )
+            .unwrap()
+        );
+        assert_eq!(3, batch.rows);
+
+        let mut tables = batch
+            .take()
+            .inserts
+            .into_iter()
+            .map(|r| r.table_name)
+            .collect::<Vec<_>>();
+        tables.sort();
+        assert_eq!(vec!["events", "monitor"], tables);
+    }
+}
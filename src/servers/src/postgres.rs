@@ -39,6 +39,7 @@ use session::Session;
 
 use self::auth_handler::PgLoginVerifier;
 use self::handler::DefaultQueryParser;
+use crate::connection::ConnectionActivity;
 use crate::query_handler::sql::ServerSqlQueryHandlerRef;
 use crate::SqlPlan;
 
@@ -78,6 +79,7 @@ pub struct PostgresServerHandler {
     session: Arc<Session>,
     portal_store: Arc<MemPortalStore<SqlPlan>>,
     query_parser: Arc<DefaultQueryParser>,
+    activity: Arc<ConnectionActivity>,
 }
 
 #[derive(Builder)]
@@ -90,7 +92,11 @@ pub(crate) struct MakePostgresServerHandler {
 }
 
 impl MakePostgresServerHandler {
-    fn make(&self, addr: Option<SocketAddr>) -> PostgresServerHandler {
+    fn make(
+        &self,
+        addr: Option<SocketAddr>,
+        activity: Arc<ConnectionActivity>,
+    ) -> PostgresServerHandler {
         let session = Arc::new(Session::new(addr, Channel::Postgres));
         PostgresServerHandler {
             query_handler: self.query_handler.clone(),
@@ -101,6 +107,7 @@ impl MakePostgresServerHandler {
             session: session.clone(),
             portal_store: Arc::new(MemPortalStore::new()),
             query_parser: Arc::new(DefaultQueryParser::new(self.query_handler.clone(), session)),
+            activity,
         }
     }
 }
@@ -17,8 +17,8 @@ use std::sync::Exclusive;
 
 use ::auth::{userinfo_by_name, Identity, Password, UserInfoRef, UserProviderRef};
 use async_trait::async_trait;
-use common_catalog::parse_catalog_and_schema_from_db_string;
 use common_error::ext::ErrorExt;
+use common_error::status_code::StatusCode;
 use futures::{Sink, SinkExt};
 use pgwire::api::auth::StartupHandler;
 use pgwire::api::{auth, ClientInfo, PgWireConnectionState};
@@ -26,6 +26,7 @@ use pgwire::error::{ErrorInfo, PgWireError, PgWireResult};
 use pgwire::messages::response::ErrorResponse;
 use pgwire::messages::startup::Authentication;
 use pgwire::messages::{PgWireBackendMessage, PgWireFrontendMessage};
+use session::database::resolve_database;
 use session::Session;
 use snafu::IntoError;
 
@@ -235,18 +236,13 @@ where
 {
     let db_ref = client.into_inner().metadata().get(super::METADATA_DATABASE);
     if let Some(db) = db_ref {
-        let (catalog, schema) = parse_catalog_and_schema_from_db_string(db);
-        if query_handler
-            .is_valid_schema(catalog, schema)
-            .await
-            .map_err(|e| PgWireError::ApiError(Box::new(e)))?
-        {
-            Ok(DbResolution::Resolved(
-                catalog.to_owned(),
-                schema.to_owned(),
-            ))
-        } else {
-            Ok(DbResolution::NotFound(format!("Database not found: {db}")))
+        // The user is authorized to access the database while authenticating.
+        match resolve_database(query_handler.as_ref(), None, Some(db), None).await {
+            Ok((catalog, schema)) => Ok(DbResolution::Resolved(catalog, schema)),
+            Err(e) if e.status_code() == StatusCode::DatabaseNotFound => {
+                Ok(DbResolution::NotFound(e.output_msg()))
+            }
+            Err(e) => Err(PgWireError::ApiError(Box::new(e))),
         }
     } else {
         Ok(DbResolution::NotFound("Database not specified".to_owned()))
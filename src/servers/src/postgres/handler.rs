@@ -21,6 +21,7 @@ use common_recordbatch::error::Result as RecordBatchResult;
 use common_recordbatch::RecordBatch;
 use datatypes::schema::SchemaRef;
 use futures::{future, stream, Stream, StreamExt};
+use once_cell::sync::Lazy;
 use pgwire::api::portal::{Format, Portal};
 use pgwire::api::query::{ExtendedQueryHandler, SimpleQueryHandler, StatementOrPortal};
 use pgwire::api::results::{DataRowEncoder, DescribeResponse, QueryResponse, Response, Tag};
@@ -29,6 +30,8 @@ use pgwire::api::store::MemPortalStore;
 use pgwire::api::{ClientInfo, Type};
 use pgwire::error::{ErrorInfo, PgWireError, PgWireResult};
 use query::query_engine::DescribeResult;
+use regex::Regex;
+use session::context::{QueryContextRef, SessionVariables};
 use session::Session;
 use sql::dialect::PostgreSqlDialect;
 use sql::parser::ParserContext;
@@ -37,7 +40,12 @@ use super::types::*;
 use super::PostgresServerHandler;
 use crate::error::Result;
 use crate::query_handler::sql::ServerSqlQueryHandlerRef;
-use crate::SqlPlan;
+use crate::{statement_timeout, SqlPlan};
+
+static SET_TIME_ZONE_PATTERN: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)^\s*SET\s+(?:SESSION\s+)?(?:TIME\s+ZONE|TIMEZONE\s*(?:=|TO))\s*'?([^'\s;]+)'?\s*;?\s*$")
+        .unwrap()
+});
 
 #[async_trait]
 impl SimpleQueryHandler for PostgresServerHandler {
@@ -49,17 +57,36 @@ impl SimpleQueryHandler for PostgresServerHandler {
     where
         C: ClientInfo + Unpin + Send + Sync,
     {
+        let _busy = self.activity.busy_guard();
+        if let Some(value) = statement_timeout::match_set_statement_timeout(query) {
+            let timeout = statement_timeout::parse_statement_timeout(value)
+                .map_err(|e| PgWireError::ApiError(Box::new(e)))?;
+            self.session.set_statement_timeout(timeout);
+            return Ok(vec![Response::Execution(Tag::new_for_execution(
+                "SET", None,
+            ))]);
+        }
+
+        if let Some(value) = match_set_time_zone(query) {
+            self.session
+                .set_variable(SessionVariables::TIME_ZONE, value)
+                .map_err(|e| PgWireError::ApiError(Box::new(e)))?;
+            return Ok(vec![Response::Execution(Tag::new_for_execution(
+                "SET", None,
+            ))]);
+        }
+
         let query_ctx = self.session.new_query_context();
         let db = query_ctx.get_db_string();
         let _timer = crate::metrics::METRIC_POSTGRES_QUERY_TIMER
             .with_label_values(&[crate::metrics::METRIC_POSTGRES_SIMPLE_QUERY, db.as_str()])
             .start_timer();
-        let outputs = self.query_handler.do_query(query, query_ctx).await;
+        let outputs = self.query_handler.do_query(query, query_ctx.clone()).await;
 
         let mut results = Vec::with_capacity(outputs.len());
 
         for output in outputs {
-            let resp = output_to_query_response(output, &Format::UnifiedText)?;
+            let resp = output_to_query_response(query_ctx.clone(), output, &Format::UnifiedText)?;
             results.push(resp);
         }
 
@@ -67,7 +94,17 @@ impl SimpleQueryHandler for PostgresServerHandler {
     }
 }
 
+/// Returns the time zone to set if the query is `SET TIME ZONE <value>` or
+/// `SET timezone = <value>`.
+fn match_set_time_zone(query: &str) -> Option<&str> {
+    SET_TIME_ZONE_PATTERN
+        .captures(query)
+        .and_then(|captures| captures.get(1))
+        .map(|value| value.as_str())
+}
+
 fn output_to_query_response<'a>(
+    query_ctx: QueryContextRef,
     output: Result<Output>,
     field_format: &Format,
 ) -> PgWireResult<Response<'a>> {
@@ -78,11 +115,16 @@ fn output_to_query_response<'a>(
         ))),
         Ok(Output::Stream(record_stream)) => {
             let schema = record_stream.schema();
-            recordbatches_to_query_response(record_stream, schema, field_format)
+            recordbatches_to_query_response(query_ctx, record_stream, schema, field_format)
         }
         Ok(Output::RecordBatches(recordbatches)) => {
             let schema = recordbatches.schema();
-            recordbatches_to_query_response(recordbatches.as_stream(), schema, field_format)
+            recordbatches_to_query_response(
+                query_ctx,
+                recordbatches.as_stream(),
+                schema,
+                field_format,
+            )
         }
         Err(e) => Ok(Response::Error(Box::new(ErrorInfo::new(
             "ERROR".to_string(),
@@ -93,6 +135,7 @@ fn output_to_query_response<'a>(
 }
 
 fn recordbatches_to_query_response<'a, S>(
+    query_ctx: QueryContextRef,
     recordbatches_stream: S,
     schema: SchemaRef,
     field_format: &Format,
@@ -105,6 +148,7 @@ where
             .map_err(|e| PgWireError::ApiError(Box::new(e)))?,
     );
     let pg_schema_ref = pg_schema.clone();
+    let time_zone = query_ctx.time_zone();
     let data_row_stream = recordbatches_stream
         .map(|record_batch_result| match record_batch_result {
             Ok(rb) => stream::iter(
@@ -120,7 +164,7 @@ where
             row.and_then(|row| {
                 let mut encoder = DataRowEncoder::new(pg_schema_ref.clone());
                 for value in row.iter() {
-                    encode_value(value, &mut encoder)?;
+                    encode_value(value, time_zone.as_ref(), &mut encoder)?;
                 }
                 encoder.finish()
             })
@@ -211,6 +255,7 @@ impl ExtendedQueryHandler for PostgresServerHandler {
     where
         C: ClientInfo + Unpin + Send + Sync,
     {
+        let _busy = self.activity.busy_guard();
         let query_ctx = self.session.new_query_context();
         let db = query_ctx.get_db_string();
         let _timer = crate::metrics::METRIC_POSTGRES_QUERY_TIMER
@@ -223,20 +268,26 @@ impl ExtendedQueryHandler for PostgresServerHandler {
             let plan = plan
                 .replace_params_with_values(parameters_to_scalar_values(plan, portal)?.as_ref())
                 .map_err(|e| PgWireError::ApiError(Box::new(e)))?;
-            self.query_handler.do_exec_plan(plan, query_ctx).await
+            self.query_handler
+                .do_exec_plan(plan, query_ctx.clone())
+                .await
         } else {
             // manually replace variables in prepared statement when no
             // logical_plan is generated. This happens when logical plan is not
             // supported for certain statements.
+            // Replaces from the last parameter, so `$1` doesn't replace the prefix of `$10`.
             let mut sql = sql_plan.query.clone();
-            for i in 0..portal.parameter_len() {
+            for i in (0..portal.parameter_len()).rev() {
                 sql = sql.replace(&format!("${}", i + 1), &parameter_to_string(portal, i)?);
             }
 
-            self.query_handler.do_query(&sql, query_ctx).await.remove(0)
+            self.query_handler
+                .do_query(&sql, query_ctx.clone())
+                .await
+                .remove(0)
         };
 
-        output_to_query_response(output, portal.result_column_format())
+        output_to_query_response(query_ctx, output, portal.result_column_format())
     }
 
     async fn do_describe<C>(
@@ -260,7 +311,10 @@ impl ExtendedQueryHandler for PostgresServerHandler {
 
                     (Some(types), sql_plan, &Format::UnifiedBinary)
                 } else {
-                    let param_types = Some(stmt.parameter_types().clone());
+                    let param_types = Some(unplanned_param_types(
+                        stmt.parameter_types(),
+                        &sql_plan.query,
+                    ));
                     (param_types, sql_plan, &Format::UnifiedBinary)
                 }
             }
@@ -280,3 +334,22 @@ impl ExtendedQueryHandler for PostgresServerHandler {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_match_set_time_zone() {
+        assert_eq!(Some("UTC"), match_set_time_zone("SET TIME ZONE 'UTC'"));
+        assert_eq!(
+            Some("Asia/Shanghai"),
+            match_set_time_zone("set timezone to 'Asia/Shanghai';")
+        );
+        assert_eq!(
+            Some("+08:00"),
+            match_set_time_zone("SET timezone = '+08:00'")
+        );
+        assert!(match_set_time_zone("SET statement_timeout = '30s'").is_none());
+    }
+}
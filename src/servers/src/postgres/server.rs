@@ -20,28 +20,30 @@ use ::auth::UserProviderRef;
 use async_trait::async_trait;
 use common_runtime::Runtime;
 use common_telemetry::logging::error;
-use common_telemetry::{debug, warn};
+use common_telemetry::{debug, info, warn};
 use futures::StreamExt;
 use pgwire::tokio::process_socket;
 use tokio_rustls::TlsAcceptor;
 
 use super::{MakePostgresServerHandler, MakePostgresServerHandlerBuilder};
+use crate::connection::{ConnectionManager, ConnectionOptions};
 use crate::error::Result;
 use crate::query_handler::sql::ServerSqlQueryHandlerRef;
 use crate::server::{AbortableStream, BaseTcpServer, Server};
-use crate::tls::TlsOption;
+use crate::tls::ReloadableTlsServerConfigRef;
 
 pub struct PostgresServer {
     base_server: BaseTcpServer,
     make_handler: Arc<MakePostgresServerHandler>,
-    tls: TlsOption,
+    tls_server_config: ReloadableTlsServerConfigRef,
+    connection: Arc<ConnectionManager>,
 }
 
 impl PostgresServer {
     /// Creates a new Postgres server with provided query_handler and async runtime
     pub fn new(
         query_handler: ServerSqlQueryHandlerRef,
-        tls: TlsOption,
+        tls_server_config: ReloadableTlsServerConfigRef,
         io_runtime: Arc<Runtime>,
         user_provider: Option<UserProviderRef>,
     ) -> PostgresServer {
@@ -49,33 +51,51 @@ impl PostgresServer {
             MakePostgresServerHandlerBuilder::default()
                 .query_handler(query_handler.clone())
                 .user_provider(user_provider.clone())
-                .force_tls(tls.should_force_tls())
+                .force_tls(tls_server_config.get_tls_option().should_force_tls())
                 .build()
                 .unwrap(),
         );
         PostgresServer {
             base_server: BaseTcpServer::create_server("Postgres", io_runtime),
             make_handler,
-            tls,
+            tls_server_config,
+            connection: Arc::new(ConnectionManager::new(
+                "postgres",
+                ConnectionOptions::default(),
+            )),
         }
     }
 
+    /// Limits connections, closes idle connections and enables TCP keepalive of connections.
+    pub fn with_connection_options(mut self, options: ConnectionOptions) -> Self {
+        self.connection = Arc::new(ConnectionManager::new("postgres", options));
+        self
+    }
+
     fn accept(
         &self,
         io_runtime: Arc<Runtime>,
         accepting_stream: AbortableStream,
-        tls_acceptor: Option<Arc<TlsAcceptor>>,
     ) -> impl Future<Output = ()> {
         let handler_maker = self.make_handler.clone();
+        let tls_server_config = self.tls_server_config.clone();
+        let connection = self.connection.clone();
         accepting_stream.for_each(move |tcp_stream| {
             let io_runtime = io_runtime.clone();
-            let tls_acceptor = tls_acceptor.clone();
+            let connection = connection.clone();
+            // Uses the latest TLS config, so reloaded certificates apply to new connections.
+            let tls_acceptor = tls_server_config
+                .get_server_config()
+                .map(|server_config| Arc::new(TlsAcceptor::from(server_config)));
             let handler_maker = handler_maker.clone();
 
             async move {
                 match tcp_stream {
                     Err(error) => error!("Broken pipe: {}", error), // IoError doesn't impl ErrorExt.
                     Ok(io_stream) => {
+                        let Some(permit) = connection.admit(&io_stream) else {
+                            return;
+                        };
                         let addr = match io_stream.peer_addr() {
                             Ok(addr) => {
                                 debug!("PostgreSQL client coming from {}", addr);
@@ -89,16 +109,24 @@ impl PostgresServer {
 
                         let _handle = io_runtime.spawn(async move {
                             crate::metrics::METRIC_POSTGRES_CONNECTIONS.inc();
-                            let pg_handler = Arc::new(handler_maker.make(addr));
-                            let r = process_socket(
+                            let activity = connection.activity();
+                            let pg_handler = Arc::new(handler_maker.make(addr, activity.clone()));
+                            let process = process_socket(
                                 io_stream,
                                 tls_acceptor.clone(),
                                 pg_handler.clone(),
                                 pg_handler.clone(),
                                 pg_handler,
-                            )
-                            .await;
+                            );
+                            let r = tokio::select! {
+                                r = process => r,
+                                _ = connection.wait_idle_timeout(&activity) => {
+                                    info!("Close idle PostgreSQL connection from {:?}", addr);
+                                    Ok(())
+                                }
+                            };
                             crate::metrics::METRIC_POSTGRES_CONNECTIONS.dec();
+                            drop(permit);
                             r
                         });
                     }
@@ -119,14 +147,13 @@ impl Server for PostgresServer {
     async fn start(&self, listening: SocketAddr) -> Result<SocketAddr> {
         let (stream, addr) = self.base_server.bind(listening).await?;
 
-        debug!("Starting PostgreSQL with TLS option: {:?}", self.tls);
-        let tls_acceptor = self
-            .tls
-            .setup()?
-            .map(|server_conf| Arc::new(TlsAcceptor::from(Arc::new(server_conf))));
+        debug!(
+            "Starting PostgreSQL with TLS option: {:?}",
+            self.tls_server_config.get_tls_option()
+        );
 
         let io_runtime = self.base_server.io_runtime();
-        let join_handle = common_runtime::spawn_read(self.accept(io_runtime, stream, tls_acceptor));
+        let join_handle = common_runtime::spawn_read(self.accept(io_runtime, stream));
 
         self.base_server.start_with(join_handle).await?;
         Ok(addr)
@@ -18,7 +18,7 @@ use std::collections::HashMap;
 use std::ops::Deref;
 
 use chrono::{NaiveDate, NaiveDateTime};
-use common_time::Interval;
+use common_time::{Interval, TimeZone};
 use datafusion_common::ScalarValue;
 use datatypes::prelude::{ConcreteDataType, Value};
 use datatypes::schema::Schema;
@@ -50,7 +50,13 @@ pub(super) fn schema_to_pg(origin: &Schema, field_formats: &Format) -> Result<Ve
         .collect::<Result<Vec<FieldInfo>>>()
 }
 
-pub(super) fn encode_value(value: &Value, builder: &mut DataRowEncoder) -> PgWireResult<()> {
+/// Encodes the value to the row, timestamps are rendered in the `time_zone` of the
+/// session, or UTC if it's absent.
+pub(super) fn encode_value(
+    value: &Value,
+    time_zone: Option<&TimeZone>,
+    builder: &mut DataRowEncoder,
+) -> PgWireResult<()> {
     match value {
         Value::Null => builder.encode_field(&None::<&i8>),
         Value::Boolean(v) => builder.encode_field(v),
@@ -76,7 +82,7 @@ pub(super) fn encode_value(value: &Value, builder: &mut DataRowEncoder) -> PgWir
             }
         }
         Value::DateTime(v) => {
-            if let Some(datetime) = v.to_chrono_datetime() {
+            if let Some(datetime) = v.to_chrono_datetime_with_timezone(time_zone.cloned()) {
                 builder.encode_field(&datetime)
             } else {
                 Err(PgWireError::ApiError(Box::new(Error::Internal {
@@ -85,7 +91,7 @@ pub(super) fn encode_value(value: &Value, builder: &mut DataRowEncoder) -> PgWir
             }
         }
         Value::Timestamp(v) => {
-            if let Some(datetime) = v.to_chrono_datetime() {
+            if let Some(datetime) = v.to_chrono_datetime_with_timezone(time_zone.cloned()) {
                 builder.encode_field(&datetime)
             } else {
                 Err(PgWireError::ApiError(Box::new(Error::Internal {
@@ -142,7 +148,6 @@ pub(super) fn type_gt_to_pg(origin: &ConcreteDataType) -> Result<Type> {
     }
 }
 
-#[allow(dead_code)]
 pub(super) fn type_pg_to_gt(origin: &Type) -> Result<ConcreteDataType> {
     // Note that we only support a small amount of pg data types
     match origin {
@@ -151,7 +156,10 @@ pub(super) fn type_pg_to_gt(origin: &Type) -> Result<ConcreteDataType> {
         &Type::INT2 => Ok(ConcreteDataType::int16_datatype()),
         &Type::INT4 => Ok(ConcreteDataType::int32_datatype()),
         &Type::INT8 => Ok(ConcreteDataType::int64_datatype()),
+        &Type::FLOAT4 => Ok(ConcreteDataType::float32_datatype()),
+        &Type::FLOAT8 => Ok(ConcreteDataType::float64_datatype()),
         &Type::VARCHAR | &Type::TEXT => Ok(ConcreteDataType::string_datatype()),
+        &Type::BYTEA => Ok(ConcreteDataType::binary_datatype()),
         &Type::TIMESTAMP => Ok(ConcreteDataType::timestamp_datatype(
             common_time::timestamp::TimeUnit::Millisecond,
         )),
@@ -164,55 +172,92 @@ pub(super) fn type_pg_to_gt(origin: &Type) -> Result<ConcreteDataType> {
     }
 }
 
+/// Returns types of parameters of a statement without a logical plan, parameters not
+/// specified by the client are of unknown types and sent as text.
+pub(super) fn unplanned_param_types(client_types: &[Type], sql: &str) -> Vec<Type> {
+    let mut types = client_types.to_vec();
+    let param_count = placeholder_count(sql);
+    if types.len() < param_count {
+        types.resize(param_count, Type::UNKNOWN);
+    }
+    types
+}
+
+/// Returns the max index of placeholders `$n` in the sql, ignoring quoted strings and
+/// identifiers.
+fn placeholder_count(sql: &str) -> usize {
+    let mut count = 0;
+    let mut quote = None;
+    let mut chars = sql.chars().peekable();
+    while let Some(c) = chars.next() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '\'' | '"') => quote = Some(c),
+            (None, '$') => {
+                let mut n = 0usize;
+                let mut has_digits = false;
+                while let Some(d) = chars.peek().and_then(|d| d.to_digit(10)) {
+                    n = n.saturating_mul(10).saturating_add(d as usize);
+                    has_digits = true;
+                    let _ = chars.next();
+                }
+                if has_digits {
+                    count = count.max(n);
+                }
+            }
+            _ => {}
+        }
+    }
+    count
+}
+
 pub(super) fn parameter_to_string(portal: &Portal<SqlPlan>, idx: usize) -> PgWireResult<String> {
-    // the index is managed from portal's parameters count so it's safe to
-    // unwrap here.
-    let param_type = portal.statement().parameter_types().get(idx).unwrap();
-    match param_type {
-        &Type::VARCHAR | &Type::TEXT => Ok(format!(
-            "'{}'",
-            portal
-                .parameter::<String>(idx, param_type)?
-                .as_deref()
-                .unwrap_or("")
-        )),
-        &Type::BOOL => Ok(portal
+    let param_type = portal
+        .statement()
+        .parameter_types()
+        .get(idx)
+        .unwrap_or(&Type::UNKNOWN);
+    let value = match param_type {
+        // Parameters of unknown types are sent as text.
+        &Type::VARCHAR | &Type::TEXT | &Type::UNKNOWN => portal
+            .parameter::<String>(idx, &Type::TEXT)?
+            .map(|v| format!("'{}'", v.replace('\'', "''"))),
+        &Type::BOOL => portal
             .parameter::<bool>(idx, param_type)?
-            .map(|v| v.to_string())
-            .unwrap_or_else(|| "".to_owned())),
-        &Type::INT4 => Ok(portal
+            .map(|v| v.to_string()),
+        &Type::INT2 => portal
+            .parameter::<i16>(idx, param_type)?
+            .map(|v| v.to_string()),
+        &Type::INT4 => portal
             .parameter::<i32>(idx, param_type)?
-            .map(|v| v.to_string())
-            .unwrap_or_else(|| "".to_owned())),
-        &Type::INT8 => Ok(portal
+            .map(|v| v.to_string()),
+        &Type::INT8 => portal
             .parameter::<i64>(idx, param_type)?
-            .map(|v| v.to_string())
-            .unwrap_or_else(|| "".to_owned())),
-        &Type::FLOAT4 => Ok(portal
+            .map(|v| v.to_string()),
+        &Type::FLOAT4 => portal
             .parameter::<f32>(idx, param_type)?
-            .map(|v| v.to_string())
-            .unwrap_or_else(|| "".to_owned())),
-        &Type::FLOAT8 => Ok(portal
+            .map(|v| v.to_string()),
+        &Type::FLOAT8 => portal
             .parameter::<f64>(idx, param_type)?
-            .map(|v| v.to_string())
-            .unwrap_or_else(|| "".to_owned())),
-        &Type::DATE => Ok(portal
+            .map(|v| v.to_string()),
+        &Type::DATE => portal
             .parameter::<NaiveDate>(idx, param_type)?
-            .map(|v| v.format("%Y-%m-%d").to_string())
-            .unwrap_or_else(|| "".to_owned())),
-        &Type::TIMESTAMP => Ok(portal
+            .map(|v| format!("'{}'", v.format("%Y-%m-%d"))),
+        &Type::TIMESTAMP => portal
             .parameter::<NaiveDateTime>(idx, param_type)?
-            .map(|v| v.format("%Y-%m-%d %H:%M:%S%.6f").to_string())
-            .unwrap_or_else(|| "".to_owned())),
-        &Type::INTERVAL => Ok(portal
+            .map(|v| format!("'{}'", v.format("%Y-%m-%d %H:%M:%S%.6f"))),
+        &Type::INTERVAL => portal
             .parameter::<PgInterval>(idx, param_type)?
-            .map(|v| v.to_string())
-            .unwrap_or_else(|| "".to_owned())),
-        _ => Err(invalid_parameter_error(
-            "unsupported_parameter_type",
-            Some(&param_type.to_string()),
-        )),
-    }
+            .map(|v| v.to_string()),
+        _ => {
+            return Err(invalid_parameter_error(
+                "unsupported_parameter_type",
+                Some(&param_type.to_string()),
+            ))
+        }
+    };
+    Ok(value.unwrap_or_else(|| "NULL".to_owned()))
 }
 
 pub(super) fn invalid_parameter_error(msg: &str, detail: Option<&str>) -> PgWireError {
@@ -264,20 +309,27 @@ pub(super) fn parameters_to_scalar_values(
     }
 
     for idx in 0..param_count {
+        let client_given_type = client_param_types
+            .get(idx)
+            .filter(|client_type| **client_type != Type::UNKNOWN);
         let server_type =
             if let Some(Some(server_infer_type)) = param_types.get(&format!("${}", idx + 1)) {
-                server_infer_type
+                server_infer_type.clone()
+            } else if let Some(client_type) = client_given_type {
+                // The server can't infer the type, e.g. `SELECT $1`, so parses the parameter
+                // by the type specified by the client.
+                type_pg_to_gt(client_type).map_err(|_| {
+                    invalid_parameter_error(
+                        "unknown_parameter_type",
+                        Some(&format!("Found type: {}", client_type)),
+                    )
+                })?
             } else {
-                // at the moment we require type information inferenced by
-                // server so here we return error if the type is unknown from
-                // server-side.
-                //
-                // It might be possible to parse the parameter just using client
-                // specified type, we will implement that if there is a case.
                 return Err(invalid_parameter_error("unknown_parameter_type", None));
             };
+        let server_type = &server_type;
 
-        let client_type = if let Some(client_given_type) = client_param_types.get(idx) {
+        let client_type = if let Some(client_given_type) = client_given_type {
             client_given_type.clone()
         } else {
             type_gt_to_pg(server_type).map_err(|e| PgWireError::ApiError(Box::new(e)))?
@@ -785,7 +837,7 @@ mod test {
         ];
         let mut builder = DataRowEncoder::new(Arc::new(schema));
         for i in values.iter() {
-            encode_value(i, &mut builder).unwrap();
+            encode_value(i, None, &mut builder).unwrap();
         }
 
         let err = encode_value(
@@ -793,6 +845,7 @@ mod test {
                 Some(Box::default()),
                 ConcreteDataType::int16_datatype(),
             )),
+            None,
             &mut builder,
         )
         .unwrap_err();
@@ -805,4 +858,20 @@ mod test {
             }
         }
     }
+
+    #[test]
+    fn test_unplanned_param_types() {
+        assert_eq!(
+            vec![Type::INT8, Type::UNKNOWN, Type::UNKNOWN],
+            unplanned_param_types(
+                &[Type::INT8],
+                "ALTER TABLE t SET a = $1, b = $2, c = '$4' $3"
+            )
+        );
+        assert_eq!(
+            vec![Type::UNKNOWN; 10],
+            unplanned_param_types(&[], "SHOW TABLES LIKE $10 \"$11\" 'it''s $12'")
+        );
+        assert!(unplanned_param_types(&[], "SELECT '$1', $a").is_empty());
+    }
 }
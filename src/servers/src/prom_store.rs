@@ -14,6 +14,9 @@
 
 //! prometheus protocol supportings
 //! handles prometheus remote_write, remote_read logic
+
+pub mod chunk;
+
 use std::cmp::Ordering;
 use std::collections::BTreeMap;
 use std::hash::{Hash, Hasher};
@@ -39,6 +42,34 @@ pub const TIMESTAMP_COLUMN_NAME: &str = "greptime_timestamp";
 pub const FIELD_COLUMN_NAME: &str = "greptime_value";
 pub const METRIC_NAME_LABEL: &str = "__name__";
 
+/// Layout of tables that remote write requests are written to.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum MetricLayout {
+    /// Writes each metric to its own table with a single `greptime_value` field.
+    #[default]
+    SingleField,
+    /// Merges metrics sharing the same labels and timestamp into a single row of
+    /// `table`, using metric names as field columns.
+    MultiField { table: String },
+}
+
+/// Options of remote write requests.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PromWriteOptions {
+    pub layout: MetricLayout,
+    /// Whether to add columns for new labels and metrics to existing tables.
+    pub auto_add_columns: bool,
+}
+
+impl Default for PromWriteOptions {
+    fn default() -> Self {
+        Self {
+            layout: MetricLayout::default(),
+            auto_add_columns: true,
+        }
+    }
+}
+
 /// Metrics for push gateway protocol
 pub struct Metrics {
     pub exposition: MetricsExposition<PrometheusType, PrometheusValue>,
@@ -357,6 +388,84 @@ pub fn to_grpc_row_insert_requests(request: WriteRequest) -> Result<(RowInsertRe
     Ok(multi_table_data.into_row_insert_requests())
 }
 
+/// Converts the write request into insert requests according to the `layout`.
+///
+/// Returns the requests and number of samples in them.
+pub fn to_row_insert_requests(
+    request: WriteRequest,
+    layout: &MetricLayout,
+) -> Result<(RowInsertRequests, usize)> {
+    match layout {
+        MetricLayout::SingleField => to_grpc_row_insert_requests(request),
+        MetricLayout::MultiField { table } => to_multi_field_row_insert_requests(request, table),
+    }
+}
+
+/// Converts the write request into an insert request of the `table_name`, samples
+/// sharing the same labels and timestamp are merged into one row whose field columns
+/// are named by metric names.
+///
+/// Returns the request and number of samples in it.
+pub fn to_multi_field_row_insert_requests(
+    request: WriteRequest,
+    table_name: &str,
+) -> Result<(RowInsertRequests, usize)> {
+    // (labels, timestamp) -> (metric name, value)
+    let mut rows: BTreeMap<(Vec<(&str, &str)>, i64), Vec<(&str, f64)>> = BTreeMap::new();
+    let mut num_samples = 0;
+    for series in &request.timeseries {
+        let mut metric_name = None;
+        let mut labels = Vec::with_capacity(series.labels.len());
+        for label in &series.labels {
+            if label.name == METRIC_NAME_LABEL {
+                metric_name = Some(label.value.as_str());
+            } else {
+                labels.push((label.name.as_str(), label.value.as_str()));
+            }
+        }
+        let metric_name = metric_name.context(error::InvalidPromRemoteRequestSnafu {
+            msg: "missing '__name__' label in time-series",
+        })?;
+        labels.sort_unstable();
+
+        for Sample { value, timestamp } in &series.samples {
+            rows.entry((labels.clone(), *timestamp))
+                .or_default()
+                .push((metric_name, *value));
+            num_samples += 1;
+        }
+    }
+
+    if rows.is_empty() {
+        return Ok((RowInsertRequests::default(), 0));
+    }
+
+    let mut multi_table_data = MultiTableData::new();
+    let table_data = multi_table_data.get_or_default_table_data(table_name, 0, rows.len());
+    for ((labels, timestamp), values) in rows {
+        let mut one_row = table_data.alloc_one_row();
+
+        let kvs = labels
+            .into_iter()
+            .map(|(name, value)| (name.to_string(), value));
+        row_writer::write_tags(table_data, kvs, &mut one_row)?;
+        for (metric_name, value) in values {
+            row_writer::write_f64(table_data, metric_name, value, &mut one_row)?;
+        }
+        row_writer::write_ts_millis(
+            table_data,
+            TIMESTAMP_COLUMN_NAME,
+            Some(timestamp),
+            &mut one_row,
+        )?;
+
+        table_data.add_row(one_row);
+    }
+
+    let (requests, _) = multi_table_data.into_row_insert_requests();
+    Ok((requests, num_samples))
+}
+
 #[inline]
 pub fn snappy_decompress(buf: &[u8]) -> Result<Vec<u8>> {
     let mut decoder = Decoder::new();
@@ -697,6 +806,118 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_write_request_to_multi_field_row_insert_exprs() {
+        let labels = |name: &str, job: &str| {
+            vec![
+                new_label(METRIC_NAME_LABEL.to_string(), name.to_string()),
+                new_label("job".to_string(), job.to_string()),
+            ]
+        };
+        let write_request = WriteRequest {
+            timeseries: vec![
+                TimeSeries {
+                    labels: labels("cpu", "node"),
+                    samples: vec![
+                        Sample {
+                            value: 1.0,
+                            timestamp: 1000,
+                        },
+                        Sample {
+                            value: 2.0,
+                            timestamp: 2000,
+                        },
+                    ],
+                    ..Default::default()
+                },
+                TimeSeries {
+                    labels: labels("mem", "node"),
+                    samples: vec![Sample {
+                        value: 10.0,
+                        timestamp: 1000,
+                    }],
+                    ..Default::default()
+                },
+            ],
+            ..Default::default()
+        };
+
+        let (requests, samples) = to_row_insert_requests(
+            write_request,
+            &MetricLayout::MultiField {
+                table: "node_metrics".to_string(),
+            },
+        )
+        .unwrap();
+        assert_eq!(3, samples);
+        assert_eq!(1, requests.inserts.len());
+        assert_eq!("node_metrics", requests.inserts[0].table_name);
+
+        let rows = requests.inserts[0].rows.as_ref().unwrap();
+        let columns = rows
+            .schema
+            .iter()
+            .map(|c| c.column_name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(vec!["job", "cpu", "mem", "greptime_timestamp"], columns);
+        let f64_value = |v: f64| api::v1::Value {
+            value_data: Some(api::v1::value::ValueData::F64Value(v)),
+        };
+        let job = api::v1::Value {
+            value_data: Some(api::v1::value::ValueData::StringValue("node".to_string())),
+        };
+        let ts = |v: i64| api::v1::Value {
+            value_data: Some(api::v1::value::ValueData::TimestampMillisecondValue(v)),
+        };
+        assert_eq!(
+            vec![
+                Row {
+                    values: vec![job.clone(), f64_value(1.0), f64_value(10.0), ts(1000)],
+                },
+                Row {
+                    values: vec![
+                        job,
+                        f64_value(2.0),
+                        api::v1::Value { value_data: None },
+                        ts(2000)
+                    ],
+                },
+            ],
+            rows.rows
+        );
+    }
+
+    #[test]
+    fn test_write_stale_marker() {
+        // Prometheus marks a series as stale by a special NaN value.
+        let stale_nan = f64::from_bits(0x7ff0000000000002);
+        let write_request = WriteRequest {
+            timeseries: vec![TimeSeries {
+                labels: vec![new_label(
+                    METRIC_NAME_LABEL.to_string(),
+                    "metric1".to_string(),
+                )],
+                samples: vec![Sample {
+                    value: stale_nan,
+                    timestamp: 1000,
+                }],
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        let exprs = to_grpc_row_insert_requests(write_request)
+            .unwrap()
+            .0
+            .inserts;
+        let rows = &exprs[0].rows.as_ref().unwrap().rows;
+        // The marker should be kept as is so the query engine can recognize it.
+        let Some(api::v1::value::ValueData::F64Value(value)) = rows[0].values[0].value_data else {
+            panic!("unexpected value: {:?}", rows[0].values[0]);
+        };
+        assert_eq!(stale_nan.to_bits(), value.to_bits());
+    }
+
     #[test]
     fn test_recordbatches_to_timeseries() {
         let schema = Arc::new(Schema::new(vec![
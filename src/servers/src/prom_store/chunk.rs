@@ -0,0 +1,430 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Encodes samples of remote read results to XOR chunks of the streamed response type.
+//!
+//! Both the chunk encoding and the framing are the same as Prometheus, see
+//! <https://github.com/prometheus/prometheus/blob/main/tsdb/chunkenc/xor.go> and
+//! <https://github.com/prometheus/prometheus/blob/main/storage/remote/chunked.go>.
+
+use std::collections::BTreeMap;
+
+use api::prom_store::remote::chunk::Encoding;
+use api::prom_store::remote::{Chunk, ChunkedReadResponse, ChunkedSeries, TimeSeries};
+use prost::Message;
+
+pub const STREAMED_CONTENT_TYPE: &str =
+    "application/x-streamed-protobuf; proto=prometheus.ChunkedReadResponse";
+
+/// Max number of samples in a chunk, which is the same as Prometheus.
+const MAX_SAMPLES_PER_CHUNK: usize = 120;
+
+/// Merges series with the same labels and encodes their samples to chunks.
+///
+/// Samples of a series are sorted by timestamp as chunks require.
+pub fn to_chunked_series(timeseries: Vec<TimeSeries>) -> Vec<ChunkedSeries> {
+    let mut merged: BTreeMap<Vec<(String, String)>, TimeSeries> = BTreeMap::new();
+    for series in timeseries {
+        let key = series
+            .labels
+            .iter()
+            .map(|label| (label.name.clone(), label.value.clone()))
+            .collect();
+        merged
+            .entry(key)
+            .and_modify(|merged| merged.samples.extend_from_slice(&series.samples))
+            .or_insert(series);
+    }
+
+    merged
+        .into_values()
+        .filter(|series| !series.samples.is_empty())
+        .map(|mut series| {
+            series.samples.sort_by_key(|sample| sample.timestamp);
+            let chunks = series
+                .samples
+                .chunks(MAX_SAMPLES_PER_CHUNK)
+                .map(|samples| {
+                    let mut encoder = XorEncoder::new();
+                    for sample in samples {
+                        encoder.append(sample.timestamp, sample.value);
+                    }
+                    Chunk {
+                        min_time_ms: samples[0].timestamp,
+                        max_time_ms: samples[samples.len() - 1].timestamp,
+                        r#type: Encoding::Xor as i32,
+                        data: encoder.finish(),
+                    }
+                })
+                .collect();
+            ChunkedSeries {
+                labels: series.labels,
+                chunks,
+            }
+        })
+        .collect()
+}
+
+/// Appends a frame of the `response` to `buf`.
+///
+/// A frame is the uvarint size of the message, the big endian CRC32 (Castagnoli)
+/// checksum of the message, and the message.
+pub fn encode_frame(response: &ChunkedReadResponse, buf: &mut Vec<u8>) {
+    let message = response.encode_to_vec();
+    put_uvarint(buf, message.len() as u64);
+    buf.extend_from_slice(&crc32c::crc32c(&message).to_be_bytes());
+    buf.extend_from_slice(&message);
+}
+
+/// Writes bits from the most significant bit of each byte.
+struct BitWriter {
+    buf: Vec<u8>,
+    /// Number of unwritten bits of the last byte.
+    free: u8,
+}
+
+impl BitWriter {
+    fn write_bit(&mut self, bit: bool) {
+        if self.free == 0 {
+            self.buf.push(0);
+            self.free = 8;
+        }
+        if bit {
+            // Safety: a byte is pushed above if the buffer is empty.
+            *self.buf.last_mut().unwrap() |= 1 << (self.free - 1);
+        }
+        self.free -= 1;
+    }
+
+    /// Writes the lowest `nbits` bits of the `value`.
+    fn write_bits(&mut self, value: u64, nbits: u8) {
+        for i in (0..nbits).rev() {
+            self.write_bit((value >> i) & 1 == 1);
+        }
+    }
+
+    fn write_bytes(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.write_bits(*byte as u64, 8);
+        }
+    }
+}
+
+/// Encodes samples by delta-of-delta timestamps and XOR values.
+struct XorEncoder {
+    writer: BitWriter,
+    num_samples: u16,
+    t: i64,
+    v: f64,
+    t_delta: u64,
+    leading: u8,
+    trailing: u8,
+}
+
+impl XorEncoder {
+    fn new() -> Self {
+        Self {
+            // The first 2 bytes are the number of samples.
+            writer: BitWriter {
+                buf: vec![0; 2],
+                free: 0,
+            },
+            num_samples: 0,
+            t: 0,
+            v: 0.0,
+            t_delta: 0,
+            leading: u8::MAX,
+            trailing: 0,
+        }
+    }
+
+    fn append(&mut self, t: i64, v: f64) {
+        let mut t_delta = 0;
+        match self.num_samples {
+            0 => {
+                let mut buf = Vec::new();
+                put_varint(&mut buf, t);
+                self.writer.write_bytes(&buf);
+                self.writer.write_bits(v.to_bits(), 64);
+            }
+            1 => {
+                t_delta = t.wrapping_sub(self.t) as u64;
+                let mut buf = Vec::new();
+                put_uvarint(&mut buf, t_delta);
+                self.writer.write_bytes(&buf);
+                self.write_value(v);
+            }
+            _ => {
+                t_delta = t.wrapping_sub(self.t) as u64;
+                let dod = t_delta.wrapping_sub(self.t_delta) as i64;
+                if dod == 0 {
+                    self.writer.write_bit(false);
+                } else if bit_range(dod, 14) {
+                    self.writer.write_bits(0b10, 2);
+                    self.writer.write_bits(dod as u64, 14);
+                } else if bit_range(dod, 17) {
+                    self.writer.write_bits(0b110, 3);
+                    self.writer.write_bits(dod as u64, 17);
+                } else if bit_range(dod, 20) {
+                    self.writer.write_bits(0b1110, 4);
+                    self.writer.write_bits(dod as u64, 20);
+                } else {
+                    self.writer.write_bits(0b1111, 4);
+                    self.writer.write_bits(dod as u64, 64);
+                }
+                self.write_value(v);
+            }
+        }
+
+        self.t = t;
+        self.v = v;
+        self.t_delta = t_delta;
+        self.num_samples += 1;
+    }
+
+    fn write_value(&mut self, v: f64) {
+        let delta = v.to_bits() ^ self.v.to_bits();
+        if delta == 0 {
+            self.writer.write_bit(false);
+            return;
+        }
+        self.writer.write_bit(true);
+
+        let leading = (delta.leading_zeros() as u8).min(31);
+        let trailing = delta.trailing_zeros() as u8;
+        if self.leading != u8::MAX && leading >= self.leading && trailing >= self.trailing {
+            // Reuses the previous window of meaningful bits.
+            self.writer.write_bit(false);
+            self.writer
+                .write_bits(delta >> self.trailing, 64 - self.leading - self.trailing);
+            return;
+        }
+
+        self.leading = leading;
+        self.trailing = trailing;
+        let significant = 64 - leading - trailing;
+        self.writer.write_bit(true);
+        self.writer.write_bits(leading as u64, 5);
+        // 64 significant bits overflow to 0, which is decoded as 64.
+        self.writer.write_bits(significant as u64, 6);
+        self.writer.write_bits(delta >> trailing, significant);
+    }
+
+    fn finish(self) -> Vec<u8> {
+        let mut buf = self.writer.buf;
+        buf[..2].copy_from_slice(&self.num_samples.to_be_bytes());
+        buf
+    }
+}
+
+/// Returns whether `x` can be represented by `nbits` bits.
+fn bit_range(x: i64, nbits: u8) -> bool {
+    -((1 << (nbits - 1)) - 1) <= x && x <= 1 << (nbits - 1)
+}
+
+fn put_uvarint(buf: &mut Vec<u8>, mut x: u64) {
+    while x >= 0x80 {
+        buf.push(x as u8 | 0x80);
+        x >>= 7;
+    }
+    buf.push(x as u8);
+}
+
+/// Zigzag encodes the signed `x`.
+fn put_varint(buf: &mut Vec<u8>, x: i64) {
+    let mut ux = (x as u64) << 1;
+    if x < 0 {
+        ux = !ux;
+    }
+    put_uvarint(buf, ux);
+}
+
+#[cfg(test)]
+mod tests {
+    use api::prom_store::remote::{Label, Sample};
+
+    use super::*;
+
+    struct BitReader<'a> {
+        buf: &'a [u8],
+        pos: usize,
+    }
+
+    impl BitReader<'_> {
+        fn read_bits(&mut self, nbits: u8) -> u64 {
+            let mut value = 0;
+            for _ in 0..nbits {
+                let bit = (self.buf[self.pos / 8] >> (7 - self.pos % 8)) & 1;
+                value = (value << 1) | bit as u64;
+                self.pos += 1;
+            }
+            value
+        }
+
+        fn read_uvarint(&mut self) -> u64 {
+            let mut value = 0;
+            for shift in (0..).step_by(7) {
+                let byte = self.read_bits(8);
+                value |= (byte & 0x7f) << shift;
+                if byte < 0x80 {
+                    break;
+                }
+            }
+            value
+        }
+
+        fn read_varint(&mut self) -> i64 {
+            let ux = self.read_uvarint();
+            let x = (ux >> 1) as i64;
+            if ux & 1 != 0 {
+                !x
+            } else {
+                x
+            }
+        }
+    }
+
+    /// Decodes a chunk like the `xorIterator` of Prometheus.
+    fn decode(chunk: &[u8]) -> Vec<(i64, f64)> {
+        let num_samples = u16::from_be_bytes([chunk[0], chunk[1]]);
+        let mut reader = BitReader {
+            buf: &chunk[2..],
+            pos: 0,
+        };
+        let mut samples = Vec::new();
+        let (mut t, mut v, mut t_delta) = (0i64, 0f64, 0u64);
+        let (mut leading, mut trailing) = (0u8, 0u8);
+        for i in 0..num_samples {
+            if i == 0 {
+                t = reader.read_varint();
+                v = f64::from_bits(reader.read_bits(64));
+                samples.push((t, v));
+                continue;
+            }
+            if i == 1 {
+                t_delta = reader.read_uvarint();
+            } else {
+                let mut prefix = 0;
+                while prefix < 4 && reader.read_bits(1) == 1 {
+                    prefix += 1;
+                }
+                let nbits = [0, 14, 17, 20, 64][prefix];
+                if nbits > 0 {
+                    let mut dod = reader.read_bits(nbits) as i64;
+                    if nbits < 64 && dod > 1 << (nbits - 1) {
+                        dod -= 1 << nbits;
+                    }
+                    t_delta = t_delta.wrapping_add(dod as u64);
+                }
+            }
+            t = t.wrapping_add(t_delta as i64);
+
+            if reader.read_bits(1) == 1 {
+                if reader.read_bits(1) == 1 {
+                    leading = reader.read_bits(5) as u8;
+                    let mut significant = reader.read_bits(6) as u8;
+                    if significant == 0 {
+                        significant = 64;
+                    }
+                    trailing = 64 - leading - significant;
+                }
+                let significant = 64 - leading - trailing;
+                let delta = reader.read_bits(significant) << trailing;
+                v = f64::from_bits(v.to_bits() ^ delta);
+            }
+            samples.push((t, v));
+        }
+        samples
+    }
+
+    #[test]
+    fn test_xor_encoder() {
+        let samples = vec![
+            (1000, 1.0),
+            (2000, 1.0),
+            (3000, 2.5),
+            (4000, 2.5),
+            (4500, -3.75),
+            (100_000, 1e10),
+            (100_001, f64::NAN),
+            (-5, 0.1),
+        ];
+        let mut encoder = XorEncoder::new();
+        for (t, v) in &samples {
+            encoder.append(*t, *v);
+        }
+        let decoded = decode(&encoder.finish());
+        assert_eq!(samples.len(), decoded.len());
+        for ((t, v), (decoded_t, decoded_v)) in samples.iter().zip(decoded) {
+            assert_eq!(*t, decoded_t);
+            assert_eq!(v.to_bits(), decoded_v.to_bits());
+        }
+    }
+
+    #[test]
+    fn test_to_chunked_series() {
+        let labels = vec![Label {
+            name: "__name__".to_string(),
+            value: "metric".to_string(),
+        }];
+        let samples = |range: std::ops::Range<i64>| {
+            range
+                .map(|i| Sample {
+                    value: i as f64,
+                    timestamp: i,
+                })
+                .collect::<Vec<_>>()
+        };
+        let timeseries = vec![
+            TimeSeries {
+                labels: labels.clone(),
+                samples: samples(100..200),
+                ..Default::default()
+            },
+            TimeSeries {
+                labels: labels.clone(),
+                samples: samples(0..100),
+                ..Default::default()
+            },
+        ];
+
+        let series = to_chunked_series(timeseries);
+        assert_eq!(1, series.len());
+        assert_eq!(labels, series[0].labels);
+        let chunks = &series[0].chunks;
+        assert_eq!(2, chunks.len());
+        assert_eq!((0, 119), (chunks[0].min_time_ms, chunks[0].max_time_ms));
+        assert_eq!((120, 199), (chunks[1].min_time_ms, chunks[1].max_time_ms));
+        let decoded = chunks
+            .iter()
+            .flat_map(|chunk| decode(&chunk.data))
+            .collect::<Vec<_>>();
+        let expect = (0..200).map(|i| (i, i as f64)).collect::<Vec<_>>();
+        assert_eq!(expect, decoded);
+    }
+
+    #[test]
+    fn test_encode_frame() {
+        let response = ChunkedReadResponse {
+            chunked_series: vec![],
+            query_index: 1,
+        };
+        let mut buf = Vec::new();
+        encode_frame(&response, &mut buf);
+        let message = response.encode_to_vec();
+        assert_eq!(message.len() as u8, buf[0]);
+        assert_eq!(crc32c::crc32c(&message).to_be_bytes(), buf[1..5]);
+        assert_eq!(message, buf[5..]);
+    }
+}
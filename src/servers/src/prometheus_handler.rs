@@ -19,8 +19,10 @@ use std::sync::Arc;
 use async_trait::async_trait;
 use catalog::CatalogManagerRef;
 use common_query::Output;
+use common_time::range::TimestampRange;
 use query::parser::PromQuery;
 use session::context::QueryContextRef;
+use table::engine::TableReference;
 
 use crate::error::Result;
 
@@ -32,5 +34,16 @@ pub type PrometheusHandlerRef = Arc<dyn PrometheusHandler + Send + Sync>;
 pub trait PrometheusHandler {
     async fn do_query(&self, query: &PromQuery, query_ctx: QueryContextRef) -> Result<Output>;
 
+    /// Returns sorted distinct values of the tag `column` in the time `range`, stops
+    /// scanning the table once there are `limit` values.
+    async fn tag_values(
+        &self,
+        table_ref: TableReference<'_>,
+        column: &str,
+        range: Option<&TimestampRange>,
+        limit: usize,
+        query_ctx: QueryContextRef,
+    ) -> Result<Vec<String>>;
+
     fn catalog_manager(&self) -> CatalogManagerRef;
 }
@@ -29,6 +29,7 @@ use std::collections::HashMap;
 use std::sync::Arc;
 
 use api::prom_store::remote::{ReadRequest, WriteRequest};
+use api::v1::{InsertRequests, RowInsertRequests};
 use async_trait::async_trait;
 use common_query::Output;
 use opentelemetry_proto::tonic::collector::metrics::v1::{
@@ -38,16 +39,21 @@ use opentelemetry_proto::tonic::collector::trace::v1::{
     ExportTraceServiceRequest, ExportTraceServiceResponse,
 };
 use session::context::QueryContextRef;
+use table::TableRef;
 
 use crate::error::Result;
 use crate::influxdb::InfluxdbRequest;
 use crate::opentsdb::codec::DataPoint;
-use crate::prom_store::Metrics;
+use crate::prom_store::{Metrics, PromWriteOptions};
 
 pub type OpentsdbProtocolHandlerRef = Arc<dyn OpentsdbProtocolHandler + Send + Sync>;
 pub type InfluxdbLineProtocolHandlerRef = Arc<dyn InfluxdbLineProtocolHandler + Send + Sync>;
 pub type PromStoreProtocolHandlerRef = Arc<dyn PromStoreProtocolHandler + Send + Sync>;
 pub type OpenTelemetryProtocolHandlerRef = Arc<dyn OpenTelemetryProtocolHandler + Send + Sync>;
+pub type MqttProtocolHandlerRef = Arc<dyn MqttProtocolHandler + Send + Sync>;
+pub type LokiProtocolHandlerRef = Arc<dyn LokiProtocolHandler + Send + Sync>;
+pub type PipelineHandlerRef = Arc<dyn PipelineHandler + Send + Sync>;
+pub type UploadHandlerRef = Arc<dyn UploadHandler + Send + Sync>;
 pub type ScriptHandlerRef = Arc<dyn ScriptHandler + Send + Sync>;
 
 #[async_trait]
@@ -80,6 +86,33 @@ pub trait OpentsdbProtocolHandler {
     async fn exec(&self, data_points: Vec<DataPoint>, ctx: QueryContextRef) -> Result<usize>;
 }
 
+#[async_trait]
+pub trait MqttProtocolHandler {
+    /// Writes rows parsed from MQTT messages, returns the number of rows written.
+    async fn insert(&self, requests: RowInsertRequests, ctx: QueryContextRef) -> Result<usize>;
+}
+
+#[async_trait]
+pub trait LokiProtocolHandler {
+    /// Writes rows parsed from Loki push requests, returns the number of rows written.
+    async fn insert(&self, requests: RowInsertRequests, ctx: QueryContextRef) -> Result<usize>;
+}
+
+#[async_trait]
+pub trait PipelineHandler {
+    /// Writes rows consumed by pipelines, returns the number of rows written.
+    async fn insert(&self, requests: RowInsertRequests, ctx: QueryContextRef) -> Result<usize>;
+}
+
+#[async_trait]
+pub trait UploadHandler {
+    /// Returns the table to upload files into, or `None` if the table doesn't exist.
+    async fn table(&self, table_name: &str, ctx: QueryContextRef) -> Result<Option<TableRef>>;
+
+    /// Writes rows decoded from uploaded files, returns the number of rows written.
+    async fn insert(&self, requests: InsertRequests, ctx: QueryContextRef) -> Result<usize>;
+}
+
 pub struct PromStoreResponse {
     pub content_type: String,
     pub content_encoding: String,
@@ -89,7 +122,12 @@ pub struct PromStoreResponse {
 #[async_trait]
 pub trait PromStoreProtocolHandler {
     /// Handling prometheus remote write requests
-    async fn write(&self, request: WriteRequest, ctx: QueryContextRef) -> Result<()>;
+    async fn write(
+        &self,
+        request: WriteRequest,
+        options: PromWriteOptions,
+        ctx: QueryContextRef,
+    ) -> Result<()>;
     /// Handling prometheus remote read requests
     async fn read(&self, request: ReadRequest, ctx: QueryContextRef) -> Result<PromStoreResponse>;
     /// Handling push gateway requests
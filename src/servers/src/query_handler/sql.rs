@@ -20,10 +20,12 @@ use common_query::Output;
 use query::parser::PromQuery;
 use query::plan::LogicalPlan;
 use session::context::QueryContextRef;
+use session::database::{SchemaCache, SchemaChecker};
 use snafu::ResultExt;
 use sql::statements::statement::Statement;
 
 use crate::error::{self, Result};
+use crate::statement_timeout::run_with_deadline;
 
 pub type SqlQueryHandlerRef<E> = Arc<dyn SqlQueryHandler<Error = E> + Send + Sync>;
 pub type ServerSqlQueryHandlerRef = SqlQueryHandlerRef<error::Error>;
@@ -64,11 +66,34 @@ pub trait SqlQueryHandler {
     ) -> std::result::Result<bool, Self::Error>;
 }
 
-pub struct ServerSqlQueryHandlerAdapter<E>(SqlQueryHandlerRef<E>);
+#[async_trait]
+impl<E> SchemaChecker for dyn SqlQueryHandler<Error = E> + Send + Sync
+where
+    E: ErrorExt + Send + Sync + 'static,
+{
+    async fn is_valid_schema(
+        &self,
+        catalog: &str,
+        schema: &str,
+    ) -> std::result::Result<bool, BoxedError> {
+        SqlQueryHandler::is_valid_schema(self, catalog, schema)
+            .await
+            .map_err(BoxedError::new)
+    }
+}
+
+pub struct ServerSqlQueryHandlerAdapter<E> {
+    handler: SqlQueryHandlerRef<E>,
+    /// Caches existing databases of the handler.
+    schema_cache: SchemaCache,
+}
 
 impl<E> ServerSqlQueryHandlerAdapter<E> {
     pub fn arc(handler: SqlQueryHandlerRef<E>) -> Arc<Self> {
-        Arc::new(Self(handler))
+        Arc::new(Self {
+            handler,
+            schema_cache: SchemaCache::default(),
+        })
     }
 }
 
@@ -80,9 +105,14 @@ where
     type Error = error::Error;
 
     async fn do_query(&self, query: &str, query_ctx: QueryContextRef) -> Vec<Result<Output>> {
-        self.0
-            .do_query(query, query_ctx)
-            .await
+        let outputs =
+            match run_with_deadline(&query_ctx, self.handler.do_query(query, query_ctx.clone()))
+                .await
+            {
+                Ok(outputs) => outputs,
+                Err(e) => return vec![Err(e)],
+            };
+        outputs
             .into_iter()
             .map(|x| {
                 x.map_err(BoxedError::new)
@@ -92,11 +122,13 @@ where
     }
 
     async fn do_exec_plan(&self, plan: LogicalPlan, query_ctx: QueryContextRef) -> Result<Output> {
-        self.0
-            .do_exec_plan(plan, query_ctx)
-            .await
-            .map_err(BoxedError::new)
-            .context(error::ExecutePlanSnafu)
+        run_with_deadline(
+            &query_ctx,
+            self.handler.do_exec_plan(plan, query_ctx.clone()),
+        )
+        .await?
+        .map_err(BoxedError::new)
+        .context(error::ExecutePlanSnafu)
     }
 
     async fn do_promql_query(
@@ -104,9 +136,16 @@ where
         query: &PromQuery,
         query_ctx: QueryContextRef,
     ) -> Vec<Result<Output>> {
-        self.0
-            .do_promql_query(query, query_ctx)
-            .await
+        let outputs = match run_with_deadline(
+            &query_ctx,
+            self.handler.do_promql_query(query, query_ctx.clone()),
+        )
+        .await
+        {
+            Ok(outputs) => outputs,
+            Err(e) => return vec![Err(e)],
+        };
+        outputs
             .into_iter()
             .map(|x| {
                 x.map_err(BoxedError::new).with_context(|_| {
@@ -124,7 +163,7 @@ where
         stmt: Statement,
         query_ctx: QueryContextRef,
     ) -> Result<Option<DescribeResult>> {
-        self.0
+        self.handler
             .do_describe(stmt, query_ctx)
             .await
             .map_err(BoxedError::new)
@@ -132,10 +171,9 @@ where
     }
 
     async fn is_valid_schema(&self, catalog: &str, schema: &str) -> Result<bool> {
-        self.0
-            .is_valid_schema(catalog, schema)
+        self.schema_cache
+            .is_valid_schema(self.handler.as_ref(), catalog, schema)
             .await
-            .map_err(BoxedError::new)
             .context(error::CheckDatabaseValiditySnafu)
     }
 }
@@ -0,0 +1,199 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Registry of running queries, to cancel them by ids.
+//!
+//! A MySQL connection is assigned an id when it's created, and queries of the connection
+//! are registered under the id. An HTTP request is assigned a new id for its query.
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Instant;
+
+use parking_lot::Mutex;
+use serde::Serialize;
+use session::context::QueryContextRef;
+use snafu::OptionExt;
+use tokio_util::sync::CancellationToken;
+
+use crate::error::{QueryCancelledSnafu, QueryNotFoundSnafu, Result};
+
+pub type QueryRegistryRef = Arc<QueryRegistry>;
+
+struct RunningQuery {
+    /// Sequence of the registration.
+    seq: u64,
+    query: String,
+    protocol: &'static str,
+    start: Instant,
+    token: CancellationToken,
+}
+
+/// A query that is running.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct QueryInfo {
+    pub id: u32,
+    pub query: String,
+    /// Protocol of the query, e.g. `mysql`.
+    pub protocol: String,
+    pub elapsed_ms: u64,
+}
+
+pub struct QueryRegistry {
+    next_id: AtomicU32,
+    next_seq: AtomicU64,
+    queries: Mutex<HashMap<u32, RunningQuery>>,
+}
+
+impl Default for QueryRegistry {
+    fn default() -> Self {
+        Self {
+            next_id: AtomicU32::new(1),
+            next_seq: AtomicU64::new(0),
+            queries: Mutex::default(),
+        }
+    }
+}
+
+impl QueryRegistry {
+    /// Allocates a new id.
+    pub fn next_id(&self) -> u32 {
+        self.next_id.fetch_add(1, Ordering::Relaxed)
+    }
+
+    /// Registers the query under the `id` until the returned guard is dropped.
+    pub fn register(
+        self: &Arc<Self>,
+        id: u32,
+        query: &str,
+        query_ctx: &QueryContextRef,
+        protocol: &'static str,
+    ) -> RunningQueryGuard {
+        let token = query_ctx.cancellation_token().clone();
+        let seq = self.next_seq.fetch_add(1, Ordering::Relaxed);
+        let _ = self.queries.lock().insert(
+            id,
+            RunningQuery {
+                seq,
+                query: query.to_string(),
+                protocol,
+                start: Instant::now(),
+                token: token.clone(),
+            },
+        );
+
+        RunningQueryGuard {
+            registry: self.clone(),
+            id,
+            seq,
+            token,
+        }
+    }
+
+    /// Cancels the query running under the `id`.
+    pub fn kill(&self, id: u32) -> Result<()> {
+        let queries = self.queries.lock();
+        let query = queries.get(&id).context(QueryNotFoundSnafu { id })?;
+        query.token.cancel();
+        Ok(())
+    }
+
+    /// Returns all running queries, ordered by ids.
+    pub fn list(&self) -> Vec<QueryInfo> {
+        let mut queries = self
+            .queries
+            .lock()
+            .iter()
+            .map(|(id, query)| QueryInfo {
+                id: *id,
+                query: query.query.clone(),
+                protocol: query.protocol.to_string(),
+                elapsed_ms: query.start.elapsed().as_millis() as u64,
+            })
+            .collect::<Vec<_>>();
+        queries.sort_unstable_by_key(|query| query.id);
+        queries
+    }
+}
+
+/// Deregisters the query on drop.
+pub struct RunningQueryGuard {
+    registry: QueryRegistryRef,
+    id: u32,
+    seq: u64,
+    token: CancellationToken,
+}
+
+impl RunningQueryGuard {
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    /// Runs `fut` until it's done or the query is cancelled.
+    pub async fn run<T>(&self, fut: impl Future<Output = Result<T>>) -> Result<T> {
+        tokio::select! {
+            result = fut => result,
+            _ = self.token.cancelled() => QueryCancelledSnafu { id: self.id }.fail(),
+        }
+    }
+}
+
+impl Drop for RunningQueryGuard {
+    fn drop(&mut self) {
+        let mut queries = self.registry.queries.lock();
+        // Only removes the query of this guard, a later query of the same id may replace it.
+        if queries
+            .get(&self.id)
+            .is_some_and(|query| query.seq == self.seq)
+        {
+            let _ = queries.remove(&self.id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use session::context::QueryContext;
+
+    use super::*;
+    use crate::error::Error;
+
+    #[tokio::test]
+    async fn test_kill_query() {
+        let registry = Arc::new(QueryRegistry::default());
+        let id = registry.next_id();
+        assert_ne!(id, registry.next_id());
+        assert!(registry.kill(id).is_err());
+
+        let query_ctx = QueryContext::arc();
+        let guard = registry.register(id, "SELECT 1", &query_ctx, "mysql");
+        let queries = registry.list();
+        assert_eq!(1, queries.len());
+        assert_eq!((id, "SELECT 1"), (queries[0].id, queries[0].query.as_str()));
+
+        registry.kill(id).unwrap();
+        assert!(query_ctx.cancellation_token().is_cancelled());
+        let result = guard.run(futures::future::pending::<Result<()>>()).await;
+        assert!(matches!(result, Err(Error::QueryCancelled { .. })));
+
+        // A later query of the same id isn't deregistered by the guard of the former one.
+        let later = registry.register(id, "SELECT 2", &QueryContext::arc(), "mysql");
+        drop(guard);
+        assert_eq!("SELECT 2", registry.list()[0].query);
+        drop(later);
+        assert!(registry.list().is_empty());
+    }
+}
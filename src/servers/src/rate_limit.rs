@@ -0,0 +1,327 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Rate limiting of requests by protocol, user and catalog, which protects datanodes
+//! from ingest spikes of some tenants.
+//!
+//! A request is limited by the first rule matching its protocol, user and catalog, and
+//! isn't limited if no rule matches. Each `(protocol, user, catalog)` matched by a rule has
+//! its own token bucket, e.g. a rule without a user limits each user separately.
+
+use std::sync::Arc;
+use std::time::Instant;
+
+use auth::UserInfo;
+use axum::extract::State;
+use axum::http::Request;
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use dashmap::DashMap;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use session::context::{Channel, QueryContextRef};
+use snafu::ensure;
+
+use crate::error::{RateLimitedSnafu, Result};
+use crate::metrics::METRIC_RATE_LIMITED_REQUESTS;
+
+/// Protocols whose requests can be limited.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RateLimitProtocol {
+    Http,
+    Grpc,
+    Mysql,
+}
+
+impl RateLimitProtocol {
+    fn from_channel(channel: Channel) -> Option<Self> {
+        match channel {
+            Channel::Http => Some(RateLimitProtocol::Http),
+            Channel::Grpc => Some(RateLimitProtocol::Grpc),
+            Channel::Mysql => Some(RateLimitProtocol::Mysql),
+            Channel::Postgres | Channel::Unknown => None,
+        }
+    }
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            RateLimitProtocol::Http => "http",
+            RateLimitProtocol::Grpc => "grpc",
+            RateLimitProtocol::Mysql => "mysql",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RateLimitOptions {
+    /// Rules to limit requests, requests aren't limited if it's empty.
+    pub rules: Vec<RateLimitRule>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RateLimitRule {
+    /// Matches requests of all protocols if it's None.
+    #[serde(default)]
+    pub protocol: Option<RateLimitProtocol>,
+    /// Matches requests of all users if it's None.
+    #[serde(default)]
+    pub user: Option<String>,
+    /// Matches requests to all catalogs if it's None.
+    #[serde(default)]
+    pub catalog: Option<String>,
+    /// Max number of requests per second.
+    pub requests_per_second: u32,
+    /// Max number of requests in a burst, `requests_per_second` by default.
+    #[serde(default)]
+    pub burst: Option<u32>,
+}
+
+impl RateLimitRule {
+    fn matches(&self, protocol: RateLimitProtocol, user: &str, catalog: &str) -> bool {
+        self.protocol.map_or(true, |p| p == protocol)
+            && self.user.as_deref().map_or(true, |u| u == user)
+            && self.catalog.as_deref().map_or(true, |c| c == catalog)
+    }
+}
+
+/// Key of a token bucket, the index of the rule and the protocol, user and catalog of requests.
+type BucketKey = (usize, RateLimitProtocol, String, String);
+
+struct TokenBucket {
+    capacity: f64,
+    /// Tokens refilled per second.
+    rate: f64,
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    fn new(rule: &RateLimitRule, now: Instant) -> TokenBucket {
+        let capacity = rule.burst.unwrap_or(rule.requests_per_second).max(1) as f64;
+        TokenBucket {
+            capacity,
+            rate: rule.requests_per_second as f64,
+            tokens: capacity,
+            last_refill: now,
+        }
+    }
+
+    fn try_acquire(&mut self, now: Instant) -> bool {
+        let elapsed = now
+            .saturating_duration_since(self.last_refill)
+            .as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.rate).min(self.capacity);
+        self.last_refill = now;
+        if self.tokens < 1.0 {
+            return false;
+        }
+        self.tokens -= 1.0;
+        true
+    }
+}
+
+/// Limits requests by [RateLimitRule]s.
+pub struct RateLimiter {
+    rules: Vec<RateLimitRule>,
+    buckets: DashMap<BucketKey, Mutex<TokenBucket>>,
+}
+
+pub type RateLimiterRef = Arc<RateLimiter>;
+
+impl RateLimiter {
+    pub fn new(options: &RateLimitOptions) -> RateLimiter {
+        RateLimiter {
+            rules: options.rules.clone(),
+            buckets: DashMap::new(),
+        }
+    }
+
+    /// Returns an error if the request of the `query_ctx` exceeds the rate limit.
+    pub fn check(&self, query_ctx: &QueryContextRef) -> Result<()> {
+        let Some(protocol) = RateLimitProtocol::from_channel(query_ctx.channel()) else {
+            return Ok(());
+        };
+        let user = query_ctx
+            .current_user()
+            .map(|user_info| user_info.username().to_string())
+            .unwrap_or_default();
+        self.check_request(protocol, &user, query_ctx.current_catalog(), Instant::now())
+    }
+
+    fn check_request(
+        &self,
+        protocol: RateLimitProtocol,
+        user: &str,
+        catalog: &str,
+        now: Instant,
+    ) -> Result<()> {
+        let Some((index, rule)) = self
+            .rules
+            .iter()
+            .enumerate()
+            .find(|(_, rule)| rule.matches(protocol, user, catalog))
+        else {
+            return Ok(());
+        };
+
+        let key = (index, protocol, user.to_string(), catalog.to_string());
+        let acquired = self
+            .buckets
+            .entry(key)
+            .or_insert_with(|| Mutex::new(TokenBucket::new(rule, now)))
+            .lock()
+            .try_acquire(now);
+        if !acquired {
+            METRIC_RATE_LIMITED_REQUESTS
+                .with_label_values(&[protocol.as_str()])
+                .inc();
+        }
+        ensure!(
+            acquired,
+            RateLimitedSnafu {
+                protocol: protocol.as_str(),
+                user,
+                catalog,
+            }
+        );
+        Ok(())
+    }
+}
+
+/// Middleware to limit HTTP requests, which responds `429 Too Many Requests` if the request
+/// exceeds the rate limit.
+pub(crate) async fn check_http_rate_limit<B>(
+    State(rate_limiter): State<Option<RateLimiterRef>>,
+    req: Request<B>,
+    next: Next<B>,
+) -> Response {
+    // The query context is inserted by the auth middleware.
+    let query_ctx = req.extensions().get::<QueryContextRef>();
+    if let (Some(rate_limiter), Some(query_ctx)) = (rate_limiter, query_ctx) {
+        if let Err(e) = rate_limiter.check(query_ctx) {
+            return e.into_response();
+        }
+    }
+    next.run(req).await
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    fn new_rule(
+        protocol: Option<RateLimitProtocol>,
+        user: Option<&str>,
+        requests_per_second: u32,
+    ) -> RateLimitRule {
+        RateLimitRule {
+            protocol,
+            user: user.map(|user| user.to_string()),
+            catalog: None,
+            requests_per_second,
+            burst: None,
+        }
+    }
+
+    #[test]
+    fn test_rate_limiter() {
+        let limiter = RateLimiter::new(&RateLimitOptions {
+            rules: vec![
+                new_rule(Some(RateLimitProtocol::Mysql), Some("admin"), 100),
+                new_rule(None, None, 2),
+            ],
+        });
+        let now = Instant::now();
+        let http = RateLimitProtocol::Http;
+
+        assert!(limiter
+            .check_request(http, "alice", "greptime", now)
+            .is_ok());
+        assert!(limiter
+            .check_request(http, "alice", "greptime", now)
+            .is_ok());
+        assert!(limiter
+            .check_request(http, "alice", "greptime", now)
+            .is_err());
+        // Other users, catalogs and protocols have their own buckets.
+        assert!(limiter.check_request(http, "bob", "greptime", now).is_ok());
+        assert!(limiter.check_request(http, "alice", "other", now).is_ok());
+        let grpc = RateLimitProtocol::Grpc;
+        assert!(limiter
+            .check_request(grpc, "alice", "greptime", now)
+            .is_ok());
+
+        // Refills tokens over time.
+        let later = now + Duration::from_millis(500);
+        assert!(limiter
+            .check_request(http, "alice", "greptime", later)
+            .is_ok());
+        assert!(limiter
+            .check_request(http, "alice", "greptime", later)
+            .is_err());
+
+        // Matches the first rule.
+        let mysql = RateLimitProtocol::Mysql;
+        for _ in 0..100 {
+            assert!(limiter
+                .check_request(mysql, "admin", "greptime", now)
+                .is_ok());
+        }
+        assert!(limiter
+            .check_request(mysql, "admin", "greptime", now)
+            .is_err());
+    }
+
+    #[test]
+    fn test_no_rules() {
+        let limiter = RateLimiter::new(&RateLimitOptions::default());
+        for _ in 0..100 {
+            assert!(limiter
+                .check_request(RateLimitProtocol::Http, "alice", "greptime", Instant::now())
+                .is_ok());
+        }
+    }
+
+    #[test]
+    fn test_rate_limit_options() {
+        let options: RateLimitOptions = serde_json::from_str(
+            r#"
+        {
+            "rules": [
+                {
+                    "protocol": "mysql",
+                    "user": "alice",
+                    "requests_per_second": 10,
+                    "burst": 20
+                },
+                {
+                    "catalog": "greptime",
+                    "requests_per_second": 100
+                }
+            ]
+        }
+        "#,
+        )
+        .unwrap();
+        assert_eq!(2, options.rules.len());
+        assert_eq!(Some(RateLimitProtocol::Mysql), options.rules[0].protocol);
+        assert_eq!(Some(20), options.rules[0].burst);
+        assert_eq!(None, options.rules[1].protocol);
+        assert_eq!(Some("greptime".to_string()), options.rules[1].catalog);
+    }
+}
@@ -227,6 +227,64 @@ fn write_by_semantic_type(
     Ok(())
 }
 
+/// Writes a JSON object as a row. Keys in `tags` are written as tags, the `timestamp_key`
+/// is written as the time index with the value `ts` and other keys are written as fields.
+pub fn write_json_object(
+    table_data: &mut TableData,
+    object: &serde_json::Map<String, serde_json::Value>,
+    tags: &[String],
+    timestamp_key: &str,
+    ts: Option<i64>,
+) -> Result<()> {
+    let mut one_row = table_data.alloc_one_row();
+    let mut tag_values = Vec::new();
+    let mut fields = Vec::new();
+    for (key, value) in object {
+        if key == timestamp_key {
+            continue;
+        }
+        if tags.contains(key) {
+            match value {
+                serde_json::Value::Null => (),
+                serde_json::Value::String(s) => tag_values.push((key.clone(), s.clone())),
+                value => tag_values.push((key.clone(), value.to_string())),
+            }
+        } else if let Some((datatype, value)) = json_to_field(value) {
+            fields.push((key.clone(), datatype, value));
+        }
+    }
+
+    write_tags(table_data, tag_values.into_iter(), &mut one_row)?;
+    write_fields(table_data, fields.into_iter(), &mut one_row)?;
+    write_ts_millis(table_data, timestamp_key, ts, &mut one_row)?;
+    table_data.add_row(one_row);
+    Ok(())
+}
+
+/// Converts a JSON value to a field value. Returns `None` if the value is null.
+fn json_to_field(value: &serde_json::Value) -> Option<(ColumnDataType, ValueData)> {
+    let field = match value {
+        serde_json::Value::Null => return None,
+        serde_json::Value::Bool(v) => (ColumnDataType::Boolean, ValueData::BoolValue(*v)),
+        serde_json::Value::Number(v) => {
+            if let Some(v) = v.as_i64() {
+                (ColumnDataType::Int64, ValueData::I64Value(v))
+            } else if let Some(v) = v.as_u64() {
+                (ColumnDataType::Uint64, ValueData::U64Value(v))
+            } else {
+                (ColumnDataType::Float64, ValueData::F64Value(v.as_f64()?))
+            }
+        }
+        serde_json::Value::String(v) => (ColumnDataType::String, ValueData::StringValue(v.clone())),
+        // Nested values are written as JSON strings.
+        value => (
+            ColumnDataType::String,
+            ValueData::StringValue(value.to_string()),
+        ),
+    };
+    Some(field)
+}
+
 pub fn write_ts_millis(
     table_data: &mut TableData,
     name: impl ToString,
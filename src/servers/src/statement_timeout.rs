@@ -0,0 +1,124 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Statement timeouts, set by `SET statement_timeout = '30s'` in a session or
+//! by the `x-greptime-timeout` header of an HTTP request.
+
+use std::future::Future;
+use std::time::Duration;
+
+use humantime_serde::re::humantime;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use session::context::QueryContextRef;
+use snafu::OptionExt;
+
+use crate::error::{InvalidStatementTimeoutSnafu, Result, StatementTimeoutSnafu};
+
+static SET_STATEMENT_TIMEOUT_PATTERN: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"(?i)^\s*SET\s+(?:SESSION\s+)?STATEMENT_TIMEOUT\s*(?:=|TO)\s*'?([^'\s;]+)'?\s*;?\s*$",
+    )
+    .unwrap()
+});
+
+/// Returns the value to set if the query is `SET statement_timeout = <value>`.
+pub(crate) fn match_set_statement_timeout(query: &str) -> Option<&str> {
+    SET_STATEMENT_TIMEOUT_PATTERN
+        .captures(query)
+        .and_then(|captures| captures.get(1))
+        .map(|value| value.as_str())
+}
+
+/// Parses a statement timeout. An integer is in milliseconds like PostgreSQL, otherwise
+/// it's a human readable duration like `30s`.
+///
+/// Returns `None` if the timeout is `0`, which disables the timeout.
+pub fn parse_statement_timeout(value: &str) -> Result<Option<Duration>> {
+    let timeout = match value.parse::<u64>() {
+        Ok(millis) => Duration::from_millis(millis),
+        Err(_) => humantime::parse_duration(value)
+            .ok()
+            .context(InvalidStatementTimeoutSnafu { value })?,
+    };
+    Ok((!timeout.is_zero()).then_some(timeout))
+}
+
+/// Runs `fut` until it's done or the query exceeds its deadline.
+///
+/// The query is cancelled once it exceeds the deadline, so the execution of the
+/// query stops as well.
+pub(crate) async fn run_with_deadline<T>(
+    query_ctx: &QueryContextRef,
+    fut: impl Future<Output = T>,
+) -> Result<T> {
+    let Some(deadline) = query_ctx.deadline() else {
+        return Ok(fut.await);
+    };
+
+    match tokio::time::timeout_at(deadline.into(), fut).await {
+        Ok(output) => Ok(output),
+        Err(_) => {
+            query_ctx.cancellation_token().cancel();
+            StatementTimeoutSnafu.fail()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Instant;
+
+    use session::context::QueryContextBuilder;
+
+    use super::*;
+    use crate::error::Error;
+
+    #[test]
+    fn test_parse_statement_timeout() {
+        assert_eq!(
+            Some("30s"),
+            match_set_statement_timeout("SET statement_timeout = '30s'")
+        );
+        assert_eq!(
+            Some("1000"),
+            match_set_statement_timeout("set session statement_timeout to 1000;")
+        );
+        assert!(match_set_statement_timeout("SET time_zone = 'UTC'").is_none());
+
+        assert_eq!(
+            Some(Duration::from_secs(30)),
+            parse_statement_timeout("30s").unwrap()
+        );
+        assert_eq!(
+            Some(Duration::from_millis(1500)),
+            parse_statement_timeout("1500").unwrap()
+        );
+        assert_eq!(None, parse_statement_timeout("0").unwrap());
+        assert!(parse_statement_timeout("soon").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_run_with_deadline() {
+        let query_ctx = QueryContextBuilder::default().build();
+        assert_eq!(1, run_with_deadline(&query_ctx, async { 1 }).await.unwrap());
+
+        let query_ctx = QueryContextBuilder::default()
+            .deadline(Some(Instant::now() + Duration::from_millis(10)))
+            .build();
+        let result = run_with_deadline(&query_ctx, futures::future::pending::<()>()).await;
+        assert!(matches!(result, Err(Error::StatementTimeout { .. })));
+        assert!(query_ctx.cancellation_token().is_cancelled());
+    }
+}
@@ -0,0 +1,383 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Live tailing of newly ingested rows.
+//!
+//! Rows are dispatched to subscribers after they are written. A subscriber only
+//! receives rows ingested through the same node.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+
+use api::helper::pb_value_to_value_ref;
+use api::v1::{RowInsertRequest, RowInsertRequests};
+use datatypes::value::Value;
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc;
+use tokio::sync::mpsc::error::TrySendError;
+
+use crate::metrics::{METRIC_TAIL_DROPPED_BATCHES, METRIC_TAIL_SUBSCRIBERS};
+
+/// Max number of batches buffered for a subscriber.
+///
+/// Batches are dropped if the subscriber is too slow to consume them so
+/// ingestion is never blocked by subscribers.
+pub const TAIL_BUFFER_SIZE: usize = 64;
+
+/// Operator of a [TailPredicate].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TailOp {
+    #[serde(rename = "=")]
+    Eq,
+    #[serde(rename = "!=")]
+    NotEq,
+}
+
+/// A predicate on a column of rows to tail.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TailPredicate {
+    pub column: String,
+    pub op: TailOp,
+    pub value: serde_json::Value,
+}
+
+impl TailPredicate {
+    fn evaluate(&self, value: &serde_json::Value) -> bool {
+        match self.op {
+            TailOp::Eq => *value == self.value,
+            TailOp::NotEq => *value != self.value,
+        }
+    }
+}
+
+/// Filter of rows to tail.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TailFilter {
+    pub table: String,
+    /// Rows must match all predicates. A missing column is treated as null.
+    #[serde(default)]
+    pub predicates: Vec<TailPredicate>,
+}
+
+/// A batch of rows sent to subscribers.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TailRows {
+    pub table: String,
+    pub columns: Vec<String>,
+    pub rows: Vec<Vec<serde_json::Value>>,
+    /// Number of batches dropped before this batch as the subscriber is too slow.
+    pub dropped: u64,
+}
+
+struct Subscriber {
+    catalog: String,
+    schema: String,
+    filter: TailFilter,
+    sender: mpsc::Sender<TailRows>,
+    /// Number of batches dropped since the last batch sent.
+    dropped: AtomicU64,
+}
+
+impl Subscriber {
+    fn send(&self, table: &str, columns: &[String], rows: &[Vec<serde_json::Value>]) {
+        let column_indices = self
+            .filter
+            .predicates
+            .iter()
+            .map(|predicate| columns.iter().position(|c| *c == predicate.column))
+            .collect::<Vec<_>>();
+        let rows = rows
+            .iter()
+            .filter(|row| {
+                self.filter
+                    .predicates
+                    .iter()
+                    .zip(column_indices.iter())
+                    .all(|(predicate, index)| {
+                        let value = index
+                            .and_then(|i| row.get(i))
+                            .unwrap_or(&serde_json::Value::Null);
+                        predicate.evaluate(value)
+                    })
+            })
+            .cloned()
+            .collect::<Vec<_>>();
+        if rows.is_empty() {
+            return;
+        }
+
+        let dropped = self.dropped.swap(0, Ordering::Relaxed);
+        let batch = TailRows {
+            table: table.to_string(),
+            columns: columns.to_vec(),
+            rows,
+            dropped,
+        };
+        match self.sender.try_send(batch) {
+            Ok(()) => (),
+            Err(TrySendError::Full(_)) => {
+                METRIC_TAIL_DROPPED_BATCHES.inc();
+                let _ = self.dropped.fetch_add(dropped + 1, Ordering::Relaxed);
+            }
+            // The subscription is dropping.
+            Err(TrySendError::Closed(_)) => (),
+        }
+    }
+}
+
+/// Hub to dispatch newly ingested rows to subscribers.
+#[derive(Default)]
+pub struct TailHub {
+    subscribers: RwLock<HashMap<u64, Arc<Subscriber>>>,
+    next_id: AtomicU64,
+}
+
+pub type TailHubRef = Arc<TailHub>;
+
+impl TailHub {
+    /// Subscribes rows of the table in `catalog` and `schema` that match the `filter`.
+    pub fn subscribe(
+        self: &Arc<Self>,
+        catalog: &str,
+        schema: &str,
+        filter: TailFilter,
+    ) -> TailSubscription {
+        let (sender, receiver) = mpsc::channel(TAIL_BUFFER_SIZE);
+        let subscriber = Subscriber {
+            catalog: catalog.to_string(),
+            schema: schema.to_string(),
+            filter,
+            sender,
+            dropped: AtomicU64::new(0),
+        };
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let mut subscribers = self.subscribers.write().unwrap();
+        let _ = subscribers.insert(id, Arc::new(subscriber));
+        METRIC_TAIL_SUBSCRIBERS.set(subscribers.len() as i64);
+
+        TailSubscription {
+            id,
+            hub: self.clone(),
+            receiver,
+        }
+    }
+
+    /// Returns true if there are subscribers.
+    pub fn has_subscribers(&self) -> bool {
+        !self.subscribers.read().unwrap().is_empty()
+    }
+
+    /// Dispatches written rows of tables in `catalog` and `schema` to subscribers.
+    pub fn publish(&self, catalog: &str, schema: &str, requests: &RowInsertRequests) {
+        let subscribers = self.subscribers.read().unwrap();
+        if subscribers.is_empty() {
+            return;
+        }
+
+        for request in &requests.inserts {
+            let matched = subscribers
+                .values()
+                .filter(|s| {
+                    s.catalog == catalog
+                        && s.schema == schema
+                        && s.filter.table == request.table_name
+                })
+                .collect::<Vec<_>>();
+            if matched.is_empty() {
+                continue;
+            }
+
+            let Some((columns, rows)) = rows_to_json(request) else {
+                continue;
+            };
+            for subscriber in matched {
+                subscriber.send(&request.table_name, &columns, &rows);
+            }
+        }
+    }
+
+    fn unsubscribe(&self, id: u64) {
+        let mut subscribers = self.subscribers.write().unwrap();
+        let _ = subscribers.remove(&id);
+        METRIC_TAIL_SUBSCRIBERS.set(subscribers.len() as i64);
+    }
+}
+
+/// Converts rows in the `request` into JSON values. Returns `None` if the request has no rows.
+fn rows_to_json(request: &RowInsertRequest) -> Option<(Vec<String>, Vec<Vec<serde_json::Value>>)> {
+    let rows = request.rows.as_ref()?;
+    let columns = rows
+        .schema
+        .iter()
+        .map(|c| c.column_name.clone())
+        .collect::<Vec<_>>();
+    let json_rows = rows
+        .rows
+        .iter()
+        .map(|row| {
+            row.values
+                .iter()
+                .zip(rows.schema.iter())
+                .map(|(value, column)| {
+                    let value =
+                        Value::from(pb_value_to_value_ref(value, &column.datatype_extension));
+                    serde_json::Value::try_from(value).unwrap_or(serde_json::Value::Null)
+                })
+                .collect()
+        })
+        .collect();
+    Some((columns, json_rows))
+}
+
+/// A subscription of rows. Dropping it unsubscribes from the hub.
+pub struct TailSubscription {
+    id: u64,
+    hub: TailHubRef,
+    receiver: mpsc::Receiver<TailRows>,
+}
+
+impl TailSubscription {
+    /// Receives the next batch of rows.
+    pub async fn recv(&mut self) -> Option<TailRows> {
+        self.receiver.recv().await
+    }
+}
+
+impl Drop for TailSubscription {
+    fn drop(&mut self) {
+        self.hub.unsubscribe(self.id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use api::v1::value::ValueData;
+    use api::v1::{ColumnDataType, ColumnSchema, Row, Rows, SemanticType};
+
+    use super::*;
+
+    fn new_requests(table: &str, hosts: &[&str]) -> RowInsertRequests {
+        let schema = vec![
+            ColumnSchema {
+                column_name: "host".to_string(),
+                datatype: ColumnDataType::String as i32,
+                semantic_type: SemanticType::Tag as i32,
+                ..Default::default()
+            },
+            ColumnSchema {
+                column_name: "ts".to_string(),
+                datatype: ColumnDataType::TimestampMillisecond as i32,
+                semantic_type: SemanticType::Timestamp as i32,
+                ..Default::default()
+            },
+        ];
+        let rows = hosts
+            .iter()
+            .enumerate()
+            .map(|(i, host)| Row {
+                values: vec![
+                    api::v1::Value {
+                        value_data: Some(ValueData::StringValue(host.to_string())),
+                    },
+                    api::v1::Value {
+                        value_data: Some(ValueData::TimestampMillisecondValue(i as i64)),
+                    },
+                ],
+            })
+            .collect();
+        RowInsertRequests {
+            inserts: vec![RowInsertRequest {
+                table_name: table.to_string(),
+                rows: Some(Rows { schema, rows }),
+            }],
+        }
+    }
+
+    fn host_filter(table: &str, host: &str) -> TailFilter {
+        TailFilter {
+            table: table.to_string(),
+            predicates: vec![TailPredicate {
+                column: "host".to_string(),
+                op: TailOp::Eq,
+                value: serde_json::Value::from(host),
+            }],
+        }
+    }
+
+    #[tokio::test]
+    async fn test_tail_filter() {
+        let hub = Arc::new(TailHub::default());
+        let mut subscription = hub.subscribe("greptime", "public", host_filter("cpu", "a"));
+        assert!(hub.has_subscribers());
+
+        // Rows of other tables and schemas are ignored.
+        hub.publish("greptime", "public", &new_requests("mem", &["a"]));
+        hub.publish("greptime", "other", &new_requests("cpu", &["a"]));
+        hub.publish("greptime", "public", &new_requests("cpu", &["a", "b", "a"]));
+
+        let rows = subscription.recv().await.unwrap();
+        assert_eq!("cpu", rows.table);
+        assert_eq!(vec!["host", "ts"], rows.columns);
+        assert_eq!(
+            vec![
+                vec![serde_json::Value::from("a"), serde_json::Value::from(0)],
+                vec![serde_json::Value::from("a"), serde_json::Value::from(2)],
+            ],
+            rows.rows
+        );
+        assert_eq!(0, rows.dropped);
+
+        drop(subscription);
+        assert!(!hub.has_subscribers());
+    }
+
+    #[tokio::test]
+    async fn test_tail_slow_subscriber() {
+        let hub = Arc::new(TailHub::default());
+        let mut subscription = hub.subscribe(
+            "greptime",
+            "public",
+            TailFilter {
+                table: "cpu".to_string(),
+                predicates: vec![],
+            },
+        );
+
+        for _ in 0..TAIL_BUFFER_SIZE + 2 {
+            hub.publish("greptime", "public", &new_requests("cpu", &["a"]));
+        }
+        for _ in 0..TAIL_BUFFER_SIZE {
+            let rows = subscription.recv().await.unwrap();
+            assert_eq!(0, rows.dropped);
+        }
+
+        hub.publish("greptime", "public", &new_requests("cpu", &["a"]));
+        let rows = subscription.recv().await.unwrap();
+        assert_eq!(2, rows.dropped);
+    }
+
+    #[test]
+    fn test_deserialize_filter() {
+        let filter: TailFilter = serde_json::from_str(
+            r#"{"table": "cpu", "predicates": [{"column": "host", "op": "!=", "value": "a"}]}"#,
+        )
+        .unwrap();
+        assert_eq!("cpu", filter.table);
+        assert_eq!(TailOp::NotEq, filter.predicates[0].op);
+
+        let filter: TailFilter = serde_json::from_str(r#"{"table": "cpu"}"#).unwrap();
+        assert!(filter.predicates.is_empty());
+    }
+}
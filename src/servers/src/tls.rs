@@ -14,12 +14,30 @@
 
 use std::fs::File;
 use std::io::{BufReader, Error, ErrorKind};
-
-use rustls::ServerConfig;
+use std::net::SocketAddr;
+use std::path::Path;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{mpsc, Arc, RwLock};
+use std::task::{Context, Poll};
+
+use axum::extract::connect_info::Connected;
+use common_telemetry::{debug, error, info};
+use notify::{EventKind, RecursiveMode, Watcher};
+use pin_project::pin_project;
+use rustls::server::WebPkiClientVerifier;
+use rustls::{RootCertStore, ServerConfig, SupportedProtocolVersion};
 use rustls_pemfile::{certs, pkcs8_private_keys, rsa_private_keys};
 use rustls_pki_types::{CertificateDer, PrivateKeyDer};
 use serde::{Deserialize, Serialize};
+use snafu::ResultExt;
 use strum::EnumString;
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+use tokio::net::{TcpListener, TcpStream};
+use tokio_rustls::server::TlsStream;
+use tokio_rustls::TlsAcceptor;
+
+use crate::error::{FileWatchSnafu, Result as WatchResult};
 
 /// TlsMode is used for Mysql and Postgres server start up.
 #[derive(Debug, Default, Serialize, Deserialize, Clone, PartialEq, Eq, EnumString)]
@@ -44,6 +62,25 @@ pub enum TlsMode {
     VerifyFull,
 }
 
+/// The minimum TLS protocol version accepted by servers.
+#[derive(Debug, Default, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum TlsVersion {
+    #[default]
+    #[serde(rename = "tls1.2")]
+    Tls12,
+    #[serde(rename = "tls1.3")]
+    Tls13,
+}
+
+impl TlsVersion {
+    fn protocol_versions(&self) -> &'static [&'static SupportedProtocolVersion] {
+        match self {
+            TlsVersion::Tls12 => rustls::ALL_VERSIONS,
+            TlsVersion::Tls13 => &[&rustls::version::TLS13],
+        }
+    }
+}
+
 #[derive(Debug, Default, Serialize, Deserialize, Clone, PartialEq, Eq)]
 #[serde(rename_all = "snake_case")]
 pub struct TlsOption {
@@ -52,6 +89,18 @@ pub struct TlsOption {
     pub cert_path: String,
     #[serde(default)]
     pub key_path: String,
+    /// CA certificates to verify certificates of clients. Clients are not authenticated
+    /// by certificates if it's empty.
+    #[serde(default)]
+    pub ca_cert_path: String,
+    /// Whether clients must present a certificate signed by the CA.
+    #[serde(default)]
+    pub require_client_auth: bool,
+    #[serde(default)]
+    pub min_version: TlsVersion,
+    /// Whether to reload certificates once the files change.
+    #[serde(default)]
+    pub watch: bool,
 }
 
 impl TlsOption {
@@ -98,9 +147,27 @@ impl TlsOption {
             }
         };
 
-        // TODO(SSebo): with_client_cert_verifier if TlsMode is Required.
-        let config = ServerConfig::builder()
-            .with_no_client_auth()
+        let builder =
+            ServerConfig::builder_with_protocol_versions(self.min_version.protocol_versions());
+        let builder = if self.ca_cert_path.is_empty() {
+            builder.with_no_client_auth()
+        } else {
+            let mut roots = RootCertStore::empty();
+            for ca_cert in certs(&mut BufReader::new(File::open(&self.ca_cert_path)?)) {
+                roots
+                    .add(ca_cert?)
+                    .map_err(|err| Error::new(ErrorKind::InvalidInput, err))?;
+            }
+            let verifier = WebPkiClientVerifier::builder(Arc::new(roots));
+            let verifier = if self.require_client_auth {
+                verifier.build()
+            } else {
+                verifier.allow_unauthenticated().build()
+            }
+            .map_err(|err| Error::new(ErrorKind::InvalidInput, err))?;
+            builder.with_client_cert_verifier(verifier)
+        };
+        let config = builder
             .with_single_cert(cert, key)
             .map_err(|err| std::io::Error::new(ErrorKind::InvalidInput, err))?;
 
@@ -110,6 +177,209 @@ impl TlsOption {
     pub fn should_force_tls(&self) -> bool {
         !matches!(self.mode, TlsMode::Disable | TlsMode::Prefer)
     }
+
+    /// Returns files to watch for changes if certificates are reloadable.
+    fn watch_paths(&self) -> Vec<&Path> {
+        if !self.watch || self.mode == TlsMode::Disable {
+            return vec![];
+        }
+        [&self.cert_path, &self.key_path, &self.ca_cert_path]
+            .into_iter()
+            .filter(|path| !path.is_empty())
+            .map(Path::new)
+            .collect()
+    }
+}
+
+/// A TLS server config that can be reloaded without restarting servers, so rotated
+/// certificates take effect for new connections.
+#[derive(Debug)]
+pub struct ReloadableTlsServerConfig {
+    tls_option: TlsOption,
+    config: RwLock<Option<Arc<ServerConfig>>>,
+    /// Increases once the config is reloaded.
+    version: AtomicUsize,
+}
+
+pub type ReloadableTlsServerConfigRef = Arc<ReloadableTlsServerConfig>;
+
+impl ReloadableTlsServerConfig {
+    pub fn try_new(tls_option: TlsOption) -> Result<Self, Error> {
+        let config = tls_option.setup()?.map(Arc::new);
+        Ok(Self {
+            tls_option,
+            config: RwLock::new(config),
+            version: AtomicUsize::new(0),
+        })
+    }
+
+    /// Reloads certificates from files, the current config is kept if it fails.
+    pub fn reload(&self) -> Result<(), Error> {
+        let config = self.tls_option.setup()?.map(Arc::new);
+        *self.config.write().unwrap() = config;
+        let _ = self.version.fetch_add(1, Ordering::Relaxed);
+        Ok(())
+    }
+
+    /// Returns the latest server config, `None` if TLS is disabled.
+    pub fn get_server_config(&self) -> Option<Arc<ServerConfig>> {
+        self.config.read().unwrap().clone()
+    }
+
+    pub fn get_tls_option(&self) -> &TlsOption {
+        &self.tls_option
+    }
+
+    pub fn get_version(&self) -> usize {
+        self.version.load(Ordering::Relaxed)
+    }
+}
+
+/// Watches certificate files of the config and reloads the config once they change,
+/// if `watch` of the TLS option is enabled.
+///
+/// Directories of the files are watched instead of the files, since tools like cert-manager
+/// rotate certificates by replacing symlinks in the directories.
+pub fn maybe_watch_tls_config(config: ReloadableTlsServerConfigRef) -> WatchResult<()> {
+    let paths = config.get_tls_option().watch_paths();
+    if paths.is_empty() {
+        return Ok(());
+    }
+
+    let (tx, rx) = mpsc::channel::<notify::Result<notify::Event>>();
+    let mut watcher = notify::recommended_watcher(tx).context(FileWatchSnafu {
+        path: format!("{paths:?}"),
+    })?;
+    for path in &paths {
+        let dir = path
+            .parent()
+            .filter(|dir| !dir.as_os_str().is_empty())
+            .unwrap_or(Path::new("."));
+        watcher
+            .watch(dir, RecursiveMode::NonRecursive)
+            .context(FileWatchSnafu {
+                path: dir.display().to_string(),
+            })?;
+    }
+
+    let config_clone = config.clone();
+    let _handle = std::thread::Builder::new()
+        .name("tls-config-watcher".to_string())
+        .spawn(move || {
+            // Keeps the watcher alive while receiving events.
+            let _watcher = watcher;
+            while let Ok(event) = rx.recv() {
+                match event {
+                    Ok(event) => {
+                        if !matches!(
+                            event.kind,
+                            EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_)
+                        ) {
+                            continue;
+                        }
+                        debug!("TLS files changed, event: {:?}", event);
+                        match config_clone.reload() {
+                            Ok(()) => info!(
+                                "Reloaded TLS config, version: {}",
+                                config_clone.get_version()
+                            ),
+                            // Files may be partially written, waits for the next event.
+                            Err(e) => error!(e; "Failed to reload TLS config"),
+                        }
+                    }
+                    Err(e) => error!(e; "Failed to watch TLS files"),
+                }
+            }
+        })?;
+    info!("Watching TLS files {:?}", paths);
+
+    Ok(())
+}
+
+/// A TLS connection accepted by [tls_incoming].
+#[pin_project]
+pub struct TlsConnection {
+    #[pin]
+    stream: TlsStream<TcpStream>,
+    remote_addr: SocketAddr,
+}
+
+impl AsyncRead for TlsConnection {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        self.project().stream.poll_read(cx, buf)
+    }
+}
+
+impl AsyncWrite for TlsConnection {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<std::io::Result<usize>> {
+        self.project().stream.poll_write(cx, buf)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        self.project().stream.poll_flush(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        self.project().stream.poll_shutdown(cx)
+    }
+}
+
+impl Connected<&TlsConnection> for SocketAddr {
+    fn connect_info(target: &TlsConnection) -> Self {
+        target.remote_addr
+    }
+}
+
+/// Accepts connections from the `listener` and yields connections that finish TLS
+/// handshakes with the latest server config. Handshakes run in their own tasks so a slow
+/// client doesn't block others.
+pub(crate) fn tls_incoming(
+    listener: TcpListener,
+    config: ReloadableTlsServerConfigRef,
+) -> tokio_stream::wrappers::ReceiverStream<std::io::Result<TlsConnection>> {
+    let (tx, rx) = tokio::sync::mpsc::channel(1024);
+    let _handle = common_runtime::spawn_bg(async move {
+        loop {
+            let (stream, remote_addr) = tokio::select! {
+                result = listener.accept() => match result {
+                    Ok(accepted) => accepted,
+                    Err(e) => {
+                        error!(e; "Failed to accept TCP connection");
+                        continue;
+                    }
+                },
+                // Stops accepting once the server is shut down.
+                _ = tx.closed() => return,
+            };
+            let Some(server_config) = config.get_server_config() else {
+                return;
+            };
+            let tx = tx.clone();
+            let _handle = common_runtime::spawn_bg(async move {
+                let _ = stream.set_nodelay(true);
+                match TlsAcceptor::from(server_config).accept(stream).await {
+                    Ok(stream) => {
+                        let _ = tx
+                            .send(Ok(TlsConnection {
+                                stream,
+                                remote_addr,
+                            }))
+                            .await;
+                    }
+                    Err(e) => debug!("Failed TLS handshake with {}, err: {}", remote_addr, e),
+                }
+            });
+        }
+    });
+    tokio_stream::wrappers::ReceiverStream::new(rx)
 }
 
 #[cfg(test)]
@@ -132,6 +402,7 @@ mod tests {
                 mode: Disable,
                 cert_path: "/path/to/cert_path".to_string(),
                 key_path: "/path/to/key_path".to_string(),
+                ..Default::default()
             },
             TlsOption::new(
                 Some(Disable),
@@ -237,4 +508,41 @@ mod tests {
         assert!(!t.key_path.is_empty());
         assert!(!t.cert_path.is_empty());
     }
+
+    #[test]
+    fn test_tls_option_min_version() {
+        let s = r#"
+        {
+            "mode": "require",
+            "cert_path": "/some_dir/some.crt",
+            "key_path": "/some_dir/some.key",
+            "min_version": "tls1.3"
+        }
+        "#;
+
+        let t: TlsOption = serde_json::from_str(s).unwrap();
+        assert_eq!(TlsVersion::Tls13, t.min_version);
+        assert!(!t.watch);
+        assert!(!t.require_client_auth);
+    }
+
+    #[test]
+    fn test_reloadable_tls_server_config() {
+        let option = TlsOption {
+            mode: TlsMode::Require,
+            cert_path: "tests/ssl/server.crt".to_string(),
+            key_path: "tests/ssl/server-rsa.key".to_string(),
+            ..Default::default()
+        };
+        let config = ReloadableTlsServerConfig::try_new(option).unwrap();
+        assert!(config.get_server_config().is_some());
+        assert_eq!(0, config.get_version());
+
+        config.reload().unwrap();
+        assert!(config.get_server_config().is_some());
+        assert_eq!(1, config.get_version());
+
+        let config = ReloadableTlsServerConfig::try_new(TlsOption::default()).unwrap();
+        assert!(config.get_server_config().is_none());
+    }
 }
@@ -0,0 +1,482 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Decodes CSV and Parquet files uploaded by the HTTP API into insert requests, so users
+//! can load files that aren't on the filesystem of the server.
+//!
+//! Columns of the file are validated against the schema of the table. Rows of a CSV file
+//! that can't be decoded are skipped, the upload fails if there are more bad rows than
+//! [UploadOptions::max_bad_rows].
+//!
+//! Files are decoded from bytes in memory instead of streams, see [crate::http::upload::upload]
+//! for limits of the file size.
+
+use std::str::FromStr;
+use std::sync::Arc;
+
+use api::v1::{InsertRequests, SemanticType};
+use bytes::Bytes;
+use common_recordbatch::RecordBatch;
+use datatypes::arrow::compute::{can_cast_types, cast};
+use datatypes::arrow::csv::ReaderBuilder;
+use datatypes::arrow::datatypes::SchemaRef as ArrowSchemaRef;
+use datatypes::arrow::error::ArrowError;
+use datatypes::arrow::record_batch::RecordBatch as DfRecordBatch;
+use datatypes::schema::{ColumnSchema, Schema, SchemaRef};
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use snafu::{ensure, OptionExt, ResultExt};
+use table::metadata::TableInfo;
+
+use crate::error::{
+    self, ConvertUploadSnafu, InvalidUploadSnafu, ReadParquetSnafu, Result, TooManyBadRowsSnafu,
+};
+use crate::grpc::flight::bulk_insert::build_insert_request;
+
+/// Number of rows in a record batch decoded from the uploaded file.
+const BATCH_SIZE: usize = 8192;
+
+/// Format of the uploaded file.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum UploadFormat {
+    #[default]
+    Csv,
+    Parquet,
+}
+
+impl FromStr for UploadFormat {
+    type Err = error::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "csv" => Ok(UploadFormat::Csv),
+            "parquet" => Ok(UploadFormat::Parquet),
+            _ => InvalidUploadSnafu {
+                reason: format!("unsupported format: {s}, expect csv or parquet"),
+            }
+            .fail(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UploadOptions {
+    pub format: UploadFormat,
+    /// Whether the first line of a CSV file is the header. Columns of a CSV file without
+    /// header must be in the same order as the table.
+    pub has_header: bool,
+    pub delimiter: u8,
+    /// Max number of rows that can't be decoded before the upload fails.
+    pub max_bad_rows: usize,
+}
+
+impl Default for UploadOptions {
+    fn default() -> Self {
+        Self {
+            format: UploadFormat::Csv,
+            has_header: true,
+            delimiter: b',',
+            max_bad_rows: 0,
+        }
+    }
+}
+
+/// Rows decoded from an uploaded file.
+#[derive(Debug)]
+pub struct DecodedUpload {
+    pub requests: InsertRequests,
+    pub rows: usize,
+    pub bad_rows: usize,
+}
+
+/// Decodes the uploaded file into insert requests of the table.
+pub fn to_insert_requests(
+    table_info: &TableInfo,
+    body: Bytes,
+    options: &UploadOptions,
+) -> Result<DecodedUpload> {
+    let (schema, batches, bad_rows) = match options.format {
+        UploadFormat::Csv => decode_csv(table_info, &body, options)?,
+        UploadFormat::Parquet => decode_parquet(table_info, body)?,
+    };
+
+    let semantic_types = schema
+        .column_schemas()
+        .iter()
+        .map(|column_schema| semantic_type(table_info, &column_schema.name))
+        .collect::<Vec<_>>();
+    let mut rows = 0;
+    let mut inserts = Vec::with_capacity(batches.len());
+    for batch in batches {
+        if batch.num_rows() == 0 {
+            continue;
+        }
+        rows += batch.num_rows();
+        let batch = RecordBatch::try_from_df_record_batch(schema.clone(), batch)
+            .context(error::CollectRecordbatchSnafu)?;
+        inserts.push(build_insert_request(
+            &table_info.name,
+            &batch,
+            semantic_types.clone(),
+        )?);
+    }
+
+    Ok(DecodedUpload {
+        requests: InsertRequests { inserts },
+        rows,
+        bad_rows,
+    })
+}
+
+fn semantic_type(table_info: &TableInfo, column_name: &str) -> SemanticType {
+    let table_schema = &table_info.meta.schema;
+    if table_schema
+        .timestamp_column()
+        .is_some_and(|column| column.name == column_name)
+    {
+        SemanticType::Timestamp
+    } else if table_info
+        .meta
+        .primary_key_indices
+        .iter()
+        .any(|index| table_schema.column_schemas()[*index].name == column_name)
+    {
+        SemanticType::Tag
+    } else {
+        SemanticType::Field
+    }
+}
+
+/// Returns the schema of the table projected to `column_names`.
+fn project_schema<'a>(
+    table_info: &TableInfo,
+    column_names: impl Iterator<Item = &'a str>,
+) -> Result<SchemaRef> {
+    let table_schema = &table_info.meta.schema;
+    let column_schemas = column_names
+        .map(|name| {
+            table_schema
+                .column_schema_by_name(name)
+                .cloned()
+                .with_context(|| InvalidUploadSnafu {
+                    reason: format!("column {name} not found in table {}", table_info.name),
+                })
+        })
+        .collect::<Result<Vec<ColumnSchema>>>()?;
+    ensure!(
+        !column_schemas.is_empty(),
+        InvalidUploadSnafu {
+            reason: "the uploaded file has no column",
+        }
+    );
+
+    Ok(Arc::new(Schema::new(column_schemas)))
+}
+
+fn decode_csv(
+    table_info: &TableInfo,
+    body: &[u8],
+    options: &UploadOptions,
+) -> Result<(SchemaRef, Vec<DfRecordBatch>, usize)> {
+    let (schema, body) = if options.has_header {
+        let end = next_line(body, 0);
+        let header = std::str::from_utf8(&body[..end]).map_err(|e| {
+            InvalidUploadSnafu {
+                reason: format!("invalid header of CSV file: {e}"),
+            }
+            .build()
+        })?;
+        let column_names = header
+            .trim_end_matches(['\r', '\n'])
+            .split(options.delimiter as char)
+            .map(|name| name.trim().trim_matches('"'));
+        (project_schema(table_info, column_names)?, &body[end..])
+    } else {
+        let column_names = table_info
+            .meta
+            .schema
+            .column_schemas()
+            .iter()
+            .map(|column| column.name.as_str());
+        (project_schema(table_info, column_names)?, body)
+    };
+    let arrow_schema = schema.arrow_schema().clone();
+
+    let mut batches = Vec::new();
+    let mut bad_rows = 0;
+    let mut offset = 0;
+    while offset < body.len() {
+        let start = offset;
+        match decode_csv_batch(body, &mut offset, &arrow_schema, options, BATCH_SIZE) {
+            Ok(batch) => {
+                batches.extend(batch);
+                if offset == start {
+                    break;
+                }
+            }
+            Err(_) => {
+                // Decodes rows of the batch one by one to skip bad rows.
+                offset = start;
+                for _ in 0..BATCH_SIZE {
+                    if offset >= body.len() {
+                        break;
+                    }
+                    let row_start = offset;
+                    match decode_csv_batch(body, &mut offset, &arrow_schema, options, 1) {
+                        Ok(batch) => {
+                            batches.extend(batch);
+                            if offset == row_start {
+                                offset = body.len();
+                            }
+                        }
+                        Err(e) => {
+                            bad_rows += 1;
+                            ensure!(
+                                bad_rows <= options.max_bad_rows,
+                                TooManyBadRowsSnafu {
+                                    max_bad_rows: options.max_bad_rows,
+                                    last_error: e.to_string(),
+                                }
+                            );
+                            if offset == row_start {
+                                offset = next_line(body, row_start);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    Ok((schema, batches, bad_rows))
+}
+
+/// Decodes at most `batch_size` rows of the CSV `body` from `offset`, and advances the
+/// `offset` past the consumed rows.
+fn decode_csv_batch(
+    body: &[u8],
+    offset: &mut usize,
+    schema: &ArrowSchemaRef,
+    options: &UploadOptions,
+    batch_size: usize,
+) -> std::result::Result<Option<DfRecordBatch>, ArrowError> {
+    let mut decoder = ReaderBuilder::new(schema.clone())
+        .with_delimiter(options.delimiter)
+        .with_batch_size(batch_size)
+        .build_decoder();
+    let mut consumed = *offset;
+    loop {
+        // Decoding an empty buffer marks the end of the body.
+        let decoded = decoder.decode(&body[consumed..])?;
+        consumed += decoded;
+        if decoded == 0 || decoder.capacity() == 0 {
+            break;
+        }
+    }
+    *offset = consumed;
+    decoder.flush()
+}
+
+/// Returns the offset after the line starting at `start`.
+fn next_line(body: &[u8], start: usize) -> usize {
+    body[start..]
+        .iter()
+        .position(|b| *b == b'\n')
+        .map(|pos| start + pos + 1)
+        .unwrap_or(body.len())
+}
+
+fn decode_parquet(
+    table_info: &TableInfo,
+    body: Bytes,
+) -> Result<(SchemaRef, Vec<DfRecordBatch>, usize)> {
+    let builder = ParquetRecordBatchReaderBuilder::try_new(body).context(ReadParquetSnafu)?;
+    let file_schema = builder.schema().clone();
+    let schema = project_schema(
+        table_info,
+        file_schema
+            .fields()
+            .iter()
+            .map(|field| field.name().as_str()),
+    )?;
+    let arrow_schema = schema.arrow_schema().clone();
+    for (file_field, field) in file_schema.fields().iter().zip(arrow_schema.fields()) {
+        ensure!(
+            can_cast_types(file_field.data_type(), field.data_type()),
+            InvalidUploadSnafu {
+                reason: format!(
+                    "can't cast column {} from {} to {}",
+                    field.name(),
+                    file_field.data_type(),
+                    field.data_type()
+                ),
+            }
+        );
+    }
+
+    let reader = builder
+        .with_batch_size(BATCH_SIZE)
+        .build()
+        .context(ReadParquetSnafu)?;
+    let mut batches = Vec::new();
+    for batch in reader {
+        let batch = batch.context(ConvertUploadSnafu)?;
+        let columns = batch
+            .columns()
+            .iter()
+            .zip(arrow_schema.fields())
+            .map(|(array, field)| cast(array, field.data_type()))
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .context(ConvertUploadSnafu)?;
+        batches.push(
+            DfRecordBatch::try_new(arrow_schema.clone(), columns).context(ConvertUploadSnafu)?,
+        );
+    }
+
+    Ok((schema, batches, 0))
+}
+
+#[cfg(test)]
+mod tests {
+    use api::v1::column::Values;
+    use datatypes::arrow::array::{Float64Array, StringArray, TimestampMillisecondArray};
+    use datatypes::arrow::datatypes::{DataType, Field, Schema as ArrowSchema, TimeUnit};
+    use datatypes::prelude::ConcreteDataType;
+    use parquet::arrow::ArrowWriter;
+    use table::test_util::table_info::test_table_info;
+
+    use super::*;
+
+    fn new_table_info() -> TableInfo {
+        let column_schemas = vec![
+            ColumnSchema::new("host", ConcreteDataType::string_datatype(), true),
+            ColumnSchema::new("cpu", ConcreteDataType::float64_datatype(), true),
+            ColumnSchema::new(
+                "ts",
+                ConcreteDataType::timestamp_millisecond_datatype(),
+                false,
+            )
+            .with_time_index(true),
+        ];
+        let schema = Arc::new(Schema::new(column_schemas));
+        let mut table_info = test_table_info(1, "foo", "public", "greptime", schema);
+        table_info.meta.primary_key_indices = vec![0];
+        table_info
+    }
+
+    #[test]
+    fn test_decode_csv() {
+        let table_info = new_table_info();
+        let body = "ts,host,cpu\n\
+                    1970-01-01T00:00:00.001,a,1.0\n\
+                    1970-01-01T00:00:00.002,b,2.0";
+        let options = UploadOptions::default();
+        let decoded = to_insert_requests(&table_info, Bytes::from(body), &options).unwrap();
+        assert_eq!(2, decoded.rows);
+        assert_eq!(0, decoded.bad_rows);
+
+        let request = &decoded.requests.inserts[0];
+        assert_eq!("foo", request.table_name);
+        assert_eq!(2, request.row_count);
+        let semantic_types = request
+            .columns
+            .iter()
+            .map(|column| (column.column_name.as_str(), column.semantic_type))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec![
+                ("ts", SemanticType::Timestamp as i32),
+                ("host", SemanticType::Tag as i32),
+                ("cpu", SemanticType::Field as i32),
+            ],
+            semantic_types
+        );
+        assert_eq!(
+            Some(Values {
+                ts_millisecond_values: vec![1, 2],
+                ..Default::default()
+            }),
+            request.columns[0].values
+        );
+    }
+
+    #[test]
+    fn test_decode_csv_bad_rows() {
+        let table_info = new_table_info();
+        let body = "a;1.0;1970-01-01T00:00:00.001\n\
+                    b;not a number;1970-01-01T00:00:00.002\n\
+                    c;3.0\n\
+                    d;4.0;1970-01-01T00:00:00.004\n";
+        let mut options = UploadOptions {
+            has_header: false,
+            delimiter: b';',
+            max_bad_rows: 2,
+            ..Default::default()
+        };
+        let decoded = to_insert_requests(&table_info, Bytes::from(body), &options).unwrap();
+        assert_eq!(2, decoded.rows);
+        assert_eq!(2, decoded.bad_rows);
+
+        options.max_bad_rows = 1;
+        let err = to_insert_requests(&table_info, Bytes::from(body), &options).unwrap_err();
+        assert!(
+            matches!(err, error::Error::TooManyBadRows { .. }),
+            "{err:?}"
+        );
+    }
+
+    #[test]
+    fn test_decode_csv_unknown_column() {
+        let table_info = new_table_info();
+        let body = "ts,memory\n1970-01-01T00:00:00.001,1.0\n";
+        let err = to_insert_requests(&table_info, Bytes::from(body), &UploadOptions::default())
+            .unwrap_err();
+        assert!(matches!(err, error::Error::InvalidUpload { .. }), "{err:?}");
+    }
+
+    #[test]
+    fn test_decode_parquet() {
+        let table_info = new_table_info();
+        let schema = Arc::new(ArrowSchema::new(vec![
+            Field::new("host", DataType::Utf8, true),
+            Field::new("cpu", DataType::Float64, true),
+            Field::new(
+                "ts",
+                DataType::Timestamp(TimeUnit::Millisecond, None),
+                false,
+            ),
+        ]));
+        let batch = DfRecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(StringArray::from(vec!["a", "b", "c"])),
+                Arc::new(Float64Array::from(vec![1.0, 2.0, 3.0])),
+                Arc::new(TimestampMillisecondArray::from(vec![1, 2, 3])),
+            ],
+        )
+        .unwrap();
+        let mut body = Vec::new();
+        let mut writer = ArrowWriter::try_new(&mut body, schema, None).unwrap();
+        writer.write(&batch).unwrap();
+        let _ = writer.close().unwrap();
+
+        let options = UploadOptions {
+            format: UploadFormat::Parquet,
+            ..Default::default()
+        };
+        let decoded = to_insert_requests(&table_info, Bytes::from(body), &options).unwrap();
+        assert_eq!(3, decoded.rows);
+        assert_eq!(1, decoded.requests.inserts.len());
+        assert_eq!(3, decoded.requests.inserts[0].columns.len());
+    }
+}
@@ -40,6 +40,7 @@ async fn test_sql_not_provided() {
     let api_state = ApiState {
         sql_handler,
         script_handler: None,
+        query_registry: Default::default(),
     };
 
     for format in ["greptimedb_v1", "influxdb_v1"] {
@@ -89,6 +90,7 @@ async fn test_sql_output_rows() {
     let api_state = ApiState {
         sql_handler,
         script_handler: None,
+        query_registry: Default::default(),
     };
 
     for format in ["greptimedb_v1", "influxdb_v1"] {
@@ -173,6 +175,7 @@ async fn test_sql_form() {
     let api_state = ApiState {
         sql_handler,
         script_handler: None,
+        query_registry: Default::default(),
     };
 
     for format in ["greptimedb_v1", "influxdb_v1"] {
@@ -270,6 +273,7 @@ async fn insert_script(
         State(ApiState {
             sql_handler: sql_handler.clone(),
             script_handler: Some(script_handler.clone()),
+            query_registry: Default::default(),
         }),
         invalid_query,
         body,
@@ -288,6 +292,7 @@ async fn insert_script(
         State(ApiState {
             sql_handler: sql_handler.clone(),
             script_handler: Some(script_handler.clone()),
+            query_registry: Default::default(),
         }),
         exec,
         body,
@@ -321,6 +326,7 @@ def test(n) -> vector[i64]:
         State(ApiState {
             sql_handler,
             script_handler: Some(script_handler),
+            query_registry: Default::default(),
         }),
         exec,
     )
@@ -391,6 +397,7 @@ def test(n, **params)  -> vector[i64]:
         State(ApiState {
             sql_handler,
             script_handler: Some(script_handler),
+            query_registry: Default::default(),
         }),
         exec,
     )
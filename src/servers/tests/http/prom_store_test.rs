@@ -30,7 +30,7 @@ use query::query_engine::DescribeResult;
 use servers::error::{Error, Result};
 use servers::http::{HttpOptions, HttpServerBuilder};
 use servers::prom_store;
-use servers::prom_store::{snappy_compress, Metrics};
+use servers::prom_store::{snappy_compress, Metrics, PromWriteOptions};
 use servers::query_handler::grpc::GrpcQueryHandler;
 use servers::query_handler::sql::SqlQueryHandler;
 use servers::query_handler::{PromStoreProtocolHandler, PromStoreResponse};
@@ -56,7 +56,12 @@ impl GrpcQueryHandler for DummyInstance {
 
 #[async_trait]
 impl PromStoreProtocolHandler for DummyInstance {
-    async fn write(&self, request: WriteRequest, ctx: QueryContextRef) -> Result<()> {
+    async fn write(
+        &self,
+        request: WriteRequest,
+        _options: PromWriteOptions,
+        ctx: QueryContextRef,
+    ) -> Result<()> {
         let _ = self
             .tx
             .send((ctx.current_schema().to_owned(), request.encode_to_vec()))
@@ -245,3 +250,25 @@ async fn test_prometheus_remote_write_read() {
         ReadRequest::decode(&(requests[3].1)[..]).unwrap()
     );
 }
+
+#[tokio::test]
+async fn test_prometheus_remote_write_multi_field_without_table() {
+    let (tx, mut rx) = mpsc::channel(100);
+
+    let app = make_test_app(tx);
+    let client = TestClient::new(app);
+
+    let write_request = WriteRequest {
+        timeseries: prom_store::mock_timeseries(),
+        ..Default::default()
+    };
+
+    // The multi_field layout requires a table
+    let result = client
+        .post("/v1/prometheus/write?layout=multi_field")
+        .body(snappy_compress(&write_request.encode_to_vec()[..]).unwrap())
+        .send()
+        .await;
+    assert_eq!(result.status(), 400);
+    assert!(rx.try_recv().is_err());
+}
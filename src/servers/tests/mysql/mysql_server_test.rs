@@ -30,7 +30,7 @@ use rand::Rng;
 use servers::error::Result;
 use servers::mysql::server::{MysqlServer, MysqlSpawnConfig, MysqlSpawnRef};
 use servers::server::Server;
-use servers::tls::TlsOption;
+use servers::tls::{ReloadableTlsServerConfig, TlsOption};
 use table::test_util::MemTable;
 use table::TableRef;
 
@@ -64,7 +64,7 @@ fn create_mysql_server(table: TableRef, opts: MysqlOpts<'_>) -> Result<Box<dyn S
         Arc::new(MysqlSpawnRef::new(query_handler, Some(Arc::new(provider)))),
         Arc::new(MysqlSpawnConfig::new(
             opts.tls.should_force_tls(),
-            opts.tls.setup()?.map(Arc::new),
+            Arc::new(ReloadableTlsServerConfig::try_new(opts.tls.clone())?),
             opts.reject_no_database,
         )),
     ))
@@ -250,6 +250,7 @@ async fn test_server_required_secure_client_plain() -> Result<()> {
         mode: servers::tls::TlsMode::Require,
         cert_path: "tests/ssl/server.crt".to_owned(),
         key_path: "tests/ssl/server-rsa.key".to_owned(),
+        ..Default::default()
     };
 
     let client_tls = false;
@@ -287,6 +288,7 @@ async fn test_server_required_secure_client_plain_with_pkcs8_priv_key() -> Resul
         mode: servers::tls::TlsMode::Require,
         cert_path: "tests/ssl/server.crt".to_owned(),
         key_path: "tests/ssl/server-pkcs8.key".to_owned(),
+        ..Default::default()
     };
 
     let client_tls = false;
@@ -587,6 +589,7 @@ async fn do_test_query_all_datatypes_with_secure_server(
                 "tests/ssl/server-rsa.key".to_owned()
             }
         },
+        ..Default::default()
     };
 
     do_test_query_all_datatypes(server_tls, client_tls).await
@@ -12,9 +12,11 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::collections::BTreeMap;
 use std::fmt::{Display, Formatter};
 use std::net::SocketAddr;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 use api::v1::region::RegionRequestHeader;
 use arc_swap::ArcSwap;
@@ -23,7 +25,11 @@ use common_catalog::consts::{DEFAULT_CATALOG_NAME, DEFAULT_SCHEMA_NAME};
 use common_catalog::{build_db_string, parse_catalog_and_schema_from_db_string};
 use common_time::TimeZone;
 use derive_builder::Builder;
+use snafu::OptionExt;
 use sql::dialect::{Dialect, GreptimeDbDialect, MySqlDialect, PostgreSqlDialect};
+use tokio_util::sync::CancellationToken;
+
+use crate::error::{InvalidVariableValueSnafu, Result};
 
 pub type QueryContextRef = Arc<QueryContext>;
 pub type ConnInfoRef = Arc<ConnInfo>;
@@ -37,6 +43,16 @@ pub struct QueryContext {
     current_user: ArcSwap<Option<UserInfoRef>>,
     time_zone: Option<TimeZone>,
     sql_dialect: Box<dyn Dialect + Send + Sync>,
+    /// Token to cancel the query.
+    cancellation_token: CancellationToken,
+    /// The query is aborted if it's still running after the deadline.
+    deadline: Option<Instant>,
+    /// Protocol of the client.
+    channel: Channel,
+    /// Address of the client.
+    client_addr: Option<SocketAddr>,
+    /// User agent of the client, e.g. the `User-Agent` header of HTTP.
+    user_agent: Option<String>,
 }
 
 impl Display for QueryContext {
@@ -59,6 +75,11 @@ impl From<&RegionRequestHeader> for QueryContext {
             current_user: Default::default(),
             time_zone: Default::default(),
             sql_dialect: Box::new(GreptimeDbDialect {}),
+            cancellation_token: Default::default(),
+            deadline: None,
+            channel: Channel::Unknown,
+            client_addr: None,
+            user_agent: None,
         }
     }
 }
@@ -128,6 +149,45 @@ impl QueryContext {
     pub fn set_current_user(&self, user: Option<UserInfoRef>) {
         let _ = self.current_user.swap(Arc::new(user));
     }
+
+    /// Returns the token to cancel the query, executions of the query should stop once
+    /// it's cancelled.
+    #[inline]
+    pub fn cancellation_token(&self) -> &CancellationToken {
+        &self.cancellation_token
+    }
+
+    #[inline]
+    pub fn deadline(&self) -> Option<Instant> {
+        self.deadline
+    }
+
+    #[inline]
+    pub fn channel(&self) -> Channel {
+        self.channel
+    }
+
+    #[inline]
+    pub fn client_addr(&self) -> Option<SocketAddr> {
+        self.client_addr
+    }
+
+    #[inline]
+    pub fn user_agent(&self) -> Option<&str> {
+        self.user_agent.as_deref()
+    }
+
+    /// Returns the name of the client, which is the product of the user agent, e.g.
+    /// `Telegraf` of `Telegraf/1.28.0 Go/1.21.0`.
+    ///
+    /// It's bounded to be used as a label of metrics, as user agents may contain versions.
+    pub fn client_name(&self) -> &str {
+        self.user_agent
+            .as_deref()
+            .and_then(|user_agent| user_agent.split(['/', ' ']).next())
+            .filter(|name| !name.is_empty())
+            .unwrap_or("unknown")
+    }
 }
 
 impl QueryContextBuilder {
@@ -146,6 +206,11 @@ impl QueryContextBuilder {
             sql_dialect: self
                 .sql_dialect
                 .unwrap_or_else(|| Box::new(GreptimeDbDialect {})),
+            cancellation_token: self.cancellation_token.unwrap_or_default(),
+            deadline: self.deadline.unwrap_or(None),
+            channel: self.channel.unwrap_or(Channel::Unknown),
+            client_addr: self.client_addr.unwrap_or(None),
+            user_agent: self.user_agent.unwrap_or(None),
         })
     }
 }
@@ -179,10 +244,14 @@ impl ConnInfo {
     }
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Channel {
     Mysql,
     Postgres,
+    Http,
+    Grpc,
+    /// Queries not from clients, e.g. internal queries.
+    Unknown,
 }
 
 impl Channel {
@@ -190,16 +259,133 @@ impl Channel {
         match self {
             Channel::Mysql => Box::new(MySqlDialect {}),
             Channel::Postgres => Box::new(PostgreSqlDialect {}),
+            Channel::Http | Channel::Grpc | Channel::Unknown => Box::new(GreptimeDbDialect {}),
+        }
+    }
+
+    /// Returns the channel whose [Channel::as_str] is `name`, or [Channel::Unknown] if there
+    /// is no such channel.
+    pub fn from_name(name: &str) -> Self {
+        match name {
+            "mysql" => Channel::Mysql,
+            "postgres" => Channel::Postgres,
+            "http" => Channel::Http,
+            "grpc" => Channel::Grpc,
+            _ => Channel::Unknown,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Channel::Mysql => "mysql",
+            Channel::Postgres => "postgres",
+            Channel::Http => "http",
+            Channel::Grpc => "grpc",
+            Channel::Unknown => "unknown",
         }
     }
 }
 
 impl Display for Channel {
     fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
-        match self {
-            Channel::Mysql => write!(f, "mysql"),
-            Channel::Postgres => write!(f, "postgres"),
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// Variables of a session, set by statements like `SET time_zone = '+08:00'`.
+///
+/// Variables the database understands are validated and kept typed, others are
+/// only recorded so `SHOW VARIABLES` and `SELECT @@name` return what clients set.
+#[derive(Debug, Clone, Default)]
+pub struct SessionVariables {
+    time_zone: Option<TimeZone>,
+    /// Timeout of each statement in the session.
+    statement_timeout: Option<Duration>,
+    others: BTreeMap<String, String>,
+}
+
+impl SessionVariables {
+    pub const TIME_ZONE: &'static str = "time_zone";
+    /// MySQL's statement timeout, in milliseconds.
+    pub const MAX_EXECUTION_TIME: &'static str = "max_execution_time";
+
+    /// Normalizes a variable name like `@@SESSION.time_zone` to `time_zone`.
+    pub fn normalize_name(name: &str) -> String {
+        let name = name.trim().to_lowercase();
+        let name = name.trim_start_matches("@@");
+        let name = name
+            .strip_prefix("session.")
+            .or_else(|| name.strip_prefix("local."))
+            .unwrap_or(name);
+        name.to_string()
+    }
+
+    pub fn time_zone(&self) -> Option<TimeZone> {
+        self.time_zone.clone()
+    }
+
+    pub fn set_time_zone(&mut self, time_zone: Option<TimeZone>) {
+        self.time_zone = time_zone;
+    }
+
+    pub fn statement_timeout(&self) -> Option<Duration> {
+        self.statement_timeout
+    }
+
+    pub fn set_statement_timeout(&mut self, timeout: Option<Duration>) {
+        self.statement_timeout = timeout;
+    }
+
+    /// Sets the variable `name` to `value`, quotes around the value are ignored.
+    pub fn set(&mut self, name: &str, value: &str) -> Result<()> {
+        let name = Self::normalize_name(name);
+        let value = value.trim().trim_matches(|c| c == '\'' || c == '"');
+        match name.as_str() {
+            Self::TIME_ZONE => {
+                self.time_zone = TimeZone::from_tz_string(value)
+                    .ok()
+                    .context(InvalidVariableValueSnafu { name: &name, value })?;
+            }
+            Self::MAX_EXECUTION_TIME => {
+                let millis = value
+                    .parse::<u64>()
+                    .ok()
+                    .context(InvalidVariableValueSnafu { name: &name, value })?;
+                self.statement_timeout = (millis > 0).then_some(Duration::from_millis(millis));
+            }
+            _ => {
+                let _ = self.others.insert(name, value.to_string());
+            }
         }
+        Ok(())
+    }
+
+    /// Returns the value of the variable `name`, or `None` if it's never set.
+    pub fn get(&self, name: &str) -> Option<String> {
+        let name = Self::normalize_name(name);
+        match name.as_str() {
+            Self::TIME_ZONE => self.time_zone.as_ref().map(|tz| tz.to_string()),
+            Self::MAX_EXECUTION_TIME => self
+                .statement_timeout
+                .map(|timeout| timeout.as_millis().to_string()),
+            _ => self.others.get(&name).cloned(),
+        }
+    }
+
+    /// Returns all variables set in the session, ordered by their names.
+    pub fn list(&self) -> Vec<(String, String)> {
+        let mut variables = self
+            .others
+            .iter()
+            .map(|(name, value)| (name.clone(), value.clone()))
+            .collect::<Vec<_>>();
+        for name in [Self::TIME_ZONE, Self::MAX_EXECUTION_TIME] {
+            if let Some(value) = self.get(name) {
+                variables.push((name.to_string(), value));
+            }
+        }
+        variables.sort();
+        variables
     }
 }
 
@@ -234,4 +420,58 @@ mod test {
         let context = QueryContext::with(DEFAULT_CATALOG_NAME, "test");
         assert_eq!("test", context.get_db_string());
     }
+
+    #[test]
+    fn test_client_name() {
+        let context = QueryContext::arc();
+        assert_eq!(Channel::Unknown, context.channel());
+        assert_eq!("unknown", context.client_name());
+
+        for (user_agent, name) in [
+            ("Telegraf/1.28.0 Go/1.21.0", "Telegraf"),
+            ("Prometheus/2.48.0", "Prometheus"),
+            ("curl", "curl"),
+            ("", "unknown"),
+        ] {
+            let context = QueryContextBuilder::default()
+                .channel(Channel::Http)
+                .user_agent(Some(user_agent.to_string()))
+                .build();
+            assert_eq!(name, context.client_name());
+        }
+    }
+
+    #[test]
+    fn test_session_variables() {
+        let session = Session::new(None, Channel::Mysql);
+        session.set_variable("time_zone", "'+08:00'").unwrap();
+        session
+            .set_variable("@@SESSION.max_execution_time", "1500")
+            .unwrap();
+        session.set_variable("autocommit", "1").unwrap();
+        assert!(session.set_variable("time_zone", "Mars/Olympus").is_err());
+        assert!(session.set_variable("max_execution_time", "soon").is_err());
+
+        assert_eq!("+08:00", session.time_zone().unwrap().to_string());
+        assert_eq!(
+            Some(Duration::from_millis(1500)),
+            session.statement_timeout()
+        );
+        assert_eq!(Some("1".to_string()), session.variable("@@autocommit"));
+        assert_eq!(
+            vec![
+                ("autocommit".to_string(), "1".to_string()),
+                ("max_execution_time".to_string(), "1500".to_string()),
+                ("time_zone".to_string(), "+08:00".to_string()),
+            ],
+            session.variables()
+        );
+
+        let query_ctx = session.new_query_context();
+        assert_eq!("+08:00", query_ctx.time_zone().unwrap().to_string());
+        assert!(query_ctx.deadline().is_some());
+
+        session.set_variable("max_execution_time", "0").unwrap();
+        assert!(session.new_query_context().deadline().is_none());
+    }
 }
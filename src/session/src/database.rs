@@ -0,0 +1,193 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Resolves the database selected by a request.
+//!
+//! Protocols select the database in different ways, e.g. the `db` parameter or header of
+//! HTTP, the database of MySQL and PostgreSQL connections. All of them are resolved by
+//! [resolve_database] so a missing or unauthorized database is reported by the same error.
+
+use std::time::Duration;
+
+use async_trait::async_trait;
+use auth::{UserInfoRef, UserProviderRef};
+use common_catalog::consts::DEFAULT_SCHEMA_NAME;
+use common_catalog::{build_db_string, parse_catalog_and_schema_from_db_string};
+use common_error::ext::BoxedError;
+use moka::future::Cache;
+use snafu::{ensure, ResultExt};
+
+use crate::error::{CheckDatabaseSnafu, DatabaseAccessDeniedSnafu, DatabaseNotFoundSnafu, Result};
+
+/// Max number of databases in a [SchemaCache].
+const SCHEMA_CACHE_CAPACITY: u64 = 1024;
+/// Time to live of databases in a [SchemaCache].
+pub const SCHEMA_CACHE_TTL: Duration = Duration::from_secs(30);
+
+/// Checks whether a database exists.
+#[async_trait]
+pub trait SchemaChecker: Send + Sync {
+    async fn is_valid_schema(
+        &self,
+        catalog: &str,
+        schema: &str,
+    ) -> std::result::Result<bool, BoxedError>;
+}
+
+/// Parses catalog and schema of the `db`, the default schema is selected if it's `None`.
+pub fn parse_database(db: Option<&str>) -> (&str, &str) {
+    parse_catalog_and_schema_from_db_string(db.unwrap_or(DEFAULT_SCHEMA_NAME))
+}
+
+/// Resolves catalog and schema of the `db`.
+///
+/// Returns an error if the database doesn't exist or the user isn't authorized to access
+/// it. The authorization is skipped if there is no `user_provider` or `user_info`.
+pub async fn resolve_database<C: SchemaChecker + ?Sized>(
+    checker: &C,
+    user_provider: Option<&UserProviderRef>,
+    db: Option<&str>,
+    user_info: Option<&UserInfoRef>,
+) -> Result<(String, String)> {
+    let (catalog, schema) = parse_database(db);
+    let db = build_db_string(catalog, schema);
+    let valid = checker
+        .is_valid_schema(catalog, schema)
+        .await
+        .context(CheckDatabaseSnafu { db: &db })?;
+    ensure!(valid, DatabaseNotFoundSnafu { db: &db });
+
+    if let (Some(user_provider), Some(user_info)) = (user_provider, user_info) {
+        user_provider
+            .authorize(catalog, schema, user_info)
+            .await
+            .context(DatabaseAccessDeniedSnafu { db: &db })?;
+    }
+
+    Ok((catalog.to_string(), schema.to_string()))
+}
+
+/// Caches existing databases to avoid checking them for each request.
+///
+/// Missing databases aren't cached so a newly created database is visible at once.
+/// A dropped database is still treated as existing until it expires.
+pub struct SchemaCache {
+    cache: Cache<(String, String), ()>,
+}
+
+impl Default for SchemaCache {
+    fn default() -> Self {
+        Self::new(SCHEMA_CACHE_TTL)
+    }
+}
+
+impl SchemaCache {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            cache: Cache::builder()
+                .max_capacity(SCHEMA_CACHE_CAPACITY)
+                .time_to_live(ttl)
+                .build(),
+        }
+    }
+
+    /// Returns whether the database exists, checks it by `checker` if it isn't cached.
+    pub async fn is_valid_schema<C: SchemaChecker + ?Sized>(
+        &self,
+        checker: &C,
+        catalog: &str,
+        schema: &str,
+    ) -> std::result::Result<bool, BoxedError> {
+        let key = (catalog.to_string(), schema.to_string());
+        if self.cache.contains_key(&key) {
+            return Ok(true);
+        }
+
+        let valid = checker.is_valid_schema(catalog, schema).await?;
+        if valid {
+            self.cache.insert(key, ()).await;
+        }
+        Ok(valid)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use common_catalog::consts::DEFAULT_CATALOG_NAME;
+    use common_error::ext::ErrorExt;
+    use common_error::status_code::StatusCode;
+
+    use super::*;
+
+    #[derive(Default)]
+    struct MockChecker {
+        checks: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl SchemaChecker for MockChecker {
+        async fn is_valid_schema(
+            &self,
+            _catalog: &str,
+            schema: &str,
+        ) -> std::result::Result<bool, BoxedError> {
+            let _ = self.checks.fetch_add(1, Ordering::Relaxed);
+            Ok(schema == "public")
+        }
+    }
+
+    #[test]
+    fn test_parse_database() {
+        assert_eq!(
+            (DEFAULT_CATALOG_NAME, DEFAULT_SCHEMA_NAME),
+            parse_database(None)
+        );
+        assert_eq!(
+            ("catalog", "schema"),
+            parse_database(Some("catalog-schema"))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_resolve_database() {
+        let checker = MockChecker::default();
+        let (catalog, schema) = resolve_database(&checker, None, None, None).await.unwrap();
+        assert_eq!((DEFAULT_CATALOG_NAME, "public"), (&*catalog, &*schema));
+
+        let err = resolve_database(&checker, None, Some("missing"), None)
+            .await
+            .unwrap_err();
+        assert_eq!(StatusCode::DatabaseNotFound, err.status_code());
+    }
+
+    #[tokio::test]
+    async fn test_schema_cache() {
+        let checker = MockChecker::default();
+        let cache = SchemaCache::default();
+        for _ in 0..2 {
+            assert!(cache
+                .is_valid_schema(&checker, DEFAULT_CATALOG_NAME, "public")
+                .await
+                .unwrap());
+            assert!(!cache
+                .is_valid_schema(&checker, DEFAULT_CATALOG_NAME, "missing")
+                .await
+                .unwrap());
+        }
+        // Only missing databases are checked again.
+        assert_eq!(3, checker.checks.load(Ordering::Relaxed));
+    }
+}
@@ -0,0 +1,66 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::any::Any;
+
+use common_error::ext::{BoxedError, ErrorExt};
+use common_error::status_code::StatusCode;
+use common_macro::stack_trace_debug;
+use snafu::{Location, Snafu};
+
+#[derive(Snafu)]
+#[snafu(visibility(pub))]
+#[stack_trace_debug]
+pub enum Error {
+    #[snafu(display("Database not found: {}", db))]
+    DatabaseNotFound { db: String, location: Location },
+
+    #[snafu(display("Access denied to database: {}", db))]
+    DatabaseAccessDenied {
+        db: String,
+        location: Location,
+        source: auth::error::Error,
+    },
+
+    #[snafu(display("Failed to check database: {}", db))]
+    CheckDatabase {
+        db: String,
+        location: Location,
+        source: BoxedError,
+    },
+
+    #[snafu(display("Invalid value '{}' for variable '{}'", value, name))]
+    InvalidVariableValue {
+        name: String,
+        value: String,
+        location: Location,
+    },
+}
+
+impl ErrorExt for Error {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            Error::DatabaseNotFound { .. } => StatusCode::DatabaseNotFound,
+            Error::DatabaseAccessDenied { source, .. } => source.status_code(),
+            Error::CheckDatabase { source, .. } => source.status_code(),
+            Error::InvalidVariableValue { .. } => StatusCode::InvalidArguments,
+        }
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
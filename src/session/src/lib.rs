@@ -13,9 +13,12 @@
 // limitations under the License.
 
 pub mod context;
+pub mod database;
+pub mod error;
 
 use std::net::SocketAddr;
-use std::sync::Arc;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
 
 use arc_swap::ArcSwap;
 use auth::UserInfoRef;
@@ -24,7 +27,7 @@ use common_catalog::consts::{DEFAULT_CATALOG_NAME, DEFAULT_SCHEMA_NAME};
 use common_time::TimeZone;
 use context::QueryContextBuilder;
 
-use crate::context::{Channel, ConnInfo, QueryContextRef};
+use crate::context::{Channel, ConnInfo, QueryContextRef, SessionVariables};
 
 /// Session for persistent connection such as MySQL, PostgreSQL etc.
 #[derive(Debug)]
@@ -33,7 +36,7 @@ pub struct Session {
     schema: ArcSwap<String>,
     user_info: ArcSwap<UserInfoRef>,
     conn_info: ConnInfo,
-    time_zone: ArcSwap<Option<TimeZone>>,
+    variables: RwLock<SessionVariables>,
 }
 
 pub type SessionRef = Arc<Session>;
@@ -45,12 +48,13 @@ impl Session {
             schema: ArcSwap::new(Arc::new(DEFAULT_SCHEMA_NAME.into())),
             user_info: ArcSwap::new(Arc::new(auth::userinfo_by_name(None))),
             conn_info: ConnInfo::new(addr, channel),
-            time_zone: ArcSwap::new(Arc::new(None)),
+            variables: RwLock::new(SessionVariables::default()),
         }
     }
 
     #[inline]
     pub fn new_query_context(&self) -> QueryContextRef {
+        let variables = self.variables.read().unwrap();
         QueryContextBuilder::default()
             .current_user(ArcSwap::new(Arc::new(Some(
                 self.user_info.load().as_ref().clone(),
@@ -58,7 +62,14 @@ impl Session {
             .current_catalog(self.catalog.load().to_string())
             .current_schema(self.schema.load().to_string())
             .sql_dialect(self.conn_info.channel.dialect())
-            .time_zone((**self.time_zone.load()).clone())
+            .channel(self.conn_info.channel)
+            .client_addr(self.conn_info.client_addr)
+            .time_zone(variables.time_zone())
+            .deadline(
+                variables
+                    .statement_timeout()
+                    .map(|timeout| Instant::now() + timeout),
+            )
             .build()
     }
 
@@ -74,12 +85,40 @@ impl Session {
 
     #[inline]
     pub fn time_zone(&self) -> Option<TimeZone> {
-        self.time_zone.load().as_ref().clone()
+        self.variables.read().unwrap().time_zone()
     }
 
     #[inline]
     pub fn set_time_zone(&self, tz: Option<TimeZone>) {
-        let _ = self.time_zone.swap(Arc::new(tz));
+        self.variables.write().unwrap().set_time_zone(tz);
+    }
+
+    #[inline]
+    pub fn statement_timeout(&self) -> Option<Duration> {
+        self.variables.read().unwrap().statement_timeout()
+    }
+
+    #[inline]
+    pub fn set_statement_timeout(&self, timeout: Option<Duration>) {
+        self.variables
+            .write()
+            .unwrap()
+            .set_statement_timeout(timeout);
+    }
+
+    /// Sets the session variable `name` to `value`, see [SessionVariables::set].
+    pub fn set_variable(&self, name: &str, value: &str) -> error::Result<()> {
+        self.variables.write().unwrap().set(name, value)
+    }
+
+    /// Returns the value of the session variable `name`.
+    pub fn variable(&self, name: &str) -> Option<String> {
+        self.variables.read().unwrap().get(name)
+    }
+
+    /// Returns all variables set in the session, ordered by their names.
+    pub fn variables(&self) -> Vec<(String, String)> {
+        self.variables.read().unwrap().list()
     }
 
     #[inline]
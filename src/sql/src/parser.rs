@@ -21,7 +21,7 @@ use sqlparser::tokenizer::{Token, TokenWithLocation};
 
 use crate::ast::{Expr, ObjectName};
 use crate::error::{self, Result, SyntaxSnafu};
-use crate::parsers::tql_parser;
+use crate::parsers::{admin_parser, tql_parser};
 use crate::statements::statement::Statement;
 use crate::statements::transform_statements;
 
@@ -123,6 +123,13 @@ impl<'a> ParserContext<'a> {
                         self.parse_tql()
                     }
 
+                    Keyword::NoKeyword
+                        if w.value.to_uppercase() == admin_parser::ADMIN
+                            && w.quote_style.is_none() =>
+                    {
+                        self.parse_admin()
+                    }
+
                     // todo(hl) support more statements.
                     _ => self.unsupported(self.peek_token_as_string()),
                 }
@@ -0,0 +1,165 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use snafu::ResultExt;
+use sqlparser::ast::{Expr, Value};
+use sqlparser::tokenizer::Token;
+
+use crate::error::{self, Result};
+use crate::parser::ParserContext;
+use crate::statements::admin::Admin;
+use crate::statements::statement::Statement;
+
+pub const ADMIN: &str = "ADMIN";
+const PROCEDURE_STATE: &str = "PROCEDURE_STATE";
+const CAPTURE_QUERY: &str = "CAPTURE_QUERY";
+
+/// `ADMIN` extension parser, including:
+/// - `ADMIN procedure_state('<procedure_id>')`
+/// - `ADMIN capture_query('<sql>', '<location>')`
+impl<'a> ParserContext<'a> {
+    pub(crate) fn parse_admin(&mut self) -> Result<Statement> {
+        let _ = self.parser.next_token();
+
+        match self.parser.peek_token().token {
+            Token::Word(w) if w.value.to_uppercase() == PROCEDURE_STATE => {
+                let _ = self.parser.next_token();
+                self.parse_procedure_state()
+            }
+            Token::Word(w) if w.value.to_uppercase() == CAPTURE_QUERY => {
+                let _ = self.parser.next_token();
+                self.parse_capture_query()
+            }
+            unexpected => self.unsupported(unexpected.to_string()),
+        }
+    }
+
+    fn parse_procedure_state(&mut self) -> Result<Statement> {
+        self.parser
+            .expect_token(&Token::LParen)
+            .context(error::SyntaxSnafu)?;
+        let arg = self.parser.parse_expr().context(error::SyntaxSnafu)?;
+        let Expr::Value(Value::SingleQuotedString(pid) | Value::DoubleQuotedString(pid)) = arg
+        else {
+            return error::InvalidSqlSnafu {
+                msg: format!("expect a procedure id string, actual: {arg}"),
+            }
+            .fail();
+        };
+        self.parser
+            .expect_token(&Token::RParen)
+            .context(error::SyntaxSnafu)?;
+
+        Ok(Statement::Admin(Admin::ProcedureState(pid)))
+    }
+
+    fn parse_capture_query(&mut self) -> Result<Statement> {
+        self.parser
+            .expect_token(&Token::LParen)
+            .context(error::SyntaxSnafu)?;
+        let query = self.parse_string_arg("query")?;
+        self.parser
+            .expect_token(&Token::Comma)
+            .context(error::SyntaxSnafu)?;
+        let location = self.parse_string_arg("location")?;
+        self.parser
+            .expect_token(&Token::RParen)
+            .context(error::SyntaxSnafu)?;
+
+        Ok(Statement::Admin(Admin::CaptureQuery { query, location }))
+    }
+
+    fn parse_string_arg(&mut self, name: &str) -> Result<String> {
+        let arg = self.parser.parse_expr().context(error::SyntaxSnafu)?;
+        let Expr::Value(Value::SingleQuotedString(value) | Value::DoubleQuotedString(value)) = arg
+        else {
+            return error::InvalidSqlSnafu {
+                msg: format!("expect a {name} string, actual: {arg}"),
+            }
+            .fail();
+        };
+        Ok(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dialect::GreptimeDbDialect;
+
+    #[test]
+    fn test_parse_admin_procedure_state() {
+        let sql = "ADMIN procedure_state('2f4d6bd4-5a9f-4e1c-9d4b-3b3b5c0e6d7a')";
+        let mut stmts = ParserContext::create_with_dialect(sql, &GreptimeDbDialect {}).unwrap();
+        assert_eq!(1, stmts.len());
+        let stmt = stmts.pop().unwrap();
+        assert_eq!(
+            stmt,
+            Statement::Admin(Admin::ProcedureState(
+                "2f4d6bd4-5a9f-4e1c-9d4b-3b3b5c0e6d7a".to_string()
+            ))
+        );
+        assert_eq!(
+            "ADMIN procedure_state('2f4d6bd4-5a9f-4e1c-9d4b-3b3b5c0e6d7a')",
+            stmt_to_string(&stmt)
+        );
+
+        let sql = "admin PROCEDURE_STATE(\"some_id\");";
+        let stmts = ParserContext::create_with_dialect(sql, &GreptimeDbDialect {}).unwrap();
+        assert_eq!(
+            stmts,
+            vec![Statement::Admin(Admin::ProcedureState(
+                "some_id".to_string()
+            ))]
+        );
+    }
+
+    #[test]
+    fn test_parse_admin_capture_query() {
+        let sql = "ADMIN capture_query('SELECT * FROM foo WHERE host = ''a''', '/tmp/capture')";
+        let mut stmts = ParserContext::create_with_dialect(sql, &GreptimeDbDialect {}).unwrap();
+        assert_eq!(1, stmts.len());
+        let stmt = stmts.pop().unwrap();
+        assert_eq!(
+            stmt,
+            Statement::Admin(Admin::CaptureQuery {
+                query: "SELECT * FROM foo WHERE host = 'a'".to_string(),
+                location: "/tmp/capture".to_string(),
+            })
+        );
+        assert_eq!(sql, stmt_to_string(&stmt));
+
+        let sql = "ADMIN capture_query('SELECT 1')";
+        assert!(ParserContext::create_with_dialect(sql, &GreptimeDbDialect {}).is_err());
+    }
+
+    #[test]
+    fn test_parse_admin_invalid() {
+        let sql = "ADMIN procedure_state(1)";
+        assert!(ParserContext::create_with_dialect(sql, &GreptimeDbDialect {}).is_err());
+
+        let sql = "ADMIN procedure_state('id'";
+        assert!(ParserContext::create_with_dialect(sql, &GreptimeDbDialect {}).is_err());
+
+        let sql = "ADMIN flush_table('foo')";
+        assert!(ParserContext::create_with_dialect(sql, &GreptimeDbDialect {}).is_err());
+    }
+
+    fn stmt_to_string(stmt: &Statement) -> String {
+        match stmt {
+            Statement::Admin(admin) => admin.to_string(),
+            _ => unreachable!(),
+        }
+    }
+}
@@ -15,6 +15,7 @@
 use common_query::AddColumnLocation;
 use snafu::ResultExt;
 use sqlparser::keywords::Keyword;
+use sqlparser::parser::IsOptional::Mandatory;
 use sqlparser::parser::ParserError;
 use sqlparser::tokenizer::Token;
 
@@ -73,6 +74,17 @@ impl<'a> ParserContext<'a> {
                     parser.peek_token()
                 )));
             }
+        } else if let Token::Word(word) = parser.peek_token().token
+            && word.value.to_ascii_uppercase() == "MODIFY"
+        {
+            let _ = parser.next_token();
+            parser.expect_keywords(&[Keyword::PRIMARY, Keyword::KEY])?;
+            let columns = parser
+                .parse_parenthesized_column_list(Mandatory, false)?
+                .into_iter()
+                .map(Self::canonicalize_identifier)
+                .collect();
+            AlterTableOperation::ModifyPrimaryKey { columns }
         } else if parser.parse_keyword(Keyword::RENAME) {
             let new_table_name_obj_raw = parser.parse_object_name()?;
             let new_table_name_obj = Self::canonicalize_object_name(new_table_name_obj_raw);
@@ -242,6 +254,33 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_parse_alter_modify_primary_key() {
+        let sql = "ALTER TABLE my_metric_1 MODIFY PRIMARY KEY ()";
+        ParserContext::create_with_dialect(sql, &GreptimeDbDialect {}).unwrap_err();
+
+        let sql = "ALTER TABLE my_metric_1 MODIFY PRIMARY KEY (b, A)";
+        let mut result = ParserContext::create_with_dialect(sql, &GreptimeDbDialect {}).unwrap();
+        assert_eq!(1, result.len());
+
+        let statement = result.remove(0);
+        match statement {
+            Statement::Alter(alter_table) => {
+                assert_eq!("my_metric_1", alter_table.table_name().0[0].value);
+
+                let alter_operation = alter_table.alter_operation();
+                match alter_operation {
+                    AlterTableOperation::ModifyPrimaryKey { columns } => {
+                        let names: Vec<_> = columns.iter().map(|c| c.value.as_str()).collect();
+                        assert_eq!(vec!["b", "a"], names);
+                    }
+                    _ => unreachable!(),
+                }
+            }
+            _ => unreachable!(),
+        }
+    }
+
     #[test]
     fn test_parse_alter_rename_table() {
         let sql = "ALTER TABLE test_table table_t";
@@ -17,11 +17,15 @@ use std::collections::HashMap;
 use snafu::ResultExt;
 use sqlparser::ast::ObjectName;
 use sqlparser::keywords::Keyword;
+use sqlparser::tokenizer::Token;
 use sqlparser::tokenizer::Token::Word;
 
 use crate::error::{self, Result};
 use crate::parser::ParserContext;
-use crate::statements::copy::{CopyDatabaseArgument, CopyTable, CopyTableArgument};
+use crate::statements::copy::{
+    CopyDatabaseArgument, CopyQueryToArgument, CopyTable, CopyTableArgument,
+};
+use crate::statements::query::Query;
 use crate::statements::statement::Statement;
 use crate::util::parse_option_string;
 
@@ -29,16 +33,20 @@ pub type With = HashMap<String, String>;
 pub type Connection = HashMap<String, String>;
 
 // COPY tbl TO 'output.parquet';
+// COPY (SELECT * FROM tbl) TO 'output/';
 impl<'a> ParserContext<'a> {
     pub(crate) fn parse_copy(&mut self) -> Result<Statement> {
         let _ = self.parser.next_token();
         let next = self.parser.peek_token();
-        let copy = if let Word(word) = next.token
+        let copy = if let Word(word) = &next.token
             && word.keyword == Keyword::DATABASE
         {
             let _ = self.parser.next_token();
             let copy_database = self.parser_copy_database()?;
             crate::statements::copy::Copy::CopyDatabase(copy_database)
+        } else if next.token == Token::LParen {
+            let copy_query_to = self.parse_copy_query_to()?;
+            crate::statements::copy::Copy::CopyQueryTo(copy_query_to)
         } else {
             let copy_table = self.parse_copy_table()?;
             crate::statements::copy::Copy::CopyTable(copy_table)
@@ -70,6 +78,27 @@ impl<'a> ParserContext<'a> {
         })
     }
 
+    fn parse_copy_query_to(&mut self) -> Result<CopyQueryToArgument> {
+        self.parser
+            .expect_token(&Token::LParen)
+            .context(error::SyntaxSnafu)?;
+        let query = self.parser.parse_query().context(error::SyntaxSnafu)?;
+        self.parser
+            .expect_token(&Token::RParen)
+            .context(error::SyntaxSnafu)?;
+        self.parser
+            .expect_keyword(Keyword::TO)
+            .context(error::SyntaxSnafu)?;
+
+        let (with, connection, location) = self.parse_copy_to()?;
+        Ok(CopyQueryToArgument {
+            query: Box::new(Query::try_from(query)?),
+            with: with.into(),
+            connection: connection.into(),
+            location,
+        })
+    }
+
     fn parse_copy_table(&mut self) -> Result<CopyTable> {
         let raw_table_name =
             self.parser
@@ -364,6 +393,39 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_parse_copy_query_to() {
+        let sql = "COPY (SELECT * FROM tbl WHERE ts > 0) TO 's3://bucket/dir/' WITH (FORMAT = 'parquet', PARTITION_BY = 'date') CONNECTION (REGION = 'us-west-2')";
+        let stmt = ParserContext::create_with_dialect(sql, &GreptimeDbDialect {})
+            .unwrap()
+            .pop()
+            .unwrap();
+
+        let Copy(crate::statements::copy::Copy::CopyQueryTo(stmt)) = stmt else {
+            unreachable!()
+        };
+        assert_eq!("SELECT * FROM tbl WHERE ts > 0", stmt.query.to_string());
+        assert_eq!("s3://bucket/dir/", stmt.location);
+        assert_eq!(
+            [
+                ("format".to_string(), "parquet".to_string()),
+                ("partition_by".to_string(), "date".to_string())
+            ]
+            .into_iter()
+            .collect::<HashMap<_, _>>(),
+            stmt.with.map
+        );
+        assert_eq!(
+            [("region".to_string(), "us-west-2".to_string())]
+                .into_iter()
+                .collect::<HashMap<_, _>>(),
+            stmt.connection.map
+        );
+
+        let sql = "COPY (SELECT * FROM tbl) 'dir/'";
+        assert!(ParserContext::create_with_dialect(sql, &GreptimeDbDialect {}).is_err());
+    }
+
     #[test]
     fn test_copy_database_to() {
         let sql = "COPY DATABASE catalog0.schema0 TO 'tbl_file.parquet' WITH (FORMAT = 'parquet') CONNECTION (FOO='Bar', ONE='two')";
@@ -34,16 +34,20 @@ use crate::error::{
 };
 use crate::parser::ParserContext;
 use crate::statements::create::{
-    CreateDatabase, CreateExternalTable, CreateTable, PartitionEntry, Partitions, TIME_INDEX,
+    CreateDatabase, CreateExternalTable, CreateFunction, CreatePipeline, CreateTable,
+    PartitionEntry, Partitions, TIME_INDEX,
 };
 use crate::statements::statement::Statement;
 use crate::statements::{
     get_data_type_by_alias_name, sql_data_type_to_concrete_data_type, sql_value_to_value,
 };
-use crate::util::parse_option_string;
+use crate::util::{parse_option_string, to_lowercase_options_map};
 
 pub const ENGINE: &str = "ENGINE";
 pub const MAXVALUE: &str = "MAXVALUE";
+pub const PIPELINE: &str = "PIPELINE";
+pub const SOURCE: &str = "SOURCE";
+pub const SINK: &str = "SINK";
 
 static LESS: Lazy<Token> = Lazy::new(|| Token::make_keyword("LESS"));
 static THAN: Lazy<Token> = Lazy::new(|| Token::make_keyword("THAN"));
@@ -59,6 +63,12 @@ impl<'a> ParserContext<'a> {
 
                 Keyword::EXTERNAL => self.parse_create_external_table(),
 
+                Keyword::FUNCTION => self.parse_create_function(),
+
+                _ if w.value.to_uppercase() == PIPELINE && w.quote_style.is_none() => {
+                    self.parse_create_pipeline()
+                }
+
                 _ => self.unsupported(w.to_string()),
             },
             unexpected => self.unsupported(unexpected.to_string()),
@@ -115,6 +125,154 @@ impl<'a> ParserContext<'a> {
         }))
     }
 
+    /// Parses `CREATE PIPELINE [IF NOT EXISTS] <name> SOURCE <type>(<options>) [SINK <table>]
+    /// [WITH (<options>)]`.
+    fn parse_create_pipeline(&mut self) -> Result<Statement> {
+        let _ = self.parser.next_token();
+        let if_not_exists =
+            self.parser
+                .parse_keywords(&[Keyword::IF, Keyword::NOT, Keyword::EXISTS]);
+        let raw_pipeline_name =
+            self.parser
+                .parse_object_name()
+                .context(error::UnexpectedSnafu {
+                    sql: self.sql,
+                    expected: "a pipeline name",
+                    actual: self.peek_token_as_string(),
+                })?;
+        let name = Self::canonicalize_object_name(raw_pipeline_name);
+
+        if !self.consume_token(SOURCE) {
+            return self.expected(SOURCE, self.parser.peek_token());
+        }
+        let source_type = self
+            .parser
+            .parse_identifier()
+            .context(error::SyntaxSnafu)?
+            .value
+            .to_lowercase();
+        self.parser
+            .expect_token(&Token::LParen)
+            .context(error::SyntaxSnafu)?;
+        let source_options = if self.parser.consume_token(&Token::RParen) {
+            vec![]
+        } else {
+            let options = self
+                .parser
+                .parse_comma_separated(|p| p.parse_sql_option())
+                .context(error::SyntaxSnafu)?;
+            self.parser
+                .expect_token(&Token::RParen)
+                .context(error::SyntaxSnafu)?;
+            options
+        };
+
+        let sink_table = if self.consume_token(SINK) {
+            let raw_table_name =
+                self.parser
+                    .parse_object_name()
+                    .context(error::UnexpectedSnafu {
+                        sql: self.sql,
+                        expected: "a table name",
+                        actual: self.peek_token_as_string(),
+                    })?;
+            Some(Self::canonicalize_object_name(raw_table_name))
+        } else {
+            None
+        };
+
+        let options = self
+            .parser
+            .parse_options(Keyword::WITH)
+            .context(error::SyntaxSnafu)?;
+
+        Ok(Statement::CreatePipeline(CreatePipeline {
+            name,
+            if_not_exists,
+            source_type,
+            source_options: to_lowercase_options_map(&source_options).into(),
+            sink_table,
+            options: to_lowercase_options_map(&options).into(),
+        }))
+    }
+
+    /// Parses `CREATE FUNCTION <name>([<type>, ...]) RETURNS <type> LANGUAGE <language>
+    /// AS '<location>' [WITH (<options>)] [CONNECTION (<options>)]`.
+    fn parse_create_function(&mut self) -> Result<Statement> {
+        let _ = self.parser.next_token();
+        let raw_function_name =
+            self.parser
+                .parse_object_name()
+                .context(error::UnexpectedSnafu {
+                    sql: self.sql,
+                    expected: "a function name",
+                    actual: self.peek_token_as_string(),
+                })?;
+        let name = Self::canonicalize_object_name(raw_function_name);
+
+        self.parser
+            .expect_token(&Token::LParen)
+            .context(error::SyntaxSnafu)?;
+        let args = if self.parser.consume_token(&Token::RParen) {
+            vec![]
+        } else {
+            let args = self
+                .parser
+                .parse_comma_separated(|p| p.parse_data_type())
+                .context(error::SyntaxSnafu)?;
+            self.parser
+                .expect_token(&Token::RParen)
+                .context(error::SyntaxSnafu)?;
+            args
+        };
+
+        self.parser
+            .expect_keyword(Keyword::RETURNS)
+            .context(error::SyntaxSnafu)?;
+        let return_type = self.parser.parse_data_type().context(error::SyntaxSnafu)?;
+
+        self.parser
+            .expect_keyword(Keyword::LANGUAGE)
+            .context(error::SyntaxSnafu)?;
+        let language = self
+            .parser
+            .parse_identifier()
+            .context(error::SyntaxSnafu)?
+            .value
+            .to_lowercase();
+
+        self.parser
+            .expect_keyword(Keyword::AS)
+            .context(error::SyntaxSnafu)?;
+        let location =
+            self.parser
+                .parse_literal_string()
+                .with_context(|_| error::UnexpectedSnafu {
+                    sql: self.sql,
+                    expected: "the location of the function",
+                    actual: self.peek_token_as_string(),
+                })?;
+
+        let options = self
+            .parser
+            .parse_options(Keyword::WITH)
+            .context(error::SyntaxSnafu)?;
+        let connection = self
+            .parser
+            .parse_options(Keyword::CONNECTION)
+            .context(error::SyntaxSnafu)?;
+
+        Ok(Statement::CreateFunction(CreateFunction {
+            name,
+            args,
+            return_type,
+            language,
+            location,
+            options: to_lowercase_options_map(&options).into(),
+            connection: to_lowercase_options_map(&connection).into(),
+        }))
+    }
+
     fn parse_create_database(&mut self) -> Result<Statement> {
         let _ = self.parser.next_token();
 
@@ -753,8 +911,8 @@ fn ensure_value_lists_strictly_increased<'a>(
                 (false, false) => {
                     let column_name = &column.name.value;
                     let cdt = sql_data_type_to_concrete_data_type(&column.data_type)?;
-                    let x = sql_value_to_value(column_name, &cdt, x)?;
-                    let y = sql_value_to_value(column_name, &cdt, y)?;
+                    let x = sql_value_to_value(column_name, &cdt, x, None)?;
+                    let y = sql_value_to_value(column_name, &cdt, y, None)?;
                     match x.cmp(&y) {
                         Ordering::Less => break,
                         Ordering::Equal => equal_tuples += 1,
@@ -998,6 +1156,88 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_parse_create_pipeline() {
+        let sql = r"CREATE PIPELINE IF NOT EXISTS my_pipeline
+SOURCE kafka('broker_endpoints' = '127.0.0.1:9092', 'Topic' = 'events', 'format' = 'json')
+SINK public.events
+WITH ('tags' = 'host', 'max_batch_size' = 100)";
+        let stmts = ParserContext::create_with_dialect(sql, &GreptimeDbDialect {}).unwrap();
+        assert_eq!(1, stmts.len());
+        let Statement::CreatePipeline(c) = &stmts[0] else {
+            unreachable!()
+        };
+        assert_eq!("my_pipeline", c.name.to_string());
+        assert!(c.if_not_exists);
+        assert_eq!("kafka", c.source_type);
+        assert_eq!(
+            HashMap::from([
+                ("broker_endpoints".to_string(), "127.0.0.1:9092".to_string()),
+                ("topic".to_string(), "events".to_string()),
+                ("format".to_string(), "json".to_string()),
+            ]),
+            c.source_options.map
+        );
+        assert_eq!("public.events", c.sink_table.as_ref().unwrap().to_string());
+        assert_eq!(
+            HashMap::from([
+                ("tags".to_string(), "host".to_string()),
+                ("max_batch_size".to_string(), "100".to_string()),
+            ]),
+            c.options.map
+        );
+
+        let sql = "CREATE PIPELINE p SOURCE kafka()";
+        let stmts = ParserContext::create_with_dialect(sql, &GreptimeDbDialect {}).unwrap();
+        let Statement::CreatePipeline(c) = &stmts[0] else {
+            unreachable!()
+        };
+        assert!(!c.if_not_exists);
+        assert!(c.source_options.map.is_empty());
+        assert!(c.sink_table.is_none());
+
+        let sql = "CREATE PIPELINE p SINK t";
+        assert!(ParserContext::create_with_dialect(sql, &GreptimeDbDialect {}).is_err());
+    }
+
+    #[test]
+    fn test_parse_create_function() {
+        let sql = r"CREATE FUNCTION public.add_one(BIGINT, DOUBLE) RETURNS BIGINT
+LANGUAGE WASM AS 's3://udf/add_one.wasm'
+WITH ('Timeout' = '1s')
+CONNECTION (REGION = 'us-west-2')";
+        let stmts = ParserContext::create_with_dialect(sql, &GreptimeDbDialect {}).unwrap();
+        assert_eq!(1, stmts.len());
+        let Statement::CreateFunction(c) = &stmts[0] else {
+            unreachable!()
+        };
+        assert_eq!("public.add_one", c.name.to_string());
+        assert_eq!(vec![DataType::BigInt(None), DataType::Double], c.args);
+        assert_eq!(DataType::BigInt(None), c.return_type);
+        assert_eq!("wasm", c.language);
+        assert_eq!("s3://udf/add_one.wasm", c.location);
+        assert_eq!(
+            HashMap::from([("timeout".to_string(), "1s".to_string())]),
+            c.options.map
+        );
+        assert_eq!(
+            HashMap::from([("region".to_string(), "us-west-2".to_string())]),
+            c.connection.map
+        );
+
+        let sql = "CREATE FUNCTION f() RETURNS INT LANGUAGE wasm AS '/tmp/f.wasm'";
+        let stmts = ParserContext::create_with_dialect(sql, &GreptimeDbDialect {}).unwrap();
+        let Statement::CreateFunction(c) = &stmts[0] else {
+            unreachable!()
+        };
+        assert!(c.args.is_empty());
+        assert!(c.options.map.is_empty());
+        assert!(c.connection.map.is_empty());
+
+        let sql = "CREATE FUNCTION f(INT) LANGUAGE wasm AS '/tmp/f.wasm'";
+        assert!(ParserContext::create_with_dialect(sql, &GreptimeDbDialect {}).is_err());
+    }
+
     #[test]
     fn test_validate_create() {
         let sql = r"
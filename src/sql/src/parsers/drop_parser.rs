@@ -17,13 +17,17 @@ use sqlparser::keywords::Keyword;
 
 use crate::error::{self, InvalidTableNameSnafu, Result};
 use crate::parser::ParserContext;
-use crate::statements::drop::DropTable;
+use crate::parsers::create_parser::PIPELINE;
+use crate::statements::drop::{DropPipeline, DropTable};
 use crate::statements::statement::Statement;
 
 /// DROP statement parser implementation
 impl<'a> ParserContext<'a> {
     pub(crate) fn parse_drop(&mut self) -> Result<Statement> {
         let _ = self.parser.next_token();
+        if self.consume_token(PIPELINE) {
+            return self.parse_drop_pipeline();
+        }
         if !self.matches_keyword(Keyword::TABLE) {
             return self.unsupported(self.peek_token_as_string());
         }
@@ -48,6 +52,21 @@ impl<'a> ParserContext<'a> {
 
         Ok(Statement::DropTable(DropTable::new(table_ident, if_exists)))
     }
+
+    fn parse_drop_pipeline(&mut self) -> Result<Statement> {
+        let if_exists = self.parser.parse_keywords(&[Keyword::IF, Keyword::EXISTS]);
+        let raw_pipeline_name =
+            self.parser
+                .parse_object_name()
+                .with_context(|_| error::UnexpectedSnafu {
+                    sql: self.sql,
+                    expected: "a pipeline name",
+                    actual: self.peek_token_as_string(),
+                })?;
+        let name = Self::canonicalize_object_name(raw_pipeline_name);
+
+        Ok(Statement::DropPipeline(DropPipeline::new(name, if_exists)))
+    }
 }
 
 #[cfg(test)]
@@ -101,4 +120,19 @@ mod tests {
             ))
         )
     }
+    #[test]
+    pub fn test_drop_pipeline() {
+        let sql = "DROP PIPELINE IF EXISTS my_schema.foo";
+        let mut stmts = ParserContext::create_with_dialect(sql, &GreptimeDbDialect {}).unwrap();
+        assert_eq!(
+            stmts.pop().unwrap(),
+            Statement::DropPipeline(DropPipeline::new(
+                ObjectName(vec![Ident::new("my_schema"), Ident::new("foo")]),
+                true
+            ))
+        );
+
+        let sql = "DROP PIPELINE";
+        assert!(ParserContext::create_with_dialect(sql, &GreptimeDbDialect {}).is_err());
+    }
 }
@@ -12,6 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+pub mod admin;
 pub mod alter;
 pub mod copy;
 pub mod create;
@@ -35,7 +36,7 @@ use api::v1::add_column_location::LocationType;
 use api::v1::{AddColumnLocation as Location, SemanticType};
 use common_base::bytes::Bytes;
 use common_query::AddColumnLocation;
-use common_time::Timestamp;
+use common_time::{TimeZone, Timestamp};
 use datatypes::prelude::ConcreteDataType;
 use datatypes::schema::constraint::{CURRENT_TIMESTAMP, CURRENT_TIMESTAMP_FN};
 use datatypes::schema::{ColumnDefaultConstraint, ColumnSchema, COMMENT_KEY};
@@ -44,7 +45,7 @@ use datatypes::value::{OrderedF32, OrderedF64, Value};
 pub use option_map::OptionMap;
 use snafu::{ensure, OptionExt, ResultExt};
 use sqlparser::ast::ExactNumberInfo;
-pub use transform::{get_data_type_by_alias_name, transform_statements};
+pub use transform::{get_data_type_by_alias_name, transform_statements, ASOF_JOIN_FUNCTION};
 
 use crate::ast::{
     ColumnDef, ColumnOption, ColumnOptionDef, DataType as SqlDataType, Expr, TimezoneInfo,
@@ -60,6 +61,7 @@ fn parse_string_to_value(
     column_name: &str,
     s: String,
     data_type: &ConcreteDataType,
+    timezone: Option<&TimeZone>,
 ) -> Result<Value> {
     ensure!(
         data_type.is_stringifiable(),
@@ -93,7 +95,7 @@ fn parse_string_to_value(
             }
         }
         ConcreteDataType::Timestamp(t) => {
-            if let Ok(ts) = Timestamp::from_str(&s) {
+            if let Ok(ts) = Timestamp::from_str_with_timezone(&s, timezone) {
                 Ok(Value::Timestamp(ts.convert_to(t.unit()).context(
                     TimestampOverflowSnafu {
                         timestamp: ts,
@@ -210,10 +212,13 @@ where
     }
 }
 
+/// Converts the SQL value to the value of `data_type`, a timestamp string without
+/// explicit offset is in the given `timezone`, or UTC if it's absent.
 pub fn sql_value_to_value(
     column_name: &str,
     data_type: &ConcreteDataType,
     sql_val: &SqlValue,
+    timezone: Option<&TimeZone>,
 ) -> Result<Value> {
     let value = match sql_val {
         SqlValue::Number(n, _) => sql_number_to_value(data_type, n)?,
@@ -231,7 +236,7 @@ pub fn sql_value_to_value(
             (*b).into()
         }
         SqlValue::DoubleQuotedString(s) | SqlValue::SingleQuotedString(s) => {
-            parse_string_to_value(column_name, s.clone(), data_type)?
+            parse_string_to_value(column_name, s.clone(), data_type, timezone)?
         }
         SqlValue::HexStringLiteral(s) => parse_hex_string(s)?,
         SqlValue::Placeholder(s) => return InvalidSqlValueSnafu { value: s }.fail(),
@@ -289,7 +294,7 @@ fn parse_column_default_constraint(
     {
         let default_constraint = match &opt.option {
             ColumnOption::Default(Expr::Value(v)) => {
-                ColumnDefaultConstraint::Value(sql_value_to_value(column_name, data_type, v)?)
+                ColumnDefaultConstraint::Value(sql_value_to_value(column_name, data_type, v, None)?)
             }
             ColumnOption::Default(Expr::Function(func)) => {
                 let mut func = format!("{func}").to_lowercase();
@@ -629,29 +634,29 @@ mod tests {
         let sql_val = SqlValue::Null;
         assert_eq!(
             Value::Null,
-            sql_value_to_value("a", &ConcreteDataType::float64_datatype(), &sql_val).unwrap()
+            sql_value_to_value("a", &ConcreteDataType::float64_datatype(), &sql_val, None).unwrap()
         );
 
         let sql_val = SqlValue::Boolean(true);
         assert_eq!(
             Value::Boolean(true),
-            sql_value_to_value("a", &ConcreteDataType::boolean_datatype(), &sql_val).unwrap()
+            sql_value_to_value("a", &ConcreteDataType::boolean_datatype(), &sql_val, None).unwrap()
         );
 
         let sql_val = SqlValue::Number("3.0".to_string(), false);
         assert_eq!(
             Value::Float64(OrderedFloat(3.0)),
-            sql_value_to_value("a", &ConcreteDataType::float64_datatype(), &sql_val).unwrap()
+            sql_value_to_value("a", &ConcreteDataType::float64_datatype(), &sql_val, None).unwrap()
         );
 
         let sql_val = SqlValue::Number("3.0".to_string(), false);
-        let v = sql_value_to_value("a", &ConcreteDataType::boolean_datatype(), &sql_val);
+        let v = sql_value_to_value("a", &ConcreteDataType::boolean_datatype(), &sql_val, None);
         assert!(v.is_err());
         assert!(format!("{v:?}")
             .contains("Fail to parse number 3.0, invalid column type: Boolean(BooleanType)"));
 
         let sql_val = SqlValue::Boolean(true);
-        let v = sql_value_to_value("a", &ConcreteDataType::float64_datatype(), &sql_val);
+        let v = sql_value_to_value("a", &ConcreteDataType::float64_datatype(), &sql_val, None);
         assert!(v.is_err());
         assert!(
             format!("{v:?}").contains(
@@ -661,11 +666,12 @@ mod tests {
         );
 
         let sql_val = SqlValue::HexStringLiteral("48656c6c6f20776f726c6421".to_string());
-        let v = sql_value_to_value("a", &ConcreteDataType::binary_datatype(), &sql_val).unwrap();
+        let v =
+            sql_value_to_value("a", &ConcreteDataType::binary_datatype(), &sql_val, None).unwrap();
         assert_eq!(Value::Binary(Bytes::from(b"Hello world!".as_slice())), v);
 
         let sql_val = SqlValue::HexStringLiteral("9AF".to_string());
-        let v = sql_value_to_value("a", &ConcreteDataType::binary_datatype(), &sql_val);
+        let v = sql_value_to_value("a", &ConcreteDataType::binary_datatype(), &sql_val, None);
         assert!(v.is_err());
         assert!(
             format!("{v:?}").contains("odd number of digits"),
@@ -673,7 +679,7 @@ mod tests {
         );
 
         let sql_val = SqlValue::HexStringLiteral("AG".to_string());
-        let v = sql_value_to_value("a", &ConcreteDataType::binary_datatype(), &sql_val);
+        let v = sql_value_to_value("a", &ConcreteDataType::binary_datatype(), &sql_val, None);
         assert!(v.is_err());
         assert!(format!("{v:?}").contains("invalid character"), "v is {v:?}",);
     }
@@ -684,6 +690,7 @@ mod tests {
             "date",
             &ConcreteDataType::date_datatype(),
             &SqlValue::DoubleQuotedString("2022-02-22".to_string()),
+            None,
         )
         .unwrap();
         assert_eq!(ConcreteDataType::date_datatype(), value.data_type());
@@ -701,6 +708,7 @@ mod tests {
             "datetime_col",
             &ConcreteDataType::datetime_datatype(),
             &SqlValue::DoubleQuotedString("2022-02-22 00:01:03+0800".to_string()),
+            None,
         )
         .unwrap();
         assert_eq!(ConcreteDataType::datetime_datatype(), value.data_type());
@@ -717,6 +725,7 @@ mod tests {
             "datetime_col",
             &ConcreteDataType::datetime_datatype(),
             &SqlValue::DoubleQuotedString("2022-02-22 00:01:61".to_string()),
+            None,
         )
         .is_err());
     }
@@ -727,6 +736,7 @@ mod tests {
             "timestamp_col",
             "2022-02-22T00:01:01+08:00".to_string(),
             &ConcreteDataType::timestamp_millisecond_datatype(),
+            None,
         )
         .unwrap()
         {
@@ -743,6 +753,7 @@ mod tests {
             "timestamp_col",
             "2022-02-22T00:01:01+08:00".to_string(),
             &ConcreteDataType::timestamp_datatype(TimeUnit::Second),
+            None,
         )
         .unwrap()
         {
@@ -759,6 +770,7 @@ mod tests {
             "timestamp_col",
             "2022-02-22T00:01:01+08:00".to_string(),
             &ConcreteDataType::timestamp_datatype(TimeUnit::Microsecond),
+            None,
         )
         .unwrap()
         {
@@ -775,6 +787,7 @@ mod tests {
             "timestamp_col",
             "2022-02-22T00:01:01+08:00".to_string(),
             &ConcreteDataType::timestamp_datatype(TimeUnit::Nanosecond),
+            None,
         )
         .unwrap()
         {
@@ -791,6 +804,7 @@ mod tests {
             "timestamp_col",
             "2022-02-22T00:01:01+08".to_string(),
             &ConcreteDataType::timestamp_datatype(TimeUnit::Nanosecond),
+            None,
         )
         .is_err());
     }
@@ -951,7 +965,8 @@ mod tests {
         assert!(sql_value_to_value(
             "test",
             &ConcreteDataType::string_datatype(),
-            &SqlValue::Placeholder("default".into())
+            &SqlValue::Placeholder("default".into()),
+            None
         )
         .is_err());
     }
@@ -0,0 +1,41 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fmt::Display;
+
+use sqlparser_derive::{Visit, VisitMut};
+
+/// `ADMIN` statement to run administration functions.
+#[derive(Debug, Clone, PartialEq, Eq, Visit, VisitMut)]
+pub enum Admin {
+    /// `ADMIN procedure_state('<procedure_id>')`, queries the state of a procedure.
+    ProcedureState(String),
+    /// `ADMIN capture_query('<sql>', '<location>')`, captures files to reproduce the query
+    /// offline to the location, which is relative to the capture directory of the server.
+    CaptureQuery { query: String, location: String },
+}
+
+impl Display for Admin {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Admin::ProcedureState(pid) => write!(f, "ADMIN procedure_state('{pid}')"),
+            Admin::CaptureQuery { query, location } => write!(
+                f,
+                "ADMIN capture_query('{}', '{}')",
+                query.replace('\'', "''"),
+                location.replace('\'', "''")
+            ),
+        }
+    }
+}
@@ -50,6 +50,8 @@ pub enum AlterTableOperation {
     },
     /// `DROP COLUMN <name>`
     DropColumn { name: Ident },
+    /// `MODIFY PRIMARY KEY (<column_name>, ...)`
+    ModifyPrimaryKey { columns: Vec<Ident> },
     /// `RENAME <new_table_name>`
     RenameTable { new_table_name: String },
 }
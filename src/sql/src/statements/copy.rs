@@ -15,12 +15,14 @@
 use sqlparser::ast::ObjectName;
 use sqlparser_derive::{Visit, VisitMut};
 
+use crate::statements::query::Query;
 use crate::statements::OptionMap;
 
 #[derive(Debug, Clone, PartialEq, Eq, Visit, VisitMut)]
 pub enum Copy {
     CopyTable(CopyTable),
     CopyDatabase(CopyDatabaseArgument),
+    CopyQueryTo(CopyQueryToArgument),
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Visit, VisitMut)]
@@ -37,6 +39,16 @@ pub struct CopyDatabaseArgument {
     pub location: String,
 }
 
+/// Copy (query) TO 'location'.
+#[derive(Debug, Clone, PartialEq, Eq, Visit, VisitMut)]
+pub struct CopyQueryToArgument {
+    pub query: Box<Query>,
+    pub with: OptionMap,
+    pub connection: OptionMap,
+    /// Directory to write files.
+    pub location: String,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Visit, VisitMut)]
 pub struct CopyTableArgument {
     pub table_name: ObjectName,
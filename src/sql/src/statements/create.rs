@@ -18,7 +18,9 @@ use common_catalog::consts::FILE_ENGINE;
 use itertools::Itertools;
 use sqlparser_derive::{Visit, VisitMut};
 
-use crate::ast::{ColumnDef, Ident, ObjectName, SqlOption, TableConstraint, Value as SqlValue};
+use crate::ast::{
+    ColumnDef, DataType, Ident, ObjectName, SqlOption, TableConstraint, Value as SqlValue,
+};
 use crate::statements::OptionMap;
 
 const LINE_SEP: &str = ",\n";
@@ -218,6 +220,45 @@ pub struct CreateExternalTable {
     pub engine: String,
 }
 
+/// CREATE PIPELINE statement.
+#[derive(Debug, PartialEq, Eq, Clone, Visit, VisitMut)]
+pub struct CreatePipeline {
+    /// Pipeline name
+    pub name: ObjectName,
+    pub if_not_exists: bool,
+    /// Type of the source in lowercase, e.g. `kafka`.
+    pub source_type: String,
+    /// Options of the source.
+    /// All keys are lowercase.
+    pub source_options: OptionMap,
+    /// Table to write
+    pub sink_table: Option<ObjectName>,
+    /// Pipeline options in `WITH`.
+    /// All keys are lowercase.
+    pub options: OptionMap,
+}
+
+/// CREATE FUNCTION statement.
+#[derive(Debug, PartialEq, Eq, Clone, Visit, VisitMut)]
+pub struct CreateFunction {
+    /// Function name
+    pub name: ObjectName,
+    /// Data types of arguments.
+    pub args: Vec<DataType>,
+    /// Data type of the return value.
+    pub return_type: DataType,
+    /// Language of the function in lowercase, e.g. `wasm`.
+    pub language: String,
+    /// Location of the function body, e.g. `s3://bucket/udf.wasm`.
+    pub location: String,
+    /// Function options in `WITH`.
+    /// All keys are lowercase.
+    pub options: OptionMap,
+    /// Options to connect to the location in `CONNECTION`.
+    /// All keys are lowercase.
+    pub connection: OptionMap,
+}
+
 #[cfg(test)]
 mod tests {
     use crate::dialect::GreptimeDbDialect;
@@ -17,11 +17,14 @@ use sqlparser::ast::Statement as SpStatement;
 use sqlparser_derive::{Visit, VisitMut};
 
 use crate::error::{ConvertToDfStatementSnafu, Error};
+use crate::statements::admin::Admin;
 use crate::statements::alter::AlterTable;
-use crate::statements::create::{CreateDatabase, CreateExternalTable, CreateTable};
+use crate::statements::create::{
+    CreateDatabase, CreateExternalTable, CreateFunction, CreatePipeline, CreateTable,
+};
 use crate::statements::delete::Delete;
 use crate::statements::describe::DescribeTable;
-use crate::statements::drop::DropTable;
+use crate::statements::drop::{DropPipeline, DropTable};
 use crate::statements::explain::Explain;
 use crate::statements::insert::Insert;
 use crate::statements::query::Query;
@@ -64,6 +67,14 @@ pub enum Statement {
     Tql(Tql),
     // TRUNCATE TABLE
     TruncateTable(TruncateTable),
+    // CREATE PIPELINE
+    CreatePipeline(CreatePipeline),
+    // DROP PIPELINE
+    DropPipeline(DropPipeline),
+    // CREATE FUNCTION
+    CreateFunction(CreateFunction),
+    // ADMIN
+    Admin(Admin),
 }
 
 /// Comment hints from SQL.
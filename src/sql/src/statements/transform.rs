@@ -20,7 +20,10 @@ use sqlparser::ast::{visit_expressions_mut, Expr};
 
 use crate::error::Result;
 use crate::statements::statement::Statement;
+mod asof_join;
 mod type_alias;
+use asof_join::AsofJoinTransformRule;
+pub use asof_join::ASOF_JOIN_FUNCTION;
 pub use type_alias::get_data_type_by_alias_name;
 use type_alias::TypeAliasTransformRule;
 
@@ -28,6 +31,7 @@ lazy_static! {
     /// [TransformRule] registry
     static ref RULES: Vec<Arc<dyn TransformRule>> = vec![
         Arc::new(TypeAliasTransformRule{}),
+        Arc::new(AsofJoinTransformRule{}),
     ];
 }
 
@@ -0,0 +1,201 @@
+// Copyright 2023 Greptime Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use sqlparser::ast::{
+    Expr, Function, FunctionArg, FunctionArgExpr, Ident, Join, JoinConstraint, JoinOperator,
+    ObjectName, Query, SetExpr, Statement as SpStatement, TableAlias, TableFactor, TableWithJoins,
+};
+
+use crate::error::{InvalidSqlSnafu, Result};
+use crate::statements::statement::Statement;
+use crate::statements::transform::TransformRule;
+
+/// Name of the function wrapping the condition of an ASOF join, the query planner
+/// rewrites joins with such condition to ASOF joins.
+pub const ASOF_JOIN_FUNCTION: &str = "__asof_join";
+
+/// The keyword before `JOIN` for an ASOF join.
+const ASOF: &str = "ASOF";
+
+/// ASOF join transformer.
+///
+/// The parser doesn't know `ASOF JOIN` so `a ASOF JOIN b ON cond` is parsed as joining `a`
+/// aliased as `ASOF` with `b`. This rule removes the alias and wraps the join condition
+/// into [ASOF_JOIN_FUNCTION], e.g. `a JOIN b ON __asof_join(cond)`.
+pub(crate) struct AsofJoinTransformRule;
+
+impl TransformRule for AsofJoinTransformRule {
+    fn visit_statement(&self, stmt: &mut Statement) -> Result<()> {
+        match stmt {
+            Statement::Query(query) => rewrite_query(&mut query.inner),
+            Statement::Explain(explain) => match &mut explain.inner {
+                SpStatement::Explain { statement, .. } => match statement.as_mut() {
+                    SpStatement::Query(query) => rewrite_query(query),
+                    _ => Ok(()),
+                },
+                _ => Ok(()),
+            },
+            _ => Ok(()),
+        }
+    }
+}
+
+fn rewrite_query(query: &mut Query) -> Result<()> {
+    if let Some(with) = &mut query.with {
+        for cte in &mut with.cte_tables {
+            rewrite_query(&mut cte.query)?;
+        }
+    }
+    rewrite_set_expr(&mut query.body)
+}
+
+fn rewrite_set_expr(set_expr: &mut SetExpr) -> Result<()> {
+    match set_expr {
+        SetExpr::Select(select) => {
+            for table in &mut select.from {
+                rewrite_table_with_joins(table)?;
+            }
+            Ok(())
+        }
+        SetExpr::Query(query) => rewrite_query(query),
+        SetExpr::SetOperation { left, right, .. } => {
+            rewrite_set_expr(left)?;
+            rewrite_set_expr(right)
+        }
+        _ => Ok(()),
+    }
+}
+
+fn rewrite_table_with_joins(table: &mut TableWithJoins) -> Result<()> {
+    rewrite_table_factor(&mut table.relation)?;
+    for i in 0..table.joins.len() {
+        rewrite_table_factor(&mut table.joins[i].relation)?;
+
+        // The `ASOF` keyword is the alias of the relation before the join.
+        let relation = if i == 0 {
+            &mut table.relation
+        } else {
+            &mut table.joins[i - 1].relation
+        };
+        if take_asof_alias(relation) {
+            wrap_join_condition(&mut table.joins[i])?;
+        }
+    }
+    Ok(())
+}
+
+fn rewrite_table_factor(factor: &mut TableFactor) -> Result<()> {
+    match factor {
+        TableFactor::Derived { subquery, .. } => rewrite_query(subquery),
+        TableFactor::NestedJoin {
+            table_with_joins, ..
+        } => rewrite_table_with_joins(table_with_joins),
+        _ => Ok(()),
+    }
+}
+
+/// Removes the alias of the relation and returns true if it is the `ASOF` keyword.
+fn take_asof_alias(factor: &mut TableFactor) -> bool {
+    let alias = match factor {
+        TableFactor::Table { alias, .. } | TableFactor::Derived { alias, .. } => alias,
+        _ => return false,
+    };
+    let is_asof = matches!(
+        alias,
+        Some(TableAlias { name, columns })
+            if columns.is_empty()
+                && name.quote_style.is_none()
+                && name.value.eq_ignore_ascii_case(ASOF)
+    );
+    if is_asof {
+        *alias = None;
+    }
+    is_asof
+}
+
+fn wrap_join_condition(join: &mut Join) -> Result<()> {
+    let condition = match &mut join.join_operator {
+        JoinOperator::Inner(JoinConstraint::On(condition))
+        | JoinOperator::LeftOuter(JoinConstraint::On(condition)) => condition,
+        _ => {
+            return InvalidSqlSnafu {
+                msg: "ASOF JOIN only supports inner or left join with an ON condition",
+            }
+            .fail()
+        }
+    };
+    *condition = Expr::Function(Function {
+        name: ObjectName(vec![Ident::new(ASOF_JOIN_FUNCTION)]),
+        args: vec![FunctionArg::Unnamed(FunctionArgExpr::Expr(
+            condition.clone(),
+        ))],
+        over: None,
+        distinct: false,
+        special: false,
+        order_by: vec![],
+    });
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dialect::GreptimeDbDialect;
+    use crate::parser::ParserContext;
+    use crate::statements::statement::Statement;
+
+    fn parse_to_string(sql: &str) -> String {
+        match ParserContext::create_with_dialect(sql, &GreptimeDbDialect {})
+            .unwrap()
+            .remove(0)
+        {
+            Statement::Query(query) => query.to_string(),
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn test_transform_asof_join() {
+        assert_eq!(
+            "SELECT * FROM a JOIN b ON __asof_join(a.host = b.host AND a.ts >= b.ts)",
+            parse_to_string("SELECT * FROM a ASOF JOIN b ON a.host = b.host AND a.ts >= b.ts")
+        );
+        assert_eq!(
+            "SELECT * FROM a LEFT JOIN b ON __asof_join(a.ts >= b.ts)",
+            parse_to_string("SELECT * FROM a ASOF LEFT JOIN b ON a.ts >= b.ts")
+        );
+        assert_eq!(
+            "SELECT * FROM (SELECT * FROM a JOIN b ON __asof_join(a.ts >= b.ts)) AS t",
+            parse_to_string("SELECT * FROM (SELECT * FROM a ASOF JOIN b ON a.ts >= b.ts) AS t")
+        );
+    }
+
+    #[test]
+    fn test_transform_asof_alias() {
+        // Not followed by a join.
+        assert_eq!(
+            "SELECT asof.ts FROM a AS asof",
+            parse_to_string("SELECT asof.ts FROM a asof")
+        );
+        // Quoted alias.
+        assert_eq!(
+            "SELECT * FROM a AS \"asof\" JOIN b ON a.ts = b.ts",
+            parse_to_string("SELECT * FROM a \"asof\" JOIN b ON a.ts = b.ts")
+        );
+        assert!(ParserContext::create_with_dialect(
+            "SELECT * FROM a ASOF JOIN b USING (ts)",
+            &GreptimeDbDialect {}
+        )
+        .is_err());
+    }
+}
@@ -20,7 +20,7 @@ use sqlparser::ast::{
 };
 
 use crate::error::Result;
-use crate::statements::create::{CreateExternalTable, CreateTable};
+use crate::statements::create::{CreateExternalTable, CreateFunction, CreateTable};
 use crate::statements::statement::Statement;
 use crate::statements::transform::TransformRule;
 use crate::statements::{sql_data_type_to_concrete_data_type, TimezoneInfo};
@@ -50,6 +50,12 @@ impl TransformRule for TypeAliasTransformRule {
                     .iter_mut()
                     .for_each(|ColumnDef { data_type, .. }| replace_type_alias(data_type));
             }
+            Statement::CreateFunction(CreateFunction {
+                args, return_type, ..
+            }) => {
+                args.iter_mut().for_each(replace_type_alias);
+                replace_type_alias(return_type);
+            }
             _ => {}
         }
 
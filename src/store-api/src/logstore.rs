@@ -30,7 +30,7 @@ pub mod namespace;
 /// `LogStore` serves as a Write-Ahead-Log for storage engine.
 #[async_trait::async_trait]
 pub trait LogStore: Send + Sync + 'static + std::fmt::Debug {
-    type Error: ErrorExt + Send + Sync + 'static;
+    type Error: LogStoreError + Send + Sync + 'static;
     type Namespace: Namespace;
     type Entry: Entry;
 
@@ -42,6 +42,10 @@ pub trait LogStore: Send + Sync + 'static + std::fmt::Debug {
 
     /// Appends a batch of entries and returns a response containing a map where the key is a region id
     /// while the value is the id of the last successfully written entry of the region.
+    ///
+    /// Entries may belong to different namespaces. Implementations should write them in as few
+    /// requests as possible, e.g. one raft-engine write or aggregated kafka produce calls,
+    /// so callers can batch writes of multiple regions.
     async fn append_batch(
         &self,
         entries: Vec<Self::Entry>,
@@ -56,6 +60,14 @@ pub trait LogStore: Send + Sync + 'static + std::fmt::Debug {
         id: EntryId,
     ) -> Result<SendableEntryStream<Self::Entry, Self::Error>, Self::Error>;
 
+    /// Returns the id of the latest entry in the log store that may belong to the namespace,
+    /// or `None` if there is no such entry.
+    ///
+    /// Entries of different namespaces may share the same id space, e.g. a kafka topic, so
+    /// the returned id is an upper bound of the ids of entries in the namespace. Callers can
+    /// compare it with the id of the last read entry to know whether they have caught up.
+    async fn latest_entry_id(&self, ns: &Self::Namespace) -> Result<Option<EntryId>, Self::Error>;
+
     /// Creates a new `Namespace` from the given ref.
     async fn create_namespace(&self, ns: &Self::Namespace) -> Result<(), Self::Error>;
 
@@ -73,12 +85,31 @@ pub trait LogStore: Send + Sync + 'static + std::fmt::Debug {
     // TODO(sunng87): confusion with `create_namespace`
     fn namespace(&self, ns_id: NamespaceId, wal_options: &WalOptions) -> Self::Namespace;
 
+    /// Returns true if the log store can create namespaces with the `wal_options`.
+    ///
+    /// Regions whose wal options are not supported can't be opened by the log store.
+    fn supports_wal_options(&self, wal_options: &WalOptions) -> bool {
+        let _ = wal_options;
+        true
+    }
+
     /// Marks all entries with ids `<=entry_id` of the given `namespace` as obsolete,
     /// so that the log store can safely delete those entries. This method does not guarantee
     /// that the obsolete entries are deleted immediately.
     async fn obsolete(&self, ns: Self::Namespace, entry_id: EntryId) -> Result<(), Self::Error>;
 }
 
+/// Errors of a [LogStore].
+pub trait LogStoreError: ErrorExt {
+    /// Returns the id of the entry if the error is caused by a corrupted entry.
+    ///
+    /// Entry streams yield such errors in place of the corrupted entries and continue
+    /// to yield remaining entries, so callers can skip the corrupted entries.
+    fn corrupted_entry_id(&self) -> Option<EntryId> {
+        None
+    }
+}
+
 /// The response of an `append` operation.
 #[derive(Debug, Default)]
 pub struct AppendResponse {
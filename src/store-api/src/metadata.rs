@@ -522,6 +522,7 @@ impl RegionMetadataBuilder {
         match kind {
             AlterKind::AddColumns { columns } => self.add_columns(columns)?,
             AlterKind::DropColumns { names } => self.drop_columns(&names),
+            AlterKind::SetNotNull { names } => self.set_not_null(&names)?,
         }
         Ok(self)
     }
@@ -602,6 +603,22 @@ impl RegionMetadataBuilder {
         self.column_metadatas
             .retain(|col| !name_set.contains(&col.column_schema.name));
     }
+
+    /// Sets columns to not null if exist.
+    fn set_not_null(&mut self, names: &[String]) -> Result<()> {
+        let name_set: HashSet<_> = names.iter().collect();
+        for col in &mut self.column_metadatas {
+            if !name_set.contains(&col.column_schema.name) {
+                continue;
+            }
+            col.column_schema = col
+                .column_schema
+                .clone()
+                .with_nullable_unset()
+                .context(InvalidSchemaSnafu)?;
+        }
+        Ok(())
+    }
 }
 
 /// Fields skipped in serialization.
@@ -1043,6 +1060,7 @@ mod test {
                 columns: vec![AddColumn {
                     column_metadata: new_column_metadata("d", true, 4),
                     location: None,
+                    backfill: false,
                 }],
             })
             .unwrap();
@@ -1056,6 +1074,7 @@ mod test {
                 columns: vec![AddColumn {
                     column_metadata: new_column_metadata("e", false, 5),
                     location: Some(AddColumnLocation::First),
+                    backfill: false,
                 }],
             })
             .unwrap();
@@ -1070,6 +1089,7 @@ mod test {
                     location: Some(AddColumnLocation::After {
                         column_name: "b".to_string(),
                     }),
+                    backfill: false,
                 }],
             })
             .unwrap();
@@ -1084,6 +1104,7 @@ mod test {
                     location: Some(AddColumnLocation::After {
                         column_name: "d".to_string(),
                     }),
+                    backfill: false,
                 }],
             })
             .unwrap();
@@ -1122,10 +1143,12 @@ mod test {
                     AddColumn {
                         column_metadata: new_column_metadata("d", true, 4),
                         location: None,
+                        backfill: false,
                     },
                     AddColumn {
                         column_metadata: new_column_metadata("d", true, 4),
                         location: None,
+                        backfill: false,
                     },
                 ],
             })
@@ -1141,6 +1164,7 @@ mod test {
                 columns: vec![AddColumn {
                     column_metadata: new_column_metadata("b", false, 2),
                     location: None,
+                    backfill: false,
                 }],
             })
             .unwrap();
@@ -1160,10 +1184,12 @@ mod test {
                     AddColumn {
                         column_metadata: new_column_metadata("d", false, 4),
                         location: None,
+                        backfill: false,
                     },
                     AddColumn {
                         column_metadata: new_column_metadata("e", false, 5),
                         location: None,
+                        backfill: false,
                     },
                 ],
             })
@@ -1190,6 +1216,48 @@ mod test {
         check_columns(&metadata, &["a", "c", "d"]);
     }
 
+    #[test]
+    fn test_set_not_null() {
+        // a (tag), b (field), c (ts)
+        let metadata = build_test_region_metadata();
+        let mut builder = RegionMetadataBuilder::from_existing(metadata);
+        // field d
+        builder
+            .alter(AlterKind::AddColumns {
+                columns: vec![AddColumn {
+                    column_metadata: new_column_metadata("d", false, 4),
+                    location: None,
+                    backfill: false,
+                }],
+            })
+            .unwrap();
+        let metadata = builder.build().unwrap();
+        assert!(metadata
+            .column_by_name("d")
+            .unwrap()
+            .column_schema
+            .is_nullable());
+
+        let mut builder = RegionMetadataBuilder::from_existing(metadata);
+        builder
+            .alter(AlterKind::SetNotNull {
+                names: vec!["d".to_string(), "x".to_string()],
+            })
+            .unwrap();
+        let metadata = builder.build().unwrap();
+        check_columns(&metadata, &["a", "b", "c", "d"]);
+        assert!(!metadata
+            .column_by_name("d")
+            .unwrap()
+            .column_schema
+            .is_nullable());
+        assert!(!metadata
+            .schema
+            .column_schema_by_name("d")
+            .unwrap()
+            .is_nullable());
+    }
+
     #[test]
     fn test_invalid_column_name() {
         let mut builder = create_builder();
@@ -20,6 +20,7 @@ use std::sync::Arc;
 use api::greptime_proto::v1::meta::{GrantedRegion as PbGrantedRegion, RegionRole as PbRegionRole};
 use async_trait::async_trait;
 use common_error::ext::BoxedError;
+use common_query::logical_plan::Expr;
 use common_recordbatch::SendableRecordBatchStream;
 use serde::{Deserialize, Serialize};
 
@@ -118,6 +119,16 @@ impl From<PbRegionRole> for RegionRole {
     }
 }
 
+/// Request to copy files of a region so a read of the region can be reproduced offline.
+#[derive(Debug, Clone, Default)]
+pub struct RegionCaptureRequest {
+    /// Filters of the read to reproduce. Files that the read would skip by the time
+    /// range of these filters are not copied.
+    pub filters: Vec<Expr>,
+    /// Local directory to copy files to. Files are laid out like a region directory.
+    pub target_dir: String,
+}
+
 #[async_trait]
 pub trait RegionEngine: Send + Sync {
     /// Name of this engine
@@ -165,6 +176,15 @@ pub trait RegionEngine: Send + Sync {
     ///
     /// Returns the `None` if the region is not found.
     fn role(&self, region_id: RegionId) -> Option<RegionRole>;
+
+    /// Copies the manifest and the SSTs a read needs of the region to a local directory.
+    ///
+    /// Returns paths of copied files relative to the target directory.
+    async fn capture_region(
+        &self,
+        region_id: RegionId,
+        request: RegionCaptureRequest,
+    ) -> Result<Vec<String>, BoxedError>;
 }
 
 pub type RegionEngineRef = Arc<dyn RegionEngine>;
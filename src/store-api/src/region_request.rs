@@ -18,6 +18,8 @@ use std::fmt::{self};
 use api::v1::add_column_location::LocationType;
 use api::v1::region::{alter_request, region_request, AlterRequest};
 use api::v1::{self, Rows, SemanticType};
+use common_time::Timestamp;
+use datatypes::value::Value;
 use snafu::{ensure, OptionExt};
 use strum::IntoStaticStr;
 
@@ -44,6 +46,7 @@ pub enum RegionRequest {
     Compact(RegionCompactRequest),
     Truncate(RegionTruncateRequest),
     Catchup(RegionCatchupRequest),
+    DeleteRange(RegionDeleteRangeRequest),
 }
 
 impl RegionRequest {
@@ -62,6 +65,7 @@ impl RegionRequest {
             RegionRequest::Compact(_) => "compact",
             RegionRequest::Truncate(_) => "truncate",
             RegionRequest::Catchup(_) => "catchup",
+            RegionRequest::DeleteRange(_) => "delete_range",
         }
     }
 
@@ -275,6 +279,13 @@ pub enum AlterKind {
         /// Name of columns to drop.
         names: Vec<String>,
     },
+    /// Sets nullable columns to not null.
+    ///
+    /// The engine should ensure existing data of these columns has no null.
+    SetNotNull {
+        /// Name of columns to set.
+        names: Vec<String>,
+    },
 }
 
 impl AlterKind {
@@ -293,6 +304,11 @@ impl AlterKind {
                     Self::validate_column_to_drop(name, metadata)?;
                 }
             }
+            AlterKind::SetNotNull { names } => {
+                for name in names {
+                    Self::validate_column_to_set_not_null(name, metadata)?;
+                }
+            }
         }
         Ok(())
     }
@@ -307,9 +323,30 @@ impl AlterKind {
             AlterKind::DropColumns { names } => names
                 .iter()
                 .any(|name| metadata.column_by_name(name).is_some()),
+            AlterKind::SetNotNull { names } => names.iter().any(|name| {
+                metadata
+                    .column_by_name(name)
+                    .map(|col| col.column_schema.is_nullable())
+                    .unwrap_or(false)
+            }),
         }
     }
 
+    /// Returns true if the region needs to backfill existing data after
+    /// the alteration.
+    pub fn need_backfill(&self) -> bool {
+        match self {
+            AlterKind::AddColumns { columns } => columns.iter().any(|col| col.backfill),
+            AlterKind::DropColumns { .. } | AlterKind::SetNotNull { .. } => false,
+        }
+    }
+
+    /// Returns true if the alteration requires validating existing data
+    /// before applying it.
+    pub fn need_validation(&self) -> bool {
+        matches!(self, AlterKind::SetNotNull { .. })
+    }
+
     /// Returns an error if the column to drop is invalid.
     fn validate_column_to_drop(name: &str, metadata: &RegionMetadata) -> Result<()> {
         let Some(column) = metadata.column_by_name(name) else {
@@ -324,6 +361,24 @@ impl AlterKind {
         );
         Ok(())
     }
+
+    /// Returns an error if the column to set not null is invalid.
+    fn validate_column_to_set_not_null(name: &str, metadata: &RegionMetadata) -> Result<()> {
+        let column = metadata
+            .column_by_name(name)
+            .with_context(|| InvalidRegionRequestSnafu {
+                region_id: metadata.region_id,
+                err: format!("column {} not found", name),
+            })?;
+        ensure!(
+            column.column_schema.clone().with_nullable_unset().is_ok(),
+            InvalidRegionRequestSnafu {
+                region_id: metadata.region_id,
+                err: format!("default value of column {} might be null", name),
+            }
+        );
+        Ok(())
+    }
 }
 
 impl TryFrom<alter_request::Kind> for AlterKind {
@@ -357,6 +412,9 @@ pub struct AddColumn {
     /// Location to add the column. If location is None, the region adds
     /// the column to the last.
     pub location: Option<AddColumnLocation>,
+    /// Whether to rewrite existing data to materialize the default value
+    /// of the column.
+    pub backfill: bool,
 }
 
 impl AddColumn {
@@ -379,6 +437,21 @@ impl AddColumn {
                 ),
             }
         );
+        ensure!(
+            !self.backfill
+                || self
+                    .column_metadata
+                    .column_schema
+                    .default_constraint()
+                    .is_some(),
+            InvalidRegionRequestSnafu {
+                region_id: metadata.region_id,
+                err: format!(
+                    "no default value to backfill column {}",
+                    self.column_metadata.column_schema.name
+                ),
+            }
+        );
 
         Ok(())
     }
@@ -411,6 +484,8 @@ impl TryFrom<v1::region::AddColumn> for AddColumn {
         Ok(AddColumn {
             column_metadata,
             location,
+            // TODO(agent): Carries the backfill flag once the protocol supports it.
+            backfill: false,
         })
     }
 }
@@ -469,6 +544,26 @@ pub struct RegionCatchupRequest {
     pub entry_id: Option<entry::Id>,
 }
 
+/// Deletes all rows in a time range of the region.
+///
+/// The region deletes these rows without enumerating their keys, so engines don't
+/// know the number of deleted rows and always return 0 affected rows. Engines that
+/// can't delete rows by range return an unsupported error.
+///
+/// This request isn't in the gRPC protocol, so frontends can't send it to datanodes
+/// and it isn't written to the WAL. It can only be sent to the engine directly.
+// TODO(agent): Add the request to the protocol and the WAL once they support it.
+#[derive(Debug, Clone)]
+pub struct RegionDeleteRangeRequest {
+    /// Inclusive start of the time index.
+    pub start: Timestamp,
+    /// Exclusive end of the time index.
+    pub end: Timestamp,
+    /// Only deletes rows whose tags are equal to these values. Deletes
+    /// all rows in the range if it's empty.
+    pub tag_filters: Vec<(String, Value)>,
+}
+
 impl fmt::Display for RegionRequest {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -483,6 +578,7 @@ impl fmt::Display for RegionRequest {
             RegionRequest::Compact(_) => write!(f, "Compact"),
             RegionRequest::Truncate(_) => write!(f, "Truncate"),
             RegionRequest::Catchup(_) => write!(f, "Catchup"),
+            RegionRequest::DeleteRange(_) => write!(f, "DeleteRange"),
         }
     }
 }
@@ -492,7 +588,8 @@ mod tests {
     use api::v1::region::RegionColumnDef;
     use api::v1::{ColumnDataType, ColumnDef};
     use datatypes::prelude::ConcreteDataType;
-    use datatypes::schema::ColumnSchema;
+    use datatypes::schema::{ColumnDefaultConstraint, ColumnSchema};
+    use datatypes::value::Value;
 
     use super::*;
     use crate::metadata::RegionMetadataBuilder;
@@ -585,6 +682,7 @@ mod tests {
                             column_id: 1,
                         },
                         location: Some(AddColumnLocation::First),
+                        backfill: false,
                     }]
                 },
             }
@@ -639,6 +737,7 @@ mod tests {
                 column_id: 4,
             },
             location: None,
+            backfill: false,
         };
         add_column.validate(&metadata).unwrap();
         assert!(add_column.need_alter(&metadata));
@@ -655,6 +754,7 @@ mod tests {
                 column_id: 4,
             },
             location: None,
+            backfill: false,
         }
         .validate(&metadata)
         .unwrap_err();
@@ -671,9 +771,49 @@ mod tests {
                 column_id: 4,
             },
             location: None,
+            backfill: false,
         };
         add_column.validate(&metadata).unwrap();
         assert!(!add_column.need_alter(&metadata));
+
+        // Backfill a column without default value.
+        AddColumn {
+            column_metadata: ColumnMetadata {
+                column_schema: ColumnSchema::new(
+                    "field_1",
+                    ConcreteDataType::string_datatype(),
+                    true,
+                ),
+                semantic_type: SemanticType::Field,
+                column_id: 4,
+            },
+            location: None,
+            backfill: true,
+        }
+        .validate(&metadata)
+        .unwrap_err();
+
+        // Backfill a column with default value.
+        let add_column = AddColumn {
+            column_metadata: ColumnMetadata {
+                column_schema: ColumnSchema::new(
+                    "field_1",
+                    ConcreteDataType::string_datatype(),
+                    true,
+                )
+                .with_default_constraint(Some(ColumnDefaultConstraint::Value(Value::from("a"))))
+                .unwrap(),
+                semantic_type: SemanticType::Field,
+                column_id: 4,
+            },
+            location: None,
+            backfill: true,
+        };
+        add_column.validate(&metadata).unwrap();
+        let kind = AlterKind::AddColumns {
+            columns: vec![add_column],
+        };
+        assert!(kind.need_backfill());
     }
 
     #[test]
@@ -691,6 +831,7 @@ mod tests {
                         column_id: 4,
                     },
                     location: None,
+                    backfill: false,
                 },
                 AddColumn {
                     column_metadata: ColumnMetadata {
@@ -703,6 +844,7 @@ mod tests {
                         column_id: 5,
                     },
                     location: None,
+                    backfill: false,
                 },
             ],
         };
@@ -733,6 +875,45 @@ mod tests {
         assert!(kind.need_alter(&metadata));
     }
 
+    #[test]
+    fn test_validate_set_not_null() {
+        let metadata = new_metadata();
+        AlterKind::SetNotNull {
+            names: vec!["xxxx".to_string()],
+        }
+        .validate(&metadata)
+        .unwrap_err();
+
+        let kind = AlterKind::SetNotNull {
+            names: vec!["ts".to_string()],
+        };
+        kind.validate(&metadata).unwrap();
+        assert!(!kind.need_alter(&metadata));
+
+        let kind = AlterKind::SetNotNull {
+            names: vec!["field_0".to_string()],
+        };
+        kind.validate(&metadata).unwrap();
+        assert!(kind.need_alter(&metadata));
+        assert!(kind.need_validation());
+        assert!(!kind.need_backfill());
+
+        let mut builder = RegionMetadataBuilder::from_existing(metadata);
+        builder.push_column_metadata(ColumnMetadata {
+            column_schema: ColumnSchema::new("field_1", ConcreteDataType::int64_datatype(), true)
+                .with_default_constraint(Some(ColumnDefaultConstraint::null_value()))
+                .unwrap(),
+            semantic_type: SemanticType::Field,
+            column_id: 4,
+        });
+        let metadata = builder.build().unwrap();
+        AlterKind::SetNotNull {
+            names: vec!["field_1".to_string()],
+        }
+        .validate(&metadata)
+        .unwrap_err();
+    }
+
     #[test]
     fn test_validate_schema_version() {
         let mut metadata = new_metadata();
@@ -763,6 +944,7 @@ mod tests {
                         column_id: 4,
                     },
                     location: None,
+                    backfill: false,
                 },
                 AddColumn {
                     column_metadata: ColumnMetadata {
@@ -775,6 +957,7 @@ mod tests {
                         column_id: 5,
                     },
                     location: None,
+                    backfill: false,
                 },
             ],
         };
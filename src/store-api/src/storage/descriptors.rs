@@ -13,6 +13,7 @@
 // limitations under the License.
 
 use std::fmt;
+use std::str::FromStr;
 
 use derive_builder::Builder;
 use serde::{Deserialize, Serialize};
@@ -118,6 +119,60 @@ impl fmt::Display for RegionId {
     }
 }
 
+/// Error returned when parsing a [RegionId] from a string fails.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseRegionIdError {
+    input: String,
+}
+
+impl fmt::Display for ParseRegionIdError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid region id: {}", self.input)
+    }
+}
+
+impl std::error::Error for ParseRegionIdError {}
+
+impl FromStr for RegionId {
+    type Err = ParseRegionIdError;
+
+    /// Parses a [RegionId] from one of the following forms:
+    /// - the u64 id, e.g. `5299989643269`
+    /// - the display form, e.g. `5299989643269(1234, 5)`
+    /// - the table id and region number, e.g. `(1234, 5)`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || ParseRegionIdError {
+            input: s.to_string(),
+        };
+        let s = s.trim();
+        let Some((id, rest)) = s.split_once('(') else {
+            return s.parse::<u64>().map(RegionId::from_u64).map_err(|_| err());
+        };
+
+        let (table_id, region_number) = rest
+            .strip_suffix(')')
+            .and_then(|parts| parts.split_once(','))
+            .ok_or_else(err)?;
+        let table_id = table_id.trim().parse::<TableId>().map_err(|_| err())?;
+        let region_number = region_number
+            .trim()
+            .parse::<RegionNumber>()
+            .map_err(|_| err())?;
+        let region_id = RegionId::new(table_id, region_number);
+
+        let id = id.trim();
+        if !id.is_empty() {
+            // The id must be consistent with the table id and region number.
+            let id = id.parse::<u64>().map_err(|_| err())?;
+            if id != region_id.as_u64() {
+                return Err(err());
+            }
+        }
+
+        Ok(region_id)
+    }
+}
+
 impl From<u64> for RegionId {
     fn from(region_id: u64) -> RegionId {
         RegionId::from_u64(region_id)
@@ -316,6 +371,36 @@ mod tests {
         assert_eq!("5299989643269(1234, 5)", format!("{:?}", region_id));
     }
 
+    #[test]
+    fn test_parse_region_id() {
+        let region_id = RegionId::new(1234, 5);
+        assert_eq!(region_id, "5299989643269".parse::<RegionId>().unwrap());
+        assert_eq!(
+            region_id,
+            region_id.to_string().parse::<RegionId>().unwrap()
+        );
+        assert_eq!(region_id, " (1234, 5) ".parse::<RegionId>().unwrap());
+        assert_eq!(region_id, "(1234,5)".parse::<RegionId>().unwrap());
+
+        for input in [
+            "",
+            "abc",
+            "-1",
+            "(1234)",
+            "(1234, 5",
+            "1234, 5)",
+            "(a, 5)",
+            "(1234, 5, 6)",
+            "1(1234, 5)",
+        ] {
+            assert!(input.parse::<RegionId>().is_err(), "input: {input}");
+        }
+        assert_eq!(
+            "invalid region id: abc",
+            "abc".parse::<RegionId>().unwrap_err().to_string()
+        );
+    }
+
     #[test]
     fn test_region_id_to_json() {
         let region_id = RegionId::from(4294967297);
@@ -14,6 +14,7 @@
 
 use common_query::logical_plan::Expr;
 use common_recordbatch::OrderOption;
+use datatypes::value::Value;
 
 #[derive(Default, Clone, Debug, PartialEq, Eq)]
 pub struct ScanRequest {
@@ -29,4 +30,25 @@ pub struct ScanRequest {
     /// If set, it contains the amount of rows needed by the caller,
     /// The data source should return *at least* this number of rows if available.
     pub limit: Option<usize>,
+    /// Only scans rows whose encoded primary key starts with this prefix.
+    pub pk_prefix: Option<PrimaryKeyPrefix>,
+}
+
+/// Prefix of the primary key to scan.
+///
+/// The storage engine can use the prefix to skip data outside the range of
+/// primary keys starting with it.
+#[derive(Default, Clone, Debug, PartialEq, Eq)]
+pub struct PrimaryKeyPrefix {
+    /// Values of the leading primary key columns, in the order of the primary key.
+    pub values: Vec<Value>,
+    /// Prefix of the string column next to `values` in the primary key.
+    pub string_prefix: Option<String>,
+}
+
+impl PrimaryKeyPrefix {
+    /// Returns true if the prefix matches all primary keys.
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty() && self.string_prefix.is_none()
+    }
 }
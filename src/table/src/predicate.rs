@@ -12,6 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::collections::HashMap;
 use std::sync::Arc;
 
 use common_query::logical_plan::{DfExpr, Expr};
@@ -20,14 +21,18 @@ use common_time::range::TimestampRange;
 use common_time::timestamp::TimeUnit;
 use common_time::Timestamp;
 use datafusion::physical_optimizer::pruning::{PruningPredicate, PruningStatistics};
-use datafusion_common::ToDFSchema;
-use datafusion_expr::expr::InList;
-use datafusion_expr::{Between, BinaryExpr, Operator};
+use datafusion_common::{ScalarValue, ToDFSchema};
+use datafusion_expr::expr::{InList, Like, TryCast};
+use datafusion_expr::{Between, BinaryExpr, Cast, Operator};
 use datafusion_physical_expr::execution_props::ExecutionProps;
 use datafusion_physical_expr::{create_physical_expr, PhysicalExpr};
 use datatypes::arrow;
-use datatypes::value::scalar_value_to_timestamp;
+use datatypes::arrow::datatypes::DataType as ArrowDataType;
+use datatypes::data_type::ConcreteDataType;
+use datatypes::value::{scalar_value_to_timestamp, Value};
 use snafu::ResultExt;
+use store_api::metadata::RegionMetadata;
+use store_api::storage::PrimaryKeyPrefix;
 
 use crate::error;
 
@@ -268,18 +273,36 @@ impl<'a> TimeRangePredicateBuilder<'a> {
 
     fn get_timestamp_filter(&self, left: &DfExpr, right: &DfExpr) -> Option<(Timestamp, bool)> {
         let (col, lit, reverse) = match (left, right) {
-            (DfExpr::Column(column), DfExpr::Literal(scalar)) => (column, scalar, false),
-            (DfExpr::Literal(scalar), DfExpr::Column(column)) => (column, scalar, true),
+            (col, DfExpr::Literal(scalar)) => (col, scalar, false),
+            (DfExpr::Literal(scalar), col) => (col, scalar, true),
             _ => {
                 return None;
             }
         };
-        if col.name != self.ts_col_name {
+        if !self.is_time_index(col) {
             return None;
         }
         scalar_value_to_timestamp(lit).map(|t| (t, reverse))
     }
 
+    /// Returns true if the `expr` is the time index column, or a cast of the column to
+    /// a timestamp type without losing precision, e.g. the type coercion casts the column
+    /// to compare it with `now()`.
+    fn is_time_index(&self, expr: &DfExpr) -> bool {
+        match expr {
+            DfExpr::Column(col) => col.name == self.ts_col_name,
+            DfExpr::Cast(Cast { expr, data_type })
+            | DfExpr::TryCast(TryCast { expr, data_type }) => match data_type {
+                ArrowDataType::Timestamp(unit, _) => {
+                    TimeUnit::from(unit).factor() <= self.ts_col_unit.factor()
+                        && self.is_time_index(expr)
+                }
+                _ => false,
+            },
+            _ => false,
+        }
+    }
+
     fn extract_from_between_expr(
         &self,
         expr: &DfExpr,
@@ -287,10 +310,7 @@ impl<'a> TimeRangePredicateBuilder<'a> {
         low: &DfExpr,
         high: &DfExpr,
     ) -> Option<TimestampRange> {
-        let DfExpr::Column(col) = expr else {
-            return None;
-        };
-        if col.name != self.ts_col_name {
+        if !self.is_time_index(expr) {
             return None;
         }
 
@@ -320,10 +340,7 @@ impl<'a> TimeRangePredicateBuilder<'a> {
         if negated {
             return None;
         }
-        let DfExpr::Column(col) = expr else {
-            return None;
-        };
-        if col.name != self.ts_col_name {
+        if !self.is_time_index(expr) {
             return None;
         }
 
@@ -346,20 +363,134 @@ impl<'a> TimeRangePredicateBuilder<'a> {
     }
 }
 
+/// `PrimaryKeyPrefixBuilder` extracts the prefix of the primary key from logical exprs
+/// so the storage only scans primary keys starting with it.
+///
+/// It finds equality filters on the leading primary key columns, which may be followed
+/// by a `LIKE 'prefix%'` filter on the next string column. The prefix is a necessary
+/// condition of the filters, so the filters still need to be evaluated after the scan.
+pub struct PrimaryKeyPrefixBuilder<'a> {
+    metadata: &'a RegionMetadata,
+    filters: &'a [Expr],
+}
+
+impl<'a> PrimaryKeyPrefixBuilder<'a> {
+    pub fn new(metadata: &'a RegionMetadata, filters: &'a [Expr]) -> Self {
+        Self { metadata, filters }
+    }
+
+    /// Returns the prefix or `None` if filters don't constrain the first primary key column.
+    pub fn build(&self) -> Option<PrimaryKeyPrefix> {
+        let mut equals = HashMap::new();
+        let mut string_prefixes = HashMap::new();
+        for expr in self.filters {
+            self.collect_from_expr(expr.df_expr(), &mut equals, &mut string_prefixes);
+        }
+
+        let mut prefix = PrimaryKeyPrefix::default();
+        for column in self.metadata.primary_key_columns() {
+            let name = column.column_schema.name.as_str();
+            if let Some(value) = equals.remove(name) {
+                prefix.values.push(value);
+                continue;
+            }
+            if column.column_schema.data_type.is_string() {
+                prefix.string_prefix = string_prefixes.remove(name);
+            }
+            break;
+        }
+
+        (!prefix.is_empty()).then_some(prefix)
+    }
+
+    /// Collects `column = literal` and `column LIKE 'prefix%'` from conjunctions in the `expr`.
+    fn collect_from_expr(
+        &self,
+        expr: &DfExpr,
+        equals: &mut HashMap<String, Value>,
+        string_prefixes: &mut HashMap<String, String>,
+    ) {
+        match expr {
+            DfExpr::BinaryExpr(BinaryExpr {
+                left,
+                op: Operator::And,
+                right,
+            }) => {
+                self.collect_from_expr(left, equals, string_prefixes);
+                self.collect_from_expr(right, equals, string_prefixes);
+            }
+            DfExpr::BinaryExpr(BinaryExpr {
+                left,
+                op: Operator::Eq,
+                right,
+            }) => {
+                let (name, scalar) = match (left.as_ref(), right.as_ref()) {
+                    (DfExpr::Column(col), DfExpr::Literal(scalar))
+                    | (DfExpr::Literal(scalar), DfExpr::Column(col)) => (&col.name, scalar),
+                    _ => return,
+                };
+                if let Some(value) = self.primary_key_value(name, scalar) {
+                    let _ = equals.entry(name.clone()).or_insert(value);
+                }
+            }
+            DfExpr::Like(Like {
+                negated: false,
+                expr,
+                pattern,
+                escape_char: None,
+                case_insensitive: false,
+            }) => {
+                let (DfExpr::Column(col), DfExpr::Literal(ScalarValue::Utf8(Some(pattern)))) =
+                    (expr.as_ref(), pattern.as_ref())
+                else {
+                    return;
+                };
+                // Wildcards and the escape character end the literal prefix.
+                let prefix: String = pattern
+                    .chars()
+                    .take_while(|c| !matches!(c, '%' | '_' | '\\'))
+                    .collect();
+                if !prefix.is_empty() {
+                    let _ = string_prefixes.entry(col.name.clone()).or_insert(prefix);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Returns the value of the literal if the column is a primary key column of the
+    /// same type.
+    fn primary_key_value(&self, name: &str, scalar: &ScalarValue) -> Option<Value> {
+        let column = self.metadata.column_by_name(name)?;
+        let _ = self.metadata.primary_key_index(column.column_id)?;
+        if scalar.is_null()
+            || ConcreteDataType::from_arrow_type(&scalar.data_type())
+                != column.column_schema.data_type
+        {
+            return None;
+        }
+        Value::try_from(scalar.clone()).ok()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::sync::Arc;
 
+    use api::v1::SemanticType;
     use common_test_util::temp_dir::{create_temp_dir, TempDir};
     use datafusion::parquet::arrow::ArrowWriter;
     use datafusion_common::{Column, ScalarValue};
-    use datafusion_expr::{col, lit, BinaryExpr, Literal, Operator};
+    use datafusion_expr::{cast, col, lit, BinaryExpr, Literal, Operator};
     use datatypes::arrow::array::Int32Array;
     use datatypes::arrow::datatypes::{DataType, Field, Schema};
     use datatypes::arrow::record_batch::RecordBatch;
     use datatypes::arrow_array::StringArray;
+    use datatypes::schema::ColumnSchema;
     use parquet::arrow::ParquetRecordBatchStreamBuilder;
     use parquet::file::properties::WriterProperties;
+    use store_api::metadata::{ColumnMetadata, RegionMetadataBuilder};
+    use store_api::storage::RegionId;
 
     use super::*;
     use crate::predicate::stats::RowGroupPruningStatistics;
@@ -528,6 +659,35 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_cast_time_index() {
+        // CAST(ts AS Timestamp(ns)) >= 1000001ns
+        check_build_predicate(
+            cast(
+                col("ts"),
+                DataType::Timestamp(
+                    arrow::datatypes::TimeUnit::Nanosecond,
+                    Some("+00:00".into()),
+                ),
+            )
+            .gt_eq(lit(ScalarValue::TimestampNanosecond(
+                Some(1000001),
+                Some("+00:00".into()),
+            ))),
+            TimestampRange::from_start(Timestamp::new_millisecond(2)),
+        );
+
+        // CAST(ts AS Timestamp(s)) <= 1s, the cast loses precision.
+        check_build_predicate(
+            cast(
+                col("ts"),
+                DataType::Timestamp(arrow::datatypes::TimeUnit::Second, None),
+            )
+            .lt_eq(lit(ScalarValue::TimestampSecond(Some(1), None))),
+            TimestampRange::min_to_max(),
+        );
+    }
+
     async fn gen_test_parquet_file(dir: &TempDir, cnt: usize) -> (String, Arc<Schema>) {
         let path = dir
             .path()
@@ -673,6 +833,78 @@ mod tests {
         assert_prune(40, vec![e.into()], vec![true, true, false, true]).await;
     }
 
+    fn prefix_test_metadata() -> RegionMetadata {
+        let mut builder = RegionMetadataBuilder::new(RegionId::new(1, 1));
+        builder
+            .push_column_metadata(ColumnMetadata {
+                column_schema: ColumnSchema::new("k0", ConcreteDataType::string_datatype(), true),
+                semantic_type: SemanticType::Tag,
+                column_id: 0,
+            })
+            .push_column_metadata(ColumnMetadata {
+                column_schema: ColumnSchema::new("k1", ConcreteDataType::int64_datatype(), true),
+                semantic_type: SemanticType::Tag,
+                column_id: 1,
+            })
+            .push_column_metadata(ColumnMetadata {
+                column_schema: ColumnSchema::new(
+                    "ts",
+                    ConcreteDataType::timestamp_millisecond_datatype(),
+                    false,
+                ),
+                semantic_type: SemanticType::Timestamp,
+                column_id: 2,
+            })
+            .push_column_metadata(ColumnMetadata {
+                column_schema: ColumnSchema::new("v", ConcreteDataType::string_datatype(), true),
+                semantic_type: SemanticType::Field,
+                column_id: 3,
+            })
+            .primary_key(vec![0, 1]);
+        builder.build().unwrap()
+    }
+
+    fn check_build_pk_prefix(exprs: Vec<DfExpr>, expect: Option<PrimaryKeyPrefix>) {
+        let metadata = prefix_test_metadata();
+        let filters: Vec<_> = exprs.into_iter().map(Expr::from).collect();
+        assert_eq!(
+            expect,
+            PrimaryKeyPrefixBuilder::new(&metadata, &filters).build()
+        );
+    }
+
+    #[test]
+    fn test_build_pk_prefix() {
+        check_build_pk_prefix(
+            vec![col("k0").eq(lit("a")), lit(1i64).eq(col("k1"))],
+            Some(PrimaryKeyPrefix {
+                values: vec![Value::from("a"), Value::Int64(1)],
+                string_prefix: None,
+            }),
+        );
+        check_build_pk_prefix(
+            vec![col("k0").like(lit("ab_c%")).and(col("v").eq(lit("a")))],
+            Some(PrimaryKeyPrefix {
+                values: vec![],
+                string_prefix: Some("ab".to_string()),
+            }),
+        );
+        // Only the first primary key column is a string.
+        check_build_pk_prefix(
+            vec![col("k0").eq(lit("a")), col("k1").like(lit("1%"))],
+            Some(PrimaryKeyPrefix {
+                values: vec![Value::from("a")],
+                string_prefix: None,
+            }),
+        );
+        // Mismatched types, disjunctions and filters on other columns don't build prefixes.
+        check_build_pk_prefix(vec![col("k0").eq(lit(1i64))], None);
+        check_build_pk_prefix(vec![col("k0").eq(lit("a")).or(col("v").eq(lit("b")))], None);
+        check_build_pk_prefix(vec![col("k1").eq(lit(1i64)), col("v").eq(lit("a"))], None);
+        check_build_pk_prefix(vec![col("k0").not_like(lit("a%"))], None);
+        check_build_pk_prefix(vec![col("k0").like(lit("%a"))], None);
+    }
+
     #[tokio::test]
     async fn test_to_physical_expr() {
         let predicate = Predicate::new(vec![
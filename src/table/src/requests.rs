@@ -19,7 +19,7 @@ use std::str::FromStr;
 use std::time::Duration;
 
 use common_base::readable_size::ReadableSize;
-use common_datasource::object_store::s3::is_supported_in_s3;
+use common_datasource::object_store::is_supported_connection_option;
 use common_query::AddColumnLocation;
 use common_time::range::TimestampRange;
 use datatypes::prelude::VectorRef;
@@ -37,6 +37,9 @@ pub const FILE_TABLE_META_KEY: &str = "__private.file_table_meta";
 pub const FILE_TABLE_LOCATION_KEY: &str = "location";
 pub const FILE_TABLE_PATTERN_KEY: &str = "pattern";
 pub const FILE_TABLE_FORMAT_KEY: &str = "format";
+/// Lists files at the location again on each scan instead of using files found when
+/// the table is created.
+pub const FILE_TABLE_LIST_ON_SCAN_KEY: &str = "list_on_scan";
 
 #[derive(Debug, Clone)]
 pub struct CreateDatabaseRequest {
@@ -86,6 +89,12 @@ pub const WRITE_BUFFER_SIZE_KEY: &str = "write_buffer_size";
 pub const TTL_KEY: &str = "ttl";
 pub const REGIONS_KEY: &str = "regions";
 pub const STORAGE_KEY: &str = "storage";
+pub const APPEND_MODE_KEY: &str = "append_mode";
+/// Key of comma separated string fields whose values are encoded by a dictionary
+/// shared by all SSTs of the region.
+pub const SHARED_DICTIONARY_COLUMNS_KEY: &str = "sst.shared_dictionary_columns";
+/// Key of the wal provider of the table, e.g. `raft_engine` or `kafka`.
+pub const WAL_PROVIDER_KEY: &str = "wal.provider";
 
 impl TryFrom<&HashMap<String, String>> for TableOptions {
     type Error = error::Error;
@@ -339,13 +348,17 @@ pub fn valid_table_option(key: &str) -> bool {
         FILE_TABLE_LOCATION_KEY
             | FILE_TABLE_FORMAT_KEY
             | FILE_TABLE_PATTERN_KEY
+            | FILE_TABLE_LIST_ON_SCAN_KEY
             | WRITE_BUFFER_SIZE_KEY
             | TTL_KEY
             | REGIONS_KEY
             | STORAGE_KEY
+            | APPEND_MODE_KEY
+            | SHARED_DICTIONARY_COLUMNS_KEY
+            | WAL_PROVIDER_KEY
             | PHYSICAL_TABLE_METADATA_KEY
             | LOGICAL_TABLE_METADATA_KEY
-    ) | is_supported_in_s3(key)
+    ) | is_supported_connection_option(key)
 }
 
 #[derive(Debug, Clone, Default, Deserialize, Serialize)]
@@ -367,10 +380,14 @@ mod tests {
         assert!(valid_table_option(FILE_TABLE_LOCATION_KEY));
         assert!(valid_table_option(FILE_TABLE_FORMAT_KEY));
         assert!(valid_table_option(FILE_TABLE_PATTERN_KEY));
+        assert!(valid_table_option(FILE_TABLE_LIST_ON_SCAN_KEY));
         assert!(valid_table_option(TTL_KEY));
         assert!(valid_table_option(REGIONS_KEY));
         assert!(valid_table_option(WRITE_BUFFER_SIZE_KEY));
         assert!(valid_table_option(STORAGE_KEY));
+        assert!(valid_table_option(APPEND_MODE_KEY));
+        assert!(valid_table_option(SHARED_DICTIONARY_COLUMNS_KEY));
+        assert!(valid_table_option(WAL_PROVIDER_KEY));
         assert!(!valid_table_option("foo"));
     }
 
@@ -12,6 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use common_recordbatch::RecordBatchMetrics;
 use datafusion::physical_plan::metrics::{
     Count, ExecutionPlanMetricsSet, Gauge, MetricBuilder, Timestamp,
 };
@@ -62,3 +63,22 @@ impl Drop for MemoryUsageMetrics {
         self.try_done()
     }
 }
+
+/// Records metrics reported by a [`common_recordbatch::RecordBatchStream`]
+/// to the metrics set of a plan.
+pub fn record_stream_metrics(
+    metrics_set: &ExecutionPlanMetricsSet,
+    partition: usize,
+    metrics: &RecordBatchMetrics,
+) {
+    for (name, value) in &metrics.counters {
+        MetricBuilder::new(metrics_set)
+            .counter(name.clone(), partition)
+            .add(*value);
+    }
+    for (name, elapsed) in &metrics.elapsed {
+        MetricBuilder::new(metrics_set)
+            .subset_time(name.clone(), partition)
+            .add_duration(*elapsed);
+    }
+}
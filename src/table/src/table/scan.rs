@@ -22,7 +22,9 @@ use common_query::error as query_error;
 use common_query::error::Result as QueryResult;
 use common_query::physical_plan::{Partitioning, PhysicalPlan, PhysicalPlanRef};
 use common_recordbatch::error::Result as RecordBatchResult;
-use common_recordbatch::{RecordBatch, RecordBatchStream, SendableRecordBatchStream};
+use common_recordbatch::{
+    RecordBatch, RecordBatchMetrics, RecordBatchStream, SendableRecordBatchStream,
+};
 use datafusion::execution::context::TaskContext;
 use datafusion::physical_plan::metrics::{ExecutionPlanMetricsSet, MetricsSet};
 use datafusion_physical_expr::PhysicalSortExpr;
@@ -30,7 +32,7 @@ use datatypes::schema::SchemaRef;
 use futures::{Stream, StreamExt};
 use snafu::OptionExt;
 
-use crate::table::metrics::MemoryUsageMetrics;
+use crate::table::metrics::{record_stream_metrics, MemoryUsageMetrics};
 
 /// Adapt greptime's [SendableRecordBatchStream] to GreptimeDB's [PhysicalPlan].
 pub struct StreamScanAdapter {
@@ -105,6 +107,9 @@ impl PhysicalPlan for StreamScanAdapter {
         Ok(Box::pin(StreamWithMetricWrapper {
             stream,
             metric: mem_usage_metrics,
+            metrics_set: self.metric.clone(),
+            partition,
+            stream_metrics_recorded: false,
         }))
     }
 
@@ -116,6 +121,23 @@ impl PhysicalPlan for StreamScanAdapter {
 pub struct StreamWithMetricWrapper {
     stream: SendableRecordBatchStream,
     metric: MemoryUsageMetrics,
+    /// Metrics set to record metrics reported by the stream.
+    metrics_set: ExecutionPlanMetricsSet,
+    partition: usize,
+    stream_metrics_recorded: bool,
+}
+
+impl StreamWithMetricWrapper {
+    /// Records metrics reported by the inner stream if they are not recorded.
+    fn try_record_stream_metrics(&mut self) {
+        if self.stream_metrics_recorded {
+            return;
+        }
+        self.stream_metrics_recorded = true;
+        if let Some(metrics) = self.stream.metrics() {
+            record_stream_metrics(&self.metrics_set, self.partition, &metrics);
+        }
+    }
 }
 
 impl Stream for StreamWithMetricWrapper {
@@ -135,20 +157,36 @@ impl Stream for StreamWithMetricWrapper {
             this.metric.record_mem_usage(batch_mem_size);
             this.metric.record_output(record_batch.num_rows());
         }
+        if let Poll::Ready(None) = &poll {
+            this.try_record_stream_metrics();
+        }
 
         poll
     }
 }
 
+impl Drop for StreamWithMetricWrapper {
+    fn drop(&mut self) {
+        // The stream might be dropped before it is exhausted, e.g. with a limit.
+        self.try_record_stream_metrics();
+    }
+}
+
 impl RecordBatchStream for StreamWithMetricWrapper {
     fn schema(&self) -> SchemaRef {
         self.stream.schema()
     }
+
+    fn metrics(&self) -> Option<RecordBatchMetrics> {
+        self.stream.metrics()
+    }
 }
 
 #[cfg(test)]
 mod test {
-    use common_recordbatch::{util, RecordBatch, RecordBatches};
+    use common_query::physical_plan::DfPhysicalPlanAdapter;
+    use common_recordbatch::adapter::PlanMetricsStream;
+    use common_recordbatch::{util, EmptyRecordBatchStream, RecordBatch, RecordBatches};
     use datafusion::prelude::SessionContext;
     use datatypes::data_type::ConcreteDataType;
     use datatypes::schema::{ColumnSchema, Schema};
@@ -198,4 +236,61 @@ mod test {
             _ => unreachable!(),
         }
     }
+
+    /// A stream reporting the number of files it scans.
+    struct FileScanStream {
+        inner: SendableRecordBatchStream,
+    }
+
+    impl RecordBatchStream for FileScanStream {
+        fn schema(&self) -> SchemaRef {
+            self.inner.schema()
+        }
+
+        fn metrics(&self) -> Option<RecordBatchMetrics> {
+            Some(RecordBatchMetrics {
+                counters: vec![("num_files".to_string(), 2)],
+                elapsed: Vec::new(),
+            })
+        }
+    }
+
+    impl Stream for FileScanStream {
+        type Item = RecordBatchResult<RecordBatch>;
+
+        fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+            self.inner.as_mut().poll_next(cx)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_table_scan_stream_metrics() {
+        let ctx = SessionContext::new();
+        let schema = Arc::new(Schema::new(vec![ColumnSchema::new(
+            "a",
+            ConcreteDataType::int32_datatype(),
+            false,
+        )]));
+        let batch = RecordBatch::new(
+            schema.clone(),
+            vec![Arc::new(Int32Vector::from_slice([1, 2])) as _],
+        )
+        .unwrap();
+        let recordbatches = RecordBatches::try_new(schema.clone(), vec![batch]).unwrap();
+        let stream = Box::pin(FileScanStream {
+            inner: recordbatches.as_stream(),
+        });
+        let scan = Arc::new(StreamScanAdapter::new(stream));
+
+        let stream = scan.execute(0, ctx.task_ctx()).unwrap();
+        let _ = util::collect(stream).await.unwrap();
+
+        // Collects metrics of the scan from the plan.
+        let stream = PlanMetricsStream::new(
+            Box::pin(EmptyRecordBatchStream::new(schema)),
+            Arc::new(DfPhysicalPlanAdapter(scan)),
+        );
+        let metrics = stream.metrics().unwrap();
+        assert_eq!(vec![("num_files".to_string(), 2)], metrics.counters);
+    }
 }
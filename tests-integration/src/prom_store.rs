@@ -24,6 +24,7 @@ mod tests {
     use frontend::instance::Instance;
     use prost::Message;
     use servers::prom_store;
+    use servers::prom_store::PromWriteOptions;
     use servers::query_handler::sql::SqlQueryHandler;
     use servers::query_handler::PromStoreProtocolHandler;
     use session::context::QueryContext;
@@ -67,7 +68,10 @@ mod tests {
         .unwrap()
         .is_ok());
 
-        instance.write(write_request, ctx.clone()).await.unwrap();
+        instance
+            .write(write_request, PromWriteOptions::default(), ctx.clone())
+            .await
+            .unwrap();
 
         let read_request = ReadRequest {
             queries: vec![
@@ -19,6 +19,7 @@ use common_base::Plugins;
 use common_catalog::consts::MIN_USER_TABLE_ID;
 use common_config::KvBackendConfig;
 use common_meta::cache_invalidator::DummyCacheInvalidator;
+use common_meta::ddl::table_meta::DefaultTableMetadataAllocator;
 use common_meta::ddl_manager::DdlManager;
 use common_meta::key::TableMetadataManager;
 use common_meta::region_keeper::MemoryRegionKeeper;
@@ -30,8 +31,9 @@ use datanode::config::DatanodeOptions;
 use datanode::datanode::DatanodeBuilder;
 use frontend::frontend::FrontendOptions;
 use frontend::instance::builder::FrontendBuilder;
-use frontend::instance::standalone::StandaloneTableMetadataAllocator;
-use frontend::instance::{FrontendInstance, Instance, StandaloneDatanodeManager};
+use frontend::instance::{
+    FrontendInstance, Instance, StandaloneDatanodeManager, StandaloneRegionCapturer,
+};
 
 use crate::test_util::{self, create_tmp_dir_and_datanode_opts, StorageType, TestGuard};
 
@@ -123,7 +125,7 @@ impl GreptimeDbStandaloneBuilder {
             common_meta::wal::WalConfig::default(),
             kv_backend.clone(),
         ));
-        let table_meta_allocator = Arc::new(StandaloneTableMetadataAllocator::new(
+        let table_meta_allocator = Arc::new(DefaultTableMetadataAllocator::new(
             table_id_sequence,
             wal_options_allocator.clone(),
         ));
@@ -142,6 +144,7 @@ impl GreptimeDbStandaloneBuilder {
 
         let instance = FrontendBuilder::new(kv_backend, datanode_manager, ddl_task_executor)
             .with_plugin(plugins)
+            .with_region_capturer(Arc::new(StandaloneRegionCapturer(datanode.region_server())))
             .try_build()
             .await
             .unwrap();
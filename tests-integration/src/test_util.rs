@@ -48,6 +48,7 @@ use servers::postgres::PostgresServer;
 use servers::query_handler::grpc::ServerGrpcQueryHandlerAdapter;
 use servers::query_handler::sql::{ServerSqlQueryHandlerAdapter, SqlQueryHandler};
 use servers::server::Server;
+use servers::tls::ReloadableTlsServerConfig;
 use servers::Mode;
 use session::context::QueryContext;
 
@@ -568,7 +569,7 @@ pub async fn setup_mysql_server_with_user_provider(
         )),
         Arc::new(MysqlSpawnConfig::new(
             false,
-            opts.tls.setup().unwrap().map(Arc::new),
+            Arc::new(ReloadableTlsServerConfig::try_new(opts.tls.clone()).unwrap()),
             opts.reject_no_database.unwrap_or(false),
         )),
     ));
@@ -616,7 +617,7 @@ pub async fn setup_pg_server_with_user_provider(
     };
     let fe_pg_server = Arc::new(Box::new(PostgresServer::new(
         ServerSqlQueryHandlerAdapter::arc(fe_instance_ref),
-        opts.tls.clone(),
+        Arc::new(ReloadableTlsServerConfig::try_new(opts.tls.clone()).unwrap()),
         runtime,
         user_provider,
     )) as Box<dyn Server>);
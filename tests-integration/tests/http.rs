@@ -415,6 +415,26 @@ pub async fn test_prom_http_api(store_type: StorageType) {
         r#"{"status":"success","data":"foo / bar"}"#
     );
 
+    // parse_query
+    let res = client
+        .get("/v1/prometheus/api/v1/parse_query?query=foo/bar")
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::OK);
+    let body = serde_json::from_str::<serde_json::Value>(&res.text().await).unwrap();
+    assert_eq!(body["status"], "success");
+    let ast = &body["data"];
+    assert_eq!(ast["type"], "binaryExpr");
+    assert_eq!(ast["op"], "/");
+    assert_eq!(ast["matching"]["card"], "one-to-one");
+    assert_eq!(ast["lhs"]["type"], "vectorSelector");
+    assert_eq!(ast["lhs"]["name"], "foo");
+    assert_eq!(
+        ast["lhs"]["matchers"],
+        serde_json::json!([{"type": "=", "name": "__name__", "value": "foo"}])
+    );
+    assert_eq!(ast["rhs"]["name"], "bar");
+
     // instant query
     let res = client
         .get("/v1/prometheus/api/v1/query?query=up&time=1")
@@ -471,6 +491,13 @@ pub async fn test_prom_http_api(store_type: StorageType) {
     // labels without match[] param
     let res = client.get("/v1/prometheus/api/v1/labels").send().await;
     assert_eq!(res.status(), StatusCode::OK);
+    let body = serde_json::from_str::<PrometheusJsonResponse>(&res.text().await).unwrap();
+    assert_eq!(body.status, "success");
+    let PrometheusResponse::Labels(labels) = body.data else {
+        unreachable!()
+    };
+    assert!(labels.contains(&"__name__".to_string()));
+    assert!(labels.contains(&"host".to_string()));
 
     // labels query with multiple match[] params
     let res = client
@@ -517,17 +544,43 @@ pub async fn test_prom_http_api(store_type: StorageType) {
         .await;
     assert_eq!(res.status(), StatusCode::OK);
 
+    // series selected by matchers
+    let res = client
+        .get("/v1/prometheus/api/v1/series?match[]={__name__=\"demo\"}&start=0&end=0")
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::OK);
+    let body = serde_json::from_str::<PrometheusJsonResponse>(&res.text().await).unwrap();
+    assert_eq!(body.status, "success");
+    let PrometheusResponse::Series(series) = body.data else {
+        unreachable!()
+    };
+    assert_eq!(series[0]["__name__"], "demo");
+
     // label values
-    // should return error if there is no match[]
+    // without match[]
+    let res = client
+        .get("/v1/prometheus/api/v1/label/host/values?start=0&end=600")
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::OK);
+    let body = serde_json::from_str::<PrometheusJsonResponse>(&res.text().await).unwrap();
+    assert_eq!(body.status, "success");
+    assert_eq!(
+        body.data,
+        serde_json::from_value::<PrometheusResponse>(json!(["host1", "host2"])).unwrap()
+    );
     let res = client
         .get("/v1/prometheus/api/v1/label/instance/values")
         .send()
         .await;
     assert_eq!(res.status(), StatusCode::OK);
-    let prom_resp = res.json::<PrometheusJsonResponse>().await;
-    assert_eq!(prom_resp.status, "error");
-    assert!(prom_resp.error.is_some_and(|err| !err.is_empty()));
-    assert!(prom_resp.error_type.is_some_and(|err| !err.is_empty()));
+    let body = serde_json::from_str::<PrometheusJsonResponse>(&res.text().await).unwrap();
+    assert_eq!(body.status, "success");
+    assert_eq!(
+        body.data,
+        serde_json::from_value::<PrometheusResponse>(json!([])).unwrap()
+    );
 
     // single match[]
     let res = client
@@ -705,6 +758,15 @@ addr = "127.0.0.1:4000"
 timeout = "30s"
 body_limit = "64MiB"
 
+[frontend.http.tls]
+mode = "disable"
+cert_path = ""
+key_path = ""
+ca_cert_path = ""
+require_client_auth = false
+min_version = "tls1.2"
+watch = false
+
 [frontend.grpc]
 addr = "127.0.0.1:4001"
 runtime_size = 8
@@ -720,6 +782,15 @@ runtime_size = 2
 mode = "disable"
 cert_path = ""
 key_path = ""
+ca_cert_path = ""
+require_client_auth = false
+min_version = "tls1.2"
+watch = false
+
+[frontend.mysql.connection]
+max_connections = 0
+idle_timeout = "0s"
+keepalive = "0s"
 
 [frontend.postgres]
 enable = true
@@ -730,6 +801,19 @@ runtime_size = 2
 mode = "disable"
 cert_path = ""
 key_path = ""
+ca_cert_path = ""
+require_client_auth = false
+min_version = "tls1.2"
+watch = false
+
+[frontend.postgres.connection]
+max_connections = 0
+idle_timeout = "0s"
+keepalive = "0s"
+
+[frontend.flight_sql]
+enable = false
+addr = "127.0.0.1:4005"
 
 [frontend.opentsdb]
 enable = true
@@ -745,6 +829,43 @@ enable = true
 [frontend.otlp]
 enable = true
 
+[frontend.loki]
+enable = true
+
+[frontend.mqtt]
+enable = false
+addr = "127.0.0.1:1883"
+runtime_size = 2
+db = "public"
+batch_size = 1000
+flush_interval = "1s"
+topics = []
+
+[frontend.pipeline]
+enable = false
+sync_interval = "10s"
+
+[frontend.rate_limit]
+rules = []
+
+[frontend.spool]
+enable = false
+dir = "/tmp/greptimedb/spool/"
+max_size = "512MiB"
+max_age = "10m"
+replay_interval = "1s"
+
+[frontend.hedged_read]
+enable = false
+percentile = 0.95
+min_delay = "10ms"
+budget_ratio = 0.05
+
+[frontend.query_rule]
+interactive_channels = ["mysql", "postgres"]
+
+[frontend.query_rule.function_renames]
+
 [frontend.logging]
 enable_otlp_tracing = false
 
@@ -752,6 +873,9 @@ enable_otlp_tracing = false
 timeout = "10s"
 connect_timeout = "1s"
 tcp_nodelay = true
+channels_per_datanode = 1
+max_in_flight_requests = 0
+health_check_interval = "10s"
 
 [frontend.export_metrics]
 enable = false
@@ -780,6 +904,15 @@ addr = "127.0.0.1:4000"
 timeout = "30s"
 body_limit = "64MiB"
 
+[datanode.http.tls]
+mode = "disable"
+cert_path = ""
+key_path = ""
+ca_cert_path = ""
+require_client_auth = false
+min_version = "tls1.2"
+watch = false
+
 [datanode.wal]
 provider = "raft_engine"
 file_size = "256MiB"
@@ -787,6 +920,9 @@ purge_threshold = "4GiB"
 purge_interval = "10m"
 read_batch_size = 128
 sync_write = false
+batch_compression_threshold = "8KiB"
+recovery_threads = 4
+recovery_read_block_size = "16KiB"
 
 [datanode.storage]
 type = "{}"
@@ -808,6 +944,12 @@ vector_cache_size = "512MiB"
 page_cache_size = "512MiB"
 sst_write_buffer_size = "8MiB"
 parallel_scan_channel_size = 32
+scan_disable_pruning = false
+scan_verify_pruning = false
+wal_corruption_policy = "fail"
+commit_journal_dir = ""
+dropped_region_purge_delay = "5m"
+dropped_region_purge_retry_interval = "5m"
 
 [[datanode.region_engine]]
 
@@ -823,6 +965,13 @@ write_interval = "30s"
 
 [datanode.export_metrics.headers]
 
+[datanode.request_limit]
+max_concurrent_scans = 0
+max_concurrent_region_scans = 0
+max_concurrent_writes = 0
+max_concurrent_region_writes = 0
+queue_timeout = "5s"
+
 [logging]
 enable_otlp_tracing = false"#,
         store_type,
@@ -845,6 +994,7 @@ fn drop_lines_with_inconsistent_results(input: String) -> String {
         "scope =",
         "num_workers =",
         "scan_parallelism =",
+        "open_region_parallelism =",
     ];
 
     input
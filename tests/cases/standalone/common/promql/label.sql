@@ -0,0 +1,35 @@
+-- from prometheus/promql/testdata/functions.test and aggregators.test
+-- cases related to label_replace, label_join, topk and bottomk
+create table test (
+    ts timestamp time index,
+    host string,
+    idc string,
+    val double,
+    primary key (host, idc),
+);
+
+insert into test values
+    (0, "host1", "idc1", 1),
+    (0, "host2", "idc1", 2),
+    (0, "host3", "idc2", 3);
+
+-- add a new label
+-- SQLNESS SORT_RESULT 3 1
+tql eval (0, 0, '1s') label_replace(test, "new_host", "$1", "host", "host(.*)");
+
+-- regex doesn't match, the series is returned unchanged
+-- SQLNESS SORT_RESULT 3 1
+tql eval (0, 0, '1s') label_replace(test, "idc", "$1", "host", "nomatch(.*)");
+
+-- SQLNESS SORT_RESULT 3 1
+tql eval (0, 0, '1s') label_join(test, "joined", "-", "host", "idc");
+
+tql eval (0, 0, '1s') label_replace(test, "new_host", "$1", "host", "(.*");
+
+-- SQLNESS SORT_RESULT 3 1
+tql eval (0, 0, '1s') topk(1, test);
+
+-- SQLNESS SORT_RESULT 3 1
+tql eval (0, 0, '1s') bottomk(1, test) by (idc);
+
+drop table test;
@@ -160,3 +160,29 @@ insert into histogram3_bucket values
 tql eval (3000, 3005, '3s') histogram_quantile(0.5, sum by(le, s) (rate(histogram3_bucket[5m])));
 
 drop table histogram3_bucket;
+
+-- not from Prometheus
+-- series have different buckets, duplicated buckets are merged
+create table histogram4_bucket (
+    ts timestamp time index,
+    le string,
+    s string,
+    val double,
+    primary key (s, le),
+);
+
+insert into histogram4_bucket values
+    (2900000, "1", "a", 0),
+    (2900000, "2", "a", 4),
+    (2900000, "+Inf", "a", 4),
+    (2900000, "0.5", "b", 1),
+    (2900000, "1", "b", 1),
+    (2900000, "1.0", "b", 2),
+    (2900000, "+Inf", "b", 8),
+    (2900000, "1", "c", 1),
+    (2900000, "2", "c", 2);
+
+-- SQLNESS SORT_RESULT 3 1
+tql eval (3000, 3000, '1s') histogram_quantile(0.25, histogram4_bucket);
+
+drop table histogram4_bucket;
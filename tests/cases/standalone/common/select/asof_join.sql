@@ -0,0 +1,34 @@
+CREATE TABLE events (
+  ts timestamp(3) time index,
+  host STRING PRIMARY KEY,
+  event STRING,
+);
+
+CREATE TABLE metrics (
+  ts timestamp(3) time index,
+  host STRING PRIMARY KEY,
+  cpu DOUBLE,
+);
+
+INSERT INTO TABLE events VALUES
+    ("1970-01-01T00:00:05+00:00", 'host1', 'a'),
+    ("1970-01-01T00:00:15+00:00", 'host1', 'b'),
+    ("1970-01-01T00:00:05+00:00", 'host2', 'c');
+
+INSERT INTO TABLE metrics VALUES
+    ("1970-01-01T00:00:00+00:00", 'host1', 1.0),
+    ("1970-01-01T00:00:10+00:00", 'host1', 2.0),
+    ("1970-01-01T00:00:20+00:00", 'host1', 3.0),
+    ("1970-01-01T00:00:10+00:00", 'host2', 4.0);
+
+SELECT events.ts, events.host, event, cpu FROM events ASOF JOIN metrics ON events.host = metrics.host AND events.ts >= metrics.ts ORDER BY events.host, events.ts;
+
+SELECT events.ts, events.host, event, cpu FROM events ASOF JOIN metrics ON metrics.ts >= events.ts AND events.host = metrics.host ORDER BY events.host, events.ts;
+
+SELECT events.ts, event, metrics.ts, cpu FROM events ASOF JOIN metrics ON events.ts > metrics.ts WHERE event != 'b' ORDER BY event;
+
+SELECT * FROM events ASOF JOIN metrics ON events.host = metrics.host;
+
+DROP TABLE events;
+
+DROP TABLE metrics;